@@ -132,6 +132,8 @@ fn make_resolved_sprite(width: usize, height: usize) -> ResolvedSprite {
         warnings: vec![],
         nine_slice: None,
         regions: Some(regions),
+        layers: None,
+        opacity: None,
     }
 }
 
@@ -150,6 +152,9 @@ fn make_sprite_inputs(count: usize, size: u32) -> Vec<SpriteInput> {
             image: RgbaImage::from_pixel(size, size, colors[i % 4]),
             origin: None,
             boxes: None,
+            attach_in: None,
+            attach_out: None,
+            slices: None,
         })
         .collect()
 }
@@ -370,6 +375,9 @@ fn bench_atlas(c: &mut Criterion) {
                 image: RgbaImage::from_pixel(size, size, Rgba([255, 0, 0, 255])),
                 origin: None,
                 boxes: None,
+                attach_in: None,
+                attach_out: None,
+                slices: None,
             }
         })
         .collect();
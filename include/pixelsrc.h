@@ -0,0 +1,84 @@
+#ifndef PIXELSRC_H
+#define PIXELSRC_H
+
+/* Generated by cbindgen from src/ffi.rs - do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Success.
+#define PXL_OK 0
+
+// A required pointer argument was null.
+#define PXL_ERR_NULL_POINTER -1
+
+// A string argument was not valid UTF-8.
+#define PXL_ERR_INVALID_UTF8 -2
+
+// No sprite with the requested name exists in the document.
+#define PXL_ERR_NOT_FOUND -3
+
+// The output buffer is smaller than `width * height * 4` bytes.
+#define PXL_ERR_BUFFER_TOO_SMALL -4
+
+// An opaque parsed document.
+typedef struct PxlDocument PxlDocument;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parse a NUL-terminated `.pxl`/`.jsonl` source string.
+//
+// Returns null (and sets the last error) if `source` is null or not UTF-8.
+struct PxlDocument *pxl_document_load(const char *source);
+
+// Read and parse a `.pxl`/`.jsonl` file.
+//
+// Returns null (and sets the last error) if the file cannot be read.
+struct PxlDocument *pxl_document_load_file(const char *path);
+
+// Release a document handle. Passing null is a no-op.
+void pxl_document_free(struct PxlDocument *doc);
+
+// Number of sprites in the document (0 if `doc` is null).
+size_t pxl_sprite_count(const struct PxlDocument *doc);
+
+// Name of the sprite at `index` in definition order, or null if out of range.
+//
+// The string is owned by the document.
+const char *pxl_sprite_name(const struct PxlDocument *doc, size_t index);
+
+// Query the rendered dimensions of a sprite.
+int32_t pxl_sprite_dimensions(struct PxlDocument *doc,
+                              const char *name,
+                              uint32_t *width,
+                              uint32_t *height);
+
+// Render a sprite as tightly packed RGBA8 into `buffer`.
+//
+// `buffer_len` must be at least `width * height * 4` (see
+// `pxl_sprite_dimensions`); otherwise `PXL_ERR_BUFFER_TOO_SMALL` is returned
+// and the buffer is left untouched.
+int32_t pxl_render_sprite(struct PxlDocument *doc,
+                          const char *name,
+                          uint8_t *buffer,
+                          size_t buffer_len);
+
+// Number of parse/render warnings collected so far on this document.
+size_t pxl_warning_count(const struct PxlDocument *doc);
+
+// Warning message at `index`, or null if out of range. Owned by the document.
+const char *pxl_warning(const struct PxlDocument *doc, size_t index);
+
+// Message describing the most recent failure on this thread, or null if none.
+const char *pxl_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PIXELSRC_H */
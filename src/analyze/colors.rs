@@ -0,0 +1,213 @@
+//! Color statistics of rendered sprites and compositions
+//!
+//! Statistics are taken from the final image, after transforms, blends and
+//! opacity, so they count colors that compositing introduced as well as the
+//! ones the palette defines. Fully transparent pixels count as one color no
+//! matter their RGB channels.
+
+use std::collections::HashMap;
+
+use image::RgbaImage;
+
+use crate::color::parse_color;
+use crate::import::LabColor;
+use crate::palettes::{get_builtin, list_builtins};
+
+/// Largest CIE76 ΔE between colors that look the same (a just-noticeable difference)
+pub const JND_DELTA_E: f64 = 2.3;
+
+/// How many pixels of an image have one color
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorCount {
+    /// RGBA color
+    pub rgba: [u8; 4],
+    /// Pixels with this color
+    pub count: usize,
+}
+
+/// How closely an image's colors follow a built-in palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteMatch {
+    /// Built-in palette name
+    pub palette: String,
+    /// Fraction of visible pixels within [`JND_DELTA_E`] of a palette color (0.0-1.0)
+    pub score: f64,
+    /// Mean ΔE from each visible pixel to its nearest palette color
+    pub mean_delta_e: f64,
+}
+
+/// Color statistics of one rendered image
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorStats {
+    /// Image width
+    pub width: u32,
+    /// Image height
+    pub height: u32,
+    /// Colors by pixel count, most used first
+    pub histogram: Vec<ColorCount>,
+    /// Fully opaque pixels
+    pub opaque_pixels: usize,
+    /// Partly transparent pixels
+    pub translucent_pixels: usize,
+    /// Fully transparent pixels
+    pub transparent_pixels: usize,
+    /// Distinct alpha values among visible pixels
+    pub alpha_levels: usize,
+    /// Nearest built-in palette, if the image has visible pixels
+    pub palette_match: Option<PaletteMatch>,
+}
+
+impl ColorStats {
+    /// Collect statistics from a rendered image.
+    pub fn new(image: &RgbaImage) -> Self {
+        let mut counts: HashMap<[u8; 4], usize> = HashMap::new();
+        for pixel in image.pixels() {
+            let rgba = if pixel[3] == 0 { [0, 0, 0, 0] } else { pixel.0 };
+            *counts.entry(rgba).or_default() += 1;
+        }
+        let mut histogram: Vec<ColorCount> =
+            counts.into_iter().map(|(rgba, count)| ColorCount { rgba, count }).collect();
+        histogram.sort_by(|a, b| b.count.cmp(&a.count).then(a.rgba.cmp(&b.rgba)));
+
+        let pixels_where = |f: fn(u8) -> bool| -> usize {
+            histogram.iter().filter(|c| f(c.rgba[3])).map(|c| c.count).sum()
+        };
+        let mut alphas: Vec<u8> = histogram.iter().map(|c| c.rgba[3]).filter(|&a| a > 0).collect();
+        alphas.sort_unstable();
+        alphas.dedup();
+
+        let palette_match = nearest_builtin_palette(&histogram);
+        Self {
+            width: image.width(),
+            height: image.height(),
+            opaque_pixels: pixels_where(|a| a == 255),
+            translucent_pixels: pixels_where(|a| a > 0 && a < 255),
+            transparent_pixels: pixels_where(|a| a == 0),
+            alpha_levels: alphas.len(),
+            histogram,
+            palette_match,
+        }
+    }
+
+    /// Distinct colors, not counting transparency.
+    pub fn unique_colors(&self) -> usize {
+        self.histogram.iter().filter(|c| c.rgba[3] > 0).count()
+    }
+
+    /// JSON form used by `pxl analyze --colors --format json`.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "width": self.width,
+            "height": self.height,
+            "unique_colors": self.unique_colors(),
+            "alpha": {
+                "opaque_pixels": self.opaque_pixels,
+                "translucent_pixels": self.translucent_pixels,
+                "transparent_pixels": self.transparent_pixels,
+                "levels": self.alpha_levels,
+            },
+            "histogram": self.histogram.iter().map(|c| serde_json::json!({
+                "color": hex(c.rgba),
+                "count": c.count,
+            })).collect::<Vec<_>>(),
+            "palette_match": self.palette_match.as_ref().map(|m| serde_json::json!({
+                "palette": m.palette,
+                "score": m.score,
+                "mean_delta_e": m.mean_delta_e,
+            })),
+        })
+    }
+}
+
+/// `#RRGGBBAA` form of a color
+fn hex(rgba: [u8; 4]) -> String {
+    format!("#{:02X}{:02X}{:02X}{:02X}", rgba[0], rgba[1], rgba[2], rgba[3])
+}
+
+/// The built-in palette whose colors are nearest the visible pixels.
+///
+/// Alpha is ignored. Palettes are ranked by score, then by mean ΔE.
+fn nearest_builtin_palette(histogram: &[ColorCount]) -> Option<PaletteMatch> {
+    let visible: Vec<(LabColor, usize)> = histogram
+        .iter()
+        .filter(|c| c.rgba[3] > 0)
+        .map(|c| (LabColor::from_rgb(c.rgba[0], c.rgba[1], c.rgba[2]), c.count))
+        .collect();
+    let total: usize = visible.iter().map(|(_, count)| count).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut best: Option<PaletteMatch> = None;
+    for name in list_builtins() {
+        let Some(palette) = get_builtin(name) else { continue };
+        let swatches: Vec<LabColor> = palette
+            .colors
+            .values()
+            .filter_map(|color| parse_color(color).ok())
+            .filter(|rgba| rgba[3] > 0)
+            .map(|rgba| LabColor::from_rgb(rgba[0], rgba[1], rgba[2]))
+            .collect();
+        if swatches.is_empty() {
+            continue;
+        }
+
+        let (mut matched, mut delta_sum) = (0, 0.0);
+        for (lab, count) in &visible {
+            let delta = swatches.iter().map(|s| lab.distance(s)).fold(f64::INFINITY, f64::min);
+            if delta <= JND_DELTA_E {
+                matched += count;
+            }
+            delta_sum += delta * *count as f64;
+        }
+        let candidate = PaletteMatch {
+            palette: name.to_string(),
+            score: matched as f64 / total as f64,
+            mean_delta_e: delta_sum / total as f64,
+        };
+        let better = best.as_ref().is_none_or(|b| {
+            candidate.score > b.score
+                || (candidate.score == b.score && candidate.mean_delta_e < b.mean_delta_e)
+        });
+        if better {
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+/// Format color statistics as a text report.
+///
+/// `top` limits how many histogram entries are listed.
+pub fn format_color_stats_text(kind: &str, name: &str, stats: &ColorStats, top: usize) -> String {
+    let mut output = String::new();
+    output.push_str(&format!("COLORS: {} '{}' ({}x{})\n", kind, name, stats.width, stats.height));
+    output.push_str("──────────\n");
+    let visible = stats.opaque_pixels + stats.translucent_pixels;
+    output.push_str(&format!("  Unique colors:   {}\n", stats.unique_colors()));
+    output.push_str(&format!(
+        "  Alpha:           {} opaque, {} translucent ({} levels), {} transparent\n",
+        stats.opaque_pixels, stats.translucent_pixels, stats.alpha_levels, stats.transparent_pixels
+    ));
+    match &stats.palette_match {
+        Some(m) => output.push_str(&format!(
+            "  Nearest palette: @{} ({:.1}% of pixels match, mean ΔE {:.1})\n",
+            m.palette,
+            m.score * 100.0,
+            m.mean_delta_e
+        )),
+        None => output.push_str("  Nearest palette: none (no visible pixels)\n"),
+    }
+
+    output.push_str("\n  Histogram:\n");
+    for c in stats.histogram.iter().filter(|c| c.rgba[3] > 0).take(top) {
+        let share = c.count as f64 * 100.0 / visible as f64;
+        output.push_str(&format!("    {}  {:>6}  {:>5.1}%\n", hex(c.rgba), c.count, share));
+    }
+    let rest = stats.unique_colors().saturating_sub(top);
+    if rest > 0 {
+        output.push_str(&format!("    ... {} more\n", rest));
+    }
+    output.push('\n');
+    output
+}
@@ -1,6 +1,9 @@
 //! Compression estimation for sprite analysis
 
+use std::collections::HashSet;
+
 use crate::models::Sprite;
+use crate::structured::token_grid;
 
 /// Statistics about RLE (Run-Length Encoding) compression opportunities
 #[derive(Debug, Clone, Default)]
@@ -145,32 +148,57 @@ impl CompressionEstimator {
 
     /// Analyze RLE opportunities across all rows of a sprite.
     ///
-    /// NOTE: This function is deprecated for v2 region-based sprites.
-    /// Grid-based analysis is no longer supported. See TTP-7i4v.
-    #[allow(unused_variables)]
-    pub fn analyze_sprite_rle(_sprite: &Sprite) -> RleStats {
-        // TODO: Update for v2 region-based format
-        RleStats::default()
+    /// Rows are the sprite's visible tokens, resolved from its regions.
+    pub fn analyze_sprite_rle(sprite: &Sprite) -> RleStats {
+        Self::analyze_rows_rle(&sprite_token_rows(sprite))
     }
 
-    /// Detect repeated rows in a sprite.
-    ///
-    /// NOTE: This function is deprecated for v2 region-based sprites.
-    /// Grid-based analysis is no longer supported. See TTP-7i4v.
-    #[allow(unused_variables)]
-    pub fn analyze_row_repetition(_sprite: &Sprite) -> RowRepetitionStats {
-        // TODO: Update for v2 region-based format
-        RowRepetitionStats::default()
+    /// Detect rows identical to the previous row in a sprite.
+    pub fn analyze_row_repetition(sprite: &Sprite) -> RowRepetitionStats {
+        Self::analyze_rows_repetition(&sprite_token_rows(sprite))
     }
 
     /// Full compression analysis for a sprite.
-    ///
-    /// NOTE: This function is deprecated for v2 region-based sprites.
-    /// Grid-based analysis is no longer supported. See TTP-7i4v.
     pub fn analyze_sprite(sprite: &Sprite) -> CompressionStats {
+        let rows = sprite_token_rows(sprite);
         CompressionStats {
-            rle: Self::analyze_sprite_rle(sprite),
-            row_repetition: Self::analyze_row_repetition(sprite),
+            rle: Self::analyze_rows_rle(&rows),
+            row_repetition: Self::analyze_rows_repetition(&rows),
+        }
+    }
+
+    /// Analyze RLE opportunities in rows of tokens (`None` is transparent).
+    pub fn analyze_rows_rle(rows: &[TokenRow]) -> RleStats {
+        let mut stats = RleStats::default();
+        for row in rows {
+            let unique: HashSet<&Option<String>> = row.iter().collect();
+            stats.total_tokens += row.len();
+            stats.total_runs += row.chunk_by(|a, b| a == b).count();
+            stats.total_unique_per_row += unique.len();
+            stats.total_rows += 1;
         }
+        stats
+    }
+
+    /// Count rows identical to the previous row.
+    pub fn analyze_rows_repetition(rows: &[TokenRow]) -> RowRepetitionStats {
+        RowRepetitionStats {
+            total_rows: rows.len(),
+            repeated_rows: rows.windows(2).filter(|pair| pair[0] == pair[1]).count(),
+            sprites_analyzed: 1,
+        }
+    }
+}
+
+/// Visible tokens along one row of a sprite (`None` is transparent)
+pub type TokenRow = Vec<Option<String>>;
+
+/// Resolve a region-based sprite's rows of visible tokens.
+///
+/// Sprites without an explicit size or regions have no rows.
+pub(crate) fn sprite_token_rows(sprite: &Sprite) -> Vec<TokenRow> {
+    match (sprite.size, &sprite.regions) {
+        (Some([w, h]), Some(regions)) => token_grid(&sprite.name, w, h, regions).0,
+        _ => Vec::new(),
     }
 }
@@ -0,0 +1,361 @@
+//! Duplicate and near-duplicate sprite detection
+//!
+//! Each sprite is rendered once and fingerprinted in all eight orientations
+//! (rotations and mirrors) with:
+//! - a pixel hash, for exact copies
+//! - a token-structure hash, for copies that differ only in color
+//! - perceptual aHash and dHash, for near-duplicates
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use image::imageops::{self, FilterType};
+use image::{GrayImage, Luma, RgbaImage};
+
+use crate::models::{Sprite, TtpObject};
+use crate::output::open_input;
+use crate::parser::parse_stream;
+use crate::registry::PaletteRegistry;
+use crate::renderer::render_sprite;
+
+/// Default minimum similarity for near-duplicates
+pub const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.9;
+
+/// One of the eight rotations/mirrors of a sprite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Identity,
+    FlipHorizontal,
+    FlipVertical,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Rotated 90° then flipped horizontally
+    Transpose,
+    /// Rotated 270° then flipped horizontally
+    Transverse,
+}
+
+impl Orientation {
+    /// All orientations, identity first
+    pub const ALL: [Orientation; 8] = [
+        Orientation::Identity,
+        Orientation::FlipHorizontal,
+        Orientation::FlipVertical,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::Transpose,
+        Orientation::Transverse,
+    ];
+
+    /// Apply this orientation to an image.
+    fn apply<P: image::Pixel + 'static>(
+        self,
+        image: &image::ImageBuffer<P, Vec<P::Subpixel>>,
+    ) -> image::ImageBuffer<P, Vec<P::Subpixel>> {
+        match self {
+            Orientation::Identity => image.clone(),
+            Orientation::FlipHorizontal => imageops::flip_horizontal(image),
+            Orientation::FlipVertical => imageops::flip_vertical(image),
+            Orientation::Rotate90 => imageops::rotate90(image),
+            Orientation::Rotate180 => imageops::rotate180(image),
+            Orientation::Rotate270 => imageops::rotate270(image),
+            Orientation::Transpose => imageops::flip_horizontal(&imageops::rotate90(image)),
+            Orientation::Transverse => imageops::flip_horizontal(&imageops::rotate270(image)),
+        }
+    }
+}
+
+impl fmt::Display for Orientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Orientation::Identity => "identical",
+            Orientation::FlipHorizontal => "mirrored",
+            Orientation::FlipVertical => "flipped vertically",
+            Orientation::Rotate90 => "rotated 90°",
+            Orientation::Rotate180 => "rotated 180°",
+            Orientation::Rotate270 => "rotated 270°",
+            Orientation::Transpose => "rotated 90° and mirrored",
+            Orientation::Transverse => "rotated 270° and mirrored",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Hashes of a sprite in one orientation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OrientedHashes {
+    pixels: u64,
+    structure: u64,
+    ahash: u64,
+    dhash: u64,
+}
+
+/// A rendered sprite's fingerprints
+#[derive(Debug, Clone)]
+pub struct SpriteFingerprint {
+    /// File the sprite was defined in
+    pub file: PathBuf,
+    /// Sprite name
+    pub sprite: String,
+    /// Hashes per orientation, in [`Orientation::ALL`] order
+    hashes: [OrientedHashes; 8],
+}
+
+impl SpriteFingerprint {
+    /// Fingerprint a sprite rendered with its resolved palette.
+    ///
+    /// Returns `None` if the sprite renders fully transparent, such as a
+    /// sprite derived from another through `source`.
+    pub fn new(file: &Path, sprite: &Sprite, palette: &HashMap<String, String>) -> Option<Self> {
+        let (image, _) = render_sprite(sprite, palette);
+        if image.pixels().all(|p| p[3] == 0) {
+            return None;
+        }
+        let tokens = token_image(&image);
+        let hashes = Orientation::ALL.map(|orientation| {
+            let image = orientation.apply(&image);
+            OrientedHashes {
+                pixels: pixel_hash(&image),
+                structure: structure_hash(&orientation.apply(&tokens)),
+                ahash: average_hash(&image),
+                dhash: difference_hash(&image),
+            }
+        });
+        Some(Self { file: file.to_path_buf(), sprite: sprite.name.clone(), hashes })
+    }
+
+    fn identity(&self) -> &OrientedHashes {
+        &self.hashes[0]
+    }
+}
+
+/// How two sprites match
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateKind {
+    /// Same pixels
+    Exact,
+    /// Same shapes with different colors (e.g. a palette swap)
+    Recolor,
+    /// Perceptual hashes agree above the threshold
+    Near,
+}
+
+impl DuplicateKind {
+    /// Lowercase name used in reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateKind::Exact => "exact",
+            DuplicateKind::Recolor => "recolor",
+            DuplicateKind::Near => "near",
+        }
+    }
+}
+
+/// A pair of sprites that duplicate each other
+#[derive(Debug, Clone)]
+pub struct DuplicateMatch {
+    /// First sprite (file, name)
+    pub first: (PathBuf, String),
+    /// Second sprite (file, name)
+    pub second: (PathBuf, String),
+    /// How the sprites match
+    pub kind: DuplicateKind,
+    /// Orientation that maps the second sprite onto the first
+    pub orientation: Orientation,
+    /// Perceptual similarity (0.0-1.0); 1.0 for exact matches
+    pub similarity: f64,
+}
+
+/// Collects sprite fingerprints across files and finds duplicates.
+#[derive(Debug, Default)]
+pub struct DuplicateFinder {
+    /// Fingerprints in the order sprites were found
+    pub fingerprints: Vec<SpriteFingerprint>,
+}
+
+impl DuplicateFinder {
+    /// Create an empty finder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render and fingerprint every non-blank sprite in a file.
+    pub fn add_file(&mut self, path: &Path) -> Result<(), String> {
+        let reader = open_input(path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let result = parse_stream(reader);
+
+        let mut palettes = PaletteRegistry::new();
+        let mut sprites = Vec::new();
+        for obj in result.objects {
+            match obj {
+                TtpObject::Palette(p) => palettes.register(p),
+                TtpObject::Sprite(s) => sprites.push(s),
+                _ => {}
+            }
+        }
+        for sprite in sprites {
+            let palette = palettes.resolve_lenient(&sprite).palette.colors;
+            self.fingerprints.extend(SpriteFingerprint::new(path, &sprite, &palette));
+        }
+        Ok(())
+    }
+
+    /// Find duplicate pairs, strongest match first.
+    ///
+    /// Each pair is reported once with its strongest kind: exact, then
+    /// recolor, then near-duplicates with `similarity >= threshold`.
+    pub fn find(&self, threshold: f64) -> Vec<DuplicateMatch> {
+        let mut matches = Vec::new();
+        for (i, a) in self.fingerprints.iter().enumerate() {
+            for b in &self.fingerprints[i + 1..] {
+                if let Some(m) = compare(a, b, threshold) {
+                    matches.push(m);
+                }
+            }
+        }
+        let rank = |kind: DuplicateKind| kind as u8;
+        matches.sort_by(|a, b| {
+            rank(a.kind).cmp(&rank(b.kind)).then(b.similarity.total_cmp(&a.similarity))
+        });
+        matches
+    }
+}
+
+/// Compare two sprites across all orientations of the second.
+fn compare(a: &SpriteFingerprint, b: &SpriteFingerprint, threshold: f64) -> Option<DuplicateMatch> {
+    let target = a.identity();
+    let oriented = Orientation::ALL.iter().zip(&b.hashes);
+    let found = |kind: DuplicateKind, orientation: Orientation, similarity: f64| DuplicateMatch {
+        first: (a.file.clone(), a.sprite.clone()),
+        second: (b.file.clone(), b.sprite.clone()),
+        kind,
+        orientation,
+        similarity,
+    };
+
+    if let Some((&orientation, _)) = oriented.clone().find(|(_, h)| h.pixels == target.pixels) {
+        return Some(found(DuplicateKind::Exact, orientation, 1.0));
+    }
+    if let Some((&orientation, h)) = oriented.clone().find(|(_, h)| h.structure == target.structure)
+    {
+        return Some(found(DuplicateKind::Recolor, orientation, similarity(target, h)));
+    }
+    let (&orientation, best) = oriented
+        .map(|(o, h)| (o, similarity(target, h)))
+        .max_by(|(_, x), (_, y)| x.total_cmp(y))
+        .expect("eight orientations");
+    (best >= threshold).then(|| found(DuplicateKind::Near, orientation, best))
+}
+
+/// Fraction of agreeing bits across both perceptual hashes.
+fn similarity(a: &OrientedHashes, b: &OrientedHashes) -> f64 {
+    let differing = (a.ahash ^ b.ahash).count_ones() + (a.dhash ^ b.dhash).count_ones();
+    1.0 - differing as f64 / 128.0
+}
+
+/// Hash of the exact pixels (all fully transparent pixels are equal).
+fn pixel_hash(image: &RgbaImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.dimensions().hash(&mut hasher);
+    for pixel in image.pixels() {
+        let rgba = if pixel[3] == 0 { [0; 4] } else { pixel.0 };
+        rgba.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Label each pixel by its color, so structure can be compared independent of palette.
+fn token_image(image: &RgbaImage) -> image::ImageBuffer<Luma<u32>, Vec<u32>> {
+    let mut labels: HashMap<[u8; 4], u32> = HashMap::new();
+    image::ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let pixel = image.get_pixel(x, y);
+        let rgba = if pixel[3] == 0 { [0; 4] } else { pixel.0 };
+        let next = labels.len() as u32;
+        Luma([*labels.entry(rgba).or_insert(next)])
+    })
+}
+
+/// Hash of which pixels share a color, renumbering colors by first appearance.
+fn structure_hash(tokens: &image::ImageBuffer<Luma<u32>, Vec<u32>>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tokens.dimensions().hash(&mut hasher);
+    let mut relabel: HashMap<u32, u32> = HashMap::new();
+    for pixel in tokens.pixels() {
+        let next = relabel.len() as u32;
+        relabel.entry(pixel[0]).or_insert(next).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Grayscale with transparency blended toward mid-gray.
+fn intensity(image: &RgbaImage, width: u32, height: u32) -> GrayImage {
+    let gray = GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+        Luma([((luma * a as u32 + 128 * (255 - a as u32)) / 255) as u8])
+    });
+    imageops::resize(&gray, width, height, FilterType::Triangle)
+}
+
+/// 64-bit average hash: pixels of an 8x8 thumbnail brighter than the mean.
+fn average_hash(image: &RgbaImage) -> u64 {
+    let thumb = intensity(image, 8, 8);
+    let mean = thumb.pixels().map(|p| p[0] as u32).sum::<u32>() / 64;
+    thumb.pixels().enumerate().fold(0, |hash, (i, p)| hash | ((p[0] as u32 > mean) as u64) << i)
+}
+
+/// 64-bit difference hash: horizontal gradients of a 9x8 thumbnail.
+fn difference_hash(image: &RgbaImage) -> u64 {
+    let thumb = intensity(image, 9, 8);
+    let mut hash = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            let brighter = thumb.get_pixel(x + 1, y)[0] > thumb.get_pixel(x, y)[0];
+            hash |= (brighter as u64) << (y * 8 + x);
+        }
+    }
+    hash
+}
+
+/// Format duplicate matches as a report section.
+pub fn format_duplicates_text(matches: &[DuplicateMatch], threshold: f64) -> String {
+    let mut output = String::new();
+    output.push_str("DUPLICATES\n");
+    output.push_str("──────────\n");
+    if matches.is_empty() {
+        output
+            .push_str(&format!("  No duplicates found (threshold {:.0}%)\n\n", threshold * 100.0));
+        return output;
+    }
+
+    let name = |(file, sprite): &(PathBuf, String)| format!("{} ({})", sprite, file.display());
+    for m in matches {
+        let relation = match m.kind {
+            DuplicateKind::Exact => format!("{}", m.orientation),
+            DuplicateKind::Recolor if m.orientation == Orientation::Identity => {
+                "recolored".to_string()
+            }
+            DuplicateKind::Recolor => format!("recolored, {}", m.orientation),
+            DuplicateKind::Near if m.orientation == Orientation::Identity => {
+                format!("{:.1}% similar", m.similarity * 100.0)
+            }
+            DuplicateKind::Near => {
+                format!("{:.1}% similar, {}", m.similarity * 100.0, m.orientation)
+            }
+        };
+        output.push_str(&format!(
+            "  {:<8} {} ~ {}: {}\n",
+            m.kind.as_str(),
+            name(&m.first),
+            name(&m.second),
+            relation
+        ));
+    }
+    output.push('\n');
+    output
+}
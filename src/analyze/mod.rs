@@ -5,19 +5,32 @@
 //! - Dimensional distribution
 //! - Structural patterns
 //! - Compression opportunities
+//! - Duplicate and near-duplicate sprites
+//! - Color histograms of rendered sprites and compositions
+//! - Trends between two reports
 
+mod colors;
 mod compression;
 mod dimensions;
+mod duplicates;
 mod families;
 mod relationships;
 mod report;
 mod roles;
 mod shapes;
 mod tokens;
+mod trends;
 
 // Re-export public API
-pub use compression::{CompressionEstimator, CompressionStats, RleStats, RowRepetitionStats};
+pub use colors::{format_color_stats_text, ColorCount, ColorStats, PaletteMatch, JND_DELTA_E};
+pub use compression::{
+    CompressionEstimator, CompressionStats, RleStats, RowRepetitionStats, TokenRow,
+};
 pub use dimensions::DimensionStats;
+pub use duplicates::{
+    format_duplicates_text, DuplicateFinder, DuplicateKind, DuplicateMatch, Orientation,
+    SpriteFingerprint, DEFAULT_SIMILARITY_THRESHOLD,
+};
 pub use families::{TokenFamily, TokenFamilyDetector};
 pub use relationships::{
     infer_relationships_batch, RegionData, RelationshipInference, RelationshipInferrer,
@@ -32,6 +45,9 @@ pub use shapes::{
     DetectedShape, ShapeDetection, Symmetric,
 };
 pub use tokens::{CoOccurrenceMatrix, TokenCounter};
+pub use trends::{
+    compare_reports, format_comparison_text, MetricChange, ReportComparison, SectionChanges,
+};
 
 #[cfg(test)]
 mod tests {
@@ -1305,4 +1321,224 @@ mod tests {
         let relationships = infer_relationships_batch(&regions, 16);
         assert!(relationships.is_empty()); // No pairs to compare
     }
+
+    // Duplicate detection tests
+
+    fn fingerprint(name: &str, regions: &str, colors: &[(&str, &str)]) -> SpriteFingerprint {
+        let json = format!(
+            r#"{{"name": "{}", "size": [4, 4], "palette": {{}}, "regions": {}}}"#,
+            name, regions
+        );
+        let sprite: Sprite = serde_json::from_str(&json).unwrap();
+        let palette =
+            colors.iter().map(|(token, color)| (token.to_string(), color.to_string())).collect();
+        SpriteFingerprint::new(std::path::Path::new("test.pxl"), &sprite, &palette).unwrap()
+    }
+
+    const BOOT: &str = r#"{"a": {"rect": [0, 0, 1, 4]}, "b": {"rect": [1, 3, 3, 1]}}"#;
+    const BOOT_MIRRORED: &str = r#"{"a": {"rect": [3, 0, 1, 4]}, "b": {"rect": [0, 3, 3, 1]}}"#;
+    const COLORS: [(&str, &str); 2] = [("a", "#FF0000"), ("b", "#0000FF")];
+
+    #[test]
+    fn test_duplicates_exact_and_mirrored() {
+        let mut finder = DuplicateFinder::new();
+        finder.fingerprints.push(fingerprint("boot", BOOT, &COLORS));
+        finder.fingerprints.push(fingerprint("boot_copy", BOOT, &COLORS));
+        finder.fingerprints.push(fingerprint("boot_left", BOOT_MIRRORED, &COLORS));
+
+        let matches = finder.find(DEFAULT_SIMILARITY_THRESHOLD);
+        assert_eq!(matches.len(), 3);
+        assert!(matches.iter().all(|m| m.kind == DuplicateKind::Exact));
+
+        let mirrored = matches.iter().find(|m| m.second.1 == "boot_left").unwrap();
+        assert_eq!(mirrored.orientation, Orientation::FlipHorizontal);
+        assert_eq!(mirrored.orientation.to_string(), "mirrored");
+    }
+
+    #[test]
+    fn test_duplicates_recolor() {
+        let mut finder = DuplicateFinder::new();
+        finder.fingerprints.push(fingerprint("boot", BOOT, &COLORS));
+        finder.fingerprints.push(fingerprint(
+            "boot_green",
+            BOOT,
+            &[("a", "#00FF00"), ("b", "#FFFF00")],
+        ));
+
+        let matches = finder.find(DEFAULT_SIMILARITY_THRESHOLD);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].kind, DuplicateKind::Recolor);
+        assert_eq!(matches[0].orientation, Orientation::Identity);
+    }
+
+    #[test]
+    fn test_duplicates_near_respects_threshold() {
+        let mut finder = DuplicateFinder::new();
+        let wide = r#"{"a": {"rect": [0, 0, 4, 3]}}"#;
+        let full = r#"{"a": {"rect": [0, 0, 4, 4]}, "b": {"points": [[0, 3]]}}"#;
+        finder.fingerprints.push(fingerprint("wide", wide, &COLORS));
+        finder.fingerprints.push(fingerprint("full", full, &COLORS));
+
+        let near = finder.find(0.0);
+        assert_eq!(near.len(), 1);
+        assert_eq!(near[0].kind, DuplicateKind::Near);
+        assert!(near[0].similarity < 1.0);
+        assert!(finder.find(1.0).is_empty());
+    }
+
+    #[test]
+    fn test_duplicates_skip_blank_sprites() {
+        let sprite: Sprite =
+            serde_json::from_str(r#"{"name": "empty", "size": [4, 4], "palette": {}}"#).unwrap();
+        let fingerprint = SpriteFingerprint::new(
+            std::path::Path::new("test.pxl"),
+            &sprite,
+            &std::collections::HashMap::new(),
+        );
+        assert!(fingerprint.is_none());
+    }
+
+    #[test]
+    fn test_format_duplicates_text() {
+        let mut finder = DuplicateFinder::new();
+        finder.fingerprints.push(fingerprint("boot", BOOT, &COLORS));
+        finder.fingerprints.push(fingerprint("boot_left", BOOT_MIRRORED, &COLORS));
+
+        let text = format_duplicates_text(&finder.find(DEFAULT_SIMILARITY_THRESHOLD), 0.9);
+        assert!(text.starts_with("DUPLICATES\n"));
+        assert!(text.contains("boot (test.pxl) ~ boot_left (test.pxl): mirrored"));
+
+        let empty = format_duplicates_text(&[], 0.9);
+        assert!(empty.contains("No duplicates found (threshold 90%)"));
+    }
+
+    // Report and trend tests
+
+    fn region_sprite(regions: &str) -> Sprite {
+        let json =
+            format!(r#"{{"name": "s", "size": [4, 4], "palette": {{}}, "regions": {}}}"#, regions);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_analyze_sprite_counts_visible_tokens() {
+        let mut report = AnalysisReport::new();
+        report.analyze_sprite(&region_sprite(
+            r#"{"body": {"rect": [0, 0, 4, 4]}, "eye": {"points": [[1, 1]], "z": 1, "role": "anchor"}}"#,
+        ));
+
+        assert_eq!(report.token_counter.get("{body}"), 15);
+        assert_eq!(report.token_counter.get("{eye}"), 1);
+        assert_eq!(report.co_occurrence.get("{body}", "{eye}"), 1);
+        assert_eq!(report.declared_roles.get("anchor"), 1);
+        assert_eq!(report.inferred_roles.total(), 1);
+    }
+
+    #[test]
+    fn test_compression_from_token_rows() {
+        let stats = CompressionEstimator::analyze_sprite(&region_sprite(
+            r#"{"a": {"rect": [0, 0, 4, 2]}, "b": {"rect": [2, 2, 2, 2]}}"#,
+        ));
+
+        // Rows: aaaa, aaaa, __bb, __bb
+        assert_eq!(stats.rle.total_tokens, 16);
+        assert_eq!(stats.rle.total_runs, 6);
+        assert_eq!(stats.rle.total_unique_per_row, 6);
+        assert_eq!(stats.row_repetition.repeated_rows, 2);
+        assert!((stats.row_repetition.compression_ratio() - 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_report_to_json() {
+        let mut report = AnalysisReport::new();
+        report.analyze_sprite(&region_sprite(r#"{"a": {"rect": [0, 0, 4, 4]}}"#));
+
+        let json = report.to_json();
+        assert_eq!(json["total_sprites"], 1);
+        assert_eq!(json["tokens"]["{a}"], 16);
+        assert_eq!(json["dimensions"]["4x4"], 1);
+        assert_eq!(json["compression"]["rle_ratio"], 4.0);
+        assert!(json["roles"]["inferred"].is_object());
+    }
+
+    #[test]
+    fn test_compare_reports() {
+        let mut old = AnalysisReport::new();
+        old.analyze_sprite(&region_sprite(r#"{"a": {"rect": [0, 0, 4, 4]}}"#));
+        let mut new = AnalysisReport::new();
+        new.analyze_sprite(&region_sprite(r#"{"a": {"rect": [0, 0, 4, 2]}}"#));
+        new.analyze_sprite(&region_sprite(r#"{"b": {"rect": [0, 0, 4, 4]}}"#));
+
+        let comparison = compare_reports(&old.to_json(), &new.to_json()).unwrap();
+        assert!(comparison.has_changes());
+
+        let sprites = comparison.metrics.iter().find(|m| m.metric == "total_sprites").unwrap();
+        assert_eq!((sprites.old, sprites.new, sprites.delta()), (1.0, 2.0, 1.0));
+
+        let tokens = comparison.sections.iter().find(|s| s.section == "tokens").unwrap();
+        let changes: Vec<(&str, f64)> =
+            tokens.changes.iter().map(|c| (c.metric.as_str(), c.delta())).collect();
+        assert_eq!(changes, [("{a}", -8.0), ("{b}", 16.0)]);
+
+        let text = format_comparison_text(&comparison);
+        assert!(text.contains("TOKEN CHANGES"));
+        assert!(text.contains("(+16)"));
+    }
+
+    #[test]
+    fn test_compare_reports_unchanged() {
+        let mut report = AnalysisReport::new();
+        report.analyze_sprite(&region_sprite(r#"{"a": {"rect": [0, 0, 4, 4]}}"#));
+
+        let comparison = compare_reports(&report.to_json(), &report.to_json()).unwrap();
+        assert!(!comparison.has_changes());
+        assert!(comparison.sections.is_empty());
+        assert!(format_comparison_text(&comparison).contains("No changes"));
+
+        assert!(compare_reports(&serde_json::json!([]), &report.to_json()).is_err());
+    }
+
+    // Color statistics tests
+
+    #[test]
+    fn test_color_stats() {
+        let mut image = image::RgbaImage::new(4, 2);
+        for x in 0..4 {
+            image.put_pixel(x, 0, image::Rgba([0x9B, 0xBC, 0x0F, 255]));
+        }
+        image.put_pixel(0, 1, image::Rgba([0x0F, 0x38, 0x0F, 255]));
+        image.put_pixel(1, 1, image::Rgba([0x0F, 0x38, 0x0F, 128]));
+        // Transparent pixels count as one color whatever their RGB
+        image.put_pixel(2, 1, image::Rgba([255, 0, 0, 0]));
+
+        let stats = ColorStats::new(&image);
+        assert_eq!(stats.unique_colors(), 3);
+        assert_eq!(stats.histogram[0], ColorCount { rgba: [0x9B, 0xBC, 0x0F, 255], count: 4 });
+        assert_eq!(stats.histogram[1], ColorCount { rgba: [0, 0, 0, 0], count: 2 });
+        assert_eq!(
+            (stats.opaque_pixels, stats.translucent_pixels, stats.transparent_pixels),
+            (5, 1, 2)
+        );
+        assert_eq!(stats.alpha_levels, 2);
+
+        let palette = stats.palette_match.as_ref().unwrap();
+        assert_eq!(palette.palette, "gameboy");
+        assert_eq!(palette.score, 1.0);
+        assert!(palette.mean_delta_e < JND_DELTA_E);
+
+        let text = format_color_stats_text("sprite", "leaf", &stats, 2);
+        assert!(text.starts_with("COLORS: sprite 'leaf' (4x2)\n"));
+        assert!(text.contains("@gameboy (100.0% of pixels match"));
+        assert!(text.contains("#9BBC0FFF       4   66.7%"));
+        assert!(text.contains("... 1 more"));
+        assert_eq!(stats.to_json()["alpha"]["translucent_pixels"], 1);
+    }
+
+    #[test]
+    fn test_color_stats_blank_image() {
+        let stats = ColorStats::new(&image::RgbaImage::new(2, 2));
+        assert_eq!(stats.unique_colors(), 0);
+        assert_eq!(stats.transparent_pixels, 4);
+        assert!(stats.palette_match.is_none());
+    }
 }
@@ -1,14 +1,17 @@
 //! Analysis report generation and file collection
 
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
 use crate::models::{Sprite, TtpObject};
+use crate::output::{is_stdio, open_input};
 use crate::parser::parse_stream;
 
+use super::compression::{sprite_token_rows, CompressionEstimator, CompressionStats};
 use super::dimensions::DimensionStats;
 use super::families::{TokenFamily, TokenFamilyDetector};
+use super::roles::{infer_roles_batch, RegionMap, RoleInferenceContext};
 use super::tokens::{CoOccurrenceMatrix, TokenCounter};
 
 /// Aggregated analysis report for a corpus.
@@ -36,6 +39,12 @@ pub struct AnalysisReport {
     pub dimension_stats: DimensionStats,
     /// Palette sizes (tokens per palette)
     pub palette_sizes: Vec<usize>,
+    /// Compression opportunities across all sprites
+    pub compression: CompressionStats,
+    /// Roles declared on regions
+    pub declared_roles: TokenCounter,
+    /// Roles inferred for regions without a declared role
+    pub inferred_roles: TokenCounter,
     /// Files that had parse errors
     pub failed_files: Vec<(PathBuf, String)>,
 }
@@ -48,8 +57,8 @@ impl AnalysisReport {
 
     /// Analyze a sprite and add its data to the report.
     ///
-    /// NOTE: This function requires update for v2 region-based format.
-    /// Grid-based analysis is deprecated. See TTP-7i4v for grid removal.
+    /// Tokens are counted once per visible pixel, so regions hidden under
+    /// higher-z regions only count where they show.
     pub fn analyze_sprite(&mut self, sprite: &Sprite) {
         self.total_sprites += 1;
 
@@ -58,15 +67,50 @@ impl AnalysisReport {
             self.dimension_stats.add(w, h);
         }
 
-        // TODO: Update for v2 region-based format
-        // Grid-based token analysis is deprecated.
-        // For now, we only count sprites and record explicit sizes.
+        let rows = sprite_token_rows(sprite);
+        if rows.is_empty() {
+            return;
+        }
+
+        let mut visible: RegionMap = HashMap::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, token) in row.iter().enumerate() {
+                if let Some(token) = token {
+                    visible.entry(token.clone()).or_default().0.insert((x as i32, y as i32));
+                }
+            }
+        }
+
+        let mut tokens = HashSet::new();
+        for (token, (pixels, _)) in &visible {
+            let token = format!("{{{}}}", token);
+            self.token_counter.add_count(&token, pixels.len());
+            tokens.insert(token);
+        }
+        self.co_occurrence.record_sprite(&tokens);
+
+        self.compression.merge(&CompressionStats {
+            rle: CompressionEstimator::analyze_rows_rle(&rows),
+            row_repetition: CompressionEstimator::analyze_rows_repetition(&rows),
+        });
+
+        // Declared roles win; infer the rest from each region's visible pixels
+        for (token, region) in sprite.regions.iter().flatten() {
+            if let Some(role) = region.role {
+                self.declared_roles.add(&role.to_string());
+                visible.remove(token);
+            }
+        }
+        let ctx = RoleInferenceContext::new(rows[0].len() as u32, rows.len() as u32);
+        let (inferred, _) = infer_roles_batch(&visible, &ctx);
+        for inference in inferred.values() {
+            self.inferred_roles.add(&inference.role.to_string());
+        }
     }
 
     /// Analyze a single file and add results to the report.
     pub fn analyze_file(&mut self, path: &Path) -> Result<(), String> {
-        let file = fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
-        let reader = BufReader::new(file);
+        let reader = open_input(path).map_err(|e| format!("Failed to open file: {}", e))?;
         let result = parse_stream(reader);
 
         for obj in result.objects {
@@ -87,6 +131,9 @@ impl AnalysisReport {
                 TtpObject::Variant(_) => {
                     self.total_variants += 1;
                 }
+                TtpObject::Sequence(_) => {
+                    // Sequences only reorder existing animations, not analyzed
+                }
                 TtpObject::Particle(_) => {
                     // Particle systems are runtime constructs, not analyzed
                 }
@@ -99,6 +146,15 @@ impl AnalysisReport {
                 TtpObject::Import(_) => {
                     // Import declarations are resolved during loading, not analyzed
                 }
+                TtpObject::Theme(_) => {
+                    // Themes only swap palettes at render time, not analyzed
+                }
+                TtpObject::Stamp(_) => {
+                    // Stamps are drawn into sprite regions during parsing
+                }
+                TtpObject::RandomSet(_) => {
+                    // Random sets only describe objects `pxl expand` generates
+                }
             }
         }
 
@@ -120,6 +176,81 @@ impl AnalysisReport {
         let detector = TokenFamilyDetector::new();
         detector.detect(&self.token_counter)
     }
+
+    /// Structured report for `--format json`.
+    ///
+    /// Full token, dimension, and role counts are included (not just the top
+    /// entries) so two reports can be compared with [`compare_reports`].
+    ///
+    /// [`compare_reports`]: super::compare_reports
+    pub fn to_json(&self) -> serde_json::Value {
+        let counts = |counter: &TokenCounter| {
+            counter
+                .sorted_by_frequency()
+                .into_iter()
+                .map(|(token, count)| (token.clone(), serde_json::json!(count)))
+                .collect::<serde_json::Map<_, _>>()
+        };
+        let dimensions: serde_json::Map<_, _> = self
+            .dimension_stats
+            .sorted_by_frequency()
+            .into_iter()
+            .map(|((w, h), count)| (format!("{}x{}", w, h), serde_json::json!(count)))
+            .collect();
+        let rle = &self.compression.rle;
+        let rows = &self.compression.row_repetition;
+
+        serde_json::json!({
+            "files_analyzed": self.files_analyzed,
+            "files_failed": self.files_failed,
+            "total_sprites": self.total_sprites,
+            "total_palettes": self.total_palettes,
+            "total_compositions": self.total_compositions,
+            "total_animations": self.total_animations,
+            "total_variants": self.total_variants,
+            "unique_tokens": self.token_counter.unique_count(),
+            "total_token_occurrences": self.token_counter.total(),
+            "tokens": counts(&self.token_counter),
+            "top_tokens": self.token_counter.top_n(10).iter().map(|(t, c)| {
+                serde_json::json!({
+                    "token": t,
+                    "count": c,
+                    "percentage": self.token_counter.percentage(t)
+                })
+            }).collect::<Vec<_>>(),
+            "co_occurrence": self.co_occurrence.top_n(10).iter().map(|((t1, t2), count)| {
+                serde_json::json!({
+                    "token1": t1,
+                    "token2": t2,
+                    "sprites": count
+                })
+            }).collect::<Vec<_>>(),
+            "token_families": self.token_families().iter().take(10).map(|family| {
+                serde_json::json!({
+                    "prefix": family.prefix,
+                    "tokens": family.tokens,
+                    "total_count": family.total_count
+                })
+            }).collect::<Vec<_>>(),
+            "dimensions": dimensions,
+            "avg_palette_size": self.avg_palette_size(),
+            "compression": {
+                "rle_ratio": rle.compression_ratio(),
+                "avg_runs_per_row": rle.avg_runs_per_row(),
+                "avg_unique_per_row": rle.avg_unique_per_row(),
+                "repeated_row_percentage": rows.repetition_percentage(),
+                "row_repetition_ratio": rows.compression_ratio(),
+                "combined_ratio": self.compression.combined_compression_ratio(),
+            },
+            "roles": {
+                "declared": counts(&self.declared_roles),
+                "inferred": counts(&self.inferred_roles),
+            },
+            "failed_files": self.failed_files.iter().map(|(path, error)| {
+                serde_json::json!({ "file": path.display().to_string(), "error": error })
+            }).collect::<Vec<_>>(),
+        })
+    }
 }
 
 /// Collect files to analyze based on input specification.
@@ -133,7 +264,7 @@ pub fn collect_files(
     // If specific files provided, use them
     if !files.is_empty() {
         for path in files {
-            if path.exists() {
+            if is_stdio(path) || path.exists() {
                 result.push(path.clone());
             } else {
                 return Err(format!("File not found: {}", path.display()));
@@ -273,6 +404,42 @@ pub fn format_report_text(report: &AnalysisReport) -> String {
         output.push('\n');
     }
 
+    // Compression
+    let rle = &report.compression.rle;
+    if rle.total_rows > 0 {
+        let rows = &report.compression.row_repetition;
+        output.push_str("COMPRESSION\n");
+        output.push_str("───────────\n");
+        output.push_str(&format!("  RLE ratio:             {:.2}x\n", rle.compression_ratio()));
+        output.push_str(&format!("  Avg runs/row:          {:.1}\n", rle.avg_runs_per_row()));
+        output
+            .push_str(&format!("  Repeated rows:         {:.1}%\n", rows.repetition_percentage()));
+        output.push_str(&format!(
+            "  Combined estimate:     {:.2}x\n",
+            report.compression.combined_compression_ratio()
+        ));
+        output.push('\n');
+    }
+
+    // Roles
+    if report.declared_roles.total() + report.inferred_roles.total() > 0 {
+        output.push_str("ROLES\n");
+        output.push_str("─────\n");
+        for (label, counter) in
+            [("declared", &report.declared_roles), ("inferred", &report.inferred_roles)]
+        {
+            let roles: Vec<String> = counter
+                .sorted_by_frequency()
+                .iter()
+                .map(|(role, count)| format!("{} {}", role, count))
+                .collect();
+            if !roles.is_empty() {
+                output.push_str(&format!("  {:<10} {}\n", label, roles.join(", ")));
+            }
+        }
+        output.push('\n');
+    }
+
     // Palette patterns
     if !report.palette_sizes.is_empty() {
         output.push_str("PALETTE PATTERNS\n");
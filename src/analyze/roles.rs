@@ -279,7 +279,11 @@ pub fn infer_roles_batch(
 
     let all_colors: Vec<[u8; 4]> = regions.values().filter_map(|(_, color)| *color).collect();
 
-    for (name, (pixels, color)) in regions {
+    // Walk regions by name so warnings come out in a stable order
+    let mut names: Vec<&String> = regions.keys().collect();
+    names.sort();
+    for name in names {
+        let (pixels, color) = &regions[name];
         let adjacent: Vec<[u8; 4]> = all_colors
             .iter()
             .filter(|c| color.map(|col| **c != col).unwrap_or(true))
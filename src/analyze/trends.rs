@@ -0,0 +1,180 @@
+//! Trend comparison between two JSON analysis reports
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Summary metrics compared between reports, as JSON paths
+const METRICS: &[&str] = &[
+    "files_analyzed",
+    "total_sprites",
+    "total_palettes",
+    "total_compositions",
+    "total_animations",
+    "total_variants",
+    "unique_tokens",
+    "total_token_occurrences",
+    "avg_palette_size",
+    "compression.rle_ratio",
+    "compression.repeated_row_percentage",
+    "compression.combined_ratio",
+];
+
+/// Count sections compared key by key, with their text report titles
+const COUNT_SECTIONS: &[(&str, &str)] = &[
+    ("tokens", "TOKEN CHANGES"),
+    ("dimensions", "DIMENSION CHANGES"),
+    ("roles.declared", "DECLARED ROLE CHANGES"),
+    ("roles.inferred", "INFERRED ROLE CHANGES"),
+];
+
+/// A metric's value in the old and new report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricChange {
+    /// Metric path (e.g. `total_sprites`) or count key (e.g. `{skin}`)
+    pub metric: String,
+    /// Value in the old report
+    pub old: f64,
+    /// Value in the new report
+    pub new: f64,
+}
+
+impl MetricChange {
+    /// Difference from old to new
+    pub fn delta(&self) -> f64 {
+        self.new - self.old
+    }
+}
+
+/// Count changes within one section (tokens, dimensions, roles)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionChanges {
+    /// Section path (e.g. `tokens` or `roles.inferred`)
+    pub section: String,
+    /// Keys whose count changed; absent keys count as 0
+    pub changes: Vec<MetricChange>,
+}
+
+/// Differences between two analysis reports
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportComparison {
+    /// Summary metrics present in both reports, changed or not
+    pub metrics: Vec<MetricChange>,
+    /// Sections with changed counts
+    pub sections: Vec<SectionChanges>,
+}
+
+impl ReportComparison {
+    /// Whether any metric or count changed.
+    pub fn has_changes(&self) -> bool {
+        !self.sections.is_empty() || self.metrics.iter().any(|m| m.delta() != 0.0)
+    }
+}
+
+/// Compare two reports produced by `pxl analyze --format json`.
+///
+/// Metrics missing from either report (e.g. from an older pxl) are skipped.
+pub fn compare_reports(old: &Value, new: &Value) -> Result<ReportComparison, String> {
+    if !old.is_object() || !new.is_object() {
+        return Err("Analysis report must be a JSON object".to_string());
+    }
+
+    let metrics = METRICS
+        .iter()
+        .filter_map(|path| {
+            let old = lookup(old, path)?.as_f64()?;
+            let new = lookup(new, path)?.as_f64()?;
+            Some(MetricChange { metric: path.to_string(), old, new })
+        })
+        .collect();
+
+    let sections = COUNT_SECTIONS
+        .iter()
+        .filter_map(|(path, _)| {
+            let old = lookup(old, path).and_then(Value::as_object)?;
+            let new = lookup(new, path).and_then(Value::as_object)?;
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            let changes: Vec<MetricChange> = keys
+                .into_iter()
+                .map(|key| MetricChange {
+                    metric: key.clone(),
+                    old: old.get(key).and_then(Value::as_f64).unwrap_or(0.0),
+                    new: new.get(key).and_then(Value::as_f64).unwrap_or(0.0),
+                })
+                .filter(|change| change.delta() != 0.0)
+                .collect();
+            (!changes.is_empty()).then(|| SectionChanges { section: path.to_string(), changes })
+        })
+        .collect();
+
+    Ok(ReportComparison { metrics, sections })
+}
+
+/// Look up a dotted path (`compression.rle_ratio`) in a JSON object.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
+
+/// Format a comparison as text.
+pub fn format_comparison_text(comparison: &ReportComparison) -> String {
+    let mut output = String::new();
+    output.push_str("Pixelsrc Analysis Comparison\n");
+    output.push_str("============================\n");
+    if !comparison.has_changes() {
+        output.push_str("No changes\n");
+    }
+    output.push('\n');
+
+    if !comparison.metrics.is_empty() {
+        output.push_str("METRICS\n");
+        output.push_str("───────\n");
+        for change in &comparison.metrics {
+            output.push_str(&format_change(change, 36));
+        }
+        output.push('\n');
+    }
+
+    for section in &comparison.sections {
+        let title = COUNT_SECTIONS
+            .iter()
+            .find(|(path, _)| *path == section.section)
+            .map_or("CHANGES", |(_, title)| title);
+        output.push_str(&format!("{}\n{}\n", title, "─".repeat(title.chars().count())));
+        for change in &section.changes {
+            output.push_str(&format_change(change, 20));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn format_change(change: &MetricChange, width: usize) -> String {
+    let delta = change.delta();
+    let delta = if delta == 0.0 {
+        String::new()
+    } else if delta > 0.0 {
+        format!("  (+{})", number(delta))
+    } else {
+        format!("  (-{})", number(-delta))
+    };
+    let line = format!(
+        "  {:<width$} {:>8} → {:<8}{}",
+        change.metric,
+        number(change.old),
+        number(change.new),
+        delta,
+        width = width
+    );
+    format!("{}\n", line.trim_end())
+}
+
+/// Whole numbers without decimals, others to two places.
+fn number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
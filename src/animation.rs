@@ -3,17 +3,7 @@
 use crate::models::{Animation, FrameTag, Sprite};
 use std::collections::HashMap;
 
-/// A warning generated during animation validation
-#[derive(Debug, Clone, PartialEq)]
-pub struct Warning {
-    pub message: String,
-}
-
-impl Warning {
-    pub fn new(message: impl Into<String>) -> Self {
-        Self { message: message.into() }
-    }
-}
+pub use crate::warning::{Warning, WarningCategory};
 
 /// Validate frame tags against the animation's frame count.
 ///
@@ -30,24 +20,33 @@ fn validate_frame_tags(
     for (tag_name, tag) in tags {
         // Check start <= end
         if tag.start > tag.end {
-            warnings.push(Warning::new(format!(
-                "Animation '{}' tag '{}' has invalid range: start ({}) > end ({})",
-                anim_name, tag_name, tag.start, tag.end
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Animation,
+                format!(
+                    "Animation '{}' tag '{}' has invalid range: start ({}) > end ({})",
+                    anim_name, tag_name, tag.start, tag.end
+                ),
+            ));
         }
 
         // Check bounds against frame count
         if tag.start as usize >= frame_count {
-            warnings.push(Warning::new(format!(
-                "Animation '{}' tag '{}' start ({}) is out of bounds (animation has {} frames)",
-                anim_name, tag_name, tag.start, frame_count
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Animation,
+                format!(
+                    "Animation '{}' tag '{}' start ({}) is out of bounds (animation has {} frames)",
+                    anim_name, tag_name, tag.start, frame_count
+                ),
+            ));
         }
         if tag.end as usize >= frame_count {
-            warnings.push(Warning::new(format!(
-                "Animation '{}' tag '{}' end ({}) is out of bounds (animation has {} frames)",
-                anim_name, tag_name, tag.end, frame_count
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Animation,
+                format!(
+                    "Animation '{}' tag '{}' end ({}) is out of bounds (animation has {} frames)",
+                    anim_name, tag_name, tag.end, frame_count
+                ),
+            ));
         }
     }
 
@@ -83,7 +82,10 @@ pub fn validate_animation(anim: &Animation, sprites: &[Sprite]) -> Vec<Warning>
 
     // Warn if animation has no frames
     if anim.frames.is_empty() {
-        warnings.push(Warning::new(format!("Animation '{}' has no frames", anim.name)));
+        warnings.push(Warning::new(
+            WarningCategory::Animation,
+            format!("Animation '{}' has no frames", anim.name),
+        ));
         return warnings;
     }
 
@@ -94,10 +96,10 @@ pub fn validate_animation(anim: &Animation, sprites: &[Sprite]) -> Vec<Warning>
     // Check each frame references an existing sprite
     for frame in &anim.frames {
         if !sprite_names.contains(frame.as_str()) {
-            warnings.push(Warning::new(format!(
-                "Animation '{}' references unknown sprite '{}'",
-                anim.name, frame
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Animation,
+                format!("Animation '{}' references unknown sprite '{}'", anim.name, frame),
+            ));
         }
     }
 
@@ -109,12 +111,15 @@ pub fn validate_animation(anim: &Animation, sprites: &[Sprite]) -> Vec<Warning>
     // Validate frame_metadata length matches frames
     if let Some(frame_meta) = &anim.frame_metadata {
         if frame_meta.len() != anim.frames.len() {
-            warnings.push(Warning::new(format!(
-                "Animation '{}' has {} frames but {} frame_metadata entries",
-                anim.name,
-                anim.frames.len(),
-                frame_meta.len()
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Animation,
+                format!(
+                    "Animation '{}' has {} frames but {} frame_metadata entries",
+                    anim.name,
+                    anim.frames.len(),
+                    frame_meta.len()
+                ),
+            ));
         }
     }
 
@@ -330,7 +335,7 @@ mod tests {
 
     #[test]
     fn test_warning_creation() {
-        let warning = Warning::new("test message");
+        let warning = Warning::new(WarningCategory::Animation, "test message");
         assert_eq!(warning.message, "test message");
     }
 
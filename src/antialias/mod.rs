@@ -21,6 +21,8 @@ use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::ordered;
+
 /// Antialiasing algorithm selection.
 ///
 /// Each algorithm has different trade-offs between quality and performance:
@@ -169,6 +171,7 @@ pub struct AntialiasConfig {
 
     /// Per-region antialiasing overrides
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub regions: Option<HashMap<String, RegionAAOverride>>,
 }
 
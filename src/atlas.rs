@@ -6,6 +6,10 @@ use image::{Rgba, RgbaImage};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::models::{CollisionBox, FrameTag, PlaybackDirection, SpriteMetadata};
+use crate::ordered;
+use crate::spritesheet::pivot_layout;
+
 /// Configuration for atlas packing
 #[derive(Debug, Clone)]
 pub struct AtlasConfig {
@@ -32,8 +36,16 @@ pub struct AtlasBox {
     pub h: u32,
 }
 
+impl AtlasBox {
+    /// Returns this box scaled by an integer factor.
+    pub fn scaled(&self, scale: u32) -> Self {
+        let s = scale as i32;
+        Self { x: self.x * s, y: self.y * s, w: self.w * scale, h: self.h * scale }
+    }
+}
+
 /// A sprite's position and size within an atlas
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AtlasFrame {
     pub x: u32,
     pub y: u32,
@@ -44,7 +56,18 @@ pub struct AtlasFrame {
     pub origin: Option<[i32; 2]>,
     /// Collision boxes (hit, hurt, collide, trigger, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "ordered::option_map")]
     pub boxes: Option<HashMap<String, AtlasBox>>,
+    /// Where this sprite connects to its parent (weapon grip, chain link)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub attach_in: Option<[i32; 2]>,
+    /// Where the next sprite or effect attaches to this one
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub attach_out: Option<[i32; 2]>,
+    /// Named sub-rectangles of the frame (UI kit states), relative to the frame
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
+    pub slices: Option<HashMap<String, AtlasBox>>,
 }
 
 /// Animation metadata for atlas export
@@ -53,7 +76,20 @@ pub struct AtlasAnimation {
     pub frames: Vec<String>,
     pub fps: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "ordered::option_map")]
     pub tags: Option<HashMap<String, AtlasTag>>,
+    /// Per-frame durations in milliseconds, when frames are not evenly timed
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub durations: Option<Vec<u32>>,
+    /// Shared frame size when frames of different sizes are lined up on their origins
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub canvas: Option<[u32; 2]>,
+    /// Where each frame's top-left sits on `canvas`, one per entry in `frames`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub offsets: Option<Vec<[u32; 2]>>,
+    /// Playback direction, when not forward
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub direction: Option<PlaybackDirection>,
 }
 
 /// A tag within an animation (frame range)
@@ -70,11 +106,29 @@ pub struct AtlasTag {
 pub struct AtlasMetadata {
     pub image: String,
     pub size: [u32; 2],
+    #[serde(serialize_with = "ordered::map")]
     pub frames: HashMap<String, AtlasFrame>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(serialize_with = "ordered::map")]
     pub animations: HashMap<String, AtlasAnimation>,
 }
 
+impl AtlasMetadata {
+    /// Frames sorted by name, so output doesn't depend on hash order.
+    pub fn frames_by_name(&self) -> Vec<(&String, &AtlasFrame)> {
+        let mut frames: Vec<_> = self.frames.iter().collect();
+        frames.sort_by_key(|(name, _)| *name);
+        frames
+    }
+
+    /// Animations sorted by name, so output doesn't depend on hash order.
+    pub fn animations_by_name(&self) -> Vec<(&String, &AtlasAnimation)> {
+        let mut animations: Vec<_> = self.animations.iter().collect();
+        animations.sort_by_key(|(name, _)| *name);
+        animations
+    }
+}
+
 /// Result of atlas packing - may produce multiple atlases
 #[derive(Debug)]
 pub struct AtlasResult {
@@ -83,7 +137,7 @@ pub struct AtlasResult {
 }
 
 /// A sprite to be packed into an atlas
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SpriteInput {
     pub name: String,
     pub image: RgbaImage,
@@ -91,6 +145,80 @@ pub struct SpriteInput {
     pub origin: Option<[i32; 2]>,
     /// Optional collision boxes for this sprite
     pub boxes: Option<HashMap<String, AtlasBox>>,
+    /// Optional attachment point from the parent sprite
+    pub attach_in: Option<[i32; 2]>,
+    /// Optional attachment point for child sprites
+    pub attach_out: Option<[i32; 2]>,
+    /// Optional named slices of this sprite
+    pub slices: Option<HashMap<String, AtlasBox>>,
+}
+
+impl SpriteInput {
+    /// Create an input from a rendered sprite, taking its origin, attachment
+    /// points, collision boxes and slices from the sprite's metadata.
+    pub fn with_metadata(
+        name: String,
+        image: RgbaImage,
+        metadata: Option<&SpriteMetadata>,
+    ) -> Self {
+        let Some(meta) = metadata else {
+            return Self {
+                name,
+                image,
+                origin: None,
+                boxes: None,
+                attach_in: None,
+                attach_out: None,
+                slices: None,
+            };
+        };
+        let atlas_boxes = |boxes: &HashMap<String, CollisionBox>| {
+            boxes
+                .iter()
+                .map(|(name, cb)| (name.clone(), AtlasBox { x: cb.x, y: cb.y, w: cb.w, h: cb.h }))
+                .collect()
+        };
+        Self {
+            name,
+            image,
+            origin: meta.origin,
+            boxes: meta.boxes.as_ref().map(atlas_boxes),
+            attach_in: meta.attach_in,
+            attach_out: meta.attach_out,
+            slices: meta.slices.as_ref().map(atlas_boxes),
+        }
+    }
+
+    /// Returns a copy scaled by an integer factor (nearest neighbour).
+    ///
+    /// The origin, attachment points, collision boxes and slices are scaled
+    /// with the image so the atlas metadata stays in pixel coordinates of the scaled output.
+    pub fn scaled(&self, scale: u32) -> Self {
+        if scale <= 1 {
+            return self.clone();
+        }
+        let s = scale as i32;
+        Self {
+            name: self.name.clone(),
+            image: image::imageops::resize(
+                &self.image,
+                self.image.width() * scale,
+                self.image.height() * scale,
+                image::imageops::FilterType::Nearest,
+            ),
+            origin: self.origin.map(|[x, y]| [x * s, y * s]),
+            boxes: self
+                .boxes
+                .as_ref()
+                .map(|b| b.iter().map(|(name, bx)| (name.clone(), bx.scaled(scale))).collect()),
+            attach_in: self.attach_in.map(|[x, y]| [x * s, y * s]),
+            attach_out: self.attach_out.map(|[x, y]| [x * s, y * s]),
+            slices: self
+                .slices
+                .as_ref()
+                .map(|b| b.iter().map(|(name, bx)| (name.clone(), bx.scaled(scale))).collect()),
+        }
+    }
 }
 
 /// A shelf in the shelf packing algorithm
@@ -107,7 +235,8 @@ const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
 /// Pack sprites into texture atlases.
 ///
 /// Uses a simple shelf-based bin packing algorithm. Sprites are sorted by height
-/// (tallest first) and placed into horizontal shelves.
+/// (tallest first, ties by name) and placed into horizontal shelves, so the same
+/// sprites always pack the same way whatever order they are given in.
 ///
 /// # Arguments
 ///
@@ -127,9 +256,12 @@ pub fn pack_atlas(
         return AtlasResult { atlases: vec![] };
     }
 
-    // Sort sprites by height descending (better packing)
+    // Sort sprites by height descending (better packing), then by name so the
+    // layout doesn't depend on the order sprites were collected in
     let mut sorted_sprites: Vec<(usize, &SpriteInput)> = sprites.iter().enumerate().collect();
-    sorted_sprites.sort_by(|a, b| b.1.image.height().cmp(&a.1.image.height()));
+    sorted_sprites.sort_by(|a, b| {
+        b.1.image.height().cmp(&a.1.image.height()).then_with(|| a.1.name.cmp(&b.1.name))
+    });
 
     let mut atlases: Vec<(RgbaImage, AtlasMetadata, Vec<Shelf>)> = vec![];
     let mut sprite_to_atlas: HashMap<String, (usize, AtlasFrame)> = HashMap::new();
@@ -162,6 +294,9 @@ pub fn pack_atlas(
                             h: sprite_h,
                             origin: sprite.origin,
                             boxes: sprite.boxes.clone(),
+                            attach_in: sprite.attach_in,
+                            attach_out: sprite.attach_out,
+                            slices: sprite.slices.clone(),
                         },
                     ),
                 );
@@ -208,6 +343,9 @@ pub fn pack_atlas(
                             h: sprite_h,
                             origin: sprite.origin,
                             boxes: sprite.boxes.clone(),
+                            attach_in: sprite.attach_in,
+                            attach_out: sprite.attach_out,
+                            slices: sprite.slices.clone(),
                         },
                     ),
                 );
@@ -335,15 +473,67 @@ fn copy_sprite_to_atlas(atlas: &mut RgbaImage, sprite: &RgbaImage, x: u32, y: u3
 }
 
 /// Add animation metadata to an atlas
+///
+/// Tags whose range falls outside `frame_names` are dropped; ranges that run
+/// past the last frame are clamped. `durations` is recorded only when it has
+/// one entry per frame and the frames are not all the same length.
 pub fn add_animation_to_atlas(
     metadata: &mut AtlasMetadata,
     name: &str,
     frame_names: &[String],
     fps: u32,
+    tags: Option<&HashMap<String, FrameTag>>,
+    durations: Option<&[u32]>,
+    direction: PlaybackDirection,
 ) {
-    metadata
-        .animations
-        .insert(name.to_string(), AtlasAnimation { frames: frame_names.to_vec(), fps, tags: None });
+    let tags = tags.map(|tags| {
+        tags.iter()
+            .filter_map(|(tag_name, tag)| {
+                let range = tag.range(frame_names.len())?;
+                Some((
+                    tag_name.clone(),
+                    AtlasTag {
+                        from: *range.start() as u32,
+                        to: *range.end() as u32,
+                        r#loop: tag.r#loop,
+                    },
+                ))
+            })
+            .collect::<HashMap<_, _>>()
+    });
+    let tags = tags.filter(|t| !t.is_empty());
+
+    // Uniform timing is already described by `fps`
+    let durations = durations
+        .filter(|d| d.len() == frame_names.len() && d.windows(2).any(|w| w[0] != w[1]))
+        .map(|d| d.to_vec());
+
+    // Frames with origins are placed on a shared canvas so the origins line up;
+    // frames packed into another atlas page can't be measured, so skip those
+    let sizes: Option<Vec<(u32, u32)>> =
+        frame_names.iter().map(|f| metadata.frames.get(f).map(|fr| (fr.w, fr.h))).collect();
+    let pivots: Vec<Option<[i32; 2]>> =
+        frame_names.iter().map(|f| metadata.frames.get(f).and_then(|fr| fr.origin)).collect();
+    let layout = sizes.and_then(|sizes| {
+        pivot_layout(&sizes, &pivots).filter(|layout| !layout.is_identity(&sizes))
+    });
+    let (canvas, offsets) = match layout {
+        Some(layout) => (Some([layout.size.0, layout.size.1]), Some(layout.offsets)),
+        None => (None, None),
+    };
+
+    metadata.animations.insert(
+        name.to_string(),
+        AtlasAnimation {
+            frames: frame_names.to_vec(),
+            fps,
+            tags,
+            durations,
+            canvas,
+            offsets,
+            direction: Some(direction).filter(|d| *d != PlaybackDirection::Forward),
+        },
+    );
 }
 
 #[cfg(test)]
@@ -356,6 +546,9 @@ mod tests {
             image: RgbaImage::from_pixel(width, height, color),
             origin: None,
             boxes: None,
+            attach_in: None,
+            attach_out: None,
+            slices: None,
         }
     }
 
@@ -388,6 +581,30 @@ mod tests {
         assert_eq!(*image.get_pixel(0, 0), red);
     }
 
+    #[test]
+    fn test_sprite_input_scaled() {
+        let mut sprite = make_solid_sprite("hero", 8, 4, Rgba([255, 0, 0, 255]));
+        sprite.origin = Some([4, 3]);
+        sprite.attach_out = Some([7, 1]);
+        sprite.boxes =
+            Some(HashMap::from([("hit".to_string(), AtlasBox { x: -1, y: 0, w: 2, h: 3 })]));
+
+        let scaled = sprite.scaled(2);
+        assert_eq!(scaled.image.dimensions(), (16, 8));
+        assert_eq!(scaled.origin, Some([8, 6]));
+        assert_eq!(scaled.attach_out, Some([14, 2]));
+        let hit = &scaled.boxes.as_ref().unwrap()["hit"];
+        assert_eq!((hit.x, hit.y, hit.w, hit.h), (-2, 0, 4, 6));
+
+        // Packed metadata reports the scaled origin
+        let result = pack_atlas(&[scaled], &AtlasConfig::default(), "hero@2x");
+        let (_, metadata) = &result.atlases[0];
+        assert_eq!(metadata.image, "hero@2x.png");
+        assert_eq!(metadata.frames["hero"].origin, Some([8, 6]));
+        assert_eq!(metadata.frames["hero"].attach_out, Some([14, 2]));
+        assert_eq!(metadata.frames["hero"].attach_in, None);
+    }
+
     #[test]
     fn test_multiple_sprites() {
         let red = Rgba([255, 0, 0, 255]);
@@ -559,11 +776,11 @@ mod tests {
             frames: HashMap::from([
                 (
                     "sprite1".to_string(),
-                    AtlasFrame { x: 0, y: 0, w: 16, h: 16, origin: None, boxes: None },
+                    AtlasFrame { x: 0, y: 0, w: 16, h: 16, ..Default::default() },
                 ),
                 (
                     "sprite2".to_string(),
-                    AtlasFrame { x: 16, y: 0, w: 16, h: 16, origin: None, boxes: None },
+                    AtlasFrame { x: 16, y: 0, w: 16, h: 16, ..Default::default() },
                 ),
             ]),
             animations: HashMap::new(),
@@ -597,12 +814,81 @@ mod tests {
             "walk",
             &["walk_1".to_string(), "walk_2".to_string()],
             10,
+            None,
+            None,
+            PlaybackDirection::Forward,
         );
 
         assert!(metadata.animations.contains_key("walk"));
         let anim = &metadata.animations["walk"];
         assert_eq!(anim.frames, vec!["walk_1", "walk_2"]);
         assert_eq!(anim.fps, 10);
+        assert!(anim.tags.is_none());
+        assert!(anim.direction.is_none());
+    }
+
+    #[test]
+    fn test_add_animation_to_atlas_with_tags() {
+        let mut metadata = AtlasMetadata {
+            image: "test.png".to_string(),
+            size: [64, 64],
+            frames: HashMap::new(),
+            animations: HashMap::new(),
+        };
+        let frames: Vec<String> = (1..=4).map(|i| format!("hero_{}", i)).collect();
+        let tags = HashMap::from([
+            ("attack".to_string(), FrameTag { start: 2, end: 9, r#loop: Some(false), fps: None }),
+            ("bogus".to_string(), FrameTag { start: 7, end: 8, r#loop: None, fps: None }),
+        ]);
+
+        add_animation_to_atlas(
+            &mut metadata,
+            "hero",
+            &frames,
+            10,
+            Some(&tags),
+            None,
+            PlaybackDirection::Pingpong,
+        );
+
+        let tags = metadata.animations["hero"].tags.as_ref().unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!((tags["attack"].from, tags["attack"].to), (2, 3));
+        assert_eq!(tags["attack"].r#loop, Some(false));
+        assert_eq!(metadata.animations["hero"].direction, Some(PlaybackDirection::Pingpong));
+    }
+
+    #[test]
+    fn test_add_animation_to_atlas_with_durations() {
+        let mut metadata = AtlasMetadata {
+            image: "test.png".to_string(),
+            size: [64, 64],
+            frames: HashMap::new(),
+            animations: HashMap::new(),
+        };
+        let frames: Vec<String> = (1..=3).map(|i| format!("slash_{}", i)).collect();
+
+        add_animation_to_atlas(
+            &mut metadata,
+            "slash",
+            &frames,
+            10,
+            None,
+            Some(&[200, 50, 300]),
+            Default::default(),
+        );
+        add_animation_to_atlas(
+            &mut metadata,
+            "even",
+            &frames,
+            10,
+            None,
+            Some(&[100, 100, 100]),
+            Default::default(),
+        );
+
+        assert_eq!(metadata.animations["slash"].durations, Some(vec![200, 50, 300]));
+        assert!(metadata.animations["even"].durations.is_none());
     }
 
     #[test]
@@ -617,6 +903,9 @@ mod tests {
                 ("hurt".to_string(), AtlasBox { x: 4, y: 0, w: 24, h: 32 }),
                 ("hit".to_string(), AtlasBox { x: 20, y: 8, w: 20, h: 16 }),
             ])),
+            attach_in: None,
+            attach_out: None,
+            slices: None,
         };
 
         let result = pack_atlas(&[sprite], &AtlasConfig::default(), "test");
@@ -641,6 +930,28 @@ mod tests {
         assert_eq!(hurt_box.h, 32);
     }
 
+    #[test]
+    fn test_sprite_input_slices() {
+        let metadata = SpriteMetadata {
+            slices: Some(HashMap::from([
+                ("button_normal".to_string(), CollisionBox { x: 0, y: 0, w: 4, h: 2 }),
+                ("button_hover".to_string(), CollisionBox { x: 4, y: 0, w: 4, h: 2 }),
+            ])),
+            ..Default::default()
+        };
+        let image = RgbaImage::from_pixel(8, 2, Rgba([255, 0, 0, 255]));
+        let sprite = SpriteInput::with_metadata("ui".to_string(), image, Some(&metadata));
+
+        let result = pack_atlas(&[sprite.scaled(2)], &AtlasConfig::default(), "ui");
+        let frame = &result.atlases[0].1.frames["ui"];
+        let hover = &frame.slices.as_ref().unwrap()["button_hover"];
+        assert_eq!((hover.x, hover.y, hover.w, hover.h), (8, 0, 8, 4));
+        assert!(frame.boxes.is_none());
+
+        let json = serde_json::to_string(frame).unwrap();
+        assert!(json.contains(r#""slices":{"#), "{}", json);
+    }
+
     #[test]
     fn test_atlas_frame_metadata_serialization() {
         // Test that metadata is correctly serialized in atlas JSON
@@ -659,6 +970,9 @@ mod tests {
                         ("hurt".to_string(), AtlasBox { x: 4, y: 0, w: 24, h: 32 }),
                         ("hit".to_string(), AtlasBox { x: 20, y: 8, w: 20, h: 16 }),
                     ])),
+                    attach_in: None,
+                    attach_out: None,
+                    slices: None,
                 },
             )]),
             animations: HashMap::new(),
@@ -676,4 +990,34 @@ mod tests {
         assert!(json.contains("\"hurt\""));
         assert!(json.contains("\"hit\""));
     }
+
+    #[test]
+    fn test_pack_is_independent_of_input_order() {
+        let red = Rgba([255, 0, 0, 255]);
+        let sprites = vec![
+            make_solid_sprite("walk_2", 8, 8, red),
+            make_solid_sprite("idle", 8, 8, red),
+            make_solid_sprite("walk_1", 8, 8, red),
+            make_solid_sprite("tall", 4, 16, red),
+        ];
+        let mut reversed = sprites.clone();
+        reversed.reverse();
+
+        let pack = |sprites: &[SpriteInput]| {
+            let result = pack_atlas(sprites, &AtlasConfig::default(), "hero");
+            serde_json::to_string(&result.atlases[0].1).unwrap()
+        };
+        let json = pack(&sprites);
+        assert_eq!(json, pack(&reversed));
+
+        // Frames are listed by name, and equal heights pack by name
+        let idle = json.find(r#""idle""#).unwrap();
+        let walk_1 = json.find(r#""walk_1""#).unwrap();
+        assert!(
+            json.find(r#""tall""#).unwrap() > idle && walk_1 < json.find(r#""walk_2""#).unwrap()
+        );
+        let result = pack_atlas(&reversed, &AtlasConfig::default(), "hero");
+        let frames = &result.atlases[0].1.frames;
+        assert!(frames["idle"].x < frames["walk_1"].x && frames["walk_1"].x < frames["walk_2"].x);
+    }
 }
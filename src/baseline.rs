@@ -0,0 +1,170 @@
+//! Validation baselines for adopting `--strict` incrementally
+//!
+//! `pxl validate --write-baseline baseline.json` records every issue the
+//! project currently has; later runs with `--baseline baseline.json` only
+//! report (and fail on) issues that aren't in it:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "issues": {
+//!     "sprite \"hero\"": {"undefined_token": 2},
+//!     "palette \"skin\"": {"invalid_color": 1}
+//!   }
+//! }
+//! ```
+//!
+//! Issues are matched on the object they belong to and their code, never on
+//! line numbers, so moving or editing unrelated objects doesn't invalidate the
+//! baseline. Each entry allows as many issues as were recorded: a third
+//! undefined token in `hero` above is reported as new.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::validate::ValidationIssue;
+
+/// Current baseline file format version
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Known issue counts, by object and issue code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub issues: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self { version: BASELINE_VERSION, issues: BTreeMap::new() }
+    }
+}
+
+impl Baseline {
+    /// Record every issue in `issues`.
+    pub fn from_issues(issues: &[ValidationIssue]) -> Self {
+        let mut baseline = Self::default();
+        for issue in issues {
+            let (object, code) = key(issue);
+            *baseline.issues.entry(object).or_default().entry(code).or_default() += 1;
+        }
+        baseline
+    }
+
+    /// Read a baseline file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read baseline '{}': {}", path.display(), e))?;
+        let baseline: Baseline = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid baseline '{}': {}", path.display(), e))?;
+        if baseline.version > BASELINE_VERSION {
+            return Err(format!(
+                "Baseline '{}' has version {}, newer than supported ({})",
+                path.display(),
+                baseline.version,
+                BASELINE_VERSION
+            ));
+        }
+        Ok(baseline)
+    }
+
+    /// Write the baseline as pretty-printed JSON.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        json.push('\n');
+        std::fs::write(path, json)
+            .map_err(|e| format!("Cannot write baseline '{}': {}", path.display(), e))
+    }
+
+    /// Total number of recorded issues
+    pub fn len(&self) -> usize {
+        self.issues.values().flat_map(|codes| codes.values()).sum()
+    }
+
+    /// Whether no issues are recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Split `issues` into those not covered by the baseline, in their
+    /// original order, and the number suppressed.
+    pub fn filter_new(&self, issues: Vec<ValidationIssue>) -> (Vec<ValidationIssue>, usize) {
+        let mut remaining = self.issues.clone();
+        let mut suppressed = 0;
+        let new = issues
+            .into_iter()
+            .filter(|issue| {
+                let (object, code) = key(issue);
+                match remaining.get_mut(&object).and_then(|codes| codes.get_mut(&code)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        suppressed += 1;
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect();
+        (new, suppressed)
+    }
+}
+
+/// The object an issue belongs to and its code.
+///
+/// Issues outside any object (such as invalid JSON) are identified by their
+/// message instead.
+fn key(issue: &ValidationIssue) -> (String, String) {
+    let object = issue.context.clone().unwrap_or_else(|| issue.message.clone());
+    (object, issue.issue_type.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::IssueType;
+
+    fn issue(line: usize, issue_type: IssueType, sprite: &str) -> ValidationIssue {
+        ValidationIssue::warning(line, issue_type, "problem")
+            .with_context(format!("sprite \"{}\"", sprite))
+    }
+
+    #[test]
+    fn test_baseline_ignores_lines_and_counts_repeats() {
+        let old = vec![
+            issue(2, IssueType::UndefinedToken, "hero"),
+            issue(2, IssueType::UndefinedToken, "hero"),
+            issue(5, IssueType::EmptyGrid, "tree"),
+        ];
+        let baseline = Baseline::from_issues(&old);
+        assert_eq!(baseline.len(), 3);
+        assert_eq!(baseline.issues["sprite \"hero\""]["undefined_token"], 2);
+
+        // Same issues on other lines, plus one more of a known kind and a new object
+        let current = vec![
+            issue(10, IssueType::UndefinedToken, "hero"),
+            issue(10, IssueType::UndefinedToken, "hero"),
+            issue(10, IssueType::UndefinedToken, "hero"),
+            issue(3, IssueType::EmptyGrid, "tree"),
+            issue(7, IssueType::EmptyGrid, "rock"),
+        ];
+        let (new, suppressed) = baseline.filter_new(current);
+        assert_eq!(suppressed, 3);
+        assert_eq!(new.len(), 2);
+        assert_eq!(new[0].context.as_deref(), Some("sprite \"hero\""));
+        assert_eq!(new[1].context.as_deref(), Some("sprite \"rock\""));
+    }
+
+    #[test]
+    fn test_baseline_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        let baseline = Baseline::from_issues(&[issue(1, IssueType::InvalidColor, "hero")]);
+        baseline.save(&path).unwrap();
+        assert_eq!(Baseline::load(&path).unwrap(), baseline);
+
+        std::fs::write(&path, r#"{"version": 99, "issues": {}}"#).unwrap();
+        assert!(Baseline::load(&path).unwrap_err().contains("newer than supported"));
+    }
+}
@@ -0,0 +1,607 @@
+//! Live two-way sync between a sprite and a PNG open in an external editor.
+//!
+//! `pxl bridge` exports a sprite to a PNG, watches that PNG for saves, and
+//! folds the edits back into the sprite's regions, so an editor like Aseprite
+//! can be used for painting while the `.pxl` file stays the source of truth.
+//!
+//! Edited pixels are matched to the nearest palette color, so the sprite keeps
+//! its tokens. Small edits are applied as region patches (the same merges as
+//! `pxl draw`); when a patch cannot reproduce the edited image exactly, the
+//! sprite's regions are rebuilt from the edited pixels instead.
+
+use notify::RecursiveMode;
+use notify_debouncer_mini::new_debouncer;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+use thiserror::Error;
+
+use image::RgbaImage;
+
+use crate::color::parse_color;
+use crate::draw::{DrawError, DrawOp, DrawPipeline, RegionEditor};
+use crate::import::extract_structured_regions;
+use crate::models::{RegionDef, Sprite, TtpObject};
+use crate::output::{save_png, OutputError};
+use crate::parser::parse_stream;
+use crate::registry::PaletteRegistry;
+use crate::renderer::render_sprite;
+use crate::watch::timestamp;
+
+/// Token erased pixels are drawn with (see [`DrawOp::Erase`])
+const ERASE_TOKEN: &str = "_";
+
+/// Error during a bridge sync
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BridgeError {
+    /// Failed to load or write the source file
+    #[error("{0}")]
+    Source(DrawError),
+    /// The sprite can't be edited through a PNG
+    #[error("sprite '{sprite}' {reason}; only plain region sprites can be bridged")]
+    Unsupported { sprite: String, reason: &'static str },
+    /// Failed to read the edited PNG
+    #[error("Failed to read {}: {source}", path.display())]
+    ReadPng { path: PathBuf, source: image::ImageError },
+    /// Failed to write the exported PNG
+    #[error("{0}")]
+    WritePng(OutputError),
+    /// The edited PNG was resized
+    #[error("PNG is {}x{} but sprite '{sprite}' is {}x{}; resizing is not supported", actual.0, actual.1, expected.0, expected.1)]
+    SizeMismatch { sprite: String, expected: (u32, u32), actual: (u32, u32) },
+    /// Failed to initialize file watcher
+    #[error("Failed to initialize file watcher: {0}")]
+    WatcherInit(notify::Error),
+    /// Failed to add watch path
+    #[error("Failed to watch path: {0}")]
+    WatchPath(notify::Error),
+    /// Channel receive error
+    #[error("Watch channel error: {0}")]
+    ChannelError(String),
+}
+
+/// How an edit was written back to the sprite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMethod {
+    /// Changed pixels were merged into the existing regions
+    Patched,
+    /// Regions were regenerated from the edited pixels
+    Rebuilt,
+}
+
+impl std::fmt::Display for SyncMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncMethod::Patched => write!(f, "patched regions"),
+            SyncMethod::Rebuilt => write!(f, "rebuilt regions"),
+        }
+    }
+}
+
+/// Result of folding an edited PNG back into a sprite
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncOutcome {
+    /// Pixels whose token changed
+    pub changed_pixels: usize,
+    /// Pixels whose color isn't in the palette and snapped to the nearest one
+    pub snapped_pixels: usize,
+    /// How the sprite was updated, or `None` if nothing changed
+    pub method: Option<SyncMethod>,
+}
+
+/// A palette color available for matching
+struct Swatch {
+    token: String,
+    rgba: [u8; 4],
+}
+
+/// Palette colors sorted by token, so ties resolve the same way every time.
+fn swatches(palette: &HashMap<String, String>) -> Vec<Swatch> {
+    let mut swatches: Vec<Swatch> = palette
+        .iter()
+        .filter_map(|(token, color)| {
+            let rgba = parse_color(color).ok()?;
+            Some(Swatch { token: token.clone(), rgba: normalize(rgba.0) })
+        })
+        .collect();
+    swatches.sort_by(|a, b| a.token.cmp(&b.token));
+    swatches
+}
+
+/// Treat every fully transparent pixel as the same color.
+fn normalize(pixel: [u8; 4]) -> [u8; 4] {
+    if pixel[3] == 0 {
+        [0, 0, 0, 0]
+    } else {
+        pixel
+    }
+}
+
+/// Token for an edited pixel: the nearest palette color, preferring the
+/// token already there when several tokens share that color.
+///
+/// Returns `None` for transparent pixels, and whether the match was exact.
+fn nearest_token(
+    pixel: [u8; 4],
+    current: Option<&str>,
+    swatches: &[Swatch],
+) -> (Option<String>, bool) {
+    let pixel = normalize(pixel);
+    if pixel[3] == 0 {
+        return (None, true);
+    }
+
+    let distance = |rgba: &[u8; 4]| -> u32 {
+        rgba.iter().zip(pixel).map(|(&a, b)| (a as i32 - b as i32).pow(2) as u32).sum()
+    };
+    let Some(best) = swatches.iter().map(|s| distance(&s.rgba)).min() else {
+        return (None, false);
+    };
+    let candidates: Vec<&Swatch> = swatches.iter().filter(|s| distance(&s.rgba) == best).collect();
+    let chosen =
+        candidates.iter().find(|s| Some(s.token.as_str()) == current).unwrap_or(&candidates[0]);
+
+    let token = (chosen.rgba[3] > 0).then(|| chosen.token.clone());
+    (token, best == 0)
+}
+
+/// Which token is visible at each pixel, row by row (`None` = transparent).
+///
+/// Renders the sprite with a unique stand-in color per token, so tokens that
+/// share a color can still be told apart.
+fn token_map(sprite: &Sprite, palette: &HashMap<String, String>) -> Vec<Option<String>> {
+    let mut tokens: Vec<&String> =
+        sprite.regions.as_ref().map(|r| r.keys().collect()).unwrap_or_default();
+    tokens.sort();
+    let stand_ins: HashMap<String, String> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            let id = i + 1;
+            ((*token).clone(), format!("#{:02X}{:02X}00", id & 0xFF, (id >> 8) & 0xFF))
+        })
+        .collect();
+
+    let transparent = |token: &str| {
+        palette.get(token).and_then(|c| parse_color(c).ok()).is_some_and(|rgba| rgba[3] == 0)
+    };
+    let (image, _) = render_sprite(sprite, &stand_ins);
+    image
+        .pixels()
+        .map(|p| {
+            if p[3] == 0 {
+                return None;
+            }
+            let id = p[0] as usize | (p[1] as usize) << 8;
+            let token = tokens.get(id.wrapping_sub(1))?;
+            (!transparent(token)).then(|| (*token).clone())
+        })
+        .collect()
+}
+
+/// Check that a sprite can round-trip through a PNG.
+fn check_supported(sprite: &Sprite) -> Result<(), BridgeError> {
+    let reason = if sprite.source.is_some() {
+        Some("references another sprite")
+    } else if sprite.layers.as_ref().is_some_and(|l| !l.is_empty()) {
+        Some("has layers")
+    } else if sprite.transform.as_ref().is_some_and(|t| !t.is_empty()) {
+        Some("has transforms")
+    } else if sprite.regions.is_none() {
+        Some("has no regions")
+    } else {
+        None
+    };
+    match reason {
+        Some(reason) => Err(BridgeError::Unsupported { sprite: sprite.name.clone(), reason }),
+        None => Ok(()),
+    }
+}
+
+/// Fold an edited image into a sprite's regions.
+///
+/// The image must be the sprite's size. Each pixel maps to the nearest color
+/// in `palette`; pixels whose token changed are patched into the existing
+/// regions when that reproduces the image exactly, otherwise every region is
+/// regenerated from the edited pixels.
+pub fn apply_edit(
+    sprite: &mut Sprite,
+    palette: &HashMap<String, String>,
+    edited: &RgbaImage,
+) -> Result<SyncOutcome, BridgeError> {
+    check_supported(sprite)?;
+    let (rendered, _) = render_sprite(sprite, palette);
+    if rendered.dimensions() != edited.dimensions() {
+        return Err(BridgeError::SizeMismatch {
+            sprite: sprite.name.clone(),
+            expected: rendered.dimensions(),
+            actual: edited.dimensions(),
+        });
+    }
+
+    let width = edited.width();
+    let swatches = swatches(palette);
+    let before = token_map(sprite, palette);
+    let mut after = Vec::with_capacity(before.len());
+    let mut snapped_pixels = 0;
+    for (pixel, current) in edited.pixels().zip(&before) {
+        let (token, exact) = nearest_token(pixel.0, current.as_deref(), &swatches);
+        if !exact {
+            snapped_pixels += 1;
+        }
+        after.push(token);
+    }
+
+    let changed: Vec<(u32, u32, Option<&String>)> = before
+        .iter()
+        .zip(&after)
+        .enumerate()
+        .filter(|(_, (b, a))| b != a)
+        .map(|(i, (_, a))| (i as u32 % width, i as u32 / width, a.as_ref()))
+        .collect();
+    if changed.is_empty() {
+        return Ok(SyncOutcome { changed_pixels: 0, snapped_pixels, method: None });
+    }
+
+    let mut patched = sprite.clone();
+    let method = if patch(&mut patched, palette, &changed) && token_map(&patched, palette) == after
+    {
+        *sprite = patched;
+        SyncMethod::Patched
+    } else {
+        rebuild(sprite, palette, &after, width);
+        SyncMethod::Rebuilt
+    };
+
+    Ok(SyncOutcome { changed_pixels: changed.len(), snapped_pixels, method: Some(method) })
+}
+
+/// Merge changed pixels into the sprite's regions, as `pxl draw --set/--erase` would.
+///
+/// Returns false if a pixel can't be expressed as a patch.
+fn patch(
+    sprite: &mut Sprite,
+    palette: &HashMap<String, String>,
+    changed: &[(u32, u32, Option<&String>)],
+) -> bool {
+    let can_erase =
+        palette.get(ERASE_TOKEN).and_then(|c| parse_color(c).ok()).is_some_and(|rgba| rgba[3] == 0);
+    let Some(regions) = sprite.regions.as_mut() else {
+        return false;
+    };
+
+    let mut editor = RegionEditor::new(regions);
+    for &(x, y, token) in changed {
+        let op = match token {
+            Some(token) => DrawOp::Set { x, y, token: token.clone() },
+            None if can_erase => DrawOp::Erase { x, y },
+            None => return false,
+        };
+        editor.apply(&op);
+    }
+    true
+}
+
+/// Replace the sprite's regions with one region per visible token.
+///
+/// Shapes come from the importer's extraction; if any shape covers the wrong
+/// pixels, every token falls back to exact point lists.
+fn rebuild(
+    sprite: &mut Sprite,
+    palette: &HashMap<String, String>,
+    tokens: &[Option<String>],
+    width: u32,
+) {
+    let height = tokens.len() as u32 / width.max(1);
+    let mut points: BTreeMap<&String, Vec<[u32; 2]>> = BTreeMap::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if let Some(token) = token {
+            points.entry(token).or_default().push([i as u32 % width, i as u32 / width]);
+        }
+    }
+
+    let old = sprite.regions.take().unwrap_or_default();
+    let region_for = |token: &String, shape: RegionDef| RegionDef {
+        role: old.get(token).and_then(|r| r.role),
+        ..shape
+    };
+
+    let shapes: HashMap<String, RegionDef> = points
+        .iter()
+        .filter_map(|(token, pts)| {
+            let json = extract_structured_regions(pts, width, height).to_json();
+            let shape = serde_json::from_value(json).ok()?;
+            Some(((*token).clone(), region_for(token, shape)))
+        })
+        .collect();
+    sprite.regions = Some(shapes);
+    if sprite.regions.as_ref().is_some_and(|r| r.len() == points.len())
+        && token_map(sprite, palette) == tokens
+    {
+        return;
+    }
+
+    sprite.regions = Some(
+        points
+            .into_iter()
+            .map(|(token, pts)| {
+                let shape = RegionDef { points: Some(pts), ..Default::default() };
+                (token.clone(), region_for(token, shape))
+            })
+            .collect(),
+    );
+}
+
+/// Load a sprite for editing along with its resolved palette.
+fn load(
+    source: &Path,
+    sprite: &str,
+) -> Result<(DrawPipeline, HashMap<String, String>), BridgeError> {
+    let content =
+        std::fs::read_to_string(source).map_err(|e| BridgeError::Source(DrawError::Io(e)))?;
+    let pipeline =
+        DrawPipeline::load_from_string(&content, Some(sprite)).map_err(BridgeError::Source)?;
+
+    let mut palettes = PaletteRegistry::new();
+    for obj in parse_stream(Cursor::new(&content)).objects {
+        if let TtpObject::Palette(p) = obj {
+            palettes.register(p);
+        }
+    }
+    let target = pipeline.sprite().expect("pipeline was loaded with a sprite");
+    check_supported(target)?;
+    let palette = palettes.resolve_lenient(target).palette.colors;
+    Ok((pipeline, palette))
+}
+
+/// Read the edited PNG and fold it into the sprite, rewriting `source` if anything changed.
+pub fn pull(source: &Path, sprite: &str, png: &Path) -> Result<SyncOutcome, BridgeError> {
+    let (mut pipeline, palette) = load(source, sprite)?;
+    let edited = image::open(png)
+        .map_err(|e| BridgeError::ReadPng { path: png.to_path_buf(), source: e })?
+        .to_rgba8();
+
+    let target = pipeline.sprite_mut().expect("pipeline was loaded with a sprite");
+    let outcome = apply_edit(target, &palette, &edited)?;
+    if outcome.method.is_some() {
+        pipeline.write_to(source).map_err(BridgeError::Source)?;
+    }
+    Ok(outcome)
+}
+
+/// Export the sprite to `png`, unless the PNG already shows the same pixels.
+///
+/// Returns whether the PNG was written.
+pub fn push(source: &Path, sprite: &str, png: &Path) -> Result<bool, BridgeError> {
+    let (pipeline, palette) = load(source, sprite)?;
+    let target = pipeline.sprite().expect("pipeline was loaded with a sprite");
+    let (rendered, _) = render_sprite(target, &palette);
+
+    if let Ok(existing) = image::open(png) {
+        let existing = existing.to_rgba8();
+        if existing.dimensions() == rendered.dimensions()
+            && existing
+                .pixels()
+                .zip(rendered.pixels())
+                .all(|(a, b)| normalize(a.0) == normalize(b.0))
+        {
+            return Ok(false);
+        }
+    }
+    save_png(&rendered, png).map_err(BridgeError::WritePng)?;
+    Ok(true)
+}
+
+/// Print the result of a pull.
+pub fn report(outcome: &SyncOutcome, png: &Path, source: &Path) {
+    match outcome.method {
+        Some(method) => println!(
+            "[{}] Synced {} pixel(s) from {} into {} ({})",
+            timestamp(),
+            outcome.changed_pixels,
+            png.display(),
+            source.display(),
+            method
+        ),
+        None => println!("[{}] No changes in {}", timestamp(), png.display()),
+    }
+    if outcome.snapped_pixels > 0 {
+        println!(
+            "[{}] Snapped {} pixel(s) to the nearest palette color",
+            timestamp(),
+            outcome.snapped_pixels
+        );
+    }
+}
+
+/// Whether an event path refers to `target` (an already canonical path).
+fn is_target(path: &Path, target: &Path) -> bool {
+    path.canonicalize().is_ok_and(|p| p == target)
+}
+
+/// Export the sprite, then keep the PNG and source in sync until interrupted (Ctrl+C).
+///
+/// Saves to the PNG are pulled into the source; changes to the source are
+/// pushed to the PNG. Each side is only rewritten when its content differs,
+/// so the bridge's own writes don't echo back.
+pub fn watch(
+    source: &Path,
+    sprite: &str,
+    png: &Path,
+    debounce: Duration,
+) -> Result<(), BridgeError> {
+    if push(source, sprite, png)? {
+        println!("[{}] Exported '{}' to {}", timestamp(), sprite, png.display());
+    }
+
+    let source_path = source.canonicalize().map_err(|e| BridgeError::Source(DrawError::Io(e)))?;
+    let png_path = png.canonicalize().map_err(|e| BridgeError::Source(DrawError::Io(e)))?;
+
+    let (tx, rx) = channel();
+    let mut debouncer = new_debouncer(debounce, tx).map_err(BridgeError::WatcherInit)?;
+    let mut dirs: Vec<&Path> =
+        [source_path.parent(), png_path.parent()].into_iter().flatten().collect();
+    dirs.dedup();
+    for dir in dirs {
+        debouncer
+            .watcher()
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(BridgeError::WatchPath)?;
+    }
+    println!(
+        "[{}] Watching {} and {} for changes (Ctrl+C to stop)...",
+        timestamp(),
+        png.display(),
+        source.display()
+    );
+
+    loop {
+        match rx.recv() {
+            Ok(Ok(events)) => {
+                let png_saved = events.iter().any(|e| is_target(&e.path, &png_path));
+                let source_saved = events.iter().any(|e| is_target(&e.path, &source_path));
+
+                if png_saved {
+                    match pull(source, sprite, png) {
+                        Ok(outcome) if outcome.method.is_some() || outcome.snapped_pixels > 0 => {
+                            report(&outcome, png, source)
+                        }
+                        Ok(_) => {}
+                        Err(e) => eprintln!("[{}] Error: {}", timestamp(), e),
+                    }
+                }
+                if png_saved || source_saved {
+                    match push(source, sprite, png) {
+                        Ok(true) => {
+                            println!("[{}] Exported '{}' to {}", timestamp(), sprite, png.display())
+                        }
+                        Ok(false) => {}
+                        Err(e) => eprintln!("[{}] Error: {}", timestamp(), e),
+                    }
+                }
+            }
+            Ok(Err(error)) => eprintln!("[{}] Watch error: {:?}", timestamp(), error),
+            Err(e) => return Err(BridgeError::ChannelError(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn palette() -> HashMap<String, String> {
+        [("_", "#00000000"), ("skin", "#FFCC99"), ("eye", "#000000"), ("shade", "#000000")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn face() -> Sprite {
+        let regions: HashMap<String, RegionDef> = serde_json::from_str(
+            r#"{"skin": {"rect": [0, 0, 4, 4], "z": 0}, "eye": {"points": [[1, 1]], "z": 10}}"#,
+        )
+        .unwrap();
+        serde_json::from_value(serde_json::json!({
+            "name": "face",
+            "size": [4, 4],
+            "palette": "face",
+            "regions": regions,
+        }))
+        .unwrap()
+    }
+
+    fn render(sprite: &Sprite) -> RgbaImage {
+        render_sprite(sprite, &palette()).0
+    }
+
+    #[test]
+    fn test_unchanged_image_is_a_no_op() {
+        let mut sprite = face();
+        let image = render(&sprite);
+        let outcome = apply_edit(&mut sprite, &palette(), &image).unwrap();
+        assert_eq!(outcome, SyncOutcome { changed_pixels: 0, snapped_pixels: 0, method: None });
+        assert_eq!(sprite, face());
+    }
+
+    #[test]
+    fn test_edit_patches_regions() {
+        let mut sprite = face();
+        let mut image = render(&sprite);
+        image.put_pixel(2, 1, Rgba([0, 0, 0, 255]));
+        // Near-black snaps to the eye color
+        image.put_pixel(1, 2, Rgba([8, 4, 0, 255]));
+
+        let outcome = apply_edit(&mut sprite, &palette(), &image).unwrap();
+        assert_eq!(outcome.method, Some(SyncMethod::Patched));
+        assert_eq!(outcome.changed_pixels, 2);
+        assert_eq!(outcome.snapped_pixels, 1);
+
+        // Existing regions are kept; the eye token gains the new pixels
+        let regions = sprite.regions.as_ref().unwrap();
+        assert_eq!(regions["skin"].rect, Some([0, 0, 4, 4]));
+        assert_eq!(regions["eye"].points, Some(vec![[1, 1], [2, 1], [1, 2]]));
+        assert_eq!(render(&sprite).get_pixel(1, 2), &Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_edit_rebuilds_when_patch_is_hidden() {
+        // The skin rect sits above the eye, so merging into the eye can't show through
+        let mut sprite = face();
+        sprite.regions.as_mut().unwrap().get_mut("eye").unwrap().z = Some(-10);
+        let mut image = render(&sprite);
+        image.put_pixel(3, 3, Rgba([0, 0, 0, 255]));
+
+        let outcome = apply_edit(&mut sprite, &palette(), &image).unwrap();
+        assert_eq!(outcome.method, Some(SyncMethod::Rebuilt));
+        assert_eq!(render(&sprite), image);
+        let tokens = token_map(&sprite, &palette());
+        assert_eq!(tokens[15].as_deref(), Some("eye"));
+        assert_eq!(tokens[5].as_deref(), Some("skin"));
+    }
+
+    #[test]
+    fn test_shared_color_keeps_current_token() {
+        let swatches = swatches(&palette());
+        assert_eq!(
+            nearest_token([0, 0, 0, 255], Some("shade"), &swatches).0.as_deref(),
+            Some("shade")
+        );
+        assert_eq!(
+            nearest_token([0, 0, 0, 255], Some("skin"), &swatches).0.as_deref(),
+            Some("eye")
+        );
+        assert_eq!(nearest_token([9, 9, 9, 0], Some("skin"), &swatches), (None, true));
+    }
+
+    #[test]
+    fn test_erase_uses_transparent_token() {
+        let mut sprite = face();
+        let mut image = render(&sprite);
+        image.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+
+        let outcome = apply_edit(&mut sprite, &palette(), &image).unwrap();
+        assert_eq!(outcome.method, Some(SyncMethod::Patched));
+        assert_eq!(render(&sprite).get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn test_rejects_resized_image() {
+        let mut sprite = face();
+        let err = apply_edit(&mut sprite, &palette(), &RgbaImage::new(8, 8)).unwrap_err();
+        assert!(matches!(err, BridgeError::SizeMismatch { expected: (4, 4), actual: (8, 8), .. }));
+    }
+
+    #[test]
+    fn test_rejects_layered_sprite() {
+        let mut sprite = face();
+        sprite.layers = Some(vec![]);
+        assert!(check_supported(&sprite).is_ok());
+        sprite.source = Some("other".to_string());
+        assert!(matches!(check_supported(&sprite), Err(BridgeError::Unsupported { .. })));
+    }
+}
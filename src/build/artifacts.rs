@@ -0,0 +1,266 @@
+//! Artifact manifests for CI caching and reproducible builds.
+//!
+//! `pxl build --manifest-out build.json` writes an [`ArtifactManifest`] after
+//! a successful build: every file the build produced, with its content hash,
+//! the source files and objects it came from, and the options used.
+//!
+//! Unlike the incremental `.pxl-manifest.json`, it has no timestamps or
+//! durations and its paths are relative to the project root, so building the
+//! same sources with the same options gives a byte-identical manifest on any
+//! machine. Comparing two manifests shows which artifacts differ.
+//!
+//! # Format
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "builder_version": "0.2.0",
+//!   "project": "my-game",
+//!   "project_version": "1.0.0",
+//!   "options": { "defaults": { "scale": 1, "padding": 1, ... }, "strict": false },
+//!   "artifacts": [
+//!     {
+//!       "path": "build/characters.png",
+//!       "hash": "9f2c4b1d0e8a7c63",
+//!       "size": 4096,
+//!       "target": "atlas:characters",
+//!       "sources": [{ "path": "src/pxl/hero.pxl", "hash": "abc123..." }],
+//!       "objects": ["palette:hero", "sprite:hero_idle"],
+//!       "options": { "atlas": { "sources": ["**/*.pxl"], ... } }
+//!     }
+//!   ]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use super::manifest::{hash_file, ManifestError};
+use super::{BuildContext, BuildPlan, BuildTarget, TargetKind};
+use crate::parser::parse_stream;
+
+/// Current artifact manifest format version.
+pub const ARTIFACT_MANIFEST_VERSION: u32 = 1;
+
+/// Every artifact of a build, in a machine-independent form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    /// Manifest format version
+    pub version: u32,
+    /// Version of pxl that built the artifacts
+    pub builder_version: String,
+    /// Project name from config
+    pub project: String,
+    /// Project version from config
+    pub project_version: String,
+    /// Options that apply to every target
+    pub options: Value,
+    /// Produced files, sorted by path
+    pub artifacts: Vec<Artifact>,
+}
+
+/// One file produced by a build.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Path relative to the project root
+    pub path: String,
+    /// FNV-1a hash of the file contents
+    pub hash: String,
+    /// Size in bytes
+    pub size: u64,
+    /// ID of the target that produced it
+    pub target: String,
+    /// Source files the target read, sorted by path
+    pub sources: Vec<SourceFile>,
+    /// Objects defined in those sources, as `kind:name`
+    pub objects: Vec<String>,
+    /// Target-specific options from config
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub options: Value,
+}
+
+/// A source file and its content hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceFile {
+    /// Path relative to the project root
+    pub path: String,
+    /// FNV-1a hash of the file contents
+    pub hash: String,
+}
+
+impl ArtifactManifest {
+    /// Collect the manifest for a finished build.
+    ///
+    /// `outputs` maps target IDs to the files they produced. Targets of
+    /// `plan` without outputs (not selected, or failed) are left out.
+    pub fn collect(
+        ctx: &BuildContext,
+        plan: &BuildPlan,
+        outputs: &HashMap<String, Vec<PathBuf>>,
+    ) -> Result<Self, ManifestError> {
+        let root = ctx.project_root();
+        let config = ctx.config();
+        let mut artifacts = Vec::new();
+
+        for target in plan.targets() {
+            let Some(files) = outputs.get(&target.id) else { continue };
+            let source_paths = target_sources(plan, target);
+
+            let mut sources = Vec::new();
+            let mut objects = BTreeSet::new();
+            for source in &source_paths {
+                sources.push(SourceFile { path: relative(root, source), hash: hash_file(source)? });
+                for obj in parse_stream(File::open(source)?).objects {
+                    objects.insert(format!("{}:{}", obj.kind(), obj.name()));
+                }
+            }
+            sources.sort_by(|a, b| a.path.cmp(&b.path));
+            sources.dedup();
+
+            let options = target_options(ctx, target);
+            for file in files.iter().filter(|f| f.is_file()) {
+                artifacts.push(Artifact {
+                    path: relative(root, file),
+                    hash: hash_file(file)?,
+                    size: fs::metadata(file)?.len(),
+                    target: target.id.clone(),
+                    sources: sources.clone(),
+                    objects: objects.iter().cloned().collect(),
+                    options: options.clone(),
+                });
+            }
+        }
+        artifacts.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.target.cmp(&b.target)));
+
+        Ok(Self {
+            version: ARTIFACT_MANIFEST_VERSION,
+            builder_version: env!("CARGO_PKG_VERSION").to_string(),
+            project: config.project.name.clone(),
+            project_version: config.project.version.clone(),
+            options: json!({ "defaults": config.defaults, "strict": ctx.is_strict() }),
+            artifacts,
+        })
+    }
+
+    /// Write the manifest as pretty-printed JSON.
+    pub fn save(&self, path: &Path) -> Result<(), ManifestError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// Sources of a target, or of the targets it depends on if it has none
+/// (exports read the atlas they export).
+fn target_sources(plan: &BuildPlan, target: &BuildTarget) -> Vec<PathBuf> {
+    if !target.sources.is_empty() {
+        return target.sources.clone();
+    }
+    plan.targets()
+        .iter()
+        .filter(|t| target.dependencies.contains(&t.id))
+        .flat_map(|t| t.sources.iter().cloned())
+        .collect()
+}
+
+/// The config section that shaped a target's output.
+fn target_options(ctx: &BuildContext, target: &BuildTarget) -> Value {
+    let config = ctx.config();
+    match target.kind {
+        TargetKind::Atlas => {
+            json!({ "atlas": config.atlases.get(&target.name), "theme": target.theme })
+        }
+        TargetKind::Animation | TargetKind::AnimationPreview => {
+            json!({ "animations": config.animations })
+        }
+        TargetKind::Export => {
+            // Export IDs are `export:{format}:{atlas}`
+            let format = target.id.split(':').nth(1).unwrap_or_default();
+            let exports = &config.exports;
+            let export = match format {
+                "godot" => json!(exports.godot),
+                "unity" => json!(exports.unity),
+                "libgdx" => json!(exports.libgdx),
+                _ => Value::Null,
+            };
+            json!({ "format": format, "export": export })
+        }
+        TargetKind::Sprite => Value::Null,
+    }
+}
+
+/// A path relative to the project root, with `/` separators.
+fn relative(root: &Path, path: &Path) -> String {
+    let path = path.strip_prefix(root).unwrap_or(path);
+    path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::loader::default_config;
+    use crate::config::AtlasConfig;
+    use tempfile::TempDir;
+
+    fn atlas_context() -> (TempDir, BuildContext) {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src/pxl");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(
+            src.join("hero.pxl"),
+            r##"{"type": "palette", "name": "hero", "colors": {"_": "#0000", "x": "#F00"}}
+{"type": "sprite", "name": "hero_idle", "size": [2, 2], "palette": "hero", "regions": {"x": {"rect": [0, 0, 2, 2]}}}"##,
+        )
+        .unwrap();
+
+        let mut config = default_config();
+        config.atlases.insert(
+            "characters".to_string(),
+            AtlasConfig {
+                sources: vec!["**/*.pxl".to_string()],
+                max_size: [64, 64],
+                padding: None,
+                power_of_two: false,
+                nine_slice: false,
+                antialias: None,
+                themes: vec![],
+            },
+        );
+        let ctx = BuildContext::new(config, temp.path().to_path_buf());
+        (temp, ctx)
+    }
+
+    #[test]
+    fn test_collect_is_machine_independent() {
+        let (temp, ctx) = atlas_context();
+        let plan = crate::build::create_build_plan(&ctx).unwrap();
+        let result = crate::build::BuildPipeline::new(ctx.clone()).build_plan(&plan).unwrap();
+        assert!(result.is_success(), "{}", result.summary());
+        let outputs: HashMap<String, Vec<PathBuf>> =
+            result.targets.iter().map(|t| (t.target_id.clone(), t.outputs.clone())).collect();
+
+        let manifest = ArtifactManifest::collect(&ctx, &plan, &outputs).unwrap();
+        let atlas = manifest.artifacts.iter().find(|a| a.path.ends_with(".png")).unwrap();
+        assert_eq!(atlas.target, "atlas:characters");
+        assert!(!atlas.path.starts_with('/'), "{}", atlas.path);
+        assert_eq!(atlas.sources[0].path, "src/pxl/hero.pxl");
+        assert_eq!(atlas.objects, ["palette:hero", "sprite:hero_idle"]);
+        assert_eq!(atlas.options["atlas"]["max_size"], json!([64, 64]));
+
+        // Same build again gives the same manifest
+        let again = ArtifactManifest::collect(&ctx, &plan, &outputs).unwrap();
+        let path = temp.path().join("build.json");
+        manifest.save(&path).unwrap();
+        let first = fs::read_to_string(&path).unwrap();
+        again.save(&path).unwrap();
+        assert_eq!(first, fs::read_to_string(&path).unwrap());
+        assert!(!first.contains("built_at"));
+    }
+}
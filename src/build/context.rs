@@ -1,6 +1,7 @@
 //! Build context containing configuration and state for a build.
 
-use crate::config::PxlConfig;
+use crate::config::{OutputConfig, OutputVars, ProfileConfig, PxlConfig};
+use crate::selection::Selection;
 use std::path::{Path, PathBuf};
 
 /// Build context containing configuration and paths for a build operation.
@@ -19,6 +20,8 @@ pub struct BuildContext {
     verbose: bool,
     /// Optional filter to build specific targets only
     target_filter: Option<Vec<String>>,
+    /// Which sprites to build (`--only`/`--exclude`)
+    selection: Selection,
 }
 
 impl BuildContext {
@@ -29,7 +32,14 @@ impl BuildContext {
     /// - `project_root` - The project root directory
     pub fn new(config: PxlConfig, project_root: PathBuf) -> Self {
         let strict = config.validate.strict;
-        Self { config, project_root, strict, verbose: false, target_filter: None }
+        Self {
+            config,
+            project_root,
+            strict,
+            verbose: false,
+            target_filter: None,
+            selection: Selection::default(),
+        }
     }
 
     /// Get the configuration.
@@ -52,6 +62,18 @@ impl BuildContext {
         self.resolve_path(&self.config.project.out)
     }
 
+    /// Output path for one build output at one export profile.
+    ///
+    /// `template` is one of the `[output]` templates.
+    pub fn output_path(
+        &self,
+        template: &str,
+        vars: OutputVars,
+        profile: &ProfileConfig,
+    ) -> PathBuf {
+        self.out_dir().join(OutputConfig::expand(template, vars, profile))
+    }
+
     /// Whether strict mode is enabled.
     pub fn is_strict(&self) -> bool {
         self.strict
@@ -85,6 +107,17 @@ impl BuildContext {
         self.target_filter.as_deref()
     }
 
+    /// Set which sprites sprite and atlas targets build.
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Get the sprite selection.
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
     /// Resolve a path relative to the project root.
     ///
     /// If the path is absolute, returns it unchanged.
@@ -4,7 +4,7 @@
 //! from the configuration.
 
 use crate::build::{BuildContext, BuildPlan, BuildTarget};
-use crate::config::AtlasConfig;
+use crate::config::{AtlasConfig, OutputVars};
 use glob::glob;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -106,14 +106,24 @@ pub fn discover_all_sources(ctx: &BuildContext) -> Result<Vec<PathBuf>, Discover
 pub fn create_build_plan(ctx: &BuildContext) -> Result<BuildPlan, DiscoveryError> {
     let mut plan = BuildPlan::new();
     let src_dir = ctx.src_dir();
-    let out_dir = ctx.out_dir();
+    let output = &ctx.config().output;
+    let profile = &ctx.config().export_profiles()[0];
 
-    // Create atlas targets
+    // Create atlas targets, plus one per theme
     for (name, atlas_config) in &ctx.config().atlases {
         let sources = discover_atlas_sources(&src_dir, atlas_config)?;
         if !sources.is_empty() {
-            let output = out_dir.join(format!("{}.png", name));
-            plan.add_target(BuildTarget::atlas(name.clone(), sources, output));
+            let vars = OutputVars { kind: "atlas", name, ext: "png", theme: None };
+            let path = ctx.output_path(&output.atlas, vars, profile);
+            plan.add_target(BuildTarget::atlas(name.clone(), sources.clone(), path));
+
+            for theme in &atlas_config.themes {
+                let vars = OutputVars { theme: Some(theme), ..vars };
+                let path = ctx.output_path(&output.atlas, vars, profile);
+                let target = BuildTarget::atlas(name.clone(), sources.clone(), path)
+                    .with_theme(theme.clone());
+                plan.add_target(target);
+            }
 
             // Add export targets for this atlas
             add_export_targets(&mut plan, ctx, name);
@@ -125,13 +135,15 @@ pub fn create_build_plan(ctx: &BuildContext) -> Result<BuildPlan, DiscoveryError
     for source in anim_sources {
         let name = source.file_stem().and_then(|s| s.to_str()).unwrap_or("unnamed").to_string();
 
-        let output = out_dir.join("animations").join(format!("{}.png", name));
-        plan.add_target(BuildTarget::animation(name.clone(), source.clone(), output.clone()));
+        let vars = OutputVars { kind: "animation", name: &name, ext: "png", theme: None };
+        let path = ctx.output_path(&output.animation, vars, profile);
+        plan.add_target(BuildTarget::animation(name.clone(), source.clone(), path));
 
         // Add preview target if enabled
         if ctx.config().animations.preview {
-            let preview_output = out_dir.join("animations").join(format!("{}.gif", name));
-            plan.add_target(BuildTarget::animation_preview(name, source, preview_output));
+            let vars = OutputVars { kind: "preview", ext: "gif", ..vars };
+            let preview_path = ctx.output_path(&output.preview, vars, profile);
+            plan.add_target(BuildTarget::animation_preview(name, source, preview_path));
         }
     }
 
@@ -176,29 +188,26 @@ fn discover_animation_sources(ctx: &BuildContext) -> Result<Vec<PathBuf>, Discov
 /// produces JSON alongside PNG as the native atlas format. The exports here
 /// are for engine-specific formats that transform the atlas metadata.
 fn add_export_targets(plan: &mut BuildPlan, ctx: &BuildContext, atlas_name: &str) {
-    let out_dir = ctx.out_dir();
     let exports = &ctx.config().exports;
-
-    // Godot export
-    if exports.godot.enabled {
-        let output = out_dir.join("godot").join(format!("{}.tres", atlas_name));
-        let target = BuildTarget::export(atlas_name.to_string(), "godot".to_string(), output)
-            .with_dependency(format!("atlas:{}", atlas_name));
-        plan.add_target(target);
-    }
-
-    // Unity export
-    if exports.unity.enabled {
-        let output = out_dir.join("unity").join(format!("{}.asset", atlas_name));
-        let target = BuildTarget::export(atlas_name.to_string(), "unity".to_string(), output)
-            .with_dependency(format!("atlas:{}", atlas_name));
-        plan.add_target(target);
-    }
-
-    // libGDX export
-    if exports.libgdx.enabled {
-        let output = out_dir.join("libgdx").join(format!("{}.atlas", atlas_name));
-        let target = BuildTarget::export(atlas_name.to_string(), "libgdx".to_string(), output)
+    let profile = &ctx.config().export_profiles()[0];
+    let formats = [
+        ("godot", "tres".to_string(), exports.godot.enabled),
+        ("unity", "asset".to_string(), exports.unity.enabled),
+        ("libgdx", "atlas".to_string(), exports.libgdx.enabled),
+    ];
+    // Unregistered plugins still get a target, so the build reports them
+    let plugins = exports.plugins.iter().map(|name| {
+        let ext = crate::plugin::exporter(name).map(|p| p.extension().to_string());
+        (name.as_str(), ext.unwrap_or_else(|| "out".to_string()), true)
+    });
+
+    for (format, ext, enabled) in formats.into_iter().chain(plugins) {
+        if !enabled {
+            continue;
+        }
+        let vars = OutputVars { kind: format, name: atlas_name, ext: &ext, theme: None };
+        let output = ctx.output_path(&ctx.config().output.export, vars, profile);
+        let target = BuildTarget::export(atlas_name.to_string(), format.to_string(), output)
             .with_dependency(format!("atlas:{}", atlas_name));
         plan.add_target(target);
     }
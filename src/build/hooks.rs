@@ -0,0 +1,412 @@
+//! Scriptable build hooks
+//!
+//! With the `scripting` feature, `pxl.toml` can name a [Rhai](https://rhai.rs)
+//! script whose functions run at fixed points of the build:
+//!
+//! ```toml
+//! [hooks]
+//! script = "scripts/build.rhai"
+//! ```
+//!
+//! | Hook | Called | Returns |
+//! |------|--------|---------|
+//! | `pre_resolve(doc, file)` | Once per source file, before anything is registered or resolved | Ignored; edit `doc` in place |
+//! | `object(obj, file)` | For every object after `pre_resolve` | A replacement object map, or `()` to keep it |
+//! | `post_render(info)` | For every sprite image before it is saved | A new file name, or `()` to keep the planned one |
+//!
+//! `doc` wraps an [`edit::Document`](crate::edit::Document): `doc.objects()`,
+//! `doc.add(obj)`, `doc.set_pixel(sprite, x, y, token)`,
+//! `doc.swap_token(sprite, from, to)`, `doc.add_frame(animation, sprite)` and
+//! `doc.text()`. Objects are maps shaped like their JSON source, and `info`
+//! has `kind`, `name`, `width`, `height`, `scale` and `path`. Any hook may
+//! `throw` to fail the target, which makes validation policies a few lines:
+//!
+//! ```rhai
+//! fn object(obj, file) {
+//!     if obj.type == "sprite" && !obj.name.starts_with("spr_") {
+//!         throw `${file}: sprite '${obj.name}' must start with spr_`;
+//!     }
+//! }
+//! ```
+//!
+//! Hooks that aren't defined are skipped. Without the feature, configuring a
+//! script is an error rather than being silently ignored.
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::config::PxlConfig;
+use crate::parser::{parse_file, ParseResult};
+
+/// Error loading or running build hooks
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum HookError {
+    /// IO error reading the script or a source file
+    #[error("Failed to read {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    /// The script doesn't compile
+    #[error("Hook script {path}: {message}")]
+    Compile { path: PathBuf, message: String },
+    /// A hook threw or returned something unusable
+    #[error("Hook '{hook}' failed: {message}")]
+    Hook { hook: &'static str, message: String },
+    /// A script is configured but this build has no scripting engine
+    #[error("pxl.toml sets hooks.script, but pxl was built without the `scripting` feature")]
+    Disabled,
+}
+
+/// An image about to be saved, as passed to `post_render`
+#[derive(Debug, Clone)]
+pub struct RenderInfo<'a> {
+    pub kind: &'a str,
+    pub name: &'a str,
+    pub width: u32,
+    pub height: u32,
+    pub scale: u32,
+    pub path: &'a Path,
+}
+
+/// Compiled hook script
+pub struct BuildHooks {
+    #[cfg(feature = "scripting")]
+    script: script::Script,
+}
+
+impl std::fmt::Debug for BuildHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BuildHooks").finish_non_exhaustive()
+    }
+}
+
+impl BuildHooks {
+    /// Compile the script named by `[hooks] script`, if any.
+    pub fn load(config: &PxlConfig, project_root: &Path) -> Result<Option<Self>, HookError> {
+        let Some(script) = &config.hooks.script else {
+            return Ok(None);
+        };
+        let path = project_root.join(script);
+        #[cfg(feature = "scripting")]
+        {
+            let source = std::fs::read_to_string(&path)
+                .map_err(|source| HookError::Io { path: path.clone(), source })?;
+            Self::compile(&source, &path).map(Some)
+        }
+        #[cfg(not(feature = "scripting"))]
+        {
+            let _ = path;
+            Err(HookError::Disabled)
+        }
+    }
+
+    /// Compile hooks from script source; `path` is used in error messages.
+    #[cfg(feature = "scripting")]
+    pub fn compile(source: &str, path: &Path) -> Result<Self, HookError> {
+        Ok(Self { script: script::Script::compile(source, path)? })
+    }
+
+    /// Parse a source file, running `pre_resolve` and `object` on it.
+    pub fn parse_file(&self, path: &Path) -> Result<ParseResult, HookError> {
+        #[cfg(feature = "scripting")]
+        {
+            let source = std::fs::read_to_string(path)
+                .map_err(|source| HookError::Io { path: path.to_path_buf(), source })?;
+            self.script.parse(&source, path)
+        }
+        #[cfg(not(feature = "scripting"))]
+        parse_file(path).map_err(|source| HookError::Io { path: path.to_path_buf(), source })
+    }
+
+    /// Run `post_render`, returning the file name to save under instead.
+    pub fn post_render(&self, info: &RenderInfo<'_>) -> Result<Option<String>, HookError> {
+        #[cfg(feature = "scripting")]
+        {
+            self.script.post_render(info)
+        }
+        #[cfg(not(feature = "scripting"))]
+        {
+            let _ = info;
+            Ok(None)
+        }
+    }
+}
+
+/// Parse a source file, through the hooks when there are any.
+pub fn parse_with_hooks(path: &Path, hooks: Option<&BuildHooks>) -> Result<ParseResult, HookError> {
+    match hooks {
+        Some(hooks) => hooks.parse_file(path),
+        None => {
+            parse_file(path).map_err(|source| HookError::Io { path: path.to_path_buf(), source })
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+mod script {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use rhai::serde::{from_dynamic, to_dynamic};
+    use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST, INT};
+
+    use super::{HookError, RenderInfo};
+    use crate::edit::{Document, EditOp};
+    use crate::models::TtpObject;
+    use crate::parser::{parse_str, ParseResult};
+
+    type RhaiResult<T> = Result<T, Box<EvalAltResult>>;
+
+    /// A document shared between Rust and a running hook
+    #[derive(Clone)]
+    struct ScriptDocument(Arc<Mutex<Document>>);
+
+    impl ScriptDocument {
+        fn with<T>(&self, f: impl FnOnce(&mut Document) -> T) -> T {
+            f(&mut self.0.lock().unwrap_or_else(PoisonError::into_inner))
+        }
+
+        fn edit(&mut self, op: EditOp) -> RhaiResult<()> {
+            self.with(|doc| doc.apply(&[op])).map(|_| ()).map_err(|e| e.to_string().into())
+        }
+    }
+
+    fn object_to_dynamic(object: &TtpObject) -> RhaiResult<Dynamic> {
+        let value = serde_json::to_value(object).map_err(|e| e.to_string())?;
+        to_dynamic(value)
+    }
+
+    fn object_from_dynamic(value: &Dynamic) -> Result<TtpObject, String> {
+        let value: serde_json::Value = from_dynamic(value).map_err(|e| e.to_string())?;
+        serde_json::from_value(value).map_err(|e| format!("invalid object: {}", e))
+    }
+
+    fn register_document(engine: &mut Engine) {
+        engine
+            .register_type_with_name::<ScriptDocument>("Document")
+            .register_fn("objects", |doc: &mut ScriptDocument| -> RhaiResult<Array> {
+                doc.with(|d| d.objects().map(object_to_dynamic).collect())
+            })
+            .register_fn("text", |doc: &mut ScriptDocument| doc.with(|d| d.text()))
+            .register_fn("add", |doc: &mut ScriptDocument, obj: Dynamic| -> RhaiResult<()> {
+                let object = object_from_dynamic(&obj)?;
+                let line = serde_json::to_string(&object).map_err(|e| e.to_string())?;
+                doc.with(|d| {
+                    let mut text = d.text();
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    text.push_str(&line);
+                    *d = Document::parse(&text);
+                });
+                Ok(())
+            })
+            .register_fn(
+                "set_pixel",
+                |doc: &mut ScriptDocument, sprite: &str, x: INT, y: INT, token: &str| {
+                    let (x, y) = (coordinate(x)?, coordinate(y)?);
+                    doc.edit(EditOp::SetPixel {
+                        sprite: sprite.to_string(),
+                        x,
+                        y,
+                        token: token.to_string(),
+                    })
+                },
+            )
+            .register_fn(
+                "swap_token",
+                |doc: &mut ScriptDocument, sprite: &str, from: &str, to: &str| {
+                    doc.edit(EditOp::SwapPaletteToken {
+                        sprite: sprite.to_string(),
+                        from: from.to_string(),
+                        to: to.to_string(),
+                    })
+                },
+            )
+            .register_fn("add_frame", |doc: &mut ScriptDocument, animation: &str, sprite: &str| {
+                doc.edit(EditOp::AddFrame {
+                    animation: animation.to_string(),
+                    sprite: sprite.to_string(),
+                    index: None,
+                })
+            });
+    }
+
+    fn coordinate(value: INT) -> RhaiResult<u32> {
+        u32::try_from(value).map_err(|_| format!("invalid coordinate {}", value).into())
+    }
+
+    pub(super) struct Script {
+        engine: Engine,
+        ast: AST,
+    }
+
+    impl Script {
+        pub(super) fn compile(source: &str, path: &Path) -> Result<Self, HookError> {
+            let mut engine = Engine::new();
+            register_document(&mut engine);
+            let ast = engine.compile(source).map_err(|e| HookError::Compile {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+            Ok(Self { engine, ast })
+        }
+
+        fn has(&self, name: &str, params: usize) -> bool {
+            self.ast.iter_functions().any(|f| f.name == name && f.params.len() == params)
+        }
+
+        fn call(
+            &self,
+            hook: &'static str,
+            args: impl rhai::FuncArgs,
+        ) -> Result<Dynamic, HookError> {
+            self.engine
+                .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, hook, args)
+                .map_err(|e| HookError::Hook { hook, message: e.to_string() })
+        }
+
+        pub(super) fn parse(&self, source: &str, path: &Path) -> Result<ParseResult, HookError> {
+            let file = path.display().to_string();
+            let mut result = if self.has("pre_resolve", 2) {
+                let doc = ScriptDocument(Arc::new(Mutex::new(Document::parse(source))));
+                let _ = self.call("pre_resolve", (doc.clone(), file.clone()))?;
+                parse_str(&doc.with(|d| d.text()))
+            } else {
+                parse_str(source)
+            };
+
+            if self.has("object", 2) {
+                for object in &mut result.objects {
+                    let hook_error = |message: String| HookError::Hook { hook: "object", message };
+                    let value = object_to_dynamic(object).map_err(|e| hook_error(e.to_string()))?;
+                    let replaced = self.call("object", (value, file.clone()))?;
+                    if !replaced.is_unit() {
+                        *object = object_from_dynamic(&replaced).map_err(hook_error)?;
+                    }
+                }
+            }
+            Ok(result)
+        }
+
+        pub(super) fn post_render(
+            &self,
+            info: &RenderInfo<'_>,
+        ) -> Result<Option<String>, HookError> {
+            if !self.has("post_render", 1) {
+                return Ok(None);
+            }
+            let mut map = rhai::Map::new();
+            map.insert("kind".into(), info.kind.into());
+            map.insert("name".into(), info.name.into());
+            map.insert("width".into(), (info.width as INT).into());
+            map.insert("height".into(), (info.height as INT).into());
+            map.insert("scale".into(), (info.scale as INT).into());
+            map.insert("path".into(), info.path.display().to_string().into());
+            let renamed = self.call("post_render", (map,))?;
+            if renamed.is_unit() {
+                return Ok(None);
+            }
+            let name = renamed.into_string().map_err(|t| HookError::Hook {
+                hook: "post_render",
+                message: format!("expected a file name or (), got {}", t),
+            })?;
+            if name.is_empty() || PathBuf::from(&name).components().count() != 1 {
+                return Err(HookError::Hook {
+                    hook: "post_render",
+                    message: format!("'{}' is not a plain file name", name),
+                });
+            }
+            Ok(Some(name))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "red", "size": [2, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+"##;
+
+    fn parse(script: &str) -> Result<ParseResult, HookError> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sprites.pxl");
+        std::fs::write(&path, SOURCE).unwrap();
+        BuildHooks::compile(script, Path::new("hooks.rhai"))?.parse_file(&path)
+    }
+
+    fn names(result: &ParseResult) -> Vec<&str> {
+        result.objects.iter().map(|o| o.name()).collect()
+    }
+
+    #[test]
+    fn test_pre_resolve_edits_document() {
+        let result = parse(
+            r#"
+            fn pre_resolve(doc, file) {
+                for obj in doc.objects() {
+                    if obj.type == "sprite" {
+                        let blue = obj;
+                        blue.name = obj.name + "_blue";
+                        doc.add(blue);
+                        doc.swap_token(blue.name, "r", "b");
+                    }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(names(&result), ["p", "red", "red_blue"]);
+        let crate::models::TtpObject::Sprite(blue) = &result.objects[2] else { panic!() };
+        assert!(blue.regions.as_ref().unwrap().contains_key("b"));
+    }
+
+    #[test]
+    fn test_object_hook_renames_and_validates() {
+        let result = parse(
+            r#"
+            fn object(obj, file) {
+                if obj.type == "sprite" {
+                    obj.name = "spr_" + obj.name;
+                    obj
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(names(&result), ["p", "spr_red"]);
+
+        let err =
+            parse(r#"fn object(obj, file) { if obj.type == "sprite" { throw "no sprites"; } }"#)
+                .unwrap_err();
+        assert!(err.to_string().contains("no sprites"), "{}", err);
+    }
+
+    #[test]
+    fn test_post_render_renames() {
+        let hooks = BuildHooks::compile(
+            r#"fn post_render(info) { if info.scale > 1 { `${info.name}-hd.png` } }"#,
+            Path::new("hooks.rhai"),
+        )
+        .unwrap();
+        let mut info = RenderInfo {
+            kind: "sprite",
+            name: "hero",
+            width: 4,
+            height: 4,
+            scale: 1,
+            path: Path::new("build/hero.png"),
+        };
+        assert_eq!(hooks.post_render(&info).unwrap(), None);
+        info.scale = 2;
+        assert_eq!(hooks.post_render(&info).unwrap().as_deref(), Some("hero-hd.png"));
+    }
+
+    #[test]
+    fn test_compile_error() {
+        let err = BuildHooks::compile("fn pre_resolve(doc, file) {", Path::new("hooks.rhai"));
+        assert!(matches!(err, Err(HookError::Compile { .. })));
+    }
+}
@@ -26,8 +26,8 @@
 //! ```
 
 use crate::build::{
-    BuildContext, BuildError, BuildManifest, BuildPlan, BuildResult, BuildTarget, ManifestError,
-    TargetResult,
+    BuildContext, BuildError, BuildManifest, BuildPipeline, BuildPlan, BuildResult, BuildStatus,
+    BuildTarget, ManifestError, TargetResult,
 };
 use std::path::PathBuf;
 use std::time::Instant;
@@ -246,63 +246,19 @@ impl IncrementalBuild {
         }
     }
 
-    /// Execute the actual build for a target kind.
+    /// Build one target through the full pipeline.
     fn execute_target_kind(&self, target: &BuildTarget) -> Result<Vec<PathBuf>, String> {
-        use crate::build::TargetKind;
-
-        match target.kind {
-            TargetKind::Sprite => self.build_sprite(target),
-            TargetKind::Atlas => self.build_atlas(target),
-            TargetKind::Animation => self.build_animation(target),
-            TargetKind::AnimationPreview => self.build_animation_preview(target),
-            TargetKind::Export => self.build_export(target),
+        let mut plan = BuildPlan::new();
+        plan.add_target(target.clone());
+        let result = BuildPipeline::new(self.context.clone())
+            .build_plan(&plan)
+            .map_err(|e| e.to_string())?;
+        match result.targets.into_iter().next() {
+            Some(TargetResult { status: BuildStatus::Failed(e), .. }) => Err(e),
+            Some(built) => Ok(built.outputs),
+            None => Ok(Vec::new()),
         }
     }
-
-    /// Build a sprite target.
-    fn build_sprite(&self, target: &BuildTarget) -> Result<Vec<PathBuf>, String> {
-        for source in &target.sources {
-            if !source.exists() {
-                return Err(format!("Source file not found: {}", source.display()));
-            }
-        }
-        Ok(vec![target.output.clone()])
-    }
-
-    /// Build an atlas target.
-    fn build_atlas(&self, target: &BuildTarget) -> Result<Vec<PathBuf>, String> {
-        for source in &target.sources {
-            if !source.exists() {
-                return Err(format!("Source file not found: {}", source.display()));
-            }
-        }
-        Ok(vec![target.output.clone()])
-    }
-
-    /// Build an animation target.
-    fn build_animation(&self, target: &BuildTarget) -> Result<Vec<PathBuf>, String> {
-        for source in &target.sources {
-            if !source.exists() {
-                return Err(format!("Source file not found: {}", source.display()));
-            }
-        }
-        Ok(vec![target.output.clone()])
-    }
-
-    /// Build an animation preview target.
-    fn build_animation_preview(&self, target: &BuildTarget) -> Result<Vec<PathBuf>, String> {
-        for source in &target.sources {
-            if !source.exists() {
-                return Err(format!("Source file not found: {}", source.display()));
-            }
-        }
-        Ok(vec![target.output.clone()])
-    }
-
-    /// Build an export target.
-    fn build_export(&self, target: &BuildTarget) -> Result<Vec<PathBuf>, String> {
-        Ok(vec![target.output.clone()])
-    }
 }
 
 /// Statistics about an incremental build.
@@ -59,6 +59,8 @@ use std::io::{BufReader, BufWriter, Read};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use crate::ordered;
+
 /// Current manifest format version.
 const MANIFEST_VERSION: u32 = 2;
 
@@ -90,6 +92,7 @@ pub struct BuildManifest {
     /// When the manifest was last updated
     pub updated_at: String,
     /// Information about each built target
+    #[serde(serialize_with = "ordered::map")]
     pub targets: HashMap<String, TargetManifest>,
     /// Aggregate build statistics from the last build
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -382,6 +385,7 @@ impl Default for BuildManifest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TargetManifest {
     /// Map of source file paths to their content hashes
+    #[serde(serialize_with = "ordered::map")]
     pub sources: HashMap<String, String>,
     /// Output file paths produced by this target
     pub outputs: Vec<String>,
@@ -389,9 +393,11 @@ pub struct TargetManifest {
     pub built_at: String,
     /// Map of output file paths to their checksums (for verification)
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(serialize_with = "ordered::map")]
     pub output_checksums: HashMap<String, String>,
     /// Map of output file paths to their sizes in bytes
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(serialize_with = "ordered::map")]
     pub output_sizes: HashMap<String, u64>,
     /// Build duration in milliseconds
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -567,7 +573,7 @@ impl TargetManifest {
 /// Compute a hash of a file's contents.
 ///
 /// Uses a simple but fast hash for build tracking purposes.
-fn hash_file(path: &Path) -> Result<String, ManifestError> {
+pub(crate) fn hash_file(path: &Path) -> Result<String, ManifestError> {
     let mut file = File::open(path)?;
     let mut contents = Vec::new();
     file.read_to_end(&mut contents)?;
@@ -578,7 +584,7 @@ fn hash_file(path: &Path) -> Result<String, ManifestError> {
 }
 
 /// FNV-1a hash algorithm.
-fn fnv1a_hash(data: &[u8]) -> u64 {
+pub(crate) fn fnv1a_hash(data: &[u8]) -> u64 {
     const FNV_OFFSET: u64 = 0xcbf29ce484222325;
     const FNV_PRIME: u64 = 0x100000001b3;
 
@@ -24,8 +24,10 @@
 //! println!("Built {} targets", result.targets_built);
 //! ```
 
+pub mod artifacts;
 pub mod context;
 pub mod discovery;
+pub mod hooks;
 pub mod incremental;
 pub mod manifest;
 pub mod parallel;
@@ -35,8 +37,10 @@ pub mod project_registry;
 pub mod result;
 pub mod target;
 
+pub use artifacts::*;
 pub use context::*;
 pub use discovery::*;
+pub use hooks::{BuildHooks, HookError, RenderInfo};
 pub use incremental::*;
 pub use manifest::*;
 pub use parallel::*;
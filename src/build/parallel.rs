@@ -244,6 +244,9 @@ impl ParallelBuild {
         let failed_atomic = AtomicBool::new(*failed.lock().expect("failed flag mutex poisoned"));
         let fail_fast = self.fail_fast;
         let context = &self.context;
+        // Defines and PNG options are per-thread, so carry them into the pool's workers
+        let defines = crate::conditions::defines();
+        let png = crate::output::png_options();
 
         // Build a custom thread pool with the configured number of threads
         let pool = rayon::ThreadPoolBuilder::new()
@@ -262,7 +265,11 @@ impl ParallelBuild {
                         return (idx, TargetResult::skipped(target.id.clone()));
                     }
 
-                    let result = self.execute_target_internal(target, context);
+                    let result = crate::conditions::with_defines(defines.clone(), || {
+                        crate::output::with_png_options(png, || {
+                            self.execute_target_internal(target, context)
+                        })
+                    });
 
                     // Mark failure for fail-fast mode
                     if result.status.is_failure() && fail_fast {
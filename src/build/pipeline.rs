@@ -2,19 +2,21 @@
 //!
 //! The pipeline coordinates the execution of build targets in the correct order.
 
-use crate::atlas::{pack_atlas, AtlasBox, AtlasConfig as PackerConfig, SpriteInput};
+use crate::atlas::{pack_atlas, AtlasConfig as PackerConfig, SpriteInput};
+use crate::build::hooks::{parse_with_hooks, BuildHooks, RenderInfo};
 use crate::build::project_registry::ProjectRegistry;
 use crate::build::{BuildContext, BuildPlan, BuildResult, BuildTarget, TargetKind, TargetResult};
+use crate::config::schema::{OutputVars, ProfileConfig};
 use crate::models::TtpObject;
-use crate::parser::parse_stream;
+use crate::output::save_png;
 use crate::registry::{PaletteRegistry, ResolvedSprite, SpriteRegistry};
 use crate::renderer::{render_resolved, render_sprite};
 use crate::resolve_imports::ImportResolver;
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
-use std::fs::{self, File};
-use std::io::BufReader;
+use std::fs;
 use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 use std::time::Instant;
 
 /// Error during build execution.
@@ -33,6 +35,9 @@ pub enum BuildError {
     /// Project registry error
     #[error("Project registry error: {0}")]
     ProjectRegistry(#[from] crate::build::project_registry::ProjectRegistryError),
+    /// Build hook script error
+    #[error("Hook error: {0}")]
+    Hooks(#[from] crate::build::HookError),
     /// Generic build error
     #[error("Build error: {0}")]
     Build(String),
@@ -46,12 +51,14 @@ pub struct BuildPipeline {
     fail_fast: bool,
     /// Whether to do a dry run (don't actually build)
     dry_run: bool,
+    /// Build hooks from `[hooks] script`, compiled on first use
+    hooks: OnceLock<Option<Arc<BuildHooks>>>,
 }
 
 impl BuildPipeline {
     /// Create a new build pipeline.
     pub fn new(context: BuildContext) -> Self {
-        Self { context, fail_fast: false, dry_run: false }
+        Self { context, fail_fast: false, dry_run: false, hooks: OnceLock::new() }
     }
 
     /// Set fail-fast mode (stop on first error).
@@ -97,6 +104,20 @@ impl BuildPipeline {
         Ok(result)
     }
 
+    /// Compile the configured hook script once.
+    fn load_hooks(&self) -> Result<(), BuildError> {
+        if self.hooks.get().is_none() {
+            let hooks =
+                BuildHooks::load(self.context.config(), self.context.project_root())?.map(Arc::new);
+            let _ = self.hooks.set(hooks);
+        }
+        Ok(())
+    }
+
+    fn hooks(&self) -> Option<&BuildHooks> {
+        self.hooks.get().and_then(Option::as_deref)
+    }
+
     /// Load the project-wide registry by parsing all source files.
     ///
     /// This is Pass 1 of the two-pass build architecture: parse all files
@@ -108,7 +129,8 @@ impl BuildPipeline {
         }
 
         let mut registry =
-            ProjectRegistry::new(self.context.config().project.name.clone(), src_dir);
+            ProjectRegistry::new(self.context.config().project.name.clone(), src_dir)
+                .with_hooks(self.hooks.get().cloned().flatten());
         registry.load_all(self.context.is_strict())?;
 
         // Load installed dependencies
@@ -154,6 +176,8 @@ impl BuildPipeline {
             fs::create_dir_all(self.context.out_dir())?;
         }
 
+        self.load_hooks()?;
+
         // Pass 1: Load all project files into shared registries
         let project_registry = self.load_project_registry()?;
 
@@ -211,6 +235,13 @@ impl BuildPipeline {
         let duration = start.elapsed();
 
         match build_result {
+            // Targets with no selected sprites build nothing
+            Ok(outputs) if outputs.is_empty() => {
+                if self.context.is_verbose() {
+                    println!("  Not selected, skipping");
+                }
+                TargetResult::skipped(target.id.clone())
+            }
             Ok(outputs) => {
                 if self.context.is_verbose() {
                     println!("  Done in {:?}", duration);
@@ -252,10 +283,7 @@ impl BuildPipeline {
             .ok_or_else(|| "No source file specified for sprite target".to_string())?;
 
         // Parse the source file
-        let file = File::open(source)
-            .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
-        let reader = BufReader::new(file);
-        let parse_result = parse_stream(reader);
+        let parse_result = parse_with_hooks(source, self.hooks()).map_err(|e| e.to_string())?;
 
         // Check for parse warnings (these may indicate problems)
         if !parse_result.warnings.is_empty() && self.context.is_strict() {
@@ -387,6 +415,9 @@ impl BuildPipeline {
         let sprite = sprite_registry
             .get_sprite(&sprite_name)
             .ok_or_else(|| format!("Sprite '{}' not found in registry", sprite_name))?;
+        if !self.context.selection().includes("sprite", &sprite.name, &sprite.labels) {
+            return Ok(Vec::new());
+        }
 
         // Determine if we need transform resolution (has source reference or transforms)
         let needs_transform_resolution = sprite.source.is_some() || sprite.transform.is_some();
@@ -442,25 +473,41 @@ impl BuildPipeline {
             }
         }
 
-        // Apply scale if configured
-        let scale = self.context.default_scale();
-        let final_image = if scale > 1 {
-            image::imageops::resize(
-                &image,
-                image.width() * scale,
-                image.height() * scale,
-                image::imageops::FilterType::Nearest,
-            )
-        } else {
-            image
-        };
+        // Save one PNG per export profile
+        let mut outputs = Vec::new();
+        for profile in self.context.config().export_profiles() {
+            let scale = profile.scale;
+            let final_image = if scale > 1 {
+                image::imageops::resize(
+                    &image,
+                    image.width() * scale,
+                    image.height() * scale,
+                    image::imageops::FilterType::Nearest,
+                )
+            } else {
+                image.clone()
+            };
 
-        // Save the PNG
-        final_image
-            .save(&target.output)
-            .map_err(|e| format!("Failed to save {}: {}", target.output.display(), e))?;
+            let mut output = profile_output_path(&target.output, &profile);
+            if let Some(hooks) = self.hooks() {
+                let info = RenderInfo {
+                    kind: "sprite",
+                    name: &target.name,
+                    width: final_image.width(),
+                    height: final_image.height(),
+                    scale,
+                    path: &output,
+                };
+                if let Some(file_name) = hooks.post_render(&info).map_err(|e| e.to_string())? {
+                    output.set_file_name(file_name);
+                }
+            }
+            save_png(&final_image, &output)
+                .map_err(|e| format!("Failed to save {}: {}", output.display(), e))?;
+            outputs.push(output);
+        }
 
-        Ok(vec![target.output.clone()])
+        Ok(outputs)
     }
 
     /// Build an atlas target.
@@ -498,8 +545,16 @@ impl BuildPipeline {
             power_of_two: atlas_config.power_of_two,
         };
 
-        let scale = self.context.default_scale();
+        let profiles = self.context.config().export_profiles();
         let is_strict = self.context.is_strict();
+        let theme = match &target.theme {
+            Some(name) => Some(
+                project_registry
+                    .and_then(|r| r.theme(name))
+                    .ok_or_else(|| format!("Theme '{}' not found", name))?,
+            ),
+            None => None,
+        };
         let is_verbose = self.context.is_verbose();
         let multi_source = target.sources.len() > 1;
 
@@ -522,10 +577,7 @@ impl BuildPipeline {
 
         for source in &target.sources {
             // Parse the source file
-            let file = File::open(source)
-                .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
-            let reader = BufReader::new(file);
-            let parse_result = parse_stream(reader);
+            let parse_result = parse_with_hooks(source, self.hooks()).map_err(|e| e.to_string())?;
 
             // Separate local items and import declarations
             let mut local_palette_registry = PaletteRegistry::new();
@@ -593,7 +645,7 @@ impl BuildPipeline {
 
             // Select registries with import support
             let has_file_imports = !file_imports.is_empty();
-            let effective_palette_reg;
+            let mut effective_palette_reg;
             let effective_sprite_reg;
 
             if has_file_imports {
@@ -628,13 +680,31 @@ impl BuildPipeline {
                 };
             }
 
+            if let Some(theme) = theme {
+                for missing in effective_palette_reg.apply_theme(theme) {
+                    let message = format!(
+                        "Theme '{}' replacement palette '{}' not found",
+                        theme.name, missing
+                    );
+                    if is_strict {
+                        return Err(message);
+                    } else if is_verbose {
+                        eprintln!("Warning: {}", message);
+                    }
+                }
+            }
+
             let palette_registry = &effective_palette_reg;
             let sprite_registry = &effective_sprite_reg;
 
-            // Create render tasks for each sprite
+            // Create render tasks for each selected sprite
             let file_stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+            let selection = self.context.selection();
 
             for sprite in sprites {
+                if !selection.includes("sprite", &sprite.name, &sprite.labels) {
+                    continue;
+                }
                 let qualified_name = if multi_source {
                     format!("{}:{}", file_stem, sprite.name)
                 } else {
@@ -703,6 +773,8 @@ impl BuildPipeline {
                     warnings: vec![],
                     nine_slice: task.sprite.nine_slice.clone(),
                     regions: task.sprite.regions.clone(),
+                    layers: task.sprite.layers.clone(),
+                    opacity: task.sprite.opacity,
                 };
                 let (image, render_warnings) = render_resolved(&resolved);
 
@@ -726,31 +798,11 @@ impl BuildPipeline {
                     }
                 }
 
-                // Apply scale if configured
-                let final_image = if scale > 1 {
-                    image::imageops::resize(
-                        &image,
-                        image.width() * scale,
-                        image.height() * scale,
-                        image::imageops::FilterType::Nearest,
-                    )
-                } else {
-                    image
-                };
-
-                // Extract metadata (origin and boxes)
-                let origin = task.sprite.metadata.as_ref().and_then(|m| m.origin);
-                let boxes = task.sprite.metadata.as_ref().and_then(|m| {
-                    m.boxes.as_ref().map(|b| {
-                        b.iter()
-                            .map(|(name, cb)| {
-                                (name.clone(), AtlasBox { x: cb.x, y: cb.y, w: cb.w, h: cb.h })
-                            })
-                            .collect::<HashMap<_, _>>()
-                    })
-                });
-
-                Ok(SpriteInput { name: task.qualified_name, image: final_image, origin, boxes })
+                Ok(SpriteInput::with_metadata(
+                    task.qualified_name,
+                    image,
+                    task.sprite.metadata.as_ref(),
+                ))
             })
             .collect();
 
@@ -759,43 +811,65 @@ impl BuildPipeline {
             render_results.into_iter().collect::<Result<Vec<_>, _>>()?;
 
         if sprite_inputs.is_empty() {
+            if !self.context.selection().is_empty() {
+                return Ok(Vec::new());
+            }
             return Err(format!("No sprites found in source files for atlas '{}'", target.name));
         }
 
-        // Pack sprites into atlas
-        let base_name = target.output.file_stem().and_then(|s| s.to_str()).unwrap_or(&target.name);
-        let result = pack_atlas(&sprite_inputs, &packer_config, base_name);
+        // Pack and save one atlas per export profile; scaling each sprite input
+        // keeps origins and collision boxes in the scaled pixel space
+        let mut outputs = Vec::new();
 
-        if result.atlases.is_empty() {
-            return Err("Failed to pack any sprites into atlas".to_string());
-        }
+        for profile in &profiles {
+            let path = self.atlas_output_path(&target.name, target.theme.as_deref(), profile);
+            let base_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or(&target.name);
+            let out_dir = path.parent().unwrap_or_else(|| std::path::Path::new("."));
+            fs::create_dir_all(out_dir)
+                .map_err(|e| format!("Failed to create output directory: {}", e))?;
 
-        // Save atlas images and metadata
-        let mut outputs = Vec::new();
-        let out_dir = target.output.parent().unwrap_or_else(|| std::path::Path::new("."));
-
-        for (image, metadata) in &result.atlases {
-            // Save the PNG
-            let png_path = out_dir.join(&metadata.image);
-            image
-                .save(&png_path)
-                .map_err(|e| format!("Failed to save atlas PNG {}: {}", png_path.display(), e))?;
-            outputs.push(png_path);
-
-            // Save the JSON metadata
-            let json_name = metadata.image.replace(".png", ".json");
-            let json_path = out_dir.join(&json_name);
-            let json_content = serde_json::to_string_pretty(&metadata)
-                .map_err(|e| format!("Failed to serialize atlas metadata: {}", e))?;
-            fs::write(&json_path, json_content).map_err(|e| {
-                format!("Failed to write atlas JSON {}: {}", json_path.display(), e)
-            })?;
-            outputs.push(json_path);
+            let scaled_inputs: Vec<SpriteInput> =
+                sprite_inputs.iter().map(|input| input.scaled(profile.scale)).collect();
+            let result = pack_atlas(&scaled_inputs, &packer_config, base_name);
+
+            if result.atlases.is_empty() {
+                return Err("Failed to pack any sprites into atlas".to_string());
+            }
+
+            for (image, metadata) in &result.atlases {
+                // Save the PNG
+                let png_path = out_dir.join(&metadata.image);
+                save_png(image, &png_path).map_err(|e| {
+                    format!("Failed to save atlas PNG {}: {}", png_path.display(), e)
+                })?;
+                outputs.push(png_path);
+
+                // Save the JSON metadata
+                let json_name = metadata.image.replace(".png", ".json");
+                let json_path = out_dir.join(&json_name);
+                let json_content = serde_json::to_string_pretty(&metadata)
+                    .map_err(|e| format!("Failed to serialize atlas metadata: {}", e))?;
+                fs::write(&json_path, json_content).map_err(|e| {
+                    format!("Failed to write atlas JSON {}: {}", json_path.display(), e)
+                })?;
+                outputs.push(json_path);
+            }
         }
 
         Ok(outputs)
     }
 
+    /// Atlas PNG path for one export profile, expanded from `output.atlas`.
+    fn atlas_output_path(
+        &self,
+        name: &str,
+        theme: Option<&str>,
+        profile: &ProfileConfig,
+    ) -> PathBuf {
+        let vars = OutputVars { kind: "atlas", name, ext: "png", theme };
+        self.context.output_path(&self.context.config().output.atlas, vars, profile)
+    }
+
     /// Build an animation target.
     fn build_animation(&self, target: &BuildTarget) -> Result<Vec<std::path::PathBuf>, String> {
         // Animation building will be implemented by downstream tasks
@@ -831,6 +905,7 @@ impl BuildPipeline {
             godot::{GodotExportOptions, GodotExporter},
             libgdx::{LibGdxExportOptions, LibGdxExporter},
             unity::{UnityExportOptions, UnityExporter, UnityFilterMode},
+            ExportOptions,
         };
 
         // Parse format from target ID (export:format:name)
@@ -841,8 +916,10 @@ impl BuildPipeline {
         let format = parts[1];
         let atlas_name = parts[2];
 
-        // Find the atlas JSON metadata file
-        let atlas_json_path = self.context.out_dir().join(format!("{}.json", atlas_name));
+        // Find the atlas JSON metadata file (engine exports use the smallest profile)
+        let profile = &self.context.config().export_profiles()[0];
+        let atlas_json_path =
+            self.atlas_output_path(atlas_name, None, profile).with_extension("json");
         if !atlas_json_path.exists() {
             return Err(format!(
                 "Atlas metadata not found: {}. Build the atlas first.",
@@ -917,7 +994,13 @@ impl BuildPipeline {
                 vec![target.output.clone()]
             }
             _ => {
-                return Err(format!("Unknown export format: {}", format));
+                let exporter = crate::plugin::exporter(format)
+                    .ok_or_else(|| format!("Unknown export format: {}", format))?;
+                exporter
+                    .export(&metadata, &target.output, &ExportOptions::default())
+                    .map_err(|e| format!("{} export failed: {}", format, e))?;
+
+                vec![target.output.clone()]
             }
         };
 
@@ -925,6 +1008,16 @@ impl BuildPipeline {
     }
 }
 
+/// Output path for a profile: `build/hero.png` -> `build/hero@2x.png`
+fn profile_output_path(output: &std::path::Path, profile: &ProfileConfig) -> PathBuf {
+    let stem = output.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let mut file_name = profile.file_stem(stem);
+    if let Some(ext) = output.extension().and_then(|e| e.to_str()) {
+        file_name = format!("{}.{}", file_name, ext);
+    }
+    output.with_file_name(file_name)
+}
+
 /// Builder for configuring and running builds.
 pub struct Build {
     context: Option<BuildContext>,
@@ -1012,7 +1105,9 @@ impl Default for Build {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::build::BuildStatus;
     use crate::config::default_config;
+    use crate::selection::Selection;
     use std::fs::File;
     use std::io::Write;
     use tempfile::TempDir;
@@ -1122,6 +1217,42 @@ mod tests {
         assert_eq!(img.width(), 1);
         assert_eq!(img.height(), 1);
     }
+
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn test_build_sprite_runs_hooks() {
+        let (temp, ctx) = create_test_context();
+        let script = r#"
+            fn object(obj, file) {
+                if obj.type == "sprite" && obj.size[0] > 4 { throw `${obj.name} is too wide`; }
+            }
+            fn post_render(info) { `${info.name}_${info.width}x${info.height}.png` }
+        "#;
+        fs::write(temp.path().join("hooks.rhai"), script).unwrap();
+        let mut config = ctx.config().clone();
+        config.hooks.script = Some(PathBuf::from("hooks.rhai"));
+        let pipeline = BuildPipeline::new(BuildContext::new(config, temp.path().to_path_buf()));
+
+        let src_dir = temp.path().join("src/pxl");
+        let dot = src_dir.join("dot.pxl");
+        fs::write(&dot, r##"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]]}}}"##).unwrap();
+        let wide = src_dir.join("wide.pxl");
+        fs::write(&wide, r##"{"type": "sprite", "name": "wide", "size": [8, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"rect": [0, 0, 8, 1]}}}"##).unwrap();
+        let out_dir = temp.path().join("build");
+        fs::create_dir_all(&out_dir).unwrap();
+        pipeline.load_hooks().unwrap();
+
+        let target = BuildTarget::sprite("dot".to_string(), dot, out_dir.join("dot.png"));
+        let result = pipeline.execute_target(&target, None);
+        assert!(result.status.is_success(), "Expected success, got: {:?}", result.status);
+        assert!(out_dir.join("dot_1x1.png").exists());
+        assert!(!out_dir.join("dot.png").exists());
+
+        let target = BuildTarget::sprite("wide".to_string(), wide, out_dir.join("wide.png"));
+        let result = pipeline.execute_target(&target, None);
+        assert!(result.status.is_failure());
+    }
+
     fn create_atlas_test_context(atlas_name: &str, sources: Vec<&str>) -> (TempDir, BuildContext) {
         use crate::config::{AtlasConfig as ConfigAtlas, ProjectConfig, PxlConfig};
 
@@ -1138,6 +1269,7 @@ mod tests {
                 power_of_two: false,
                 nine_slice: false,
                 antialias: None,
+                themes: vec![],
             },
         );
 
@@ -1205,6 +1337,42 @@ mod tests {
         assert!(json_content.contains("green"), "JSON should contain green sprite");
     }
 
+    #[test]
+    fn test_build_atlas_selection() {
+        let (temp, ctx) = create_atlas_test_context("ui", vec!["**/*.pxl"]);
+        let selection = Selection {
+            only: vec!["label=ui".parse().unwrap()],
+            exclude: vec!["name=debug_*".parse().unwrap()],
+        };
+        let ctx = ctx.with_selection(selection);
+
+        let source = temp.path().join("src/pxl/ui.pxl");
+        let content = r##"{"type": "sprite", "name": "button", "labels": ["ui"], "size": [1, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "debug_grid", "labels": ["ui"], "size": [1, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "hero", "size": [1, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]]}}}"##;
+        fs::write(&source, content).unwrap();
+
+        let out_dir = temp.path().join("build");
+        fs::create_dir_all(&out_dir).unwrap();
+        let target =
+            BuildTarget::atlas("ui".to_string(), vec![source.clone()], out_dir.join("ui.png"));
+
+        let result = BuildPipeline::new(ctx.clone()).execute_target(&target, None);
+        assert!(result.status.is_success(), "Expected success, got: {:?}", result.status);
+        let json = fs::read_to_string(out_dir.join("ui.json")).unwrap();
+        assert!(json.contains("button"));
+        assert!(!json.contains("debug_grid"));
+        assert!(!json.contains("hero"));
+
+        // Nothing selected: the target is skipped rather than failing
+        let ctx = ctx.with_selection(Selection {
+            only: vec!["label=enemy".parse().unwrap()],
+            ..Default::default()
+        });
+        let result = BuildPipeline::new(ctx).execute_target(&target, None);
+        assert_eq!(result.status, BuildStatus::Skipped);
+    }
+
     #[test]
     fn test_build_atlas_with_metadata() {
         let (temp, ctx) = create_atlas_test_context("player", vec!["*.pxl"]);
@@ -1237,6 +1405,156 @@ mod tests {
         assert!(json_content.contains("\"hurt\""), "JSON should contain hurt box");
     }
 
+    #[test]
+    fn test_build_atlas_export_profiles() {
+        use crate::config::schema::ProfileConfig;
+
+        let (temp, ctx) = create_atlas_test_context("player", vec!["*.pxl"]);
+        let mut config = ctx.config().clone();
+        config
+            .profiles
+            .insert("1x".to_string(), ProfileConfig { scale: 1, naming: "{name}".to_string() });
+        config
+            .profiles
+            .insert("2x".to_string(), ProfileConfig { scale: 2, naming: "{name}@2x".to_string() });
+        let ctx = BuildContext::new(config, temp.path().to_path_buf());
+
+        let src_dir = temp.path().join("src/pxl");
+        let sprite_file = src_dir.join("player.pxl");
+        let sprite_content = r##"{"type": "sprite", "name": "player", "size": [4, 4], "palette": {"r": "#FF0000"}, "regions": {"r": {"rect": [0, 0, 4, 4], "z": 0}}, "metadata": {"origin": [2, 4], "boxes": {"hurt": {"x": 0, "y": 1, "w": 4, "h": 3}}}}"##;
+        File::create(&sprite_file).unwrap().write_all(sprite_content.as_bytes()).unwrap();
+
+        let out_dir = temp.path().join("build");
+        fs::create_dir_all(&out_dir).unwrap();
+        let pipeline = BuildPipeline::new(ctx);
+
+        let target =
+            BuildTarget::atlas("player".to_string(), vec![sprite_file], out_dir.join("player.png"));
+        let result = pipeline.execute_target(&target, None);
+        assert!(result.status.is_success(), "Expected success, got: {:?}", result.status);
+
+        let small = image::open(out_dir.join("player.png")).expect("1x atlas");
+        let large = image::open(out_dir.join("player@2x.png")).expect("2x atlas");
+        assert_eq!((small.width(), small.height()), (4, 4));
+        assert_eq!((large.width(), large.height()), (8, 8));
+
+        // Origins and boxes are scaled with the sprite
+        let json = fs::read_to_string(out_dir.join("player@2x.json")).unwrap();
+        let metadata: crate::atlas::AtlasMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(metadata.image, "player@2x.png");
+        let frame = &metadata.frames["player"];
+        assert_eq!(frame.origin, Some([4, 8]));
+        let hurt = &frame.boxes.as_ref().unwrap()["hurt"];
+        assert_eq!((hurt.x, hurt.y, hurt.w, hurt.h), (0, 2, 8, 6));
+    }
+
+    #[test]
+    fn test_build_export_plugin() {
+        use crate::atlas::AtlasMetadata;
+        use crate::export::ExportOptions;
+        use crate::plugin::{register_exporter, ExporterPlugin};
+
+        struct FrameNames;
+
+        impl ExporterPlugin for FrameNames {
+            fn name(&self) -> &str {
+                "test-frame-names"
+            }
+
+            fn extension(&self) -> &str {
+                "names"
+            }
+
+            fn export(
+                &self,
+                metadata: &AtlasMetadata,
+                output_path: &std::path::Path,
+                _options: &ExportOptions,
+            ) -> crate::export::Result<()> {
+                let mut names: Vec<&str> = metadata.frames.keys().map(|k| k.as_str()).collect();
+                names.sort_unstable();
+                fs::write(output_path, names.join("\n"))?;
+                Ok(())
+            }
+        }
+
+        register_exporter(FrameNames).unwrap();
+        let (temp, ctx) = create_atlas_test_context("chars", vec!["*.pxl"]);
+        let mut config = ctx.config().clone();
+        config.exports.plugins = vec!["test-frame-names".to_string()];
+        let ctx = BuildContext::new(config, temp.path().to_path_buf());
+
+        let sprite = r##"{"type": "sprite", "name": "hero", "size": [1, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]]}}}"##;
+        fs::write(temp.path().join("src/pxl/hero.pxl"), sprite).unwrap();
+
+        let result = BuildPipeline::new(ctx).build().unwrap();
+        assert!(result.is_success(), "{}", result.summary());
+        let exported = temp.path().join("build/test-frame-names/chars.names");
+        assert_eq!(fs::read_to_string(exported).unwrap(), "hero");
+    }
+
+    #[test]
+    fn test_build_atlas_output_template_and_theme() {
+        let (temp, ctx) = create_atlas_test_context("chars", vec!["**/*.pxl"]);
+        let mut config = ctx.config().clone();
+        config.output.atlas = "{theme}/{name}@{scale}x.{ext}".to_string();
+        config.atlases.get_mut("chars").unwrap().themes = vec!["night".to_string()];
+        let ctx = BuildContext::new(config, temp.path().to_path_buf());
+
+        let content = [
+            r##"{"type": "palette", "name": "day", "colors": {"{c}": "#FF0000"}}"##,
+            r##"{"type": "palette", "name": "dark", "colors": {"{c}": "#0000FF"}}"##,
+            r##"{"type": "theme", "name": "night", "palettes": {"day": "dark"}}"##,
+            r##"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": "day", "regions": {"{c}": {"points": [[0, 0]], "z": 0}}}"##,
+        ]
+        .join("\n");
+        fs::write(temp.path().join("src/pxl/dot.pxl"), content).unwrap();
+
+        let result = BuildPipeline::new(ctx).build().unwrap();
+        assert!(result.is_success(), "Build should succeed: {:?}", result);
+
+        let out_dir = temp.path().join("build");
+        let day = image::open(out_dir.join("chars@1x.png")).expect("unthemed atlas").to_rgba8();
+        let night =
+            image::open(out_dir.join("night/chars@1x.png")).expect("themed atlas").to_rgba8();
+        assert_eq!(day.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(night.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert!(out_dir.join("night/chars@1x.json").exists());
+    }
+
+    #[test]
+    fn test_build_sprite_export_profiles() {
+        use crate::config::schema::ProfileConfig;
+
+        let (temp, ctx) = create_test_context();
+        let mut config = ctx.config().clone();
+        config.profiles.insert(
+            "2x".to_string(),
+            ProfileConfig { scale: 2, naming: "{name}@{scale}x".to_string() },
+        );
+        config.profiles.insert(
+            "4x".to_string(),
+            ProfileConfig { scale: 4, naming: "{name}@{scale}x".to_string() },
+        );
+        let ctx = BuildContext::new(config, temp.path().to_path_buf());
+
+        let sprite_file = temp.path().join("src/pxl/dot.pxl");
+        let sprite_content = r##"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]], "z": 0}}}"##;
+        File::create(&sprite_file).unwrap().write_all(sprite_content.as_bytes()).unwrap();
+
+        let out_dir = temp.path().join("build");
+        fs::create_dir_all(&out_dir).unwrap();
+        let pipeline = BuildPipeline::new(ctx);
+
+        let target = BuildTarget::sprite("dot".to_string(), sprite_file, out_dir.join("dot.png"));
+        let result = pipeline.execute_target(&target, None);
+        assert!(result.status.is_success(), "Expected success, got: {:?}", result.status);
+
+        assert!(!out_dir.join("dot.png").exists());
+        assert_eq!(image::open(out_dir.join("dot@2x.png")).unwrap().width(), 2);
+        assert_eq!(image::open(out_dir.join("dot@4x.png")).unwrap().width(), 4);
+    }
+
     #[test]
     fn test_build_atlas_no_sprites_error() {
         let (temp, ctx) = create_atlas_test_context("empty", vec!["*.pxl"]);
@@ -1410,6 +1728,7 @@ mod tests {
                 power_of_two: false,
                 nine_slice: false,
                 antialias: None,
+                themes: vec![],
             },
         );
 
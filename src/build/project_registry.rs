@@ -10,16 +10,16 @@
 //! references by maintaining a shared namespace across the entire project.
 
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use thiserror::Error;
 
 use crate::build::discover_files;
+use crate::build::hooks::{parse_with_hooks, BuildHooks, HookError};
 use crate::config::schema::Dependency;
-use crate::models::TtpObject;
-use crate::parser::parse_stream;
+use crate::models::{Theme, TtpObject};
+use crate::parser::parse_file;
 use crate::registry::{CompositionRegistry, PaletteRegistry, SpriteRegistry, TransformRegistry};
 
 /// Error type for project registry operations.
@@ -49,6 +49,9 @@ pub enum ProjectRegistryError {
     /// Dependency config error
     #[error("Dependency '{name}' config error: {detail}")]
     DependencyConfigError { name: String, detail: String },
+    /// A build hook failed
+    #[error("{0}")]
+    Hooks(HookError),
 }
 
 /// Warning from project registry loading (lenient mode).
@@ -86,6 +89,8 @@ pub struct ProjectRegistry {
     pub transforms: TransformRegistry,
     /// Composition registry (all compositions from all files)
     pub compositions: CompositionRegistry,
+    /// Themes by name (last definition wins)
+    themes: HashMap<String, Theme>,
     /// Canonical name → location for palettes
     palette_locations: HashMap<String, ItemLocation>,
     /// Canonical name → location for sprites
@@ -110,6 +115,8 @@ pub struct ProjectRegistry {
     warnings: Vec<ProjectRegistryWarning>,
     /// Files that were loaded
     loaded_files: Vec<PathBuf>,
+    /// Hooks run on project files as they're parsed
+    hooks: Option<Arc<BuildHooks>>,
 }
 
 impl ProjectRegistry {
@@ -122,6 +129,7 @@ impl ProjectRegistry {
             sprites: SpriteRegistry::new(),
             transforms: TransformRegistry::new(),
             compositions: CompositionRegistry::new(),
+            themes: HashMap::new(),
             palette_locations: HashMap::new(),
             sprite_locations: HashMap::new(),
             variant_locations: HashMap::new(),
@@ -134,9 +142,18 @@ impl ProjectRegistry {
             composition_short_names: HashMap::new(),
             warnings: Vec::new(),
             loaded_files: Vec::new(),
+            hooks: None,
         }
     }
 
+    /// Run build hooks on project files as they're loaded.
+    ///
+    /// Installed dependencies are parsed as published, without hooks.
+    pub fn with_hooks(mut self, hooks: Option<Arc<BuildHooks>>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     /// Load all `.pxl` and `.jsonl` files from the project source directory.
     ///
     /// In strict mode, name collisions are errors. In lenient mode, they produce
@@ -163,10 +180,11 @@ impl ProjectRegistry {
         file_path: &Path,
         strict: bool,
     ) -> Result<(), ProjectRegistryError> {
-        let file = File::open(file_path)
-            .map_err(|e| ProjectRegistryError::Io { path: file_path.to_path_buf(), source: e })?;
-        let reader = BufReader::new(file);
-        let parse_result = parse_stream(reader);
+        let parse_result =
+            parse_with_hooks(file_path, self.hooks.as_deref()).map_err(|e| match e {
+                HookError::Io { path, source } => ProjectRegistryError::Io { path, source },
+                e => ProjectRegistryError::Hooks(e),
+            })?;
 
         let file_module = self.file_to_module_path(file_path);
 
@@ -232,6 +250,9 @@ impl ProjectRegistry {
                     )?;
                     self.compositions.register(c);
                 }
+                TtpObject::Theme(t) => {
+                    self.themes.insert(t.name.clone(), t);
+                }
                 // Animation, Particle, StateRules — not yet indexed in project registry
                 _ => {}
             }
@@ -366,10 +387,8 @@ impl ProjectRegistry {
         file_path: &Path,
         strict: bool,
     ) -> Result<(), ProjectRegistryError> {
-        let file = File::open(file_path)
+        let parse_result = parse_file(file_path)
             .map_err(|e| ProjectRegistryError::Io { path: file_path.to_path_buf(), source: e })?;
-        let reader = BufReader::new(file);
-        let parse_result = parse_stream(reader);
 
         // Module path relative to the dep's src root
         let relative = file_path.strip_prefix(dep_src_root).unwrap_or(file_path);
@@ -455,6 +474,11 @@ impl ProjectRegistry {
         &self.src_root
     }
 
+    /// Get a theme by name.
+    pub fn theme(&self, name: &str) -> Option<&Theme> {
+        self.themes.get(name)
+    }
+
     /// Get warnings accumulated during loading.
     pub fn warnings(&self) -> &[ProjectRegistryWarning] {
         &self.warnings
@@ -952,7 +976,7 @@ fn validate_name(name: &str, file_path: &Path) -> Result<(), ProjectRegistryErro
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use std::fs::{self, File};
     use std::io::Write;
     use tempfile::TempDir;
 
@@ -47,6 +47,8 @@ pub struct BuildTarget {
     pub output: PathBuf,
     /// Dependencies (other target IDs that must be built first)
     pub dependencies: Vec<String>,
+    /// Theme applied to the palettes for this target
+    pub theme: Option<String>,
 }
 
 impl BuildTarget {
@@ -60,13 +62,22 @@ impl BuildTarget {
             sources: vec![source],
             output,
             dependencies: vec![],
+            theme: None,
         }
     }
 
     /// Create a new atlas target.
     pub fn atlas(name: String, sources: Vec<PathBuf>, output: PathBuf) -> Self {
         let id = format!("atlas:{}", name);
-        Self { id, kind: TargetKind::Atlas, name, sources, output, dependencies: vec![] }
+        Self {
+            id,
+            kind: TargetKind::Atlas,
+            name,
+            sources,
+            output,
+            dependencies: vec![],
+            theme: None,
+        }
     }
 
     /// Create a new animation target.
@@ -79,6 +90,7 @@ impl BuildTarget {
             sources: vec![source],
             output,
             dependencies: vec![],
+            theme: None,
         }
     }
 
@@ -93,13 +105,29 @@ impl BuildTarget {
             sources: vec![source],
             output,
             dependencies: vec![dep],
+            theme: None,
         }
     }
 
     /// Create a new export target.
     pub fn export(name: String, format: String, output: PathBuf) -> Self {
         let id = format!("export:{}:{}", format, name);
-        Self { id, kind: TargetKind::Export, name, sources: vec![], output, dependencies: vec![] }
+        Self {
+            id,
+            kind: TargetKind::Export,
+            name,
+            sources: vec![],
+            output,
+            dependencies: vec![],
+            theme: None,
+        }
+    }
+
+    /// Apply a theme to this target, adding it to the target ID.
+    pub fn with_theme(mut self, theme: String) -> Self {
+        self.id = format!("{}:{}", self.id, theme);
+        self.theme = Some(theme);
+        self
     }
 
     /// Add a dependency to this target.
@@ -0,0 +1,286 @@
+//! Compiling projects into `.pxb` runtime bundles
+//!
+//! `pxl build --bundle out.pxb` renders every sprite, variant and animation
+//! in a project once and stores the results as a [`SpritePool`], so games
+//! load plain RGBA8 data instead of parsing and resolving JSONL at startup.
+//!
+//! Animation frames that render identically to a sprite refer to that
+//! sprite; other frames (transformed, keyframed, palette-cycled, or padded
+//! to line up frames on their origins) are added as sprites named
+//! `animation/index`. Every sprite is also packed onto
+//! atlas pages for games that upload one texture.
+
+use std::collections::{BTreeMap, HashMap};
+
+use image::RgbaImage;
+
+use crate::atlas::{pack_atlas, AtlasConfig, SpriteInput};
+use crate::color::parse_color;
+use crate::models::{CollisionBox, SpriteMetadata};
+use crate::runtime::{
+    AnimationData, AtlasData, AtlasPlacement, AttachPoint, FrameData, HitBox, PaletteData,
+    SpriteData, SpriteId, SpritePool,
+};
+use crate::serve::PreviewCatalog;
+
+/// Compile every object in a catalog into a sprite pool.
+///
+/// Objects that fail to render are left out and returned as `(key, error)`
+/// pairs, keyed like snapshots (`sprite/name`, `animation/name`).
+pub fn compile_bundle(catalog: &PreviewCatalog) -> (SpritePool, Vec<(String, String)>) {
+    let mut builder = PoolBuilder::default();
+    let mut errors = Vec::new();
+
+    for name in catalog.sprite_names() {
+        match catalog.render_sprite(name) {
+            Ok(image) => {
+                let meta = catalog.sprite(name).and_then(|s| s.metadata.as_ref());
+                builder.add(sprite_data(name.to_string(), image, meta));
+            }
+            Err(e) => errors.push((format!("sprite/{}", name), e)),
+        }
+    }
+
+    let mut animations = Vec::new();
+    for name in catalog.animation_names() {
+        match compile_animation(catalog, name, &mut builder) {
+            Ok(animation) => animations.push(animation),
+            Err(e) => errors.push((format!("animation/{}", name), e)),
+        }
+    }
+
+    let atlases = pack_sprites(&builder.sprites);
+    let metadata = vec![("generator".to_string(), format!("pxl {}", env!("CARGO_PKG_VERSION")))];
+    let pool = SpritePool::new(builder.sprites, animations)
+        .with_palettes(compile_palettes(catalog))
+        .with_atlases(atlases)
+        .with_metadata(metadata);
+    (pool, errors)
+}
+
+/// Sprites collected so far, with lookup by name and by pixels
+#[derive(Default)]
+struct PoolBuilder {
+    sprites: Vec<SpriteData>,
+    ids: HashMap<String, SpriteId>,
+    by_pixels: HashMap<(u32, u32, Vec<u8>), SpriteId>,
+}
+
+impl PoolBuilder {
+    fn add(&mut self, sprite: SpriteData) -> SpriteId {
+        let id = SpriteId(self.sprites.len() as u32);
+        self.ids.insert(sprite.name.clone(), id);
+        self.by_pixels.entry((sprite.width, sprite.height, sprite.pixels.clone())).or_insert(id);
+        self.sprites.push(sprite);
+        id
+    }
+
+    /// Id of the first sprite with exactly these pixels, adding the image as
+    /// a sprite named `name` if there's none.
+    fn intern(&mut self, name: String, image: RgbaImage) -> SpriteId {
+        let (width, height) = image.dimensions();
+        let key = (width, height, image.into_raw());
+        if let Some(&id) = self.by_pixels.get(&key) {
+            return id;
+        }
+        let (width, height, pixels) = key;
+        self.add(SpriteData {
+            name,
+            width,
+            height,
+            pixels,
+            origin: [0, 0],
+            boxes: Vec::new(),
+            attach_in: None,
+            attach_out: None,
+        })
+    }
+}
+
+fn hit_box(name: &str, b: &CollisionBox) -> HitBox {
+    HitBox { name: name.to_string(), x: b.x, y: b.y, w: b.w, h: b.h }
+}
+
+fn sprite_data(name: String, image: RgbaImage, meta: Option<&SpriteMetadata>) -> SpriteData {
+    let (width, height) = image.dimensions();
+    let mut boxes: Vec<HitBox> = meta
+        .and_then(|m| m.boxes.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|(name, b)| hit_box(name, b))
+        .collect();
+    boxes.sort_by(|a, b| a.name.cmp(&b.name));
+    SpriteData {
+        name,
+        width,
+        height,
+        pixels: image.into_raw(),
+        origin: meta.and_then(|m| m.origin).unwrap_or([0, 0]),
+        boxes,
+        attach_in: meta.and_then(|m| m.attach_in),
+        attach_out: meta.and_then(|m| m.attach_out),
+    }
+}
+
+fn compile_animation(
+    catalog: &PreviewCatalog,
+    name: &str,
+    builder: &mut PoolBuilder,
+) -> Result<AnimationData, String> {
+    let (images, durations, looping) = catalog.render_animation(name)?;
+    let anim = catalog.animation(name);
+    // Per-frame boxes only line up when each listed frame renders to one image
+    let frame_meta =
+        anim.and_then(|a| a.frame_metadata.as_ref()).filter(|m| m.len() == images.len());
+
+    let mut frames = Vec::with_capacity(images.len());
+    for (i, (image, duration_ms)) in images.into_iter().zip(durations).enumerate() {
+        let sprite = builder.intern(format!("{}/{}", name, i), image);
+        let mut boxes: BTreeMap<String, HitBox> = builder.sprites[sprite.0 as usize]
+            .boxes
+            .iter()
+            .map(|b| (b.name.clone(), b.clone()))
+            .collect();
+        if let Some(overrides) = frame_meta.and_then(|m| m[i].boxes.as_ref()) {
+            for (box_name, b) in overrides {
+                match b {
+                    Some(b) => boxes.insert(box_name.clone(), hit_box(box_name, b)),
+                    None => boxes.remove(box_name),
+                };
+            }
+        }
+        frames.push(FrameData { sprite, duration_ms, boxes: boxes.into_values().collect() });
+    }
+
+    let mut attachments = Vec::new();
+    for attachment in anim.and_then(|a| a.attachments.as_ref()).into_iter().flatten() {
+        let chain = attachment
+            .chain
+            .iter()
+            .map(|segment| {
+                builder.ids.get(segment).copied().ok_or_else(|| {
+                    format!("attachment '{}': sprite '{}' not found", attachment.name, segment)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        attachments.push(AttachPoint {
+            name: attachment.name.clone(),
+            anchor: attachment.anchor,
+            chain,
+            z_index: attachment.z_index(),
+        });
+    }
+
+    Ok(AnimationData { name: name.to_string(), frames, looping, attachments })
+}
+
+/// Palettes sorted by name, with colors that resolve to a fixed RGBA value.
+fn compile_palettes(catalog: &PreviewCatalog) -> Vec<PaletteData> {
+    let mut palettes: Vec<PaletteData> = catalog
+        .palettes()
+        .iter()
+        .map(|(name, palette)| {
+            let mut colors: Vec<(String, [u8; 4])> = palette
+                .colors
+                .iter()
+                .filter_map(|(token, color)| Some((token.clone(), parse_color(color).ok()?.0)))
+                .collect();
+            colors.sort();
+            PaletteData { name: name.clone(), colors }
+        })
+        .collect();
+    palettes.sort_by(|a, b| a.name.cmp(&b.name));
+    palettes
+}
+
+fn pack_sprites(sprites: &[SpriteData]) -> Vec<AtlasData> {
+    let inputs: Vec<SpriteInput> = sprites
+        .iter()
+        .filter_map(|s| {
+            let image = RgbaImage::from_raw(s.width, s.height, s.pixels.clone())?;
+            Some(SpriteInput::with_metadata(s.name.clone(), image, None))
+        })
+        .collect();
+    let ids: HashMap<&str, SpriteId> =
+        sprites.iter().enumerate().map(|(i, s)| (s.name.as_str(), SpriteId(i as u32))).collect();
+
+    pack_atlas(&inputs, &AtlasConfig::default(), "bundle")
+        .atlases
+        .into_iter()
+        .map(|(image, meta)| {
+            let mut placements: Vec<AtlasPlacement> = meta
+                .frames
+                .iter()
+                .map(|(name, frame)| AtlasPlacement {
+                    sprite: ids[name.as_str()],
+                    x: frame.x,
+                    y: frame.y,
+                })
+                .collect();
+            placements.sort_by_key(|p| p.sprite);
+            AtlasData { width: image.width(), height: image.height(), placements }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"
+{"type": "palette", "name": "hero", "colors": {"_": "#00000000", "a": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "step_a", "palette": "hero", "size": [2, 1], "regions": {"a": {"rect": [0, 0, 2, 1]}}, "metadata": {"origin": [1, 0], "boxes": {"hurt": {"x": 0, "y": 0, "w": 2, "h": 1}}}}
+{"type": "sprite", "name": "step_b", "palette": "hero", "size": [2, 1], "regions": {"b": {"rect": [0, 0, 2, 1]}}, "metadata": {"origin": [1, 0]}}
+{"type": "animation", "name": "walk", "frames": ["step_a", "step_b"], "duration": 80, "frame_metadata": [{}, {"boxes": {"hurt": null, "hit": {"x": 1, "y": 0, "w": 1, "h": 1}}}]}
+"##;
+
+    #[test]
+    fn test_compile_bundle() {
+        let catalog = PreviewCatalog::from_source(SOURCE);
+        let (pool, errors) = compile_bundle(&catalog);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let step_a = pool.sprite_by_name("step_a").unwrap();
+        assert_eq!((step_a.width, step_a.height), (2, 1));
+        assert_eq!(&step_a.pixels[..4], &[255, 0, 0, 255]);
+        assert_eq!(step_a.origin, [1, 0]);
+        assert_eq!(step_a.boxes[0].name, "hurt");
+
+        let walk = pool.animation("walk").unwrap();
+        assert_eq!(walk.frames.len(), 2);
+        assert_eq!(walk.frames[0].sprite, pool.sprite_id("step_a").unwrap());
+        assert_eq!(walk.frames[1].sprite, pool.sprite_id("step_b").unwrap());
+        assert_eq!(walk.frames[0].duration_ms, 80);
+        assert_eq!(walk.frames[0].boxes[0].name, "hurt");
+        assert_eq!(walk.frames[1].boxes.len(), 1);
+        assert_eq!(walk.frames[1].boxes[0].name, "hit");
+        // Frames matching sprites don't add sprites
+        assert_eq!(pool.sprites().len(), 2);
+
+        let hero = pool.palette("hero").unwrap();
+        assert!(hero.colors.contains(&("a".to_string(), [255, 0, 0, 255])));
+        assert!(pool.metadata("generator").unwrap().starts_with("pxl "));
+
+        let placed: usize = pool.atlases().iter().map(|a| a.placements.len()).sum();
+        assert_eq!(placed, 2);
+    }
+
+    #[test]
+    fn test_compile_bundle_round_trips() {
+        let (pool, _) = compile_bundle(&PreviewCatalog::from_source(SOURCE));
+        assert_eq!(SpritePool::from_bytes(&pool.to_bytes()).unwrap(), pool);
+    }
+
+    #[test]
+    fn test_compile_bundle_missing_chain_sprite() {
+        let source = format!(
+            "{}{}",
+            SOURCE,
+            r#"{"type": "animation", "name": "cape", "frames": ["step_a"], "attachments": [{"name": "tail", "anchor": [0, 0], "chain": ["nope"]}]}"#
+        );
+        let (pool, errors) = compile_bundle(&PreviewCatalog::from_source(&source));
+        assert!(pool.animation("cape").is_none());
+        assert_eq!(errors[0].0, "animation/cape");
+        assert!(errors[0].1.contains("nope"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Animate command implementation
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::idle::{breathe, BreatheOptions};
+use crate::models::TtpObject;
+use crate::parser::parse_stream;
+use crate::registry::{PaletteRegistry, SpriteRegistry};
+use crate::suggest::{format_suggestion, suggest};
+
+use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
+
+/// Animation generated by `pxl animate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnimatePreset {
+    /// Subtle idle loop: squash a row and darken slightly, then recover
+    Breathe,
+}
+
+/// Execute the animate command
+pub fn run_animate(
+    input: &Path,
+    sprite: &str,
+    preset: AnimatePreset,
+    options: &BreatheOptions,
+    output: Option<&Path>,
+) -> ExitCode {
+    let file = match File::open(input) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error: Cannot open input file '{}': {}", input.display(), e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+
+    let parsed = parse_stream(BufReader::new(file));
+    for warning in &parsed.warnings {
+        eprintln!("Warning: line {}: {}", warning.line, warning.message);
+    }
+    let mut palettes = PaletteRegistry::new();
+    let mut sprites = SpriteRegistry::new();
+    for obj in parsed.objects {
+        match obj {
+            TtpObject::Palette(p) => palettes.register(p),
+            TtpObject::Sprite(s) => sprites.register_sprite(s),
+            TtpObject::Variant(v) => sprites.register_variant(v),
+            _ => {}
+        }
+    }
+
+    if !sprites.contains(sprite) {
+        eprintln!("Error: No sprite named '{}' found in input", sprite);
+        let names: Vec<&str> = sprites.names().map(|s| s.as_str()).collect();
+        if let Some(suggestion) = format_suggestion(&suggest(sprite, &names, 3)) {
+            eprintln!("{}", suggestion);
+        }
+        return ExitCode::from(EXIT_ERROR);
+    }
+    let resolved = match sprites.resolve(sprite, &palettes, false) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            eprintln!("Error: sprite '{}': {}", sprite, e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    for warning in &resolved.warnings {
+        eprintln!("Warning: sprite '{}': {}", sprite, warning.message);
+    }
+
+    let objects = match preset {
+        AnimatePreset::Breathe => breathe(sprite, &resolved, options),
+    };
+    let objects = match objects {
+        Ok(objects) => objects,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+
+    let mut jsonl = String::new();
+    for obj in &objects {
+        let value = serde_json::to_value(obj).expect("object serialization");
+        jsonl.push_str(&value.to_string());
+        jsonl.push('\n');
+    }
+
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, &jsonl) {
+                eprintln!("Error: Failed to write '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+            eprintln!("Wrote {} objects to {}", objects.len(), path.display());
+        }
+        None => print!("{}", jsonl),
+    }
+    ExitCode::from(EXIT_SUCCESS)
+}
@@ -0,0 +1,45 @@
+//! CLI dispatch for the `pxl bridge` command.
+
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use crate::bridge::{pull, push, report, watch, BridgeError};
+use crate::config::schema::WatchConfig;
+use crate::draw::DrawError;
+
+use super::{EXIT_ERROR, EXIT_SUCCESS};
+
+/// Execute the bridge command
+pub fn run_bridge(input: &Path, sprite: &str, png: &Path, once: bool) -> ExitCode {
+    let result =
+        if once { sync_once(input, sprite, png) } else { watch_forever(input, sprite, png) };
+    match result {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(BridgeError::Source(DrawError::SpriteNotFound(name))) => {
+            eprintln!("Error: sprite '{}' not found in '{}'", name, input.display());
+            ExitCode::from(EXIT_ERROR)
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+/// Pull the PNG into the source if it exists, then export the sprite
+fn sync_once(input: &Path, sprite: &str, png: &Path) -> Result<(), BridgeError> {
+    if png.exists() {
+        let outcome = pull(input, sprite, png)?;
+        report(&outcome, png, input);
+    }
+    if push(input, sprite, png)? {
+        println!("Exported '{}' to {}", sprite, png.display());
+    }
+    Ok(())
+}
+
+fn watch_forever(input: &Path, sprite: &str, png: &Path) -> Result<(), BridgeError> {
+    let debounce = Duration::from_millis(WatchConfig::default().debounce_ms as u64);
+    watch(input, sprite, png, debounce)
+}
@@ -1,8 +1,12 @@
 //! Build command implementations (build, new, init)
 
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
+use crate::selection::Selection;
+
 use super::{EXIT_ERROR, EXIT_SUCCESS};
 
 /// Run the build command
@@ -13,6 +17,8 @@ pub fn run_build(
     dry_run: bool,
     force: bool,
     verbose: bool,
+    selection: Selection,
+    manifest_out: Option<&Path>,
 ) -> ExitCode {
     use crate::build::{BuildContext, BuildPipeline, IncrementalBuild, IncrementalStats};
     use crate::config::loader::{find_config, load_config, merge_cli_overrides, CliOverrides};
@@ -81,7 +87,8 @@ pub fn run_build(
         println!("  Output: {}", out_dir.display());
 
         // Use BuildPipeline in dry-run mode to discover targets
-        let context = BuildContext::new(config, project_root).with_verbose(verbose);
+        let context =
+            BuildContext::new(config, project_root).with_verbose(verbose).with_selection(selection);
         let pipeline = BuildPipeline::new(context).with_dry_run(true);
 
         match pipeline.build() {
@@ -101,7 +108,8 @@ pub fn run_build(
     // Watch mode using incremental build pipeline
     if watch {
         let watch_config = config.watch.clone();
-        let context = BuildContext::new(config, project_root).with_verbose(verbose);
+        let context =
+            BuildContext::new(config, project_root).with_verbose(verbose).with_selection(selection);
 
         println!("Starting watch mode...");
         if force {
@@ -117,6 +125,29 @@ pub fn run_build(
                 ExitCode::from(EXIT_ERROR)
             }
         }
+    } else if !selection.is_empty() {
+        // A slice always rebuilds, and leaves the incremental manifest alone
+        println!("Building selection (ignoring cache)...");
+
+        let context =
+            BuildContext::new(config, project_root).with_verbose(verbose).with_selection(selection);
+        match BuildPipeline::new(context.clone()).build() {
+            Ok(result) if result.is_success() => {
+                println!("{}", result.summary());
+                match manifest_out {
+                    Some(path) => write_artifact_manifest(&context, &result, None, path),
+                    None => ExitCode::from(EXIT_SUCCESS),
+                }
+            }
+            Ok(result) => {
+                eprintln!("{}", result.summary());
+                ExitCode::from(EXIT_ERROR)
+            }
+            Err(e) => {
+                eprintln!("Build error: {}", e);
+                ExitCode::from(EXIT_ERROR)
+            }
+        }
     } else {
         // Single build using IncrementalBuild
         if force {
@@ -126,7 +157,7 @@ pub fn run_build(
         }
 
         let context = BuildContext::new(config, project_root).with_verbose(verbose);
-        let mut incremental = IncrementalBuild::new(context).with_force(force);
+        let mut incremental = IncrementalBuild::new(context.clone()).with_force(force);
 
         match incremental.run() {
             Ok(result) => {
@@ -138,7 +169,15 @@ pub fn run_build(
                     } else {
                         println!("{}", result.summary());
                     }
-                    ExitCode::from(EXIT_SUCCESS)
+                    match manifest_out {
+                        Some(path) => write_artifact_manifest(
+                            &context,
+                            &result,
+                            Some(incremental.manifest()),
+                            path,
+                        ),
+                        None => ExitCode::from(EXIT_SUCCESS),
+                    }
                 } else {
                     eprintln!("{}", result.summary());
                     ExitCode::from(EXIT_ERROR)
@@ -152,6 +191,119 @@ pub fn run_build(
     }
 }
 
+/// Write the `--manifest-out` artifact manifest of a successful build.
+///
+/// Targets skipped as up to date take their outputs from the incremental
+/// manifest, so the artifact manifest always covers the whole build.
+fn write_artifact_manifest(
+    context: &crate::build::BuildContext,
+    result: &crate::build::BuildResult,
+    previous: Option<&crate::build::BuildManifest>,
+    path: &Path,
+) -> ExitCode {
+    use crate::build::{create_build_plan, ArtifactManifest, BuildStatus};
+
+    let plan = match create_build_plan(context) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("Error: Cannot plan artifact manifest: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    let mut outputs: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for target in &result.targets {
+        match (&target.status, previous.and_then(|m| m.get_target(&target.target_id))) {
+            (BuildStatus::Success, _) => {
+                outputs.insert(target.target_id.clone(), target.outputs.clone());
+            }
+            (BuildStatus::Skipped, Some(record)) => {
+                let files = record.outputs().iter().map(PathBuf::from).collect();
+                outputs.insert(target.target_id.clone(), files);
+            }
+            _ => {}
+        }
+    }
+
+    match ArtifactManifest::collect(context, &plan, &outputs).and_then(|m| m.save(path)) {
+        Ok(()) => {
+            println!("Wrote artifact manifest to {}", path.display());
+            ExitCode::from(EXIT_SUCCESS)
+        }
+        Err(e) => {
+            eprintln!("Error: Cannot write artifact manifest '{}': {}", path.display(), e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+/// Run the build command with `--bundle`: compile the project into a `.pxb` file
+pub fn run_build_bundle(
+    src: Option<&Path>,
+    bundle: &Path,
+    dry_run: bool,
+    verbose: bool,
+) -> ExitCode {
+    use crate::bundle::compile_bundle;
+    use crate::serve::{expand_sources, PreviewCatalog};
+
+    let source = match super::serve::default_source(src) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    if !source.exists() {
+        eprintln!("Error: Source directory not found: {}", source.display());
+        eprintln!("Create the directory or specify a different path with --src");
+        return ExitCode::from(EXIT_ERROR);
+    }
+
+    let files = expand_sources(std::slice::from_ref(&source));
+    if dry_run {
+        println!("Dry run - would bundle:");
+        println!("  Source: {} ({} files)", source.display(), files.len());
+        println!("  Output: {}", bundle.display());
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+
+    let catalog = PreviewCatalog::load(&files);
+    for warning in &catalog.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    let (pool, errors) = compile_bundle(&catalog);
+    for (key, error) in &errors {
+        eprintln!("Warning: {}: {}", key, error);
+    }
+    if verbose {
+        for sprite in pool.sprites() {
+            println!("  sprite {} ({}x{})", sprite.name, sprite.width, sprite.height);
+        }
+        for animation in pool.animations() {
+            println!("  animation {} ({} frames)", animation.name, animation.frames.len());
+        }
+    }
+
+    if let Some(parent) = bundle.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            eprintln!("Error: Cannot create '{}': {}", parent.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    }
+    if let Err(e) = pool.save(bundle) {
+        eprintln!("Error: Cannot write '{}': {}", bundle.display(), e);
+        return ExitCode::from(EXIT_ERROR);
+    }
+    println!(
+        "Bundled {} sprites, {} animations and {} palettes into {}",
+        pool.sprites().len(),
+        pool.animations().len(),
+        pool.palettes().len(),
+        bundle.display()
+    );
+    ExitCode::from(EXIT_SUCCESS)
+}
+
 /// Run the new command
 pub fn run_new(asset_type: &str, name: &str, palette: Option<&str>) -> ExitCode {
     use crate::scaffold::{new_animation, new_palette, new_sprite, ScaffoldError};
@@ -198,38 +350,62 @@ pub fn run_new(asset_type: &str, name: &str, palette: Option<&str>) -> ExitCode
 }
 
 /// Run the init command
-pub fn run_init(path: Option<&Path>, name: Option<&str>, preset: &str) -> ExitCode {
-    use crate::init::{init_project, InitError};
+pub fn run_init(
+    path: Option<&Path>,
+    name: Option<&str>,
+    preset: &str,
+    interactive: bool,
+) -> ExitCode {
+    use crate::init::{init_project, InitError, Preset};
 
-    // Determine project path
-    let project_path = match path {
-        Some(p) => p.to_path_buf(),
-        None => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
-    };
+    let (project_path, project_name, preset) = if interactive {
+        let stdin = std::io::stdin();
+        match prompt_init(&mut stdin.lock(), path, name, preset) {
+            Ok(answers) => answers,
+            Err(e) => {
+                eprintln!("Error: Failed to read input: {}", e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+    } else {
+        // Determine project path
+        let project_path = match path {
+            Some(p) => p.to_path_buf(),
+            None => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        };
 
-    // Determine project name
-    let project_name = name
-        .map(|n| n.to_string())
-        .or_else(|| project_path.file_name().map(|n| n.to_string_lossy().into_owned()))
-        .unwrap_or_else(|| "my-project".to_string());
+        // Determine project name
+        let project_name =
+            name.map(|n| n.to_string()).unwrap_or_else(|| default_project_name(&project_path));
+
+        (project_path, project_name, preset.to_string())
+    };
 
     // Run initialization
-    match init_project(&project_path, &project_name, preset) {
+    match init_project(&project_path, &project_name, &preset) {
         Ok(()) => {
-            println!("Created pixelsrc project '{}' at {}", project_name, project_path.display());
+            let preset = Preset::from_str(&preset).unwrap_or(Preset::Minimal);
+            println!(
+                "Created pixelsrc project '{}' at {} ({} preset)",
+                project_name,
+                project_path.display(),
+                preset.name()
+            );
             println!();
-            println!("Project structure:");
+            println!("Project files:");
             println!("  {}/", project_path.display());
-            println!("  ├── pxl.toml");
-            println!("  ├── .gitignore");
-            println!("  ├── src/pxl/");
-            println!("  │   ├── palettes/main.pxl");
-            println!("  │   └── sprites/example.pxl");
-            println!("  └── build/");
+            let files = project_files(&project_path);
+            for (i, file) in files.iter().enumerate() {
+                let branch = if i + 1 == files.len() { "└──" } else { "├──" };
+                println!("  {} {}", branch, file);
+            }
             println!();
             println!("Next steps:");
             println!("  cd {}", project_path.display());
-            println!("  pxl render src/pxl/sprites/example.pxl");
+            println!("  pxl render {}", preset.example_file());
+            if !matches!(preset, Preset::Minimal | Preset::Artist) {
+                println!("  just watch    # rebuild on save");
+            }
             ExitCode::from(EXIT_SUCCESS)
         }
         Err(InitError::DirectoryExists(dir)) => {
@@ -239,7 +415,8 @@ pub fn run_init(path: Option<&Path>, name: Option<&str>, preset: &str) -> ExitCo
         }
         Err(InitError::UnknownPreset(preset)) => {
             eprintln!("Error: Unknown preset '{}'", preset);
-            eprintln!("Available presets: minimal, artist, animator, game");
+            let names: Vec<&str> = Preset::ALL.iter().map(|p| p.name()).collect();
+            eprintln!("Available presets: {}", names.join(", "));
             ExitCode::from(EXIT_ERROR)
         }
         Err(e) => {
@@ -248,3 +425,81 @@ pub fn run_init(path: Option<&Path>, name: Option<&str>, preset: &str) -> ExitCo
         }
     }
 }
+
+/// Project name derived from the directory name.
+fn default_project_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "my-project".to_string())
+}
+
+/// Ask for the project directory, name, and preset, skipping answers given as flags.
+///
+/// Empty answers (and end of input) take the default shown in brackets.
+fn prompt_init(
+    input: &mut impl BufRead,
+    path: Option<&Path>,
+    name: Option<&str>,
+    preset: &str,
+) -> std::io::Result<(PathBuf, String, String)> {
+    use crate::init::Preset;
+
+    let project_path = match path {
+        Some(p) => p.to_path_buf(),
+        None => PathBuf::from(prompt(input, "Project directory", ".")?),
+    };
+    let project_path = if project_path == Path::new(".") {
+        std::env::current_dir().unwrap_or(project_path)
+    } else {
+        project_path
+    };
+
+    let project_name = match name {
+        Some(n) => n.to_string(),
+        None => prompt(input, "Project name", &default_project_name(&project_path))?,
+    };
+
+    println!("Presets:");
+    for (i, p) in Preset::ALL.iter().enumerate() {
+        println!("  {}) {:<9} {}", i + 1, p.name(), p.description());
+    }
+    let default = Preset::from_str(preset).unwrap_or(Preset::Minimal);
+    let preset = loop {
+        let answer = prompt(input, "Preset", default.name())?;
+        let chosen = match answer.parse::<usize>() {
+            Ok(n) => n.checked_sub(1).and_then(|i| Preset::ALL.get(i).copied()),
+            Err(_) => Preset::from_str(&answer),
+        };
+        match chosen {
+            Some(p) => break p,
+            None => println!("Unknown preset '{}', enter a number or name from the list", answer),
+        }
+    };
+
+    Ok((project_path, project_name, preset.name().to_string()))
+}
+
+/// Print a prompt and read one answer, falling back to `default`.
+fn prompt(input: &mut impl BufRead, label: &str, default: &str) -> std::io::Result<String> {
+    print!("{} [{}]: ", label, default);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let answer = line.trim();
+    Ok(if answer.is_empty() { default.to_string() } else { answer.to_string() })
+}
+
+/// Source files created in a new project, relative to its root.
+fn project_files(root: &Path) -> Vec<String> {
+    let mut files = vec!["pxl.toml".to_string(), "justfile".to_string()];
+    let pattern = root.join("src/**/*.pxl");
+    if let Ok(paths) = glob::glob(&pattern.to_string_lossy()) {
+        let mut sources: Vec<String> = paths
+            .flatten()
+            .filter_map(|p| p.strip_prefix(root).ok().map(|p| p.display().to_string()))
+            .collect();
+        sources.sort();
+        files.extend(sources);
+    }
+    files
+}
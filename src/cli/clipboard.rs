@@ -0,0 +1,137 @@
+//! Copy and paste command implementations
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use image::RgbaImage;
+
+use crate::clipboard::{copy_image, paste_png, ClipboardImage};
+use crate::gif::{encode_gif_with_durations, GifOptions};
+use crate::output::{encode_png, scale_image};
+use crate::serve::PreviewCatalog;
+use crate::suggest::{format_suggestion, suggest};
+
+use super::import::{import_options, write_import_result};
+use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
+
+/// Execute the copy command
+pub fn run_copy(input: &Path, name: &str, scale: u8) -> ExitCode {
+    if scale == 0 {
+        eprintln!("Error: --scale must be at least 1");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+    if !input.exists() {
+        eprintln!("Error: Cannot open input file '{}'", input.display());
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
+    let catalog = PreviewCatalog::load(&[input.to_path_buf()]);
+    for warning in &catalog.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let png = |result: Result<RgbaImage, String>| {
+        result.and_then(|image| encode_png(&scale_image(image, scale)).map_err(|e| e.to_string()))
+    };
+
+    // Sprites and compositions copy as PNG, animations as GIF
+    let (bytes, kind) = if catalog.sprite_names().contains(&name) {
+        (png(catalog.render_sprite(name)), ClipboardImage::Png)
+    } else if catalog.composition_names().contains(&name) {
+        (png(catalog.render_composition(name)), ClipboardImage::Png)
+    } else if catalog.animation_names().contains(&name) {
+        let gif = catalog.render_animation(name).and_then(|(frames, durations, loops)| {
+            let frames: Vec<RgbaImage> =
+                frames.into_iter().map(|f| scale_image(f, scale)).collect();
+            let mut bytes = Vec::new();
+            encode_gif_with_durations(
+                &frames,
+                &durations,
+                loops,
+                &GifOptions::default(),
+                &mut bytes,
+            )
+            .map(|()| bytes)
+            .map_err(|e| e.to_string())
+        });
+        (gif, ClipboardImage::Gif)
+    } else {
+        eprintln!("Error: No sprite, composition, or animation named '{}' found in input", name);
+        let mut names = catalog.sprite_names();
+        names.extend(catalog.composition_names());
+        names.extend(catalog.animation_names());
+        if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+            eprintln!("{}", suggestion);
+        }
+        return ExitCode::from(EXIT_ERROR);
+    };
+
+    let bytes = match bytes {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    match copy_image(&bytes, kind) {
+        Ok(()) => {
+            let format = if kind == ClipboardImage::Gif { "GIF" } else { "PNG" };
+            println!("Copied {} to clipboard as {}", name, format);
+            ExitCode::from(EXIT_SUCCESS)
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+/// Execute the paste command
+pub fn run_paste(
+    output: Option<&Path>,
+    import: bool,
+    name: &str,
+    max_colors: usize,
+    analyze: bool,
+) -> ExitCode {
+    if import && !(2..=256).contains(&max_colors) {
+        eprintln!("Error: --max-colors must be between 2 and 256");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
+    let bytes = match paste_png() {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    if !import {
+        let output_path =
+            output.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(format!("{}.png", name)));
+        if let Err(e) = std::fs::write(&output_path, &bytes) {
+            eprintln!("Error: Failed to write '{}': {}", output_path.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        println!("Saved: {}", output_path.display());
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+
+    let options = import_options(analyze, 0.7, false, true);
+    let result = match crate::import::import_from_image_data(&bytes, name, max_colors, &options) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    // Same extension rule as `pxl import`
+    let output_path = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        let ext = if analyze { "pxl" } else { "jsonl" };
+        PathBuf::from(format!("{}.{}", name, ext))
+    });
+    write_import_result(&result, &output_path, analyze, false, true)
+}
@@ -0,0 +1,56 @@
+//! Daemon command implementation
+
+use std::process::ExitCode;
+
+use super::{EXIT_ERROR, EXIT_SUCCESS};
+
+/// Run the daemon command
+pub fn run_daemon(host: &str, port: u16) -> ExitCode {
+    use crate::daemon::Daemon;
+
+    // IPv6 hosts such as `::1` need brackets before the port
+    let addr = if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    // Outputs are confined to the enclosing project (the directory holding
+    // pxl.toml), or to the working directory outside a project
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    let root = crate::config::loader::find_config_from(cwd.clone())
+        .and_then(|config| config.parent().map(|dir| dir.to_path_buf()))
+        .unwrap_or(cwd);
+    let daemon = match Daemon::bind(&addr, &root) {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    match daemon.local_addr() {
+        Ok(addr) => {
+            println!("Listening on {}", addr);
+            println!("Token {}", daemon.token());
+            println!("Writing under {}", daemon.root().display());
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    }
+
+    match daemon.run() {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
@@ -56,7 +56,7 @@ fn parse_rect_arg(arg: &str) -> Result<DrawOp, String> {
 }
 
 /// Parse `x,y` coordinate string.
-fn parse_coords(s: &str) -> Result<(u32, u32), String> {
+pub(super) fn parse_coords(s: &str) -> Result<(u32, u32), String> {
     let (x_str, y_str) =
         s.split_once(',').ok_or_else(|| format!("invalid coordinates '{}', expected x,y", s))?;
 
@@ -0,0 +1,67 @@
+//! Expand command implementation
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::parser::parse_stream;
+use crate::random_set::RandomSetExpander;
+use crate::suggest::{format_suggestion, suggest};
+
+use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
+
+/// Execute the expand command
+pub fn run_expand(input: &Path, set: &str, count: usize, output: Option<&Path>) -> ExitCode {
+    let file = match File::open(input) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error: Cannot open input file '{}': {}", input.display(), e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+
+    let parsed = parse_stream(BufReader::new(file));
+    for warning in &parsed.warnings {
+        eprintln!("Warning: line {}: {}", warning.line, warning.message);
+    }
+    let expander = RandomSetExpander::new(&parsed.objects);
+
+    if expander.set_names().all(|name| name != set) {
+        eprintln!("Error: No random_set named '{}' found in input", set);
+        let names: Vec<&str> = expander.set_names().map(|s| s.as_str()).collect();
+        if let Some(suggestion) = format_suggestion(&suggest(set, &names, 3)) {
+            eprintln!("{}", suggestion);
+        }
+        return ExitCode::from(EXIT_ERROR);
+    }
+    let expansion = match expander.expand(set, count) {
+        Ok(expansion) => expansion,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    for warning in &expansion.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let mut jsonl = String::new();
+    for obj in &expansion.objects {
+        let value = serde_json::to_value(obj).expect("object serialization");
+        jsonl.push_str(&value.to_string());
+        jsonl.push('\n');
+    }
+
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, &jsonl) {
+                eprintln!("Error: Failed to write '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+            eprintln!("Wrote {} objects to {}", expansion.objects.len(), path.display());
+        }
+        None => print!("{}", jsonl),
+    }
+    ExitCode::from(EXIT_SUCCESS)
+}
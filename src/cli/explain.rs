@@ -8,15 +8,34 @@ use std::process::ExitCode;
 
 use crate::config::loader::find_config_from;
 use crate::diff::{diff_files, format_diff};
-use crate::explain::{explain_object, format_explanation, resolve_palette_colors, Explanation};
+use crate::explain::{
+    describe_sprite, explain_object, explain_pixel, format_explanation, format_pixel_explanation,
+    format_sprite_description, resolve_palette_colors, Explanation, PixelExplanation,
+    SpriteDescription,
+};
 use crate::models::TtpObject;
 use crate::parser::parse_stream;
+use crate::registry::{PaletteRegistry, SpriteRegistry};
 use crate::suggest::{format_suggestion, suggest, Suggester, SuggestionFix, SuggestionType};
 
 use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
 
 /// Execute the explain command
-pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> ExitCode {
+pub fn run_explain(
+    input: &PathBuf,
+    name_filter: Option<&str>,
+    json: bool,
+    describe: bool,
+    pixel: Option<&str>,
+) -> ExitCode {
+    let pixel = match pixel.map(super::draw::parse_coords).transpose() {
+        Ok(pixel) => pixel,
+        Err(e) => {
+            eprintln!("Error: --pixel: {}", e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+
     // Open input file
     let file = match File::open(input) {
         Ok(f) => f,
@@ -35,6 +54,10 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
         return ExitCode::from(EXIT_ERROR);
     }
 
+    if let (Some((x, y)), Some(name)) = (pixel, name_filter) {
+        return run_explain_pixel(&parse_result.objects, name, [x, y], json);
+    }
+
     // Build palette lookup for color resolution
     // Map palette name -> (token -> color)
     let mut known_palettes: HashMap<String, HashMap<String, String>> = HashMap::new();
@@ -46,6 +69,7 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
 
     // Explain each object
     let mut explanations: Vec<Explanation> = Vec::new();
+    let mut descriptions: Vec<Option<SpriteDescription>> = Vec::new();
 
     for obj in &parse_result.objects {
         let obj_name = match obj {
@@ -53,11 +77,15 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
             TtpObject::Sprite(s) => &s.name,
             TtpObject::Composition(c) => &c.name,
             TtpObject::Animation(a) => &a.name,
+            TtpObject::Sequence(s) => &s.name,
             TtpObject::Variant(v) => &v.name,
             TtpObject::Particle(p) => &p.name,
             TtpObject::Transform(t) => &t.name,
             TtpObject::StateRules(sr) => &sr.name,
             TtpObject::Import(i) => &i.from,
+            TtpObject::Theme(t) => &t.name,
+            TtpObject::Stamp(s) => &s.name,
+            TtpObject::RandomSet(r) => &r.name,
         };
 
         // Apply name filter if specified
@@ -77,6 +105,14 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
 
         let exp = explain_object(obj, resolved_colors.as_ref());
         explanations.push(exp);
+
+        // Rendered description (sprites only)
+        descriptions.push(match obj {
+            TtpObject::Sprite(sprite) if describe => {
+                Some(describe_sprite(sprite, &resolved_colors.clone().unwrap_or_default()))
+            }
+            _ => None,
+        });
     }
 
     if explanations.is_empty() {
@@ -90,11 +126,15 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
                     TtpObject::Sprite(s) => s.name.clone(),
                     TtpObject::Composition(c) => c.name.clone(),
                     TtpObject::Animation(a) => a.name.clone(),
+                    TtpObject::Sequence(s) => s.name.clone(),
                     TtpObject::Variant(v) => v.name.clone(),
                     TtpObject::Particle(p) => p.name.clone(),
                     TtpObject::Transform(t) => t.name.clone(),
                     TtpObject::StateRules(sr) => sr.name.clone(),
                     TtpObject::Import(i) => i.from.clone(),
+                    TtpObject::Theme(t) => t.name.clone(),
+                    TtpObject::Stamp(s) => s.name.clone(),
+                    TtpObject::RandomSet(r) => r.name.clone(),
                 })
                 .collect();
             let name_refs: Vec<&str> = all_names.iter().map(|s| s.as_str()).collect();
@@ -108,7 +148,7 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
     // Output
     if json {
         // JSON output
-        let json_explanations: Vec<serde_json::Value> = explanations
+        let mut json_explanations: Vec<serde_json::Value> = explanations
             .iter()
             .map(|exp| match exp {
                 Explanation::Sprite(s) => serde_json::json!({
@@ -149,6 +189,16 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
                     "duration_ms": a.duration_ms,
                     "loops": a.loops,
                 }),
+                Explanation::Sequence(s) => serde_json::json!({
+                    "type": "sequence",
+                    "name": s.name,
+                    "steps": s.steps.iter().map(|(animation, repeat, crossfade)| serde_json::json!({
+                        "animation": animation,
+                        "repeat": repeat,
+                        "crossfade_ms": crossfade,
+                    })).collect::<Vec<_>>(),
+                    "loops": s.loops,
+                }),
                 Explanation::Composition(c) => serde_json::json!({
                     "type": "composition",
                     "name": c.name,
@@ -200,9 +250,37 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
                     "alias": i.alias,
                     "imported_types": i.imported_types,
                 }),
+                Explanation::Theme(t) => serde_json::json!({
+                    "type": "theme",
+                    "name": t.name,
+                    "palettes": t.swaps.iter().cloned().collect::<std::collections::BTreeMap<_, _>>(),
+                }),
+                Explanation::Stamp(s) => serde_json::json!({
+                    "type": "stamp",
+                    "name": s.name,
+                    "width": s.width,
+                    "height": s.height,
+                    "pixel_count": s.pixel_count,
+                    "tokens": s.tokens,
+                }),
+                Explanation::RandomSet(r) => serde_json::json!({
+                    "type": "random_set",
+                    "name": r.name,
+                    "choices": r.choices.iter().map(|(name, share)| serde_json::json!({
+                        "name": name,
+                        "share": share,
+                    })).collect::<Vec<_>>(),
+                    "regions": r.tokens.iter().cloned().collect::<std::collections::BTreeMap<_, _>>(),
+                }),
             })
             .collect();
 
+        for (value, desc) in json_explanations.iter_mut().zip(&descriptions) {
+            if let Some(desc) = desc {
+                value["description"] = description_json(desc);
+            }
+        }
+
         let output = if json_explanations.len() == 1 {
             serde_json::to_string_pretty(&json_explanations[0]).expect("JSON value serialization")
         } else {
@@ -217,12 +295,107 @@ pub fn run_explain(input: &PathBuf, name_filter: Option<&str>, json: bool) -> Ex
                 println!();
             }
             print!("{}", format_explanation(exp));
+            if let Some(desc) = &descriptions[i] {
+                println!();
+                println!("DESCRIPTION");
+                println!("-----------");
+                print!("{}", format_sprite_description(desc));
+            }
         }
     }
 
     ExitCode::from(EXIT_SUCCESS)
 }
 
+/// Explain one pixel of a sprite or variant
+fn run_explain_pixel(objects: &[TtpObject], name: &str, pixel: [u32; 2], json: bool) -> ExitCode {
+    let mut palettes = PaletteRegistry::new();
+    let mut sprites = SpriteRegistry::new();
+    for obj in objects {
+        match obj {
+            TtpObject::Palette(p) => palettes.register(p.clone()),
+            TtpObject::Sprite(s) => sprites.register_sprite(s.clone()),
+            TtpObject::Variant(v) => sprites.register_variant(v.clone()),
+            _ => {}
+        }
+    }
+
+    let exp = match explain_pixel(name, pixel, &sprites, &palettes) {
+        Ok(exp) => exp,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            if !sprites.contains(name) {
+                let names: Vec<&str> = sprites.names().map(|s| s.as_str()).collect();
+                if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                    eprintln!("{}", suggestion);
+                }
+            }
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&pixel_json(&exp)).expect("JSON value serialization")
+        );
+    } else {
+        print!("{}", format_pixel_explanation(&exp));
+    }
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+/// Convert a pixel explanation to JSON
+fn pixel_json(exp: &PixelExplanation) -> serde_json::Value {
+    serde_json::json!({
+        "type": "pixel",
+        "sprite": exp.sprite,
+        "x": exp.x,
+        "y": exp.y,
+        "color": exp.color,
+        "color_name": exp.color_name,
+        "source": exp.source,
+        "layer": exp.layer,
+        "token": exp.token,
+        "token_color": exp.token_color,
+        "palette": exp.palette,
+        "transforms": exp.transforms.iter().map(|step| serde_json::json!({
+            "transform": step.transform,
+            "effect": step.effect,
+            "from": step.from,
+            "to": step.to,
+        })).collect::<Vec<_>>(),
+        "note": exp.note,
+        "fallback": exp.fallback,
+    })
+}
+
+/// Convert a sprite description to JSON
+fn description_json(desc: &SpriteDescription) -> serde_json::Value {
+    serde_json::json!({
+        "width": desc.width,
+        "height": desc.height,
+        "visible_pixels": desc.visible_pixels,
+        "coverage": desc.coverage,
+        "content_bounds": desc.content_bounds,
+        "symmetry": desc.symmetry,
+        "summary": format_sprite_description(desc).lines().take_while(|l| !l.is_empty()).collect::<Vec<_>>().join(" "),
+        "regions": desc.regions.iter().map(|r| serde_json::json!({
+            "tokens": r.tokens,
+            "color": r.color,
+            "color_name": r.color_name,
+            "role": r.role,
+            "role_inferred": r.role_inferred,
+            "pixels": r.pixels,
+            "percentage": r.percentage,
+            "bounds": r.bounds,
+            "shape": r.shape,
+            "shape_confidence": r.shape_confidence,
+            "position": r.position,
+        })).collect::<Vec<_>>(),
+    })
+}
+
 /// Execute the diff command
 pub fn run_diff(file_a: &PathBuf, file_b: &PathBuf, sprite: Option<&str>, json: bool) -> ExitCode {
     // Get display names for the files
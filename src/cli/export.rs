@@ -0,0 +1,340 @@
+//! Export command implementation (emoji, ANSI, custom emoji, slice and video output)
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use image::RgbaImage;
+
+use crate::emoji::{render_custom_emoji, render_emoji_art};
+use crate::locale::apply_locale;
+use crate::models::{Sprite, TtpObject};
+use crate::output::{generate_output_path, input_stem, is_stdio, open_input, save_png_with_text};
+use crate::parser::parse_stream;
+use crate::provenance::Provenance;
+use crate::registry::PaletteRegistry;
+use crate::selection::Selection;
+use crate::suggest::{format_suggestion, suggest};
+use crate::terminal::render_image_ansi;
+use crate::warning::{SourceMap, Warnings};
+#[cfg(feature = "video")]
+use crate::{
+    serve::PreviewCatalog,
+    video::{encode_video, VideoFormat, VideoOptions},
+};
+
+use super::render::{palette_name, render_sprite_image};
+use super::{print_saved, EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
+
+/// Output format for the export command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Grid of colored emoji squares, one per pixel
+    Emoji,
+    /// Unicode half-blocks with 24-bit ANSI colors, two pixels per cell
+    Ansi,
+    /// 128x128 padded PNG for Slack/Discord custom emoji
+    Discord,
+    /// One PNG per named slice of the sprite's metadata
+    Slices,
+    /// H.264 MP4 video of an animation (via ffmpeg)
+    #[cfg(feature = "video")]
+    Mp4,
+    /// VP9 WebM video of an animation, with transparency (via ffmpeg)
+    #[cfg(feature = "video")]
+    Webm,
+}
+
+impl ExportFormat {
+    /// Video format this export writes, if any
+    #[cfg(feature = "video")]
+    fn video(self) -> Option<VideoFormat> {
+        match self {
+            ExportFormat::Mp4 => Some(VideoFormat::Mp4),
+            ExportFormat::Webm => Some(VideoFormat::Webm),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(feature = "video"))]
+    fn video(self) -> Option<std::convert::Infallible> {
+        None
+    }
+}
+
+/// Execute the export command
+#[allow(clippy::too_many_arguments)]
+pub fn run_export(
+    input: &Path,
+    format: ExportFormat,
+    sprite_filter: Option<&str>,
+    output: Option<&Path>,
+    padding: u32,
+    locale: Option<&str>,
+    animation: Option<&str>,
+    fps: Option<u32>,
+    scale: Option<u8>,
+    selection: &Selection,
+) -> ExitCode {
+    match format.video() {
+        #[cfg(feature = "video")]
+        Some(video) => {
+            let options =
+                VideoOptions { format: video, fps: fps.unwrap_or(30), scale: scale.unwrap_or(1) };
+            return run_video_export(input, animation, output, &options, selection);
+        }
+        #[cfg(not(feature = "video"))]
+        Some(never) => match never {},
+        None if animation.is_some() || fps.is_some() || scale.is_some() => {
+            eprintln!("Error: --animation, --fps and --scale only apply to video formats");
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+        None => {}
+    }
+
+    let reader = match open_input(input) {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("Error: Cannot open input file '{}': {}", input.display(), e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+
+    let parse_result = parse_stream(reader);
+    let file = (!is_stdio(input)).then_some(input);
+    let mut all_warnings = Warnings::new(SourceMap::new(file, &parse_result));
+    all_warnings.extend(parse_result.warnings.iter().cloned());
+    let mut objects = parse_result.objects;
+    if let Some(locale) = locale {
+        all_warnings.extend(apply_locale(&mut objects, locale));
+    }
+
+    // Sprites in file order, so the default is the first one defined
+    let mut sprites: Vec<Sprite> = Vec::new();
+    let mut registry = PaletteRegistry::new();
+    for obj in objects {
+        match obj {
+            TtpObject::Palette(palette) => registry.register(palette),
+            TtpObject::Sprite(sprite) => {
+                sprites.retain(|s| s.name != sprite.name);
+                sprites.push(sprite);
+            }
+            _ => {}
+        }
+    }
+
+    let sprite = match sprite_filter {
+        Some(name) => match sprites.iter().find(|s| s.name == name) {
+            Some(sprite) => sprite,
+            None => {
+                eprintln!("Error: No sprite named '{}' found in input", name);
+                let names: Vec<&str> = sprites.iter().map(|s| s.name.as_str()).collect();
+                if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                    eprintln!("{}", suggestion);
+                }
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+        None => match sprites.iter().find(|s| selection.includes("sprite", &s.name, &s.labels)) {
+            Some(sprite) => sprite,
+            None if !selection.is_empty() && !sprites.is_empty() => {
+                eprintln!("Error: No sprites match --only/--exclude");
+                return ExitCode::from(EXIT_ERROR);
+            }
+            None => {
+                eprintln!("Error: No sprites found in input file");
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+    };
+
+    let input_dir = input.parent().unwrap_or(Path::new("."));
+    let mut include_visited: HashSet<PathBuf> = HashSet::new();
+    let image = match render_sprite_image(
+        sprite,
+        &registry,
+        input_dir,
+        &mut include_visited,
+        &mut all_warnings,
+        false,
+    ) {
+        Ok(image) => image,
+        Err(code) => return code,
+    };
+
+    let text = match format {
+        ExportFormat::Emoji => render_emoji_art(&image),
+        ExportFormat::Ansi => render_image_ansi(&image),
+        #[cfg(feature = "video")]
+        ExportFormat::Mp4 | ExportFormat::Webm => unreachable!("video exports return early"),
+        ExportFormat::Slices => {
+            return export_slices(input, sprite, &image, output, &all_warnings);
+        }
+        ExportFormat::Discord => {
+            // Default: {input}_{sprite}_emoji.png next to the input
+            let output_path = output.map(Path::to_path_buf).unwrap_or_else(|| {
+                input_dir.join(format!("{}_{}_emoji.png", input_stem(input), sprite.name))
+            });
+            let emoji = render_custom_emoji(&image, padding);
+            let text = Provenance::new(input, &sprite.name, sprite)
+                .with_palette(palette_name(sprite))
+                .entries();
+            if let Err(e) = save_png_with_text(&emoji, &output_path, &text) {
+                eprintln!("Error: Failed to save '{}': {}", output_path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+            print_saved(&output_path);
+            print_warnings(&all_warnings);
+            return ExitCode::from(EXIT_SUCCESS);
+        }
+    };
+
+    // Text formats go to stdout unless an output file is given
+    match output.filter(|path| !is_stdio(path)) {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, &text) {
+                eprintln!("Error: Failed to write '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+            println!("Saved: {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+
+    print_warnings(&all_warnings);
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+/// Save each named slice of a rendered sprite as its own PNG.
+///
+/// Files are named like `pxl render` output for several objects, with the
+/// slice name in place of the sprite name (`-o dir/` gives `dir/{slice}.png`).
+/// Slices are clipped to the sprite.
+fn export_slices(
+    input: &Path,
+    sprite: &Sprite,
+    image: &RgbaImage,
+    output: Option<&Path>,
+    all_warnings: &Warnings,
+) -> ExitCode {
+    let slices = sprite.metadata.as_ref().and_then(|m| m.slices.as_ref());
+    let Some(slices) = slices.filter(|slices| !slices.is_empty()) else {
+        eprintln!("Error: sprite '{}' has no slices in its metadata", sprite.name);
+        return ExitCode::from(EXIT_ERROR);
+    };
+    if output.is_some_and(is_stdio) {
+        eprintln!("Error: slices are written to files, not stdout");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
+    let mut names: Vec<&String> = slices.keys().collect();
+    names.sort();
+    let mut warnings = Vec::new();
+    for name in names {
+        let slice = &slices[name];
+        let x0 = slice.x.clamp(0, image.width() as i32) as u32;
+        let y0 = slice.y.clamp(0, image.height() as i32) as u32;
+        let x1 = (slice.x + slice.w as i32).clamp(0, image.width() as i32) as u32;
+        let y1 = (slice.y + slice.h as i32).clamp(0, image.height() as i32) as u32;
+        if x1 <= x0 || y1 <= y0 {
+            warnings
+                .push(format!("sprite '{}': slice '{}' is outside the sprite", sprite.name, name));
+            continue;
+        }
+        if (x1 - x0, y1 - y0) != (slice.w, slice.h) {
+            warnings.push(format!(
+                "sprite '{}': slice '{}' extends outside the sprite and was clipped",
+                sprite.name, name
+            ));
+        }
+
+        let part = image::imageops::crop_imm(image, x0, y0, x1 - x0, y1 - y0).to_image();
+        let output_path = generate_output_path(input, name, output, false);
+        let text = Provenance::new(input, &sprite.name, sprite)
+            .with_palette(palette_name(sprite))
+            .entries();
+        if let Err(e) = save_png_with_text(&part, &output_path, &text) {
+            eprintln!("Error: Failed to save '{}': {}", output_path.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        print_saved(&output_path);
+    }
+
+    print_warnings(all_warnings);
+    print_warnings(&warnings);
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+/// Export an animation as MP4 or WebM through ffmpeg
+#[cfg(feature = "video")]
+fn run_video_export(
+    input: &Path,
+    animation: Option<&str>,
+    output: Option<&Path>,
+    options: &VideoOptions,
+    selection: &Selection,
+) -> ExitCode {
+    if is_stdio(input) || output.is_some_and(is_stdio) {
+        eprintln!("Error: Video export needs an input file and an output file, not stdin/stdout");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+    if options.fps == 0 {
+        eprintln!("Error: --fps must be at least 1");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+    if !input.exists() {
+        eprintln!("Error: Cannot open input file '{}'", input.display());
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
+    let catalog = PreviewCatalog::load(&[input.to_path_buf()]);
+    print_warnings(&catalog.warnings);
+    let names = catalog.animation_names();
+    let name = match animation {
+        Some(name) if names.contains(&name) => name,
+        Some(name) => {
+            eprintln!("Error: No animation named '{}' found in input", name);
+            if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                eprintln!("{}", suggestion);
+            }
+            return ExitCode::from(EXIT_ERROR);
+        }
+        None => match catalog.select_animations(selection).first() {
+            Some(name) => name,
+            None if !selection.is_empty() && !names.is_empty() => {
+                eprintln!("Error: No animations match --only/--exclude");
+                return ExitCode::from(EXIT_ERROR);
+            }
+            None => {
+                eprintln!("Error: No animations found in input file");
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+    };
+
+    let (frames, durations) = match catalog.render_animation(name) {
+        Ok((frames, durations, _)) => (frames, durations),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    // Default: {input}_{animation}.{mp4,webm} next to the input
+    let output_path = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        let file = format!("{}_{}.{}", input_stem(input), name, options.format.extension());
+        input.parent().unwrap_or(Path::new(".")).join(file)
+    });
+    if let Err(e) = encode_video(&frames, &durations, options, &output_path) {
+        eprintln!("Error: Failed to export '{}': {}", output_path.display(), e);
+        return ExitCode::from(EXIT_ERROR);
+    }
+    print_saved(&output_path);
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+fn print_warnings<'a, W: std::fmt::Display + 'a>(warnings: impl IntoIterator<Item = &'a W>) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
@@ -15,6 +15,7 @@ pub fn run_import(
     confidence: f64,
     hints: bool,
     shapes: bool,
+    sheet: Option<(crate::import::SheetSlicing, bool)>,
 ) -> ExitCode {
     // Validate max_colors
     if !(2..=256).contains(&max_colors) {
@@ -34,16 +35,85 @@ pub fn run_import(
         .unwrap_or_else(|| input.file_stem().unwrap_or_default().to_string_lossy().to_string());
 
     // Import the PNG with analysis options
-    let options = crate::import::ImportOptions {
-        analyze,
-        confidence_threshold: confidence,
-        hints,
-        extract_shapes: shapes,
-        half_sprite: false, // TODO: Add CLI flag when needed
-        dither_handling: crate::import::DitherHandling::Keep, // TODO: Add CLI flag when needed
-        detect_upscale: analyze, // Enable upscale detection when analysis is on
-        detect_outlines: analyze, // Enable outline detection when analysis is on
-    };
+    let options = import_options(analyze, confidence, hints, shapes);
+
+    // Generate output path
+    let output_path = output.map(|p| p.to_path_buf()).unwrap_or_else(|| {
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        // Use .pxl extension if analysis is enabled, otherwise .jsonl
+        let ext = if analyze { "pxl" } else { "jsonl" };
+        input.parent().unwrap_or(std::path::Path::new(".")).join(format!("{}.{}", stem, ext))
+    });
+
+    // Sheets become one sprite per frame plus an animation
+    if let Some((slicing, dedupe)) = sheet {
+        if analyze || hints {
+            eprintln!("Error: --analyze and --hints are not supported with --sheet");
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+        let result = match crate::import::import_sheet(
+            input, &name, max_colors, slicing, dedupe, &options,
+        ) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        if let Err(e) = std::fs::write(&output_path, result.to_jsonl()) {
+            eprintln!("Error: Failed to write '{}': {}", output_path.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        println!(
+            "Imported: {} ({} frames, {} sprites, {} colors)",
+            output_path.display(),
+            result.sequence.len(),
+            result.frames.len(),
+            result.palette.len()
+        );
+        for frame in &result.frames {
+            println!(
+                "  {} ({}x{} at {},{})",
+                frame.result.name, frame.result.width, frame.result.height, frame.x, frame.y
+            );
+        }
+        for warning in &result.warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+
+    // Layered files become one sprite per layer plus a composition
+    if crate::import::is_layered_path(input) {
+        let limits = super::serve::project_limits(input);
+        let layered =
+            crate::import::import_layered_with_limits(input, &name, max_colors, &options, &limits);
+        let result = match layered {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        if let Err(e) = std::fs::write(&output_path, result.to_jsonl(analyze)) {
+            eprintln!("Error: Failed to write '{}': {}", output_path.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        println!(
+            "Imported: {} ({}x{}, {} layers)",
+            output_path.display(),
+            result.width,
+            result.height,
+            result.layers.len()
+        );
+        for layer in &result.layers {
+            println!("  {} ({} colors)", layer.result.name, layer.result.palette.len());
+        }
+        for warning in &result.warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        return ExitCode::from(EXIT_SUCCESS);
+    }
 
     let result = match crate::import::import_png_with_options(input, &name, max_colors, &options) {
         Ok(r) => r,
@@ -53,18 +123,40 @@ pub fn run_import(
         }
     };
 
-    // Generate output path
-    let output_path = output.map(|p| p.to_path_buf()).unwrap_or_else(|| {
-        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
-        // Use .pxl extension if analysis is enabled, otherwise .jsonl
-        let ext = if analyze { "pxl" } else { "jsonl" };
-        input.parent().unwrap_or(std::path::Path::new(".")).join(format!("{}.{}", stem, ext))
-    });
+    write_import_result(&result, &output_path, analyze, hints, shapes)
+}
+
+/// Importer options shared by `pxl import` and `pxl paste --import`
+pub(super) fn import_options(
+    analyze: bool,
+    confidence: f64,
+    hints: bool,
+    shapes: bool,
+) -> crate::import::ImportOptions {
+    crate::import::ImportOptions {
+        analyze,
+        confidence_threshold: confidence,
+        hints,
+        extract_shapes: shapes,
+        half_sprite: false, // TODO: Add CLI flag when needed
+        dither_handling: crate::import::DitherHandling::Keep, // TODO: Add CLI flag when needed
+        detect_upscale: analyze, // Enable upscale detection when analysis is on
+        detect_outlines: analyze, // Enable outline detection when analysis is on
+    }
+}
 
+/// Write an import result and print its summary
+pub(super) fn write_import_result(
+    result: &crate::import::ImportResult,
+    output_path: &std::path::Path,
+    analyze: bool,
+    hints: bool,
+    shapes: bool,
+) -> ExitCode {
     // Write output (JSONL for legacy, structured for analysis)
     let output_content = if analyze { result.to_structured_jsonl() } else { result.to_jsonl() };
 
-    if let Err(e) = std::fs::write(&output_path, &output_content) {
+    if let Err(e) = std::fs::write(output_path, &output_content) {
         eprintln!("Error: Failed to write '{}': {}", output_path.display(), e);
         return ExitCode::from(EXIT_ERROR);
     }
@@ -137,3 +229,72 @@ pub fn run_import(
 
     ExitCode::from(EXIT_SUCCESS)
 }
+
+/// Execute the verify-roundtrip command
+pub fn run_verify_roundtrip(
+    input: &std::path::Path,
+    sprite: Option<&str>,
+    max_colors: usize,
+    extract_shapes: bool,
+    json: bool,
+) -> ExitCode {
+    use crate::roundtrip::{verify_roundtrip, RoundtripOptions};
+
+    if !(2..=256).contains(&max_colors) {
+        eprintln!("Error: --max-colors must be between 2 and 256");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
+    let source = match std::fs::read_to_string(input) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Error: Cannot read '{}': {}", input.display(), e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+
+    let options = RoundtripOptions { max_colors, extract_shapes };
+    let reports = match verify_roundtrip(&source, sprite, &options) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    let all_exact = reports.iter().all(|r| r.is_exact());
+
+    if json {
+        let output = serde_json::json!({
+            "exact": all_exact,
+            "sprites": reports.iter().map(|r| serde_json::json!({
+                "sprite": r.sprite,
+                "exact": r.is_exact(),
+                "original_tokens": r.original_tokens,
+                "imported_tokens": r.imported_tokens,
+                "mismatched_pixels": r.mismatched_pixels,
+                "lossy_steps": r.lossy_steps.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output).expect("JSON value serialization"));
+    } else {
+        for report in &reports {
+            let status = if report.is_exact() { "ok" } else { "LOSSY" };
+            println!(
+                "{:5} {} ({} tokens -> {})",
+                status, report.sprite, report.original_tokens, report.imported_tokens
+            );
+            for step in &report.lossy_steps {
+                println!("      {}", step);
+            }
+        }
+        let lossy = reports.iter().filter(|r| !r.is_exact()).count();
+        println!("\n{} sprite(s) verified, {} lossy", reports.len(), lossy);
+    }
+
+    if all_exact {
+        ExitCode::from(EXIT_SUCCESS)
+    } else {
+        ExitCode::from(EXIT_ERROR)
+    }
+}
@@ -1,4 +1,4 @@
-//! Info command implementations (prime, prompts, palettes)
+//! Info command implementations (prime, prompts, palettes, plugins)
 
 use clap::Subcommand;
 use std::process::ExitCode;
@@ -116,7 +116,9 @@ pub fn run_palettes(action: PaletteAction) -> ExitCode {
                 Some(palette) => {
                     println!("Palette: @{}", palette_name);
                     println!();
-                    for (key, color) in &palette.colors {
+                    let mut colors: Vec<_> = palette.colors.iter().collect();
+                    colors.sort();
+                    for (key, color) in colors {
                         println!("  {} => {}", key, color);
                     }
                     ExitCode::from(EXIT_SUCCESS)
@@ -140,3 +142,25 @@ pub fn run_palettes(action: PaletteAction) -> ExitCode {
         }
     }
 }
+
+/// Run the plugins command
+pub fn run_plugins(json: bool) -> ExitCode {
+    let plugins = crate::plugin::plugins();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&plugins).expect("JSON value serialization"));
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+    if plugins.is_empty() {
+        println!("No plugins registered");
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+    println!("Registered plugins:");
+    for plugin in &plugins {
+        if plugin.description.is_empty() {
+            println!("  {:<9} {}", plugin.kind, plugin.name);
+        } else {
+            println!("  {:<9} {} - {}", plugin.kind, plugin.name, plugin.description);
+        }
+    }
+    ExitCode::from(EXIT_SUCCESS)
+}
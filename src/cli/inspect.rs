@@ -0,0 +1,49 @@
+//! Inspect command implementation (PNG provenance metadata)
+
+use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::output::open_input;
+use crate::provenance::{read_text, KEY_PREFIX};
+
+use super::{EXIT_ERROR, EXIT_SUCCESS};
+
+/// Print the text metadata embedded in a PNG
+pub fn run_inspect(input: &Path, json: bool) -> ExitCode {
+    let mut png = Vec::new();
+    if let Err(e) = open_input(input).and_then(|mut r| r.read_to_end(&mut png)) {
+        eprintln!("Error: Cannot read '{}': {}", input.display(), e);
+        return ExitCode::from(EXIT_ERROR);
+    }
+    let entries = match read_text(&png) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Error: {}: {}", input.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    if json {
+        // pixelsrc keys lose their prefix; other tools' keys are kept as-is
+        let fields: serde_json::Map<String, serde_json::Value> = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let key = key.strip_prefix(KEY_PREFIX).map(str::to_string).unwrap_or(key);
+                (key, serde_json::Value::String(value))
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&fields).unwrap_or_default());
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+
+    if entries.is_empty() {
+        println!("No metadata in {}", input.display());
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+    let width = entries.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, value) in &entries {
+        println!("{:width$}  {}", format!("{}:", key), value, width = width + 1);
+    }
+    ExitCode::from(EXIT_SUCCESS)
+}
@@ -0,0 +1,138 @@
+//! Batch rendering from a job manifest (`pxl render --manifest`)
+//!
+//! A manifest lists many render jobs so build scripts can render hundreds of
+//! objects in one process. Every distinct input file is parsed once and the
+//! jobs run on a thread pool.
+//!
+//! ```json
+//! {
+//!   "input": "sprites.pxl",
+//!   "jobs": [
+//!     { "sprite": "hero", "scale": 4, "output": "out/hero.png" },
+//!     { "animation": "walk", "format": "gif", "output": "out/walk.gif" }
+//!   ]
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::output::{is_stdio, open_input};
+use crate::render_job::{write_output, RenderJob};
+use crate::serve::PreviewCatalog;
+
+use super::{print_saved, EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
+
+/// A batch of render jobs
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RenderManifest {
+    /// Input used by jobs that don't name their own
+    #[serde(default)]
+    input: Option<PathBuf>,
+    jobs: Vec<RenderJob>,
+}
+
+/// Run every job in a render manifest.
+///
+/// Relative `input` and `output` paths in the manifest are resolved against
+/// the manifest's directory; `default_input` (the positional render input)
+/// is used by jobs when the manifest names none.
+pub fn run_manifest(manifest_path: &Path, default_input: Option<&Path>) -> ExitCode {
+    let mut source = String::new();
+    if let Err(e) = open_input(manifest_path).and_then(|mut r| r.read_to_string(&mut source)) {
+        eprintln!("Error: Cannot read manifest {}: {}", manifest_path.display(), e);
+        return ExitCode::from(EXIT_ERROR);
+    }
+    let manifest: RenderManifest = match serde_json::from_str(&source) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Error: Invalid manifest {}: {}", manifest_path.display(), e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+
+    let base = if is_stdio(manifest_path) {
+        PathBuf::new()
+    } else {
+        manifest_path.parent().map(Path::to_path_buf).unwrap_or_default()
+    };
+    let manifest_input = manifest.input.as_ref().map(|p| base.join(p));
+    let default_input = manifest_input.or_else(|| default_input.map(Path::to_path_buf));
+
+    // Reject malformed jobs up front so nothing is half-rendered
+    let mut invalid = false;
+    let mut inputs = Vec::with_capacity(manifest.jobs.len());
+    for (index, job) in manifest.jobs.iter().enumerate() {
+        let input = job.input.as_ref().map(|p| base.join(p)).or_else(|| default_input.clone());
+        let result = job.validate().and_then(|()| {
+            input.ok_or_else(|| "no input (set 'input' on the job or the manifest)".to_string())
+        });
+        match result {
+            Ok(input) => inputs.push(input),
+            Err(e) => {
+                eprintln!("Error: Job {} ({}): {}", index + 1, job.label(), e);
+                invalid = true;
+            }
+        }
+    }
+    if invalid {
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
+    // Parse each distinct input once, shared by every job that uses it
+    let mut catalogs: HashMap<&Path, Result<PreviewCatalog, String>> = HashMap::new();
+    for input in &inputs {
+        catalogs.entry(input).or_insert_with(|| {
+            if !input.exists() {
+                return Err(format!("Input file not found: {}", input.display()));
+            }
+            let catalog = PreviewCatalog::load(std::slice::from_ref(input));
+            for warning in &catalog.warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            Ok(catalog)
+        });
+    }
+
+    // The seed and PNG options are per-thread, so carry them into the workers
+    let seed = crate::rng::global_seed();
+    let png = crate::output::png_options();
+    let results: Vec<Result<PathBuf, String>> = manifest
+        .jobs
+        .par_iter()
+        .zip(&inputs)
+        .map(|(job, input)| {
+            let catalog = catalogs[input.as_path()].as_ref().map_err(Clone::clone)?;
+            let output = base.join(&job.output);
+            let bytes = crate::rng::with_global_seed(seed, || {
+                crate::output::with_png_options(png, || job.encode(catalog))
+            })?;
+            write_output(&bytes, &output)?;
+            Ok(output)
+        })
+        .collect();
+
+    let mut failed = 0;
+    for (index, (job, result)) in manifest.jobs.iter().zip(&results).enumerate() {
+        match result {
+            Ok(output) => print_saved(output),
+            Err(e) => {
+                eprintln!("Error: Job {} ({}): {}", index + 1, job.label(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        eprintln!("{} of {} jobs failed", failed, results.len());
+        ExitCode::from(EXIT_ERROR)
+    } else {
+        ExitCode::from(EXIT_SUCCESS)
+    }
+}
@@ -4,16 +4,26 @@
 //! for specific command implementations.
 
 mod agent;
+mod animate;
+mod bridge;
 mod build;
+mod clipboard;
+mod daemon;
 mod draw;
+mod expand;
 mod explain;
+mod export;
 mod import;
 mod info;
+mod inspect;
 mod install;
+mod manifest;
 mod mask;
 mod render;
 pub mod scaffold;
+mod serve;
 mod show;
+mod snapshot;
 mod validate;
 
 use clap::{Parser, Subcommand};
@@ -22,10 +32,21 @@ use std::process::ExitCode;
 
 use glob::glob;
 
+use crate::config::loader::{find_config, load_config};
+use crate::flipbook::FlipbookConfig;
+use crate::gif::{GifDither, GifOptions, GifPalette};
+use crate::output::{
+    with_png_options, Background, CanvasSize, FrameOptions, PngCompression, PngFilter, PngOptions,
+};
+use crate::selection::{Selection, Selector};
+
 // Re-export subcommand types used in Commands enum
 pub use agent::AgentAction;
+pub use animate::AnimatePreset;
+pub use export::ExportFormat;
 pub use info::PaletteAction;
 pub use scaffold::ScaffoldAction;
+pub use snapshot::SnapshotAction;
 
 /// Exit codes per Pixelsrc spec
 pub(crate) const EXIT_SUCCESS: u8 = 0;
@@ -37,6 +58,13 @@ pub fn is_pixelsrc_file(path: &std::path::Path) -> bool {
     matches!(path.extension().and_then(|e| e.to_str()), Some("pxl") | Some("jsonl"))
 }
 
+/// Print the `Saved: <path>` confirmation, unless the output went to stdout.
+pub(crate) fn print_saved(path: &std::path::Path) {
+    if !crate::output::is_stdio(path) {
+        println!("Saved: {}", path.display());
+    }
+}
+
 /// Find all Pixelsrc files in a directory (recursively).
 ///
 /// Searches for both `.pxl` and `.jsonl` files.
@@ -65,20 +93,27 @@ pub fn find_pixelsrc_files(dir: &std::path::Path) -> Vec<PathBuf> {
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Define a flag for `when` conditions in documents (repeatable)
+    #[arg(long = "define", value_name = "FLAG", global = true)]
+    pub define: Vec<String>,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// Render sprites from a Pixelsrc file to PNG
     Render {
-        /// Input file containing palette and sprite definitions (.pxl or .jsonl)
-        input: PathBuf,
+        /// Input file containing palette and sprite definitions (.pxl or .jsonl, - for stdin)
+        #[arg(required_unless_present = "manifest")]
+        input: Option<PathBuf>,
 
         /// Output file or directory.
         /// If omitted: {input}_{sprite}.png
         /// If file (single sprite): output.png
         /// If file (multiple): output_{sprite}.png
         /// If directory (ends with /): dir/{sprite}.png
+        /// If -: write the single PNG or GIF to stdout
         #[arg(short, long)]
         output: Option<PathBuf>,
 
@@ -90,6 +125,34 @@ pub enum Commands {
         #[arg(short = 'c', long)]
         composition: Option<String>,
 
+        /// Show composition layers and layer groups matching this glob (repeatable)
+        #[arg(long = "show-layer", value_name = "GLOB")]
+        show_layer: Vec<String>,
+
+        /// Hide composition layers and layer groups matching this glob (repeatable)
+        #[arg(long = "hide-layer", value_name = "GLOB")]
+        hide_layer: Vec<String>,
+
+        /// Only render objects matching a selector: label=GLOB, name=GLOB or type=GLOB (repeatable)
+        #[arg(long, value_name = "SELECTOR")]
+        only: Vec<Selector>,
+
+        /// Skip objects matching a selector: label=GLOB, name=GLOB or type=GLOB (repeatable)
+        #[arg(long, value_name = "SELECTOR")]
+        exclude: Vec<Selector>,
+
+        /// Render with a theme's palette swaps applied to every sprite
+        #[arg(long, value_name = "NAME")]
+        theme: Option<String>,
+
+        /// Resolve locale substitutions declared by sprites, variants and compositions
+        #[arg(long, value_name = "LOCALE")]
+        locale: Option<String>,
+
+        /// Don't embed provenance text chunks (source, object, hash) in PNG output
+        #[arg(long)]
+        no_metadata: bool,
+
         /// Strict mode: treat warnings as errors
         #[arg(long)]
         strict: bool,
@@ -114,10 +177,46 @@ pub enum Commands {
         #[arg(long)]
         animation: Option<String>,
 
-        /// Output format: atlas, atlas-aseprite, atlas-godot, atlas-unity, atlas-libgdx
+        /// Only export the frames of this animation tag (GIF, spritesheet, or atlas)
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Render these sprites or compositions (comma-separated, bottom first)
+        /// as a pseudo-3D sprite stack; with --gif, output a full rotation
+        #[arg(long, value_delimiter = ',')]
+        stack: Vec<String>,
+
+        /// Vertical pixels between stacked layers
+        #[arg(long, default_value = "1")]
+        stack_spacing: u32,
+
+        /// Rotation of the sprite stack in degrees (clockwise)
+        #[arg(long, default_value = "0", allow_negative_numbers = true)]
+        stack_angle: f64,
+
+        /// Number of frames in a rotating sprite stack GIF
+        #[arg(long, default_value = "16", value_parser = clap::value_parser!(u32).range(1..=360))]
+        stack_frames: u32,
+
+        /// Output format: atlas, atlas-aseprite, atlas-godot, atlas-unity, atlas-libgdx,
+        /// cur (one cursor per sprite), ani (animated cursor), ora (layered OpenRaster),
+        /// web (layered canvas bundle per composition), flipbook (printable flip-book
+        /// sheet, PDF or PNG), cycle-strip (palette cycle steps with their durations)
         #[arg(long)]
         format: Option<String>,
 
+        /// Paper size of a flip-book sheet
+        #[arg(long, value_enum, default_value = "a4")]
+        paper: crate::flipbook::PaperSize,
+
+        /// Print resolution of a flip-book sheet (dots per inch)
+        #[arg(long, default_value = "300", value_parser = clap::value_parser!(u32).range(72..=1200))]
+        dpi: u32,
+
+        /// Add the combined timeline of all palette cycles to a cycle strip
+        #[arg(long)]
+        cycle_timeline: bool,
+
         /// Maximum atlas size (e.g., "512x512", "1024x1024")
         #[arg(long)]
         max_size: Option<String>,
@@ -130,11 +229,20 @@ pub enum Commands {
         #[arg(long)]
         power_of_two: bool,
 
+        /// Only render this rectangle of each sprite or composition (x,y,w,h in pixels,
+        /// before scaling)
+        #[arg(long, value_name = "X,Y,W,H")]
+        crop: Option<crate::renderer::Rect>,
+
         /// Render nine-slice sprite to target size (e.g., "64x32")
         /// Requires sprite to have nine_slice attribute defined
         #[arg(long)]
         nine_slice: Option<String>,
 
+        /// Also write lighting maps next to each sprite or atlas (normal, emissive)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        maps: Vec<crate::material::MapKind>,
+
         /// Apply antialiasing algorithm (nearest, scale2x, hq2x, hq4x, xbr2x, xbr4x, aa-blur)
         #[arg(long, value_enum)]
         antialias: Option<crate::antialias::AAAlgorithm>,
@@ -158,10 +266,78 @@ pub enum Commands {
         /// Disable project context detection (skip pxl.toml lookup)
         #[arg(long)]
         no_project: bool,
+
+        /// Write color channels premultiplied by alpha
+        #[arg(long)]
+        premultiply_alpha: bool,
+
+        /// Flatten output onto an opaque background color (e.g., "#000000", "white")
+        #[arg(long)]
+        matte: Option<String>,
+
+        /// Composite output over a background: a color or "checkerboard[:SIZE]"
+        #[arg(long, value_name = "COLOR")]
+        background: Option<Background>,
+
+        /// Space around the output image, in output pixels
+        #[arg(long, value_name = "PX", default_value = "0")]
+        margin: u32,
+
+        /// Letterbox onto a fixed canvas (e.g., 1920x1080), scaled up by a whole factor and centered
+        #[arg(long, value_name = "WxH")]
+        canvas: Option<CanvasSize>,
+
+        /// Draw debug guides (comma-separated): grid[:N], cells, nine-slice, boxes, origin
+        #[arg(long, value_delimiter = ',', value_name = "KINDS")]
+        overlay: Vec<crate::overlay::Overlay>,
+
+        /// Write indexed PNG-8 using the resolved palette (falls back to RGBA past 256 colors)
+        #[arg(long)]
+        indexed: bool,
+
+        /// GIF color tables: one shared by every frame, or one per frame
+        #[arg(long, value_enum, default_value = "global")]
+        gif_palette: GifPalette,
+
+        /// Dithering when GIF colors have to be quantized
+        #[arg(long, value_enum, default_value = "none")]
+        gif_dither: GifDither,
+
+        /// GIF pixels with alpha below this are transparent, the rest opaque
+        #[arg(long, value_name = "ALPHA", default_value = "128")]
+        gif_alpha_threshold: u8,
+
+        /// Store every GIF frame in full instead of only the changed area
+        #[arg(long)]
+        no_gif_optimize: bool,
+
+        /// PNG zlib compression level
+        #[arg(long, value_enum, default_value = "default")]
+        png_compression: PngCompression,
+
+        /// PNG scanline filter strategy
+        #[arg(long, value_enum, default_value = "adaptive")]
+        png_filter: PngFilter,
+
+        /// Byte-identical output for identical pixels: omit provenance and other ancillary PNG chunks
+        #[arg(long)]
+        reproducible: bool,
+
+        /// Global random seed for jitter and particle effects (default: 0)
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Also save each layer of a layered sprite as {name}_{layer}.png
+        #[arg(long)]
+        split_layers: bool,
+
+        /// Render every job in a JSON manifest in one process (input is the jobs' default)
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
     },
-    /// Import a PNG image and convert to Pixelsrc format
+    /// Import a PNG or layered image (.ora, .psd) and convert to Pixelsrc format
     Import {
-        /// Input PNG file to convert
+        /// Input PNG, OpenRaster (.ora) or PSD file to convert
         input: PathBuf,
 
         /// Output file (default: {input}.jsonl, use .pxl extension for new format)
@@ -191,6 +367,15 @@ pub enum Commands {
         /// Disable extraction of structured shapes (polygons, rects)
         #[arg(long)]
         no_shapes: bool,
+
+        /// Import a sprite sheet as frame sprites plus an animation, cut into
+        /// WxH cells or at transparent gutters with 'auto'
+        #[arg(long, value_name = "auto|WxH")]
+        sheet: Option<crate::import::SheetSlicing>,
+
+        /// Reuse one sprite for identical sheet frames
+        #[arg(long, requires = "sheet")]
+        dedupe: bool,
     },
 
     /// Show GenAI prompt templates for sprite generation
@@ -206,9 +391,16 @@ pub enum Commands {
         action: PaletteAction,
     },
 
+    /// List registered transform and exporter plugins
+    Plugins {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Analyze pixelsrc files and extract corpus metrics
     Analyze {
-        /// Files to analyze
+        /// Files to analyze (- for stdin)
         #[arg(required_unless_present = "dir")]
         files: Vec<PathBuf>,
 
@@ -224,14 +416,31 @@ pub enum Commands {
         #[arg(long, default_value = "text")]
         format: String,
 
-        /// Write output to file instead of stdout
+        /// Write output to file instead of stdout (- for stdout)
         #[arg(long, short)]
         output: Option<PathBuf>,
+
+        /// Report duplicate and near-duplicate sprites instead of corpus metrics
+        #[arg(long)]
+        duplicates: bool,
+
+        /// Minimum similarity (0.0-1.0) for near-duplicates
+        #[arg(long, default_value_t = crate::analyze::DEFAULT_SIMILARITY_THRESHOLD, requires = "duplicates")]
+        threshold: f64,
+
+        /// Compare against a previous `--format json` report
+        #[arg(long, value_name = "OLD", conflicts_with = "duplicates")]
+        compare: Option<PathBuf>,
+
+        /// Report the color histogram, alpha usage and nearest built-in palette
+        /// of a rendered sprite or composition
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["duplicates", "compare"])]
+        colors: Option<String>,
     },
 
     /// Format pixelsrc files for readability
     Fmt {
-        /// Input file(s) to format
+        /// Input file(s) to format (- formats stdin to stdout)
         #[arg(required = true)]
         files: Vec<PathBuf>,
 
@@ -242,6 +451,11 @@ pub enum Commands {
         /// Write to stdout instead of in-place
         #[arg(long)]
         stdout: bool,
+
+        /// Rewrite delimited composition maps to single characters, assigning
+        /// a character per cell and writing the sprites mapping
+        #[arg(long)]
+        legend: bool,
     },
 
     /// Print pixelsrc format guide for AI context injection
@@ -257,7 +471,7 @@ pub enum Commands {
 
     /// Validate pixelsrc files for common mistakes
     Validate {
-        /// Files to validate (omit if using --stdin)
+        /// Files to validate (- or --stdin for stdin)
         #[arg(required_unless_present = "stdin")]
         files: Vec<PathBuf>,
 
@@ -272,6 +486,75 @@ pub enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Only report issues not recorded in this baseline file
+        #[arg(long, value_name = "FILE", conflicts_with = "write_baseline")]
+        baseline: Option<PathBuf>,
+
+        /// Record the current issues to a baseline file and exit successfully
+        #[arg(long, value_name = "FILE")]
+        write_baseline: Option<PathBuf>,
+    },
+
+    /// Check pixelsrc files against style conventions configured in pxl.toml
+    Lint {
+        /// Files to lint
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// pxl.toml to read `[lint]` settings from (default: discovered from the first file)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+
+    /// Apply safe automatic fixes for lint findings
+    ///
+    /// Extracts inline palettes into named palettes, removes duplicate
+    /// palettes, and strips legacy `{token}` braces from palette keys,
+    /// rewriting only the lines of the objects it changes.
+    Fix {
+        /// Files to fix in place
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Report fixes without writing (exit 1 if any are needed)
+        #[arg(long)]
+        check: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Shrink sprites to the bounding box of their visible pixels
+    ///
+    /// Rewrites `size` and moves regions, the origin, attach points,
+    /// collision boxes, and nine-slice borders with the content, rewriting
+    /// only the lines of the sprites it crops.
+    Crop {
+        /// Files to crop in place
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Crop to the non-transparent bounding box (currently the only mode)
+        #[arg(long, required = true)]
+        auto: bool,
+
+        /// Only crop this sprite
+        #[arg(short, long)]
+        sprite: Option<String>,
+
+        /// Report crops without writing (exit 1 if any are needed)
+        #[arg(long)]
+        check: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Verify pixelsrc content for AI agents (returns JSON)
@@ -321,6 +604,14 @@ pub enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Render sprites and describe colors, shapes, symmetry, and layout
+        #[arg(long)]
+        describe: bool,
+
+        /// Explain why one pixel of the named sprite has its color (x,y after transforms)
+        #[arg(long, value_name = "X,Y", requires = "name")]
+        pixel: Option<String>,
     },
 
     /// Compare sprites semantically between two files
@@ -396,11 +687,69 @@ pub enum Commands {
         #[arg(long)]
         onion_fade: bool,
 
-        /// Output file (PNG) for onion skin preview
+        /// Output file (PNG) for the sprite or onion skin preview
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
 
+    /// Export a sprite as emoji art, ANSI art, a Slack/Discord custom emoji or
+    /// one PNG per named slice, or an animation as video (with the `video` feature)
+    Export {
+        /// Input file containing sprite definitions (- for stdin)
+        input: PathBuf,
+
+        /// Export format
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+
+        /// Sprite name (default: first sprite in the file)
+        #[arg(short, long)]
+        sprite: Option<String>,
+
+        /// Output file, or - for stdout
+        /// (default: stdout for emoji/ansi, {input}_{sprite}_emoji.png for discord)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Transparent padding around a custom emoji (pixels)
+        #[arg(long, default_value = "4")]
+        padding: u32,
+
+        /// Resolve locale substitutions declared by sprites
+        #[arg(long, value_name = "LOCALE")]
+        locale: Option<String>,
+
+        /// Animation to export as video (default: first animation by name)
+        #[arg(short, long)]
+        animation: Option<String>,
+
+        /// Video frame rate (default: 30)
+        #[arg(long)]
+        fps: Option<u32>,
+
+        /// Video scale factor (default: 1)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..))]
+        scale: Option<u8>,
+
+        /// Pick the default sprite or animation from objects matching a selector (repeatable)
+        #[arg(long, value_name = "SELECTOR")]
+        only: Vec<Selector>,
+
+        /// Skip objects matching a selector when picking the default (repeatable)
+        #[arg(long, value_name = "SELECTOR")]
+        exclude: Vec<Selector>,
+    },
+
+    /// Show the provenance metadata embedded in a rendered PNG
+    Inspect {
+        /// PNG file to inspect (- for stdin)
+        input: PathBuf,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Build all assets according to pxl.toml
     Build {
         /// Override output directory
@@ -426,6 +775,197 @@ pub enum Commands {
         /// Verbose output
         #[arg(short, long)]
         verbose: bool,
+
+        /// Only build sprites matching a selector: label=GLOB, name=GLOB or type=GLOB (repeatable)
+        #[arg(long, value_name = "SELECTOR")]
+        only: Vec<Selector>,
+
+        /// Skip sprites matching a selector (repeatable)
+        #[arg(long, value_name = "SELECTOR")]
+        exclude: Vec<Selector>,
+
+        /// Compile every sprite and animation into a binary .pxb bundle at this path
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["watch", "only", "exclude"])]
+        bundle: Option<PathBuf>,
+
+        /// Write a manifest of every artifact with content hashes, sources and options
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["watch", "dry_run", "bundle"])]
+        manifest_out: Option<PathBuf>,
+
+        /// PNG zlib compression level
+        #[arg(long, value_enum, default_value = "default")]
+        png_compression: PngCompression,
+
+        /// PNG scanline filter strategy
+        #[arg(long, value_enum, default_value = "adaptive")]
+        png_filter: PngFilter,
+
+        /// Byte-identical output for identical pixels: omit provenance and other ancillary PNG chunks
+        #[arg(long)]
+        reproducible: bool,
+    },
+
+    /// Render sprites, re-import the PNGs, and report any lossy steps
+    #[command(name = "verify-roundtrip")]
+    VerifyRoundtrip {
+        /// Input .pxl file
+        input: PathBuf,
+
+        /// Only verify this sprite
+        #[arg(short, long)]
+        sprite: Option<String>,
+
+        /// Maximum colors the importer may keep
+        #[arg(long, default_value = "16")]
+        max_colors: usize,
+
+        /// Import raw points instead of extracting shapes
+        #[arg(long)]
+        points: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Serve live previews of a project over HTTP
+    Serve {
+        /// Source file or directory (default: src dir from pxl.toml, or current directory)
+        path: Option<PathBuf>,
+
+        /// Port to listen on
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Disable file watching and live reload
+        #[arg(long)]
+        no_watch: bool,
+    },
+
+    /// Record or check rendered output of every object for regression testing
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+
+    /// Keep sources parsed in memory and answer render/validate requests over a local socket
+    Daemon {
+        /// Port to listen on (0 picks a free port)
+        #[arg(short, long, default_value = "7878")]
+        port: u16,
+
+        /// Loopback address to bind (other addresses are refused)
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+
+    /// Generate an animation from a single sprite, printed as JSONL
+    ///
+    /// The new sprites and animation are printed to stdout (or written to
+    /// --output), ready to append to the input file.
+    ///
+    /// Examples:
+    ///   pxl animate hero.pxl hero --preset breathe >> hero.pxl
+    ///   pxl animate hero.pxl hero --preset breathe --frames 2 --depth 2
+    Animate {
+        /// Input file containing the sprite
+        input: PathBuf,
+
+        /// Sprite or variant to animate
+        sprite: String,
+
+        /// Animation to generate
+        #[arg(long, value_enum)]
+        preset: AnimatePreset,
+
+        /// Frames per cycle (2-4)
+        #[arg(long, default_value_t = crate::idle::DEFAULT_FRAMES)]
+        frames: u32,
+
+        /// Rows squashed at the deepest point
+        #[arg(long, default_value_t = 1)]
+        depth: u32,
+
+        /// Lightness removed at the deepest point, in percentage points
+        #[arg(long, default_value_t = 4.0)]
+        shade: f64,
+
+        /// Duration of each frame in milliseconds
+        #[arg(long, default_value_t = crate::idle::DEFAULT_FRAME_MS)]
+        duration: u32,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate distinct sprites or compositions from a random_set, printed as JSONL
+    ///
+    /// The generated objects are printed to stdout (or written to --output),
+    /// ready to append to the input file. The same seed always gives the
+    /// same objects.
+    ///
+    /// Examples:
+    ///   pxl expand rocks.pxl rock --count 20 >> rocks.pxl
+    ///   pxl expand rocks.pxl rock --count 20 --seed 7 -o rocks_gen.pxl
+    Expand {
+        /// Input file containing the random set and its choices
+        input: PathBuf,
+
+        /// Random set to expand
+        set: String,
+
+        /// Number of objects to generate
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+
+        /// Global random seed (default: 0)
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Render a sprite, composition, or animation to the system clipboard
+    Copy {
+        /// Input file containing definitions
+        input: PathBuf,
+
+        /// Sprite, composition, or animation name (animations copy as GIF)
+        name: String,
+
+        /// Scale factor
+        #[arg(long, default_value = "1")]
+        scale: u8,
+    },
+
+    /// Save or import an image from the system clipboard
+    Paste {
+        /// Convert the clipboard image to Pixelsrc format instead of saving the PNG
+        #[arg(long)]
+        import: bool,
+
+        /// Output file (default: {name}.png, or {name}.jsonl with --import)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Name for the output file and imported sprite
+        #[arg(short, long, default_value = "clipboard")]
+        name: String,
+
+        /// Maximum number of colors in the imported palette (2-256)
+        #[arg(long, default_value = "16")]
+        max_colors: usize,
+
+        /// Enable role/relationship inference analysis (writes .pxl)
+        #[arg(long)]
+        analyze: bool,
     },
 
     /// Create a new asset from template
@@ -450,9 +990,13 @@ pub enum Commands {
         #[arg(long)]
         name: Option<String>,
 
-        /// Preset template: minimal, artist, animator, game
+        /// Preset template: minimal, artist, animator, game, gameboy, pico8, hd
         #[arg(long, default_value = "minimal")]
         preset: String,
+
+        /// Prompt for directory, name, and preset
+        #[arg(short, long)]
+        interactive: bool,
     },
 
     /// Fetch and cache external dependencies declared in pxl.toml
@@ -535,6 +1079,23 @@ pub enum Commands {
         dry_run: bool,
     },
 
+    /// Sync a sprite with a PNG edited in an external editor (e.g. Aseprite)
+    Bridge {
+        /// Input .pxl file holding the sprite
+        input: PathBuf,
+
+        /// Sprite name to sync
+        sprite: String,
+
+        /// PNG file shared with the editor
+        #[arg(long)]
+        png: PathBuf,
+
+        /// Sync once and exit: pull the PNG if it exists, then export
+        #[arg(long)]
+        once: bool,
+    },
+
     /// Query sprite state: tokens, bounds, coordinates (read-only)
     Mask {
         /// Input .pxl file to query
@@ -582,52 +1143,137 @@ pub enum Commands {
 pub fn run() -> ExitCode {
     let cli = Cli::parse();
 
-    match cli.command {
+    // Flags from the project's pxl.toml apply alongside --define
+    let mut defines = cli.define;
+    if let Some(config) = find_config().and_then(|path| load_config(Some(&path)).ok()) {
+        defines.extend(config.defaults.defines);
+    }
+    crate::conditions::with_defines(defines, || dispatch(cli.command))
+}
+
+/// Run a parsed subcommand.
+fn dispatch(command: Commands) -> ExitCode {
+    match command {
         Commands::Render {
             input,
             output,
             sprite,
             composition,
+            show_layer,
+            hide_layer,
+            only,
+            exclude,
+            theme,
+            locale,
+            no_metadata,
             strict,
             scale,
             gif,
             spritesheet,
             emoji,
             animation,
+            tag,
+            stack,
+            stack_spacing,
+            stack_angle,
+            stack_frames,
             format,
+            paper,
+            dpi,
+            cycle_timeline,
             max_size,
             padding,
             power_of_two,
             nine_slice,
+            crop,
+            maps,
             antialias,
             aa_strength,
             anchor_mode,
             no_semantic_aa,
             gradient_shadows,
             no_project,
-        } => render::run_render(
-            &input,
-            output.as_deref(),
-            sprite.as_deref(),
-            composition.as_deref(),
-            strict,
-            scale,
-            gif,
-            spritesheet,
-            emoji,
-            animation.as_deref(),
-            format.as_deref(),
-            max_size.as_deref(),
-            padding,
-            power_of_two,
-            nine_slice.as_deref(),
-            antialias,
-            aa_strength,
-            anchor_mode,
-            no_semantic_aa,
-            gradient_shadows,
-            no_project,
-        ),
+            premultiply_alpha,
+            matte,
+            background,
+            margin,
+            canvas,
+            overlay,
+            indexed,
+            gif_palette,
+            gif_dither,
+            gif_alpha_threshold,
+            no_gif_optimize,
+            png_compression,
+            png_filter,
+            reproducible,
+            seed,
+            split_layers,
+            manifest,
+        } => crate::rng::with_global_seed(seed.unwrap_or(crate::rng::DEFAULT_SEED), || {
+            let png = PngOptions {
+                compression: png_compression,
+                filter: png_filter,
+                strip_metadata: reproducible,
+            };
+            if let Some(manifest) = manifest {
+                return with_png_options(png, || {
+                    manifest::run_manifest(&manifest, input.as_deref())
+                });
+            }
+            with_png_options(png, || {
+                render::run_render(
+                    &input.expect("clap requires input without --manifest"),
+                    output.as_deref(),
+                    sprite.as_deref(),
+                    composition.as_deref(),
+                    strict,
+                    scale,
+                    gif,
+                    spritesheet,
+                    emoji,
+                    animation.as_deref(),
+                    format.as_deref(),
+                    max_size.as_deref(),
+                    padding,
+                    power_of_two,
+                    nine_slice.as_deref(),
+                    antialias,
+                    aa_strength,
+                    anchor_mode,
+                    no_semantic_aa,
+                    gradient_shadows,
+                    no_project,
+                    tag.as_deref(),
+                    &stack,
+                    stack_spacing,
+                    stack_angle,
+                    stack_frames,
+                    &maps,
+                    premultiply_alpha,
+                    matte.as_deref(),
+                    split_layers,
+                    &show_layer,
+                    &hide_layer,
+                    &Selection { only, exclude },
+                    theme.as_deref(),
+                    locale.as_deref(),
+                    !no_metadata,
+                    FlipbookConfig { paper, dpi, ..Default::default() },
+                    cycle_timeline,
+                    crop,
+                    indexed,
+                    FrameOptions { background, margin, canvas },
+                    &overlay,
+                    GifOptions {
+                        palette: gif_palette,
+                        dither: gif_dither,
+                        alpha_threshold: gif_alpha_threshold,
+                        optimize: !no_gif_optimize,
+                    },
+                )
+            })
+        }),
         Commands::Import {
             input,
             output,
@@ -637,6 +1283,8 @@ pub fn run() -> ExitCode {
             confidence,
             hints,
             no_shapes,
+            sheet,
+            dedupe,
         } => import::run_import(
             &input,
             output.as_deref(),
@@ -646,16 +1294,51 @@ pub fn run() -> ExitCode {
             confidence,
             hints,
             !no_shapes,
+            sheet.map(|slicing| (slicing, dedupe)),
         ),
         Commands::Prompts { template } => info::run_prompts(template.as_deref()),
         Commands::Palettes { action } => info::run_palettes(action),
-        Commands::Analyze { files, dir, recursive, format, output } => {
-            validate::run_analyze(&files, dir.as_deref(), recursive, &format, output.as_deref())
+        Commands::Plugins { json } => info::run_plugins(json),
+        Commands::Analyze {
+            files,
+            dir,
+            recursive,
+            format,
+            output,
+            duplicates,
+            threshold,
+            compare,
+            colors,
+        } => validate::run_analyze(
+            &files,
+            dir.as_deref(),
+            recursive,
+            &format,
+            output.as_deref(),
+            duplicates.then_some(threshold),
+            compare.as_deref(),
+            colors.as_deref(),
+        ),
+        Commands::Fmt { files, check, stdout, legend } => {
+            validate::run_fmt(&files, check, stdout, legend)
         }
-        Commands::Fmt { files, check, stdout } => validate::run_fmt(&files, check, stdout),
         Commands::Prime { brief, section } => info::run_prime(brief, section.as_deref()),
-        Commands::Validate { files, stdin, strict, json } => {
-            validate::run_validate(&files, stdin, strict, json)
+        Commands::Validate { files, stdin, strict, json, baseline, write_baseline } => {
+            validate::run_validate(
+                &files,
+                stdin,
+                strict,
+                json,
+                baseline.as_deref(),
+                write_baseline.as_deref(),
+            )
+        }
+        Commands::Lint { files, json, config } => {
+            validate::run_lint(&files, json, config.as_deref())
+        }
+        Commands::Fix { files, check, json } => validate::run_fix(&files, check, json),
+        Commands::Crop { files, auto: _, sprite, check, json } => {
+            validate::run_crop(&files, sprite.as_deref(), check, json)
         }
         Commands::AgentVerify {
             stdin,
@@ -674,8 +1357,8 @@ pub fn run() -> ExitCode {
             resolve_colors,
             analyze_timing,
         ),
-        Commands::Explain { input, name, json } => {
-            explain::run_explain(&input, name.as_deref(), json)
+        Commands::Explain { input, name, json, describe, pixel } => {
+            explain::run_explain(&input, name.as_deref(), json, describe, pixel.as_deref())
         }
         Commands::Diff { file_a, file_b, sprite, json } => {
             explain::run_diff(&file_a, &file_b, sprite.as_deref(), json)
@@ -706,14 +1389,91 @@ pub fn run() -> ExitCode {
             onion_fade,
             output.as_deref(),
         ),
-        Commands::Build { out, src, watch, dry_run, force, verbose } => {
-            build::run_build(out.as_deref(), src.as_deref(), watch, dry_run, force, verbose)
+        Commands::Export {
+            input,
+            format,
+            sprite,
+            output,
+            padding,
+            locale,
+            animation,
+            fps,
+            scale,
+            only,
+            exclude,
+        } => export::run_export(
+            &input,
+            format,
+            sprite.as_deref(),
+            output.as_deref(),
+            padding,
+            locale.as_deref(),
+            animation.as_deref(),
+            fps,
+            scale,
+            &Selection { only, exclude },
+        ),
+        Commands::Inspect { input, json } => inspect::run_inspect(&input, json),
+        Commands::Build {
+            out,
+            src,
+            watch,
+            dry_run,
+            force,
+            verbose,
+            only,
+            exclude,
+            bundle,
+            manifest_out,
+            png_compression,
+            png_filter,
+            reproducible,
+        } => {
+            let png = PngOptions {
+                compression: png_compression,
+                filter: png_filter,
+                strip_metadata: reproducible,
+            };
+            with_png_options(png, || match bundle {
+                Some(bundle) => build::run_build_bundle(src.as_deref(), &bundle, dry_run, verbose),
+                None => build::run_build(
+                    out.as_deref(),
+                    src.as_deref(),
+                    watch,
+                    dry_run,
+                    force,
+                    verbose,
+                    Selection { only, exclude },
+                    manifest_out.as_deref(),
+                ),
+            })
+        }
+        Commands::VerifyRoundtrip { input, sprite, max_colors, points, json } => {
+            import::run_verify_roundtrip(&input, sprite.as_deref(), max_colors, !points, json)
+        }
+        Commands::Serve { path, port, host, no_watch } => {
+            serve::run_serve(path.as_deref(), &host, port, !no_watch)
+        }
+        Commands::Snapshot { action } => snapshot::run_snapshot(action),
+        Commands::Daemon { port, host } => daemon::run_daemon(&host, port),
+        Commands::Animate { input, sprite, preset, frames, depth, shade, duration, output } => {
+            let options = crate::idle::BreatheOptions { frames, depth, shade, frame_ms: duration };
+            animate::run_animate(&input, &sprite, preset, &options, output.as_deref())
+        }
+        Commands::Expand { input, set, count, seed, output } => {
+            crate::rng::with_global_seed(seed.unwrap_or(crate::rng::DEFAULT_SEED), || {
+                expand::run_expand(&input, &set, count, output.as_deref())
+            })
+        }
+        Commands::Copy { input, name, scale } => clipboard::run_copy(&input, &name, scale),
+        Commands::Paste { import, output, name, max_colors, analyze } => {
+            clipboard::run_paste(output.as_deref(), import, &name, max_colors, analyze)
         }
         Commands::New { asset_type, name, palette } => {
             build::run_new(&asset_type, &name, palette.as_deref())
         }
-        Commands::Init { path, name, preset } => {
-            build::run_init(path.as_deref(), name.as_deref(), &preset)
+        Commands::Init { path, name, preset, interactive } => {
+            build::run_init(path.as_deref(), name.as_deref(), &preset, interactive)
         }
         Commands::Install { clean, verbose } => install::run_install(clean, verbose),
         #[cfg(feature = "lsp")]
@@ -747,6 +1507,9 @@ pub fn run() -> ExitCode {
             output.as_deref(),
             dry_run,
         ),
+        Commands::Bridge { input, sprite, png, once } => {
+            bridge::run_bridge(&input, &sprite, &png, once)
+        }
         Commands::Mask {
             input,
             sprite,
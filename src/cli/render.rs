@@ -1,28 +1,53 @@
 //! Render command implementation and helpers
 
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::BufReader;
 use std::path::PathBuf;
 use std::process::ExitCode;
 
 use crate::antialias::{AAAlgorithm, AnchorMode};
-use crate::atlas::{add_animation_to_atlas, pack_atlas, AtlasBox, AtlasConfig, SpriteInput};
+use crate::atlas::{add_animation_to_atlas, pack_atlas, AtlasConfig, SpriteInput};
 use crate::build::project_registry::ProjectRegistry;
-use crate::composition::render_composition;
+use crate::color::parse_color;
+use crate::composition::{
+    apply_palette_lock, render_composition, render_composition_layers, PaletteLockReport,
+};
 use crate::config::loader::{find_config_from, load_config};
-use crate::gif::render_gif;
+use crate::config::LimitsConfig;
+use crate::cursor::{hotspot_from_origin, save_ani, save_cur};
+use crate::flipbook::{render_flipbook, save_pdf, FlipbookConfig};
+use crate::gif::{render_gif_with_durations, GifOptions};
 use crate::include::{is_include_ref, parse_include_ref, resolve_include_with_detection};
-use crate::models::{Animation, Composition, PaletteRef, Sprite, TtpObject};
-use crate::output::{generate_output_path, save_png, scale_image};
-use crate::palette_cycle::{generate_cycle_frames, get_cycle_duration};
+use crate::limits::check_object;
+use crate::locale::apply_locale;
+use crate::material::{MapKind, MaterialPalette};
+use crate::models::{
+    Animation, Composition, FrameTag, NineSlice, PaletteRef, PlaybackDirection, Role, Sequence,
+    Sprite, SpriteMetadata, Theme, TtpObject,
+};
+use crate::ora::{composite_op, save_ora, OraLayer};
+use crate::output::{
+    generate_output_path, input_stem, is_stdio, open_input, save_indexed_png_with_text, save_png,
+    save_png_with_text, scale_image, AlphaOptions, FrameOptions, OutputError,
+};
+use crate::overlay::{draw_overlays, Guides, Overlay};
+use crate::palette_cycle::{
+    cycle_strip_rows, generate_cycle_frames, get_cycle_duration, render_cycle_strip,
+};
 use crate::parser::parse_stream;
+use crate::provenance::Provenance;
 use crate::registry::{PaletteRegistry, PaletteSource, ResolvedPalette, SpriteRegistry};
-use crate::renderer::{render_resolved, render_sprite};
-use crate::spritesheet::render_spritesheet;
+use crate::renderer::{
+    apply_opacity, crop_image, render_resolved, render_resolved_layers, render_resolved_region,
+    render_sprite, Rect,
+};
+use crate::selection::Selection;
+use crate::sequence::{apply_crossfades, flatten_sequence, Crossfade};
+use crate::spritesheet::{align_frames, pivot_layout, render_spritesheet};
+use crate::stack::{render_stack, render_stack_rotation, StackOptions};
 use crate::suggest::{format_suggestion, suggest};
+use crate::warning::{SourceMap, Warning, WarningCategory, Warnings};
 
-use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
+use super::{print_saved, EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
 
 /// Execute the render command
 #[allow(clippy::too_many_arguments)]
@@ -48,7 +73,38 @@ pub fn run_render(
     _no_semantic_aa: bool,
     _gradient_shadows: bool,
     no_project: bool,
+    tag_filter: Option<&str>,
+    stack: &[String],
+    stack_spacing: u32,
+    stack_angle: f64,
+    stack_frames: u32,
+    maps: &[MapKind],
+    premultiply_alpha: bool,
+    matte_arg: Option<&str>,
+    split_layers: bool,
+    show_layers: &[String],
+    hide_layers: &[String],
+    selection: &Selection,
+    theme: Option<&str>,
+    locale: Option<&str>,
+    metadata: bool,
+    flipbook: FlipbookConfig,
+    cycle_timeline: bool,
+    crop: Option<Rect>,
+    indexed: bool,
+    framing: FrameOptions,
+    overlays: &[Overlay],
+    gif: GifOptions,
 ) -> ExitCode {
+    let matte = match matte_arg.map(parse_color).transpose() {
+        Ok(matte) => matte,
+        Err(e) => {
+            eprintln!("Error: Invalid --matte color '{}': {}", matte_arg.unwrap_or_default(), e);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    };
+    let alpha = AlphaOptions { premultiply: premultiply_alpha, matte };
+
     // Parse nine-slice target size if provided
     let nine_slice_size = if let Some(size_str) = nine_slice_arg {
         let parts: Vec<&str> = size_str.split('x').collect();
@@ -73,16 +129,120 @@ pub fn run_render(
         None
     };
 
+    // Cropping applies to plain sprite and composition PNGs
+    if crop.is_some() {
+        let unsupported = if gif_output || spritesheet_output {
+            Some("animations".to_string())
+        } else if let Some(fmt) = format {
+            Some(format!("--format {}", fmt))
+        } else if !stack.is_empty() {
+            Some("--stack".to_string())
+        } else if !maps.is_empty() {
+            Some("--maps".to_string())
+        } else if split_layers {
+            Some("--split-layers".to_string())
+        } else {
+            None
+        };
+        if let Some(option) = unsupported {
+            eprintln!("Error: --crop cannot be used with {}", option);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    }
+
+    // Framing applies to plain sprite and composition PNGs
+    if !framing.is_identity() {
+        let unsupported = if gif_output || spritesheet_output {
+            Some("animations".to_string())
+        } else if let Some(fmt) = format {
+            Some(format!("--format {}", fmt))
+        } else if !stack.is_empty() {
+            Some("--stack".to_string())
+        } else if !maps.is_empty() {
+            Some("--maps".to_string())
+        } else if split_layers {
+            Some("--split-layers".to_string())
+        } else {
+            None
+        };
+        if let Some(option) = unsupported {
+            eprintln!("Error: --background, --margin and --canvas cannot be used with {}", option);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    }
+
+    // Overlays apply to plain sprite and composition PNGs
+    if !overlays.is_empty() {
+        let unsupported = if gif_output || spritesheet_output {
+            Some("animations".to_string())
+        } else if let Some(fmt) = format {
+            Some(format!("--format {}", fmt))
+        } else if !stack.is_empty() {
+            Some("--stack".to_string())
+        } else if !maps.is_empty() {
+            Some("--maps".to_string())
+        } else if split_layers {
+            Some("--split-layers".to_string())
+        } else {
+            None
+        };
+        if let Some(option) = unsupported {
+            eprintln!("Error: --overlay cannot be used with {}", option);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    }
+
+    // Indexed output applies to plain sprite and composition PNGs
+    if indexed {
+        let unsupported = if gif_output || spritesheet_output {
+            Some("animations".to_string())
+        } else if let Some(fmt) = format {
+            Some(format!("--format {}", fmt))
+        } else if !stack.is_empty() {
+            Some("--stack".to_string())
+        } else {
+            None
+        };
+        if let Some(option) = unsupported {
+            eprintln!("Error: --indexed cannot be used with {}", option);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    }
+
+    // Stdout takes exactly one PNG or GIF
+    if output.is_some_and(is_stdio) {
+        let unsupported = if let Some(fmt) = format {
+            Some(format!("--format {}", fmt))
+        } else if !maps.is_empty() {
+            Some("--maps".to_string())
+        } else if split_layers {
+            Some("--split-layers".to_string())
+        } else {
+            None
+        };
+        if let Some(option) = unsupported {
+            eprintln!("Error: {} cannot be written to stdout", option);
+            return ExitCode::from(EXIT_INVALID_ARGS);
+        }
+    }
+
     // Auto-detect project context from pxl.toml in parent directories
+    let mut limits = LimitsConfig::default();
     let project_registry = if no_project {
         None
     } else {
-        // Canonicalize the input path so parent-dir walking works from the file's real location
-        let input_abs = std::fs::canonicalize(input).unwrap_or_else(|_| input.clone());
-        let start_dir = input_abs.parent().unwrap_or(std::path::Path::new(".")).to_path_buf();
+        // Canonicalize the input path so parent-dir walking works from the file's real
+        // location; stdin input looks from the working directory
+        let start_dir = if is_stdio(input) {
+            PathBuf::from(".")
+        } else {
+            let input_abs = std::fs::canonicalize(input).unwrap_or_else(|_| input.clone());
+            input_abs.parent().unwrap_or(std::path::Path::new(".")).to_path_buf()
+        };
         if let Some(config_path) = find_config_from(start_dir) {
             match load_config(Some(&config_path)) {
                 Ok(config) => {
+                    limits = config.limits.clone();
                     let project_root = config_path.parent().unwrap();
                     let src_dir = project_root.join(&config.project.src);
                     if src_dir.exists() {
@@ -132,9 +292,9 @@ pub fn run_render(
         }
     };
 
-    // Open input file
-    let file = match File::open(input) {
-        Ok(f) => f,
+    // Open input file (or stdin)
+    let reader = match open_input(input) {
+        Ok(reader) => reader,
         Err(e) => {
             eprintln!("Error: Cannot open input file '{}': {}", input.display(), e);
             return ExitCode::from(EXIT_INVALID_ARGS);
@@ -142,16 +302,14 @@ pub fn run_render(
     };
 
     // Parse JSONL stream
-    let reader = BufReader::new(file);
     let parse_result = parse_stream(reader);
 
-    // Collect all warnings
-    let mut all_warnings: Vec<String> = Vec::new();
+    // Collect all warnings, pointing each at the line defining its object
+    let file = (!is_stdio(input)).then_some(input.as_path());
+    let mut all_warnings = Warnings::new(SourceMap::new(file, &parse_result));
 
     // Add parse warnings
-    for warning in &parse_result.warnings {
-        all_warnings.push(format!("line {}: {}", warning.line, warning.message));
-    }
+    all_warnings.extend(parse_result.warnings.iter().cloned());
 
     // In strict mode, parse warnings are fatal
     if strict && !parse_result.warnings.is_empty() {
@@ -167,8 +325,21 @@ pub fn run_render(
     let mut sprites_by_name: HashMap<String, Sprite> = HashMap::new();
     let mut animations_by_name: HashMap<String, Animation> = HashMap::new();
     let mut compositions_by_name: HashMap<String, Composition> = HashMap::new();
+    let mut sequences: Vec<Sequence> = Vec::new();
+    let mut themes_by_name: HashMap<String, Theme> = HashMap::new();
+
+    // Locale substitutions swap objects before anything is registered
+    let mut objects = parse_result.objects;
+    if let Some(locale) = locale {
+        all_warnings.extend(apply_locale(&mut objects, locale));
+    }
 
-    for obj in parse_result.objects {
+    for obj in objects {
+        // Guardrails are checked before anything is allocated for the object
+        if let Err(e) = check_object(&obj, &limits) {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
         match obj {
             TtpObject::Palette(palette) => {
                 local_palette_registry.register(palette);
@@ -220,6 +391,10 @@ pub fn run_render(
                 // Register variant with sprite registry for transform resolution
                 local_sprite_registry.register_variant(variant);
             }
+            TtpObject::Sequence(sequence) => {
+                // Flattened into animations once every animation is known
+                sequences.push(sequence);
+            }
             TtpObject::Particle(_) => {
                 // Particle systems are runtime constructs, not rendered statically
             }
@@ -233,12 +408,108 @@ pub fn run_render(
             TtpObject::Import(_) => {
                 // Import declarations are resolved during loading
             }
+            TtpObject::Theme(theme) => {
+                themes_by_name.insert(theme.name.clone(), theme);
+            }
+            TtpObject::Stamp(_) => {
+                // Stamps are drawn into sprite regions during parsing
+            }
+            TtpObject::RandomSet(_) => {
+                // Random sets are expanded into sprites by `pxl expand`
+            }
+        }
+    }
+
+    // Sequences render as ordinary animations; crossfades are applied to the frames
+    let mut sequence_crossfades: HashMap<String, Vec<Crossfade>> = HashMap::new();
+    for sequence in &sequences {
+        if animations_by_name.contains_key(&sequence.name) {
+            all_warnings.push(format!(
+                "Sequence '{}' has the same name as an animation, skipping",
+                sequence.name
+            ));
+            continue;
+        }
+        match flatten_sequence(sequence, &animations_by_name) {
+            Ok(flat) => {
+                sequence_crossfades.insert(sequence.name.clone(), flat.crossfades);
+                animations_by_name.insert(sequence.name.clone(), flat.animation);
+            }
+            Err(e) => all_warnings.push(e),
+        }
+    }
+
+    // Layer toggles: shown first, so --hide-layer wins when both match
+    for (globs, hidden, flag) in
+        [(show_layers, false, "--show-layer"), (hide_layers, true, "--hide-layer")]
+    {
+        for glob in globs {
+            let pattern = match glob::Pattern::new(glob) {
+                Ok(pattern) => pattern,
+                Err(e) => {
+                    eprintln!("Error: Invalid {} pattern '{}': {}", flag, glob, e);
+                    return ExitCode::from(EXIT_INVALID_ARGS);
+                }
+            };
+            let matched: usize = compositions_by_name
+                .values_mut()
+                .map(|comp| comp.set_hidden_matching(&pattern, hidden))
+                .sum();
+            if matched == 0 {
+                all_warnings
+                    .push(format!("{} '{}' matched no composition layers or groups", flag, glob));
+            }
+        }
+    }
+
+    // --only/--exclude pick what gets rendered; unselected sprites and
+    // compositions still resolve as animation frames and composition layers
+    let all_sprites = sprites_by_name.clone();
+    let all_compositions = compositions_by_name.clone();
+    sprites_by_name.retain(|name, s| selection.includes("sprite", name, &s.labels));
+    compositions_by_name.retain(|name, c| selection.includes("composition", name, &c.labels));
+    animations_by_name.retain(|name, a| {
+        let kind = if sequence_crossfades.contains_key(name) { "sequence" } else { "animation" };
+        selection.includes(kind, name, &a.labels)
+    });
+
+    if strict && !all_warnings.is_empty() {
+        for warning in &all_warnings {
+            eprintln!("Error: {}", warning);
         }
+        return ExitCode::from(EXIT_ERROR);
     }
 
     // Select registries: project-wide (two-pass) or file-local (single-pass)
     let registry =
         project_registry.as_ref().map(|r| &r.palettes).unwrap_or(&local_palette_registry);
+
+    // Themes swap palettes for everything rendered below
+    let themed_registry;
+    let registry = match theme {
+        Some(theme_name) => {
+            let theme_def = themes_by_name
+                .get(theme_name)
+                .or_else(|| project_registry.as_ref().and_then(|r| r.theme(theme_name)));
+            let Some(theme_def) = theme_def else {
+                eprintln!("Error: Theme '{}' not found", theme_name);
+                return ExitCode::from(EXIT_INVALID_ARGS);
+            };
+            let mut swapped = registry.clone();
+            for missing in swapped.apply_theme(theme_def) {
+                let msg =
+                    format!("Theme '{}' replacement palette '{}' not found", theme_name, missing);
+                if strict {
+                    eprintln!("Error: {}", msg);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+                all_warnings.push(msg);
+            }
+            themed_registry = swapped;
+            &themed_registry
+        }
+        None => registry,
+    };
     let sprite_registry =
         project_registry.as_ref().map(|r| &r.sprites).unwrap_or(&local_sprite_registry);
 
@@ -248,29 +519,134 @@ pub fn run_render(
     // Track visited files for circular include detection
     let mut include_visited: HashSet<PathBuf> = HashSet::new();
 
+    // Lighting maps follow sprite pixels, so only sprite and atlas output carry them
+    if !maps.is_empty() && (!stack.is_empty() || gif_output || spritesheet_output) {
+        all_warnings.push("--maps is only supported for sprite and atlas output, ignoring");
+    }
+
+    // Handle sprite stack rendering (--stack)
+    if !stack.is_empty() {
+        return run_stack_render(
+            input,
+            output,
+            stack,
+            &StackOptions { spacing: stack_spacing, angle: stack_angle },
+            stack_frames,
+            &all_sprites,
+            &all_compositions,
+            sprite_registry,
+            registry,
+            input_dir,
+            &mut include_visited,
+            &mut all_warnings,
+            strict,
+            scale,
+            alpha,
+            gif_output.then_some(gif),
+        );
+    }
+
     // Handle animation rendering (--gif or --spritesheet)
     if gif_output || spritesheet_output {
         return run_animation_render(
             input,
             output,
             &animations_by_name,
-            &sprites_by_name,
-            &compositions_by_name,
+            &sequence_crossfades,
+            &all_sprites,
+            &all_compositions,
             sprite_registry,
             registry,
             input_dir,
             &mut include_visited,
             &mut all_warnings,
             strict,
+            limits.max_frames,
             scale,
-            gif_output,
+            alpha,
+            metadata,
+            if gif_output { AnimationOutput::Gif(gif) } else { AnimationOutput::Spritesheet },
             animation_filter,
+            tag_filter,
         );
     }
 
-    // Handle atlas format rendering (--format atlas)
+    // Handle atlas and cursor format rendering (--format atlas, cur, ani)
     if let Some(fmt) = format {
-        if fmt.starts_with("atlas") {
+        if fmt == "ani" || fmt == "flipbook" || fmt == "cycle-strip" {
+            return run_animation_render(
+                input,
+                output,
+                &animations_by_name,
+                &sequence_crossfades,
+                &all_sprites,
+                &all_compositions,
+                sprite_registry,
+                registry,
+                input_dir,
+                &mut include_visited,
+                &mut all_warnings,
+                strict,
+                limits.max_frames,
+                scale,
+                alpha,
+                metadata,
+                match fmt {
+                    "ani" => AnimationOutput::Cursor,
+                    "flipbook" => AnimationOutput::Flipbook(flipbook),
+                    _ => AnimationOutput::CycleStrip { timeline: cycle_timeline },
+                },
+                animation_filter,
+                tag_filter,
+            );
+        } else if fmt == "cur" {
+            return run_cursor_render(
+                input,
+                output,
+                sprite_filter,
+                &sprites_by_name,
+                registry,
+                input_dir,
+                &mut include_visited,
+                &mut all_warnings,
+                strict,
+                scale,
+                alpha,
+            );
+        } else if fmt == "ora" {
+            return run_ora_render(
+                input,
+                output,
+                sprite_filter,
+                composition_filter,
+                &sprites_by_name,
+                &compositions_by_name,
+                sprite_registry,
+                registry,
+                input_dir,
+                &mut include_visited,
+                &mut all_warnings,
+                strict,
+                scale,
+                alpha,
+            );
+        } else if fmt == "web" {
+            return run_web_render(
+                input,
+                output,
+                composition_filter,
+                &sprites_by_name,
+                &compositions_by_name,
+                sprite_registry,
+                registry,
+                input_dir,
+                &mut include_visited,
+                &mut all_warnings,
+                strict,
+                scale,
+                alpha,
+            );
+        } else if fmt.starts_with("atlas") {
             return run_atlas_render(
                 input,
                 output,
@@ -283,13 +659,17 @@ pub fn run_render(
                 &mut all_warnings,
                 strict,
                 scale,
+                alpha,
                 fmt,
                 max_size_arg,
                 padding,
                 power_of_two,
+                animation_filter,
+                tag_filter,
+                maps,
             );
         } else {
-            eprintln!("Error: Unknown format '{}'. Supported: atlas, atlas-aseprite, atlas-godot, atlas-unity, atlas-libgdx", fmt);
+            eprintln!("Error: Unknown format '{}'. Supported: atlas, atlas-aseprite, atlas-godot, atlas-unity, atlas-libgdx, cur, ani, ora, web, flipbook, cycle-strip", fmt);
             return ExitCode::from(EXIT_INVALID_ARGS);
         }
     }
@@ -301,7 +681,7 @@ pub fn run_render(
             output,
             comp_name,
             &compositions_by_name,
-            &sprites_by_name,
+            &all_sprites,
             sprite_registry,
             registry,
             input_dir,
@@ -309,6 +689,12 @@ pub fn run_render(
             &mut all_warnings,
             strict,
             scale,
+            alpha,
+            metadata,
+            crop,
+            indexed,
+            framing,
+            overlays,
         );
     }
 
@@ -334,12 +720,26 @@ pub fn run_render(
 
     // Check if we have anything to render
     if sprites.is_empty() && compositions_by_name.is_empty() {
+        if !selection.is_empty() {
+            eprintln!("Error: No sprites or compositions match --only/--exclude");
+            return ExitCode::from(EXIT_ERROR);
+        }
         eprintln!("Error: No sprites or compositions found in input file");
         return ExitCode::from(EXIT_ERROR);
     }
 
     let is_single_output = sprites.len() == 1 && compositions_by_name.is_empty();
 
+    let output_count =
+        sprites.len() + if render_compositions { compositions_by_name.len() } else { 0 };
+    if output.is_some_and(is_stdio) && output_count > 1 {
+        eprintln!(
+            "Error: Writing to stdout needs exactly one image, but {} would be rendered; select one with --sprite or --composition",
+            output_count
+        );
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
     // Render each sprite
     if render_sprites {
         for sprite in &sprites {
@@ -371,7 +771,10 @@ pub fn run_render(
                             eprintln!("Error: sprite '{}': {}", sprite.name, e);
                             return ExitCode::from(EXIT_ERROR);
                         }
-                        all_warnings.push(format!("sprite '{}': {}", sprite.name, e));
+                        all_warnings.push(
+                            Warning::new(WarningCategory::Palette, e.to_string())
+                                .for_object("sprite", &sprite.name),
+                        );
                         std::collections::HashMap::new()
                     }
                 }
@@ -380,8 +783,9 @@ pub fn run_render(
                 let resolved = match sprite_registry.resolve(&sprite.name, registry, strict) {
                     Ok(r) => {
                         for warning in &r.warnings {
-                            all_warnings
-                                .push(format!("sprite '{}': {}", sprite.name, warning.message));
+                            all_warnings.push(
+                                Warning::from(warning.clone()).for_object("sprite", &sprite.name),
+                            );
                         }
                         r
                     }
@@ -390,7 +794,10 @@ pub fn run_render(
                             eprintln!("Error: sprite '{}': {}", sprite.name, e);
                             return ExitCode::from(EXIT_ERROR);
                         }
-                        all_warnings.push(format!("sprite '{}': {}", sprite.name, e));
+                        all_warnings.push(
+                            Warning::new(WarningCategory::Sprite, e.to_string())
+                                .for_object("sprite", &sprite.name),
+                        );
                         continue;
                     }
                 };
@@ -399,14 +806,14 @@ pub fn run_render(
 
             // Get regions from resolved source if sprite has a source reference
             // This is critical for derived sprites that reference a regions-based source
-            let resolved_regions = if sprite.source.is_some() {
+            let (resolved_regions, resolved_layers, opacity) = if sprite.source.is_some() {
                 // Need to re-resolve to get the regions (palette was already extracted above)
                 match sprite_registry.resolve(&sprite.name, registry, false) {
-                    Ok(r) => r.regions,
-                    Err(_) => sprite.regions.clone(),
+                    Ok(r) => (r.regions, r.layers, r.opacity),
+                    Err(_) => (sprite.regions.clone(), sprite.layers.clone(), sprite.opacity),
                 }
             } else {
-                sprite.regions.clone()
+                (sprite.regions.clone(), sprite.layers.clone(), sprite.opacity)
             };
 
             // Create resolved sprite for rendering with correct regions
@@ -427,71 +834,66 @@ pub fn run_render(
                 warnings: vec![],
                 nine_slice: sprite.nine_slice.clone(),
                 regions: resolved_regions,
+                layers: resolved_layers,
+                opacity,
             };
 
-            // Render the resolved sprite
-            let (mut image, render_warnings) = render_resolved(&render_sprite_data);
-
-            // Apply transforms from sprite.transform if present
-            if let Some(ref transform_specs) = sprite.transform {
-                use crate::models::TransformSpec;
-                use crate::transforms::{apply_image_transform, parse_transform_str};
-
-                for spec in transform_specs {
-                    let transform_result = match spec {
-                        TransformSpec::String(s) => parse_transform_str(s),
-                        TransformSpec::Object { op, params } => {
-                            // Convert object to JSON and parse
-                            let mut obj = serde_json::Map::new();
-                            obj.insert("op".to_string(), serde_json::Value::String(op.clone()));
-                            for (k, v) in params {
-                                obj.insert(k.clone(), v.clone());
-                            }
-                            crate::transforms::parse_transform_value(&serde_json::Value::Object(
-                                obj,
-                            ))
-                        }
-                    };
-
-                    match transform_result {
-                        Ok(transform) => {
-                            // Skip animation transforms (they don't apply to images)
-                            if crate::transforms::is_animation_transform(&transform) {
-                                continue;
-                            }
-                            match apply_image_transform(&image, &transform, Some(&final_palette)) {
-                                Ok(transformed) => image = transformed,
-                                Err(e) => {
-                                    let msg =
-                                        format!("sprite '{}': transform error: {}", sprite.name, e);
-                                    if strict {
-                                        eprintln!("Error: {}", msg);
-                                        return ExitCode::from(EXIT_ERROR);
-                                    }
-                                    all_warnings.push(msg);
-                                }
-                            }
-                        }
+            // Render the resolved sprite; transforms and nine-slice need the
+            // whole image, otherwise only the cropped part is rasterized
+            let crop_whole = sprite.transform.is_some() || nine_slice_size.is_some();
+            let (mut image, render_warnings) = match crop {
+                Some(rect) if !crop_whole => {
+                    match render_resolved_region(&render_sprite_data, rect) {
+                        Ok(rendered) => rendered,
                         Err(e) => {
-                            let msg = format!("sprite '{}': invalid transform: {}", sprite.name, e);
-                            if strict {
-                                eprintln!("Error: {}", msg);
-                                return ExitCode::from(EXIT_ERROR);
-                            }
-                            all_warnings.push(msg);
+                            eprintln!("Error: sprite '{}': {}", sprite.name, e);
+                            return ExitCode::from(EXIT_ERROR);
                         }
                     }
                 }
-            }
+                _ => render_resolved(&render_sprite_data),
+            };
+
+            // Separate layer images skip sprite transforms and nine-slice
+            let layer_images: Vec<(String, image::RgbaImage)> = if split_layers {
+                render_resolved_layers(&render_sprite_data)
+                    .map(|(layers, _)| layers)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(name, mut layer)| {
+                        apply_opacity(&mut layer, render_sprite_data.opacity);
+                        (name, alpha.apply(scale_image(layer, scale)))
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            // Apply transforms from sprite.transform, remapping the nine-slice to match
+            let TransformedSprite {
+                image: transformed,
+                nine_slice,
+                metadata: transformed_metadata,
+            } = match apply_sprite_transforms(
+                sprite,
+                image,
+                &final_palette,
+                &mut all_warnings,
+                strict,
+            ) {
+                Ok(transformed) => transformed,
+                Err(code) => return code,
+            };
+            image = transformed;
 
             // Apply nine-slice rendering if requested
             if let Some((target_w, target_h)) = nine_slice_size {
-                if let Some(ref nine_slice) = sprite.nine_slice {
+                if let Some(ref nine_slice) = nine_slice {
                     let (ns_image, ns_warnings) =
                         crate::renderer::render_nine_slice(&image, nine_slice, target_w, target_h);
                     image = ns_image;
                     for warning in ns_warnings {
-                        all_warnings.push(format!("sprite '{}': {}", sprite.name, warning.message));
+                        all_warnings.push(warning.for_object("sprite", &sprite.name));
                     }
                 } else {
                     eprintln!(
@@ -501,12 +903,51 @@ pub fn run_render(
                 }
             }
 
-            // Apply scaling if requested
-            let image = scale_image(image, scale);
+            if let Some(rect) = crop.filter(|_| crop_whole) {
+                image = match crop_image(&image, rect) {
+                    Ok(cropped) => cropped,
+                    Err(e) => {
+                        eprintln!("Error: sprite '{}': {}", sprite.name, e);
+                        return ExitCode::from(EXIT_ERROR);
+                    }
+                };
+            }
+
+            // Lighting maps are derived from unscaled pixels so normals follow the art
+            let map_images: Vec<(MapKind, image::RgbaImage)> = if maps.is_empty() {
+                Vec::new()
+            } else {
+                let materials = MaterialPalette::from_regions(
+                    &render_sprite_data.regions.clone().unwrap_or_default(),
+                    &final_palette,
+                    palette_roles(&sprite.palette, registry),
+                );
+                maps.iter()
+                    .map(|&kind| (kind, scale_image(materials.render_map(&image, kind), scale)))
+                    .collect()
+            };
+
+            // Apply scaling, overlays, framing and alpha handling if requested
+            let mut image = scale_image(image, scale);
+            if !overlays.is_empty() {
+                let guides = Guides::for_sprite(transformed_metadata.as_ref(), nine_slice.as_ref());
+                let guides = match crop {
+                    Some(rect) => guides.cropped(rect),
+                    None => guides,
+                };
+                draw_overlays(&mut image, scale as u32, overlays, &guides);
+            }
+            let image = match framing.apply(image) {
+                Ok(image) => alpha.apply(image),
+                Err(e) => {
+                    eprintln!("Error: sprite '{}': {}", sprite.name, e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            };
 
             // Collect render warnings
             for warning in render_warnings {
-                all_warnings.push(format!("sprite '{}': {}", sprite.name, warning.message));
+                all_warnings.push(warning.for_object("sprite", &sprite.name));
             }
 
             // In strict mode, render warnings are fatal
@@ -520,23 +961,53 @@ pub fn run_render(
             // Generate output path
             let output_path = generate_output_path(input, &sprite.name, output, is_single_output);
 
-            // Save PNG
-            if let Err(e) = save_png(&image, &output_path) {
+            // Save PNG, tagged with where it came from
+            let text = provenance_text(
+                metadata,
+                Provenance::new(input, &sprite.name, sprite).with_palette(palette_name(sprite)),
+            );
+            let palette = indexed.then(|| png_palette(&final_palette));
+            if let Err(e) =
+                save_output_png(&image, &output_path, &text, palette.as_deref(), &mut all_warnings)
+            {
                 eprintln!("Error: Failed to save '{}': {}", output_path.display(), e);
                 return ExitCode::from(EXIT_ERROR);
             }
 
-            println!("Saved: {}", output_path.display());
+            print_saved(&output_path);
+
+            for (kind, map_image) in &map_images {
+                let map_path = map_output_path(&output_path, *kind);
+                if let Err(e) = save_png(map_image, &map_path) {
+                    eprintln!("Error: Failed to save '{}': {}", map_path.display(), e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+                println!("Saved: {}", map_path.display());
+            }
+
+            for (layer_name, layer_image) in &layer_images {
+                let layer_path = suffixed_output_path(&output_path, layer_name);
+                if let Err(e) = save_png(layer_image, &layer_path) {
+                    eprintln!("Error: Failed to save '{}': {}", layer_path.display(), e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+                println!("Saved: {}", layer_path.display());
+            }
         }
     }
 
     // Render compositions (when no --sprite filter is active)
     if render_compositions {
         for (comp_name, comp) in &compositions_by_name {
+            if !maps.is_empty() {
+                all_warnings
+                    .push(format!("--maps is not supported for composition '{}'", comp_name));
+            }
+
             // Render the composition with sprite registry for transform support (TRF-9)
             let result = render_composition_to_image(
                 comp,
-                &sprites_by_name,
+                &all_sprites,
                 sprite_registry,
                 registry,
                 input_dir,
@@ -546,12 +1017,26 @@ pub fn run_render(
             );
 
             let image = match result {
-                Ok(img) => img,
+                Ok(img) => match crop.map(|rect| crop_image(&img, rect)) {
+                    Some(Ok(cropped)) => cropped,
+                    Some(Err(e)) => {
+                        eprintln!("Error: composition '{}': {}", comp_name, e);
+                        return ExitCode::from(EXIT_ERROR);
+                    }
+                    None => img,
+                },
                 Err(code) => return code,
             };
 
-            // Apply scaling if requested
-            let image = scale_image(image, scale);
+            // Apply scaling, overlays, framing and alpha handling if requested
+            let image = overlay_composition(scale_image(image, scale), scale, comp, crop, overlays);
+            let image = match framing.apply(image) {
+                Ok(image) => alpha.apply(image),
+                Err(e) => {
+                    eprintln!("Error: composition '{}': {}", comp_name, e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            };
 
             // In strict mode, check for accumulated warnings
             if strict && !all_warnings.is_empty() {
@@ -565,13 +1050,17 @@ pub fn run_render(
             let is_single = compositions_by_name.len() == 1 && sprites.is_empty();
             let output_path = generate_output_path(input, comp_name, output, is_single);
 
-            // Save PNG
-            if let Err(e) = save_png(&image, &output_path) {
+            // Save PNG, tagged with where it came from
+            let text = provenance_text(metadata, Provenance::new(input, comp_name, comp));
+            let palette = indexed.then(Vec::new);
+            if let Err(e) =
+                save_output_png(&image, &output_path, &text, palette.as_deref(), &mut all_warnings)
+            {
                 eprintln!("Error: Failed to save '{}': {}", output_path.display(), e);
                 return ExitCode::from(EXIT_ERROR);
             }
 
-            println!("Saved: {}", output_path.display());
+            print_saved(&output_path);
         }
     }
 
@@ -583,6 +1072,75 @@ pub fn run_render(
     ExitCode::from(EXIT_SUCCESS)
 }
 
+/// Save a rendered PNG, as indexed PNG-8 when given a palette (`--indexed`).
+///
+/// Images with more than 256 colors are saved as RGBA with a warning.
+fn save_output_png(
+    image: &image::RgbaImage,
+    path: &std::path::Path,
+    text: &[(String, String)],
+    palette: Option<&[image::Rgba<u8>]>,
+    warnings: &mut Warnings,
+) -> Result<(), OutputError> {
+    let Some(palette) = palette else {
+        return save_png_with_text(image, path, text);
+    };
+    if !save_indexed_png_with_text(image, path, text, palette)? {
+        warnings.push(format!(
+            "'{}' uses more than 256 colors, saved as RGBA instead of indexed",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// PNG-8 palette for a resolved sprite palette: transparent colors first,
+/// then the rest by token name, the order `pxl fmt` writes palettes in.
+fn png_palette(colors: &HashMap<String, String>) -> Vec<image::Rgba<u8>> {
+    let mut entries: Vec<(&String, image::Rgba<u8>)> = colors
+        .iter()
+        .filter_map(|(token, color)| Some((token, parse_color(color).ok()?)))
+        .collect();
+    entries.sort_by_key(|(token, color)| (color[3] != 0, *token));
+    entries.into_iter().map(|(_, color)| color).collect()
+}
+
+/// Draw `--overlay` guides on a scaled composition image.
+fn overlay_composition(
+    mut image: image::RgbaImage,
+    scale: u8,
+    comp: &Composition,
+    crop: Option<Rect>,
+    overlays: &[Overlay],
+) -> image::RgbaImage {
+    if !overlays.is_empty() {
+        let guides = Guides::for_composition(comp);
+        let guides = match crop {
+            Some(rect) => guides.cropped(rect),
+            None => guides,
+        };
+        draw_overlays(&mut image, scale as u32, overlays, &guides);
+    }
+    image
+}
+
+/// Provenance text chunks for a rendered PNG, or none with `--no-metadata`.
+fn provenance_text(metadata: bool, provenance: Provenance) -> Vec<(String, String)> {
+    if metadata {
+        provenance.entries()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Name of a sprite's palette, unless it is inline.
+pub(super) fn palette_name(sprite: &Sprite) -> Option<&str> {
+    match &sprite.palette {
+        PaletteRef::Named(name) => Some(name),
+        PaletteRef::Inline(_) => None,
+    }
+}
+
 /// Render a specific composition
 /// TRF-9: Now uses SpriteRegistry for transform support
 #[allow(clippy::too_many_arguments)]
@@ -596,9 +1154,15 @@ fn run_composition_render(
     palette_registry: &PaletteRegistry,
     input_dir: &std::path::Path,
     include_visited: &mut HashSet<PathBuf>,
-    all_warnings: &mut Vec<String>,
+    all_warnings: &mut Warnings,
     strict: bool,
     scale: u8,
+    alpha: AlphaOptions,
+    metadata: bool,
+    crop: Option<Rect>,
+    indexed: bool,
+    framing: FrameOptions,
+    overlays: &[Overlay],
 ) -> ExitCode {
     // Find the composition
     let comp = match compositions.get(comp_name) {
@@ -626,12 +1190,26 @@ fn run_composition_render(
     );
 
     let image = match result {
-        Ok(img) => img,
+        Ok(img) => match crop.map(|rect| crop_image(&img, rect)) {
+            Some(Ok(cropped)) => cropped,
+            Some(Err(e)) => {
+                eprintln!("Error: composition '{}': {}", comp.name, e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+            None => img,
+        },
         Err(code) => return code,
     };
 
-    // Apply scaling if requested
-    let image = scale_image(image, scale);
+    // Apply scaling, overlays, framing and alpha handling if requested
+    let image = overlay_composition(scale_image(image, scale), scale, comp, crop, overlays);
+    let image = match framing.apply(image) {
+        Ok(image) => alpha.apply(image),
+        Err(e) => {
+            eprintln!("Error: composition '{}': {}", comp_name, e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
 
     // In strict mode, check for accumulated warnings
     if strict && !all_warnings.is_empty() {
@@ -644,13 +1222,15 @@ fn run_composition_render(
     // Generate output path
     let output_path = generate_output_path(input, comp_name, output, true);
 
-    // Save PNG
-    if let Err(e) = save_png(&image, &output_path) {
+    // Save PNG, tagged with where it came from
+    let text = provenance_text(metadata, Provenance::new(input, comp_name, comp));
+    let palette = indexed.then(Vec::new);
+    if let Err(e) = save_output_png(&image, &output_path, &text, palette.as_deref(), all_warnings) {
         eprintln!("Error: Failed to save '{}': {}", output_path.display(), e);
         return ExitCode::from(EXIT_ERROR);
     }
 
-    println!("Saved: {}", output_path.display());
+    print_saved(&output_path);
 
     // Print warnings to stderr (in lenient mode)
     for warning in all_warnings.iter() {
@@ -660,23 +1240,416 @@ fn run_composition_render(
     ExitCode::from(EXIT_SUCCESS)
 }
 
-/// Render a composition to an image buffer
-/// TRF-9: Now uses SpriteRegistry to resolve sprites with transforms applied
+/// Render sprites and compositions as layered OpenRaster images (--format ora)
+///
+/// Composition layers keep their blend mode and opacity, with the base sprite
+/// as the bottom layer; a sprite's `layers` (or the whole sprite, if it has
+/// none) become plain layers. Each file also carries the flattened image.
 #[allow(clippy::too_many_arguments)]
-fn render_composition_to_image(
-    comp: &Composition,
+fn run_ora_render(
+    input: &std::path::Path,
+    output: Option<&std::path::Path>,
+    sprite_filter: Option<&str>,
+    composition_filter: Option<&str>,
     sprites: &HashMap<String, Sprite>,
+    compositions: &HashMap<String, Composition>,
     sprite_registry: &SpriteRegistry,
     palette_registry: &PaletteRegistry,
     input_dir: &std::path::Path,
     include_visited: &mut HashSet<PathBuf>,
-    all_warnings: &mut Vec<String>,
+    all_warnings: &mut Warnings,
     strict: bool,
-) -> Result<image::RgbaImage, ExitCode> {
-    use image::RgbaImage;
-
-    // Collect all sprite names referenced by the composition
-    let mut required_sprites: std::collections::HashSet<String> = std::collections::HashSet::new();
+    scale: u8,
+    alpha: AlphaOptions,
+) -> ExitCode {
+    let mut selected_sprites: Vec<&Sprite> = match sprite_filter {
+        Some(name) => match sprites.get(name) {
+            Some(sprite) => vec![sprite],
+            None => {
+                eprintln!("Error: No sprite named '{}' found in input", name);
+                let names: Vec<&str> = sprites.keys().map(|s| s.as_str()).collect();
+                if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                    eprintln!("{}", suggestion);
+                }
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+        None if composition_filter.is_some() => Vec::new(),
+        None => sprites.values().collect(),
+    };
+    selected_sprites.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut selected_compositions: Vec<&Composition> = match composition_filter {
+        Some(name) => match compositions.get(name) {
+            Some(comp) => vec![comp],
+            None => {
+                eprintln!("Error: No composition named '{}' found in input", name);
+                let names: Vec<&str> = compositions.keys().map(|s| s.as_str()).collect();
+                if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                    eprintln!("{}", suggestion);
+                }
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+        None if sprite_filter.is_some() => Vec::new(),
+        None => compositions.values().collect(),
+    };
+    selected_compositions.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if selected_sprites.is_empty() && selected_compositions.is_empty() {
+        eprintln!("Error: No sprites or compositions found in input file");
+        return ExitCode::from(EXIT_ERROR);
+    }
+
+    let is_single = selected_sprites.len() + selected_compositions.len() == 1;
+    let finish = |image: image::RgbaImage| alpha.apply(scale_image(image, scale));
+    let mut documents: Vec<(String, Vec<OraLayer>, image::RgbaImage)> = Vec::new();
+
+    for sprite in selected_sprites {
+        let merged = match render_sprite_image(
+            sprite,
+            palette_registry,
+            input_dir,
+            include_visited,
+            all_warnings,
+            strict,
+        ) {
+            Ok(image) => image,
+            Err(code) => return code,
+        };
+        let layers = match sprite.layers.as_deref().filter(|l| !l.is_empty()) {
+            Some(layers) => {
+                // Palette and render warnings were already collected for the merged image
+                let palette = resolve_sprite_palette(
+                    sprite,
+                    palette_registry,
+                    input_dir,
+                    include_visited,
+                    &mut Warnings::default(),
+                    false,
+                )
+                .unwrap_or_default();
+                let (mut images, _) = crate::structured::render_layers(
+                    &sprite.name,
+                    sprite.size,
+                    sprite.regions.as_ref(),
+                    layers,
+                    &palette,
+                );
+                for (_, image) in &mut images {
+                    apply_opacity(image, sprite.opacity);
+                }
+                images
+            }
+            None => vec![(sprite.name.clone(), merged.clone())],
+        };
+        let layers = layers
+            .into_iter()
+            .map(|(name, image)| OraLayer {
+                name,
+                image: finish(image),
+                x: 0,
+                y: 0,
+                blend: crate::composition::BlendMode::Normal,
+                opacity: 1.0,
+            })
+            .collect();
+        documents.push((sprite.name.clone(), layers, finish(merged)));
+    }
+
+    for comp in selected_compositions {
+        let rendered_sprites = match render_composition_sprites(
+            comp,
+            sprites,
+            sprite_registry,
+            palette_registry,
+            input_dir,
+            include_visited,
+            all_warnings,
+            strict,
+        ) {
+            Ok(rendered) => rendered,
+            Err(code) => return code,
+        };
+        let merged = match render_composition(comp, &rendered_sprites, strict, None) {
+            Ok((mut image, _)) => {
+                lock_composition(
+                    comp,
+                    &mut image,
+                    &rendered_sprites,
+                    palette_registry,
+                    all_warnings,
+                );
+                image
+            }
+            Err(e) => {
+                eprintln!("Error: composition '{}': {}", comp.name, e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        let (layers, comp_warnings) =
+            match render_composition_layers(comp, &rendered_sprites, strict, None) {
+                Ok(result) => result,
+                Err(e) => {
+                    eprintln!("Error: composition '{}': {}", comp.name, e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            };
+        for warning in comp_warnings {
+            all_warnings.push(warning.for_object("composition", &comp.name));
+        }
+        let layers: Vec<OraLayer> = layers
+            .into_iter()
+            .map(|layer| {
+                if composite_op(layer.blend).is_none() {
+                    all_warnings.push(format!(
+                        "composition '{}': layer '{}': OpenRaster has no {} blend mode, using normal",
+                        comp.name,
+                        layer.name,
+                        format!("{:?}", layer.blend).to_lowercase()
+                    ));
+                }
+                OraLayer {
+                    name: layer.name,
+                    image: finish(layer.image),
+                    x: 0,
+                    y: 0,
+                    blend: layer.blend,
+                    opacity: layer.opacity,
+                }
+            })
+            .collect();
+        documents.push((comp.name.clone(), layers, finish(merged)));
+    }
+
+    if strict && !all_warnings.is_empty() {
+        for warning in all_warnings.iter() {
+            eprintln!("Error: {}", warning);
+        }
+        return ExitCode::from(EXIT_ERROR);
+    }
+
+    for (name, layers, merged) in &documents {
+        let output_path =
+            generate_output_path(input, name, output, is_single).with_extension("ora");
+        if let Err(e) = save_ora(layers, merged, &output_path) {
+            eprintln!("Error: Failed to save '{}': {}", output_path.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        print_saved(&output_path);
+    }
+
+    // Print warnings to stderr (in lenient mode)
+    for warning in all_warnings.iter() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+/// Render compositions as web bundles for an HTML canvas (--format web)
+///
+/// Each composition becomes a directory of per-layer PNGs, a `scene.json`
+/// manifest with offsets, blend modes, parallax and scrolling, and the
+/// `loader.js` that reassembles them (see [`crate::web`]).
+#[allow(clippy::too_many_arguments)]
+fn run_web_render(
+    input: &std::path::Path,
+    output: Option<&std::path::Path>,
+    composition_filter: Option<&str>,
+    sprites: &HashMap<String, Sprite>,
+    compositions: &HashMap<String, Composition>,
+    sprite_registry: &SpriteRegistry,
+    palette_registry: &PaletteRegistry,
+    input_dir: &std::path::Path,
+    include_visited: &mut HashSet<PathBuf>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+    scale: u8,
+    alpha: AlphaOptions,
+) -> ExitCode {
+    let mut selected: Vec<&Composition> = match composition_filter {
+        Some(name) => match compositions.get(name) {
+            Some(comp) => vec![comp],
+            None => {
+                eprintln!("Error: No composition named '{}' found in input", name);
+                let names: Vec<&str> = compositions.keys().map(|s| s.as_str()).collect();
+                if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                    eprintln!("{}", suggestion);
+                }
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+        None => compositions.values().collect(),
+    };
+    selected.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if selected.is_empty() {
+        eprintln!("Error: No compositions found in input file");
+        return ExitCode::from(EXIT_ERROR);
+    }
+
+    let is_single = selected.len() == 1;
+    let factor = f64::from(scale.max(1));
+    let mut bundles = Vec::new();
+    for comp in selected {
+        let rendered_sprites = match render_composition_sprites(
+            comp,
+            sprites,
+            sprite_registry,
+            palette_registry,
+            input_dir,
+            include_visited,
+            all_warnings,
+            strict,
+        ) {
+            Ok(rendered) => rendered,
+            Err(code) => return code,
+        };
+        let (layers, comp_warnings) =
+            match render_composition_layers(comp, &rendered_sprites, strict, None) {
+                Ok(result) => result,
+                Err(e) => {
+                    eprintln!("Error: composition '{}': {}", comp.name, e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            };
+        for warning in comp_warnings {
+            all_warnings.push(warning.for_object("composition", &comp.name));
+        }
+
+        let layers: Vec<_> = layers
+            .into_iter()
+            .map(|mut layer| {
+                if crate::web::canvas_composite_op(layer.blend).is_none() {
+                    all_warnings.push(format!(
+                        "composition '{}': layer '{}': canvas has no {} blend mode, using normal",
+                        comp.name,
+                        layer.name,
+                        format!("{:?}", layer.blend).to_lowercase()
+                    ));
+                }
+                layer.image = alpha.apply(scale_image(layer.image, scale));
+                layer.scroll = layer.scroll.map(|[x, y]| [x * factor, y * factor]);
+                layer
+            })
+            .collect();
+        // Exported layers span the canvas
+        let (width, height) = match (layers.first(), comp.size) {
+            (Some(layer), _) => layer.image.dimensions(),
+            (None, Some([w, h])) => (w * u32::from(scale), h * u32::from(scale)),
+            (None, None) => (0, 0),
+        };
+        bundles.push(crate::web::WebBundle::new(&comp.name, width, height, layers));
+    }
+
+    if strict && !all_warnings.is_empty() {
+        for warning in all_warnings.iter() {
+            eprintln!("Error: {}", warning);
+        }
+        return ExitCode::from(EXIT_ERROR);
+    }
+
+    for bundle in &bundles {
+        let dir = generate_output_path(input, &bundle.manifest.name, output, is_single)
+            .with_extension("");
+        if let Err(e) = bundle.save(&dir) {
+            eprintln!("Error: Failed to save '{}': {}", dir.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        print_saved(&dir.join("scene.json"));
+    }
+
+    // Print warnings to stderr (in lenient mode)
+    for warning in all_warnings.iter() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+/// Render a composition to an image buffer
+/// TRF-9: Now uses SpriteRegistry to resolve sprites with transforms applied
+#[allow(clippy::too_many_arguments)]
+fn render_composition_to_image(
+    comp: &Composition,
+    sprites: &HashMap<String, Sprite>,
+    sprite_registry: &SpriteRegistry,
+    palette_registry: &PaletteRegistry,
+    input_dir: &std::path::Path,
+    include_visited: &mut HashSet<PathBuf>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+) -> Result<image::RgbaImage, ExitCode> {
+    let rendered_sprites = render_composition_sprites(
+        comp,
+        sprites,
+        sprite_registry,
+        palette_registry,
+        input_dir,
+        include_visited,
+        all_warnings,
+        strict,
+    )?;
+
+    // Render the composition
+    // TODO(CSS-9): Pass variable registry when available from palette parsing
+    let result = render_composition(comp, &rendered_sprites, strict, None);
+
+    match result {
+        Ok((mut image, comp_warnings)) => {
+            // Collect composition warnings
+            for warning in comp_warnings {
+                all_warnings.push(warning.for_object("composition", &comp.name));
+            }
+            lock_composition(comp, &mut image, &rendered_sprites, palette_registry, all_warnings);
+            Ok(image)
+        }
+        Err(e) => {
+            eprintln!("Error: composition '{}': {}", comp.name, e);
+            Err(ExitCode::from(EXIT_ERROR))
+        }
+    }
+}
+
+/// Snap a rendered composition to its `palette_lock` palette, reporting how
+/// many pixels changed.
+fn lock_composition(
+    comp: &Composition,
+    image: &mut image::RgbaImage,
+    rendered_sprites: &HashMap<String, image::RgbaImage>,
+    palette_registry: &PaletteRegistry,
+    all_warnings: &mut Warnings,
+) {
+    let mut warnings = Vec::new();
+    let report = apply_palette_lock(comp, image, rendered_sprites, palette_registry, &mut warnings);
+    for warning in warnings {
+        all_warnings.push(warning.for_object("composition", &comp.name));
+    }
+    if let Some(PaletteLockReport { palette, pixels, snapped, .. }) = report {
+        eprintln!(
+            "Palette lock: composition '{}' snapped {} of {} pixels to {}",
+            comp.name, snapped, pixels, palette
+        );
+    }
+}
+
+/// Render every sprite a composition references, keyed by sprite name
+#[allow(clippy::too_many_arguments)]
+fn render_composition_sprites(
+    comp: &Composition,
+    sprites: &HashMap<String, Sprite>,
+    sprite_registry: &SpriteRegistry,
+    palette_registry: &PaletteRegistry,
+    input_dir: &std::path::Path,
+    include_visited: &mut HashSet<PathBuf>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+) -> Result<HashMap<String, image::RgbaImage>, ExitCode> {
+    use image::RgbaImage;
+
+    // Collect all sprite names referenced by the composition
+    let mut required_sprites: std::collections::HashSet<String> = std::collections::HashSet::new();
 
     // Add base sprite if specified
     if let Some(ref base_name) = comp.base {
@@ -684,7 +1657,12 @@ fn render_composition_to_image(
     }
 
     // Add sprites from the sprites map
-    for sprite_name in comp.sprites.values().flatten() {
+    for sprite_name in comp.sprite_names() {
+        required_sprites.insert(sprite_name.to_string());
+    }
+
+    // Add sprites used as layer masks
+    for sprite_name in comp.mask_sprites() {
         required_sprites.insert(sprite_name.clone());
     }
 
@@ -700,7 +1678,8 @@ fn render_composition_to_image(
             Ok(resolved) => {
                 // Collect any sprite warnings
                 for warning in &resolved.warnings {
-                    all_warnings.push(format!("sprite '{}': {}", sprite_name, warning.message));
+                    all_warnings
+                        .push(Warning::from(warning.clone()).for_object("sprite", sprite_name));
                 }
                 resolved
             }
@@ -737,7 +1716,10 @@ fn render_composition_to_image(
                                     eprintln!("Error: sprite '{}': {}", sprite_name, e);
                                     return Err(ExitCode::from(EXIT_ERROR));
                                 }
-                                all_warnings.push(format!("sprite '{}': {}", sprite_name, e));
+                                all_warnings.push(
+                                    Warning::new(WarningCategory::Palette, e.to_string())
+                                        .for_object("sprite", sprite_name),
+                                );
                                 std::collections::HashMap::new()
                             }
                         }
@@ -762,6 +1744,8 @@ fn render_composition_to_image(
             warnings: vec![],
             nine_slice: resolved_sprite.nine_slice.clone(),
             regions: resolved_sprite.regions.clone(),
+            layers: resolved_sprite.layers.clone(),
+            opacity: resolved_sprite.opacity,
         };
 
         // Render the resolved sprite (transforms already applied)
@@ -769,7 +1753,7 @@ fn render_composition_to_image(
 
         // Collect render warnings
         for warning in render_warnings {
-            all_warnings.push(format!("sprite '{}': {}", sprite_name, warning.message));
+            all_warnings.push(warning.for_object("sprite", sprite_name));
         }
 
         if strict && !all_warnings.is_empty() {
@@ -782,26 +1766,26 @@ fn render_composition_to_image(
         rendered_sprites.insert(sprite_name.clone(), image);
     }
 
-    // Render the composition
-    // TODO(CSS-9): Pass variable registry when available from palette parsing
-    let result = render_composition(comp, &rendered_sprites, strict, None);
+    Ok(rendered_sprites)
+}
 
-    match result {
-        Ok((image, comp_warnings)) => {
-            // Collect composition warnings
-            for warning in comp_warnings {
-                all_warnings.push(format!("composition '{}': {}", comp.name, warning.message));
-            }
-            Ok(image)
-        }
-        Err(e) => {
-            eprintln!("Error: composition '{}': {}", comp.name, e);
-            Err(ExitCode::from(EXIT_ERROR))
-        }
-    }
+/// Container written by [`run_animation_render`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnimationOutput {
+    Gif(GifOptions),
+    Spritesheet,
+    /// Animated Windows cursor (.ani)
+    Cursor,
+    /// Printable flip-book sheet (.pdf, or one .png per page)
+    Flipbook(FlipbookConfig),
+    /// Palette cycle steps laid out with their durations (.png)
+    CycleStrip {
+        timeline: bool,
+    },
 }
 
-/// Render an animation as GIF or spritesheet
+/// Render an animation as GIF, spritesheet, animated cursor, flip-book or
+/// palette cycle strip
 /// TRF-9: Now uses SpriteRegistry for transform support
 // TTP-9qjwr: Added compositions parameter to support compositions as animation frames
 #[allow(clippy::too_many_arguments)]
@@ -809,17 +1793,22 @@ fn run_animation_render(
     input: &std::path::Path,
     output: Option<&std::path::Path>,
     animations: &HashMap<String, Animation>,
+    sequence_crossfades: &HashMap<String, Vec<Crossfade>>,
     sprites: &HashMap<String, Sprite>,
     compositions: &HashMap<String, Composition>,
     sprite_registry: &SpriteRegistry,
     palette_registry: &PaletteRegistry,
     input_dir: &std::path::Path,
     include_visited: &mut HashSet<PathBuf>,
-    all_warnings: &mut Vec<String>,
+    all_warnings: &mut Warnings,
     strict: bool,
+    max_frames: usize,
     scale: u8,
-    gif_output: bool,
+    alpha: AlphaOptions,
+    metadata: bool,
+    output_kind: AnimationOutput,
     animation_filter: Option<&str>,
+    tag_filter: Option<&str>,
 ) -> ExitCode {
     // Find the animation to render
     let animation = if let Some(name) = animation_filter {
@@ -835,8 +1824,8 @@ fn run_animation_render(
             }
         }
     } else {
-        // Use the first animation found
-        match animations.values().next() {
+        // Use the first animation by name
+        match animations.values().min_by(|a, b| a.name.cmp(&b.name)) {
             Some(anim) => anim,
             None => {
                 eprintln!("Error: No animations found in input file");
@@ -876,11 +1865,36 @@ fn run_animation_render(
         all_warnings.push(warning_msg);
     }
 
+    // Per-frame timing from `durations` / `frame_metadata`
+    let per_frame_durations = match animation.frame_durations() {
+        Ok(durations) => durations,
+        Err(e) => {
+            if strict {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+            all_warnings.push(format!(
+                "{}; using {}ms for every frame",
+                e,
+                animation.duration_ms()
+            ));
+            vec![animation.duration_ms(); animation.frames.len()]
+        }
+    };
+
+    let is_palette_cycle = animation.has_palette_cycle() && animation.frames.len() == 1;
+    if matches!(output_kind, AnimationOutput::CycleStrip { .. }) && !is_palette_cycle {
+        eprintln!(
+            "Error: Animation '{}' has no palette_cycle on a single frame to lay out",
+            animation.name
+        );
+        return ExitCode::from(EXIT_ERROR);
+    }
+
     // Check if this is a palette-cycle animation
     // Palette cycling is used when animation has palette_cycle defined
-    let (frame_images, frame_duration) = if animation.has_palette_cycle()
-        && animation.frames.len() == 1
-    {
+    let mut cycle_strip = None;
+    let (frame_images, frame_durations, frame_pivots) = if is_palette_cycle {
         // Palette cycle mode: generate frames by rotating colors
         let frame_name = &animation.frames[0];
         let sprite = match sprites.get(frame_name) {
@@ -914,7 +1928,10 @@ fn run_animation_render(
                             eprintln!("Error: sprite '{}': {}", sprite.name, e);
                             return ExitCode::from(EXIT_ERROR);
                         }
-                        all_warnings.push(format!("sprite '{}': {}", sprite.name, e));
+                        all_warnings.push(
+                            Warning::new(WarningCategory::Palette, e.to_string())
+                                .for_object("sprite", &sprite.name),
+                        );
                         ResolvedPalette {
                             colors: std::collections::HashMap::new(),
                             source: PaletteSource::Fallback,
@@ -925,7 +1942,8 @@ fn run_animation_render(
             _ => match palette_registry.resolve(sprite, strict) {
                 Ok(result) => {
                     if let Some(warning) = result.warning {
-                        all_warnings.push(format!("sprite '{}': {}", sprite.name, warning.message));
+                        all_warnings
+                            .push(Warning::from(warning).for_object("sprite", &sprite.name));
                         if strict {
                             for warning in all_warnings.iter() {
                                 eprintln!("Error: {}", warning);
@@ -943,11 +1961,23 @@ fn run_animation_render(
         };
 
         // Generate palette-cycled frames
-        let (frames, cycle_warnings) = generate_cycle_frames(sprite, &resolved.colors, animation);
+        let (frames, mut cycle_warnings) =
+            generate_cycle_frames(sprite, &resolved.colors, animation, max_frames);
+
+        if let AnimationOutput::CycleStrip { timeline } = output_kind {
+            let (rows, strip_warnings) =
+                cycle_strip_rows(sprite, &resolved.colors, animation, timeline, max_frames);
+            cycle_warnings.retain(|w| !strip_warnings.contains(w));
+            cycle_warnings.extend(strip_warnings);
+            cycle_strip = Some(render_cycle_strip(&rows, scale as u32));
+        }
 
         // Collect warnings
         for warning in cycle_warnings {
-            all_warnings.push(format!("sprite '{}': {}", sprite.name, warning));
+            all_warnings.push(
+                Warning::new(WarningCategory::Animation, warning)
+                    .for_object("sprite", &sprite.name),
+            );
         }
 
         if strict && !all_warnings.is_empty() {
@@ -962,83 +1992,35 @@ fn run_animation_render(
 
         // Use cycle duration for GIF timing
         let duration = get_cycle_duration(animation);
+        let durations = vec![duration; scaled_frames.len()];
 
-        (scaled_frames, duration)
+        (scaled_frames, durations, Vec::new())
     } else {
         // Traditional frame-based animation
         // TTP-9qjwr: Now supports both sprites and compositions as frames
         let mut frame_images = Vec::new();
-        for frame_name in &animation.frames {
+        let mut frame_durations = Vec::new();
+        let mut frame_pivots = Vec::new();
+        for (frame_index, frame_name) in animation.frames.iter().enumerate() {
             // First try to get as sprite
             if let Some(sprite) = sprites.get(frame_name) {
-                // Resolve palette
-                let resolved = match &sprite.palette {
-                    PaletteRef::Named(name) if is_include_ref(name) => {
-                        let (include_path, palette_name) =
-                            parse_include_ref(name).expect("is_include_ref validated prefix");
-                        match resolve_include_with_detection(
-                            include_path,
-                            input_dir,
-                            include_visited,
-                            palette_name,
-                        ) {
-                            Ok(palette) => ResolvedPalette {
-                                colors: palette.colors,
-                                source: PaletteSource::Named(name.clone()),
-                            },
-                            Err(e) => {
-                                if strict {
-                                    eprintln!("Error: sprite '{}': {}", sprite.name, e);
-                                    return ExitCode::from(EXIT_ERROR);
-                                }
-                                all_warnings.push(format!("sprite '{}': {}", sprite.name, e));
-                                ResolvedPalette {
-                                    colors: std::collections::HashMap::new(),
-                                    source: PaletteSource::Fallback,
-                                }
-                            }
-                        }
-                    }
-                    _ => match palette_registry.resolve(sprite, strict) {
-                        Ok(result) => {
-                            if let Some(warning) = result.warning {
-                                all_warnings
-                                    .push(format!("sprite '{}': {}", sprite.name, warning.message));
-                                if strict {
-                                    for warning in all_warnings.iter() {
-                                        eprintln!("Error: {}", warning);
-                                    }
-                                    return ExitCode::from(EXIT_ERROR);
-                                }
-                            }
-                            result.palette
-                        }
-                        Err(e) => {
-                            eprintln!("Error: sprite '{}': {}", sprite.name, e);
-                            return ExitCode::from(EXIT_ERROR);
-                        }
-                    },
+                let image = match render_sprite_image(
+                    sprite,
+                    palette_registry,
+                    input_dir,
+                    include_visited,
+                    all_warnings,
+                    strict,
+                ) {
+                    Ok(image) => scale_image(image, scale),
+                    Err(code) => return code,
                 };
 
-                // Render sprite
-                let (image, render_warnings) = render_sprite(sprite, &resolved.colors);
-
-                // Apply scaling if requested
-                let image = scale_image(image, scale);
-
-                // Collect render warnings
-                for warning in render_warnings {
-                    all_warnings.push(format!("sprite '{}': {}", sprite.name, warning.message));
-                }
-
-                if strict && !all_warnings.is_empty() {
-                    for warning in all_warnings.iter() {
-                        eprintln!("Error: {}", warning);
-                    }
-                    return ExitCode::from(EXIT_ERROR);
-                }
-
+                let s = scale as i32;
+                let origin = sprite.metadata.as_ref().and_then(|m| m.origin);
                 frame_images.push(image);
+                frame_durations.push(per_frame_durations[frame_index]);
+                frame_pivots.push(origin.map(|[x, y]| [x * s, y * s]));
             } else if let Some(comp) = compositions.get(frame_name) {
                 // TTP-9qjwr: Render composition as animation frame
                 let result = render_composition_to_image(
@@ -1057,6 +2039,8 @@ fn run_animation_render(
                         // Apply scaling if requested
                         let image = scale_image(image, scale);
                         frame_images.push(image);
+                        frame_durations.push(per_frame_durations[frame_index]);
+                        frame_pivots.push(None);
                     }
                     Err(code) => return code,
                 }
@@ -1064,7 +2048,7 @@ fn run_animation_render(
             // If neither sprite nor composition found, skip (warned above)
         }
 
-        (frame_images, animation.duration_ms())
+        (frame_images, frame_durations, frame_pivots)
     };
 
     if frame_images.is_empty() {
@@ -1072,35 +2056,469 @@ fn run_animation_render(
         return ExitCode::from(EXIT_ERROR);
     }
 
-    // Generate output path
-    let output_path = if let Some(path) = output {
+    // Line frames up on their sprite origins instead of padding from the top-left
+    let sizes: Vec<(u32, u32)> = frame_images.iter().map(|f| f.dimensions()).collect();
+    let layout = pivot_layout(&sizes, &frame_pivots);
+    let frame_images = match &layout {
+        Some(layout) => align_frames(frame_images, layout),
+        None => frame_images,
+    };
+
+    // Blend sequence steps together; indices only line up if no frame was skipped
+    let (frame_images, frame_durations) = match sequence_crossfades.get(&animation.name) {
+        Some(crossfades) if frame_images.len() == animation.frames.len() => {
+            apply_crossfades(frame_images, frame_durations, crossfades)
+        }
+        _ => (frame_images, frame_durations),
+    };
+
+    // Restrict to a tagged frame range (--tag)
+    let mut frame_images = frame_images;
+    let mut frame_durations = frame_durations;
+    let mut loops = animation.loops();
+    if let Some(tag_name) = tag_filter {
+        let Some(tag) = animation.tag(tag_name) else {
+            eprintln!("Error: Animation '{}' has no tag named '{}'", animation.name, tag_name);
+            if let Some(suggestion) =
+                format_suggestion(&suggest(tag_name, &animation.tag_names(), 3))
+            {
+                eprintln!("{}", suggestion);
+            }
+            return ExitCode::from(EXIT_ERROR);
+        };
+        let Some(range) = tag.range(frame_images.len()) else {
+            eprintln!(
+                "Error: Tag '{}' range {}..{} is outside animation '{}' ({} frames)",
+                tag_name,
+                tag.start,
+                tag.end,
+                animation.name,
+                frame_images.len()
+            );
+            return ExitCode::from(EXIT_ERROR);
+        };
+        frame_images = frame_images.drain(range.clone()).collect();
+        frame_durations = frame_durations.drain(range).collect();
+        if let Some(fps) = tag.fps.filter(|fps| *fps > 0) {
+            frame_durations = vec![1000 / fps; frame_images.len()];
+        }
+        loops = tag.r#loop.unwrap_or(loops);
+    }
+
+    // Expand the frame order for reverse/ping-pong playback
+    let frame_images = animation.direction().apply(frame_images);
+    let frame_durations = animation.direction().apply(frame_durations);
+
+    // Generate output path
+    let output_path = if let Some(path) = output {
+        path.to_path_buf()
+    } else {
+        // Default: input_animation.gif or input_animation.png (input_animation_tag with --tag)
+        let extension = match output_kind {
+            AnimationOutput::Gif(_) => "gif",
+            AnimationOutput::Spritesheet => "png",
+            AnimationOutput::Cursor => "ani",
+            AnimationOutput::Flipbook(_) => "pdf",
+            AnimationOutput::CycleStrip { .. } => "png",
+        };
+        let stem = input_stem(input);
+        let suffix = tag_filter.map(|t| format!("_{}", t)).unwrap_or_default();
+        input
+            .parent()
+            .unwrap_or(std::path::Path::new("."))
+            .join(format!("{}_{}{}.{}", stem, animation.name, suffix, extension))
+    };
+
+    // Output as GIF, spritesheet, animated cursor, flip-book or cycle strip
+    let mut saved_paths = Vec::new();
+    match output_kind {
+        AnimationOutput::Gif(gif) => {
+            let frame_images = alpha.apply_frames(frame_images);
+            if let Err(e) = render_gif_with_durations(
+                &frame_images,
+                &frame_durations,
+                loops,
+                &gif,
+                &output_path,
+            ) {
+                eprintln!("Error: Failed to save GIF '{}': {}", output_path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+        AnimationOutput::Spritesheet => {
+            // Applied to the whole sheet so padding between smaller frames is matted too
+            let sheet = alpha.apply(render_spritesheet(&frame_images, None));
+            let text =
+                provenance_text(metadata, Provenance::new(input, &animation.name, animation));
+            if let Err(e) = save_png_with_text(&sheet, &output_path, &text) {
+                eprintln!("Error: Failed to save spritesheet '{}': {}", output_path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+        AnimationOutput::Cursor => {
+            // One hotspot for the whole cursor, from the first sprite frame's origin
+            let origin = animation
+                .frames
+                .iter()
+                .find_map(|name| sprites.get(name))
+                .and_then(|sprite| sprite.metadata.as_ref())
+                .and_then(|m| m.origin);
+            let (width, height) = frame_images[0].dimensions();
+            // Aligned frames share one pivot, already in scaled pixels
+            let hotspot = match &layout {
+                Some(layout) => hotspot_from_origin(Some(layout.pivot), 1, width, height),
+                None => hotspot_from_origin(origin, scale as u32, width, height),
+            };
+            if !loops {
+                all_warnings.push(format!(
+                    "Animation '{}' does not loop, but animated cursors always loop",
+                    animation.name
+                ));
+            }
+            let frame_images = alpha.apply_frames(frame_images);
+            if let Err(e) = save_ani(&frame_images, &frame_durations, hotspot, &output_path) {
+                eprintln!("Error: Failed to save cursor '{}': {}", output_path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+        AnimationOutput::Flipbook(config) => {
+            let pages = match render_flipbook(&frame_images, &config) {
+                Ok(pages) => pages,
+                Err(e) => {
+                    eprintln!("Error: Animation '{}': {}", animation.name, e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            };
+            let is_png = output_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png"));
+            if !is_png {
+                if let Err(e) = save_pdf(&pages, config.dpi, &animation.name, &output_path) {
+                    eprintln!("Error: Failed to save flip-book '{}': {}", output_path.display(), e);
+                    return ExitCode::from(EXIT_ERROR);
+                }
+            } else {
+                // One PNG per page: walk.png, or walk_p1.png, walk_p2.png, ...
+                let text =
+                    provenance_text(metadata, Provenance::new(input, &animation.name, animation));
+                for (index, page) in pages.iter().enumerate() {
+                    let page_path = if pages.len() == 1 {
+                        output_path.clone()
+                    } else {
+                        suffixed_output_path(&output_path, &format!("p{}", index + 1))
+                    };
+                    if let Err(e) = save_png_with_text(page, &page_path, &text) {
+                        eprintln!(
+                            "Error: Failed to save flip-book '{}': {}",
+                            page_path.display(),
+                            e
+                        );
+                        return ExitCode::from(EXIT_ERROR);
+                    }
+                    saved_paths.push(page_path);
+                }
+            }
+        }
+        AnimationOutput::CycleStrip { .. } => {
+            let strip = alpha.apply(cycle_strip.expect("cycle strips are palette cycles"));
+            let text =
+                provenance_text(metadata, Provenance::new(input, &animation.name, animation));
+            if let Err(e) = save_png_with_text(&strip, &output_path, &text) {
+                eprintln!("Error: Failed to save cycle strip '{}': {}", output_path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+    }
+
+    if saved_paths.is_empty() {
+        saved_paths.push(output_path);
+    }
+    for path in &saved_paths {
+        print_saved(path);
+    }
+
+    // Print warnings to stderr (in lenient mode)
+    for warning in all_warnings.iter() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+/// Render a sprite, resolving its palette (including `@include:` palettes)
+///
+/// Warnings are collected into `all_warnings`; in strict mode any warning is
+/// reported and turned into an error exit code.
+pub(super) fn render_sprite_image(
+    sprite: &Sprite,
+    palette_registry: &PaletteRegistry,
+    input_dir: &std::path::Path,
+    include_visited: &mut HashSet<PathBuf>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+) -> Result<image::RgbaImage, ExitCode> {
+    let palette = resolve_sprite_palette(
+        sprite,
+        palette_registry,
+        input_dir,
+        include_visited,
+        all_warnings,
+        strict,
+    )?;
+
+    // Render sprite
+    let (image, render_warnings) = render_sprite(sprite, &palette);
+
+    // Collect render warnings
+    for warning in render_warnings {
+        all_warnings.push(warning.for_object("sprite", &sprite.name));
+    }
+
+    if strict && !all_warnings.is_empty() {
+        for warning in all_warnings.iter() {
+            eprintln!("Error: {}", warning);
+        }
+        return Err(ExitCode::from(EXIT_ERROR));
+    }
+
+    Ok(image)
+}
+
+/// A rendered sprite after its `transform` list, with metadata remapped to match
+struct TransformedSprite {
+    image: image::RgbaImage,
+    metadata: Option<SpriteMetadata>,
+    nine_slice: Option<NineSlice>,
+}
+
+/// Apply a sprite's `transform` list to its rendered image.
+///
+/// The origin, attach points, collision boxes and nine-slice borders are
+/// carried through each step so they stay on the same pixels. Invalid
+/// transforms are errors in strict mode and warnings otherwise; metadata that
+/// a transform makes ambiguous is always reported as a warning.
+fn apply_sprite_transforms(
+    sprite: &Sprite,
+    mut image: image::RgbaImage,
+    palette: &HashMap<String, String>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+) -> Result<TransformedSprite, ExitCode> {
+    use crate::transforms::{
+        apply_image_transform, is_animation_transform, parse_transform_spec, transform_metadata,
+        transform_nine_slice,
+    };
+
+    let mut metadata = sprite.metadata.clone();
+    let mut nine_slice = sprite.nine_slice.clone();
+    for spec in sprite.transform.iter().flatten() {
+        let transform = match parse_transform_spec(spec) {
+            Ok(transform) => transform,
+            Err(e) => {
+                let msg = format!("sprite '{}': invalid transform: {}", sprite.name, e);
+                if strict {
+                    eprintln!("Error: {}", msg);
+                    return Err(ExitCode::from(EXIT_ERROR));
+                }
+                all_warnings.push(msg);
+                continue;
+            }
+        };
+        // Skip animation transforms (they don't apply to images)
+        if is_animation_transform(&transform) {
+            continue;
+        }
+        let transformed = match apply_image_transform(&image, &transform, Some(palette)) {
+            Ok(transformed) => transformed,
+            Err(e) => {
+                let msg = format!("sprite '{}': transform error: {}", sprite.name, e);
+                if strict {
+                    eprintln!("Error: {}", msg);
+                    return Err(ExitCode::from(EXIT_ERROR));
+                }
+                all_warnings.push(msg);
+                continue;
+            }
+        };
+
+        let (from, to) = (image.dimensions(), transformed.dimensions());
+        let mut warnings = Vec::new();
+        if let Some(meta) = &metadata {
+            let (meta, meta_warnings) = transform_metadata(meta, &transform, from, to);
+            metadata = Some(meta);
+            warnings.extend(meta_warnings);
+        }
+        if let Some(ns) = &nine_slice {
+            let (ns, ns_warnings) = transform_nine_slice(ns, &transform, from, to);
+            nine_slice = ns;
+            warnings.extend(ns_warnings);
+        }
+        for warning in warnings {
+            all_warnings.push(
+                Warning::new(WarningCategory::Transform, warning)
+                    .for_object("sprite", &sprite.name),
+            );
+        }
+        image = transformed;
+    }
+
+    Ok(TransformedSprite { image, metadata, nine_slice })
+}
+
+/// Resolve a sprite's palette colors, including `@include:` palettes
+fn resolve_sprite_palette(
+    sprite: &Sprite,
+    palette_registry: &PaletteRegistry,
+    input_dir: &std::path::Path,
+    include_visited: &mut HashSet<PathBuf>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+) -> Result<HashMap<String, String>, ExitCode> {
+    let resolved = match &sprite.palette {
+        PaletteRef::Named(name) if is_include_ref(name) => {
+            let (include_path, palette_name) =
+                parse_include_ref(name).expect("is_include_ref validated prefix");
+            match resolve_include_with_detection(
+                include_path,
+                input_dir,
+                include_visited,
+                palette_name,
+            ) {
+                Ok(palette) => ResolvedPalette {
+                    colors: palette.colors,
+                    source: PaletteSource::Named(name.clone()),
+                },
+                Err(e) => {
+                    if strict {
+                        eprintln!("Error: sprite '{}': {}", sprite.name, e);
+                        return Err(ExitCode::from(EXIT_ERROR));
+                    }
+                    all_warnings.push(
+                        Warning::new(WarningCategory::Palette, e.to_string())
+                            .for_object("sprite", &sprite.name),
+                    );
+                    ResolvedPalette {
+                        colors: std::collections::HashMap::new(),
+                        source: PaletteSource::Fallback,
+                    }
+                }
+            }
+        }
+        _ => match palette_registry.resolve(sprite, strict) {
+            Ok(result) => {
+                if let Some(warning) = result.warning {
+                    all_warnings.push(Warning::from(warning).for_object("sprite", &sprite.name));
+                    if strict {
+                        for warning in all_warnings.iter() {
+                            eprintln!("Error: {}", warning);
+                        }
+                        return Err(ExitCode::from(EXIT_ERROR));
+                    }
+                }
+                result.palette
+            }
+            Err(e) => {
+                eprintln!("Error: sprite '{}': {}", sprite.name, e);
+                return Err(ExitCode::from(EXIT_ERROR));
+            }
+        },
+    };
+
+    Ok(resolved.colors)
+}
+
+/// Render sprites or compositions as a pseudo-3D sprite stack (--stack)
+///
+/// Layers are listed bottom first. A PNG shows the stack at `options.angle`;
+/// with `--gif` the stack spins through a full turn over `frames` frames.
+#[allow(clippy::too_many_arguments)]
+fn run_stack_render(
+    input: &std::path::Path,
+    output: Option<&std::path::Path>,
+    layer_names: &[String],
+    options: &StackOptions,
+    frames: u32,
+    sprites: &HashMap<String, Sprite>,
+    compositions: &HashMap<String, Composition>,
+    sprite_registry: &SpriteRegistry,
+    palette_registry: &PaletteRegistry,
+    input_dir: &std::path::Path,
+    include_visited: &mut HashSet<PathBuf>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+    scale: u8,
+    alpha: AlphaOptions,
+    gif: Option<GifOptions>,
+) -> ExitCode {
+    let mut layers = Vec::with_capacity(layer_names.len());
+    for name in layer_names {
+        let result = if let Some(sprite) = sprites.get(name) {
+            render_sprite_image(
+                sprite,
+                palette_registry,
+                input_dir,
+                include_visited,
+                all_warnings,
+                strict,
+            )
+        } else if let Some(comp) = compositions.get(name) {
+            render_composition_to_image(
+                comp,
+                sprites,
+                sprite_registry,
+                palette_registry,
+                input_dir,
+                include_visited,
+                all_warnings,
+                strict,
+            )
+        } else {
+            eprintln!("Error: No sprite or composition named '{}' found for --stack", name);
+            let names: Vec<&str> =
+                sprites.keys().chain(compositions.keys()).map(|s| s.as_str()).collect();
+            if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                eprintln!("{}", suggestion);
+            }
+            return ExitCode::from(EXIT_ERROR);
+        };
+        match result {
+            Ok(image) => layers.push(image),
+            Err(code) => return code,
+        }
+    }
+
+    // Default: input_stack.gif or input_stack.png
+    let output_path = if let Some(path) = output {
         path.to_path_buf()
     } else {
-        // Default: input_animation.gif or input_animation.png
-        let extension = if gif_output { "gif" } else { "png" };
-        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = if gif.is_some() { "gif" } else { "png" };
+        let stem = input_stem(input);
         input
             .parent()
             .unwrap_or(std::path::Path::new("."))
-            .join(format!("{}_{}.{}", stem, animation.name, extension))
+            .join(format!("{}_stack.{}", stem, extension))
     };
 
-    // Output as GIF or spritesheet
-    if gif_output {
-        if let Err(e) = render_gif(&frame_images, frame_duration, animation.loops(), &output_path) {
+    if let Some(gif) = gif {
+        let frame_images: Vec<_> = render_stack_rotation(&layers, options, frames)
+            .into_iter()
+            .map(|f| alpha.apply(scale_image(f, scale)))
+            .collect();
+        // Same default frame time as animations
+        let durations = vec![100; frame_images.len()];
+        if let Err(e) =
+            render_gif_with_durations(&frame_images, &durations, true, &gif, &output_path)
+        {
             eprintln!("Error: Failed to save GIF '{}': {}", output_path.display(), e);
             return ExitCode::from(EXIT_ERROR);
         }
     } else {
-        // Spritesheet output
-        let sheet = render_spritesheet(&frame_images, None);
-        if let Err(e) = save_png(&sheet, &output_path) {
-            eprintln!("Error: Failed to save spritesheet '{}': {}", output_path.display(), e);
+        let image = alpha.apply(scale_image(render_stack(&layers, options), scale));
+        if let Err(e) = save_png(&image, &output_path) {
+            eprintln!("Error: Failed to save '{}': {}", output_path.display(), e);
             return ExitCode::from(EXIT_ERROR);
         }
     }
 
-    println!("Saved: {}", output_path.display());
+    print_saved(&output_path);
 
     // Print warnings to stderr (in lenient mode)
     for warning in all_warnings.iter() {
@@ -1110,6 +2528,100 @@ fn run_animation_render(
     ExitCode::from(EXIT_SUCCESS)
 }
 
+/// Render sprites as Windows cursors (--format cur)
+///
+/// Each sprite becomes a `.cur` whose hotspot is its `metadata.origin`
+/// (scaled with the image), or the top-left pixel without one.
+#[allow(clippy::too_many_arguments)]
+fn run_cursor_render(
+    input: &std::path::Path,
+    output: Option<&std::path::Path>,
+    sprite_filter: Option<&str>,
+    sprites: &HashMap<String, Sprite>,
+    palette_registry: &PaletteRegistry,
+    input_dir: &std::path::Path,
+    include_visited: &mut HashSet<PathBuf>,
+    all_warnings: &mut Warnings,
+    strict: bool,
+    scale: u8,
+    alpha: AlphaOptions,
+) -> ExitCode {
+    let mut selected: Vec<&Sprite> = match sprite_filter {
+        Some(name) => match sprites.get(name) {
+            Some(sprite) => vec![sprite],
+            None => {
+                eprintln!("Error: No sprite named '{}' found in input", name);
+                let names: Vec<&str> = sprites.keys().map(|s| s.as_str()).collect();
+                if let Some(suggestion) = format_suggestion(&suggest(name, &names, 3)) {
+                    eprintln!("{}", suggestion);
+                }
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+        None => sprites.values().collect(),
+    };
+    if selected.is_empty() {
+        eprintln!("Error: No sprites found in input file");
+        return ExitCode::from(EXIT_ERROR);
+    }
+    selected.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let is_single = selected.len() == 1;
+    for sprite in selected {
+        let image = match render_sprite_image(
+            sprite,
+            palette_registry,
+            input_dir,
+            include_visited,
+            all_warnings,
+            strict,
+        ) {
+            Ok(image) => alpha.apply(scale_image(image, scale)),
+            Err(code) => return code,
+        };
+
+        let origin = sprite.metadata.as_ref().and_then(|m| m.origin);
+        let hotspot = hotspot_from_origin(origin, scale as u32, image.width(), image.height());
+        let output_path =
+            generate_output_path(input, &sprite.name, output, is_single).with_extension("cur");
+        if let Err(e) = save_cur(&image, hotspot, &output_path) {
+            eprintln!("Error: Failed to save cursor '{}': {}", output_path.display(), e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        print_saved(&output_path);
+    }
+
+    // Print warnings to stderr (in lenient mode)
+    for warning in all_warnings.iter() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    ExitCode::from(EXIT_SUCCESS)
+}
+
+/// Token roles of a sprite's named palette, if it has any
+fn palette_roles<'a>(
+    palette: &PaletteRef,
+    registry: &'a PaletteRegistry,
+) -> Option<&'a HashMap<String, Role>> {
+    match palette {
+        PaletteRef::Named(name) => registry.get(name).and_then(|p| p.roles.as_ref()),
+        PaletteRef::Inline(_) => None,
+    }
+}
+
+/// Path for a lighting map saved next to an image: `hero.png` -> `hero_normal.png`
+fn map_output_path(path: &std::path::Path, kind: MapKind) -> PathBuf {
+    suffixed_output_path(path, kind.suffix())
+}
+
+/// Path for a companion image saved next to an image: `hero.png` + `arm` -> `hero_arm.png`
+fn suffixed_output_path(path: &std::path::Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|e| e.to_string_lossy()).unwrap_or("png".into());
+    path.with_file_name(format!("{}_{}.{}", stem, suffix, extension))
+}
+
 /// Parse max-size argument (e.g., "512x512") into (width, height)
 fn parse_max_size(arg: Option<&str>) -> Result<(u32, u32), String> {
     match arg {
@@ -1140,13 +2652,17 @@ fn run_atlas_render(
     palette_registry: &PaletteRegistry,
     input_dir: &std::path::Path,
     include_visited: &mut HashSet<PathBuf>,
-    all_warnings: &mut Vec<String>,
+    all_warnings: &mut Warnings,
     strict: bool,
     scale: u8,
+    alpha: AlphaOptions,
     format: &str,
     max_size_arg: Option<&str>,
     padding: u32,
     power_of_two: bool,
+    animation_filter: Option<&str>,
+    tag_filter: Option<&str>,
+    maps: &[MapKind],
 ) -> ExitCode {
     // Parse max-size
     let max_size = match parse_max_size(max_size_arg) {
@@ -1162,6 +2678,7 @@ fn run_atlas_render(
 
     // Render all sprites to images
     let mut sprite_inputs: Vec<SpriteInput> = Vec::new();
+    let mut sprite_maps: HashMap<String, Vec<(MapKind, image::RgbaImage)>> = HashMap::new();
 
     for sprite in sprites.values() {
         // Resolve palette
@@ -1184,7 +2701,10 @@ fn run_atlas_render(
                             eprintln!("Error: sprite '{}': {}", sprite.name, e);
                             return ExitCode::from(EXIT_ERROR);
                         }
-                        all_warnings.push(format!("sprite '{}': {}", sprite.name, e));
+                        all_warnings.push(
+                            Warning::new(WarningCategory::Palette, e.to_string())
+                                .for_object("sprite", &sprite.name),
+                        );
                         continue;
                     }
                 }
@@ -1192,7 +2712,8 @@ fn run_atlas_render(
             _ => match palette_registry.resolve(sprite, strict) {
                 Ok(result) => {
                     if let Some(warning) = result.warning {
-                        all_warnings.push(format!("sprite '{}': {}", sprite.name, warning.message));
+                        all_warnings
+                            .push(Warning::from(warning).for_object("sprite", &sprite.name));
                         if strict {
                             for w in all_warnings.iter() {
                                 eprintln!("Error: {}", w);
@@ -1209,15 +2730,32 @@ fn run_atlas_render(
             },
         };
 
-        // Render sprite
+        // Render sprite, carrying its metadata through any transforms
         let (image, render_warnings) = render_sprite(sprite, &resolved.colors);
+        let TransformedSprite { image, metadata, .. } =
+            match apply_sprite_transforms(sprite, image, &resolved.colors, all_warnings, strict) {
+                Ok(transformed) => transformed,
+                Err(code) => return code,
+            };
 
-        // Apply scaling if requested
-        let image = scale_image(image, scale);
+        // Lighting maps are packed with the same placement as the sprite
+        if !maps.is_empty() {
+            let materials = MaterialPalette::from_regions(
+                &sprite.regions.clone().unwrap_or_default(),
+                &resolved.colors,
+                palette_roles(&sprite.palette, palette_registry),
+            );
+            sprite_maps.insert(
+                sprite.name.clone(),
+                maps.iter()
+                    .map(|&kind| (kind, scale_image(materials.render_map(&image, kind), scale)))
+                    .collect(),
+            );
+        }
 
         // Collect render warnings
         for warning in render_warnings {
-            all_warnings.push(format!("sprite '{}': {}", sprite.name, warning.message));
+            all_warnings.push(warning.for_object("sprite", &sprite.name));
         }
 
         if strict && !all_warnings.is_empty() {
@@ -1227,22 +2765,9 @@ fn run_atlas_render(
             return ExitCode::from(EXIT_ERROR);
         }
 
-        // Extract metadata for atlas export
-        let (origin, boxes) = if let Some(ref meta) = sprite.metadata {
-            let origin = meta.origin;
-            let boxes = meta.boxes.as_ref().map(|b| {
-                b.iter()
-                    .map(|(name, cb)| {
-                        (name.clone(), AtlasBox { x: cb.x, y: cb.y, w: cb.w, h: cb.h })
-                    })
-                    .collect()
-            });
-            (origin, boxes)
-        } else {
-            (None, None)
-        };
-
-        sprite_inputs.push(SpriteInput { name: sprite.name.clone(), image, origin, boxes });
+        // Scale origin, attachment points and collision boxes along with the image
+        let input = SpriteInput::with_metadata(sprite.name.clone(), image, metadata.as_ref());
+        sprite_inputs.push(input.scaled(scale as u32));
     }
 
     if sprite_inputs.is_empty() {
@@ -1254,11 +2779,7 @@ fn run_atlas_render(
     let base_name = if let Some(out_path) = output {
         out_path.file_stem().and_then(|s| s.to_str()).unwrap_or("atlas").to_string()
     } else {
-        input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .map(|s| format!("{}_atlas", s))
-            .unwrap_or_else(|| "atlas".to_string())
+        format!("{}_atlas", input_stem(input))
     };
 
     let output_dir = output
@@ -1273,12 +2794,81 @@ fn run_atlas_render(
         return ExitCode::from(EXIT_ERROR);
     }
 
+    // With --tag, export only that tag's frame range of each animation that defines it
+    // (name, frames, fps, tags, per-frame durations, direction)
+    type ExportedAnimation =
+        (String, Vec<String>, u32, HashMap<String, FrameTag>, Option<Vec<u32>>, PlaybackDirection);
+    let mut exported_animations: Vec<ExportedAnimation> = Vec::new();
+    let mut sorted_animations: Vec<&Animation> = animations.values().collect();
+    sorted_animations.sort_by(|a, b| a.name.cmp(&b.name));
+    for anim in sorted_animations {
+        if animation_filter.is_some_and(|name| name != anim.name) {
+            continue;
+        }
+        let fps = 1000 / anim.duration_ms().max(1);
+        let durations = match anim.frame_durations() {
+            Ok(durations) => Some(durations),
+            Err(e) => {
+                all_warnings.push(e);
+                None
+            }
+        };
+        match tag_filter {
+            None => exported_animations.push((
+                anim.name.clone(),
+                anim.frames.clone(),
+                fps,
+                anim.tags.clone().unwrap_or_default(),
+                durations,
+                anim.direction(),
+            )),
+            Some(tag_name) => {
+                let Some(tag) = anim.tag(tag_name) else { continue };
+                let Some(range) = tag.range(anim.frames.len()) else {
+                    all_warnings.push(format!(
+                        "Animation '{}' tag '{}' is out of range, skipping",
+                        anim.name, tag_name
+                    ));
+                    continue;
+                };
+                let frames = anim.frames[range.clone()].to_vec();
+                let rebased = FrameTag { start: 0, end: frames.len() as u32 - 1, ..tag.clone() };
+                // A tag fps override replaces per-frame timing
+                let durations = match tag.fps {
+                    Some(_) => None,
+                    None => durations.map(|d| d[range].to_vec()),
+                };
+                exported_animations.push((
+                    anim.name.clone(),
+                    frames,
+                    tag.fps.unwrap_or(fps),
+                    HashMap::from([(tag_name.to_string(), rebased)]),
+                    durations,
+                    anim.direction(),
+                ));
+            }
+        }
+    }
+    if let Some(tag_name) = tag_filter {
+        if exported_animations.is_empty() {
+            eprintln!("Error: No animation has a tag named '{}'", tag_name);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    }
+
     // Save each atlas
     for (image, mut metadata) in result.atlases {
         // Add animation metadata
-        for anim in animations.values() {
-            let fps = 1000 / anim.duration_ms().max(1);
-            add_animation_to_atlas(&mut metadata, &anim.name, &anim.frames, fps);
+        for (name, frames, fps, tags, durations, direction) in &exported_animations {
+            add_animation_to_atlas(
+                &mut metadata,
+                name,
+                frames,
+                *fps,
+                Some(tags),
+                durations.as_deref(),
+                *direction,
+            );
         }
 
         // Determine file paths
@@ -1287,6 +2877,7 @@ fn run_atlas_render(
         let json_path = output_dir.join(&json_name);
 
         // Save PNG
+        let image = alpha.apply(image);
         if let Err(e) = save_png(&image, &image_path) {
             eprintln!("Error: Failed to save atlas '{}': {}", image_path.display(), e);
             return ExitCode::from(EXIT_ERROR);
@@ -1294,12 +2885,12 @@ fn run_atlas_render(
 
         // Generate JSON based on format variant
         let json_content = match format {
-            "atlas" => serde_json::to_string_pretty(&metadata).expect("metadata serialization"),
+            "atlas" => generate_atlas_json(&metadata),
             "atlas-aseprite" => generate_aseprite_json(&metadata),
             "atlas-godot" => generate_godot_json(&metadata),
             "atlas-unity" => generate_unity_json(&metadata),
             "atlas-libgdx" => generate_libgdx_atlas(&metadata),
-            _ => serde_json::to_string_pretty(&metadata).expect("metadata serialization"),
+            _ => generate_atlas_json(&metadata),
         };
 
         // Determine JSON file extension for libGDX
@@ -1316,6 +2907,27 @@ fn run_atlas_render(
         }
 
         println!("Saved: {} + {}", image_path.display(), final_json_path.display());
+
+        // Lay out each lighting map exactly like the albedo atlas
+        for (i, kind) in maps.iter().enumerate() {
+            let mut map_atlas = image::RgbaImage::new(image.width(), image.height());
+            for (name, frame) in &metadata.frames {
+                if let Some((_, map_image)) = sprite_maps.get(name).and_then(|m| m.get(i)) {
+                    image::imageops::replace(
+                        &mut map_atlas,
+                        map_image,
+                        frame.x as i64,
+                        frame.y as i64,
+                    );
+                }
+            }
+            let map_path = map_output_path(&image_path, *kind);
+            if let Err(e) = save_png(&map_atlas, &map_path) {
+                eprintln!("Error: Failed to save atlas '{}': {}", map_path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+            println!("Saved: {}", map_path.display());
+        }
     }
 
     // Print warnings
@@ -1327,25 +2939,83 @@ fn run_atlas_render(
 }
 
 /// Generate Aseprite-compatible JSON format
+/// Generate the native atlas JSON, recording the seed the atlas was rendered with.
+fn generate_atlas_json(metadata: &crate::atlas::AtlasMetadata) -> String {
+    let mut value = serde_json::to_value(metadata).expect("metadata serialization");
+    value["seed"] = serde_json::json!(crate::rng::global_seed());
+    serde_json::to_string_pretty(&value).expect("metadata serialization")
+}
+
 fn generate_aseprite_json(metadata: &crate::atlas::AtlasMetadata) -> String {
+    let mut anim_names: Vec<&String> = metadata.animations.keys().collect();
+    anim_names.sort();
+
+    // Aseprite stores timing per frame. Frames used by an animation take its
+    // timing; when several animations share a frame the first by name wins.
+    let mut frame_durations: HashMap<&str, u32> = HashMap::new();
+    // Frames lined up on their origins are described as trimmed from the
+    // animation's shared canvas, again first animation by name wins
+    let mut frame_sources: HashMap<&str, ([u32; 2], [u32; 2])> = HashMap::new();
+    for anim_name in &anim_names {
+        let anim = &metadata.animations[*anim_name];
+        for (i, frame_name) in anim.frames.iter().enumerate() {
+            let duration = anim
+                .durations
+                .as_ref()
+                .and_then(|d| d.get(i).copied())
+                .unwrap_or(1000 / anim.fps.max(1));
+            frame_durations.entry(frame_name.as_str()).or_insert(duration);
+            if let (Some(canvas), Some(offset)) =
+                (anim.canvas, anim.offsets.as_ref().and_then(|o| o.get(i)))
+            {
+                frame_sources.entry(frame_name.as_str()).or_insert((*offset, canvas));
+            }
+        }
+    }
+
     let frames: serde_json::Map<String, serde_json::Value> = metadata
         .frames
         .iter()
         .map(|(name, frame)| {
-            (
-                format!("{}.png", name),
-                serde_json::json!({
-                    "frame": {"x": frame.x, "y": frame.y, "w": frame.w, "h": frame.h},
-                    "rotated": false,
-                    "trimmed": false,
-                    "spriteSourceSize": {"x": 0, "y": 0, "w": frame.w, "h": frame.h},
-                    "sourceSize": {"w": frame.w, "h": frame.h}
-                }),
-            )
+            let ([x, y], [w, h]) =
+                frame_sources.get(name.as_str()).copied().unwrap_or(([0, 0], [frame.w, frame.h]));
+            let mut value = serde_json::json!({
+                "frame": {"x": frame.x, "y": frame.y, "w": frame.w, "h": frame.h},
+                "rotated": false,
+                "trimmed": ([w, h] != [frame.w, frame.h]),
+                "spriteSourceSize": {"x": x, "y": y, "w": frame.w, "h": frame.h},
+                "sourceSize": {"w": w, "h": h}
+            });
+            if let Some(duration) = frame_durations.get(name.as_str()) {
+                value["duration"] = serde_json::json!(duration);
+            }
+            (format!("{}.png", name), value)
         })
         .collect();
 
-    let meta = serde_json::json!({
+    // Frame tags, sorted by animation then start frame. Aseprite indexes the
+    // (name-sorted) frames object, so each tag's first and last frame are
+    // looked up there; tags whose frames are on another atlas page are skipped.
+    let frame_index = |name: &str| frames.keys().position(|key| *key == format!("{}.png", name));
+    let mut frame_tags: Vec<serde_json::Value> = Vec::new();
+    for anim_name in anim_names {
+        let anim = &metadata.animations[anim_name];
+        let Some(tags) = &anim.tags else { continue };
+        let mut sorted: Vec<(&String, &crate::atlas::AtlasTag)> = tags.iter().collect();
+        sorted.sort_by_key(|(name, tag)| (tag.from, tag.to, *name));
+        for (tag_name, tag) in sorted {
+            let index = |i: u32| anim.frames.get(i as usize).and_then(|name| frame_index(name));
+            let (Some(from), Some(to)) = (index(tag.from), index(tag.to)) else { continue };
+            frame_tags.push(serde_json::json!({
+                "name": tag_name,
+                "from": from,
+                "to": to,
+                "direction": anim.direction.unwrap_or_default(),
+            }));
+        }
+    }
+
+    let mut meta = serde_json::json!({
         "app": "pixelsrc",
         "version": "1.0",
         "image": metadata.image,
@@ -1353,6 +3023,41 @@ fn generate_aseprite_json(metadata: &crate::atlas::AtlasMetadata) -> String {
         "size": {"w": metadata.size[0], "h": metadata.size[1]},
         "scale": "1"
     });
+    if !frame_tags.is_empty() {
+        meta["frameTags"] = serde_json::Value::Array(frame_tags);
+    }
+
+    // Origins and attachment points become 1x1 slices, keyed by each frame's
+    // index in the (name-sorted) frames object
+    type Point = fn(&crate::atlas::AtlasFrame) -> Option<[i32; 2]>;
+    let points: [(&str, &str, Point); 3] = [
+        ("origin", "#ff0000ff", |f| f.origin),
+        ("attach_in", "#00ff00ff", |f| f.attach_in),
+        ("attach_out", "#0000ffff", |f| f.attach_out),
+    ];
+    let slices: Vec<serde_json::Value> = points
+        .iter()
+        .filter_map(|(name, color, point)| {
+            let keys: Vec<serde_json::Value> = frames
+                .keys()
+                .enumerate()
+                .filter_map(|(index, key)| {
+                    let frame = &metadata.frames[key.strip_suffix(".png").unwrap_or(key)];
+                    let [x, y] = point(frame)?;
+                    Some(serde_json::json!({
+                        "frame": index,
+                        "bounds": {"x": x, "y": y, "w": 1, "h": 1},
+                        "pivot": {"x": 0, "y": 0}
+                    }))
+                })
+                .collect();
+            (!keys.is_empty())
+                .then(|| serde_json::json!({"name": name, "color": color, "keys": keys}))
+        })
+        .collect();
+    if !slices.is_empty() {
+        meta["slices"] = serde_json::Value::Array(slices);
+    }
 
     serde_json::to_string_pretty(&serde_json::json!({
         "frames": frames,
@@ -1367,10 +3072,20 @@ fn generate_godot_json(metadata: &crate::atlas::AtlasMetadata) -> String {
         .frames
         .iter()
         .map(|(name, frame)| {
-            serde_json::json!({
+            let mut sprite = serde_json::json!({
                 "name": name,
                 "region": {"x": frame.x, "y": frame.y, "w": frame.w, "h": frame.h}
-            })
+            });
+            for (key, point) in [
+                ("origin", frame.origin),
+                ("attach_in", frame.attach_in),
+                ("attach_out", frame.attach_out),
+            ] {
+                if let Some([x, y]) = point {
+                    sprite[key] = serde_json::json!({"x": x, "y": y});
+                }
+            }
+            sprite
         })
         .collect();
 
@@ -1390,7 +3105,7 @@ fn generate_unity_json(metadata: &crate::atlas::AtlasMetadata) -> String {
         .frames
         .iter()
         .map(|(name, frame)| {
-            serde_json::json!({
+            let mut sprite = serde_json::json!({
                 "name": name,
                 "rect": {
                     "x": frame.x,
@@ -1399,7 +3114,20 @@ fn generate_unity_json(metadata: &crate::atlas::AtlasMetadata) -> String {
                     "height": frame.h
                 },
                 "pivot": {"x": 0.5, "y": 0.5}
-            })
+            });
+            // Attachment points in pixels from the sprite's bottom-left corner
+            let attach = [("attach_in", frame.attach_in), ("attach_out", frame.attach_out)];
+            let points: serde_json::Map<String, serde_json::Value> = attach
+                .into_iter()
+                .filter_map(|(key, point)| {
+                    let [x, y] = point?;
+                    Some((key.to_string(), serde_json::json!({"x": x, "y": frame.h as i32 - y})))
+                })
+                .collect();
+            if !points.is_empty() {
+                sprite["attachPoints"] = serde_json::Value::Object(points);
+            }
+            sprite
         })
         .collect();
 
@@ -1421,7 +3149,7 @@ fn generate_libgdx_atlas(metadata: &crate::atlas::AtlasMetadata) -> String {
         "repeat: none".to_string(),
     ];
 
-    for (name, frame) in &metadata.frames {
+    for (name, frame) in metadata.frames_by_name() {
         lines.push(name.clone());
         lines.push("  rotate: false".to_string());
         lines.push(format!("  xy: {}, {}", frame.x, frame.y));
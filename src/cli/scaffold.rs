@@ -6,6 +6,8 @@ use clap::Subcommand;
 use std::path::Path;
 use std::process::ExitCode;
 
+use crate::scaffold::ScreenLayout;
+
 use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
 
 /// Output format for scaffold commands.
@@ -26,6 +28,7 @@ pub enum ScaffoldAction {
     ///   pxl scaffold sprite --name hero --size 16x16
     ///   pxl scaffold sprite --name hero --size 16x16 --palette medieval
     ///   pxl scaffold sprite --name hero --size 16x16 --tokens "skin,hair,eye"
+    ///   pxl scaffold sprite --name hero --size 16x16 --palette @pico8 --layout "head,torso,legs"
     Sprite {
         /// Sprite name
         #[arg(long)]
@@ -40,9 +43,14 @@ pub enum ScaffoldAction {
         palette: Option<String>,
 
         /// Comma-separated token names for auto-generated palette
-        #[arg(long)]
+        #[arg(long, conflicts_with = "layout")]
         tokens: Option<String>,
 
+        /// Comma-separated parts from top to bottom (e.g., "head,torso,legs");
+        /// emits a commented skeleton with a region per part
+        #[arg(long)]
+        layout: Option<String>,
+
         /// Output file (default: stdout)
         #[arg(short, long)]
         output: Option<std::path::PathBuf>,
@@ -83,6 +91,37 @@ pub enum ScaffoldAction {
         format: ScaffoldFormat,
     },
 
+    /// Generate a game screen composition with placeholder sprites
+    ///
+    /// Examples:
+    ///   pxl scaffold screen title
+    ///   pxl scaffold screen inventory --resolution 640x360 --palette @pico8
+    Screen {
+        /// Screen layout
+        #[arg(value_enum)]
+        layout: ScreenLayout,
+
+        /// Composition name (default: "<layout>_screen")
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Target resolution as WxH
+        #[arg(long, default_value = "320x180")]
+        resolution: String,
+
+        /// Built-in palette to take placeholder colors from
+        #[arg(long)]
+        palette: Option<String>,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+
+        /// Output format
+        #[arg(long, default_value = "pxl", value_enum)]
+        format: ScaffoldFormat,
+    },
+
     /// Generate a palette from preset or color list
     ///
     /// Examples:
@@ -152,7 +191,7 @@ fn write_output(content: &str, output: Option<&Path>) -> ExitCode {
 /// Execute the scaffold command.
 pub fn run_scaffold(action: ScaffoldAction) -> ExitCode {
     match action {
-        ScaffoldAction::Sprite { name, size, palette, tokens, output, format: _ } => {
+        ScaffoldAction::Sprite { name, size, palette, tokens, layout, output, format: _ } => {
             let (w, h) = match parse_size(&size) {
                 Ok(s) => s,
                 Err(e) => {
@@ -161,6 +200,23 @@ pub fn run_scaffold(action: ScaffoldAction) -> ExitCode {
                 }
             };
 
+            if let Some(layout) = layout {
+                let parts: Vec<String> = layout.split(',').map(|s| s.trim().to_string()).collect();
+                return match crate::scaffold::generate_sprite_layout(
+                    &name,
+                    w,
+                    h,
+                    palette.as_deref(),
+                    &parts,
+                ) {
+                    Ok(c) => write_output(&c, output.as_deref()),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        ExitCode::from(EXIT_INVALID_ARGS)
+                    }
+                };
+            }
+
             let token_list: Vec<String> = tokens
                 .as_deref()
                 .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
@@ -209,6 +265,25 @@ pub fn run_scaffold(action: ScaffoldAction) -> ExitCode {
             }
         }
 
+        ScaffoldAction::Screen { layout, name, resolution, palette, output, format: _ } => {
+            let (w, h) = match parse_size(&resolution) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return ExitCode::from(EXIT_INVALID_ARGS);
+                }
+            };
+            let name = name.unwrap_or_else(|| format!("{}_screen", layout.as_str()));
+
+            match crate::scaffold::generate_screen(layout, &name, w, h, palette.as_deref()) {
+                Ok(c) => write_output(&c, output.as_deref()),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    ExitCode::from(EXIT_INVALID_ARGS)
+                }
+            }
+        }
+
         ScaffoldAction::Palette { name, preset, colors, token_prefix, output, format: _ } => {
             let content = crate::scaffold::generate_palette_scaffold(
                 &name,
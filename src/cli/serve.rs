@@ -0,0 +1,72 @@
+//! Serve command implementation
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use super::{EXIT_ERROR, EXIT_SUCCESS};
+use crate::config::LimitsConfig;
+
+/// Run the serve command
+pub fn run_serve(path: Option<&Path>, host: &str, port: u16, watch: bool) -> ExitCode {
+    use crate::serve::{serve, ServeOptions};
+
+    let source = match default_source(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    if !source.exists() {
+        eprintln!("Error: Source not found: {}", source.display());
+        return ExitCode::from(EXIT_ERROR);
+    }
+
+    let addr = format!("{}:{}", host, port);
+    println!("Serving {} at http://{}/", source.display(), addr);
+    if watch {
+        println!("Watching for changes (Ctrl+C to stop)");
+    }
+
+    let limits = project_limits(&source);
+    match serve(ServeOptions { addr, sources: vec![source], watch, limits }) {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+/// The `[limits]` of the project containing `source`, or the defaults.
+pub(super) fn project_limits(source: &Path) -> LimitsConfig {
+    use crate::config::loader::{find_config_from, load_config};
+
+    let source = std::fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf());
+    let start = if source.is_dir() { source.as_path() } else { source.parent().unwrap_or(&source) };
+    find_config_from(start.to_path_buf())
+        .and_then(|config_path| load_config(Some(&config_path)).ok())
+        .map(|config| config.limits)
+        .unwrap_or_default()
+}
+
+/// Source to use when none is given: the project's source directory when a
+/// pxl.toml is present, otherwise the current directory.
+pub(super) fn default_source(path: Option<&Path>) -> Result<PathBuf, String> {
+    use crate::config::loader::{find_config, load_config};
+
+    if let Some(p) = path {
+        return Ok(p.to_path_buf());
+    }
+    let Some(config_path) = find_config() else {
+        return Ok(std::env::current_dir().unwrap_or_default());
+    };
+    let config = load_config(Some(&config_path)).map_err(|e| e.to_string())?;
+    let root = config_path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    Ok(if config.project.src.is_absolute() {
+        config.project.src
+    } else {
+        root.join(&config.project.src)
+    })
+}
@@ -8,10 +8,13 @@ use std::process::ExitCode;
 
 use crate::models::{Animation, Sprite, TtpObject};
 use crate::onion::{parse_hex_color, render_onion_skin, OnionConfig};
+use crate::output::{save_png, AlphaOptions};
 use crate::parser::parse_stream;
 use crate::registry::PaletteRegistry;
 use crate::renderer::render_sprite;
 use crate::suggest::{format_suggestion, suggest};
+use crate::terminal::{render_image_ansi, TRANSPARENT_CELL};
+use crate::underlay::underlay_sprite;
 
 use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
 
@@ -149,7 +152,7 @@ pub fn run_show(
 
         // Output to file or terminal
         if let Some(output_path) = output {
-            if let Err(e) = result.save(output_path) {
+            if let Err(e) = save_png(&result, output_path) {
                 eprintln!("Error: Failed to save image: {}", e);
                 return ExitCode::from(EXIT_ERROR);
             }
@@ -172,7 +175,6 @@ pub fn run_show(
             println!();
 
             // Convert image to terminal output
-            use crate::terminal::render_image_ansi;
             let ansi_output = render_image_ansi(&result);
             print!("{}", ansi_output);
 
@@ -213,18 +215,46 @@ pub fn run_show(
         }
     };
 
-    // Grid-based terminal rendering is deprecated
-    // Use render command to generate PNG output instead
-    eprintln!("Error: Terminal view requires grid format, which is deprecated.");
-    eprintln!(
-        "Use 'pxl render' to generate PNG output, or use sprites with structured regions format."
-    );
+    let resolved_palette = match registry.resolve(sprite, false) {
+        Ok(result) => result.palette.colors,
+        Err(e) => {
+            eprintln!("Error: sprite '{}': {}", sprite.name, e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    let (image, _warnings) = render_sprite(sprite, &resolved_palette);
+
+    // Previews draw the sprite over its reference image, if it has one
+    let base_dir = file.parent().unwrap_or(Path::new(""));
+    let has_reference = sprite.reference.is_some();
+    let image = match underlay_sprite(image.clone(), sprite, base_dir) {
+        Ok(preview) => preview,
+        Err(e) => {
+            eprintln!("Warning: {}", e);
+            image
+        }
+    };
 
-    // Show basic sprite info
-    let (width, height) =
-        if let Some(size) = sprite.size { (size[0] as usize, size[1] as usize) } else { (0, 0) };
-    println!("Sprite: {} ({}x{})", sprite.name, width, height);
-    println!("Has regions: {}", sprite.regions.is_some());
+    if let Some(output_path) = output {
+        if let Err(e) = save_png(&image, output_path) {
+            eprintln!("Error: Failed to save image: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        println!("Preview saved: {}", output_path.display());
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+
+    println!("Sprite: {} ({}x{})", sprite.name, image.width(), image.height());
+    println!();
+    // The terminal shows any alpha as opaque, so flatten a faded reference
+    // onto the same gray used for transparent cells
+    let image = if has_reference {
+        AlphaOptions { matte: Some(TRANSPARENT_CELL), ..Default::default() }.apply(image)
+    } else {
+        image
+    };
+    print!("{}", render_image_ansi(&image));
+    println!();
 
-    ExitCode::from(EXIT_ERROR)
+    ExitCode::from(EXIT_SUCCESS)
 }
@@ -0,0 +1,163 @@
+//! Snapshot command implementation
+
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::serve::{expand_sources, PreviewCatalog};
+use crate::snapshot::{
+    compare, diff_image, image_path, pixel_diff, render_all, snapshot_of, Change, RenderedObject,
+    Snapshot, DEFAULT_SNAPSHOT,
+};
+
+use super::serve::default_source;
+use super::{EXIT_ERROR, EXIT_SUCCESS};
+
+#[derive(Subcommand)]
+pub enum SnapshotAction {
+    /// Render every object and record its size and pixel hash
+    ///
+    /// Examples:
+    ///   pxl snapshot write
+    ///   pxl snapshot write src/ --images snapshots/
+    Write {
+        /// Source file or directory (default: src dir from pxl.toml, or current directory)
+        path: Option<PathBuf>,
+
+        /// Snapshot file to write
+        #[arg(long, default_value = DEFAULT_SNAPSHOT)]
+        snapshot: PathBuf,
+
+        /// Also save each rendered object as a PNG under this directory
+        #[arg(long, value_name = "DIR")]
+        images: Option<PathBuf>,
+    },
+
+    /// Render every object and compare against the snapshot
+    ///
+    /// Exits with an error if anything was added, removed or changed.
+    ///
+    /// Examples:
+    ///   pxl snapshot check
+    ///   pxl snapshot check --images snapshots/ --diff snapshot-diffs/
+    Check {
+        /// Source file or directory (default: src dir from pxl.toml, or current directory)
+        path: Option<PathBuf>,
+
+        /// Snapshot file to compare against
+        #[arg(long, default_value = DEFAULT_SNAPSHOT)]
+        snapshot: PathBuf,
+
+        /// Baseline PNGs saved by `snapshot write --images`, used to count changed pixels
+        #[arg(long, value_name = "DIR")]
+        images: Option<PathBuf>,
+
+        /// Write an image for each changed object under this directory
+        #[arg(long, value_name = "DIR")]
+        diff: Option<PathBuf>,
+    },
+}
+
+/// Run the snapshot command
+pub fn run_snapshot(action: SnapshotAction) -> ExitCode {
+    let result = match action {
+        SnapshotAction::Write { path, snapshot, images } => {
+            run_write(path.as_deref(), &snapshot, images.as_deref())
+        }
+        SnapshotAction::Check { path, snapshot, images, diff } => {
+            run_check(path.as_deref(), &snapshot, images.as_deref(), diff.as_deref())
+        }
+    };
+    match result {
+        Ok(true) => ExitCode::from(EXIT_SUCCESS),
+        Ok(false) => ExitCode::from(EXIT_ERROR),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+/// Render every object under `path`, printing load warnings and render errors.
+fn render_project(path: Option<&Path>) -> Result<Vec<RenderedObject>, String> {
+    let source = default_source(path)?;
+    if !source.exists() {
+        return Err(format!("Source not found: {}", source.display()));
+    }
+    let catalog = PreviewCatalog::load(&expand_sources(&[source]));
+    for warning in &catalog.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    let (rendered, errors) = render_all(&catalog);
+    for (key, error) in &errors {
+        eprintln!("Warning: {}: {}", key, error);
+    }
+    Ok(rendered)
+}
+
+fn save_image(dir: &Path, key: &str, image: &image::RgbaImage) -> Result<(), String> {
+    let path = image_path(dir, key);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Cannot create '{}': {}", parent.display(), e))?;
+    }
+    crate::output::save_png(image, &path)
+        .map_err(|e| format!("Cannot write '{}': {}", path.display(), e))
+}
+
+fn run_write(path: Option<&Path>, snapshot: &Path, images: Option<&Path>) -> Result<bool, String> {
+    let rendered = render_project(path)?;
+    snapshot_of(&rendered).save(snapshot)?;
+    if let Some(dir) = images {
+        for object in &rendered {
+            save_image(dir, &object.key, &object.image())?;
+        }
+    }
+    println!("Wrote {} objects to {}", rendered.len(), snapshot.display());
+    Ok(true)
+}
+
+fn run_check(
+    path: Option<&Path>,
+    snapshot: &Path,
+    images: Option<&Path>,
+    diff: Option<&Path>,
+) -> Result<bool, String> {
+    let expected = Snapshot::load(snapshot)?;
+    let rendered = render_project(path)?;
+    let changes = compare(&expected, &snapshot_of(&rendered));
+    if changes.is_empty() {
+        println!("{} objects match {}", rendered.len(), snapshot.display());
+        return Ok(true);
+    }
+
+    for (key, change) in &changes {
+        let current = rendered.iter().find(|o| &o.key == key).map(|o| o.image());
+        let baseline = images.and_then(|dir| image::open(image_path(dir, key)).ok());
+        let baseline = baseline.map(|img| img.to_rgba8());
+
+        match (change, &baseline, &current) {
+            (Change::Pixels, Some(old), Some(new)) => match pixel_diff(old, new) {
+                Some(pixels) => println!("{}: {} ({})", key, change, pixels),
+                None => println!("{}: {}", key, change),
+            },
+            _ => println!("{}: {}", key, change),
+        }
+
+        if let (Some(dir), Some(new)) = (diff, &current) {
+            match &baseline {
+                Some(old) => save_image(dir, key, &diff_image(old, new))?,
+                None => save_image(dir, key, new)?,
+            }
+        }
+    }
+    println!();
+    let plural = if changes.len() == 1 { "" } else { "s" };
+    println!(
+        "{} object{} differ from {}; run `pxl snapshot write` to accept",
+        changes.len(),
+        plural,
+        snapshot.display()
+    );
+    Ok(false)
+}
@@ -1,28 +1,60 @@
-//! Validation command implementations (validate, agent-verify, analyze, fmt)
+//! Validation command implementations (validate, lint, agent-verify, analyze, fmt)
 
+use std::io::Read;
 use std::path::PathBuf;
 use std::process::ExitCode;
 
-use crate::analyze::{collect_files, format_report_text, AnalysisReport};
-use crate::fmt::format_pixelsrc;
+use crate::analyze::{
+    collect_files, compare_reports, format_color_stats_text, format_comparison_text,
+    format_duplicates_text, format_report_text, AnalysisReport, ColorStats, DuplicateFinder,
+};
+use crate::baseline::Baseline;
+use crate::config::{LintConfig, LintLevel};
+use crate::crop::auto_crop_str;
+use crate::fix::fix_str;
+use crate::fmt::{format_pixelsrc, format_pixelsrc_with_legend};
+use crate::lint::Linter;
 use crate::lsp_agent_client::LspAgentClient;
+use crate::output::is_stdio;
+use crate::serve::PreviewCatalog;
+use crate::suggest::{format_suggestion, suggest};
 use crate::validate::{Severity, Validator};
 
 use super::{EXIT_ERROR, EXIT_INVALID_ARGS, EXIT_SUCCESS};
 
+/// Histogram entries listed by `--colors` text reports
+const COLOR_HISTOGRAM_TOP: usize = 16;
+
 /// Execute the analyze command
+#[allow(clippy::too_many_arguments)]
 pub fn run_analyze(
     files: &[PathBuf],
     dir: Option<&std::path::Path>,
     recursive: bool,
     format: &str,
     output: Option<&std::path::Path>,
+    duplicates: Option<f64>,
+    compare: Option<&std::path::Path>,
+    colors: Option<&str>,
 ) -> ExitCode {
     // Validate format
     if format != "text" && format != "json" {
         eprintln!("Error: --format must be 'text' or 'json'");
         return ExitCode::from(EXIT_INVALID_ARGS);
     }
+    if duplicates.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+        eprintln!("Error: --threshold must be between 0.0 and 1.0");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+
+    // Load the previous report before doing any work
+    let old_report: Option<serde_json::Value> = match compare.map(read_report).transpose() {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
 
     // Collect files to analyze
     let file_list = match collect_files(files, dir, recursive) {
@@ -38,10 +70,17 @@ pub fn run_analyze(
         return ExitCode::from(EXIT_INVALID_ARGS);
     }
 
+    if let Some(threshold) = duplicates {
+        return run_duplicates(&file_list, format, output, threshold);
+    }
+    if let Some(name) = colors {
+        return run_colors(&file_list, format, output, name);
+    }
+
     // Run analysis with progress indication
     let mut report = AnalysisReport::new();
     let total_files = file_list.len();
-    let show_progress = total_files > 1 && output.is_some();
+    let show_progress = total_files > 1 && output.is_some_and(|path| !is_stdio(path));
 
     for (i, path) in file_list.iter().enumerate() {
         if show_progress {
@@ -57,49 +96,142 @@ pub fn run_analyze(
     }
 
     // Format output
+    let output_text = if let Some(old_report) = old_report {
+        let comparison = match compare_reports(&old_report, &report.to_json()) {
+            Ok(comparison) => comparison,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        if format == "json" {
+            serde_json::json!(comparison).to_string()
+        } else {
+            format_comparison_text(&comparison)
+        }
+    } else if format == "json" {
+        report.to_json().to_string()
+    } else {
+        format_report_text(&report)
+    };
+
+    write_analysis(&output_text, output)
+}
+
+/// Read a previous `--format json` analysis report
+fn read_report(path: &std::path::Path) -> Result<serde_json::Value, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid analysis report '{}': {}", path.display(), e))
+}
+
+/// Find duplicate sprites across the analyzed files
+fn run_duplicates(
+    file_list: &[PathBuf],
+    format: &str,
+    output: Option<&std::path::Path>,
+    threshold: f64,
+) -> ExitCode {
+    let mut finder = DuplicateFinder::new();
+    let mut failed_files = Vec::new();
+    for path in file_list {
+        if let Err(e) = finder.add_file(path) {
+            failed_files.push((path, e));
+        }
+    }
+    let matches = finder.find(threshold);
+
     let output_text = if format == "json" {
-        // JSON output
+        let sprite = |(file, name): &(PathBuf, String)| serde_json::json!({ "file": file.display().to_string(), "sprite": name });
         serde_json::json!({
-            "files_analyzed": report.files_analyzed,
-            "files_failed": report.files_failed,
-            "total_sprites": report.total_sprites,
-            "total_palettes": report.total_palettes,
-            "total_compositions": report.total_compositions,
-            "total_animations": report.total_animations,
-            "total_variants": report.total_variants,
-            "unique_tokens": report.token_counter.unique_count(),
-            "total_token_occurrences": report.token_counter.total(),
-            "top_tokens": report.token_counter.top_n(10).iter().map(|(t, c)| {
-                serde_json::json!({
-                    "token": t,
-                    "count": c,
-                    "percentage": report.token_counter.percentage(t)
-                })
-            }).collect::<Vec<_>>(),
-            "co_occurrence": report.co_occurrence.top_n(10).iter().map(|((t1, t2), count)| {
-                serde_json::json!({
-                    "token1": t1,
-                    "token2": t2,
-                    "sprites": count
-                })
+            "sprites_compared": finder.fingerprints.len(),
+            "threshold": threshold,
+            "failed_files": failed_files.iter().map(|(path, e)| {
+                serde_json::json!({ "file": path.display().to_string(), "error": e })
             }).collect::<Vec<_>>(),
-            "token_families": report.token_families().iter().take(10).map(|family| {
+            "duplicates": matches.iter().map(|m| {
                 serde_json::json!({
-                    "prefix": family.prefix,
-                    "tokens": family.tokens,
-                    "total_count": family.total_count
+                    "kind": m.kind.as_str(),
+                    "first": sprite(&m.first),
+                    "second": sprite(&m.second),
+                    "transform": m.orientation.to_string(),
+                    "similarity": m.similarity,
                 })
             }).collect::<Vec<_>>(),
-            "avg_palette_size": report.avg_palette_size(),
         })
         .to_string()
     } else {
-        format_report_text(&report)
+        for (path, e) in &failed_files {
+            eprintln!("Warning: Skipping '{}': {}", path.display(), e);
+        }
+        format!(
+            "Compared {} sprites\n\n{}",
+            finder.fingerprints.len(),
+            format_duplicates_text(&matches, threshold)
+        )
+    };
+
+    write_analysis(&output_text, output)
+}
+
+/// Report color statistics of one rendered sprite or composition
+fn run_colors(
+    file_list: &[PathBuf],
+    format: &str,
+    output: Option<&std::path::Path>,
+    name: &str,
+) -> ExitCode {
+    let catalog = if file_list.iter().any(|path| is_stdio(path)) {
+        let mut source = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+            eprintln!("Error: Cannot read stdin: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        PreviewCatalog::from_source(&source)
+    } else {
+        PreviewCatalog::load(file_list)
+    };
+
+    let sprites = catalog.sprite_names();
+    let compositions = catalog.composition_names();
+    let (kind, rendered) = if sprites.contains(&name) {
+        ("sprite", catalog.render_sprite(name))
+    } else if compositions.contains(&name) {
+        ("composition", catalog.render_composition(name))
+    } else {
+        eprintln!("Error: No sprite or composition named '{}' found", name);
+        let candidates: Vec<&str> = sprites.iter().chain(&compositions).copied().collect();
+        if let Some(suggestion) = format_suggestion(&suggest(name, &candidates, 3)) {
+            eprintln!("{}", suggestion);
+        }
+        return ExitCode::from(EXIT_ERROR);
     };
+    let image = match rendered {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    let stats = ColorStats::new(&image);
+    let output_text = if format == "json" {
+        let mut json = stats.to_json();
+        json["kind"] = kind.into();
+        json["name"] = name.into();
+        json.to_string()
+    } else {
+        format_color_stats_text(kind, name, &stats, COLOR_HISTOGRAM_TOP)
+    };
+
+    write_analysis(&output_text, output)
+}
 
-    // Write output
-    if let Some(output_path) = output {
-        if let Err(e) = std::fs::write(output_path, &output_text) {
+/// Write analysis output to a file or stdout
+fn write_analysis(output_text: &str, output: Option<&std::path::Path>) -> ExitCode {
+    if let Some(output_path) = output.filter(|path| !is_stdio(path)) {
+        if let Err(e) = std::fs::write(output_path, output_text) {
             eprintln!("Error: Failed to write '{}': {}", output_path.display(), e);
             return ExitCode::from(EXIT_ERROR);
         }
@@ -112,12 +244,19 @@ pub fn run_analyze(
 }
 
 /// Execute the fmt command
-pub fn run_fmt(files: &[PathBuf], check: bool, stdout_mode: bool) -> ExitCode {
+pub fn run_fmt(files: &[PathBuf], check: bool, stdout_mode: bool, legend: bool) -> ExitCode {
     let mut needs_formatting = false;
 
     for file in files {
-        // Read file content
-        let content = match std::fs::read_to_string(file) {
+        // Read file content; `-` is formatted from stdin to stdout
+        let from_stdin = is_stdio(file);
+        let content = if from_stdin {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content).map(|_| content)
+        } else {
+            std::fs::read_to_string(file)
+        };
+        let content = match content {
             Ok(c) => c,
             Err(e) => {
                 eprintln!("Error: Cannot read '{}': {}", file.display(), e);
@@ -126,7 +265,9 @@ pub fn run_fmt(files: &[PathBuf], check: bool, stdout_mode: bool) -> ExitCode {
         };
 
         // Format the content
-        let formatted = match format_pixelsrc(&content) {
+        let formatted =
+            if legend { format_pixelsrc_with_legend(&content) } else { format_pixelsrc(&content) };
+        let formatted = match formatted {
             Ok(f) => f,
             Err(e) => {
                 eprintln!("Error: Cannot format '{}': {}", file.display(), e);
@@ -140,7 +281,7 @@ pub fn run_fmt(files: &[PathBuf], check: bool, stdout_mode: bool) -> ExitCode {
                 eprintln!("{}: needs formatting", file.display());
                 needs_formatting = true;
             }
-        } else if stdout_mode {
+        } else if stdout_mode || from_stdin {
             // Stdout mode: print formatted content
             print!("{}", formatted);
         } else {
@@ -165,11 +306,48 @@ pub fn run_fmt(files: &[PathBuf], check: bool, stdout_mode: bool) -> ExitCode {
 }
 
 /// Execute the validate command
-pub fn run_validate(files: &[PathBuf], stdin: bool, strict: bool, json: bool) -> ExitCode {
+pub fn run_validate(
+    files: &[PathBuf],
+    stdin: bool,
+    strict: bool,
+    json: bool,
+    baseline: Option<&std::path::Path>,
+    write_baseline: Option<&std::path::Path>,
+) -> ExitCode {
     use crate::config::loader::find_config_from;
     use std::io::{self, BufRead};
 
-    let mut validator = Validator::new();
+    // `pxl validate -` is the same as `--stdin`
+    let dash = files.iter().any(|path| is_stdio(path));
+    if dash && files.len() > 1 {
+        eprintln!("Error: Cannot validate stdin together with other files");
+        return ExitCode::from(EXIT_INVALID_ARGS);
+    }
+    let stdin = stdin || dash;
+
+    // Load the baseline before doing any work
+    let baseline = match baseline.map(Baseline::load).transpose() {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    // Project config from the nearest pxl.toml (above the first file, or the working directory)
+    let search_dir = files
+        .first()
+        .filter(|_| !stdin)
+        .and_then(|first_file| first_file.canonicalize().ok())
+        .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let project = find_config_from(search_dir).and_then(|config_path| {
+        let config = crate::config::loader::load_config(Some(&config_path)).ok()?;
+        Some((config_path, config))
+    });
+
+    let limits = project.as_ref().map(|(_, config)| config.limits.clone()).unwrap_or_default();
+    let mut validator = Validator::new().with_limits(limits);
 
     if stdin {
         // Read from stdin
@@ -202,23 +380,12 @@ pub fn run_validate(files: &[PathBuf], stdin: bool, strict: bool, json: bool) ->
         }
 
         // Try project-aware import validation
-        // Look for pxl.toml to find project context
-        if let Some(first_file) = files.first() {
-            let search_dir = first_file
-                .canonicalize()
-                .ok()
-                .and_then(|p| p.parent().map(|d| d.to_path_buf()))
-                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
-
-            if let Some(config_path) = find_config_from(search_dir) {
-                if let Some(project_root) = config_path.parent() {
-                    if let Ok(config) = crate::config::loader::load_config(Some(&config_path)) {
-                        let src_root = project_root.join(&config.project.src);
-                        if src_root.exists() {
-                            for path in files {
-                                validator.validate_imports_with_project(path, &src_root);
-                            }
-                        }
+        if let Some((config_path, config)) = &project {
+            if let Some(project_root) = config_path.parent() {
+                let src_root = project_root.join(&config.project.src);
+                if src_root.exists() {
+                    for path in files {
+                        validator.validate_imports_with_project(path, &src_root);
                     }
                 }
             }
@@ -226,6 +393,28 @@ pub fn run_validate(files: &[PathBuf], stdin: bool, strict: bool, json: bool) ->
     }
 
     let issues = validator.into_issues();
+
+    if let Some(path) = write_baseline {
+        let baseline = Baseline::from_issues(&issues);
+        if let Err(e) = baseline.save(path) {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+        let n = baseline.len();
+        eprintln!(
+            "Wrote {} issue{} to baseline {}",
+            n,
+            if n == 1 { "" } else { "s" },
+            path.display()
+        );
+        return ExitCode::from(EXIT_SUCCESS);
+    }
+
+    // Issues recorded in the baseline neither print nor fail
+    let (issues, suppressed) = match &baseline {
+        Some(baseline) => baseline.filter_new(issues),
+        None => (issues, 0),
+    };
     let error_count = issues.iter().filter(|i| matches!(i.severity, Severity::Error)).count();
     let warning_count = issues.iter().filter(|i| matches!(i.severity, Severity::Warning)).count();
 
@@ -272,11 +461,14 @@ pub fn run_validate(files: &[PathBuf], stdin: bool, strict: bool, json: bool) ->
             })
             .collect();
 
-        let output = serde_json::json!({
+        let mut output = serde_json::json!({
             "valid": !has_failures,
             "errors": errors,
             "warnings": warnings,
         });
+        if baseline.is_some() {
+            output["baselined"] = serde_json::json!(suppressed);
+        }
 
         println!("{}", serde_json::to_string_pretty(&output).expect("JSON value serialization"));
     } else {
@@ -321,6 +513,13 @@ pub fn run_validate(files: &[PathBuf], stdin: bool, strict: bool, json: bool) ->
                 println!("Hint: Run with --strict to treat warnings as errors.");
             }
         }
+        if suppressed > 0 {
+            println!(
+                "{} baselined issue{} suppressed.",
+                suppressed,
+                if suppressed == 1 { "" } else { "s" }
+            );
+        }
     }
 
     if has_failures {
@@ -330,6 +529,252 @@ pub fn run_validate(files: &[PathBuf], stdin: bool, strict: bool, json: bool) ->
     }
 }
 
+/// Execute the lint command
+pub fn run_lint(files: &[PathBuf], json: bool, config: Option<&std::path::Path>) -> ExitCode {
+    use crate::config::loader::{find_config_from, load_config};
+
+    // Explicit config, else pxl.toml above the first file, else defaults
+    let config_path = config.map(|p| p.to_path_buf()).or_else(|| {
+        let first = files.first()?.canonicalize().ok()?;
+        find_config_from(first.parent()?.to_path_buf())
+    });
+    let lint_config = match config_path {
+        Some(path) => match load_config(Some(&path)) {
+            Ok(config) => config.lint,
+            Err(e) => {
+                eprintln!("Error: {}: {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        },
+        None => LintConfig::default(),
+    };
+    let linter = Linter::new(lint_config);
+
+    let mut results = Vec::new();
+    for path in files {
+        match linter.lint_file(path) {
+            Ok(diagnostics) => results.push((path, diagnostics)),
+            Err(e) => {
+                eprintln!("Error: Cannot read '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+    }
+
+    let all = results.iter().flat_map(|(_, diagnostics)| diagnostics);
+    let deny_count = all.clone().filter(|d| d.level == LintLevel::Deny).count();
+    let warn_count = all.filter(|d| d.level == LintLevel::Warn).count();
+
+    if json {
+        let diagnostics: Vec<_> = results
+            .iter()
+            .flat_map(|(path, diagnostics)| {
+                diagnostics.iter().map(move |d| {
+                    serde_json::json!({
+                        "file": path.display().to_string(),
+                        "line": d.line,
+                        "rule": d.rule,
+                        "level": d.level,
+                        "message": d.message,
+                    })
+                })
+            })
+            .collect();
+        let output = serde_json::json!({
+            "passed": deny_count == 0,
+            "errors": deny_count,
+            "warnings": warn_count,
+            "diagnostics": diagnostics,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).expect("JSON value serialization"));
+    } else {
+        for (path, diagnostics) in &results {
+            for d in diagnostics {
+                let severity = if d.level == LintLevel::Deny { "error" } else { "warning" };
+                eprintln!("{}:{}: {}[{}]: {}", path.display(), d.line, severity, d.rule, d.message);
+            }
+        }
+        if deny_count + warn_count == 0 {
+            println!("No lint issues found.");
+        } else {
+            println!(
+                "Found {} error{}, {} warning{}.",
+                deny_count,
+                if deny_count == 1 { "" } else { "s" },
+                warn_count,
+                if warn_count == 1 { "" } else { "s" }
+            );
+        }
+    }
+
+    if deny_count > 0 {
+        ExitCode::from(EXIT_ERROR)
+    } else {
+        ExitCode::from(EXIT_SUCCESS)
+    }
+}
+
+/// Execute the fix command
+pub fn run_fix(files: &[PathBuf], check: bool, json: bool) -> ExitCode {
+    let mut results = Vec::new();
+    for path in files {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error: Cannot read '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        let result = match fix_str(&content) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error: Cannot fix '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        if !check && !result.fixes.is_empty() {
+            if let Err(e) = std::fs::write(path, &result.text) {
+                eprintln!("Error: Cannot write '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+        results.push((path, result.fixes));
+    }
+
+    let total: usize = results.iter().map(|(_, fixes)| fixes.len()).sum();
+    if json {
+        let fixes: Vec<_> = results
+            .iter()
+            .flat_map(|(path, fixes)| {
+                fixes.iter().map(move |f| {
+                    serde_json::json!({
+                        "file": path.display().to_string(),
+                        "line": f.line,
+                        "fix": f.fix,
+                        "message": f.message,
+                    })
+                })
+            })
+            .collect();
+        let output = serde_json::json!({
+            "applied": !check,
+            "count": total,
+            "fixes": fixes,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).expect("JSON value serialization"));
+    } else {
+        for (path, fixes) in &results {
+            for f in fixes {
+                eprintln!("{}:{}: [{}] {}", path.display(), f.line, f.fix, f.message);
+            }
+        }
+        let verb = if check { "needed" } else { "applied" };
+        match total {
+            0 => println!("Nothing to fix."),
+            1 => println!("1 fix {}.", verb),
+            n => println!("{} fixes {}.", n, verb),
+        }
+    }
+
+    if check && total > 0 {
+        ExitCode::from(EXIT_ERROR)
+    } else {
+        ExitCode::from(EXIT_SUCCESS)
+    }
+}
+
+/// Execute the crop command
+pub fn run_crop(files: &[PathBuf], sprite: Option<&str>, check: bool, json: bool) -> ExitCode {
+    let mut results = Vec::new();
+    for path in files {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error: Cannot read '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        let result = match auto_crop_str(&content, sprite) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error: Cannot crop '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        };
+        if !check && !result.crops.is_empty() {
+            if let Err(e) = std::fs::write(path, &result.text) {
+                eprintln!("Error: Cannot write '{}': {}", path.display(), e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+        results.push((path, result));
+    }
+
+    let total: usize = results.iter().map(|(_, result)| result.crops.len()).sum();
+    if json {
+        let crops: Vec<_> = results
+            .iter()
+            .flat_map(|(path, result)| {
+                result.crops.iter().map(move |c| {
+                    serde_json::json!({
+                        "file": path.display().to_string(),
+                        "line": c.line,
+                        "sprite": c.sprite,
+                        "from": c.from,
+                        "to": c.to,
+                        "offset": c.offset,
+                    })
+                })
+            })
+            .collect();
+        let warnings: Vec<_> = results
+            .iter()
+            .flat_map(|(path, result)| {
+                result.warnings.iter().map(move |w| format!("{}: {}", path.display(), w))
+            })
+            .collect();
+        let output = serde_json::json!({
+            "applied": !check,
+            "count": total,
+            "crops": crops,
+            "warnings": warnings,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).expect("JSON value serialization"));
+    } else {
+        for (path, result) in &results {
+            for c in &result.crops {
+                eprintln!(
+                    "{}:{}: sprite '{}' {}x{} -> {}x{} (content moved by -{}, -{})",
+                    path.display(),
+                    c.line,
+                    c.sprite,
+                    c.from[0],
+                    c.from[1],
+                    c.to[0],
+                    c.to[1],
+                    c.offset[0],
+                    c.offset[1]
+                );
+            }
+            for w in &result.warnings {
+                eprintln!("Warning: {}: {}", path.display(), w);
+            }
+        }
+        let verb = if check { "to crop" } else { "cropped" };
+        match total {
+            0 => println!("Nothing to crop."),
+            1 => println!("1 sprite {}.", verb),
+            n => println!("{} sprites {}.", n, verb),
+        }
+    }
+
+    if check && total > 0 {
+        ExitCode::from(EXIT_ERROR)
+    } else {
+        ExitCode::from(EXIT_SUCCESS)
+    }
+}
+
 /// Execute the agent-verify command
 pub fn run_agent_verify(
     stdin: bool,
@@ -0,0 +1,439 @@
+//! System clipboard access for `pxl copy` and `pxl paste`
+//!
+//! Images are moved through the platform's own clipboard tools rather than a
+//! native binding such as `arboard`, which only exchanges raw RGBA bitmaps:
+//! the tools take encoded PNG and GIF data, so copied animations keep their
+//! frames, and the CLI stays free of per-platform windowing dependencies.
+//!
+//! | Platform | Tool |
+//! |----------|------|
+//! | Linux (Wayland) | `wl-copy` / `wl-paste` (wl-clipboard) |
+//! | Linux (X11) | `xclip` |
+//! | macOS | `osascript` |
+//! | Windows | `powershell` |
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+/// Error type for clipboard operations
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ClipboardError {
+    /// No clipboard tool is known for this platform
+    #[error("Clipboard is not supported on this platform")]
+    Unsupported,
+    /// The clipboard tool could not be started
+    #[error("Failed to run '{tool}': {source} (is it installed?)")]
+    Spawn {
+        tool: &'static str,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The clipboard tool ran but reported failure
+    #[error("'{tool}' failed: {message}")]
+    Tool { tool: &'static str, message: String },
+    /// The clipboard does not hold an image
+    #[error("Clipboard does not contain an image")]
+    NoImage,
+    /// IO error while staging clipboard data
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Image formats that can be placed on the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardImage {
+    Png,
+    Gif,
+}
+
+impl ClipboardImage {
+    /// MIME type used by the Linux clipboard tools.
+    pub fn mime(self) -> &'static str {
+        match self {
+            ClipboardImage::Png => "image/png",
+            ClipboardImage::Gif => "image/gif",
+        }
+    }
+
+    /// AppleScript clipboard class for the format.
+    fn apple_class(self) -> &'static str {
+        match self {
+            ClipboardImage::Png => "«class PNGf»",
+            ClipboardImage::Gif => "«class GIFf»",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ClipboardImage::Png => "png",
+            ClipboardImage::Gif => "gif",
+        }
+    }
+}
+
+/// Clipboard tool used to talk to the system clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// wl-clipboard (`wl-copy` / `wl-paste`)
+    Wayland,
+    /// `xclip`
+    X11,
+    /// `osascript`
+    MacOs,
+    /// `powershell`
+    Windows,
+}
+
+/// A clipboard tool invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Invocation {
+    tool: &'static str,
+    args: Vec<String>,
+    /// Image bytes are piped to the tool's stdin
+    stdin: bool,
+    /// Image bytes are read from the tool's stdout
+    stdout: bool,
+}
+
+impl Backend {
+    /// Pick the backend for the current platform and session.
+    pub fn detect() -> Result<Self, ClipboardError> {
+        if cfg!(target_os = "macos") {
+            Ok(Backend::MacOs)
+        } else if cfg!(windows) {
+            Ok(Backend::Windows)
+        } else if cfg!(unix) {
+            if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                Ok(Backend::Wayland)
+            } else {
+                Ok(Backend::X11)
+            }
+        } else {
+            Err(ClipboardError::Unsupported)
+        }
+    }
+
+    /// Whether this backend exchanges image data through a file instead of a pipe.
+    fn uses_file(self) -> bool {
+        matches!(self, Backend::MacOs | Backend::Windows)
+    }
+
+    fn copy_invocation(self, kind: ClipboardImage, file: &Path) -> Invocation {
+        let file = file.display().to_string();
+        match self {
+            Backend::Wayland => Invocation {
+                tool: "wl-copy",
+                args: vec!["--type".into(), kind.mime().into()],
+                stdin: true,
+                stdout: false,
+            },
+            Backend::X11 => Invocation {
+                tool: "xclip",
+                args: vec![
+                    "-selection".into(),
+                    "clipboard".into(),
+                    "-t".into(),
+                    kind.mime().into(),
+                    "-i".into(),
+                ],
+                stdin: true,
+                stdout: false,
+            },
+            Backend::MacOs => Invocation {
+                tool: "osascript",
+                args: vec![
+                    "-e".into(),
+                    format!(
+                        "set the clipboard to (read (POSIX file \"{}\") as {})",
+                        applescript_escape(&file),
+                        kind.apple_class()
+                    ),
+                ],
+                stdin: false,
+                stdout: false,
+            },
+            Backend::Windows => {
+                // Clipboard bitmaps can't hold animation, so GIFs are copied as a file
+                let script = match kind {
+                    ClipboardImage::Png => format!(
+                        "Add-Type -AssemblyName System.Windows.Forms, System.Drawing; \
+                         [System.Windows.Forms.Clipboard]::SetImage(\
+                         [System.Drawing.Image]::FromFile('{}'))",
+                        powershell_escape(&file)
+                    ),
+                    ClipboardImage::Gif => {
+                        format!("Set-Clipboard -LiteralPath '{}'", powershell_escape(&file))
+                    }
+                };
+                Invocation {
+                    tool: "powershell",
+                    args: vec!["-NoProfile".into(), "-STA".into(), "-Command".into(), script],
+                    stdin: false,
+                    stdout: false,
+                }
+            }
+        }
+    }
+
+    fn paste_invocation(self, file: &Path) -> Invocation {
+        let file = file.display().to_string();
+        match self {
+            Backend::Wayland => Invocation {
+                tool: "wl-paste",
+                args: vec!["--no-newline".into(), "--type".into(), "image/png".into()],
+                stdin: false,
+                stdout: true,
+            },
+            Backend::X11 => Invocation {
+                tool: "xclip",
+                args: vec![
+                    "-selection".into(),
+                    "clipboard".into(),
+                    "-t".into(),
+                    "image/png".into(),
+                    "-o".into(),
+                ],
+                stdin: false,
+                stdout: true,
+            },
+            Backend::MacOs => Invocation {
+                tool: "osascript",
+                args: vec![
+                    "-e".into(),
+                    "set png to (the clipboard as «class PNGf»)".into(),
+                    "-e".into(),
+                    format!(
+                        "set f to open for access (POSIX file \"{}\") with write permission",
+                        applescript_escape(&file)
+                    ),
+                    "-e".into(),
+                    "write png to f".into(),
+                    "-e".into(),
+                    "close access f".into(),
+                ],
+                stdin: false,
+                stdout: false,
+            },
+            Backend::Windows => Invocation {
+                tool: "powershell",
+                args: vec![
+                    "-NoProfile".into(),
+                    "-STA".into(),
+                    "-Command".into(),
+                    format!(
+                        "Add-Type -AssemblyName System.Drawing; \
+                         $img = Get-Clipboard -Format Image; \
+                         if ($img -eq $null) {{ exit 1 }}; \
+                         $img.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png)",
+                        powershell_escape(&file)
+                    ),
+                ],
+                stdin: false,
+                stdout: false,
+            },
+        }
+    }
+}
+
+fn applescript_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn powershell_escape(s: &str) -> String {
+    s.replace('\'', "''")
+}
+
+/// A fresh, uniquely named temporary file, created without following symlinks.
+fn temp_file(extension: &str) -> Result<NamedTempFile, ClipboardError> {
+    Ok(tempfile::Builder::new()
+        .prefix("pxl_clipboard_")
+        .suffix(&format!(".{}", extension))
+        .tempfile()?)
+}
+
+fn command(invocation: &Invocation, stderr: Stdio) -> Command {
+    let mut command = Command::new(invocation.tool);
+    command
+        .args(&invocation.args)
+        .stdin(if invocation.stdin { Stdio::piped() } else { Stdio::null() })
+        .stdout(if invocation.stdout { Stdio::piped() } else { Stdio::null() })
+        .stderr(stderr);
+    command
+}
+
+/// The tool's error output, or its exit status when it printed nothing.
+fn tool_error(invocation: &Invocation, status: ExitStatus, stderr: &[u8]) -> ClipboardError {
+    let message = String::from_utf8_lossy(stderr).trim().to_string();
+    let message = if message.is_empty() { status.to_string() } else { message };
+    ClipboardError::Tool { tool: invocation.tool, message }
+}
+
+/// Run a paste tool and collect its output.
+///
+/// A paste tool that fails without saying why found no image.
+fn run(invocation: &Invocation) -> Result<Vec<u8>, ClipboardError> {
+    let output = command(invocation, Stdio::piped())
+        .output()
+        .map_err(|source| ClipboardError::Spawn { tool: invocation.tool, source })?;
+    if !output.status.success() {
+        if output.stderr.trim_ascii().is_empty() {
+            return Err(ClipboardError::NoImage);
+        }
+        return Err(tool_error(invocation, output.status, &output.stderr));
+    }
+    Ok(output.stdout)
+}
+
+/// Run a copy tool, feeding it `input` on stdin.
+///
+/// `xclip` and `wl-copy` leave a background process serving the selection
+/// that inherits stderr, so a piped stderr would not close until another
+/// application takes the clipboard. Stderr goes to a temporary file instead
+/// and only the exit status is waited on.
+fn run_copy(invocation: &Invocation, input: Option<&[u8]>) -> Result<(), ClipboardError> {
+    let log = temp_file("log")?;
+    let mut child = command(invocation, Stdio::from(log.reopen()?))
+        .spawn()
+        .map_err(|source| ClipboardError::Spawn { tool: invocation.tool, source })?;
+    if let (Some(mut stdin), Some(bytes)) = (child.stdin.take(), input) {
+        stdin.write_all(bytes)?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        let stderr = std::fs::read(log.path()).unwrap_or_default();
+        return Err(tool_error(invocation, status, &stderr));
+    }
+    Ok(())
+}
+
+/// Place encoded image bytes on the system clipboard.
+pub fn copy_image(bytes: &[u8], kind: ClipboardImage) -> Result<(), ClipboardError> {
+    let backend = Backend::detect()?;
+    if !backend.uses_file() {
+        return run_copy(&backend.copy_invocation(kind, Path::new("")), Some(bytes));
+    }
+
+    let mut file = temp_file(kind.extension())?;
+    file.write_all(bytes)?;
+    let path = file.into_temp_path();
+    let result = run_copy(&backend.copy_invocation(kind, &path), None);
+    // Windows pastes GIFs from the file itself, so it has to outlive this process
+    if result.is_ok() && backend == Backend::Windows && kind == ClipboardImage::Gif {
+        path.keep().map_err(|e| ClipboardError::Io(e.error))?;
+    }
+    result
+}
+
+/// Read a PNG image from the system clipboard.
+pub fn paste_png() -> Result<Vec<u8>, ClipboardError> {
+    let backend = Backend::detect()?;
+    let bytes = if backend.uses_file() {
+        let path = temp_file(ClipboardImage::Png.extension())?.into_temp_path();
+        run(&backend.paste_invocation(&path))?;
+        std::fs::read(&path)?
+    } else {
+        run(&backend.paste_invocation(Path::new("")))?
+    };
+
+    if bytes.is_empty() {
+        return Err(ClipboardError::NoImage);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linux_copy_pipes_mime_type() {
+        let path = Path::new("/tmp/unused.gif");
+        let wayland = Backend::Wayland.copy_invocation(ClipboardImage::Gif, path);
+        assert_eq!(wayland.tool, "wl-copy");
+        assert_eq!(wayland.args, ["--type", "image/gif"]);
+        assert!(wayland.stdin);
+
+        let x11 = Backend::X11.copy_invocation(ClipboardImage::Png, path);
+        assert_eq!(x11.tool, "xclip");
+        assert!(x11.args.iter().any(|a| a == "image/png"));
+        assert!(x11.stdin && !x11.stdout);
+    }
+
+    #[test]
+    fn test_linux_paste_reads_stdout() {
+        let path = Path::new("/tmp/unused.png");
+        for backend in [Backend::Wayland, Backend::X11] {
+            let invocation = backend.paste_invocation(path);
+            assert!(invocation.stdout && !invocation.stdin);
+            assert!(invocation.args.iter().any(|a| a == "image/png"));
+        }
+    }
+
+    #[test]
+    fn test_file_backends_quote_paths() {
+        let mac = Backend::MacOs.copy_invocation(ClipboardImage::Png, Path::new("/tmp/a\"b.png"));
+        assert_eq!(
+            mac.args[1],
+            "set the clipboard to (read (POSIX file \"/tmp/a\\\"b.png\") as «class PNGf»)"
+        );
+
+        let win = Backend::Windows.copy_invocation(ClipboardImage::Gif, Path::new("C:\\it's.gif"));
+        assert_eq!(win.args[3], "Set-Clipboard -LiteralPath 'C:\\it''s.gif'");
+        assert!(Backend::Windows.uses_file() && !Backend::X11.uses_file());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_does_not_wait_for_background_owner() {
+        // Like xclip: read the data, then leave a child holding stderr open
+        let invocation = Invocation {
+            tool: "sh",
+            args: vec!["-c".into(), "cat >/dev/null; (sleep 10 &)".into()],
+            stdin: true,
+            stdout: false,
+        };
+        let start = std::time::Instant::now();
+        run_copy(&invocation, Some(b"png")).unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_reports_tool_stderr() {
+        let invocation = Invocation {
+            tool: "sh",
+            args: vec!["-c".into(), "echo 'no display' >&2; exit 1".into()],
+            stdin: false,
+            stdout: false,
+        };
+        let err = run_copy(&invocation, None).unwrap_err();
+        assert_eq!(err.to_string(), "'sh' failed: no display");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_silent_copy_failure_reports_exit_status() {
+        let invocation = Invocation {
+            tool: "sh",
+            args: vec!["-c".into(), "exit 3".into()],
+            stdin: false,
+            stdout: false,
+        };
+        let err = run_copy(&invocation, None).unwrap_err();
+        assert!(matches!(err, ClipboardError::Tool { .. }), "{:?}", err);
+        assert!(err.to_string().ends_with("exit status: 3"), "{}", err);
+    }
+
+    #[test]
+    fn test_temp_files_are_unique() {
+        let (a, b) = (temp_file("png").unwrap(), temp_file("png").unwrap());
+        assert_ne!(a.path(), b.path());
+        let name = a.path().file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("pxl_clipboard_") && name.ends_with(".png"), "{}", name);
+    }
+}
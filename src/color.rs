@@ -2,8 +2,14 @@
 //!
 //! Supports the following formats:
 //! - Hex: `#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`
-//! - Functional: `rgb()`, `rgba()`, `hsl()`, `hsla()`, `hwb()`, `oklch()`
+//! - Functional: `rgb()`, `rgba()`, `hsl()`, `hsla()`, `hwb()`, `lab()`, `lch()`,
+//!   `oklab()`, `oklch()`
+//! - Predefined color spaces: `color(display-p3 ...)`, `color(srgb ...)`, etc.
+//! - Relative colors: `rgb(from <color> r g b / 0.5)`
 //! - Named: `red`, `blue`, `transparent`, etc.
+//!
+//! Colors outside the sRGB gamut are gamut-mapped (CSS Color 4 OKLCH chroma
+//! reduction) rather than clipped per channel.
 
 use image::Rgba;
 use lightningcss::traits::Parse;
@@ -54,6 +60,14 @@ impl<T: std::fmt::Display> From<lightningcss::error::Error<T>> for ColorError {
 /// - `hsla(0, 100%, 50%, 0.5)`
 /// - `hwb(0 0% 0%)` - hue, whiteness, blackness
 /// - `oklch(0.628 0.258 29.23)` - OKLCH color space
+/// - `lab(54.29% 80.8 69.9)` / `lch(54.29% 106.8 40.85)` - CIE Lab and LCH
+/// - `color(display-p3 1 0.5 0)` - predefined RGB and XYZ color spaces
+///
+/// ## Relative Colors
+/// - `rgb(from #336699 r g b / 50%)` - channels of an origin color
+/// - `hsl(from red calc(h + 120) s l)` - with `calc()` arithmetic
+///
+/// Out-of-gamut colors are mapped into sRGB by reducing OKLCH chroma.
 ///
 /// ## Named Colors
 /// - CSS named colors: `red`, `blue`, `green`, `transparent`, etc.
@@ -146,12 +160,92 @@ fn parse_hex_color(s: &str) -> Result<Rgba<u8>, ColorError> {
     }
 }
 
-/// Parse a CSS color using lightningcss (rgb, hsl, hwb, oklch, named colors)
+/// Parse a CSS color using lightningcss (rgb, hsl, hwb, lab, lch, oklch, color(), named colors)
 fn parse_css_color(s: &str) -> Result<Rgba<u8>, ColorError> {
-    let css_color = CssColor::parse_string(s).map_err(|e| ColorError::CssParse(e.to_string()))?;
+    let normalized = normalize_relative_hue(s);
+    let css_color = CssColor::parse_string(normalized.as_deref().unwrap_or(s))
+        .map_err(|e| ColorError::CssParse(e.to_string()))?;
     css_color_to_rgba(css_color)
 }
 
+/// Give unitless hue terms in relative-color `calc()` an explicit `deg` unit.
+///
+/// CSS resolves the `h` channel keyword to a number, so `calc(h + 120)` is
+/// valid, but lightningcss resolves it to an angle and rejects mixing it
+/// with plain numbers. Numbers added to or subtracted from another term are
+/// rewritten as degrees (`calc(h + 120deg)`), which means the same thing.
+/// Returns `None` when nothing needs rewriting.
+fn normalize_relative_hue(s: &str) -> Option<String> {
+    let open = s.find('(')?;
+    let hue_index = match s[..open].trim().to_ascii_lowercase().as_str() {
+        "hsl" | "hsla" | "hwb" => 0,
+        "lch" | "oklch" => 2,
+        _ => return None,
+    };
+    let inner = s[open + 1..].trim_end().strip_suffix(')')?;
+    let args = split_top_level(inner);
+    if !args.first()?.eq_ignore_ascii_case("from") {
+        return None;
+    }
+
+    // args: "from", origin, channel, channel, channel, ["/", alpha]
+    let hue = *args.get(2 + hue_index)?;
+    let expr = hue.strip_prefix("calc(").and_then(|e| e.strip_suffix(')'))?;
+
+    let tokens: Vec<&str> = expr.split_whitespace().collect();
+    let is_additive = |i: Option<&&str>| matches!(i, Some(&"+") | Some(&"-"));
+    let mut changed = false;
+    let rewritten: Vec<String> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            let start = token.len() - token.trim_start_matches('(').len();
+            let end = token.trim_end_matches(')').len();
+            let number = token.get(start..end).unwrap_or("");
+            let adjacent =
+                is_additive(tokens.get(i + 1)) || (i > 0 && is_additive(tokens.get(i - 1)));
+            if adjacent && !number.is_empty() && number.parse::<f64>().is_ok() {
+                changed = true;
+                format!("{}deg{}", &token[..end], &token[end..])
+            } else {
+                token.to_string()
+            }
+        })
+        .collect();
+    if !changed {
+        return None;
+    }
+
+    let mut parts: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    parts[2 + hue_index] = format!("calc({})", rewritten.join(" "));
+    Some(format!("{}({})", &s[..open], parts.join(" ")))
+}
+
+/// Split on whitespace that isn't nested inside parentheses.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if c.is_whitespace() && depth == 0 {
+            if let Some(st) = start.take() {
+                parts.push(&s[st..i]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(st) = start {
+        parts.push(&s[st..]);
+    }
+    parts
+}
+
 /// Convert a lightningcss CssColor to RGBA
 fn css_color_to_rgba(color: CssColor) -> Result<Rgba<u8>, ColorError> {
     use lightningcss::values::color::FloatColor;
@@ -721,6 +815,86 @@ mod tests {
         assert!(red.0[2] < 10); // B close to 0
     }
 
+    #[test]
+    fn test_parse_lab_lch_functional() {
+        // CSS Color 4 spec example: lab(29.2345% 39.3825 20.0664) = rgb(125 35 41)
+        assert_eq!(
+            parse_color("lab(29.2345% 39.3825 20.0664)").expect("lab"),
+            Rgba([125, 35, 41, 255])
+        );
+        assert_eq!(parse_color("lab(100 0 0)").expect("lab white"), Rgba([255, 255, 255, 255]));
+        // lch is lab in polar form
+        assert_eq!(
+            parse_color("lch(50% 72 56)").expect("lch"),
+            parse_color("lab(50% 40.26 59.69)").expect("lab")
+        );
+        assert_eq!(parse_color("lch(50% 0 0 / 0.5)").expect("lch alpha").0[3], 128);
+    }
+
+    #[test]
+    fn test_parse_display_p3_gamut_mapped() {
+        // In-gamut P3 colors convert exactly
+        assert_eq!(
+            parse_color("color(display-p3 0.5 0.5 0.5)").expect("p3 gray"),
+            Rgba([128, 128, 128, 255])
+        );
+        assert_eq!(parse_color("color(srgb 1 0 0)").expect("srgb"), Rgba([255, 0, 0, 255]));
+
+        // P3 red is outside sRGB: chroma is reduced instead of clipping to #FF0000
+        let red = parse_color("color(display-p3 1 0 0)").expect("p3 red");
+        assert_eq!(red.0[0], 255);
+        assert!(red.0[1] > 0 && red.0[1] < 30, "green: {}", red.0[1]);
+        assert!(red.0[2] > 0 && red.0[2] < 30, "blue: {}", red.0[2]);
+    }
+
+    #[test]
+    fn test_parse_relative_color() {
+        assert_eq!(
+            parse_color("rgb(from #336699 r g b / 0.5)").expect("relative alpha"),
+            Rgba([51, 102, 153, 128])
+        );
+        assert_eq!(
+            parse_color("rgb(from red calc(r / 2) g b)").expect("relative calc"),
+            Rgba([128, 0, 0, 255])
+        );
+        assert_eq!(
+            parse_color("hsl(from red h s calc(l - 20))").expect("relative hsl"),
+            Rgba([153, 0, 0, 255])
+        );
+        assert_eq!(
+            parse_color("lch(from #336699 l c h)").expect("relative lch"),
+            Rgba([51, 102, 153, 255])
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_color_unitless_hue() {
+        let green = Rgba([0, 255, 0, 255]);
+        assert_eq!(parse_color("hsl(from red calc(h + 120) s l)").expect("hsl"), green);
+        assert_eq!(parse_color("hsl(from red calc(h + 120deg) s l)").expect("hsl deg"), green);
+        assert_eq!(parse_color("hwb(from red calc((h + 60) * 2) w b)").expect("hwb"), green);
+        assert_eq!(
+            parse_color("oklch(from red l c calc(h + 180))").expect("oklch"),
+            parse_color("oklch(from red l c calc(h + 180deg))").expect("oklch deg")
+        );
+    }
+
+    #[test]
+    fn test_normalize_relative_hue() {
+        assert_eq!(
+            normalize_relative_hue("hsl(from rgb(255 0 0) calc(h - 30) s l / 0.5)").as_deref(),
+            Some("hsl(from rgb(255 0 0) calc(h - 30deg) s l / 0.5)")
+        );
+        assert_eq!(
+            normalize_relative_hue("oklch(from red l c calc(2 * h + 10))").as_deref(),
+            Some("oklch(from red l c calc(2 * h + 10deg))")
+        );
+        // Nothing to rewrite
+        assert_eq!(normalize_relative_hue("hsl(from red calc(h * 2) s l)"), None);
+        assert_eq!(normalize_relative_hue("rgb(from red calc(r + 10) g b)"), None);
+        assert_eq!(normalize_relative_hue("hsl(120 100% 50%)"), None);
+    }
+
     #[test]
     fn test_parse_named_colors() {
         // Basic named colors
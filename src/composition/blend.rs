@@ -124,6 +124,20 @@ pub(crate) fn blit_sprite_blended(
     }
 }
 
+/// Clip an image by a mask's alpha channel.
+///
+/// Each pixel's alpha is scaled by the mask alpha at the same position
+/// (or by its complement when `invert` is set). Pixels outside the mask
+/// count as fully transparent mask.
+pub(crate) fn apply_mask(image: &mut RgbaImage, mask: &RgbaImage, invert: bool) {
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let coverage =
+            if x < mask.width() && y < mask.height() { mask.get_pixel(x, y)[3] } else { 0 };
+        let coverage = if invert { 255 - coverage } else { coverage };
+        pixel[3] = ((pixel[3] as u16 * coverage as u16 + 127) / 255) as u8;
+    }
+}
+
 /// Blend source pixel over destination using the specified blend mode and opacity.
 pub(crate) fn blend_pixels(
     src: &Rgba<u8>,
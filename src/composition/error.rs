@@ -2,17 +2,7 @@
 
 use thiserror::Error;
 
-/// A warning generated during composition rendering
-#[derive(Debug, Clone, PartialEq)]
-pub struct Warning {
-    pub message: String,
-}
-
-impl Warning {
-    pub fn new(message: impl Into<String>) -> Self {
-        Self { message: message.into() }
-    }
-}
+pub use crate::warning::{Warning, WarningCategory};
 
 /// Error when rendering a composition in strict mode.
 #[derive(Debug, Clone, PartialEq, Error)]
@@ -0,0 +1,184 @@
+//! Lighting: simple 2D light sources over a finished composition
+//!
+//! A composition with `ambient` or `lights` gets a final pass over its
+//! blended image. Each visible pixel is multiplied by the light reaching it:
+//! the ambient color (white by default) plus every light within range, scaled
+//! by its falloff and intensity. A dark ambient with a warm light makes a
+//! torch-lit night scene; lights alone brighten and tint a daytime one.
+//!
+//! ```json5
+//! { type: "composition", name: "camp", ambient: "#303050",
+//!   lights: [{ at: [10, 4], radius: 12, color: "#FFDD88", falloff: "smooth" }], ... }
+//! ```
+//!
+//! Lighting runs before `palette_lock`, so a locked composition snaps its lit
+//! colors back to the palette. Alpha is kept.
+
+use image::{Rgba, RgbaImage};
+
+use crate::color::parse_color;
+use crate::models::{Composition, Falloff, Light};
+
+use super::error::{Warning, WarningCategory};
+
+/// Apply a composition's `ambient` and `lights` to its rendered image.
+///
+/// Lights and ambient colors that can't be parsed are skipped with a warning.
+pub fn apply_lighting(comp: &Composition, image: &mut RgbaImage, warnings: &mut Vec<Warning>) {
+    if comp.lights.is_empty() && comp.ambient.is_none() {
+        return;
+    }
+
+    let ambient = match comp.ambient.as_deref().map(parse_color) {
+        None => [1.0; 3],
+        Some(Ok(rgba)) => unit_rgb(rgba),
+        Some(Err(e)) => {
+            warnings.push(Warning::new(
+                WarningCategory::Composition,
+                format!("Invalid ambient color in composition '{}': {}", comp.name, e),
+            ));
+            [1.0; 3]
+        }
+    };
+
+    let mut lights = Vec::new();
+    for light in &comp.lights {
+        match light.color.as_deref().map_or(Ok(Rgba([255; 4])), parse_color) {
+            Ok(rgba) => lights.push((light, unit_rgb(rgba))),
+            Err(e) => warnings.push(Warning::new(
+                WarningCategory::Composition,
+                format!(
+                    "Invalid color for light at [{}, {}] in composition '{}': {}",
+                    light.at[0], light.at[1], comp.name, e
+                ),
+            )),
+        }
+    }
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let mut light_rgb = ambient;
+        for (light, color) in &lights {
+            let strength = light_strength(light, x, y);
+            if strength > 0.0 {
+                for (channel, c) in light_rgb.iter_mut().zip(color) {
+                    *channel += c * strength;
+                }
+            }
+        }
+        for (value, light) in pixel.0.iter_mut().zip(light_rgb) {
+            *value = (f64::from(*value) * light).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+/// How much of a light reaches pixel `(x, y)`: its intensity at the center,
+/// dropping by its falloff to 0 at the radius.
+pub fn light_strength(light: &Light, x: u32, y: u32) -> f64 {
+    if light.radius == 0 {
+        return 0.0;
+    }
+    let dx = f64::from(x) - f64::from(light.at[0]);
+    let dy = f64::from(y) - f64::from(light.at[1]);
+    let t = (dx * dx + dy * dy).sqrt() / f64::from(light.radius);
+    if t >= 1.0 {
+        return 0.0;
+    }
+    let falloff = match light.falloff.unwrap_or_default() {
+        Falloff::Linear => 1.0 - t,
+        Falloff::Smooth => 1.0 - t * t * (3.0 - 2.0 * t),
+        Falloff::Hard => 1.0,
+    };
+    falloff * light.intensity.unwrap_or(1.0).max(0.0)
+}
+
+fn unit_rgb(rgba: Rgba<u8>) -> [f64; 3] {
+    [f64::from(rgba[0]) / 255.0, f64::from(rgba[1]) / 255.0, f64::from(rgba[2]) / 255.0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comp(ambient: Option<&str>, lights: Vec<Light>) -> Composition {
+        Composition {
+            name: "scene".to_string(),
+            base: None,
+            size: Some([8, 1]),
+            cell_size: None,
+            sprites: Default::default(),
+            layers: Vec::new(),
+            groups: Vec::new(),
+            locale: Default::default(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights,
+            ambient: ambient.map(str::to_string),
+            camera: None,
+        }
+    }
+
+    fn torch(falloff: Falloff) -> Light {
+        Light {
+            at: [0, 0],
+            radius: 4,
+            color: Some("#FF8000".to_string()),
+            falloff: Some(falloff),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_light_strength_falloff() {
+        let linear = torch(Falloff::Linear);
+        assert_eq!(light_strength(&linear, 0, 0), 1.0);
+        assert_eq!(light_strength(&linear, 2, 0), 0.5);
+        assert_eq!(light_strength(&linear, 4, 0), 0.0);
+
+        // Smoothstep stays brighter near the center and dimmer near the edge
+        let smooth = torch(Falloff::Smooth);
+        assert_eq!(light_strength(&smooth, 2, 0), 0.5);
+        assert!(light_strength(&smooth, 1, 0) > light_strength(&linear, 1, 0));
+        assert!(light_strength(&smooth, 3, 0) < light_strength(&linear, 3, 0));
+
+        assert_eq!(light_strength(&torch(Falloff::Hard), 3, 0), 1.0);
+        let dim = Light { intensity: Some(0.5), ..torch(Falloff::Hard) };
+        assert_eq!(light_strength(&dim, 3, 0), 0.5);
+    }
+
+    #[test]
+    fn test_apply_lighting_multiplies_ambient_and_adds_lights() {
+        let mut image = RgbaImage::from_pixel(8, 1, Rgba([200, 200, 200, 255]));
+        image.put_pixel(7, 0, Rgba([0, 0, 0, 0]));
+        let night = comp(Some("#808080"), vec![torch(Falloff::Hard)]);
+
+        let mut warnings = Vec::new();
+        apply_lighting(&night, &mut image, &mut warnings);
+        assert!(warnings.is_empty());
+
+        // Lit: red clamps, green gets half the torch on top of the ambient half
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 201, 100, 255]));
+        // Out of range: ambient only
+        assert_eq!(*image.get_pixel(5, 0), Rgba([100, 100, 100, 255]));
+        // Transparent pixels stay untouched
+        assert_eq!(*image.get_pixel(7, 0), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_apply_lighting_skips_invalid_colors() {
+        let mut image = RgbaImage::from_pixel(8, 1, Rgba([100, 100, 100, 255]));
+        let broken = Light { color: Some("torch".to_string()), ..torch(Falloff::Hard) };
+        let scene = comp(Some("dark"), vec![broken]);
+
+        let mut warnings = Vec::new();
+        apply_lighting(&scene, &mut image, &mut warnings);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].message.contains("ambient"));
+        assert!(warnings[1].message.contains("light at [0, 0]"));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([100, 100, 100, 255]));
+    }
+}
@@ -0,0 +1,121 @@
+//! Palette locking: snapping a blended composition back to a palette
+//!
+//! Blend modes and partial opacity mix colors that no palette defines. A
+//! composition with `palette_lock` has every visible pixel of its final image
+//! replaced by the nearest (CIE76) color of the locked palette. Alpha is kept,
+//! so translucent pixels stay translucent.
+
+use std::collections::{HashMap, HashSet};
+
+use image::RgbaImage;
+
+use crate::color::parse_color;
+use crate::import::LabColor;
+use crate::models::{Composition, PaletteLock};
+use crate::palettes::get_builtin;
+use crate::registry::PaletteRegistry;
+
+use super::error::{Warning, WarningCategory};
+
+/// How many pixels a palette lock changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteLockReport {
+    /// What the composition was locked to: a palette name or `sprites`
+    pub palette: String,
+    /// Colors in the locked palette
+    pub colors: usize,
+    /// Visible pixels of the image
+    pub pixels: usize,
+    /// Pixels that were off-palette and snapped
+    pub snapped: usize,
+}
+
+/// Snap a rendered composition to its `palette_lock` palette.
+///
+/// `sprites` are the rendered sprites the composition placed; `true` locks to
+/// their colors. Returns `None` if the composition has no lock or its palette
+/// can't be found, which is reported as a warning.
+pub fn apply_palette_lock(
+    comp: &Composition,
+    image: &mut RgbaImage,
+    sprites: &HashMap<String, RgbaImage>,
+    palettes: &PaletteRegistry,
+    warnings: &mut Vec<Warning>,
+) -> Option<PaletteLockReport> {
+    let (palette, colors) = match comp.palette_lock.as_ref()? {
+        PaletteLock::Enabled(false) => return None,
+        PaletteLock::Enabled(true) => ("sprites".to_string(), sprite_colors(comp, sprites)),
+        PaletteLock::Palette(name) => {
+            let palette = match name.strip_prefix('@') {
+                Some(builtin) => get_builtin(builtin),
+                None => palettes.get(name).cloned(),
+            };
+            let Some(palette) = palette else {
+                warnings.push(Warning::new(
+                    WarningCategory::Composition,
+                    format!(
+                        "palette_lock palette '{}' not found for composition '{}'",
+                        name, comp.name
+                    ),
+                ));
+                return None;
+            };
+            let colors = palette
+                .colors
+                .values()
+                .filter_map(|color| parse_color(color).ok())
+                .filter(|rgba| rgba[3] > 0)
+                .map(|rgba| [rgba[0], rgba[1], rgba[2]])
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            (name.clone(), colors)
+        }
+    };
+    if colors.is_empty() {
+        warnings.push(Warning::new(
+            WarningCategory::Composition,
+            format!("palette_lock of composition '{}' has no colors to snap to", comp.name),
+        ));
+        return None;
+    }
+
+    let pixels = image.pixels().filter(|p| p[3] > 0).count();
+    let snapped = snap_to_colors(image, &colors);
+    Some(PaletteLockReport { palette, colors: colors.len(), pixels, snapped })
+}
+
+/// Distinct visible colors of the base and placed sprites of a composition.
+fn sprite_colors(comp: &Composition, sprites: &HashMap<String, RgbaImage>) -> Vec<[u8; 3]> {
+    let mut colors = HashSet::new();
+    let placed = comp.base.iter().map(String::as_str).chain(comp.sprite_names());
+    for image in placed.filter_map(|name| sprites.get(name)) {
+        colors.extend(image.pixels().filter(|p| p[3] > 0).map(|p| [p[0], p[1], p[2]]));
+    }
+    colors.into_iter().collect()
+}
+
+/// Replace the RGB of every visible pixel with its nearest color in `colors`.
+///
+/// Returns how many pixels changed.
+pub fn snap_to_colors(image: &mut RgbaImage, colors: &[[u8; 3]]) -> usize {
+    let swatches: Vec<(LabColor, [u8; 3])> =
+        colors.iter().map(|&c| (LabColor::from_rgb(c[0], c[1], c[2]), c)).collect();
+    let mut nearest: HashMap<[u8; 3], [u8; 3]> = HashMap::new();
+    let mut snapped = 0;
+    for pixel in image.pixels_mut().filter(|p| p[3] > 0) {
+        let rgb = [pixel[0], pixel[1], pixel[2]];
+        let target = *nearest.entry(rgb).or_insert_with(|| {
+            let lab = LabColor::from_rgb(rgb[0], rgb[1], rgb[2]);
+            swatches
+                .iter()
+                .min_by(|a, b| lab.distance(&a.0).total_cmp(&lab.distance(&b.0)))
+                .map_or(rgb, |s| s.1)
+        });
+        if target != rgb {
+            pixel.0 = [target[0], target[1], target[2], pixel[3]];
+            snapped += 1;
+        }
+    }
+    snapped
+}
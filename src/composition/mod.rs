@@ -3,15 +3,23 @@
 mod blend;
 mod context;
 mod error;
+mod light;
+mod lock;
 mod render;
 mod resolve;
+mod shadow;
 
 // Re-export public API
 pub use blend::BlendMode;
 pub use context::RenderContext;
-pub use error::{CompositionError, Warning};
-pub use render::{render_composition, render_composition_nested};
+pub use error::{CompositionError, Warning, WarningCategory};
+pub use light::{apply_lighting, light_strength};
+pub use lock::{apply_palette_lock, snap_to_colors, PaletteLockReport};
+pub use render::{
+    render_composition, render_composition_layers, render_composition_nested, RenderedLayer,
+};
 pub use resolve::{resolve_blend_mode, resolve_opacity};
+pub use shadow::cast_contact_shadow;
 
 /// Result type alias for composition operations.
 pub type Result<T> = std::result::Result<T, CompositionError>;
@@ -19,7 +27,9 @@ pub type Result<T> = std::result::Result<T, CompositionError>;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Composition, CompositionLayer};
+    use crate::models::{
+        AutoSort, Composition, CompositionLayer, LayerGroup, Parallax, Repeat, SpriteRef,
+    };
     use image::{Rgba, RgbaImage};
     use std::collections::HashMap;
 
@@ -32,6 +42,15 @@ mod tests {
             cell_size: None,
             sprites: HashMap::new(),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
         let sprites = HashMap::new();
 
@@ -53,7 +72,7 @@ mod tests {
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("X".to_string(), Some("red_pixel".to_string())),
+                ("X".to_string(), Some("red_pixel".into())),
             ]),
             layers: vec![CompositionLayer {
                 name: Some("main".to_string()),
@@ -61,6 +80,15 @@ mod tests {
                 map: Some(vec!["X.".to_string(), ".X".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // Create a 1x1 red sprite
@@ -93,12 +121,12 @@ mod tests {
             ..Default::default()
         }];
 
-        let (width, height) = infer_size_from_layers(&layers, [1, 1]);
+        let (width, height) = infer_size_from_layers(&layers, None, [1, 1]);
         assert_eq!(width, 3);
         assert_eq!(height, 2);
 
         // With cell_size [4, 4]
-        let (width, height) = infer_size_from_layers(&layers, [4, 4]);
+        let (width, height) = infer_size_from_layers(&layers, None, [4, 4]);
         assert_eq!(width, 12);
         assert_eq!(height, 8);
     }
@@ -117,6 +145,15 @@ mod tests {
                 map: Some(vec!["X".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let (_, warnings) = render_composition(&comp, &HashMap::new(), false, None).unwrap();
@@ -132,13 +169,22 @@ mod tests {
             base: None,
             size: Some([1, 1]),
             cell_size: None,
-            sprites: HashMap::from([("X".to_string(), Some("missing_sprite".to_string()))]),
+            sprites: HashMap::from([("X".to_string(), Some("missing_sprite".into()))]),
             layers: vec![CompositionLayer {
                 name: None,
                 fill: None,
                 map: Some(vec!["X".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // Empty sprites map - sprite not provided
@@ -198,13 +244,22 @@ mod tests {
             base: None,
             size: None,
             cell_size: None, // Should default to [1, 1]
-            sprites: HashMap::from([("X".to_string(), Some("pixel".to_string()))]),
+            sprites: HashMap::from([("X".to_string(), Some("pixel".into()))]),
             layers: vec![CompositionLayer {
                 name: None,
                 fill: None,
                 map: Some(vec!["XX".to_string(), "XX".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let mut pixel = RgbaImage::new(1, 1);
@@ -230,8 +285,8 @@ mod tests {
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("R".to_string(), Some("red_pixel".to_string())),
-                ("B".to_string(), Some("blue_pixel".to_string())),
+                ("R".to_string(), Some("red_pixel".into())),
+                ("B".to_string(), Some("blue_pixel".into())),
             ]),
             layers: vec![
                 CompositionLayer {
@@ -247,6 +302,15 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let mut red_sprite = RgbaImage::new(1, 1);
@@ -283,8 +347,8 @@ mod tests {
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("R".to_string(), Some("red_pixel".to_string())),
-                ("B".to_string(), Some("blue_pixel".to_string())),
+                ("R".to_string(), Some("red_pixel".into())),
+                ("B".to_string(), Some("blue_pixel".into())),
             ]),
             layers: vec![
                 CompositionLayer {
@@ -300,6 +364,15 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let mut red_sprite = RgbaImage::new(1, 1);
@@ -338,9 +411,9 @@ mod tests {
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("R".to_string(), Some("red_pixel".to_string())),
-                ("G".to_string(), Some("green_pixel".to_string())),
-                ("B".to_string(), Some("blue_pixel".to_string())),
+                ("R".to_string(), Some("red_pixel".into())),
+                ("G".to_string(), Some("green_pixel".into())),
+                ("B".to_string(), Some("blue_pixel".into())),
             ]),
             layers: vec![
                 CompositionLayer {
@@ -362,6 +435,15 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let mut red_sprite = RgbaImage::new(1, 1);
@@ -402,7 +484,7 @@ mod tests {
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("R".to_string(), Some("red_pixel".to_string())),
+                ("R".to_string(), Some("red_pixel".into())),
             ]),
             layers: vec![
                 CompositionLayer {
@@ -418,6 +500,15 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let mut red_sprite = RgbaImage::new(1, 1);
@@ -447,7 +538,7 @@ mod tests {
             cell_size: Some([2, 2]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("X".to_string(), Some("pixel".to_string())),
+                ("X".to_string(), Some("pixel".into())),
             ]),
             layers: vec![CompositionLayer {
                 name: None,
@@ -455,6 +546,15 @@ mod tests {
                 map: Some(vec!["X.".to_string(), ".X".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // 2x2 sprite exactly fits 2x2 cell
@@ -481,7 +581,7 @@ mod tests {
             cell_size: Some([4, 4]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("X".to_string(), Some("pixel".to_string())),
+                ("X".to_string(), Some("pixel".into())),
             ]),
             layers: vec![CompositionLayer {
                 name: None,
@@ -489,6 +589,15 @@ mod tests {
                 map: Some(vec!["X".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // 2x2 sprite fits in 4x4 cell
@@ -511,7 +620,7 @@ mod tests {
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("X".to_string(), Some("big_sprite".to_string())),
+                ("X".to_string(), Some("big_sprite".into())),
             ]),
             layers: vec![CompositionLayer {
                 name: None,
@@ -519,6 +628,15 @@ mod tests {
                 map: Some(vec!["X.".to_string(), "..".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // 2x2 sprite doesn't fit in 1x1 cell
@@ -553,7 +671,7 @@ mod tests {
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("X".to_string(), Some("big_sprite".to_string())),
+                ("X".to_string(), Some("big_sprite".into())),
             ]),
             layers: vec![CompositionLayer {
                 name: None,
@@ -561,6 +679,15 @@ mod tests {
                 map: Some(vec!["X.".to_string(), "..".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // 2x2 sprite doesn't fit in 1x1 cell
@@ -605,6 +732,15 @@ mod tests {
                 map: Some(vec!["..".to_string(), "..".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let (_, warnings) = render_composition(&comp, &HashMap::new(), false, None).unwrap();
@@ -628,6 +764,15 @@ mod tests {
                 map: Some(vec!["..".to_string(), "..".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let result = render_composition(&comp, &HashMap::new(), true, None);
@@ -658,6 +803,15 @@ mod tests {
                 map: Some(vec!["...".to_string(), "...".to_string(), "...".to_string()]), // 3x3 instead of 2x2
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let (_, warnings) = render_composition(&comp, &HashMap::new(), false, None).unwrap();
@@ -682,6 +836,15 @@ mod tests {
                 map: Some(vec!["...".to_string(), "...".to_string(), "...".to_string()]), // 3x3 instead of 2x2
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let result = render_composition(&comp, &HashMap::new(), true, None);
@@ -718,6 +881,15 @@ mod tests {
                 map: Some(vec!["....".to_string(), "....".to_string()]), // Doesn't match 3x3
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // In strict mode, no errors for [1, 1] cell size
@@ -738,7 +910,7 @@ mod tests {
             cell_size: Some([2, 2]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("X".to_string(), Some("pixel".to_string())),
+                ("X".to_string(), Some("pixel".into())),
             ]),
             layers: vec![CompositionLayer {
                 name: None,
@@ -746,6 +918,15 @@ mod tests {
                 map: Some(vec!["X.".to_string(), ".X".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         // 2x2 sprite fills exactly one cell
@@ -808,8 +989,8 @@ mod tests {
             size: Some([1, 1]),
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
-                ("B".to_string(), Some("base".to_string())),
-                ("O".to_string(), Some("overlay".to_string())),
+                ("B".to_string(), Some("base".into())),
+                ("O".to_string(), Some("overlay".into())),
             ]),
             layers: vec![
                 CompositionLayer {
@@ -826,6 +1007,15 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let mut base_sprite = RgbaImage::new(1, 1);
@@ -858,8 +1048,8 @@ mod tests {
             size: Some([1, 1]),
             cell_size: Some([1, 1]),
             sprites: HashMap::from([
-                ("B".to_string(), Some("base".to_string())),
-                ("O".to_string(), Some("overlay".to_string())),
+                ("B".to_string(), Some("base".into())),
+                ("O".to_string(), Some("overlay".into())),
             ]),
             layers: vec![
                 CompositionLayer {
@@ -876,6 +1066,15 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         let mut base_sprite = RgbaImage::new(1, 1);
@@ -983,8 +1182,17 @@ mod tests {
                 base: None,
                 size,
                 cell_size: Some([2, 2]),
-                sprites,
+                sprites: sprites.into_iter().map(|(k, v)| (k, v.map(SpriteRef::from))).collect(),
                 layers,
+                groups: vec![],
+                locale: HashMap::new(),
+                auto_sort: None,
+                map_delimiter: None,
+                labels: Vec::new(),
+                palette_lock: None,
+                lights: Vec::new(),
+                ambient: None,
+                camera: None,
             }
         }
 
@@ -1194,4 +1402,579 @@ mod tests {
             assert!(warnings[0].message.contains("not found"));
         }
     }
+
+    #[test]
+    fn test_render_composition_layers_unblended() {
+        let comp = Composition {
+            name: "layered".to_string(),
+            base: Some("bg".to_string()),
+            size: None,
+            cell_size: None,
+            sprites: HashMap::from([
+                (".".to_string(), None),
+                ("X".to_string(), Some("dot".into())),
+            ]),
+            layers: vec![CompositionLayer {
+                map: Some(vec![".X".to_string()]),
+                blend: Some("multiply".to_string()),
+                opacity: Some(crate::models::VarOr::Value(0.5)),
+                ..Default::default()
+            }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
+        };
+        let sprites = HashMap::from([
+            ("bg".to_string(), RgbaImage::from_pixel(2, 2, Rgba([0, 0, 255, 255]))),
+            ("dot".to_string(), RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 255]))),
+        ]);
+
+        let (layers, warnings) = render_composition_layers(&comp, &sprites, false, None).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(layers.len(), 2);
+        assert_eq!((layers[0].name.as_str(), layers[0].blend), ("base", BlendMode::Normal));
+        assert_eq!((layers[1].name.as_str(), layers[1].blend), ("layer_1", BlendMode::Multiply));
+        assert_eq!(layers[1].opacity, 0.5);
+
+        // Each layer covers the full canvas with its own pixels only
+        assert_eq!(layers[1].image.dimensions(), (2, 2));
+        assert_eq!(*layers[1].image.get_pixel(1, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(layers[1].image.get_pixel(0, 0)[3], 0);
+    }
+
+    /// Background layer plus two overlapping layers in an "fg" group
+    fn grouped_composition(group: LayerGroup) -> Composition {
+        Composition {
+            name: "grouped".to_string(),
+            base: None,
+            size: Some([2, 1]),
+            cell_size: None,
+            sprites: HashMap::from([
+                (".".to_string(), None),
+                ("R".to_string(), Some("red".into())),
+                ("B".to_string(), Some("blue".into())),
+            ]),
+            layers: vec![
+                CompositionLayer {
+                    name: Some("bg".to_string()),
+                    map: Some(vec!["RR".to_string()]),
+                    ..Default::default()
+                },
+                CompositionLayer {
+                    name: Some("fg_back".to_string()),
+                    map: Some(vec!["RB".to_string()]),
+                    group: Some("fg".to_string()),
+                    ..Default::default()
+                },
+                CompositionLayer {
+                    name: Some("fg_front".to_string()),
+                    map: Some(vec!["B.".to_string()]),
+                    group: Some("fg".to_string()),
+                    ..Default::default()
+                },
+            ],
+            groups: vec![group],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
+        }
+    }
+
+    fn red_blue_sprites() -> HashMap<String, RgbaImage> {
+        HashMap::from([
+            ("red".to_string(), RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 255]))),
+            ("blue".to_string(), RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255]))),
+        ])
+    }
+
+    #[test]
+    fn test_group_opacity_applies_to_composited_group() {
+        let group = LayerGroup {
+            name: "fg".to_string(),
+            opacity: Some(crate::models::VarOr::Value(0.5)),
+            ..Default::default()
+        };
+        let comp = grouped_composition(group);
+
+        let (image, warnings) = render_composition(&comp, &red_blue_sprites(), true, None).unwrap();
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        // Inside the group blue fully covers red, so only blue shows at half opacity
+        let left = image.get_pixel(0, 0);
+        assert!(left[0] > 120 && left[0] < 135 && left[2] > 120 && left[2] < 135, "{:?}", left);
+        assert_eq!(left, image.get_pixel(1, 0));
+    }
+
+    #[test]
+    fn test_hidden_group_and_layer_skipped() {
+        let group = LayerGroup { name: "fg".to_string(), hidden: true, ..Default::default() };
+        let comp = grouped_composition(group);
+        let (image, _) = render_composition(&comp, &red_blue_sprites(), false, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([255, 0, 0, 255]));
+
+        let mut comp =
+            grouped_composition(LayerGroup { name: "fg".to_string(), ..Default::default() });
+        let pattern = glob::Pattern::new("*front").unwrap();
+        assert_eq!(comp.set_hidden_matching(&pattern, true), 1);
+        let (image, _) = render_composition(&comp, &red_blue_sprites(), false, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_undefined_group_renders_layer_with_warning() {
+        let mut comp = grouped_composition(LayerGroup::default());
+        comp.groups.clear();
+
+        let (image, warnings) =
+            render_composition(&comp, &red_blue_sprites(), false, None).unwrap();
+
+        assert!(warnings.iter().any(|w| w.message.contains("Layer group 'fg' not defined")));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_render_composition_layers_flattens_groups() {
+        let group = LayerGroup {
+            name: "fg".to_string(),
+            blend: Some("multiply".to_string()),
+            ..Default::default()
+        };
+        let comp = grouped_composition(group);
+
+        let (layers, _) =
+            render_composition_layers(&comp, &red_blue_sprites(), false, None).unwrap();
+
+        let names: Vec<&str> = layers.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["bg", "fg"]);
+        assert_eq!(layers[1].blend, BlendMode::Multiply);
+        assert_eq!(*layers[1].image.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+    }
+
+    fn masked_composition(mask: &str, mask_invert: bool) -> Composition {
+        Composition {
+            name: "masked".to_string(),
+            base: None,
+            size: Some([2, 1]),
+            cell_size: None,
+            sprites: HashMap::from([
+                (".".to_string(), None),
+                ("R".to_string(), Some("red".into())),
+                ("B".to_string(), Some("blue".into())),
+            ]),
+            layers: vec![
+                CompositionLayer {
+                    name: Some("spot".to_string()),
+                    map: Some(vec!["B.".to_string()]),
+                    hidden: true,
+                    ..Default::default()
+                },
+                CompositionLayer {
+                    name: Some("lit".to_string()),
+                    map: Some(vec!["RR".to_string()]),
+                    mask: Some(mask.to_string()),
+                    mask_invert,
+                    ..Default::default()
+                },
+            ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
+        }
+    }
+
+    #[test]
+    fn test_layer_mask_clips_to_mask_layer_alpha() {
+        let comp = masked_composition("spot", false);
+        let (image, warnings) = render_composition(&comp, &red_blue_sprites(), true, None).unwrap();
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        // The hidden mask layer is not drawn, only its shape clips "lit"
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(1, 0)[3], 0);
+    }
+
+    #[test]
+    fn test_layer_mask_invert_and_sprite_mask() {
+        let comp = masked_composition("spot", true);
+        let (image, _) = render_composition(&comp, &red_blue_sprites(), true, None).unwrap();
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+        assert_eq!(*image.get_pixel(1, 0), Rgba([255, 0, 0, 255]));
+
+        // A mask naming a sprite covers only the sprite's pixels from the origin
+        let comp = masked_composition("blue", false);
+        let (image, _) = render_composition(&comp, &red_blue_sprites(), true, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(1, 0)[3], 0);
+    }
+
+    #[test]
+    fn test_missing_mask_renders_layer_with_warning() {
+        let comp = masked_composition("nowhere", false);
+        let (image, warnings) =
+            render_composition(&comp, &red_blue_sprites(), false, None).unwrap();
+
+        assert!(warnings.iter().any(|w| w.message.contains("Mask 'nowhere' not found")));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_layer_transform_adjusts_colors_before_blending() {
+        let mut comp = grouped_composition(LayerGroup::default());
+        comp.layers.truncate(1);
+        comp.layers[0].transform = Some(vec![
+            crate::models::TransformSpec::String("hue-rotate:120".to_string()),
+            crate::models::TransformSpec::String("brightness:0.5".to_string()),
+        ]);
+        comp.layers[0].opacity = Some(crate::models::VarOr::Value(0.5));
+
+        let (image, warnings) = render_composition(&comp, &red_blue_sprites(), true, None).unwrap();
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        // Red becomes dark green, then the layer opacity applies
+        let pixel = image.get_pixel(0, 0);
+        assert_eq!((pixel[0], pixel[2]), (0, 0));
+        assert!(pixel[1] > 120 && pixel[1] < 136, "{:?}", pixel);
+        assert!(pixel[3] > 120 && pixel[3] < 136, "{:?}", pixel);
+    }
+
+    #[test]
+    fn test_invalid_layer_transform_warns() {
+        let mut comp = grouped_composition(LayerGroup::default());
+        comp.layers.truncate(1);
+        comp.layers[0].transform =
+            Some(vec![crate::models::TransformSpec::String("tint:{night}".to_string())]);
+
+        let (image, warnings) =
+            render_composition(&comp, &red_blue_sprites(), false, None).unwrap();
+
+        assert!(warnings.iter().any(|w| w.message.contains("Layer transform error")));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_layer_z_reorders_drawing() {
+        let mut comp = grouped_composition(LayerGroup::default());
+        comp.groups.clear();
+        comp.layers.truncate(1);
+        comp.layers.insert(
+            0,
+            CompositionLayer {
+                map: Some(vec!["BB".to_string()]),
+                z: Some(1),
+                ..Default::default()
+            },
+        );
+
+        let (image, _) = render_composition(&comp, &red_blue_sprites(), true, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+
+        let (layers, _) =
+            render_composition_layers(&comp, &red_blue_sprites(), true, None).unwrap();
+        let names: Vec<_> = layers.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["bg", "layer_1"]);
+    }
+
+    #[test]
+    fn test_auto_sort_y_draws_lower_sprites_in_front() {
+        // A tall tree on row 0 and a tall lamp on row 1 overlap at row 1
+        let sprites = HashMap::from([
+            ("tree".to_string(), RgbaImage::from_pixel(1, 2, Rgba([0, 255, 0, 255]))),
+            ("lamp".to_string(), RgbaImage::from_pixel(1, 2, Rgba([0, 0, 255, 255]))),
+        ]);
+        let mut comp = Composition {
+            name: "street".to_string(),
+            base: None,
+            size: Some([1, 3]),
+            cell_size: None,
+            sprites: HashMap::from([
+                (".".to_string(), None),
+                ("T".to_string(), Some("tree".into())),
+                ("L".to_string(), Some("lamp".into())),
+            ]),
+            layers: vec![
+                CompositionLayer {
+                    map: Some(vec![".".to_string(), "L".to_string(), ".".to_string()]),
+                    ..Default::default()
+                },
+                CompositionLayer {
+                    map: Some(vec!["T".to_string(), ".".to_string(), ".".to_string()]),
+                    ..Default::default()
+                },
+            ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
+        };
+
+        let (image, _) = render_composition(&comp, &sprites, false, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 1), Rgba([0, 255, 0, 255]));
+
+        comp.auto_sort = Some(AutoSort::Y);
+        let (image, _) = render_composition(&comp, &sprites, false, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 1), Rgba([0, 0, 255, 255]));
+
+        // Layers on different z levels are not interleaved
+        comp.layers[1].z = Some(1);
+        let (image, _) = render_composition(&comp, &sprites, false, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 1), Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn test_render_delimited_map() {
+        let sprites = HashMap::from([
+            ("tree".to_string(), RgbaImage::from_pixel(1, 1, Rgba([0, 255, 0, 255]))),
+            ("rock".to_string(), RgbaImage::from_pixel(1, 1, Rgba([128, 128, 128, 255]))),
+        ]);
+        let comp = Composition {
+            name: "forest".to_string(),
+            base: None,
+            size: None,
+            cell_size: None,
+            // "big" is an explicit key; "tree" and "rock" name sprites directly
+            sprites: HashMap::from([("big".to_string(), Some("rock".into()))]),
+            layers: vec![CompositionLayer {
+                map: Some(vec!["tree | rock | .".to_string(), "big||tree".to_string()]),
+                ..Default::default()
+            }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: Some("|".to_string()),
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
+        };
+
+        let (image, warnings) = render_composition(&comp, &sprites, true, None).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 255, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([128, 128, 128, 255]));
+        assert_eq!(image.get_pixel(2, 0)[3], 0);
+        assert_eq!(*image.get_pixel(0, 1), Rgba([128, 128, 128, 255]));
+        assert_eq!(image.get_pixel(1, 1)[3], 0);
+        assert_eq!(*image.get_pixel(2, 1), Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn test_sprite_instance_overrides() {
+        // A red-blue arrow, plus a variant drawn in green
+        let sprites = HashMap::from([
+            ("arrow".to_string(), RgbaImage::from_fn(2, 1, |x, _| red_blue(x))),
+            ("arrow_green".to_string(), RgbaImage::from_pixel(2, 1, Rgba([0, 255, 0, 255]))),
+        ]);
+        let instance = |json: &str| Some(serde_json::from_str::<SpriteRef>(json).unwrap());
+        let comp = Composition {
+            name: "signs".to_string(),
+            base: None,
+            size: Some([6, 2]),
+            cell_size: Some([2, 1]),
+            sprites: HashMap::from([
+                (".".to_string(), None),
+                ("F".to_string(), instance(r#"{"sprite": "arrow", "flip": "h"}"#)),
+                ("O".to_string(), instance(r#"{"sprite": "arrow", "offset": [-1, 0]}"#)),
+                ("G".to_string(), instance(r#"{"sprite": "arrow", "variant": "arrow_green"}"#)),
+            ]),
+            layers: vec![CompositionLayer {
+                map: Some(vec!["F.O".to_string(), ".G.".to_string()]),
+                ..Default::default()
+            }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
+        };
+
+        let (image, warnings) = render_composition(&comp, &sprites, true, None).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!((*image.get_pixel(0, 0), *image.get_pixel(1, 0)), (red_blue(1), red_blue(0)));
+        assert_eq!((*image.get_pixel(3, 0), *image.get_pixel(4, 0)), (red_blue(0), red_blue(1)));
+        assert_eq!(*image.get_pixel(2, 1), Rgba([0, 255, 0, 255]));
+
+        // Offsets past the top-left edge clip: the top arrow vanishes and the
+        // bottom one moves up with only its blue half left on the canvas
+        let mut comp = comp;
+        comp.sprites
+            .insert("F".to_string(), instance(r#"{"sprite": "arrow", "offset": [-1, -1]}"#));
+        comp.layers[0].map = Some(vec!["F..".to_string(), "F..".to_string()]);
+        let (image, _) = render_composition(&comp, &sprites, true, None).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), red_blue(1));
+        assert_eq!(image.get_pixel(1, 0)[3], 0);
+        assert_eq!(image.get_pixel(0, 1)[3], 0);
+    }
+
+    #[test]
+    fn test_camera_parallax_and_repeat() {
+        let comp = Composition {
+            name: "valley".to_string(),
+            base: None,
+            size: Some([6, 2]),
+            cell_size: None,
+            sprites: HashMap::from([
+                (".".to_string(), None),
+                ("R".to_string(), Some("red".into())),
+                ("B".to_string(), Some("blue".into())),
+            ]),
+            layers: vec![
+                CompositionLayer {
+                    map: Some(vec!["R.".to_string()]),
+                    repeat: Some(Repeat::X),
+                    ..Default::default()
+                },
+                CompositionLayer {
+                    map: Some(vec!["......".to_string(), "..B...".to_string()]),
+                    parallax: Some(Parallax::Axes([0.5, 0.0])),
+                    ..Default::default()
+                },
+            ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: Some([3, 0]),
+        };
+        let sprites = HashMap::from([
+            ("red".to_string(), RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 255]))),
+            ("blue".to_string(), RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255]))),
+        ]);
+
+        let (image, warnings) = render_composition(&comp, &sprites, false, None).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let row = |y| -> String {
+            (0..6)
+                .map(|x| match image.get_pixel(x, y).0 {
+                    [255, 0, 0, 255] => 'R',
+                    [0, 0, 255, 255] => 'B',
+                    _ => '.',
+                })
+                .collect()
+        };
+        // The repeated row moves with the camera and still covers the canvas;
+        // the half-speed layer moves 2 of 3 pixels
+        assert_eq!(row(0), ".R.R.R");
+        assert_eq!(row(1), "B.....");
+    }
+
+    #[test]
+    fn test_palette_lock() {
+        use crate::models::{Palette, PaletteLock};
+        use crate::registry::PaletteRegistry;
+
+        // Gray multiplied over red gives a dark red no sprite uses
+        let sprites = HashMap::from([
+            ("red".to_string(), RgbaImage::from_pixel(1, 1, Rgba([200, 0, 0, 255]))),
+            ("gray".to_string(), RgbaImage::from_pixel(1, 1, Rgba([128, 128, 128, 255]))),
+        ]);
+        let layer = |map: &str, blend: Option<&str>| CompositionLayer {
+            map: Some(vec![map.to_string()]),
+            blend: blend.map(str::to_string),
+            ..Default::default()
+        };
+        let mut comp = Composition {
+            name: "shade".to_string(),
+            base: None,
+            size: Some([2, 1]),
+            cell_size: None,
+            sprites: HashMap::from([
+                (".".to_string(), None),
+                ("R".to_string(), Some("red".into())),
+                ("G".to_string(), Some("gray".into())),
+            ]),
+            layers: vec![layer("RR", None), layer("G.", Some("multiply"))],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: Some(PaletteLock::Enabled(true)),
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
+        };
+        let (blended, _) = render_composition(&comp, &sprites, false, None).unwrap();
+        assert_eq!(*blended.get_pixel(0, 0), Rgba([100, 0, 0, 255]));
+
+        // Locked to the sprites' own colors: only the blended pixel moves
+        let palettes = PaletteRegistry::new();
+        let mut image = blended.clone();
+        let mut warnings = Vec::new();
+        let report = apply_palette_lock(&comp, &mut image, &sprites, &palettes, &mut warnings);
+        let report = report.unwrap();
+        assert_eq!((report.palette.as_str(), report.colors), ("sprites", 2));
+        assert_eq!((report.pixels, report.snapped), (2, 1));
+        assert!(warnings.is_empty());
+        assert_eq!(*image.get_pixel(0, 0), Rgba([200, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([200, 0, 0, 255]));
+
+        // Locked to a named palette
+        let mut palettes = PaletteRegistry::new();
+        palettes.register(Palette {
+            name: "dusk".to_string(),
+            colors: HashMap::from([
+                ("_".to_string(), "#00000000".to_string()),
+                ("a".to_string(), "#660000".to_string()),
+                ("b".to_string(), "#FF0000".to_string()),
+            ]),
+            ..Default::default()
+        });
+        comp.palette_lock = Some(PaletteLock::Palette("dusk".to_string()));
+        let mut image = blended.clone();
+        let report = apply_palette_lock(&comp, &mut image, &sprites, &palettes, &mut warnings);
+        assert_eq!(report.unwrap().snapped, 2);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0x66, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([255, 0, 0, 255]));
+
+        // Unknown palettes and disabled locks leave the image alone
+        comp.palette_lock = Some(PaletteLock::Palette("@nope".to_string()));
+        let mut image = blended.clone();
+        assert!(apply_palette_lock(&comp, &mut image, &sprites, &palettes, &mut warnings).is_none());
+        assert_eq!(warnings.len(), 1);
+        comp.palette_lock = Some(PaletteLock::Enabled(false));
+        assert!(apply_palette_lock(&comp, &mut image, &sprites, &palettes, &mut warnings).is_none());
+        assert_eq!(image, blended);
+    }
+
+    fn red_blue(x: u32) -> Rgba<u8> {
+        if x == 0 {
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([0, 0, 255, 255])
+        }
+    }
 }
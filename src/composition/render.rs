@@ -1,16 +1,22 @@
 //! Composition rendering functions
 
-use image::{Rgba, RgbaImage};
-use std::collections::HashMap;
+use image::{imageops, Rgba, RgbaImage};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
-use crate::models::Composition;
+use crate::models::{
+    split_map_row, AutoSort, Composition, CompositionLayer, Flip, LayerGroup, Repeat, SpriteRef,
+};
 use crate::registry::CompositionRegistry;
+use crate::transforms::{apply_image_transform, parse_transform_spec};
 use crate::variables::VariableRegistry;
 
-use super::blend::{blit_sprite, blit_sprite_blended};
+use super::blend::{apply_mask, blit_sprite, blit_sprite_blended, BlendMode};
 use super::context::RenderContext;
-use super::error::{CompositionError, Warning};
+use super::error::{CompositionError, Warning, WarningCategory};
+use super::light::apply_lighting;
 use super::resolve::{resolve_blend_mode, resolve_opacity};
+use super::shadow::cast_contact_shadow;
 
 /// Render a composition to an RGBA image buffer.
 ///
@@ -91,10 +97,13 @@ pub fn render_composition(
         match sprites.get(base_name) {
             Some(img) => Some(img),
             None => {
-                warnings.push(Warning::new(format!(
-                    "Base sprite '{}' not found for composition '{}'",
-                    base_name, comp.name
-                )));
+                warnings.push(Warning::new(
+                    WarningCategory::Composition,
+                    format!(
+                        "Base sprite '{}' not found for composition '{}'",
+                        base_name, comp.name
+                    ),
+                ));
                 None
             }
         }
@@ -113,12 +122,13 @@ pub fn render_composition(
         (base_img.width(), base_img.height())
     } else {
         // Infer from layers
-        let (inferred_w, inferred_h) = infer_size_from_layers(&comp.layers, cell_size);
+        let (inferred_w, inferred_h) =
+            infer_size_from_layers(&comp.layers, comp.map_delimiter.as_deref(), cell_size);
         if inferred_w == 0 || inferred_h == 0 {
-            warnings.push(Warning::new(format!(
-                "Could not infer size for composition '{}', using 1x1",
-                comp.name
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Composition,
+                format!("Could not infer size for composition '{}', using 1x1", comp.name),
+            ));
             (1, 1)
         } else {
             (inferred_w, inferred_h)
@@ -138,17 +148,20 @@ pub fn render_composition(
                     composition_name: comp.name.clone(),
                 });
             } else {
-                warnings.push(Warning::new(format!(
-                    "Size ({}x{}) is not divisible by cell_size ({}x{}) in composition '{}'",
-                    width, height, cell_size[0], cell_size[1], comp.name
-                )));
+                warnings.push(Warning::new(
+                    WarningCategory::Composition,
+                    format!(
+                        "Size ({}x{}) is not divisible by cell_size ({}x{}) in composition '{}'",
+                        width, height, cell_size[0], cell_size[1], comp.name
+                    ),
+                ));
             }
         }
     }
 
     // Calculate expected grid dimensions for map validation
-    let expected_cols = if cell_size[0] > 0 { width / cell_size[0] } else { width };
-    let expected_rows = if cell_size[1] > 0 { height / cell_size[1] } else { height };
+    let expected_cols = width.checked_div(cell_size[0]).unwrap_or(width);
+    let expected_rows = height.checked_div(cell_size[1]).unwrap_or(height);
 
     // Create canvas (transparent by default)
     let mut canvas = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 0]));
@@ -158,106 +171,146 @@ pub fn render_composition(
         blit_sprite(&mut canvas, base_img, 0, 0);
     }
 
-    // Render each layer (bottom to top)
-    for layer in &comp.layers {
-        // Parse layer blend mode and opacity with CSS variable resolution (ATF-10, CSS-9)
-        let (blend_mode, blend_warning) = resolve_blend_mode(layer.blend.as_deref(), variables);
-        if let Some(w) = blend_warning {
-            warnings.push(w);
-        }
-
-        let (opacity, opacity_warning) = resolve_opacity(layer.opacity.as_ref(), variables);
-        if let Some(w) = opacity_warning {
-            warnings.push(w);
-        }
-
-        if let Some(ref map) = layer.map {
-            // Validate map dimensions match expected grid (only when cell_size > [1,1])
-            if cell_size[0] > 1 || cell_size[1] > 1 {
-                let actual_rows = map.len();
-                let actual_cols = map.iter().map(|r| r.chars().count()).max().unwrap_or(0);
-
-                if actual_rows != expected_rows as usize || actual_cols != expected_cols as usize {
-                    if strict {
-                        return Err(CompositionError::MapDimensionMismatch {
-                            layer_name: layer.name.clone(),
-                            actual_dimensions: (actual_cols, actual_rows),
-                            expected_dimensions: (expected_cols, expected_rows),
-                            composition_name: comp.name.clone(),
-                        });
-                    } else {
-                        let layer_desc = layer
-                            .name
-                            .as_ref()
-                            .map(|n| format!("layer '{}'", n))
-                            .unwrap_or_else(|| "unnamed layer".to_string());
-                        warnings.push(Warning::new(format!(
-                            "Map dimensions ({}x{}) don't match expected grid size ({}x{}) for {} in composition '{}'",
-                            actual_cols, actual_rows, expected_cols, expected_rows, layer_desc, comp.name
-                        )));
-                    }
+    // Render each layer (bottom to top, by z)
+    for step in draw_steps(comp, &mut warnings) {
+        match step {
+            // Grouped layers are composited together, then blended as one layer
+            DrawStep::Group(group) => {
+                let group_comp = group_composition(comp, &group.name, width, height);
+                let (image, group_warnings) =
+                    render_composition(&group_comp, sprites, strict, variables)?;
+                warnings.extend(group_warnings);
+                blend_group(&mut canvas, &image, group, variables, &mut warnings);
+            }
+            // Layers with transforms or a mask are rendered alone, adjusted, then
+            // blended as one image
+            DrawStep::Effects(layer) => {
+                let (blend_mode, opacity) = resolve_layer_blend(layer, variables, &mut warnings);
+                let mut render =
+                    |c: &Composition| render_composition(c, sprites, strict, variables);
+                let (image, effect_warnings) =
+                    render_layer_effects(comp, layer, sprites, [width, height], &mut render)?;
+                warnings.extend(effect_warnings);
+                if let Some(shadow) = &layer.contact_shadow {
+                    cast_contact_shadow(&mut canvas, &image, shadow, &comp.name, &mut warnings);
                 }
+                blit_sprite_blended(&mut canvas, &image, 0, 0, blend_mode, opacity);
             }
-
-            for (row_idx, row) in map.iter().enumerate() {
-                for (col_idx, char_key) in row.chars().enumerate() {
-                    let key = char_key.to_string();
-
-                    // Look up sprite name from sprites map
-                    let sprite_name = match comp.sprites.get(&key) {
-                        Some(Some(name)) => name,
-                        Some(None) => continue, // null means transparent/skip
-                        None => {
-                            warnings.push(Warning::new(format!(
-                                "Unknown sprite key '{}' in composition '{}'",
-                                key, comp.name
-                            )));
-                            continue;
-                        }
+            DrawStep::Cells(layers) => {
+                let mut cells = Vec::new();
+                for layer in layers {
+                    // Parse layer blend mode and opacity with CSS variable resolution (ATF-10, CSS-9)
+                    let (blend_mode, opacity) =
+                        resolve_layer_blend(layer, variables, &mut warnings);
+                    let [shift_x, shift_y] = layer_shift(comp, layer);
+                    let Some(ref map) = layer.map else {
+                        continue;
                     };
 
-                    // Get the rendered sprite image
-                    let sprite_image = match sprites.get(sprite_name) {
-                        Some(img) => img,
-                        None => {
-                            warnings.push(Warning::new(format!(
-                                "Sprite '{}' not found for composition '{}'",
-                                sprite_name, comp.name
-                            )));
-                            continue;
-                        }
-                    };
-
-                    // Check for size mismatch (Task 2.5)
-                    let sprite_width = sprite_image.width();
-                    let sprite_height = sprite_image.height();
-                    if sprite_width > cell_size[0] || sprite_height > cell_size[1] {
-                        if strict {
-                            return Err(CompositionError::SizeMismatch {
-                                sprite_name: sprite_name.clone(),
-                                sprite_size: (sprite_width, sprite_height),
-                                cell_size: (cell_size[0], cell_size[1]),
-                                composition_name: comp.name.clone(),
-                            });
-                        } else {
-                            warnings.push(Warning::new(format!(
-                                "Sprite '{}' ({}x{}) exceeds cell size ({}x{}) in composition '{}', anchoring from top-left",
-                                sprite_name, sprite_width, sprite_height, cell_size[0], cell_size[1], comp.name
-                            )));
+                    // Validate map dimensions match expected grid (only when cell_size > [1,1])
+                    if cell_size[0] > 1 || cell_size[1] > 1 {
+                        let actual_rows = map.len();
+                        let actual_cols =
+                            map.iter().map(|r| comp.map_cells(r).len()).max().unwrap_or(0);
+
+                        if actual_rows != expected_rows as usize
+                            || actual_cols != expected_cols as usize
+                        {
+                            if strict {
+                                return Err(CompositionError::MapDimensionMismatch {
+                                    layer_name: layer.name.clone(),
+                                    actual_dimensions: (actual_cols, actual_rows),
+                                    expected_dimensions: (expected_cols, expected_rows),
+                                    composition_name: comp.name.clone(),
+                                });
+                            } else {
+                                let layer_desc = layer
+                                    .name
+                                    .as_ref()
+                                    .map(|n| format!("layer '{}'", n))
+                                    .unwrap_or_else(|| "unnamed layer".to_string());
+                                warnings.push(Warning::new(WarningCategory::Composition, format!(
+                                    "Map dimensions ({}x{}) don't match expected grid size ({}x{}) for {} in composition '{}'",
+                                    actual_cols, actual_rows, expected_cols, expected_rows, layer_desc, comp.name
+                                )));
+                            }
                         }
                     }
 
-                    // Calculate position
-                    let x = (col_idx as u32) * cell_size[0];
-                    let y = (row_idx as u32) * cell_size[1];
+                    for (row_idx, row) in map.iter().enumerate() {
+                        for (col_idx, key) in comp.map_cells(row).into_iter().enumerate() {
+                            // Look up sprite name from sprites map
+                            let entry = match comp.cell_sprite(key) {
+                                Some(Some(entry)) => entry,
+                                Some(None) => continue, // null means transparent/skip
+                                None => {
+                                    warnings.push(Warning::new(
+                                        WarningCategory::Composition,
+                                        format!(
+                                            "Unknown sprite key '{}' in composition '{}'",
+                                            key, comp.name
+                                        ),
+                                    ));
+                                    continue;
+                                }
+                            };
+                            let sprite_name = entry.name();
+
+                            // Get the rendered sprite image
+                            let sprite_image = match sprites.get(sprite_name) {
+                                Some(img) => img,
+                                None => {
+                                    warnings.push(Warning::new(
+                                        WarningCategory::Composition,
+                                        format!(
+                                            "Sprite '{}' not found for composition '{}'",
+                                            sprite_name, comp.name
+                                        ),
+                                    ));
+                                    continue;
+                                }
+                            };
+
+                            // Check for size mismatch (Task 2.5)
+                            let sprite_width = sprite_image.width();
+                            let sprite_height = sprite_image.height();
+                            if sprite_width > cell_size[0] || sprite_height > cell_size[1] {
+                                if strict {
+                                    return Err(CompositionError::SizeMismatch {
+                                        sprite_name: sprite_name.to_string(),
+                                        sprite_size: (sprite_width, sprite_height),
+                                        cell_size: (cell_size[0], cell_size[1]),
+                                        composition_name: comp.name.clone(),
+                                    });
+                                } else {
+                                    warnings.push(Warning::new(WarningCategory::Composition, format!(
+                                        "Sprite '{}' ({}x{}) exceeds cell size ({}x{}) in composition '{}', anchoring from top-left",
+                                        sprite_name, sprite_width, sprite_height, cell_size[0], cell_size[1], comp.name
+                                    )));
+                                }
+                            }
 
-                    // Blit sprite onto canvas with blend mode and opacity (ATF-10)
-                    blit_sprite_blended(&mut canvas, sprite_image, x, y, blend_mode, opacity);
+                            let (x, y) = (
+                                col_idx as i64 * cell_size[0] as i64 + shift_x,
+                                row_idx as i64 * cell_size[1] as i64 + shift_y,
+                            );
+                            cells.push(Cell::instance(
+                                Cow::Borrowed(sprite_image),
+                                &entry,
+                                (x, y),
+                                blend_mode,
+                                opacity,
+                            ));
+                        }
+                    }
                 }
+                draw_cells(&mut canvas, cells, comp.auto_sort);
             }
         }
     }
 
+    apply_lighting(comp, &mut canvas, &mut warnings);
+
     Ok((canvas, warnings))
 }
 
@@ -311,14 +364,13 @@ fn render_composition_inner(
     let cell_size = comp.cell_size.unwrap_or([1, 1]);
 
     // Look up base sprite/composition if specified (NC-4: supports nested compositions)
-    let base_image: Option<std::borrow::Cow<'_, RgbaImage>> = if let Some(ref base_name) = comp.base
-    {
+    let base_image: Option<Cow<'_, RgbaImage>> = if let Some(ref base_name) = comp.base {
         if let Some(img) = sprites.get(base_name) {
-            Some(std::borrow::Cow::Borrowed(img))
+            Some(Cow::Borrowed(img))
         } else if let Some(reg) = composition_registry {
             if let Some(nested_comp) = reg.get(base_name) {
                 if let Some(cached) = ctx.get_cached(base_name) {
-                    Some(std::borrow::Cow::Owned(cached.clone()))
+                    Some(Cow::Owned(cached.clone()))
                 } else {
                     let (rendered, nested_warnings) = render_composition_nested(
                         nested_comp,
@@ -330,20 +382,20 @@ fn render_composition_inner(
                     )?;
                     warnings.extend(nested_warnings);
                     ctx.cache(base_name.to_string(), rendered.clone());
-                    Some(std::borrow::Cow::Owned(rendered))
+                    Some(Cow::Owned(rendered))
                 }
             } else {
-                warnings.push(Warning::new(format!(
-                    "Base '{}' not found for composition '{}'",
-                    base_name, comp.name
-                )));
+                warnings.push(Warning::new(
+                    WarningCategory::Composition,
+                    format!("Base '{}' not found for composition '{}'", base_name, comp.name),
+                ));
                 None
             }
         } else {
-            warnings.push(Warning::new(format!(
-                "Base sprite '{}' not found for composition '{}'",
-                base_name, comp.name
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Composition,
+                format!("Base sprite '{}' not found for composition '{}'", base_name, comp.name),
+            ));
             None
         }
     } else {
@@ -356,7 +408,7 @@ fn render_composition_inner(
     } else if let Some(ref base_img) = base_image {
         (base_img.width(), base_img.height())
     } else {
-        let (w, h) = infer_size_from_layers(&comp.layers, cell_size);
+        let (w, h) = infer_size_from_layers(&comp.layers, comp.map_delimiter.as_deref(), cell_size);
         if w == 0 || h == 0 {
             (1, 1)
         } else {
@@ -373,42 +425,78 @@ fn render_composition_inner(
     }
 
     // Render each layer
-    for layer in &comp.layers {
-        let (blend_mode, blend_warning) = resolve_blend_mode(layer.blend.as_deref(), variables);
-        if let Some(w) = blend_warning {
-            warnings.push(w);
-        }
-
-        let (opacity, opacity_warning) = resolve_opacity(layer.opacity.as_ref(), variables);
-        if let Some(w) = opacity_warning {
-            warnings.push(w);
-        }
-
-        if let Some(ref map) = layer.map {
-            for (row_idx, row) in map.iter().enumerate() {
-                for (col_idx, char_key) in row.chars().enumerate() {
-                    let key = char_key.to_string();
-
-                    let sprite_name = match comp.sprites.get(&key) {
-                        Some(Some(name)) => name,
-                        Some(None) => continue,
-                        None => {
-                            warnings.push(Warning::new(format!(
-                                "Unknown sprite key '{}' in composition '{}'",
-                                key, comp.name
-                            )));
-                            continue;
-                        }
+    for step in draw_steps(comp, &mut warnings) {
+        match step {
+            DrawStep::Group(group) => {
+                let group_comp = group_composition(comp, &group.name, width, height);
+                let (image, group_warnings) = render_composition_inner(
+                    &group_comp,
+                    sprites,
+                    composition_registry,
+                    ctx,
+                    strict,
+                    variables,
+                )?;
+                warnings.extend(group_warnings);
+                blend_group(&mut canvas, &image, group, variables, &mut warnings);
+            }
+            DrawStep::Effects(layer) => {
+                let (blend_mode, opacity) = resolve_layer_blend(layer, variables, &mut warnings);
+                let mut render = |c: &Composition| {
+                    render_composition_inner(
+                        c,
+                        sprites,
+                        composition_registry,
+                        ctx,
+                        strict,
+                        variables,
+                    )
+                };
+                let (image, effect_warnings) =
+                    render_layer_effects(comp, layer, sprites, [width, height], &mut render)?;
+                warnings.extend(effect_warnings);
+                if let Some(shadow) = &layer.contact_shadow {
+                    cast_contact_shadow(&mut canvas, &image, shadow, &comp.name, &mut warnings);
+                }
+                blit_sprite_blended(&mut canvas, &image, 0, 0, blend_mode, opacity);
+            }
+            DrawStep::Cells(layers) => {
+                let mut cells = Vec::new();
+                for layer in layers {
+                    let (blend_mode, opacity) =
+                        resolve_layer_blend(layer, variables, &mut warnings);
+                    let [shift_x, shift_y] = layer_shift(comp, layer);
+                    let Some(ref map) = layer.map else {
+                        continue;
                     };
-
-                    // Get sprite/composition image (NC-4: check compositions too)
-                    let sprite_image: std::borrow::Cow<'_, RgbaImage> =
-                        if let Some(img) = sprites.get(sprite_name) {
-                            std::borrow::Cow::Borrowed(img)
-                        } else if let Some(reg) = composition_registry {
-                            if let Some(nested_comp) = reg.get(sprite_name) {
+                    for (row_idx, row) in map.iter().enumerate() {
+                        for (col_idx, key) in comp.map_cells(row).into_iter().enumerate() {
+                            let entry = match comp.cell_sprite(key) {
+                                Some(Some(entry)) => entry,
+                                Some(None) => continue,
+                                None => {
+                                    warnings.push(Warning::new(
+                                        WarningCategory::Composition,
+                                        format!(
+                                            "Unknown sprite key '{}' in composition '{}'",
+                                            key, comp.name
+                                        ),
+                                    ));
+                                    continue;
+                                }
+                            };
+                            let sprite_name = entry.name();
+
+                            // Get sprite/composition image (NC-4: check compositions too)
+                            let sprite_image: Cow<'_, RgbaImage> = if let Some(img) =
+                                sprites.get(sprite_name)
+                            {
+                                Cow::Borrowed(img)
+                            } else if let Some(nested_comp) =
+                                composition_registry.and_then(|reg| reg.get(sprite_name))
+                            {
                                 if let Some(cached) = ctx.get_cached(sprite_name) {
-                                    std::borrow::Cow::Owned(cached.clone())
+                                    Cow::Owned(cached.clone())
                                 } else {
                                     let (rendered, nested_warnings) = render_composition_nested(
                                         nested_comp,
@@ -420,38 +508,468 @@ fn render_composition_inner(
                                     )?;
                                     warnings.extend(nested_warnings);
                                     ctx.cache(sprite_name.to_string(), rendered.clone());
-                                    std::borrow::Cow::Owned(rendered)
+                                    Cow::Owned(rendered)
                                 }
                             } else {
-                                warnings.push(Warning::new(format!(
-                                    "Sprite '{}' not found for composition '{}'",
-                                    sprite_name, comp.name
-                                )));
+                                warnings.push(Warning::new(
+                                    WarningCategory::Composition,
+                                    format!(
+                                        "Sprite '{}' not found for composition '{}'",
+                                        sprite_name, comp.name
+                                    ),
+                                ));
                                 continue;
-                            }
-                        } else {
-                            warnings.push(Warning::new(format!(
-                                "Sprite '{}' not found for composition '{}'",
-                                sprite_name, comp.name
-                            )));
-                            continue;
-                        };
-
-                    let x = (col_idx as u32) * cell_size[0];
-                    let y = (row_idx as u32) * cell_size[1];
-
-                    blit_sprite_blended(&mut canvas, &sprite_image, x, y, blend_mode, opacity);
+                            };
+
+                            let (x, y) = (
+                                col_idx as i64 * cell_size[0] as i64 + shift_x,
+                                row_idx as i64 * cell_size[1] as i64 + shift_y,
+                            );
+                            cells.push(Cell::instance(
+                                sprite_image,
+                                &entry,
+                                (x, y),
+                                blend_mode,
+                                opacity,
+                            ));
+                        }
+                    }
                 }
+                draw_cells(&mut canvas, cells, comp.auto_sort);
             }
         }
     }
 
+    apply_lighting(comp, &mut canvas, &mut warnings);
+
     Ok((canvas, warnings))
 }
 
+/// One step of drawing a composition's layers, bottom to top.
+enum DrawStep<'a> {
+    /// A layer group, composited at the place of its first member
+    Group(&'a LayerGroup),
+    /// A layer with transforms, a mask, repeat or a contact shadow, rendered on its own
+    Effects(&'a CompositionLayer),
+    /// Map layers drawn cell by cell; cells of consecutive layers with the
+    /// same `z` share one step so `auto_sort` can interleave them
+    Cells(Vec<&'a CompositionLayer>),
+}
+
+/// Plan the drawing of a composition's visible layers, ordered by `z`.
+fn draw_steps<'a>(comp: &'a Composition, warnings: &mut Vec<Warning>) -> Vec<DrawStep<'a>> {
+    let mut steps: Vec<DrawStep<'a>> = Vec::new();
+    let mut groups = HashSet::new();
+    for layer in comp.layers_in_order() {
+        if !comp.is_layer_visible(layer) {
+            continue;
+        }
+        if let Some(group) = layer_group(comp, layer, warnings) {
+            if groups.insert(group.name.as_str()) {
+                steps.push(DrawStep::Group(group));
+            }
+            continue;
+        }
+        if has_layer_effects(layer) {
+            steps.push(DrawStep::Effects(layer));
+            continue;
+        }
+        match steps.last_mut() {
+            Some(DrawStep::Cells(run)) if comp.auto_sort.is_some() && run[0].z == layer.z => {
+                run.push(layer)
+            }
+            _ => steps.push(DrawStep::Cells(vec![layer])),
+        }
+    }
+    steps
+}
+
+/// A sprite placed in one map cell
+struct Cell<'a> {
+    image: Cow<'a, RgbaImage>,
+    x: i64,
+    y: i64,
+    blend_mode: BlendMode,
+    opacity: f64,
+}
+
+impl<'a> Cell<'a> {
+    /// Place a sprite at a cell position, applying the entry's flip and offset.
+    fn instance(
+        image: Cow<'a, RgbaImage>,
+        entry: &SpriteRef,
+        (x, y): (i64, i64),
+        blend_mode: BlendMode,
+        opacity: f64,
+    ) -> Self {
+        let image = match entry.flip() {
+            None => image,
+            Some(Flip::H) => Cow::Owned(imageops::flip_horizontal(image.as_ref())),
+            Some(Flip::V) => Cow::Owned(imageops::flip_vertical(image.as_ref())),
+            Some(Flip::Hv) => Cow::Owned(imageops::rotate180(image.as_ref())),
+        };
+        let [dx, dy] = entry.offset();
+        Cell { image, x: x + dx as i64, y: y + dy as i64, blend_mode, opacity }
+    }
+}
+
+/// Blend and opacity of a layer, with CSS variable resolution (ATF-10, CSS-9).
+fn resolve_layer_blend(
+    layer: &CompositionLayer,
+    variables: Option<&VariableRegistry>,
+    warnings: &mut Vec<Warning>,
+) -> (BlendMode, f64) {
+    let (blend_mode, blend_warning) = resolve_blend_mode(layer.blend.as_deref(), variables);
+    warnings.extend(blend_warning);
+    let (opacity, opacity_warning) = resolve_opacity(layer.opacity.as_ref(), variables);
+    warnings.extend(opacity_warning);
+    (blend_mode, opacity)
+}
+
+/// Draw map cells in map order, or by the bottom edge of their sprite with
+/// `auto_sort: "y"` so lower sprites overlap higher ones.
+fn draw_cells(canvas: &mut RgbaImage, mut cells: Vec<Cell<'_>>, auto_sort: Option<AutoSort>) {
+    if auto_sort == Some(AutoSort::Y) {
+        // Stable, so ties keep layer then map order
+        cells.sort_by_key(|cell| cell.y + cell.image.height() as i64);
+    }
+    for cell in &cells {
+        // Offsets can push a sprite past the top or left edge; clip that part
+        let (skip_x, skip_y) = ((-cell.x).max(0) as u32, (-cell.y).max(0) as u32);
+        let (width, height) = cell.image.dimensions();
+        if skip_x >= width || skip_y >= height {
+            continue;
+        }
+        let image = if skip_x == 0 && skip_y == 0 {
+            Cow::Borrowed(cell.image.as_ref())
+        } else {
+            let view = imageops::crop_imm(cell.image.as_ref(), skip_x, skip_y, width, height);
+            Cow::Owned(view.to_image())
+        };
+        let (x, y) = (cell.x.max(0) as u32, cell.y.max(0) as u32);
+        blit_sprite_blended(canvas, &image, x, y, cell.blend_mode, cell.opacity);
+    }
+}
+
+/// The defined group a layer belongs to, warning when the group is undefined.
+fn layer_group<'a>(
+    comp: &'a Composition,
+    layer: &CompositionLayer,
+    warnings: &mut Vec<Warning>,
+) -> Option<&'a LayerGroup> {
+    let name = layer.group.as_deref()?;
+    let group = comp.group(name);
+    if group.is_none() {
+        warnings.push(Warning::new(
+            WarningCategory::Composition,
+            format!("Layer group '{}' not defined in composition '{}'", name, comp.name),
+        ));
+    }
+    group
+}
+
+/// A composition holding only the visible layers of one group, without the base.
+fn group_composition(comp: &Composition, group: &str, width: u32, height: u32) -> Composition {
+    let layers = comp
+        .layers
+        .iter()
+        .filter(|l| l.group.as_deref() == Some(group) && comp.is_layer_visible(l))
+        .map(|l| CompositionLayer { group: None, ..l.clone() })
+        .collect();
+    Composition {
+        base: None,
+        size: Some([width, height]),
+        layers,
+        groups: Vec::new(),
+        lights: Vec::new(),
+        ambient: None,
+        ..comp.clone()
+    }
+}
+
+/// Blend a composited group onto the canvas with the group's blend and opacity.
+fn blend_group(
+    canvas: &mut RgbaImage,
+    image: &RgbaImage,
+    group: &LayerGroup,
+    variables: Option<&VariableRegistry>,
+    warnings: &mut Vec<Warning>,
+) {
+    let (blend_mode, blend_warning) = resolve_blend_mode(group.blend.as_deref(), variables);
+    warnings.extend(blend_warning);
+    let (opacity, opacity_warning) = resolve_opacity(group.opacity.as_ref(), variables);
+    warnings.extend(opacity_warning);
+    blit_sprite_blended(canvas, image, 0, 0, blend_mode, opacity);
+}
+
+/// Whether a layer needs rendering on its own before blending.
+fn has_layer_effects(layer: &CompositionLayer) -> bool {
+    layer.mask.is_some()
+        || layer.repeat.is_some()
+        || layer.contact_shadow.is_some()
+        || layer.transform.as_ref().is_some_and(|t| !t.is_empty())
+}
+
+/// A single layer on its own, with effects, blending and grouping stripped.
+fn isolated_layer(comp: &Composition, layer: &CompositionLayer, size: [u32; 2]) -> Composition {
+    let layer = CompositionLayer {
+        transform: None,
+        blend: None,
+        opacity: None,
+        group: None,
+        hidden: false,
+        mask: None,
+        mask_invert: false,
+        contact_shadow: None,
+        repeat: None,
+        ..layer.clone()
+    };
+    Composition {
+        base: None,
+        size: Some(size),
+        layers: vec![layer],
+        groups: Vec::new(),
+        lights: Vec::new(),
+        ambient: None,
+        ..comp.clone()
+    }
+}
+
+/// Renders a composition, as `render_composition` or its nested variant.
+type RenderFn<'a> =
+    dyn FnMut(&Composition) -> Result<(RgbaImage, Vec<Warning>), CompositionError> + 'a;
+
+/// Render a layer alone, apply its transforms, then clip it by its mask.
+///
+/// Transforms (such as color adjustments) apply to the layer's pixels in
+/// order. The mask names another layer of the composition (rendered even
+/// when hidden) or, failing that, a sprite placed at the canvas origin.
+fn render_layer_effects(
+    comp: &Composition,
+    layer: &CompositionLayer,
+    sprites: &HashMap<String, RgbaImage>,
+    size: [u32; 2],
+    render: &mut RenderFn<'_>,
+) -> Result<(RgbaImage, Vec<Warning>), CompositionError> {
+    let (mut image, mut warnings) = match layer.repeat {
+        // Tiles come from the unshifted layer, then repeat from its shifted position
+        Some(repeat) => {
+            let unshifted = Composition { camera: None, ..isolated_layer(comp, layer, size) };
+            let (tile, warnings) = render(&unshifted)?;
+            let image = repeat_layer(
+                &tile,
+                repeat,
+                layer_period(comp, layer, size),
+                layer_shift(comp, layer),
+            );
+            (image, warnings)
+        }
+        None => render(&isolated_layer(comp, layer, size))?,
+    };
+
+    for spec in layer.transform.iter().flatten() {
+        let applied = parse_transform_spec(spec)
+            .and_then(|transform| apply_image_transform(&image, &transform, None));
+        match applied {
+            Ok(transformed) => image = transformed,
+            Err(e) => warnings.push(Warning::new(
+                WarningCategory::Composition,
+                format!("Layer transform error in composition '{}': {}", comp.name, e),
+            )),
+        }
+    }
+
+    let Some(mask) = layer.mask.as_deref() else {
+        return Ok((image, warnings));
+    };
+    let mask_layer = comp.layers.iter().find(|l| l.name.as_deref() == Some(mask));
+    let mask_image = if let Some(mask_layer) = mask_layer {
+        let (mask_image, _) = render(&isolated_layer(comp, mask_layer, size))?;
+        mask_image
+    } else if let Some(sprite) = sprites.get(mask) {
+        sprite.clone()
+    } else {
+        warnings.push(Warning::new(
+            WarningCategory::Composition,
+            format!("Mask '{}' not found for composition '{}'", mask, comp.name),
+        ));
+        return Ok((image, warnings));
+    };
+
+    apply_mask(&mut image, &mask_image, layer.mask_invert);
+    Ok((image, warnings))
+}
+
+/// How far a layer shifts for the composition's camera: `-camera * parallax`.
+fn layer_shift(comp: &Composition, layer: &CompositionLayer) -> [i64; 2] {
+    let Some([x, y]) = comp.camera else {
+        return [0, 0];
+    };
+    let [px, py] = layer.parallax();
+    [-(f64::from(x) * px).round() as i64, -(f64::from(y) * py).round() as i64]
+}
+
+/// Size of the tile a repeated layer is made of: the extent of its map, or
+/// the canvas for layers without one.
+fn layer_period(comp: &Composition, layer: &CompositionLayer, size: [u32; 2]) -> [u32; 2] {
+    let Some(map) = &layer.map else {
+        return size;
+    };
+    let [cell_w, cell_h] = comp.cell_size();
+    let cols = map.iter().map(|row| comp.map_cells(row).len()).max().unwrap_or(0) as u32;
+    [(cols * cell_w).max(1), (map.len() as u32 * cell_h).max(1)]
+}
+
+/// Tile the top-left `period` of `tile` across a canvas of the same size,
+/// starting from `shift`, along the repeated axes.
+fn repeat_layer(tile: &RgbaImage, repeat: Repeat, period: [u32; 2], shift: [i64; 2]) -> RgbaImage {
+    let (width, height) = tile.dimensions();
+    let [period_w, period_h] = [period[0].min(width).max(1), period[1].min(height).max(1)];
+    let tile = imageops::crop_imm(tile, 0, 0, period_w, period_h).to_image();
+    let positions = |repeats: bool, shift: i64, period: u32, span: u32| -> Vec<i64> {
+        if !repeats {
+            return vec![shift];
+        }
+        let period = i64::from(period);
+        (shift.rem_euclid(period) - period..i64::from(span)).step_by(period as usize).collect()
+    };
+    let [repeat_x, repeat_y] = repeat.axes();
+    let mut image = RgbaImage::new(width, height);
+    for y in positions(repeat_y, shift[1], period_h, height) {
+        for x in positions(repeat_x, shift[0], period_w, width) {
+            imageops::overlay(&mut image, &tile, x, y);
+        }
+    }
+    image
+}
+
+/// A composition layer rendered on its own canvas, for layered export.
+#[derive(Debug, Clone)]
+pub struct RenderedLayer {
+    /// Layer name (`base` for the base sprite, `layer_{n}` when unnamed)
+    pub name: String,
+    /// Layer pixels at full canvas size, before blending and opacity
+    pub image: RgbaImage,
+    /// Blend mode used to composite this layer
+    pub blend: BlendMode,
+    /// Layer opacity (0.0-1.0)
+    pub opacity: f64,
+    /// Camera parallax factors `[x, y]` (1.0 moves with the scene)
+    pub parallax: [f64; 2],
+    /// Axes the layer is tiled along, if any
+    pub repeat: Option<Repeat>,
+    /// Size of one tile of a repeated layer (the canvas size otherwise)
+    pub tile_size: [u32; 2],
+    /// Drift in pixels per second, if the layer scrolls
+    pub scroll: Option<[f64; 2]>,
+}
+
+/// Render each layer of a composition separately, bottom to top.
+///
+/// The base sprite, if any, becomes a bottom `normal` layer named `base`.
+/// Hidden layers are skipped, and each layer group is flattened into one
+/// layer named after the group, carrying the group's blend and opacity.
+/// Layer transforms and masks are already applied to exported layers; lighting
+/// and contact shadows apply only to the blended image, so exported layers
+/// have neither.
+/// Every layer is rendered on a canvas the size of the full composition with
+/// its `blend` and `opacity` left unapplied, so an editor can re-composite
+/// them (see [`crate::ora`]).
+pub fn render_composition_layers(
+    comp: &Composition,
+    sprites: &HashMap<String, RgbaImage>,
+    strict: bool,
+    variables: Option<&VariableRegistry>,
+) -> Result<(Vec<RenderedLayer>, Vec<Warning>), CompositionError> {
+    // Render the whole composition once for canvas size and validation warnings
+    let (canvas, warnings) = render_composition(comp, sprites, strict, variables)?;
+    let size = Some([canvas.width(), canvas.height()]);
+
+    let mut layers = Vec::new();
+    if comp.base.as_ref().is_some_and(|base| sprites.contains_key(base)) {
+        let base_only = Composition {
+            size,
+            layers: Vec::new(),
+            lights: Vec::new(),
+            ambient: None,
+            ..comp.clone()
+        };
+        let (image, _) = render_composition(&base_only, sprites, strict, variables)?;
+        layers.push(RenderedLayer {
+            name: "base".to_string(),
+            image,
+            blend: BlendMode::Normal,
+            opacity: 1.0,
+            parallax: [1.0, 1.0],
+            repeat: None,
+            tile_size: [canvas.width(), canvas.height()],
+            scroll: None,
+        });
+    }
+
+    // Layers stack in z order but keep their position-based default names
+    let mut ordered: Vec<_> = comp.layers.iter().enumerate().collect();
+    ordered.sort_by_key(|(_, layer)| layer.z.unwrap_or(0));
+
+    let mut exported_groups = HashSet::new();
+    for (index, layer) in ordered {
+        if !comp.is_layer_visible(layer) {
+            continue;
+        }
+        if let Some(group) = layer_group(comp, layer, &mut Vec::new()) {
+            if exported_groups.insert(group.name.as_str()) {
+                let group_comp =
+                    group_composition(comp, &group.name, canvas.width(), canvas.height());
+                let (image, _) = render_composition(&group_comp, sprites, strict, variables)?;
+                let (blend, _) = resolve_blend_mode(group.blend.as_deref(), variables);
+                let (opacity, _) = resolve_opacity(group.opacity.as_ref(), variables);
+                // A group moves as one, by its first visible member
+                layers.push(RenderedLayer {
+                    name: group.name.clone(),
+                    image,
+                    blend,
+                    opacity,
+                    parallax: layer.parallax(),
+                    repeat: None,
+                    tile_size: [canvas.width(), canvas.height()],
+                    scroll: layer.scroll,
+                });
+            }
+            continue;
+        }
+
+        let (blend, _) = resolve_blend_mode(layer.blend.as_deref(), variables);
+        let (opacity, _) = resolve_opacity(layer.opacity.as_ref(), variables);
+        let size = [canvas.width(), canvas.height()];
+        let (image, _) = if has_layer_effects(layer) {
+            let mut render = |c: &Composition| render_composition(c, sprites, strict, variables);
+            render_layer_effects(comp, layer, sprites, size, &mut render)?
+        } else {
+            render_composition(&isolated_layer(comp, layer, size), sprites, strict, variables)?
+        };
+        let name = layer.name.clone().unwrap_or_else(|| format!("layer_{}", index + 1));
+        layers.push(RenderedLayer {
+            name,
+            image,
+            blend,
+            opacity,
+            parallax: layer.parallax(),
+            repeat: layer.repeat,
+            tile_size: match layer.repeat {
+                Some(_) => layer_period(comp, layer, size),
+                None => size,
+            },
+            scroll: layer.scroll,
+        });
+    }
+
+    Ok((layers, warnings))
+}
+
 /// Infer canvas size from layer maps and cell size
 pub(crate) fn infer_size_from_layers(
     layers: &[crate::models::CompositionLayer],
+    delimiter: Option<&str>,
     cell_size: [u32; 2],
 ) -> (u32, u32) {
     let mut max_cols = 0u32;
@@ -460,7 +978,8 @@ pub(crate) fn infer_size_from_layers(
     for layer in layers {
         if let Some(ref map) = layer.map {
             let rows = map.len() as u32;
-            let cols = map.iter().map(|r| r.chars().count() as u32).max().unwrap_or(0);
+            let cols =
+                map.iter().map(|r| split_map_row(r, delimiter).len() as u32).max().unwrap_or(0);
             max_rows = max_rows.max(rows);
             max_cols = max_cols.max(cols);
         }
@@ -4,7 +4,7 @@ use crate::models::VarOr;
 use crate::variables::VariableRegistry;
 
 use super::blend::BlendMode;
-use super::error::Warning;
+use super::error::{Warning, WarningCategory};
 
 /// Resolve a blend mode string, potentially containing a var() reference.
 ///
@@ -25,10 +25,13 @@ pub fn resolve_blend_mode(
                 Err(e) => {
                     return (
                         BlendMode::Normal,
-                        Some(Warning::new(format!(
-                            "Failed to resolve blend mode variable '{}': {}, using normal",
-                            blend_str, e
-                        ))),
+                        Some(Warning::new(
+                            WarningCategory::Composition,
+                            format!(
+                                "Failed to resolve blend mode variable '{}': {}, using normal",
+                                blend_str, e
+                            ),
+                        )),
                     );
                 }
             }
@@ -36,7 +39,7 @@ pub fn resolve_blend_mode(
             // No registry provided but var() used - warn and use default
             return (
                 BlendMode::Normal,
-                Some(Warning::new(format!(
+                Some(Warning::new(WarningCategory::Composition, format!(
                     "Blend mode '{}' contains var() but no variable registry provided, using normal",
                     blend_str
                 ))),
@@ -51,7 +54,10 @@ pub fn resolve_blend_mode(
         Some(mode) => (mode, None),
         None => (
             BlendMode::Normal,
-            Some(Warning::new(format!("Unknown blend mode '{}', using normal", resolved))),
+            Some(Warning::new(
+                WarningCategory::Composition,
+                format!("Unknown blend mode '{}', using normal", resolved),
+            )),
         ),
     }
 }
@@ -78,7 +84,7 @@ pub fn resolve_opacity(
                             Ok(v) => (v.clamp(0.0, 1.0), None),
                             Err(_) => (
                                 1.0,
-                                Some(Warning::new(format!(
+                                Some(Warning::new(WarningCategory::Composition, format!(
                                     "Opacity variable '{}' resolved to '{}' which is not a valid number, using 1.0",
                                     var_str, resolved
                                 ))),
@@ -87,19 +93,25 @@ pub fn resolve_opacity(
                     }
                     Err(e) => (
                         1.0,
-                        Some(Warning::new(format!(
-                            "Failed to resolve opacity variable '{}': {}, using 1.0",
-                            var_str, e
-                        ))),
+                        Some(Warning::new(
+                            WarningCategory::Composition,
+                            format!(
+                                "Failed to resolve opacity variable '{}': {}, using 1.0",
+                                var_str, e
+                            ),
+                        )),
                     ),
                 }
             } else {
                 (
                     1.0,
-                    Some(Warning::new(format!(
+                    Some(Warning::new(
+                        WarningCategory::Composition,
+                        format!(
                         "Opacity '{}' contains var() but no variable registry provided, using 1.0",
                         var_str
-                    ))),
+                    ),
+                    )),
                 )
             }
         }
@@ -0,0 +1,139 @@
+//! Contact shadows: soft shadows a layer casts onto the layers below it
+//!
+//! A composition layer with `contact_shadow` darkens the pixels of the layers
+//! already drawn beneath it, just outside its silhouette in the shadow's
+//! direction, so sprites sit in a scene without a hand-placed shadow each:
+//!
+//! ```json5
+//! { map: ["..H.."], contact_shadow: { direction: "down", size: 2, opacity: 0.5 } }
+//! ```
+//!
+//! The shadow is strongest next to the silhouette and fades out over `size`
+//! pixels. It only falls on visible pixels below, never on empty canvas, and
+//! keeps their alpha.
+
+use image::{Rgba, RgbaImage};
+
+use crate::color::parse_color;
+use crate::models::ContactShadow;
+
+use super::error::{Warning, WarningCategory};
+
+/// Darken `canvas` where the silhouette of `layer` casts its contact shadow.
+///
+/// `layer` is the upper layer on its own, at canvas size, before it is drawn.
+/// An invalid shadow color falls back to black with a warning.
+pub fn cast_contact_shadow(
+    canvas: &mut RgbaImage,
+    layer: &RgbaImage,
+    shadow: &ContactShadow,
+    comp_name: &str,
+    warnings: &mut Vec<Warning>,
+) {
+    let size = shadow.size.unwrap_or(ContactShadow::DEFAULT_SIZE);
+    let opacity = shadow.opacity.unwrap_or(ContactShadow::DEFAULT_OPACITY).clamp(0.0, 1.0);
+    if size == 0 || opacity == 0.0 {
+        return;
+    }
+    let color = match shadow.color.as_deref().map(parse_color) {
+        None => Rgba([0, 0, 0, 255]),
+        Some(Ok(color)) => color,
+        Some(Err(e)) => {
+            warnings.push(Warning::new(
+                WarningCategory::Composition,
+                format!("Invalid contact shadow color in composition '{}': {}", comp_name, e),
+            ));
+            Rgba([0, 0, 0, 255])
+        }
+    };
+
+    let [dx, dy] = shadow.direction.unwrap_or_default().step();
+    let (width, height) = (canvas.width().min(layer.width()), canvas.height().min(layer.height()));
+    let mut shade = vec![0.0f64; width as usize * height as usize];
+    for (x, y, pixel) in layer.enumerate_pixels().filter(|(_, _, p)| p[3] > 0) {
+        let coverage = f64::from(pixel[3]) / 255.0;
+        for k in 1..=size {
+            let sx = i64::from(x) + i64::from(dx) * i64::from(k);
+            let sy = i64::from(y) + i64::from(dy) * i64::from(k);
+            let (Ok(sx), Ok(sy)) = (u32::try_from(sx), u32::try_from(sy)) else {
+                break;
+            };
+            if sx >= width || sy >= height || layer.get_pixel(sx, sy)[3] > 0 {
+                break;
+            }
+            let strength = opacity * coverage * f64::from(size - k + 1) / f64::from(size);
+            let cell = &mut shade[(sy * width + sx) as usize];
+            *cell = cell.max(strength);
+        }
+    }
+
+    for (i, strength) in shade.into_iter().enumerate().filter(|(_, s)| *s > 0.0) {
+        let (x, y) = (i as u32 % width, i as u32 / width);
+        let pixel = canvas.get_pixel_mut(x, y);
+        if pixel[3] == 0 {
+            continue;
+        }
+        for c in 0..3 {
+            let mixed = f64::from(pixel[c]) * (1.0 - strength) + f64::from(color[c]) * strength;
+            pixel[c] = mixed.round() as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ShadowDirection;
+
+    /// A white floor with a 1px-wide post standing in its top-left corner
+    fn scene() -> (RgbaImage, RgbaImage) {
+        let floor = RgbaImage::from_pixel(4, 4, Rgba([200, 200, 200, 255]));
+        let mut post = RgbaImage::new(4, 4);
+        post.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+        post.put_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        (floor, post)
+    }
+
+    fn column(image: &RgbaImage, x: u32) -> Vec<u8> {
+        (0..image.height()).map(|y| image.get_pixel(x, y)[0]).collect()
+    }
+
+    #[test]
+    fn test_shadow_fades_below_silhouette() {
+        let (mut floor, post) = scene();
+        let mut warnings = Vec::new();
+        cast_contact_shadow(&mut floor, &post, &ContactShadow::default(), "yard", &mut warnings);
+        assert!(warnings.is_empty());
+
+        // Under the post: half, then a quarter darker; nothing beside it
+        assert_eq!(column(&floor, 1), [200, 200, 100, 150]);
+        assert_eq!(column(&floor, 0), [200; 4]);
+    }
+
+    #[test]
+    fn test_shadow_direction_and_color() {
+        let (mut floor, post) = scene();
+        let shadow = ContactShadow {
+            direction: Some(ShadowDirection::Right),
+            size: Some(1),
+            opacity: Some(1.0),
+            color: Some("#0000FF".to_string()),
+        };
+        cast_contact_shadow(&mut floor, &post, &shadow, "yard", &mut Vec::new());
+        assert_eq!(*floor.get_pixel(2, 0), Rgba([0, 0, 255, 255]));
+        assert_eq!(*floor.get_pixel(2, 1), Rgba([0, 0, 255, 255]));
+        assert_eq!(*floor.get_pixel(3, 0), Rgba([200, 200, 200, 255]));
+    }
+
+    #[test]
+    fn test_shadow_skips_empty_canvas() {
+        let (_, post) = scene();
+        let mut canvas = RgbaImage::new(4, 4);
+        let mut warnings = Vec::new();
+        let shadow = ContactShadow { color: Some("soot".to_string()), ..Default::default() };
+        cast_contact_shadow(&mut canvas, &post, &shadow, "yard", &mut warnings);
+        assert!(canvas.pixels().all(|p| p.0 == [0, 0, 0, 0]));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("contact shadow color"));
+    }
+}
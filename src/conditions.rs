@@ -0,0 +1,203 @@
+//! Conditional objects and `--define` flags.
+//!
+//! Any object may carry a `when` condition naming flags that must be
+//! defined for it to take part in the document, so one file can hold both
+//! standard and HD assets, or debug-only helpers:
+//!
+//! ```json
+//! {"type": "sprite", "name": "hero", "when": "!hd", "size": [16, 16], ...}
+//! {"type": "sprite", "name": "hero", "when": "hd", "size": [32, 32], ...}
+//! {"type": "sprite", "name": "grid", "when": "feature(debug) && !release", ...}
+//! ```
+//!
+//! Flags come from `--define` on the command line and `defaults.defines`
+//! in `pxl.toml`. Objects whose condition doesn't hold are dropped while
+//! parsing, before names are registered.
+//!
+//! ```
+//! use pixelsrc::conditions::{self, evaluate};
+//!
+//! assert_eq!(evaluate("hd"), Ok(false));
+//! conditions::with_defines(["hd".to_string()], || {
+//!     assert_eq!(evaluate("feature(hd)"), Ok(true));
+//!     assert_eq!(evaluate("hd && !debug"), Ok(true));
+//! });
+//! ```
+//!
+//! Like the global seed, the defined flags are scoped to the current thread.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+/// Object field holding the condition.
+pub const WHEN_FIELD: &str = "when";
+
+thread_local! {
+    static DEFINES: RefCell<BTreeSet<String>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+/// The flags defined on this thread, in name order.
+pub fn defines() -> Vec<String> {
+    DEFINES.with(|d| d.borrow().iter().cloned().collect())
+}
+
+/// Whether `flag` is defined on this thread.
+pub fn is_defined(flag: &str) -> bool {
+    DEFINES.with(|d| d.borrow().contains(flag))
+}
+
+/// Run `f` with exactly `flags` defined, restoring the previous flags afterwards.
+pub fn with_defines<R>(flags: impl IntoIterator<Item = String>, f: impl FnOnce() -> R) -> R {
+    struct Restore(BTreeSet<String>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DEFINES.with(|d| *d.borrow_mut() = std::mem::take(&mut self.0));
+        }
+    }
+
+    let flags = flags.into_iter().collect();
+    let _restore = Restore(DEFINES.with(|d| d.replace(flags)));
+    f()
+}
+
+/// Evaluate a condition against the flags defined on this thread.
+///
+/// Conditions combine flag names with `!`, `&&`, `||` and parentheses;
+/// `feature(name)` is the same as `name`.
+pub fn evaluate(condition: &str) -> Result<bool, String> {
+    let mut parser = Condition { source: condition, pos: 0 };
+    let value = parser.any()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(c) => Err(parser.error(&format!("unexpected '{}'", c))),
+    }
+}
+
+/// Check and remove the `when` field of a parsed object.
+///
+/// Returns whether the object is active: `true` without a condition.
+pub fn take_condition(object: &mut serde_json::Value) -> Result<bool, String> {
+    let Some(condition) = object.as_object_mut().and_then(|o| o.remove(WHEN_FIELD)) else {
+        return Ok(true);
+    };
+    match condition.as_str() {
+        Some(condition) => evaluate(condition),
+        None => Err(format!("\"{}\" must be a string", WHEN_FIELD)),
+    }
+}
+
+/// Recursive-descent parser for conditions
+struct Condition<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Condition<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("invalid condition '{}': {}", self.source, message)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.source[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.source[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.peek();
+        if self.source[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn any(&mut self) -> Result<bool, String> {
+        let mut value = self.all()?;
+        while self.eat("||") {
+            value |= self.all()?;
+        }
+        Ok(value)
+    }
+
+    fn all(&mut self) -> Result<bool, String> {
+        let mut value = self.unary()?;
+        while self.eat("&&") {
+            value &= self.unary()?;
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<bool, String> {
+        if self.eat("!") {
+            return Ok(!self.unary()?);
+        }
+        if self.eat("(") {
+            let value = self.any()?;
+            return if self.eat(")") { Ok(value) } else { Err(self.error("expected ')'")) };
+        }
+
+        let flag = self.flag()?;
+        if flag == "feature" && self.eat("(") {
+            let flag = self.flag()?;
+            if !self.eat(")") {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(is_defined(flag));
+        }
+        Ok(is_defined(flag))
+    }
+
+    fn flag(&mut self) -> Result<&'a str, String> {
+        self.peek();
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a flag name"));
+        }
+        self.pos += len;
+        Ok(&self.source[self.pos - len..self.pos])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_operators() {
+        with_defines(["hd".to_string(), "debug".to_string()], || {
+            assert_eq!(evaluate("hd"), Ok(true));
+            assert_eq!(evaluate("sd"), Ok(false));
+            assert_eq!(evaluate("!sd && feature(debug)"), Ok(true));
+            assert_eq!(evaluate("sd || !(hd && debug)"), Ok(false));
+            assert_eq!(evaluate("sd || hd && debug"), Ok(true), "&& binds tighter than ||");
+            assert_eq!(defines(), ["debug", "hd"]);
+        });
+        assert!(!is_defined("hd"), "flags are restored afterwards");
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        for condition in ["", "hd &&", "(hd", "feature(hd", "hd debug", "hd & debug"] {
+            let err = evaluate(condition).unwrap_err();
+            assert!(err.starts_with("invalid condition"), "{}: {}", condition, err);
+        }
+    }
+
+    #[test]
+    fn test_take_condition() {
+        let mut object = serde_json::json!({"type": "sprite", "when": "hd"});
+        assert_eq!(take_condition(&mut object), Ok(false));
+        assert!(object.get(WHEN_FIELD).is_none(), "condition is removed");
+
+        let mut object = serde_json::json!({"type": "sprite"});
+        assert_eq!(take_condition(&mut object), Ok(true));
+
+        let mut object = serde_json::json!({"type": "sprite", "when": true});
+        assert!(take_condition(&mut object).is_err());
+    }
+}
@@ -3,8 +3,9 @@
 //! Provides functions to find, load, and merge configuration.
 
 use super::schema::{
-    AnimationsConfig, DefaultsConfig, ExportsConfig, FormatConfig, ImportConfig, ProjectConfig,
-    PxlConfig, TelemetryConfig, ValidateConfig, WatchConfig,
+    AnimationsConfig, DefaultsConfig, ExportsConfig, FormatConfig, HooksConfig, ImportConfig,
+    LimitsConfig, LintConfig, OutputConfig, ProjectConfig, PxlConfig, TelemetryConfig,
+    ValidateConfig, WatchConfig,
 };
 use std::collections::HashMap;
 use std::env;
@@ -194,8 +195,13 @@ pub fn default_config() -> PxlConfig {
         animations: AnimationsConfig::default(),
         exports: ExportsConfig::default(),
         validate: ValidateConfig::default(),
+        lint: LintConfig::default(),
         watch: WatchConfig::default(),
         dependencies: HashMap::new(),
+        profiles: HashMap::new(),
+        output: OutputConfig::default(),
+        limits: LimitsConfig::default(),
+        hooks: HooksConfig::default(),
     }
 }
 
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::ordered;
+
 /// Format version configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FormatConfig {
@@ -188,6 +190,9 @@ pub struct DefaultsConfig {
     /// Default antialiasing settings
     #[serde(default)]
     pub antialias: AntialiasConfig,
+    /// Flags defined for `when` conditions, in addition to `--define`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub defines: Vec<String>,
 }
 
 impl Default for DefaultsConfig {
@@ -196,6 +201,7 @@ impl Default for DefaultsConfig {
             scale: default_scale(),
             padding: default_padding(),
             antialias: AntialiasConfig::default(),
+            defines: Vec::new(),
         }
     }
 }
@@ -208,6 +214,160 @@ fn default_padding() -> u32 {
     1
 }
 
+/// Export profile: emit every sprite and atlas at an extra scale
+///
+/// Example:
+/// ```toml
+/// [profiles.2x]
+/// scale = 2
+/// naming = "{name}@2x"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// Scale factor for this profile (replaces `defaults.scale`)
+    pub scale: u32,
+    /// Output file stem template; supports `{name}` and `{scale}`
+    #[serde(default = "default_profile_naming")]
+    pub naming: String,
+}
+
+fn default_profile_naming() -> String {
+    "{name}@{scale}x".to_string()
+}
+
+impl ProfileConfig {
+    /// Expand the naming template for an output named `name`.
+    pub fn file_stem(&self, name: &str) -> String {
+        self.naming.replace("{name}", name).replace("{scale}", &self.scale.to_string())
+    }
+}
+
+/// Output path templates, relative to `project.out`
+///
+/// Templates support `{name}`, `{type}`, `{scale}`, `{ext}` and `{theme}`.
+/// Without `{scale}`, the export profile's `naming` is applied to `{name}`.
+///
+/// Example:
+/// ```toml
+/// [output]
+/// atlas = "{type}/{name}@{scale}x.{ext}"
+/// export = "engines/{type}/{name}.{ext}"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// Template for atlas images and their metadata
+    #[serde(default = "default_atlas_output")]
+    pub atlas: String,
+    /// Template for animation spritesheets
+    #[serde(default = "default_animation_output")]
+    pub animation: String,
+    /// Template for animation preview GIFs
+    #[serde(default = "default_animation_output")]
+    pub preview: String,
+    /// Template for game engine exports
+    #[serde(default = "default_export_output")]
+    pub export: String,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            atlas: default_atlas_output(),
+            animation: default_animation_output(),
+            preview: default_animation_output(),
+            export: default_export_output(),
+        }
+    }
+}
+
+fn default_atlas_output() -> String {
+    "{name}.{ext}".to_string()
+}
+
+fn default_animation_output() -> String {
+    "animations/{name}.{ext}".to_string()
+}
+
+fn default_export_output() -> String {
+    "{type}/{name}.{ext}".to_string()
+}
+
+/// Variables for one expansion of an output template
+#[derive(Debug, Clone, Copy)]
+pub struct OutputVars<'a> {
+    /// Output type (`atlas`, `animation`, `preview`, or an export format)
+    pub kind: &'a str,
+    /// Target name
+    pub name: &'a str,
+    /// File extension without the dot
+    pub ext: &'a str,
+    /// Theme applied to this output, if any
+    pub theme: Option<&'a str>,
+}
+
+/// Variables allowed in output templates
+const OUTPUT_VARIABLES: &[&str] = &["name", "type", "scale", "ext", "theme"];
+
+impl OutputConfig {
+    /// Templates by field name, for validation.
+    fn templates(&self) -> [(&'static str, &String); 4] {
+        [
+            ("atlas", &self.atlas),
+            ("animation", &self.animation),
+            ("preview", &self.preview),
+            ("export", &self.export),
+        ]
+    }
+
+    /// Expand `template` for one output at one export profile.
+    ///
+    /// A themed output whose template has no `{theme}` gets `-<theme>`
+    /// appended to its name. Path components left empty by an unset
+    /// `{theme}` are dropped.
+    pub fn expand(template: &str, vars: OutputVars, profile: &ProfileConfig) -> PathBuf {
+        let mut name = vars.name.to_string();
+        if let Some(theme) = vars.theme.filter(|_| !template.contains("{theme}")) {
+            name = format!("{}-{}", name, theme);
+        }
+        if !template.contains("{scale}") {
+            name = profile.file_stem(&name);
+        }
+
+        let expanded = template
+            .replace("{name}", &name)
+            .replace("{type}", vars.kind)
+            .replace("{scale}", &profile.scale.to_string())
+            .replace("{ext}", vars.ext)
+            .replace("{theme}", vars.theme.unwrap_or_default());
+        expanded.split('/').filter(|part| !part.is_empty()).collect()
+    }
+
+    /// Describe what is wrong with a template, if anything.
+    fn template_error(template: &str) -> Option<String> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                return Some("has an unclosed '{'".to_string());
+            };
+            let variable = &rest[start + 1..start + len];
+            if !OUTPUT_VARIABLES.contains(&variable) {
+                return Some(format!("uses unknown variable '{{{}}}'", variable));
+            }
+            rest = &rest[start + len + 1..];
+        }
+
+        if !template.contains("{name}") {
+            Some("must contain '{name}'".to_string())
+        } else if !template.ends_with(".{ext}") {
+            Some("must end with '.{ext}'".to_string())
+        } else if template.starts_with('/') || template.split('/').any(|part| part == "..") {
+            Some("must stay inside the output directory".to_string())
+        } else {
+            None
+        }
+    }
+}
+
 /// Atlas configuration for sprite packing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AtlasConfig {
@@ -228,6 +388,9 @@ pub struct AtlasConfig {
     /// Antialiasing settings (overrides defaults)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub antialias: Option<AntialiasConfig>,
+    /// Themes to build extra copies of this atlas with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub themes: Vec<String>,
 }
 
 fn default_max_size() -> [u32; 2] {
@@ -383,6 +546,9 @@ pub struct ExportsConfig {
     /// libGDX export
     #[serde(default)]
     pub libgdx: LibGdxExportConfig,
+    /// Exporter plugins to run for every atlas, by registered name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<String>,
 }
 
 /// Validation settings for the build process
@@ -412,6 +578,105 @@ fn default_missing_refs() -> ValidationLevel {
     ValidationLevel::Error
 }
 
+/// How a lint rule reports violations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// Rule is disabled
+    Allow,
+    /// Report violations without failing
+    Warn,
+    /// Report violations and fail `pxl lint`
+    Deny,
+}
+
+impl std::fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warn"),
+            LintLevel::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// Style lint settings for `pxl lint`
+///
+/// ```toml
+/// [lint]
+/// max_colors = 8
+///
+/// [lint.rules]
+/// named-palette = "deny"
+/// snake-case-names = "allow"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// Most distinct colors a sprite may use (`max-colors` rule)
+    #[serde(default = "default_max_colors")]
+    pub max_colors: usize,
+    /// Level per rule name, overriding each rule's default
+    #[serde(default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub rules: HashMap<String, LintLevel>,
+}
+
+fn default_max_colors() -> usize {
+    16
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self { max_colors: default_max_colors(), rules: HashMap::new() }
+    }
+}
+
+/// Resource guardrails applied by `render` and `validate`
+///
+/// Definitions over a limit are rejected before anything is allocated for
+/// them, so hostile or accidental multi-gigapixel inputs fail fast.
+///
+/// ```toml
+/// [limits]
+/// max_size = [4096, 4096]
+/// max_colors = 1024
+/// max_frames = 4096
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LimitsConfig {
+    /// Largest sprite or composition canvas, as [width, height]
+    #[serde(default = "default_limit_size")]
+    pub max_size: [u32; 2],
+    /// Most colors a palette or sprite may define
+    #[serde(default = "default_limit_colors")]
+    pub max_colors: usize,
+    /// Most frames an animation may have
+    #[serde(default = "default_limit_frames")]
+    pub max_frames: usize,
+}
+
+fn default_limit_size() -> [u32; 2] {
+    [4096, 4096]
+}
+
+fn default_limit_colors() -> usize {
+    1024
+}
+
+fn default_limit_frames() -> usize {
+    4096
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_size: default_limit_size(),
+            max_colors: default_limit_colors(),
+            max_frames: default_limit_frames(),
+        }
+    }
+}
+
 /// A single dependency declaration.
 ///
 /// Dependencies can be either path-based (local filesystem) or git-based (remote repository).
@@ -473,6 +738,15 @@ impl Default for WatchConfig {
     }
 }
 
+/// Build hook configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Rhai script defining build hooks, relative to the project root
+    /// (requires the `scripting` feature)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub script: Option<PathBuf>,
+}
+
 /// Complete pxl.toml configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PxlConfig {
@@ -492,6 +766,7 @@ pub struct PxlConfig {
     pub defaults: DefaultsConfig,
     /// Atlas definitions
     #[serde(default)]
+    #[serde(serialize_with = "ordered::map")]
     pub atlases: HashMap<String, AtlasConfig>,
     /// Animation settings
     #[serde(default)]
@@ -502,12 +777,29 @@ pub struct PxlConfig {
     /// Validation settings
     #[serde(default)]
     pub validate: ValidateConfig,
+    /// Style lint settings
+    #[serde(default)]
+    pub lint: LintConfig,
     /// Watch mode settings
     #[serde(default)]
     pub watch: WatchConfig,
     /// External project dependencies
     #[serde(default)]
+    #[serde(serialize_with = "ordered::map")]
     pub dependencies: HashMap<String, Dependency>,
+    /// Multi-resolution export profiles
+    #[serde(default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub profiles: HashMap<String, ProfileConfig>,
+    /// Output path templates
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// Resource guardrails
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    /// Build hooks
+    #[serde(default)]
+    pub hooks: HooksConfig,
 }
 
 /// Configuration validation error
@@ -554,6 +846,25 @@ impl PxlConfig {
             });
         }
 
+        // Validate limits
+        if self.limits.max_size[0] == 0 || self.limits.max_size[1] == 0 {
+            errors.push(ConfigValidationError {
+                field: "limits.max_size".to_string(),
+                message: "dimensions must be positive".to_string(),
+            });
+        }
+        for (field, value) in [
+            ("limits.max_colors", self.limits.max_colors),
+            ("limits.max_frames", self.limits.max_frames),
+        ] {
+            if value == 0 {
+                errors.push(ConfigValidationError {
+                    field: field.to_string(),
+                    message: "must be a positive integer".to_string(),
+                });
+            }
+        }
+
         // Validate atlases
         for (name, atlas) in &self.atlases {
             if atlas.sources.is_empty() {
@@ -580,6 +891,39 @@ impl PxlConfig {
             }
         }
 
+        // Validate profiles
+        let mut profile_names: Vec<&String> = self.profiles.keys().collect();
+        profile_names.sort();
+        let mut stems: HashMap<String, &String> = HashMap::new();
+        for name in profile_names {
+            let profile = &self.profiles[name];
+            if profile.scale == 0 {
+                errors.push(ConfigValidationError {
+                    field: format!("profiles.{}.scale", name),
+                    message: "must be a positive integer".to_string(),
+                });
+            }
+            if !profile.naming.contains("{name}") {
+                errors.push(ConfigValidationError {
+                    field: format!("profiles.{}.naming", name),
+                    message: "must contain '{name}'".to_string(),
+                });
+            }
+            if let Some(other) = stems.insert(profile.file_stem("name"), name) {
+                errors.push(ConfigValidationError {
+                    field: format!("profiles.{}.naming", name),
+                    message: format!("produces the same file names as profile '{}'", other),
+                });
+            }
+        }
+
+        // Validate output templates
+        for (field, template) in self.output.templates() {
+            if let Some(message) = OutputConfig::template_error(template) {
+                errors.push(ConfigValidationError { field: format!("output.{}", field), message });
+            }
+        }
+
         // Validate animations
         if self.animations.preview_scale == 0 {
             errors.push(ConfigValidationError {
@@ -596,6 +940,24 @@ impl PxlConfig {
             });
         }
 
+        // Validate lint rules
+        let mut rule_names: Vec<&String> = self.lint.rules.keys().collect();
+        rule_names.sort();
+        for name in rule_names {
+            if crate::lint::find_rule(name).is_none() {
+                errors.push(ConfigValidationError {
+                    field: format!("lint.rules.{}", name),
+                    message: "is not a known lint rule".to_string(),
+                });
+            }
+        }
+        if self.lint.max_colors == 0 {
+            errors.push(ConfigValidationError {
+                field: "lint.max_colors".to_string(),
+                message: "must be a positive integer".to_string(),
+            });
+        }
+
         // Validate dependencies
         for (name, dep) in &self.dependencies {
             // Dependency names cannot contain ':' or '/'
@@ -657,6 +1019,22 @@ impl PxlConfig {
         self.validate().is_empty()
     }
 
+    /// Export profiles to build, smallest scale first.
+    ///
+    /// Without `[profiles]`, a single profile at `defaults.scale` keeps the
+    /// plain output names.
+    pub fn export_profiles(&self) -> Vec<ProfileConfig> {
+        if self.profiles.is_empty() {
+            return vec![ProfileConfig {
+                scale: self.defaults.scale,
+                naming: "{name}".to_string(),
+            }];
+        }
+        let mut profiles: Vec<ProfileConfig> = self.profiles.values().cloned().collect();
+        profiles.sort_by(|a, b| a.scale.cmp(&b.scale).then_with(|| a.naming.cmp(&b.naming)));
+        profiles
+    }
+
     /// Get effective padding for an atlas (atlas-specific or default)
     pub fn effective_padding(&self, atlas: &AtlasConfig) -> u32 {
         atlas.padding.unwrap_or(self.defaults.padding)
@@ -992,6 +1370,44 @@ name = "test"
         assert!(!config.validate.allow_cycles);
     }
 
+    #[test]
+    fn test_lint_config() {
+        let toml = r#"
+[project]
+name = "test"
+
+[lint]
+max_colors = 8
+
+[lint.rules]
+named-palette = "deny"
+snake-case-names = "allow"
+"#;
+        let config: PxlConfig = toml::from_str(toml).expect("lint config should parse");
+        assert_eq!(config.lint.max_colors, 8);
+        assert_eq!(config.lint.rules["named-palette"], LintLevel::Deny);
+        assert_eq!(config.lint.rules["snake-case-names"], LintLevel::Allow);
+        assert!(config.validate().is_empty());
+
+        let defaults: PxlConfig = toml::from_str("[project]\nname = \"test\"").unwrap();
+        assert_eq!(defaults.lint.max_colors, 16);
+        assert!(defaults.lint.rules.is_empty());
+    }
+
+    #[test]
+    fn test_validation_unknown_lint_rule() {
+        let toml = r#"
+[project]
+name = "test"
+
+[lint.rules]
+no-such-rule = "warn"
+"#;
+        let config: PxlConfig = toml::from_str(toml).expect("lint config should parse");
+        let errors = config.validate();
+        assert!(errors.iter().any(|e| e.field == "lint.rules.no-such-rule"));
+    }
+
     #[test]
     fn test_antialias_config_defaults() {
         let toml = r#"
@@ -1347,4 +1763,149 @@ sources = ["sprites/**"]
         assert!(config.atlases.contains_key("characters"));
         assert!(config.validate().is_empty());
     }
+
+    #[test]
+    fn test_export_profiles() {
+        let toml = r#"
+[project]
+name = "test"
+
+[defaults]
+scale = 3
+
+[profiles.hd]
+scale = 4
+naming = "{name}-hd"
+
+[profiles.1x]
+scale = 1
+naming = "{name}"
+
+[profiles.2x]
+scale = 2
+"#;
+        let config: PxlConfig = toml::from_str(toml).expect("profiles should parse");
+        assert!(config.validate().is_empty());
+
+        let profiles = config.export_profiles();
+        let scales: Vec<u32> = profiles.iter().map(|p| p.scale).collect();
+        assert_eq!(scales, vec![1, 2, 4]);
+        assert_eq!(profiles[0].file_stem("hero"), "hero");
+        assert_eq!(profiles[1].file_stem("hero"), "hero@2x");
+        assert_eq!(profiles[2].file_stem("hero"), "hero-hd");
+    }
+
+    #[test]
+    fn test_export_profiles_default() {
+        let toml = r#"
+[project]
+name = "test"
+
+[defaults]
+scale = 2
+"#;
+        let config: PxlConfig = toml::from_str(toml).unwrap();
+        let profiles = config.export_profiles();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].scale, 2);
+        assert_eq!(profiles[0].file_stem("hero"), "hero");
+    }
+
+    #[test]
+    fn test_validation_profiles() {
+        let toml = r#"
+[project]
+name = "test"
+
+[profiles.a]
+scale = 0
+naming = "sprite"
+
+[profiles.b]
+scale = 2
+naming = "{name}-x{scale}"
+
+[profiles.c]
+scale = 2
+naming = "{name}-x2"
+"#;
+        let config: PxlConfig = toml::from_str(toml).unwrap();
+        let errors = config.validate();
+        assert!(errors.iter().any(|e| e.field == "profiles.a.scale"));
+        assert!(errors.iter().any(|e| e.field == "profiles.a.naming"));
+        assert!(errors
+            .iter()
+            .any(|e| e.field == "profiles.c.naming" && e.message.contains("profile 'b'")));
+    }
+
+    #[test]
+    fn test_output_template_expand() {
+        let one = ProfileConfig { scale: 1, naming: "{name}".to_string() };
+        let two = ProfileConfig { scale: 2, naming: "{name}@2x".to_string() };
+        let vars = OutputVars { kind: "atlas", name: "chars", ext: "png", theme: None };
+
+        // Default templates keep the flat layout and profile naming
+        let output = OutputConfig::default();
+        assert_eq!(OutputConfig::expand(&output.atlas, vars, &one), PathBuf::from("chars.png"));
+        assert_eq!(OutputConfig::expand(&output.atlas, vars, &two), PathBuf::from("chars@2x.png"));
+
+        // `{scale}` takes over from profile naming
+        let template = "{type}/{name}@{scale}x.{ext}";
+        assert_eq!(OutputConfig::expand(template, vars, &two), PathBuf::from("atlas/chars@2x.png"));
+
+        // Unset `{theme}` drops its directory; themes without `{theme}` suffix the name
+        let themed = OutputVars { theme: Some("night"), ..vars };
+        let template = "{theme}/{name}.{ext}";
+        assert_eq!(OutputConfig::expand(template, vars, &one), PathBuf::from("chars.png"));
+        assert_eq!(OutputConfig::expand(template, themed, &one), PathBuf::from("night/chars.png"));
+        assert_eq!(
+            OutputConfig::expand(&output.atlas, themed, &two),
+            PathBuf::from("chars-night@2x.png")
+        );
+    }
+
+    #[test]
+    fn test_validation_output_templates() {
+        let toml = r#"
+[project]
+name = "test"
+
+[output]
+atlas = "{name}.png"
+animation = "{tag}/{name}.{ext}"
+preview = "../{name}.{ext}"
+export = "{type}.{ext}"
+"#;
+        let config: PxlConfig = toml::from_str(toml).unwrap();
+        let errors = config.validate();
+        let message = |field: &str| {
+            errors.iter().find(|e| e.field == field).map(|e| e.message.clone()).unwrap_or_default()
+        };
+        assert!(message("output.atlas").contains("'.{ext}'"));
+        assert!(message("output.animation").contains("'{tag}'"));
+        assert!(message("output.preview").contains("inside the output directory"));
+        assert!(message("output.export").contains("'{name}'"));
+        assert!(PxlConfig { output: OutputConfig::default(), ..config }.is_valid());
+    }
+
+    #[test]
+    fn test_limits_config() {
+        let config: PxlConfig = toml::from_str(
+            r#"
+[project]
+name = "test"
+
+[limits]
+max_size = [256, 0]
+max_frames = 0
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.limits.max_colors, 1024);
+        let errors = config.validate();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert!(fields.contains(&"limits.max_size"));
+        assert!(fields.contains(&"limits.max_frames"));
+        assert!(!fields.contains(&"limits.max_colors"));
+    }
 }
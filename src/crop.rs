@@ -0,0 +1,378 @@
+//! Auto-cropping of sprites with excess transparent canvas
+//!
+//! [`auto_crop_str`] renders each sprite, finds the bounding box of its
+//! non-transparent pixels, and shrinks `size` to it. Regions, the origin,
+//! attach points, collision boxes, and nine-slice borders move with the
+//! pixels, as do per-frame boxes of animations that use the sprite. As in
+//! [`crate::fix`], only the lines of the objects that change are rewritten.
+//!
+//! ```
+//! use pixelsrc::crop::auto_crop_str;
+//!
+//! let result = auto_crop_str(
+//!     r##"{"type": "sprite", "name": "dot", "size": [8, 8], "palette": {"x": "#FF0000"}, "regions": {"x": {"rect": [2, 3, 2, 2]}}}"##,
+//!     None,
+//! )
+//! .unwrap();
+//! assert_eq!(result.crops[0].offset, [2, 3]);
+//! assert!(result.text.contains(r#""size":[2,2]"#));
+//! ```
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use image::RgbaImage;
+
+use crate::models::{RegionDef, Sprite, TtpObject};
+use crate::parser::parse_stream;
+use crate::registry::{PaletteRegistry, SpriteRegistry};
+use crate::renderer::render_resolved;
+use crate::transforms::{transform_metadata, transform_nine_slice, Transform};
+
+/// A sprite that was cropped
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedCrop {
+    /// Line number (1-indexed) of the sprite in the original source
+    pub line: usize,
+    /// Sprite name
+    pub sprite: String,
+    /// Size before cropping
+    pub from: [u32; 2],
+    /// Size after cropping
+    pub to: [u32; 2],
+    /// How far content moved up and left: the top-left of the kept area
+    pub offset: [u32; 2],
+}
+
+/// The cropped source, the crops applied to it, and anything to look at
+#[derive(Debug, Clone, PartialEq)]
+pub struct CropResult {
+    /// Cropped source text (identical to the input when nothing was cropped)
+    pub text: String,
+    /// Applied crops, in source order
+    pub crops: Vec<AppliedCrop>,
+    /// Sprites that were skipped, metadata that moved out of the sprite, and
+    /// animations whose frames no longer line up
+    pub warnings: Vec<String>,
+}
+
+/// Crop every sprite in `source` (or only the one named `only`) to the
+/// bounding box of its non-transparent pixels.
+///
+/// Sprites built from a `source` sprite or with sprite-level transforms are
+/// left alone, as are sprites whose regions can't be moved (paths, region
+/// transforms, symmetry) or that would render differently once moved. Fails
+/// only if a cropped object cannot be serialized.
+pub fn auto_crop_str(source: &str, only: Option<&str>) -> Result<CropResult, serde_json::Error> {
+    let parsed = parse_stream(Cursor::new(source));
+    let mut objects = parsed.objects;
+
+    let mut palette_registry = PaletteRegistry::new();
+    let mut sprite_registry = SpriteRegistry::new();
+    for obj in &objects {
+        match obj {
+            TtpObject::Palette(p) => palette_registry.register(p.clone()),
+            TtpObject::Sprite(s) => sprite_registry.register_sprite(s.clone()),
+            TtpObject::Variant(v) => sprite_registry.register_variant(v.clone()),
+            _ => {}
+        }
+    }
+
+    let mut crops = Vec::new();
+    let mut warnings = Vec::new();
+    let mut changed = vec![false; objects.len()];
+    let mut offsets: HashMap<String, [u32; 2]> = HashMap::new();
+
+    for (index, obj) in objects.iter_mut().enumerate() {
+        let TtpObject::Sprite(sprite) = obj else {
+            continue;
+        };
+        if only.is_some_and(|name| name != sprite.name) {
+            continue;
+        }
+        if sprite.source.is_some() || (sprite.regions.is_none() && sprite.layers.is_none()) {
+            continue;
+        }
+        let Some(image) = render(&sprite_registry, &palette_registry, &sprite.name) else {
+            continue;
+        };
+        let Some([x, y, w, h]) = opaque_bounds(&image) else {
+            warnings.push(format!("sprite '{}' is fully transparent, skipped", sprite.name));
+            continue;
+        };
+        let from = image.dimensions();
+        if (w, h) == from {
+            continue;
+        }
+        if sprite.transform.is_some() {
+            warnings.push(format!("sprite '{}' has transforms, skipped", sprite.name));
+            continue;
+        }
+
+        let cropped = match crop_sprite(sprite, [x, y, w, h], from) {
+            Ok((cropped, notes)) => {
+                warnings
+                    .extend(notes.into_iter().map(|n| format!("sprite '{}': {}", sprite.name, n)));
+                cropped
+            }
+            Err(reason) => {
+                warnings.push(format!("sprite '{}' not cropped: {}", sprite.name, reason));
+                continue;
+            }
+        };
+
+        // Shapes like flood fills and jitter can depend on where they sit, so
+        // only keep the crop if the sprite still renders the same pixels
+        sprite_registry.register_sprite(cropped.clone());
+        let expected = image::imageops::crop_imm(&image, x, y, w, h).to_image();
+        if render(&sprite_registry, &palette_registry, &sprite.name).as_ref() != Some(&expected) {
+            sprite_registry.register_sprite(sprite.clone());
+            warnings.push(format!(
+                "sprite '{}' not cropped: its regions render differently once moved",
+                sprite.name
+            ));
+            continue;
+        }
+
+        crops.push(AppliedCrop {
+            line: parsed.spans[index].start,
+            sprite: sprite.name.clone(),
+            from: [from.0, from.1],
+            to: [w, h],
+            offset: [x, y],
+        });
+        offsets.insert(sprite.name.clone(), [x, y]);
+        *sprite = cropped;
+        changed[index] = true;
+    }
+
+    // Per-frame boxes follow their frame's sprite; frames drawn from their
+    // top-left corner drift apart when cropped by different amounts
+    for (index, obj) in objects.iter_mut().enumerate() {
+        let TtpObject::Animation(anim) = obj else {
+            continue;
+        };
+        if !anim.frames.iter().any(|f| offsets.contains_key(f)) {
+            continue;
+        }
+        if let Some(frame_metadata) = &mut anim.frame_metadata {
+            for (frame, meta) in anim.frames.iter().zip(frame_metadata.iter_mut()) {
+                let Some([dx, dy]) = offsets.get(frame) else {
+                    continue;
+                };
+                for b in meta.boxes.iter_mut().flat_map(|boxes| boxes.values_mut()).flatten() {
+                    b.x -= *dx as i32;
+                    b.y -= *dy as i32;
+                    changed[index] = true;
+                }
+            }
+        }
+
+        let frame_offset = |frame: &String| offsets.get(frame).copied().unwrap_or([0, 0]);
+        let Some(first) = anim.frames.first().map(frame_offset) else {
+            continue;
+        };
+        let unpinned = anim.frames.iter().any(|f| {
+            sprite_registry
+                .get_sprite(f)
+                .is_some_and(|s| s.metadata.as_ref().and_then(|m| m.origin).is_none())
+        });
+        if unpinned && anim.frames.iter().any(|f| frame_offset(f) != first) {
+            warnings.push(format!(
+                "animation '{}': frames were cropped by different offsets and will lose \
+                 alignment; give each frame an origin to keep them lined up",
+                anim.name
+            ));
+        }
+    }
+
+    let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+    for (index, obj) in objects.iter().enumerate().rev() {
+        if changed[index] {
+            let span = &parsed.spans[index];
+            lines.splice(span.start - 1..span.end - 1, [serde_json::to_string(obj)?]);
+        }
+    }
+
+    let mut text = lines.join("\n");
+    if source.ends_with('\n') && !text.is_empty() {
+        text.push('\n');
+    }
+    crops.sort_by_key(|crop| crop.line);
+    Ok(CropResult { text, crops, warnings })
+}
+
+fn render(sprites: &SpriteRegistry, palettes: &PaletteRegistry, name: &str) -> Option<RgbaImage> {
+    let resolved = sprites.resolve(name, palettes, false).ok()?;
+    Some(render_resolved(&resolved).0)
+}
+
+/// Bounding box `[x, y, w, h]` of the pixels with any opacity.
+pub fn opaque_bounds(image: &RgbaImage) -> Option<[u32; 4]> {
+    let mut bounds: Option<[u32; 4]> = None;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let [x0, y0, x1, y1] = bounds.get_or_insert([x, y, x, y]);
+        *x0 = (*x0).min(x);
+        *y0 = (*y0).min(y);
+        *x1 = (*x1).max(x);
+        *y1 = (*y1).max(y);
+    }
+    bounds.map(|[x0, y0, x1, y1]| [x0, y0, x1 - x0 + 1, y1 - y0 + 1])
+}
+
+/// A copy of `sprite` cut down to `rect`, with warnings about metadata that
+/// ended up outside it.
+fn crop_sprite(
+    sprite: &Sprite,
+    [x, y, w, h]: [u32; 4],
+    from: (u32, u32),
+) -> Result<(Sprite, Vec<String>), String> {
+    let mut cropped = sprite.clone();
+    let regions = cropped.regions.iter_mut().flatten();
+    let layers = cropped.layers.iter_mut().flatten().flat_map(|l| l.regions.iter_mut());
+    for (name, region) in regions.chain(layers) {
+        shift_region(region, x, y).map_err(|reason| format!("region '{}' {}", name, reason))?;
+    }
+    cropped.size = Some([w, h]);
+
+    let crop = Transform::Crop { x, y, w, h };
+    let mut warnings = Vec::new();
+    if let Some(metadata) = &sprite.metadata {
+        let (metadata, notes) = transform_metadata(metadata, &crop, from, (w, h));
+        cropped.metadata = Some(metadata);
+        warnings.extend(notes);
+    }
+    if let Some(nine_slice) = &sprite.nine_slice {
+        let (nine_slice, notes) = transform_nine_slice(nine_slice, &crop, from, (w, h));
+        cropped.nine_slice = nine_slice;
+        warnings.extend(notes);
+    }
+    Ok((cropped, warnings))
+}
+
+/// Move a region's coordinates up by `dy` and left by `dx`.
+///
+/// Fails with the reason when the region can't be moved: paths, region
+/// transforms, and symmetry depend on absolute positions, and shapes that
+/// start above or left of the kept area would need negative coordinates.
+fn shift_region(region: &mut RegionDef, dx: u32, dy: u32) -> Result<(), String> {
+    if region.path.is_some() {
+        return Err("uses a path".to_string());
+    }
+    if region.transform.is_some() {
+        return Err("has a transform".to_string());
+    }
+    if region.symmetric.is_some() {
+        return Err("is symmetric".to_string());
+    }
+
+    let outside = || "starts outside the cropped area".to_string();
+    let point = |[x, y]: [u32; 2]| -> Result<[u32; 2], String> {
+        Ok([x.checked_sub(dx).ok_or_else(outside)?, y.checked_sub(dy).ok_or_else(outside)?])
+    };
+    for list in [&mut region.points, &mut region.line, &mut region.polygon].into_iter().flatten() {
+        for p in list.iter_mut() {
+            *p = point(*p)?;
+        }
+    }
+    for rect in [&mut region.rect, &mut region.stroke, &mut region.ellipse].into_iter().flatten() {
+        [rect[0], rect[1]] = point([rect[0], rect[1]])?;
+    }
+    if let Some(circle) = &mut region.circle {
+        [circle[0], circle[1]] = point([circle[0], circle[1]])?;
+    }
+    // Range limits only clip, so columns and rows cut off by the crop can go
+    if let Some(range) = &mut region.x {
+        *range = range.map(|v| v.saturating_sub(dx));
+    }
+    if let Some(range) = &mut region.y {
+        *range = range.map(|v| v.saturating_sub(dy));
+    }
+
+    if let Some(base) = &mut region.base {
+        shift_region(base, dx, dy)?;
+    }
+    let compound =
+        [&mut region.union, &mut region.subtract, &mut region.intersect, &mut region.xor];
+    for shapes in compound.into_iter().flatten() {
+        for shape in shapes.iter_mut() {
+            shift_region(shape, dx, dy)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALETTE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}"##;
+
+    fn crop(source: &str) -> CropResult {
+        auto_crop_str(&format!("{}\n{}\n", PALETTE, source), None).unwrap()
+    }
+
+    #[test]
+    fn test_auto_crop_shifts_regions_and_metadata() {
+        let result = crop(
+            r#"{"type": "sprite", "name": "gem", "size": [10, 10], "palette": "p", "regions": {"r": {"union": [{"rect": [3, 2, 4, 2]}, {"points": [[5, 6]]}]}, "b": {"circle": [5, 3, 1]}}, "metadata": {"origin": [5, 7], "boxes": {"hit": {"x": 3, "y": 2, "w": 4, "h": 5}}}}"#,
+        );
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+        assert_eq!(result.crops.len(), 1);
+        let applied = &result.crops[0];
+        assert_eq!(
+            (applied.line, applied.from, applied.to, applied.offset),
+            (2, [10, 10], [4, 5], [3, 2])
+        );
+
+        let objects = parse_stream(Cursor::new(&result.text)).objects;
+        let TtpObject::Sprite(gem) = &objects[1] else { panic!("expected sprite") };
+        assert_eq!(gem.size, Some([4, 5]));
+        let regions = gem.regions.as_ref().unwrap();
+        assert_eq!(regions["r"].union.as_ref().unwrap()[1].points, Some(vec![[2, 4]]));
+        assert_eq!(regions["b"].circle, Some([2, 1, 1]));
+        let metadata = gem.metadata.as_ref().unwrap();
+        assert_eq!(metadata.origin, Some([2, 5]));
+        assert_eq!(
+            (metadata.boxes.as_ref().unwrap()["hit"].x, metadata.boxes.as_ref().unwrap()["hit"].y),
+            (0, 0)
+        );
+
+        // Already tight: nothing to do, and the source is untouched
+        let again = auto_crop_str(&result.text, None).unwrap();
+        assert!(again.crops.is_empty());
+        assert_eq!(again.text, result.text);
+    }
+
+    #[test]
+    fn test_auto_crop_skips_and_warns() {
+        let result = crop(
+            r#"{"type": "sprite", "name": "wave", "size": [8, 8], "palette": "p", "regions": {"r": {"path": "M2,2 L5,2 L5,5 Z"}}}
+{"type": "sprite", "name": "empty", "size": [4, 4], "palette": "p", "regions": {"_": {"rect": [0, 0, 4, 4]}}}
+{"type": "sprite", "name": "a", "size": [4, 4], "palette": "p", "regions": {"r": {"rect": [1, 1, 2, 2]}}}
+{"type": "sprite", "name": "b", "size": [4, 4], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+{"type": "animation", "name": "blink", "frames": ["a", "b"]}"#,
+        );
+        let names: Vec<&str> = result.crops.iter().map(|c| c.sprite.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        let warnings = result.warnings.join("\n");
+        assert!(
+            warnings.contains("sprite 'wave' not cropped: region 'r' uses a path"),
+            "{}",
+            warnings
+        );
+        assert!(warnings.contains("sprite 'empty' is fully transparent"), "{}", warnings);
+        assert!(warnings.contains("animation 'blink'"), "{}", warnings);
+
+        // Cropping one sprite by name leaves the others alone
+        let source = format!(
+            "{}\n{}",
+            PALETTE,
+            r#"{"type": "sprite", "name": "a", "size": [4, 4], "palette": "p", "regions": {"r": {"rect": [1, 1, 2, 2]}}}"#
+        );
+        assert!(auto_crop_str(&source, Some("other")).unwrap().crops.is_empty());
+    }
+}
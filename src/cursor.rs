@@ -0,0 +1,264 @@
+//! Windows cursor (.cur / .ani) encoding
+//!
+//! A `.cur` file is an icon directory with a single entry whose hotspot is
+//! stored in the entry header. Images are written as 32-bit BMP data with an
+//! AND mask, which every Windows version (and most cursor editors) can read.
+//!
+//! An `.ani` file is a RIFF `ACON` container: an `anih` header, a `rate`
+//! chunk with per-frame display times in jiffies (1/60 s), and a `fram` list
+//! holding one complete `.cur` per frame.
+
+use std::fs;
+use std::path::Path;
+
+use image::RgbaImage;
+
+use crate::output::OutputError;
+
+/// Largest cursor width or height the format can describe
+pub const MAX_CURSOR_SIZE: u32 = 256;
+
+/// Hotspot for a cursor from a sprite's `metadata.origin`.
+///
+/// The origin is scaled along with the image and clamped into its bounds;
+/// without an origin the hotspot is the top-left pixel.
+pub fn hotspot_from_origin(
+    origin: Option<[i32; 2]>,
+    scale: u32,
+    width: u32,
+    height: u32,
+) -> (u16, u16) {
+    let [x, y] = origin.unwrap_or([0, 0]);
+    let clamp = |v: i32, max: u32| (v.max(0) as u32 * scale).min(max.saturating_sub(1)) as u16;
+    (clamp(x, width), clamp(y, height))
+}
+
+/// Encode an image as a `.cur` file with the given hotspot.
+pub fn encode_cur(image: &RgbaImage, hotspot: (u16, u16)) -> Result<Vec<u8>, OutputError> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 || width > MAX_CURSOR_SIZE || height > MAX_CURSOR_SIZE {
+        return Err(OutputError::CursorSize { width, height });
+    }
+
+    let bitmap = encode_bitmap(image);
+    let mut out = Vec::with_capacity(22 + bitmap.len());
+
+    // ICONDIR: reserved, type (2 = cursor), image count
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+
+    // ICONDIRENTRY; a size byte of 0 means 256
+    out.push(width as u8);
+    out.push(height as u8);
+    out.push(0); // color count
+    out.push(0); // reserved
+    out.extend_from_slice(&hotspot.0.to_le_bytes());
+    out.extend_from_slice(&hotspot.1.to_le_bytes());
+    out.extend_from_slice(&(bitmap.len() as u32).to_le_bytes());
+    out.extend_from_slice(&22u32.to_le_bytes());
+
+    out.extend_from_slice(&bitmap);
+    Ok(out)
+}
+
+/// Encode frames as an animated `.ani` cursor.
+///
+/// `durations_ms` holds one entry per frame; frames past its end reuse the
+/// last entry. Every frame shares `hotspot`. Animated cursors always loop.
+pub fn encode_ani(
+    frames: &[RgbaImage],
+    durations_ms: &[u32],
+    hotspot: (u16, u16),
+) -> Result<Vec<u8>, OutputError> {
+    let fallback = durations_ms.last().copied().unwrap_or(100);
+    let jiffies: Vec<u32> = (0..frames.len())
+        .map(|i| {
+            let ms = durations_ms.get(i).copied().unwrap_or(fallback);
+            ((ms * 60 + 500) / 1000).max(1)
+        })
+        .collect();
+
+    let mut body = Vec::new();
+    body.extend_from_slice(b"ACON");
+
+    // anih: header size, frames, steps, width/height/bit count/planes (unused
+    // for icon frames), default rate, flags (1 = frames are icons/cursors)
+    let mut header = Vec::with_capacity(36);
+    for value in [36, frames.len() as u32, frames.len() as u32, 0, 0, 0, 0] {
+        header.extend_from_slice(&value.to_le_bytes());
+    }
+    header.extend_from_slice(&jiffies.first().copied().unwrap_or(6).to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes());
+    write_chunk(&mut body, b"anih", &header);
+
+    let rate: Vec<u8> = jiffies.iter().flat_map(|j| j.to_le_bytes()).collect();
+    write_chunk(&mut body, b"rate", &rate);
+
+    let mut list = Vec::new();
+    list.extend_from_slice(b"fram");
+    for frame in frames {
+        let hotspot = (
+            hotspot.0.min(frame.width().saturating_sub(1) as u16),
+            hotspot.1.min(frame.height().saturating_sub(1) as u16),
+        );
+        write_chunk(&mut list, b"icon", &encode_cur(frame, hotspot)?);
+    }
+    write_chunk(&mut body, b"LIST", &list);
+
+    let mut out = Vec::with_capacity(8 + body.len());
+    write_chunk(&mut out, b"RIFF", &body);
+    Ok(out)
+}
+
+/// Save an image as a `.cur` file.
+pub fn save_cur(image: &RgbaImage, hotspot: (u16, u16), path: &Path) -> Result<(), OutputError> {
+    write_file(path, &encode_cur(image, hotspot)?)
+}
+
+/// Save frames as an `.ani` file.
+pub fn save_ani(
+    frames: &[RgbaImage],
+    durations_ms: &[u32],
+    hotspot: (u16, u16),
+    path: &Path,
+) -> Result<(), OutputError> {
+    write_file(path, &encode_ani(frames, durations_ms, hotspot)?)
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), OutputError> {
+    // Create parent directories if they don't exist
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// BITMAPINFOHEADER, bottom-up BGRA pixels and the 1-bit AND mask.
+fn encode_bitmap(image: &RgbaImage) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let mask_stride = width.div_ceil(32) * 4;
+    let image_size = width * height * 4 + mask_stride * height;
+
+    let mut out = Vec::with_capacity(40 + image_size as usize);
+    out.extend_from_slice(&40u32.to_le_bytes());
+    out.extend_from_slice(&(width as i32).to_le_bytes());
+    // Height covers both the color bitmap and the mask
+    out.extend_from_slice(&(height as i32 * 2).to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // planes
+    out.extend_from_slice(&32u16.to_le_bytes()); // bits per pixel
+    out.extend_from_slice(&0u32.to_le_bytes()); // BI_RGB
+    out.extend_from_slice(&image_size.to_le_bytes());
+    out.extend_from_slice(&[0; 16]); // resolution and palette counts
+
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let [r, g, b, a] = image.get_pixel(x, y).0;
+            out.extend_from_slice(&[b, g, r, a]);
+        }
+    }
+
+    // Mask bits are set for transparent pixels
+    for y in (0..height).rev() {
+        let mut row = vec![0u8; mask_stride as usize];
+        for x in 0..width {
+            if image.get_pixel(x, y)[3] == 0 {
+                row[(x / 8) as usize] |= 0x80 >> (x % 8);
+            }
+        }
+        out.extend_from_slice(&row);
+    }
+
+    out
+}
+
+/// Append a RIFF chunk, padded to an even length.
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_encode_cur_header_and_mask() {
+        let mut image = RgbaImage::from_pixel(3, 2, Rgba([255, 0, 0, 255]));
+        image.put_pixel(1, 0, Rgba([0, 0, 0, 0]));
+        let cur = encode_cur(&image, (2, 1)).unwrap();
+
+        assert_eq!(u16_at(&cur, 2), 2, "type should be cursor");
+        assert_eq!((cur[6], cur[7]), (3, 2));
+        assert_eq!((u16_at(&cur, 10), u16_at(&cur, 12)), (2, 1), "hotspot");
+        assert_eq!(u32_at(&cur, 14) as usize, cur.len() - 22);
+        assert_eq!(u32_at(&cur, 22 + 8), 4, "bitmap height includes the mask");
+
+        // Top row is stored last: first BGRA pixel of it is red
+        let top_row = 22 + 40 + 3 * 4;
+        assert_eq!(&cur[top_row..top_row + 4], &[0, 0, 255, 255]);
+        // AND mask, top row last: only the transparent pixel is masked
+        let mask = 22 + 40 + 2 * 3 * 4;
+        assert_eq!(cur[mask], 0x00);
+        assert_eq!(cur[mask + 4], 0x40);
+    }
+
+    #[test]
+    fn test_encode_cur_rejects_oversized_images() {
+        let image = RgbaImage::new(257, 16);
+        assert!(matches!(
+            encode_cur(&image, (0, 0)),
+            Err(OutputError::CursorSize { width: 257, height: 16 })
+        ));
+        // 256 is stored as 0 in the directory entry
+        let cur = encode_cur(&RgbaImage::new(256, 256), (0, 0)).unwrap();
+        assert_eq!((cur[6], cur[7]), (0, 0));
+    }
+
+    #[test]
+    fn test_encode_ani_structure() {
+        let frames = vec![RgbaImage::new(4, 4), RgbaImage::new(4, 4), RgbaImage::new(2, 2)];
+        let ani = encode_ani(&frames, &[100, 250], (3, 3)).unwrap();
+
+        assert_eq!(&ani[0..4], b"RIFF");
+        assert_eq!(u32_at(&ani, 4) as usize, ani.len() - 8);
+        assert_eq!(&ani[8..12], b"ACON");
+        assert_eq!(&ani[12..16], b"anih");
+        assert_eq!(u32_at(&ani, 20 + 4), 3, "frame count");
+
+        // rate chunk: 100ms = 6 jiffies, 250ms = 15, last entry reused
+        assert_eq!(&ani[56..60], b"rate");
+        let rates: Vec<u32> = (0..3).map(|i| u32_at(&ani, 64 + i * 4)).collect();
+        assert_eq!(rates, vec![6, 15, 15]);
+
+        assert_eq!(&ani[76..80], b"LIST");
+        assert_eq!(&ani[84..88], b"fram");
+        assert_eq!(&ani[88..92], b"icon");
+        // Hotspot is clamped into the smaller frame
+        let third = ani.windows(4).rposition(|w| w == b"icon").unwrap() + 8;
+        assert_eq!((u16_at(&ani, third + 10), u16_at(&ani, third + 12)), (1, 1));
+    }
+
+    #[test]
+    fn test_hotspot_from_origin() {
+        assert_eq!(hotspot_from_origin(None, 2, 8, 8), (0, 0));
+        assert_eq!(hotspot_from_origin(Some([2, 3]), 2, 8, 8), (4, 6));
+        assert_eq!(hotspot_from_origin(Some([-1, 9]), 1, 8, 8), (0, 7));
+    }
+}
@@ -0,0 +1,641 @@
+//! Long-running render daemon for `pxl daemon`
+//!
+//! Keeps parsed source files and encoded renders in memory and answers
+//! requests from editors and build systems over a loopback TCP socket, so
+//! repeated renders skip process startup and parsing entirely. A source file
+//! is re-parsed (and its cached renders dropped) when its modification time
+//! changes. Like `pxl serve`, only the input file itself is read: `import`
+//! objects and `@include:` palettes are not followed, so a render depends on
+//! that one file's modification time alone.
+//!
+//! The daemon only binds loopback addresses, and every request must carry
+//! the random `token` printed at startup, so neither other machines nor web
+//! pages that post to the port can drive it. A line that isn't a valid,
+//! authenticated request closes the connection. Render outputs must resolve
+//! inside the project directory.
+//!
+//! # Protocol
+//!
+//! Newline-delimited JSON: one request object per line, answered by one
+//! response object per line, in order. Every request has a `method`, the
+//! daemon's `token`, and an optional `id` that is echoed back. Responses carry `"ok": true` plus the
+//! method's result fields, or `"ok": false` and an `error` message.
+//!
+//! | Method | Fields | Result |
+//! |--------|--------|--------|
+//! | `render` | A render job (`input`, `sprite`/`composition`/`animation`, `format`, `scale`, `crop`, `output`) | `output`, `bytes`, `cached` |
+//! | `validate` | `input` | `valid`, `errors`, `warnings` |
+//! | `ping` | | `version` |
+//! | `shutdown` | | Stops the daemon after responding |
+//!
+//! Relative paths are resolved against the daemon's working directory.
+//!
+//! At most [`MAX_DOCUMENTS`] source files stay parsed, each caching up to
+//! [`RENDER_CACHE_BYTES`] of renders; the oldest entries are dropped first.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::render_job::{write_output, RenderJob};
+use crate::serve::PreviewCatalog;
+use crate::validate::{Severity, ValidationIssue, Validator};
+
+/// Source files kept parsed at once
+pub const MAX_DOCUMENTS: usize = 32;
+
+/// Encoded bytes cached per source file
+pub const RENDER_CACHE_BYTES: usize = 16 << 20;
+
+/// Connections served at once; further clients are turned away
+pub const MAX_CONNECTIONS: usize = 16;
+
+/// How long a new connection may take to send its first request
+const FIRST_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Error starting the daemon
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DaemonError {
+    /// Failed to bind the listening socket
+    #[error("Failed to bind {addr}: {source}")]
+    Bind { addr: String, source: std::io::Error },
+    /// The address is reachable from other machines
+    #[error("Refusing to bind {addr}: the daemon only listens on loopback addresses")]
+    NotLoopback { addr: String },
+    /// The project directory can't be resolved
+    #[error("Failed to open project directory {path}: {source}")]
+    Root { path: String, source: std::io::Error },
+    /// The operating system couldn't supply a random token
+    #[error("Failed to generate an access token: {0}")]
+    Token(getrandom::Error),
+}
+
+/// A parsed source file and the renders produced from it
+struct Document {
+    modified: Option<SystemTime>,
+    catalog: PreviewCatalog,
+    renders: Mutex<RenderCache>,
+    /// Tick of the last request that used this document
+    used: AtomicU64,
+}
+
+/// Encoded renders of one document, evicted oldest first past [`RENDER_CACHE_BYTES`]
+#[derive(Default)]
+struct RenderCache {
+    entries: HashMap<String, Arc<Vec<u8>>>,
+    order: VecDeque<String>,
+    bytes: usize,
+}
+
+impl RenderCache {
+    fn insert(&mut self, key: String, render: Arc<Vec<u8>>) {
+        if render.len() > RENDER_CACHE_BYTES || self.entries.contains_key(&key) {
+            return;
+        }
+        while self.bytes + render.len() > RENDER_CACHE_BYTES {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.bytes -= evicted.len();
+            }
+        }
+        self.bytes += render.len();
+        self.order.push_back(key.clone());
+        self.entries.insert(key, render);
+    }
+}
+
+/// One protocol request
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    #[serde(default)]
+    token: String,
+    method: String,
+    #[serde(flatten)]
+    params: Map<String, Value>,
+}
+
+/// Documents and renders shared by every connection.
+pub struct DaemonState {
+    documents: Mutex<HashMap<PathBuf, Arc<Document>>>,
+    tick: AtomicU64,
+    shutdown: AtomicBool,
+    connections: AtomicUsize,
+    /// `--define` flags in effect when the daemon started
+    defines: Vec<String>,
+    /// Canonical directory that render outputs must stay inside
+    root: PathBuf,
+    /// Secret every request must repeat
+    token: String,
+}
+
+impl DaemonState {
+    /// Create an empty state writing under `root` and accepting requests
+    /// that carry `token`, keeping the flags defined on this thread.
+    pub fn new(root: &Path, token: String) -> std::io::Result<Self> {
+        Ok(Self {
+            documents: Mutex::default(),
+            tick: AtomicU64::new(0),
+            shutdown: AtomicBool::new(false),
+            connections: AtomicUsize::new(0),
+            defines: crate::conditions::defines(),
+            root: root.canonicalize()?,
+            token,
+        })
+    }
+
+    /// Whether a `shutdown` request has been handled.
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// Number of source files currently parsed.
+    pub fn document_count(&self) -> usize {
+        self.documents.lock().expect("document cache poisoned").len()
+    }
+
+    /// Handle one request line and return its response object.
+    ///
+    /// A line that isn't JSON or lacks the daemon's token returns `Err` with
+    /// a final error response; the connection should then be closed.
+    pub fn handle_line(&self, line: &str) -> Result<Value, Value> {
+        let request: Request = serde_json::from_str(line)
+            .map_err(|e| json!({ "id": null, "ok": false, "error": e.to_string() }))?;
+        if !tokens_match(&request.token, &self.token) {
+            return Err(
+                json!({ "id": request.id, "ok": false, "error": "missing or invalid token" }),
+            );
+        }
+        let result = match request.method.as_str() {
+            "render" => self.render(request.params),
+            "validate" => self.validate(request.params),
+            "ping" => Ok(json!({ "version": env!("CARGO_PKG_VERSION") })),
+            "shutdown" => {
+                self.shutdown.store(true, Ordering::SeqCst);
+                Ok(json!({}))
+            }
+            other => Err(format!("unknown method '{}'", other)),
+        };
+
+        let mut response = match result {
+            Ok(Value::Object(fields)) => fields,
+            Ok(_) => Map::new(),
+            Err(e) => Map::from_iter([("error".to_string(), json!(e))]),
+        };
+        let ok = !response.contains_key("error");
+        response.insert("id".to_string(), request.id);
+        response.insert("ok".to_string(), json!(ok));
+        Ok(Value::Object(response))
+    }
+
+    /// The parsed document for `path`, re-parsing it if it changed on disk.
+    ///
+    /// Parsing happens outside the cache lock, so a large file doesn't stall
+    /// requests for other documents.
+    fn document(&self, path: &Path) -> Result<Arc<Document>, String> {
+        let metadata = std::fs::metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let modified = metadata.modified().ok();
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed);
+
+        let cached = self.documents.lock().expect("document cache poisoned").get(path).cloned();
+        if let Some(document) = cached {
+            if modified.is_some() && document.modified == modified {
+                document.used.store(tick, Ordering::Relaxed);
+                return Ok(document);
+            }
+        }
+
+        let document = Arc::new(Document {
+            modified,
+            catalog: crate::conditions::with_defines(self.defines.clone(), || {
+                PreviewCatalog::load(&[path.to_path_buf()])
+            }),
+            renders: Mutex::default(),
+            used: AtomicU64::new(tick),
+        });
+        let mut documents = self.documents.lock().expect("document cache poisoned");
+        if documents.len() >= MAX_DOCUMENTS && !documents.contains_key(path) {
+            let oldest = documents
+                .iter()
+                .min_by_key(|(_, document)| document.used.load(Ordering::Relaxed))
+                .map(|(path, _)| path.clone());
+            if let Some(oldest) = oldest {
+                documents.remove(&oldest);
+            }
+        }
+        documents.insert(path.to_path_buf(), Arc::clone(&document));
+        Ok(document)
+    }
+
+    /// Resolve a render output, refusing paths that leave the project directory.
+    ///
+    /// Symlinks along the existing part of the path are followed before the
+    /// check, so a link inside the project can't point the write elsewhere.
+    fn output_path(&self, output: &Path) -> Result<PathBuf, String> {
+        let outside = || {
+            format!(
+                "output '{}' is outside the project directory {}",
+                output.display(),
+                self.root.display()
+            )
+        };
+        let mut existing =
+            std::path::absolute(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+        let mut missing = Vec::new();
+        while existing.symlink_metadata().is_err() {
+            missing.push(existing.file_name().ok_or_else(outside)?.to_owned());
+            existing.pop();
+        }
+        let mut resolved =
+            existing.canonicalize().map_err(|e| format!("{}: {}", output.display(), e))?;
+        if !resolved.starts_with(&self.root) {
+            return Err(outside());
+        }
+        resolved.extend(missing.iter().rev());
+        Ok(resolved)
+    }
+
+    fn render(&self, params: Map<String, Value>) -> Result<Value, String> {
+        let job: RenderJob = serde_json::from_value(Value::Object(params))
+            .map_err(|e| format!("invalid render request: {}", e))?;
+        job.validate()?;
+        let input = job.input.as_deref().ok_or("render needs an 'input' file")?;
+        let output = self.output_path(&job.output)?;
+        let document = self.document(input)?;
+
+        let key = format!("{}|{:?}|{}|{:?}", job.label(), job.format(), job.scale, job.crop);
+        let cached =
+            document.renders.lock().expect("render cache poisoned").entries.get(&key).cloned();
+        let (bytes, hit) = match cached {
+            Some(bytes) => (bytes, true),
+            None => {
+                let bytes = Arc::new(job.encode(&document.catalog)?);
+                document
+                    .renders
+                    .lock()
+                    .expect("render cache poisoned")
+                    .insert(key, Arc::clone(&bytes));
+                (bytes, false)
+            }
+        };
+
+        write_output(&bytes, &output)?;
+        Ok(json!({
+            "output": job.output.display().to_string(),
+            "bytes": bytes.len(),
+            "cached": hit,
+        }))
+    }
+
+    fn validate(&self, params: Map<String, Value>) -> Result<Value, String> {
+        let input =
+            params.get("input").and_then(Value::as_str).ok_or("validate needs an 'input' file")?;
+        let mut validator = Validator::new();
+        validator.validate_file(Path::new(input)).map_err(|e| format!("{}: {}", input, e))?;
+
+        let issues = validator.into_issues();
+        let of = |severity: Severity| -> Vec<Value> {
+            issues.iter().filter(|i| i.severity == severity).map(issue_json).collect()
+        };
+        let (errors, warnings) = (of(Severity::Error), of(Severity::Warning));
+        Ok(json!({ "valid": errors.is_empty(), "errors": errors, "warnings": warnings }))
+    }
+}
+
+fn issue_json(issue: &ValidationIssue) -> Value {
+    let mut obj = json!({
+        "line": issue.line,
+        "type": issue.issue_type.to_string(),
+        "message": issue.message,
+    });
+    if let Some(ref ctx) = issue.context {
+        obj["context"] = json!(ctx);
+    }
+    if let Some(ref sug) = issue.suggestion {
+        obj["suggestion"] = json!(sug);
+    }
+    obj
+}
+
+/// Compare tokens without stopping at the first differing byte.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// A fresh 128-bit token from the operating system's random source, as hex.
+fn random_token() -> Result<String, getrandom::Error> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Frees a connection slot when the connection ends.
+struct ConnectionSlot<'a>(&'a AtomicUsize);
+
+impl Drop for ConnectionSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn handle_connection(state: &DaemonState, stream: TcpStream, wake: SocketAddr) {
+    let _slot = ConnectionSlot(&state.connections);
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let _ = stream.set_read_timeout(Some(FIRST_REQUEST_TIMEOUT));
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (response, close) = match state.handle_line(&line) {
+            Ok(response) => (response, false),
+            Err(response) => (response, true),
+        };
+        if writeln!(writer, "{}", response).and_then(|()| writer.flush()).is_err() || close {
+            return;
+        }
+        // Authenticated clients may keep the connection open while idle
+        let _ = writer.set_read_timeout(None);
+        if state.is_shutdown() {
+            // Unblock the accept loop so it sees the shutdown flag
+            let _ = TcpStream::connect(wake);
+            return;
+        }
+    }
+}
+
+/// A bound daemon, ready to [`run`](Daemon::run).
+pub struct Daemon {
+    listener: TcpListener,
+    state: Arc<DaemonState>,
+}
+
+impl Daemon {
+    /// Bind the daemon's socket, e.g. `127.0.0.1:7878` (port 0 picks a free port),
+    /// writing render outputs under `root` only.
+    ///
+    /// Addresses that don't resolve to loopback only are rejected.
+    pub fn bind(addr: &str, root: &Path) -> Result<Self, DaemonError> {
+        let bind_error = |source| DaemonError::Bind { addr: addr.to_string(), source };
+        let addrs: Vec<SocketAddr> = addr.to_socket_addrs().map_err(bind_error)?.collect();
+        if addrs.iter().any(|a| !a.ip().is_loopback()) {
+            return Err(DaemonError::NotLoopback { addr: addr.to_string() });
+        }
+        let token = random_token().map_err(DaemonError::Token)?;
+        let state = DaemonState::new(root, token)
+            .map_err(|source| DaemonError::Root { path: root.display().to_string(), source })?;
+        let listener = TcpListener::bind(&addrs[..]).map_err(bind_error)?;
+        Ok(Self { listener, state: Arc::new(state) })
+    }
+
+    /// The token clients must send with every request.
+    pub fn token(&self) -> &str {
+        &self.state.token
+    }
+
+    /// The canonical directory render outputs are confined to.
+    pub fn root(&self) -> &Path {
+        &self.state.root
+    }
+
+    /// The bound address.
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serve connections until a `shutdown` request is handled.
+    pub fn run(self) -> std::io::Result<()> {
+        let wake = self.local_addr()?;
+        for stream in self.listener.incoming() {
+            if self.state.is_shutdown() {
+                break;
+            }
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            if self.state.connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                self.state.connections.fetch_sub(1, Ordering::SeqCst);
+                let busy = json!({ "id": null, "ok": false, "error": "too many connections" });
+                let _ = writeln!(stream, "{}", busy);
+                continue;
+            }
+            let state = Arc::clone(&self.state);
+            std::thread::spawn(move || handle_connection(&state, stream, wake));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000"}}
+{"type": "sprite", "name": "red", "size": [2, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}"##;
+
+    const TOKEN: &str = "secret";
+
+    fn state(root: &Path) -> DaemonState {
+        DaemonState::new(root, TOKEN.to_string()).unwrap()
+    }
+
+    fn request(state: &DaemonState, mut request: Value) -> Value {
+        request["token"] = json!(TOKEN);
+        state.handle_line(&request.to_string()).expect("authenticated request")
+    }
+
+    #[test]
+    fn test_render_caches_until_source_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("art.pxl");
+        std::fs::write(&input, SOURCE).unwrap();
+        let output = dir.path().join("out/red.png");
+
+        let state = state(dir.path());
+        let render = json!({
+            "id": 7, "method": "render", "input": input, "sprite": "red", "scale": 2, "output": output,
+        });
+        let first = request(&state, render.clone());
+        assert_eq!(first["ok"], true, "{}", first);
+        assert_eq!(first["id"], 7);
+        assert_eq!(first["cached"], false);
+        assert_eq!(image::open(&output).unwrap().to_rgba8().dimensions(), (4, 4));
+
+        assert_eq!(request(&state, render.clone())["cached"], true);
+        assert_eq!(state.document_count(), 1);
+
+        // A new modification time drops the cached render
+        let file = std::fs::File::options().write(true).open(&input).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(request(&state, render)["cached"], false);
+    }
+
+    #[test]
+    fn test_bind_rejects_non_loopback() {
+        let root = Path::new(".");
+        let daemon = Daemon::bind("127.0.0.1:0", root).unwrap();
+        assert_eq!(daemon.token().len(), 32);
+        assert_ne!(daemon.token(), Daemon::bind("127.0.0.1:0", root).unwrap().token());
+        for addr in ["0.0.0.0:0", "[::]:0"] {
+            let err = Daemon::bind(addr, root).err().expect("non-loopback bind");
+            assert!(matches!(err, DaemonError::NotLoopback { .. }), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_responses() {
+        let state = state(Path::new("."));
+        let missing =
+            request(&state, json!({ "method": "render", "sprite": "red", "output": "a.png" }));
+        assert_eq!(missing["ok"], false);
+        assert!(missing["error"].as_str().unwrap().contains("input"));
+
+        let unknown = request(&state, json!({ "id": "x", "method": "explode" }));
+        assert_eq!(unknown["id"], "x");
+        assert_eq!(unknown["error"], "unknown method 'explode'");
+    }
+
+    #[test]
+    fn test_unauthenticated_lines_close_the_connection() {
+        let state = state(Path::new("."));
+        assert_eq!(state.handle_line("POST / HTTP/1.1").unwrap_err()["ok"], false);
+
+        let ping = json!({ "id": 1, "method": "ping" });
+        let missing = state.handle_line(&ping.to_string()).unwrap_err();
+        assert_eq!(missing["error"], "missing or invalid token");
+        let mut wrong = ping.clone();
+        wrong["token"] = json!("secreT");
+        assert!(state.handle_line(&wrong.to_string()).is_err());
+        assert_eq!(request(&state, ping)["ok"], true);
+    }
+
+    #[test]
+    fn test_outputs_stay_in_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let input = project.join("art.pxl");
+        std::fs::write(&input, SOURCE).unwrap();
+
+        let state = state(&project);
+        let render = |output: PathBuf| {
+            request(
+                &state,
+                json!({ "method": "render", "input": input, "sprite": "red", "output": output }),
+            )
+        };
+        assert_eq!(render(project.join("new/dir/red.png"))["ok"], true);
+
+        let escapes = [
+            dir.path().join("red.png"),
+            project.join("../red.png"),
+            project.join("missing/../../red.png"),
+        ];
+        for output in escapes {
+            let response = render(output.clone());
+            assert!(
+                response["error"].as_str().unwrap().contains("outside the project"),
+                "{}: {}",
+                output.display(),
+                response
+            );
+        }
+        assert!(!dir.path().join("red.png").exists());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path(), project.join("link")).unwrap();
+            assert_eq!(render(project.join("link/red.png"))["ok"], false);
+            assert!(!dir.path().join("red.png").exists());
+        }
+    }
+
+    #[test]
+    fn test_crop_is_clipped_to_the_sprite() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("art.pxl");
+        std::fs::write(&input, SOURCE).unwrap();
+        let output = dir.path().join("tile.png");
+
+        let state = state(dir.path());
+        let render = |crop: Value| {
+            request(
+                &state,
+                json!({ "method": "render", "input": input, "sprite": "red", "crop": crop, "output": output }),
+            )
+        };
+        let huge = render(json!([1, 0, 4_000_000_000u32, 4_000_000_000u32]));
+        assert_eq!(huge["ok"], true, "{}", huge);
+        assert_eq!(image::open(&output).unwrap().to_rgba8().dimensions(), (1, 2));
+
+        let outside = render(json!([2, 0, 1, 1]));
+        assert!(
+            outside["error"].as_str().unwrap().contains("outside the 2x2 image"),
+            "{}",
+            outside
+        );
+        let overflow = render(json!([1, 0, u32::MAX, 1]));
+        assert!(overflow["error"].as_str().unwrap().contains("largest coordinate"), "{}", overflow);
+    }
+
+    #[test]
+    fn test_render_cache_is_bounded() {
+        let mut cache = RenderCache::default();
+        let half = Arc::new(vec![0u8; RENDER_CACHE_BYTES / 2]);
+        cache.insert("a".to_string(), Arc::clone(&half));
+        cache.insert("b".to_string(), Arc::clone(&half));
+        cache.insert("c".to_string(), half);
+        assert!(!cache.entries.contains_key("a"));
+        assert!(cache.entries.contains_key("b") && cache.entries.contains_key("c"));
+        assert_eq!(cache.bytes, RENDER_CACHE_BYTES);
+
+        cache.insert("huge".to_string(), Arc::new(vec![0u8; RENDER_CACHE_BYTES + 1]));
+        assert!(!cache.entries.contains_key("huge"));
+    }
+
+    #[test]
+    fn test_document_cache_is_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(dir.path());
+        for i in 0..=MAX_DOCUMENTS {
+            let input = dir.path().join(format!("art{}.pxl", i));
+            std::fs::write(&input, SOURCE).unwrap();
+            state.document(&input).unwrap();
+        }
+        assert_eq!(state.document_count(), MAX_DOCUMENTS);
+        let documents = state.documents.lock().unwrap();
+        assert!(!documents.contains_key(&dir.path().join("art0.pxl")));
+    }
+
+    #[test]
+    fn test_validate_and_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("art.pxl");
+        std::fs::write(&input, SOURCE).unwrap();
+
+        let state = state(dir.path());
+        let valid = request(&state, json!({ "method": "validate", "input": input }));
+        assert_eq!(valid["valid"], true, "{}", valid);
+
+        assert!(!state.is_shutdown());
+        assert_eq!(request(&state, json!({ "method": "shutdown" }))["ok"], true);
+        assert!(state.is_shutdown());
+    }
+}
@@ -159,7 +159,7 @@ impl<'a> RegionEditor<'a> {
 
     /// Merge a shape into the token's region. Uses smart extend for compatible
     /// shapes, or union-wraps for different shapes.
-    fn merge_shape(&mut self, token: &str, new_region: RegionDef) -> Vec<String> {
+    pub fn merge_shape(&mut self, token: &str, new_region: RegionDef) -> Vec<String> {
         match self.regions.get_mut(token) {
             Some(existing) if can_extend(existing, &new_region) => {
                 extend_region(existing, &new_region);
@@ -191,6 +191,7 @@ fn is_points_only(r: &RegionDef) -> bool {
         && r.base.is_none()
         && r.subtract.is_none()
         && r.intersect.is_none()
+        && r.xor.is_none()
 }
 
 /// Check if a region has only `line` set (no other shape primitives or compounds).
@@ -208,6 +209,7 @@ fn is_line_only(r: &RegionDef) -> bool {
         && r.base.is_none()
         && r.subtract.is_none()
         && r.intersect.is_none()
+        && r.xor.is_none()
 }
 
 /// Check if we can smart-extend existing region with same-shape optimization.
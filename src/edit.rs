@@ -0,0 +1,489 @@
+//! Undoable edits on a parsed document.
+//!
+//! A [`Document`] keeps the source text alongside the objects parsed from it.
+//! [`Document::apply`] runs a batch of [`EditOp`]s as one transaction: either
+//! every op succeeds or the document is left untouched. Only the lines of the
+//! objects an edit touches are rewritten, so the rest of the file keeps its
+//! formatting and comments, and the returned [`LineChange`]s let an editor
+//! (LSP code actions, a TUI, an agent) patch its buffer instead of reloading
+//! the whole file. Every transaction can be undone and redone.
+//!
+//! ```
+//! use pixelsrc::edit::{Document, EditOp};
+//!
+//! let mut doc = Document::parse(concat!(
+//!     r##"{"type": "palette", "name": "p", "colors": {"x": "#FF0000"}}"##, "\n",
+//!     r#"{"type": "sprite", "name": "dot", "size": [2, 2], "palette": "p", "regions": {}}"#,
+//! ));
+//! let changes = doc
+//!     .apply(&[EditOp::SetPixel { sprite: "dot".into(), x: 1, y: 0, token: "x".into() }])
+//!     .unwrap();
+//! assert_eq!(changes[0].line, 1);
+//! assert!(doc.text().contains(r#""x":{"points":[[1,0]]}"#));
+//!
+//! doc.undo();
+//! assert!(doc.text().ends_with(r#""regions": {}}"#));
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::ops::Range;
+
+use thiserror::Error;
+
+use crate::draw::{DrawOp, RegionEditor};
+use crate::models::{RegionDef, TtpObject, Warning};
+use crate::parser::parse_stream;
+
+/// A single edit operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditOp {
+    /// Set one pixel of a sprite to a token (as `pxl draw --set`)
+    SetPixel { sprite: String, x: u32, y: u32, token: String },
+    /// Fill a shape in a sprite with a token, merged into the token's region
+    FillRegion { sprite: String, token: String, region: Box<RegionDef> },
+    /// Repaint everything drawn with one token using another
+    SwapPaletteToken { sprite: String, from: String, to: String },
+    /// Insert a sprite into an animation's frame list (`index: None` appends)
+    AddFrame { animation: String, sprite: String, index: Option<usize> },
+}
+
+/// Error applying an [`EditOp`]
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum EditError {
+    /// No sprite with this name
+    #[error("sprite '{0}' not found")]
+    SpriteNotFound(String),
+    /// No animation with this name
+    #[error("animation '{0}' not found")]
+    AnimationNotFound(String),
+    /// The sprite has no region for the token
+    #[error("sprite '{sprite}' has no region for token '{token}'")]
+    TokenNotFound { sprite: String, token: String },
+    /// A pixel lies outside the sprite
+    #[error("pixel ({x}, {y}) is outside sprite '{sprite}' ({width}x{height})")]
+    OutOfBounds { sprite: String, x: u32, y: u32, width: u32, height: u32 },
+    /// The animation isn't a plain frame list
+    #[error("animation '{animation}' {reason}; frames can only be added to a frame list")]
+    NotFrameList { animation: String, reason: &'static str },
+    /// The frame index is past the end of the animation
+    #[error("frame index {index} is out of range for animation '{animation}' ({len} frames)")]
+    FrameOutOfRange { animation: String, index: usize, len: usize },
+    /// An edited object could not be serialized
+    #[error("failed to serialize edited object: {0}")]
+    Serialize(String),
+}
+
+/// Replace `removed` lines starting at 0-based `line` with `inserted`.
+///
+/// Changes from one call are ordered bottom-up, so applying them in order
+/// never shifts the line numbers of the changes still to come.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineChange {
+    /// First replaced line (0-based)
+    pub line: usize,
+    /// Number of lines removed
+    pub removed: usize,
+    /// Lines inserted in their place
+    pub inserted: Vec<String>,
+}
+
+/// A parsed object and the 0-based source lines it occupies
+#[derive(Debug, Clone)]
+struct Entry {
+    object: TtpObject,
+    lines: Range<usize>,
+}
+
+/// One object's text and value before and after a transaction
+#[derive(Debug, Clone)]
+struct ObjectChange {
+    index: usize,
+    before: (TtpObject, Vec<String>),
+    after: (TtpObject, Vec<String>),
+}
+
+/// A document with its source text, parsed objects and edit history.
+#[derive(Debug, Clone)]
+pub struct Document {
+    lines: Vec<String>,
+    trailing_newline: bool,
+    entries: Vec<Entry>,
+    warnings: Vec<Warning>,
+    undo_stack: Vec<Vec<ObjectChange>>,
+    redo_stack: Vec<Vec<ObjectChange>>,
+}
+
+impl Document {
+    /// Parse a document. Malformed objects are reported in [`Document::warnings`].
+    pub fn parse(source: &str) -> Self {
+        let parsed = parse_stream(Cursor::new(source));
+        let entries = parsed
+            .objects
+            .into_iter()
+            .zip(parsed.spans)
+            .map(|(object, span)| Entry { object, lines: span.start - 1..span.end - 1 })
+            .collect();
+        Self {
+            lines: source.lines().map(str::to_string).collect(),
+            trailing_newline: source.ends_with('\n'),
+            entries,
+            warnings: parsed.warnings,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// The current source text.
+    pub fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        if self.trailing_newline {
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The parsed objects, in source order.
+    pub fn objects(&self) -> impl Iterator<Item = &TtpObject> {
+        self.entries.iter().map(|e| &e.object)
+    }
+
+    /// Warnings from parsing the original source.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Whether there is a transaction to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Whether there is an undone transaction to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Apply `ops` as one transaction, returning the line changes it made.
+    ///
+    /// If any op fails the document is unchanged. A transaction that changes
+    /// nothing returns no line changes and is not recorded for undo.
+    pub fn apply(&mut self, ops: &[EditOp]) -> Result<Vec<LineChange>, EditError> {
+        let mut edited: BTreeMap<usize, TtpObject> = BTreeMap::new();
+        for op in ops {
+            let index = self.target(op)?;
+            let object = edited.entry(index).or_insert_with(|| self.entries[index].object.clone());
+            apply_op(object, op)?;
+        }
+
+        let mut transaction = Vec::new();
+        for (index, object) in edited {
+            let entry = &self.entries[index];
+            if object == entry.object {
+                continue;
+            }
+            let text =
+                serde_json::to_string(&object).map_err(|e| EditError::Serialize(e.to_string()))?;
+            transaction.push(ObjectChange {
+                index,
+                before: (entry.object.clone(), self.lines[entry.lines.clone()].to_vec()),
+                after: (object, vec![text]),
+            });
+        }
+        if transaction.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let changes = self.replay(&transaction, false);
+        self.undo_stack.push(transaction);
+        self.redo_stack.clear();
+        Ok(changes)
+    }
+
+    /// Undo the last transaction, returning its line changes, or `None` if there is none.
+    pub fn undo(&mut self) -> Option<Vec<LineChange>> {
+        let transaction = self.undo_stack.pop()?;
+        let changes = self.replay(&transaction, true);
+        self.redo_stack.push(transaction);
+        Some(changes)
+    }
+
+    /// Redo the last undone transaction, returning its line changes, or `None` if there is none.
+    pub fn redo(&mut self) -> Option<Vec<LineChange>> {
+        let transaction = self.redo_stack.pop()?;
+        let changes = self.replay(&transaction, false);
+        self.undo_stack.push(transaction);
+        Some(changes)
+    }
+
+    /// Write one side of a transaction into the document, bottom-up.
+    fn replay(&mut self, transaction: &[ObjectChange], undo: bool) -> Vec<LineChange> {
+        let mut changes = Vec::with_capacity(transaction.len());
+        for change in transaction.iter().rev() {
+            let (object, text) = if undo { &change.before } else { &change.after };
+            let span = self.entries[change.index].lines.clone();
+            self.lines.splice(span.clone(), text.iter().cloned());
+
+            let delta = text.len() as isize - span.len() as isize;
+            for entry in &mut self.entries[change.index + 1..] {
+                entry.lines = shift(&entry.lines, delta);
+            }
+            let entry = &mut self.entries[change.index];
+            entry.object = object.clone();
+            entry.lines = span.start..span.start + text.len();
+
+            changes.push(LineChange {
+                line: span.start,
+                removed: span.len(),
+                inserted: text.clone(),
+            });
+        }
+        changes
+    }
+
+    /// Index of the object an op edits (the last definition wins, as in rendering).
+    fn target(&self, op: &EditOp) -> Result<usize, EditError> {
+        match op {
+            EditOp::SetPixel { sprite, .. }
+            | EditOp::FillRegion { sprite, .. }
+            | EditOp::SwapPaletteToken { sprite, .. } => self
+                .entries
+                .iter()
+                .rposition(|e| matches!(&e.object, TtpObject::Sprite(s) if &s.name == sprite))
+                .ok_or_else(|| EditError::SpriteNotFound(sprite.clone())),
+            EditOp::AddFrame { animation, .. } => self
+                .entries
+                .iter()
+                .rposition(|e| matches!(&e.object, TtpObject::Animation(a) if &a.name == animation))
+                .ok_or_else(|| EditError::AnimationNotFound(animation.clone())),
+        }
+    }
+}
+
+fn shift(lines: &Range<usize>, delta: isize) -> Range<usize> {
+    (lines.start as isize + delta) as usize..(lines.end as isize + delta) as usize
+}
+
+/// Apply one op to the object [`Document::target`] picked for it.
+fn apply_op(object: &mut TtpObject, op: &EditOp) -> Result<(), EditError> {
+    match (object, op) {
+        (TtpObject::Sprite(sprite), EditOp::SetPixel { x, y, token, .. }) => {
+            if let Some([width, height]) = sprite.size {
+                if *x >= width || *y >= height {
+                    return Err(EditError::OutOfBounds {
+                        sprite: sprite.name.clone(),
+                        x: *x,
+                        y: *y,
+                        width,
+                        height,
+                    });
+                }
+            }
+            let regions = sprite.regions.get_or_insert_with(Default::default);
+            RegionEditor::new(regions).apply(&DrawOp::Set { x: *x, y: *y, token: token.clone() });
+        }
+        (TtpObject::Sprite(sprite), EditOp::FillRegion { token, region, .. }) => {
+            let regions = sprite.regions.get_or_insert_with(Default::default);
+            RegionEditor::new(regions).merge_shape(token, (**region).clone());
+        }
+        (TtpObject::Sprite(sprite), EditOp::SwapPaletteToken { from, to, .. }) => {
+            let region = sprite.regions.as_mut().and_then(|r| r.remove(from)).ok_or_else(|| {
+                EditError::TokenNotFound { sprite: sprite.name.clone(), token: from.clone() }
+            })?;
+            let regions = sprite.regions.get_or_insert_with(Default::default);
+            RegionEditor::new(regions).merge_shape(to, region);
+        }
+        (TtpObject::Animation(anim), EditOp::AddFrame { sprite, index, .. }) => {
+            let reason = if anim.keyframes.is_some() {
+                Some("uses keyframes")
+            } else if anim.source.is_some() {
+                Some("references another animation")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Err(EditError::NotFrameList { animation: anim.name.clone(), reason });
+            }
+
+            let len = anim.frames.len();
+            let at = index.unwrap_or(len);
+            if at > len {
+                return Err(EditError::FrameOutOfRange {
+                    animation: anim.name.clone(),
+                    index: at,
+                    len,
+                });
+            }
+            anim.frames.insert(at, sprite.clone());
+
+            // Keep per-frame data aligned with the frames
+            if let Some(durations) = anim.durations.as_mut().filter(|d| !d.is_empty()) {
+                let neighbor = durations[at.saturating_sub(1).min(durations.len() - 1)].clone();
+                durations.insert(at.min(durations.len()), neighbor);
+            }
+            if let Some(metadata) = anim.frame_metadata.as_mut().filter(|m| at <= m.len()) {
+                metadata.insert(at, Default::default());
+            }
+            for tag in anim.tags.iter_mut().flat_map(|t| t.values_mut()) {
+                if tag.start as usize >= at {
+                    tag.start += 1;
+                }
+                if tag.end as usize >= at {
+                    tag.end += 1;
+                }
+            }
+        }
+        _ => unreachable!("Document::target picks an object of the op's kind"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "x": "#FF0000", "y": "#00FF00"}}
+// The hero sprite
+{
+  "type": "sprite",
+  "name": "dot",
+  "size": [4, 4],
+  "palette": "p",
+  "regions": {"x": {"rect": [0, 0, 2, 2]}}
+}
+
+{"type": "animation", "name": "blink", "frames": ["dot", "dot"], "durations": [100, 200], "tags": {"end": {"start": 1, "end": 1}}}
+"##;
+
+    fn set(x: u32, y: u32, token: &str) -> EditOp {
+        EditOp::SetPixel { sprite: "dot".into(), x, y, token: token.into() }
+    }
+
+    /// Apply line changes to a copy of the original lines
+    fn patch(text: &str, changes: &[LineChange]) -> String {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        for change in changes {
+            lines
+                .splice(change.line..change.line + change.removed, change.inserted.iter().cloned());
+        }
+        lines.join("\n") + "\n"
+    }
+
+    #[test]
+    fn test_edit_rewrites_only_touched_object() {
+        let mut doc = Document::parse(SOURCE);
+        let changes = doc.apply(&[set(3, 3, "y"), set(0, 3, "y")]).unwrap();
+
+        // The multi-line sprite collapses to one line; nothing else moves
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].line, changes[0].removed), (2, 7));
+        assert!(changes[0].inserted[0].contains(r#""y":{"points":[[3,3],[0,3]]}"#));
+        assert_eq!(patch(SOURCE, &changes), doc.text());
+
+        let text = doc.text();
+        let lines: Vec<&str> = text.lines().collect();
+        let original: Vec<&str> = SOURCE.lines().collect();
+        assert_eq!(lines[..2], original[..2]);
+        assert_eq!(lines[3..], original[9..]);
+    }
+
+    #[test]
+    fn test_undo_and_redo_restore_text() {
+        let mut doc = Document::parse(SOURCE);
+        let edit = doc
+            .apply(&[
+                EditOp::AddFrame { animation: "blink".into(), sprite: "dot".into(), index: None },
+                set(1, 1, "y"),
+            ])
+            .unwrap();
+        let edited = doc.text();
+        // Bottom-up: the animation comes before the sprite above it
+        assert_eq!(edit.iter().map(|c| c.line).collect::<Vec<_>>(), [10, 2]);
+
+        let undo = doc.undo().unwrap();
+        assert_eq!(doc.text(), SOURCE);
+        assert_eq!(patch(&edited, &undo), SOURCE);
+        assert!(!doc.can_undo());
+
+        doc.redo().unwrap();
+        assert_eq!(doc.text(), edited);
+        assert!(doc.redo().is_none());
+    }
+
+    #[test]
+    fn test_failed_transaction_leaves_document_unchanged() {
+        let mut doc = Document::parse(SOURCE);
+        let err = doc.apply(&[set(0, 0, "y"), set(4, 0, "y")]).unwrap_err();
+        assert!(matches!(err, EditError::OutOfBounds { x: 4, .. }));
+        assert_eq!(doc.text(), SOURCE);
+        assert!(!doc.can_undo());
+
+        let missing = EditOp::SetPixel { sprite: "nope".into(), x: 0, y: 0, token: "y".into() };
+        assert_eq!(doc.apply(&[missing]).unwrap_err(), EditError::SpriteNotFound("nope".into()));
+    }
+
+    #[test]
+    fn test_swap_palette_token_and_fill_region() {
+        let mut doc = Document::parse(SOURCE);
+        doc.apply(&[
+            EditOp::FillRegion {
+                sprite: "dot".into(),
+                token: "y".into(),
+                region: Box::new(RegionDef { rect: Some([2, 2, 2, 2]), ..Default::default() }),
+            },
+            EditOp::SwapPaletteToken { sprite: "dot".into(), from: "x".into(), to: "y".into() },
+        ])
+        .unwrap();
+
+        let Some(TtpObject::Sprite(sprite)) = doc.objects().nth(1) else {
+            panic!("expected sprite")
+        };
+        let regions = sprite.regions.as_ref().unwrap();
+        assert!(!regions.contains_key("x"));
+        assert_eq!(regions["y"].union.as_ref().map(Vec::len), Some(2));
+
+        let err = doc.apply(&[EditOp::SwapPaletteToken {
+            sprite: "dot".into(),
+            from: "x".into(),
+            to: "y".into(),
+        }]);
+        assert!(matches!(err, Err(EditError::TokenNotFound { .. })));
+    }
+
+    #[test]
+    fn test_add_frame_keeps_frame_data_aligned() {
+        let mut doc = Document::parse(SOURCE);
+        doc.apply(&[EditOp::AddFrame {
+            animation: "blink".into(),
+            sprite: "dot".into(),
+            index: Some(1),
+        }])
+        .unwrap();
+
+        let Some(TtpObject::Animation(anim)) = doc.objects().nth(2) else {
+            panic!("expected animation")
+        };
+        assert_eq!(anim.frames.len(), 3);
+        assert_eq!(anim.frame_durations().unwrap(), [100, 100, 200]);
+        let tag = &anim.tags.as_ref().unwrap()["end"];
+        assert_eq!((tag.start, tag.end), (2, 2));
+
+        let err = doc.apply(&[EditOp::AddFrame {
+            animation: "blink".into(),
+            sprite: "dot".into(),
+            index: Some(9),
+        }]);
+        assert!(matches!(err, Err(EditError::FrameOutOfRange { len: 3, .. })));
+    }
+
+    #[test]
+    fn test_no_op_is_not_recorded() {
+        let mut doc = Document::parse(SOURCE);
+        let swap =
+            EditOp::SwapPaletteToken { sprite: "dot".into(), from: "x".into(), to: "x".into() };
+        assert!(doc.apply(&[swap]).unwrap().is_empty());
+        assert!(doc.apply(&[]).unwrap().is_empty());
+        assert_eq!(doc.text(), SOURCE);
+        assert!(!doc.can_undo());
+    }
+}
@@ -1,9 +1,14 @@
-//! Emoji art output for terminal preview
+//! Emoji art output for terminal preview and chat
 //!
 //! Maps colors to emoji based on hue/saturation/lightness for quick
-//! terminal-based visualization of sprites.
+//! terminal-based visualization of sprites, and sizes sprites as custom
+//! emoji images for Slack and Discord.
 
-use image::Rgba;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+
+/// Width and height of a custom emoji image (Slack and Discord recommend 128x128)
+pub const CUSTOM_EMOJI_SIZE: u32 = 128;
 
 /// Available emoji for color mapping
 const BLACK: &str = "⬛";
@@ -144,7 +149,7 @@ fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
 /// Render an RGBA image to emoji art string.
 ///
 /// Each pixel becomes one emoji character. Rows are separated by newlines.
-pub fn render_emoji_art(image: &image::RgbaImage) -> String {
+pub fn render_emoji_art(image: &RgbaImage) -> String {
     let mut output = String::new();
 
     for y in 0..image.height() {
@@ -158,6 +163,37 @@ pub fn render_emoji_art(image: &image::RgbaImage) -> String {
     output
 }
 
+/// Fit an image into a square custom emoji with transparent padding.
+///
+/// The sprite is scaled by the largest whole factor that fits inside
+/// `CUSTOM_EMOJI_SIZE - 2 * padding` so pixels stay square, then centered.
+/// Sprites larger than that area are shrunk to fit instead.
+pub fn render_custom_emoji(image: &RgbaImage, padding: u32) -> RgbaImage {
+    let mut canvas = RgbaImage::new(CUSTOM_EMOJI_SIZE, CUSTOM_EMOJI_SIZE);
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return canvas;
+    }
+
+    let inner = CUSTOM_EMOJI_SIZE.saturating_sub(padding * 2).max(1);
+    let factor = inner / width.max(height);
+    let (scaled_w, scaled_h) = if factor >= 1 {
+        (width * factor, height * factor)
+    } else {
+        let ratio = inner as f64 / width.max(height) as f64;
+        (
+            ((width as f64 * ratio).round() as u32).max(1),
+            ((height as f64 * ratio).round() as u32).max(1),
+        )
+    };
+
+    let scaled = imageops::resize(image, scaled_w, scaled_h, FilterType::Nearest);
+    let x = (CUSTOM_EMOJI_SIZE - scaled_w) / 2;
+    let y = (CUSTOM_EMOJI_SIZE - scaled_h) / 2;
+    imageops::replace(&mut canvas, &scaled, x as i64, y as i64);
+    canvas
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,6 +295,31 @@ mod tests {
         assert_eq!(output, "🟥🟩🟦\n⬛⬜⬜\n");
     }
 
+    #[test]
+    fn test_render_custom_emoji_integer_scale() {
+        // 16x8 with 8px padding: 112px inner area fits a 7x scale
+        let image = RgbaImage::from_pixel(16, 8, Rgba([255, 0, 0, 255]));
+        let emoji = render_custom_emoji(&image, 8);
+
+        assert_eq!(emoji.dimensions(), (CUSTOM_EMOJI_SIZE, CUSTOM_EMOJI_SIZE));
+        // 112x56 centered: x 8..120, y 36..92
+        assert_eq!(emoji.get_pixel(8, 36)[3], 255);
+        assert_eq!(emoji.get_pixel(119, 91)[3], 255);
+        assert_eq!(emoji.get_pixel(7, 36)[3], 0);
+        assert_eq!(emoji.get_pixel(8, 35)[3], 0);
+        assert_eq!(emoji.get_pixel(8, 92)[3], 0);
+    }
+
+    #[test]
+    fn test_render_custom_emoji_shrinks_large_sprites() {
+        let image = RgbaImage::from_pixel(200, 100, Rgba([0, 0, 255, 255]));
+        let emoji = render_custom_emoji(&image, 4);
+
+        assert_eq!(emoji.dimensions(), (CUSTOM_EMOJI_SIZE, CUSTOM_EMOJI_SIZE));
+        assert_eq!(emoji.get_pixel(4, 64)[3], 255);
+        assert_eq!(emoji.get_pixel(3, 64)[3], 0);
+    }
+
     #[test]
     fn test_hsl_conversion() {
         // Test RGB to HSL conversion
@@ -3,12 +3,24 @@
 //! Provides explanations of sprite structure, tokens, colors, and patterns
 //! for AI assistants and human users to understand sprite definitions.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use crate::analyze::{
+    detect_shape, detect_symmetry, DetectedShape, RoleInferenceContext, RoleInferrer, Symmetric,
+};
 use crate::color::parse_color;
-use crate::models::{Animation, Composition, PaletteRef, Particle, Sprite, TtpObject, Variant};
+use crate::models::{
+    Animation, Composition, PaletteRef, Particle, RegionDef, Sequence, Sprite, TransformSpec,
+    TtpObject, Variant,
+};
 use crate::palettes;
+use crate::registry::{PaletteRegistry, PaletteSource, ResolvedSprite, SpriteRegistry};
+use crate::renderer::{render_resolved, render_sprite};
 use crate::state::StateRules;
+use crate::structured::{token_grid, BASE_LAYER};
+use crate::transforms::{
+    apply_image_transform, is_animation_transform, parse_transform_spec, source_pixel,
+};
 
 /// Token usage statistics within a sprite
 #[derive(Debug, Clone)]
@@ -153,6 +165,17 @@ pub struct StateRulesExplanation {
     pub selectors: Vec<String>,
 }
 
+/// Explanation of a sequence's steps
+#[derive(Debug)]
+pub struct SequenceExplanation {
+    /// Sequence name
+    pub name: String,
+    /// Steps as (animation, repeat count, crossfade in ms)
+    pub steps: Vec<(String, u32, Option<u32>)>,
+    /// Whether it loops
+    pub loops: bool,
+}
+
 /// Explanation for import declarations
 #[derive(Debug)]
 pub struct ImportExplanation {
@@ -168,6 +191,41 @@ pub struct ImportExplanation {
     pub imported_types: Vec<String>,
 }
 
+/// Explanation of a theme's palette swaps
+#[derive(Debug)]
+pub struct ThemeExplanation {
+    /// Theme name
+    pub name: String,
+    /// Palette swaps as (palette, replacement), sorted by palette
+    pub swaps: Vec<(String, String)>,
+}
+
+/// Explanation of a stamp pattern
+#[derive(Debug)]
+pub struct StampExplanation {
+    /// Stamp name
+    pub name: String,
+    /// Grid width
+    pub width: u32,
+    /// Grid height
+    pub height: u32,
+    /// Filled pixels
+    pub pixel_count: usize,
+    /// Palette tokens the stamp draws with, sorted
+    pub tokens: Vec<String>,
+}
+
+/// Explanation of a random set's alternatives
+#[derive(Debug)]
+pub struct RandomSetExplanation {
+    /// Set name
+    pub name: String,
+    /// Choices as (name, share of the total weight)
+    pub choices: Vec<(String, f64)>,
+    /// Tokens with region alternatives as (token, alternatives), sorted by token
+    pub tokens: Vec<(String, usize)>,
+}
+
 /// Unified explanation for any pixelsrc object
 #[derive(Debug)]
 pub enum Explanation {
@@ -175,11 +233,15 @@ pub enum Explanation {
     Palette(PaletteExplanation),
     Transform(TransformExplanation),
     Animation(AnimationExplanation),
+    Sequence(SequenceExplanation),
     Composition(CompositionExplanation),
     Variant(VariantExplanation),
     Particle(ParticleExplanation),
     StateRules(StateRulesExplanation),
     Import(ImportExplanation),
+    Theme(ThemeExplanation),
+    Stamp(StampExplanation),
+    RandomSet(RandomSetExplanation),
 }
 
 /// Analyze a sprite and produce an explanation
@@ -207,7 +269,7 @@ pub fn explain_sprite(
         transparent_count: 0,
         transparency_ratio: 0.0,
         consistent_rows: true,
-        issues: if sprite.regions.is_none() {
+        issues: if sprite.regions.is_none() && sprite.layers.is_none() {
             vec!["Sprite has no regions defined - use structured regions format".to_string()]
         } else {
             Vec::new()
@@ -247,6 +309,25 @@ pub fn explain_animation(animation: &Animation) -> AnimationExplanation {
     }
 }
 
+/// Explain a sequence
+pub fn explain_sequence(sequence: &Sequence) -> SequenceExplanation {
+    SequenceExplanation {
+        name: sequence.name.clone(),
+        steps: sequence
+            .steps
+            .iter()
+            .map(|step| {
+                (
+                    step.animation.clone(),
+                    step.repeat_count(),
+                    step.crossfade.as_ref().and_then(|d| d.as_milliseconds()),
+                )
+            })
+            .collect(),
+        loops: sequence.loops(),
+    }
+}
+
 /// Explain a composition
 pub fn explain_composition(composition: &Composition) -> CompositionExplanation {
     CompositionExplanation {
@@ -317,6 +398,38 @@ pub fn explain_state_rules(state_rules: &StateRules) -> StateRulesExplanation {
     }
 }
 
+/// Explain a theme
+pub fn explain_theme(theme: &crate::models::Theme) -> ThemeExplanation {
+    let mut swaps: Vec<(String, String)> =
+        theme.palettes.iter().map(|(from, to)| (from.clone(), to.clone())).collect();
+    swaps.sort();
+    ThemeExplanation { name: theme.name.clone(), swaps }
+}
+
+/// Explain a random set
+pub fn explain_random_set(set: &crate::models::RandomSet) -> RandomSetExplanation {
+    let total: f64 = set.choices.iter().map(|c| c.weight()).sum();
+    let choices = set
+        .choices
+        .iter()
+        .map(|c| (c.name().to_string(), if total > 0.0 { c.weight() / total } else { 0.0 }))
+        .collect();
+    let mut tokens: Vec<(String, usize)> =
+        set.regions.iter().map(|(token, alts)| (token.clone(), alts.len())).collect();
+    tokens.sort();
+    RandomSetExplanation { name: set.name.clone(), choices, tokens }
+}
+
+/// Explain a stamp
+pub fn explain_stamp(stamp: &crate::models::Stamp) -> StampExplanation {
+    let [width, height] = stamp.size();
+    let pixels = stamp.pixels();
+    let mut tokens: Vec<String> = pixels.iter().map(|(_, _, token)| token.to_string()).collect();
+    tokens.sort();
+    tokens.dedup();
+    StampExplanation { name: stamp.name.clone(), width, height, pixel_count: pixels.len(), tokens }
+}
+
 /// Explain an import declaration
 pub fn explain_import(import: &crate::models::Import) -> ImportExplanation {
     let mut imported_types = Vec::new();
@@ -356,6 +469,7 @@ pub fn explain_object(
             Explanation::Palette(explain_palette(&palette.name, &palette.colors))
         }
         TtpObject::Animation(anim) => Explanation::Animation(explain_animation(anim)),
+        TtpObject::Sequence(seq) => Explanation::Sequence(explain_sequence(seq)),
         TtpObject::Composition(comp) => Explanation::Composition(explain_composition(comp)),
         TtpObject::Variant(variant) => Explanation::Variant(explain_variant(variant)),
         TtpObject::Particle(particle) => Explanation::Particle(explain_particle(particle)),
@@ -364,6 +478,9 @@ pub fn explain_object(
             Explanation::StateRules(explain_state_rules(state_rules))
         }
         TtpObject::Import(import) => Explanation::Import(explain_import(import)),
+        TtpObject::Theme(theme) => Explanation::Theme(explain_theme(theme)),
+        TtpObject::Stamp(stamp) => Explanation::Stamp(explain_stamp(stamp)),
+        TtpObject::RandomSet(set) => Explanation::RandomSet(explain_random_set(set)),
     }
 }
 
@@ -556,6 +673,31 @@ pub fn format_animation_explanation(exp: &AnimationExplanation) -> String {
     output
 }
 
+/// Format a sequence explanation as human-readable text
+pub fn format_sequence_explanation(exp: &SequenceExplanation) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("Sequence: {}\n", exp.name));
+    output.push_str(&format!("Steps: {}\n", exp.steps.len()));
+    output.push_str(&format!("Loops: {}\n", if exp.loops { "yes" } else { "no" }));
+    output.push('\n');
+
+    output.push_str("STEPS\n");
+    output.push_str("-----\n");
+    for (i, (animation, repeat, crossfade)) in exp.steps.iter().enumerate() {
+        output.push_str(&format!("  {}: {}", i + 1, animation));
+        if *repeat != 1 {
+            output.push_str(&format!(" x{}", repeat));
+        }
+        if let Some(ms) = crossfade {
+            output.push_str(&format!(" (crossfade {}ms)", ms));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
 /// Format a composition explanation as human-readable text
 pub fn format_composition_explanation(exp: &CompositionExplanation) -> String {
     let mut output = String::new();
@@ -601,12 +743,16 @@ pub fn format_explanation(exp: &Explanation) -> String {
         Explanation::Sprite(s) => format_sprite_explanation(s),
         Explanation::Palette(p) => format_palette_explanation(p),
         Explanation::Animation(a) => format_animation_explanation(a),
+        Explanation::Sequence(s) => format_sequence_explanation(s),
         Explanation::Composition(c) => format_composition_explanation(c),
         Explanation::Variant(v) => format_variant_explanation(v),
         Explanation::Particle(p) => format_particle_explanation(p),
         Explanation::Transform(t) => format_transform_explanation(t),
         Explanation::StateRules(sr) => format_state_rules_explanation(sr),
         Explanation::Import(i) => format_import_explanation(i),
+        Explanation::Theme(t) => format_theme_explanation(t),
+        Explanation::Stamp(s) => format_stamp_explanation(s),
+        Explanation::RandomSet(r) => format_random_set_explanation(r),
     }
 }
 
@@ -638,6 +784,38 @@ fn format_import_explanation(i: &ImportExplanation) -> String {
     lines.join("\n")
 }
 
+/// Format a theme explanation as human-readable text
+fn format_theme_explanation(t: &ThemeExplanation) -> String {
+    let mut lines = vec![format!("Theme: {}", t.name)];
+    lines.push(format!("  Palette swaps: {}", t.swaps.len()));
+    for (from, to) in &t.swaps {
+        lines.push(format!("    {} → {}", from, to));
+    }
+    lines.join("\n")
+}
+
+/// Format a stamp explanation as human-readable text
+fn format_stamp_explanation(s: &StampExplanation) -> String {
+    let mut lines = vec![format!("Stamp: {}", s.name)];
+    lines.push(format!("  Size: {}x{}", s.width, s.height));
+    lines.push(format!("  Pixels: {}", s.pixel_count));
+    lines.push(format!("  Tokens: {}", s.tokens.join(", ")));
+    lines.join("\n")
+}
+
+/// Format a random set explanation as human-readable text
+fn format_random_set_explanation(r: &RandomSetExplanation) -> String {
+    let mut lines = vec![format!("Random Set: {}", r.name)];
+    lines.push(format!("  Choices: {}", r.choices.len()));
+    for (name, share) in &r.choices {
+        lines.push(format!("    {} ({:.0}%)", name, share * 100.0));
+    }
+    for (token, alternatives) in &r.tokens {
+        lines.push(format!("  Region {}: {} alternatives", token, alternatives));
+    }
+    lines.join("\n")
+}
+
 /// Format a transform explanation as human-readable text
 fn format_transform_explanation(t: &TransformExplanation) -> String {
     let mut lines = vec![format!("Transform: {} ({})", t.name, t.transform_type)];
@@ -667,6 +845,256 @@ fn format_particle_explanation(p: &ParticleExplanation) -> String {
     lines.join("\n")
 }
 
+/// One visible color region of a rendered sprite
+#[derive(Debug, Clone)]
+pub struct ColorRegionDescription {
+    /// Tokens that render to this color (sorted)
+    pub tokens: Vec<String>,
+    /// Hex color (#RRGGBBAA)
+    pub color: String,
+    /// Human-readable color name
+    pub color_name: Option<String>,
+    /// Semantic role: declared on the region, or inferred from its pixels
+    pub role: Option<String>,
+    /// Whether `role` was inferred rather than declared
+    pub role_inferred: bool,
+    /// Number of pixels with this color
+    pub pixels: usize,
+    /// Percentage of visible pixels
+    pub percentage: f64,
+    /// Bounding box: [x, y, width, height]
+    pub bounds: [i32; 4],
+    /// Detected shape (rect, stroke, ellipse, line, polygon)
+    pub shape: String,
+    /// Detection confidence for `shape`
+    pub shape_confidence: f64,
+    /// Coarse position of the region's center (e.g. "top-left", "center")
+    pub position: String,
+}
+
+/// Structured description of a rendered sprite, for readers who can't see it
+#[derive(Debug, Clone)]
+pub struct SpriteDescription {
+    /// Sprite name
+    pub name: String,
+    /// Rendered width in pixels
+    pub width: u32,
+    /// Rendered height in pixels
+    pub height: u32,
+    /// Number of non-transparent pixels
+    pub visible_pixels: usize,
+    /// Percentage of the canvas covered by visible pixels
+    pub coverage: f64,
+    /// Bounding box of all visible pixels: [x, y, width, height]
+    pub content_bounds: Option<[i32; 4]>,
+    /// Mirror symmetry of the rendered image ("left-right", "top-bottom", "both")
+    pub symmetry: Option<String>,
+    /// Color regions, largest first
+    pub regions: Vec<ColorRegionDescription>,
+}
+
+fn bounds_of(pixels: &HashSet<(i32, i32)>) -> Option<[i32; 4]> {
+    let min_x = pixels.iter().map(|p| p.0).min()?;
+    let max_x = pixels.iter().map(|p| p.0).max()?;
+    let min_y = pixels.iter().map(|p| p.1).min()?;
+    let max_y = pixels.iter().map(|p| p.1).max()?;
+    Some([min_x, min_y, max_x - min_x + 1, max_y - min_y + 1])
+}
+
+/// Name the third of the canvas a bounding box's center falls into.
+fn position_label(bounds: [i32; 4], width: u32, height: u32) -> String {
+    let third = |center: f64, extent: u32| {
+        let t = center / extent.max(1) as f64;
+        if t < 1.0 / 3.0 {
+            0
+        } else if t < 2.0 / 3.0 {
+            1
+        } else {
+            2
+        }
+    };
+    let cx = bounds[0] as f64 + bounds[2] as f64 / 2.0;
+    let cy = bounds[1] as f64 + bounds[3] as f64 / 2.0;
+    let vertical = ["top", "middle", "bottom"][third(cy, height)];
+    let horizontal = ["left", "center", "right"][third(cx, width)];
+    match (vertical, horizontal) {
+        ("middle", "center") => "center".to_string(),
+        ("middle", h) => h.to_string(),
+        (v, "center") => v.to_string(),
+        (v, h) => format!("{}-{}", v, h),
+    }
+}
+
+fn shape_name(shape: &DetectedShape) -> &'static str {
+    match shape {
+        DetectedShape::Rect(_) => "rect",
+        DetectedShape::Stroke(_) => "stroke",
+        DetectedShape::Ellipse(_) => "ellipse",
+        DetectedShape::Line(_) => "line",
+        DetectedShape::Polygon(_) => "polygon",
+    }
+}
+
+/// Render a sprite and describe what it looks like.
+///
+/// Groups visible pixels by color, maps each color back to the tokens that
+/// produce it, and reports shape, layout, and role for each group along with
+/// overall coverage and symmetry.
+pub fn describe_sprite(
+    sprite: &Sprite,
+    palette_colors: &HashMap<String, String>,
+) -> SpriteDescription {
+    let (image, _) = render_sprite(sprite, palette_colors);
+    let (width, height) = image.dimensions();
+
+    let mut by_color: BTreeMap<[u8; 4], HashSet<(i32, i32)>> = BTreeMap::new();
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] > 0 {
+            by_color.entry(pixel.0).or_default().insert((x as i32, y as i32));
+        }
+    }
+    let visible_pixels: usize = by_color.values().map(|p| p.len()).sum();
+    let all_pixels: HashSet<(i32, i32)> = by_color.values().flatten().copied().collect();
+
+    let mut tokens_by_color: HashMap<[u8; 4], Vec<String>> = HashMap::new();
+    for (token, hex) in palette_colors {
+        if let Ok(rgba) = parse_color(hex) {
+            tokens_by_color
+                .entry([rgba[0], rgba[1], rgba[2], rgba[3]])
+                .or_default()
+                .push(token.clone());
+        }
+    }
+
+    let declared_role = |tokens: &[String]| {
+        let regions = sprite.regions.as_ref()?;
+        tokens.iter().find_map(|t| regions.get(t).and_then(|r| r.role.as_ref()))
+    };
+
+    let ctx = RoleInferenceContext::new(width, height);
+    let colors: Vec<[u8; 4]> = by_color.keys().copied().collect();
+    let mut regions: Vec<ColorRegionDescription> = by_color
+        .iter()
+        .map(|(rgba, pixels)| {
+            let mut tokens = tokens_by_color.get(rgba).cloned().unwrap_or_default();
+            tokens.sort();
+            let bounds = bounds_of(pixels).unwrap_or_default();
+            let (shape, shape_confidence) = detect_shape(pixels);
+            let (role, role_inferred) = match declared_role(&tokens) {
+                Some(role) => (Some(role.to_string()), false),
+                None => {
+                    let others: Vec<[u8; 4]> =
+                        colors.iter().filter(|c| *c != rgba).copied().collect();
+                    let inferred = RoleInferrer::infer_role(pixels, &ctx, Some(*rgba), &others);
+                    (inferred.map(|r| r.role.to_string()), true)
+                }
+            };
+            let color = format!("#{:02X}{:02X}{:02X}{:02X}", rgba[0], rgba[1], rgba[2], rgba[3]);
+            ColorRegionDescription {
+                tokens,
+                color_name: describe_color(&color),
+                color,
+                role,
+                role_inferred,
+                pixels: pixels.len(),
+                percentage: pixels.len() as f64 * 100.0 / visible_pixels.max(1) as f64,
+                bounds,
+                shape: shape_name(&shape).to_string(),
+                shape_confidence,
+                position: position_label(bounds, width, height),
+            }
+        })
+        .collect();
+    regions.sort_by(|a, b| b.pixels.cmp(&a.pixels).then_with(|| a.color.cmp(&b.color)));
+
+    let symmetry = if visible_pixels == 0 {
+        None
+    } else {
+        detect_symmetry(image.as_raw(), width, height).map(|s| {
+            match s {
+                Symmetric::X => "left-right",
+                Symmetric::Y => "top-bottom",
+                Symmetric::XY => "both",
+            }
+            .to_string()
+        })
+    };
+
+    SpriteDescription {
+        name: sprite.name.clone(),
+        width,
+        height,
+        visible_pixels,
+        coverage: visible_pixels as f64 * 100.0 / (width as usize * height as usize).max(1) as f64,
+        content_bounds: bounds_of(&all_pixels),
+        symmetry,
+        regions,
+    }
+}
+
+/// Format a sprite description as prose followed by a region table
+pub fn format_sprite_description(desc: &SpriteDescription) -> String {
+    let mut output = String::new();
+
+    let symmetry = match desc.symmetry.as_deref() {
+        Some("both") => ", symmetric on both axes".to_string(),
+        Some(axis) => format!(", {} symmetric", axis),
+        None => String::new(),
+    };
+    output.push_str(&format!(
+        "'{}' is a {}x{} sprite using {} color{}, covering {:.0}% of the canvas{}.\n",
+        desc.name,
+        desc.width,
+        desc.height,
+        desc.regions.len(),
+        if desc.regions.len() == 1 { "" } else { "s" },
+        desc.coverage,
+        symmetry
+    ));
+
+    if let Some(main) = desc.regions.first() {
+        let label = main.tokens.first().map(|t| t.as_str()).unwrap_or(main.color.as_str());
+        output.push_str(&format!(
+            "The dominant color is {} ({}), {} {} in the {}.\n",
+            label,
+            main.color_name.as_deref().unwrap_or(&main.color),
+            if main.shape.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" },
+            main.shape,
+            main.position
+        ));
+    }
+
+    if !desc.regions.is_empty() {
+        output.push('\n');
+        output.push_str("REGIONS\n");
+        output.push_str("-------\n");
+        for region in &desc.regions {
+            let tokens =
+                if region.tokens.is_empty() { "?".to_string() } else { region.tokens.join(",") };
+            let role = match (&region.role, region.role_inferred) {
+                (Some(role), true) => format!(" role={}?", role),
+                (Some(role), false) => format!(" role={}", role),
+                (None, _) => String::new(),
+            };
+            output.push_str(&format!(
+                "  {:12} {} {:>5.1}% {:8} at [{}, {}] {}x{} ({}){}\n",
+                tokens,
+                region.color,
+                region.percentage,
+                region.shape,
+                region.bounds[0],
+                region.bounds[1],
+                region.bounds[2],
+                region.bounds[3],
+                region.position,
+                role
+            ));
+        }
+    }
+
+    output
+}
+
 /// Resolve palette colors from a sprite's palette reference
 pub fn resolve_palette_colors(
     palette_ref: &PaletteRef,
@@ -686,6 +1114,294 @@ pub fn resolve_palette_colors(
     }
 }
 
+/// One sprite transform on the way from a source pixel to the output
+#[derive(Debug, Clone)]
+pub struct PixelTransformStep {
+    /// Transform as written in the sprite (e.g., "mirror-h")
+    pub transform: String,
+    /// What the transform does
+    pub effect: String,
+    /// Pixel position before the transform, if it had one
+    pub from: Option<[u32; 2]>,
+    /// Pixel position after the transform
+    pub to: [u32; 2],
+}
+
+/// Why one pixel of a rendered sprite has its color
+#[derive(Debug, Clone)]
+pub struct PixelExplanation {
+    /// Sprite or variant name
+    pub sprite: String,
+    /// Pixel position in the rendered sprite
+    pub x: u32,
+    pub y: u32,
+    /// Rendered color (#RRGGBBAA)
+    pub color: String,
+    /// Human-readable color name (if determinable)
+    pub color_name: Option<String>,
+    /// Position before the sprite's transforms, if a region drew the pixel
+    pub source: Option<[u32; 2]>,
+    /// Layer holding the visible token (layered sprites only)
+    pub layer: Option<String>,
+    /// Topmost token covering the source pixel
+    pub token: Option<String>,
+    /// Color the palette gives the token
+    pub token_color: Option<String>,
+    /// Where the token's color is defined
+    pub palette: String,
+    /// Transforms applied after rendering, in order
+    pub transforms: Vec<PixelTransformStep>,
+    /// Why the pixel doesn't trace back to a region, if it doesn't
+    pub note: Option<String>,
+    /// Why the magenta fallback color was used, if it was
+    pub fallback: Option<String>,
+}
+
+/// Transform name as written in a sprite's `transform` list
+fn transform_label(spec: &TransformSpec) -> String {
+    match spec {
+        TransformSpec::String(s) => s.clone(),
+        TransformSpec::Object { op, .. } => op.clone(),
+    }
+}
+
+/// The visible token at a pixel of a resolved sprite, and its layer.
+///
+/// Layers are searched top-down in render order; a token whose color is
+/// transparent only counts if no layer below it draws anything.
+fn visible_token(resolved: &ResolvedSprite, [x, y]: [u32; 2]) -> (Option<String>, Option<String>) {
+    let Some([width, height]) = resolved.size else {
+        return (None, None);
+    };
+    let layers = resolved.layers.as_deref().filter(|l| !l.is_empty());
+
+    // (layer name, z, regions, jitter seed name), as in render_layers
+    let mut ordered: Vec<(&str, i32, &HashMap<String, RegionDef>, String)> = Vec::new();
+    if let Some(regions) = &resolved.regions {
+        ordered.push((BASE_LAYER, i32::MIN, regions, resolved.name.clone()));
+    }
+    for layer in layers.into_iter().flatten() {
+        let seed_name = format!("{}/{}", resolved.name, layer.name);
+        ordered.push((&layer.name, layer.z.unwrap_or(0), &layer.regions, seed_name));
+    }
+    ordered.sort_by_key(|(_, z, ..)| *z);
+
+    let candidates: Vec<(Option<String>, String)> = ordered
+        .into_iter()
+        .rev()
+        .filter_map(|(layer, _, regions, seed_name)| {
+            let (grid, _) = token_grid(&seed_name, width, height, regions);
+            let token = grid.get(y as usize)?.get(x as usize)?.clone()?;
+            Some((layers.map(|_| layer.to_string()), token))
+        })
+        .collect();
+    let opaque = |token: &String| {
+        resolved.palette.get(token).and_then(|c| parse_color(c).ok()).is_none_or(|c| c[3] > 0)
+    };
+    match candidates.iter().find(|(_, token)| opaque(token)).or(candidates.first()) {
+        Some((layer, token)) => (layer.clone(), Some(token.clone())),
+        None => (None, None),
+    }
+}
+
+/// Explain why pixel `(x, y)` of a rendered sprite or variant has its color.
+///
+/// The pixel is traced back through the sprite's `transform` list to the
+/// region that drew it, then to the palette that defines that region's token.
+/// Coordinates are in the transformed (rendered) sprite.
+pub fn explain_pixel(
+    name: &str,
+    [x, y]: [u32; 2],
+    sprites: &SpriteRegistry,
+    palettes: &PaletteRegistry,
+) -> Result<PixelExplanation, String> {
+    let variant = sprites.get_variant(name);
+    let sprite = match variant {
+        Some(v) => sprites.root_sprite(&v.base).ok_or_else(|| {
+            format!("Variant '{}' references unknown base sprite '{}'", v.name, v.base)
+        })?,
+        None => {
+            sprites.get_sprite(name).ok_or_else(|| format!("No sprite named '{}' found", name))?
+        }
+    };
+    let resolved = sprites.resolve(name, palettes, false).map_err(|e| e.to_string())?;
+    let (image, _) = render_resolved(&resolved);
+
+    // Variants render without transforms, like the sprite registry resolves them
+    let mut images = vec![image];
+    let mut steps = Vec::new();
+    let specs = if variant.is_some() { None } else { sprite.transform.as_ref() };
+    for spec in specs.into_iter().flatten() {
+        let Ok(transform) = parse_transform_spec(spec) else {
+            continue;
+        };
+        if is_animation_transform(&transform) {
+            continue;
+        }
+        let current = images.last().expect("rendered image");
+        if let Ok(next) = apply_image_transform(current, &transform, Some(&resolved.palette)) {
+            steps.push((transform_label(spec), transform));
+            images.push(next);
+        }
+    }
+
+    let output = images.last().expect("rendered image");
+    if x >= output.width() || y >= output.height() {
+        return Err(format!(
+            "Pixel ({}, {}) is outside sprite '{}' ({}x{})",
+            x,
+            y,
+            name,
+            output.width(),
+            output.height()
+        ));
+    }
+    let rgba = output.get_pixel(x, y);
+    let color = format!("#{:02X}{:02X}{:02X}{:02X}", rgba[0], rgba[1], rgba[2], rgba[3]);
+
+    // Walk back through the transforms until the pixel leaves the sprite
+    let mut position = Some([x, y]);
+    let mut note = None;
+    let mut transforms = Vec::new();
+    for (i, (label, transform)) in steps.iter().enumerate().rev() {
+        let Some(to) = position else {
+            break;
+        };
+        let (before, after) = (&images[i], &images[i + 1]);
+        let from = match source_pixel(transform, before.dimensions(), after.dimensions(), to) {
+            Ok(from) => from,
+            Err(reason) => {
+                note = Some(format!("can't be traced through {} because {}", label, reason));
+                None
+            }
+        };
+        if note.is_none() {
+            let visible = after.get_pixel(to[0], to[1])[3] > 0;
+            match from {
+                Some([fx, fy]) if visible && before.get_pixel(fx, fy)[3] == 0 => {
+                    note = Some(format!("drawn by {}", label));
+                }
+                None if visible => note = Some(format!("drawn by {}", label)),
+                None => note = Some(format!("added by {}, outside the original sprite", label)),
+                _ => {}
+            }
+        }
+        transforms.push(PixelTransformStep {
+            transform: label.clone(),
+            effect: crate::transforms::explain_transform(transform),
+            from,
+            to,
+        });
+        position = if note.is_some() { None } else { from };
+    }
+    transforms.reverse();
+
+    let (layer, token) = match position {
+        Some(source) => visible_token(&resolved, source),
+        None => (None, None),
+    };
+    let token_color = token.as_ref().and_then(|t| resolved.palette.get(t).cloned());
+
+    let resolution = palettes.resolve_lenient(sprite);
+    let palette = match (&token, variant) {
+        (Some(t), Some(v)) if v.palette.contains_key(t) => format!("variant '{}'", v.name),
+        _ => match &resolution.palette.source {
+            PaletteSource::Named(n) => format!("palette '{}'", n),
+            PaletteSource::Builtin(n) => format!("built-in palette '@{}'", n),
+            PaletteSource::Inline => format!("inline palette of sprite '{}'", sprite.name),
+            PaletteSource::Fallback => match &sprite.palette {
+                PaletteRef::Named(n) => format!("missing palette '{}'", n),
+                PaletteRef::Inline(_) => "missing palette".to_string(),
+            },
+        },
+    };
+
+    let fallback = match (&token, &token_color) {
+        (Some(t), None) => Some(match &resolution.warning {
+            Some(warning) if resolution.palette.source == PaletteSource::Fallback => {
+                format!("{}, so token {} has no color", warning.message, t)
+            }
+            _ => format!("token {} is not defined in {}", t, palette),
+        }),
+        (Some(t), Some(c)) if parse_color(c).is_err() => {
+            Some(format!("color '{}' for token {} is invalid", c, t))
+        }
+        _ => None,
+    };
+
+    Ok(PixelExplanation {
+        sprite: name.to_string(),
+        x,
+        y,
+        color_name: describe_color(&color),
+        color,
+        source: position,
+        layer,
+        token,
+        token_color,
+        palette,
+        transforms,
+        note,
+        fallback,
+    })
+}
+
+/// Format a pixel explanation as human-readable text
+pub fn format_pixel_explanation(exp: &PixelExplanation) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("Pixel: ({}, {}) of {}\n", exp.x, exp.y, exp.sprite));
+    let name = exp.color_name.as_ref().map(|n| format!(" ({})", n)).unwrap_or_default();
+    output.push_str(&format!("Color: {}{}\n", exp.color, name));
+
+    if let Some(note) = &exp.note {
+        output.push_str(&format!("Origin: {}\n", note));
+    } else if let Some([sx, sy]) = exp.source {
+        match &exp.token {
+            Some(token) => {
+                let layer = exp.layer.as_ref().map(|l| format!(" on layer {}", l));
+                output.push_str(&format!(
+                    "Token: {} at ({}, {}){}\n",
+                    token,
+                    sx,
+                    sy,
+                    layer.unwrap_or_default()
+                ));
+                match &exp.token_color {
+                    Some(c) => {
+                        output.push_str(&format!("Palette: {} = {} ({})\n", token, c, exp.palette))
+                    }
+                    None => output
+                        .push_str(&format!("Palette: {} is undefined ({})\n", token, exp.palette)),
+                }
+            }
+            None => output.push_str(&format!("Token: none (no region covers ({}, {}))\n", sx, sy)),
+        }
+    }
+    if let Some(fallback) = &exp.fallback {
+        output.push_str(&format!("Fallback: magenta, because {}\n", fallback));
+    }
+
+    if !exp.transforms.is_empty() {
+        output.push('\n');
+        output.push_str("TRANSFORMS\n");
+        output.push_str("----------\n");
+        for step in &exp.transforms {
+            let from = match step.from {
+                Some([fx, fy]) => format!("({}, {})", fx, fy),
+                None => "-".to_string(),
+            };
+            let to = format!("({}, {})", step.to[0], step.to[1]);
+            output.push_str(&format!(
+                "  {:12} {:>10} -> {:10} {}\n",
+                step.transform, from, to, step.effect
+            ));
+        }
+    }
+
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -739,6 +1455,54 @@ mod tests {
         assert!(!exp.is_builtin);
     }
 
+    fn describe_source(source: &str) -> SpriteDescription {
+        let sprite: Sprite = serde_json::from_str(source).unwrap();
+        let colors = match &sprite.palette {
+            PaletteRef::Inline(colors) => colors.clone(),
+            PaletteRef::Named(_) => HashMap::new(),
+        };
+        describe_sprite(&sprite, &colors)
+    }
+
+    #[test]
+    fn test_describe_sprite_layout_and_symmetry() {
+        let desc = describe_source(
+            r##"{"name": "box", "size": [6, 6], "palette": {"_": "#00000000", "edge": "#000000", "body": "#FF0000"}, "regions": {"edge": {"stroke": [0, 0, 6, 6], "z": 1}, "body": {"rect": [1, 1, 4, 4], "role": "fill"}}}"##,
+        );
+
+        assert_eq!((desc.width, desc.height), (6, 6));
+        assert_eq!(desc.visible_pixels, 36);
+        assert_eq!(desc.symmetry.as_deref(), Some("both"));
+        assert_eq!(desc.regions.len(), 2);
+
+        let edge = &desc.regions[0];
+        assert_eq!(edge.tokens, vec!["edge".to_string()]);
+        assert_eq!(edge.shape, "stroke");
+        assert_eq!(edge.pixels, 20);
+
+        let body = &desc.regions[1];
+        assert_eq!(body.shape, "rect");
+        assert_eq!(body.bounds, [1, 1, 4, 4]);
+        assert_eq!(body.position, "center");
+        assert_eq!(body.role.as_deref(), Some("fill"));
+        assert!(!body.role_inferred);
+    }
+
+    #[test]
+    fn test_describe_sprite_position_and_asymmetry() {
+        let desc = describe_source(
+            r##"{"name": "dot", "size": [9, 9], "palette": {"_": "#00000000", "x": "#00FF00"}, "regions": {"x": {"rect": [0, 0, 2, 2]}}}"##,
+        );
+
+        assert_eq!(desc.symmetry, None);
+        assert_eq!(desc.content_bounds, Some([0, 0, 2, 2]));
+        assert_eq!(desc.regions[0].position, "top-left");
+
+        let text = format_sprite_description(&desc);
+        assert!(text.contains("'dot' is a 9x9 sprite using 1 color"));
+        assert!(text.contains("top-left"));
+    }
+
     #[test]
     fn test_format_sprite_explanation() {
         let exp = SpriteExplanation {
@@ -778,4 +1542,56 @@ mod tests {
         assert!(output.contains("{x}"));
         assert!(output.contains("transparent"));
     }
+
+    fn pixel_registries(lines: &[&str]) -> (SpriteRegistry, PaletteRegistry) {
+        let mut sprites = SpriteRegistry::new();
+        let mut palettes = PaletteRegistry::new();
+        for line in lines {
+            match serde_json::from_str::<TtpObject>(line).unwrap() {
+                TtpObject::Palette(p) => palettes.register(p),
+                TtpObject::Sprite(s) => sprites.register_sprite(s),
+                TtpObject::Variant(v) => sprites.register_variant(v),
+                _ => {}
+            }
+        }
+        (sprites, palettes)
+    }
+
+    #[test]
+    fn test_explain_pixel() {
+        let (sprites, palettes) = pixel_registries(&[
+            r##"{"type": "palette", "name": "hero", "colors": {"r": "#FF0000", "b": "#0000FF"}}"##,
+            r##"{"type": "sprite", "name": "knight", "size": [4, 2], "palette": "hero", "regions": {"r": {"rect": [0, 0, 2, 2]}, "b": {"points": [[1, 1]], "z": 1}, "q": {"points": [[3, 0]]}}, "transform": ["mirror-h"]}"##,
+            r##"{"type": "variant", "name": "knight_green", "base": "knight", "palette": {"r": "#00FF00"}}"##,
+            r##"{"type": "sprite", "name": "layered", "size": [2, 1], "palette": "hero", "regions": {"r": {"rect": [0, 0, 2, 1]}}, "layers": [{"name": "top", "regions": {"b": {"points": [[1, 0]]}}}]}"##,
+        ]);
+
+        // Mirrored: output column 2 came from column 1, where "b" is on top
+        let exp = explain_pixel("knight", [2, 1], &sprites, &palettes).unwrap();
+        assert_eq!(exp.color, "#0000FFFF");
+        assert_eq!(exp.source, Some([1, 1]));
+        assert_eq!(exp.token.as_deref(), Some("b"));
+        assert_eq!(exp.palette, "palette 'hero'");
+        assert_eq!(exp.transforms.len(), 1);
+        assert_eq!(exp.transforms[0].from, Some([1, 1]));
+        assert_eq!(exp.fallback, None);
+
+        // An undefined token renders magenta
+        let exp = explain_pixel("knight", [0, 0], &sprites, &palettes).unwrap();
+        assert_eq!(exp.color, "#FF00FFFF");
+        assert!(exp.fallback.unwrap().contains("token q is not defined"));
+
+        // Variant overrides are reported as the color's source, without transforms
+        let exp = explain_pixel("knight_green", [0, 0], &sprites, &palettes).unwrap();
+        assert_eq!(exp.token_color.as_deref(), Some("#00FF00"));
+        assert_eq!(exp.palette, "variant 'knight_green'");
+        assert!(exp.transforms.is_empty());
+
+        let exp = explain_pixel("layered", [1, 0], &sprites, &palettes).unwrap();
+        assert_eq!(exp.layer.as_deref(), Some("top"));
+        assert_eq!(exp.token.as_deref(), Some("b"));
+
+        assert!(explain_pixel("knight", [4, 0], &sprites, &palettes).is_err());
+        assert!(explain_pixel("nobody", [0, 0], &sprites, &palettes).is_err());
+    }
 }
@@ -157,8 +157,8 @@ impl GodotExporter {
 
         // Generate AtlasTexture for each frame
         if options.atlas_textures {
-            for (frame_name, frame) in &metadata.frames {
-                let content = self.generate_atlas_texture(
+            for (frame_name, frame) in metadata.frames_by_name() {
+                let mut content = self.generate_atlas_texture(
                     &metadata.image,
                     frame.x,
                     frame.y,
@@ -166,6 +166,16 @@ impl GodotExporter {
                     frame.h,
                     &options.resource_path,
                 );
+                // Anchor points ride along as resource metadata (`get_meta("attach_out")`)
+                for (key, point) in [
+                    ("origin", frame.origin),
+                    ("attach_in", frame.attach_in),
+                    ("attach_out", frame.attach_out),
+                ] {
+                    if let Some([x, y]) = point {
+                        content.push_str(&format!("metadata/{} = Vector2({}, {})\n", key, x, y));
+                    }
+                }
 
                 let output_path = output_dir.join(format!("{}.tres", frame_name));
                 let mut file = File::create(&output_path)?;
@@ -240,7 +250,7 @@ region = Rect2({}, {}, {}, {})
         let mut frame_to_id: std::collections::HashMap<String, u32> =
             std::collections::HashMap::new();
 
-        for anim in metadata.animations.values() {
+        for (_, anim) in metadata.animations_by_name() {
             for frame_name in &anim.frames {
                 if !frame_to_id.contains_key(frame_name) {
                     let texture_path = format!("{}/{}.tres", resource_path, frame_name);
@@ -259,7 +269,7 @@ region = Rect2({}, {}, {}, {})
 
         // Build animations array
         let mut anim_entries = Vec::new();
-        for (anim_name, anim) in &metadata.animations {
+        for (anim_name, anim) in metadata.animations_by_name() {
             let fps = anim.fps as f64;
 
             // Build frames array
@@ -474,15 +484,15 @@ mod tests {
             frames: HashMap::from([
                 (
                     "player_idle".to_string(),
-                    AtlasFrame { x: 0, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                    AtlasFrame { x: 0, y: 0, w: 32, h: 32, ..Default::default() },
                 ),
                 (
                     "player_walk_1".to_string(),
-                    AtlasFrame { x: 32, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                    AtlasFrame { x: 32, y: 0, w: 32, h: 32, ..Default::default() },
                 ),
                 (
                     "player_walk_2".to_string(),
-                    AtlasFrame { x: 64, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                    AtlasFrame { x: 64, y: 0, w: 32, h: 32, ..Default::default() },
                 ),
             ]),
             animations: HashMap::from([(
@@ -491,6 +501,10 @@ mod tests {
                     frames: vec!["player_walk_1".to_string(), "player_walk_2".to_string()],
                     fps: 10,
                     tags: None,
+                    durations: None,
+                    canvas: None,
+                    offsets: None,
+                    direction: None,
                 },
             )]),
         }
@@ -575,6 +589,25 @@ mod tests {
         assert!(content.contains("Rect2(0, 0, 32, 32)"));
     }
 
+    #[test]
+    fn test_export_godot_attach_point_metadata() {
+        let temp = TempDir::new().unwrap();
+        let mut metadata = create_test_metadata();
+        let idle = metadata.frames.get_mut("player_idle").unwrap();
+        idle.origin = Some([16, 32]);
+        idle.attach_out = Some([28, 12]);
+
+        export_godot(&metadata, temp.path(), "res://game").unwrap();
+
+        let content = fs::read_to_string(temp.path().join("player_idle.tres")).unwrap();
+        assert!(content.contains("metadata/origin = Vector2(16, 32)"));
+        assert!(content.contains("metadata/attach_out = Vector2(28, 12)"));
+        assert!(!content.contains("metadata/attach_in"));
+
+        let content = fs::read_to_string(temp.path().join("player_walk_1.tres")).unwrap();
+        assert!(!content.contains("metadata/"));
+    }
+
     #[test]
     fn test_export_godot_sprite_frames_content() {
         let temp = TempDir::new().unwrap();
@@ -597,7 +630,7 @@ mod tests {
             size: [64, 64],
             frames: HashMap::from([(
                 "icon".to_string(),
-                AtlasFrame { x: 0, y: 0, w: 64, h: 64, origin: None, boxes: None },
+                AtlasFrame { x: 0, y: 0, w: 64, h: 64, ..Default::default() },
             )]),
             animations: HashMap::new(),
         };
@@ -704,11 +737,11 @@ mod tests {
             frames: HashMap::from([
                 (
                     "frame1".to_string(),
-                    AtlasFrame { x: 0, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                    AtlasFrame { x: 0, y: 0, w: 32, h: 32, ..Default::default() },
                 ),
                 (
                     "frame2".to_string(),
-                    AtlasFrame { x: 32, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                    AtlasFrame { x: 32, y: 0, w: 32, h: 32, ..Default::default() },
                 ),
             ]),
             animations: HashMap::from([(
@@ -717,6 +750,10 @@ mod tests {
                     frames: vec!["frame1".to_string(), "frame2".to_string()],
                     fps: 10, // 10 fps = 0.2 seconds for 2 frames
                     tags: None,
+                    durations: None,
+                    canvas: None,
+                    offsets: None,
+                    direction: None,
                 },
             )]),
         };
@@ -781,19 +818,19 @@ mod tests {
             frames: HashMap::from([
                 (
                     "idle_1".to_string(),
-                    AtlasFrame { x: 0, y: 0, w: 16, h: 16, origin: None, boxes: None },
+                    AtlasFrame { x: 0, y: 0, w: 16, h: 16, ..Default::default() },
                 ),
                 (
                     "idle_2".to_string(),
-                    AtlasFrame { x: 16, y: 0, w: 16, h: 16, origin: None, boxes: None },
+                    AtlasFrame { x: 16, y: 0, w: 16, h: 16, ..Default::default() },
                 ),
                 (
                     "walk_1".to_string(),
-                    AtlasFrame { x: 32, y: 0, w: 16, h: 16, origin: None, boxes: None },
+                    AtlasFrame { x: 32, y: 0, w: 16, h: 16, ..Default::default() },
                 ),
                 (
                     "walk_2".to_string(),
-                    AtlasFrame { x: 48, y: 0, w: 16, h: 16, origin: None, boxes: None },
+                    AtlasFrame { x: 48, y: 0, w: 16, h: 16, ..Default::default() },
                 ),
             ]),
             animations: HashMap::from([
@@ -803,6 +840,10 @@ mod tests {
                         frames: vec!["idle_1".to_string(), "idle_2".to_string()],
                         fps: 5,
                         tags: None,
+                        durations: None,
+                        canvas: None,
+                        offsets: None,
+                        direction: None,
                     },
                 ),
                 (
@@ -811,6 +852,10 @@ mod tests {
                         frames: vec!["walk_1".to_string(), "walk_2".to_string()],
                         fps: 10,
                         tags: None,
+                        durations: None,
+                        canvas: None,
+                        offsets: None,
+                        direction: None,
                     },
                 ),
             ]),
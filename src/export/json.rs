@@ -147,7 +147,7 @@ mod tests {
             frames: HashMap::from([
                 (
                     "sprite1".to_string(),
-                    AtlasFrame { x: 0, y: 0, w: 16, h: 16, origin: None, boxes: None },
+                    AtlasFrame { x: 0, y: 0, w: 16, h: 16, ..Default::default() },
                 ),
                 (
                     "sprite2".to_string(),
@@ -161,6 +161,9 @@ mod tests {
                             "hit".to_string(),
                             AtlasBox { x: 2, y: 2, w: 12, h: 12 },
                         )])),
+                        attach_in: None,
+                        attach_out: None,
+                        slices: None,
                     },
                 ),
             ]),
@@ -170,6 +173,10 @@ mod tests {
                     frames: vec!["sprite1".to_string(), "sprite2".to_string()],
                     fps: 10,
                     tags: None,
+                    durations: None,
+                    canvas: None,
+                    offsets: None,
+                    direction: None,
                 },
             )]),
         }
@@ -293,7 +300,7 @@ mod tests {
             size: [32, 32],
             frames: HashMap::from([(
                 "sprite".to_string(),
-                AtlasFrame { x: 0, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                AtlasFrame { x: 0, y: 0, w: 32, h: 32, ..Default::default() },
             )]),
             animations: HashMap::new(),
         };
@@ -260,7 +260,7 @@ impl LibGdxExporter {
     ) -> std::collections::HashMap<&'a str, i32> {
         let mut indices = std::collections::HashMap::new();
 
-        for animation in metadata.animations.values() {
+        for (_, animation) in metadata.animations_by_name() {
             for (i, frame_name) in animation.frames.iter().enumerate() {
                 indices.insert(frame_name.as_str(), i as i32);
             }
@@ -301,15 +301,15 @@ mod tests {
         let mut frames = HashMap::new();
         frames.insert(
             "player_idle".to_string(),
-            AtlasFrame { x: 0, y: 0, w: 32, h: 32, origin: None, boxes: None },
+            AtlasFrame { x: 0, y: 0, w: 32, h: 32, ..Default::default() },
         );
         frames.insert(
             "player_walk_1".to_string(),
-            AtlasFrame { x: 32, y: 0, w: 32, h: 32, origin: Some([16, 32]), boxes: None },
+            AtlasFrame { x: 32, y: 0, w: 32, h: 32, origin: Some([16, 32]), ..Default::default() },
         );
         frames.insert(
             "player_walk_2".to_string(),
-            AtlasFrame { x: 64, y: 0, w: 32, h: 32, origin: Some([16, 32]), boxes: None },
+            AtlasFrame { x: 64, y: 0, w: 32, h: 32, origin: Some([16, 32]), ..Default::default() },
         );
 
         let mut animations = HashMap::new();
@@ -319,6 +319,10 @@ mod tests {
                 frames: vec!["player_walk_1".to_string(), "player_walk_2".to_string()],
                 fps: 10,
                 tags: None,
+                durations: None,
+                canvas: None,
+                offsets: None,
+                direction: None,
             },
         );
 
@@ -553,7 +557,7 @@ mod tests {
         let mut frames = HashMap::new();
         frames.insert(
             "sprite".to_string(),
-            AtlasFrame { x: 0, y: 0, w: 16, h: 16, origin: None, boxes: None },
+            AtlasFrame { x: 0, y: 0, w: 16, h: 16, ..Default::default() },
         );
 
         let metadata = AtlasMetadata {
@@ -54,6 +54,7 @@
 use crate::atlas::AtlasMetadata;
 use crate::export::{ExportOptions, Exporter, Result};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
@@ -124,6 +125,9 @@ pub struct UnitySprite {
     pub pivot: UnityVector2,
     /// Border for 9-slice (left, bottom, right, top)
     pub border: UnityVector4,
+    /// Attachment points in pixels from the sprite's bottom-left corner
+    #[serde(rename = "attachPoints", skip_serializing_if = "BTreeMap::is_empty")]
+    pub attach_points: BTreeMap<String, UnityVector2>,
 }
 
 /// Unity rectangle.
@@ -296,7 +300,7 @@ impl UnityExporter {
 
         // Generate .anim files for animations
         if options.generate_anim_files && options.include_animations {
-            for (anim_name, anim) in &metadata.animations {
+            for (anim_name, anim) in metadata.animations_by_name() {
                 let anim_path = output_dir.join(format!("{}.anim", anim_name));
                 let anim_content = self.generate_animation_clip(anim_name, anim, metadata, options);
                 let mut file = File::create(&anim_path)?;
@@ -639,6 +643,18 @@ AnimationClip:
                     },
                     pivot,
                     border: UnityVector4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 },
+                    attach_points: [
+                        ("attach_in", frame.attach_in),
+                        ("attach_out", frame.attach_out),
+                    ]
+                    .into_iter()
+                    .filter_map(|(key, point)| {
+                        let [x, y] = point?;
+                        // Flip Y for Unity coordinate system
+                        let point = UnityVector2 { x: x as f32, y: frame.h as f32 - y as f32 };
+                        Some((key.to_string(), point))
+                    })
+                    .collect(),
                 }
             })
             .collect();
@@ -784,15 +800,18 @@ mod tests {
                         h: 32,
                         origin: Some([16, 32]), // Bottom center
                         boxes: None,
+                        attach_in: None,
+                        attach_out: None,
+                        slices: None,
                     },
                 ),
                 (
                     "player_walk_1".to_string(),
-                    AtlasFrame { x: 32, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                    AtlasFrame { x: 32, y: 0, w: 32, h: 32, ..Default::default() },
                 ),
                 (
                     "player_walk_2".to_string(),
-                    AtlasFrame { x: 64, y: 0, w: 32, h: 32, origin: None, boxes: None },
+                    AtlasFrame { x: 64, y: 0, w: 32, h: 32, ..Default::default() },
                 ),
             ]),
             animations: HashMap::from([(
@@ -801,6 +820,10 @@ mod tests {
                     frames: vec!["player_walk_1".to_string(), "player_walk_2".to_string()],
                     fps: 10,
                     tags: None,
+                    durations: None,
+                    canvas: None,
+                    offsets: None,
+                    direction: None,
                 },
             )]),
         }
@@ -909,6 +932,26 @@ mod tests {
         assert_eq!(player_idle["pivot"]["y"], 0.0);
     }
 
+    #[test]
+    fn test_export_sprite_attach_points() {
+        let exporter = UnityExporter::new();
+        let mut metadata = create_test_metadata();
+        metadata.frames.get_mut("player_idle").unwrap().attach_out = Some([28, 12]);
+
+        let json = exporter.export_to_string(&metadata, &UnityExportOptions::default()).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let sprites = data["sprites"].as_array().unwrap();
+        let player_idle = sprites.iter().find(|s| s["name"] == "player_idle").unwrap();
+
+        // 12px from the top of a 32px sprite is 20px up from the bottom
+        assert_eq!(player_idle["attachPoints"]["attach_out"]["x"], 28.0);
+        assert_eq!(player_idle["attachPoints"]["attach_out"]["y"], 20.0);
+        assert!(player_idle["attachPoints"].get("attach_in").is_none());
+
+        let player_walk = sprites.iter().find(|s| s["name"] != "player_idle").unwrap();
+        assert!(player_walk.get("attachPoints").is_none());
+    }
+
     #[test]
     fn test_export_sprite_default_pivot() {
         let exporter = UnityExporter::new();
@@ -0,0 +1,165 @@
+//! Sprite template inheritance (`extends`)
+//!
+//! A sprite naming a template with `extends` starts from the template's
+//! size, palette, regions and metadata, and overrides or merges its own
+//! fields on top (see [`Sprite::inherit`]):
+//!
+//! ```json5
+//! { type: "sprite", name: "base_humanoid", size: [16, 24], palette: "skin",
+//!   regions: { body: { rect: [4, 6, 8, 12] }, head: { rect: [5, 0, 6, 6] } },
+//!   metadata: { origin: [8, 24], boxes: { hurt: { x: 4, y: 0, w: 8, h: 24 } } } }
+//! { type: "sprite", name: "knight", extends: "base_humanoid",
+//!   regions: { helmet: { rect: [5, 0, 6, 3] } } }
+//! ```
+//!
+//! Templates are resolved per document, after parsing, so a template may be
+//! defined before or after the sprites that extend it and may itself extend
+//! another template. Unknown templates and circular chains leave the sprite
+//! as written and produce a warning.
+
+use std::collections::HashMap;
+
+use crate::models::{Sprite, TtpObject};
+
+/// Replace every sprite in `objects` that `extends` a template with its
+/// flattened form.
+///
+/// Returns a warning message for each sprite that couldn't be resolved,
+/// with that sprite's index in `objects`.
+pub fn resolve_extends(objects: &mut [TtpObject]) -> Vec<(usize, String)> {
+    let sources: &[TtpObject] = objects;
+
+    // Later definitions of a name win, as in the sprite registry
+    let mut by_name = HashMap::new();
+    for (index, object) in sources.iter().enumerate() {
+        if let TtpObject::Sprite(sprite) = object {
+            by_name.insert(sprite.name.as_str(), index);
+        }
+    }
+
+    let mut resolver = Resolver { objects: sources, by_name, resolved: HashMap::new() };
+    let mut flattened = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, object) in sources.iter().enumerate() {
+        let TtpObject::Sprite(sprite) = object else {
+            continue;
+        };
+        if sprite.extends.is_none() {
+            continue;
+        }
+        match resolver.flatten(sprite, &mut Vec::new()) {
+            Ok(sprite) => flattened.push((index, sprite)),
+            Err(message) => warnings.push((index, message)),
+        }
+    }
+
+    for (index, sprite) in flattened {
+        objects[index] = TtpObject::Sprite(sprite);
+    }
+    warnings
+}
+
+struct Resolver<'a> {
+    objects: &'a [TtpObject],
+    by_name: HashMap<&'a str, usize>,
+    /// Flattened templates, by name
+    resolved: HashMap<String, Sprite>,
+}
+
+impl Resolver<'_> {
+    /// `sprite` with its template chain applied.
+    ///
+    /// The `chain` parameter tracks sprites in the current resolution chain
+    /// to detect circular references.
+    fn flatten(&mut self, sprite: &Sprite, chain: &mut Vec<String>) -> Result<Sprite, String> {
+        let Some(base_name) = &sprite.extends else {
+            return Ok(sprite.clone());
+        };
+
+        chain.push(sprite.name.clone());
+        if chain.contains(base_name) {
+            chain.push(base_name.clone());
+            return Err(format!("Circular extends detected: {}", chain.join(" -> ")));
+        }
+
+        let base = match self.resolved.get(base_name) {
+            Some(base) => base.clone(),
+            None => {
+                let template = self.by_name.get(base_name.as_str()).and_then(|&index| match &self
+                    .objects[index]
+                {
+                    TtpObject::Sprite(template) => Some(template),
+                    _ => None,
+                });
+                let Some(template) = template else {
+                    return Err(format!(
+                        "Sprite '{}' extends unknown sprite '{}'",
+                        sprite.name, base_name
+                    ));
+                };
+                let base = self.flatten(template, chain)?;
+                self.resolved.insert(base_name.clone(), base.clone());
+                base
+            }
+        };
+        chain.pop();
+        Ok(sprite.inherit(&base))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PaletteRef;
+
+    fn sprite(name: &str, extends: Option<&str>) -> TtpObject {
+        TtpObject::Sprite(Sprite {
+            name: name.to_string(),
+            extends: extends.map(str::to_string),
+            ..Default::default()
+        })
+    }
+
+    fn get(objects: &[TtpObject], index: usize) -> &Sprite {
+        match &objects[index] {
+            TtpObject::Sprite(sprite) => sprite,
+            _ => panic!("Expected sprite"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_chain_in_any_order() {
+        let mut objects = vec![
+            sprite("knight", Some("humanoid")),
+            sprite("humanoid", Some("body")),
+            sprite("body", None),
+        ];
+        if let TtpObject::Sprite(body) = &mut objects[2] {
+            body.size = Some([16, 24]);
+            body.palette = PaletteRef::Named("skin".to_string());
+        }
+
+        assert!(resolve_extends(&mut objects).is_empty());
+        let knight = get(&objects, 0);
+        assert_eq!(knight.size, Some([16, 24]));
+        assert_eq!(knight.palette, PaletteRef::Named("skin".to_string()));
+        assert_eq!(knight.extends, None);
+        assert_eq!(get(&objects, 1).size, Some([16, 24]));
+    }
+
+    #[test]
+    fn test_resolve_cycle_and_unknown() {
+        let mut objects =
+            vec![sprite("a", Some("b")), sprite("b", Some("a")), sprite("c", Some("missing"))];
+        let warnings = resolve_extends(&mut objects);
+        assert_eq!(
+            warnings,
+            [
+                (0, "Circular extends detected: a -> b -> a".to_string()),
+                (1, "Circular extends detected: b -> a -> b".to_string()),
+                (2, "Sprite 'c' extends unknown sprite 'missing'".to_string()),
+            ]
+        );
+        assert_eq!(get(&objects, 0).extends.as_deref(), Some("b"), "left as written");
+    }
+}
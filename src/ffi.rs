@@ -0,0 +1,387 @@
+//! C ABI for embedding pixelsrc in native engines (feature `ffi`).
+//!
+//! Exposes a small handle-based API: load a document, query sprites, and
+//! render into a caller-provided RGBA buffer. The header lives at
+//! `include/pixelsrc.h` and is regenerated with `just ffi-header` (cbindgen).
+//!
+//! # Ownership
+//!
+//! - `pxl_document_load*` returns an owned handle that must be released with
+//!   [`pxl_document_free`].
+//! - Strings returned by the library (sprite names, errors) are borrowed and
+//!   remain valid until the owning document is freed (or, for
+//!   [`pxl_last_error`], until the next failing call on the same thread).
+//!
+//! # Errors
+//!
+//! Functions returning `i32` use the `PXL_*` status codes below. On failure a
+//! human-readable message is available from [`pxl_last_error`].
+
+// Raw pointer handling is inherent to a C ABI; this is the only module that needs it.
+#![allow(unsafe_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::io::Cursor;
+
+use image::RgbaImage;
+
+use crate::models::{Sprite, TtpObject};
+use crate::parser::parse_stream;
+use crate::registry::PaletteRegistry;
+use crate::renderer::render_sprite;
+
+/// Success.
+pub const PXL_OK: i32 = 0;
+/// A required pointer argument was null.
+pub const PXL_ERR_NULL_POINTER: i32 = -1;
+/// A string argument was not valid UTF-8.
+pub const PXL_ERR_INVALID_UTF8: i32 = -2;
+/// No sprite with the requested name exists in the document.
+pub const PXL_ERR_NOT_FOUND: i32 = -3;
+/// The output buffer is smaller than `width * height * 4` bytes.
+pub const PXL_ERR_BUFFER_TOO_SMALL: i32 = -4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// An opaque parsed document.
+pub struct PxlDocument {
+    palettes: PaletteRegistry,
+    sprites: HashMap<String, Sprite>,
+    /// Sprite names in definition order, NUL-terminated for C callers
+    names: Vec<CString>,
+    /// Rendered sprites, filled lazily on first dimension/render query
+    cache: HashMap<String, RgbaImage>,
+    warnings: Vec<CString>,
+}
+
+impl PxlDocument {
+    fn parse(source: &str) -> Self {
+        let parse_result = parse_stream(Cursor::new(source));
+        let mut palettes = PaletteRegistry::new();
+        let mut sprites = HashMap::new();
+        let mut names = Vec::new();
+        let mut warnings: Vec<CString> = parse_result
+            .warnings
+            .iter()
+            .filter_map(|w| CString::new(format!("line {}: {}", w.line, w.message)).ok())
+            .collect();
+
+        for obj in parse_result.objects {
+            match obj {
+                TtpObject::Palette(p) => palettes.register(p),
+                TtpObject::Sprite(s) => {
+                    if !sprites.contains_key(&s.name) {
+                        match CString::new(s.name.clone()) {
+                            Ok(name) => names.push(name),
+                            Err(_) => {
+                                warnings.extend(CString::new("sprite name contains NUL byte").ok());
+                                continue;
+                            }
+                        }
+                    }
+                    sprites.insert(s.name.clone(), s);
+                }
+                _ => {}
+            }
+        }
+
+        Self { palettes, sprites, names, cache: HashMap::new(), warnings }
+    }
+
+    /// Render (or fetch from cache) the named sprite.
+    fn rendered(&mut self, name: &str) -> Option<&RgbaImage> {
+        if !self.cache.contains_key(name) {
+            let sprite = self.sprites.get(name)?;
+            let resolved = self.palettes.resolve_lenient(sprite);
+            if let Some(w) = &resolved.warning {
+                self.warnings
+                    .extend(CString::new(format!("sprite '{}': {}", name, w.message)).ok());
+            }
+            let (image, render_warnings) = render_sprite(sprite, &resolved.palette.colors);
+            for w in render_warnings {
+                self.warnings
+                    .extend(CString::new(format!("sprite '{}': {}", name, w.message)).ok());
+            }
+            self.cache.insert(name.to_string(), image);
+        }
+        self.cache.get(name)
+    }
+}
+
+/// Convert a C string argument to `&str`, recording an error on failure.
+///
+/// # Safety
+/// `ptr` must be null or point to a valid NUL-terminated string.
+unsafe fn str_arg<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, i32> {
+    if ptr.is_null() {
+        set_last_error(format!("{} is null", what));
+        return Err(PXL_ERR_NULL_POINTER);
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        set_last_error(format!("{} is not valid UTF-8", what));
+        PXL_ERR_INVALID_UTF8
+    })
+}
+
+/// Look up a sprite on a document handle, recording an error if missing.
+///
+/// # Safety
+/// `doc` must be null or a live handle from `pxl_document_load*`; `name` as for [`str_arg`].
+unsafe fn sprite_image<'a>(
+    doc: *mut PxlDocument,
+    name: *const c_char,
+) -> Result<&'a RgbaImage, i32> {
+    let Some(doc) = doc.as_mut() else {
+        set_last_error("document is null");
+        return Err(PXL_ERR_NULL_POINTER);
+    };
+    let name = str_arg(name, "sprite name")?;
+    doc.rendered(name).ok_or_else(|| {
+        set_last_error(format!("sprite '{}' not found", name));
+        PXL_ERR_NOT_FOUND
+    })
+}
+
+/// Parse a NUL-terminated `.pxl`/`.jsonl` source string.
+///
+/// Returns null (and sets the last error) if `source` is null or not UTF-8.
+///
+/// # Safety
+/// `source` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_document_load(source: *const c_char) -> *mut PxlDocument {
+    match str_arg(source, "source") {
+        Ok(source) => Box::into_raw(Box::new(PxlDocument::parse(source))),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Read and parse a `.pxl`/`.jsonl` file.
+///
+/// Returns null (and sets the last error) if the file cannot be read.
+///
+/// # Safety
+/// `path` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_document_load_file(path: *const c_char) -> *mut PxlDocument {
+    let Ok(path) = str_arg(path, "path") else {
+        return std::ptr::null_mut();
+    };
+    match std::fs::read_to_string(path) {
+        Ok(source) => Box::into_raw(Box::new(PxlDocument::parse(&source))),
+        Err(e) => {
+            set_last_error(format!("cannot read '{}': {}", path, e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Release a document handle. Passing null is a no-op.
+///
+/// # Safety
+/// `doc` must be null or a handle from `pxl_document_load*` that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_document_free(doc: *mut PxlDocument) {
+    if !doc.is_null() {
+        drop(Box::from_raw(doc));
+    }
+}
+
+/// Number of sprites in the document (0 if `doc` is null).
+///
+/// # Safety
+/// `doc` must be null or a live document handle.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_sprite_count(doc: *const PxlDocument) -> usize {
+    doc.as_ref().map(|d| d.names.len()).unwrap_or(0)
+}
+
+/// Name of the sprite at `index` in definition order, or null if out of range.
+///
+/// The string is owned by the document.
+///
+/// # Safety
+/// `doc` must be null or a live document handle.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_sprite_name(doc: *const PxlDocument, index: usize) -> *const c_char {
+    doc.as_ref().and_then(|d| d.names.get(index)).map(|n| n.as_ptr()).unwrap_or(std::ptr::null())
+}
+
+/// Query the rendered dimensions of a sprite.
+///
+/// # Safety
+/// `doc` must be null or a live document handle; `name` a NUL-terminated
+/// string; `width`/`height` null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_sprite_dimensions(
+    doc: *mut PxlDocument,
+    name: *const c_char,
+    width: *mut u32,
+    height: *mut u32,
+) -> i32 {
+    if width.is_null() || height.is_null() {
+        set_last_error("width/height output pointer is null");
+        return PXL_ERR_NULL_POINTER;
+    }
+    match sprite_image(doc, name) {
+        Ok(image) => {
+            *width = image.width();
+            *height = image.height();
+            PXL_OK
+        }
+        Err(code) => code,
+    }
+}
+
+/// Render a sprite as tightly packed RGBA8 into `buffer`.
+///
+/// `buffer_len` must be at least `width * height * 4` (see
+/// [`pxl_sprite_dimensions`]); otherwise `PXL_ERR_BUFFER_TOO_SMALL` is returned
+/// and the buffer is left untouched.
+///
+/// # Safety
+/// `doc` must be null or a live document handle; `name` a NUL-terminated
+/// string; `buffer` null or valid for `buffer_len` bytes of writes.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_render_sprite(
+    doc: *mut PxlDocument,
+    name: *const c_char,
+    buffer: *mut u8,
+    buffer_len: usize,
+) -> i32 {
+    if buffer.is_null() {
+        set_last_error("buffer is null");
+        return PXL_ERR_NULL_POINTER;
+    }
+    match sprite_image(doc, name) {
+        Ok(image) => {
+            let raw = image.as_raw();
+            if buffer_len < raw.len() {
+                set_last_error(format!(
+                    "buffer too small: need {} bytes, got {}",
+                    raw.len(),
+                    buffer_len
+                ));
+                return PXL_ERR_BUFFER_TOO_SMALL;
+            }
+            std::ptr::copy_nonoverlapping(raw.as_ptr(), buffer, raw.len());
+            PXL_OK
+        }
+        Err(code) => code,
+    }
+}
+
+/// Number of parse/render warnings collected so far on this document.
+///
+/// # Safety
+/// `doc` must be null or a live document handle.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_warning_count(doc: *const PxlDocument) -> usize {
+    doc.as_ref().map(|d| d.warnings.len()).unwrap_or(0)
+}
+
+/// Warning message at `index`, or null if out of range. Owned by the document.
+///
+/// # Safety
+/// `doc` must be null or a live document handle.
+#[no_mangle]
+pub unsafe extern "C" fn pxl_warning(doc: *const PxlDocument, index: usize) -> *const c_char {
+    doc.as_ref().and_then(|d| d.warnings.get(index)).map(|w| w.as_ptr()).unwrap_or(std::ptr::null())
+}
+
+/// Message describing the most recent failure on this thread, or null if none.
+#[no_mangle]
+pub extern "C" fn pxl_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map(|m| m.as_ptr()).unwrap_or(std::ptr::null()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEART: &str = "{\"type\": \"palette\", \"name\": \"reds\", \"colors\": {\"_\": \"#00000000\", \"r\": \"#FF0000\"}}\n{\"type\": \"sprite\", \"name\": \"heart\", \"size\": [2, 2], \"palette\": \"reds\", \"regions\": {\"r\": {\"points\": [[0, 0], [1, 1]], \"z\": 0}}}\0";
+
+    fn load(source: &str) -> *mut PxlDocument {
+        unsafe { pxl_document_load(source.as_ptr() as *const c_char) }
+    }
+
+    #[test]
+    fn test_load_and_list_sprites() {
+        let doc = load(HEART);
+        assert!(!doc.is_null());
+        unsafe {
+            assert_eq!(pxl_sprite_count(doc), 1);
+            let name = CStr::from_ptr(pxl_sprite_name(doc, 0));
+            assert_eq!(name.to_str().unwrap(), "heart");
+            assert!(pxl_sprite_name(doc, 1).is_null());
+            pxl_document_free(doc);
+        }
+    }
+
+    #[test]
+    fn test_render_into_buffer() {
+        let doc = load(HEART);
+        let name = c"heart";
+        unsafe {
+            let (mut w, mut h) = (0u32, 0u32);
+            assert_eq!(pxl_sprite_dimensions(doc, name.as_ptr(), &mut w, &mut h), PXL_OK);
+            assert_eq!((w, h), (2, 2));
+
+            let mut buf = vec![0u8; (w * h * 4) as usize];
+            assert_eq!(pxl_render_sprite(doc, name.as_ptr(), buf.as_mut_ptr(), buf.len()), PXL_OK);
+            assert_eq!(&buf[0..4], &[255, 0, 0, 255]);
+            assert_eq!(buf[7], 0, "pixel (1, 0) is transparent");
+            pxl_document_free(doc);
+        }
+    }
+
+    #[test]
+    fn test_render_buffer_too_small() {
+        let doc = load(HEART);
+        unsafe {
+            let mut buf = vec![0u8; 4];
+            let status = pxl_render_sprite(doc, c"heart".as_ptr(), buf.as_mut_ptr(), buf.len());
+            assert_eq!(status, PXL_ERR_BUFFER_TOO_SMALL);
+            let err = CStr::from_ptr(pxl_last_error()).to_str().unwrap();
+            assert!(err.contains("16 bytes"), "{}", err);
+            pxl_document_free(doc);
+        }
+    }
+
+    #[test]
+    fn test_unknown_sprite_and_null_handles() {
+        let doc = load(HEART);
+        unsafe {
+            let (mut w, mut h) = (0u32, 0u32);
+            let status = pxl_sprite_dimensions(doc, c"ghost".as_ptr(), &mut w, &mut h);
+            assert_eq!(status, PXL_ERR_NOT_FOUND);
+            assert_eq!(
+                pxl_sprite_dimensions(std::ptr::null_mut(), c"heart".as_ptr(), &mut w, &mut h),
+                PXL_ERR_NULL_POINTER
+            );
+            assert!(pxl_document_load(std::ptr::null()).is_null());
+            assert_eq!(pxl_sprite_count(std::ptr::null()), 0);
+            pxl_document_free(std::ptr::null_mut());
+            pxl_document_free(doc);
+        }
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        unsafe {
+            let doc = pxl_document_load_file(c"/nonexistent/file.pxl".as_ptr());
+            assert!(doc.is_null());
+            let err = CStr::from_ptr(pxl_last_error()).to_str().unwrap();
+            assert!(err.contains("cannot read"));
+        }
+    }
+}
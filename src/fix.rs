@@ -0,0 +1,460 @@
+//! Safe automatic rewrites for Pixelsrc files
+//!
+//! [`crate::lint`] and [`crate::suggest`] report problems; [`fix_str`] repairs
+//! the ones that have exactly one correct fix and cannot change how anything
+//! renders. Only the lines of the objects a fix touches are rewritten, so
+//! comments, blank lines, and the formatting of untouched objects survive.
+//!
+//! ```
+//! use pixelsrc::fix::{fix_str, EXTRACT_INLINE_PALETTE};
+//!
+//! let result = fix_str(
+//!     r##"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}"##,
+//! )
+//! .unwrap();
+//! assert_eq!(result.fixes[0].fix, EXTRACT_INLINE_PALETTE);
+//! assert!(result.text.contains(r#""palette":"dot_palette""#));
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Cursor;
+use std::ops::Range;
+
+use crate::models::{Palette, PaletteRef, Sprite, TtpObject};
+use crate::parser::parse_stream;
+
+/// Move a sprite's inline palette into a named palette (fixes `named-palette`)
+pub const EXTRACT_INLINE_PALETTE: &str = "extract-inline-palette";
+/// Remove palettes identical to an earlier one and point sprites at the survivor
+pub const DEDUPE_PALETTES: &str = "dedupe-palettes";
+/// Strip legacy `{token}` braces from palette keys so they match region names
+pub const NORMALIZE_TOKENS: &str = "normalize-tokens";
+
+/// All fixes, in the order they are applied
+pub const FIXES: &[&str] = &[NORMALIZE_TOKENS, DEDUPE_PALETTES, EXTRACT_INLINE_PALETTE];
+
+/// A fix that was applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedFix {
+    /// Line number (1-indexed) of the fixed object in the original source
+    pub line: usize,
+    /// Name of the fix
+    pub fix: &'static str,
+    /// Human-readable description of the change
+    pub message: String,
+}
+
+/// The fixed source and the fixes applied to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixResult {
+    /// Fixed source text (identical to the input when nothing was fixed)
+    pub text: String,
+    /// Applied fixes, in source order
+    pub fixes: Vec<AppliedFix>,
+}
+
+/// A parsed object, its 0-based source lines, and what the fixes did to it
+struct Slot {
+    object: TtpObject,
+    lines: Range<usize>,
+    changed: bool,
+    removed: bool,
+    /// Objects to insert before this one
+    inserted: Vec<TtpObject>,
+}
+
+impl Slot {
+    fn line(&self) -> usize {
+        self.lines.start + 1
+    }
+}
+
+/// Apply every safe fix to `source`.
+///
+/// Lines that fail to parse are left as they are. Fails only if a fixed
+/// object cannot be serialized.
+pub fn fix_str(source: &str) -> Result<FixResult, serde_json::Error> {
+    let parsed = parse_stream(Cursor::new(source));
+    let mut slots: Vec<Slot> = parsed
+        .objects
+        .into_iter()
+        .zip(parsed.spans)
+        .map(|(object, span)| Slot {
+            object,
+            lines: span.start - 1..span.end - 1,
+            changed: false,
+            removed: false,
+            inserted: Vec::new(),
+        })
+        .collect();
+
+    let mut fixes = Vec::new();
+    normalize_tokens(&mut slots, &mut fixes);
+    dedupe_palettes(&mut slots, &mut fixes);
+    extract_inline_palettes(&mut slots, &mut fixes);
+    fixes.sort_by_key(|fix| fix.line);
+
+    let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+    for slot in slots.iter().rev() {
+        if !slot.changed && !slot.removed && slot.inserted.is_empty() {
+            continue;
+        }
+        let mut text =
+            slot.inserted.iter().map(serde_json::to_string).collect::<Result<Vec<_>, _>>()?;
+        if slot.changed {
+            text.push(serde_json::to_string(&slot.object)?);
+        } else if !slot.removed {
+            text.extend_from_slice(&lines[slot.lines.clone()]);
+        }
+        lines.splice(slot.lines.clone(), text);
+    }
+
+    let mut text = lines.join("\n");
+    if source.ends_with('\n') && !text.is_empty() {
+        text.push('\n');
+    }
+    Ok(FixResult { text, fixes })
+}
+
+/// Rename `{token}` keys to `token` in palettes, inline palettes, and variant
+/// overrides.
+///
+/// A key is left alone if the bare name is already a key, or if some sprite in
+/// the file still draws a region named with braces.
+fn normalize_tokens(slots: &mut [Slot], fixes: &mut Vec<AppliedFix>) {
+    let braced_regions: HashSet<String> = slots
+        .iter()
+        .filter_map(|slot| match &slot.object {
+            TtpObject::Sprite(sprite) => Some(region_names(sprite)),
+            _ => None,
+        })
+        .flatten()
+        .filter(|name| unbrace(name).is_some())
+        .collect();
+
+    for slot in slots.iter_mut() {
+        let line = slot.line();
+        let (kind, name, renames) = match &mut slot.object {
+            TtpObject::Palette(palette) => {
+                let renames = rename_keys(&mut palette.colors, &braced_regions);
+                if let Some(roles) = &mut palette.roles {
+                    apply_renames(roles, &renames);
+                }
+                if let Some(relationships) = &mut palette.relationships {
+                    apply_renames(relationships, &renames);
+                    for relationship in relationships.values_mut() {
+                        if let Some(target) = renames.get(&relationship.target) {
+                            relationship.target = target.clone();
+                        }
+                    }
+                }
+                for aliases in [&mut palette.aliases, &mut palette.deprecated].into_iter().flatten()
+                {
+                    for target in aliases.values_mut() {
+                        if let Some(renamed) = renames.get(target) {
+                            *target = renamed.clone();
+                        }
+                    }
+                }
+                ("palette", &palette.name, renames)
+            }
+            TtpObject::Sprite(Sprite { name, palette: PaletteRef::Inline(colors), .. }) => {
+                ("sprite", &*name, rename_keys(colors, &braced_regions))
+            }
+            TtpObject::Variant(variant) => {
+                ("variant", &variant.name, rename_keys(&mut variant.palette, &braced_regions))
+            }
+            _ => continue,
+        };
+        if renames.is_empty() {
+            continue;
+        }
+        let list: Vec<String> = renames.iter().map(|(from, to)| format!("{from} → {to}")).collect();
+        fixes.push(AppliedFix {
+            line,
+            fix: NORMALIZE_TOKENS,
+            message: format!("{} '{}': renamed {}", kind, name, list.join(", ")),
+        });
+        slot.changed = true;
+    }
+}
+
+/// Region names a sprite draws, including those on layers.
+fn region_names(sprite: &Sprite) -> impl Iterator<Item = String> + '_ {
+    let layers = sprite.layers.iter().flatten().flat_map(|layer| layer.regions.keys());
+    sprite.regions.iter().flat_map(|regions| regions.keys()).chain(layers).cloned()
+}
+
+/// The name inside `{name}`, if `token` is braced.
+fn unbrace(token: &str) -> Option<&str> {
+    token.strip_prefix('{')?.strip_suffix('}').filter(|inner| !inner.is_empty())
+}
+
+/// Strip braces from `map`'s keys where that is safe, returning the renames.
+fn rename_keys<V>(
+    map: &mut HashMap<String, V>,
+    braced_regions: &HashSet<String>,
+) -> BTreeMap<String, String> {
+    let renames: BTreeMap<String, String> = map
+        .keys()
+        .filter(|key| !braced_regions.contains(*key))
+        .filter_map(|key| Some((key.clone(), unbrace(key)?.to_string())))
+        .filter(|(_, bare)| !map.contains_key(bare))
+        .collect();
+    apply_renames(map, &renames);
+    renames
+}
+
+fn apply_renames<V>(map: &mut HashMap<String, V>, renames: &BTreeMap<String, String>) {
+    for (from, to) in renames {
+        if let Some(value) = map.remove(from) {
+            map.insert(to.clone(), value);
+        }
+    }
+}
+
+/// Remove palettes whose contents match an earlier palette's, pointing the
+/// sprites that used them at the earlier one.
+///
+/// Palettes defined more than once under the same name are skipped, since
+/// which definition wins depends on order.
+fn dedupe_palettes(slots: &mut [Slot], fixes: &mut Vec<AppliedFix>) {
+    let mut name_counts: HashMap<&str, usize> = HashMap::new();
+    for slot in slots.iter() {
+        if let TtpObject::Palette(palette) = &slot.object {
+            *name_counts.entry(palette.name.as_str()).or_default() += 1;
+        }
+    }
+    let unique: HashSet<String> = name_counts
+        .into_iter()
+        .filter(|(name, count)| *count == 1 && !name.is_empty())
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    let mut kept: Vec<&Palette> = Vec::new();
+    let mut replaced: HashMap<String, String> = HashMap::new();
+    let mut removed = Vec::new();
+    for (index, slot) in slots.iter().enumerate() {
+        let TtpObject::Palette(palette) = &slot.object else { continue };
+        if !unique.contains(&palette.name) {
+            continue;
+        }
+        match kept.iter().find(|other| same_contents(other, palette)) {
+            Some(original) => {
+                replaced.insert(palette.name.clone(), original.name.clone());
+                removed.push(index);
+                fixes.push(AppliedFix {
+                    line: slot.line(),
+                    fix: DEDUPE_PALETTES,
+                    message: format!(
+                        "palette '{}': removed duplicate of '{}'",
+                        palette.name, original.name
+                    ),
+                });
+            }
+            None => kept.push(palette),
+        }
+    }
+
+    for index in removed {
+        slots[index].removed = true;
+    }
+    for slot in slots.iter_mut() {
+        if let TtpObject::Sprite(Sprite { palette: PaletteRef::Named(name), .. }) = &mut slot.object
+        {
+            if let Some(original) = replaced.get(name) {
+                *name = original.clone();
+                slot.changed = true;
+            }
+        }
+    }
+}
+
+/// Whether two palettes define the same colors, ramps, roles, relationships,
+/// and aliases.
+fn same_contents(a: &Palette, b: &Palette) -> bool {
+    a.colors == b.colors
+        && a.ramps == b.ramps
+        && a.roles == b.roles
+        && a.relationships == b.relationships
+        && a.aliases == b.aliases
+        && a.deprecated == b.deprecated
+}
+
+/// Replace inline sprite palettes with a reference to a named palette, reusing
+/// an identical palette where one exists and inserting a new one otherwise.
+fn extract_inline_palettes(slots: &mut [Slot], fixes: &mut Vec<AppliedFix>) {
+    let mut names: HashSet<String> = HashSet::new();
+    let mut available: Vec<(String, HashMap<String, String>)> = Vec::new();
+    for slot in slots.iter().filter(|slot| !slot.removed) {
+        match &slot.object {
+            TtpObject::Palette(palette) => {
+                names.insert(palette.name.clone());
+                if palette.ramps.is_none()
+                    && palette.roles.is_none()
+                    && palette.relationships.is_none()
+                    && palette.aliases.is_none()
+                    && palette.deprecated.is_none()
+                {
+                    available.push((palette.name.clone(), palette.colors.clone()));
+                }
+            }
+            TtpObject::Sprite(sprite) => {
+                names.insert(sprite.name.clone());
+            }
+            _ => {}
+        }
+    }
+    // A palette defined twice is ambiguous; don't reuse either definition
+    available.retain(|(name, _)| {
+        slots
+            .iter()
+            .filter(|s| matches!(&s.object, TtpObject::Palette(p) if &p.name == name))
+            .count()
+            == 1
+    });
+
+    for slot in slots.iter_mut() {
+        let line = slot.line();
+        let TtpObject::Sprite(sprite) = &mut slot.object else { continue };
+        let PaletteRef::Inline(colors) = &sprite.palette else { continue };
+
+        let message = match available.iter().find(|(_, other)| other == colors) {
+            Some((name, _)) => {
+                let message =
+                    format!("sprite '{}': uses identical palette '{}'", sprite.name, name);
+                sprite.palette = PaletteRef::Named(name.clone());
+                message
+            }
+            None => {
+                let name = unique_name(&format!("{}_palette", sprite.name), &names);
+                names.insert(name.clone());
+                available.push((name.clone(), colors.clone()));
+                slot.inserted.push(TtpObject::Palette(Palette {
+                    name: name.clone(),
+                    colors: colors.clone(),
+                    ..Default::default()
+                }));
+                let message =
+                    format!("sprite '{}': extracted inline palette into '{}'", sprite.name, name);
+                sprite.palette = PaletteRef::Named(name);
+                message
+            }
+        };
+        fixes.push(AppliedFix { line, fix: EXTRACT_INLINE_PALETTE, message });
+        slot.changed = true;
+    }
+}
+
+/// `base`, or `base_2`, `base_3`, ... if it is taken.
+fn unique_name(base: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(base) {
+        return base.to_string();
+    }
+    (2..).map(|n| format!("{base}_{n}")).find(|name| !taken.contains(name)).expect("unbounded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(lines: &[&str]) -> FixResult {
+        fix_str(&(lines.join("\n") + "\n")).unwrap()
+    }
+
+    fn objects(text: &str) -> Vec<TtpObject> {
+        parse_stream(Cursor::new(text)).objects
+    }
+
+    #[test]
+    fn test_clean_file_unchanged() {
+        let source = concat!(
+            "// comment\n",
+            r##"{"type": "palette", "name": "p", "colors": {"x": "#FF0000"}}"##,
+            "\n",
+            r#"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": "p", "regions": {"x": {"points": [[0, 0]]}}}"#,
+            "\n",
+        );
+        let result = fix_str(source).unwrap();
+        assert!(result.fixes.is_empty());
+        assert_eq!(result.text, source);
+    }
+
+    #[test]
+    fn test_extract_inline_palette() {
+        let result = fix(&[
+            r##"{"type": "palette", "name": "dot_palette", "colors": {"y": "#00FF00"}}"##,
+            r##"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}"##,
+            r##"{"type": "sprite", "name": "dot2", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}"##,
+        ]);
+        assert_eq!(result.fixes.len(), 2);
+        assert!(result.fixes[0].message.contains("'dot_palette_2'"));
+        assert!(result.fixes[1].message.contains("uses identical palette 'dot_palette_2'"));
+
+        let objects = objects(&result.text);
+        assert_eq!(objects.len(), 4);
+        let TtpObject::Palette(palette) = &objects[1] else { panic!("expected palette") };
+        assert_eq!(palette.name, "dot_palette_2");
+        assert_eq!(palette.colors["x"], "#FF0000");
+        for object in &objects[2..] {
+            let TtpObject::Sprite(sprite) = object else { panic!("expected sprite") };
+            assert_eq!(sprite.palette, PaletteRef::Named("dot_palette_2".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_dedupe_palettes() {
+        let result = fix(&[
+            r##"{"type": "palette", "name": "a", "colors": {"x": "#FF0000"}}"##,
+            "",
+            r##"{"type": "palette", "name": "b", "colors": {"x": "#FF0000"}}"##,
+            r#"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": "b", "regions": {"x": {"points": [[0, 0]]}}}"#,
+        ]);
+        assert_eq!(result.fixes.len(), 1);
+        assert_eq!(result.fixes[0].fix, DEDUPE_PALETTES);
+        assert_eq!(result.fixes[0].line, 3);
+
+        let lines: Vec<&str> = result.text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "");
+        assert!(lines[2].contains(r#""palette":"a""#));
+    }
+
+    #[test]
+    fn test_dedupe_skips_redefined_names() {
+        let result = fix(&[
+            r##"{"type": "palette", "name": "a", "colors": {"x": "#FF0000"}}"##,
+            r##"{"type": "palette", "name": "b", "colors": {"x": "#FF0000"}}"##,
+            r##"{"type": "palette", "name": "b", "colors": {"x": "#0000FF"}}"##,
+        ]);
+        assert!(result.fixes.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_tokens() {
+        let result = fix(&[
+            r##"{"type": "palette", "name": "p", "colors": {"{_}": "#00000000", "{body}": "#4488FF"}, "roles": {"{body}": "fill"}}"##,
+            r#"{"type": "sprite", "name": "s", "size": [1, 1], "palette": "p", "regions": {"body": {"points": [[0, 0]]}}}"#,
+            r##"{"type": "variant", "name": "v", "base": "s", "palette": {"{body}": "#FF0000"}}"##,
+        ]);
+        assert_eq!(result.fixes.len(), 2);
+        assert_eq!(result.fixes[0].message, "palette 'p': renamed {_} → _, {body} → body");
+
+        let objects = objects(&result.text);
+        let TtpObject::Palette(palette) = &objects[0] else { panic!("expected palette") };
+        assert_eq!(palette.colors["body"], "#4488FF");
+        assert!(palette.roles.as_ref().unwrap().contains_key("body"));
+        let TtpObject::Variant(variant) = &objects[2] else { panic!("expected variant") };
+        assert_eq!(variant.palette["body"], "#FF0000");
+    }
+
+    #[test]
+    fn test_normalize_keeps_braced_regions() {
+        let result = fix(&[
+            r##"{"type": "palette", "name": "p", "colors": {"{a}": "#FF0000", "{b}": "#00FF00", "c": "#0000FF", "{c}": "#000000"}}"##,
+            r#"{"type": "sprite", "name": "s", "size": [1, 1], "palette": "p", "regions": {"{a}": {"points": [[0, 0]]}}}"#,
+        ]);
+        assert_eq!(result.fixes.len(), 1);
+        assert_eq!(result.fixes[0].message, "palette 'p': renamed {b} → b");
+    }
+}
@@ -0,0 +1,474 @@
+//! Printable flip-book sheets for animations
+//!
+//! Frames are laid out as cards on paper-sized pages at a given DPI. Each
+//! card has a binding strip on its left edge, where the frame number is
+//! printed, and the frame scaled up by the largest whole factor that fits.
+//! Cut marks in the page margin line up with the card edges, so the sheet
+//! can be printed, cut into cards, stacked in order and bound.
+//!
+//! Pages are written as a PDF with one full-page image per sheet, or as
+//! PNGs. The PDF writer only needs what a page image uses: a catalog, a page
+//! tree, and per page a content stream drawing one Flate-compressed RGB
+//! image.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use image::{Rgba, RgbaImage};
+use thiserror::Error;
+
+use crate::output::OutputError;
+
+/// Page margin holding the cut marks, in millimetres
+const MARGIN_MM: f64 = 10.0;
+
+/// Paper the sheet is cut from
+const PAPER: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// Cut marks
+const MARK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// Frame numbers
+const LABEL: Rgba<u8> = Rgba([96, 96, 96, 255]);
+
+/// 3x5 digit glyphs, one row per byte with the high bit on the left
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b011, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Paper size of a flip-book sheet (portrait).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PaperSize {
+    /// 210 x 297 mm
+    #[default]
+    A4,
+    /// 148 x 210 mm
+    A5,
+    /// 8.5 x 11 in
+    Letter,
+}
+
+impl PaperSize {
+    /// Width and height in millimetres.
+    pub fn size_mm(self) -> (f64, f64) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::A5 => (148.0, 210.0),
+            PaperSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+/// Options for laying out a flip-book sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlipbookConfig {
+    /// Paper size of each page
+    pub paper: PaperSize,
+    /// Print resolution in dots per inch
+    pub dpi: u32,
+    /// Cards per row
+    pub columns: u32,
+}
+
+impl Default for FlipbookConfig {
+    fn default() -> Self {
+        Self { paper: PaperSize::A4, dpi: 300, columns: 2 }
+    }
+}
+
+/// Error laying out a flip-book.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FlipbookError {
+    /// There are no frames to print
+    #[error("no frames to lay out")]
+    NoFrames,
+    /// A frame doesn't fit a card even at 1x
+    #[error(
+        "{width}x{height} frames don't fit a {columns}-column {paper:?} page at {dpi} DPI; \
+         use fewer columns or a higher DPI"
+    )]
+    FrameTooLarge { width: u32, height: u32, paper: PaperSize, dpi: u32, columns: u32 },
+}
+
+/// Card grid of a flip-book page, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlipbookLayout {
+    /// Page width and height
+    pub page: (u32, u32),
+    /// Margin around the card grid
+    pub margin: u32,
+    /// Card width and height
+    pub card: (u32, u32),
+    /// Width of the binding strip on the left of each card
+    pub binding: u32,
+    /// Padding around the frame inside a card
+    pub padding: u32,
+    /// Cards per row
+    pub columns: u32,
+    /// Rows of cards per page
+    pub rows: u32,
+    /// Whole-number factor frames are scaled by
+    pub scale: u32,
+}
+
+impl FlipbookLayout {
+    /// Lay out cards for frames of `frame_size` (the largest frame).
+    pub fn new(frame_size: (u32, u32), config: &FlipbookConfig) -> Result<Self, FlipbookError> {
+        let too_large = FlipbookError::FrameTooLarge {
+            width: frame_size.0,
+            height: frame_size.1,
+            paper: config.paper,
+            dpi: config.dpi,
+            columns: config.columns,
+        };
+        let (width_mm, height_mm) = config.paper.size_mm();
+        let page = (mm_to_px(width_mm, config.dpi), mm_to_px(height_mm, config.dpi));
+        let margin = mm_to_px(MARGIN_MM, config.dpi);
+        let columns = config.columns.max(1);
+
+        let card_width = page.0.saturating_sub(2 * margin) / columns;
+        let binding = card_width / 4;
+        let padding = (card_width / 32).max(1);
+        let max_width = card_width.saturating_sub(binding + 2 * padding);
+        let max_height = page.1.saturating_sub(2 * margin + 2 * padding);
+        let scale = (max_width / frame_size.0.max(1)).min(max_height / frame_size.1.max(1));
+        if scale == 0 {
+            return Err(too_large);
+        }
+
+        let card = (card_width, frame_size.1 * scale + 2 * padding);
+        let rows = (page.1 - 2 * margin) / card.1;
+        Ok(Self { page, margin, card, binding, padding, columns, rows, scale })
+    }
+
+    /// Number of cards on a full page.
+    pub fn cards_per_page(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+}
+
+/// Lay out animation frames as flip-book pages.
+///
+/// Frames are flattened onto white paper; smaller frames are centered in
+/// cards sized for the largest one.
+pub fn render_flipbook(
+    frames: &[RgbaImage],
+    config: &FlipbookConfig,
+) -> Result<Vec<RgbaImage>, FlipbookError> {
+    if frames.is_empty() {
+        return Err(FlipbookError::NoFrames);
+    }
+    let frame_size = (
+        frames.iter().map(|f| f.width()).max().unwrap_or(1),
+        frames.iter().map(|f| f.height()).max().unwrap_or(1),
+    );
+    let layout = FlipbookLayout::new(frame_size, config)?;
+
+    let mut pages = Vec::new();
+    for (page_index, page_frames) in frames.chunks(layout.cards_per_page()).enumerate() {
+        let mut page = RgbaImage::from_pixel(layout.page.0, layout.page.1, PAPER);
+        for (i, frame) in page_frames.iter().enumerate() {
+            let col = i as u32 % layout.columns;
+            let row = i as u32 / layout.columns;
+            let card_x = layout.margin + col * layout.card.0;
+            let card_y = layout.margin + row * layout.card.1;
+
+            // Frame centered in the area right of the binding strip
+            let area_x = card_x + layout.binding + layout.padding;
+            let area_width = layout.card.0 - layout.binding - 2 * layout.padding;
+            let x = area_x + (area_width - frame.width() * layout.scale) / 2;
+            let y = card_y + layout.padding + (frame_size.1 - frame.height()) * layout.scale / 2;
+            draw_scaled(&mut page, frame, x, y, layout.scale);
+
+            let number = page_index * layout.cards_per_page() + i + 1;
+            let digit_size = (layout.binding / 16).max(1);
+            draw_number(
+                &mut page,
+                number,
+                card_x + layout.padding,
+                card_y + layout.padding,
+                digit_size,
+            );
+        }
+        let rows_used = (page_frames.len() as u32).div_ceil(layout.columns);
+        draw_cut_marks(&mut page, &layout, rows_used, (config.dpi / 150).max(1));
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+/// Encode pages as a PDF, each page sized from its pixels at `dpi`.
+pub fn encode_pdf(pages: &[RgbaImage], dpi: u32, title: &str) -> Result<Vec<u8>, OutputError> {
+    let mut pdf = PdfWriter::default();
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 3 + 3 * i).collect();
+    pdf.object(b"<< /Type /Catalog /Pages 2 0 R >>");
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+    pdf.object(
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).as_bytes(),
+    );
+
+    for (page, &id) in pages.iter().zip(&page_ids) {
+        let (width, height) = page.dimensions();
+        let points = |px: u32| px as f64 * 72.0 / dpi.max(1) as f64;
+        let (w, h) = (points(width), points(height));
+        pdf.object(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+                w,
+                h,
+                id + 2,
+                id + 1
+            )
+            .as_bytes(),
+        );
+        pdf.stream("", format!("q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q", w, h).as_bytes());
+
+        let rgb: Vec<u8> = page.pixels().flat_map(|p| [p[0], p[1], p[2]]).collect();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&rgb)?;
+        let dict = format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+             /BitsPerComponent 8 /Filter /FlateDecode ",
+            width, height
+        );
+        pdf.stream(&dict, &encoder.finish()?);
+    }
+
+    let info = format!(
+        "<< /Title ({}) /Producer (pixelsrc {}) >>",
+        escape_pdf_string(title),
+        env!("CARGO_PKG_VERSION")
+    );
+    pdf.object(info.as_bytes());
+    Ok(pdf.finish())
+}
+
+/// Save pages as a PDF file.
+pub fn save_pdf(
+    pages: &[RgbaImage],
+    dpi: u32,
+    title: &str,
+    path: &Path,
+) -> Result<(), OutputError> {
+    let bytes = encode_pdf(pages, dpi, title)?;
+    // Create parent directories if they don't exist
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+fn mm_to_px(mm: f64, dpi: u32) -> u32 {
+    (mm / 25.4 * dpi as f64).round() as u32
+}
+
+/// Draw `frame` scaled by `scale` at (x, y), alpha-blended over the page
+fn draw_scaled(page: &mut RgbaImage, frame: &RgbaImage, x: u32, y: u32, scale: u32) {
+    for (fx, fy, pixel) in frame.enumerate_pixels() {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 {
+            continue;
+        }
+        for dy in 0..scale {
+            for dx in 0..scale {
+                let (px, py) = (x + fx * scale + dx, y + fy * scale + dy);
+                let under = page.get_pixel_mut(px, py);
+                for c in 0..3 {
+                    under[c] =
+                        ((pixel[c] as u32 * alpha + under[c] as u32 * (255 - alpha)) / 255) as u8;
+                }
+            }
+        }
+    }
+}
+
+fn fill_rect(page: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    let x_end = (x + width).min(page.width());
+    let y_end = (y + height).min(page.height());
+    for py in y..y_end {
+        for px in x..x_end {
+            page.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// Draw a number with the 3x5 digit font, each font pixel `size` wide
+pub(crate) fn draw_number(page: &mut RgbaImage, number: usize, x: u32, y: u32, size: u32) {
+    for (i, digit) in number.to_string().bytes().enumerate() {
+        let glyph = DIGITS[(digit - b'0') as usize];
+        let glyph_x = x + i as u32 * 4 * size;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    let px = glyph_x + col * size;
+                    fill_rect(page, px, y + row as u32 * size, size, size, LABEL);
+                }
+            }
+        }
+    }
+}
+
+/// Ticks in the margin along every card edge, stopping short of the cards
+fn draw_cut_marks(page: &mut RgbaImage, layout: &FlipbookLayout, rows: u32, thickness: u32) {
+    let gap = layout.margin / 3;
+    let length = layout.margin - gap;
+    let grid_right = layout.margin + layout.columns * layout.card.0;
+    let grid_bottom = layout.margin + rows * layout.card.1;
+
+    for col in 0..=layout.columns {
+        let x = (layout.margin + col * layout.card.0).saturating_sub(thickness / 2);
+        fill_rect(page, x, 0, thickness, length, MARK);
+        fill_rect(page, x, grid_bottom + gap, thickness, length, MARK);
+    }
+    for row in 0..=rows {
+        let y = (layout.margin + row * layout.card.1).saturating_sub(thickness / 2);
+        fill_rect(page, 0, y, length, thickness, MARK);
+        fill_rect(page, grid_right + gap, y, length, thickness, MARK);
+    }
+}
+
+fn escape_pdf_string(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_ascii() && !c.is_ascii_control())
+        .flat_map(|c| match c {
+            '(' | ')' | '\\' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Numbered PDF objects with the byte offsets the cross-reference table needs
+struct PdfWriter {
+    bytes: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Default for PdfWriter {
+    fn default() -> Self {
+        // The binary comment marks the file as binary for transfer tools
+        Self { bytes: b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec(), offsets: Vec::new() }
+    }
+}
+
+impl PdfWriter {
+    fn object(&mut self, body: &[u8]) {
+        self.offsets.push(self.bytes.len());
+        self.bytes.extend_from_slice(format!("{} 0 obj\n", self.offsets.len()).as_bytes());
+        self.bytes.extend_from_slice(body);
+        self.bytes.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, dict: &str, data: &[u8]) {
+        let mut body = format!("<< {}/Length {} >>\nstream\n", dict, data.len()).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\nendstream");
+        self.object(&body);
+    }
+
+    /// Write the cross-reference table and trailer; the last object is the info dictionary
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.bytes.len();
+        let count = self.offsets.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", count + 1);
+        for offset in &self.offsets {
+            table.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            count + 1,
+            count,
+            xref
+        ));
+        self.bytes.extend_from_slice(table.as_bytes());
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(count: usize) -> Vec<RgbaImage> {
+        (0..count).map(|_| RgbaImage::from_pixel(16, 16, Rgba([255, 0, 0, 255]))).collect()
+    }
+
+    #[test]
+    fn test_layout_fits_cards_on_page() {
+        let layout = FlipbookLayout::new((16, 16), &FlipbookConfig::default()).unwrap();
+        assert_eq!(layout.page, (2480, 3508));
+        assert_eq!(layout.margin, 118);
+        assert_eq!(layout.card.0, 1122);
+        assert!(layout.rows * layout.card.1 <= layout.page.1 - 2 * layout.margin);
+        assert!(layout.binding + 16 * layout.scale + 2 * layout.padding <= layout.card.0);
+
+        let config = FlipbookConfig { dpi: 10, ..Default::default() };
+        assert!(matches!(
+            FlipbookLayout::new((64, 64), &config),
+            Err(FlipbookError::FrameTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_render_flipbook_pages() {
+        let config = FlipbookConfig { paper: PaperSize::A5, dpi: 100, columns: 2 };
+        let layout = FlipbookLayout::new((16, 16), &config).unwrap();
+        let count = layout.cards_per_page() + 1;
+        let pages = render_flipbook(&frames(count), &config).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].dimensions(), layout.page);
+
+        // First card: number in the binding strip, frame to its right
+        let page = &pages[0];
+        let (x, y) = (layout.margin + layout.padding, layout.margin + layout.padding);
+        // The top row of "1" is its middle column
+        assert_eq!(*page.get_pixel(x + layout.binding / 16, y), LABEL);
+        let frame_y = layout.margin + layout.card.1 / 2;
+        let frame_x = layout.margin + layout.binding + layout.card.0 / 3;
+        assert_eq!(*page.get_pixel(frame_x, frame_y), Rgba([255, 0, 0, 255]));
+
+        // Cut marks sit in the margin, not on the cards
+        assert_eq!(*page.get_pixel(layout.margin, 0), MARK);
+        assert_eq!(*page.get_pixel(0, layout.margin), MARK);
+        assert_eq!(*page.get_pixel(layout.margin, layout.margin - 1), PAPER);
+
+        assert_eq!(render_flipbook(&[], &config), Err(FlipbookError::NoFrames));
+    }
+
+    #[test]
+    fn test_encode_pdf_structure() {
+        let pages = vec![RgbaImage::from_pixel(300, 150, PAPER); 2];
+        let pdf = encode_pdf(&pages, 150, "walk (loop)").unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/MediaBox [0 0 144.00 72.00]"));
+        assert!(text.contains("/Title (walk \\(loop\\))"));
+        assert!(text.ends_with("%%EOF\n"));
+
+        // Every cross-reference entry points at its object
+        let startxref = text.rfind("startxref\n").unwrap() + "startxref\n".len();
+        let xref: usize = text[startxref..].lines().next().unwrap().parse().unwrap();
+        let table = std::str::from_utf8(&pdf[xref..]).unwrap();
+        for (i, line) in table.lines().skip(3).take(8).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            let object = format!("{} 0 obj", i + 1);
+            assert!(pdf[offset..].starts_with(object.as_bytes()), "object {}", i + 1);
+        }
+    }
+}
@@ -6,8 +6,9 @@
 //! - Keeping palettes, animations, and variants as single-line JSON
 
 use crate::models::{
-    Animation, Composition, CompositionLayer, Palette, PaletteRef, Particle, Sprite, TtpObject,
-    Variant,
+    Animation, AutoSort, Composition, CompositionLayer, LayerGroup, LocaleOverride, Palette,
+    PaletteLock, PaletteRef, Parallax, Particle, PlaybackDirection, Repeat, Sprite, SpriteRef,
+    TtpObject, Variant,
 };
 use std::collections::HashMap;
 use std::io::Cursor;
@@ -21,6 +22,16 @@ use std::io::Cursor;
 ///
 /// Returns the formatted content with blank lines between objects.
 pub fn format_pixelsrc(content: &str) -> Result<String, String> {
+    format_objects(content, false)
+}
+
+/// Format like [`format_pixelsrc`], also rewriting delimited composition maps
+/// to single characters (see [`apply_legend`]).
+pub fn format_pixelsrc_with_legend(content: &str) -> Result<String, String> {
+    format_objects(content, true)
+}
+
+fn format_objects(content: &str, legend: bool) -> Result<String, String> {
     let reader = Cursor::new(content);
     let deserializer = serde_json::Deserializer::from_reader(reader);
     let iterator = deserializer.into_iter::<TtpObject>();
@@ -30,7 +41,10 @@ pub fn format_pixelsrc(content: &str) -> Result<String, String> {
 
     for item in iterator {
         match item {
-            Ok(obj) => {
+            Ok(mut obj) => {
+                if let (true, TtpObject::Composition(comp)) = (legend, &mut obj) {
+                    apply_legend(comp)?;
+                }
                 if !first {
                     output.push('\n'); // Blank line between objects
                 }
@@ -50,6 +64,105 @@ pub fn format_pixelsrc(content: &str) -> Result<String, String> {
     Ok(output)
 }
 
+/// Characters assigned to map cells whose own first letter is taken
+const LEGEND_CHARS: &str =
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789#@%&*+=-~^!?$<>()[]{}:;,/|'`";
+
+/// Rewrite a composition's delimited maps (`map_delimiter`) to one character
+/// per cell, writing the assigned characters into `sprites`.
+///
+/// Single-character keys keep their character; longer keys get their first
+/// letter if it's free, then the first free character of a fixed set. Cells
+/// that name sprites directly get an explicit entry. Sprite map entries no
+/// map uses are dropped. Compositions without `map_delimiter` are unchanged.
+pub fn apply_legend(comp: &mut Composition) -> Result<(), String> {
+    if comp.map_delimiter.is_none() {
+        return Ok(());
+    }
+    let source = comp.clone();
+
+    let mut keys: Vec<&str> = Vec::new();
+    for row in source.layers.iter().flat_map(|layer| layer.map.iter().flatten()) {
+        for key in source.map_cells(row) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    // Single characters first, so longer keys can't take them
+    let mut legend: HashMap<&str, char> = HashMap::new();
+    let mut used: Vec<char> = Vec::new();
+    let chars = |key: &str| key.chars().next().filter(|_| key.chars().count() == 1);
+    for &key in &keys {
+        if let Some(c) = chars(key).filter(|c| !used.contains(c)) {
+            legend.insert(key, c);
+            used.push(c);
+        }
+    }
+    for &key in &keys {
+        if legend.contains_key(key) {
+            continue;
+        }
+        let empty = matches!(source.cell_sprite(key), Some(None));
+        let first = key.chars().next().filter(|c| !c.is_whitespace() && *c != '"' && *c != '\\');
+        let preferred = empty.then_some('.').into_iter().chain(first).chain(first.map(|c| {
+            if c.is_lowercase() {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            }
+        }));
+        let Some(c) = preferred.chain(LEGEND_CHARS.chars()).find(|c| !used.contains(c)) else {
+            return Err(format!(
+                "Composition '{}' has {} distinct map cells, too many for single characters",
+                source.name,
+                keys.len()
+            ));
+        };
+        legend.insert(key, c);
+        used.push(c);
+    }
+
+    let sprites: HashMap<String, Option<SpriteRef>> = keys
+        .iter()
+        .map(|&key| {
+            let entry = source.cell_sprite(key).flatten().map(|entry| entry.into_owned());
+            (legend[key].to_string(), entry)
+        })
+        .collect();
+    let rename = |key: &str| legend.get(key).map_or_else(|| key.to_string(), |c| c.to_string());
+    let locale: HashMap<String, LocaleOverride> = source
+        .locale
+        .iter()
+        .map(|(name, over)| {
+            let sprites = over.sprites.iter().map(|(k, v)| (rename(k), v.clone())).collect();
+            (name.clone(), LocaleOverride { sprites, ..over.clone() })
+        })
+        .collect();
+    let layers: Vec<Option<Vec<String>>> = comp
+        .layers
+        .iter()
+        .map(|layer| {
+            layer.map.as_ref().map(|map| {
+                map.iter()
+                    .map(|row| source.map_cells(row).into_iter().map(|k| legend[k]).collect())
+                    .collect()
+            })
+        })
+        .collect();
+    for (layer, map) in comp.layers.iter_mut().zip(layers) {
+        layer.map = map;
+        if let Some(fill) = &layer.fill {
+            layer.fill = Some(rename(fill));
+        }
+    }
+    comp.sprites = sprites;
+    comp.locale = locale;
+    comp.map_delimiter = None;
+    Ok(())
+}
+
 /// Format a single TtpObject.
 fn format_object(obj: &TtpObject) -> String {
     match obj {
@@ -57,11 +170,15 @@ fn format_object(obj: &TtpObject) -> String {
         TtpObject::Sprite(s) => format_sprite(s),
         TtpObject::Composition(c) => format_composition(c),
         TtpObject::Animation(a) => format_animation(a),
+        TtpObject::Sequence(s) => format_sequence(s),
         TtpObject::Variant(v) => format_variant(v),
         TtpObject::Particle(p) => format_particle(p),
         TtpObject::Transform(t) => format_transform(t),
         TtpObject::StateRules(sr) => format_state_rules(sr),
         TtpObject::Import(i) => format_import(i),
+        TtpObject::Theme(t) => format_theme(t),
+        TtpObject::Stamp(s) => format_stamp(s),
+        TtpObject::RandomSet(r) => format_random_set(r),
     }
 }
 
@@ -87,10 +204,28 @@ fn format_palette(palette: &Palette) -> String {
         s.push('"');
     }
 
-    s.push_str("}}");
+    s.push('}');
+    s.push_str(&format_token_map("aliases", palette.aliases.as_ref()));
+    s.push_str(&format_token_map("deprecated", palette.deprecated.as_ref()));
+    s.push('}');
     s
 }
 
+/// Format a palette's token-to-token map as a field, sorted by key, or
+/// nothing if absent.
+fn format_token_map(field: &str, map: Option<&HashMap<String, String>>) -> String {
+    let Some(map) = map else {
+        return String::new();
+    };
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort();
+    let entries: Vec<String> = entries
+        .iter()
+        .map(|(k, v)| format!(r#""{}": "{}""#, escape_json_string(k), escape_json_string(v)))
+        .collect();
+    format!(r#", "{}": {{{}}}"#, field, entries.join(", "))
+}
+
 /// Format a sprite with visual grid (one row per line).
 fn format_sprite(sprite: &Sprite) -> String {
     let mut s = String::new();
@@ -99,37 +234,96 @@ fn format_sprite(sprite: &Sprite) -> String {
     s.push_str(r#"{"type": "sprite", "name": ""#);
     s.push_str(&escape_json_string(&sprite.name));
     s.push('"');
+    s.push_str(&format_labels(&sprite.labels));
+
+    // Template (if present)
+    if let Some(extends) = &sprite.extends {
+        s.push_str(r#", "extends": ""#);
+        s.push_str(&escape_json_string(extends));
+        s.push('"');
+    }
 
     // Size (if present)
     if let Some([w, h]) = sprite.size {
         s.push_str(&format!(r#", "size": [{}, {}]"#, w, h));
     }
 
-    // Palette reference
-    s.push_str(r#", "palette": "#);
+    // Palette reference (a template may supply it)
     match &sprite.palette {
+        _ if sprite.palette_unset() => {}
         PaletteRef::Named(name) => {
+            s.push_str(r#", "palette": "#);
             s.push('"');
             s.push_str(&escape_json_string(name));
             s.push('"');
         }
         PaletteRef::Inline(colors) => {
+            s.push_str(r#", "palette": "#);
             s.push_str(&format_inline_palette(colors));
         }
     }
 
+    s.push_str(&format_opacity(sprite.opacity));
+
     // Regions (if present)
     if let Some(regions) = &sprite.regions {
         s.push_str(r#", "regions": "#);
-        if let Ok(json) = serde_json::to_string(regions) {
+        if let Ok(json) = serde_json::to_string(&crate::ordered::Sorted(regions)) {
+            s.push_str(&json);
+        }
+    }
+
+    // Stamp placements (if present)
+    if let Some(stamps) = &sprite.stamps {
+        if let Ok(json) = serde_json::to_string(stamps) {
+            s.push_str(r#", "stamps": "#);
             s.push_str(&json);
         }
     }
 
+    // Reference image (if present)
+    if let Some(reference) = &sprite.reference {
+        if let Ok(json) = serde_json::to_string(reference) {
+            s.push_str(r#", "reference": "#);
+            s.push_str(&json);
+        }
+    }
+
+    s.push_str(&format_locale(&sprite.locale));
     s.push('}');
     s
 }
 
+/// Format an `"opacity"` field (empty if unset).
+fn format_opacity(opacity: Option<f64>) -> String {
+    match opacity {
+        Some(opacity) => format!(r#", "opacity": {}"#, serde_json::Value::from(opacity)),
+        None => String::new(),
+    }
+}
+
+/// Format selection labels as a `"labels"` field (empty if none).
+fn format_labels(labels: &[String]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> =
+        labels.iter().map(|l| format!(r#""{}""#, escape_json_string(l))).collect();
+    format!(r#", "labels": [{}]"#, labels.join(", "))
+}
+
+/// Format locale overrides as a trailing `"locale"` field (empty if none).
+fn format_locale(locale: &HashMap<String, LocaleOverride>) -> String {
+    if locale.is_empty() {
+        return String::new();
+    }
+    // Round-trip through Value so keys come out sorted
+    match serde_json::to_value(locale) {
+        Ok(value) => format!(r#", "locale": {}"#, value),
+        Err(_) => String::new(),
+    }
+}
+
 /// Format an inline palette as JSON object.
 fn format_inline_palette(colors: &HashMap<String, String>) -> String {
     let mut sorted: Vec<_> = colors.iter().collect();
@@ -158,6 +352,7 @@ fn format_composition(comp: &Composition) -> String {
     s.push_str(r#"{"type": "composition", "name": ""#);
     s.push_str(&escape_json_string(&comp.name));
     s.push('"');
+    s.push_str(&format_labels(&comp.labels));
 
     // Base sprite (if present)
     if let Some(ref base) = comp.base {
@@ -176,6 +371,49 @@ fn format_composition(comp: &Composition) -> String {
         s.push_str(&format!(r#", "cell_size": [{}, {}]"#, w, h));
     }
 
+    // Depth sorting (if set)
+    if let Some(AutoSort::Y) = comp.auto_sort {
+        s.push_str(r#", "auto_sort": "y""#);
+    }
+
+    // Cell delimiter (if set)
+    if let Some(ref delimiter) = comp.map_delimiter {
+        s.push_str(r#", "map_delimiter": ""#);
+        s.push_str(&escape_json_string(delimiter));
+        s.push('"');
+    }
+
+    // Camera position (if set)
+    if let Some([x, y]) = comp.camera {
+        s.push_str(&format!(r#", "camera": [{}, {}]"#, x, y));
+    }
+
+    // Palette lock (if set)
+    match comp.palette_lock {
+        Some(PaletteLock::Enabled(enabled)) => {
+            s.push_str(&format!(r#", "palette_lock": {}"#, enabled));
+        }
+        Some(PaletteLock::Palette(ref palette)) => {
+            s.push_str(r#", "palette_lock": ""#);
+            s.push_str(&escape_json_string(palette));
+            s.push('"');
+        }
+        None => {}
+    }
+
+    // Lighting (if set)
+    if let Some(ref ambient) = comp.ambient {
+        s.push_str(r#", "ambient": ""#);
+        s.push_str(&escape_json_string(ambient));
+        s.push('"');
+    }
+    if !comp.lights.is_empty() {
+        if let Ok(json) = serde_json::to_string(&comp.lights) {
+            s.push_str(r#", "lights": "#);
+            s.push_str(&json);
+        }
+    }
+
     // Sprites map
     s.push_str(r#", "sprites": {"#);
     let mut sprites: Vec<_> = comp.sprites.iter().collect();
@@ -188,11 +426,14 @@ fn format_composition(comp: &Composition) -> String {
         s.push_str(&escape_json_string(key));
         s.push_str(r#"": "#);
         match value {
-            Some(name) => {
+            Some(SpriteRef::Name(name)) => {
                 s.push('"');
                 s.push_str(&escape_json_string(name));
                 s.push('"');
             }
+            Some(SpriteRef::Instance(inst)) => {
+                s.push_str(&serde_json::to_string(inst).unwrap_or_else(|_| "null".to_string()))
+            }
             None => s.push_str("null"),
         }
     }
@@ -215,6 +456,15 @@ fn format_composition(comp: &Composition) -> String {
         s.push(']');
     }
 
+    // Layer groups (if any)
+    if !comp.groups.is_empty() {
+        let groups: Vec<String> = comp.groups.iter().map(format_layer_group).collect();
+        s.push_str(r#", "groups": ["#);
+        s.push_str(&groups.join(", "));
+        s.push(']');
+    }
+
+    s.push_str(&format_locale(&comp.locale));
     s.push('}');
     s
 }
@@ -269,6 +519,116 @@ fn format_layer(layer: &CompositionLayer) -> String {
             }
             s.push_str("  ]");
         }
+        first = false;
+    }
+
+    // Group membership
+    if let Some(ref group) = layer.group {
+        if !first {
+            s.push_str(", ");
+        }
+        s.push_str(r#""group": ""#);
+        s.push_str(&escape_json_string(group));
+        s.push('"');
+        first = false;
+    }
+
+    // Depth
+    if let Some(z) = layer.z {
+        if !first {
+            s.push_str(", ");
+        }
+        s.push_str(&format!(r#""z": {}"#, z));
+        first = false;
+    }
+
+    if layer.hidden {
+        if !first {
+            s.push_str(", ");
+        }
+        s.push_str(r#""hidden": true"#);
+        first = false;
+    }
+
+    // Mask
+    if let Some(ref mask) = layer.mask {
+        if !first {
+            s.push_str(", ");
+        }
+        s.push_str(r#""mask": ""#);
+        s.push_str(&escape_json_string(mask));
+        s.push('"');
+        if layer.mask_invert {
+            s.push_str(r#", "mask_invert": true"#);
+        }
+        first = false;
+    }
+
+    // Contact shadow
+    if let Some(ref shadow) = layer.contact_shadow {
+        if let Ok(json) = serde_json::to_string(shadow) {
+            if !first {
+                s.push_str(", ");
+            }
+            s.push_str(r#""contact_shadow": "#);
+            s.push_str(&json);
+            first = false;
+        }
+    }
+
+    // Camera and tiling
+    if let Some(parallax) = layer.parallax {
+        if !first {
+            s.push_str(", ");
+        }
+        match parallax {
+            Parallax::Uniform(factor) => s.push_str(&format!(r#""parallax": {}"#, factor)),
+            Parallax::Axes([x, y]) => s.push_str(&format!(r#""parallax": [{}, {}]"#, x, y)),
+        }
+        first = false;
+    }
+    if let Some(repeat) = layer.repeat {
+        if !first {
+            s.push_str(", ");
+        }
+        let axes = match repeat {
+            Repeat::X => "x",
+            Repeat::Y => "y",
+            Repeat::Both => "both",
+        };
+        s.push_str(&format!(r#""repeat": "{}""#, axes));
+        first = false;
+    }
+    if let Some([x, y]) = layer.scroll {
+        if !first {
+            s.push_str(", ");
+        }
+        s.push_str(&format!(r#""scroll": [{}, {}]"#, x, y));
+    }
+
+    s.push('}');
+    s
+}
+
+/// Format a composition layer group as single-line JSON.
+fn format_layer_group(group: &LayerGroup) -> String {
+    let mut s = String::from(r#"{"name": ""#);
+    s.push_str(&escape_json_string(&group.name));
+    s.push('"');
+
+    if let Some(ref blend) = group.blend {
+        s.push_str(r#", "blend": ""#);
+        s.push_str(&escape_json_string(blend));
+        s.push('"');
+    }
+
+    if let Some(ref opacity) = group.opacity {
+        let value = serde_json::to_string(opacity).unwrap_or_default();
+        s.push_str(&format!(r#", "opacity": {}"#, value));
+    }
+
+    if group.hidden {
+        s.push_str(r#", "hidden": true"#);
     }
 
     s.push('}');
@@ -281,23 +641,42 @@ fn format_animation(anim: &Animation) -> String {
 
     s.push_str(r#"{"type": "animation", "name": ""#);
     s.push_str(&escape_json_string(&anim.name));
-    s.push_str(r#"", "frames": ["#);
-
-    for (i, frame) in anim.frames.iter().enumerate() {
-        if i > 0 {
-            s.push_str(", ");
+    s.push('"');
+    s.push_str(&format_labels(&anim.labels));
+
+    // Motion preset replaces the frame list
+    match &anim.motion {
+        Some(motion) if anim.frames.is_empty() => {
+            if let Ok(json) = serde_json::to_string(motion) {
+                s.push_str(r#", "motion": "#);
+                s.push_str(&json);
+            }
+        }
+        _ => {
+            s.push_str(r#", "frames": ["#);
+            for (i, frame) in anim.frames.iter().enumerate() {
+                if i > 0 {
+                    s.push_str(", ");
+                }
+                s.push('"');
+                s.push_str(&escape_json_string(frame));
+                s.push('"');
+            }
+            s.push(']');
         }
-        s.push('"');
-        s.push_str(&escape_json_string(frame));
-        s.push('"');
     }
-    s.push(']');
 
     // Duration (if specified)
     if let Some(ref duration) = anim.duration {
         s.push_str(&format!(r#", "duration": {}"#, duration));
     }
 
+    // Per-frame durations (if specified)
+    if let Some(ref durations) = anim.durations {
+        let values: Vec<String> = durations.iter().map(|d| d.to_string()).collect();
+        s.push_str(&format!(r#", "durations": [{}]"#, values.join(", ")));
+    }
+
     // Loop (if specified as false - true is default)
     if let Some(loops) = anim.r#loop {
         if !loops {
@@ -305,6 +684,11 @@ fn format_animation(anim: &Animation) -> String {
         }
     }
 
+    // Direction (if not the default forward)
+    if let Some(direction) = anim.direction.filter(|d| *d != PlaybackDirection::Forward) {
+        s.push_str(&format!(r#", "direction": "{}""#, direction.as_str()));
+    }
+
     s.push('}');
     s
 }
@@ -333,7 +717,19 @@ fn format_variant(variant: &Variant) -> String {
         s.push('"');
     }
 
-    s.push_str("}}");
+    s.push('}');
+
+    // Region patches, with keys sorted
+    if !variant.regions.is_empty() {
+        if let Ok(value) = serde_json::to_value(&variant.regions) {
+            s.push_str(r#", "regions": "#);
+            s.push_str(&value.to_string());
+        }
+    }
+
+    s.push_str(&format_opacity(variant.opacity));
+    s.push_str(&format_locale(&variant.locale));
+    s.push('}');
     s
 }
 
@@ -393,12 +789,36 @@ fn format_transform(transform: &crate::models::TransformDef) -> String {
     })
 }
 
+fn format_sequence(sequence: &crate::models::Sequence) -> String {
+    serde_json::to_string(&TtpObject::Sequence(sequence.clone())).unwrap_or_else(|_| {
+        format!(r#"{{"type": "sequence", "name": "{}"}}"#, escape_json_string(&sequence.name))
+    })
+}
+
 fn format_import(import: &crate::models::Import) -> String {
     serde_json::to_string(&crate::models::TtpObject::Import(import.clone())).unwrap_or_else(|_| {
         format!(r#"{{"type": "import", "from": "{}"}}"#, escape_json_string(&import.from))
     })
 }
 
+fn format_theme(theme: &crate::models::Theme) -> String {
+    serde_json::to_string(&TtpObject::Theme(theme.clone())).unwrap_or_else(|_| {
+        format!(r#"{{"type": "theme", "name": "{}"}}"#, escape_json_string(&theme.name))
+    })
+}
+
+fn format_stamp(stamp: &crate::models::Stamp) -> String {
+    serde_json::to_string(&TtpObject::Stamp(stamp.clone())).unwrap_or_else(|_| {
+        format!(r#"{{"type": "stamp", "name": "{}"}}"#, escape_json_string(&stamp.name))
+    })
+}
+
+fn format_random_set(set: &crate::models::RandomSet) -> String {
+    serde_json::to_string(&TtpObject::RandomSet(set.clone())).unwrap_or_else(|_| {
+        format!(r#"{{"type": "random_set", "name": "{}"}}"#, escape_json_string(&set.name))
+    })
+}
+
 fn format_state_rules(state_rules: &crate::state::StateRules) -> String {
     // Use serde_json for simplicity since StateRules has nested structures
     serde_json::to_string(state_rules).unwrap_or_else(|_| {
@@ -477,6 +897,17 @@ mod tests {
         assert!(formatted.contains(r#""type": "sprite""#));
     }
 
+    #[test]
+    fn test_format_sprite_extends() {
+        let sprite = Sprite {
+            name: "knight".to_string(),
+            extends: Some("humanoid".to_string()),
+            ..Default::default()
+        };
+        let formatted = format_sprite(&sprite);
+        assert_eq!(formatted, r#"{"type": "sprite", "name": "knight", "extends": "humanoid"}"#);
+    }
+
     #[test]
     fn test_format_animation_single_line() {
         let anim = Animation {
@@ -508,6 +939,13 @@ mod tests {
         assert!(!formatted.contains('\n'));
         assert!(formatted.contains(r#""type": "variant""#));
         assert!(formatted.contains(r#""base": "hero""#));
+        assert!(!formatted.contains("opacity"));
+
+        let ghost = Variant { opacity: Some(0.5), ..variant.clone() };
+        assert!(format_variant(&ghost).contains(r#""opacity": 0.5"#));
+
+        let patched = Variant { regions: HashMap::from([("hair".to_string(), None)]), ..variant };
+        assert!(format_variant(&patched).contains(r#""regions": {"hair":null}"#));
     }
 
     #[test]
@@ -525,6 +963,154 @@ mod tests {
         assert_eq!(objects.len(), 2);
     }
 
+    #[test]
+    fn test_format_keeps_locale() {
+        let input = r#"{"type": "variant", "name": "title", "base": "title_en", "palette": {}, "locale": {"ja": {"sprite": "title_ja"}}}
+{"type": "composition", "name": "menu", "sprites": {"T": "title"}, "layers": [], "locale": {"ja": {"sprites": {"T": "title_ja"}}}}"#;
+
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(formatted.contains(r#""locale": {"ja":{"sprite":"title_ja"}}"#), "{}", formatted);
+
+        let reader = Cursor::new(&formatted);
+        let deserializer = serde_json::Deserializer::from_reader(reader);
+        let objects: Vec<TtpObject> = deserializer.into_iter().map(|r| r.unwrap()).collect();
+        match &objects[1] {
+            TtpObject::Composition(comp) => {
+                assert_eq!(comp.locale["ja"].sprites["T"].as_deref(), Some("title_ja"));
+            }
+            other => panic!("expected composition, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_format_keeps_labels() {
+        let input = r#"{"type": "sprite", "name": "hero", "labels": ["ui", "player"], "palette": {}}
+{"type": "animation", "name": "walk", "labels": ["player"], "frames": ["hero"]}"#;
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(
+            formatted.contains(r#""name": "hero", "labels": ["ui", "player"]"#),
+            "{}",
+            formatted
+        );
+        assert!(formatted.contains(r#""name": "walk", "labels": ["player"], "frames""#));
+    }
+
+    #[test]
+    fn test_format_keeps_palette_aliases() {
+        let input = r##"{"type": "palette", "name": "p", "colors": {"skin": "#E8B89D"}, "deprecated": {"flesh": "skin"}, "aliases": {"skn": "skin", "face": "skin"}}"##;
+        let formatted = format_pixelsrc(input).unwrap();
+        assert_eq!(
+            formatted.trim_end(),
+            r##"{"type": "palette", "name": "p", "colors": {"skin": "#E8B89D"}, "aliases": {"face": "skin", "skn": "skin"}, "deprecated": {"flesh": "skin"}}"##
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_motion() {
+        let input = r#"{"type": "animation", "name": "hop", "motion": {"preset": "bounce", "sprite": "ball", "amplitude": 6}, "duration": 600}"#;
+        let formatted = format_pixelsrc(input).unwrap();
+        assert_eq!(
+            formatted.trim_end(),
+            r#"{"type": "animation", "name": "hop", "motion": {"preset":"bounce","sprite":"ball","amplitude":6.0}, "duration": 600}"#
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_reference() {
+        let input = r#"{"type": "sprite", "name": "hero", "size": [2, 2], "palette": {}, "reference": {"path": "hero.png"}}"#;
+
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(
+            formatted.contains(r#""reference": {"path":"hero.png","opacity":0.3}"#),
+            "{}",
+            formatted
+        );
+        assert_eq!(format_pixelsrc(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_format_keeps_stamps() {
+        let input = r#"{"type": "stamp", "name": "leaf", "grid": [".g", "gg"]}
+{"type": "sprite", "name": "bush", "size": [8, 8], "palette": {}, "stamps": [{"name": "leaf", "at": [2, 3], "rotate": 90}]}"#;
+
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(formatted.contains(r#"{"type":"stamp","name":"leaf","grid":[".g","gg"]}"#));
+        assert!(
+            formatted.contains(r#""stamps": [{"name":"leaf","at":[2,3],"rotate":90}]"#),
+            "{}",
+            formatted
+        );
+        assert_eq!(format_pixelsrc(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_format_keeps_depth_sorting() {
+        let input = r#"{"type": "composition", "name": "street", "auto_sort": "y", "sprites": {"T": "tree"}, "layers": [{"map": ["T"], "z": 2}]}"#;
+
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(formatted.contains(r#""auto_sort": "y""#), "{}", formatted);
+        assert!(formatted.contains(r#""z": 2"#), "{}", formatted);
+    }
+
+    #[test]
+    fn test_format_keeps_parallax_and_scroll() {
+        let input = r#"{"type": "composition", "name": "sky", "camera": [8, 0], "sprites": {"C": "cloud"}, "layers": [{"map": ["C"], "parallax": 0.25, "scroll": [4, 0]}, {"map": ["C"], "parallax": [0.5, 0], "repeat": "x"}]}"#;
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(formatted.contains(r#""camera": [8, 0]"#), "{}", formatted);
+        assert!(formatted.contains(r#""parallax": 0.25, "scroll": [4, 0]"#), "{}", formatted);
+        assert!(formatted.contains(r#""parallax": [0.5, 0], "repeat": "x""#), "{}", formatted);
+    }
+
+    #[test]
+    fn test_format_keeps_contact_shadow() {
+        let input = r#"{"type": "composition", "name": "yard", "sprites": {"H": "hero"}, "layers": [{"map": ["H"], "contact_shadow": {"direction": "down-right", "opacity": 0.4}}]}"#;
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(
+            formatted.contains(r#""contact_shadow": {"direction":"down-right","opacity":0.4}"#),
+            "{}",
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_lighting() {
+        let input = r##"{"type": "composition", "name": "camp", "ambient": "#303050", "lights": [{"at": [10, 4], "radius": 12, "color": "#FFDD88", "falloff": "smooth"}], "sprites": {"T": "tent"}, "layers": [{"map": ["T"]}]}"##;
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(formatted.contains(r##""ambient": "#303050""##), "{}", formatted);
+        assert!(
+            formatted.contains(
+                r##""lights": [{"at":[10,4],"radius":12,"color":"#FFDD88","falloff":"smooth"}]"##
+            ),
+            "{}",
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_palette_lock() {
+        for lock in [r#"true"#, r#""@pico8""#] {
+            let input = format!(
+                r#"{{"type": "composition", "name": "dusk", "palette_lock": {}, "sprites": {{"T": "tree"}}, "layers": [{{"map": ["T"]}}]}}"#,
+                lock
+            );
+            let formatted = format_pixelsrc(&input).unwrap();
+            assert!(formatted.contains(&format!(r#""palette_lock": {}"#, lock)), "{}", formatted);
+        }
+    }
+
+    #[test]
+    fn test_format_keeps_sprite_instances() {
+        let input = r#"{"type": "composition", "name": "park", "sprites": {"T": {"sprite": "tree", "offset": [0, -3], "flip": "h"}, "B": "bench"}, "layers": [{"map": ["TB"]}]}"#;
+
+        let formatted = format_pixelsrc(input).unwrap();
+        assert!(
+            formatted.contains(r#""T": {"sprite":"tree","offset":[0,-3],"flip":"h"}"#),
+            "{}",
+            formatted
+        );
+        assert_eq!(format_pixelsrc(&formatted).unwrap(), formatted);
+    }
+
     #[test]
     fn test_escape_json_string() {
         assert_eq!(escape_json_string("hello"), "hello");
@@ -533,6 +1119,30 @@ mod tests {
         assert_eq!(escape_json_string("line1\nline2"), r"line1\nline2");
     }
 
+    #[test]
+    fn test_format_legend() {
+        let input = r#"{"type": "composition", "name": "forest", "map_delimiter": "|", "sprites": {"t": "torch", "mossy": {"sprite": "rock", "flip": "h"}}, "layers": [{"map": ["tree | rock | .", "t|mossy|tree"]}], "locale": {"ja": {"sprites": {"mossy": "rock_ja"}}}}"#;
+
+        let formatted = format_pixelsrc_with_legend(input).unwrap();
+        let Ok(TtpObject::Composition(comp)) = serde_json::from_str(&formatted) else {
+            panic!("not a composition: {}", formatted);
+        };
+        assert_eq!(comp.map_delimiter, None);
+        // "t" keeps its character, so "tree" falls back to "T"
+        let map = comp.layers[0].map.as_ref().unwrap();
+        assert_eq!(map, &vec!["Tr.".to_string(), "tmT".to_string()]);
+        assert_eq!(comp.sprites["T"], Some("tree".into()));
+        assert_eq!(comp.sprites["t"], Some("torch".into()));
+        assert_eq!(comp.sprites["r"], Some("rock".into()));
+        assert_eq!(comp.sprites["."], None);
+        assert_eq!(comp.sprites["m"].as_ref().unwrap().name(), "rock");
+        assert_eq!(comp.locale["ja"].sprites["m"], Some("rock_ja".to_string()));
+
+        // Without a delimiter, --legend changes nothing
+        let plain = r#"{"type": "composition", "name": "c", "sprites": {"A": "a"}, "layers": [{"map": ["A"]}]}"#;
+        assert_eq!(format_pixelsrc_with_legend(plain), format_pixelsrc(plain));
+    }
+
     #[test]
     fn test_format_composition_visual_layers() {
         let comp = Composition {
@@ -542,7 +1152,7 @@ mod tests {
             cell_size: Some([8, 8]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("H".to_string(), Some("hero".to_string())),
+                ("H".to_string(), Some("hero".into())),
             ]),
             layers: vec![
                 CompositionLayer {
@@ -558,6 +1168,15 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
         let formatted = format_composition(&comp);
         // Should have layers and maps on separate lines
@@ -1,12 +1,69 @@
 //! GIF animation rendering
-
-use crate::output::OutputError;
-use image::codecs::gif::{GifEncoder, Repeat};
-use image::{Frame, RgbaImage};
-use std::fs::File;
-use std::io::BufWriter;
+//!
+//! Frames are reduced to binary transparency with [`GifOptions::alpha_threshold`]
+//! and mapped onto 256-color tables. When the colors fit, the tables are exact,
+//! which is the usual case for pixel art; otherwise they're quantized with
+//! NeuQuant and optionally dithered. With [`GifOptions::optimize`], each frame
+//! after the first stores only the rectangle that changed, and frames identical
+//! to the previous one are merged into its delay.
+
+use crate::output::{create_output, OutputError};
+use color_quant::NeuQuant;
+use gif::{DisposalMethod, Encoder, Repeat};
+use image::RgbaImage;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
 use std::path::Path;
 
+/// How GIF color tables are built.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GifPalette {
+    /// One color table shared by every frame
+    #[default]
+    Global,
+    /// A color table per frame, for animations with more than 256 colors overall
+    PerFrame,
+}
+
+/// Dithering applied when colors are quantized to fit a color table.
+///
+/// Exact color tables are never dithered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GifDither {
+    /// Map each pixel to the nearest color
+    #[default]
+    None,
+    /// Diffuse the error to neighbouring pixels
+    FloydSteinberg,
+    /// 4x4 Bayer matrix, which keeps frame differences small
+    Ordered,
+}
+
+/// GIF encoding settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GifOptions {
+    /// Global or per-frame color tables
+    pub palette: GifPalette,
+    /// Dithering for quantized color tables
+    pub dither: GifDither,
+    /// Pixels with alpha below this are transparent, the rest opaque
+    pub alpha_threshold: u8,
+    /// Store only the changed part of each frame
+    pub optimize: bool,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            palette: GifPalette::Global,
+            dither: GifDither::None,
+            alpha_threshold: 128,
+            optimize: true,
+        }
+    }
+}
+
 /// Render a sequence of frames as an animated GIF.
 ///
 /// # Arguments
@@ -14,7 +71,7 @@ use std::path::Path;
 /// * `frames` - The image frames to include in the animation
 /// * `duration_ms` - Duration per frame in milliseconds
 /// * `loop_anim` - Whether the animation should loop infinitely
-/// * `path` - Output file path
+/// * `path` - Output file path (`-` writes to stdout)
 ///
 /// # Returns
 ///
@@ -30,35 +87,328 @@ pub fn render_gif(
         return Ok(());
     }
 
-    // Create parent directories if they don't exist
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() && !parent.exists() {
-            std::fs::create_dir_all(parent)?;
-        }
+    encode_gif(frames, duration_ms, loop_anim, create_output(path)?)
+}
+
+/// Render an animated GIF where each frame has its own duration.
+///
+/// `durations_ms` holds one entry per frame; frames past its end reuse the
+/// last entry.
+pub fn render_gif_with_durations(
+    frames: &[RgbaImage],
+    durations_ms: &[u32],
+    loop_anim: bool,
+    options: &GifOptions,
+    path: &Path,
+) -> Result<(), OutputError> {
+    if frames.is_empty() {
+        return Ok(());
     }
 
-    let file = File::create(path)?;
-    let writer = BufWriter::new(file);
-    let mut encoder = GifEncoder::new(writer);
+    encode_gif_with_durations(frames, durations_ms, loop_anim, options, create_output(path)?)
+}
+
+/// Encode a sequence of frames as an animated GIF into any writer.
+///
+/// Same behavior as [`render_gif`], but writes to memory, sockets, etc.
+pub fn encode_gif<W: Write>(
+    frames: &[RgbaImage],
+    duration_ms: u32,
+    loop_anim: bool,
+    writer: W,
+) -> Result<(), OutputError> {
+    encode_gif_with_durations(frames, &[duration_ms], loop_anim, &GifOptions::default(), writer)
+}
 
-    // Set repeat behavior
-    let repeat = if loop_anim { Repeat::Infinite } else { Repeat::Finite(0) };
-    encoder.set_repeat(repeat)?;
+/// Encode an animated GIF with per-frame durations into any writer.
+///
+/// Same behavior as [`render_gif_with_durations`]. Frames are placed at the
+/// top-left of a canvas the size of the first frame.
+pub fn encode_gif_with_durations<W: Write>(
+    frames: &[RgbaImage],
+    durations_ms: &[u32],
+    loop_anim: bool,
+    options: &GifOptions,
+    writer: W,
+) -> Result<(), OutputError> {
+    if frames.is_empty() {
+        return Ok(());
+    }
 
-    // GIF uses centiseconds (1/100th of a second) for delays
-    // Convert milliseconds to centiseconds (divide by 10)
-    let delay_cs = (duration_ms / 10).max(1) as u16;
+    let (width, height) = frames[0].dimensions();
+    let (Ok(screen_w), Ok(screen_h)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(OutputError::GifSize { width, height });
+    };
+
+    // Binary transparency: `None` pixels are transparent
+    let pixels: Vec<Vec<Option<[u8; 3]>>> = frames
+        .iter()
+        .map(|frame| {
+            let mut out = vec![None; width as usize * height as usize];
+            for (x, y, p) in frame.enumerate_pixels() {
+                if x < width && y < height && p[3] >= options.alpha_threshold {
+                    out[(y * width + x) as usize] = Some([p[0], p[1], p[2]]);
+                }
+            }
+            out
+        })
+        .collect();
+
+    // Frames after the first need a transparent index for unchanged pixels
+    let needs_transparent = |frame: &[Option<[u8; 3]>], index: usize| {
+        (options.optimize && index > 0) || frame.iter().any(Option::is_none)
+    };
+    let global = match options.palette {
+        GifPalette::Global => {
+            let all: Vec<&[Option<[u8; 3]>]> = pixels.iter().map(Vec::as_slice).collect();
+            let transparent = pixels.iter().enumerate().any(|(i, f)| needs_transparent(f, i));
+            Some(ColorTable::build(&all, transparent))
+        }
+        GifPalette::PerFrame => None,
+    };
+
+    let mut encoder =
+        Encoder::new(writer, screen_w, screen_h, global.as_ref().map_or(&[], |t| &t.rgb))?;
+    encoder.set_repeat(if loop_anim { Repeat::Infinite } else { Repeat::Finite(0) })?;
+
+    let fallback = durations_ms.last().copied().unwrap_or(100);
+    let full = Rect { x: 0, y: 0, width, height };
+    let mut plans: Vec<FramePlan> = Vec::with_capacity(frames.len());
+    let mut canvas: Vec<Option<[u8; 3]>> = vec![None; pixels[0].len()];
+    for (i, frame) in pixels.iter().enumerate() {
+        let local = match &global {
+            Some(_) => None,
+            None => Some(ColorTable::build(&[frame], needs_transparent(frame, i))),
+        };
+        let table = global.as_ref().or(local.as_ref()).expect("global or local table");
+        let indices = table.index(frame, width, options.dither);
+        let shown: Vec<Option<[u8; 3]>> = indices.iter().map(|&i| table.color(i)).collect();
+
+        // GIF uses centiseconds (1/100th of a second) for delays
+        let duration_ms = durations_ms.get(i).copied().unwrap_or(fallback);
+        let delay = (duration_ms / 10).clamp(1, u16::MAX as u32) as u16;
+
+        if !options.optimize {
+            plans.push(FramePlan {
+                rect: full,
+                dispose: DisposalMethod::Background,
+                delay,
+                indices,
+                shown,
+                before: None,
+                table: local,
+            });
+            continue;
+        }
 
-    // Encode each frame
-    for rgba_image in frames {
-        let delay = image::Delay::from_numer_denom_ms(delay_cs as u32 * 10, 1);
-        let frame = Frame::from_parts(rgba_image.clone(), 0, 0, delay);
-        encoder.encode_frame(frame)?;
+        if let Some(prev) = plans.last_mut() {
+            if shown == canvas {
+                prev.delay = prev.delay.saturating_add(delay);
+                continue;
+            }
+            // Pixels can't turn transparent on top of a kept frame, so clear it
+            if canvas.iter().zip(&shown).any(|(c, s)| c.is_some() && s.is_none()) {
+                prev.rect = full;
+                prev.dispose = DisposalMethod::Background;
+                canvas.fill(None);
+            }
+        }
+        let rect = if plans.is_empty() {
+            full
+        } else {
+            Rect::changed(&canvas, &shown, width).unwrap_or(Rect {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+            })
+        };
+        let before = std::mem::replace(&mut canvas, shown.clone());
+        plans.push(FramePlan {
+            rect,
+            dispose: DisposalMethod::Keep,
+            delay,
+            indices,
+            shown,
+            before: Some(before),
+            table: local,
+        });
+    }
+
+    for plan in &plans {
+        let table = global.as_ref().or(plan.table.as_ref()).expect("global or local table");
+        let Rect { x, y, width: w, height: h } = plan.rect;
+        let mut buffer = Vec::with_capacity(w as usize * h as usize);
+        for row in y..y + h {
+            for col in x..x + w {
+                let p = (row * width + col) as usize;
+                let unchanged = plan.before.as_ref().is_some_and(|b| b[p] == plan.shown[p]);
+                match table.transparent {
+                    Some(t) if unchanged => buffer.push(t),
+                    _ => buffer.push(plan.indices[p]),
+                }
+            }
+        }
+        let frame = gif::Frame {
+            left: x as u16,
+            top: y as u16,
+            width: w as u16,
+            height: h as u16,
+            buffer: Cow::Owned(buffer),
+            palette: plan.table.as_ref().map(|t| t.rgb.clone()),
+            transparent: table.transparent,
+            delay: plan.delay,
+            dispose: plan.dispose,
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame)?;
     }
 
     Ok(())
 }
 
+/// A frame ready to encode: which part to store and how to dispose of it
+struct FramePlan {
+    rect: Rect,
+    dispose: DisposalMethod,
+    delay: u16,
+    /// Color table index of every pixel in the frame
+    indices: Vec<u8>,
+    /// The frame as it looks after quantization
+    shown: Vec<Option<[u8; 3]>>,
+    /// Canvas the frame is drawn over, when only changes are stored
+    before: Option<Vec<Option<[u8; 3]>>>,
+    /// Per-frame color table
+    table: Option<ColorTable>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Rect {
+    /// Bounding box of the pixels that differ, if any
+    fn changed(a: &[Option<[u8; 3]>], b: &[Option<[u8; 3]>], width: u32) -> Option<Rect> {
+        let (mut x0, mut y0, mut x1, mut y1) = (u32::MAX, u32::MAX, 0, 0);
+        for (p, _) in a.iter().zip(b).enumerate().filter(|(_, (a, b))| a != b) {
+            let (x, y) = (p as u32 % width, p as u32 / width);
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+        }
+        (x0 <= x1).then(|| Rect { x: x0, y: y0, width: x1 - x0 + 1, height: y1 - y0 + 1 })
+    }
+}
+
+/// A GIF color table, with the optional transparent entry last
+struct ColorTable {
+    rgb: Vec<u8>,
+    colors: Vec<[u8; 3]>,
+    transparent: Option<u8>,
+    /// Whether every opaque color is in the table as-is
+    exact: bool,
+}
+
+impl ColorTable {
+    /// Exact colors in order of appearance when they fit, else NeuQuant
+    fn build(frames: &[&[Option<[u8; 3]>]], transparent: bool) -> ColorTable {
+        let max = if transparent { 255 } else { 256 };
+        let mut colors: Vec<[u8; 3]> = Vec::new();
+        let mut seen: HashSet<[u8; 3]> = HashSet::new();
+        for color in frames.iter().flat_map(|f| f.iter().flatten()) {
+            if seen.insert(*color) {
+                colors.push(*color);
+            }
+        }
+
+        let exact = colors.len() <= max;
+        if !exact {
+            let samples: Vec<u8> = frames
+                .iter()
+                .flat_map(|f| f.iter().flatten())
+                .flat_map(|[r, g, b]| [*r, *g, *b, 255])
+                .collect();
+            // Sample every pixel of small images, fewer of large ones
+            let samplefac = (samples.len() / (4 << 16)).clamp(1, 30) as i32;
+            let quant = NeuQuant::new(samplefac, max, &samples);
+            colors = quant.color_map_rgb().chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+        }
+        if colors.is_empty() {
+            colors.push([0, 0, 0]);
+        }
+        let transparent = transparent.then(|| {
+            colors.push([0, 0, 0]);
+            (colors.len() - 1) as u8
+        });
+        let rgb = colors.iter().flatten().copied().collect();
+        ColorTable { rgb, colors, transparent, exact }
+    }
+
+    fn color(&self, index: u8) -> Option<[u8; 3]> {
+        (Some(index) != self.transparent).then(|| self.colors[index as usize])
+    }
+
+    /// Index of the closest opaque color
+    fn nearest(&self, color: [i32; 3], cache: &mut HashMap<[i32; 3], u8>) -> u8 {
+        *cache.entry(color).or_insert_with(|| {
+            let opaque = self.colors.len() - self.transparent.is_some() as usize;
+            let distance =
+                |c: &[u8; 3]| -> i32 { (0..3).map(|i| (c[i] as i32 - color[i]).pow(2)).sum() };
+            (0..opaque).min_by_key(|&i| distance(&self.colors[i])).unwrap_or(0) as u8
+        })
+    }
+
+    /// Map a frame's pixels to table indices
+    fn index(&self, frame: &[Option<[u8; 3]>], width: u32, dither: GifDither) -> Vec<u8> {
+        const BAYER: [[i32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+        let dither = if self.exact { GifDither::None } else { dither };
+        let width = width as usize;
+        let mut cache = HashMap::new();
+        let mut errors = vec![[0i32; 3]; frame.len()];
+        let mut out = Vec::with_capacity(frame.len());
+        for (p, pixel) in frame.iter().enumerate() {
+            let Some(rgb) = pixel else {
+                out.push(self.transparent.unwrap_or(0));
+                continue;
+            };
+            let (x, y) = (p % width, p / width);
+            let offset = match dither {
+                GifDither::Ordered => (BAYER[y % 4][x % 4] * 2 - 15) * 2,
+                _ => 0,
+            };
+            let wanted: [i32; 3] =
+                std::array::from_fn(|c| (rgb[c] as i32 + errors[p][c] + offset).clamp(0, 255));
+            let index = self.nearest(wanted, &mut cache);
+            out.push(index);
+
+            if dither == GifDither::FloydSteinberg {
+                let got = self.colors[index as usize];
+                let error: [i32; 3] = std::array::from_fn(|c| wanted[c] - got[c] as i32);
+                let right = x + 1 < width;
+                let mut spread = |q: usize, weight: i32| {
+                    if let Some(e) = errors.get_mut(q) {
+                        for c in 0..3 {
+                            e[c] += error[c] * weight / 16;
+                        }
+                    }
+                };
+                if right {
+                    spread(p + 1, 7);
+                    spread(p + width + 1, 1);
+                }
+                if x > 0 {
+                    spread(p + width - 1, 3);
+                }
+                spread(p + width, 5);
+            }
+        }
+        out
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,6 +526,17 @@ mod tests {
         assert!(path.exists());
     }
 
+    #[test]
+    fn test_encode_gif_to_memory() {
+        let frames = vec![
+            create_test_frame(2, 2, Rgba([255, 0, 0, 255])),
+            create_test_frame(2, 2, Rgba([0, 0, 255, 255])),
+        ];
+        let mut bytes = Vec::new();
+        encode_gif(&frames, 100, true, &mut bytes).unwrap();
+        assert_eq!(&bytes[0..6], b"GIF89a");
+    }
+
     #[test]
     fn test_render_gif_minimum_delay() {
         let dir = tempdir().unwrap();
@@ -191,4 +552,111 @@ mod tests {
         assert!(result.is_ok());
         assert!(path.exists());
     }
+
+    #[test]
+    fn test_encode_gif_per_frame_durations() {
+        use image::codecs::gif::GifDecoder;
+        use image::AnimationDecoder;
+
+        let frames = vec![
+            create_test_frame(2, 2, Rgba([255, 0, 0, 255])),
+            create_test_frame(2, 2, Rgba([0, 255, 0, 255])),
+            create_test_frame(2, 2, Rgba([0, 0, 255, 255])),
+        ];
+        let mut bytes = Vec::new();
+        encode_gif_with_durations(
+            &frames,
+            &[300, 50, 120],
+            true,
+            &GifOptions::default(),
+            &mut bytes,
+        )
+        .unwrap();
+
+        let decoded = GifDecoder::new(std::io::Cursor::new(bytes)).unwrap();
+        let delays: Vec<u32> = decoded
+            .into_frames()
+            .map(|f| {
+                let (numer, denom) = f.unwrap().delay().numer_denom_ms();
+                numer / denom
+            })
+            .collect();
+        assert_eq!(delays, vec![300, 50, 120]);
+    }
+
+    /// Decode a GIF into composited RGBA frames and their delays in ms
+    fn decode(bytes: Vec<u8>) -> Vec<(RgbaImage, u32)> {
+        use image::codecs::gif::GifDecoder;
+        use image::AnimationDecoder;
+
+        let decoder = GifDecoder::new(std::io::Cursor::new(bytes)).unwrap();
+        decoder
+            .into_frames()
+            .map(|f| {
+                let f = f.unwrap();
+                let (numer, denom) = f.delay().numer_denom_ms();
+                (f.into_buffer(), numer / denom)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_gif_optimized_frames() {
+        let red = Rgba([255, 0, 0, 255]);
+        let clear = Rgba([0, 0, 0, 0]);
+        let mut moved = create_test_frame(4, 4, clear);
+        moved.put_pixel(1, 1, red);
+        let mut shadow = moved.clone();
+        shadow.put_pixel(2, 2, Rgba([0, 0, 255, 100]));
+        let frames = vec![create_test_frame(4, 4, red), moved.clone(), shadow, moved.clone()];
+
+        let mut bytes = Vec::new();
+        let options = GifOptions::default();
+        encode_gif_with_durations(&frames, &[100, 100, 50, 100], true, &options, &mut bytes)
+            .unwrap();
+
+        // Below the alpha threshold the shadow is transparent, so frames 2-4 merge
+        let decoded = decode(bytes);
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].0, frames[0]);
+        assert_eq!(decoded[1], (moved.clone(), 250), "no ghosting from the red frame");
+
+        // Without optimization every frame is stored; threshold 1 keeps the shadow
+        let options = GifOptions { alpha_threshold: 1, optimize: false, ..Default::default() };
+        let mut bytes = Vec::new();
+        encode_gif_with_durations(&frames, &[100], true, &options, &mut bytes).unwrap();
+        let decoded = decode(bytes);
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(*decoded[2].0.get_pixel(2, 2), Rgba([0, 0, 255, 255]));
+        assert_eq!(decoded[3].0, moved);
+    }
+
+    #[test]
+    fn test_encode_gif_quantized_palettes() {
+        // 16x32 gradient: 512 colors, too many for one table
+        let gradient = RgbaImage::from_fn(16, 32, |x, y| Rgba([x as u8 * 16, y as u8 * 8, 0, 255]));
+        let flat = create_test_frame(16, 32, Rgba([0, 200, 0, 255]));
+        let frames = vec![gradient.clone(), flat.clone()];
+
+        for dither in [GifDither::None, GifDither::FloydSteinberg, GifDither::Ordered] {
+            for palette in [GifPalette::Global, GifPalette::PerFrame] {
+                let options = GifOptions { palette, dither, ..Default::default() };
+                let mut bytes = Vec::new();
+                encode_gif_with_durations(&frames, &[100], true, &options, &mut bytes).unwrap();
+                let decoded = decode(bytes);
+                assert_eq!(decoded.len(), 2);
+                let error: u32 = decoded[0]
+                    .0
+                    .pixels()
+                    .zip(gradient.pixels())
+                    .map(|(a, b)| (0..3).map(|c| a[c].abs_diff(b[c]) as u32).sum::<u32>())
+                    .sum();
+                assert!(error / 512 < 64, "{:?}/{:?}: mean error {}", palette, dither, error / 512);
+                if palette == GifPalette::PerFrame {
+                    // The flat frame gets its own exact table
+                    assert_eq!(decoded[1].0, flat);
+                }
+            }
+        }
+    }
 }
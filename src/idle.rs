@@ -0,0 +1,302 @@
+//! Idle animations generated from a single static sprite
+//!
+//! `pxl animate --preset breathe` turns one sprite into a short looping idle:
+//! each frame squashes the sprite's visible rows by up to `depth` pixels,
+//! keeping its bottom edge in place, and darkens its colors slightly at the
+//! bottom of the breath. The result is a set of new sprites plus an animation,
+//! ready to append to the source file:
+//!
+//! ```json
+//! {"type": "sprite", "name": "hero_breathe_1", "size": [16, 16], "palette": {...}, "regions": {...}}
+//! {"type": "animation", "name": "hero_breathe", "frames": ["hero", "hero_breathe_1", "hero_breathe_2", "hero_breathe_1"], "duration": 300}
+//! ```
+//!
+//! The first frame is always the untouched source sprite.
+
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::TAU;
+
+use crate::color::{apply_color_shift, parse_color};
+use crate::import::extract_structured_regions;
+use crate::models::{Animation, Duration, PaletteRef, RegionDef, Sprite, TtpObject};
+use crate::registry::ResolvedSprite;
+use crate::structured::token_grid;
+
+/// Default number of frames in a breathing cycle.
+pub const DEFAULT_FRAMES: u32 = 4;
+
+/// Default frame duration in milliseconds.
+pub const DEFAULT_FRAME_MS: u32 = 300;
+
+/// Options for [`breathe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreatheOptions {
+    /// Frames per cycle (2-4)
+    pub frames: u32,
+    /// Rows removed at the deepest point of the breath
+    pub depth: u32,
+    /// Lightness (in percentage points) removed at the deepest point
+    pub shade: f64,
+    /// Duration of each frame in milliseconds
+    pub frame_ms: u32,
+}
+
+impl Default for BreatheOptions {
+    fn default() -> Self {
+        Self { frames: DEFAULT_FRAMES, depth: 1, shade: 4.0, frame_ms: DEFAULT_FRAME_MS }
+    }
+}
+
+/// A grid of visible tokens, top to bottom; `None` is uncovered.
+type TokenGrid = Vec<Vec<Option<String>>>;
+
+/// How far into the breath frame `index` of `frames` is, from 0.0 to 1.0.
+///
+/// Rounded so mirrored frames on either side of the deepest one compare equal.
+fn breath(index: u32, frames: u32) -> f64 {
+    let amount = (1.0 - (TAU * index as f64 / frames as f64).cos()) / 2.0;
+    (amount * 1e6).round() / 1e6
+}
+
+/// Squash the visible rows of `grid` by `rows`, keeping the bottom row in
+/// place. Rows are dropped evenly, so the middle of the sprite gives first.
+fn squash(grid: &TokenGrid, rows: u32) -> TokenGrid {
+    let visible = |row: &Vec<Option<String>>| row.iter().any(Option::is_some);
+    let (Some(top), Some(bottom)) = (grid.iter().position(visible), grid.iter().rposition(visible))
+    else {
+        return grid.clone();
+    };
+    let content = bottom - top + 1;
+    let rows = (rows as usize).min(content - 1);
+    if rows == 0 {
+        return grid.clone();
+    }
+
+    let kept = content - rows;
+    let mut squashed = grid.clone();
+    for row in &mut squashed[top..top + rows] {
+        row.iter_mut().for_each(|cell| *cell = None);
+    }
+    for j in 0..kept {
+        let source = top + ((j as f64 + 0.5) * content as f64 / kept as f64) as usize;
+        squashed[top + rows + j] = grid[source.min(bottom)].clone();
+    }
+    squashed
+}
+
+/// Build structured regions from a token grid.
+fn grid_regions(grid: &TokenGrid, width: u32, height: u32) -> HashMap<String, RegionDef> {
+    let mut points: BTreeMap<&str, Vec<[u32; 2]>> = BTreeMap::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, token) in row.iter().enumerate() {
+            if let Some(token) = token {
+                points.entry(token).or_default().push([x as u32, y as u32]);
+            }
+        }
+    }
+    points
+        .into_iter()
+        .filter_map(|(token, points)| {
+            let mut region = extract_structured_regions(&points, width, height).to_json();
+            sort_points(&mut region);
+            serde_json::from_value(region).ok().map(|region| (token.to_string(), region))
+        })
+        .collect()
+}
+
+/// Sort every `points` and `union` list in a region, so output is stable
+/// between runs.
+fn sort_points(region: &mut serde_json::Value) {
+    match region {
+        serde_json::Value::Object(map) => {
+            map.values_mut().for_each(sort_points);
+            if let Some(serde_json::Value::Array(points)) = map.get_mut("points") {
+                points.sort_by_key(|p| (p[1].as_u64(), p[0].as_u64()));
+            }
+            if let Some(serde_json::Value::Array(shapes)) = map.get_mut("union") {
+                shapes.sort_by_key(|shape| shape.to_string());
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(sort_points),
+        _ => {}
+    }
+}
+
+/// Darken every parseable, visible color in `palette` by `lightness` points.
+fn shade_palette(palette: &HashMap<String, String>, lightness: f64) -> HashMap<String, String> {
+    palette
+        .iter()
+        .map(|(token, color)| {
+            let shaded = match parse_color(color) {
+                Ok(rgba) if rgba.0[3] > 0 && lightness > 0.0 => {
+                    apply_color_shift(&rgba, 0.0, 0.0, -lightness)
+                }
+                _ => color.clone(),
+            };
+            (token.clone(), shaded)
+        })
+        .collect()
+}
+
+/// Generate a breathing idle animation for the sprite `name`.
+///
+/// Returns the new sprites followed by the animation, named `{name}_breathe`.
+/// Frames that come out identical share one sprite.
+pub fn breathe(
+    name: &str,
+    resolved: &ResolvedSprite,
+    options: &BreatheOptions,
+) -> Result<Vec<TtpObject>, String> {
+    if !(2..=4).contains(&options.frames) {
+        return Err(format!("breathe needs 2 to 4 frames, got {}", options.frames));
+    }
+    if options.depth == 0 {
+        return Err("breathe depth must be at least 1 row".to_string());
+    }
+    if !options.shade.is_finite() || options.shade < 0.0 {
+        return Err(format!("breathe shade must be a non-negative number, got {}", options.shade));
+    }
+    if resolved.layers.as_ref().is_some_and(|layers| !layers.is_empty()) {
+        return Err(format!("sprite '{}' has layers, which breathe does not support", name));
+    }
+    let (Some([width, height]), Some(regions)) = (resolved.size, &resolved.regions) else {
+        return Err(format!("sprite '{}' needs a size and regions", name));
+    };
+
+    let (grid, _) = token_grid(name, width, height, regions);
+    let used: HashMap<String, String> = resolved
+        .palette
+        .iter()
+        .filter(|(token, _)| grid.iter().flatten().flatten().any(|t| t == *token))
+        .map(|(token, color)| (token.clone(), color.clone()))
+        .collect();
+
+    let mut sprites: Vec<Sprite> = Vec::new();
+    let mut keys: Vec<(u32, u64)> = Vec::new();
+    let mut frames = Vec::new();
+    for index in 0..options.frames {
+        let amount = breath(index, options.frames);
+        // Halves round down, so a one-row breath only drops at its deepest frame
+        let rows = (amount * options.depth as f64 - 0.5).ceil().max(0.0) as u32;
+        let lightness = amount * options.shade;
+        if index == 0 {
+            frames.push(name.to_string());
+            continue;
+        }
+
+        let key = (rows, lightness.to_bits());
+        if let Some(existing) = keys.iter().position(|k| *k == key) {
+            frames.push(sprites[existing].name.clone());
+            continue;
+        }
+        let sprite = Sprite {
+            name: format!("{}_breathe_{}", name, sprites.len() + 1),
+            size: Some([width, height]),
+            palette: PaletteRef::Inline(shade_palette(&used, lightness)),
+            regions: Some(grid_regions(&squash(&grid, rows), width, height)),
+            opacity: resolved.opacity,
+            ..Default::default()
+        };
+        frames.push(sprite.name.clone());
+        keys.push(key);
+        sprites.push(sprite);
+    }
+
+    let animation = Animation {
+        name: format!("{}_breathe", name),
+        frames,
+        duration: Some(Duration::Milliseconds(options.frame_ms)),
+        ..Default::default()
+    };
+    let mut objects: Vec<TtpObject> = sprites.into_iter().map(TtpObject::Sprite).collect();
+    objects.push(TtpObject::Animation(animation));
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> TokenGrid {
+        rows.iter()
+            .map(|row| row.chars().map(|c| (c != '.').then(|| c.to_string())).collect())
+            .collect()
+    }
+
+    fn resolved() -> ResolvedSprite {
+        let regions: HashMap<String, RegionDef> = serde_json::from_str(
+            r#"{"h": {"rect": [1, 0, 2, 1]}, "b": {"rect": [0, 1, 4, 2]}, "f": {"rect": [0, 3, 4, 1]}}"#,
+        )
+        .unwrap();
+        ResolvedSprite {
+            name: "hero".to_string(),
+            size: Some([4, 5]),
+            palette: HashMap::from([
+                ("h".to_string(), "#FFCC99".to_string()),
+                ("b".to_string(), "#3366CC".to_string()),
+                ("f".to_string(), "#222222".to_string()),
+                ("_".to_string(), "transparent".to_string()),
+            ]),
+            warnings: Vec::new(),
+            nine_slice: None,
+            regions: Some(regions),
+            layers: None,
+            opacity: None,
+        }
+    }
+
+    #[test]
+    fn test_squash_keeps_bottom() {
+        let before = grid(&[".hh.", "bbbb", "cccc", "ffff", "...."]);
+        assert_eq!(squash(&before, 1), grid(&["....", ".hh.", "cccc", "ffff", "...."]));
+        assert_eq!(squash(&before, 0), before);
+        // Always keeps at least one visible row
+        assert_eq!(squash(&before, 9), grid(&["....", "....", "....", "cccc", "...."]));
+    }
+
+    #[test]
+    fn test_breathe_frames() {
+        let objects = breathe("hero", &resolved(), &BreatheOptions::default()).unwrap();
+        assert_eq!(objects.len(), 3, "frames 1 and 3 share a sprite");
+
+        let TtpObject::Animation(animation) = &objects[2] else { panic!("expected animation") };
+        assert_eq!(animation.name, "hero_breathe");
+        assert_eq!(
+            animation.frames,
+            ["hero", "hero_breathe_1", "hero_breathe_2", "hero_breathe_1"]
+        );
+        assert_eq!(animation.duration_ms(), DEFAULT_FRAME_MS);
+
+        let TtpObject::Sprite(half) = &objects[0] else { panic!("expected sprite") };
+        let TtpObject::Sprite(deep) = &objects[1] else { panic!("expected sprite") };
+        let tokens = |sprite: &Sprite| token_grid("t", 4, 5, sprite.regions.as_ref().unwrap()).0;
+        assert_eq!(half.size, Some([4, 5]));
+        assert_eq!(tokens(half), grid(&[".hh.", "bbbb", "bbbb", "ffff", "...."]));
+        assert_eq!(tokens(deep), grid(&["....", ".hh.", "bbbb", "ffff", "...."]));
+
+        let PaletteRef::Inline(colors) = &deep.palette else { panic!("expected inline palette") };
+        assert!(!colors.contains_key("_"), "only used tokens are kept");
+        let shaded = parse_color(&colors["b"]).unwrap();
+        let base = parse_color("#3366CC").unwrap();
+        assert!(shaded.0[2] < base.0[2], "deepest frame is darker");
+    }
+
+    #[test]
+    fn test_breathe_two_frames() {
+        let options = BreatheOptions { frames: 2, ..Default::default() };
+        let objects = breathe("hero", &resolved(), &options).unwrap();
+        let TtpObject::Animation(animation) = objects.last().unwrap() else { panic!() };
+        assert_eq!(animation.frames, ["hero", "hero_breathe_1"]);
+    }
+
+    #[test]
+    fn test_breathe_invalid_options() {
+        let err = |options| breathe("hero", &resolved(), &options).unwrap_err();
+        assert!(err(BreatheOptions { frames: 5, ..Default::default() }).contains("2 to 4"));
+        assert!(err(BreatheOptions { depth: 0, ..Default::default() }).contains("depth"));
+
+        let no_regions = ResolvedSprite { regions: None, ..resolved() };
+        let err = breathe("hero", &no_regions, &BreatheOptions::default()).unwrap_err();
+        assert!(err.contains("regions"));
+    }
+}
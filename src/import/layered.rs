@@ -0,0 +1,307 @@
+//! Layered image import (.ora, .psd) into sprites plus a composition.
+//!
+//! Each visible layer becomes its own canvas-sized sprite, with the layer's
+//! offset baked into its regions, quantized to its own palette. A
+//! composition named after the file recreates the layer stack, keeping each
+//! layer's blend mode and opacity. Its `cell_size` is the whole canvas, so
+//! every layer map is a single cell.
+
+use std::path::Path;
+
+use image::{imageops, DynamicImage, RgbaImage};
+
+use super::{import_dynamic_image, ImportOptions, ImportResult};
+use crate::composition::BlendMode;
+use crate::config::LimitsConfig;
+use crate::ora::{decode_ora, OraImage};
+
+use super::psd::decode_psd;
+
+/// Token the importer assigns to fully transparent pixels
+const TRANSPARENT_TOKEN: &str = "{_}";
+
+/// Map characters for composition layers, one per imported layer
+const LAYER_KEYS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// A layer imported as a sprite.
+#[derive(Debug, Clone)]
+pub struct ImportedLayer {
+    /// Original layer name
+    pub layer_name: String,
+    /// The layer's sprite and palette
+    pub result: ImportResult,
+    /// Layer blend mode
+    pub blend: BlendMode,
+    /// Layer opacity (0.0-1.0)
+    pub opacity: f64,
+}
+
+/// Result of importing a layered image.
+#[derive(Debug, Clone)]
+pub struct LayeredImport {
+    /// Composition name
+    pub name: String,
+    /// Canvas width
+    pub width: u32,
+    /// Canvas height
+    pub height: u32,
+    /// Imported layers, bottom first
+    pub layers: Vec<ImportedLayer>,
+    /// Layers or properties that could not be imported exactly
+    pub warnings: Vec<String>,
+}
+
+impl LayeredImport {
+    /// Serialize to JSONL: a palette and sprite per layer, then the composition.
+    ///
+    /// With `structured`, layers use the analysis output format
+    /// (see [`ImportResult::to_structured_jsonl`]).
+    pub fn to_jsonl(&self, structured: bool) -> String {
+        let mut lines: Vec<String> = self
+            .layers
+            .iter()
+            .map(|layer| {
+                if structured {
+                    layer.result.to_structured_jsonl()
+                } else {
+                    layer.result.to_jsonl()
+                }
+            })
+            .collect();
+
+        let mut sprites = serde_json::Map::new();
+        sprites.insert(".".to_string(), serde_json::Value::Null);
+        let layers: Vec<serde_json::Value> = self
+            .layers
+            .iter()
+            .zip(LAYER_KEYS.chars())
+            .map(|(layer, key)| {
+                sprites.insert(key.to_string(), serde_json::json!(layer.result.name));
+
+                let mut value =
+                    serde_json::json!({ "name": layer.layer_name, "map": [key.to_string()] });
+                if layer.blend != BlendMode::Normal {
+                    value["blend"] = serde_json::json!(layer.blend);
+                }
+                if layer.opacity < 1.0 {
+                    value["opacity"] = serde_json::json!((layer.opacity * 1000.0).round() / 1000.0);
+                }
+                value
+            })
+            .collect();
+
+        lines.push(
+            serde_json::json!({
+                "type": "composition",
+                "name": self.name,
+                "size": [self.width, self.height],
+                "cell_size": [self.width, self.height],
+                "sprites": sprites,
+                "layers": layers,
+            })
+            .to_string(),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Whether a path names a layered image format (`.ora` or `.psd`).
+pub fn is_layered_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("ora") || e.eq_ignore_ascii_case("psd"))
+}
+
+/// Import an `.ora` or `.psd` file as one sprite per layer plus a composition.
+///
+/// Each layer is quantized to at most `max_colors` colors independently.
+/// Canvases and layers larger than the default `[limits]` size are rejected.
+pub fn import_layered<P: AsRef<Path>>(
+    path: P,
+    name: &str,
+    max_colors: usize,
+    options: &ImportOptions,
+) -> Result<LayeredImport, String> {
+    import_layered_with_limits(path, name, max_colors, options, &LimitsConfig::default())
+}
+
+/// Like [`import_layered`], with a project's `[limits]`.
+pub fn import_layered_with_limits<P: AsRef<Path>>(
+    path: P,
+    name: &str,
+    max_colors: usize,
+    options: &ImportOptions,
+    limits: &LimitsConfig,
+) -> Result<LayeredImport, String> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let decoded = if bytes.starts_with(b"8BPS") {
+        decode_psd(&bytes, limits.max_size)?
+    } else {
+        decode_ora(&bytes, limits.max_size)?
+    };
+    import_layers(decoded, name, max_colors, options)
+}
+
+/// Convert decoded layers into sprites plus a composition.
+fn import_layers(
+    decoded: OraImage,
+    name: &str,
+    max_colors: usize,
+    options: &ImportOptions,
+) -> Result<LayeredImport, String> {
+    let mut warnings = decoded.warnings;
+    let mut layers: Vec<ImportedLayer> = Vec::new();
+
+    for layer in decoded.layers {
+        let Some(image) =
+            place_on_canvas(&layer.image, layer.x, layer.y, decoded.width, decoded.height)
+        else {
+            warnings.push(format!("Skipping empty layer '{}'", layer.name));
+            continue;
+        };
+        if layers.len() == LAYER_KEYS.len() {
+            return Err(format!("Too many layers to import (at most {})", LAYER_KEYS.len()));
+        }
+
+        // Sprite names must be unique even when layer names repeat
+        let base = format!("{}_{}", name, slug(&layer.name));
+        let mut sprite_name = base.clone();
+        let mut n = 2;
+        while layers.iter().any(|l| l.result.name == sprite_name) {
+            sprite_name = format!("{}_{}", base, n);
+            n += 1;
+        }
+
+        let mut result = import_dynamic_image(
+            DynamicImage::ImageRgba8(image),
+            &sprite_name,
+            max_colors,
+            options,
+        )?;
+        // Uncovered canvas stays transparent without a region for it
+        result.regions.remove(TRANSPARENT_TOKEN);
+        if let Some(structured) = result.structured_regions.as_mut() {
+            structured.remove(TRANSPARENT_TOKEN);
+        }
+        layers.push(ImportedLayer {
+            layer_name: layer.name,
+            result,
+            blend: layer.blend,
+            opacity: layer.opacity,
+        });
+    }
+
+    if layers.is_empty() {
+        return Err("No visible, non-empty layers to import".to_string());
+    }
+
+    Ok(LayeredImport {
+        name: name.to_string(),
+        width: decoded.width,
+        height: decoded.height,
+        layers,
+        warnings,
+    })
+}
+
+/// Draw a layer at (x, y) on a transparent canvas.
+///
+/// Returns `None` if none of the layer's opaque pixels land on the canvas.
+fn place_on_canvas(
+    image: &RgbaImage,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) -> Option<RgbaImage> {
+    let mut canvas = RgbaImage::new(width, height);
+    imageops::replace(&mut canvas, image, x as i64, y as i64);
+    canvas.pixels().any(|p| p[3] > 0).then_some(canvas)
+}
+
+/// Lowercase identifier from a layer name (`"Back Hair #2"` -> `back_hair_2`).
+fn slug(name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if slug.is_empty() {
+        "layer".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ora::OraLayer;
+    use image::Rgba;
+
+    fn layer(name: &str, image: RgbaImage, x: i32, y: i32) -> OraLayer {
+        OraLayer { name: name.to_string(), image, x, y, blend: BlendMode::Normal, opacity: 1.0 }
+    }
+
+    #[test]
+    fn test_place_on_canvas() {
+        let mut image = RgbaImage::new(4, 4);
+        image.put_pixel(1, 2, Rgba([255, 0, 0, 255]));
+        let canvas = place_on_canvas(&image, 2, 1, 8, 8).unwrap();
+        assert_eq!(canvas.dimensions(), (8, 8));
+        assert_eq!(canvas.get_pixel(3, 3)[3], 255);
+
+        // Pixels outside the canvas are dropped
+        assert!(place_on_canvas(&image, -4, 0, 8, 8).is_none());
+        assert!(place_on_canvas(&RgbaImage::new(2, 2), 0, 0, 8, 8).is_none());
+    }
+
+    #[test]
+    fn test_import_layers_builds_composition() {
+        let decoded = OraImage {
+            width: 6,
+            height: 4,
+            layers: vec![
+                layer("Sky", RgbaImage::from_pixel(6, 4, Rgba([0, 0, 255, 255])), 0, 0),
+                layer("Sun Disc", RgbaImage::from_pixel(2, 2, Rgba([255, 255, 0, 255])), 3, 1),
+                OraLayer {
+                    blend: BlendMode::Multiply,
+                    opacity: 0.25,
+                    ..layer("Sun Disc", RgbaImage::from_pixel(1, 1, Rgba([9, 9, 9, 255])), 0, 3)
+                },
+                layer("empty", RgbaImage::new(2, 2), 0, 0),
+            ],
+            warnings: vec![],
+        };
+        let import = import_layers(decoded, "scene", 4, &ImportOptions::default()).unwrap();
+        assert_eq!(import.warnings, ["Skipping empty layer 'empty'"]);
+
+        let names: Vec<&str> = import.layers.iter().map(|l| l.result.name.as_str()).collect();
+        assert_eq!(names, ["scene_sky", "scene_sun_disc", "scene_sun_disc_2"]);
+
+        let jsonl = import.to_jsonl(false);
+        let comp: serde_json::Value = serde_json::from_str(jsonl.lines().last().unwrap()).unwrap();
+        assert_eq!(comp["type"], "composition");
+        assert_eq!(comp["cell_size"], serde_json::json!([6, 4]));
+        assert_eq!(comp["sprites"]["B"], "scene_sun_disc");
+        assert_eq!(comp["layers"][1]["map"], serde_json::json!(["B"]));
+
+        assert_eq!(comp["layers"][2]["blend"], "multiply");
+        assert_eq!(comp["layers"][2]["opacity"], 0.25);
+        assert!(comp["layers"][0].get("blend").is_none());
+
+        // The sun keeps its offset inside a canvas-sized sprite, without a transparent region
+        let sun: serde_json::Value = serde_json::from_str(jsonl.lines().nth(3).unwrap()).unwrap();
+        assert_eq!(sun["size"], serde_json::json!([6, 4]));
+        assert!(sun["regions"].get("{_}").is_none());
+        assert_eq!(sun["regions"].as_object().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Back Hair #2"), "back_hair_2");
+        assert_eq!(slug("***"), "layer");
+    }
+}
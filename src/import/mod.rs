@@ -5,10 +5,15 @@
 //! - Quantize colors using median cut algorithm if too many colors
 //! - Generate Pixelsrc JSONL output with palette and sprite definitions
 //! - Detect shapes, symmetry, roles, and relationships when analysis is enabled
+//! - Import layered OpenRaster/PSD files as per-layer sprites plus a composition
+//! - Slice sprite sheets into frame sprites plus an animation
 
 mod analysis;
 mod color_quantization;
 mod detection;
+mod layered;
+mod psd;
+mod sheet;
 mod structured_regions;
 
 use image::GenericImageView;
@@ -22,7 +27,13 @@ use crate::analyze::{
 use crate::models::{RelationshipType, Role};
 
 pub use color_quantization::Color;
+pub(crate) use color_quantization::LabColor;
 use color_quantization::{find_closest_color, median_cut_quantize_lab};
+pub use layered::{
+    import_layered, import_layered_with_limits, is_layered_path, ImportedLayer, LayeredImport,
+};
+pub use psd::decode_psd;
+pub use sheet::{import_sheet, SheetFrame, SheetImport, SheetSlicing};
 pub use structured_regions::{
     extract_structured_regions, filter_points_for_half_sprite,
     filter_structured_region_for_half_sprite, StructuredRegion,
@@ -192,7 +203,13 @@ impl ImportResult {
             "name": format!("{}_palette", self.name),
             "colors": self.palette
         });
+        let sprite_json = self.sprite_json(&format!("{}_palette", self.name));
 
+        format!("{}\n{}", palette_json, sprite_json)
+    }
+
+    /// The sprite object alone, referencing the named palette.
+    pub(crate) fn sprite_json(&self, palette: &str) -> serde_json::Value {
         // Build regions object - use structured regions if available, fallback to points
         let regions: HashMap<String, serde_json::Value> =
             if let Some(ref structured) = self.structured_regions {
@@ -204,15 +221,13 @@ impl ImportResult {
                     .collect()
             };
 
-        let sprite_json = serde_json::json!({
+        serde_json::json!({
             "type": "sprite",
             "name": self.name,
             "size": [self.width, self.height],
-            "palette": format!("{}_palette", self.name),
+            "palette": palette,
             "regions": regions
-        });
-
-        format!("{}\n{}", palette_json, sprite_json)
+        })
     }
 
     /// Serialize to structured JSONL format (v2 with regions, roles, relationships).
@@ -0,0 +1,389 @@
+//! Basic Photoshop (.psd) layer decoding.
+//!
+//! Reads the layer records and channel data of 8-bit RGB documents, the
+//! common case for pixel art exported from Photoshop, Krita or Aseprite.
+//! Layers are decoded into the same model as OpenRaster files so both go
+//! through one import path. Masks, adjustment layers, effects and group
+//! structure are ignored; groups are flattened into their child layers.
+
+use image::{Rgba, RgbaImage};
+
+use crate::composition::BlendMode;
+use crate::ora::{OraImage, OraLayer};
+
+/// PSD color mode for RGB documents
+const COLOR_MODE_RGB: u16 = 3;
+
+/// Layer flag bit set on hidden layers
+const FLAG_HIDDEN: u8 = 0x02;
+
+/// Blend mode for a PSD blend key, `None` if pixelsrc has no equivalent.
+pub fn blend_for_psd_key(key: &[u8; 4]) -> Option<BlendMode> {
+    match key {
+        b"norm" => Some(BlendMode::Normal),
+        b"mul " => Some(BlendMode::Multiply),
+        b"scrn" => Some(BlendMode::Screen),
+        b"over" => Some(BlendMode::Overlay),
+        b"lddg" => Some(BlendMode::Add),
+        b"fsub" => Some(BlendMode::Subtract),
+        b"diff" => Some(BlendMode::Difference),
+        b"dark" => Some(BlendMode::Darken),
+        b"lite" => Some(BlendMode::Lighten),
+        _ => None,
+    }
+}
+
+/// Big-endian cursor over PSD bytes.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.at.checked_add(len).ok_or_else(|| "Truncated PSD file".to_string())?;
+        let slice = self.bytes.get(self.at..end).ok_or_else(|| "Truncated PSD file".to_string())?;
+        self.at = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(self.u32()? as i32)
+    }
+
+    /// Skip a section prefixed by its u32 length
+    fn skip_section(&mut self) -> Result<(), String> {
+        let len = self.u32()? as usize;
+        self.take(len).map(|_| ())
+    }
+}
+
+/// Layer record fields needed to decode its pixels
+struct LayerRecord {
+    name: String,
+    top: i32,
+    left: i32,
+    width: u32,
+    height: u32,
+    channels: Vec<(i16, usize)>,
+    blend_key: [u8; 4],
+    opacity: u8,
+    flags: u8,
+}
+
+/// Decode the layers of an 8-bit RGB `.psd` file.
+///
+/// The canvas and every layer must fit within `max_size`, so a malformed
+/// header can't make the decoder allocate an arbitrarily large image.
+pub fn decode_psd(bytes: &[u8], max_size: [u32; 2]) -> Result<OraImage, String> {
+    let mut r = Reader { bytes, at: 0 };
+    if r.take(4)? != b"8BPS" || r.u16()? != 1 {
+        return Err("Not a PSD file (only version 1 .psd is supported)".to_string());
+    }
+    r.take(6)?; // reserved
+    r.u16()?; // channel count of the merged image
+    let height = r.u32()?;
+    let width = r.u32()?;
+    let depth = r.u16()?;
+    let color_mode = r.u16()?;
+    if depth != 8 || color_mode != COLOR_MODE_RGB {
+        return Err(format!(
+            "Unsupported PSD format: {}-bit, color mode {} (only 8-bit RGB is supported)",
+            depth, color_mode
+        ));
+    }
+    check_size("PSD canvas", width as i64, height as i64, max_size)?;
+
+    r.skip_section()?; // color mode data
+    r.skip_section()?; // image resources
+    let layer_and_mask_len = r.u32()?;
+    if layer_and_mask_len == 0 {
+        return Err("PSD file has no layers".to_string());
+    }
+    r.u32()?; // layer info length
+    let count = (r.u16()? as i16).unsigned_abs();
+
+    let mut records = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let (top, left, bottom, right) = (r.i32()?, r.i32()?, r.i32()?, r.i32()?);
+        let channel_count = r.u16()?;
+        let mut channels = Vec::with_capacity(channel_count as usize);
+        for _ in 0..channel_count {
+            channels.push((r.u16()? as i16, r.u32()? as usize));
+        }
+        if r.take(4)? != b"8BIM" {
+            return Err("Invalid PSD layer record".to_string());
+        }
+        let blend_key: [u8; 4] = r.take(4)?.try_into().unwrap();
+        let opacity = r.u8()?;
+        r.u8()?; // clipping
+        let flags = r.u8()?;
+        r.u8()?; // filler
+
+        let extra_len = r.u32()? as usize;
+        let extra_end = r.at.saturating_add(extra_len);
+        r.skip_section()?; // layer mask data
+        r.skip_section()?; // blending ranges
+        let name_len = r.u8()? as usize;
+        let name = String::from_utf8_lossy(r.take(name_len)?).into_owned();
+        r.at = extra_end; // padding and additional layer info
+
+        // Edges are i32, so compute the extent in i64 to avoid overflow
+        let width = (right as i64 - left as i64).max(0);
+        let height = (bottom as i64 - top as i64).max(0);
+        check_size(&format!("Layer '{}'", name), width, height, max_size)?;
+        records.push(LayerRecord {
+            name,
+            top,
+            left,
+            width: width as u32,
+            height: height as u32,
+            channels,
+            blend_key,
+            opacity,
+            flags,
+        });
+    }
+
+    let mut layers = Vec::new();
+    let mut warnings = Vec::new();
+    for record in records {
+        let channels = record
+            .channels
+            .iter()
+            .map(|&(id, len)| Ok((id, r.take(len)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Group markers and adjustment layers have no pixels
+        if record.width == 0 || record.height == 0 {
+            continue;
+        }
+        if record.flags & FLAG_HIDDEN != 0 {
+            warnings.push(format!("Skipping hidden layer '{}'", record.name));
+            continue;
+        }
+
+        let mut image = RgbaImage::from_pixel(record.width, record.height, Rgba([0, 0, 0, 255]));
+        for (id, data) in channels {
+            let channel = match id {
+                0..=2 => id as usize,
+                -1 => 3,
+                _ => continue, // layer and vector masks
+            };
+            let samples = decode_channel(data, record.width, record.height)?;
+            for (pixel, value) in image.pixels_mut().zip(samples) {
+                pixel[channel] = value;
+            }
+        }
+
+        let blend = blend_for_psd_key(&record.blend_key).unwrap_or_else(|| {
+            warnings.push(format!(
+                "Layer '{}': unsupported blend mode '{}', using normal",
+                record.name,
+                String::from_utf8_lossy(&record.blend_key)
+            ));
+            BlendMode::Normal
+        });
+        layers.push(OraLayer {
+            name: record.name,
+            image,
+            x: record.left,
+            y: record.top,
+            blend,
+            opacity: record.opacity as f64 / 255.0,
+        });
+    }
+
+    // Layer records are stored bottom first
+    Ok(OraImage { width, height, layers, warnings })
+}
+
+/// Error unless a `width` x `height` image fits within `max_size`.
+fn check_size(what: &str, width: i64, height: i64, max_size: [u32; 2]) -> Result<(), String> {
+    let [max_width, max_height] = max_size;
+    if width > max_width as i64 || height > max_height as i64 {
+        return Err(format!(
+            "{} is {}x{}, larger than the {}x{} size limit",
+            what, width, height, max_width, max_height
+        ));
+    }
+    Ok(())
+}
+
+/// Decode one channel's samples (raw or PackBits RLE).
+fn decode_channel(data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let len = width as usize * height as usize;
+    let compression = data.get(..2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    match compression {
+        Some(0) => Ok(data[2..].iter().copied().chain(std::iter::repeat(0)).take(len).collect()),
+        Some(1) => {
+            // Row byte counts precede the PackBits data
+            let mut input = data.get(2 + height as usize * 2..).unwrap_or_default().iter();
+            let mut out = Vec::with_capacity(len);
+            while out.len() < len {
+                let Some(&header) = input.next() else { break };
+                let header = header as i8;
+                if header >= 0 {
+                    out.extend(input.by_ref().take(header as usize + 1));
+                } else if header != -128 {
+                    let value = *input.next().unwrap_or(&0);
+                    out.extend(std::iter::repeat_n(value, (1 - header as isize) as usize));
+                }
+            }
+            out.resize(len, 0);
+            Ok(out)
+        }
+        Some(other) => Err(format!("Unsupported PSD channel compression {}", other)),
+        None if len == 0 => Ok(Vec::new()),
+        None => Err("Truncated PSD channel data".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A square, single-color test layer: (name, left, top, size, rgba, blend key, opacity)
+    type TestLayer<'a> = (&'a str, i32, i32, u32, [u8; 4], &'a [u8; 4], u8);
+
+    /// Build a PSD with the given layers
+    fn build_psd(width: u32, height: u32, layers: &[TestLayer]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"8BPS");
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&[0; 6]);
+        out.extend_from_slice(&3u16.to_be_bytes());
+        out.extend_from_slice(&height.to_be_bytes());
+        out.extend_from_slice(&width.to_be_bytes());
+        out.extend_from_slice(&8u16.to_be_bytes());
+        out.extend_from_slice(&COLOR_MODE_RGB.to_be_bytes());
+        out.extend_from_slice(&0u32.to_be_bytes()); // color mode data
+        out.extend_from_slice(&0u32.to_be_bytes()); // image resources
+
+        let mut info = Vec::new();
+        info.extend_from_slice(&(layers.len() as u16).to_be_bytes());
+        let mut channel_data = Vec::new();
+        for &(name, left, top, size, rgba, key, opacity) in layers {
+            for v in [top, left, top + size as i32, left + size as i32] {
+                info.extend_from_slice(&v.to_be_bytes());
+            }
+            info.extend_from_slice(&4u16.to_be_bytes());
+            for (id, value) in [(0i16, rgba[0]), (1, rgba[1]), (2, rgba[2]), (-1, rgba[3])] {
+                info.extend_from_slice(&id.to_be_bytes());
+                // Alpha is RLE compressed, color channels raw
+                let data: Vec<u8> = if id == -1 {
+                    let mut d = 1u16.to_be_bytes().to_vec();
+                    for _ in 0..size {
+                        d.extend_from_slice(&2u16.to_be_bytes());
+                    }
+                    for _ in 0..size {
+                        d.extend_from_slice(&[(1 - size as i32) as u8, value]);
+                    }
+                    d
+                } else {
+                    let mut d = 0u16.to_be_bytes().to_vec();
+                    d.extend(std::iter::repeat_n(value, (size * size) as usize));
+                    d
+                };
+                info.extend_from_slice(&(data.len() as u32).to_be_bytes());
+                channel_data.extend(data);
+            }
+            info.extend_from_slice(b"8BIM");
+            info.extend_from_slice(key);
+            info.extend_from_slice(&[opacity, 0, 0, 0]);
+            let mut extra = vec![0; 8]; // empty mask and blending ranges
+            extra.push(name.len() as u8);
+            extra.extend_from_slice(name.as_bytes());
+            while !extra.len().is_multiple_of(4) {
+                extra.push(0);
+            }
+            info.extend_from_slice(&(extra.len() as u32).to_be_bytes());
+            info.extend(extra);
+        }
+        info.extend(channel_data);
+
+        out.extend_from_slice(&(info.len() as u32 + 4).to_be_bytes());
+        out.extend_from_slice(&(info.len() as u32).to_be_bytes());
+        out.extend(info);
+        out
+    }
+
+    #[test]
+    fn test_decode_psd_layers() {
+        let psd = build_psd(
+            8,
+            6,
+            &[
+                ("ground", 0, 0, 4, [0, 200, 0, 255], b"norm", 255),
+                ("shade", 2, 3, 2, [10, 20, 30, 128], b"mul ", 128),
+            ],
+        );
+        let image = decode_psd(&psd, [4096, 4096]).unwrap();
+        assert_eq!((image.width, image.height), (8, 6));
+        assert!(image.warnings.is_empty(), "{:?}", image.warnings);
+
+        let names: Vec<&str> = image.layers.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["ground", "shade"]);
+        let shade = &image.layers[1];
+        assert_eq!((shade.x, shade.y), (2, 3));
+        assert_eq!(shade.image.dimensions(), (2, 2));
+        assert_eq!(shade.image.get_pixel(1, 1).0, [10, 20, 30, 128]);
+        assert_eq!(shade.blend, BlendMode::Multiply);
+        assert!((shade.opacity - 128.0 / 255.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decode_psd_rejects_other_formats() {
+        assert!(decode_psd(b"GIF89a", [4096, 4096]).is_err());
+        let mut psd = build_psd(2, 2, &[]);
+        psd[23] = 16; // depth
+        assert!(decode_psd(&psd, [4096, 4096]).unwrap_err().contains("16-bit"));
+    }
+
+    #[test]
+    fn test_decode_psd_size_limits() {
+        let psd = build_psd(8, 6, &[("big", 0, 0, 4, [0, 0, 0, 255], b"norm", 255)]);
+        let err = decode_psd(&psd, [4, 4]).unwrap_err();
+        assert_eq!(err, "PSD canvas is 8x6, larger than the 4x4 size limit");
+        let err = decode_psd(&psd, [8, 3]).unwrap_err();
+        assert!(err.starts_with("PSD canvas is 8x6"), "{}", err);
+        let err = decode_psd(&psd, [8, 6]).map(|_| ());
+        assert!(err.is_ok(), "{:?}", err);
+
+        let psd = build_psd(2, 2, &[("big", -3, 0, 4, [0, 0, 0, 255], b"norm", 255)]);
+        let err = decode_psd(&psd, [3, 8]).unwrap_err();
+        assert_eq!(err, "Layer 'big' is 4x4, larger than the 3x8 size limit");
+    }
+
+    #[test]
+    fn test_decode_psd_extreme_layer_edges() {
+        let mut psd = build_psd(2, 2, &[("edge", 0, 0, 1, [0, 0, 0, 255], b"norm", 255)]);
+        // Layer record edges follow the 38-byte header, both section lengths
+        // and the layer count: left = i32::MIN, right = i32::MAX
+        let record = 26 + 8 + 4 + 4 + 2;
+        psd[record + 4..record + 8].copy_from_slice(&i32::MIN.to_be_bytes());
+        psd[record + 12..record + 16].copy_from_slice(&i32::MAX.to_be_bytes());
+        let err = decode_psd(&psd, [4096, 4096]).unwrap_err();
+        assert!(err.contains("larger than the 4096x4096 size limit"), "{}", err);
+    }
+
+    #[test]
+    fn test_blend_keys() {
+        assert_eq!(blend_for_psd_key(b"lddg"), Some(BlendMode::Add));
+        assert_eq!(blend_for_psd_key(b"hue "), None);
+    }
+}
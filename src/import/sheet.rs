@@ -0,0 +1,388 @@
+//! Sprite sheet import: slice a sheet into frame sprites plus an animation.
+//!
+//! The whole sheet is quantized once, so every frame shares one palette and a
+//! token means the same color in every frame. Cells come from a fixed cell
+//! size, or from the fully transparent gutter rows and columns between
+//! frames. Fully transparent cells are skipped. With deduplication, frames
+//! identical to an earlier one reuse its sprite in the animation.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use image::{DynamicImage, RgbaImage};
+
+use super::{extract_structured_regions, import_dynamic_image, ImportOptions, ImportResult};
+
+/// Token the importer assigns to fully transparent pixels
+const TRANSPARENT_TOKEN: &str = "{_}";
+
+/// How a sheet is cut into frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetSlicing {
+    /// Split at fully transparent rows and columns
+    Auto,
+    /// A regular grid of fixed-size cells, read left to right, top to bottom
+    Cells { width: u32, height: u32 },
+}
+
+impl FromStr for SheetSlicing {
+    type Err = String;
+
+    /// Parse `auto` or a `WxH` cell size.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+        let parsed = s.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+        match parsed {
+            Some((width, height)) if width > 0 && height > 0 => Ok(Self::Cells { width, height }),
+            _ => Err(format!("expected 'auto' or WxH with positive sizes, got '{}'", s)),
+        }
+    }
+}
+
+/// A frame cut from the sheet.
+#[derive(Debug, Clone)]
+pub struct SheetFrame {
+    /// Left edge of the frame's cell in the sheet
+    pub x: u32,
+    /// Top edge of the frame's cell in the sheet
+    pub y: u32,
+    /// The frame's sprite, using the shared palette
+    pub result: ImportResult,
+}
+
+/// Result of importing a sprite sheet.
+#[derive(Debug, Clone)]
+pub struct SheetImport {
+    /// Animation name; the palette and frame sprites are named after it
+    pub name: String,
+    /// Palette shared by every frame
+    pub palette: HashMap<String, String>,
+    /// Imported frame sprites, in sheet order
+    pub frames: Vec<SheetFrame>,
+    /// Index into `frames` for each animation step
+    pub sequence: Vec<usize>,
+    /// Parts of the sheet that could not be imported exactly
+    pub warnings: Vec<String>,
+}
+
+impl SheetImport {
+    /// Name of the shared palette.
+    pub fn palette_name(&self) -> String {
+        format!("{}_palette", self.name)
+    }
+
+    /// Serialize to JSONL: the shared palette, a sprite per frame, then the
+    /// animation.
+    pub fn to_jsonl(&self) -> String {
+        let palette_name = self.palette_name();
+        let mut lines = vec![serde_json::json!({
+            "type": "palette",
+            "name": palette_name,
+            "colors": self.palette,
+        })
+        .to_string()];
+        lines.extend(self.frames.iter().map(|f| f.result.sprite_json(&palette_name).to_string()));
+
+        let frames: Vec<&str> =
+            self.sequence.iter().map(|&i| self.frames[i].result.name.as_str()).collect();
+        lines.push(
+            serde_json::json!({ "type": "animation", "name": self.name, "frames": frames })
+                .to_string(),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Import a sprite sheet image as one sprite per frame plus an animation.
+///
+/// The sheet is quantized to at most `max_colors` colors as a whole. With
+/// `dedupe`, frames identical to an earlier one are not emitted again.
+pub fn import_sheet<P: AsRef<Path>>(
+    path: P,
+    name: &str,
+    max_colors: usize,
+    slicing: SheetSlicing,
+    dedupe: bool,
+    options: &ImportOptions,
+) -> Result<SheetImport, String> {
+    let image = image::open(path.as_ref()).map_err(|e| format!("Failed to open image: {}", e))?;
+    import_sheet_image(image.to_rgba8(), name, max_colors, slicing, dedupe, options)
+}
+
+/// Slice a decoded sheet and import its frames.
+fn import_sheet_image(
+    image: RgbaImage,
+    name: &str,
+    max_colors: usize,
+    slicing: SheetSlicing,
+    dedupe: bool,
+    options: &ImportOptions,
+) -> Result<SheetImport, String> {
+    let (cells, warnings) = slice(&image, slicing)?;
+    let cells: Vec<Cell> = cells.into_iter().filter(|cell| cell.is_occupied(&image)).collect();
+    if cells.is_empty() {
+        return Err("Sheet has no non-transparent frames".to_string());
+    }
+
+    // Quantize once; per-frame analysis does not apply to a shared palette
+    let quantize = ImportOptions {
+        analyze: false,
+        hints: false,
+        extract_shapes: false,
+        half_sprite: false,
+        detect_upscale: false,
+        detect_outlines: false,
+        ..options.clone()
+    };
+    let width = image.width();
+    let sheet = import_dynamic_image(DynamicImage::ImageRgba8(image), name, max_colors, &quantize)?;
+    let mut tokens: Vec<&str> =
+        vec![TRANSPARENT_TOKEN; sheet.width as usize * sheet.height as usize];
+    for (token, points) in &sheet.regions {
+        for &[x, y] in points {
+            tokens[(y * width + x) as usize] = token;
+        }
+    }
+
+    let mut frames: Vec<SheetFrame> = Vec::new();
+    let mut sequence = Vec::with_capacity(cells.len());
+    let mut seen: HashMap<(u32, u32, Vec<String>), usize> = HashMap::new();
+    for cell in cells {
+        let mut grid = Vec::with_capacity(cell.height as usize);
+        let mut regions: HashMap<String, Vec<[u32; 2]>> = HashMap::new();
+        for y in 0..cell.height {
+            let mut row = String::new();
+            for x in 0..cell.width {
+                let token = tokens[((cell.y + y) * width + cell.x + x) as usize];
+                row.push_str(token);
+                // Uncovered pixels stay transparent without a region for them
+                if token != TRANSPARENT_TOKEN {
+                    regions.entry(token.to_string()).or_default().push([x, y]);
+                }
+            }
+            grid.push(row);
+        }
+
+        let key = (cell.width, cell.height, grid.clone());
+        if dedupe {
+            if let Some(&index) = seen.get(&key) {
+                sequence.push(index);
+                continue;
+            }
+        }
+        seen.entry(key).or_insert(frames.len());
+        sequence.push(frames.len());
+
+        let structured_regions = options.extract_shapes.then(|| {
+            regions
+                .iter()
+                .map(|(token, points)| {
+                    (token.clone(), extract_structured_regions(points, cell.width, cell.height))
+                })
+                .collect()
+        });
+        frames.push(SheetFrame {
+            x: cell.x,
+            y: cell.y,
+            result: ImportResult {
+                name: format!("{}_{}", name, frames.len() + 1),
+                width: cell.width,
+                height: cell.height,
+                palette: sheet.palette.clone(),
+                grid,
+                regions,
+                structured_regions,
+                analysis: None,
+                half_sprite: false,
+            },
+        });
+    }
+
+    Ok(SheetImport { name: name.to_string(), palette: sheet.palette, frames, sequence, warnings })
+}
+
+/// A rectangle of the sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Cell {
+    fn is_occupied(&self, image: &RgbaImage) -> bool {
+        (self.y..self.y + self.height)
+            .any(|y| (self.x..self.x + self.width).any(|x| image.get_pixel(x, y)[3] > 0))
+    }
+}
+
+/// Cut the sheet into cells, row by row, plus warnings for leftover pixels.
+fn slice(image: &RgbaImage, slicing: SheetSlicing) -> Result<(Vec<Cell>, Vec<String>), String> {
+    let (sheet_width, sheet_height) = image.dimensions();
+    let mut warnings = Vec::new();
+    let (columns, rows) = match slicing {
+        SheetSlicing::Cells { width, height } => {
+            if width > sheet_width || height > sheet_height {
+                return Err(format!(
+                    "Cell size {}x{} is larger than the {}x{} sheet",
+                    width, height, sheet_width, sheet_height
+                ));
+            }
+            if sheet_width % width != 0 || sheet_height % height != 0 {
+                warnings.push(format!(
+                    "Sheet size {}x{} is not a multiple of the {}x{} cell; ignoring the partial cells at the edges",
+                    sheet_width, sheet_height, width, height
+                ));
+            }
+            let columns = (0..sheet_width / width).map(|i| (i * width, width)).collect();
+            let rows = (0..sheet_height / height).map(|i| (i * height, height)).collect();
+            (columns, rows)
+        }
+        SheetSlicing::Auto => {
+            let opaque = |x: u32, y: u32| image.get_pixel(x, y)[3] > 0;
+            let columns = bands(sheet_width, |x| (0..sheet_height).any(|y| opaque(x, y)));
+            let rows = bands(sheet_height, |y| (0..sheet_width).any(|x| opaque(x, y)));
+            (columns, rows)
+        }
+    };
+
+    let cells = rows
+        .iter()
+        .flat_map(|&(y, height)| {
+            columns.iter().map(move |&(x, width)| Cell { x, y, width, height })
+        })
+        .collect();
+    Ok((cells, warnings))
+}
+
+/// Runs of occupied lines as `(start, length)` pairs.
+fn bands(len: u32, occupied: impl Fn(u32) -> bool) -> Vec<(u32, u32)> {
+    let mut bands = Vec::new();
+    let mut start = None;
+    for i in 0..len {
+        match (occupied(i), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                bands.push((s, i - s));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        bands.push((s, len - s));
+    }
+    bands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn fill(image: &mut RgbaImage, x: u32, y: u32, w: u32, h: u32, color: Rgba<u8>) {
+        for py in y..y + h {
+            for px in x..x + w {
+                image.put_pixel(px, py, color);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_slicing() {
+        assert_eq!("auto".parse::<SheetSlicing>(), Ok(SheetSlicing::Auto));
+        assert_eq!(
+            "16x8".parse::<SheetSlicing>(),
+            Ok(SheetSlicing::Cells { width: 16, height: 8 })
+        );
+        assert!("0x8".parse::<SheetSlicing>().is_err());
+        assert!("16".parse::<SheetSlicing>().is_err());
+    }
+
+    #[test]
+    fn test_bands() {
+        let occupied = [false, true, true, false, false, true];
+        assert_eq!(bands(6, |i| occupied[i as usize]), vec![(1, 2), (5, 1)]);
+        assert_eq!(bands(3, |_| false), vec![]);
+    }
+
+    #[test]
+    fn test_fixed_cells_share_palette_and_dedupe() {
+        // Four 4x4 cells: red, blue, red, empty
+        let mut image = RgbaImage::new(16, 4);
+        fill(&mut image, 0, 0, 4, 4, RED);
+        fill(&mut image, 5, 1, 2, 2, BLUE);
+        fill(&mut image, 8, 0, 4, 4, RED);
+        let slicing = SheetSlicing::Cells { width: 4, height: 4 };
+        let options = ImportOptions::default();
+
+        let all = import_sheet_image(image.clone(), "walk", 16, slicing, false, &options).unwrap();
+        assert_eq!(all.frames.len(), 3);
+        assert_eq!(all.sequence, vec![0, 1, 2]);
+        assert_eq!((all.frames[2].x, all.frames[2].y), (8, 0));
+
+        let deduped = import_sheet_image(image, "walk", 16, slicing, true, &options).unwrap();
+        assert_eq!(deduped.frames.len(), 2);
+        assert_eq!(deduped.sequence, vec![0, 1, 0]);
+        assert!(deduped.warnings.is_empty());
+
+        // Same token for red in both frames, and no region for transparency
+        let red = &deduped.frames[0].result;
+        let blue = &deduped.frames[1].result;
+        assert_eq!(red.regions.len(), 1);
+        assert_eq!(blue.regions.values().next().unwrap().len(), 4);
+        assert!(!blue.regions.contains_key(TRANSPARENT_TOKEN));
+        assert!(!red.regions.keys().any(|t| blue.regions.contains_key(t)));
+
+        let jsonl = deduped.to_jsonl();
+        let lines: Vec<serde_json::Value> =
+            jsonl.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1]["palette"], "walk_palette");
+        assert_eq!(lines[3]["type"], "animation");
+        assert_eq!(lines[3]["frames"], serde_json::json!(["walk_1", "walk_2", "walk_1"]));
+    }
+
+    #[test]
+    fn test_auto_slicing_uses_gutters() {
+        // Two 3x2 frames separated by a one-pixel gutter
+        let mut image = RgbaImage::new(8, 4);
+        fill(&mut image, 1, 1, 3, 2, RED);
+        fill(&mut image, 5, 1, 3, 2, BLUE);
+        let result = import_sheet_image(
+            image,
+            "s",
+            16,
+            SheetSlicing::Auto,
+            false,
+            &ImportOptions::default(),
+        )
+        .unwrap();
+        let cells: Vec<_> =
+            result.frames.iter().map(|f| (f.x, f.y, f.result.width, f.result.height)).collect();
+        assert_eq!(cells, vec![(1, 1, 3, 2), (5, 1, 3, 2)]);
+    }
+
+    #[test]
+    fn test_cell_errors() {
+        let image = RgbaImage::new(8, 8);
+        let slicing = SheetSlicing::Cells { width: 16, height: 4 };
+        assert!(slice(&image, slicing).is_err());
+        let err =
+            import_sheet_image(image, "s", 16, SheetSlicing::Auto, false, &Default::default())
+                .unwrap_err();
+        assert!(err.contains("no non-transparent frames"));
+
+        let (cells, warnings) =
+            slice(&RgbaImage::new(10, 4), SheetSlicing::Cells { width: 4, height: 4 }).unwrap();
+        assert_eq!(cells.len(), 2);
+        assert_eq!(warnings.len(), 1);
+    }
+}
@@ -6,7 +6,9 @@ use std::fs;
 use std::path::Path;
 use thiserror::Error;
 
-use crate::templates::justfile::{generate_justfile, JustfileTemplate};
+use crate::templates::justfile::{
+    generate_justfile, generate_justfile_with_options, JustfileOptions, JustfileTemplate,
+};
 
 /// Error during project initialization
 #[derive(Debug, Error)]
@@ -22,7 +24,9 @@ pub enum InitError {
     #[error("Failed to write file: {0}")]
     WriteFile(std::io::Error),
     /// Unknown preset
-    #[error("Unknown preset '{0}'. Available: minimal, artist, animator, game")]
+    #[error(
+        "Unknown preset '{0}'. Available: minimal, artist, animator, game, gameboy, pico8, hd"
+    )]
     UnknownPreset(String),
 }
 
@@ -37,9 +41,26 @@ pub enum Preset {
     Animator,
     /// Full game asset pipeline with atlases, multi-export
     Game,
+    /// GB-style game: 4-color palette, 8x8 sprites and tiles
+    GameBoy,
+    /// PICO-8 style game: 16-color palette, 128x128 sprite sheet
+    Pico8,
+    /// Modern HD pixel art: shading ramps, 32x32 characters
+    Hd,
 }
 
 impl Preset {
+    /// All presets, in the order the init wizard lists them
+    pub const ALL: [Preset; 7] = [
+        Preset::Minimal,
+        Preset::Artist,
+        Preset::Animator,
+        Preset::Game,
+        Preset::GameBoy,
+        Preset::Pico8,
+        Preset::Hd,
+    ];
+
     /// Parse preset name from string
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
@@ -47,9 +68,50 @@ impl Preset {
             "artist" => Some(Preset::Artist),
             "animator" => Some(Preset::Animator),
             "game" => Some(Preset::Game),
+            "gameboy" | "gb" => Some(Preset::GameBoy),
+            "pico8" | "pico-8" => Some(Preset::Pico8),
+            "hd" => Some(Preset::Hd),
             _ => None,
         }
     }
+
+    /// Preset name as accepted by `--preset`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Minimal => "minimal",
+            Preset::Artist => "artist",
+            Preset::Animator => "animator",
+            Preset::Game => "game",
+            Preset::GameBoy => "gameboy",
+            Preset::Pico8 => "pico8",
+            Preset::Hd => "hd",
+        }
+    }
+
+    /// One-line description shown by the init wizard
+    pub fn description(&self) -> &'static str {
+        match self {
+            Preset::Minimal => "Single sprite, basic render",
+            Preset::Artist => "Static art with palettes and variants",
+            Preset::Animator => "Animation frames with GIF previews",
+            Preset::Game => "Game asset pipeline with atlases and exports",
+            Preset::GameBoy => "GB-style game: 4-color palette, 8x8 sprites and tiles",
+            Preset::Pico8 => "PICO-8 style game: 16 colors, 128x128 sprite sheet",
+            Preset::Hd => "Modern HD pixel art: shading ramps, 32x32 characters",
+        }
+    }
+
+    /// Example sprite file to render first, relative to the project root
+    pub fn example_file(&self) -> &'static str {
+        match self {
+            Preset::Minimal => "src/pxl/sprites/example.pxl",
+            Preset::Artist => "src/pxl/sprites/character.pxl",
+            Preset::Animator => "src/pxl/sprites/walk.pxl",
+            Preset::Game => "src/pxl/sprites/player/idle.pxl",
+            Preset::GameBoy | Preset::Pico8 => "src/pxl/sprites/player.pxl",
+            Preset::Hd => "src/pxl/sprites/hero.pxl",
+        }
+    }
 }
 
 /// Initialize a new pixelsrc project.
@@ -60,7 +122,8 @@ impl Preset {
 /// # Arguments
 /// - `path` - Directory to create the project in
 /// - `name` - Project name (used in pxl.toml)
-/// - `preset` - Preset template name ("minimal", "artist", "animator", "game")
+/// - `preset` - Preset template name ("minimal", "artist", "animator", "game",
+///   "gameboy", "pico8", "hd")
 ///
 /// # Returns
 /// - `Ok(())` on success
@@ -88,6 +151,9 @@ pub fn init_project(path: &Path, name: &str, preset: &str) -> Result<(), InitErr
         Preset::Artist => init_artist(path, name),
         Preset::Animator => init_animator(path, name),
         Preset::Game => init_game(path, name),
+        Preset::GameBoy => init_gameboy(path, name),
+        Preset::Pico8 => init_pico8(path, name),
+        Preset::Hd => init_hd(path, name),
     }
 }
 
@@ -477,6 +543,356 @@ fn generate_ui_elements() -> String {
     .to_string()
 }
 
+/// Generate a game justfile with build, watch, and per-atlas recipes.
+fn generate_atlas_justfile(name: &str, atlases: &[&str]) -> String {
+    let options = JustfileOptions {
+        include_watch: true,
+        atlas_names: atlases.iter().map(|a| a.to_string()).collect(),
+        ..Default::default()
+    };
+    generate_justfile_with_options(JustfileTemplate::Game, name, &options)
+}
+
+// ============================================================================
+// Game Boy Preset
+// ============================================================================
+
+/// Initialize a GB-style game project structure.
+///
+/// Four-color palette, 8x8 sprites and background tiles, lint capped at 4 colors.
+fn init_gameboy(path: &Path, name: &str) -> Result<(), InitError> {
+    // Create directory structure
+    create_dir(path)?;
+    create_dir(&path.join("src/pxl/palettes"))?;
+    create_dir(&path.join("src/pxl/sprites"))?;
+    create_dir(&path.join("src/pxl/tiles"))?;
+    create_dir(&path.join("src/pxl/animations"))?;
+    create_dir(&path.join("build/animations"))?;
+
+    // Write pxl.toml
+    let pxl_toml = generate_gameboy_config(name);
+    write_file(&path.join("pxl.toml"), &pxl_toml)?;
+
+    // Write .gitignore
+    let gitignore = generate_gitignore();
+    write_file(&path.join(".gitignore"), &gitignore)?;
+
+    // Write justfile
+    let justfile = generate_atlas_justfile(name, &["sprites", "tiles"]);
+    write_file(&path.join("justfile"), &justfile)?;
+
+    // Write palette
+    let palette = generate_gameboy_palette();
+    write_file(&path.join("src/pxl/palettes/dmg.pxl"), &palette)?;
+
+    // Write player sprites
+    let player = generate_gameboy_player();
+    write_file(&path.join("src/pxl/sprites/player.pxl"), &player)?;
+
+    // Write background tiles
+    let tiles = generate_gameboy_tiles();
+    write_file(&path.join("src/pxl/tiles/ground.pxl"), &tiles)?;
+
+    // Write animation
+    let animation = generate_player_idle_animation();
+    write_file(&path.join("src/pxl/animations/player.pxl"), &animation)?;
+
+    // Write build/.gitkeep
+    write_file(&path.join("build/.gitkeep"), "")?;
+
+    Ok(())
+}
+
+/// Generate GB preset pxl.toml configuration.
+fn generate_gameboy_config(name: &str) -> String {
+    format!(
+        r#"[project]
+name = "{}"
+version = "0.1.0"
+src = "src/pxl"
+out = "build"
+
+# 160x144 screen, 8x8 sprites and tiles
+[defaults]
+scale = 1
+padding = 0
+
+[atlases.sprites]
+sources = ["sprites/**/*.pxl"]
+max_size = [128, 128]
+padding = 0
+power_of_two = true
+
+[atlases.tiles]
+sources = ["tiles/**/*.pxl"]
+max_size = [128, 128]
+padding = 0
+power_of_two = true
+
+[animations]
+sources = ["animations/**/*.pxl"]
+preview = true
+preview_scale = 4
+
+[validate]
+strict = true
+unused_palettes = "warn"
+missing_refs = "error"
+
+[lint]
+max_colors = 4
+
+[watch]
+debounce_ms = 100
+clear_screen = true
+"#,
+        name
+    )
+}
+
+/// Generate the 4-color DMG palette.
+fn generate_gameboy_palette() -> String {
+    r##"{"type": "palette", "name": "dmg", "colors": {"_": "#00000000", "lightest": "#9BBC0F", "light": "#8BAC0F", "dark": "#306230", "darkest": "#0F380F"}}"##.to_string()
+}
+
+/// Generate 8x8 GB player sprites.
+fn generate_gameboy_player() -> String {
+    r#"{"type": "sprite", "name": "player_idle_1", "size": [8, 8], "palette": "dmg", "regions": {"darkest": {"union": [{"stroke": [1, 0, 6, 5]}, {"points": [[2, 7], [5, 7]]}], "z": 0}, "lightest": {"rect": [2, 1, 4, 3], "z": 1}, "dark": {"union": [{"points": [[3, 2], [4, 2]]}, {"rect": [2, 5, 4, 2]}], "z": 2}}}
+{"type": "sprite", "name": "player_idle_2", "size": [8, 8], "palette": "dmg", "regions": {"darkest": {"union": [{"stroke": [1, 1, 6, 5]}, {"points": [[2, 7], [5, 7]]}], "z": 0}, "lightest": {"rect": [2, 2, 4, 3], "z": 1}, "dark": {"union": [{"points": [[3, 3], [4, 3]]}, {"rect": [2, 6, 4, 1]}], "z": 2}}}"#
+    .to_string()
+}
+
+/// Generate 8x8 GB background tiles.
+fn generate_gameboy_tiles() -> String {
+    r#"{"type": "sprite", "name": "ground", "size": [8, 8], "palette": "dmg", "regions": {"light": {"rect": [0, 0, 8, 8], "z": 0}, "dark": {"rect": [0, 0, 8, 2], "z": 1}, "darkest": {"points": [[1, 4], [5, 3], [3, 6], [6, 6]], "z": 2}}}
+{"type": "sprite", "name": "brick", "size": [8, 8], "palette": "dmg", "regions": {"dark": {"rect": [0, 0, 8, 8], "z": 0}, "darkest": {"union": [{"rect": [0, 3, 8, 1]}, {"rect": [0, 7, 8, 1]}, {"rect": [3, 0, 1, 3]}, {"rect": [7, 4, 1, 3]}], "z": 1}}}"#
+    .to_string()
+}
+
+/// Generate the two-frame player idle animation shared by the retro presets.
+fn generate_player_idle_animation() -> String {
+    r#"{"type": "include", "path": "../sprites/player.pxl"}
+{"type": "animation", "name": "player_idle", "frames": ["player_idle_1", "player_idle_2"], "duration": 400}"#
+        .to_string()
+}
+
+// ============================================================================
+// PICO-8 Preset
+// ============================================================================
+
+/// Initialize a PICO-8 style game project structure.
+///
+/// Sixteen-color palette, 8x8 sprites packed into a 128x128 sheet.
+fn init_pico8(path: &Path, name: &str) -> Result<(), InitError> {
+    // Create directory structure
+    create_dir(path)?;
+    create_dir(&path.join("src/pxl/palettes"))?;
+    create_dir(&path.join("src/pxl/sprites"))?;
+    create_dir(&path.join("src/pxl/animations"))?;
+    create_dir(&path.join("build/animations"))?;
+
+    // Write pxl.toml
+    let pxl_toml = generate_pico8_config(name);
+    write_file(&path.join("pxl.toml"), &pxl_toml)?;
+
+    // Write .gitignore
+    let gitignore = generate_gitignore();
+    write_file(&path.join(".gitignore"), &gitignore)?;
+
+    // Write justfile
+    let justfile = generate_atlas_justfile(name, &["sheet"]);
+    write_file(&path.join("justfile"), &justfile)?;
+
+    // Write palette
+    let palette = generate_pico8_palette();
+    write_file(&path.join("src/pxl/palettes/pico8.pxl"), &palette)?;
+
+    // Write player sprites
+    let player = generate_pico8_player();
+    write_file(&path.join("src/pxl/sprites/player.pxl"), &player)?;
+
+    // Write item sprites
+    let items = generate_pico8_items();
+    write_file(&path.join("src/pxl/sprites/items.pxl"), &items)?;
+
+    // Write animation
+    let animation = generate_player_idle_animation();
+    write_file(&path.join("src/pxl/animations/player.pxl"), &animation)?;
+
+    // Write build/.gitkeep
+    write_file(&path.join("build/.gitkeep"), "")?;
+
+    Ok(())
+}
+
+/// Generate PICO-8 preset pxl.toml configuration.
+fn generate_pico8_config(name: &str) -> String {
+    format!(
+        r#"[project]
+name = "{}"
+version = "0.1.0"
+src = "src/pxl"
+out = "build"
+
+# 128x128 screen, 8x8 sprites on a single 128x128 sheet
+[defaults]
+scale = 1
+padding = 0
+
+[atlases.sheet]
+sources = ["sprites/**/*.pxl"]
+max_size = [128, 128]
+padding = 0
+
+[animations]
+sources = ["animations/**/*.pxl"]
+preview = true
+preview_scale = 4
+
+[validate]
+strict = true
+unused_palettes = "warn"
+missing_refs = "error"
+
+[lint]
+max_colors = 16
+
+[watch]
+debounce_ms = 100
+clear_screen = true
+"#,
+        name
+    )
+}
+
+/// Generate the PICO-8 16-color palette.
+fn generate_pico8_palette() -> String {
+    r##"{"type": "palette", "name": "pico8", "colors": {"_": "#00000000", "black": "#000000", "dark_blue": "#1D2B53", "dark_purple": "#7E2553", "dark_green": "#008751", "brown": "#AB5236", "dark_gray": "#5F574F", "light_gray": "#C2C3C7", "white": "#FFF1E8", "red": "#FF004D", "orange": "#FFA300", "yellow": "#FFEC27", "green": "#00E436", "blue": "#29ADFF", "indigo": "#83769C", "pink": "#FF77A8", "peach": "#FFCCAA"}}"##.to_string()
+}
+
+/// Generate 8x8 PICO-8 player sprites.
+fn generate_pico8_player() -> String {
+    r#"{"type": "sprite", "name": "player_idle_1", "size": [8, 8], "palette": "pico8", "regions": {"red": {"rect": [1, 0, 6, 2], "z": 0}, "peach": {"rect": [2, 2, 4, 3], "z": 0}, "black": {"points": [[3, 3], [4, 3]], "z": 1}, "blue": {"rect": [1, 5, 6, 2], "z": 0}, "dark_blue": {"points": [[2, 7], [5, 7]], "z": 0}}}
+{"type": "sprite", "name": "player_idle_2", "size": [8, 8], "palette": "pico8", "regions": {"red": {"rect": [1, 1, 6, 2], "z": 0}, "peach": {"rect": [2, 3, 4, 2], "z": 0}, "black": {"points": [[3, 3], [4, 3]], "z": 1}, "blue": {"rect": [1, 5, 6, 2], "z": 0}, "dark_blue": {"points": [[1, 7], [6, 7]], "z": 0}}}"#
+    .to_string()
+}
+
+/// Generate 8x8 PICO-8 item sprites.
+fn generate_pico8_items() -> String {
+    r#"{"type": "sprite", "name": "coin", "size": [8, 8], "palette": "pico8", "regions": {"yellow": {"circle": [4, 4, 3], "z": 0}, "orange": {"rect": [4, 2, 1, 5], "z": 1}, "white": {"points": [[3, 2]], "z": 2}}}
+{"type": "sprite", "name": "heart", "size": [8, 8], "palette": "pico8", "regions": {"red": {"union": [{"rect": [1, 1, 2, 1]}, {"rect": [5, 1, 2, 1]}, {"rect": [0, 2, 8, 2]}, {"rect": [1, 4, 6, 1]}, {"rect": [2, 5, 4, 1]}, {"rect": [3, 6, 2, 1]}], "z": 0}, "pink": {"points": [[1, 2]], "z": 1}}}"#
+    .to_string()
+}
+
+// ============================================================================
+// HD Preset
+// ============================================================================
+
+/// Initialize a modern HD pixel art project structure.
+///
+/// Shading-ramp palette, 32x32 characters, and a larger padded atlas.
+fn init_hd(path: &Path, name: &str) -> Result<(), InitError> {
+    // Create directory structure
+    create_dir(path)?;
+    create_dir(&path.join("src/pxl/palettes"))?;
+    create_dir(&path.join("src/pxl/sprites"))?;
+    create_dir(&path.join("src/pxl/animations"))?;
+    create_dir(&path.join("build/animations"))?;
+
+    // Write pxl.toml
+    let pxl_toml = generate_hd_config(name);
+    write_file(&path.join("pxl.toml"), &pxl_toml)?;
+
+    // Write .gitignore
+    let gitignore = generate_gitignore();
+    write_file(&path.join(".gitignore"), &gitignore)?;
+
+    // Write justfile
+    let justfile = generate_atlas_justfile(name, &["characters"]);
+    write_file(&path.join("justfile"), &justfile)?;
+
+    // Write palette
+    let palette = generate_hd_palette();
+    write_file(&path.join("src/pxl/palettes/main.pxl"), &palette)?;
+
+    // Write character sprites
+    let hero = generate_hd_hero();
+    write_file(&path.join("src/pxl/sprites/hero.pxl"), &hero)?;
+
+    // Write animation
+    let animation = generate_hd_animation();
+    write_file(&path.join("src/pxl/animations/hero.pxl"), &animation)?;
+
+    // Write build/.gitkeep
+    write_file(&path.join("build/.gitkeep"), "")?;
+
+    Ok(())
+}
+
+/// Generate HD preset pxl.toml configuration.
+fn generate_hd_config(name: &str) -> String {
+    format!(
+        r#"[project]
+name = "{}"
+version = "0.1.0"
+src = "src/pxl"
+out = "build"
+
+# 32x32 characters, previewed at 2x
+[defaults]
+scale = 2
+padding = 1
+
+[atlases.characters]
+sources = ["sprites/**/*.pxl"]
+max_size = [2048, 2048]
+padding = 2
+power_of_two = true
+
+[animations]
+sources = ["animations/**/*.pxl"]
+preview = true
+preview_scale = 4
+sheet_layout = "horizontal"
+
+[export.generic]
+enabled = true
+
+[validate]
+strict = true
+unused_palettes = "warn"
+missing_refs = "error"
+
+[lint]
+max_colors = 32
+
+[watch]
+debounce_ms = 100
+clear_screen = true
+"#,
+        name
+    )
+}
+
+/// Generate the HD palette with shadow/base/light ramps per material.
+fn generate_hd_palette() -> String {
+    r##"{"type": "palette", "name": "main", "colors": {"_": "#00000000", "outline": "#1B1A26", "eye": "#F4F4F4", "skin_shadow": "#C27C5A", "skin": "#F0A982", "skin_light": "#FFD4B8", "hair_dark": "#4A2C2A", "hair": "#7A4634", "cloth_dark": "#23476B", "cloth": "#3A6EA5", "cloth_light": "#6FA3D6", "metal": "#C9CED6", "boot": "#3B2F2F"}}"##.to_string()
+}
+
+/// Generate 32x32 HD hero sprites.
+fn generate_hd_hero() -> String {
+    r#"{"type": "sprite", "name": "hero_idle_1", "size": [32, 32], "palette": "main", "regions": {"outline": {"union": [{"ellipse": [16, 9, 7, 7]}, {"rect": [9, 15, 14, 12]}, {"rect": [10, 26, 5, 6]}, {"rect": [17, 26, 5, 6]}], "z": 0}, "skin_shadow": {"ellipse": [16, 9, 6, 6], "z": 1}, "skin": {"ellipse": [15, 8, 5, 5], "z": 2}, "skin_light": {"circle": [13, 6, 2], "z": 3}, "hair": {"rect": [11, 3, 10, 3], "z": 4}, "hair_dark": {"union": [{"rect": [10, 5, 2, 5]}, {"rect": [20, 5, 2, 5]}], "z": 4}, "eye": {"union": [{"rect": [13, 9, 1, 2]}, {"rect": [18, 9, 1, 2]}], "z": 5}, "cloth_dark": {"rect": [10, 16, 12, 10], "z": 1}, "cloth": {"rect": [10, 16, 9, 9], "z": 2}, "cloth_light": {"rect": [11, 17, 2, 6], "z": 3}, "metal": {"rect": [10, 22, 12, 2], "z": 4}, "boot": {"union": [{"rect": [11, 27, 3, 4]}, {"rect": [18, 27, 3, 4]}], "z": 1}}}
+{"type": "sprite", "name": "hero_idle_2", "size": [32, 32], "palette": "main", "regions": {"outline": {"union": [{"ellipse": [16, 10, 7, 7]}, {"rect": [9, 16, 14, 11]}, {"rect": [10, 26, 5, 6]}, {"rect": [17, 26, 5, 6]}], "z": 0}, "skin_shadow": {"ellipse": [16, 10, 6, 6], "z": 1}, "skin": {"ellipse": [15, 9, 5, 5], "z": 2}, "skin_light": {"circle": [13, 7, 2], "z": 3}, "hair": {"rect": [11, 4, 10, 3], "z": 4}, "hair_dark": {"union": [{"rect": [10, 6, 2, 5]}, {"rect": [20, 6, 2, 5]}], "z": 4}, "eye": {"union": [{"rect": [13, 10, 1, 2]}, {"rect": [18, 10, 1, 2]}], "z": 5}, "cloth_dark": {"rect": [10, 17, 12, 9], "z": 1}, "cloth": {"rect": [10, 17, 9, 8], "z": 2}, "cloth_light": {"rect": [11, 18, 2, 5], "z": 3}, "metal": {"rect": [10, 22, 12, 2], "z": 4}, "boot": {"union": [{"rect": [11, 27, 3, 4]}, {"rect": [18, 27, 3, 4]}], "z": 1}}}"#
+    .to_string()
+}
+
+/// Generate HD hero animation.
+fn generate_hd_animation() -> String {
+    r#"{"type": "include", "path": "../sprites/hero.pxl"}
+{"type": "animation", "name": "hero_idle", "frames": ["hero_idle_1", "hero_idle_2"], "duration": 300}"#
+        .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -489,6 +905,9 @@ mod tests {
         assert_eq!(Preset::from_str("artist"), Some(Preset::Artist));
         assert_eq!(Preset::from_str("animator"), Some(Preset::Animator));
         assert_eq!(Preset::from_str("game"), Some(Preset::Game));
+        assert_eq!(Preset::from_str("gb"), Some(Preset::GameBoy));
+        assert_eq!(Preset::from_str("PICO-8"), Some(Preset::Pico8));
+        assert_eq!(Preset::from_str("hd"), Some(Preset::Hd));
         assert_eq!(Preset::from_str("unknown"), None);
     }
 
@@ -858,4 +1277,75 @@ mod tests {
         assert!(ui.contains("button_normal"));
         assert!(ui.contains("button_hover"));
     }
+
+    // ========================================================================
+    // Retro and HD Preset Tests
+    // ========================================================================
+
+    #[test]
+    fn test_preset_names_round_trip() {
+        for preset in Preset::ALL {
+            assert_eq!(Preset::from_str(preset.name()), Some(preset));
+        }
+    }
+
+    #[test]
+    fn test_init_gameboy_creates_structure() {
+        let temp = TempDir::new().unwrap();
+        let project_path = temp.path().join("gb");
+
+        init_project(&project_path, "gb", "gameboy").unwrap();
+
+        assert!(project_path.join("src/pxl/palettes/dmg.pxl").exists());
+        assert!(project_path.join("src/pxl/sprites/player.pxl").exists());
+        assert!(project_path.join("src/pxl/tiles/ground.pxl").exists());
+        assert!(project_path.join("src/pxl/animations/player.pxl").exists());
+
+        let config = fs::read_to_string(project_path.join("pxl.toml")).unwrap();
+        assert!(config.contains("[atlases.tiles]"));
+        assert!(config.contains("max_colors = 4"));
+
+        let justfile = fs::read_to_string(project_path.join("justfile")).unwrap();
+        assert!(justfile.contains("watch:"));
+        assert!(justfile.contains("atlas-tiles:"));
+        assert!(!justfile.contains("atlas-ui:"));
+    }
+
+    #[test]
+    fn test_init_presets_write_valid_config_and_sources() {
+        for preset in [Preset::GameBoy, Preset::Pico8, Preset::Hd] {
+            let temp = TempDir::new().unwrap();
+            let project_path = temp.path().join("proj");
+
+            init_project(&project_path, "proj", preset.name()).unwrap();
+
+            let config =
+                crate::config::loader::load_config(Some(&project_path.join("pxl.toml"))).unwrap();
+            assert!(!config.atlases.is_empty());
+            assert!(config.validate().is_empty());
+
+            let example = fs::read_to_string(project_path.join(preset.example_file())).unwrap();
+            let parsed = crate::parser::parse_stream(example.as_bytes());
+            assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+            assert!(!parsed.objects.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_init_palettes_fit_lint_limits() {
+        for (preset, file, colors) in
+            [(Preset::GameBoy, "dmg.pxl", 4), (Preset::Pico8, "pico8.pxl", 16)]
+        {
+            let temp = TempDir::new().unwrap();
+            let project_path = temp.path().join("proj");
+
+            init_project(&project_path, "proj", preset.name()).unwrap();
+
+            let palette =
+                fs::read_to_string(project_path.join("src/pxl/palettes").join(file)).unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&palette).unwrap();
+            let opaque = parsed["colors"].as_object().unwrap().len() - 1;
+            assert_eq!(opaque, colors);
+        }
+    }
 }
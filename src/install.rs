@@ -515,8 +515,13 @@ mod tests {
             animations: AnimationsConfig::default(),
             exports: ExportsConfig::default(),
             validate: ValidateConfig::default(),
+            lint: LintConfig::default(),
             watch: WatchConfig::default(),
             dependencies: deps,
+            profiles: HashMap::new(),
+            output: OutputConfig::default(),
+            limits: LimitsConfig::default(),
+            hooks: HooksConfig::default(),
         }
     }
 
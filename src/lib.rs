@@ -38,46 +38,84 @@ pub mod analyze;
 pub mod animation;
 pub mod antialias;
 pub mod atlas;
+pub mod baseline;
+pub mod bridge;
 pub mod build;
+pub mod bundle;
 pub mod cli;
+pub mod clipboard;
 pub mod color;
 pub mod composition;
+pub mod conditions;
 pub mod config;
+pub mod crop;
+pub mod cursor;
+pub mod daemon;
 pub mod diff;
 pub mod draw;
+pub mod edit;
 pub mod emoji;
 pub mod explain;
 pub mod export;
+pub mod extends;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fix;
+pub mod flipbook;
 pub mod fmt;
 pub mod gif;
+pub mod idle;
 pub mod import;
 pub mod include;
 pub mod init;
 pub mod install;
+pub mod limits;
+pub mod lint;
+pub mod locale;
 #[cfg(feature = "lsp")]
 pub mod lsp;
 pub mod lsp_agent_client;
 pub mod mask;
+pub mod material;
 #[cfg(feature = "mcp")]
 pub mod mcp;
 pub mod models;
 pub mod modifiers;
 pub mod motion;
+pub mod motion_preset;
 pub mod onion;
+pub mod ora;
+pub mod ordered;
 pub mod output;
+pub mod overlay;
 pub mod palette_cycle;
 pub mod palette_parser;
+pub mod palette_swap;
 pub mod palettes;
 pub mod parser;
 pub mod particle;
 pub mod path;
+pub mod playback;
+pub mod plugin;
 pub mod prime;
+pub mod provenance;
+pub mod random_set;
 pub mod registry;
+pub mod render_job;
 pub mod renderer;
 pub mod resolve_imports;
+pub mod rng;
+pub mod roundtrip;
+pub mod runtime;
 pub mod scaffold;
+pub mod selection;
+pub mod sequence;
+pub mod serve;
 pub mod shapes;
+pub mod snapshot;
 pub mod spritesheet;
+pub mod stack;
+pub mod stamp;
 pub mod state;
 pub mod structured;
 pub mod suggest;
@@ -85,9 +123,15 @@ pub mod telemetry;
 pub mod templates;
 pub mod terminal;
 pub mod transforms;
+pub mod underlay;
 pub mod validate;
 pub mod variables;
+#[cfg(feature = "video")]
+pub mod video;
+pub mod warning;
 pub mod watch;
+pub mod web;
+pub mod workspace;
 
 #[cfg(feature = "python")]
 pub mod python;
@@ -0,0 +1,149 @@
+//! Resource guardrails for sprites, palettes, compositions, and animations
+//!
+//! [`check_object`] compares a parsed object against the `[limits]` settings
+//! (see [`LimitsConfig`]) using only its declared dimensions and counts, so
+//! an oversized definition is rejected before any pixels are allocated.
+
+use std::collections::HashSet;
+
+use crate::config::LimitsConfig;
+use crate::models::{Animation, Composition, Sprite, TtpObject};
+
+/// Check one object against `limits`, describing the first limit it exceeds.
+pub fn check_object(obj: &TtpObject, limits: &LimitsConfig) -> Result<(), String> {
+    match obj {
+        TtpObject::Palette(palette) => {
+            check_colors("Palette", &palette.name, palette.colors.len(), limits)
+        }
+        TtpObject::Sprite(sprite) => check_sprite(sprite, limits),
+        TtpObject::Composition(comp) => check_composition(comp, limits),
+        TtpObject::Animation(anim) => check_animation(anim, limits),
+        _ => Ok(()),
+    }
+}
+
+/// Check a sprite's declared size and number of region colors.
+pub fn check_sprite(sprite: &Sprite, limits: &LimitsConfig) -> Result<(), String> {
+    if let Some([width, height]) = sprite.size {
+        check_size("Sprite", &sprite.name, width as u64, height as u64, limits)?;
+    }
+    let tokens: HashSet<&String> = sprite
+        .regions
+        .iter()
+        .flatten()
+        .chain(sprite.layers.iter().flatten().flat_map(|layer| &layer.regions))
+        .map(|(token, _)| token)
+        .collect();
+    check_colors("Sprite", &sprite.name, tokens.len(), limits)
+}
+
+/// Check a composition's canvas, declared or inferred from its layer maps.
+pub fn check_composition(comp: &Composition, limits: &LimitsConfig) -> Result<(), String> {
+    let (width, height) = match comp.size {
+        Some([width, height]) => (width as u64, height as u64),
+        None => {
+            let [cell_w, cell_h] = comp.cell_size();
+            let maps = comp.layers.iter().filter_map(|layer| layer.map.as_ref());
+            let (cols, rows) = maps.fold((0, 0), |(cols, rows), map| {
+                let width = map.iter().map(|row| comp.map_cells(row).len()).max().unwrap_or(0);
+                (cols.max(width as u64), rows.max(map.len() as u64))
+            });
+            (cols * cell_w as u64, rows * cell_h as u64)
+        }
+    };
+    check_size("Composition", &comp.name, width, height, limits)
+}
+
+/// Check an animation's frame or keyframe count.
+pub fn check_animation(anim: &Animation, limits: &LimitsConfig) -> Result<(), String> {
+    let frames = anim.frames.len().max(anim.keyframes.as_ref().map_or(0, |k| k.len()));
+    if frames > limits.max_frames {
+        return Err(format!(
+            "Animation '{}' has {} frames, over the limit of {} (limits.max_frames)",
+            anim.name, frames, limits.max_frames
+        ));
+    }
+    Ok(())
+}
+
+fn check_size(
+    kind: &str,
+    name: &str,
+    width: u64,
+    height: u64,
+    limits: &LimitsConfig,
+) -> Result<(), String> {
+    let [max_w, max_h] = limits.max_size;
+    if width > max_w as u64 || height > max_h as u64 {
+        return Err(format!(
+            "{} '{}' is {}x{}, over the limit of {}x{} (limits.max_size)",
+            kind, name, width, height, max_w, max_h
+        ));
+    }
+    Ok(())
+}
+
+fn check_colors(
+    kind: &str,
+    name: &str,
+    colors: usize,
+    limits: &LimitsConfig,
+) -> Result<(), String> {
+    if colors > limits.max_colors {
+        return Err(format!(
+            "{} '{}' has {} colors, over the limit of {} (limits.max_colors)",
+            kind, name, colors, limits.max_colors
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> TtpObject {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn small() -> LimitsConfig {
+        LimitsConfig { max_size: [8, 8], max_colors: 2, max_frames: 2 }
+    }
+
+    #[test]
+    fn test_sprite_size_and_colors() {
+        let ok = parse(
+            r#"{"type": "sprite", "name": "s", "size": [8, 4], "palette": "p", "regions": {"a": {"rect": [0, 0, 1, 1]}}}"#,
+        );
+        assert!(check_object(&ok, &small()).is_ok());
+
+        let huge = parse(
+            r#"{"type": "sprite", "name": "big", "size": [100000, 100000], "palette": "p", "regions": {}}"#,
+        );
+        let err = check_object(&huge, &small()).unwrap_err();
+        assert_eq!(err, "Sprite 'big' is 100000x100000, over the limit of 8x8 (limits.max_size)");
+
+        let colorful = parse(
+            r#"{"type": "sprite", "name": "c", "size": [2, 2], "palette": "p", "regions": {"a": {"rect": [0, 0, 1, 1]}, "b": {"rect": [1, 0, 1, 1]}, "c": {"rect": [0, 1, 1, 1]}}}"#,
+        );
+        assert!(check_object(&colorful, &small()).unwrap_err().contains("3 colors"));
+    }
+
+    #[test]
+    fn test_palette_composition_and_animation() {
+        let palette = parse(
+            r##"{"type": "palette", "name": "p", "colors": {"a": "#000", "b": "#111", "c": "#222"}}"##,
+        );
+        assert!(check_object(&palette, &small()).unwrap_err().contains("limits.max_colors"));
+
+        // Inferred canvas: 3 map columns of 4px cells
+        let comp = parse(
+            r#"{"type": "composition", "name": "scene", "cell_size": [4, 4], "sprites": {}, "layers": [{"map": ["AAA"]}]}"#,
+        );
+        assert!(check_object(&comp, &small()).unwrap_err().contains("12x4"));
+
+        let anim = parse(r#"{"type": "animation", "name": "walk", "frames": ["a", "b", "c"]}"#);
+        assert!(check_object(&anim, &small()).unwrap_err().contains("3 frames"));
+        assert!(check_object(&anim, &LimitsConfig::default()).is_ok());
+    }
+}
@@ -0,0 +1,357 @@
+//! Style linting for Pixelsrc files
+//!
+//! Where [`crate::validate`] checks that a file is correct, the linter checks
+//! that it follows a team's conventions. Each rule has a default level that
+//! can be overridden under `[lint.rules]` in `pxl.toml`.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use image::Rgba;
+
+use crate::color::parse_color;
+use crate::config::{LintConfig, LintLevel};
+use crate::models::{PaletteRef, Sprite, TtpObject, Variant};
+use crate::parser::parse_stream;
+
+/// A lint rule and its default level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintRule {
+    /// Rule name used in `pxl.toml` and diagnostics
+    pub name: &'static str,
+    /// One-line description of the convention
+    pub description: &'static str,
+    /// Level when not configured
+    pub default_level: LintLevel,
+}
+
+/// Sprites must reference a named palette rather than an inline one
+pub const NAMED_PALETTE: &str = "named-palette";
+/// Sprites must not use more than `lint.max_colors` distinct colors
+pub const MAX_COLORS: &str = "max-colors";
+/// Object names must be snake_case
+pub const SNAKE_CASE_NAMES: &str = "snake-case-names";
+/// Variant color overrides must reuse colors from a defined palette
+pub const NO_MAGIC_VARIANT_COLORS: &str = "no-magic-variant-colors";
+
+/// All lint rules
+pub const RULES: &[LintRule] = &[
+    LintRule {
+        name: NAMED_PALETTE,
+        description: "sprites must reference a named palette instead of an inline one",
+        default_level: LintLevel::Warn,
+    },
+    LintRule {
+        name: MAX_COLORS,
+        description: "sprites must not use more than `lint.max_colors` distinct colors",
+        default_level: LintLevel::Warn,
+    },
+    LintRule {
+        name: SNAKE_CASE_NAMES,
+        description:
+            "palette, sprite, variant, composition, and animation names must be snake_case",
+        default_level: LintLevel::Warn,
+    },
+    LintRule {
+        name: NO_MAGIC_VARIANT_COLORS,
+        description: "variant color overrides must reuse a color from a defined palette",
+        default_level: LintLevel::Warn,
+    },
+];
+
+/// Look up a lint rule by name.
+pub fn find_rule(name: &str) -> Option<&'static LintRule> {
+    RULES.iter().find(|rule| rule.name == name)
+}
+
+/// A rule violation
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintDiagnostic {
+    /// Line number (1-indexed) of the object that violates the rule
+    pub line: usize,
+    /// Name of the violated rule
+    pub rule: &'static str,
+    /// Configured level (`Warn` or `Deny`)
+    pub level: LintLevel,
+    /// Human-readable message
+    pub message: String,
+}
+
+/// Checks Pixelsrc files against configured lint rules
+#[derive(Debug, Clone, Default)]
+pub struct Linter {
+    config: LintConfig,
+}
+
+impl Linter {
+    /// Create a linter with rule levels from `[lint]` config.
+    pub fn new(config: LintConfig) -> Self {
+        Self { config }
+    }
+
+    /// Effective level of a rule.
+    pub fn level(&self, rule: &str) -> LintLevel {
+        self.config
+            .rules
+            .get(rule)
+            .copied()
+            .or_else(|| find_rule(rule).map(|r| r.default_level))
+            .unwrap_or(LintLevel::Allow)
+    }
+
+    /// Lint a file.
+    pub fn lint_file(&self, path: &Path) -> std::io::Result<Vec<LintDiagnostic>> {
+        let file = File::open(path)?;
+        Ok(self.lint_objects(parse_stream(BufReader::new(file))))
+    }
+
+    /// Lint file content.
+    pub fn lint_str(&self, content: &str) -> Vec<LintDiagnostic> {
+        self.lint_objects(parse_stream(content.as_bytes()))
+    }
+
+    fn lint_objects(&self, result: crate::parser::ParseResult) -> Vec<LintDiagnostic> {
+        let objects: Vec<(usize, TtpObject)> =
+            result.spans.iter().map(|span| span.start).zip(result.objects).collect();
+
+        // Palettes first, so sprites and variants can refer to any palette in the file
+        let palettes: HashMap<&str, &HashMap<String, String>> = objects
+            .iter()
+            .filter_map(|(_, obj)| match obj {
+                TtpObject::Palette(p) => Some((p.name.as_str(), &p.colors)),
+                _ => None,
+            })
+            .collect();
+
+        let mut diagnostics = Vec::new();
+        let mut report = |line: usize, rule: &'static str, message: String| {
+            let level = self.level(rule);
+            if level != LintLevel::Allow {
+                diagnostics.push(LintDiagnostic { line, rule, level, message });
+            }
+        };
+
+        for (line, obj) in &objects {
+            let named = match obj {
+                TtpObject::Palette(p) => Some(("Palette", &p.name)),
+                TtpObject::Sprite(s) => Some(("Sprite", &s.name)),
+                TtpObject::Variant(v) => Some(("Variant", &v.name)),
+                TtpObject::Composition(c) => Some(("Composition", &c.name)),
+                TtpObject::Animation(a) => Some(("Animation", &a.name)),
+                _ => None,
+            };
+            if let Some((kind, name)) = named {
+                if !is_snake_case(name) {
+                    report(
+                        *line,
+                        SNAKE_CASE_NAMES,
+                        format!("{} '{}' should be snake_case ({})", kind, name, snake_case(name)),
+                    );
+                }
+            }
+
+            match obj {
+                TtpObject::Sprite(sprite) => {
+                    if matches!(sprite.palette, PaletteRef::Inline(_)) {
+                        report(
+                            *line,
+                            NAMED_PALETTE,
+                            format!("Sprite '{}' uses an inline palette", sprite.name),
+                        );
+                    }
+                    let colors = sprite_color_count(sprite, &palettes);
+                    if colors > self.config.max_colors {
+                        report(
+                            *line,
+                            MAX_COLORS,
+                            format!(
+                                "Sprite '{}' uses {} colors (max {})",
+                                sprite.name, colors, self.config.max_colors
+                            ),
+                        );
+                    }
+                }
+                TtpObject::Variant(variant) => {
+                    for token in magic_variant_colors(variant, &palettes) {
+                        report(
+                            *line,
+                            NO_MAGIC_VARIANT_COLORS,
+                            format!(
+                                "Variant '{}' sets '{}' to {}, which is not in any palette",
+                                variant.name, token, variant.palette[&token]
+                            ),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Whether a name is lowercase words joined by single underscores.
+fn is_snake_case(name: &str) -> bool {
+    !name.is_empty()
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.split('_').all(|word| {
+            !word.is_empty() && word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// Suggested snake_case form of a name (`WalkCycle-2` -> `walk_cycle_2`).
+fn snake_case(name: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        word.push(c.to_ascii_lowercase());
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words.join("_")
+}
+
+/// Distinct visible colors a sprite's regions use.
+///
+/// Tokens without a known color each count as one color.
+fn sprite_color_count(
+    sprite: &Sprite,
+    palettes: &HashMap<&str, &HashMap<String, String>>,
+) -> usize {
+    let palette = match &sprite.palette {
+        PaletteRef::Named(name) => palettes.get(name.as_str()).copied(),
+        PaletteRef::Inline(colors) => Some(colors),
+    };
+    let tokens: HashSet<&String> = sprite
+        .regions
+        .iter()
+        .flatten()
+        .chain(sprite.layers.iter().flatten().flat_map(|layer| &layer.regions))
+        .map(|(token, _)| token)
+        .collect();
+
+    let mut colors: HashSet<Rgba<u8>> = HashSet::new();
+    let mut unknown = 0;
+    for token in tokens {
+        let color = palette
+            .and_then(|p| p.get(token).or_else(|| p.get(&format!("{{{}}}", token))))
+            .and_then(|c| parse_color(c).ok());
+        match color {
+            Some(rgba) if rgba[3] == 0 => {}
+            Some(rgba) => {
+                colors.insert(rgba);
+            }
+            None => unknown += 1,
+        }
+    }
+    colors.len() + unknown
+}
+
+/// Tokens a variant overrides with colors that no palette defines, sorted.
+fn magic_variant_colors(
+    variant: &Variant,
+    palettes: &HashMap<&str, &HashMap<String, String>>,
+) -> Vec<String> {
+    let known: HashSet<Rgba<u8>> = palettes
+        .values()
+        .flat_map(|colors| colors.values())
+        .filter_map(|c| parse_color(c).ok())
+        .collect();
+
+    let mut tokens: Vec<String> = variant
+        .palette
+        .iter()
+        .filter(|(_, color)| parse_color(color).is_ok_and(|rgba| !known.contains(&rgba)))
+        .map(|(token, _)| token.clone())
+        .collect();
+    tokens.sort();
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALETTE: &str = r##"{"type": "palette", "name": "hero", "colors": {"_": "#00000000", "skin": "#FFCC99", "hair": "#663300"}}"##;
+
+    fn lint(lines: &[&str]) -> Vec<LintDiagnostic> {
+        Linter::default().lint_str(&lines.join("\n"))
+    }
+
+    #[test]
+    fn test_clean_file() {
+        let sprite = r#"{"type": "sprite", "name": "hero_idle", "size": [2, 2], "palette": "hero", "regions": {"skin": {"rect": [0, 0, 2, 2]}}}"#;
+        assert!(lint(&[PALETTE, sprite]).is_empty());
+    }
+
+    #[test]
+    fn test_named_palette() {
+        let sprite = r##"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}"##;
+        let diagnostics = lint(&[sprite]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, NAMED_PALETTE);
+        assert_eq!(diagnostics[0].level, LintLevel::Warn);
+        assert_eq!(diagnostics[0].line, 1);
+    }
+
+    #[test]
+    fn test_max_colors() {
+        let sprite = r#"{"type": "sprite", "name": "hero_idle", "size": [2, 2], "palette": "hero", "regions": {"_": {"points": [[1, 1]]}, "skin": {"points": [[0, 0]]}, "hair": {"points": [[1, 0]]}}}"#;
+        let config = LintConfig { max_colors: 1, ..Default::default() };
+        let diagnostics = Linter::new(config).lint_str(&[PALETTE, sprite].join("\n"));
+
+        // Transparent pixels are not counted
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, MAX_COLORS);
+        assert_eq!(diagnostics[0].message, "Sprite 'hero_idle' uses 2 colors (max 1)");
+        assert_eq!(diagnostics[0].line, 2);
+    }
+
+    #[test]
+    fn test_snake_case_names() {
+        let animation = r#"{"type": "animation", "name": "WalkCycle", "frames": ["a"]}"#;
+        let diagnostics = lint(&[animation]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, SNAKE_CASE_NAMES);
+        assert!(diagnostics[0].message.contains("(walk_cycle)"));
+
+        assert!(is_snake_case("walk_cycle_2"));
+        assert!(!is_snake_case("walk__cycle"));
+        assert!(!is_snake_case("2_walk"));
+        assert_eq!(snake_case("vault-dweller"), "vault_dweller");
+        assert_eq!(snake_case("heroIdleHD"), "hero_idle_hd");
+    }
+
+    #[test]
+    fn test_no_magic_variant_colors() {
+        let variant = r##"{"type": "variant", "name": "hero_dark", "base": "hero_idle", "palette": {"skin": "#663300", "hair": "#123456"}}"##;
+        let diagnostics = lint(&[PALETTE, variant]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, NO_MAGIC_VARIANT_COLORS);
+        assert!(diagnostics[0].message.contains("'hair' to #123456"));
+    }
+
+    #[test]
+    fn test_configured_levels() {
+        let animation = r#"{"type": "animation", "name": "WalkCycle", "frames": ["a"]}"#;
+        let mut config = LintConfig::default();
+        config.rules.insert(SNAKE_CASE_NAMES.to_string(), LintLevel::Deny);
+        let linter = Linter::new(config.clone());
+        assert_eq!(linter.lint_str(animation)[0].level, LintLevel::Deny);
+
+        config.rules.insert(SNAKE_CASE_NAMES.to_string(), LintLevel::Allow);
+        assert!(Linter::new(config).lint_str(animation).is_empty());
+    }
+}
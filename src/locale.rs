@@ -0,0 +1,154 @@
+//! Locale substitutions for localized renders (`--locale`)
+//!
+//! Sprites, variants, and compositions declare per-locale overrides (see
+//! [`LocaleOverride`]) instead of forking whole files per region.
+//! [`apply_locale`] resolves one locale over the parsed objects before they
+//! are registered:
+//!
+//! - A sprite or variant whose override names a `sprite` is replaced by a
+//!   copy of that sprite or variant under its own name, so compositions,
+//!   animations, and output file names that refer to it are unchanged.
+//! - A composition's `sprites` override replaces entries in its sprite map.
+//!
+//! Objects without an override for the locale render as usual.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{LocaleOverride, SpriteRef, TtpObject};
+
+/// The override an object declares for `locale`, if any.
+fn override_for<'a>(obj: &'a TtpObject, locale: &str) -> Option<&'a LocaleOverride> {
+    match obj {
+        TtpObject::Sprite(sprite) => sprite.locale.get(locale),
+        TtpObject::Variant(variant) => variant.locale.get(locale),
+        TtpObject::Composition(comp) => comp.locale.get(locale),
+        _ => None,
+    }
+}
+
+/// Name of a sprite or variant, the objects a `sprite` override can swap.
+fn swappable_name(obj: &TtpObject) -> Option<&String> {
+    match obj {
+        TtpObject::Sprite(sprite) => Some(&sprite.name),
+        TtpObject::Variant(variant) => Some(&variant.name),
+        _ => None,
+    }
+}
+
+/// Resolve `locale` substitutions in place, returning a warning for each
+/// override that names a missing sprite or sprite map key.
+pub fn apply_locale(objects: &mut [TtpObject], locale: &str) -> Vec<String> {
+    // Snapshot substitution targets first so swaps don't chain
+    let wanted: HashSet<&str> =
+        objects.iter().filter_map(|obj| override_for(obj, locale)?.sprite.as_deref()).collect();
+    let targets: HashMap<String, TtpObject> = objects
+        .iter()
+        .filter_map(|obj| {
+            let name = swappable_name(obj)?;
+            wanted.contains(name.as_str()).then(|| (name.clone(), obj.clone()))
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    for obj in objects.iter_mut() {
+        let Some(over) = override_for(obj, locale).cloned() else {
+            continue;
+        };
+        match obj {
+            TtpObject::Sprite(_) | TtpObject::Variant(_) => {
+                let Some(target) = over.sprite else {
+                    continue;
+                };
+                let name = swappable_name(obj).expect("sprite or variant").clone();
+                match targets.get(&target) {
+                    Some(replacement) => {
+                        *obj = replacement.clone();
+                        match obj {
+                            TtpObject::Sprite(s) => s.name = name,
+                            TtpObject::Variant(v) => v.name = name,
+                            _ => {}
+                        }
+                    }
+                    None => warnings.push(format!(
+                        "'{}' locale '{}' sprite '{}' not found, keeping the original",
+                        name, locale, target
+                    )),
+                }
+            }
+            TtpObject::Composition(comp) => {
+                for (key, sprite) in over.sprites {
+                    if !comp.sprites.contains_key(&key) {
+                        warnings.push(format!(
+                            "Composition '{}' locale '{}' replaces unknown sprite key '{}'",
+                            comp.name, locale, key
+                        ));
+                    }
+                    comp.sprites.insert(key, sprite.map(SpriteRef::Name));
+                }
+            }
+            _ => {}
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Vec<TtpObject> {
+        source.lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    const SOURCE: &str = r#"{"type": "sprite", "name": "title", "palette": "p", "regions": {}, "locale": {"ja": {"sprite": "title_ja"}, "de": {"sprite": "title_de"}}}
+{"type": "sprite", "name": "title_ja", "size": [3, 1], "palette": "p", "regions": {}}
+{"type": "variant", "name": "button", "base": "title", "palette": {}, "locale": {"ja": {"sprite": "button_ja"}}}
+{"type": "variant", "name": "button_ja", "base": "title_ja", "palette": {}}
+{"type": "composition", "name": "menu", "sprites": {"T": "title", "B": "button"}, "layers": [], "locale": {"ja": {"sprites": {"B": "title_ja", "X": null}}}}"#;
+
+    #[test]
+    fn test_apply_locale_swaps_objects() {
+        let mut objects = parse(SOURCE);
+        let warnings = apply_locale(&mut objects, "ja");
+
+        match &objects[0] {
+            TtpObject::Sprite(s) => {
+                assert_eq!(s.name, "title");
+                assert_eq!(s.size, Some([3, 1]));
+            }
+            other => panic!("expected sprite, got {:?}", other),
+        }
+        match &objects[2] {
+            TtpObject::Variant(v) => {
+                assert_eq!(v.name, "button");
+                assert_eq!(v.base, "title_ja");
+            }
+            other => panic!("expected variant, got {:?}", other),
+        }
+        match &objects[4] {
+            TtpObject::Composition(c) => {
+                assert_eq!(c.sprites["B"].as_ref().map(SpriteRef::name), Some("title_ja"));
+                assert_eq!(c.sprites["T"].as_ref().map(SpriteRef::name), Some("title"));
+            }
+            other => panic!("expected composition, got {:?}", other),
+        }
+        assert_eq!(
+            warnings,
+            vec!["Composition 'menu' locale 'ja' replaces unknown sprite key 'X'".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_apply_locale_missing_target_and_unknown_locale() {
+        let mut objects = parse(SOURCE);
+        let warnings = apply_locale(&mut objects, "de");
+        assert_eq!(
+            warnings,
+            vec!["'title' locale 'de' sprite 'title_de' not found, keeping the original"]
+        );
+        assert_eq!(objects, parse(SOURCE));
+
+        assert!(apply_locale(&mut objects, "fr").is_empty());
+        assert_eq!(objects, parse(SOURCE));
+    }
+}
@@ -65,7 +65,7 @@ pub fn get_shape_completions() -> Vec<CompletionItem> {
         ),
         make_completion(
             "path",
-            "SVG-lite path (M, L, H, V, Z)",
+            "SVG-lite path (M, L, H, V, Q, C, A, Z)",
             CompletionItemKind::PROPERTY,
             "path: \"M0,0 L8,8\"",
         ),
@@ -105,6 +105,12 @@ pub fn get_compound_completions() -> Vec<CompletionItem> {
             CompletionItemKind::PROPERTY,
             "intersect: [{ rect: [0, 0, 4, 4] }]",
         ),
+        make_completion(
+            "xor",
+            "Keep pixels in an odd number of shapes",
+            CompletionItemKind::PROPERTY,
+            "xor: [{ rect: [0, 0, 6, 6] }, { rect: [1, 1, 4, 4] }]",
+        ),
         make_completion(
             "except",
             "Subtract token pixels",
@@ -141,6 +147,24 @@ pub fn get_modifier_completions() -> Vec<CompletionItem> {
             CompletionItemKind::PROPERTY,
             "thickness: 1",
         ),
+        make_completion(
+            "fill-rule",
+            "Path fill rule: \"nonzero\", \"evenodd\"",
+            CompletionItemKind::PROPERTY,
+            "\"fill-rule\": \"evenodd\"",
+        ),
+        make_completion(
+            "expand",
+            "Grow region by N pixels",
+            CompletionItemKind::PROPERTY,
+            "expand: 1",
+        ),
+        make_completion(
+            "contract",
+            "Shrink region by N pixels",
+            CompletionItemKind::PROPERTY,
+            "contract: 1",
+        ),
         make_completion(
             "within",
             "Validate containment",
@@ -161,6 +185,18 @@ pub fn get_modifier_completions() -> Vec<CompletionItem> {
             CompletionItemKind::PROPERTY,
             "\"auto-outline\": \"body\"",
         ),
+        make_completion(
+            "outline-corners",
+            "Auto-outline corners: square or rounded",
+            CompletionItemKind::PROPERTY,
+            "\"outline-corners\": \"rounded\"",
+        ),
+        make_completion(
+            "outline-colors",
+            "Auto-outline color per neighboring token or role",
+            CompletionItemKind::PROPERTY,
+            "\"outline-colors\": {\"fill\": \"*_1\"}",
+        ),
         make_completion(
             "auto-shadow",
             "Generate shadow",
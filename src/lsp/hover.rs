@@ -1,5 +1,18 @@
 //! Hover information for structured format elements.
 
+use std::path::Path;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::Value;
+
+use crate::models::SpriteReference;
+use crate::output::{encode_png, scale_image};
+use crate::serve::PreviewCatalog;
+use crate::underlay::{apply_underlay, load_reference};
+
+/// Longest side, in pixels, that reference hover previews are scaled up to
+const PREVIEW_SIZE: u32 = 128;
+
 /// Get hover information for a role
 pub fn get_role_hover(role: &str) -> Option<String> {
     match role {
@@ -47,7 +60,7 @@ pub fn get_shape_hover(shape: &str) -> Option<String> {
             "**Shape: Polygon**\n\nFilled polygon from vertices.\n\n```json5\npolygon: [[0, 0], [8, 0], [4, 8]]\n```".to_string(),
         ),
         "path" => Some(
-            "**Shape: Path**\n\nSVG-lite path syntax.\n\n**Supported commands:** M (move), L (line), H (horizontal), V (vertical), Z (close)\n\n```json5\npath: \"M2,0 L6,0 L8,2 Z\"\n```".to_string(),
+            "**Shape: Path**\n\nSVG-lite path syntax.\n\n**Supported commands:** M (move), L (line), H (horizontal), V (vertical), Q (quadratic curve), C (cubic curve), A (arc), Z (close)\n\n```json5\npath: \"M2,0 L6,0 L8,2 Z\"\n```\n\nOptional: `fill-rule` (`\"nonzero\"` or `\"evenodd\"`)".to_string(),
         ),
         "fill" => Some(
             "**Shape: Fill**\n\nFlood fill inside a boundary.\n\n```json5\nfill: \"inside(outline)\"\n```\n\nOptional: `seed: [x, y]` (starting point)".to_string(),
@@ -74,6 +87,12 @@ pub fn get_modifier_hover(modifier: &str) -> Option<String> {
         "repeat" => Some(
             "**Transform: Repeat**\n\nTile a shape.\n\n```json5\nrepeat: [count_x, count_y],\nspacing: [gap_x, gap_y],\n\"offset-alternate\": true\n```".to_string(),
         ),
+        "expand" => Some(
+            "**Modifier: Expand**\n\nGrow the region by N pixels in every direction.\n\n```json5\nexpand: 1\n```".to_string(),
+        ),
+        "contract" => Some(
+            "**Modifier: Contract**\n\nShrink the region by N pixels from every edge.\n\n```json5\ncontract: 1\n```".to_string(),
+        ),
         "jitter" => Some(
             "**Transform: Jitter**\n\nControlled randomness.\n\n```json5\njitter: { x: [-1, 1], y: [-2, 0] },\nseed: 42\n```".to_string(),
         ),
@@ -90,8 +109,18 @@ pub fn get_structured_format_hover(line: &str, char_pos: u32) -> Option<String>
     // List of keywords to check for hover
     let shape_keywords =
         ["points", "line", "rect", "stroke", "ellipse", "circle", "polygon", "path", "fill"];
-    let modifier_keywords =
-        ["symmetric", "z", "within", "adjacent-to", "repeat", "jitter", "round", "thickness"];
+    let modifier_keywords = [
+        "symmetric",
+        "z",
+        "within",
+        "adjacent-to",
+        "repeat",
+        "jitter",
+        "round",
+        "thickness",
+        "expand",
+        "contract",
+    ];
     let role_keywords = ["boundary", "anchor", "fill", "shadow", "highlight"];
 
     // Find the word at cursor position
@@ -171,3 +200,57 @@ pub fn get_structured_format_hover(line: &str, char_pos: u32) -> Option<String>
 
     None
 }
+
+/// Hover for a sprite's `reference` image: its path, size and opacity, plus a
+/// preview of the sprite drawn over it.
+///
+/// `line` must be a whole sprite object; `content` is the full document, used
+/// to resolve the sprite's palette. Relative paths resolve against `base_dir`.
+pub fn get_reference_hover(
+    content: &str,
+    line: &str,
+    char_pos: u32,
+    base_dir: &Path,
+) -> Option<String> {
+    let obj: Value = serde_json::from_str(line).ok()?;
+    if obj.get("type")?.as_str()? != "sprite" {
+        return None;
+    }
+    let name = obj.get("name")?.as_str()?;
+    let reference: SpriteReference = serde_json::from_value(obj.get("reference")?.clone()).ok()?;
+
+    // Only hover over the `"reference": {...}` span
+    let start = line.find("\"reference\"")?;
+    let end = start + line[start..].find('}')?;
+    let pos = char_pos as usize;
+    if pos < start || pos > end {
+        return None;
+    }
+
+    let mut text = format!("**Reference image**: `{}`\n\n", reference.path);
+    let underlay = match load_reference(&reference, base_dir) {
+        Ok(image) => image,
+        Err(e) => return Some(format!("{}⚠ **Not loaded**: {}", text, e)),
+    };
+    text.push_str(&format!(
+        "**Size**: {}x{}\n\n**Opacity**: {}\n\nShown under previews only; never rendered into exports.",
+        underlay.width(),
+        underlay.height(),
+        reference.opacity
+    ));
+
+    let catalog = PreviewCatalog::from_source(content);
+    if let Ok(image) = catalog.render_sprite(name) {
+        let scale = (PREVIEW_SIZE / image.width().max(image.height()).max(1)).clamp(1, 16);
+        let preview =
+            apply_underlay(&scale_image(image, scale as u8), &underlay, reference.opacity);
+        if let Ok(png) = encode_png(&preview) {
+            text.push_str(&format!(
+                "\n\n![{}](data:image/png;base64,{})",
+                name,
+                BASE64_STANDARD.encode(png)
+            ));
+        }
+    }
+    Some(text)
+}
@@ -6,7 +6,8 @@
 //!
 //! This LSP provides intelligent completions for the structured region format:
 //! - Shape primitives: `points`, `line`, `rect`, `stroke`, `ellipse`, `circle`, `polygon`, `path`, `fill`
-//! - Modifiers: `symmetric`, `z`, `round`, `thickness`, `repeat`, `spacing`, `transform`, `jitter`
+//! - Compound operations: `union`, `base`, `subtract`, `intersect`, `xor`, `except`
+//! - Modifiers: `symmetric`, `z`, `round`, `thickness`, `expand`, `contract`, `repeat`, `spacing`, `transform`, `jitter`
 //! - Constraints: `within`, `adjacent-to`, `x`, `y`
 //! - Roles: `boundary`, `anchor`, `fill`, `shadow`, `highlight`
 //! - Relationships: `derives-from`, `contained-within`, `adjacent-to`, `paired-with`
@@ -30,6 +31,7 @@ mod tests {
     use super::*;
     use crate::motion::{Interpolation, StepPosition};
     use crate::validate::{IssueType, ValidationIssue};
+    use std::path::Path;
     use tower_lsp::lsp_types::{DiagnosticSeverity, NumberOrString, SymbolKind};
 
     #[test]
@@ -139,6 +141,25 @@ mod tests {
         assert!(hover.is_none());
     }
 
+    #[test]
+    fn test_get_reference_hover() {
+        let dir = tempfile::tempdir().unwrap();
+        image::RgbaImage::new(3, 5).save(dir.path().join("ref.png")).unwrap();
+        let line = r##"{"type": "sprite", "name": "s", "size": [2, 2], "palette": {"x": "#FF0000"}, "regions": {"x": {"rect": [0, 0, 1, 1]}}, "reference": {"path": "ref.png", "opacity": 0.5}}"##;
+        let key = line.find("reference").unwrap() as u32;
+
+        let hover = hover::get_reference_hover(line, line, key, dir.path()).unwrap();
+        assert!(hover.contains("`ref.png`"));
+        assert!(hover.contains("**Size**: 3x5"));
+        assert!(hover.contains("**Opacity**: 0.5"));
+        assert!(hover.contains("data:image/png;base64,"));
+
+        // Outside the reference object, or with a missing file
+        assert!(hover::get_reference_hover(line, line, 5, dir.path()).is_none());
+        let missing = hover::get_reference_hover(line, line, key, Path::new("/nonexistent"));
+        assert!(missing.unwrap().contains("Not loaded"));
+    }
+
     // === Shape Completions Tests ===
 
     #[test]
@@ -887,4 +908,23 @@ also not json"##;
         let hsla = color_utils::rgba_to_hsl(1.0, 0.0, 0.0, 0.5);
         assert_eq!(hsla, "hsla(0, 100%, 50%, 0.50)");
     }
+
+    #[test]
+    fn test_document_diagnostics() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("hero.pxl");
+        let content = r#"{"type": "sprite", "name": "hero", "palette": "@include:shared", "size": [1, 1]}
+not json"#;
+
+        let diagnostics = PixelsrcLanguageServer::document_diagnostics(content, Some(&path), None);
+        let include = |d: &tower_lsp::lsp_types::Diagnostic| {
+            d.range.start.line == 0 && d.message.contains("Include file not found")
+        };
+        assert!(diagnostics.iter().any(include), "{:?}", diagnostics);
+        assert!(diagnostics.iter().any(|d| d.range.start.line == 1), "{:?}", diagnostics);
+
+        // Without a path, includes can't be resolved and aren't checked
+        let diagnostics = PixelsrcLanguageServer::document_diagnostics(content, None, None);
+        assert!(!diagnostics.iter().any(include));
+    }
 }
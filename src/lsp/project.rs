@@ -10,6 +10,7 @@ use tower_lsp::lsp_types::*;
 
 use crate::build::project_registry::ProjectRegistry;
 use crate::config::loader::{find_config_from, load_config};
+use crate::include::{parse_include_ref, resolve_include};
 
 /// Cached project context for the LSP server.
 #[derive(Debug)]
@@ -29,7 +30,11 @@ impl ProjectContext {
     /// then loads the project registry from the configured source directory.
     pub fn from_file(file_path: &Path) -> Option<Self> {
         let start_dir = file_path.parent()?.to_path_buf();
-        let config_path = find_config_from(start_dir)?;
+        Self::load(find_config_from(start_dir)?)
+    }
+
+    /// Load the project registry described by a `pxl.toml`.
+    pub fn load(config_path: PathBuf) -> Option<Self> {
         let config = load_config(Some(&config_path)).ok()?;
 
         let project_root = config_path.parent()?;
@@ -47,44 +52,29 @@ impl ProjectContext {
             registry.load_dependencies(&config.dependencies, project_root, false).ok()?;
         }
 
-        Some(ProjectContext { registry, config_path: config_path.clone(), src_root: src_dir })
+        Some(ProjectContext { registry, config_path, src_root: src_dir })
     }
 
     /// Reload the project registry (e.g., after a file change).
     pub fn reload(&mut self) -> bool {
-        let config = match load_config(Some(&self.config_path)) {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
-
-        let project_root = match self.config_path.parent() {
-            Some(p) => p,
-            None => return false,
-        };
-
-        let src_dir = project_root.join(&config.project.src);
-        if !src_dir.exists() {
-            return false;
-        }
-
-        let mut registry = ProjectRegistry::new(config.project.name.clone(), src_dir.clone());
-        match registry.load_all(false) {
-            Ok(()) => {
-                // Load dependencies if declared
-                if !config.dependencies.is_empty()
-                    && registry
-                        .load_dependencies(&config.dependencies, project_root, false)
-                        .is_err()
-                {
-                    return false;
-                }
-                self.registry = registry;
-                self.src_root = src_dir;
+        match Self::load(self.config_path.clone()) {
+            Some(ctx) => {
+                *self = ctx;
                 true
             }
-            Err(_) => false,
+            None => false,
         }
     }
+
+    /// Source files of the project itself, without its dependencies.
+    pub fn project_files(&self) -> Vec<PathBuf> {
+        self.registry
+            .loaded_files()
+            .iter()
+            .filter(|path| path.starts_with(&self.src_root))
+            .cloned()
+            .collect()
+    }
 }
 
 /// A resolved cross-file item with its location and type.
@@ -586,6 +576,45 @@ pub fn check_cross_file_references(content: &str, registry: &ProjectRegistry) ->
     diagnostics
 }
 
+/// Collect diagnostics for `@include:` palette references that don't resolve.
+///
+/// Include paths are resolved relative to `base_dir`, the directory of the
+/// document being checked.
+pub fn check_include_references(content: &str, base_dir: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        let Ok(obj) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        let Some(palette_ref) = obj.get("palette").and_then(|p| p.as_str()) else {
+            continue;
+        };
+        let Some((path, name)) = parse_include_ref(palette_ref) else {
+            continue;
+        };
+        let Err(err) = resolve_include(path, base_dir, name) else {
+            continue;
+        };
+        let start = find_value_position(line, "palette", palette_ref).unwrap_or(0);
+        diagnostics.push(Diagnostic {
+            range: Range {
+                start: Position { line: line_num as u32, character: start as u32 },
+                end: Position {
+                    line: line_num as u32,
+                    character: (start + palette_ref.len()) as u32,
+                },
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("pixelsrc".to_string()),
+            message: err.to_string(),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
 fn capitalize(s: &str) -> String {
     let mut c = s.chars();
     match c.next() {
@@ -843,6 +872,34 @@ mod tests {
         assert!(diags[0].message.contains("nonexistent"));
     }
 
+    #[test]
+    fn test_project_files() {
+        let temp = TempDir::new().unwrap();
+        let src = create_test_project(temp.path());
+        let ctx = ProjectContext::from_file(&src.join("characters/hero.pxl")).unwrap();
+
+        let files = ctx.project_files();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|f| f.starts_with(&src)));
+    }
+
+    #[test]
+    fn test_check_include_references() {
+        let temp = TempDir::new().unwrap();
+        let src = create_test_project(temp.path());
+        let dir = src.join("characters");
+
+        let content = r#"{"type": "sprite", "name": "a", "palette": "@include:../palettes/mono", "size": [1, 1]}
+{"type": "sprite", "name": "b", "palette": "@include:../palettes/gone", "size": [1, 1]}
+{"type": "sprite", "name": "c", "palette": "@include:../palettes/mono#neon", "size": [1, 1]}"#;
+        let diags = check_include_references(content, &dir);
+        assert_eq!(diags.len(), 2, "{:?}", diags);
+        assert_eq!(diags[0].range.start.line, 1);
+        assert!(diags[0].message.contains("not found"));
+        assert_eq!(diags[1].range.start.line, 2);
+        assert!(diags[1].message.contains("neon"));
+    }
+
     #[test]
     fn test_capitalize() {
         assert_eq!(capitalize("palette"), "Palette");
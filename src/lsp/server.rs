@@ -4,8 +4,10 @@ use crate::transforms::explain_transform;
 use crate::validate::{Severity, ValidationIssue, Validator};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
@@ -18,11 +20,11 @@ use super::completions::{
     get_role_completions, get_shape_completions, get_state_apply_completions,
     get_state_selector_completions,
 };
-use super::hover::get_structured_format_hover;
+use super::hover::{get_reference_hover, get_structured_format_hover};
 use super::project::{
-    check_cross_file_references, extract_reference_at_position, get_cross_file_completions,
-    get_import_path_completions, goto_cross_file_definition, hover_cross_file_reference,
-    is_import_from_context, ProjectContext,
+    check_cross_file_references, check_include_references, extract_reference_at_position,
+    get_cross_file_completions, get_import_path_completions, goto_cross_file_definition,
+    hover_cross_file_reference, is_import_from_context, ProjectContext,
 };
 use super::symbols::{
     build_variable_registry, collect_css_variables, collect_defined_tokens, extract_symbols,
@@ -36,18 +38,30 @@ use super::timing_utils::{
 use super::transform_utils::parse_transform_context;
 use super::types::CompletionContext;
 
+/// How long edits must pause before the whole project is revalidated
+const PROJECT_VALIDATION_DELAY: Duration = Duration::from_millis(400);
+
 /// The Pixelsrc Language Server
 pub struct PixelsrcLanguageServer {
     client: Client,
     /// Document state tracking for open files
-    documents: RwLock<HashMap<Url, String>>,
-    /// Project context for cross-file support (loaded lazily)
-    project: RwLock<Option<ProjectContext>>,
+    documents: Arc<RwLock<HashMap<Url, String>>>,
+    /// Project context for cross-file support (loaded lazily). Reloads build
+    /// a new context and swap it in, so readers only ever wait for the swap
+    project: Arc<RwLock<Option<Arc<ProjectContext>>>>,
+    /// Bumped on every edit; a scheduled project validation that sees a newer
+    /// value has been superseded and stops
+    generation: Arc<AtomicU64>,
 }
 
 impl PixelsrcLanguageServer {
     pub fn new(client: Client) -> Self {
-        Self { client, documents: RwLock::new(HashMap::new()), project: RwLock::new(None) }
+        Self {
+            client,
+            documents: Arc::new(RwLock::new(HashMap::new())),
+            project: Arc::new(RwLock::new(None)),
+            generation: Arc::new(AtomicU64::new(0)),
+        }
     }
 
     /// Try to initialize or refresh the project context from a file URI.
@@ -66,21 +80,12 @@ impl PixelsrcLanguageServer {
         if needs_init {
             if let Some(ctx) = ProjectContext::from_file(&file_path) {
                 if let Ok(mut project) = self.project.write() {
-                    *project = Some(ctx);
+                    *project = Some(Arc::new(ctx));
                 }
             }
         }
     }
 
-    /// Reload the project registry (e.g., after a file change).
-    fn reload_project_registry(&self) {
-        if let Ok(mut project) = self.project.write() {
-            if let Some(ctx) = project.as_mut() {
-                ctx.reload();
-            }
-        }
-    }
-
     /// Get the file path for a URI, if it's a .pxl or .jsonl file in the project.
     fn uri_to_project_file(&self, uri: &Url) -> Option<PathBuf> {
         let path = uri.to_file_path().ok()?;
@@ -92,25 +97,121 @@ impl PixelsrcLanguageServer {
         }
     }
 
-    /// Validate document content and publish diagnostics
+    /// Validate a document on its own and publish its diagnostics.
+    ///
+    /// Cross-file references are checked against the last loaded project
+    /// registry; [`Self::schedule_project_validation`] refreshes it.
     async fn validate_and_publish(&self, uri: &Url, content: &str) {
+        let path = uri.to_file_path().ok();
+        let diagnostics = match self.project.read() {
+            Ok(project) => Self::document_diagnostics(content, path.as_deref(), project.as_deref()),
+            Err(_) => Self::document_diagnostics(content, path.as_deref(), None),
+        };
+
+        self.client.publish_diagnostics(uri.clone(), diagnostics, None).await;
+    }
+
+    /// Diagnostics for one document: line validation, then references into
+    /// the project and `@include:` files.
+    pub fn document_diagnostics(
+        content: &str,
+        path: Option<&Path>,
+        project: Option<&ProjectContext>,
+    ) -> Vec<Diagnostic> {
         let mut validator = Validator::new();
         for (line_num, line) in content.lines().enumerate() {
             validator.validate_line(line_num + 1, line);
         }
+        validator.finish();
 
         let mut diagnostics: Vec<Diagnostic> =
             validator.issues().iter().map(Self::issue_to_diagnostic).collect();
 
-        // Add cross-file reference diagnostics if project context is available
-        if let Ok(project) = self.project.read() {
-            if let Some(ctx) = project.as_ref() {
-                let cross_file_diags = check_cross_file_references(content, &ctx.registry);
-                diagnostics.extend(cross_file_diags);
-            }
+        if let Some(ctx) = project {
+            diagnostics.extend(check_cross_file_references(content, &ctx.registry));
+        }
+        if let Some(dir) = path.and_then(Path::parent) {
+            diagnostics.extend(check_include_references(content, dir));
         }
 
-        self.client.publish_diagnostics(uri.clone(), diagnostics, None).await;
+        diagnostics
+    }
+
+    /// Revalidate the whole project once edits pause.
+    ///
+    /// After [`PROJECT_VALIDATION_DELAY`] the registry is reloaded and every
+    /// project file and open document gets fresh diagnostics, so a rename in
+    /// one file flags the references it broke in others. A newer edit cancels
+    /// a pending or running validation.
+    fn schedule_project_validation(&self) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let client = self.client.clone();
+        let documents = Arc::clone(&self.documents);
+        let project = Arc::clone(&self.project);
+        let latest = Arc::clone(&self.generation);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(PROJECT_VALIDATION_DELAY).await;
+            let superseded = move || latest.load(Ordering::SeqCst) != generation;
+            if superseded() {
+                return;
+            }
+
+            // Reloading and reading files happens off the async runtime, on a
+            // snapshot of the documents and the current project context
+            let snapshot = project.read().ok().and_then(|project| project.clone());
+            let Ok(documents) = documents.read().map(|documents| documents.clone()) else {
+                return;
+            };
+            let work = {
+                let superseded = superseded.clone();
+                tokio::task::spawn_blocking(move || {
+                    let ctx = snapshot.map(|old| {
+                        ProjectContext::load(old.config_path.clone()).map_or(old, Arc::new)
+                    });
+                    let mut files: Vec<PathBuf> =
+                        ctx.as_ref().map(|ctx| ctx.project_files()).unwrap_or_default();
+                    files.extend(documents.keys().filter_map(|uri| uri.to_file_path().ok()));
+                    files.sort();
+                    files.dedup();
+
+                    let mut results = Vec::new();
+                    for path in files {
+                        if superseded() {
+                            return None;
+                        }
+                        let Ok(uri) = Url::from_file_path(&path) else { continue };
+                        let content = match documents.get(&uri) {
+                            Some(text) => text.clone(),
+                            None => match std::fs::read_to_string(&path) {
+                                Ok(text) => text,
+                                Err(_) => continue,
+                            },
+                        };
+                        let diagnostics =
+                            Self::document_diagnostics(&content, Some(&path), ctx.as_deref());
+                        results.push((uri, diagnostics));
+                    }
+                    Some((ctx, results))
+                })
+            };
+            let Ok(Some((ctx, results))) = work.await else {
+                return;
+            };
+            if superseded() {
+                return;
+            }
+            if let (Some(ctx), Ok(mut project)) = (ctx, project.write()) {
+                *project = Some(ctx);
+            }
+
+            for (uri, diagnostics) in results {
+                if superseded() {
+                    return;
+                }
+                client.publish_diagnostics(uri, diagnostics, None).await;
+            }
+        });
     }
 
     /// Convert a ValidationIssue to an LSP Diagnostic
@@ -154,11 +255,9 @@ impl PixelsrcLanguageServer {
                 let obj_type = obj.get("type").and_then(|t| t.as_str());
 
                 match obj_type {
-                    Some("sprite") => {
-                        // Check if we're in the regions field
-                        if line.contains("\"regions\"") {
-                            return CompletionContext::Regions;
-                        }
+                    // Check if we're in the regions field
+                    Some("sprite") if line.contains("\"regions\"") => {
+                        return CompletionContext::Regions;
                     }
                     Some("palette") => {
                         if line.contains("\"roles\"") {
@@ -189,10 +288,12 @@ impl PixelsrcLanguageServer {
                         // Likely inside a region definition
                         return CompletionContext::RegionDef;
                     }
-                    Some("palette") if line.contains(':') => {
-                        if content.contains("\"roles\"") && !line.contains("\"colors\"") {
-                            return CompletionContext::Roles;
-                        }
+                    Some("palette")
+                        if line.contains(':')
+                            && content.contains("\"roles\"")
+                            && !line.contains("\"colors\"") =>
+                    {
+                        return CompletionContext::Roles;
                     }
                     _ => {}
                 }
@@ -262,6 +363,9 @@ impl LanguageServer for PixelsrcLanguageServer {
 
         // Validate and publish diagnostics
         self.validate_and_publish(&uri, &text).await;
+        if self.uri_to_project_file(&uri).is_some() {
+            self.schedule_project_validation();
+        }
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -284,13 +388,11 @@ impl LanguageServer for PixelsrcLanguageServer {
                 return;
             }
 
-            // Reload project registry when project files change
+            // Validate the edited document now, the rest of the project once edits pause
+            self.validate_and_publish(&uri, &change.text).await;
             if self.uri_to_project_file(&uri).is_some() {
-                self.reload_project_registry();
+                self.schedule_project_validation();
             }
-
-            // Validate and publish diagnostics
-            self.validate_and_publish(&uri, &change.text).await;
         }
     }
 
@@ -332,6 +434,19 @@ impl LanguageServer for PixelsrcLanguageServer {
             None => return Ok(None),
         };
 
+        // Check for a sprite's reference image (before shapes, which claim `path`)
+        let base_dir =
+            uri.to_file_path().ok().and_then(|p| p.parent().map(PathBuf::from)).unwrap_or_default();
+        if let Some(hover_text) = get_reference_hover(&content, line, pos.character, &base_dir) {
+            return Ok(Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: hover_text,
+                }),
+                range: None,
+            }));
+        }
+
         // Check for structured format elements (roles, shapes, modifiers)
         if let Some(hover_text) = get_structured_format_hover(line, pos.character) {
             return Ok(Some(Hover {
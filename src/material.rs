@@ -0,0 +1,239 @@
+//! Normal and emissive map generation
+//!
+//! Dynamic 2D lighting needs more than the color (albedo) image: a normal map
+//! describing which way each pixel faces, and an emissive mask for pixels that
+//! glow in the dark. Both are derived from region metadata:
+//!
+//! - `height` (0.0-1.0) on a region sets its surface height; without it the
+//!   height comes from the region's semantic role, or the token's palette
+//!   role (highlights raised, shadows and outlines sunk).
+//! - `emissive: true` puts the region's pixels into the emissive map.
+//!
+//! Maps are generated from the final rendered image by matching each pixel's
+//! color back to its region, so any transform, nine-slice or atlas placement
+//! applied to the albedo can be applied to the maps the same way.
+
+use std::collections::HashMap;
+
+use image::{Rgba, RgbaImage};
+
+use crate::color::parse_color;
+use crate::models::{RegionDef, Role};
+
+/// Height used for opaque pixels with no matching region
+const DEFAULT_HEIGHT: f32 = 0.5;
+
+/// How strongly height differences tilt the normals
+const NORMAL_STRENGTH: f32 = 2.0;
+
+/// Secondary output generated alongside a sprite render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum MapKind {
+    /// Tangent-space normal map (OpenGL convention, green up)
+    Normal,
+    /// Emissive mask: glowing pixels in color, everything else transparent
+    Emissive,
+}
+
+impl MapKind {
+    /// Filename suffix for this map, e.g. `hero_normal.png`.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            MapKind::Normal => "normal",
+            MapKind::Emissive => "emissive",
+        }
+    }
+}
+
+/// Surface properties of a region.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// Surface height from 0.0 (sunk) to 1.0 (raised)
+    pub height: f32,
+    /// Whether the surface glows
+    pub emissive: bool,
+}
+
+/// Default surface height for a semantic role.
+pub fn default_height_for_role(role: Option<Role>) -> f32 {
+    match role {
+        Some(Role::Highlight) => 1.0,
+        Some(Role::Anchor) => 0.75,
+        Some(Role::Fill) | None => DEFAULT_HEIGHT,
+        Some(Role::Shadow) => 0.25,
+        Some(Role::Boundary) => 0.0,
+    }
+}
+
+/// Lookup from rendered pixel color to the material of its region.
+#[derive(Debug, Clone, Default)]
+pub struct MaterialPalette {
+    by_color: HashMap<[u8; 4], Material>,
+}
+
+impl MaterialPalette {
+    /// Build the lookup from a sprite's regions and resolved palette.
+    ///
+    /// `roles` are the palette's token roles, used for regions without their
+    /// own. If several regions share a color, the highest surface wins and the
+    /// color is emissive if any of them is.
+    pub fn from_regions(
+        regions: &HashMap<String, RegionDef>,
+        palette: &HashMap<String, String>,
+        roles: Option<&HashMap<String, Role>>,
+    ) -> Self {
+        let mut by_color: HashMap<[u8; 4], Material> = HashMap::new();
+        for (token, region) in regions {
+            let Some(color) = palette.get(token).and_then(|hex| parse_color(hex).ok()) else {
+                continue;
+            };
+            let role = region.role.or_else(|| roles.and_then(|r| r.get(token).copied()));
+            let material = Material {
+                height: region
+                    .height
+                    .unwrap_or_else(|| default_height_for_role(role))
+                    .clamp(0.0, 1.0),
+                emissive: region.emissive.unwrap_or(false),
+            };
+            by_color
+                .entry(color.0)
+                .and_modify(|m| {
+                    m.height = m.height.max(material.height);
+                    m.emissive |= material.emissive;
+                })
+                .or_insert(material);
+        }
+        Self { by_color }
+    }
+
+    /// Material for a rendered pixel, or `None` if it is transparent.
+    pub fn material_at(&self, pixel: &Rgba<u8>) -> Option<Material> {
+        if pixel[3] == 0 {
+            return None;
+        }
+        Some(
+            self.by_color
+                .get(&pixel.0)
+                .copied()
+                .unwrap_or(Material { height: DEFAULT_HEIGHT, emissive: false }),
+        )
+    }
+
+    /// Generate the requested map for a rendered image.
+    pub fn render_map(&self, image: &RgbaImage, kind: MapKind) -> RgbaImage {
+        match kind {
+            MapKind::Normal => self.render_normal_map(image),
+            MapKind::Emissive => self.render_emissive_map(image),
+        }
+    }
+
+    /// Generate a normal map for a rendered image.
+    ///
+    /// Transparent pixels count as height 0, so sprite silhouettes bevel
+    /// outwards. Normals are encoded as `rgb = n * 0.5 + 0.5` with green
+    /// pointing up; alpha follows the albedo.
+    pub fn render_normal_map(&self, image: &RgbaImage) -> RgbaImage {
+        let (width, height) = image.dimensions();
+        let heights: Vec<f32> =
+            image.pixels().map(|p| self.material_at(p).map(|m| m.height).unwrap_or(0.0)).collect();
+        let height_at = |x: i64, y: i64| -> f32 {
+            if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                0.0
+            } else {
+                heights[(y as u32 * width + x as u32) as usize]
+            }
+        };
+
+        RgbaImage::from_fn(width, height, |x, y| {
+            let alpha = image.get_pixel(x, y)[3];
+            if alpha == 0 {
+                return Rgba([128, 128, 255, 0]);
+            }
+            let (x, y) = (x as i64, y as i64);
+            // Central differences; image y runs down, normal y runs up
+            let nx = -(height_at(x + 1, y) - height_at(x - 1, y)) * NORMAL_STRENGTH;
+            let ny = (height_at(x, y + 1) - height_at(x, y - 1)) * NORMAL_STRENGTH;
+            let len = (nx * nx + ny * ny + 1.0).sqrt();
+            let encode = |n: f32| ((n / len * 0.5 + 0.5) * 255.0).round() as u8;
+            Rgba([encode(nx), encode(ny), encode(1.0), alpha])
+        })
+    }
+
+    /// Generate an emissive mask for a rendered image.
+    pub fn render_emissive_map(&self, image: &RgbaImage) -> RgbaImage {
+        RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+            let pixel = image.get_pixel(x, y);
+            match self.material_at(pixel) {
+                Some(m) if m.emissive => *pixel,
+                _ => Rgba([0, 0, 0, 0]),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(role: Option<Role>, height: Option<f32>, emissive: Option<bool>) -> RegionDef {
+        RegionDef { role, height, emissive, ..Default::default() }
+    }
+
+    fn materials() -> MaterialPalette {
+        let regions = HashMap::from([
+            ("body".to_string(), region(Some(Role::Fill), None, None)),
+            ("shine".to_string(), region(None, None, None)),
+            ("eye".to_string(), region(None, Some(0.9), Some(true))),
+        ]);
+        // Palette roles apply to regions without their own
+        let roles = HashMap::from([
+            ("body".to_string(), Role::Boundary),
+            ("shine".to_string(), Role::Highlight),
+        ]);
+        let palette = HashMap::from([
+            ("body".to_string(), "#808080".to_string()),
+            ("shine".to_string(), "#FFFFFF".to_string()),
+            ("eye".to_string(), "#FF0000".to_string()),
+        ]);
+        MaterialPalette::from_regions(&regions, &palette, Some(&roles))
+    }
+
+    #[test]
+    fn test_material_from_role_and_hints() {
+        let materials = materials();
+        let shine = materials.material_at(&Rgba([255, 255, 255, 255])).unwrap();
+        assert_eq!(shine, Material { height: 1.0, emissive: false });
+        let eye = materials.material_at(&Rgba([255, 0, 0, 255])).unwrap();
+        assert_eq!(eye, Material { height: 0.9, emissive: true });
+        // Unknown opaque colors get the default height
+        assert_eq!(materials.material_at(&Rgba([1, 2, 3, 255])).unwrap().height, DEFAULT_HEIGHT);
+        assert_eq!(materials.material_at(&Rgba([0, 0, 0, 0])), None);
+    }
+
+    #[test]
+    fn test_normal_map_flat_and_sloped() {
+        let gray = Rgba([128, 128, 128, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        let mut image = RgbaImage::from_pixel(5, 3, gray);
+        image.put_pixel(3, 1, white);
+        let normals = materials().render_normal_map(&image);
+
+        assert_eq!(normals.dimensions(), (5, 3));
+        // Equal neighbours on both sides face straight out
+        assert_eq!(normals.get_pixel(1, 1).0, [128, 128, 255, 255]);
+        // Left of a raised pixel the surface tilts left
+        assert!(normals.get_pixel(2, 1)[0] < 128);
+        // Right of it the surface tilts right
+        assert!(normals.get_pixel(4, 1)[0] > 128);
+    }
+
+    #[test]
+    fn test_emissive_map() {
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([128, 128, 128, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+        let emissive = materials().render_emissive_map(&image);
+
+        assert_eq!(emissive.get_pixel(0, 0).0, [0, 0, 0, 0]);
+        assert_eq!(emissive.get_pixel(1, 0).0, [255, 0, 0, 255]);
+    }
+}
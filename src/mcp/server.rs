@@ -13,8 +13,9 @@ use super::prompts;
 use super::resources;
 use super::tools::{
     analyze::AnalyzeInput, diff::DiffInput, explain::ExplainInput, format::FormatInput,
-    import::ImportInput, palettes::PalettesInput, prime::PrimeInput, render::RenderInput,
-    scaffold::ScaffoldInput, suggest::SuggestInput, validate::ValidateInput,
+    import::ImportInput, palette_swap::PaletteSwapInput, palettes::PalettesInput,
+    prime::PrimeInput, render::RenderInput, scaffold::ScaffoldInput, suggest::SuggestInput,
+    validate::ValidateInput,
 };
 use crate::analyze::{collect_files, AnalysisReport};
 use crate::lsp_agent_client::LspAgentClient;
@@ -83,6 +84,38 @@ impl PixelsrcMcpServer {
         }
     }
 
+    // ── pixelsrc_palette_swap ────────────────────────────────────────
+
+    /// Recolor a sprite by generating a variant with palette overrides.
+    /// Returns a PNG preview and the variant JSONL line to append to the file.
+    #[tool(
+        description = "Recolor a sprite as a variant. Provide token colors and/or a palette (@builtin or named) to swap in. Returns a PNG preview and the variant JSONL line to append to the source."
+    )]
+    fn pixelsrc_palette_swap(
+        &self,
+        Parameters(input): Parameters<PaletteSwapInput>,
+    ) -> Result<CallToolResult, McpError> {
+        match super::tools::palette_swap::run_palette_swap(input) {
+            Ok(output) => {
+                let mut summary = output.variant_jsonl;
+                if !output.unused_tokens.is_empty() {
+                    summary.push_str(&format!(
+                        "\nTokens not in base palette: {}",
+                        output.unused_tokens.join(", ")
+                    ));
+                }
+                if !output.warnings.is_empty() {
+                    summary.push_str(&format!("\nWarnings:\n{}", output.warnings.join("\n")));
+                }
+                Ok(CallToolResult::success(vec![
+                    Content::image(output.base64_png, "image/png"),
+                    Content::text(summary),
+                ]))
+            }
+            Err(msg) => Ok(CallToolResult::error(vec![Content::text(msg)])),
+        }
+    }
+
     // ── pixelsrc_prime ───────────────────────────────────────────────
 
     #[tool(
@@ -274,7 +307,7 @@ impl PixelsrcMcpServer {
     #[tool(
         name = "pixelsrc_scaffold",
         description = "Generate a skeleton .pxl source for a given asset type. \
-                        Supports: sprite, animation, palette, composition. \
+                        Supports: sprite, animation, palette, composition, screen. \
                         Returns the .pxl content as a string (does not write to disk)."
     )]
     pub fn pixelsrc_scaffold(
@@ -286,6 +319,18 @@ impl PixelsrcMcpServer {
 
         match input.asset_type.to_lowercase().as_str() {
             "sprite" => {
+                if let Some(layout) = &input.layout {
+                    let parts: Vec<String> =
+                        layout.split(',').map(|s| s.trim().to_string()).collect();
+                    return scaffold::generate_sprite_layout(
+                        &input.name,
+                        width,
+                        height,
+                        input.palette.as_deref(),
+                        &parts,
+                    );
+                }
+
                 let tokens: Vec<String> = input
                     .tokens
                     .as_deref()
@@ -347,8 +392,26 @@ impl PixelsrcMcpServer {
                     input.palette.as_deref(),
                 )
             }
+            "screen" => {
+                use clap::ValueEnum;
+
+                let layout = input.layout.as_deref().unwrap_or("title");
+                let layout = scaffold::ScreenLayout::from_str(layout, true).map_err(|_| {
+                    format!(
+                        "Unknown screen layout '{}'. Available: title, hud, dialogue, inventory",
+                        layout
+                    )
+                })?;
+                scaffold::generate_screen(
+                    layout,
+                    &input.name,
+                    input.width.unwrap_or(320),
+                    input.height.unwrap_or(180),
+                    input.palette.as_deref(),
+                )
+            }
             other => Err(format!(
-                "Unknown asset type '{}'. Available: sprite, animation, palette, composition",
+                "Unknown asset type '{}'. Available: sprite, animation, palette, composition, screen",
                 other
             )),
         }
@@ -478,8 +541,8 @@ impl ServerHandler for PixelsrcMcpServer {
             instructions: Some(
                 "Pixelsrc MCP server — render, validate, explain, and manipulate \
                  pixel art in the .pxl format. Use pixelsrc_render to generate PNGs, \
-                 pixelsrc_validate to check source, and pixelsrc_prime to get the \
-                 format reference."
+                 pixelsrc_validate to check source, pixelsrc_palette_swap to recolor \
+                 sprites, and pixelsrc_prime to get the format reference."
                     .into(),
             ),
         }
@@ -63,11 +63,15 @@ pub fn run_explain(input: ExplainInput) -> Result<String, String> {
             TtpObject::Sprite(s) => &s.name,
             TtpObject::Composition(c) => &c.name,
             TtpObject::Animation(a) => &a.name,
+            TtpObject::Sequence(s) => &s.name,
             TtpObject::Variant(v) => &v.name,
             TtpObject::Particle(p) => &p.name,
             TtpObject::Transform(t) => &t.name,
             TtpObject::StateRules(sr) => &sr.name,
             TtpObject::Import(i) => &i.from,
+            TtpObject::Theme(t) => &t.name,
+            TtpObject::Stamp(s) => &s.name,
+            TtpObject::RandomSet(r) => &r.name,
         };
 
         if let Some(ref filter) = input.name {
@@ -146,6 +150,16 @@ fn explanation_to_json(exp: &Explanation) -> serde_json::Value {
             "duration_ms": a.duration_ms,
             "loops": a.loops,
         }),
+        Explanation::Sequence(s) => serde_json::json!({
+            "type": "sequence",
+            "name": s.name,
+            "steps": s.steps.iter().map(|(animation, repeat, crossfade)| serde_json::json!({
+                "animation": animation,
+                "repeat": repeat,
+                "crossfade_ms": crossfade,
+            })).collect::<Vec<_>>(),
+            "loops": s.loops,
+        }),
         Explanation::Composition(c) => serde_json::json!({
             "type": "composition",
             "name": c.name,
@@ -197,6 +211,28 @@ fn explanation_to_json(exp: &Explanation) -> serde_json::Value {
             "alias": i.alias,
             "imported_types": i.imported_types,
         }),
+        Explanation::Theme(t) => serde_json::json!({
+            "type": "theme",
+            "name": t.name,
+            "palettes": t.swaps.iter().cloned().collect::<std::collections::BTreeMap<_, _>>(),
+        }),
+        Explanation::Stamp(s) => serde_json::json!({
+            "type": "stamp",
+            "name": s.name,
+            "width": s.width,
+            "height": s.height,
+            "pixel_count": s.pixel_count,
+            "tokens": s.tokens,
+        }),
+        Explanation::RandomSet(r) => serde_json::json!({
+            "type": "random_set",
+            "name": r.name,
+            "choices": r.choices.iter().map(|(name, share)| serde_json::json!({
+                "name": name,
+                "share": share,
+            })).collect::<Vec<_>>(),
+            "regions": r.tokens.iter().cloned().collect::<std::collections::BTreeMap<_, _>>(),
+        }),
     }
 }
 
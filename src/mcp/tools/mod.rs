@@ -8,6 +8,7 @@ pub mod diff;
 pub mod explain;
 pub mod format;
 pub mod import;
+pub mod palette_swap;
 pub mod palettes;
 pub mod prime;
 pub mod render;
@@ -0,0 +1,84 @@
+//! MCP palette swap tool — recolor a sprite as a variant.
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::output::{encode_png, scale_image};
+use crate::palette_swap::{apply_palette_swap, PaletteSwap};
+
+/// Input parameters for the pixelsrc_palette_swap tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PaletteSwapInput {
+    /// Inline .pxl source content (JSONL) containing the sprite.
+    #[schemars(description = "Inline .pxl source content (JSONL) containing the sprite")]
+    pub source: Option<String>,
+
+    /// Path to a .pxl file on disk.
+    #[schemars(description = "Path to a .pxl file on disk")]
+    pub path: Option<String>,
+
+    /// Name of the sprite to recolor.
+    #[schemars(description = "Name of the sprite to recolor")]
+    pub sprite: String,
+
+    /// Token-to-color overrides, e.g. {"skin": "#FF6666"}.
+    #[schemars(description = "Token-to-color overrides, e.g. {\"skin\": \"#FF6666\"}")]
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+
+    /// Palette whose colors replace matching tokens (@builtin or a palette in the source).
+    #[schemars(
+        description = "Palette whose colors replace matching tokens (@builtin or a palette in the source)"
+    )]
+    pub palette: Option<String>,
+
+    /// Name for the generated variant (default: {sprite}_swap).
+    #[schemars(description = "Name for the generated variant (default: {sprite}_swap)")]
+    pub name: Option<String>,
+
+    /// Scale factor for the preview image (1-16, default: 1).
+    #[schemars(description = "Scale factor for the preview image (1-16, default: 1)")]
+    pub scale: Option<u8>,
+}
+
+/// Palette swap result containing the variant line and a base64 PNG preview.
+#[derive(Debug)]
+pub struct PaletteSwapOutput {
+    pub variant_jsonl: String,
+    pub base64_png: String,
+    pub unused_tokens: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Execute the palette swap tool logic.
+pub fn run_palette_swap(input: PaletteSwapInput) -> Result<PaletteSwapOutput, String> {
+    let source = if let Some(s) = input.source {
+        s
+    } else if let Some(ref p) = input.path {
+        std::fs::read_to_string(p).map_err(|e| format!("Failed to read file '{}': {}", p, e))?
+    } else {
+        return Err("Either 'source' (inline .pxl text) or 'path' (file path) is required".into());
+    };
+
+    let swap = PaletteSwap {
+        sprite: input.sprite,
+        name: input.name,
+        palette: input.palette,
+        colors: input.colors,
+    };
+    let result = apply_palette_swap(&source, &swap)?;
+
+    let scale = input.scale.unwrap_or(1).clamp(1, 16);
+    let png_bytes = encode_png(&scale_image(result.image.clone(), scale))
+        .map_err(|e| format!("PNG encoding failed: {}", e))?;
+
+    Ok(PaletteSwapOutput {
+        variant_jsonl: result.to_jsonl(),
+        base64_png: base64::engine::general_purpose::STANDARD.encode(&png_bytes),
+        unused_tokens: result.unused_tokens,
+        warnings: result.warnings,
+    })
+}
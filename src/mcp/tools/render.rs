@@ -7,7 +7,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::models::TtpObject;
-use crate::output::scale_image;
+use crate::output::{encode_png, scale_image};
 use crate::parser::parse_stream;
 use crate::registry::PaletteRegistry;
 use crate::renderer::render_sprite;
@@ -109,14 +109,7 @@ pub fn run_render(input: RenderInput) -> Result<RenderOutput, String> {
     let image = scale_image(image, scale);
 
     // 8. Encode to PNG
-    let mut png_bytes = Vec::new();
-    {
-        use image::ImageEncoder;
-        let encoder = image::codecs::png::PngEncoder::new(&mut png_bytes);
-        encoder
-            .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8)
-            .map_err(|e| format!("PNG encoding failed: {}", e))?;
-    }
+    let png_bytes = encode_png(&image).map_err(|e| format!("PNG encoding failed: {}", e))?;
 
     // 9. Encode to base64
     let base64_png = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
@@ -5,7 +5,8 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ScaffoldInput {
-    /// Asset type to scaffold: "sprite", "animation" (or "anim"), "palette", or "composition".
+    /// Asset type to scaffold: "sprite", "animation" (or "anim"), "palette", "composition",
+    /// or "screen".
     pub asset_type: String,
 
     /// Name for the generated asset. Must be lowercase letters, numbers, and underscores.
@@ -15,10 +16,10 @@ pub struct ScaffoldInput {
     /// (forest, medieval, synthwave, ocean). For sprite/composition, this names the palette.
     pub palette: Option<String>,
 
-    /// Width in pixels (for sprite/composition). Default: 16.
+    /// Width in pixels (for sprite/composition/screen). Default: 16 (320 for screens).
     pub width: Option<u32>,
 
-    /// Height in pixels (for sprite/composition). Default: 16.
+    /// Height in pixels (for sprite/composition/screen). Default: 16 (180 for screens).
     pub height: Option<u32>,
 
     /// Cell width for compositions. Default: 8.
@@ -30,6 +31,11 @@ pub struct ScaffoldInput {
     /// Comma-separated list of token names for sprite palette generation.
     pub tokens: Option<String>,
 
+    /// For sprites, comma-separated parts from top to bottom (e.g. "head,torso,legs"),
+    /// generating a region per part with colors from `palette` if it names a built-in.
+    /// For screens, the layout: "title" (default), "hud", "dialogue", or "inventory".
+    pub layout: Option<String>,
+
     /// Comma-separated hex colors for palette generation (e.g. "#FF0000,#00FF00").
     pub colors: Option<String>,
 }
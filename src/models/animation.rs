@@ -3,10 +3,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::core::Duration;
+use super::core::{Duration, PlaybackDirection};
 use super::palette::PaletteCycle;
 use super::sprite::{FrameMetadata, FrameTag};
 use super::transform::TransformSpec;
+use crate::ordered;
 
 /// A CSS-style keyframe defining properties at a specific point in an animation.
 ///
@@ -40,6 +41,92 @@ pub struct CssKeyframe {
     pub offset: Option<[i32; 2]>,
 }
 
+/// How fractional keyframe offsets are placed on the pixel grid.
+///
+/// Interpolating `offset` between keyframes gives fractional positions when
+/// a sprite moves less than a pixel per sampled frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubpixelMode {
+    /// Round to the nearest pixel, so slow motion moves in visible 1px jumps
+    #[default]
+    Round,
+    /// Alternate between the two nearest pixels across frames, in proportion
+    /// to the fraction, so slow motion reads as smooth
+    Dither,
+}
+
+/// Built-in motion curves for [`MotionPreset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MotionPresetKind {
+    /// Hop up and land, following a parabola
+    Bounce,
+    /// Jitter left and right
+    Shake,
+    /// Drift gently up and down
+    Float,
+    /// Stretch tall and thin, then squash short and wide, anchored at the bottom
+    SquashStretch,
+}
+
+/// A parameterized motion preset applied to one sprite.
+///
+/// Expanded into CSS keyframes when the animation is played, so common
+/// curves don't need to be authored by hand.
+///
+/// # Example
+/// ```json
+/// {
+///   "type": "animation",
+///   "name": "ball_bounce",
+///   "motion": { "preset": "bounce", "sprite": "ball", "amplitude": 6, "cycles": 3, "decay": 0.6 },
+///   "duration": "900ms"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MotionPreset {
+    /// Which curve to generate
+    pub preset: MotionPresetKind,
+    /// Sprite to move
+    pub sprite: String,
+    /// Strength: pixels for bounce, shake and float; scale change for
+    /// squash_stretch (default depends on the preset)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub amplitude: Option<f64>,
+    /// Repetitions within the animation's duration (default: 1, or 3 for shake)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cycles: Option<u32>,
+    /// Share of the amplitude kept from one cycle to the next, 0.0-1.0 (default: 1.0)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub decay: Option<f64>,
+}
+
+impl MotionPreset {
+    /// Returns the amplitude, using the preset's default if not specified.
+    pub fn amplitude(&self) -> f64 {
+        self.amplitude.unwrap_or(match self.preset {
+            MotionPresetKind::Bounce => 4.0,
+            MotionPresetKind::Shake => 1.0,
+            MotionPresetKind::Float => 2.0,
+            MotionPresetKind::SquashStretch => 0.2,
+        })
+    }
+
+    /// Returns the number of cycles, using the preset's default if not specified.
+    pub fn cycles(&self) -> u32 {
+        self.cycles.unwrap_or(match self.preset {
+            MotionPresetKind::Shake => 3,
+            _ => 1,
+        })
+    }
+
+    /// Returns the per-cycle decay (default: 1.0, no decay).
+    pub fn decay(&self) -> f64 {
+        self.decay.unwrap_or(1.0)
+    }
+}
+
 /// Motion follow mode for secondary motion attachments.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -89,6 +176,7 @@ pub struct Attachment {
     pub z_index: Option<i32>,
     /// Keyframe data for explicit positioning per frame (keyed by frame number as string)
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub keyframes: Option<HashMap<String, AttachmentKeyframe>>,
 }
 
@@ -164,7 +252,11 @@ pub struct Animation {
     /// }
     /// ```
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub keyframes: Option<HashMap<String, CssKeyframe>>,
+    /// Motion preset expanded into `keyframes` (replaces `frames` and `keyframes`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub motion: Option<MotionPreset>,
     /// Reference to another animation by name (mutually exclusive with `frames`)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub source: Option<String>,
@@ -175,17 +267,27 @@ pub struct Animation {
     /// Accepts both raw milliseconds (100) and CSS time strings ("500ms", "1s").
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub duration: Option<Duration>,
+    /// Per-frame durations, one per entry in `frames` (overrides `duration`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub durations: Option<Vec<Duration>>,
     /// CSS timing function for keyframes interpolation (e.g., "linear", "ease", "ease-in-out",
     /// "cubic-bezier(0.25, 0.1, 0.25, 1.0)", "steps(4, jump-end)")
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub timing_function: Option<String>,
+    /// Placement of fractional keyframe offsets: "round" (default) or "dither"
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub subpixel: Option<SubpixelMode>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub r#loop: Option<bool>,
+    /// Playback direction: "forward" (default), "reverse", or "pingpong"
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub direction: Option<PlaybackDirection>,
     /// Palette cycles for color animation effects (water, fire, energy, etc.)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub palette_cycle: Option<Vec<PaletteCycle>>,
     /// Frame tags for game engine integration - maps tag name to frame range
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub tags: Option<HashMap<String, FrameTag>>,
     /// Per-frame metadata (collision boxes that vary per frame)
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -193,6 +295,9 @@ pub struct Animation {
     /// Attachments for secondary motion (hair, capes, tails)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub attachments: Option<Vec<Attachment>>,
+    /// Selection labels for `--only label=...` and `--exclude label=...`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub labels: Vec<String>,
 }
 
 impl Animation {
@@ -210,11 +315,57 @@ impl Animation {
             .unwrap_or(Self::DEFAULT_DURATION_MS)
     }
 
+    /// Returns how long each frame is shown, in milliseconds.
+    ///
+    /// A frame's `frame_metadata` duration wins over its `durations` entry,
+    /// which wins over the animation-wide `duration`. Fails if `durations` does
+    /// not have one entry per frame or an entry is not a valid duration.
+    pub fn frame_durations(&self) -> Result<Vec<u32>, String> {
+        let default = self.duration_ms();
+        let count = self.frames.len();
+        let per_frame = match &self.durations {
+            Some(durations) if durations.len() != count => {
+                return Err(format!(
+                    "animation '{}' has {} durations for {} frames",
+                    self.name,
+                    durations.len(),
+                    count
+                ));
+            }
+            Some(durations) => durations.iter().map(Some).collect(),
+            None => vec![None; count],
+        };
+
+        let metadata = self.frame_metadata.as_deref().unwrap_or(&[]);
+        per_frame
+            .into_iter()
+            .enumerate()
+            .map(|(i, duration)| {
+                match metadata.get(i).and_then(|m| m.duration.as_ref()).or(duration) {
+                    Some(d) => d.as_milliseconds().ok_or_else(|| {
+                        format!("animation '{}' frame {}: invalid duration {}", self.name, i, d)
+                    }),
+                    None => Ok(default),
+                }
+            })
+            .collect()
+    }
+
     /// Returns whether the animation should loop (default: true).
     pub fn loops(&self) -> bool {
         self.r#loop.unwrap_or(true)
     }
 
+    /// Returns how fractional keyframe offsets are placed (default: round).
+    pub fn subpixel(&self) -> SubpixelMode {
+        self.subpixel.unwrap_or_default()
+    }
+
+    /// Returns the playback direction (default: forward).
+    pub fn direction(&self) -> PlaybackDirection {
+        self.direction.unwrap_or_default()
+    }
+
     /// Returns whether this animation uses CSS-style keyframes.
     pub fn is_css_keyframes(&self) -> bool {
         self.keyframes.as_ref().is_some_and(|kf| !kf.is_empty())
@@ -240,6 +391,19 @@ impl Animation {
         self.palette_cycle.as_deref().unwrap_or(&[])
     }
 
+    /// Returns the frame tag with the given name, if defined.
+    pub fn tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.as_ref().and_then(|tags| tags.get(name))
+    }
+
+    /// Returns the defined tag names, sorted.
+    pub fn tag_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> =
+            self.tags.iter().flat_map(|tags| tags.keys().map(|k| k.as_str())).collect();
+        names.sort_unstable();
+        names
+    }
+
     /// Returns whether this animation has secondary motion attachments.
     pub fn has_attachments(&self) -> bool {
         self.attachments.as_ref().map(|a| !a.is_empty()).unwrap_or(false)
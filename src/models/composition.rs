@@ -1,10 +1,13 @@
 //! Composition types for layering sprites.
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use super::core::VarOr;
+use super::locale::LocaleOverride;
 use super::transform::TransformSpec;
+use crate::ordered;
 
 /// A layer within a composition.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
@@ -27,6 +30,252 @@ pub struct CompositionLayer {
     /// Can be a number (0.5) or a var() string ("var(--layer-opacity)").
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub opacity: Option<VarOr<f64>>,
+    /// Name of the layer group this layer belongs to
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub group: Option<String>,
+    /// Skip this layer when rendering. Default: false
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub hidden: bool,
+    /// Layer or sprite name whose alpha clips this layer's content
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mask: Option<String>,
+    /// Keep content where the mask is transparent instead. Default: false
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub mask_invert: bool,
+    /// Drawing priority; higher layers draw on top, ties keep file order. Default: 0
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub z: Option<i32>,
+    /// Soft shadow cast onto the layers below, next to this layer's silhouette
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub contact_shadow: Option<ContactShadow>,
+    /// How far this layer moves with the composition's `camera`: 0.0 stays
+    /// put, 1.0 moves with the scene. Default: 1.0
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub parallax: Option<Parallax>,
+    /// Tile the layer's map across the canvas along an axis
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub repeat: Option<Repeat>,
+    /// Drift `[x, y]` in pixels per second in web export, wrapping around the
+    /// canvas (clouds, water)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub scroll: Option<[f64; 2]>,
+}
+
+impl CompositionLayer {
+    /// Parallax factors `[x, y]` (default: `[1.0, 1.0]`).
+    pub fn parallax(&self) -> [f64; 2] {
+        self.parallax.map_or([1.0, 1.0], Parallax::factors)
+    }
+}
+
+/// A layer's parallax factor: one for both axes, or `[x, y]`.
+///
+/// # Example
+/// ```json
+/// { "parallax": 0.5 }
+/// { "parallax": [0.5, 0.25] }
+/// ```
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Parallax {
+    Uniform(f64),
+    Axes([f64; 2]),
+}
+
+impl Parallax {
+    /// Factors `[x, y]`.
+    pub fn factors(self) -> [f64; 2] {
+        match self {
+            Parallax::Uniform(factor) => [factor, factor],
+            Parallax::Axes(factors) => factors,
+        }
+    }
+}
+
+/// Axes a layer is tiled along.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Repeat {
+    X,
+    Y,
+    Both,
+}
+
+impl Repeat {
+    /// Whether the layer repeats horizontally and vertically.
+    pub fn axes(self) -> [bool; 2] {
+        match self {
+            Repeat::X => [true, false],
+            Repeat::Y => [false, true],
+            Repeat::Both => [true, true],
+        }
+    }
+}
+
+/// A soft shadow a composition layer casts onto the layers below it, where
+/// its silhouette meets them.
+///
+/// # Example
+/// ```json
+/// { "direction": "down", "size": 2, "opacity": 0.5, "color": "#000000" }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ContactShadow {
+    /// Which way the shadow falls from the silhouette (default: down)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub direction: Option<ShadowDirection>,
+    /// Shadow length in pixels, fading out along it (default: 2)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub size: Option<u32>,
+    /// Opacity next to the silhouette, 0.0-1.0 (default: 0.5)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub opacity: Option<f64>,
+    /// Shadow color (default: black)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub color: Option<String>,
+}
+
+impl ContactShadow {
+    /// Default shadow length in pixels.
+    pub const DEFAULT_SIZE: u32 = 2;
+    /// Default opacity next to the silhouette.
+    pub const DEFAULT_OPACITY: f64 = 0.5;
+}
+
+/// Direction a contact shadow falls in.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShadowDirection {
+    #[default]
+    Down,
+    Up,
+    Left,
+    Right,
+    DownLeft,
+    DownRight,
+    UpLeft,
+    UpRight,
+}
+
+impl ShadowDirection {
+    /// One pixel step `[dx, dy]` in this direction.
+    pub fn step(self) -> [i32; 2] {
+        match self {
+            ShadowDirection::Down => [0, 1],
+            ShadowDirection::Up => [0, -1],
+            ShadowDirection::Left => [-1, 0],
+            ShadowDirection::Right => [1, 0],
+            ShadowDirection::DownLeft => [-1, 1],
+            ShadowDirection::DownRight => [1, 1],
+            ShadowDirection::UpLeft => [-1, -1],
+            ShadowDirection::UpRight => [1, -1],
+        }
+    }
+}
+
+/// A named group of composition layers.
+///
+/// Member layers are composited together first; the group's `blend` and
+/// `opacity` then apply to that result as if it were a single layer, drawn at
+/// the position of the group's first layer.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LayerGroup {
+    pub name: String,
+    /// Blend mode for the composited group. Default: "normal"
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub blend: Option<String>,
+    /// Group opacity from 0.0 (transparent) to 1.0 (opaque). Default: 1.0
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub opacity: Option<VarOr<f64>>,
+    /// Skip every layer in the group when rendering. Default: false
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub hidden: bool,
+}
+
+/// Automatic depth sorting of composition cells.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoSort {
+    /// Draw map cells by the bottom edge of their sprite, top to bottom
+    Y,
+}
+
+/// Mirroring applied to a sprite instance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Flip {
+    /// Mirror left to right
+    H,
+    /// Mirror top to bottom
+    V,
+    /// Mirror both ways (a 180° rotation)
+    Hv,
+}
+
+/// A sprite placed by a map key, with per-instance overrides.
+///
+/// # Example
+/// ```json
+/// { "sprite": "tree", "variant": "tree_autumn", "offset": [0, -3], "flip": "h" }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SpriteInstance {
+    pub sprite: String,
+    /// Variant of `sprite` drawn in its place
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub variant: Option<String>,
+    /// Pixel offset `[dx, dy]` from the cell position
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub offset: Option<[i32; 2]>,
+    /// Mirror the sprite before drawing it
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub flip: Option<Flip>,
+}
+
+/// A composition sprite map entry: a sprite name or an instance with overrides.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SpriteRef {
+    Name(String),
+    Instance(SpriteInstance),
+}
+
+impl SpriteRef {
+    /// Name of the sprite or variant to draw.
+    pub fn name(&self) -> &str {
+        match self {
+            SpriteRef::Name(name) => name,
+            SpriteRef::Instance(inst) => inst.variant.as_deref().unwrap_or(&inst.sprite),
+        }
+    }
+
+    /// Pixel offset from the cell position (`[0, 0]` for plain names).
+    pub fn offset(&self) -> [i32; 2] {
+        match self {
+            SpriteRef::Name(_) => [0, 0],
+            SpriteRef::Instance(inst) => inst.offset.unwrap_or([0, 0]),
+        }
+    }
+
+    /// Mirroring to apply, if any.
+    pub fn flip(&self) -> Option<Flip> {
+        match self {
+            SpriteRef::Name(_) => None,
+            SpriteRef::Instance(inst) => inst.flip,
+        }
+    }
+}
+
+impl From<String> for SpriteRef {
+    fn from(name: String) -> Self {
+        SpriteRef::Name(name)
+    }
+}
+
+impl From<&str> for SpriteRef {
+    fn from(name: &str) -> Self {
+        SpriteRef::Name(name.to_string())
+    }
 }
 
 /// A composition that layers sprites onto a canvas.
@@ -39,8 +288,101 @@ pub struct Composition {
     pub size: Option<[u32; 2]>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cell_size: Option<[u32; 2]>,
-    pub sprites: HashMap<String, Option<String>>,
+    /// Map keys to sprites; `null` leaves the cell empty
+    #[serde(serialize_with = "ordered::map")]
+    pub sprites: HashMap<String, Option<SpriteRef>>,
     pub layers: Vec<CompositionLayer>,
+    /// Layer groups referenced by `CompositionLayer::group`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub groups: Vec<LayerGroup>,
+    /// Depth sort map cells across layers of equal `z` before compositing
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub auto_sort: Option<AutoSort>,
+    /// Separator between map cells, allowing multi-character keys
+    /// (`"tree|rock|."`). Default: every character is a cell
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub map_delimiter: Option<String>,
+    /// Per-locale substitutions, selected with `--locale`
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub locale: HashMap<String, LocaleOverride>,
+    /// Selection labels for `--only label=...` and `--exclude label=...`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub labels: Vec<String>,
+    /// Snap the blended output back to a palette
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub palette_lock: Option<PaletteLock>,
+    /// Light sources added to the finished image, before `palette_lock`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub lights: Vec<Light>,
+    /// Color the finished image is multiplied by before lights are added
+    /// (`"#404060"` for night). Default: white, leaving colors unchanged
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ambient: Option<String>,
+    /// View position `[x, y]`: layers shift by `-camera * parallax`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub camera: Option<[i32; 2]>,
+}
+
+/// A 2D point light of a composition's lighting pass.
+///
+/// # Example
+/// ```json
+/// { "at": [10, 4], "radius": 12, "color": "#FFDD88", "falloff": "smooth" }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Light {
+    /// Center `[x, y]` in canvas pixels
+    pub at: [i32; 2],
+    /// Distance in pixels at which the light fades out
+    pub radius: u32,
+    /// Light color (default: white)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub color: Option<String>,
+    /// Strength at the center, 0.0-1.0 or more to overexpose (default: 1.0)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub intensity: Option<f64>,
+    /// How brightness drops toward the radius (default: linear)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub falloff: Option<Falloff>,
+}
+
+/// How a light's brightness drops from its center to its radius.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Falloff {
+    /// Brightness drops evenly with distance
+    #[default]
+    Linear,
+    /// Smoothstep: bright core with a soft edge
+    Smooth,
+    /// Full brightness out to the radius, then none
+    Hard,
+}
+
+/// The palette a composition's output is snapped to after blending.
+///
+/// # Example
+/// ```json
+/// { "palette_lock": true }
+/// { "palette_lock": "@pico8" }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum PaletteLock {
+    /// `true` locks to the colors of the composition's own sprites; `false` disables the lock
+    Enabled(bool),
+    /// A palette by name, `@` prefixed for built-ins
+    Palette(String),
+}
+
+/// Split a map row into cell keys: on `delimiter` (trimming each cell) if
+/// given, otherwise one cell per character.
+pub fn split_map_row<'a>(row: &'a str, delimiter: Option<&str>) -> Vec<&'a str> {
+    match delimiter {
+        Some(delimiter) if !delimiter.is_empty() => row.split(delimiter).map(str::trim).collect(),
+        _ => row.char_indices().map(|(i, c)| &row[i..i + c.len_utf8()]).collect(),
+    }
 }
 
 impl Composition {
@@ -51,4 +393,81 @@ impl Composition {
     pub fn cell_size(&self) -> [u32; 2] {
         self.cell_size.unwrap_or(Self::DEFAULT_CELL_SIZE)
     }
+
+    /// Split a map row into cell keys, by `map_delimiter` or into characters.
+    ///
+    /// Delimited cells are trimmed, so `"tree | rock"` is two cells.
+    pub fn map_cells<'a>(&self, row: &'a str) -> Vec<&'a str> {
+        split_map_row(row, self.map_delimiter.as_deref())
+    }
+
+    /// The sprite map entry for a cell key: `Some(None)` for an empty cell,
+    /// `None` if the key is unknown.
+    ///
+    /// Delimited maps may name sprites directly: keys missing from `sprites`
+    /// draw the sprite of that name, and `.` or blank cells are empty.
+    pub fn cell_sprite<'a>(&'a self, key: &'a str) -> Option<Option<Cow<'a, SpriteRef>>> {
+        match self.sprites.get(key) {
+            Some(entry) => Some(entry.as_ref().map(Cow::Borrowed)),
+            None if self.map_delimiter.is_none() => None,
+            None if key.is_empty() || key == "." => Some(None),
+            None => Some(Some(Cow::Owned(SpriteRef::Name(key.to_string())))),
+        }
+    }
+
+    /// Layers in drawing order: by `z`, then file order.
+    pub fn layers_in_order(&self) -> Vec<&CompositionLayer> {
+        let mut layers: Vec<&CompositionLayer> = self.layers.iter().collect();
+        layers.sort_by_key(|layer| layer.z.unwrap_or(0));
+        layers
+    }
+
+    /// Look up a layer group by name.
+    pub fn group(&self, name: &str) -> Option<&LayerGroup> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// Names of the sprites and variants the sprite map draws.
+    ///
+    /// Includes sprites that delimited maps name directly.
+    pub fn sprite_names(&self) -> impl Iterator<Item = &str> {
+        let direct = self
+            .layers
+            .iter()
+            .filter(|_| self.map_delimiter.is_some())
+            .flat_map(|layer| layer.map.iter().flatten())
+            .flat_map(|row| self.map_cells(row))
+            .filter(|key| !key.is_empty() && *key != "." && !self.sprites.contains_key(*key));
+        self.sprites.values().flatten().map(SpriteRef::name).chain(direct)
+    }
+
+    /// Sprite names used as layer masks (masks that don't name a layer).
+    pub fn mask_sprites(&self) -> impl Iterator<Item = &String> {
+        self.layers
+            .iter()
+            .filter_map(|l| l.mask.as_ref())
+            .filter(|mask| !self.layers.iter().any(|l| l.name.as_deref() == Some(mask.as_str())))
+    }
+
+    /// Whether a layer renders: neither it nor its group is hidden.
+    pub fn is_layer_visible(&self, layer: &CompositionLayer) -> bool {
+        !layer.hidden
+            && !layer.group.as_deref().and_then(|g| self.group(g)).is_some_and(|g| g.hidden)
+    }
+
+    /// Show or hide every layer and group whose name matches `pattern`.
+    ///
+    /// Returns how many layers and groups matched.
+    pub fn set_hidden_matching(&mut self, pattern: &glob::Pattern, hidden: bool) -> usize {
+        let mut matched = 0;
+        let layers = self.layers.iter_mut().map(|l| (l.name.as_deref(), &mut l.hidden));
+        let groups = self.groups.iter_mut().map(|g| (Some(g.name.as_str()), &mut g.hidden));
+        for (name, flag) in layers.chain(groups) {
+            if name.is_some_and(|n| pattern.matches(n)) {
+                *flag = hidden;
+                matched += 1;
+            }
+        }
+        matched
+    }
 }
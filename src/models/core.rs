@@ -146,6 +146,69 @@ impl From<&str> for Duration {
     }
 }
 
+/// Playback direction for animations and palette cycles.
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::models::PlaybackDirection;
+///
+/// let dir: PlaybackDirection = serde_json::from_str("\"pingpong\"").unwrap();
+/// assert_eq!(dir.frame_order(4), vec![0, 1, 2, 3, 2, 1]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackDirection {
+    /// First frame to last
+    #[default]
+    Forward,
+    /// Last frame to first
+    Reverse,
+    /// Forward, then back again without repeating the end frames
+    Pingpong,
+}
+
+impl PlaybackDirection {
+    /// Returns the frame indices for one pass over `len` frames.
+    ///
+    /// Ping-pong does not repeat the first or last frame, so a looping
+    /// animation of four frames plays `0 1 2 3 2 1 0 1 ...`.
+    pub fn frame_order(&self, len: usize) -> Vec<usize> {
+        match self {
+            PlaybackDirection::Forward => (0..len).collect(),
+            PlaybackDirection::Reverse => (0..len).rev().collect(),
+            PlaybackDirection::Pingpong => {
+                (0..len).chain((1..len.saturating_sub(1)).rev()).collect()
+            }
+        }
+    }
+
+    /// Reorders `frames` into one pass in this direction.
+    pub fn apply<T: Clone>(&self, frames: Vec<T>) -> Vec<T> {
+        match self {
+            PlaybackDirection::Forward => frames,
+            _ => self.frame_order(frames.len()).into_iter().map(|i| frames[i].clone()).collect(),
+        }
+    }
+
+    /// Number of frames in one pass over `len` frames.
+    pub fn pass_length(&self, len: usize) -> usize {
+        match self {
+            PlaybackDirection::Pingpong if len > 2 => 2 * len - 2,
+            _ => len,
+        }
+    }
+
+    /// Lowercase name as used in the JSON format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlaybackDirection::Forward => "forward",
+            PlaybackDirection::Reverse => "reverse",
+            PlaybackDirection::Pingpong => "pingpong",
+        }
+    }
+}
+
 /// Parse a CSS duration string into milliseconds.
 ///
 /// Supports:
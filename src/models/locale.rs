@@ -0,0 +1,33 @@
+//! Locale override type for regional sprite swaps.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::ordered;
+
+/// Substitutions an object declares for one locale.
+///
+/// Selected with `--locale` when rendering. Sprites and variants use `sprite`
+/// to render another sprite or variant in their place; compositions use
+/// `sprites` to replace entries in their sprite map.
+///
+/// # Example
+/// ```json
+/// {
+///   "type": "composition",
+///   "name": "menu",
+///   "sprites": { "T": "title_en", "B": "button" },
+///   "layers": [{ "map": ["TB"] }],
+///   "locale": { "ja": { "sprites": { "T": "title_ja" } } }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LocaleOverride {
+    /// Sprite or variant rendered in place of this one
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sprite: Option<String>,
+    /// Composition sprite map entries to replace (`null` for an empty cell)
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub sprites: HashMap<String, Option<String>>,
+}
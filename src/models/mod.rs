@@ -4,26 +4,45 @@ mod animation;
 mod composition;
 mod core;
 mod import;
+mod locale;
 mod object;
 mod palette;
 mod particle;
+mod random_set;
 mod region;
+mod sequence;
 mod sprite;
+mod stamp;
+mod theme;
 mod transform;
 mod variant;
 
 // Re-export all public types
-pub use animation::{Animation, Attachment, AttachmentKeyframe, CssKeyframe, FollowMode};
-pub use composition::{Composition, CompositionLayer};
-pub use core::{parse_css_duration, Duration, VarOr};
+pub use animation::{
+    Animation, Attachment, AttachmentKeyframe, CssKeyframe, FollowMode, MotionPreset,
+    MotionPresetKind, SubpixelMode,
+};
+pub use composition::{
+    split_map_row, AutoSort, Composition, CompositionLayer, ContactShadow, Falloff, Flip,
+    LayerGroup, Light, PaletteLock, Parallax, Repeat, ShadowDirection, SpriteInstance, SpriteRef,
+};
+pub use core::{parse_css_duration, Duration, PlaybackDirection, VarOr};
 pub use import::Import;
+pub use locale::LocaleOverride;
 pub use object::{TtpObject, Warning};
 pub use palette::{
     ColorRamp, ColorShift, Palette, PaletteCycle, PaletteRef, Relationship, RelationshipType, Role,
 };
 pub use particle::{Particle, ParticleEmitter, VelocityRange};
+pub use random_set::{RandomChoice, RandomSet, RegionChoice};
 pub use region::{JitterSpec, RegionDef};
-pub use sprite::{CollisionBox, FrameMetadata, FrameTag, NineSlice, Sprite, SpriteMetadata};
+pub use sequence::{Sequence, SequenceStep};
+pub use sprite::{
+    CollisionBox, FrameMetadata, FrameTag, NineSlice, Sprite, SpriteLayer, SpriteMetadata,
+    SpriteReference,
+};
+pub use stamp::{Stamp, StampPlacement};
+pub use theme::Theme;
 pub use transform::{
     Easing, Keyframe, KeyframeSpec, PropertyKeyframes, TransformDef, TransformSpec,
 };
@@ -296,7 +315,7 @@ mod tests {
                 assert!(comp.cell_size.is_none());
                 assert_eq!(comp.sprites.len(), 2);
                 assert_eq!(comp.sprites.get("."), Some(&None));
-                assert_eq!(comp.sprites.get("X"), Some(&Some("sprite_x".to_string())));
+                assert_eq!(comp.sprites.get("X"), Some(&Some("sprite_x".into())));
                 assert_eq!(comp.layers.len(), 1);
                 assert_eq!(comp.layers[0].name, Some("layer1".to_string()));
                 assert_eq!(comp.layers[0].map, Some(vec!["X.".to_string()]));
@@ -330,7 +349,7 @@ mod tests {
             cell_size: Some([4, 4]),
             sprites: HashMap::from([
                 (".".to_string(), None),
-                ("A".to_string(), Some("sprite_a".to_string())),
+                ("A".to_string(), Some("sprite_a".into())),
             ]),
             layers: vec![CompositionLayer {
                 name: Some("layer1".to_string()),
@@ -338,6 +357,15 @@ mod tests {
                 map: Some(vec!["A.".to_string(), ".A".to_string()]),
                 ..Default::default()
             }],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
         let obj = TtpObject::Composition(comp.clone());
         let json = serde_json::to_string(&obj).unwrap();
@@ -377,6 +405,15 @@ mod tests {
             cell_size: Some([8, 8]),
             sprites: HashMap::new(),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
         assert_eq!(comp.cell_size(), [8, 8]);
 
@@ -388,11 +425,58 @@ mod tests {
             cell_size: None,
             sprites: HashMap::new(),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
         assert_eq!(comp_default.cell_size(), Composition::DEFAULT_CELL_SIZE);
         assert_eq!(comp_default.cell_size(), [1, 1]);
     }
 
+    #[test]
+    fn test_composition_parse_layer_groups() {
+        let json = r#"{"type": "composition", "name": "forest", "sprites": {}, "layers": [{"name": "trees", "group": "fg"}, {"name": "fog", "hidden": true}], "groups": [{"name": "fg", "blend": "screen", "opacity": 0.5, "hidden": true}]}"#;
+        let obj: TtpObject = serde_json::from_str(json).unwrap();
+        match obj {
+            TtpObject::Composition(comp) => {
+                let group = comp.group("fg").unwrap();
+                assert_eq!(group.blend.as_deref(), Some("screen"));
+                assert_eq!(group.opacity, Some(VarOr::Value(0.5)));
+                assert!(group.hidden);
+                assert_eq!(comp.layers[0].group.as_deref(), Some("fg"));
+                assert!(!comp.is_layer_visible(&comp.layers[0]));
+                assert!(comp.layers[1].hidden);
+
+                // Defaults stay out of serialized output
+                let out = serde_json::to_string(&comp.layers[0]).unwrap();
+                assert!(!out.contains("hidden"));
+            }
+            _ => panic!("Expected composition"),
+        }
+    }
+
+    #[test]
+    fn test_composition_parse_layer_mask() {
+        let json = r#"{"type": "composition", "name": "cave", "sprites": {}, "layers": [{"name": "light", "hidden": true}, {"name": "dark", "mask": "light", "mask_invert": true}, {"name": "glow", "mask": "halo"}]}"#;
+        let obj: TtpObject = serde_json::from_str(json).unwrap();
+        match obj {
+            TtpObject::Composition(comp) => {
+                assert_eq!(comp.layers[1].mask.as_deref(), Some("light"));
+                assert!(comp.layers[1].mask_invert);
+                assert!(!comp.layers[2].mask_invert);
+                // Only masks that don't name a layer need sprites
+                assert_eq!(comp.mask_sprites().collect::<Vec<_>>(), ["halo"]);
+            }
+            _ => panic!("Expected composition"),
+        }
+    }
+
     #[test]
     fn test_animation_parse_full() {
         // Animation with all fields specified
@@ -538,6 +622,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sequence_parse() {
+        let json = r#"{"type": "sequence", "name": "reel", "steps": [{"animation": "idle", "repeat": 2}, {"animation": "attack", "crossfade": "0.1s"}]}"#;
+        let obj: TtpObject = serde_json::from_str(json).unwrap();
+        let TtpObject::Sequence(seq) = obj else { panic!("expected sequence") };
+        assert_eq!(seq.name, "reel");
+        assert!(seq.loops());
+        assert_eq!(seq.steps[0].repeat_count(), 2);
+        assert_eq!(seq.steps[1].repeat_count(), 1);
+        assert_eq!(seq.steps[1].crossfade.as_ref().and_then(|d| d.as_milliseconds()), Some(100));
+    }
+
+    #[test]
+    fn test_theme_parse() {
+        let json =
+            r#"{"type": "theme", "name": "night", "palettes": {"forest_day": "forest_night"}}"#;
+        let obj: TtpObject = serde_json::from_str(json).unwrap();
+        let TtpObject::Theme(theme) = obj else { panic!("expected theme") };
+        assert_eq!(theme.name, "night");
+        assert_eq!(theme.palettes.get("forest_day").map(String::as_str), Some("forest_night"));
+    }
+
+    #[test]
+    fn test_animation_frame_durations() {
+        let json = r#"{"name": "attack", "frames": ["a", "b", "c"], "duration": 80, "durations": [300, "50ms", 200], "frame_metadata": [{}, {}, {"duration": "0.5s"}]}"#;
+        let anim: Animation = serde_json::from_str(json).unwrap();
+        assert_eq!(anim.frame_durations().unwrap(), vec![300, 50, 500]);
+
+        // Without `durations`, every frame uses `duration`
+        let plain: Animation =
+            serde_json::from_str(r#"{"name": "idle", "frames": ["a", "b"], "duration": 80}"#)
+                .unwrap();
+        assert_eq!(plain.frame_durations().unwrap(), vec![80, 80]);
+
+        let mismatched: Animation =
+            serde_json::from_str(r#"{"name": "bad", "frames": ["a", "b"], "durations": [100]}"#)
+                .unwrap();
+        assert!(mismatched.frame_durations().unwrap_err().contains("1 durations for 2 frames"));
+
+        let invalid: Animation =
+            serde_json::from_str(r#"{"name": "bad", "frames": ["a"], "durations": ["soon"]}"#)
+                .unwrap();
+        assert!(invalid.frame_durations().is_err());
+    }
+
+    #[test]
+    fn test_playback_direction() {
+        let json = r#"{"name": "bounce", "frames": ["a", "b", "c"], "direction": "pingpong", "palette_cycle": [{"tokens": ["{x}", "{y}"], "direction": "reverse"}]}"#;
+        let anim: Animation = serde_json::from_str(json).unwrap();
+        assert_eq!(anim.direction(), PlaybackDirection::Pingpong);
+        assert_eq!(anim.palette_cycles()[0].direction(), PlaybackDirection::Reverse);
+        assert_eq!(PlaybackDirection::Forward.frame_order(3), vec![0, 1, 2]);
+        assert_eq!(PlaybackDirection::Reverse.frame_order(3), vec![2, 1, 0]);
+        assert_eq!(PlaybackDirection::Pingpong.frame_order(2), vec![0, 1]);
+        assert_eq!(
+            PlaybackDirection::Pingpong.apply(vec!['a', 'b', 'c']),
+            vec!['a', 'b', 'c', 'b']
+        );
+
+        // Forward is the default and is omitted when serializing
+        let plain: Animation = serde_json::from_str(r#"{"name": "a", "frames": ["x"]}"#).unwrap();
+        assert_eq!(plain.direction(), PlaybackDirection::Forward);
+        assert!(!serde_json::to_string(&plain).unwrap().contains("direction"));
+
+        assert!(serde_json::from_str::<Animation>(
+            r#"{"name": "a", "frames": ["x"], "direction": "sideways"}"#
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_animation_palette_cycle_roundtrip() {
         let anim = Animation {
@@ -548,6 +702,7 @@ mod tests {
             palette_cycle: Some(vec![PaletteCycle {
                 tokens: vec!["{a}".to_string(), "{b}".to_string()],
                 duration: Some(150),
+                direction: None,
             }]),
             tags: None,
             frame_metadata: None,
@@ -584,6 +739,53 @@ mod tests {
         assert!(anim.palette_cycles().is_empty());
     }
 
+    #[test]
+    fn test_sprite_inherit_merge_rules() {
+        let base: Sprite = serde_json::from_str(
+            r##"{"name": "humanoid", "size": [16, 24], "palette": {"skin": "#FC9", "eye": "#000"},
+                "regions": {"body": {"rect": [4, 6, 8, 12]}, "head": {"rect": [5, 0, 6, 6]}},
+                "layers": [{"name": "back"}, {"name": "front", "z": 1}],
+                "metadata": {"origin": [8, 24], "boxes": {"hurt": {"x": 0, "y": 0, "w": 16, "h": 24}}},
+                "labels": ["character"]}"##,
+        )
+        .unwrap();
+        let knight: Sprite = serde_json::from_str(
+            r##"{"name": "knight", "extends": "humanoid", "palette": {"eye": "#00F", "steel": "#AAA"},
+                "regions": {"head": {"rect": [5, 0, 6, 3]}, "visor": {"rect": [6, 2, 4, 1]}},
+                "layers": [{"name": "front", "z": 5}, {"name": "cape"}],
+                "metadata": {"boxes": {"hit": {"x": 12, "y": 8, "w": 4, "h": 4}}},
+                "labels": ["character", "enemy"]}"##,
+        )
+        .unwrap();
+
+        let merged = knight.inherit(&base);
+        assert_eq!(merged.name, "knight");
+        assert_eq!(merged.extends, None);
+        assert_eq!(merged.size, Some([16, 24]));
+        match &merged.palette {
+            PaletteRef::Inline(colors) => {
+                assert_eq!(colors.len(), 3);
+                assert_eq!(colors["skin"], "#FC9");
+                assert_eq!(colors["eye"], "#00F");
+            }
+            _ => panic!("Expected inline palette"),
+        }
+        let regions = merged.regions.as_ref().unwrap();
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions["head"].rect, Some([5, 0, 6, 3]));
+        let layers: Vec<_> =
+            merged.layers.iter().flatten().map(|l| (l.name.as_str(), l.z)).collect();
+        assert_eq!(layers, [("back", None), ("front", Some(5)), ("cape", None)]);
+        let metadata = merged.metadata.as_ref().unwrap();
+        assert_eq!(metadata.origin, Some([8, 24]));
+        assert_eq!(metadata.boxes.as_ref().unwrap().len(), 2);
+        assert_eq!(merged.labels, ["character", "enemy"]);
+
+        // A named palette replaces the template's outright
+        let named = Sprite { palette: PaletteRef::Named("armor".to_string()), ..knight.clone() };
+        assert_eq!(named.inherit(&base).palette, PaletteRef::Named("armor".to_string()));
+    }
+
     #[test]
     fn test_variant_parse_basic() {
         // Variant with single color override
@@ -618,6 +820,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_variant_parse_region_patches() {
+        let json = r##"{"type": "variant", "name": "hero_bald", "base": "hero", "regions": {"hair": null, "hat": {"rect": [0, 0, 4, 1]}}}"##;
+        let obj: TtpObject = serde_json::from_str(json).unwrap();
+        match obj {
+            TtpObject::Variant(variant) => {
+                assert!(variant.palette.is_empty(), "palette is optional");
+                assert_eq!(variant.regions.len(), 2);
+                assert_eq!(variant.regions.get("hair"), Some(&None));
+                assert!(matches!(variant.regions.get("hat"), Some(Some(_))));
+            }
+            _ => panic!("Expected variant"),
+        }
+    }
+
     #[test]
     fn test_variant_roundtrip() {
         let variant = Variant {
@@ -789,6 +1006,7 @@ mod tests {
             ])),
             attach_in: None,
             attach_out: None,
+            slices: None,
         };
         let json = serde_json::to_string(&metadata).unwrap();
         let parsed: SpriteMetadata = serde_json::from_str(&json).unwrap();
@@ -1277,6 +1495,7 @@ mod tests {
             boxes: None,
             attach_in: Some([4, 0]),
             attach_out: Some([4, 8]),
+            slices: None,
         };
         let json = serde_json::to_string(&metadata).unwrap();
         let parsed: SpriteMetadata = serde_json::from_str(&json).unwrap();
@@ -2371,4 +2590,24 @@ mod tests {
             _ => panic!("Expected import"),
         }
     }
+
+    #[test]
+    fn test_frame_tag_range() {
+        let tag = FrameTag { start: 1, end: 3, r#loop: None, fps: None };
+        assert_eq!(tag.range(5), Some(1..=3));
+        assert_eq!(tag.range(3), Some(1..=2));
+        assert_eq!(tag.range(1), None);
+
+        let inverted = FrameTag { start: 3, end: 1, r#loop: None, fps: None };
+        assert_eq!(inverted.range(5), None);
+
+        let anim = Animation {
+            name: "hero".to_string(),
+            tags: Some(HashMap::from([("walk".to_string(), tag), ("idle".to_string(), inverted)])),
+            ..Default::default()
+        };
+        assert_eq!(anim.tag("walk").map(|t| t.start), Some(1));
+        assert!(anim.tag("attack").is_none());
+        assert_eq!(anim.tag_names(), vec!["idle", "walk"]);
+    }
 }
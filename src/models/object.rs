@@ -7,11 +7,15 @@ use super::composition::Composition;
 use super::import::Import;
 use super::palette::Palette;
 use super::particle::Particle;
+use super::random_set::RandomSet;
+use super::sequence::Sequence;
 use super::sprite::Sprite;
+use super::stamp::Stamp;
+use super::theme::Theme;
 use super::transform::TransformDef;
 use super::variant::Variant;
 
-/// A Pixelsrc object - Palette, Sprite, Variant, Composition, Animation, Particle, Transform, Import, or StateRules.
+/// A Pixelsrc object - Palette, Sprite, Variant, Composition, Animation, Sequence, Particle, Transform, Import, StateRules, Theme, Stamp, or RandomSet.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum TtpObject {
@@ -20,10 +24,66 @@ pub enum TtpObject {
     Variant(Variant),
     Composition(Composition),
     Animation(Animation),
+    Sequence(Sequence),
     Particle(Particle),
     Transform(TransformDef),
     Import(Import),
     StateRules(crate::state::StateRules),
+    Theme(Theme),
+    Stamp(Stamp),
+    #[serde(rename = "random_set", alias = "random-set")]
+    RandomSet(RandomSet),
+}
+
+impl TtpObject {
+    /// The object's `"type"` field, e.g. `"sprite"` or `"state-rules"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TtpObject::Palette(_) => "palette",
+            TtpObject::Sprite(_) => "sprite",
+            TtpObject::Variant(_) => "variant",
+            TtpObject::Composition(_) => "composition",
+            TtpObject::Animation(_) => "animation",
+            TtpObject::Sequence(_) => "sequence",
+            TtpObject::Particle(_) => "particle",
+            TtpObject::Transform(_) => "transform",
+            TtpObject::Import(_) => "import",
+            TtpObject::StateRules(_) => "state-rules",
+            TtpObject::Theme(_) => "theme",
+            TtpObject::Stamp(_) => "stamp",
+            TtpObject::RandomSet(_) => "random_set",
+        }
+    }
+
+    /// The object's name (an import's `from` path).
+    pub fn name(&self) -> &str {
+        match self {
+            TtpObject::Palette(p) => &p.name,
+            TtpObject::Sprite(s) => &s.name,
+            TtpObject::Variant(v) => &v.name,
+            TtpObject::Composition(c) => &c.name,
+            TtpObject::Animation(a) => &a.name,
+            TtpObject::Sequence(s) => &s.name,
+            TtpObject::Particle(p) => &p.name,
+            TtpObject::Transform(t) => &t.name,
+            TtpObject::Import(i) => &i.from,
+            TtpObject::StateRules(sr) => &sr.name,
+            TtpObject::Theme(t) => &t.name,
+            TtpObject::Stamp(s) => &s.name,
+            TtpObject::RandomSet(r) => &r.name,
+        }
+    }
+
+    /// The object's selection labels; only renderable objects carry them.
+    pub fn labels(&self) -> &[String] {
+        match self {
+            TtpObject::Sprite(s) => &s.labels,
+            TtpObject::Composition(c) => &c.labels,
+            TtpObject::Animation(a) => &a.labels,
+            TtpObject::Sequence(s) => &s.labels,
+            _ => &[],
+        }
+    }
 }
 
 /// A warning message from parsing/rendering.
@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::core::PlaybackDirection;
+use crate::ordered;
+
 /// Per-step color shift for ramp generation.
 ///
 /// All values are deltas applied per step. For example, `lightness: -15` means
@@ -137,16 +140,28 @@ pub struct Palette {
     #[serde(default)]
     pub name: String,
     #[serde(default)]
+    #[serde(serialize_with = "ordered::map")]
     pub colors: HashMap<String, String>,
     /// Color ramps for automatic generation of shadow/highlight variants
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub ramps: Option<HashMap<String, ColorRamp>>,
     /// Semantic roles for tokens (maps token to its role)
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub roles: Option<HashMap<String, Role>>,
     /// Semantic relationships between tokens
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub relationships: Option<HashMap<String, Relationship>>,
+    /// Alternative token names (maps alias to the token it stands for)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
+    pub aliases: Option<HashMap<String, String>>,
+    /// Old token names that still resolve but warn (maps old name to its replacement)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
+    pub deprecated: Option<HashMap<String, String>>,
 }
 
 /// Reference to a palette - either a named reference or inline definition.
@@ -175,6 +190,9 @@ pub struct PaletteCycle {
     /// Duration per cycle step in milliseconds (default: animation duration)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub duration: Option<u32>,
+    /// Rotation direction (default: forward)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub direction: Option<PlaybackDirection>,
 }
 
 impl PaletteCycle {
@@ -188,4 +206,37 @@ impl PaletteCycle {
     pub fn cycle_length(&self) -> usize {
         self.tokens.len()
     }
+
+    /// Returns the rotation direction (default: forward).
+    pub fn direction(&self) -> PlaybackDirection {
+        self.direction.unwrap_or_default()
+    }
+
+    /// Returns the number of steps before the cycle repeats.
+    ///
+    /// Equal to [`cycle_length`](Self::cycle_length) except for ping-pong
+    /// cycles, which rotate out and back again.
+    pub fn period(&self) -> usize {
+        self.direction().pass_length(self.cycle_length())
+    }
+
+    /// Returns the rotation offset applied at animation step `step`.
+    pub fn rotation_at(&self, step: usize) -> usize {
+        let len = self.cycle_length();
+        if len == 0 {
+            return 0;
+        }
+        let step = step % self.period();
+        match self.direction() {
+            PlaybackDirection::Forward => step,
+            PlaybackDirection::Reverse => (len - step) % len,
+            PlaybackDirection::Pingpong => {
+                if step < len {
+                    step
+                } else {
+                    2 * (len - 1) - step
+                }
+            }
+        }
+    }
 }
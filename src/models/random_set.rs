@@ -0,0 +1,101 @@
+//! Random set types: weighted alternatives expanded into concrete objects.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::region::RegionDef;
+use crate::ordered;
+
+/// Weighted alternatives that `pxl expand` turns into concrete sprites or
+/// compositions, for variety without authoring every permutation.
+///
+/// Each generated object starts from one of `choices`. Sprite choices then
+/// take one alternative per token of `regions`; composition choices have
+/// each sprite map entry that names another random set replaced by a pick
+/// from that set.
+///
+/// # Example
+/// ```json
+/// {
+///   "type": "random_set",
+///   "name": "rock",
+///   "choices": ["rock_round", {"name": "rock_flat", "weight": 2}],
+///   "regions": {
+///     "moss": [{"region": {"rect": [1, 0, 3, 1]}}, {"weight": 2}],
+///     "crack": [{"region": {"line": [[2, 1], [3, 3]]}}, {"region": {"points": [[1, 2]]}}]
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RandomSet {
+    pub name: String,
+    /// Sprites or compositions to start from
+    pub choices: Vec<RandomChoice>,
+    /// Alternatives per palette token, applied to sprite choices
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub regions: HashMap<String, Vec<RegionChoice>>,
+    /// Random seed (default: derived from the set name)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub seed: Option<u64>,
+}
+
+/// A weighted choice of a random set: a name, or a name with a weight.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum RandomChoice {
+    Name(String),
+    Weighted {
+        name: String,
+        /// Relative weight (default: 1)
+        #[serde(default = "default_weight")]
+        weight: f64,
+    },
+}
+
+impl RandomChoice {
+    /// Name of the sprite, composition or random set chosen.
+    pub fn name(&self) -> &str {
+        match self {
+            RandomChoice::Name(name) => name,
+            RandomChoice::Weighted { name, .. } => name,
+        }
+    }
+
+    /// Relative weight (1 for plain names).
+    pub fn weight(&self) -> f64 {
+        match self {
+            RandomChoice::Name(_) => 1.0,
+            RandomChoice::Weighted { weight, .. } => *weight,
+        }
+    }
+}
+
+impl From<&str> for RandomChoice {
+    fn from(name: &str) -> Self {
+        RandomChoice::Name(name.to_string())
+    }
+}
+
+/// One alternative for a token's region: a region, or none to leave the
+/// token out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RegionChoice {
+    /// Region drawn for the token; omitted to remove the token
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub region: Option<RegionDef>,
+    /// Relative weight (default: 1)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub weight: Option<f64>,
+}
+
+impl RegionChoice {
+    /// Relative weight (default: 1).
+    pub fn weight(&self) -> f64 {
+        self.weight.unwrap_or(1.0)
+    }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
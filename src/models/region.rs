@@ -1,9 +1,13 @@
 //! Region definitions for structured sprites.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use super::palette::Role;
 use crate::antialias::RegionAAOverride;
+use crate::ordered;
+use crate::shapes::{FillRule, OutlineCorners};
 
 /// Jitter specification for controlled randomness.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -63,10 +67,14 @@ pub struct RegionDef {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub polygon: Option<Vec<[u32; 2]>>,
 
-    /// SVG-lite path syntax (M, L, H, V, Z commands only)
+    /// SVG-lite path syntax (M, L, H, V, Q, C, A, Z commands)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub path: Option<String>,
 
+    /// Fill rule for path: "nonzero" (default) or "evenodd"
+    #[serde(skip_serializing_if = "Option::is_none", default, rename = "fill-rule")]
+    pub fill_rule: Option<FillRule>,
+
     /// Flood fill inside a boundary: "inside(token_name)"
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub fill: Option<String>,
@@ -88,6 +96,10 @@ pub struct RegionDef {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub intersect: Option<Vec<RegionDef>>,
 
+    /// Keep pixels covered by an odd number of shapes
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub xor: Option<Vec<RegionDef>>,
+
     // Pixel-affecting modifiers (require forward definition)
     /// Subtract these tokens' pixels
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -97,6 +109,16 @@ pub struct RegionDef {
     #[serde(skip_serializing_if = "Option::is_none", default, rename = "auto-outline")]
     pub auto_outline: Option<String>,
 
+    /// Corner style for auto-outline: "square" (default) or "rounded"
+    #[serde(skip_serializing_if = "Option::is_none", default, rename = "outline-corners")]
+    pub outline_corners: Option<OutlineCorners>,
+
+    /// Selective auto-outline colors: palette token to use next to each
+    /// neighboring token or role, where `*` stands for the neighbor's token
+    #[serde(skip_serializing_if = "Option::is_none", default, rename = "outline-colors")]
+    #[serde(serialize_with = "ordered::option_map")]
+    pub outline_colors: Option<HashMap<String, String>>,
+
     /// Generate shadow from token
     #[serde(skip_serializing_if = "Option::is_none", default, rename = "auto-shadow")]
     pub auto_shadow: Option<String>,
@@ -128,6 +150,14 @@ pub struct RegionDef {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub symmetric: Option<String>,
 
+    /// Grow the region by N pixels (morphological dilate)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expand: Option<u32>,
+
+    /// Shrink the region by N pixels (morphological erode)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub contract: Option<u32>,
+
     /// Explicit render order (default: definition order)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub z: Option<i32>,
@@ -170,6 +200,14 @@ pub struct RegionDef {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub role: Option<Role>,
 
+    /// Surface height 0.0-1.0 for normal map generation (default: from role)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub height: Option<f32>,
+
+    /// Whether this region glows (included in the emissive map)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub emissive: Option<bool>,
+
     // Antialiasing override
     /// Per-region antialiasing configuration (overrides sprite/atlas/defaults)
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -0,0 +1,62 @@
+//! Sequence type for chaining animations.
+
+use serde::{Deserialize, Serialize};
+
+use super::core::Duration;
+
+/// One step of a [`Sequence`]: an animation played one or more times.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SequenceStep {
+    /// Animation to play
+    pub animation: String,
+    /// Number of times to play the animation (default: 1)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub repeat: Option<u32>,
+    /// Crossfade from the previous step into this one
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub crossfade: Option<Duration>,
+}
+
+impl SequenceStep {
+    /// Returns the number of times to play the animation (default: 1).
+    pub fn repeat_count(&self) -> u32 {
+        self.repeat.unwrap_or(1)
+    }
+}
+
+/// A sequence chains animations into a single renderable animation.
+///
+/// Useful for trailers and preview reels: idle twice, then attack, then idle,
+/// with optional crossfades between steps.
+///
+/// # Example
+/// ```json
+/// {
+///   "type": "sequence",
+///   "name": "reel",
+///   "steps": [
+///     { "animation": "idle", "repeat": 2 },
+///     { "animation": "attack", "crossfade": 100 },
+///     { "animation": "idle" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Sequence {
+    pub name: String,
+    /// Steps in playback order
+    pub steps: Vec<SequenceStep>,
+    /// Whether the whole sequence loops (default: true)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub r#loop: Option<bool>,
+    /// Selection labels for `--only label=...` and `--exclude label=...`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub labels: Vec<String>,
+}
+
+impl Sequence {
+    /// Returns whether the sequence should loop (default: true).
+    pub fn loops(&self) -> bool {
+        self.r#loop.unwrap_or(true)
+    }
+}
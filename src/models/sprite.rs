@@ -3,10 +3,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::core::Duration;
+use super::locale::LocaleOverride;
 use super::palette::PaletteRef;
 use super::region::RegionDef;
+use super::stamp::StampPlacement;
 use super::transform::TransformSpec;
 use crate::antialias::AntialiasConfig;
+use crate::ordered;
 
 /// Nine-slice region definition for scalable sprites.
 ///
@@ -42,7 +46,8 @@ pub struct CollisionBox {
 
 /// Sprite metadata for game engine integration.
 ///
-/// Contains origin point, collision boxes, and attachment points for sprites.
+/// Contains origin point, collision boxes, attachment points and named slices
+/// for sprites.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct SpriteMetadata {
     /// Sprite origin point `[x, y]` - used for positioning and rotation
@@ -50,6 +55,7 @@ pub struct SpriteMetadata {
     pub origin: Option<[i32; 2]>,
     /// Collision boxes (hit, hurt, collide, trigger, etc.)
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub boxes: Option<HashMap<String, CollisionBox>>,
     /// Where this sprite connects to parent/previous segment in a chain `[x, y]`
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -57,6 +63,10 @@ pub struct SpriteMetadata {
     /// Where the next segment attaches to this sprite `[x, y]`
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub attach_out: Option<[i32; 2]>,
+    /// Named sub-rectangles exported as images of their own (UI kit states)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
+    pub slices: Option<HashMap<String, CollisionBox>>,
 }
 
 /// Per-frame metadata for animations.
@@ -67,7 +77,11 @@ pub struct FrameMetadata {
     /// Per-frame collision boxes (can override or nullify sprite-level boxes)
     /// Use `null` value to disable a box for this frame
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub boxes: Option<HashMap<String, Option<CollisionBox>>>,
+    /// How long this frame is shown (overrides `durations` and `duration`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub duration: Option<Duration>,
 }
 
 /// A frame tag for game engine integration - identifies named ranges of frames.
@@ -85,25 +99,104 @@ pub struct FrameTag {
     pub fps: Option<u32>,
 }
 
+impl FrameTag {
+    /// Returns the tag's frame indices for an animation with `frame_count` frames.
+    ///
+    /// Returns `None` if the range is inverted or starts past the last frame.
+    /// An end past the last frame is clamped.
+    pub fn range(&self, frame_count: usize) -> Option<std::ops::RangeInclusive<usize>> {
+        let start = self.start as usize;
+        if self.start > self.end || start >= frame_count {
+            return None;
+        }
+        Some(start..=(self.end as usize).min(frame_count - 1))
+    }
+}
+
+/// A named layer of regions inside a sprite.
+///
+/// Layers render back-to-front by `z` (ties keep declaration order), on top of
+/// the sprite's own `regions`, and can be exported as separate images.
+///
+/// Example:
+/// ```json5
+/// layers: [
+///   { name: "body", regions: { skin: { rect: [4, 2, 8, 12] } } },
+///   { name: "weapon", z: 10, regions: { blade: { line: [[12, 2], [12, 10]] } } }
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SpriteLayer {
+    /// Layer name, used for export file names
+    pub name: String,
+    /// Stacking order (higher = on top, default: 0)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub z: Option<i32>,
+    /// Structured regions drawn on this layer
+    #[serde(default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub regions: HashMap<String, RegionDef>,
+    /// Layer opacity from 0.0 (transparent) to 1.0 (opaque). Default: 1.0
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub opacity: Option<f64>,
+}
+
+fn default_reference_opacity() -> f64 {
+    0.3
+}
+
+/// A reference image drawn under a sprite in previews, for tracing.
+///
+/// Previews (`pxl show`, `pxl serve`, editor hovers) stretch the image to the
+/// sprite's size and draw it beneath the pixels. Renders and exports ignore it.
+///
+/// Example: `"reference": {"path": "concept/hero.png", "opacity": 0.3}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpriteReference {
+    /// Image path, relative to the file that defines the sprite
+    pub path: String,
+    /// Underlay opacity (0.0-1.0, default: 0.3)
+    #[serde(default = "default_reference_opacity")]
+    pub opacity: f64,
+}
+
 /// A sprite definition.
 ///
 /// A sprite uses `regions` for structured rendering, or can reference another sprite via `source`
 /// with optional transforms applied. The `regions` and `source` fields are mutually exclusive.
+///
+/// With `extends`, a sprite starts from a template sprite and overrides or
+/// merges its fields (see [`Sprite::inherit`]).
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Sprite {
     pub name: String,
+    /// Template sprite to inherit size, palette, regions and metadata from
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub extends: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub size: Option<[u32; 2]>,
+    /// Palette reference (may be omitted when `extends` supplies one)
+    #[serde(default)]
     pub palette: PaletteRef,
     /// Reference to another sprite by name (mutually exclusive with `regions`)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub source: Option<String>,
     /// Structured regions for rendering
     #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde(serialize_with = "ordered::option_map")]
     pub regions: Option<HashMap<String, RegionDef>>,
+    /// Named, z-ordered layers rendered over `regions`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub layers: Option<Vec<SpriteLayer>>,
+    /// Stamps drawn into `regions`, in order
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stamps: Option<Vec<StampPlacement>>,
     /// Transforms to apply when resolving this sprite
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub transform: Option<Vec<TransformSpec>>,
+    /// Opacity from 0.0 (transparent) to 1.0 (opaque), applied when rendering. Default: 1.0
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub opacity: Option<f64>,
     /// Sprite metadata for game engine integration (origin, collision boxes)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub metadata: Option<SpriteMetadata>,
@@ -113,4 +206,103 @@ pub struct Sprite {
     /// Per-sprite antialiasing configuration (overrides atlas/defaults)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub antialias: Option<AntialiasConfig>,
+    /// Per-locale substitutions, selected with `--locale`
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub locale: HashMap<String, LocaleOverride>,
+    /// Preview-only reference image drawn under the sprite
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reference: Option<SpriteReference>,
+    /// Selection labels for `--only label=...` and `--exclude label=...`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub labels: Vec<String>,
+}
+
+impl Sprite {
+    /// Whether `palette` was left unset (an empty name), as a sprite that
+    /// `extends` a template may do.
+    pub fn palette_unset(&self) -> bool {
+        matches!(&self.palette, PaletteRef::Named(name) if name.is_empty())
+    }
+
+    /// This sprite with the fields it leaves unset taken from `base`.
+    ///
+    /// Single-valued fields (`size`, `source`, `stamps`, `transform`, `opacity`,
+    /// `nine_slice`, `antialias`, `reference`) and metadata points override
+    /// the base's. Maps merge, with this sprite's entries replacing the
+    /// base's: `regions`, collision `boxes` and `slices` by name, `layers` by
+    /// layer name (in the base's order, new layers last), `locale` by locale,
+    /// and an inline `palette` over an inline base palette by token. A named
+    /// palette replaces the base's outright. `labels` are combined.
+    pub fn inherit(&self, base: &Sprite) -> Sprite {
+        let palette = match (&self.palette, &base.palette) {
+            _ if self.palette_unset() => base.palette.clone(),
+            (PaletteRef::Inline(own), PaletteRef::Inline(inherited)) => {
+                PaletteRef::Inline(merge_maps(Some(inherited), Some(own)).unwrap_or_default())
+            }
+            (own, _) => own.clone(),
+        };
+
+        let layers = match (&base.layers, &self.layers) {
+            (Some(inherited), Some(own)) => {
+                let mut layers = inherited.clone();
+                for layer in own {
+                    match layers.iter_mut().find(|l| l.name == layer.name) {
+                        Some(existing) => *existing = layer.clone(),
+                        None => layers.push(layer.clone()),
+                    }
+                }
+                Some(layers)
+            }
+            (inherited, own) => own.clone().or_else(|| inherited.clone()),
+        };
+
+        let metadata = match (&base.metadata, &self.metadata) {
+            (Some(inherited), Some(own)) => Some(SpriteMetadata {
+                origin: own.origin.or(inherited.origin),
+                boxes: merge_maps(inherited.boxes.as_ref(), own.boxes.as_ref()),
+                attach_in: own.attach_in.or(inherited.attach_in),
+                attach_out: own.attach_out.or(inherited.attach_out),
+                slices: merge_maps(inherited.slices.as_ref(), own.slices.as_ref()),
+            }),
+            (inherited, own) => own.clone().or_else(|| inherited.clone()),
+        };
+
+        let mut labels = base.labels.clone();
+        labels.extend(self.labels.iter().filter(|l| !base.labels.contains(l)).cloned());
+
+        Sprite {
+            name: self.name.clone(),
+            extends: None,
+            size: self.size.or(base.size),
+            palette,
+            source: self.source.clone().or_else(|| base.source.clone()),
+            regions: merge_maps(base.regions.as_ref(), self.regions.as_ref()),
+            layers,
+            stamps: self.stamps.clone().or_else(|| base.stamps.clone()),
+            transform: self.transform.clone().or_else(|| base.transform.clone()),
+            opacity: self.opacity.or(base.opacity),
+            metadata,
+            nine_slice: self.nine_slice.clone().or_else(|| base.nine_slice.clone()),
+            antialias: self.antialias.clone().or_else(|| base.antialias.clone()),
+            locale: merge_maps(Some(&base.locale), Some(&self.locale)).unwrap_or_default(),
+            reference: self.reference.clone().or_else(|| base.reference.clone()),
+            labels,
+        }
+    }
+}
+
+/// `base` with `own`'s entries added or replacing; `None` if both are.
+fn merge_maps<V: Clone>(
+    base: Option<&HashMap<String, V>>,
+    own: Option<&HashMap<String, V>>,
+) -> Option<HashMap<String, V>> {
+    match (base, own) {
+        (None, None) => None,
+        (base, own) => {
+            let mut merged = base.cloned().unwrap_or_default();
+            merged.extend(own.into_iter().flatten().map(|(k, v)| (k.clone(), v.clone())));
+            Some(merged)
+        }
+    }
 }
@@ -0,0 +1,79 @@
+//! Stamp types: small pixel patterns placed repeatedly inside sprites.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::composition::{split_map_row, Flip};
+use crate::ordered;
+
+/// A reusable pixel pattern, drawn into sprites with `stamps`.
+///
+/// Each grid character is one pixel. `.` and spaces are empty; any other
+/// character is the palette token of the same name unless `tokens` maps it
+/// to another one.
+///
+/// # Example
+/// ```json
+/// {
+///   "type": "stamp",
+///   "name": "leaf",
+///   "grid": [".g.", "gdg", ".s."],
+///   "tokens": { "g": "leaf_light", "d": "leaf_dark", "s": "stem" }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Stamp {
+    pub name: String,
+    /// Pattern rows, one character per pixel
+    pub grid: Vec<String>,
+    /// Palette token for a grid character (default: the character itself)
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub tokens: HashMap<String, String>,
+}
+
+impl Stamp {
+    /// Width and height of the grid; rows may be ragged.
+    pub fn size(&self) -> [u32; 2] {
+        let width = self.grid.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        [width as u32, self.grid.len() as u32]
+    }
+
+    /// The filled cells of the grid as `(x, y, token)`.
+    pub fn pixels(&self) -> Vec<(u32, u32, &str)> {
+        let mut pixels = Vec::new();
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in split_map_row(row, None).into_iter().enumerate() {
+                if cell == "." || cell == " " {
+                    continue;
+                }
+                let token = self.tokens.get(cell).map_or(cell, String::as_str);
+                pixels.push((x as u32, y as u32, token));
+            }
+        }
+        pixels
+    }
+}
+
+/// A stamp drawn at a position in a sprite.
+///
+/// `flip` mirrors the pattern, then `rotate` turns it clockwise by 90, 180
+/// or 270 degrees; `at` is the top-left corner of the result.
+///
+/// # Example
+/// ```json
+/// { "name": "leaf", "at": [3, 5], "flip": "h", "rotate": 90 }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StampPlacement {
+    /// Stamp name
+    pub name: String,
+    /// Top-left corner `[x, y]`, may be negative to clip the stamp
+    pub at: [i32; 2],
+    /// Mirror the stamp before placing it
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub flip: Option<Flip>,
+    /// Clockwise rotation in degrees: 0, 90, 180 or 270
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rotate: Option<u32>,
+}
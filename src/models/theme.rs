@@ -0,0 +1,28 @@
+//! Theme type for project-wide palette swaps.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::ordered;
+
+/// A theme swaps palettes project-wide.
+///
+/// Rendering with a theme resolves every reference to a mapped palette
+/// against its replacement, so a whole scene can switch from day to night
+/// without defining per-sprite variants.
+///
+/// # Example
+/// ```json
+/// {
+///   "type": "theme",
+///   "name": "night",
+///   "palettes": { "forest_day": "forest_night", "hero": "hero_night" }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    /// Palette name to replacement palette name (`@name` for built-ins)
+    #[serde(serialize_with = "ordered::map")]
+    pub palettes: HashMap<String, String>,
+}
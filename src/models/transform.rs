@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::ordered;
+
 /// Transform specification - can be string or object in JSON.
 ///
 /// Supports both simple string syntax (`"mirror-h"`, `"rotate:90"`) and
@@ -110,6 +112,7 @@ pub struct Keyframe {
     pub frame: u32,
     /// Property values at this keyframe (property name -> value)
     #[serde(flatten)]
+    #[serde(serialize_with = "ordered::map")]
     pub values: HashMap<String, f64>,
 }
 
@@ -1,21 +1,39 @@
-//! Variant type for palette-only sprite modifications.
+//! Variant type for palette and region modifications of a sprite.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::locale::LocaleOverride;
+use super::region::RegionDef;
 use super::transform::TransformSpec;
+use crate::ordered;
 
-/// A variant is a palette-only modification of a base sprite.
+/// A variant is a modification of a base sprite.
 ///
-/// Variants allow creating color variations of sprites without duplicating
-/// the region data. The variant copies the base sprite's regions and applies
-/// palette overrides.
+/// Variants allow creating variations of sprites without duplicating the
+/// region data. The variant copies the base sprite's regions, applies palette
+/// overrides and patches individual regions. The base may itself be a
+/// variant, so patches can build on each other.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Variant {
     pub name: String,
     pub base: String,
+    #[serde(default)]
+    #[serde(serialize_with = "ordered::map")]
     pub palette: HashMap<String, String>,
+    /// Region patches: each replaces or adds the base region for its token,
+    /// and `null` removes it
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub regions: HashMap<String, Option<RegionDef>>,
     /// Transforms to apply when resolving this variant
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub transform: Option<Vec<TransformSpec>>,
+    /// Opacity from 0.0 to 1.0, replacing the base sprite's when set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub opacity: Option<f64>,
+    /// Per-locale substitutions, selected with `--locale`
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    #[serde(serialize_with = "ordered::map")]
+    pub locale: HashMap<String, LocaleOverride>,
 }
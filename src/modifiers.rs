@@ -6,6 +6,8 @@
 use std::collections::HashSet;
 use std::ops::Range;
 
+use crate::rng::Rng;
+
 /// Axis specification for symmetric operations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SymmetryAxis {
@@ -187,22 +189,41 @@ pub fn apply_repeat(pixels: &HashSet<(i32, i32)>, count: i32, spacing: i32) -> H
 /// // Result will have one pixel near (10, 10), within ±5 in x and y
 /// ```
 pub fn apply_jitter(pixels: &HashSet<(i32, i32)>, jitter: i32, seed: u64) -> HashSet<(i32, i32)> {
-    let mut result = HashSet::new();
-
-    for &(x, y) in pixels {
-        let mut local_seed = seed.wrapping_add(x as u64).wrapping_add(y as u64);
-        let dx = pseudo_random(&mut local_seed) % (2 * jitter + 1) - jitter;
-        let dy = pseudo_random(&mut local_seed) % (2 * jitter + 1) - jitter;
-        result.insert((x + dx, y + dy));
-    }
-
-    result
+    apply_jitter_range(pixels, [-jitter, jitter], [-jitter, jitter], seed)
 }
 
-/// Simple deterministic pseudo-random number generator for jitter.
-fn pseudo_random(seed: &mut u64) -> i32 {
-    *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
-    (*seed % 2147483648) as i32
+/// Apply jitter with independent horizontal and vertical ranges.
+///
+/// Each pixel is moved by a `dx` in `x_range` and a `dy` in `y_range`
+/// (both inclusive). Every pixel draws from its own stream keyed by its
+/// coordinates, so the result does not depend on set iteration order.
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::modifiers::apply_jitter_range;
+/// use std::collections::HashSet;
+///
+/// let pixels: HashSet<(i32, i32)> = [(4, 15), (8, 15)].into_iter().collect();
+/// let result = apply_jitter_range(&pixels, [0, 0], [-2, 0], 42);
+/// assert!(result.iter().all(|&(_, y)| (13..=15).contains(&y)));
+/// ```
+pub fn apply_jitter_range(
+    pixels: &HashSet<(i32, i32)>,
+    x_range: [i32; 2],
+    y_range: [i32; 2],
+    seed: u64,
+) -> HashSet<(i32, i32)> {
+    pixels
+        .iter()
+        .map(|&(x, y)| {
+            let key = ((x as u32 as u64) << 32) | y as u32 as u64;
+            let mut rng = Rng::new(seed ^ key);
+            let dx = rng.range_i32(x_range[0], x_range[1]);
+            let dy = rng.range_i32(y_range[0], y_range[1]);
+            (x + dx, y + dy)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -493,6 +493,20 @@ pub fn generate_motion_frames(
     frames
 }
 
+/// Place a fractional coordinate on the pixel grid for one frame of motion.
+///
+/// Rounds down or up so that, across consecutive frames, the share of frames
+/// rounded up matches the fractional part: a sprite held at `x = 3.25` sits
+/// at 4 about one frame in four. Per-frame thresholds follow the golden-ratio
+/// sequence, which spreads the rounded-up frames evenly for any fraction.
+/// Frame 0 rounds to nearest.
+pub fn temporal_dither(value: f64, frame: usize) -> i32 {
+    const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+    let threshold = (0.5 + frame as f64 * GOLDEN_RATIO_CONJUGATE).fract();
+    let floor = value.floor();
+    floor as i32 + i32::from(value - floor >= threshold)
+}
+
 /// Parse interpolation mode from string
 pub fn parse_interpolation(s: &str) -> Option<Interpolation> {
     match s.to_lowercase().as_str() {
@@ -1546,4 +1560,22 @@ mod tests {
         assert_eq!(frame_at_half, 2);
         assert_eq!(frame_at_end, 3);
     }
+
+    #[test]
+    fn test_temporal_dither() {
+        // Whole pixels never move
+        assert!((0..50).all(|frame| temporal_dither(3.0, frame) == 3));
+        assert!((0..50).all(|frame| temporal_dither(-2.0, frame) == -2));
+
+        // The share of frames rounded up follows the fraction
+        for (value, expected) in [(3.25_f64, 25), (3.5, 50), (-1.75, 25)] {
+            let ceil = value.floor() as i32 + 1;
+            let up = (0..100).filter(|&frame| temporal_dither(value, frame) == ceil).count();
+            assert!(up.abs_diff(expected) <= 2, "{} rounded up {} times", value, up);
+        }
+
+        // Frame 0 rounds to nearest
+        assert_eq!(temporal_dither(0.4, 0), 0);
+        assert_eq!(temporal_dither(0.6, 0), 1);
+    }
 }
@@ -0,0 +1,208 @@
+//! Motion presets: bounce, shake, float and squash-and-stretch
+//!
+//! An animation with a `motion` field has its CSS keyframes generated from a
+//! parameterized curve instead of authored by hand:
+//!
+//! ```json
+//! {"type": "animation", "name": "hop", "motion": {"preset": "bounce", "sprite": "ball", "amplitude": 6}, "duration": "600ms"}
+//! ```
+//!
+//! Each cycle is sampled into evenly spaced keyframes, which playback then
+//! interpolates like any other keyframe animation.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use crate::models::{Animation, CssKeyframe, MotionPreset, MotionPresetKind};
+
+/// Keyframes generated per cycle for smooth presets.
+pub const SAMPLES_PER_CYCLE: u32 = 16;
+
+/// Keyframes generated per cycle for shake (rest, right, rest, left).
+pub const SHAKE_SAMPLES_PER_CYCLE: u32 = 4;
+
+/// Check a preset's parameters.
+pub fn validate_preset(motion: &MotionPreset) -> Result<(), String> {
+    let amplitude = motion.amplitude();
+    if !amplitude.is_finite() || amplitude < 0.0 {
+        return Err(format!("motion amplitude must be a non-negative number, got {}", amplitude));
+    }
+    if motion.preset == MotionPresetKind::SquashStretch && amplitude >= 1.0 {
+        return Err(format!(
+            "squash_stretch amplitude is a scale change and must be below 1.0, got {}",
+            amplitude
+        ));
+    }
+    if motion.cycles() == 0 {
+        return Err("motion cycles must be at least 1".to_string());
+    }
+    let decay = motion.decay();
+    if !(0.0..=1.0).contains(&decay) {
+        return Err(format!("motion decay must be between 0.0 and 1.0, got {}", decay));
+    }
+    Ok(())
+}
+
+/// Generate CSS keyframes for a preset.
+///
+/// `size` is the sprite's size, used to keep squash_stretch anchored at the
+/// bottom center; without it the sprite scales from its top-left corner.
+pub fn preset_keyframes(
+    motion: &MotionPreset,
+    size: Option<[u32; 2]>,
+) -> Result<HashMap<String, CssKeyframe>, String> {
+    validate_preset(motion)?;
+
+    let per_cycle = match motion.preset {
+        MotionPresetKind::Shake => SHAKE_SAMPLES_PER_CYCLE,
+        _ => SAMPLES_PER_CYCLE,
+    };
+    let total = motion.cycles() * per_cycle;
+
+    let mut keyframes = HashMap::new();
+    for i in 0..=total {
+        // The last keyframe closes the final cycle
+        let cycle = (i / per_cycle).min(motion.cycles() - 1);
+        let phase = (i - cycle * per_cycle) as f64 / per_cycle as f64;
+        let strength = motion.amplitude() * motion.decay().powi(cycle as i32);
+
+        let wave = (TAU * phase).sin();
+        let mut keyframe =
+            CssKeyframe { sprite: Some(motion.sprite.clone()), ..Default::default() };
+        match motion.preset {
+            MotionPresetKind::Bounce => {
+                let height = strength * 4.0 * phase * (1.0 - phase);
+                keyframe.offset = Some([0, -height.round() as i32]);
+            }
+            MotionPresetKind::Float => {
+                keyframe.offset = Some([0, -(strength * wave).round() as i32]);
+            }
+            MotionPresetKind::Shake => {
+                keyframe.offset = Some([(strength * wave).round() as i32, 0]);
+            }
+            MotionPresetKind::SquashStretch => {
+                // Stretch tall first, then squash; width keeps the area constant
+                let sy = 1.0 + strength * wave;
+                let sx = 1.0 / sy;
+                keyframe.transform = Some(format!("scale({:.3}, {:.3})", sx, sy));
+                if let Some([w, h]) = size {
+                    keyframe.offset = Some([
+                        (w as f64 * (1.0 - sx) / 2.0).round() as i32,
+                        (h as f64 * (1.0 - sy)).round() as i32,
+                    ]);
+                }
+            }
+        }
+
+        let percent = (i as f64 / total as f64 * 100_000.0).round() / 1000.0;
+        keyframes.insert(format!("{}%", percent), keyframe);
+    }
+    Ok(keyframes)
+}
+
+/// Return a copy of the animation with its motion preset expanded into
+/// `keyframes`, or `None` if it has no preset.
+///
+/// `size` is the preset sprite's size, as for [`preset_keyframes`].
+pub fn expand_motion(
+    animation: &Animation,
+    size: Option<[u32; 2]>,
+) -> Option<Result<Animation, String>> {
+    let motion = animation.motion.as_ref()?;
+    Some(preset_keyframes(motion, size).map(|keyframes| Animation {
+        frames: Vec::new(),
+        keyframes: Some(keyframes),
+        motion: None,
+        ..animation.clone()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(kind: MotionPresetKind) -> MotionPreset {
+        MotionPreset {
+            preset: kind,
+            sprite: "ball".to_string(),
+            amplitude: None,
+            cycles: None,
+            decay: None,
+        }
+    }
+
+    fn offsets(keyframes: &HashMap<String, CssKeyframe>) -> Vec<[i32; 2]> {
+        let animation = Animation { keyframes: Some(keyframes.clone()), ..Default::default() };
+        animation.sorted_keyframes().iter().map(|(_, kf)| kf.offset.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_bounce_keyframes() {
+        let bounce = MotionPreset {
+            amplitude: Some(8.0),
+            cycles: Some(2),
+            decay: Some(0.5),
+            ..preset(MotionPresetKind::Bounce)
+        };
+        let keyframes = preset_keyframes(&bounce, None).unwrap();
+        assert_eq!(keyframes.len(), 33);
+        assert!(keyframes.values().all(|kf| kf.sprite.as_deref() == Some("ball")));
+
+        let ys: Vec<i32> = offsets(&keyframes).iter().map(|o| o[1]).collect();
+        assert_eq!((ys[0], ys[8], ys[16], ys[24], ys[32]), (0, -8, 0, -4, 0));
+        assert!(ys.iter().all(|&y| y <= 0), "bounce stays above the ground");
+    }
+
+    #[test]
+    fn test_shake_and_float_keyframes() {
+        let shake = preset_keyframes(&preset(MotionPresetKind::Shake), None).unwrap();
+        let xs: Vec<i32> = offsets(&shake).iter().map(|o| o[0]).collect();
+        assert_eq!(xs, vec![0, 1, 0, -1, 0, 1, 0, -1, 0, 1, 0, -1, 0]);
+
+        let float = preset_keyframes(&preset(MotionPresetKind::Float), None).unwrap();
+        let ys: Vec<i32> = offsets(&float).iter().map(|o| o[1]).collect();
+        assert_eq!((ys[0], ys[4], ys[8], ys[12], ys[16]), (0, -2, 0, 2, 0));
+    }
+
+    #[test]
+    fn test_squash_stretch_keyframes() {
+        let squash =
+            preset_keyframes(&preset(MotionPresetKind::SquashStretch), Some([10, 10])).unwrap();
+        let animation = Animation { keyframes: Some(squash), ..Default::default() };
+        let sorted = animation.sorted_keyframes();
+
+        let (_, stretch) = sorted[4];
+        assert_eq!(stretch.transform.as_deref(), Some("scale(0.833, 1.200)"));
+        assert_eq!(stretch.offset, Some([1, -2]), "stays on the bottom center");
+        let (_, squashed) = sorted[12];
+        assert_eq!(squashed.transform.as_deref(), Some("scale(1.250, 0.800)"));
+        assert_eq!(squashed.offset, Some([-1, 2]));
+    }
+
+    #[test]
+    fn test_invalid_presets() {
+        let too_big =
+            MotionPreset { amplitude: Some(1.0), ..preset(MotionPresetKind::SquashStretch) };
+        assert!(validate_preset(&too_big).unwrap_err().contains("below 1.0"));
+        let no_cycles = MotionPreset { cycles: Some(0), ..preset(MotionPresetKind::Float) };
+        assert!(preset_keyframes(&no_cycles, None).is_err());
+        let bad_decay = MotionPreset { decay: Some(1.5), ..preset(MotionPresetKind::Bounce) };
+        assert!(validate_preset(&bad_decay).unwrap_err().contains("decay"));
+    }
+
+    #[test]
+    fn test_expand_motion() {
+        let animation = Animation {
+            name: "hop".to_string(),
+            frames: vec!["ignored".to_string()],
+            motion: Some(preset(MotionPresetKind::Bounce)),
+            ..Default::default()
+        };
+        let expanded = expand_motion(&animation, None).unwrap().unwrap();
+        assert!(expanded.is_css_keyframes());
+        assert!(expanded.frames.is_empty() && expanded.motion.is_none());
+        assert_eq!(expanded.name, "hop");
+
+        assert!(expand_motion(&Animation::default(), None).is_none());
+    }
+}
@@ -0,0 +1,482 @@
+//! OpenRaster (.ora) encoding and decoding for layered export and import
+//!
+//! An `.ora` file is a ZIP archive that Krita, GIMP and MyPaint open as an
+//! editable layered image. It holds an uncompressed `mimetype` entry first,
+//! a `stack.xml` describing the layers top-most first, one PNG per layer
+//! under `data/`, and a flattened `mergedimage.png` plus thumbnail for
+//! viewers that don't understand layers.
+//!
+//! Entries are written with the ZIP "stored" method, which every reader
+//! supports; PNG data is already compressed. Decoding also accepts
+//! deflated entries, as written by painting tools.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::DeflateDecoder;
+use regex::Regex;
+
+use image::{imageops, RgbaImage};
+
+use crate::composition::BlendMode;
+use crate::output::{encode_png, OutputError};
+
+/// Largest thumbnail width or height allowed by the OpenRaster spec
+const MAX_THUMBNAIL_SIZE: u32 = 256;
+
+/// Most bytes all entries of an archive may inflate to when decoding
+const MAX_ARCHIVE_SIZE: usize = 256 << 20;
+
+/// A layer of an OpenRaster image.
+#[derive(Debug, Clone)]
+pub struct OraLayer {
+    /// Layer name shown in the editor
+    pub name: String,
+    /// Layer pixels
+    pub image: RgbaImage,
+    /// Horizontal offset of the layer on the canvas
+    pub x: i32,
+    /// Vertical offset of the layer on the canvas
+    pub y: i32,
+    /// Blend mode, mapped to an OpenRaster `composite-op`
+    pub blend: BlendMode,
+    /// Layer opacity (0.0-1.0)
+    pub opacity: f64,
+}
+
+/// OpenRaster `composite-op` for a blend mode.
+///
+/// Returns `None` for modes OpenRaster cannot express (`subtract`); those
+/// layers are written as `svg:src-over`.
+pub fn composite_op(blend: BlendMode) -> Option<&'static str> {
+    match blend {
+        BlendMode::Normal => Some("svg:src-over"),
+        BlendMode::Multiply => Some("svg:multiply"),
+        BlendMode::Screen => Some("svg:screen"),
+        BlendMode::Overlay => Some("svg:overlay"),
+        BlendMode::Add => Some("svg:plus"),
+        BlendMode::Subtract => None,
+        BlendMode::Difference => Some("svg:difference"),
+        BlendMode::Darken => Some("svg:darken"),
+        BlendMode::Lighten => Some("svg:lighten"),
+    }
+}
+
+/// OpenRaster `composite-op` mapped back to a blend mode.
+///
+/// Unknown ops (`svg:hue`, `svg:color-dodge`, ...) return `None`.
+pub fn blend_for_composite_op(op: &str) -> Option<BlendMode> {
+    [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Add,
+        BlendMode::Difference,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+    ]
+    .into_iter()
+    .find(|&blend| composite_op(blend) == Some(op))
+}
+
+/// A decoded OpenRaster image.
+#[derive(Debug, Clone)]
+pub struct OraImage {
+    /// Canvas width
+    pub width: u32,
+    /// Canvas height
+    pub height: u32,
+    /// Visible layers, bottom first
+    pub layers: Vec<OraLayer>,
+    /// Problems found while decoding (unsupported blend modes, hidden layers, ...)
+    pub warnings: Vec<String>,
+}
+
+/// Decode an `.ora` archive.
+///
+/// Nested stacks are flattened into a single list of layers; their own
+/// offsets and opacity are not applied. Hidden layers are skipped. The
+/// canvas and every layer image must fit within `max_size`.
+pub fn decode_ora(bytes: &[u8], max_size: [u32; 2]) -> Result<OraImage, String> {
+    let mut entries = read_zip(bytes, MAX_ARCHIVE_SIZE)?;
+    let stack = entries.remove("stack.xml").ok_or("Missing stack.xml in OpenRaster file")?;
+    let stack = String::from_utf8(stack).map_err(|_| "stack.xml is not valid UTF-8")?;
+
+    let tag_pattern = Regex::new(r"<(image|layer)\b[^>]*>").unwrap();
+    let attr_pattern = Regex::new(r#"([\w:-]+)\s*=\s*"([^"]*)""#).unwrap();
+    let mut tags = tag_pattern.captures_iter(&stack).map(|tag| {
+        let attrs: HashMap<String, String> = attr_pattern
+            .captures_iter(&tag[0])
+            .map(|a| (a[1].to_string(), unescape_xml(&a[2])))
+            .collect();
+        (tag[1].to_string(), attrs)
+    });
+
+    let image_attrs = match tags.next() {
+        Some((kind, attrs)) if kind == "image" => attrs,
+        _ => return Err("Missing <image> in stack.xml".to_string()),
+    };
+    let dimension = |name: &str| {
+        image_attrs
+            .get(name)
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .ok_or(format!("Missing or invalid image '{}' in stack.xml", name))
+    };
+    let (width, height) = (dimension("w")?, dimension("h")?);
+    let [max_width, max_height] = max_size;
+    if width > max_width || height > max_height {
+        return Err(format!(
+            "OpenRaster canvas is {}x{}, larger than the {}x{} size limit",
+            width, height, max_width, max_height
+        ));
+    }
+
+    let mut layers = Vec::new();
+    let mut warnings = Vec::new();
+    for (_, attrs) in tags.filter(|(kind, _)| kind == "layer") {
+        let name =
+            attrs.get("name").cloned().unwrap_or_else(|| format!("layer_{}", layers.len() + 1));
+        if attrs.get("visibility").map(String::as_str) == Some("hidden") {
+            warnings.push(format!("Skipping hidden layer '{}'", name));
+            continue;
+        }
+        let src = attrs.get("src").ok_or(format!("Layer '{}' has no src", name))?;
+        let data = entries.get(src).ok_or(format!("Missing layer image '{}'", src))?;
+        let image = decode_layer_png(data, max_size)
+            .map_err(|e| format!("Failed to decode layer '{}': {}", name, e))?;
+
+        let op = attrs.get("composite-op").map(String::as_str).unwrap_or("svg:src-over");
+        let blend = blend_for_composite_op(op).unwrap_or_else(|| {
+            warnings
+                .push(format!("Layer '{}': unsupported composite-op '{}', using normal", name, op));
+            BlendMode::Normal
+        });
+        let number = |key: &str| attrs.get(key).and_then(|v| v.trim().parse::<f64>().ok());
+        layers.push(OraLayer {
+            image,
+            x: number("x").unwrap_or(0.0) as i32,
+            y: number("y").unwrap_or(0.0) as i32,
+            blend,
+            opacity: number("opacity").unwrap_or(1.0).clamp(0.0, 1.0),
+            name,
+        });
+    }
+
+    // stack.xml lists the top layer first
+    layers.reverse();
+    Ok(OraImage { width, height, layers, warnings })
+}
+
+/// Decode a layer image, refusing images larger than `max_size`.
+fn decode_layer_png(data: &[u8], max_size: [u32; 2]) -> image::ImageResult<RgbaImage> {
+    let mut limits = image::io::Limits::default();
+    limits.max_image_width = Some(max_size[0]);
+    limits.max_image_height = Some(max_size[1]);
+    let mut reader = image::io::Reader::new(std::io::Cursor::new(data)).with_guessed_format()?;
+    reader.limits(limits);
+    Ok(reader.decode()?.to_rgba8())
+}
+
+/// Encode layers (bottom first) and their flattened image as an `.ora` archive.
+pub fn encode_ora(layers: &[OraLayer], merged: &RgbaImage) -> Result<Vec<u8>, OutputError> {
+    let (width, height) = merged.dimensions();
+    let mut zip = ZipWriter::default();
+    zip.add("mimetype", b"image/openraster");
+
+    let mut stack = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<image version=\"0.0.5\" w=\"{}\" h=\"{}\">\n  <stack>\n",
+        width, height
+    );
+    for (index, layer) in layers.iter().enumerate().rev() {
+        stack.push_str(&format!(
+            "    <layer name=\"{}\" src=\"data/layer{}.png\" composite-op=\"{}\" opacity=\"{:.3}\" x=\"{}\" y=\"{}\" visibility=\"visible\"/>\n",
+            escape_xml(&layer.name),
+            index,
+            composite_op(layer.blend).unwrap_or("svg:src-over"),
+            layer.opacity.clamp(0.0, 1.0),
+            layer.x,
+            layer.y
+        ));
+    }
+    stack.push_str("  </stack>\n</image>\n");
+    zip.add("stack.xml", stack.as_bytes());
+
+    for (index, layer) in layers.iter().enumerate() {
+        zip.add(&format!("data/layer{}.png", index), &encode_png(&layer.image)?);
+    }
+    zip.add("mergedimage.png", &encode_png(merged)?);
+
+    let thumbnail = if width > MAX_THUMBNAIL_SIZE || height > MAX_THUMBNAIL_SIZE {
+        let factor = width.max(height).div_ceil(MAX_THUMBNAIL_SIZE);
+        imageops::resize(
+            merged,
+            (width / factor).max(1),
+            (height / factor).max(1),
+            imageops::FilterType::Nearest,
+        )
+    } else {
+        merged.clone()
+    };
+    zip.add("Thumbnails/thumbnail.png", &encode_png(&thumbnail)?);
+
+    Ok(zip.finish())
+}
+
+/// Save layers (bottom first) and their flattened image as an `.ora` file.
+pub fn save_ora(layers: &[OraLayer], merged: &RgbaImage, path: &Path) -> Result<(), OutputError> {
+    let bytes = encode_ora(layers, merged)?;
+    // Create parent directories if they don't exist
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Read every file in a ZIP archive (stored or deflated entries).
+///
+/// Fails once the entries add up to more than `max_size` bytes, so a small
+/// archive can't inflate into an unbounded amount of memory.
+fn read_zip(bytes: &[u8], max_size: usize) -> Result<HashMap<String, Vec<u8>>, String> {
+    let invalid = || "Invalid or truncated ZIP archive".to_string();
+    let u16_at = |at: usize| -> Result<usize, String> {
+        let b = bytes.get(at..at + 2).ok_or_else(invalid)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |at: usize| -> Result<usize, String> {
+        let b = bytes.get(at..at + 4).ok_or_else(invalid)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+
+    // End of central directory record, searched backwards past any comment
+    let eocd = (0..bytes.len().saturating_sub(21))
+        .rev()
+        .find(|&at| bytes[at..].starts_with(&0x0605_4b50u32.to_le_bytes()))
+        .ok_or_else(invalid)?;
+    let count = u16_at(eocd + 10)?;
+    let mut at = u32_at(eocd + 16)?;
+
+    let mut files = HashMap::new();
+    let mut remaining = max_size;
+    for _ in 0..count {
+        if u32_at(at)? != 0x0201_4b50 {
+            return Err(invalid());
+        }
+        let method = u16_at(at + 10)?;
+        let compressed_size = u32_at(at + 20)?;
+        let name_len = u16_at(at + 28)?;
+        let next = at + 46 + name_len + u16_at(at + 30)? + u16_at(at + 32)?;
+        let name = bytes.get(at + 46..at + 46 + name_len).ok_or_else(invalid)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+
+        let local = u32_at(at + 42)?;
+        let start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+        let data = bytes.get(start..start + compressed_size).ok_or_else(invalid)?;
+        let data = match method {
+            0 => data.to_vec(),
+            8 => {
+                let mut out = Vec::new();
+                DeflateDecoder::new(data)
+                    .take(remaining as u64 + 1)
+                    .read_to_end(&mut out)
+                    .map_err(|e| format!("Failed to inflate '{}': {}", name, e))?;
+                out
+            }
+            _ => {
+                return Err(format!("Unsupported ZIP compression method {} for '{}'", method, name))
+            }
+        };
+        remaining = remaining.checked_sub(data.len()).ok_or_else(|| {
+            format!("ZIP archive expands past the {} MiB limit at '{}'", max_size >> 20, name)
+        })?;
+        files.insert(name, data);
+        at = next;
+    }
+    Ok(files)
+}
+
+/// Minimal ZIP archive writer using the "stored" (uncompressed) method.
+#[derive(Default)]
+struct ZipWriter {
+    out: Vec<u8>,
+    central: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+
+        // Local file header
+        self.out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.out.extend_from_slice(&Self::entry_fields(name, crc, size));
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(data);
+
+        // Central directory header
+        self.central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        self.central.extend_from_slice(&Self::entry_fields(name, crc, size));
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        self.central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        self.central.extend_from_slice(&offset.to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+
+        self.entries += 1;
+    }
+
+    /// Header fields shared by local and central headers, from version needed to extra length
+    fn entry_fields(name: &str, crc: u32, size: u32) -> Vec<u8> {
+        let mut fields = Vec::with_capacity(26);
+        fields.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        fields.extend_from_slice(&0u16.to_le_bytes()); // flags
+        fields.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        fields.extend_from_slice(&0u16.to_le_bytes()); // mod time
+        fields.extend_from_slice(&0x21u16.to_le_bytes()); // mod date: 1980-01-01
+        fields.extend_from_slice(&crc.to_le_bytes());
+        fields.extend_from_slice(&size.to_le_bytes()); // compressed size
+        fields.extend_from_slice(&size.to_le_bytes()); // uncompressed size
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        fields.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        fields
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let central_offset = self.out.len() as u32;
+        let central_size = self.central.len() as u32;
+        self.out.append(&mut self.central);
+
+        // End of central directory record
+        self.out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // central directory disk
+        self.out.extend_from_slice(&self.entries.to_le_bytes());
+        self.out.extend_from_slice(&self.entries.to_le_bytes());
+        self.out.extend_from_slice(&central_size.to_le_bytes());
+        self.out.extend_from_slice(&central_offset.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.out
+    }
+}
+
+/// CRC-32 (IEEE) checksum as used by ZIP and PNG.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(
+            crc ^ byte as u32,
+            |c, _| if c & 1 != 0 { (c >> 1) ^ 0xedb8_8320 } else { c >> 1 },
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_composite_ops() {
+        assert_eq!(composite_op(BlendMode::Normal), Some("svg:src-over"));
+        assert_eq!(composite_op(BlendMode::Add), Some("svg:plus"));
+        assert_eq!(composite_op(BlendMode::Subtract), None);
+    }
+
+    #[test]
+    fn test_encode_ora_layout() {
+        let merged = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+        let layers = vec![
+            OraLayer {
+                name: "base".to_string(),
+                image: merged.clone(),
+                x: 0,
+                y: 0,
+                blend: BlendMode::Normal,
+                opacity: 1.0,
+            },
+            OraLayer {
+                name: "shade <1>".to_string(),
+                image: RgbaImage::new(2, 2),
+                x: 0,
+                y: 0,
+                blend: BlendMode::Multiply,
+                opacity: 0.5,
+            },
+        ];
+        let ora = encode_ora(&layers, &merged).unwrap();
+
+        // mimetype must be the first, uncompressed entry
+        assert_eq!(u32_at(&ora, 0), 0x0403_4b50);
+        assert_eq!(&ora[30..38], b"mimetype");
+        assert_eq!(&ora[38..54], b"image/openraster");
+
+        let text = String::from_utf8_lossy(&ora);
+        let top = text.find("name=\"shade &lt;1&gt;\" src=\"data/layer1.png\"").unwrap();
+        let bottom = text.find("name=\"base\" src=\"data/layer0.png\"").unwrap();
+        assert!(top < bottom, "stack.xml lists the top layer first");
+        assert!(text.contains("composite-op=\"svg:multiply\" opacity=\"0.500\""));
+        assert!(text.contains("w=\"2\" h=\"2\""));
+
+        // End of central directory counts every entry
+        let eocd = ora.len() - 22;
+        assert_eq!(u32_at(&ora, eocd), 0x0605_4b50);
+        assert_eq!(u16::from_le_bytes([ora[eocd + 10], ora[eocd + 11]]), 6);
+    }
+
+    #[test]
+    fn test_read_zip_caps_inflated_size() {
+        use flate2::write::DeflateEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0u8; 1 << 20]).unwrap();
+        let deflated = encoder.finish().unwrap();
+
+        let mut zip = ZipWriter::default();
+        zip.add("bomb", &deflated);
+        let mut bytes = zip.finish();
+        // Mark the entry as deflated in both the local and central headers
+        let central = u32_at(&bytes, bytes.len() - 6) as usize;
+        bytes[8] = 8;
+        bytes[central + 10] = 8;
+
+        assert_eq!(read_zip(&bytes, 2 << 20).unwrap()["bomb"].len(), 1 << 20);
+        let err = read_zip(&bytes, 1 << 19).unwrap_err();
+        assert!(err.contains("expands past") && err.contains("'bomb'"), "{}", err);
+    }
+}
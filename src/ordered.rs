@@ -0,0 +1,77 @@
+//! Key-ordered serialization of hash maps
+//!
+//! Maps stay `HashMap`s in memory, but everything written out (atlas
+//! metadata, build manifests, `pxl fmt` output, JSON reports) lists their
+//! entries sorted by key, so the same input always gives byte-identical
+//! output. Use with `#[serde(serialize_with = "crate::ordered::map")]`, or
+//! `ordered::option_map` for `Option<HashMap<..>>` fields.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use serde::{Serialize, Serializer};
+
+/// A borrowed map that serializes in key order.
+pub struct Sorted<'a, K, V, H>(pub &'a HashMap<K, V, H>);
+
+impl<K: Ord + Hash + Serialize, V: Serialize, H: BuildHasher> Serialize for Sorted<'_, K, V, H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().collect::<BTreeMap<_, _>>())
+    }
+}
+
+/// Serialize a map with its entries sorted by key.
+pub fn map<K, V, H, S>(map: &HashMap<K, V, H>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Hash + Serialize,
+    V: Serialize,
+    H: BuildHasher,
+    S: Serializer,
+{
+    Sorted(map).serialize(serializer)
+}
+
+/// Serialize an optional map with its entries sorted by key.
+pub fn option_map<K, V, H, S>(
+    map: &Option<HashMap<K, V, H>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Hash + Serialize,
+    V: Serialize,
+    H: BuildHasher,
+    S: Serializer,
+{
+    match map {
+        Some(map) => serializer.serialize_some(&Sorted(map)),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Frames {
+        #[serde(serialize_with = "map")]
+        frames: HashMap<String, u32>,
+        #[serde(serialize_with = "option_map", skip_serializing_if = "Option::is_none")]
+        tags: Option<HashMap<String, u32>>,
+    }
+
+    #[test]
+    fn test_maps_serialize_in_key_order() {
+        let frames: HashMap<String, u32> = ["walk_3", "idle", "walk_1", "jump", "walk_2"]
+            .iter()
+            .map(|k| (k.to_string(), 0))
+            .collect();
+        let value = Frames { frames: frames.clone(), tags: Some(frames) };
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"frames":{"idle":0,"jump":0,"walk_1":0,"walk_2":0,"walk_3":0},"tags":{"idle":0,"jump":0,"walk_1":0,"walk_2":0,"walk_3":0}}"#
+        );
+        let value = Frames { frames: HashMap::new(), tags: None };
+        assert_eq!(serde_json::to_string(&value).unwrap(), r#"{"frames":{}}"#);
+    }
+}
@@ -1,10 +1,15 @@
 //! PNG output and file path generation
 
 use crate::antialias::{AAAlgorithm, AntialiasConfig};
+use crate::provenance::{embed_text, write_chunk, PngTextError, SIGNATURE};
 use image::imageops::FilterType;
-use image::RgbaImage;
-use std::io;
+use image::{Rgba, RgbaImage};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use thiserror::Error;
 
 /// Error type for output operations
@@ -17,6 +22,61 @@ pub enum OutputError {
     /// Image encoding error
     #[error("Image error: {0}")]
     Image(#[from] image::ImageError),
+    /// Image too large for a .cur/.ani cursor
+    #[error("Cursor images can be at most 256x256, got {width}x{height}")]
+    CursorSize { width: u32, height: u32 },
+    /// PNG text metadata couldn't be embedded
+    #[error("PNG metadata error: {0}")]
+    PngText(#[from] PngTextError),
+    /// GIF encoding error
+    #[error("GIF error: {0}")]
+    Gif(#[from] gif::EncodingError),
+    /// Image too large for a GIF
+    #[error("GIF images can be at most 65535x65535, got {width}x{height}")]
+    GifSize { width: u32, height: u32 },
+    /// Image doesn't fit the `--canvas` size once margins are taken off
+    #[error("A {width}x{height} image doesn't fit a {}x{} canvas with its margin", canvas.0, canvas.1)]
+    CanvasTooSmall { width: u32, height: u32, canvas: (u32, u32) },
+}
+
+/// Command-line path that stands for stdin (inputs) or stdout (outputs).
+pub const STDIO_PATH: &str = "-";
+
+/// Whether `path` is `-`, i.e. stdin or stdout.
+pub fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == STDIO_PATH
+}
+
+/// Open an input file for reading, or stdin when `path` is `-`.
+pub fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if is_stdio(path) {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// Create an output file for writing, or stdout when `path` is `-`.
+///
+/// Missing parent directories are created.
+pub fn create_output(path: &Path) -> io::Result<Box<dyn Write>> {
+    if is_stdio(path) {
+        return Ok(Box::new(io::stdout().lock()));
+    }
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    Ok(Box::new(BufWriter::new(File::create(path)?)))
+}
+
+/// File stem used to name outputs derived from `input` (`stdin` for `-`).
+pub fn input_stem(input: &Path) -> String {
+    if is_stdio(input) {
+        return "stdin".to_string();
+    }
+    input.file_stem().and_then(|s| s.to_str()).unwrap_or("output").to_string()
 }
 
 /// Save an RGBA image to a PNG file.
@@ -24,22 +84,259 @@ pub enum OutputError {
 /// # Arguments
 ///
 /// * `image` - The image to save
-/// * `path` - The output file path
+/// * `path` - The output file path (`-` writes the PNG to stdout)
 ///
 /// # Returns
 ///
 /// * `Ok(())` on success
 /// * `Err(OutputError)` on failure
 pub fn save_png(image: &RgbaImage, path: &Path) -> Result<(), OutputError> {
-    // Create parent directories if they don't exist
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() && !parent.exists() {
-            std::fs::create_dir_all(parent)?;
+    // Creates parent directories if they don't exist
+    let mut out = create_output(path)?;
+    out.write_all(&encode_png(image)?)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Save an RGBA image to a PNG file with text chunks (see [`crate::provenance`]).
+///
+/// Behaves like [`save_png`], which it falls back to when `text` is empty or
+/// the current [`PngOptions`] strip metadata.
+pub fn save_png_with_text(
+    image: &RgbaImage,
+    path: &Path,
+    text: &[(String, String)],
+) -> Result<(), OutputError> {
+    if text.is_empty() || png_options().strip_metadata {
+        return save_png(image, path);
+    }
+    let png = embed_text(&encode_png(image)?, text)?;
+    let mut out = create_output(path)?;
+    out.write_all(&png)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Encode an RGBA image as PNG bytes in memory.
+///
+/// Uses the current [`PngOptions`], so the same pixels always encode to the
+/// same bytes. Used by callers that stream images (HTTP previews, bindings)
+/// instead of writing files.
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, OutputError> {
+    use image::ImageEncoder;
+
+    let options = png_options();
+    let mut png_data = Vec::new();
+    image::codecs::png::PngEncoder::new_with_quality(
+        &mut png_data,
+        options.compression.into(),
+        options.filter.into(),
+    )
+    .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8)?;
+    Ok(png_data)
+}
+
+/// Save an image as an 8-bit indexed PNG (PNG-8) with text chunks.
+///
+/// See [`encode_indexed_png`] for how `palette` is used. Falls back to
+/// [`save_png_with_text`] and returns `false` when the image needs more than
+/// 256 colors.
+pub fn save_indexed_png_with_text(
+    image: &RgbaImage,
+    path: &Path,
+    text: &[(String, String)],
+    palette: &[Rgba<u8>],
+) -> Result<bool, OutputError> {
+    let Some(png) = encode_indexed_png(image, palette)? else {
+        save_png_with_text(image, path, text)?;
+        return Ok(false);
+    };
+    let png =
+        if text.is_empty() || png_options().strip_metadata { png } else { embed_text(&png, text)? };
+    let mut out = create_output(path)?;
+    out.write_all(&png)?;
+    out.flush()?;
+    Ok(true)
+}
+
+/// Encode an image as an 8-bit indexed PNG (PNG-8).
+///
+/// The PNG palette starts with `palette` in order, so its indices are kept;
+/// colors the image uses that aren't in it are appended. Fully transparent
+/// pixels share the first fully transparent entry, and alpha is written as a
+/// `tRNS` chunk. Returns `None` if more than 256 colors would be needed.
+///
+/// Scanlines are always unfiltered; only the [`PngOptions`] compression level
+/// applies.
+pub fn encode_indexed_png(
+    image: &RgbaImage,
+    palette: &[Rgba<u8>],
+) -> Result<Option<Vec<u8>>, OutputError> {
+    use flate2::write::ZlibEncoder;
+
+    if palette.len() > 256 {
+        return Ok(None);
+    }
+    // All fully transparent colors look the same, so they share one entry
+    let key = |Rgba([r, g, b, a]): Rgba<u8>| if a == 0 { [0; 4] } else { [r, g, b, a] };
+    let mut entries = palette.to_vec();
+    let mut lookup: HashMap<[u8; 4], u8> = HashMap::new();
+    for (index, color) in entries.iter().enumerate() {
+        lookup.entry(key(*color)).or_insert(index as u8);
+    }
+
+    let (width, height) = image.dimensions();
+    let mut scanlines = Vec::with_capacity((width as usize + 1) * height as usize);
+    for row in image.rows() {
+        scanlines.push(0); // filter type: none
+        for pixel in row {
+            let index = match lookup.get(&key(*pixel)) {
+                Some(&index) => index,
+                None if entries.len() == 256 => return Ok(None),
+                None => {
+                    let index = entries.len() as u8;
+                    entries.push(*pixel);
+                    lookup.insert(key(*pixel), index);
+                    index
+                }
+            };
+            scanlines.push(index);
         }
     }
 
-    image.save(path)?;
-    Ok(())
+    let mut png = SIGNATURE.to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type 3 (indexed), default compression, filter, no interlace
+    header.extend_from_slice(&[8, 3, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    let colors: Vec<u8> = entries.iter().flat_map(|c| [c[0], c[1], c[2]]).collect();
+    write_chunk(&mut png, b"PLTE", &colors);
+    // tRNS may stop after the last entry that isn't opaque
+    let alphas: Vec<u8> = entries.iter().map(|c| c[3]).collect();
+    if let Some(last) = alphas.iter().rposition(|&a| a != 255) {
+        write_chunk(&mut png, b"tRNS", &alphas[..=last]);
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), png_options().compression.level());
+    encoder.write_all(&scanlines)?;
+    write_chunk(&mut png, b"IDAT", &encoder.finish()?);
+    write_chunk(&mut png, b"IEND", &[]);
+    Ok(Some(png))
+}
+
+/// zlib compression level for PNG image data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PngCompression {
+    /// Fastest encoding, largest files
+    Fast,
+    /// Balanced speed and size
+    #[default]
+    Default,
+    /// Smallest files, slowest encoding
+    Best,
+}
+
+impl PngCompression {
+    fn level(self) -> flate2::Compression {
+        match self {
+            PngCompression::Fast => flate2::Compression::fast(),
+            PngCompression::Default => flate2::Compression::default(),
+            PngCompression::Best => flate2::Compression::best(),
+        }
+    }
+}
+
+impl From<PngCompression> for image::codecs::png::CompressionType {
+    fn from(compression: PngCompression) -> Self {
+        match compression {
+            PngCompression::Fast => Self::Fast,
+            PngCompression::Default => Self::Default,
+            PngCompression::Best => Self::Best,
+        }
+    }
+}
+
+/// Scanline filter strategy for PNG image data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PngFilter {
+    /// No filtering
+    None,
+    /// Difference from the pixel to the left
+    Sub,
+    /// Difference from the pixel above
+    Up,
+    /// Difference from the average of left and above
+    Avg,
+    /// Paeth predictor
+    Paeth,
+    /// Pick the best filter per scanline
+    #[default]
+    Adaptive,
+}
+
+impl From<PngFilter> for image::codecs::png::FilterType {
+    fn from(filter: PngFilter) -> Self {
+        match filter {
+            PngFilter::None => Self::NoFilter,
+            PngFilter::Sub => Self::Sub,
+            PngFilter::Up => Self::Up,
+            PngFilter::Avg => Self::Avg,
+            PngFilter::Paeth => Self::Paeth,
+            PngFilter::Adaptive => Self::Adaptive,
+        }
+    }
+}
+
+/// PNG encoder settings used by every PNG written through this module.
+///
+/// The defaults are pinned rather than left to the encoder library, so a
+/// given image encodes to the same bytes on every platform. Like the global
+/// seed in [`crate::rng`], the options are scoped to the current thread:
+///
+/// ```
+/// use pixelsrc::output::{encode_png, with_png_options, PngCompression, PngOptions};
+///
+/// let image = image::RgbaImage::new(8, 8);
+/// let a = encode_png(&image).unwrap();
+/// assert_eq!(a, encode_png(&image).unwrap());
+///
+/// let fast = PngOptions { compression: PngCompression::Fast, ..Default::default() };
+/// let b = with_png_options(fast, || encode_png(&image).unwrap());
+/// assert_eq!(image::load_from_memory(&b).unwrap().to_rgba8(), image);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PngOptions {
+    /// zlib compression level
+    pub compression: PngCompression,
+    /// Scanline filter strategy
+    pub filter: PngFilter,
+    /// Drop ancillary chunks (provenance text) so only pixels affect the bytes
+    pub strip_metadata: bool,
+}
+
+thread_local! {
+    static PNG_OPTIONS: Cell<PngOptions> = Cell::new(PngOptions::default());
+}
+
+/// The PNG options in effect on this thread.
+pub fn png_options() -> PngOptions {
+    PNG_OPTIONS.with(Cell::get)
+}
+
+/// Run `f` with `options` as the PNG options, restoring the previous options afterwards.
+pub fn with_png_options<R>(options: PngOptions, f: impl FnOnce() -> R) -> R {
+    struct Restore(PngOptions);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            PNG_OPTIONS.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(PNG_OPTIONS.with(|o| o.replace(options)));
+    f()
 }
 
 /// Scale image by integer factor using nearest-neighbor interpolation.
@@ -64,6 +361,194 @@ pub fn scale_image(image: RgbaImage, factor: u8) -> RgbaImage {
     image::imageops::resize(&image, new_w, new_h, FilterType::Nearest)
 }
 
+/// How alpha is written to exported images.
+///
+/// Applied as the last step before encoding, after scaling and any frame
+/// blending, so every export path (sprites, compositions, spritesheets,
+/// atlases, GIFs) treats alpha the same way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AlphaOptions {
+    /// Store color channels premultiplied by alpha
+    pub premultiply: bool,
+    /// Flatten onto this background color, producing a fully opaque image.
+    /// The matte's own alpha is ignored.
+    pub matte: Option<Rgba<u8>>,
+}
+
+impl AlphaOptions {
+    /// Whether these options leave images unchanged.
+    pub fn is_identity(&self) -> bool {
+        !self.premultiply && self.matte.is_none()
+    }
+
+    /// Apply the alpha handling to an image.
+    ///
+    /// With a matte, pixels are composited over the matte color (source-over),
+    /// so the result is opaque and premultiplication has no further effect.
+    pub fn apply(&self, mut image: RgbaImage) -> RgbaImage {
+        if self.is_identity() {
+            return image;
+        }
+        for pixel in image.pixels_mut() {
+            let Rgba([r, g, b, a]) = *pixel;
+            *pixel = match self.matte {
+                Some(Rgba([mr, mg, mb, _])) => Rgba([
+                    blend_channel(r, mr, a),
+                    blend_channel(g, mg, a),
+                    blend_channel(b, mb, a),
+                    255,
+                ]),
+                None => Rgba([mul_alpha(r, a), mul_alpha(g, a), mul_alpha(b, a), a]),
+            };
+        }
+        image
+    }
+
+    /// Apply the alpha handling to every frame of an animation.
+    pub fn apply_frames(&self, frames: Vec<RgbaImage>) -> Vec<RgbaImage> {
+        frames.into_iter().map(|f| self.apply(f)).collect()
+    }
+}
+
+/// Background drawn behind [`FrameOptions`] output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    /// A single color
+    Solid(Rgba<u8>),
+    /// Light and dark gray squares of `size` output pixels, like an image
+    /// editor's transparency grid
+    Checkerboard { size: u32 },
+}
+
+impl Background {
+    /// Default checkerboard square size in output pixels.
+    pub const CHECKER_SIZE: u32 = 8;
+    const CHECKER_LIGHT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const CHECKER_DARK: Rgba<u8> = Rgba([204, 204, 204, 255]);
+
+    fn color_at(&self, x: u32, y: u32) -> Rgba<u8> {
+        match *self {
+            Background::Solid(color) => color,
+            Background::Checkerboard { size } if (x / size + y / size).is_multiple_of(2) => {
+                Self::CHECKER_LIGHT
+            }
+            Background::Checkerboard { .. } => Self::CHECKER_DARK,
+        }
+    }
+}
+
+/// Parses a color (`#1a1c2c`, `navy`) or `checkerboard[:SIZE]`.
+impl FromStr for Background {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(rest) = s.strip_prefix("checkerboard") else {
+            return crate::color::parse_color(s).map(Background::Solid).map_err(|e| e.to_string());
+        };
+        let size = match rest.strip_prefix(':') {
+            Some(size) => {
+                size.parse().map_err(|_| format!("invalid checkerboard size '{}'", size))?
+            }
+            None if rest.is_empty() => Self::CHECKER_SIZE,
+            None => return Err(format!("invalid background '{}'", s)),
+        };
+        if size == 0 {
+            return Err("checkerboard size must be positive".to_string());
+        }
+        Ok(Background::Checkerboard { size })
+    }
+}
+
+/// Fixed output canvas size, parsed from `WxH` (e.g. `1920x1080`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanvasSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FromStr for CanvasSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parsed = s.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+        match parsed {
+            Some((width, height)) if width > 0 && height > 0 => Ok(Self { width, height }),
+            _ => Err(format!("expected WxH with positive sizes, got '{}'", s)),
+        }
+    }
+}
+
+/// Presentation framing for exported images: a margin, a fixed canvas and a
+/// background, applied in one pass after scaling.
+///
+/// With a canvas the image is letterboxed: enlarged by the largest integer
+/// factor that fits inside the canvas less its margin, then centered. Without
+/// one the canvas is the image plus its margin. The background fills the
+/// whole canvas and the image is composited over it (source-over).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameOptions {
+    /// Fill behind the image; transparent when unset
+    pub background: Option<Background>,
+    /// Minimum space around the image, in output pixels
+    pub margin: u32,
+    /// Letterbox onto a canvas of this size
+    pub canvas: Option<CanvasSize>,
+}
+
+impl FrameOptions {
+    /// Whether these options leave images unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.background.is_none() && self.margin == 0 && self.canvas.is_none()
+    }
+
+    /// Frame an image, or fail if it doesn't fit the canvas.
+    pub fn apply(&self, image: RgbaImage) -> Result<RgbaImage, OutputError> {
+        if self.is_identity() {
+            return Ok(image);
+        }
+        let (width, height) = image.dimensions();
+        let margin = self.margin.saturating_mul(2);
+        let (canvas_w, canvas_h, factor) = match self.canvas {
+            Some(CanvasSize { width: cw, height: ch }) => {
+                let inner_w = cw.saturating_sub(margin);
+                let inner_h = ch.saturating_sub(margin);
+                let factor = (inner_w / width.max(1)).min(inner_h / height.max(1));
+                if factor == 0 {
+                    return Err(OutputError::CanvasTooSmall { width, height, canvas: (cw, ch) });
+                }
+                (cw, ch, factor)
+            }
+            None => (width.saturating_add(margin), height.saturating_add(margin), 1),
+        };
+
+        let image = if factor > 1 {
+            image::imageops::resize(&image, width * factor, height * factor, FilterType::Nearest)
+        } else {
+            image
+        };
+        let mut canvas = match self.background {
+            Some(background) => {
+                RgbaImage::from_fn(canvas_w, canvas_h, |x, y| background.color_at(x, y))
+            }
+            None => RgbaImage::new(canvas_w, canvas_h),
+        };
+        let x = (canvas_w - image.width()) / 2;
+        let y = (canvas_h - image.height()) / 2;
+        image::imageops::overlay(&mut canvas, &image, x as i64, y as i64);
+        Ok(canvas)
+    }
+}
+
+/// `value * alpha / 255`, rounded
+fn mul_alpha(value: u8, alpha: u8) -> u8 {
+    ((value as u32 * alpha as u32 + 127) / 255) as u8
+}
+
+/// Source-over of `src` with `alpha` onto an opaque `dst`, rounded
+fn blend_channel(src: u8, dst: u8, alpha: u8) -> u8 {
+    ((src as u32 * alpha as u32 + dst as u32 * (255 - alpha as u32) + 127) / 255) as u8
+}
+
 /// Apply antialiasing to an image using the specified configuration.
 ///
 /// This function is the pipeline integration point for antialiasing algorithms.
@@ -271,6 +756,7 @@ pub fn skew_y(image: &RgbaImage, degrees: f32) -> RgbaImage {
 /// | With `-o output.png` (single sprite) | `output.png` |
 /// | With `-o output.png` (multiple) | `output_{name}.png` |
 /// | With `-o dir/` | `dir/{name}.png` |
+/// | With `-o -` | stdout |
 ///
 /// # Arguments
 ///
@@ -289,6 +775,7 @@ pub fn generate_output_path(
     is_single_sprite: bool,
 ) -> PathBuf {
     match output_arg {
+        Some(output) if is_stdio(output) => output.to_path_buf(),
         Some(output) => {
             // Check if output is a directory (ends with / or is existing directory)
             let is_dir = output.as_os_str().to_string_lossy().ends_with('/') || output.is_dir();
@@ -312,7 +799,7 @@ pub fn generate_output_path(
         }
         None => {
             // Default: {input_stem}_{sprite_name}.png
-            let input_stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+            let input_stem = input_stem(input);
             let parent = input.parent().unwrap_or(Path::new(""));
             if parent.as_os_str().is_empty() {
                 PathBuf::from(format!("{}_{}.png", input_stem, sprite_name))
@@ -326,7 +813,83 @@ pub fn generate_output_path(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use image::Rgba;
+
+    #[test]
+    fn test_alpha_options_identity() {
+        let image = RgbaImage::from_pixel(2, 2, Rgba([200, 100, 50, 128]));
+        let options = AlphaOptions::default();
+        assert!(options.is_identity());
+        assert_eq!(options.apply(image.clone()), image);
+    }
+
+    #[test]
+    fn test_alpha_options_premultiply() {
+        let mut image = RgbaImage::new(3, 1);
+        image.put_pixel(0, 0, Rgba([200, 100, 50, 128]));
+        image.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+        image.put_pixel(2, 0, Rgba([255, 0, 0, 0]));
+
+        let result = AlphaOptions { premultiply: true, matte: None }.apply(image);
+        assert_eq!(*result.get_pixel(0, 0), Rgba([100, 50, 25, 128]));
+        assert_eq!(*result.get_pixel(1, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*result.get_pixel(2, 0), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_alpha_options_matte() {
+        let mut image = RgbaImage::new(3, 1);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 0]));
+        image.put_pixel(2, 0, Rgba([255, 255, 255, 128]));
+
+        // Matte wins over premultiply: the result is opaque
+        let options = AlphaOptions { premultiply: true, matte: Some(Rgba([0, 0, 255, 0])) };
+        let result = options.apply(image);
+        assert_eq!(*result.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*result.get_pixel(1, 0), Rgba([0, 0, 255, 255]));
+        assert_eq!(*result.get_pixel(2, 0), Rgba([128, 128, 255, 255]));
+    }
+
+    #[test]
+    fn test_frame_options() {
+        let mut image = RgbaImage::new(2, 1);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+
+        // A margin alone grows a transparent canvas
+        let result = FrameOptions { margin: 1, ..Default::default() }.apply(image.clone()).unwrap();
+        assert_eq!(result.dimensions(), (4, 3));
+        assert_eq!(*result.get_pixel(1, 1), Rgba([255, 0, 0, 255]));
+        assert_eq!(*result.get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+
+        // Letterboxing scales by the largest integer factor and centers
+        let options = FrameOptions {
+            background: Some(Background::Solid(Rgba([0, 0, 255, 255]))),
+            margin: 1,
+            canvas: Some(CanvasSize { width: 10, height: 8 }),
+        };
+        let result = options.apply(image.clone()).unwrap();
+        assert_eq!(result.dimensions(), (10, 8));
+        // 4x fits 8x6 inside the margin: 8x4 at (1, 2)
+        assert_eq!(*result.get_pixel(1, 2), Rgba([255, 0, 0, 255]));
+        assert_eq!(*result.get_pixel(4, 5), Rgba([255, 0, 0, 255]));
+        assert_eq!(*result.get_pixel(5, 5), Rgba([0, 0, 255, 255]), "transparent over background");
+        assert_eq!(*result.get_pixel(1, 1), Rgba([0, 0, 255, 255]));
+
+        let small = FrameOptions { canvas: Some(CanvasSize { width: 1, height: 1 }), ..options };
+        assert!(matches!(small.apply(image), Err(OutputError::CanvasTooSmall { .. })));
+    }
+
+    #[test]
+    fn test_parse_frame_args() {
+        assert_eq!("#FF0000".parse(), Ok(Background::Solid(Rgba([255, 0, 0, 255]))));
+        assert_eq!("checkerboard".parse(), Ok(Background::Checkerboard { size: 8 }));
+        assert_eq!("checkerboard:4".parse(), Ok(Background::Checkerboard { size: 4 }));
+        assert!("checkerboard:0".parse::<Background>().is_err());
+        assert!("checkerboards".parse::<Background>().is_err());
+        assert_eq!("1920x1080".parse(), Ok(CanvasSize { width: 1920, height: 1080 }));
+        assert!("1920".parse::<CanvasSize>().is_err());
+        assert!("0x10".parse::<CanvasSize>().is_err());
+    }
 
     #[test]
     fn test_generate_output_path_default_single() {
@@ -344,6 +907,16 @@ mod tests {
         assert_eq!(path2, PathBuf::from("input_enemy.png"));
     }
 
+    #[test]
+    fn test_generate_output_path_stdio() {
+        // Stdin input names files after "stdin"; `-o -` always means stdout
+        let path = generate_output_path(Path::new("-"), "hero", None, false);
+        assert_eq!(path, PathBuf::from("stdin_hero.png"));
+        let path =
+            generate_output_path(Path::new("input.jsonl"), "hero", Some(Path::new("-")), false);
+        assert_eq!(path, PathBuf::from("-"));
+    }
+
     #[test]
     fn test_generate_output_path_explicit_file_single() {
         // Single sprite with -o output.png
@@ -466,6 +1039,30 @@ mod tests {
         assert_eq!(*loaded.get_pixel(1, 1), Rgba([0, 0, 0, 0]));
     }
 
+    #[test]
+    fn test_encode_indexed_png() {
+        let mut image = RgbaImage::new(3, 1);
+        image.put_pixel(0, 0, Rgba([0, 0, 255, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 128]));
+        image.put_pixel(2, 0, Rgba([9, 9, 9, 0])); // Transparent, any color
+
+        let palette = [Rgba([0, 0, 0, 0]), Rgba([0, 255, 0, 255]), Rgba([0, 0, 255, 255])];
+        let png = encode_indexed_png(&image, &palette).unwrap().unwrap();
+
+        // Color type 3 (indexed); palette indices kept, the missing color appended
+        assert_eq!(png[25], 3);
+        let loaded = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(*loaded.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+        assert_eq!(*loaded.get_pixel(1, 0), Rgba([255, 0, 0, 128]));
+        assert_eq!(*loaded.get_pixel(2, 0), Rgba([0, 0, 0, 0]));
+        let plte = png.windows(4).position(|w| w == b"PLTE").unwrap();
+        assert_eq!(&png[plte - 4..plte], &12u32.to_be_bytes(), "four RGB entries");
+
+        // More than 256 colors don't fit
+        let gradient = RgbaImage::from_fn(257, 1, |x, _| Rgba([x as u8, (x >> 8) as u8, 0, 255]));
+        assert!(encode_indexed_png(&gradient, &[]).unwrap().is_none());
+    }
+
     #[test]
     fn test_save_png_creates_parent_dirs() {
         use tempfile::tempdir;
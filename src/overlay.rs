@@ -0,0 +1,308 @@
+//! Debug overlays drawn over previews: pixel grid, composition cells, origin,
+//! collision boxes and nine-slice guides.
+//!
+//! Overlays are drawn after scaling, so lines fall between source pixels and
+//! stay one output pixel wide. Guide positions are in source pixels and come
+//! from the sprite's metadata or the composition's cell size:
+//!
+//! | Overlay | Drawn as |
+//! |---------|----------|
+//! | `grid[:N]` | Gray lines every N pixels (default 1) |
+//! | `cells` | Cyan lines at composition cell boundaries |
+//! | `nine-slice` | Magenta lines at the nine-slice borders |
+//! | `boxes` | Collision box outlines, colored by box name |
+//! | `origin` | Orange crosshair on the origin pixel |
+
+use std::str::FromStr;
+
+use image::{Rgba, RgbaImage};
+
+use crate::models::{CollisionBox, Composition, NineSlice, SpriteMetadata};
+use crate::renderer::Rect;
+
+const GRID_COLOR: Rgba<u8> = Rgba([128, 128, 128, 128]);
+const CELL_COLOR: Rgba<u8> = Rgba([0, 255, 255, 192]);
+const NINE_SLICE_COLOR: Rgba<u8> = Rgba([255, 0, 255, 192]);
+const ORIGIN_COLOR: Rgba<u8> = Rgba([255, 128, 0, 255]);
+
+/// Crosshair arm length in source pixels, beyond the origin pixel itself
+const ORIGIN_ARM: u32 = 2;
+
+/// One kind of overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    /// Pixel grid with a line every N pixels
+    Grid(u32),
+    /// Composition cell boundaries
+    Cells,
+    /// Nine-slice border guides
+    NineSlice,
+    /// Collision box outlines
+    Boxes,
+    /// Origin crosshair
+    Origin,
+}
+
+impl FromStr for Overlay {
+    type Err = String;
+
+    /// Parse `grid`, `grid:N`, `cells`, `nine-slice`, `boxes` or `origin`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "grid" => Ok(Self::Grid(1)),
+            "cells" => Ok(Self::Cells),
+            "nine-slice" | "nine_slice" => Ok(Self::NineSlice),
+            "boxes" => Ok(Self::Boxes),
+            "origin" => Ok(Self::Origin),
+            other => match other.strip_prefix("grid:").map(str::parse::<u32>) {
+                Some(Ok(n)) if n > 0 => Ok(Self::Grid(n)),
+                Some(_) => Err(format!("grid spacing must be a positive integer, got '{}'", s)),
+                None => Err(format!(
+                    "unknown overlay '{}' (expected grid[:N], cells, nine-slice, boxes or origin)",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// Where guides go on an image, in source pixels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Guides {
+    /// Origin point
+    pub origin: Option<[i32; 2]>,
+    /// Named collision boxes
+    pub boxes: Vec<(String, CollisionBox)>,
+    /// Nine-slice borders
+    pub nine_slice: Option<NineSlice>,
+    /// Composition cell size
+    pub cell_size: Option<[u32; 2]>,
+    /// Image position of source pixel (0, 0), moved by cropping
+    pub offset: [i32; 2],
+}
+
+impl Guides {
+    /// Guides for a sprite from its (transformed) metadata and nine-slice.
+    pub fn for_sprite(metadata: Option<&SpriteMetadata>, nine_slice: Option<&NineSlice>) -> Self {
+        let mut boxes: Vec<(String, CollisionBox)> = metadata
+            .and_then(|m| m.boxes.as_ref())
+            .map(|b| b.iter().map(|(name, b)| (name.clone(), b.clone())).collect())
+            .unwrap_or_default();
+        boxes.sort_by(|a, b| a.0.cmp(&b.0));
+        Self {
+            origin: metadata.and_then(|m| m.origin),
+            boxes,
+            nine_slice: nine_slice.cloned(),
+            ..Default::default()
+        }
+    }
+
+    /// Guides for a composition: its cell boundaries.
+    pub fn for_composition(composition: &Composition) -> Self {
+        Self { cell_size: Some(composition.cell_size()), ..Default::default() }
+    }
+
+    /// The same guides on an image cropped to `rect`.
+    pub fn cropped(mut self, rect: Rect) -> Self {
+        self.offset[0] -= rect.x as i32;
+        self.offset[1] -= rect.y as i32;
+        self
+    }
+}
+
+/// Draw `overlays` on an image that was scaled up by `scale`.
+pub fn draw_overlays(image: &mut RgbaImage, scale: u32, overlays: &[Overlay], guides: &Guides) {
+    let scale = scale.max(1) as i32;
+    let [ox, oy] = guides.offset;
+    let (width, height) = (image.width() as i32, image.height() as i32);
+    let mut canvas = Canvas { image };
+
+    // Lines on pixel boundaries, in source coordinates, repeated every `step`
+    let repeating_lines = |step: [u32; 2], color: Rgba<u8>, canvas: &mut Canvas| {
+        let [sx, sy] = step.map(|s| s.max(1) as i32);
+        let mut x = ox.rem_euclid(sx);
+        while x * scale < width {
+            if x > 0 {
+                canvas.vline(x * scale, 0, height - 1, color);
+            }
+            x += sx;
+        }
+        let mut y = oy.rem_euclid(sy);
+        while y * scale < height {
+            if y > 0 {
+                canvas.hline(0, width - 1, y * scale, color);
+            }
+            y += sy;
+        }
+    };
+
+    for overlay in overlays {
+        if let Overlay::Grid(n) = *overlay {
+            repeating_lines([n, n], GRID_COLOR, &mut canvas);
+        }
+    }
+    if overlays.contains(&Overlay::Cells) {
+        if let Some(cell) = guides.cell_size {
+            repeating_lines(cell, CELL_COLOR, &mut canvas);
+        }
+    }
+    if overlays.contains(&Overlay::NineSlice) {
+        if let Some(ns) = &guides.nine_slice {
+            // Borders are measured from the image edges, so they follow resizing
+            let source = (width / scale, height / scale);
+            let xs = [ns.left as i32, source.0 - ns.right as i32];
+            let ys = [ns.top as i32, source.1 - ns.bottom as i32];
+            for x in xs {
+                canvas.vline(x * scale, 0, height - 1, NINE_SLICE_COLOR);
+            }
+            for y in ys {
+                canvas.hline(0, width - 1, y * scale, NINE_SLICE_COLOR);
+            }
+        }
+    }
+    if overlays.contains(&Overlay::Boxes) {
+        for (name, b) in &guides.boxes {
+            let x0 = (b.x + ox) * scale;
+            let y0 = (b.y + oy) * scale;
+            let x1 = x0 + b.w as i32 * scale - 1;
+            let y1 = y0 + b.h as i32 * scale - 1;
+            let color = box_color(name);
+            canvas.hline(x0, x1, y0, color);
+            canvas.hline(x0, x1, y1, color);
+            canvas.vline(x0, y0, y1, color);
+            canvas.vline(x1, y0, y1, color);
+        }
+    }
+    if overlays.contains(&Overlay::Origin) {
+        if let Some([x, y]) = guides.origin {
+            let cx = (x + ox) * scale + scale / 2;
+            let cy = (y + oy) * scale + scale / 2;
+            let arm = ORIGIN_ARM as i32 * scale + scale / 2;
+            canvas.hline(cx - arm, cx + arm, cy, ORIGIN_COLOR);
+            canvas.vline(cx, cy - arm, cy + arm, ORIGIN_COLOR);
+        }
+    }
+}
+
+/// Outline color for a collision box, by its conventional name.
+fn box_color(name: &str) -> Rgba<u8> {
+    match name {
+        "hit" => Rgba([255, 0, 0, 255]),
+        "hurt" => Rgba([0, 255, 0, 255]),
+        "collide" => Rgba([0, 128, 255, 255]),
+        "trigger" => Rgba([255, 255, 0, 255]),
+        _ => Rgba([255, 255, 255, 255]),
+    }
+}
+
+/// Clipped, alpha-blended line drawing.
+struct Canvas<'a> {
+    image: &'a mut RgbaImage,
+}
+
+impl Canvas<'_> {
+    fn blend(&mut self, x: i32, y: i32, color: Rgba<u8>) {
+        if x < 0 || y < 0 || x >= self.image.width() as i32 || y >= self.image.height() as i32 {
+            return;
+        }
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+        let src_a = color[3] as f32 / 255.0;
+        let dst_a = pixel[3] as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a <= 0.0 {
+            return;
+        }
+        for c in 0..3 {
+            let value = (color[c] as f32 * src_a + pixel[c] as f32 * dst_a * (1.0 - src_a)) / out_a;
+            pixel[c] = value.round() as u8;
+        }
+        pixel[3] = (out_a * 255.0).round() as u8;
+    }
+
+    fn hline(&mut self, x0: i32, x1: i32, y: i32, color: Rgba<u8>) {
+        for x in x0..=x1 {
+            self.blend(x, y, color);
+        }
+    }
+
+    fn vline(&mut self, x: i32, y0: i32, y1: i32, color: Rgba<u8>) {
+        for y in y0..=y1 {
+            self.blend(x, y, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_overlay() {
+        assert_eq!("grid".parse::<Overlay>(), Ok(Overlay::Grid(1)));
+        assert_eq!("grid:8".parse::<Overlay>(), Ok(Overlay::Grid(8)));
+        assert_eq!("Nine-Slice".parse::<Overlay>(), Ok(Overlay::NineSlice));
+        assert!("grid:0".parse::<Overlay>().is_err());
+        assert!("axes".parse::<Overlay>().unwrap_err().contains("unknown overlay"));
+    }
+
+    #[test]
+    fn test_grid_lines_fall_between_pixels() {
+        let mut image = RgbaImage::new(8, 8);
+        draw_overlays(&mut image, 2, &[Overlay::Grid(2)], &Guides::default());
+        // Lines at source x = 2 -> output column 4, never on the outer edge
+        assert_eq!(image.get_pixel(4, 1)[3], 128);
+        assert_eq!(image.get_pixel(0, 1)[3], 0);
+        assert_eq!(image.get_pixel(2, 1)[3], 0);
+        assert_eq!(image.get_pixel(1, 4)[3], 128);
+    }
+
+    #[test]
+    fn test_boxes_and_origin() {
+        let metadata = SpriteMetadata {
+            origin: Some([1, 1]),
+            boxes: Some(HashMap::from([(
+                "hit".to_string(),
+                CollisionBox { x: 0, y: 0, w: 2, h: 2 },
+            )])),
+            ..Default::default()
+        };
+        let guides = Guides::for_sprite(Some(&metadata), None);
+        let mut image = RgbaImage::new(12, 12);
+        draw_overlays(&mut image, 2, &[Overlay::Boxes], &guides);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(3, 3), Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(1, 1)[3], 0);
+        assert_eq!(image.get_pixel(4, 4)[3], 0);
+
+        let mut image = RgbaImage::new(12, 12);
+        draw_overlays(&mut image, 2, &[Overlay::Origin], &guides);
+        // Center of origin pixel (1, 1) at scale 2 is output (3, 3)
+        assert_eq!(*image.get_pixel(3, 3), ORIGIN_COLOR);
+        assert_eq!(*image.get_pixel(8, 3), ORIGIN_COLOR);
+        assert_eq!(image.get_pixel(9, 3)[3], 0);
+
+        // Cropping moves the guides with the pixels
+        let mut image = RgbaImage::new(12, 12);
+        let cropped = guides.cropped(Rect { x: 1, y: 0, width: 6, height: 6 });
+        draw_overlays(&mut image, 2, &[Overlay::Origin], &cropped);
+        assert_eq!(*image.get_pixel(1, 3), ORIGIN_COLOR);
+    }
+
+    #[test]
+    fn test_nine_slice_and_cells() {
+        let ns = NineSlice { left: 1, right: 2, top: 1, bottom: 1 };
+        let guides = Guides { nine_slice: Some(ns), cell_size: Some([3, 3]), ..Default::default() };
+        let mut image = RgbaImage::new(6, 6);
+        draw_overlays(&mut image, 1, &[Overlay::NineSlice], &guides);
+        assert_eq!(*image.get_pixel(1, 0), NINE_SLICE_COLOR);
+        assert_eq!(*image.get_pixel(4, 0), NINE_SLICE_COLOR);
+        assert_eq!(*image.get_pixel(0, 5), NINE_SLICE_COLOR);
+        assert_eq!(image.get_pixel(2, 2)[3], 0);
+
+        let mut image = RgbaImage::new(6, 6);
+        draw_overlays(&mut image, 1, &[Overlay::Cells], &guides);
+        assert_eq!(*image.get_pixel(3, 0), CELL_COLOR);
+        assert_eq!(image.get_pixel(2, 0)[3], 0);
+    }
+}
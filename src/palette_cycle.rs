@@ -4,11 +4,16 @@
 //! rotate through a set of palette tokens, creating effects like shimmering
 //! water, flickering fire, or pulsing energy without needing multiple sprite frames.
 
+use crate::flipbook::draw_number;
 use crate::models::{Animation, PaletteCycle, Sprite};
 use crate::renderer::render_sprite;
+use image::imageops::{self, FilterType};
 use image::RgbaImage;
 use std::collections::HashMap;
 
+/// Gap between cycle strip cells and rows, in unscaled pixels
+const STRIP_GAP: u32 = 2;
+
 /// Apply a palette cycle rotation step to a palette.
 ///
 /// Given a cycle with tokens [A, B, C] and step 1:
@@ -57,11 +62,9 @@ pub fn apply_cycles_step(
     let mut result = original_palette.clone();
 
     for cycle in cycles {
-        // Each cycle rotates independently at its own rate
-        let cycle_len = cycle.cycle_length();
-        if cycle_len > 0 {
-            let cycle_step = step % cycle_len;
-            result = apply_cycle_step(&result, cycle, cycle_step);
+        // Each cycle rotates independently at its own rate and direction
+        if cycle.cycle_length() > 0 {
+            result = apply_cycle_step(&result, cycle, cycle.rotation_at(step));
         }
     }
 
@@ -70,17 +73,18 @@ pub fn apply_cycles_step(
 
 /// Calculate the total number of frames needed to complete all cycle animations.
 ///
-/// Returns the LCM of all cycle lengths to ensure all cycles complete at least once.
+/// Returns the LCM of all cycle periods to ensure all cycles complete at least once.
+/// A ping-pong cycle of `n` tokens has a period of `2n - 2` steps.
 /// If no cycles, returns 1 (single frame).
 pub fn calculate_total_frames(cycles: &[PaletteCycle]) -> usize {
     if cycles.is_empty() {
         return 1;
     }
 
-    // Calculate LCM of all cycle lengths
+    // Calculate LCM of all cycle periods
     let mut total = 1usize;
     for cycle in cycles {
-        let len = cycle.cycle_length();
+        let len = cycle.period();
         if len > 0 {
             total = lcm(total, len);
         }
@@ -89,12 +93,34 @@ pub fn calculate_total_frames(cycles: &[PaletteCycle]) -> usize {
     total.max(1)
 }
 
-/// Calculate least common multiple of two numbers.
+/// Frames to render for `animation`'s palette cycles, capped at `max_frames`.
+///
+/// Returns the capped frame count and, when the LCM of the cycle periods is
+/// over the cap, a warning naming the full count so callers can truncate
+/// leniently or fail in strict mode.
+pub fn capped_total_frames(animation: &Animation, max_frames: usize) -> (usize, Option<String>) {
+    let cycles = animation.palette_cycles();
+    let total = calculate_total_frames(cycles);
+    if total <= max_frames {
+        return (total, None);
+    }
+    let periods: Vec<String> = cycles.iter().map(|c| c.period().to_string()).collect();
+    let warning = format!(
+        "Animation '{}' palette cycles need {} frames (LCM of periods {}), truncated to {} (limits.max_frames)",
+        animation.name,
+        total,
+        periods.join(", "),
+        max_frames
+    );
+    (max_frames, Some(warning))
+}
+
+/// Calculate least common multiple of two numbers, saturating on overflow.
 fn lcm(a: usize, b: usize) -> usize {
     if a == 0 || b == 0 {
         return 0;
     }
-    (a * b) / gcd(a, b)
+    (a / gcd(a, b)).saturating_mul(b)
 }
 
 /// Calculate greatest common divisor using Euclidean algorithm.
@@ -110,17 +136,20 @@ fn gcd(mut a: usize, mut b: usize) -> usize {
 /// Generate all frames for a palette-cycle animation.
 ///
 /// For each frame, applies the palette cycle rotation and renders the sprite.
+/// At most `max_frames` frames are rendered (see [`capped_total_frames`]).
 /// Returns a vector of rendered frames.
 pub fn generate_cycle_frames(
     sprite: &Sprite,
     base_palette: &HashMap<String, String>,
     animation: &Animation,
+    max_frames: usize,
 ) -> (Vec<RgbaImage>, Vec<String>) {
     let mut frames = Vec::new();
     let mut all_warnings = Vec::new();
 
     let cycles = animation.palette_cycles();
-    let total_frames = calculate_total_frames(cycles);
+    let (total_frames, cap_warning) = capped_total_frames(animation, max_frames);
+    all_warnings.extend(cap_warning);
 
     for step in 0..total_frames {
         // Apply all cycle rotations for this step
@@ -157,10 +186,104 @@ pub fn get_cycle_duration(animation: &Animation) -> u32 {
     animation.duration_ms()
 }
 
+/// One row of a palette cycle strip: rendered steps and their durations
+#[derive(Debug, Clone)]
+pub struct CycleStripRow {
+    /// Rendered steps, left to right
+    pub frames: Vec<RgbaImage>,
+    /// Duration of each step in milliseconds
+    pub durations: Vec<u32>,
+}
+
+/// Rows of a palette cycle preview strip for `animation`.
+///
+/// Each cycle gets a row showing one period of its steps with only that
+/// cycle rotating, labeled with the cycle's step duration. With `timeline`,
+/// a last row shows the combined steps playback goes through (the LCM of
+/// the periods, capped at `max_frames`).
+pub fn cycle_strip_rows(
+    sprite: &Sprite,
+    base_palette: &HashMap<String, String>,
+    animation: &Animation,
+    timeline: bool,
+    max_frames: usize,
+) -> (Vec<CycleStripRow>, Vec<String>) {
+    let mut rows = Vec::new();
+    let mut all_warnings = Vec::new();
+
+    for cycle in animation.palette_cycles() {
+        let steps = cycle.period().min(max_frames);
+        let mut frames = Vec::with_capacity(steps);
+        for step in 0..steps {
+            let palette = apply_cycle_step(base_palette, cycle, cycle.rotation_at(step));
+            let (image, warnings) = render_sprite(sprite, &palette);
+            for w in warnings {
+                if !all_warnings.contains(&w.message) {
+                    all_warnings.push(w.message);
+                }
+            }
+            frames.push(image);
+        }
+        let durations = vec![cycle.duration_ms(animation.duration_ms()); frames.len()];
+        rows.push(CycleStripRow { frames, durations });
+    }
+
+    if timeline {
+        let (frames, warnings) = generate_cycle_frames(sprite, base_palette, animation, max_frames);
+        for w in warnings {
+            if !all_warnings.contains(&w) {
+                all_warnings.push(w);
+            }
+        }
+        let durations = vec![get_cycle_duration(animation); frames.len()];
+        rows.push(CycleStripRow { frames, durations });
+    }
+
+    (rows, all_warnings)
+}
+
+/// Lay out strip rows as one image, each step scaled by `scale` with its
+/// duration in milliseconds printed underneath.
+pub fn render_cycle_strip(rows: &[CycleStripRow], scale: u32) -> RgbaImage {
+    let scale = scale.max(1);
+    let digit = (scale / 2).max(1);
+    let gap = STRIP_GAP * scale;
+    let frames = rows.iter().flat_map(|r| &r.frames);
+    let frame_width = frames.clone().map(|f| f.width()).max().unwrap_or(0) * scale;
+    let frame_height = frames.map(|f| f.height()).max().unwrap_or(0) * scale;
+    let label_width = |ms: u32| (ms.to_string().len() as u32 * 4 - 1) * digit;
+    let widest_label =
+        rows.iter().flat_map(|r| &r.durations).map(|&ms| label_width(ms)).max().unwrap_or(0);
+
+    let cell = (frame_width.max(widest_label), frame_height + digit + 5 * digit);
+    let columns = rows.iter().map(|r| r.frames.len() as u32).max().unwrap_or(0);
+    let width = (columns * (cell.0 + gap)).saturating_sub(gap);
+    let height = (rows.len() as u32 * (cell.1 + gap)).saturating_sub(gap);
+
+    let mut strip = RgbaImage::new(width.max(1), height.max(1));
+    for (row_index, row) in rows.iter().enumerate() {
+        let y = row_index as u32 * (cell.1 + gap);
+        for (column, (frame, &ms)) in row.frames.iter().zip(&row.durations).enumerate() {
+            let x = column as u32 * (cell.0 + gap);
+            let scaled = imageops::resize(
+                frame,
+                frame.width() * scale,
+                frame.height() * scale,
+                FilterType::Nearest,
+            );
+            let frame_x = x + (cell.0 - scaled.width()) / 2;
+            imageops::replace(&mut strip, &scaled, frame_x as i64, y as i64);
+            let label_x = x + (cell.0 - label_width(ms)) / 2;
+            draw_number(&mut strip, ms as usize, label_x, y + frame_height + digit, digit);
+        }
+    }
+    strip
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Duration;
+    use crate::models::{Duration, PlaybackDirection};
 
     fn make_palette(colors: &[(&str, &str)]) -> HashMap<String, String> {
         colors.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
@@ -173,6 +296,7 @@ mod tests {
         let cycle = PaletteCycle {
             tokens: vec!["{w1}".to_string(), "{w2}".to_string(), "{w3}".to_string()],
             duration: None,
+            direction: None,
         };
 
         // Step 0: no rotation
@@ -204,8 +328,11 @@ mod tests {
     fn test_apply_cycle_preserves_other_colors() {
         let palette = make_palette(&[("{w1}", "#001"), ("{w2}", "#002"), ("{static}", "#999")]);
 
-        let cycle =
-            PaletteCycle { tokens: vec!["{w1}".to_string(), "{w2}".to_string()], duration: None };
+        let cycle = PaletteCycle {
+            tokens: vec!["{w1}".to_string(), "{w2}".to_string()],
+            duration: None,
+            direction: None,
+        };
 
         let result = apply_cycle_step(&palette, &cycle, 1);
 
@@ -221,7 +348,7 @@ mod tests {
     fn test_apply_cycle_empty_cycle() {
         let palette = make_palette(&[("{a}", "#111")]);
 
-        let cycle = PaletteCycle { tokens: vec![], duration: None };
+        let cycle = PaletteCycle { tokens: vec![], duration: None, direction: None };
 
         let result = apply_cycle_step(&palette, &cycle, 5);
         assert_eq!(result.get("{a}"), Some(&"#111".to_string()));
@@ -241,10 +368,12 @@ mod tests {
             PaletteCycle {
                 tokens: vec!["{w1}".to_string(), "{w2}".to_string()],
                 duration: Some(200),
+                direction: None,
             },
             PaletteCycle {
                 tokens: vec!["{f1}".to_string(), "{f2}".to_string(), "{f3}".to_string()],
                 duration: Some(100),
+                direction: None,
             },
         ];
 
@@ -267,15 +396,21 @@ mod tests {
         let cycles1 = vec![PaletteCycle {
             tokens: vec!["{a}".to_string(), "{b}".to_string(), "{c}".to_string()],
             duration: None,
+            direction: None,
         }];
         assert_eq!(calculate_total_frames(&cycles1), 3);
 
         // Two cycles: length 2 and length 3 -> LCM = 6
         let cycles2 = vec![
-            PaletteCycle { tokens: vec!["{a}".to_string(), "{b}".to_string()], duration: None },
+            PaletteCycle {
+                tokens: vec!["{a}".to_string(), "{b}".to_string()],
+                duration: None,
+                direction: None,
+            },
             PaletteCycle {
                 tokens: vec!["{c}".to_string(), "{d}".to_string(), "{e}".to_string()],
                 duration: None,
+                direction: None,
             },
         ];
         assert_eq!(calculate_total_frames(&cycles2), 6);
@@ -285,13 +420,121 @@ mod tests {
         assert_eq!(calculate_total_frames(&cycles_empty), 1);
     }
 
+    #[test]
+    fn test_cycle_direction() {
+        let palette = make_palette(&[("{a}", "#001"), ("{b}", "#002"), ("{c}", "#003")]);
+        let tokens = vec!["{a}".to_string(), "{b}".to_string(), "{c}".to_string()];
+
+        let reverse = PaletteCycle {
+            tokens: tokens.clone(),
+            duration: None,
+            direction: Some(PlaybackDirection::Reverse),
+        };
+        // Step 1 rotates backwards: a gets c's color
+        let result = apply_cycles_step(&palette, std::slice::from_ref(&reverse), 1);
+        assert_eq!(result.get("{a}"), Some(&"#003".to_string()));
+        assert_eq!(calculate_total_frames(&[reverse]), 3);
+
+        let pingpong =
+            PaletteCycle { tokens, duration: None, direction: Some(PlaybackDirection::Pingpong) };
+        let rotations: Vec<usize> = (0..5).map(|step| pingpong.rotation_at(step)).collect();
+        assert_eq!(rotations, vec![0, 1, 2, 1, 0]);
+
+        // Ping-pong over 3 tokens takes 4 steps; LCM with a 3-step cycle is 12
+        let forward = PaletteCycle {
+            tokens: vec!["{x}".to_string(), "{y}".to_string(), "{z}".to_string()],
+            duration: None,
+            direction: None,
+        };
+        assert_eq!(calculate_total_frames(&[pingpong, forward]), 12);
+    }
+
     #[test]
     fn test_lcm_gcd() {
         assert_eq!(gcd(12, 8), 4);
         assert_eq!(gcd(7, 3), 1);
         assert_eq!(lcm(3, 4), 12);
         assert_eq!(lcm(6, 8), 24);
+        assert_eq!(lcm(usize::MAX, 2), usize::MAX);
+    }
+
+    #[test]
+    fn test_capped_total_frames() {
+        let cycle = |n: usize| PaletteCycle {
+            tokens: (0..n).map(|i| format!("{{t{}}}", i)).collect(),
+            duration: None,
+            direction: None,
+        };
+        let anim = Animation {
+            name: "storm".to_string(),
+            frames: vec!["f".to_string()],
+            palette_cycle: Some(vec![cycle(7), cycle(11), cycle(13)]),
+            ..Default::default()
+        };
+        assert_eq!(capped_total_frames(&anim, 1001), (1001, None));
+
+        let (frames, warning) = capped_total_frames(&anim, 100);
+        assert_eq!(frames, 100);
+        assert_eq!(
+            warning.unwrap(),
+            "Animation 'storm' palette cycles need 1001 frames (LCM of periods 7, 11, 13), truncated to 100 (limits.max_frames)"
+        );
     }
+    #[test]
+    fn test_cycle_strip() {
+        let sprite: Sprite = serde_json::from_str(
+            r#"{"name": "pool", "size": [5, 1], "regions": {"w1": {"points": [[0, 0]]}, "w2": {"points": [[1, 0]]}, "f1": {"points": [[2, 0]]}, "f2": {"points": [[3, 0]]}, "f3": {"points": [[4, 0]]}}}"#,
+        )
+        .unwrap();
+        let palette = make_palette(&[
+            ("w1", "#000001"),
+            ("w2", "#000002"),
+            ("f1", "#F00001"),
+            ("f2", "#F00002"),
+            ("f3", "#F00003"),
+        ]);
+        let anim = Animation {
+            name: "pool".to_string(),
+            frames: vec!["pool".to_string()],
+            duration: Some(Duration::Milliseconds(100)),
+            palette_cycle: Some(vec![
+                PaletteCycle {
+                    tokens: vec!["w1".to_string(), "w2".to_string()],
+                    duration: Some(250),
+                    direction: None,
+                },
+                PaletteCycle {
+                    tokens: vec!["f1".to_string(), "f2".to_string(), "f3".to_string()],
+                    duration: None,
+                    direction: None,
+                },
+            ]),
+            ..Default::default()
+        };
+
+        let (rows, warnings) = cycle_strip_rows(&sprite, &palette, &anim, false, 1000);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].durations, vec![250, 250]);
+        assert_eq!(rows[1].durations, vec![100, 100, 100]);
+        // Only the row's own cycle rotates
+        assert_eq!(rows[0].frames[1].get_pixel(0, 0).0, [0, 0, 2, 255]);
+        assert_eq!(rows[0].frames[1].get_pixel(2, 0).0, [0xF0, 0, 1, 255]);
+
+        let (rows, _) = cycle_strip_rows(&sprite, &palette, &anim, true, 1000);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].frames.len(), 6);
+        assert_eq!(rows[2].durations[0], 250);
+
+        // 6 columns as wide as a 3-digit label (11px), 2px apart; each row is
+        // a 1px frame over a 1px gap and a 5px label
+        let strip = render_cycle_strip(&rows, 1);
+        assert_eq!(strip.dimensions(), (6 * 11 + 5 * 2, 3 * 7 + 2 * 2));
+        // Second step of the water row, centered in its cell
+        assert_eq!(strip.get_pixel(13 + 3, 0).0, [0, 0, 2, 255]);
+        assert_eq!(render_cycle_strip(&rows, 4).width(), 6 * 22 + 5 * 8);
+    }
+
     #[test]
     fn test_get_cycle_duration() {
         // Animation with cycle that has explicit duration
@@ -303,6 +546,7 @@ mod tests {
             palette_cycle: Some(vec![PaletteCycle {
                 tokens: vec!["{a}".to_string()],
                 duration: Some(150),
+                direction: None,
             }]),
             tags: None,
             frame_metadata: None,
@@ -320,6 +564,7 @@ mod tests {
             palette_cycle: Some(vec![PaletteCycle {
                 tokens: vec!["{a}".to_string()],
                 duration: None,
+                direction: None,
             }]),
             tags: None,
             frame_metadata: None,
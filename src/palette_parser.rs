@@ -392,6 +392,22 @@ mod tests {
         assert_eq!(result.colors.get("{color}"), Some(&Rgba([255, 128, 0, 255])));
     }
 
+    #[test]
+    fn test_relative_color_from_var() {
+        let raw = make_palette(&[
+            ("--base", "#336699"),
+            ("{base_ghost}", "rgb(from var(--base) r g b / 0.5)"),
+            ("{base_dark}", "lch(from var(--base) calc(l - 20) c h)"),
+        ]);
+
+        let parser = PaletteParser::new();
+        let result = parser.parse(&raw, ParseMode::Strict).unwrap();
+
+        assert_eq!(result.colors.get("{base_ghost}"), Some(&Rgba([51, 102, 153, 128])));
+        let dark = result.colors["{base_dark}"];
+        assert!(dark.0[2] < 153 && dark.0[2] > dark.0[0], "darker but still blue: {:?}", dark);
+    }
+
     // ========== Lenient mode tests ==========
 
     #[test]
@@ -0,0 +1,187 @@
+//! Palette swaps expressed as variants
+//!
+//! A palette swap recolors an existing sprite without touching its regions.
+//! The result is a [`Variant`] object that can be appended to the source file,
+//! plus a render of the recolored sprite. This backs the MCP
+//! `pixelsrc_palette_swap` tool so agents can recolor art through a stable API.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use image::RgbaImage;
+
+use crate::models::{TtpObject, Variant};
+use crate::palettes;
+use crate::parser::parse_stream;
+use crate::registry::{PaletteRegistry, SpriteRegistry};
+use crate::renderer::render_resolved;
+
+/// A requested palette swap.
+#[derive(Debug, Clone, Default)]
+pub struct PaletteSwap {
+    /// Sprite to recolor
+    pub sprite: String,
+    /// Name for the generated variant (default: `{sprite}_swap`)
+    pub name: Option<String>,
+    /// Palette whose colors replace matching tokens (`@builtin` or a palette in the source)
+    pub palette: Option<String>,
+    /// Explicit token overrides, applied after `palette`
+    pub colors: HashMap<String, String>,
+}
+
+/// Result of applying a [`PaletteSwap`].
+#[derive(Debug)]
+pub struct PaletteSwapResult {
+    /// The generated variant
+    pub variant: Variant,
+    /// The variant rendered at 1x
+    pub image: RgbaImage,
+    /// Override tokens that the base sprite's palette does not define
+    pub unused_tokens: Vec<String>,
+    /// Parse and render warnings
+    pub warnings: Vec<String>,
+}
+
+impl PaletteSwapResult {
+    /// The variant as a single JSONL line, ready to append to a .pxl file.
+    pub fn to_jsonl(&self) -> String {
+        serde_json::to_string(&TtpObject::Variant(self.variant.clone())).unwrap_or_default()
+    }
+}
+
+/// Strip optional `{...}` braces from a token name.
+fn bare_token(token: &str) -> &str {
+    token.strip_prefix('{').and_then(|t| t.strip_suffix('}')).unwrap_or(token)
+}
+
+/// Apply `swap` to the sprite it names in `source`.
+pub fn apply_palette_swap(source: &str, swap: &PaletteSwap) -> Result<PaletteSwapResult, String> {
+    let parse_result = parse_stream(Cursor::new(source));
+    let mut warnings: Vec<String> =
+        parse_result.warnings.iter().map(|w| format!("line {}: {}", w.line, w.message)).collect();
+
+    let mut palette_registry = PaletteRegistry::new();
+    let mut sprite_registry = SpriteRegistry::new();
+    for obj in parse_result.objects {
+        match obj {
+            TtpObject::Palette(p) => palette_registry.register(p),
+            TtpObject::Sprite(s) => sprite_registry.register_sprite(s),
+            _ => {}
+        }
+    }
+
+    let base = sprite_registry
+        .get_sprite(&swap.sprite)
+        .ok_or_else(|| format!("Sprite '{}' not found", swap.sprite))?;
+    let base_colors = palette_registry
+        .resolve(base, false)
+        .map_err(|e| format!("Failed to resolve palette for '{}': {}", swap.sprite, e))?
+        .palette
+        .colors;
+
+    let mut overrides: HashMap<String, String> = HashMap::new();
+    if let Some(palette_name) = &swap.palette {
+        let palette = match palette_name.strip_prefix('@') {
+            Some(builtin) => palettes::get_builtin(builtin),
+            None => palette_registry.get(palette_name).cloned(),
+        }
+        .ok_or_else(|| format!("Palette '{}' not found", palette_name))?;
+        for (token, color) in palette.colors {
+            if base_colors.contains_key(&token) {
+                overrides.insert(token, color);
+            }
+        }
+    }
+
+    let mut unused_tokens = Vec::new();
+    for (token, color) in &swap.colors {
+        let token = bare_token(token);
+        if !base_colors.contains_key(token) {
+            unused_tokens.push(token.to_string());
+        }
+        overrides.insert(token.to_string(), color.clone());
+    }
+    unused_tokens.sort();
+
+    let variant = Variant {
+        name: swap.name.clone().unwrap_or_else(|| format!("{}_swap", swap.sprite)),
+        base: swap.sprite.clone(),
+        palette: overrides,
+        regions: HashMap::new(),
+        transform: None,
+        opacity: None,
+        locale: HashMap::new(),
+    };
+
+    sprite_registry.register_variant(variant.clone());
+    let resolved = sprite_registry
+        .resolve(&variant.name, &palette_registry, false)
+        .map_err(|e| format!("Failed to resolve variant '{}': {}", variant.name, e))?;
+    warnings.extend(resolved.warnings.iter().map(|w| w.message.clone()));
+    let (image, render_warnings) = render_resolved(&resolved);
+    warnings.extend(render_warnings.into_iter().map(|w| w.message));
+
+    Ok(PaletteSwapResult { variant, image, unused_tokens, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "skin": "#FFCC99", "hair": "#663300"}}
+{"type": "palette", "name": "night", "colors": {"skin": "#334466", "cape": "#000000"}}
+{"type": "sprite", "name": "hero", "size": [2, 1], "palette": "p", "regions": {"skin": {"rect": [0, 0, 1, 1]}, "hair": {"rect": [1, 0, 1, 1]}}}"##;
+
+    #[test]
+    fn test_swap_explicit_colors() {
+        let swap = PaletteSwap {
+            sprite: "hero".to_string(),
+            colors: HashMap::from([("{skin}".to_string(), "#FF0000".to_string())]),
+            ..Default::default()
+        };
+        let result = apply_palette_swap(SOURCE, &swap).unwrap();
+        assert_eq!(result.variant.name, "hero_swap");
+        assert_eq!(result.variant.palette.get("skin").unwrap(), "#FF0000");
+        assert_eq!(result.image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(result.image.get_pixel(1, 0).0, [0x66, 0x33, 0x00, 255]);
+        assert!(result.unused_tokens.is_empty());
+    }
+
+    #[test]
+    fn test_swap_from_palette_only_overrides_known_tokens() {
+        let swap = PaletteSwap {
+            sprite: "hero".to_string(),
+            name: Some("hero_night".to_string()),
+            palette: Some("night".to_string()),
+            ..Default::default()
+        };
+        let result = apply_palette_swap(SOURCE, &swap).unwrap();
+        assert_eq!(result.variant.palette.len(), 1);
+        assert!(result.to_jsonl().contains("\"type\":\"variant\""));
+        assert!(result.to_jsonl().contains("\"name\":\"hero_night\""));
+    }
+
+    #[test]
+    fn test_swap_reports_unused_tokens() {
+        let swap = PaletteSwap {
+            sprite: "hero".to_string(),
+            colors: HashMap::from([("cape".to_string(), "#0000FF".to_string())]),
+            ..Default::default()
+        };
+        let result = apply_palette_swap(SOURCE, &swap).unwrap();
+        assert_eq!(result.unused_tokens, vec!["cape".to_string()]);
+    }
+
+    #[test]
+    fn test_swap_errors() {
+        let missing_sprite = PaletteSwap { sprite: "nope".to_string(), ..Default::default() };
+        assert!(apply_palette_swap(SOURCE, &missing_sprite).is_err());
+
+        let missing_palette = PaletteSwap {
+            sprite: "hero".to_string(),
+            palette: Some("@does-not-exist".to_string()),
+            ..Default::default()
+        };
+        assert!(apply_palette_swap(SOURCE, &missing_palette).is_err());
+    }
+}
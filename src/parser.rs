@@ -3,8 +3,14 @@
 //! Supports both single-line JSONL and multi-line JSON5 formats.
 //! JSON5 adds support for comments, trailing commas, and unquoted keys.
 
+use crate::conditions::{take_condition, WHEN_FIELD};
+use crate::extends::resolve_extends;
 use crate::models::{TtpObject, Warning};
+use crate::stamp::resolve_stamps;
+use crate::variables::VariableRegistry;
 use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
 use thiserror::Error;
 
 /// Error type for parsing failures.
@@ -20,6 +26,8 @@ pub struct ParseError {
 pub struct ParseResult {
     pub objects: Vec<TtpObject>,
     pub warnings: Vec<Warning>,
+    /// 1-based source line range of each object in `objects`
+    pub spans: Vec<Range<usize>>,
 }
 
 /// Parse a single JSON5 string into a TtpObject.
@@ -30,80 +38,58 @@ pub fn parse_line(line: &str, line_number: usize) -> Result<TtpObject, ParseErro
     json5::from_str(line).map_err(|e| ParseError { message: e.to_string(), line: line_number })
 }
 
-/// Parse a stream of JSON5 objects into Pixelsrc objects.
-///
-/// Supports both formats:
-/// - Single-line JSONL (one JSON5 object per line)
-/// - Multi-line JSON5 (objects can span multiple lines, separated by whitespace)
-///
-/// JSON5 features supported:
-/// - Comments (// single-line and /* multi-line */)
-/// - Trailing commas in arrays and objects
-/// - Unquoted object keys
-///
-/// Collects warnings for malformed objects and continues parsing.
-pub fn parse_stream<R: Read>(reader: R) -> ParseResult {
-    use std::io::BufRead;
-
-    let mut result = ParseResult::default();
-    let buf_reader = std::io::BufReader::new(reader);
-    let mut lines = buf_reader.lines();
-
-    let mut accumulator = String::new();
-    let mut start_line = 1;
-    let mut current_line = 1;
-    let mut brace_depth = 0;
-    let mut bracket_depth = 0;
-    let mut in_string = false;
-    let mut escape_next = false;
-    let mut in_multi_line_comment = false;
+/// Parse one complete object.
+fn parse_object(text: &str) -> Result<TtpObject, String> {
+    json5::from_str(text).map_err(|e| e.to_string())
+}
 
-    while let Some(Ok(line)) = lines.next() {
-        // Skip empty lines when not accumulating
-        if accumulator.is_empty() && line.trim().is_empty() {
-            current_line += 1;
-            continue;
-        }
+/// Parse one complete object as an untyped JSON value, like [`parse_object`].
+fn parse_value(text: &str) -> Result<serde_json::Value, String> {
+    json5::from_str(text).map_err(|e| e.to_string())
+}
 
-        // Skip standalone comment lines when not accumulating
-        if accumulator.is_empty() {
-            let trimmed = line.trim();
-            if trimmed.starts_with("//") || trimmed.starts_with("/*") {
-                current_line += 1;
-                continue;
-            }
-        }
+/// Whether `line` is skipped between objects: blank or a standalone comment.
+fn is_skippable(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("/*")
+}
 
-        // Add line to accumulator
-        if !accumulator.is_empty() {
-            accumulator.push('\n');
-        }
-        accumulator.push_str(&line);
+/// Tracks brace/bracket depth across lines to detect complete objects.
+///
+/// Comments and strings are tracked so braces inside them don't count.
+#[derive(Debug, Default)]
+struct ObjectScanner {
+    brace_depth: i32,
+    bracket_depth: i32,
+    in_string: bool,
+    escape_next: bool,
+    in_multi_line_comment: bool,
+}
 
-        // Track brace/bracket depth to detect complete objects
-        // Must handle comments to avoid counting braces inside them
+impl ObjectScanner {
+    fn scan_line(&mut self, line: &str) {
         let mut in_single_line_comment = false;
         let mut prev_char: Option<char> = None;
 
         for ch in line.chars() {
             // Handle multi-line comment end
-            if in_multi_line_comment {
+            if self.in_multi_line_comment {
                 if prev_char == Some('*') && ch == '/' {
-                    in_multi_line_comment = false;
+                    self.in_multi_line_comment = false;
                 }
                 prev_char = Some(ch);
                 continue;
             }
 
             // Check for comment starts (when not in string)
-            if !in_string && !in_single_line_comment {
+            if !self.in_string && !in_single_line_comment {
                 if prev_char == Some('/') && ch == '/' {
                     in_single_line_comment = true;
                     prev_char = Some(ch);
                     continue;
                 }
                 if prev_char == Some('/') && ch == '*' {
-                    in_multi_line_comment = true;
+                    self.in_multi_line_comment = true;
                     prev_char = Some(ch);
                     continue;
                 }
@@ -116,38 +102,180 @@ pub fn parse_stream<R: Read>(reader: R) -> ParseResult {
                 continue;
             }
 
-            if escape_next {
-                escape_next = false;
+            if self.escape_next {
+                self.escape_next = false;
                 continue;
             }
 
             match ch {
-                '\\' if in_string => escape_next = true,
-                '"' if !in_string => in_string = true,
-                '"' if in_string => in_string = false,
-                '{' if !in_string => brace_depth += 1,
-                '}' if !in_string => brace_depth -= 1,
-                '[' if !in_string => bracket_depth += 1,
-                ']' if !in_string => bracket_depth -= 1,
+                '\\' if self.in_string => self.escape_next = true,
+                '"' if !self.in_string => self.in_string = true,
+                '"' if self.in_string => self.in_string = false,
+                '{' if !self.in_string => self.brace_depth += 1,
+                '}' if !self.in_string => self.brace_depth -= 1,
+                '[' if !self.in_string => self.bracket_depth += 1,
+                ']' if !self.in_string => self.bracket_depth -= 1,
                 _ => {}
             }
         }
+    }
 
-        // Try to parse when braces are balanced
-        if brace_depth == 0 && bracket_depth == 0 && !accumulator.trim().is_empty() {
-            match json5::from_str::<TtpObject>(&accumulator) {
-                Ok(obj) => result.objects.push(obj),
-                Err(e) => {
-                    result.warnings.push(Warning { message: e.to_string(), line: start_line });
-                    // Stop parsing after error - can't reliably find next object boundary
-                    return result;
+    /// Whether every brace and bracket opened so far has been closed.
+    fn balanced(&self) -> bool {
+        self.brace_depth == 0 && self.bracket_depth == 0
+    }
+
+    /// Reset string state once an object has been parsed.
+    fn end_object(&mut self) {
+        self.in_string = false;
+        self.escape_next = false;
+    }
+}
+
+/// Parse `text` and record the object, or a warning on failure.
+///
+/// Objects whose `when` condition doesn't hold for the current defines are
+/// skipped. `calc()` expressions in the object's fields are evaluated
+/// against the `--` variables of palettes parsed earlier, which are added
+/// to `variables` as each palette is recorded.
+///
+/// Returns `false` if the object is malformed. A bad condition or an
+/// expression that fails to evaluate only skips its object, since the
+/// object boundary is still known.
+fn push_object(
+    result: &mut ParseResult,
+    variables: &mut VariableRegistry,
+    text: &str,
+    span: Range<usize>,
+) -> bool {
+    let parsed = if text.contains("calc(") || text.contains(WHEN_FIELD) {
+        match parse_value(text) {
+            Ok(mut value) => {
+                let evaluated = take_condition(&mut value).and_then(|active| {
+                    if !active {
+                        return Ok(false);
+                    }
+                    variables.evaluate_expressions(&mut value).map_err(|e| e.to_string())?;
+                    Ok(true)
+                });
+                match evaluated {
+                    Ok(true) => serde_json::from_value(value).map_err(|e| e.to_string()),
+                    Ok(false) => return true,
+                    Err(message) => {
+                        result.warnings.push(Warning { message, line: span.start });
+                        return true;
+                    }
+                }
+            }
+            Err(message) => Err(message),
+        }
+    } else {
+        parse_object(text)
+    };
+
+    match parsed {
+        Ok(obj) => {
+            if let TtpObject::Palette(palette) = &obj {
+                for (name, value) in
+                    palette.colors.iter().filter(|(name, _)| name.starts_with("--"))
+                {
+                    variables.define(name, value);
                 }
             }
+            result.objects.push(obj);
+            result.spans.push(span);
+            true
+        }
+        Err(message) => {
+            result.warnings.push(Warning { message, line: span.start });
+            false
+        }
+    }
+}
+
+/// Resolve document-level references once every object has been parsed.
+///
+/// Sprites that `extends` a template are flattened; a sprite whose template
+/// can't be resolved is kept as written, with a warning at its first line.
+/// Stamps are then drawn into the regions of the sprites that place them.
+/// A sprite still without a palette afterwards is dropped with a warning.
+fn finish(mut result: ParseResult) -> ParseResult {
+    for (index, message) in resolve_extends(&mut result.objects) {
+        result.warnings.push(Warning { message, line: result.spans[index].start });
+    }
+    for (index, message) in resolve_stamps(&mut result.objects) {
+        result.warnings.push(Warning { message, line: result.spans[index].start });
+    }
+
+    let objects = std::mem::take(&mut result.objects);
+    let spans = std::mem::take(&mut result.spans);
+    for (obj, span) in objects.into_iter().zip(spans) {
+        if let TtpObject::Sprite(sprite) = &obj {
+            if sprite.palette_unset() {
+                let message = format!("Sprite '{}' has no palette", sprite.name);
+                result.warnings.push(Warning { message, line: span.start });
+                continue;
+            }
+        }
+        result.objects.push(obj);
+        result.spans.push(span);
+    }
+    result
+}
 
+/// Parse a stream of JSON5 objects into Pixelsrc objects.
+///
+/// Supports both formats:
+/// - Single-line JSONL (one JSON5 object per line)
+/// - Multi-line JSON5 (objects can span multiple lines, separated by whitespace)
+///
+/// JSON5 features supported:
+/// - Comments (// single-line and /* multi-line */)
+/// - Trailing commas in arrays and objects
+/// - Unquoted object keys
+///
+/// Collects warnings for malformed objects and continues parsing.
+///
+/// When the whole input is already in memory, [`parse_str`] gives the same
+/// result without copying it line by line.
+pub fn parse_stream<R: Read>(reader: R) -> ParseResult {
+    use std::io::BufRead;
+
+    let mut result = ParseResult::default();
+    let buf_reader = std::io::BufReader::new(reader);
+    let mut lines = buf_reader.lines();
+
+    let mut accumulator = String::new();
+    let mut start_line = 1;
+    let mut current_line = 1;
+    let mut scanner = ObjectScanner::default();
+    let mut variables = VariableRegistry::new();
+
+    while let Some(Ok(line)) = lines.next() {
+        // Skip empty and standalone comment lines when not accumulating
+        if accumulator.is_empty() && is_skippable(&line) {
+            current_line += 1;
+            continue;
+        }
+
+        // Add line to accumulator
+        if accumulator.is_empty() {
+            start_line = current_line;
+        } else {
+            accumulator.push('\n');
+        }
+        accumulator.push_str(&line);
+        scanner.scan_line(&line);
+
+        // Try to parse when braces are balanced
+        if scanner.balanced() && !accumulator.trim().is_empty() {
+            // Stop parsing after error - can't reliably find next object boundary
+            if !push_object(&mut result, &mut variables, &accumulator, start_line..current_line + 1)
+            {
+                return finish(result);
+            }
             accumulator.clear();
-            start_line = current_line + 1;
-            in_string = false;
-            escape_next = false;
+            scanner.end_object();
         }
 
         current_line += 1;
@@ -155,15 +283,69 @@ pub fn parse_stream<R: Read>(reader: R) -> ParseResult {
 
     // Handle any remaining accumulated content
     if !accumulator.trim().is_empty() {
-        match json5::from_str::<TtpObject>(&accumulator) {
-            Ok(obj) => result.objects.push(obj),
-            Err(e) => {
-                result.warnings.push(Warning { message: e.to_string(), line: start_line });
+        push_object(&mut result, &mut variables, &accumulator, start_line..current_line);
+    }
+
+    finish(result)
+}
+
+/// Parse objects from a string that is already in memory.
+///
+/// Same results as [`parse_stream`], but each object is parsed from a slice
+/// of `input` instead of being reassembled line by line into a new string.
+/// Parsed objects still own their strings: `json5` doesn't deserialize
+/// borrowed data, so there is no zero-copy object mode.
+pub fn parse_str(input: &str) -> ParseResult {
+    let mut result = ParseResult::default();
+    let mut object_start: Option<usize> = None;
+    let mut start_line = 1;
+    let mut current_line = 1;
+    let mut offset = 0;
+    let mut scanner = ObjectScanner::default();
+    let mut variables = VariableRegistry::new();
+
+    for raw in input.split_inclusive('\n') {
+        let line_start = offset;
+        offset += raw.len();
+        let line = raw.strip_suffix('\n').unwrap_or(raw);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        // Skip empty and standalone comment lines when not inside an object
+        if object_start.is_none() && is_skippable(line) {
+            current_line += 1;
+            continue;
+        }
+
+        let start = *object_start.get_or_insert_with(|| {
+            start_line = current_line;
+            line_start
+        });
+        scanner.scan_line(line);
+
+        let text = &input[start..line_start + line.len()];
+        if scanner.balanced() && !text.trim().is_empty() {
+            // Stop parsing after error - can't reliably find next object boundary
+            if !push_object(&mut result, &mut variables, text, start_line..current_line + 1) {
+                return finish(result);
             }
+            object_start = None;
+            scanner.end_object();
         }
+
+        current_line += 1;
     }
 
-    result
+    // Handle any remaining unbalanced content
+    if let Some(start) = object_start {
+        push_object(&mut result, &mut variables, &input[start..], start_line..current_line);
+    }
+
+    finish(result)
+}
+
+/// Open a file and parse it with [`parse_stream`], a line at a time.
+pub fn parse_file(path: &Path) -> std::io::Result<ParseResult> {
+    Ok(parse_stream(std::fs::File::open(path)?))
 }
 
 #[cfg(test)]
@@ -307,6 +489,7 @@ mod tests {
         let result = parse_stream(Cursor::new(input));
         assert_eq!(result.objects.len(), 3);
         assert!(result.warnings.is_empty());
+        assert_eq!(result.spans, [1..2, 2..9, 9..10]);
     }
 
     #[test]
@@ -321,6 +504,7 @@ mod tests {
         let result = parse_stream(Cursor::new(input));
         assert_eq!(result.objects.len(), 3);
         assert!(result.warnings.is_empty());
+        assert_eq!(result.spans, [1..2, 4..5, 6..7]);
     }
 
     #[test]
@@ -519,4 +703,114 @@ mod tests {
             _ => panic!("Expected sprite as second object"),
         }
     }
+    #[test]
+    #[serial]
+    fn test_parse_str_matches_stream() {
+        use std::fs;
+        use std::path::Path;
+
+        for dir in ["tests/fixtures/valid", "tests/fixtures/invalid"] {
+            let Ok(entries) = fs::read_dir(Path::new(dir)) else {
+                continue; // Skip if fixtures not available
+            };
+            for entry in entries {
+                let path = entry.unwrap().path();
+                if !path.extension().is_some_and(|e| e == "jsonl" || e == "pxl") {
+                    continue;
+                }
+                let input = fs::read_to_string(&path).unwrap();
+                let streamed = parse_file(&path).unwrap();
+                let in_memory = parse_str(&input);
+                assert_eq!(in_memory.objects, streamed.objects, "objects differ in {:?}", path);
+                assert_eq!(in_memory.spans, streamed.spans, "spans differ in {:?}", path);
+                assert_eq!(in_memory.warnings, streamed.warnings, "warnings differ in {:?}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_str_multiline() {
+        let input = "// header\r\n{type: 'palette', name: 'a',\r\n  colors: {x: '#FFF',},}\r\n\r\n{\"type\": \"palette\", \"name\": \"b\", \"colors\": {}}\n{\"type\": \"palette\"";
+        let result = parse_str(input);
+        assert_eq!(result.objects.len(), 2);
+        assert_eq!(result.spans, [2..4, 5..6]);
+        // The unterminated trailing object is reported at its first line
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].line, 6);
+        assert_eq!(result.objects, parse_stream(Cursor::new(input)).objects);
+    }
+
+    #[test]
+    fn test_parse_stream_evaluates_expressions() {
+        let input = r##"{"type": "palette", "name": "p", "colors": {"--tile": "8", "--beat": "240", "x": "#FFF"}}
+{"type": "sprite", "name": "a", "size": ["calc(var(--tile) * 4)", 32], "palette": "p", "regions": {"x": {"rect": [0, 0, "calc(var(--tile) / 2)", 1]}}}
+{"type": "sprite", "name": "bad", "size": ["calc(var(--tile) / 0)", 1], "palette": "p", "regions": {}}
+{"type": "animation", "name": "walk", "frames": ["a"], "duration": "calc(var(--beat) / 2)"}"##;
+        let result = parse_str(input);
+        // The bad expression skips its object without stopping the stream
+        assert_eq!(result.objects.len(), 3);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].line, 3);
+        assert!(result.warnings[0].message.contains("division by zero"));
+
+        match &result.objects[1] {
+            TtpObject::Sprite(s) => assert_eq!(s.size, Some([32, 32])),
+            _ => panic!("Expected sprite"),
+        }
+        match &result.objects[2] {
+            TtpObject::Animation(a) => assert_eq!(a.duration_ms(), 120),
+            _ => panic!("Expected animation"),
+        }
+        assert_eq!(result.objects, parse_stream(Cursor::new(input)).objects);
+    }
+
+    #[test]
+    fn test_parse_stream_skips_inactive_objects() {
+        let input = r##"{"type": "palette", "name": "p", "when": "hd", "colors": {"--size": "32"}}
+{"type": "palette", "name": "p", "when": "!hd", "colors": {"--size": "16"}}
+{"type": "sprite", "name": "bad", "when": "hd &&", "size": [1, 1], "palette": "p", "regions": {}}
+{"type": "sprite", "name": "hero", "size": ["calc(var(--size))", 8], "palette": "p", "regions": {}}"##;
+        let sizes = || {
+            let result = parse_str(input);
+            assert_eq!(result.objects.len(), 2);
+            assert_eq!(result.warnings.len(), 1);
+            assert!(result.warnings[0].message.contains("invalid condition"));
+            match &result.objects[1] {
+                TtpObject::Sprite(s) => s.size,
+                _ => panic!("Expected sprite"),
+            }
+        };
+        assert_eq!(sizes(), Some([16, 8]));
+        let hd = crate::conditions::with_defines(["hd".to_string()], sizes);
+        assert_eq!(hd, Some([32, 8]));
+    }
+
+    #[test]
+    fn test_parse_stream_resolves_extends() {
+        let input = r##"{"type": "sprite", "name": "knight", "extends": "humanoid", "regions": {"x": {"rect": [0, 0, 1, 1]}}}
+{"type": "sprite", "name": "humanoid", "size": [4, 4], "palette": "p", "regions": {"y": {"rect": [1, 1, 2, 2]}}}
+{"type": "sprite", "name": "loose", "size": [1, 1], "regions": {}}
+{"type": "sprite", "name": "orphan", "extends": "missing", "palette": "p"}"##;
+        let result = parse_str(input);
+        let names: Vec<_> = result.objects.iter().map(|o| o.name()).collect();
+        assert_eq!(names, ["knight", "humanoid", "orphan"]);
+        match &result.objects[0] {
+            TtpObject::Sprite(s) => {
+                assert_eq!(s.size, Some([4, 4]));
+                assert!(matches!(s.palette, PaletteRef::Named(ref n) if n == "p"));
+                assert_eq!(s.regions.as_ref().map(|r| r.len()), Some(2));
+            }
+            _ => panic!("Expected sprite"),
+        }
+        let warnings: Vec<_> =
+            result.warnings.iter().map(|w| (w.line, w.message.as_str())).collect();
+        assert_eq!(
+            warnings,
+            [
+                (4, "Sprite 'orphan' extends unknown sprite 'missing'"),
+                (3, "Sprite 'loose' has no palette"),
+            ]
+        );
+        assert_eq!(result.spans, [1..2, 2..3, 4..5]);
+    }
 }
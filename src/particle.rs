@@ -27,6 +27,7 @@
 use image::{Rgba, RgbaImage};
 
 use crate::models::Particle;
+use crate::rng::Rng;
 
 /// A live particle instance during simulation.
 #[derive(Debug, Clone)]
@@ -64,47 +65,6 @@ impl LiveParticle {
     }
 }
 
-/// A simple deterministic PRNG (xorshift64) for reproducible particle effects.
-#[derive(Debug, Clone)]
-struct Rng {
-    state: u64,
-}
-
-impl Rng {
-    fn new(seed: u64) -> Self {
-        // Ensure non-zero state
-        Self { state: if seed == 0 { 0x12345678_9ABCDEF0 } else { seed } }
-    }
-
-    /// Generate next u64 value.
-    fn next_u64(&mut self) -> u64 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        x
-    }
-
-    /// Generate a random f64 in [0.0, 1.0).
-    fn next_f64(&mut self) -> f64 {
-        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
-    }
-
-    /// Generate a random f64 in [min, max].
-    fn range(&mut self, min: f64, max: f64) -> f64 {
-        min + (max - min) * self.next_f64()
-    }
-
-    /// Generate a random u32 in [min, max].
-    fn range_u32(&mut self, min: u32, max: u32) -> u32 {
-        if min >= max {
-            return min;
-        }
-        min + (self.next_u64() % (max - min + 1) as u64) as u32
-    }
-}
-
 /// Particle system simulation and rendering engine.
 ///
 /// Takes a particle definition and its sprite image, then simulates and
@@ -120,9 +80,11 @@ pub struct ParticleEngine<'a> {
 
 impl<'a> ParticleEngine<'a> {
     /// Create a new particle engine from a definition and sprite image.
+    ///
+    /// The emitter's random stream is seeded from its `seed` (or the system
+    /// name) and the current global seed, see [`crate::rng`].
     pub fn new(def: &'a Particle, sprite: &'a RgbaImage) -> Self {
-        let seed = def.emitter.seed.unwrap_or(42);
-        Self { def, sprite, rng: Rng::new(seed) }
+        Self { def, sprite, rng: Rng::for_object(&def.name, def.emitter.seed) }
     }
 
     /// Generate all frames for the particle system.
@@ -1,10 +1,13 @@
-//! SVG-lite path parser for basic path commands
+//! SVG-lite path parser for region paths
 //!
 //! Supports a subset of SVG path syntax:
 //! - M (moveto)
 //! - L (lineto)
 //! - H (horizontal lineto)
 //! - V (vertical lineto)
+//! - Q (quadratic Bézier)
+//! - C (cubic Bézier)
+//! - A (elliptical arc)
 //! - Z (closepath)
 //!
 //! Each command supports both absolute (uppercase) and relative (lowercase) variants.
@@ -34,6 +37,9 @@ pub enum PathError {
 
 /// Parse an SVG-lite path string into polygon vertices.
 ///
+/// All subpaths are concatenated into a single vertex list, and curves are
+/// flattened into line segments (see [`parse_path_contours`]).
+///
 /// # Supported Commands
 ///
 /// - `M x,y` - Move to absolute position
@@ -44,6 +50,9 @@ pub enum PathError {
 /// - `h dx` - Horizontal line to relative x
 /// - `V y` - Vertical line to absolute y
 /// - `v dy` - Vertical line to relative y
+/// - `Q x1,y1 x,y` / `q` - Quadratic Bézier curve
+/// - `C x1,y1 x2,y2 x,y` / `c` - Cubic Bézier curve
+/// - `A rx,ry rotation large-arc,sweep x,y` / `a` - Elliptical arc
 /// - `Z` or `z` - Close path (line back to start)
 ///
 /// # Examples
@@ -64,12 +73,40 @@ pub enum PathError {
 ///
 /// Returns `PathError` if the path is invalid or malformed.
 pub fn parse_path(path: &str) -> Result<Vec<[f32; 2]>, PathError> {
+    Ok(parse_path_contours(path)?.concat())
+}
+
+/// Parse an SVG-lite path string into one vertex list per subpath.
+///
+/// Each `M`/`m` (and any drawing after a `Z`) starts a new contour. Curves and
+/// arcs are flattened into segments roughly one pixel long, so the contours can
+/// be filled directly with [`crate::shapes::rasterize_path`].
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::path::parse_path_contours;
+///
+/// // A square with a square hole
+/// let contours = parse_path_contours("M0,0 H8 V8 H0 Z M2,2 H6 V6 H2 Z").unwrap();
+/// assert_eq!(contours.len(), 2);
+///
+/// // A quadratic curve ends exactly on its end point
+/// let contours = parse_path_contours("M0,8 Q4,0 8,8").unwrap();
+/// assert_eq!(contours[0].last(), Some(&[8.0, 8.0]));
+/// ```
+///
+/// # Errors
+///
+/// Returns `PathError` if the path is invalid or malformed.
+pub fn parse_path_contours(path: &str) -> Result<Vec<Vec<[f32; 2]>>, PathError> {
     let path = path.trim();
     if path.is_empty() {
         return Err(PathError::Empty);
     }
 
-    let mut vertices = Vec::new();
+    let mut contours: Vec<Vec<[f32; 2]>> = Vec::new();
+    let mut vertices: Vec<[f32; 2]> = Vec::new();
     let mut current_pos = [0.0, 0.0];
     let mut start_pos = [0.0, 0.0];
     let mut tokens = tokenize_path(path);
@@ -83,12 +120,18 @@ pub fn parse_path(path: &str) -> Result<Vec<[f32; 2]>, PathError> {
             return Err(PathError::MissingMoveto);
         }
 
+        // Drawing after a close path starts a new subpath at the start point
+        if vertices.is_empty() && has_moveto && !matches!(cmd.as_str(), "M" | "m" | "Z" | "z") {
+            vertices.push(current_pos);
+        }
+
         match cmd.as_str() {
             "M" => {
                 // Absolute moveto
                 let (x, y) = parse_coordinate_pair(&mut tokens, &cmd)?;
                 current_pos = [x, y];
                 start_pos = current_pos;
+                finish_contour(&mut contours, &mut vertices);
                 vertices.push(current_pos);
                 has_moveto = true;
             }
@@ -103,6 +146,7 @@ pub fn parse_path(path: &str) -> Result<Vec<[f32; 2]>, PathError> {
                     current_pos = [dx, dy];
                 }
                 start_pos = current_pos;
+                finish_contour(&mut contours, &mut vertices);
                 vertices.push(current_pos);
                 has_moveto = true;
             }
@@ -143,13 +187,43 @@ pub fn parse_path(path: &str) -> Result<Vec<[f32; 2]>, PathError> {
                 current_pos[1] += dy;
                 vertices.push(current_pos);
             }
+            "Q" | "q" => {
+                // Quadratic Bézier: control point, end point
+                let n = parse_numbers(&mut tokens, &cmd, 4)?;
+                let origin = if cmd == "q" { current_pos } else { [0.0, 0.0] };
+                let control = offset(origin, n[0], n[1]);
+                let end = offset(origin, n[2], n[3]);
+                flatten_quadratic(current_pos, control, end, &mut vertices);
+                current_pos = end;
+            }
+            "C" | "c" => {
+                // Cubic Bézier: two control points, end point
+                let n = parse_numbers(&mut tokens, &cmd, 6)?;
+                let origin = if cmd == "c" { current_pos } else { [0.0, 0.0] };
+                let c1 = offset(origin, n[0], n[1]);
+                let c2 = offset(origin, n[2], n[3]);
+                let end = offset(origin, n[4], n[5]);
+                flatten_cubic(current_pos, c1, c2, end, &mut vertices);
+                current_pos = end;
+            }
+            "A" | "a" => {
+                // Elliptical arc: rx ry x-axis-rotation large-arc-flag sweep-flag x y
+                let n = parse_numbers(&mut tokens, &cmd, 7)?;
+                let origin = if cmd == "a" { current_pos } else { [0.0, 0.0] };
+                let end = offset(origin, n[5], n[6]);
+                let arc = Arc {
+                    radii: [n[0], n[1]],
+                    rotation: n[2],
+                    large_arc: n[3] != 0.0,
+                    sweep: n[4] != 0.0,
+                };
+                flatten_arc(current_pos, end, &arc, &mut vertices);
+                current_pos = end;
+            }
             "Z" | "z" => {
-                // Close path - no coordinates needed
-                // Don't add start_pos again if we're already there
-                if current_pos != start_pos {
-                    current_pos = start_pos;
-                    // Z doesn't add a vertex, it just closes to start
-                }
+                // Close path - no coordinates needed, the contour closes implicitly
+                current_pos = start_pos;
+                finish_contour(&mut contours, &mut vertices);
             }
             _ => {
                 return Err(PathError::UnknownCommand(cmd.chars().next().unwrap_or('?')));
@@ -157,7 +231,128 @@ pub fn parse_path(path: &str) -> Result<Vec<[f32; 2]>, PathError> {
         }
     }
 
-    Ok(vertices)
+    finish_contour(&mut contours, &mut vertices);
+    Ok(contours)
+}
+
+/// Move the vertices of the current subpath into `contours`.
+fn finish_contour(contours: &mut Vec<Vec<[f32; 2]>>, vertices: &mut Vec<[f32; 2]>) {
+    if !vertices.is_empty() {
+        contours.push(std::mem::take(vertices));
+    }
+}
+
+fn offset(origin: [f32; 2], dx: f32, dy: f32) -> [f32; 2] {
+    [origin[0] + dx, origin[1] + dy]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (b[0] - a[0]).hypot(b[1] - a[1])
+}
+
+/// Number of segments for a curve of roughly `length` pixels (about one per pixel).
+fn segment_count(length: f32) -> usize {
+    (length.ceil() as usize).clamp(1, 1024)
+}
+
+/// Flatten a quadratic Bézier from `p0`, pushing every point after `p0`.
+fn flatten_quadratic(p0: [f32; 2], p1: [f32; 2], p2: [f32; 2], out: &mut Vec<[f32; 2]>) {
+    let n = segment_count(distance(p0, p1) + distance(p1, p2));
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let u = 1.0 - t;
+        let point = |k: usize| u * u * p0[k] + 2.0 * u * t * p1[k] + t * t * p2[k];
+        out.push(if i == n { p2 } else { [point(0), point(1)] });
+    }
+}
+
+/// Flatten a cubic Bézier from `p0`, pushing every point after `p0`.
+fn flatten_cubic(p0: [f32; 2], p1: [f32; 2], p2: [f32; 2], p3: [f32; 2], out: &mut Vec<[f32; 2]>) {
+    let n = segment_count(distance(p0, p1) + distance(p1, p2) + distance(p2, p3));
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let u = 1.0 - t;
+        let point = |k: usize| {
+            u * u * u * p0[k]
+                + 3.0 * u * u * t * p1[k]
+                + 3.0 * u * t * t * p2[k]
+                + t * t * t * p3[k]
+        };
+        out.push(if i == n { p3 } else { [point(0), point(1)] });
+    }
+}
+
+/// Parameters of an SVG elliptical arc command.
+struct Arc {
+    radii: [f32; 2],
+    /// Rotation of the ellipse's x-axis in degrees
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+}
+
+/// Flatten an elliptical arc from `from` to `to`, pushing every point after `from`.
+///
+/// Follows the SVG endpoint-to-center conversion, including scaling up radii
+/// that are too small to span the endpoints.
+fn flatten_arc(from: [f32; 2], to: [f32; 2], arc: &Arc, out: &mut Vec<[f32; 2]>) {
+    if from == to {
+        return;
+    }
+    let (mut rx, mut ry) = (arc.radii[0].abs() as f64, arc.radii[1].abs() as f64);
+    if rx == 0.0 || ry == 0.0 {
+        // Degenerate radii draw a straight line
+        out.push(to);
+        return;
+    }
+
+    let (sin_phi, cos_phi) = (arc.rotation as f64).to_radians().sin_cos();
+    let (x1, y1) = (from[0] as f64, from[1] as f64);
+    let (x2, y2) = (to[0] as f64, to[1] as f64);
+
+    // Endpoints in the ellipse's rotated frame, relative to their midpoint
+    let dx = (x1 - x2) / 2.0;
+    let dy = (y1 - y2) / 2.0;
+    let x1p = cos_phi * dx + sin_phi * dy;
+    let y1p = -sin_phi * dx + cos_phi * dy;
+
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let denominator = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let sign = if arc.large_arc == arc.sweep { -1.0 } else { 1.0 };
+    let coef = sign * (numerator / denominator).max(0.0).sqrt();
+    let cxp = coef * rx * y1p / ry;
+    let cyp = -coef * ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (x1 + x2) / 2.0;
+    let cy = sin_phi * cxp + cos_phi * cyp + (y1 + y2) / 2.0;
+
+    let angle = |ux: f64, uy: f64| uy.atan2(ux);
+    let theta1 = angle((x1p - cxp) / rx, (y1p - cyp) / ry);
+    let mut delta = angle((-x1p - cxp) / rx, (-y1p - cyp) / ry) - theta1;
+    if arc.sweep && delta < 0.0 {
+        delta += std::f64::consts::TAU;
+    } else if !arc.sweep && delta > 0.0 {
+        delta -= std::f64::consts::TAU;
+    }
+
+    let n = segment_count((delta.abs() * rx.max(ry)) as f32);
+    for i in 1..=n {
+        if i == n {
+            out.push(to);
+            break;
+        }
+        let theta = theta1 + delta * i as f64 / n as f64;
+        let (sin_t, cos_t) = theta.sin_cos();
+        let x = cos_phi * rx * cos_t - sin_phi * ry * sin_t + cx;
+        let y = sin_phi * rx * cos_t + cos_phi * ry * sin_t + cy;
+        out.push([x as f32, y as f32]);
+    }
 }
 
 /// Tokenize a path string into command and number tokens
@@ -168,7 +363,8 @@ fn tokenize_path(path: &str) -> Vec<String> {
     for ch in path.chars() {
         match ch {
             // Command characters
-            'M' | 'm' | 'L' | 'l' | 'H' | 'h' | 'V' | 'v' | 'Z' | 'z' => {
+            'M' | 'm' | 'L' | 'l' | 'H' | 'h' | 'V' | 'v' | 'Q' | 'q' | 'C' | 'c' | 'A' | 'a'
+            | 'Z' | 'z' => {
                 if !current.is_empty() {
                     tokens.push(current.clone());
                     current.clear();
@@ -215,6 +411,20 @@ fn parse_coordinate_pair(tokens: &mut Vec<String>, cmd: &str) -> Result<(f32, f3
     Ok((x, y))
 }
 
+/// Parse `count` numbers for a command from tokens
+fn parse_numbers(tokens: &mut Vec<String>, cmd: &str, count: usize) -> Result<Vec<f32>, PathError> {
+    if tokens.len() < count {
+        return Err(PathError::NotEnoughCoordinates(
+            cmd.chars().next().expect("cmd is a non-empty path command"),
+        ));
+    }
+
+    tokens
+        .drain(..count)
+        .map(|s| s.parse::<f32>().map_err(|e| PathError::InvalidNumber(s.clone(), e.to_string())))
+        .collect()
+}
+
 /// Parse a single coordinate from tokens
 fn parse_single_coordinate(tokens: &mut Vec<String>, cmd: &str) -> Result<f32, PathError> {
     if tokens.is_empty() {
@@ -299,8 +509,8 @@ mod tests {
 
     #[test]
     fn test_parse_path_unknown_command() {
-        let result = parse_path("M0,0 Q10,10");
-        assert!(matches!(result, Err(PathError::UnknownCommand('Q'))));
+        let result = parse_path("M0,0 T10,10");
+        assert!(matches!(result, Err(PathError::UnknownCommand('T'))));
     }
 
     #[test]
@@ -330,4 +540,72 @@ mod tests {
         let vertices = parse_path("M0,0 L5,0 L5,5 z").unwrap();
         assert_eq!(vertices, vec![[0.0, 0.0], [5.0, 0.0], [5.0, 5.0]]);
     }
+
+    #[test]
+    fn test_parse_path_contours_split_on_moveto_and_close() {
+        let contours = parse_path_contours("M0,0 H8 V8 H0 Z M2,2 H6 V6 H2 Z").unwrap();
+        assert_eq!(contours.len(), 2);
+        assert_eq!(contours[1], vec![[2.0, 2.0], [6.0, 2.0], [6.0, 6.0], [2.0, 6.0]]);
+
+        // Drawing after Z restarts from the subpath's start point
+        let contours = parse_path_contours("M1,1 L4,1 L4,4 Z L1,6").unwrap();
+        assert_eq!(contours[1], vec![[1.0, 1.0], [1.0, 6.0]]);
+    }
+
+    #[test]
+    fn test_parse_path_quadratic() {
+        let vertices = parse_path("M0,8 Q4,0 8,8").unwrap();
+        assert_eq!(vertices.first(), Some(&[0.0, 8.0]));
+        assert_eq!(vertices.last(), Some(&[8.0, 8.0]));
+        // The curve's apex at t=0.5 is halfway to the control point
+        assert!(vertices.iter().any(|&[x, y]| x == 4.0 && y == 4.0));
+        assert!(vertices.iter().all(|&[_, y]| (4.0..=8.0).contains(&y)));
+
+        let relative = parse_path("M0,8 q4,-8 8,0").unwrap();
+        assert_eq!(vertices, relative);
+    }
+
+    #[test]
+    fn test_parse_path_cubic() {
+        let vertices = parse_path("M0,0 C0,6 6,6 6,0").unwrap();
+        assert_eq!(vertices.last(), Some(&[6.0, 0.0]));
+        let deepest = vertices.iter().map(|&[_, y]| y).fold(0.0, f32::max);
+        assert!((deepest - 4.5).abs() < 0.01, "apex at {}", deepest);
+
+        let relative = parse_path("M0,0 c0,6 6,6 6,0").unwrap();
+        assert_eq!(vertices, relative);
+    }
+
+    #[test]
+    fn test_parse_path_arc_semicircle() {
+        // Half circle of radius 4 around (4, 4), bulging downwards
+        let vertices = parse_path("M0,4 A4,4 0 0,0 8,4").unwrap();
+        assert_eq!(vertices.last(), Some(&[8.0, 4.0]));
+        for &[x, y] in &vertices {
+            let r = ((x - 4.0).powi(2) + (y - 4.0).powi(2)).sqrt();
+            assert!((r - 4.0).abs() < 0.01, "({}, {}) off circle", x, y);
+            assert!(y >= 4.0 - 1e-4);
+        }
+
+        // Sweep flag picks the other half
+        let upper = parse_path("M0,4 a4,4 0 0,1 8,0").unwrap();
+        assert!(upper.iter().all(|&[_, y]| y <= 4.0 + 1e-4));
+    }
+
+    #[test]
+    fn test_parse_path_arc_scales_small_radii() {
+        // Radius 1 can't span 8px, so it's scaled up to a semicircle of radius 4
+        let vertices = parse_path("M0,0 A1,1 0 0,1 8,0").unwrap();
+        let lowest = vertices.iter().map(|&[_, y]| y).fold(0.0, f32::min);
+        assert!((-4.0..-3.9).contains(&lowest), "apex at {}", lowest);
+    }
+
+    #[test]
+    fn test_parse_path_curve_not_enough_numbers() {
+        assert!(matches!(parse_path("M0,0 Q1,1 2"), Err(PathError::NotEnoughCoordinates('Q'))));
+        assert!(matches!(
+            parse_path("M0,0 a1,1 0 0 1 2"),
+            Err(PathError::NotEnoughCoordinates('a'))
+        ));
+    }
 }
@@ -0,0 +1,933 @@
+//! Animation playback - precomputed frame timelines for real-time players
+//!
+//! Real-time consumers (the WASM playground, editor previews) need to show
+//! the right frame for an arbitrary wall-clock time without re-rendering on
+//! every tick. This module renders every frame of an animation once, up
+//! front, and answers "which frame is visible at `t` milliseconds?" lookups.
+//!
+//! All three animation styles are supported:
+//! - Frame arrays (`frames: [...]`)
+//! - Palette cycles on a single sprite (`palette_cycle`)
+//! - CSS keyframes (`keyframes: {"0%": ..., "100%": ...}`), sampled at a fixed rate
+
+use std::collections::HashMap;
+
+use image::{Rgba, RgbaImage};
+
+use crate::config::LimitsConfig;
+use crate::models::{Animation, CssKeyframe, PlaybackDirection, Sprite, SubpixelMode};
+use crate::motion::{ease, parse_timing_function, temporal_dither, Interpolation};
+use crate::motion_preset::expand_motion;
+use crate::palette_cycle::{
+    apply_cycles_step, capped_total_frames, generate_cycle_frames, get_cycle_duration,
+};
+use crate::registry::PaletteRegistry;
+use crate::renderer::render_sprite;
+use crate::spritesheet::{align_frames, pivot_layout};
+use crate::transforms::{apply_image_transforms, parse_css_transform};
+
+/// Sampling interval used when baking CSS keyframe animations into frames (20 fps).
+pub const KEYFRAME_SAMPLE_INTERVAL_MS: u32 = 50;
+
+/// A fully rendered animation with per-frame durations.
+#[derive(Debug, Clone)]
+pub struct AnimationTimeline {
+    /// Rendered frames, all sharing the same dimensions
+    pub frames: Vec<RgbaImage>,
+    /// Display duration of each frame in milliseconds
+    pub durations: Vec<u32>,
+    /// Whether playback wraps around after the last frame
+    pub loops: bool,
+}
+
+impl AnimationTimeline {
+    /// Number of precomputed frames.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Total duration of one pass through the animation in milliseconds.
+    pub fn total_duration_ms(&self) -> u64 {
+        self.durations.iter().map(|&d| d as u64).sum()
+    }
+
+    /// Width of the frames in pixels (0 if there are no frames).
+    pub fn width(&self) -> u32 {
+        self.frames.first().map(|f| f.width()).unwrap_or(0)
+    }
+
+    /// Height of the frames in pixels (0 if there are no frames).
+    pub fn height(&self) -> u32 {
+        self.frames.first().map(|f| f.height()).unwrap_or(0)
+    }
+
+    /// Returns the index of the frame visible at `time_ms`.
+    ///
+    /// Looping timelines wrap around; non-looping timelines hold the last
+    /// frame once the end is reached. Negative times clamp to the first frame.
+    pub fn frame_index_at(&self, time_ms: f64) -> usize {
+        position_at(&self.durations, self.loops, time_ms).map_or(0, |(index, _)| index)
+    }
+
+    /// Returns the frame visible at `time_ms`, or `None` if the timeline is empty.
+    pub fn frame_at(&self, time_ms: f64) -> Option<&RgbaImage> {
+        self.frames.get(self.frame_index_at(time_ms))
+    }
+}
+
+/// The item of a pass through `durations` playing at `time_ms`, and how far
+/// through that item playback is (0.0 to 1.0).
+///
+/// Looping passes wrap around; non-looping passes hold the last item once the
+/// end is reached. Negative times clamp to the first item. Returns `None` for
+/// an empty pass.
+fn position_at(durations: &[u32], loops: bool, time_ms: f64) -> Option<(usize, f64)> {
+    if durations.is_empty() {
+        return None;
+    }
+
+    let total: u64 = durations.iter().map(|&d| d as u64).sum();
+    if total == 0 || time_ms <= 0.0 {
+        return Some((0, 0.0));
+    }
+
+    let mut t = time_ms;
+    if loops {
+        t %= total as f64;
+    } else if t >= total as f64 {
+        return Some((durations.len() - 1, 1.0));
+    }
+
+    let mut elapsed = 0.0;
+    for (i, &d) in durations.iter().enumerate() {
+        if t < elapsed + d as f64 {
+            return Some((i, (t - elapsed) / d as f64));
+        }
+        elapsed += d as f64;
+    }
+    Some((durations.len() - 1, 1.0))
+}
+
+/// Render a sprite with its palette resolved leniently, collecting warnings.
+fn render_frame_sprite(
+    sprite: &Sprite,
+    palettes: &PaletteRegistry,
+    warnings: &mut Vec<String>,
+) -> RgbaImage {
+    let resolved = palettes.resolve_lenient(sprite);
+    if let Some(w) = resolved.warning {
+        warnings.push(format!("sprite '{}': {}", sprite.name, w.message));
+    }
+    let (image, render_warnings) = render_sprite(sprite, &resolved.palette.colors);
+    for w in render_warnings {
+        warnings.push(format!("sprite '{}': {}", sprite.name, w.message));
+    }
+    image
+}
+
+/// Copy `image` onto a transparent canvas of the given size at `offset`,
+/// multiplying alpha by `opacity`. Pixels falling outside the canvas are dropped.
+fn place_on_canvas(
+    image: &RgbaImage,
+    width: u32,
+    height: u32,
+    offset: (i32, i32),
+    opacity: f64,
+) -> RgbaImage {
+    let mut canvas = RgbaImage::new(width, height);
+    let opacity = opacity.clamp(0.0, 1.0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let tx = x as i32 + offset.0;
+        let ty = y as i32 + offset.1;
+        if tx < 0 || ty < 0 || tx >= width as i32 || ty >= height as i32 {
+            continue;
+        }
+        let alpha = (pixel[3] as f64 * opacity).round() as u8;
+        canvas.put_pixel(tx as u32, ty as u32, Rgba([pixel[0], pixel[1], pixel[2], alpha]));
+    }
+    canvas
+}
+
+/// Pad every frame to the largest frame's dimensions (top-left aligned).
+fn normalize_frame_sizes(frames: Vec<RgbaImage>) -> Vec<RgbaImage> {
+    let width = frames.iter().map(|f| f.width()).max().unwrap_or(0);
+    let height = frames.iter().map(|f| f.height()).max().unwrap_or(0);
+    frames
+        .into_iter()
+        .map(|f| {
+            if f.width() == width && f.height() == height {
+                f
+            } else {
+                place_on_canvas(&f, width, height, (0, 0), 1.0)
+            }
+        })
+        .collect()
+}
+
+/// Number of evenly spaced samples a CSS keyframe animation is baked into,
+/// and its total duration in milliseconds.
+fn keyframe_samples(animation: &Animation) -> (u32, u32) {
+    let total_ms = animation.duration_ms().max(1);
+    (total_ms.div_ceil(KEYFRAME_SAMPLE_INTERVAL_MS).max(1), total_ms)
+}
+
+/// CSS keyframes prepared for sampling at any point in the animation.
+///
+/// The sprite switches at each keyframe that names one (step behaviour), while
+/// `opacity` and `offset` are interpolated using the animation's timing function.
+/// Fractional offsets are rounded, or dithered over time with `subpixel: "dither"`.
+struct KeyframeSampler<'a> {
+    keyframes: Vec<(f64, &'a CssKeyframe)>,
+    interpolation: Interpolation,
+    subpixel: SubpixelMode,
+    /// Image shown while each keyframe is the most recent one: the latest
+    /// named sprite, with the keyframe's CSS transform applied
+    posed: Vec<Option<RgbaImage>>,
+    /// Size of the largest referenced sprite
+    size: (u32, u32),
+}
+
+/// A sampled keyframe state: active keyframe, offset and opacity
+type KeyframePose = (usize, (i32, i32), f64);
+
+impl<'a> KeyframeSampler<'a> {
+    /// Render the sprites the keyframes reference, or `None` without keyframes.
+    fn new(
+        animation: &'a Animation,
+        sprites: &HashMap<String, Sprite>,
+        palettes: &PaletteRegistry,
+        warnings: &mut Vec<String>,
+    ) -> Option<Self> {
+        let keyframes = animation.sorted_keyframes();
+        if keyframes.is_empty() {
+            return None;
+        }
+
+        let interpolation = match animation.timing_function.as_deref() {
+            Some(tf) => parse_timing_function(tf).unwrap_or_else(|e| {
+                warnings.push(format!("animation '{}': {}", animation.name, e));
+                Interpolation::Linear
+            }),
+            None => Interpolation::Linear,
+        };
+
+        // Render each referenced sprite once
+        let mut rendered: HashMap<&str, RgbaImage> = HashMap::new();
+        for (_, kf) in &keyframes {
+            let Some(name) = kf.sprite.as_deref() else { continue };
+            if rendered.contains_key(name) {
+                continue;
+            }
+            match sprites.get(name) {
+                Some(sprite) => {
+                    rendered.insert(name, render_frame_sprite(sprite, palettes, warnings));
+                }
+                None => warnings.push(format!(
+                    "Animation '{}' keyframe references unknown sprite '{}'",
+                    animation.name, name
+                )),
+            }
+        }
+
+        let width = rendered.values().map(|i| i.width()).max().unwrap_or(0);
+        let height = rendered.values().map(|i| i.height()).max().unwrap_or(0);
+
+        let posed = (0..keyframes.len())
+            .map(|index| {
+                // Most recent keyframe (at or before this one) that names a sprite
+                let sprite_name = keyframes[..=index]
+                    .iter()
+                    .rev()
+                    .find_map(|(_, kf)| kf.sprite.as_deref())
+                    .or_else(|| keyframes.iter().find_map(|(_, kf)| kf.sprite.as_deref()));
+                let image = sprite_name.and_then(|name| rendered.get(name))?;
+
+                // CSS transforms are discrete, so use the active keyframe's transform as-is
+                let transformed =
+                    match keyframes[index].1.transform.as_deref().map(parse_css_transform) {
+                        Some(Ok(css)) => match css.to_transforms() {
+                            Ok(ops) => {
+                                apply_image_transforms(image, &ops, None).unwrap_or_else(|e| {
+                                    warnings.push(format!("animation '{}': {}", animation.name, e));
+                                    image.clone()
+                                })
+                            }
+                            Err(e) => {
+                                warnings.push(format!("animation '{}': {}", animation.name, e));
+                                image.clone()
+                            }
+                        },
+                        Some(Err(e)) => {
+                            warnings.push(format!("animation '{}': {}", animation.name, e));
+                            image.clone()
+                        }
+                        None => image.clone(),
+                    };
+                Some(transformed)
+            })
+            .collect();
+
+        Some(Self {
+            keyframes,
+            interpolation,
+            subpixel: animation.subpixel(),
+            posed,
+            size: (width, height),
+        })
+    }
+
+    /// The state `t` of the way through the animation (0.0 to 1.0).
+    ///
+    /// `step` is the sample index, which drives temporal dithering.
+    fn pose_at(&self, t: f64, step: usize) -> KeyframePose {
+        let keyframes = &self.keyframes;
+
+        // Bracketing keyframes for interpolation
+        let next_idx = keyframes.iter().position(|(pct, _)| *pct > t).unwrap_or(keyframes.len());
+        let prev_idx = next_idx.saturating_sub(1);
+        let (prev_pct, prev) = keyframes[prev_idx];
+        let (next_pct, next) = keyframes[next_idx.min(keyframes.len() - 1)];
+
+        let local_t = if next_pct > prev_pct {
+            ease(((t - prev_pct) / (next_pct - prev_pct)).clamp(0.0, 1.0), &self.interpolation)
+        } else {
+            0.0
+        };
+
+        let lerp = |a: f64, b: f64| a + (b - a) * local_t;
+
+        let opacity =
+            lerp(prev.opacity.unwrap_or(1.0), next.opacity.or(prev.opacity).unwrap_or(1.0));
+        let prev_offset = prev.offset.unwrap_or([0, 0]);
+        let next_offset = next.offset.or(prev.offset).unwrap_or([0, 0]);
+        let x = lerp(prev_offset[0] as f64, next_offset[0] as f64);
+        let y = lerp(prev_offset[1] as f64, next_offset[1] as f64);
+        let offset = match self.subpixel {
+            SubpixelMode::Round => (x.round() as i32, y.round() as i32),
+            SubpixelMode::Dither => (temporal_dither(x, step), temporal_dither(y, step)),
+        };
+
+        (prev_idx, offset, opacity)
+    }
+
+    /// Canvas bounds `(left, top, right, bottom)` relative to the sprite's origin.
+    ///
+    /// Frames are clipped to the largest sprite's size unless `fit` is set, in
+    /// which case the canvas grows to keep every sampled offset and transformed
+    /// frame in view.
+    fn bounds(&self, fit: bool, sample_count: u32) -> (i32, i32, i32, i32) {
+        let (mut left, mut top, mut right, mut bottom) =
+            (0, 0, self.size.0 as i32, self.size.1 as i32);
+        if fit {
+            for i in 0..sample_count {
+                let (index, (x, y), _) = self.pose_at(i as f64 / sample_count as f64, i as usize);
+                let Some(image) = &self.posed[index] else { continue };
+                left = left.min(x);
+                top = top.min(y);
+                right = right.max(x + image.width() as i32);
+                bottom = bottom.max(y + image.height() as i32);
+            }
+        }
+        (left, top, right, bottom)
+    }
+
+    /// Draw a pose on a canvas with the given bounds.
+    fn render(&self, pose: KeyframePose, bounds: (i32, i32, i32, i32)) -> RgbaImage {
+        let (index, (x, y), opacity) = pose;
+        let (left, top, right, bottom) = bounds;
+        let (width, height) = ((right - left) as u32, (bottom - top) as u32);
+        match &self.posed[index] {
+            Some(image) => place_on_canvas(image, width, height, (x - left, y - top), opacity),
+            None => RgbaImage::new(width, height),
+        }
+    }
+}
+
+/// Bake a CSS keyframe animation into evenly spaced frames.
+///
+/// See [`KeyframeSampler`] for how keyframes are interpolated and `fit` for
+/// [`KeyframeSampler::bounds`].
+fn sample_css_keyframes(
+    animation: &Animation,
+    sprites: &HashMap<String, Sprite>,
+    palettes: &PaletteRegistry,
+    fit: bool,
+    warnings: &mut Vec<String>,
+) -> (Vec<RgbaImage>, Vec<u32>) {
+    let Some(sampler) = KeyframeSampler::new(animation, sprites, palettes, warnings) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let (sample_count, total_ms) = keyframe_samples(animation);
+    let bounds = sampler.bounds(fit, sample_count);
+    let frames = (0..sample_count)
+        .map(|i| {
+            sampler.render(sampler.pose_at(i as f64 / sample_count as f64, i as usize), bounds)
+        })
+        .collect();
+    (frames, sample_durations(sample_count, total_ms))
+}
+
+/// Split `total_ms` as evenly as possible over `sample_count` samples.
+fn sample_durations(sample_count: u32, total_ms: u32) -> Vec<u32> {
+    let base_duration = total_ms / sample_count;
+    let remainder = total_ms % sample_count;
+    (0..sample_count).map(|i| base_duration + u32::from(i < remainder)).collect()
+}
+
+/// The keyframe animation a motion preset expands to, if `animation` has one.
+fn expand_motion_preset(
+    animation: &Animation,
+    sprites: &HashMap<String, Sprite>,
+    warnings: &mut Vec<String>,
+) -> Option<Animation> {
+    let size = animation.motion.as_ref().and_then(|m| sprites.get(&m.sprite)).and_then(|s| s.size);
+    match expand_motion(animation, size)? {
+        Ok(expanded) => Some(expanded),
+        Err(e) => {
+            warnings.push(format!("animation '{}': {}", animation.name, e));
+            None
+        }
+    }
+}
+
+/// Precompute every frame of an animation for real-time playback.
+///
+/// Sprites are resolved against `palettes` in lenient mode; problems are
+/// reported as warnings rather than errors so a preview always has something
+/// to show. Frames are lined up on their sprite origins, if any, and padded
+/// to a common size.
+pub fn precompute_animation(
+    animation: &Animation,
+    sprites: &HashMap<String, Sprite>,
+    palettes: &PaletteRegistry,
+) -> (AnimationTimeline, Vec<String>) {
+    let mut warnings = Vec::new();
+
+    // Motion presets play as the keyframes they expand to
+    let expanded = expand_motion_preset(animation, sprites, &mut warnings);
+    let animation = expanded.as_ref().unwrap_or(animation);
+
+    let (frames, durations) = if animation.is_css_keyframes() {
+        sample_css_keyframes(animation, sprites, palettes, expanded.is_some(), &mut warnings)
+    } else if animation.has_palette_cycle() && animation.frames.len() == 1 {
+        match sprites.get(&animation.frames[0]) {
+            Some(sprite) => {
+                let resolved = palettes.resolve_lenient(sprite);
+                if let Some(w) = resolved.warning {
+                    warnings.push(format!("sprite '{}': {}", sprite.name, w.message));
+                }
+                let max_frames = LimitsConfig::default().max_frames;
+                let (frames, cycle_warnings) =
+                    generate_cycle_frames(sprite, &resolved.palette.colors, animation, max_frames);
+                warnings.extend(
+                    cycle_warnings.into_iter().map(|w| format!("sprite '{}': {}", sprite.name, w)),
+                );
+                let duration = get_cycle_duration(animation);
+                let durations = vec![duration; frames.len()];
+                (frames, durations)
+            }
+            None => {
+                warnings.push(format!(
+                    "Animation '{}' references unknown sprite '{}'",
+                    animation.name, animation.frames[0]
+                ));
+                (Vec::new(), Vec::new())
+            }
+        }
+    } else {
+        let frame_durations = animation.frame_durations().unwrap_or_else(|e| {
+            warnings.push(e);
+            vec![animation.duration_ms(); animation.frames.len()]
+        });
+        let mut frames = Vec::new();
+        let mut durations = Vec::new();
+        let mut pivots = Vec::new();
+        for (frame_name, duration) in animation.frames.iter().zip(frame_durations) {
+            match sprites.get(frame_name) {
+                Some(sprite) => {
+                    frames.push(render_frame_sprite(sprite, palettes, &mut warnings));
+                    durations.push(duration);
+                    pivots.push(sprite.metadata.as_ref().and_then(|m| m.origin));
+                }
+                None => warnings.push(format!(
+                    "Animation '{}' references unknown sprite '{}'",
+                    animation.name, frame_name
+                )),
+            }
+        }
+        // Frames with an origin line up on it rather than on their top-left
+        let sizes: Vec<(u32, u32)> = frames.iter().map(|f| f.dimensions()).collect();
+        match pivot_layout(&sizes, &pivots) {
+            Some(layout) => (align_frames(frames, &layout), durations),
+            None => (frames, durations),
+        }
+    };
+
+    if frames.is_empty() {
+        warnings.push(format!("Animation '{}' has no frames", animation.name));
+    }
+
+    let direction = animation.direction();
+    let frames = direction.apply(frames);
+    let durations = direction.apply(durations);
+
+    let timeline = AnimationTimeline {
+        frames: normalize_frame_sizes(frames),
+        durations,
+        loops: animation.loops(),
+    };
+
+    (timeline, warnings)
+}
+
+/// Render the frame of an animation visible `time_ms` milliseconds after
+/// playback started, without precomputing the rest.
+///
+/// This is the scrubbing counterpart of [`precompute_animation`] for editors
+/// and previews: frame arrays and palette cycles render only the frame (or
+/// cycle step) at `time_ms`, on the same canvas the timeline would use.
+/// CSS keyframes and motion presets are evaluated at exactly `time_ms`, with
+/// the timing function applied, rather than at the nearest 20 fps sample.
+///
+/// Like [`precompute_animation`], problems are reported as warnings; an
+/// animation with nothing to show renders as an empty image.
+pub fn animation_frame_at(
+    animation: &Animation,
+    sprites: &HashMap<String, Sprite>,
+    palettes: &PaletteRegistry,
+    time_ms: f64,
+) -> (RgbaImage, Vec<String>) {
+    let mut warnings = Vec::new();
+
+    let expanded = expand_motion_preset(animation, sprites, &mut warnings);
+    let fit = expanded.is_some();
+    let animation = expanded.as_ref().unwrap_or(animation);
+    let direction = animation.direction();
+    let loops = animation.loops();
+
+    let frame = if animation.is_css_keyframes() {
+        KeyframeSampler::new(animation, sprites, palettes, &mut warnings).and_then(|sampler| {
+            let (sample_count, total_ms) = keyframe_samples(animation);
+            let order = direction.frame_order(sample_count as usize);
+            let samples = sample_durations(sample_count, total_ms);
+            let durations: Vec<u32> = order.iter().map(|&i| samples[i]).collect();
+            let (index, progress) = position_at(&durations, loops, time_ms)?;
+
+            // Reversed stretches play each sample from its end back to its start
+            let step = order[index];
+            let backwards = match direction {
+                PlaybackDirection::Forward => false,
+                PlaybackDirection::Reverse => true,
+                PlaybackDirection::Pingpong => index >= sample_count as usize,
+            };
+            let t = if backwards { step as f64 + 1.0 - progress } else { step as f64 + progress };
+
+            let bounds = sampler.bounds(fit, sample_count);
+            Some(sampler.render(sampler.pose_at(t / sample_count as f64, step), bounds))
+        })
+    } else if animation.has_palette_cycle() && animation.frames.len() == 1 {
+        match sprites.get(&animation.frames[0]) {
+            Some(sprite) => {
+                let resolved = palettes.resolve_lenient(sprite);
+                if let Some(w) = resolved.warning {
+                    warnings.push(format!("sprite '{}': {}", sprite.name, w.message));
+                }
+                let max_frames = LimitsConfig::default().max_frames;
+                let (step_count, cap_warning) = capped_total_frames(animation, max_frames);
+                warnings.extend(cap_warning.map(|w| format!("sprite '{}': {}", sprite.name, w)));
+
+                let order = direction.frame_order(step_count);
+                let durations = vec![get_cycle_duration(animation); order.len()];
+                position_at(&durations, loops, time_ms).map(|(index, _)| {
+                    let palette = apply_cycles_step(
+                        &resolved.palette.colors,
+                        animation.palette_cycles(),
+                        order[index],
+                    );
+                    let (image, render_warnings) = render_sprite(sprite, &palette);
+                    warnings.extend(
+                        render_warnings
+                            .into_iter()
+                            .map(|w| format!("sprite '{}': {}", sprite.name, w.message)),
+                    );
+                    image
+                })
+            }
+            None => {
+                warnings.push(format!(
+                    "Animation '{}' references unknown sprite '{}'",
+                    animation.name, animation.frames[0]
+                ));
+                None
+            }
+        }
+    } else {
+        let frame_durations = animation.frame_durations().unwrap_or_else(|e| {
+            warnings.push(e);
+            vec![animation.duration_ms(); animation.frames.len()]
+        });
+        let mut frames = Vec::new();
+        for (frame_name, duration) in animation.frames.iter().zip(frame_durations) {
+            match sprites.get(frame_name) {
+                Some(sprite) => frames.push((sprite, duration)),
+                None => warnings.push(format!(
+                    "Animation '{}' references unknown sprite '{}'",
+                    animation.name, frame_name
+                )),
+            }
+        }
+
+        let order = direction.frame_order(frames.len());
+        let durations: Vec<u32> = order.iter().map(|&i| frames[i].1).collect();
+        position_at(&durations, loops, time_ms).map(|(index, _)| {
+            let shown = order[index];
+            let image = render_frame_sprite(frames[shown].0, palettes, &mut warnings);
+
+            // The other frames only contribute their size to the shared canvas
+            let sizes: Vec<(u32, u32)> = frames
+                .iter()
+                .enumerate()
+                .map(|(i, (sprite, _))| match sprite.size {
+                    _ if i == shown => image.dimensions(),
+                    Some([width, height]) => (width, height),
+                    None => render_frame_sprite(sprite, palettes, &mut Vec::new()).dimensions(),
+                })
+                .collect();
+            let pivots: Vec<Option<[i32; 2]>> = frames
+                .iter()
+                .map(|(sprite, _)| sprite.metadata.as_ref().and_then(|m| m.origin))
+                .collect();
+
+            // Frames with an origin line up on it rather than on their top-left
+            let (width, height, [x, y]) = match pivot_layout(&sizes, &pivots) {
+                Some(layout) => (layout.size.0, layout.size.1, layout.offsets[shown]),
+                None => (
+                    sizes.iter().map(|s| s.0).max().unwrap_or(0),
+                    sizes.iter().map(|s| s.1).max().unwrap_or(0),
+                    [0, 0],
+                ),
+            };
+            place_on_canvas(&image, width, height, (x as i32, y as i32), 1.0)
+        })
+    };
+
+    let frame = frame.unwrap_or_else(|| {
+        warnings.push(format!("Animation '{}' has no frames", animation.name));
+        RgbaImage::new(0, 0)
+    });
+    (frame, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CssKeyframe, Duration, MotionPreset, MotionPresetKind, PaletteCycle, PaletteRef,
+        PlaybackDirection, RegionDef, SpriteMetadata,
+    };
+
+    fn dot_sprite(name: &str, color: &str) -> Sprite {
+        Sprite {
+            name: name.to_string(),
+            size: Some([1, 1]),
+            palette: PaletteRef::Inline(HashMap::from([("x".to_string(), color.to_string())])),
+            regions: Some(HashMap::from([(
+                "x".to_string(),
+                RegionDef { points: Some(vec![[0, 0]]), ..Default::default() },
+            )])),
+            ..Default::default()
+        }
+    }
+
+    fn sprite_map(sprites: Vec<Sprite>) -> HashMap<String, Sprite> {
+        sprites.into_iter().map(|s| (s.name.clone(), s)).collect()
+    }
+
+    #[test]
+    fn test_frame_based_timeline() {
+        let sprites = sprite_map(vec![dot_sprite("a", "#FF0000"), dot_sprite("b", "#00FF00")]);
+        let anim = Animation {
+            name: "blink".to_string(),
+            frames: vec!["a".to_string(), "b".to_string()],
+            duration: Some(Duration::Milliseconds(100)),
+            ..Default::default()
+        };
+
+        let (timeline, warnings) = precompute_animation(&anim, &sprites, &PaletteRegistry::new());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(timeline.frame_count(), 2);
+        assert_eq!(timeline.total_duration_ms(), 200);
+        assert_eq!(timeline.frame_index_at(0.0), 0);
+        assert_eq!(timeline.frame_index_at(99.0), 0);
+        assert_eq!(timeline.frame_index_at(100.0), 1);
+        // Loops back around
+        assert_eq!(timeline.frame_index_at(250.0), 0);
+        assert_eq!(timeline.frame_at(150.0).unwrap().get_pixel(0, 0)[1], 255);
+    }
+
+    #[test]
+    fn test_non_looping_holds_last_frame() {
+        let sprites = sprite_map(vec![dot_sprite("a", "#FF0000"), dot_sprite("b", "#00FF00")]);
+        let anim = Animation {
+            name: "once".to_string(),
+            frames: vec!["a".to_string(), "b".to_string()],
+            r#loop: Some(false),
+            ..Default::default()
+        };
+
+        let (timeline, _) = precompute_animation(&anim, &sprites, &PaletteRegistry::new());
+        assert_eq!(timeline.frame_index_at(10_000.0), 1);
+        assert_eq!(timeline.frame_index_at(-5.0), 0);
+    }
+
+    #[test]
+    fn test_pingpong_timeline() {
+        let sprites = sprite_map(vec![
+            dot_sprite("a", "#FF0000"),
+            dot_sprite("b", "#00FF00"),
+            dot_sprite("c", "#0000FF"),
+        ]);
+        let anim = Animation {
+            name: "bounce".to_string(),
+            frames: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            direction: Some(PlaybackDirection::Pingpong),
+            ..Default::default()
+        };
+
+        let (timeline, _) = precompute_animation(&anim, &sprites, &PaletteRegistry::new());
+        assert_eq!(timeline.frame_count(), 4);
+        assert_eq!(timeline.total_duration_ms(), 400);
+        // a b c b, then back to a
+        assert_eq!(timeline.frame_at(350.0).unwrap().get_pixel(0, 0)[1], 255);
+        assert_eq!(timeline.frame_at(400.0).unwrap().get_pixel(0, 0)[0], 255);
+    }
+
+    #[test]
+    fn test_palette_cycle_timeline() {
+        let mut sprite = dot_sprite("water", "#0000FF");
+        sprite.palette = PaletteRef::Inline(HashMap::from([
+            ("x".to_string(), "#0000FF".to_string()),
+            ("y".to_string(), "#00FF00".to_string()),
+        ]));
+        let sprites = sprite_map(vec![sprite]);
+        let anim = Animation {
+            name: "shimmer".to_string(),
+            frames: vec!["water".to_string()],
+            palette_cycle: Some(vec![PaletteCycle {
+                tokens: vec!["x".to_string(), "y".to_string()],
+                duration: Some(80),
+                direction: None,
+            }]),
+            ..Default::default()
+        };
+
+        let (timeline, _) = precompute_animation(&anim, &sprites, &PaletteRegistry::new());
+        assert_eq!(timeline.frame_count(), 2);
+        assert_eq!(timeline.durations, vec![80, 80]);
+        assert_eq!(timeline.frame_at(0.0).unwrap().get_pixel(0, 0)[2], 255);
+        assert_eq!(timeline.frame_at(90.0).unwrap().get_pixel(0, 0)[1], 255);
+    }
+
+    #[test]
+    fn test_css_keyframes_sampling() {
+        let sprites = sprite_map(vec![dot_sprite("a", "#FF0000"), dot_sprite("b", "#00FF00")]);
+        let anim = Animation {
+            name: "fade".to_string(),
+            keyframes: Some(HashMap::from([
+                (
+                    "0%".to_string(),
+                    CssKeyframe {
+                        sprite: Some("a".to_string()),
+                        opacity: Some(0.0),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "50%".to_string(),
+                    CssKeyframe { sprite: Some("b".to_string()), ..Default::default() },
+                ),
+                ("100%".to_string(), CssKeyframe { opacity: Some(1.0), ..Default::default() }),
+            ])),
+            duration: Some(Duration::Milliseconds(200)),
+            ..Default::default()
+        };
+
+        let (timeline, warnings) = precompute_animation(&anim, &sprites, &PaletteRegistry::new());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(timeline.frame_count(), 4);
+        assert_eq!(timeline.total_duration_ms(), 200);
+
+        let first = timeline.frame_at(0.0).unwrap().get_pixel(0, 0);
+        assert_eq!(first[0], 255);
+        assert_eq!(first[3], 0, "opacity starts at 0");
+
+        let later = timeline.frame_at(150.0).unwrap().get_pixel(0, 0);
+        assert_eq!(later[1], 255, "sprite switches to 'b' at 50%");
+    }
+
+    #[test]
+    fn test_css_keyframes_subpixel_dither() {
+        let sprites = sprite_map(vec![dot_sprite("a", "#FF0000")]);
+        let mut anim = Animation {
+            name: "drift".to_string(),
+            keyframes: Some(HashMap::from([
+                (
+                    "from".to_string(),
+                    CssKeyframe {
+                        sprite: Some("a".to_string()),
+                        offset: Some([0, 0]),
+                        ..Default::default()
+                    },
+                ),
+                ("to".to_string(), CssKeyframe { offset: Some([1, 0]), ..Default::default() }),
+            ])),
+            duration: Some(Duration::Milliseconds(400)),
+            ..Default::default()
+        };
+
+        // The dot is visible on the 1x1 canvas while at x = 0
+        let positions = |anim: &Animation| -> Vec<u8> {
+            let (timeline, _) = precompute_animation(anim, &sprites, &PaletteRegistry::new());
+            timeline.frames.iter().map(|f| u8::from(f.get_pixel(0, 0)[3] == 0)).collect()
+        };
+
+        assert_eq!(positions(&anim), vec![0, 0, 0, 0, 1, 1, 1, 1]);
+        anim.subpixel = Some(SubpixelMode::Dither);
+        assert_eq!(positions(&anim), vec![0, 1, 0, 1, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_motion_preset_timeline() {
+        let sprites = sprite_map(vec![dot_sprite("ball", "#FF0000")]);
+        let anim = Animation {
+            name: "hop".to_string(),
+            motion: Some(MotionPreset {
+                preset: MotionPresetKind::Bounce,
+                sprite: "ball".to_string(),
+                amplitude: Some(2.0),
+                cycles: None,
+                decay: None,
+            }),
+            duration: Some(Duration::Milliseconds(400)),
+            ..Default::default()
+        };
+
+        let (timeline, warnings) = precompute_animation(&anim, &sprites, &PaletteRegistry::new());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(timeline.frame_count(), 8);
+        // The canvas grows to fit the hop instead of clipping it
+        assert_eq!((timeline.width(), timeline.height()), (1, 3));
+        assert_eq!(timeline.frames[0].get_pixel(0, 2)[3], 255, "starts on the ground");
+        assert_eq!(timeline.frames[4].get_pixel(0, 0)[3], 255, "peaks halfway");
+    }
+
+    #[test]
+    fn test_missing_frame_warns() {
+        let anim = Animation {
+            name: "broken".to_string(),
+            frames: vec!["ghost".to_string()],
+            ..Default::default()
+        };
+        let (timeline, warnings) =
+            precompute_animation(&anim, &HashMap::new(), &PaletteRegistry::new());
+        assert_eq!(timeline.frame_count(), 0);
+        assert!(timeline.frame_at(0.0).is_none());
+        assert!(warnings.iter().any(|w| w.contains("ghost")));
+    }
+
+    #[test]
+    fn test_frame_at_matches_timeline() {
+        // Frames of different sizes lined up on their origins, played ping-pong
+        let mut tall = dot_sprite("b", "#00FF00");
+        tall.size = Some([1, 2]);
+        tall.metadata = Some(SpriteMetadata { origin: Some([0, 1]), ..Default::default() });
+        let sprites =
+            sprite_map(vec![dot_sprite("a", "#FF0000"), tall, dot_sprite("c", "#0000FF")]);
+        let frames = Animation {
+            name: "bounce".to_string(),
+            frames: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            direction: Some(PlaybackDirection::Pingpong),
+            ..Default::default()
+        };
+
+        let mut water = dot_sprite("water", "#0000FF");
+        water.palette = PaletteRef::Inline(HashMap::from([
+            ("x".to_string(), "#0000FF".to_string()),
+            ("y".to_string(), "#00FF00".to_string()),
+            ("z".to_string(), "#FF0000".to_string()),
+        ]));
+        let cycle = Animation {
+            name: "shimmer".to_string(),
+            frames: vec!["water".to_string()],
+            palette_cycle: Some(vec![PaletteCycle {
+                tokens: vec!["x".to_string(), "y".to_string(), "z".to_string()],
+                duration: Some(80),
+                direction: None,
+            }]),
+            r#loop: Some(false),
+            ..Default::default()
+        };
+        let sprites = sprite_map(sprites.into_values().chain([water]).collect());
+
+        let palettes = PaletteRegistry::new();
+        for anim in [&frames, &cycle] {
+            let (timeline, _) = precompute_animation(anim, &sprites, &palettes);
+            for t in [0.0, 99.0, 100.0, 250.0, 350.0, 1000.0] {
+                let (frame, warnings) = animation_frame_at(anim, &sprites, &palettes, t);
+                assert!(warnings.is_empty(), "{:?}", warnings);
+                assert_eq!(&frame, timeline.frame_at(t).unwrap(), "{} at {}ms", anim.name, t);
+            }
+        }
+    }
+
+    #[test]
+    fn test_frame_at_evaluates_keyframes_exactly() {
+        let sprites = sprite_map(vec![dot_sprite("a", "#FF0000")]);
+        let mut anim = Animation {
+            name: "fade".to_string(),
+            keyframes: Some(HashMap::from([
+                (
+                    "0%".to_string(),
+                    CssKeyframe {
+                        sprite: Some("a".to_string()),
+                        opacity: Some(0.0),
+                        ..Default::default()
+                    },
+                ),
+                ("100%".to_string(), CssKeyframe { opacity: Some(1.0), ..Default::default() }),
+            ])),
+            duration: Some(Duration::Milliseconds(200)),
+            ..Default::default()
+        };
+        let alpha = |anim: &Animation, t: f64| {
+            let (frame, _) = animation_frame_at(anim, &sprites, &PaletteRegistry::new(), t);
+            frame.get_pixel(0, 0)[3]
+        };
+
+        // Between the 50ms samples the timeline would hold
+        assert_eq!(alpha(&anim, 25.0), 32);
+        assert_eq!(alpha(&anim, 100.0), 128);
+        assert_eq!(alpha(&anim, 225.0), 32, "loops");
+
+        anim.timing_function = Some("ease-in".to_string());
+        assert!(alpha(&anim, 100.0) < 128, "timing function is applied");
+
+        anim.timing_function = None;
+        anim.direction = Some(PlaybackDirection::Reverse);
+        assert_eq!(alpha(&anim, 25.0), 223);
+    }
+
+    #[test]
+    fn test_frame_at_without_frames() {
+        let anim = Animation {
+            name: "broken".to_string(),
+            frames: vec!["ghost".to_string()],
+            ..Default::default()
+        };
+        let (frame, warnings) =
+            animation_frame_at(&anim, &HashMap::new(), &PaletteRegistry::new(), 0.0);
+        assert_eq!(frame.dimensions(), (0, 0));
+        assert!(warnings.iter().any(|w| w.contains("ghost")));
+    }
+}
@@ -0,0 +1,324 @@
+//! Plugins adding custom transforms and exporters
+//!
+//! Programs embedding pixelsrc register plugins once at startup; transforms
+//! and exports consult the registry for any name they don't know. A custom
+//! `pxl` binary is a plugin registration followed by [`crate::cli::run`]:
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use image::RgbaImage;
+//! use pixelsrc::plugin::{register_transform, TransformPlugin};
+//! use pixelsrc::transforms::TransformError;
+//!
+//! struct Invert;
+//!
+//! impl TransformPlugin for Invert {
+//!     fn name(&self) -> &str {
+//!         "invert"
+//!     }
+//!
+//!     fn apply(
+//!         &self,
+//!         image: &RgbaImage,
+//!         _params: &HashMap<String, serde_json::Value>,
+//!     ) -> Result<RgbaImage, TransformError> {
+//!         let mut out = image.clone();
+//!         for p in out.pixels_mut() {
+//!             p.0 = [255 - p[0], 255 - p[1], 255 - p[2], p[3]];
+//!         }
+//!         Ok(out)
+//!     }
+//! }
+//!
+//! fn main() -> std::process::ExitCode {
+//!     register_transform(Invert).unwrap();
+//!     pixelsrc::cli::run()
+//! }
+//! ```
+//!
+//! Sprites then use `"transform": ["invert"]`. Plugin names are matched
+//! case-insensitively and can't shadow built-in transforms or export formats.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use image::RgbaImage;
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::atlas::AtlasMetadata;
+use crate::export::ExportOptions;
+use crate::transforms::{parse_transform_str, Transform, TransformError};
+
+/// Export formats built into `pxl build`
+pub const BUILTIN_EXPORTERS: &[&str] = &["godot", "unity", "libgdx"];
+
+/// A custom image transform.
+pub trait TransformPlugin: Send + Sync {
+    /// Operation name used in `"transform"` lists
+    fn name(&self) -> &str;
+
+    /// One-line description shown by `pxl plugins` and `pxl explain`
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Transform an image.
+    ///
+    /// `params` holds the object syntax's fields other than `op`
+    /// (`{"op": "invert", "amount": 2}`); the string syntax passes anything
+    /// after the first colon as `"args"` (`"invert:2"`).
+    fn apply(
+        &self,
+        image: &RgbaImage,
+        params: &HashMap<String, Value>,
+    ) -> Result<RgbaImage, TransformError>;
+}
+
+/// A custom export format for atlas metadata.
+pub trait ExporterPlugin: Send + Sync {
+    /// Format name, as listed in `[export] plugins`
+    fn name(&self) -> &str;
+
+    /// One-line description shown by `pxl plugins`
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// File extension of the exported file
+    fn extension(&self) -> &str;
+
+    /// Export atlas metadata to `output_path`.
+    fn export(
+        &self,
+        metadata: &AtlasMetadata,
+        output_path: &Path,
+        options: &ExportOptions,
+    ) -> crate::export::Result<()>;
+}
+
+/// Error registering a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum PluginError {
+    /// A plugin with this name is already registered
+    #[error("a {kind} plugin named '{name}' is already registered")]
+    Duplicate { kind: PluginKind, name: String },
+    /// The name belongs to a built-in transform or export format
+    #[error("'{name}' is a built-in {kind}")]
+    Builtin { kind: PluginKind, name: String },
+}
+
+/// What a plugin extends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginKind {
+    Transform,
+    Exporter,
+}
+
+impl std::fmt::Display for PluginKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginKind::Transform => f.pad("transform"),
+            PluginKind::Exporter => f.pad("exporter"),
+        }
+    }
+}
+
+/// A registered plugin, as listed by `pxl plugins`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginInfo {
+    pub kind: PluginKind,
+    pub name: String,
+    pub description: String,
+}
+
+struct Registry {
+    transforms: BTreeMap<String, Arc<dyn TransformPlugin>>,
+    exporters: BTreeMap<String, Arc<dyn ExporterPlugin>>,
+}
+
+static REGISTRY: RwLock<Registry> =
+    RwLock::new(Registry { transforms: BTreeMap::new(), exporters: BTreeMap::new() });
+
+fn read() -> std::sync::RwLockReadGuard<'static, Registry> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write() -> std::sync::RwLockWriteGuard<'static, Registry> {
+    REGISTRY.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Register a transform plugin.
+pub fn register_transform(plugin: impl TransformPlugin + 'static) -> Result<(), PluginError> {
+    let name = plugin.name().to_lowercase();
+    let kind = PluginKind::Transform;
+    let builtin = !matches!(
+        parse_transform_str(&name),
+        Ok(Transform::Plugin { .. }) | Err(TransformError::UnknownOperation(_))
+    );
+    if builtin {
+        return Err(PluginError::Builtin { kind, name });
+    }
+    let mut registry = write();
+    if registry.transforms.contains_key(&name) {
+        return Err(PluginError::Duplicate { kind, name });
+    }
+    registry.transforms.insert(name, Arc::new(plugin));
+    Ok(())
+}
+
+/// Register an exporter plugin.
+pub fn register_exporter(plugin: impl ExporterPlugin + 'static) -> Result<(), PluginError> {
+    let name = plugin.name().to_lowercase();
+    let kind = PluginKind::Exporter;
+    if BUILTIN_EXPORTERS.contains(&name.as_str()) {
+        return Err(PluginError::Builtin { kind, name });
+    }
+    let mut registry = write();
+    if registry.exporters.contains_key(&name) {
+        return Err(PluginError::Duplicate { kind, name });
+    }
+    registry.exporters.insert(name, Arc::new(plugin));
+    Ok(())
+}
+
+/// Look up a transform plugin by name.
+pub fn transform(name: &str) -> Option<Arc<dyn TransformPlugin>> {
+    read().transforms.get(&name.to_lowercase()).cloned()
+}
+
+/// Look up an exporter plugin by name.
+pub fn exporter(name: &str) -> Option<Arc<dyn ExporterPlugin>> {
+    read().exporters.get(&name.to_lowercase()).cloned()
+}
+
+/// Every registered plugin, transforms first, each sorted by name.
+pub fn plugins() -> Vec<PluginInfo> {
+    let registry = read();
+    let transforms = registry.transforms.iter().map(|(name, p)| PluginInfo {
+        kind: PluginKind::Transform,
+        name: name.clone(),
+        description: p.description().to_string(),
+    });
+    let exporters = registry.exporters.iter().map(|(name, p)| PluginInfo {
+        kind: PluginKind::Exporter,
+        name: name.clone(),
+        description: p.description().to_string(),
+    });
+    transforms.chain(exporters).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::{apply_image_transform, parse_transform_value};
+
+    // The registry is global, so each test registers plugins under its own names.
+    struct Fill(&'static str, [u8; 4]);
+
+    impl TransformPlugin for Fill {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "Fill every pixel"
+        }
+
+        fn apply(
+            &self,
+            image: &RgbaImage,
+            params: &HashMap<String, Value>,
+        ) -> Result<RgbaImage, TransformError> {
+            let alpha = match params.get("args").or_else(|| params.get("alpha")) {
+                Some(Value::String(s)) => {
+                    s.parse().map_err(|_| TransformError::InvalidParameter {
+                        op: self.0.to_string(),
+                        message: format!("bad alpha '{}'", s),
+                    })?
+                }
+                Some(v) => v.as_u64().unwrap_or(255) as u8,
+                None => self.1[3],
+            };
+            let [r, g, b, _] = self.1;
+            Ok(RgbaImage::from_pixel(image.width(), image.height(), image::Rgba([r, g, b, alpha])))
+        }
+    }
+
+    struct Text(&'static str);
+
+    impl ExporterPlugin for Text {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn extension(&self) -> &str {
+            "txt"
+        }
+
+        fn export(
+            &self,
+            metadata: &AtlasMetadata,
+            output_path: &Path,
+            _options: &ExportOptions,
+        ) -> crate::export::Result<()> {
+            let mut names: Vec<&String> = metadata.frames.keys().collect();
+            names.sort();
+            std::fs::write(output_path, format!("{:?}", names))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_transform_plugin_parses_and_applies() {
+        register_transform(Fill("test-fill", [1, 2, 3, 255])).unwrap();
+
+        let transform = parse_transform_str("Test-Fill:7").unwrap();
+        assert!(matches!(&transform, Transform::Plugin { name, .. } if name == "test-fill"));
+        let image = RgbaImage::new(2, 1);
+        let out = apply_image_transform(&image, &transform, None).unwrap();
+        assert_eq!(out.get_pixel(1, 0).0, [1, 2, 3, 7]);
+
+        let value = serde_json::json!({"op": "test-fill", "alpha": 9});
+        let out = apply_image_transform(&image, &parse_transform_value(&value).unwrap(), None);
+        assert_eq!(out.unwrap().get_pixel(0, 0).0, [1, 2, 3, 9]);
+
+        assert!(parse_transform_str("test-unregistered").is_err());
+    }
+
+    #[test]
+    fn test_register_rejects_builtin_and_duplicate_names() {
+        assert!(matches!(
+            register_transform(Fill("rotate", [0; 4])),
+            Err(PluginError::Builtin { .. })
+        ));
+        assert!(matches!(register_exporter(Text("godot")), Err(PluginError::Builtin { .. })));
+
+        register_transform(Fill("test-twice", [0; 4])).unwrap();
+        assert_eq!(
+            register_transform(Fill("TEST-TWICE", [0; 4])),
+            Err(PluginError::Duplicate {
+                kind: PluginKind::Transform,
+                name: "test-twice".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_plugins_lists_registered() {
+        register_transform(Fill("test-listed", [0; 4])).unwrap();
+        register_exporter(Text("test-text")).unwrap();
+
+        let listed = plugins();
+        let fill = listed.iter().find(|p| p.name == "test-listed").unwrap();
+        assert_eq!(fill.kind, PluginKind::Transform);
+        assert_eq!(fill.description, "Fill every pixel");
+        let text = listed.iter().find(|p| p.name == "test-text").unwrap();
+        assert_eq!(text.kind, PluginKind::Exporter);
+        assert_eq!(exporter("TEST-TEXT").unwrap().extension(), "txt");
+    }
+}
@@ -0,0 +1,302 @@
+//! Provenance metadata for exported PNGs
+//!
+//! Rendered PNGs carry text chunks naming the source file, object, palette,
+//! a hash of the object's definition, and the pixelsrc version, so an asset
+//! found in a game build can be traced back to the definition that produced
+//! it. `pxl inspect` reads them back with [`read_text`].
+//!
+//! Values that are plain ASCII are written as `tEXt` chunks, anything else as
+//! uncompressed UTF-8 `iTXt` chunks. Reading also understands `zTXt` and
+//! compressed `iTXt`, so text written by other tools shows up too.
+
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::ZlibDecoder;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::build::manifest::fnv1a_hash;
+use crate::ora::crc32;
+use crate::output::is_stdio;
+
+/// PNG file signature
+pub(crate) const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Keyword prefix for pixelsrc's own text chunks
+pub const KEY_PREFIX: &str = "pixelsrc:";
+
+/// Error reading or writing PNG text chunks
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PngTextError {
+    /// The data doesn't start with the PNG signature
+    #[error("not a PNG file")]
+    NotPng,
+    /// A chunk runs past the end of the data
+    #[error("truncated PNG chunk")]
+    Truncated,
+    /// Keywords must be 1-79 printable Latin-1 characters
+    #[error("invalid PNG text keyword '{0}'")]
+    InvalidKeyword(String),
+    /// A text chunk that can't be decoded
+    #[error("malformed {0} chunk")]
+    Malformed(&'static str),
+}
+
+/// Where a rendered image came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// Source file, if not read from stdin
+    pub source: Option<String>,
+    /// Name of the rendered sprite, composition, or animation
+    pub object: String,
+    /// Named palette of a sprite
+    pub palette: Option<String>,
+    /// [`content_hash`] of the object's definition
+    pub hash: String,
+}
+
+impl Provenance {
+    /// Provenance for `object` rendered from `input`.
+    pub fn new(input: &Path, object: &str, definition: &impl Serialize) -> Self {
+        Self {
+            source: (!is_stdio(input)).then(|| input.display().to_string()),
+            object: object.to_string(),
+            palette: None,
+            hash: content_hash(definition),
+        }
+    }
+
+    /// Record the palette the object was rendered with.
+    pub fn with_palette(mut self, palette: Option<&str>) -> Self {
+        self.palette = palette.map(str::to_string);
+        self
+    }
+
+    /// Text chunk entries, keyed with [`KEY_PREFIX`].
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = Vec::with_capacity(5);
+        let mut push = |key: &str, value: &str| {
+            entries.push((format!("{}{}", KEY_PREFIX, key), value.to_string()));
+        };
+        if let Some(source) = &self.source {
+            push("source", source);
+        }
+        push("object", &self.object);
+        if let Some(palette) = &self.palette {
+            push("palette", palette);
+        }
+        push("hash", &self.hash);
+        push("version", env!("CARGO_PKG_VERSION"));
+        entries
+    }
+}
+
+/// Stable hash of an object definition, e.g. `fnv1a:3f2a...`.
+///
+/// Hashes the definition's JSON with keys sorted, so it changes exactly when
+/// the definition does.
+pub fn content_hash(definition: &impl Serialize) -> String {
+    let json = serde_json::to_value(definition).map(|v| v.to_string()).unwrap_or_default();
+    format!("fnv1a:{:016x}", fnv1a_hash(json.as_bytes()))
+}
+
+/// Insert text chunks after a PNG's header chunk.
+pub fn embed_text(png: &[u8], entries: &[(String, String)]) -> Result<Vec<u8>, PngTextError> {
+    let chunks = chunks(png)?;
+    let header_end = chunks.first().map(|c| c.end).ok_or(PngTextError::Truncated)?;
+
+    let mut out = Vec::with_capacity(png.len() + entries.len() * 64);
+    out.extend_from_slice(&png[..header_end]);
+    for (keyword, value) in entries {
+        if keyword.is_empty()
+            || keyword.len() > 79
+            || !keyword.bytes().all(|b| (0x20..0x7f).contains(&b))
+        {
+            return Err(PngTextError::InvalidKeyword(keyword.clone()));
+        }
+        let mut data = keyword.as_bytes().to_vec();
+        data.push(0);
+        if value.is_ascii() {
+            data.extend_from_slice(value.as_bytes());
+            write_chunk(&mut out, b"tEXt", &data);
+        } else {
+            // Uncompressed, no language tag or translated keyword
+            data.extend_from_slice(&[0, 0, 0, 0]);
+            data.extend_from_slice(value.as_bytes());
+            write_chunk(&mut out, b"iTXt", &data);
+        }
+    }
+    out.extend_from_slice(&png[header_end..]);
+    Ok(out)
+}
+
+/// Read every text chunk (`tEXt`, `zTXt`, `iTXt`) in file order.
+pub fn read_text(png: &[u8]) -> Result<Vec<(String, String)>, PngTextError> {
+    let mut entries = Vec::new();
+    for chunk in chunks(png)? {
+        let data = &png[chunk.data.clone()];
+        let entry = match &chunk.kind {
+            b"tEXt" => {
+                let (keyword, text) = split_keyword(data, "tEXt")?;
+                (keyword, latin1(text))
+            }
+            b"zTXt" => {
+                let (keyword, rest) = split_keyword(data, "zTXt")?;
+                let compressed = rest.get(1..).ok_or(PngTextError::Malformed("zTXt"))?;
+                (keyword, latin1(&inflate(compressed, "zTXt")?))
+            }
+            b"iTXt" => {
+                let (keyword, rest) = split_keyword(data, "iTXt")?;
+                let [flag, _method, rest @ ..] = rest else {
+                    return Err(PngTextError::Malformed("iTXt"));
+                };
+                // Skip the language tag and translated keyword
+                let mut fields = rest.splitn(3, |&b| b == 0);
+                let text = fields.nth(2).ok_or(PngTextError::Malformed("iTXt"))?;
+                let text = if *flag == 1 { inflate(text, "iTXt")? } else { text.to_vec() };
+                let text = String::from_utf8(text).map_err(|_| PngTextError::Malformed("iTXt"))?;
+                (keyword, text)
+            }
+            _ => continue,
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Location of one chunk within the PNG data
+struct Chunk {
+    kind: [u8; 4],
+    data: std::ops::Range<usize>,
+    end: usize,
+}
+
+fn chunks(png: &[u8]) -> Result<Vec<Chunk>, PngTextError> {
+    if !png.starts_with(SIGNATURE) {
+        return Err(PngTextError::NotPng);
+    }
+    let mut chunks = Vec::new();
+    let mut at = SIGNATURE.len();
+    while at < png.len() {
+        let header = png.get(at..at + 8).ok_or(PngTextError::Truncated)?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = [header[4], header[5], header[6], header[7]];
+        let data = at + 8..at + 8 + len;
+        let end = data.end + 4;
+        if end > png.len() {
+            return Err(PngTextError::Truncated);
+        }
+        chunks.push(Chunk { kind, data, end });
+        at = end;
+        if &kind == b"IEND" {
+            break;
+        }
+    }
+    Ok(chunks)
+}
+
+/// Append a PNG chunk with its length and CRC.
+pub(crate) fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn split_keyword<'a>(
+    data: &'a [u8],
+    kind: &'static str,
+) -> Result<(String, &'a [u8]), PngTextError> {
+    let nul = data.iter().position(|&b| b == 0).ok_or(PngTextError::Malformed(kind))?;
+    Ok((latin1(&data[..nul]), &data[nul + 1..]))
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+fn inflate(data: &[u8], kind: &'static str) -> Result<Vec<u8>, PngTextError> {
+    let mut out = Vec::new();
+    ZlibDecoder::new(data).read_to_end(&mut out).map_err(|_| PngTextError::Malformed(kind))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn png() -> Vec<u8> {
+        crate::output::encode_png(&image::RgbaImage::new(2, 2)).unwrap()
+    }
+
+    #[test]
+    fn test_embed_and_read_roundtrip() {
+        let entries = vec![
+            ("pixelsrc:object".to_string(), "hero".to_string()),
+            ("pixelsrc:source".to_string(), "art/タイトル.pxl".to_string()),
+        ];
+        let tagged = embed_text(&png(), &entries).unwrap();
+        assert_eq!(read_text(&tagged).unwrap(), entries);
+
+        // Still a valid PNG with the same pixels
+        let image = image::load_from_memory(&tagged).unwrap();
+        assert_eq!(image.to_rgba8().dimensions(), (2, 2));
+        assert!(read_text(&png()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_compressed_text() {
+        let mut compressed = flate2::write::ZlibEncoder::new(Vec::new(), Default::default());
+        compressed.write_all(b"made elsewhere").unwrap();
+        let mut data = b"Comment\0\0".to_vec();
+        data.extend(compressed.finish().unwrap());
+
+        let base = png();
+        let mut tagged = base[..33].to_vec();
+        write_chunk(&mut tagged, b"zTXt", &data);
+        tagged.extend_from_slice(&base[33..]);
+        assert_eq!(
+            read_text(&tagged).unwrap(),
+            vec![("Comment".to_string(), "made elsewhere".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(read_text(b"GIF89a"), Err(PngTextError::NotPng)));
+        assert!(matches!(read_text(&png()[..40]), Err(PngTextError::Truncated)));
+        let bad = vec![(String::new(), "x".to_string())];
+        assert!(matches!(embed_text(&png(), &bad), Err(PngTextError::InvalidKeyword(_))));
+    }
+
+    #[test]
+    fn test_provenance_entries() {
+        let sprite = serde_json::json!({"name": "hero", "size": [2, 2]});
+        let provenance =
+            Provenance::new(Path::new("hero.pxl"), "hero", &sprite).with_palette(Some("warm"));
+        let keys: Vec<String> = provenance.entries().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(
+            keys,
+            [
+                "pixelsrc:source",
+                "pixelsrc:object",
+                "pixelsrc:palette",
+                "pixelsrc:hash",
+                "pixelsrc:version"
+            ]
+        );
+        assert!(provenance.hash.starts_with("fnv1a:"));
+
+        // Key order doesn't change the hash, content does
+        let reordered = serde_json::json!({"size": [2, 2], "name": "hero"});
+        assert_eq!(content_hash(&reordered), provenance.hash);
+        assert_ne!(content_hash(&serde_json::json!({"name": "hero"})), provenance.hash);
+
+        assert_eq!(Provenance::new(Path::new("-"), "hero", &sprite).source, None);
+    }
+}
@@ -12,7 +12,8 @@ use crate::color;
 /// representation.
 ///
 /// Accepts any format supported by the pixelsrc color parser: hex (`#f00`,
-/// `#ff0000`), functional (`rgb()`, `hsl()`, `hwb()`, `oklch()`), named
+/// `#ff0000`), functional (`rgb()`, `hsl()`, `hwb()`, `lab()`, `lch()`,
+/// `oklch()`, `color()`), relative (`rgb(from red r g b / 0.5)`), named
 /// (`red`, `blue`, `transparent`), and `color-mix()`.
 ///
 /// Returns a lowercase hex string. Colors with full opacity use the 6-digit
@@ -133,16 +133,9 @@ impl PyRegistry {
 
         let (image, _) = crate::renderer::render_resolved(&resolved);
 
-        let mut png_data = Vec::new();
-        {
-            use image::ImageEncoder;
-            let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
-            encoder
-                .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8)
-                .map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!("PNG encoding failed: {}", e))
-                })?;
-        }
+        let png_data = crate::output::encode_png(&image).map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("PNG encoding failed: {}", e))
+        })?;
 
         Ok(PyBytes::new(py, &png_data))
     }
@@ -160,24 +153,12 @@ impl PyRegistry {
 
             let (image, _) = crate::renderer::render_resolved(&resolved);
 
-            let mut png_data = Vec::new();
-            {
-                use image::ImageEncoder;
-                let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
-                encoder
-                    .write_image(
-                        image.as_raw(),
-                        image.width(),
-                        image.height(),
-                        image::ColorType::Rgba8,
-                    )
-                    .map_err(|e| {
-                        pyo3::exceptions::PyRuntimeError::new_err(format!(
-                            "PNG encoding failed for '{}': {}",
-                            name, e
-                        ))
-                    })?;
-            }
+            let png_data = crate::output::encode_png(&image).map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                    "PNG encoding failed for '{}': {}",
+                    name, e
+                ))
+            })?;
 
             dict.set_item(name, PyBytes::new(py, &png_data))?;
         }
@@ -53,14 +53,7 @@ pub fn render_to_png<'py>(py: Python<'py>, pxl: &str) -> Bound<'py, PyBytes> {
     let resolved = registry.resolve_lenient(sprite);
     let (image, _render_warnings) = render_sprite(sprite, &resolved.palette.colors);
 
-    let mut png_data = Vec::new();
-    {
-        use image::ImageEncoder;
-        let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
-        encoder
-            .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8)
-            .ok();
-    }
+    let png_data = crate::output::encode_png(&image).unwrap_or_default();
 
     PyBytes::new(py, &png_data)
 }
@@ -0,0 +1,372 @@
+//! Random sets: seeded expansion of weighted alternatives
+//!
+//! `pxl expand` turns a `random_set` into concrete objects, for variety
+//! without hand-authoring every permutation:
+//!
+//! ```json5
+//! { type: "random_set", name: "rock", choices: ["rock_round", { name: "rock_flat", weight: 2 }],
+//!   regions: { moss: [{ region: { rect: [1, 0, 3, 1] } }, { weight: 2 }] } }
+//! ```
+//!
+//! Expanding `rock` five times gives the sprites `rock_1` to `rock_5`, each
+//! a copy of a weighted pick of `choices` with one weighted alternative per
+//! token of `regions` (an alternative without a region removes the token).
+//! A composition choice is copied with every sprite map entry that names a
+//! random set replaced by a pick from that set. A choice may itself be a
+//! random set, which is expanded in its place.
+//!
+//! Picks draw from the set's [`Rng`] stream (its `seed`, or its name, under
+//! the global seed), so an expansion is the same on every run and platform.
+//! Generated objects are distinct: repeats are drawn again, and a set with
+//! fewer combinations than requested yields as many as it has.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{Composition, RandomSet, Sprite, SpriteRef, TtpObject};
+use crate::rng::Rng;
+
+/// Draws per requested object before giving up on finding a new combination.
+pub const MAX_ATTEMPTS_PER_OBJECT: usize = 32;
+
+/// The result of expanding a random set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    /// Generated sprites or compositions, in order
+    pub objects: Vec<TtpObject>,
+    /// Problems that didn't stop the expansion
+    pub warnings: Vec<String>,
+}
+
+/// Sprites, compositions and random sets a random set can choose from.
+#[derive(Debug, Clone, Default)]
+pub struct RandomSetExpander {
+    sprites: HashMap<String, Sprite>,
+    compositions: HashMap<String, Composition>,
+    sets: HashMap<String, RandomSet>,
+}
+
+impl RandomSetExpander {
+    /// Collect the choosable objects of a parsed document.
+    ///
+    /// Later definitions of a name win.
+    pub fn new(objects: &[TtpObject]) -> Self {
+        let mut expander = Self::default();
+        for object in objects {
+            match object {
+                TtpObject::Sprite(s) => {
+                    expander.sprites.insert(s.name.clone(), s.clone());
+                }
+                TtpObject::Composition(c) => {
+                    expander.compositions.insert(c.name.clone(), c.clone());
+                }
+                TtpObject::RandomSet(r) => {
+                    expander.sets.insert(r.name.clone(), r.clone());
+                }
+                _ => {}
+            }
+        }
+        expander
+    }
+
+    /// Names of the known random sets.
+    pub fn set_names(&self) -> impl Iterator<Item = &String> {
+        self.sets.keys()
+    }
+
+    /// Generate `count` distinct objects from the random set `name`.
+    ///
+    /// Objects are named `{name}_{n}`, numbered from 1 and zero-padded to
+    /// the width of `count`.
+    pub fn expand(&self, name: &str, count: usize) -> Result<Expansion, String> {
+        let set =
+            self.sets.get(name).ok_or_else(|| format!("No random_set named '{}' found", name))?;
+        let mut rng = Rng::for_object(&set.name, set.seed);
+        let width = count.to_string().len();
+
+        let mut objects = Vec::new();
+        let mut seen = HashSet::new();
+        let mut attempts = 0;
+        while objects.len() < count && attempts < count * MAX_ATTEMPTS_PER_OBJECT {
+            attempts += 1;
+            let object_name = format!("{}_{:0width$}", set.name, objects.len() + 1);
+            let object = self.generate(set, &object_name, &mut rng, &mut Vec::new())?;
+            if seen.insert(content_key(&object)) {
+                objects.push(object);
+            }
+        }
+
+        let mut warnings = Vec::new();
+        if objects.len() < count {
+            warnings.push(format!(
+                "random_set '{}' gave only {} distinct objects of {} requested",
+                set.name,
+                objects.len(),
+                count
+            ));
+        }
+        Ok(Expansion { objects, warnings })
+    }
+
+    /// One object from `set`, named `name`.
+    fn generate(
+        &self,
+        set: &RandomSet,
+        name: &str,
+        rng: &mut Rng,
+        stack: &mut Vec<String>,
+    ) -> Result<TtpObject, String> {
+        enter(stack, &set.name)?;
+        let choice = pick_choice(set, rng)?;
+        let object = if let Some(nested) = self.sets.get(choice) {
+            self.generate(nested, name, rng, stack)?
+        } else if let Some(sprite) = self.sprites.get(choice) {
+            TtpObject::Sprite(Sprite { name: name.to_string(), ..sprite.clone() })
+        } else if let Some(comp) = self.compositions.get(choice) {
+            let mut comp = Composition { name: name.to_string(), ..comp.clone() };
+            self.pick_map_entries(&mut comp, rng, stack)?;
+            TtpObject::Composition(comp)
+        } else {
+            return Err(format!(
+                "random_set '{}' choice '{}' is not a sprite, composition or random set",
+                set.name, choice
+            ));
+        };
+        stack.pop();
+
+        match object {
+            TtpObject::Sprite(mut sprite) => {
+                pick_regions(set, &mut sprite, rng)?;
+                Ok(TtpObject::Sprite(sprite))
+            }
+            object => Ok(object),
+        }
+    }
+
+    /// Replace the composition's sprite map entries that name random sets
+    /// with picks from those sets.
+    fn pick_map_entries(
+        &self,
+        comp: &mut Composition,
+        rng: &mut Rng,
+        stack: &mut Vec<String>,
+    ) -> Result<(), String> {
+        let mut keys: Vec<String> = comp.sprites.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            let Some(Some(entry)) = comp.sprites.get_mut(&key) else { continue };
+            let referenced = match entry {
+                SpriteRef::Name(name) => name,
+                SpriteRef::Instance(inst) if inst.variant.is_none() => &mut inst.sprite,
+                SpriteRef::Instance(_) => continue,
+            };
+            let Some(set) = self.sets.get(referenced.as_str()) else { continue };
+            *referenced = self.pick_name(set, rng, stack)?;
+        }
+        Ok(())
+    }
+
+    /// The name of a sprite or composition picked from `set`, following
+    /// nested random sets.
+    fn pick_name(
+        &self,
+        set: &RandomSet,
+        rng: &mut Rng,
+        stack: &mut Vec<String>,
+    ) -> Result<String, String> {
+        enter(stack, &set.name)?;
+        let choice = pick_choice(set, rng)?;
+        let name = match self.sets.get(choice) {
+            Some(nested) => self.pick_name(nested, rng, stack)?,
+            None => choice.to_string(),
+        };
+        stack.pop();
+        Ok(name)
+    }
+}
+
+/// Push `name` onto the expansion stack, failing on a cycle.
+fn enter(stack: &mut Vec<String>, name: &str) -> Result<(), String> {
+    if stack.iter().any(|s| s == name) {
+        return Err(format!("random_set cycle: {} -> {}", stack.join(" -> "), name));
+    }
+    stack.push(name.to_string());
+    Ok(())
+}
+
+/// A weighted pick from the set's choices.
+fn pick_choice<'a>(set: &'a RandomSet, rng: &mut Rng) -> Result<&'a str, String> {
+    let weights: Vec<f64> = set.choices.iter().map(|c| c.weight()).collect();
+    let index = weighted_index(&weights, rng)
+        .ok_or_else(|| format!("random_set '{}' has no choices with positive weight", set.name))?;
+    Ok(set.choices[index].name())
+}
+
+/// Apply one weighted alternative per token of the set's `regions`.
+fn pick_regions(set: &RandomSet, sprite: &mut Sprite, rng: &mut Rng) -> Result<(), String> {
+    let mut tokens: Vec<&String> = set.regions.keys().collect();
+    tokens.sort();
+    for token in tokens {
+        let alternatives = &set.regions[token];
+        let weights: Vec<f64> = alternatives.iter().map(|a| a.weight()).collect();
+        let index = weighted_index(&weights, rng).ok_or_else(|| {
+            format!(
+                "random_set '{}' region '{}' has no alternatives with positive weight",
+                set.name, token
+            )
+        })?;
+        match &alternatives[index].region {
+            Some(region) => {
+                sprite
+                    .regions
+                    .get_or_insert_with(HashMap::new)
+                    .insert(token.clone(), region.clone());
+            }
+            None => {
+                if let Some(regions) = &mut sprite.regions {
+                    regions.remove(token);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Index picked with probability proportional to its weight.
+///
+/// Weights that aren't positive and finite are never picked; `None` if no
+/// weight is.
+pub fn weighted_index(weights: &[f64], rng: &mut Rng) -> Option<usize> {
+    let usable = |w: f64| w.is_finite() && w > 0.0;
+    let total: f64 = weights.iter().copied().filter(|&w| usable(w)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut target = rng.next_f64() * total;
+    let mut last = None;
+    for (index, &weight) in weights.iter().enumerate().filter(|(_, &w)| usable(w)) {
+        if target < weight {
+            return Some(index);
+        }
+        target -= weight;
+        last = Some(index);
+    }
+    // Rounding can leave a sliver past the last weight
+    last
+}
+
+/// An object's content without its name, for spotting repeats.
+fn content_key(object: &TtpObject) -> String {
+    let mut value = serde_json::to_value(object).expect("object serialization");
+    if let Some(map) = value.as_object_mut() {
+        map.remove("name");
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_stream;
+    use std::io::Cursor;
+
+    const ROCKS: &str = r##"{"type": "palette", "name": "stone", "colors": {"_": "#0000", "r": "#888", "m": "#3A5", "c": "#222"}}
+{"type": "sprite", "name": "rock_round", "size": [4, 4], "palette": "stone", "regions": {"r": {"circle": [2, 2, 2]}}}
+{"type": "sprite", "name": "rock_flat", "size": [4, 4], "palette": "stone", "regions": {"r": {"rect": [0, 2, 4, 2]}, "m": {"points": [[0, 2]]}}}
+{"type": "random_set", "name": "rock", "choices": ["rock_round", {"name": "rock_flat", "weight": 2}], "regions": {"m": [{"region": {"rect": [1, 1, 2, 1]}}, {"weight": 2}], "c": [{"region": {"points": [[1, 3]]}}, {"region": {"points": [[2, 2]]}}]}}"##;
+
+    fn expander(input: &str) -> RandomSetExpander {
+        RandomSetExpander::new(&parse_stream(Cursor::new(input)).objects)
+    }
+
+    fn sprites(expansion: &Expansion) -> Vec<&Sprite> {
+        expansion
+            .objects
+            .iter()
+            .map(|o| match o {
+                TtpObject::Sprite(s) => s,
+                other => panic!("expected sprite, got {}", other.kind()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_is_seeded_and_distinct() {
+        let expander = expander(ROCKS);
+        let first = expander.expand("rock", 6).unwrap();
+        assert!(first.warnings.is_empty(), "{:?}", first.warnings);
+        assert_eq!(first, expander.expand("rock", 6).unwrap());
+
+        let rocks = sprites(&first);
+        let names: Vec<&str> = rocks.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["rock_1", "rock_2", "rock_3", "rock_4", "rock_5", "rock_6"]);
+        let keys: HashSet<String> = first.objects.iter().map(content_key).collect();
+        assert_eq!(keys.len(), 6);
+        for rock in rocks {
+            let regions = rock.regions.as_ref().unwrap();
+            assert!(regions.contains_key("r") && regions.contains_key("c"));
+            if let Some(moss) = regions.get("m") {
+                assert_eq!(moss.rect, Some([1, 1, 2, 1]));
+            }
+        }
+
+        let reseeded = crate::rng::with_global_seed(3, || expander.expand("rock", 6).unwrap());
+        assert_ne!(first, reseeded);
+    }
+
+    #[test]
+    fn test_expand_stops_at_distinct_combinations() {
+        // 2 choices x 2 moss x 2 crack alternatives
+        let expansion = expander(ROCKS).expand("rock", 20).unwrap();
+        assert_eq!(expansion.objects.len(), 8);
+        assert_eq!(expansion.objects[0].name(), "rock_01");
+        assert_eq!(
+            expansion.warnings,
+            ["random_set 'rock' gave only 8 distinct objects of 20 requested"]
+        );
+    }
+
+    #[test]
+    fn test_expand_compositions_pick_map_entries() {
+        let input = format!(
+            "{}\n{}\n{}",
+            ROCKS,
+            r#"{"type": "random_set", "name": "boulder", "choices": ["rock_round", "rock_flat"]}"#,
+            r#"{"type": "random_set", "name": "field", "choices": ["meadow"]}
+{"type": "composition", "name": "meadow", "size": [8, 4], "sprites": {".": null, "R": "boulder", "F": {"sprite": "boulder", "offset": [0, 1]}}, "layers": [{"map": ["R.F."]}]}"#
+        );
+        let expansion = expander(&input).expand("field", 3).unwrap();
+        assert_eq!(expansion.objects.len(), 3, "{:?}", expansion.warnings);
+        for object in &expansion.objects {
+            let TtpObject::Composition(comp) = object else { panic!("expected composition") };
+            assert!(comp.name.starts_with("field_"));
+            let r = comp.sprites["R"].as_ref().unwrap();
+            let f = comp.sprites["F"].as_ref().unwrap();
+            assert!(r.name().starts_with("rock_"), "{}", r.name());
+            assert!(f.name().starts_with("rock_"), "{}", f.name());
+            assert_eq!(f.offset(), [0, 1]);
+        }
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let input = r#"{"type": "random_set", "name": "a", "choices": ["b"]}
+{"type": "random_set", "name": "b", "choices": ["a"]}
+{"type": "random_set", "name": "ghost", "choices": ["nothing"]}"#;
+        let expander = expander(input);
+        assert!(expander.expand("a", 1).unwrap_err().contains("cycle: a -> b -> a"));
+        assert!(expander.expand("ghost", 1).unwrap_err().contains("'nothing' is not a sprite"));
+        assert!(expander.expand("missing", 1).unwrap_err().contains("No random_set"));
+    }
+
+    #[test]
+    fn test_weighted_index() {
+        let mut rng = Rng::new(7);
+        let mut counts = [0; 3];
+        for _ in 0..3000 {
+            counts[weighted_index(&[1.0, 0.0, 2.0], &mut rng).unwrap()] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!((1800..2200).contains(&counts[2]), "{:?}", counts);
+        assert_eq!(weighted_index(&[0.0, -1.0, f64::NAN], &mut rng), None);
+    }
+}
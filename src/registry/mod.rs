@@ -416,6 +416,37 @@ mod tests {
         assert_eq!(result.colors.get("{darkest}"), Some(&"#0F380F".to_string()));
     }
 
+    #[test]
+    fn test_palette_registry_apply_theme() {
+        let mut registry = PaletteRegistry::new();
+        registry.register(mono_palette());
+        registry.register(Palette {
+            name: "inverted".to_string(),
+            colors: HashMap::from([
+                ("{on}".to_string(), "#000000".to_string()),
+                ("{off}".to_string(), "#FFFFFF".to_string()),
+            ]),
+            ..Default::default()
+        });
+        let theme = crate::models::Theme {
+            name: "night".to_string(),
+            palettes: HashMap::from([
+                ("mono".to_string(), "inverted".to_string()),
+                ("inverted".to_string(), "@gameboy".to_string()),
+                ("other".to_string(), "missing".to_string()),
+            ]),
+        };
+
+        let missing = registry.apply_theme(&theme);
+
+        assert_eq!(missing, ["missing"]);
+        let result = registry.resolve_strict(&checker_sprite_named()).unwrap();
+        assert_eq!(result.source, PaletteSource::Named("mono".to_string()));
+        assert_eq!(result.colors.get("{on}"), Some(&"#000000".to_string()));
+        // Swaps read the registry from before the theme applied
+        assert!(registry.get("inverted").unwrap().colors.contains_key("{lightest}"));
+    }
+
     #[test]
     fn test_all_builtins_resolvable() {
         let registry = PaletteRegistry::new();
@@ -955,7 +986,10 @@ mod tests {
             name: "variant".to_string(),
             base: "base".to_string(),
             palette: HashMap::from([("{a}".to_string(), "#0000FF".to_string())]),
+            regions: HashMap::new(),
             transform: Some(vec![TransformSpec::String("mirror-h".to_string())]),
+            opacity: None,
+            locale: HashMap::new(),
         };
         sprite_registry.register_variant(variant);
 
@@ -967,6 +1001,95 @@ mod tests {
         assert_eq!(result.palette.get("{b}").unwrap(), "#00FF00");
     }
 
+    #[test]
+    fn test_resolve_opacity() {
+        let palette_registry = PaletteRegistry::new();
+        let mut sprite_registry = SpriteRegistry::new();
+        sprite_registry.register_sprite(Sprite { opacity: Some(0.5), ..hero_sprite() });
+        sprite_registry.register_sprite(Sprite {
+            name: "hero_copy".to_string(),
+            source: Some("hero".to_string()),
+            ..Default::default()
+        });
+        sprite_registry.register_variant(hero_red_variant());
+        sprite_registry.register_variant(Variant { opacity: Some(0.25), ..hero_alt_variant() });
+
+        let opacity =
+            |name| sprite_registry.resolve(name, &palette_registry, false).unwrap().opacity;
+        assert_eq!(opacity("hero"), Some(0.5));
+        assert_eq!(opacity("hero_copy"), Some(0.5), "derived sprites keep the source's opacity");
+        assert_eq!(opacity("hero_red"), Some(0.5), "variants inherit the base's opacity");
+        assert_eq!(opacity("hero_alt"), Some(0.25), "variant opacity replaces the base's");
+    }
+
+    #[test]
+    fn test_resolve_variant_region_patches() {
+        let palette_registry = PaletteRegistry::new();
+        let mut sprite_registry = SpriteRegistry::new();
+        let region = |json: &str| serde_json::from_str::<RegionDef>(json).unwrap();
+        sprite_registry.register_sprite(Sprite {
+            regions: Some(HashMap::from([
+                ("skin".to_string(), region(r#"{"rect": [0, 0, 4, 2]}"#)),
+                ("hair".to_string(), region(r#"{"rect": [0, 0, 4, 1]}"#)),
+            ])),
+            ..hero_sprite()
+        });
+        sprite_registry.register_variant(Variant {
+            name: "hero_hat".to_string(),
+            base: "hero".to_string(),
+            regions: HashMap::from([
+                ("hat".to_string(), Some(region(r#"{"rect": [0, 0, 4, 1], "z": 5}"#))),
+                ("hair".to_string(), None),
+            ]),
+            ..Default::default()
+        });
+        // Patches build on other variants
+        sprite_registry.register_variant(Variant {
+            name: "hero_hat_red".to_string(),
+            base: "hero_hat".to_string(),
+            palette: HashMap::from([("{hat}".to_string(), "#FF0000".to_string())]),
+            regions: HashMap::from([("gone".to_string(), None)]),
+            ..Default::default()
+        });
+
+        let hat = sprite_registry.resolve("hero_hat", &palette_registry, true).unwrap();
+        let regions = hat.regions.unwrap();
+        assert!(regions.contains_key("hat"));
+        assert!(!regions.contains_key("hair"), "null removes a region");
+        assert_eq!(regions["skin"], region(r#"{"rect": [0, 0, 4, 2]}"#));
+        assert!(hat.warnings.is_empty());
+
+        let red = sprite_registry.resolve("hero_hat_red", &palette_registry, false).unwrap();
+        assert_eq!(red.size, Some([4, 4]));
+        assert!(red.regions.unwrap().contains_key("hat"));
+        assert_eq!(red.palette["{hat}"], "#FF0000");
+        assert_eq!(red.palette["{skin}"], "#FFCC99");
+        assert_eq!(red.warnings.len(), 1);
+        assert!(red.warnings[0].message.contains("removes region 'gone'"));
+        assert_eq!(sprite_registry.root_sprite("hero_hat_red").unwrap().name, "hero");
+    }
+
+    #[test]
+    fn test_resolve_variant_cycle() {
+        let palette_registry = PaletteRegistry::new();
+        let mut sprite_registry = SpriteRegistry::new();
+        for (name, base) in [("a", "b"), ("b", "a")] {
+            sprite_registry.register_variant(Variant {
+                name: name.to_string(),
+                base: base.to_string(),
+                ..Default::default()
+            });
+        }
+
+        let err = sprite_registry.resolve("a", &palette_registry, true).unwrap_err();
+        assert!(matches!(err, SpriteError::CircularReference { .. }));
+        assert!(err.to_string().contains("a -> b -> a"));
+
+        let lenient = sprite_registry.resolve("a", &palette_registry, false).unwrap();
+        assert!(lenient.warnings[0].message.contains("Circular reference"));
+        assert!(sprite_registry.root_sprite("a").is_none());
+    }
+
     // ========== Registry Trait Tests ==========
 
     #[test]
@@ -1099,10 +1222,19 @@ mod tests {
             size: Some([16, 16]),
             cell_size: Some([8, 8]),
             sprites: HashMap::from([
-                ("hero".to_string(), Some("hero".to_string())),
-                ("bg".to_string(), Some("background".to_string())),
+                ("hero".to_string(), Some("hero".into())),
+                ("bg".to_string(), Some("background".into())),
             ]),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         }
     }
 
@@ -1114,6 +1246,15 @@ mod tests {
             cell_size: None,
             sprites: HashMap::new(),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         }
     }
 
@@ -1147,6 +1288,15 @@ mod tests {
             cell_size: None,
             sprites: HashMap::new(),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
         let comp2 = Composition {
             name: "scene".to_string(),
@@ -1155,6 +1305,15 @@ mod tests {
             cell_size: None,
             sprites: HashMap::new(),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
 
         registry.register(comp1);
@@ -1303,6 +1462,15 @@ mod tests {
             cell_size: None,
             sprites: HashMap::new(),
             layers: vec![],
+            groups: vec![],
+            locale: HashMap::new(),
+            auto_sort: None,
+            map_delimiter: None,
+            labels: Vec::new(),
+            palette_lock: None,
+            lights: Vec::new(),
+            ambient: None,
+            camera: None,
         };
         composition_registry.register(composition);
 
@@ -1345,6 +1513,8 @@ mod tests {
             )])),
             roles: None,
             relationships: None,
+            aliases: None,
+            deprecated: None,
         };
 
         registry.register(palette);
@@ -1363,6 +1533,66 @@ mod tests {
         assert!(stored.ramps.is_none(), "Ramps should be None after expansion");
     }
 
+    #[test]
+    fn test_palette_alias_expansion() {
+        let mut registry = PaletteRegistry::new();
+        registry.register(Palette {
+            name: "skin_tones".to_string(),
+            colors: HashMap::from([
+                ("{skin}".to_string(), "#E8B89D".to_string()),
+                ("{hair}".to_string(), "#4A2F1B".to_string()),
+            ]),
+            aliases: Some(HashMap::from([
+                ("{skn}".to_string(), "{skin}".to_string()),
+                ("{face}".to_string(), "{skn}".to_string()),
+                ("{hair}".to_string(), "{skin}".to_string()),
+                ("{missing}".to_string(), "{nothing}".to_string()),
+            ])),
+            deprecated: Some(HashMap::from([("{flesh}".to_string(), "{skin}".to_string())])),
+            ..Default::default()
+        });
+
+        let stored = registry.get("skin_tones").unwrap();
+        assert_eq!(stored.colors["{skn}"], "#E8B89D");
+        assert_eq!(stored.colors["{face}"], "#E8B89D", "aliases can chain");
+        assert_eq!(stored.colors["{flesh}"], "#E8B89D");
+        assert_eq!(stored.colors["{hair}"], "#4A2F1B", "defined tokens keep their color");
+        assert!(!stored.colors.contains_key("{missing}"));
+    }
+
+    #[test]
+    fn test_palette_deprecation_warnings() {
+        let mut palette_registry = PaletteRegistry::new();
+        palette_registry.register(Palette {
+            name: "p".to_string(),
+            colors: HashMap::from([("skin".to_string(), "#E8B89D".to_string())]),
+            deprecated: Some(HashMap::from([("flesh".to_string(), "skin".to_string())])),
+            ..Default::default()
+        });
+        let sprite = Sprite {
+            name: "hero".to_string(),
+            size: Some([1, 1]),
+            palette: PaletteRef::Named("p".to_string()),
+            regions: Some(HashMap::from([(
+                "flesh".to_string(),
+                RegionDef { points: Some(vec![[0, 0]]), ..Default::default() },
+            )])),
+            ..Default::default()
+        };
+
+        let warnings = palette_registry.deprecation_warnings(&sprite);
+        assert_eq!(warnings, vec![PaletteWarning::deprecated_token("p", "flesh", "skin")],);
+
+        let mut sprite_registry = SpriteRegistry::new();
+        sprite_registry.register_sprite(sprite);
+        let resolved = sprite_registry.resolve("hero", &palette_registry, true).unwrap();
+        assert_eq!(resolved.palette["flesh"], "#E8B89D");
+        assert_eq!(
+            resolved.warnings[0].message,
+            "Token flesh in palette 'p' is deprecated, use skin instead"
+        );
+    }
+
     #[test]
     fn test_palette_ramp_expansion_5_steps() {
         use crate::models::ColorRamp;
@@ -1383,6 +1613,8 @@ mod tests {
             )])),
             roles: None,
             relationships: None,
+            aliases: None,
+            deprecated: None,
         };
 
         registry.register(palette);
@@ -1420,6 +1652,8 @@ mod tests {
             )])),
             roles: None,
             relationships: None,
+            aliases: None,
+            deprecated: None,
         };
 
         registry.register(palette);
@@ -1466,6 +1700,8 @@ mod tests {
             ])),
             roles: None,
             relationships: None,
+            aliases: None,
+            deprecated: None,
         };
 
         registry.register(palette);
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use thiserror::Error;
 
 use crate::color::generate_ramp;
-use crate::models::{Palette, PaletteRef, Sprite};
+use crate::models::{Palette, PaletteRef, Sprite, Theme};
 use crate::palette_parser::{PaletteParser, ParseMode};
 use crate::palettes;
 
@@ -60,6 +60,15 @@ impl PaletteWarning {
     pub fn builtin_not_found(name: &str) -> Self {
         Self { message: format!("Built-in palette '{}' not found", name) }
     }
+
+    pub fn deprecated_token(palette: &str, token: &str, replacement: &str) -> Self {
+        Self {
+            message: format!(
+                "Token {} in palette '{}' is deprecated, use {} instead",
+                token, palette, replacement
+            ),
+        }
+    }
 }
 
 /// Resolution result for lenient mode - always succeeds but may have warnings.
@@ -114,12 +123,47 @@ impl PaletteRegistry {
     /// Register a palette in the registry.
     ///
     /// If a palette with the same name already exists, it is replaced.
-    /// Color ramps are automatically expanded into individual color tokens.
+    /// Color ramps are automatically expanded into individual color tokens,
+    /// and aliases and deprecated names into copies of the tokens they name.
     pub fn register(&mut self, palette: Palette) {
-        let expanded = Self::expand_ramps(palette);
+        let expanded = Self::expand_aliases(Self::expand_ramps(palette));
         self.palettes.insert(expanded.name.clone(), expanded);
     }
 
+    /// Give each alias and deprecated token the color of the token it names.
+    ///
+    /// Targets may themselves be aliases. Tokens already defined in `colors`
+    /// keep their own color, and aliases whose chain never reaches a color
+    /// (or loops) are left undefined, so they render as unknown tokens.
+    fn expand_aliases(mut palette: Palette) -> Palette {
+        let targets: HashMap<String, String> = palette
+            .aliases
+            .iter()
+            .chain(palette.deprecated.iter())
+            .flatten()
+            .map(|(alias, target)| (alias.clone(), target.clone()))
+            .collect();
+
+        for alias in targets.keys() {
+            if palette.colors.contains_key(alias) {
+                continue;
+            }
+            let mut target = &targets[alias];
+            for _ in 0..targets.len() {
+                if let Some(color) = palette.colors.get(target) {
+                    palette.colors.insert(alias.clone(), color.clone());
+                    break;
+                }
+                match targets.get(target) {
+                    Some(next) => target = next,
+                    None => break,
+                }
+            }
+        }
+
+        palette
+    }
+
     /// Expand color ramps into individual color tokens.
     ///
     /// For each ramp, generates tokens like:
@@ -172,11 +216,69 @@ impl PaletteRegistry {
         self.palettes.get(name)
     }
 
+    /// Warnings for deprecated tokens of the sprite's named palette that the
+    /// sprite's own regions and layers draw with.
+    ///
+    /// Each warning names the replacement token. Sorted by token.
+    pub fn deprecation_warnings(&self, sprite: &Sprite) -> Vec<PaletteWarning> {
+        let PaletteRef::Named(name) = &sprite.palette else {
+            return Vec::new();
+        };
+        let Some(deprecated) = self.palettes.get(name).and_then(|p| p.deprecated.as_ref()) else {
+            return Vec::new();
+        };
+
+        let layer_regions = sprite.layers.iter().flatten().map(|layer| &layer.regions);
+        let mut tokens: Vec<&String> = sprite
+            .regions
+            .iter()
+            .chain(layer_regions)
+            .flat_map(|regions| regions.keys())
+            .filter(|token| deprecated.contains_key(*token))
+            .collect();
+        tokens.sort();
+        tokens.dedup();
+
+        tokens
+            .into_iter()
+            .map(|token| PaletteWarning::deprecated_token(name, token, &deprecated[token]))
+            .collect()
+    }
+
     /// Check if a palette with the given name exists.
     pub fn contains(&self, name: &str) -> bool {
         self.palettes.contains_key(name)
     }
 
+    /// Swap palettes according to a theme.
+    ///
+    /// Each palette named in the theme is replaced by a copy of its
+    /// replacement (a registered palette or an `@name` built-in), so every
+    /// sprite referencing it resolves to the replacement colors. Swaps all
+    /// read the registry as it was before the theme, so `a → b, b → a` works.
+    ///
+    /// Returns the replacement names that could not be found, sorted.
+    pub fn apply_theme(&mut self, theme: &Theme) -> Vec<String> {
+        let mut missing = Vec::new();
+        let mut swapped = Vec::new();
+        for (name, replacement) in &theme.palettes {
+            let palette = match replacement.strip_prefix('@') {
+                Some(builtin) => palettes::get_builtin(builtin),
+                None => self.palettes.get(replacement).cloned(),
+            };
+            match palette {
+                Some(palette) => swapped.push(Palette { name: name.clone(), ..palette }),
+                None => missing.push(replacement.clone()),
+            }
+        }
+        for palette in swapped {
+            self.register(palette);
+        }
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+
     /// Resolve a sprite's palette reference in strict mode.
     ///
     /// Returns an error if a named palette is not found.
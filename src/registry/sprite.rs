@@ -3,7 +3,7 @@
 use std::collections::HashMap;
 use thiserror::Error;
 
-use crate::models::{RegionDef, Sprite, Variant};
+use crate::models::{RegionDef, Sprite, SpriteLayer, Variant};
 
 use super::palette::PaletteRegistry;
 use super::traits::Registry;
@@ -72,6 +72,10 @@ pub struct ResolvedSprite {
     pub nine_slice: Option<crate::models::NineSlice>,
     /// Structured regions for rendering
     pub regions: Option<HashMap<String, RegionDef>>,
+    /// Named layers rendered over `regions`
+    pub layers: Option<Vec<SpriteLayer>>,
+    /// Opacity applied to the rendered sprite (from the variant, else the sprite)
+    pub opacity: Option<f64>,
 }
 
 /// Registry for sprites and variants.
@@ -110,6 +114,22 @@ impl SpriteRegistry {
         self.variants.get(name)
     }
 
+    /// The sprite at the root of `name`'s variant chain.
+    ///
+    /// That is the sprite itself, or the sprite a variant is based on,
+    /// following bases through other variants. `None` if the chain ends at an
+    /// unknown name or loops.
+    pub fn root_sprite(&self, name: &str) -> Option<&Sprite> {
+        let mut current = name;
+        for _ in 0..=self.variants.len() {
+            if let Some(sprite) = self.sprites.get(current) {
+                return Some(sprite);
+            }
+            current = &self.variants.get(current)?.base;
+        }
+        None
+    }
+
     /// Check if a name refers to a sprite or variant.
     pub fn contains(&self, name: &str) -> bool {
         self.sprites.contains_key(name) || self.variants.contains_key(name)
@@ -148,6 +168,8 @@ impl SpriteRegistry {
                 warnings: vec![SpriteWarning::not_found(name)],
                 nine_slice: None,
                 regions: None,
+                layers: None,
+                opacity: None,
             })
         }
     }
@@ -194,6 +216,8 @@ impl SpriteRegistry {
                     }],
                     nine_slice: None,
                     regions: None,
+                    layers: None,
+                    opacity: None,
                 });
             }
         }
@@ -202,7 +226,9 @@ impl SpriteRegistry {
         visited.push(sprite.name.clone());
 
         // Resolve source sprite's regions and size if this sprite references another
-        let (base_regions, base_size) = if let Some(source_name) = &sprite.source {
+        let (base_regions, base_layers, base_size, base_opacity) = if let Some(source_name) =
+            &sprite.source
+        {
             match self.sprites.get(source_name) {
                 Some(source_sprite) => {
                     let source_resolved = self.resolve_sprite_internal(
@@ -212,7 +238,12 @@ impl SpriteRegistry {
                         visited,
                     )?;
                     warnings.extend(source_resolved.warnings);
-                    (source_resolved.regions, source_resolved.size)
+                    (
+                        source_resolved.regions,
+                        source_resolved.layers,
+                        source_resolved.size,
+                        source_resolved.opacity,
+                    )
                 }
                 None => {
                     if strict {
@@ -222,12 +253,12 @@ impl SpriteRegistry {
                         });
                     } else {
                         warnings.push(SpriteWarning::source_not_found(&sprite.name, source_name));
-                        (None, None)
+                        (None, None, None, None)
                     }
                 }
             }
         } else {
-            (sprite.regions.clone(), None)
+            (sprite.regions.clone(), sprite.layers.clone(), None, None)
         };
 
         // Resolve the sprite's palette
@@ -248,6 +279,9 @@ impl SpriteRegistry {
                 HashMap::new()
             }
         };
+        for warning in palette_registry.deprecation_warnings(sprite) {
+            warnings.push(SpriteWarning { message: warning.message });
+        }
 
         Ok(ResolvedSprite {
             name: sprite.name.clone(),
@@ -257,6 +291,9 @@ impl SpriteRegistry {
             warnings,
             nine_slice: sprite.nine_slice.clone(),
             regions: base_regions,
+            layers: base_layers,
+            // Derived sprites keep their source's opacity unless they set their own
+            opacity: sprite.opacity.or(base_opacity),
         })
     }
 
@@ -267,28 +304,119 @@ impl SpriteRegistry {
         palette_registry: &PaletteRegistry,
         strict: bool,
     ) -> Result<ResolvedSprite, SpriteError> {
-        // Look up the base sprite
-        let base_sprite = match self.sprites.get(&variant.base) {
-            Some(sprite) => sprite,
-            None => {
-                if strict {
-                    return Err(SpriteError::BaseNotFound {
-                        variant: variant.name.clone(),
-                        base: variant.base.clone(),
-                    });
-                } else {
-                    return Ok(ResolvedSprite {
-                        name: variant.name.clone(),
-                        size: None,
-                        palette: HashMap::new(),
-                        warnings: vec![SpriteWarning::base_not_found(&variant.name, &variant.base)],
-                        nine_slice: None,
-                        regions: None,
-                    });
-                }
+        self.resolve_variant_internal(variant, palette_registry, strict, &mut Vec::new())
+    }
+
+    /// Internal variant resolution with cycle detection.
+    ///
+    /// A variant's base is a sprite or another variant; `visited` tracks the
+    /// variants in the current chain, as for sprite `source` references.
+    fn resolve_variant_internal(
+        &self,
+        variant: &Variant,
+        palette_registry: &PaletteRegistry,
+        strict: bool,
+        visited: &mut Vec<String>,
+    ) -> Result<ResolvedSprite, SpriteError> {
+        // Check for circular reference
+        if visited.contains(&variant.name) {
+            visited.push(variant.name.clone());
+            if strict {
+                return Err(SpriteError::CircularReference {
+                    sprite: variant.name.clone(),
+                    chain: visited.clone(),
+                });
             }
+            return Ok(ResolvedSprite {
+                name: variant.name.clone(),
+                size: None,
+                palette: HashMap::new(),
+                warnings: vec![SpriteWarning {
+                    message: format!("Circular reference detected: {}", visited.join(" -> ")),
+                }],
+                nine_slice: None,
+                regions: None,
+                layers: None,
+                opacity: None,
+            });
+        }
+        visited.push(variant.name.clone());
+
+        // Look up the base sprite, or the base variant to patch further
+        let base = if let Some(base_sprite) = self.sprites.get(&variant.base) {
+            self.expand_base_sprite(base_sprite, palette_registry, strict)?
+        } else if let Some(base_variant) = self.variants.get(&variant.base) {
+            self.resolve_variant_internal(base_variant, palette_registry, strict, visited)?
+        } else if strict {
+            return Err(SpriteError::BaseNotFound {
+                variant: variant.name.clone(),
+                base: variant.base.clone(),
+            });
+        } else {
+            return Ok(ResolvedSprite {
+                name: variant.name.clone(),
+                size: None,
+                palette: HashMap::new(),
+                warnings: vec![SpriteWarning::base_not_found(&variant.name, &variant.base)],
+                nine_slice: None,
+                regions: None,
+                layers: None,
+                opacity: None,
+            });
         };
 
+        let mut warnings = base.warnings;
+
+        // Merge palettes: start with base, override with variant's palette
+        let mut merged_palette = base.palette;
+        for (token, color) in &variant.palette {
+            merged_palette.insert(token.clone(), color.clone());
+        }
+
+        // Patch regions: replace or add each given region, remove `null` ones
+        let mut regions = base.regions;
+        if !variant.regions.is_empty() {
+            let patched = regions.get_or_insert_with(HashMap::new);
+            let mut tokens: Vec<_> = variant.regions.keys().collect();
+            tokens.sort();
+            for token in tokens {
+                match &variant.regions[token] {
+                    Some(region) => {
+                        patched.insert(token.clone(), region.clone());
+                    }
+                    None => {
+                        if patched.remove(token).is_none() {
+                            warnings.push(SpriteWarning {
+                                message: format!(
+                                    "Variant '{}' removes region '{}', which base '{}' does not have",
+                                    variant.name, token, variant.base
+                                ),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(ResolvedSprite {
+            name: variant.name.clone(),
+            size: base.size,
+            palette: merged_palette,
+            warnings,
+            nine_slice: base.nine_slice,
+            regions,
+            layers: base.layers,
+            opacity: variant.opacity.or(base.opacity),
+        })
+    }
+
+    /// Expand a variant's base sprite: its own palette, regions and layers.
+    fn expand_base_sprite(
+        &self,
+        base_sprite: &Sprite,
+        palette_registry: &PaletteRegistry,
+        strict: bool,
+    ) -> Result<ResolvedSprite, SpriteError> {
         let mut warnings = Vec::new();
 
         // Resolve the base sprite's palette
@@ -307,19 +435,15 @@ impl SpriteRegistry {
             }
         };
 
-        // Merge palettes: start with base, override with variant's palette
-        let mut merged_palette = base_palette;
-        for (token, color) in &variant.palette {
-            merged_palette.insert(token.clone(), color.clone());
-        }
-
         Ok(ResolvedSprite {
-            name: variant.name.clone(),
+            name: base_sprite.name.clone(),
             size: base_sprite.size,
-            palette: merged_palette,
+            palette: base_palette,
             warnings,
             nine_slice: base_sprite.nine_slice.clone(),
             regions: base_sprite.regions.clone(),
+            layers: base_sprite.layers.clone(),
+            opacity: base_sprite.opacity,
         })
     }
 
@@ -0,0 +1,234 @@
+//! Single-object render jobs
+//!
+//! A [`RenderJob`] names one sprite, composition, or animation plus its
+//! format, scale, optional crop rectangle, and output path. Jobs are the unit of work for
+//! `pxl render --manifest` and `pxl daemon`, and render from a shared
+//! [`PreviewCatalog`] so a source file is parsed once for many jobs.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::gif::{encode_gif_with_durations, GifOptions};
+use crate::output::{create_output, encode_png, is_stdio, scale_image};
+use crate::renderer::{crop_image, Rect};
+use crate::serve::PreviewCatalog;
+use crate::spritesheet::render_spritesheet;
+
+/// One object rendered to one output file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenderJob {
+    /// Source file (defaults are up to the caller)
+    #[serde(default)]
+    pub input: Option<PathBuf>,
+    #[serde(default)]
+    pub sprite: Option<String>,
+    #[serde(default)]
+    pub composition: Option<String>,
+    #[serde(default)]
+    pub animation: Option<String>,
+    /// Defaults to `gif` for `.gif` outputs, `spritesheet` for other
+    /// animation outputs, and `png` otherwise
+    #[serde(default)]
+    pub format: Option<JobFormat>,
+    #[serde(default = "default_scale")]
+    pub scale: u8,
+    /// Only render this `[x, y, w, h]` rectangle of a sprite or composition
+    #[serde(default)]
+    pub crop: Option<Rect>,
+    pub output: PathBuf,
+}
+
+/// Encoded output of a [`RenderJob`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobFormat {
+    Png,
+    Gif,
+    Spritesheet,
+}
+
+fn default_scale() -> u8 {
+    1
+}
+
+/// What a job renders
+#[derive(Debug, Clone, Copy)]
+pub enum JobTarget<'a> {
+    Sprite(&'a str),
+    Composition(&'a str),
+    Animation(&'a str),
+}
+
+impl RenderJob {
+    /// The object to render; exactly one of sprite, composition, or animation.
+    pub fn target(&self) -> Result<JobTarget<'_>, String> {
+        match (&self.sprite, &self.composition, &self.animation) {
+            (Some(name), None, None) => Ok(JobTarget::Sprite(name)),
+            (None, Some(name), None) => Ok(JobTarget::Composition(name)),
+            (None, None, Some(name)) => Ok(JobTarget::Animation(name)),
+            _ => Err("needs exactly one of sprite, composition or animation".to_string()),
+        }
+    }
+
+    /// The explicit format, or the default for the target and output extension.
+    pub fn format(&self) -> JobFormat {
+        self.format.unwrap_or_else(|| {
+            if self.output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif")) {
+                JobFormat::Gif
+            } else if self.animation.is_some() {
+                JobFormat::Spritesheet
+            } else {
+                JobFormat::Png
+            }
+        })
+    }
+
+    /// Short label for progress and error messages.
+    pub fn label(&self) -> String {
+        match self.target() {
+            Ok(JobTarget::Sprite(name)) => format!("sprite '{}'", name),
+            Ok(JobTarget::Composition(name)) => format!("composition '{}'", name),
+            Ok(JobTarget::Animation(name)) => format!("animation '{}'", name),
+            Err(_) => self.output.display().to_string(),
+        }
+    }
+
+    /// Check the job without rendering it.
+    pub fn validate(&self) -> Result<(), String> {
+        let target = self.target()?;
+        if !(1..=128).contains(&self.scale) {
+            return Err(format!("scale must be 1-128, got {}", self.scale));
+        }
+        if is_stdio(&self.output) {
+            return Err("output cannot be stdout".to_string());
+        }
+        if let Some(rect) = self.crop {
+            if rect.width == 0 || rect.height == 0 {
+                return Err("crop width and height must be positive".to_string());
+            }
+            if matches!(target, JobTarget::Animation(_)) {
+                return Err("crop only applies to sprites and compositions".to_string());
+            }
+        }
+        match (target, self.format()) {
+            (JobTarget::Animation(_), JobFormat::Png) => {
+                Err("animations render as gif or spritesheet, not png".to_string())
+            }
+            (
+                JobTarget::Sprite(_) | JobTarget::Composition(_),
+                JobFormat::Gif | JobFormat::Spritesheet,
+            ) => Err("gif and spritesheet formats need an animation".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Render the job and encode it as PNG or GIF bytes.
+    pub fn encode(&self, catalog: &PreviewCatalog) -> Result<Vec<u8>, String> {
+        let scale = |image| scale_image(image, self.scale);
+        let png = |image| encode_png(&image).map_err(|e| e.to_string());
+        match self.target()? {
+            JobTarget::Sprite(name) => match self.crop {
+                Some(rect) => png(scale(catalog.render_sprite_region(name, rect)?)),
+                None => png(scale(catalog.render_sprite(name)?)),
+            },
+            JobTarget::Composition(name) => {
+                let image = catalog.render_composition(name)?;
+                png(scale(match self.crop {
+                    Some(rect) => crop_image(&image, rect)?,
+                    None => image,
+                }))
+            }
+            JobTarget::Animation(name) => {
+                let (frames, durations, loops) = catalog.render_animation(name)?;
+                let frames: Vec<_> = frames.into_iter().map(scale).collect();
+                if self.format() == JobFormat::Gif {
+                    let mut bytes = Vec::new();
+                    encode_gif_with_durations(
+                        &frames,
+                        &durations,
+                        loops,
+                        &GifOptions::default(),
+                        &mut bytes,
+                    )
+                    .map_err(|e| e.to_string())?;
+                    Ok(bytes)
+                } else {
+                    png(render_spritesheet(&frames, None))
+                }
+            }
+        }
+    }
+}
+
+/// Write encoded job output, creating parent directories as needed.
+pub fn write_output(bytes: &[u8], path: &Path) -> Result<(), String> {
+    create_output(path)
+        .and_then(|mut file| file.write_all(bytes))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "red", "size": [2, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+{"type": "sprite", "name": "blue", "size": [2, 2], "palette": "p", "regions": {"b": {"rect": [0, 0, 2, 2]}}}
+{"type": "animation", "name": "flash", "frames": ["red", "blue"], "duration": 100}"##;
+
+    fn job(json: serde_json::Value) -> RenderJob {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_format_defaults() {
+        assert_eq!(
+            job(serde_json::json!({"sprite": "a", "output": "a.png"})).format(),
+            JobFormat::Png
+        );
+        assert_eq!(
+            job(serde_json::json!({"animation": "a", "output": "a.GIF"})).format(),
+            JobFormat::Gif
+        );
+        assert_eq!(
+            job(serde_json::json!({"animation": "a", "output": "a.png"})).format(),
+            JobFormat::Spritesheet
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(job(serde_json::json!({"sprite": "a", "output": "a.png"})).validate().is_ok());
+        let both = job(serde_json::json!({"sprite": "a", "animation": "b", "output": "a.png"}));
+        assert!(both.validate().unwrap_err().contains("exactly one"));
+        let gif_sprite = job(serde_json::json!({"sprite": "a", "output": "a.gif"}));
+        assert!(gif_sprite.validate().is_err());
+        let zero = job(serde_json::json!({"sprite": "a", "scale": 0, "output": "a.png"}));
+        assert!(zero.validate().is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        let catalog = PreviewCatalog::from_source(SOURCE);
+        let sprite = job(serde_json::json!({"sprite": "red", "scale": 2, "output": "a.png"}));
+        let image = image::load_from_memory(&sprite.encode(&catalog).unwrap()).unwrap();
+        assert_eq!(image.to_rgba8().dimensions(), (4, 4));
+
+        let gif = job(serde_json::json!({"animation": "flash", "output": "a.gif"}));
+        assert_eq!(&gif.encode(&catalog).unwrap()[0..6], b"GIF89a");
+
+        let crop =
+            job(serde_json::json!({"sprite": "red", "crop": [1, 0, 1, 2], "output": "a.png"}));
+        let image = image::load_from_memory(&crop.encode(&catalog).unwrap()).unwrap();
+        assert_eq!(image.to_rgba8().dimensions(), (1, 2));
+        let crop_anim =
+            job(serde_json::json!({"animation": "flash", "crop": [0, 0, 1, 1], "output": "a.gif"}));
+        assert!(crop_anim.validate().is_err());
+
+        let missing = job(serde_json::json!({"sprite": "green", "output": "a.png"}));
+        assert!(missing.encode(&catalog).unwrap_err().contains("not found"));
+    }
+}
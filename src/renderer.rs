@@ -2,24 +2,102 @@
 
 use crate::models::Sprite;
 use crate::registry::ResolvedSprite;
-use crate::structured::render_structured;
+use crate::structured::{
+    composite_layers, render_layers, render_structured, render_structured_region, LayerImages,
+};
 use image::{Rgba, RgbaImage};
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::str::FromStr;
 
-/// A warning generated during rendering
-#[derive(Debug, Clone, PartialEq)]
-pub struct Warning {
-    pub message: String,
+pub use crate::warning::{Warning, WarningCategory};
+
+/// Transparent color used for padding
+const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+/// A rectangle of pixels, e.g. the part of an image to render.
+///
+/// Parses from `"x,y,w,h"` on the command line and deserializes from an
+/// `[x, y, w, h]` array in JSON. Either way the width and height must be
+/// positive and the far edges must fit in a `u32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "[u32; 4]")]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
 }
 
-impl Warning {
-    pub fn new(message: impl Into<String>) -> Self {
-        Self { message: message.into() }
+impl Rect {
+    /// The part of this rectangle inside a `width`x`height` image.
+    ///
+    /// Fails if nothing of the rectangle is left, so callers never allocate
+    /// more than the image itself or an empty buffer.
+    pub fn clamp_to(self, width: u32, height: u32) -> Result<Rect, String> {
+        if self.x >= width || self.y >= height {
+            return Err(format!(
+                "crop {},{},{},{} lies outside the {}x{} image",
+                self.x, self.y, self.width, self.height, width, height
+            ));
+        }
+        Ok(Rect {
+            x: self.x,
+            y: self.y,
+            width: self.width.min(width - self.x),
+            height: self.height.min(height - self.y),
+        })
     }
 }
 
-/// Transparent color used for padding
-const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+impl TryFrom<[u32; 4]> for Rect {
+    type Error = String;
+
+    fn try_from([x, y, width, height]: [u32; 4]) -> Result<Self, Self::Error> {
+        if width == 0 || height == 0 {
+            return Err(format!("width and height must be positive, got {}x{}", width, height));
+        }
+        if x.checked_add(width).is_none() || y.checked_add(height).is_none() {
+            return Err(format!(
+                "{},{},{},{} extends past the largest coordinate",
+                x, y, width, height
+            ));
+        }
+        Ok(Self { x, y, width, height })
+    }
+}
+
+impl FromStr for Rect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values: Result<Vec<u32>, _> = s.split(',').map(|p| p.trim().parse()).collect();
+        let Ok(&[x, y, width, height]) = values.as_deref() else {
+            return Err(format!("expected x,y,w,h, got '{}'", s));
+        };
+        Self::try_from([x, y, width, height])
+    }
+}
+
+/// Copy the `rect` part of an image, clipped to the image's edges.
+///
+/// Fails if `rect` lies entirely outside the image.
+pub fn crop_image(image: &RgbaImage, rect: Rect) -> Result<RgbaImage, String> {
+    let rect = rect.clamp_to(image.width(), image.height())?;
+    Ok(image::imageops::crop_imm(image, rect.x, rect.y, rect.width, rect.height).to_image())
+}
+
+/// Multiply every pixel's alpha by `opacity`, clamped to 0.0-1.0.
+///
+/// `None` (and 1.0) leave the image untouched.
+pub fn apply_opacity(image: &mut RgbaImage, opacity: Option<f64>) {
+    let Some(opacity) = opacity.map(|o| o.clamp(0.0, 1.0)).filter(|&o| o < 1.0) else {
+        return;
+    };
+    for pixel in image.pixels_mut() {
+        pixel[3] = (pixel[3] as f64 * opacity).round() as u8;
+    }
+}
 
 /// Render a sprite to an RGBA image buffer.
 ///
@@ -43,6 +121,8 @@ const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
 /// - Row too long: Truncate with warning
 /// - Invalid color format: Use magenta with warning
 ///
+/// The sprite's `opacity`, if any, is applied to the result.
+///
 /// # Examples
 ///
 /// See tests/demos/ for usage examples with the regions-based format.
@@ -50,23 +130,44 @@ pub fn render_sprite(
     sprite: &Sprite,
     palette: &HashMap<String, String>,
 ) -> (RgbaImage, Vec<Warning>) {
+    let (mut image, warnings) = render_sprite_opaque(sprite, palette);
+    apply_opacity(&mut image, sprite.opacity);
+    (image, warnings)
+}
+
+/// [`render_sprite`] without the sprite's opacity.
+fn render_sprite_opaque(
+    sprite: &Sprite,
+    palette: &HashMap<String, String>,
+) -> (RgbaImage, Vec<Warning>) {
+    // Layered sprites composite their layers back-to-front
+    if let Some(layers) = sprite.layers.as_deref().filter(|l| !l.is_empty()) {
+        let (images, warnings) =
+            render_layers(&sprite.name, sprite.size, sprite.regions.as_ref(), layers, palette);
+        return (composite_layers(&images), warnings);
+    }
+
     // Structured sprites use regions for rendering
     if let Some(regions) = &sprite.regions {
         return render_structured(&sprite.name, sprite.size, regions, palette);
     }
 
     // Grid format is no longer supported - sprites must use regions
-    let warnings = vec![Warning::new(format!(
-        "Sprite '{}' uses deprecated grid format. Please convert to structured regions format.",
-        sprite.name
-    ))];
+    let warnings = vec![Warning::new(
+        WarningCategory::Render,
+        format!(
+            "Sprite '{}' uses deprecated grid format. Please convert to structured regions format.",
+            sprite.name
+        ),
+    )];
     (RgbaImage::from_pixel(1, 1, TRANSPARENT), warnings)
 }
 
 /// Render a ResolvedSprite (sprite or expanded variant) to an RGBA image buffer.
 ///
 /// This function is similar to `render_sprite` but takes a `ResolvedSprite`
-/// which already has the merged palette ready for rendering.
+/// which already has the merged palette ready for rendering, and applies its
+/// `opacity`.
 ///
 /// # Examples
 ///
@@ -78,19 +179,78 @@ pub fn render_sprite(
 /// let (image, warnings) = render_resolved(&resolved);
 /// ```
 pub fn render_resolved(resolved: &ResolvedSprite) -> (RgbaImage, Vec<Warning>) {
+    let (mut image, warnings) = render_resolved_opaque(resolved);
+    apply_opacity(&mut image, resolved.opacity);
+    (image, warnings)
+}
+
+/// [`render_resolved`] without the sprite's opacity.
+fn render_resolved_opaque(resolved: &ResolvedSprite) -> (RgbaImage, Vec<Warning>) {
+    // Layered sprites composite their layers back-to-front
+    if let Some((images, warnings)) = render_resolved_layers(resolved) {
+        return (composite_layers(&images), warnings);
+    }
+
     // Structured sprites use regions for rendering
     if let Some(regions) = &resolved.regions {
         return render_structured(&resolved.name, resolved.size, regions, &resolved.palette);
     }
 
     // Grid format is no longer supported - sprites must use regions
-    let warnings = vec![Warning::new(format!(
+    let warnings = vec![Warning::new(WarningCategory::Render, format!(
         "Sprite/variant '{}' uses deprecated grid format. Please convert to structured regions format.",
         resolved.name
     ))];
     (RgbaImage::from_pixel(1, 1, TRANSPARENT), warnings)
 }
 
+/// Render only the `rect` part of a ResolvedSprite.
+///
+/// `rect` is clipped to the sprite, and the result matches cropping
+/// [`render_resolved`]'s output with [`crop_image`]; a `rect` outside the
+/// sprite is an error. Region sprites write only the pixels inside `rect`;
+/// layered sprites are composited in full, then cropped.
+pub fn render_resolved_region(
+    resolved: &ResolvedSprite,
+    rect: Rect,
+) -> Result<(RgbaImage, Vec<Warning>), String> {
+    if resolved.layers.as_deref().is_some_and(|l| !l.is_empty()) {
+        let (image, warnings) = render_resolved(resolved);
+        return Ok((crop_image(&image, rect)?, warnings));
+    }
+
+    if let Some(regions) = &resolved.regions {
+        let (mut image, warnings) = render_structured_region(
+            &resolved.name,
+            resolved.size,
+            regions,
+            &resolved.palette,
+            rect,
+        )?;
+        apply_opacity(&mut image, resolved.opacity);
+        return Ok((image, warnings));
+    }
+
+    let (image, warnings) = render_resolved(resolved);
+    Ok((crop_image(&image, rect)?, warnings))
+}
+
+/// Render each layer of a layered ResolvedSprite to its own image.
+///
+/// Returns `None` if the sprite declares no layers. Otherwise the layers are
+/// returned back-to-front, with top-level regions as the implicit `base` layer
+/// (see [`render_layers`]).
+pub fn render_resolved_layers(resolved: &ResolvedSprite) -> Option<(LayerImages, Vec<Warning>)> {
+    let layers = resolved.layers.as_deref().filter(|l| !l.is_empty())?;
+    Some(render_layers(
+        &resolved.name,
+        resolved.size,
+        resolved.regions.as_ref(),
+        layers,
+        &resolved.palette,
+    ))
+}
+
 /// Render a nine-slice sprite to a target size.
 ///
 /// Nine-slice (or 9-patch) sprites are divided into 9 regions:
@@ -138,35 +298,47 @@ pub fn render_nine_slice(
     let min_height = nine_slice.top + nine_slice.bottom;
 
     if min_width > src_width {
-        warnings.push(Warning::new(format!(
-            "Nine-slice borders (left={} + right={}) exceed source width ({})",
-            nine_slice.left, nine_slice.right, src_width
-        )));
+        warnings.push(Warning::new(
+            WarningCategory::Render,
+            format!(
+                "Nine-slice borders (left={} + right={}) exceed source width ({})",
+                nine_slice.left, nine_slice.right, src_width
+            ),
+        ));
         return (source.clone(), warnings);
     }
 
     if min_height > src_height {
-        warnings.push(Warning::new(format!(
-            "Nine-slice borders (top={} + bottom={}) exceed source height ({})",
-            nine_slice.top, nine_slice.bottom, src_height
-        )));
+        warnings.push(Warning::new(
+            WarningCategory::Render,
+            format!(
+                "Nine-slice borders (top={} + bottom={}) exceed source height ({})",
+                nine_slice.top, nine_slice.bottom, src_height
+            ),
+        ));
         return (source.clone(), warnings);
     }
 
     // Validate target size can accommodate the fixed borders
     if target_width < min_width {
-        warnings.push(Warning::new(format!(
-            "Target width ({}) is less than minimum nine-slice width ({})",
-            target_width, min_width
-        )));
+        warnings.push(Warning::new(
+            WarningCategory::Render,
+            format!(
+                "Target width ({}) is less than minimum nine-slice width ({})",
+                target_width, min_width
+            ),
+        ));
         return (source.clone(), warnings);
     }
 
     if target_height < min_height {
-        warnings.push(Warning::new(format!(
-            "Target height ({}) is less than minimum nine-slice height ({})",
-            target_height, min_height
-        )));
+        warnings.push(Warning::new(
+            WarningCategory::Render,
+            format!(
+                "Target height ({}) is less than minimum nine-slice height ({})",
+                target_height, min_height
+            ),
+        ));
         return (source.clone(), warnings);
     }
 
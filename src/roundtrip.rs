@@ -0,0 +1,299 @@
+//! Render → import round-trip verification
+//!
+//! Renders each sprite, re-imports the PNG through [`crate::import`], renders
+//! the imported definition again, and compares the two. Any loss is attributed
+//! to a specific step so regressions in the importer/exporter pair are easy to
+//! locate:
+//!
+//! - **Color quantization**: the sprite uses more colors than the importer keeps
+//! - **Region approximation**: extracted shapes cover different pixels
+//! - **Token merge**: several tokens render to the same color and collapse into one
+//! - **Dimensions**: the imported sprite has a different size
+
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+
+use image::RgbaImage;
+
+use crate::import::{import_from_image_data, ImportOptions};
+use crate::models::{Sprite, TtpObject};
+use crate::output::encode_png;
+use crate::parser::parse_stream;
+use crate::registry::PaletteRegistry;
+use crate::renderer::render_sprite;
+
+/// Options for [`verify_roundtrip`]
+#[derive(Debug, Clone)]
+pub struct RoundtripOptions {
+    /// Maximum colors the importer may keep
+    pub max_colors: usize,
+    /// Import with shape extraction (rects/polygons) instead of raw points
+    pub extract_shapes: bool,
+}
+
+impl Default for RoundtripOptions {
+    fn default() -> Self {
+        Self { max_colors: 16, extract_shapes: true }
+    }
+}
+
+/// A step of the round trip that lost information
+#[derive(Debug, Clone, PartialEq)]
+pub enum LossyStep {
+    /// Imported size differs from the rendered size
+    Dimensions { original: (u32, u32), imported: (u32, u32) },
+    /// Colors were merged by quantization
+    ColorQuantization { original_colors: usize, imported_colors: usize, pixels: usize },
+    /// Region extraction changed which pixels are covered
+    RegionApproximation { pixels: usize },
+    /// Tokens that render to the same color were collapsed into one
+    TokenMerge { tokens: Vec<String> },
+}
+
+impl std::fmt::Display for LossyStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LossyStep::Dimensions { original, imported } => write!(
+                f,
+                "dimensions changed: {}x{} -> {}x{}",
+                original.0, original.1, imported.0, imported.1
+            ),
+            LossyStep::ColorQuantization { original_colors, imported_colors, pixels } => write!(
+                f,
+                "color quantization: {} -> {} colors ({} pixels changed)",
+                original_colors, imported_colors, pixels
+            ),
+            LossyStep::RegionApproximation { pixels } => {
+                write!(f, "region approximation: {} pixels differ", pixels)
+            }
+            LossyStep::TokenMerge { tokens } => {
+                write!(f, "tokens share a color and merge: {}", tokens.join(", "))
+            }
+        }
+    }
+}
+
+/// Round-trip result for one sprite
+#[derive(Debug, Clone)]
+pub struct RoundtripReport {
+    /// Sprite name
+    pub sprite: String,
+    /// Tokens in the original sprite's regions
+    pub original_tokens: usize,
+    /// Tokens in the imported sprite
+    pub imported_tokens: usize,
+    /// Pixels that differ between the original and re-imported renders
+    pub mismatched_pixels: usize,
+    /// Steps that lost information
+    pub lossy_steps: Vec<LossyStep>,
+}
+
+impl RoundtripReport {
+    /// True if the re-imported sprite renders pixel-identically.
+    pub fn is_exact(&self) -> bool {
+        self.mismatched_pixels == 0
+            && !self.lossy_steps.iter().any(|s| matches!(s, LossyStep::Dimensions { .. }))
+    }
+}
+
+fn unique_colors(image: &RgbaImage) -> HashSet<[u8; 4]> {
+    image.pixels().map(|p| normalize(p.0)).collect()
+}
+
+/// Treat every fully transparent pixel as the same color.
+fn normalize(pixel: [u8; 4]) -> [u8; 4] {
+    if pixel[3] == 0 {
+        [0, 0, 0, 0]
+    } else {
+        pixel
+    }
+}
+
+/// Render, re-import, and re-render a single sprite.
+pub fn verify_sprite(
+    sprite: &Sprite,
+    palette: &HashMap<String, String>,
+    options: &RoundtripOptions,
+) -> Result<RoundtripReport, String> {
+    let (original, _) = render_sprite(sprite, palette);
+    let png = encode_png(&original).map_err(|e| format!("PNG encoding failed: {}", e))?;
+
+    let import_options =
+        ImportOptions { extract_shapes: options.extract_shapes, ..Default::default() };
+    let imported = import_from_image_data(&png, &sprite.name, options.max_colors, &import_options)?;
+
+    // Render the imported definition exactly as a user would after `pxl import`
+    let parsed = parse_stream(Cursor::new(imported.to_jsonl()));
+    let mut palettes = PaletteRegistry::new();
+    let mut imported_sprite = None;
+    for obj in parsed.objects {
+        match obj {
+            TtpObject::Palette(p) => palettes.register(p),
+            TtpObject::Sprite(s) => imported_sprite = Some(s),
+            _ => {}
+        }
+    }
+    let imported_sprite =
+        imported_sprite.ok_or_else(|| "importer produced no sprite".to_string())?;
+    let imported_palette = palettes.resolve_lenient(&imported_sprite).palette.colors;
+    let (reimported, _) = render_sprite(&imported_sprite, &imported_palette);
+
+    let mut lossy_steps = Vec::new();
+
+    if original.dimensions() != reimported.dimensions() {
+        lossy_steps.push(LossyStep::Dimensions {
+            original: original.dimensions(),
+            imported: reimported.dimensions(),
+        });
+    }
+
+    // Attribute each differing pixel: if the original color survived import,
+    // the region shapes are to blame; otherwise quantization replaced it.
+    let original_colors = unique_colors(&original);
+    let kept_colors = unique_colors(&reimported);
+    let mut quantized_pixels = 0;
+    let mut region_pixels = 0;
+    let (width, height) = original.dimensions();
+    for y in 0..height {
+        for x in 0..width {
+            let before = normalize(original.get_pixel(x, y).0);
+            let after = reimported.get_pixel_checked(x, y).map(|p| normalize(p.0));
+            if after == Some(before) {
+                continue;
+            }
+            if kept_colors.contains(&before) {
+                region_pixels += 1;
+            } else {
+                quantized_pixels += 1;
+            }
+        }
+    }
+
+    if quantized_pixels > 0 {
+        lossy_steps.push(LossyStep::ColorQuantization {
+            original_colors: original_colors.len(),
+            imported_colors: kept_colors.len(),
+            pixels: quantized_pixels,
+        });
+    }
+    if region_pixels > 0 {
+        lossy_steps.push(LossyStep::RegionApproximation { pixels: region_pixels });
+    }
+
+    // Tokens that render identically can't be told apart after import
+    let original_tokens: Vec<&String> =
+        sprite.regions.as_ref().map(|r| r.keys().collect()).unwrap_or_default();
+    let mut by_color: HashMap<[u8; 4], Vec<String>> = HashMap::new();
+    for token in &original_tokens {
+        if let Some(rgba) = palette.get(*token).and_then(|c| crate::color::parse_color(c).ok()) {
+            let rgba = normalize([rgba[0], rgba[1], rgba[2], rgba[3]]);
+            if rgba[3] > 0 {
+                by_color.entry(rgba).or_default().push((*token).clone());
+            }
+        }
+    }
+    let mut merged: Vec<Vec<String>> = by_color.into_values().filter(|t| t.len() > 1).collect();
+    for tokens in &mut merged {
+        tokens.sort();
+    }
+    merged.sort();
+    lossy_steps.extend(merged.into_iter().map(|tokens| LossyStep::TokenMerge { tokens }));
+
+    Ok(RoundtripReport {
+        sprite: sprite.name.clone(),
+        original_tokens: original_tokens.len(),
+        imported_tokens: imported_sprite.regions.as_ref().map(|r| r.len()).unwrap_or(0),
+        mismatched_pixels: quantized_pixels + region_pixels,
+        lossy_steps,
+    })
+}
+
+/// Verify every sprite in `source` (optionally only `sprite_filter`).
+pub fn verify_roundtrip(
+    source: &str,
+    sprite_filter: Option<&str>,
+    options: &RoundtripOptions,
+) -> Result<Vec<RoundtripReport>, String> {
+    let parsed = parse_stream(Cursor::new(source));
+    let mut palettes = PaletteRegistry::new();
+    let mut sprites = Vec::new();
+    for obj in parsed.objects {
+        match obj {
+            TtpObject::Palette(p) => palettes.register(p),
+            TtpObject::Sprite(s) => sprites.push(s),
+            _ => {}
+        }
+    }
+
+    if let Some(name) = sprite_filter {
+        sprites.retain(|s| s.name == name);
+        if sprites.is_empty() {
+            return Err(format!("Sprite '{}' not found", name));
+        }
+    }
+
+    sprites
+        .iter()
+        .map(|sprite| {
+            let palette = palettes.resolve_lenient(sprite).palette.colors;
+            verify_sprite(sprite, &palette, options)
+                .map_err(|e| format!("sprite '{}': {}", sprite.name, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "a": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "flag", "size": [4, 2], "palette": "p", "regions": {"a": {"rect": [0, 0, 2, 2]}, "b": {"rect": [2, 0, 2, 2]}}}"##;
+
+    #[test]
+    fn test_simple_sprite_roundtrips_exactly() {
+        let reports = verify_roundtrip(SIMPLE, None, &RoundtripOptions::default()).unwrap();
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert!(report.is_exact(), "unexpected loss: {:?}", report.lossy_steps);
+        assert_eq!(report.original_tokens, 2);
+    }
+
+    #[test]
+    fn test_quantization_is_reported() {
+        let source = r##"{"type": "palette", "name": "p", "colors": {"a": "#FF0000", "b": "#FE0000", "c": "#0000FF"}}
+{"type": "sprite", "name": "s", "size": [3, 1], "palette": "p", "regions": {"a": {"points": [[0, 0]]}, "b": {"points": [[1, 0]]}, "c": {"points": [[2, 0]]}}}"##;
+        let options = RoundtripOptions { max_colors: 2, ..Default::default() };
+        let report = &verify_roundtrip(source, None, &options).unwrap()[0];
+        assert!(!report.is_exact());
+        assert!(report
+            .lossy_steps
+            .iter()
+            .any(|s| matches!(s, LossyStep::ColorQuantization { original_colors: 3, .. })));
+    }
+
+    #[test]
+    fn test_token_merge_is_reported() {
+        let source = r##"{"type": "palette", "name": "p", "colors": {"a": "#FF0000", "b": "#FF0000"}}
+{"type": "sprite", "name": "s", "size": [2, 1], "palette": "p", "regions": {"a": {"points": [[0, 0]]}, "b": {"points": [[1, 0]]}}}"##;
+        let report = &verify_roundtrip(source, None, &RoundtripOptions::default()).unwrap()[0];
+        assert!(report.is_exact());
+        assert_eq!(
+            report.lossy_steps,
+            vec![LossyStep::TokenMerge { tokens: vec!["a".to_string(), "b".to_string()] }]
+        );
+        assert_eq!(report.imported_tokens, 1);
+    }
+
+    #[test]
+    fn test_sprite_filter() {
+        assert!(verify_roundtrip(SIMPLE, Some("missing"), &RoundtripOptions::default()).is_err());
+        let reports = verify_roundtrip(SIMPLE, Some("flag"), &RoundtripOptions::default()).unwrap();
+        assert_eq!(reports[0].sprite, "flag");
+    }
+
+    #[test]
+    fn test_lossy_step_display() {
+        let step = LossyStep::RegionApproximation { pixels: 3 };
+        assert_eq!(step.to_string(), "region approximation: 3 pixels differ");
+    }
+}
@@ -0,0 +1,738 @@
+//! Runtime sprite pool for games shipping compiled pixelsrc data
+//!
+//! A [`SpritePool`] holds fully resolved sprites (RGBA8 pixels), animation
+//! frame timing tables, collision boxes, and attachment points as plain
+//! structs. It loads from a compiled `.pxb` bundle with a small hand-written
+//! binary reader, so games don't parse JSON or resolve palettes at startup.
+//!
+//! ```no_run
+//! use pixelsrc::runtime::SpritePool;
+//!
+//! let pool = SpritePool::load("assets/sprites.pxb".as_ref()).unwrap();
+//! let walk = pool.animation("hero_walk").unwrap();
+//! let frame = walk.frame_at(250).unwrap();
+//! let sprite = pool.sprite(frame.sprite).unwrap();
+//! // upload sprite.pixels as a sprite.width x sprite.height RGBA8 texture...
+//! ```
+//!
+//! # Bundle layout
+//!
+//! All integers are little-endian. A bundle is the magic `PXB\0`, a `u32`
+//! format version, then chunks of `[tag: 4 bytes][length: u32][payload]`.
+//! Readers skip chunks they don't know, so later versions can add chunks
+//! without breaking older loaders. Strings are a `u32` byte length followed
+//! by UTF-8.
+//!
+//! | Chunk | Payload |
+//! |-------|---------|
+//! | `SPRT` | `u32` count, then per sprite: name, width, height, origin, attach-in/out flags and points, boxes, `width * height * 4` pixel bytes |
+//! | `ANIM` | `u32` count, then per animation: name, loop flag, frames (sprite index, duration, boxes), attachments |
+//! | `PALT` | `u32` count, then per palette: name, `u32` color count, then per color: token, 4 RGBA bytes |
+//! | `ATLS` | `u32` count, then per atlas page: width, height, `u32` placement count, then per placement: sprite index, x, y |
+//! | `META` | `u32` count, then key and value strings |
+//!
+//! `pxl build --bundle` writes bundles; see [`crate::bundle`].
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use thiserror::Error;
+
+/// Magic bytes at the start of every bundle
+pub const BUNDLE_MAGIC: [u8; 4] = *b"PXB\0";
+
+/// Current bundle format version
+pub const BUNDLE_VERSION: u32 = 1;
+
+const CHUNK_SPRITES: [u8; 4] = *b"SPRT";
+const CHUNK_ANIMATIONS: [u8; 4] = *b"ANIM";
+const CHUNK_PALETTES: [u8; 4] = *b"PALT";
+const CHUNK_ATLASES: [u8; 4] = *b"ATLS";
+const CHUNK_METADATA: [u8; 4] = *b"META";
+
+/// Error type for loading bundles
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BundleError {
+    /// IO error reading or writing the bundle
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    /// The data doesn't start with the bundle magic
+    #[error("Not a pixelsrc bundle")]
+    BadMagic,
+    /// The bundle was written by a newer pixelsrc
+    #[error("Bundle version {0} is newer than supported ({BUNDLE_VERSION})")]
+    UnsupportedVersion(u32),
+    /// The data ends in the middle of a value
+    #[error("Bundle is truncated")]
+    Truncated,
+    /// A string isn't valid UTF-8
+    #[error("Bundle contains an invalid string")]
+    InvalidString,
+    /// A frame, attachment or atlas placement refers to a sprite index past
+    /// the sprite table
+    #[error("Bundle refers to sprite {index}, but has only {count}")]
+    InvalidSprite { index: u32, count: usize },
+    /// An atlas placement doesn't fit on its page
+    #[error("Sprite '{0}' is placed outside its atlas page")]
+    InvalidPlacement(String),
+}
+
+/// Index of a sprite in a [`SpritePool`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpriteId(pub u32);
+
+/// A named collision box, relative to the sprite's top-left corner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitBox {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// A rendered sprite and its metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteData {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// Row-major RGBA8 pixels, `width * height * 4` bytes
+    pub pixels: Vec<u8>,
+    /// Origin point (`[0, 0]` if the sprite doesn't set one)
+    pub origin: [i32; 2],
+    pub boxes: Vec<HitBox>,
+    /// Where the sprite connects to the previous segment of a chain
+    pub attach_in: Option<[i32; 2]>,
+    /// Where the next segment of a chain connects
+    pub attach_out: Option<[i32; 2]>,
+}
+
+/// One animation frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameData {
+    pub sprite: SpriteId,
+    pub duration_ms: u32,
+    /// Collision boxes in effect for this frame
+    pub boxes: Vec<HitBox>,
+}
+
+/// A chain of sprites attached to an animation (hair, capes, tails)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachPoint {
+    pub name: String,
+    /// Attachment point on the parent sprite
+    pub anchor: [i32; 2],
+    /// Chain segments, parent first
+    pub chain: Vec<SpriteId>,
+    /// Draw order relative to the parent (negative is behind)
+    pub z_index: i32,
+}
+
+/// An animation's frame timing table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationData {
+    pub name: String,
+    pub frames: Vec<FrameData>,
+    pub looping: bool,
+    pub attachments: Vec<AttachPoint>,
+}
+
+impl AnimationData {
+    /// Length of one pass through the frames, in milliseconds
+    pub fn total_ms(&self) -> u64 {
+        self.frames.iter().map(|f| f.duration_ms as u64).sum()
+    }
+
+    /// Index of the frame shown `time_ms` after the animation starts.
+    ///
+    /// Looping animations wrap around; others hold their last frame.
+    /// Returns `None` for an animation without frames.
+    pub fn frame_index_at(&self, time_ms: u64) -> Option<usize> {
+        let total = self.total_ms();
+        if self.frames.is_empty() {
+            return None;
+        }
+        if total == 0 {
+            return Some(0);
+        }
+        let mut t = if self.looping { time_ms % total } else { time_ms };
+        for (i, frame) in self.frames.iter().enumerate() {
+            if t < frame.duration_ms as u64 {
+                return Some(i);
+            }
+            t -= frame.duration_ms as u64;
+        }
+        Some(self.frames.len() - 1)
+    }
+
+    /// The frame shown `time_ms` after the animation starts
+    pub fn frame_at(&self, time_ms: u64) -> Option<&FrameData> {
+        self.frame_index_at(time_ms).map(|i| &self.frames[i])
+    }
+}
+
+/// A palette with its colors resolved to RGBA
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteData {
+    pub name: String,
+    /// Token and color pairs, sorted by token
+    pub colors: Vec<(String, [u8; 4])>,
+}
+
+/// Where a sprite sits on an atlas page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasPlacement {
+    pub sprite: SpriteId,
+    pub x: u32,
+    pub y: u32,
+}
+
+/// One atlas page. The page image is every placed sprite's pixels copied to
+/// its placement on a transparent `width x height` canvas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtlasData {
+    pub width: u32,
+    pub height: u32,
+    pub placements: Vec<AtlasPlacement>,
+}
+
+impl AtlasData {
+    /// Compose the page image as row-major RGBA8 pixels.
+    pub fn pixels(&self, pool: &SpritePool) -> Vec<u8> {
+        let stride = self.width as usize * 4;
+        let mut pixels = vec![0; stride * self.height as usize];
+        for placement in &self.placements {
+            let Some(sprite) = pool.sprite(placement.sprite) else { continue };
+            let row_len = sprite.width as usize * 4;
+            for (y, row) in sprite.pixels.chunks_exact(row_len.max(1)).enumerate() {
+                let start = (placement.y as usize + y) * stride + placement.x as usize * 4;
+                if let Some(dest) = pixels.get_mut(start..start + row_len) {
+                    dest.copy_from_slice(row);
+                }
+            }
+        }
+        pixels
+    }
+}
+
+/// Sprites and animations loaded for a game, with lookup by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpritePool {
+    sprites: Vec<SpriteData>,
+    animations: Vec<AnimationData>,
+    palettes: Vec<PaletteData>,
+    atlases: Vec<AtlasData>,
+    metadata: Vec<(String, String)>,
+    sprite_ids: HashMap<String, SpriteId>,
+    animation_ids: HashMap<String, usize>,
+}
+
+impl SpritePool {
+    /// Build a pool from resolved sprites and animations.
+    ///
+    /// Frames and attachments refer to sprites by their index in `sprites`.
+    pub fn new(sprites: Vec<SpriteData>, animations: Vec<AnimationData>) -> Self {
+        let sprite_ids =
+            sprites.iter().enumerate().map(|(i, s)| (s.name.clone(), SpriteId(i as u32))).collect();
+        let animation_ids =
+            animations.iter().enumerate().map(|(i, a)| (a.name.clone(), i)).collect();
+        Self { sprites, animations, sprite_ids, animation_ids, ..Default::default() }
+    }
+
+    /// Set the resolved palettes.
+    pub fn with_palettes(mut self, palettes: Vec<PaletteData>) -> Self {
+        self.palettes = palettes;
+        self
+    }
+
+    /// Set the atlas pages.
+    pub fn with_atlases(mut self, atlases: Vec<AtlasData>) -> Self {
+        self.atlases = atlases;
+        self
+    }
+
+    /// Set free-form metadata (generator, project name, ...).
+    pub fn with_metadata(mut self, metadata: Vec<(String, String)>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Read a bundle file.
+    pub fn load(path: &Path) -> Result<Self, BundleError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Write the pool as a bundle file.
+    pub fn save(&self, path: &Path) -> Result<(), BundleError> {
+        Ok(std::fs::write(path, self.to_bytes())?)
+    }
+
+    /// All sprites, indexed by [`SpriteId`]
+    pub fn sprites(&self) -> &[SpriteData] {
+        &self.sprites
+    }
+
+    /// All animations, in bundle order
+    pub fn animations(&self) -> &[AnimationData] {
+        &self.animations
+    }
+
+    /// All palettes, in bundle order
+    pub fn palettes(&self) -> &[PaletteData] {
+        &self.palettes
+    }
+
+    /// Get a palette by name
+    pub fn palette(&self, name: &str) -> Option<&PaletteData> {
+        self.palettes.iter().find(|p| p.name == name)
+    }
+
+    /// Atlas pages packing every sprite
+    pub fn atlases(&self) -> &[AtlasData] {
+        &self.atlases
+    }
+
+    /// Get a metadata value by key
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Look up a sprite's id by name
+    pub fn sprite_id(&self, name: &str) -> Option<SpriteId> {
+        self.sprite_ids.get(name).copied()
+    }
+
+    /// Get a sprite by id
+    pub fn sprite(&self, id: SpriteId) -> Option<&SpriteData> {
+        self.sprites.get(id.0 as usize)
+    }
+
+    /// Get a sprite by name
+    pub fn sprite_by_name(&self, name: &str) -> Option<&SpriteData> {
+        self.sprite_id(name).and_then(|id| self.sprite(id))
+    }
+
+    /// Get an animation by name
+    pub fn animation(&self, name: &str) -> Option<&AnimationData> {
+        self.animation_ids.get(name).map(|&i| &self.animations[i])
+    }
+
+    /// Serialize the pool in the bundle format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&BUNDLE_MAGIC);
+        put_u32(&mut out, BUNDLE_VERSION);
+
+        let mut chunk = Vec::new();
+        put_u32(&mut chunk, self.sprites.len() as u32);
+        for sprite in &self.sprites {
+            put_str(&mut chunk, &sprite.name);
+            put_u32(&mut chunk, sprite.width);
+            put_u32(&mut chunk, sprite.height);
+            put_point(&mut chunk, sprite.origin);
+            let flags = sprite.attach_in.is_some() as u8 | (sprite.attach_out.is_some() as u8) << 1;
+            chunk.push(flags);
+            for point in sprite.attach_in.iter().chain(&sprite.attach_out) {
+                put_point(&mut chunk, *point);
+            }
+            put_boxes(&mut chunk, &sprite.boxes);
+            chunk.extend_from_slice(&sprite.pixels);
+        }
+        put_chunk(&mut out, CHUNK_SPRITES, &chunk);
+
+        let mut chunk = Vec::new();
+        put_u32(&mut chunk, self.animations.len() as u32);
+        for anim in &self.animations {
+            put_str(&mut chunk, &anim.name);
+            chunk.push(anim.looping as u8);
+            put_u32(&mut chunk, anim.frames.len() as u32);
+            for frame in &anim.frames {
+                put_u32(&mut chunk, frame.sprite.0);
+                put_u32(&mut chunk, frame.duration_ms);
+                put_boxes(&mut chunk, &frame.boxes);
+            }
+            put_u32(&mut chunk, anim.attachments.len() as u32);
+            for attachment in &anim.attachments {
+                put_str(&mut chunk, &attachment.name);
+                put_point(&mut chunk, attachment.anchor);
+                put_u32(&mut chunk, attachment.chain.len() as u32);
+                for id in &attachment.chain {
+                    put_u32(&mut chunk, id.0);
+                }
+                put_i32(&mut chunk, attachment.z_index);
+            }
+        }
+        put_chunk(&mut out, CHUNK_ANIMATIONS, &chunk);
+
+        let mut chunk = Vec::new();
+        put_u32(&mut chunk, self.palettes.len() as u32);
+        for palette in &self.palettes {
+            put_str(&mut chunk, &palette.name);
+            put_u32(&mut chunk, palette.colors.len() as u32);
+            for (token, rgba) in &palette.colors {
+                put_str(&mut chunk, token);
+                chunk.extend_from_slice(rgba);
+            }
+        }
+        put_chunk(&mut out, CHUNK_PALETTES, &chunk);
+
+        let mut chunk = Vec::new();
+        put_u32(&mut chunk, self.atlases.len() as u32);
+        for atlas in &self.atlases {
+            put_u32(&mut chunk, atlas.width);
+            put_u32(&mut chunk, atlas.height);
+            put_u32(&mut chunk, atlas.placements.len() as u32);
+            for placement in &atlas.placements {
+                put_u32(&mut chunk, placement.sprite.0);
+                put_u32(&mut chunk, placement.x);
+                put_u32(&mut chunk, placement.y);
+            }
+        }
+        put_chunk(&mut out, CHUNK_ATLASES, &chunk);
+
+        let mut chunk = Vec::new();
+        put_u32(&mut chunk, self.metadata.len() as u32);
+        for (key, value) in &self.metadata {
+            put_str(&mut chunk, key);
+            put_str(&mut chunk, value);
+        }
+        put_chunk(&mut out, CHUNK_METADATA, &chunk);
+        out
+    }
+
+    /// Parse a bundle.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BundleError> {
+        let mut reader = Reader(bytes);
+        if reader.take(4).ok() != Some(&BUNDLE_MAGIC[..]) {
+            return Err(BundleError::BadMagic);
+        }
+        let version = reader.u32()?;
+        if version > BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(version));
+        }
+
+        let mut sprites = Vec::new();
+        let mut animations = Vec::new();
+        let mut palettes = Vec::new();
+        let mut atlases = Vec::new();
+        let mut metadata = Vec::new();
+        while !reader.0.is_empty() {
+            let tag = reader.take(4)?;
+            let len = reader.u32()? as usize;
+            let mut chunk = Reader(reader.take(len)?);
+            match <[u8; 4]>::try_from(tag).unwrap_or_default() {
+                CHUNK_SPRITES => {
+                    for _ in 0..chunk.u32()? {
+                        sprites.push(chunk.sprite()?);
+                    }
+                }
+                CHUNK_ANIMATIONS => {
+                    for _ in 0..chunk.u32()? {
+                        animations.push(chunk.animation()?);
+                    }
+                }
+                CHUNK_PALETTES => {
+                    for _ in 0..chunk.u32()? {
+                        palettes.push(chunk.palette()?);
+                    }
+                }
+                CHUNK_ATLASES => {
+                    for _ in 0..chunk.u32()? {
+                        atlases.push(chunk.atlas()?);
+                    }
+                }
+                CHUNK_METADATA => {
+                    for _ in 0..chunk.u32()? {
+                        metadata.push((chunk.string()?, chunk.string()?));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let count = sprites.len();
+        let ids = animations.iter().flat_map(|a: &AnimationData| {
+            let frames = a.frames.iter().map(|f| f.sprite);
+            frames.chain(a.attachments.iter().flat_map(|at| at.chain.iter().copied()))
+        });
+        let placed = atlases.iter().flat_map(|a: &AtlasData| a.placements.iter().map(|p| p.sprite));
+        for SpriteId(index) in ids.chain(placed) {
+            if index as usize >= count {
+                return Err(BundleError::InvalidSprite { index, count });
+            }
+        }
+        for atlas in &atlases {
+            for placement in &atlas.placements {
+                let sprite = &sprites[placement.sprite.0 as usize];
+                if placement.x as u64 + sprite.width as u64 > atlas.width as u64
+                    || placement.y as u64 + sprite.height as u64 > atlas.height as u64
+                {
+                    return Err(BundleError::InvalidPlacement(sprite.name.clone()));
+                }
+            }
+        }
+        Ok(Self::new(sprites, animations)
+            .with_palettes(palettes)
+            .with_atlases(atlases)
+            .with_metadata(metadata))
+    }
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_i32(out: &mut Vec<u8>, value: i32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_point(out: &mut Vec<u8>, [x, y]: [i32; 2]) {
+    put_i32(out, x);
+    put_i32(out, y);
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    put_u32(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn put_boxes(out: &mut Vec<u8>, boxes: &[HitBox]) {
+    put_u32(out, boxes.len() as u32);
+    for b in boxes {
+        put_str(out, &b.name);
+        put_point(out, [b.x, b.y]);
+        put_u32(out, b.w);
+        put_u32(out, b.h);
+    }
+}
+
+fn put_chunk(out: &mut Vec<u8>, tag: [u8; 4], payload: &[u8]) {
+    out.extend_from_slice(&tag);
+    put_u32(out, payload.len() as u32);
+    out.extend_from_slice(payload);
+}
+
+/// Cursor over bundle bytes
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BundleError> {
+        if self.0.len() < len {
+            return Err(BundleError::Truncated);
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, BundleError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, BundleError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap_or_default()))
+    }
+
+    fn i32(&mut self) -> Result<i32, BundleError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap_or_default()))
+    }
+
+    fn point(&mut self) -> Result<[i32; 2], BundleError> {
+        Ok([self.i32()?, self.i32()?])
+    }
+
+    fn string(&mut self) -> Result<String, BundleError> {
+        let len = self.u32()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| BundleError::InvalidString)
+    }
+
+    fn boxes(&mut self) -> Result<Vec<HitBox>, BundleError> {
+        (0..self.u32()?)
+            .map(|_| {
+                let name = self.string()?;
+                let [x, y] = self.point()?;
+                Ok(HitBox { name, x, y, w: self.u32()?, h: self.u32()? })
+            })
+            .collect()
+    }
+
+    fn sprite(&mut self) -> Result<SpriteData, BundleError> {
+        let name = self.string()?;
+        let (width, height) = (self.u32()?, self.u32()?);
+        let origin = self.point()?;
+        let flags = self.u8()?;
+        let attach_in = if flags & 1 != 0 { Some(self.point()?) } else { None };
+        let attach_out = if flags & 2 != 0 { Some(self.point()?) } else { None };
+        let boxes = self.boxes()?;
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|n| n.checked_mul(4))
+            .ok_or(BundleError::Truncated)?;
+        let pixels = self.take(len)?.to_vec();
+        Ok(SpriteData { name, width, height, pixels, origin, boxes, attach_in, attach_out })
+    }
+
+    fn palette(&mut self) -> Result<PaletteData, BundleError> {
+        let name = self.string()?;
+        let colors = (0..self.u32()?)
+            .map(|_| {
+                let token = self.string()?;
+                let rgba = self.take(4)?;
+                Ok((token, [rgba[0], rgba[1], rgba[2], rgba[3]]))
+            })
+            .collect::<Result<_, BundleError>>()?;
+        Ok(PaletteData { name, colors })
+    }
+
+    fn atlas(&mut self) -> Result<AtlasData, BundleError> {
+        let (width, height) = (self.u32()?, self.u32()?);
+        let placements = (0..self.u32()?)
+            .map(|_| {
+                let sprite = SpriteId(self.u32()?);
+                Ok(AtlasPlacement { sprite, x: self.u32()?, y: self.u32()? })
+            })
+            .collect::<Result<_, BundleError>>()?;
+        Ok(AtlasData { width, height, placements })
+    }
+
+    fn animation(&mut self) -> Result<AnimationData, BundleError> {
+        let name = self.string()?;
+        let looping = self.u8()? != 0;
+        let frames = (0..self.u32()?)
+            .map(|_| {
+                let sprite = SpriteId(self.u32()?);
+                let duration_ms = self.u32()?;
+                Ok(FrameData { sprite, duration_ms, boxes: self.boxes()? })
+            })
+            .collect::<Result<_, BundleError>>()?;
+        let attachments = (0..self.u32()?)
+            .map(|_| {
+                let name = self.string()?;
+                let anchor = self.point()?;
+                let chain =
+                    (0..self.u32()?)
+                        .map(|_| Ok(SpriteId(self.u32()?)))
+                        .collect::<Result<_, BundleError>>()?;
+                Ok(AttachPoint { name, anchor, chain, z_index: self.i32()? })
+            })
+            .collect::<Result<_, BundleError>>()?;
+        Ok(AnimationData { name, frames, looping, attachments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> SpritePool {
+        let sprite = |name: &str, color: u8| SpriteData {
+            name: name.to_string(),
+            width: 2,
+            height: 1,
+            pixels: vec![color, 0, 0, 255, 0, color, 0, 255],
+            origin: [1, 1],
+            boxes: vec![HitBox { name: "hurt".to_string(), x: 0, y: 0, w: 2, h: 1 }],
+            attach_in: None,
+            attach_out: Some([1, 0]),
+        };
+        let frame = |sprite: u32, duration_ms: u32| FrameData {
+            sprite: SpriteId(sprite),
+            duration_ms,
+            boxes: Vec::new(),
+        };
+        let walk = AnimationData {
+            name: "walk".to_string(),
+            frames: vec![frame(0, 100), frame(1, 50)],
+            looping: true,
+            attachments: vec![AttachPoint {
+                name: "scarf".to_string(),
+                anchor: [1, 0],
+                chain: vec![SpriteId(1)],
+                z_index: -1,
+            }],
+        };
+        let atlas = AtlasData {
+            width: 2,
+            height: 2,
+            placements: vec![
+                AtlasPlacement { sprite: SpriteId(0), x: 0, y: 0 },
+                AtlasPlacement { sprite: SpriteId(1), x: 0, y: 1 },
+            ],
+        };
+        let palette = PaletteData {
+            name: "hero".to_string(),
+            colors: vec![("skin".to_string(), [1, 2, 3, 4])],
+        };
+        SpritePool::new(vec![sprite("step_a", 255), sprite("step_b", 128)], vec![walk])
+            .with_palettes(vec![palette])
+            .with_atlases(vec![atlas])
+            .with_metadata(vec![("project".to_string(), "demo".to_string())])
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let pool = pool();
+        let loaded = SpritePool::from_bytes(&pool.to_bytes()).unwrap();
+        assert_eq!(loaded, pool);
+        let id = loaded.sprite_id("step_b").unwrap();
+        assert_eq!(loaded.sprite(id).unwrap().pixels[0], 128);
+        assert_eq!(loaded.animation("walk").unwrap().attachments[0].z_index, -1);
+        assert!(loaded.sprite_by_name("missing").is_none());
+        assert_eq!(loaded.palette("hero").unwrap().colors[0].1, [1, 2, 3, 4]);
+        assert_eq!(loaded.metadata("project"), Some("demo"));
+    }
+
+    #[test]
+    fn test_atlas_pixels() {
+        let pool = pool();
+        let pixels = pool.atlases()[0].pixels(&pool);
+        assert_eq!(pixels.len(), 2 * 2 * 4);
+        assert_eq!(&pixels[..8], &pool.sprites()[0].pixels[..]);
+        assert_eq!(&pixels[8..], &pool.sprites()[1].pixels[..]);
+
+        let mut outside = pool.clone();
+        outside.atlases[0].placements[1].x = 1;
+        assert!(matches!(
+            SpritePool::from_bytes(&outside.to_bytes()),
+            Err(BundleError::InvalidPlacement(name)) if name == "step_b"
+        ));
+    }
+
+    #[test]
+    fn test_frame_at() {
+        let pool = pool();
+        let mut walk = pool.animation("walk").unwrap().clone();
+        assert_eq!(walk.total_ms(), 150);
+        assert_eq!(walk.frame_index_at(0), Some(0));
+        assert_eq!(walk.frame_index_at(120), Some(1));
+        assert_eq!(walk.frame_at(160).unwrap().sprite, SpriteId(0));
+
+        walk.looping = false;
+        assert_eq!(walk.frame_index_at(1000), Some(1));
+        walk.frames.clear();
+        assert_eq!(walk.frame_at(0), None);
+    }
+
+    #[test]
+    fn test_bundle_errors() {
+        let bytes = pool().to_bytes();
+        assert!(matches!(SpritePool::from_bytes(b"PNG\0"), Err(BundleError::BadMagic)));
+        assert!(matches!(
+            SpritePool::from_bytes(&bytes[..bytes.len() - 3]),
+            Err(BundleError::Truncated)
+        ));
+
+        let mut newer = bytes.clone();
+        newer[4..8].copy_from_slice(&99u32.to_le_bytes());
+        assert!(matches!(SpritePool::from_bytes(&newer), Err(BundleError::UnsupportedVersion(99))));
+
+        // Unknown chunks are skipped
+        let mut extended = bytes.clone();
+        put_chunk(&mut extended, *b"XTRA", &[1, 2, 3]);
+        assert_eq!(SpritePool::from_bytes(&extended).unwrap(), pool());
+    }
+}
@@ -6,6 +6,7 @@ use std::fs;
 use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::color::parse_color;
 use crate::config::loader::find_config;
 
 /// Error during asset scaffolding
@@ -248,6 +249,194 @@ pub fn generate_sprite(
     format!("{}\n\n{}\n", palette_json, sprite_json)
 }
 
+/// Shape of a known layout part: relative height, width as a fraction of the
+/// sprite, and whether it comes in a mirrored pair
+struct PartShape {
+    weight: u32,
+    width: f64,
+    paired: bool,
+}
+
+/// Known layout parts; anything else gets [`DEFAULT_PART_SHAPE`]
+const LAYOUT_PARTS: &[(&str, PartShape)] = &[
+    ("hat", PartShape { weight: 1, width: 0.625, paired: false }),
+    ("hair", PartShape { weight: 1, width: 0.5, paired: false }),
+    ("head", PartShape { weight: 3, width: 0.5, paired: false }),
+    ("neck", PartShape { weight: 1, width: 0.25, paired: false }),
+    ("torso", PartShape { weight: 4, width: 0.625, paired: false }),
+    ("body", PartShape { weight: 4, width: 0.625, paired: false }),
+    ("arms", PartShape { weight: 3, width: 0.875, paired: true }),
+    ("hands", PartShape { weight: 1, width: 0.875, paired: true }),
+    ("wings", PartShape { weight: 3, width: 1.0, paired: true }),
+    ("tail", PartShape { weight: 2, width: 0.25, paired: false }),
+    ("legs", PartShape { weight: 4, width: 0.5, paired: true }),
+    ("feet", PartShape { weight: 1, width: 0.625, paired: true }),
+];
+
+const DEFAULT_PART_SHAPE: PartShape = PartShape { weight: 2, width: 0.75, paired: false };
+
+/// Generate a commented sprite skeleton from a top-to-bottom list of parts.
+///
+/// Each part becomes a palette token tagged with the `fill` role and a named
+/// region whose rect is a placeholder bounding box, stacked in order and
+/// centered. Paired parts (legs, arms, ...) cover the left side only and are
+/// mirrored with `"symmetric": "x"`. With a built-in palette (`@pico8` or
+/// `pico8`), part colors are taken from it, preferring a color named like
+/// the part.
+pub fn generate_sprite_layout(
+    name: &str,
+    width: u32,
+    height: u32,
+    palette_name: Option<&str>,
+    parts: &[String],
+) -> Result<String, String> {
+    if parts.is_empty() {
+        return Err("layout must name at least one part".to_string());
+    }
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() || part == "_" {
+            return Err(format!("invalid layout part '{}'", part));
+        }
+        if parts[..i].contains(part) {
+            return Err(format!("layout part '{}' is listed twice", part));
+        }
+    }
+    if parts.len() as u32 > height {
+        return Err(format!("{} layout parts don't fit in {} rows", parts.len(), height));
+    }
+
+    let colors = match palette_name {
+        Some(palette) => {
+            let builtin = palette.strip_prefix('@').unwrap_or(palette);
+            let palette = crate::palettes::get_builtin(builtin).ok_or_else(|| {
+                format!(
+                    "unknown built-in palette '{}'. Available: {}",
+                    builtin,
+                    crate::palettes::list_builtins().join(", ")
+                )
+            })?;
+            builtin_part_colors(&palette, parts)
+        }
+        None => (0..parts.len())
+            .map(|i| hsl_to_hex(i as f64 / parts.len() as f64 * 360.0, 0.7, 0.5))
+            .collect(),
+    };
+
+    let shapes: Vec<&PartShape> = parts
+        .iter()
+        .map(|part| {
+            LAYOUT_PARTS
+                .iter()
+                .find(|(known, _)| known == part)
+                .map_or(&DEFAULT_PART_SHAPE, |(_, shape)| shape)
+        })
+        .collect();
+
+    // Stack parts top to bottom, each at least one row tall
+    let total: u32 = shapes.iter().map(|shape| shape.weight).sum();
+    let mut rows = Vec::with_capacity(parts.len());
+    let mut cumulative = 0;
+    let mut y = 0;
+    for (i, shape) in shapes.iter().enumerate() {
+        cumulative += shape.weight;
+        let remaining = (parts.len() - i - 1) as u32;
+        let end = (height * cumulative / total).clamp(y + 1, height - remaining);
+        rows.push((y, end - y));
+        y = end;
+    }
+
+    let mut output = format!(
+        "// {}: {}x{} sprite skeleton, parts from top to bottom: {}\n",
+        name,
+        width,
+        height,
+        parts.join(", ")
+    );
+    output.push_str(
+        "// Each region is a placeholder bounding rect; reshape it and add detail regions.\n",
+    );
+    if let Some(palette) = palette_name {
+        output.push_str(&format!("// Colors are taken from the built-in {} palette.\n", palette));
+    }
+    if shapes.iter().any(|shape| shape.paired) {
+        output.push_str(
+            "// Paired parts cover the left side only; \"symmetric\": \"x\" mirrors them.\n",
+        );
+    }
+
+    let pal_name = format!("{}_palette", name);
+    let color_entries: Vec<String> = std::iter::once("    \"_\": \"#00000000\"".to_string())
+        .chain(
+            parts.iter().zip(&colors).map(|(part, hex)| format!("    \"{}\": \"{}\"", part, hex)),
+        )
+        .collect();
+    let role_entries: Vec<String> =
+        parts.iter().map(|part| format!("    \"{}\": \"fill\"", part)).collect();
+    output.push_str(&format!(
+        "{{\n  \"type\": \"palette\",\n  \"name\": \"{}\",\n  \"colors\": {{\n{}\n  }},\n  \"roles\": {{\n{}\n  }}\n}}\n\n",
+        pal_name,
+        color_entries.join(",\n"),
+        role_entries.join(",\n")
+    ));
+
+    let mut region_entries = Vec::with_capacity(parts.len());
+    for ((part, shape), (y, rows)) in parts.iter().zip(&shapes).zip(rows) {
+        let part_width = ((width as f64 * shape.width).round() as u32).clamp(1, width);
+        let x = (width - part_width) / 2;
+        if shape.paired && width >= 2 {
+            // Left half, leaving a gap at the center line when there's room
+            let half = width / 2 - x;
+            let side = if half >= 3 { half - 1 } else { half.max(1) };
+            region_entries.push(format!(
+                "    \"{}\": {{\"rect\": [{}, {}, {}, {}], \"symmetric\": \"x\"}}",
+                part, x, y, side, rows
+            ));
+        } else {
+            region_entries.push(format!(
+                "    \"{}\": {{\"rect\": [{}, {}, {}, {}]}}",
+                part, x, y, part_width, rows
+            ));
+        }
+    }
+    output.push_str(&format!(
+        "{{\n  \"type\": \"sprite\",\n  \"name\": \"{}\",\n  \"size\": [{}, {}],\n  \"palette\": \"{}\",\n  \"regions\": {{\n{}\n  }}\n}}\n",
+        name,
+        width,
+        height,
+        pal_name,
+        region_entries.join(",\n")
+    ));
+
+    Ok(output)
+}
+
+/// Pick a color from a built-in palette for each part: the color named like
+/// the part if there is one, otherwise the next unused opaque color by name.
+fn builtin_part_colors(palette: &crate::models::Palette, parts: &[String]) -> Vec<String> {
+    let mut available: Vec<(&str, &str)> = palette
+        .colors
+        .iter()
+        .map(|(token, hex)| (token.trim_matches(|c| c == '{' || c == '}'), hex.as_str()))
+        .filter(|(token, hex)| *token != "_" && parse_color(hex).is_ok_and(|c| c[3] > 0))
+        .collect();
+    // Black and white are last resorts so parts stay distinguishable from outlines
+    available.sort_by_key(|(token, _)| (matches!(*token, "black" | "white"), *token));
+
+    let mut colors = vec![None; parts.len()];
+    for (color, part) in colors.iter_mut().zip(parts) {
+        if let Some(i) = available.iter().position(|(token, _)| token == part) {
+            *color = Some(available.remove(i).1.to_string());
+        }
+    }
+    let mut next = available.into_iter().cycle();
+    colors
+        .into_iter()
+        .map(|color| {
+            color.unwrap_or_else(|| next.next().map_or("#000000", |(_, hex)| hex).to_string())
+        })
+        .collect()
+}
+
 /// Generate a composition scaffold with tile sprites.
 ///
 /// Returns an error if the number of required tiles exceeds 62 (A-Z + a-z + 0-9).
@@ -363,6 +552,405 @@ pub fn generate_palette_scaffold(
     Ok(output)
 }
 
+/// A common game screen layout for [`generate_screen`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ScreenLayout {
+    /// Full-screen background, centered logo, and a menu with a cursor
+    Title,
+    /// Bar along the top with health, item, and score widgets
+    Hud,
+    /// Box along the bottom with a portrait, text area, and next indicator
+    Dialogue,
+    /// Panel with a title bar, a grid of item slots, and a selection cursor
+    Inventory,
+}
+
+impl ScreenLayout {
+    /// Layout name, used for the default composition name (`title_screen`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreenLayout::Title => "title",
+            ScreenLayout::Hud => "hud",
+            ScreenLayout::Dialogue => "dialogue",
+            ScreenLayout::Inventory => "inventory",
+        }
+    }
+}
+
+/// Palette tokens used by screen tiles
+const SCREEN_TOKENS: &[(&str, &str)] =
+    &[("background", "#1D2B53"), ("panel", "#29366F"), ("frame", "#F4F4F4"), ("accent", "#EF7D57")];
+
+/// How a screen element is tiled
+#[derive(Clone, Copy, PartialEq)]
+enum ScreenStyle {
+    /// Solid background tiles
+    Background,
+    /// Solid accent tiles
+    Accent,
+    /// Filled panel with a frame, from nine tiles
+    Panel,
+    /// Accent outline with a see-through middle, from eight tiles
+    Outline,
+}
+
+/// Nine-slice tile positions, top-left to bottom-right
+const SLICES: [&str; 9] = ["nw", "n", "ne", "w", "center", "e", "sw", "s", "se"];
+/// Map keys for panel tiles, laid out like a numeric keypad
+const PANEL_KEYS: [char; 9] = ['7', '8', '9', '4', '5', '6', '1', '2', '3'];
+/// Map keys for outline tiles, laid out like the left of a keyboard
+const OUTLINE_KEYS: [char; 9] = ['q', 'w', 'e', 'a', 's', 'd', 'z', 'x', 'c'];
+
+/// An element of a screen: a rect of cells tiled on a layer
+struct ScreenElement {
+    name: &'static str,
+    layer: &'static str,
+    style: ScreenStyle,
+    rect: [u32; 4],
+}
+
+/// Generate a screen composition tiled with placeholder sprites.
+///
+/// The screen is divided into square cells of the largest size (16, 8, or 4
+/// pixels) that divides the resolution and still leaves a grid of at least
+/// 20x12 cells. Every placeholder sprite is exactly one cell, so the result
+/// renders cleanly with `--strict`: panels and outlines are drawn from
+/// nine-slice tiles (keys `7`-`9`/`4`-`6`/`1`-`3` and `q`-`e`/`a`-`d`/`z`-`c`,
+/// arranged like the tiles they place), solid areas from single tiles. Each
+/// element sits on its own named layer. With a built-in palette, tile colors
+/// are taken from it.
+pub fn generate_screen(
+    layout: ScreenLayout,
+    name: &str,
+    width: u32,
+    height: u32,
+    palette_name: Option<&str>,
+) -> Result<String, String> {
+    let cell = [16, 8, 4]
+        .into_iter()
+        .find(|cell| {
+            width.is_multiple_of(*cell)
+                && height.is_multiple_of(*cell)
+                && width / cell >= 20
+                && height / cell >= 12
+        })
+        .ok_or_else(|| {
+            format!("resolution {}x{} must be a multiple of 4 and at least 80x48", width, height)
+        })?;
+    let (cols, rows) = (width / cell, height / cell);
+    let elements = screen_elements(layout, cols, rows, cell);
+
+    let tokens: Vec<String> = SCREEN_TOKENS.iter().map(|(token, _)| token.to_string()).collect();
+    let colors: Vec<String> = match palette_name {
+        Some(palette) => {
+            let builtin = palette.strip_prefix('@').unwrap_or(palette);
+            let palette = crate::palettes::get_builtin(builtin).ok_or_else(|| {
+                format!(
+                    "unknown built-in palette '{}'. Available: {}",
+                    builtin,
+                    crate::palettes::list_builtins().join(", ")
+                )
+            })?;
+            builtin_part_colors(&palette, &tokens)
+        }
+        None => SCREEN_TOKENS.iter().map(|(_, hex)| hex.to_string()).collect(),
+    };
+
+    let mut output = format!(
+        "// {}: {} screen at {}x{}, {}x{} cells of {}px\n",
+        name,
+        layout.as_str(),
+        width,
+        height,
+        cols,
+        rows,
+        cell
+    );
+    output.push_str("// Elements as pixel rects [x, y, w, h]:\n");
+    for (i, element) in elements.iter().enumerate() {
+        if elements[..i].iter().any(|other| other.name == element.name) {
+            continue;
+        }
+        let [x, y, w, h] = element.rect.map(|v| v * cell);
+        let count = elements.iter().filter(|other| other.name == element.name).count();
+        let more = if count > 1 { format!(" and {} more", count - 1) } else { String::new() };
+        output.push_str(&format!("//   {}: [{}, {}, {}, {}]{}\n", element.name, x, y, w, h, more));
+    }
+    if let Some(palette) = palette_name {
+        output.push_str(&format!("// Colors are taken from the built-in {} palette.\n", palette));
+    }
+
+    let pal_name = format!("{}_palette", name);
+    let color_entries: Vec<String> = std::iter::once("    \"_\": \"#00000000\"".to_string())
+        .chain(
+            tokens
+                .iter()
+                .zip(&colors)
+                .map(|(token, hex)| format!("    \"{}\": \"{}\"", token, hex)),
+        )
+        .collect();
+    output.push_str(&format!(
+        "{{\n  \"type\": \"palette\",\n  \"name\": \"{}\",\n  \"colors\": {{\n{}\n  }},\n  \"roles\": {{\n    \"background\": \"fill\",\n    \"panel\": \"fill\",\n    \"frame\": \"boundary\",\n    \"accent\": \"highlight\"\n  }}\n}}\n",
+        pal_name,
+        color_entries.join(",\n")
+    ));
+
+    // Paint each layer's map, recording which tiles are used
+    let mut used: Vec<(char, String, String)> = Vec::new();
+    let mut layers: Vec<(&str, Vec<Vec<char>>)> = Vec::new();
+    for element in &elements {
+        let index = match layers.iter().position(|(layer, _)| *layer == element.layer) {
+            Some(index) => index,
+            None => {
+                layers.push((element.layer, vec![vec!['.'; cols as usize]; rows as usize]));
+                layers.len() - 1
+            }
+        };
+        let [x0, y0, w, h] = element.rect;
+        for y in y0..y0 + h {
+            for x in x0..x0 + w {
+                let slice = (if y == y0 {
+                    0
+                } else if y == y0 + h - 1 {
+                    2
+                } else {
+                    1
+                }) * 3
+                    + if x == x0 {
+                        0
+                    } else if x == x0 + w - 1 {
+                        2
+                    } else {
+                        1
+                    };
+                let Some((key, tile, regions)) = screen_tile(element.style, slice, cell) else {
+                    continue;
+                };
+                layers[index].1[y as usize][x as usize] = key;
+                if !used.iter().any(|(used, _, _)| *used == key) {
+                    used.push((key, tile, regions));
+                }
+            }
+        }
+    }
+    // Background first, then panel and outline tiles in reading order, accents on top
+    let order: Vec<char> = std::iter::once('B')
+        .chain(PANEL_KEYS)
+        .chain(OUTLINE_KEYS)
+        .chain(std::iter::once('A'))
+        .collect();
+    used.sort_by_key(|(key, _, _)| order.iter().position(|k| k == key));
+
+    for (_, tile, regions) in &used {
+        output.push_str(&format!(
+            "\n{{\n  \"type\": \"sprite\",\n  \"name\": \"{}_{}\",\n  \"size\": [{}, {}],\n  \"palette\": \"{}\",\n  \"regions\": {{\n{}\n  }}\n}}\n",
+            name, tile, cell, cell, pal_name, regions
+        ));
+    }
+
+    let sprite_entries: Vec<String> = std::iter::once("    \".\": null".to_string())
+        .chain(used.iter().map(|(key, tile, _)| format!("    \"{}\": \"{}_{}\"", key, name, tile)))
+        .collect();
+    let layer_entries: Vec<String> = layers
+        .iter()
+        .map(|(layer, map)| {
+            let rows: Vec<String> = map
+                .iter()
+                .map(|row| format!("        \"{}\"", row.iter().collect::<String>()))
+                .collect();
+            format!(
+                "    {{\n      \"name\": \"{}\",\n      \"map\": [\n{}\n      ]\n    }}",
+                layer,
+                rows.join(",\n")
+            )
+        })
+        .collect();
+
+    output.push_str(&format!(
+        "\n{{\n  \"type\": \"composition\",\n  \"name\": \"{}\",\n  \"size\": [{}, {}],\n  \"cell_size\": [{}, {}],\n  \"sprites\": {{\n{}\n  }},\n  \"layers\": [\n{}\n  ]\n}}\n",
+        name,
+        width,
+        height,
+        cell,
+        cell,
+        sprite_entries.join(",\n"),
+        layer_entries.join(",\n")
+    ));
+
+    Ok(output)
+}
+
+/// The map key, sprite name suffix, and regions of the tile for one nine-slice
+/// position of a style, or `None` where the style leaves the cell empty.
+fn screen_tile(style: ScreenStyle, slice: usize, cell: u32) -> Option<(char, String, String)> {
+    let solid = |key, token: &str| {
+        (
+            key,
+            token.to_string(),
+            format!("    \"{}\": {{\"rect\": [0, 0, {}, {}]}}", token, cell, cell),
+        )
+    };
+    let (keys, prefix, fill, edge_token) = match style {
+        ScreenStyle::Background => return Some(solid('B', "background")),
+        ScreenStyle::Accent => return Some(solid('A', "accent")),
+        ScreenStyle::Panel => (PANEL_KEYS, "panel", true, "frame"),
+        ScreenStyle::Outline if slice == 4 => return None,
+        ScreenStyle::Outline => (OUTLINE_KEYS, "outline", false, "accent"),
+    };
+
+    // One-pixel edges on the sides of the slice that face outward
+    let (row, col) = (slice / 3, slice % 3);
+    let last = cell - 1;
+    let mut edges = Vec::new();
+    if row == 0 {
+        edges.push(format!("{{\"rect\": [0, 0, {}, 1]}}", cell));
+    }
+    if row == 2 {
+        edges.push(format!("{{\"rect\": [0, {}, {}, 1]}}", last, cell));
+    }
+    if col == 0 {
+        edges.push(format!("{{\"rect\": [0, 0, 1, {}]}}", cell));
+    }
+    if col == 2 {
+        edges.push(format!("{{\"rect\": [{}, 0, 1, {}]}}", last, cell));
+    }
+
+    let mut regions = Vec::new();
+    if fill {
+        regions.push(format!("    \"panel\": {{\"rect\": [0, 0, {}, {}]}}", cell, cell));
+    }
+    match edges.len() {
+        0 => {}
+        1 => regions.push(format!("    \"{}\": {}", edge_token, with_z(&edges[0], fill))),
+        _ => regions.push(format!(
+            "    \"{}\": {}",
+            edge_token,
+            with_z(&format!("{{\"union\": [{}]}}", edges.join(", ")), fill)
+        )),
+    }
+    Some((keys[slice], format!("{}_{}", prefix, SLICES[slice]), regions.join(",\n")))
+}
+
+/// Add `"z": 1` to a region object so it draws over the panel fill.
+fn with_z(region: &str, raised: bool) -> String {
+    if raised {
+        format!("{}, \"z\": 1}}", &region[..region.len() - 1])
+    } else {
+        region.to_string()
+    }
+}
+
+/// Elements of a screen layout on a `cols` x `rows` grid of `cell`-pixel cells.
+fn screen_elements(layout: ScreenLayout, cols: u32, rows: u32, cell: u32) -> Vec<ScreenElement> {
+    let element = |name, layer, style, rect| ScreenElement { name, layer, style, rect };
+    // Cells spanning roughly `px` pixels, at least two so nine-slice tiles fit
+    let cells = |px: u32| (px / cell).max(2);
+
+    match layout {
+        ScreenLayout::Title => {
+            let (logo_w, logo_h) = (cols * 3 / 4, rows / 4);
+            let (menu_w, menu_h) = (cols / 3, rows / 4);
+            let menu = [(cols - menu_w) / 2, rows * 9 / 16, menu_w, menu_h];
+            vec![
+                element("background", "background", ScreenStyle::Background, [0, 0, cols, rows]),
+                element(
+                    "logo",
+                    "logo",
+                    ScreenStyle::Panel,
+                    [(cols - logo_w) / 2, rows / 8, logo_w, logo_h],
+                ),
+                element("menu", "menu", ScreenStyle::Panel, menu),
+                element("cursor", "cursor", ScreenStyle::Accent, [menu[0] + 1, menu[1] + 1, 1, 1]),
+            ]
+        }
+        ScreenLayout::Hud => {
+            let bar_h = (rows / 8).clamp(4, cols / 4);
+            let widget_h = bar_h - 2;
+            let score_w = cols / 4;
+            vec![
+                element("bar", "bar", ScreenStyle::Panel, [0, 0, cols, bar_h]),
+                element("health", "widgets", ScreenStyle::Accent, [1, 1, cols / 3, widget_h]),
+                element(
+                    "item",
+                    "widgets",
+                    ScreenStyle::Outline,
+                    [(cols - widget_h) / 2, 1, widget_h, widget_h],
+                ),
+                element(
+                    "score",
+                    "widgets",
+                    ScreenStyle::Outline,
+                    [cols - score_w - 1, 1, score_w, widget_h],
+                ),
+            ]
+        }
+        ScreenLayout::Dialogue => {
+            let box_h = (rows / 3).max(5);
+            let dialogue = [1, rows - box_h - 1, cols - 2, box_h];
+            let portrait = (box_h - 2).min(cols / 4);
+            let text_x = dialogue[0] + portrait + 2;
+            let right = dialogue[0] + dialogue[2] - 1;
+            vec![
+                element("box", "box", ScreenStyle::Panel, dialogue),
+                element(
+                    "portrait",
+                    "portrait",
+                    ScreenStyle::Outline,
+                    [dialogue[0] + 1, dialogue[1] + 1, portrait, portrait],
+                ),
+                element(
+                    "text",
+                    "text",
+                    ScreenStyle::Outline,
+                    [text_x, dialogue[1] + 1, right - text_x, box_h - 3],
+                ),
+                element(
+                    "next",
+                    "text",
+                    ScreenStyle::Accent,
+                    [right - 1, dialogue[1] + box_h - 2, 1, 1],
+                ),
+            ]
+        }
+        ScreenLayout::Inventory => {
+            let panel = [cols / 8, rows / 8, cols - cols / 8 * 2, rows - rows / 8 * 2];
+            let title_h = cells(8);
+            let (slot, gap) = (cells(16), (4 / cell).max(1));
+            let area_y = panel[1] + title_h + 2;
+            let area_w = panel[2] - 2;
+            let area_h = (panel[1] + panel[3] - 1).saturating_sub(area_y);
+            let slot_cols = ((area_w + gap) / (slot + gap)).max(1);
+            let slot_rows = ((area_h + gap) / (slot + gap)).max(1);
+            // Center the slot grid horizontally in the panel
+            let grid_w = slot_cols * (slot + gap) - gap;
+            let area_x = panel[0] + 1 + area_w.saturating_sub(grid_w) / 2;
+
+            let mut elements = vec![
+                element("panel", "panel", ScreenStyle::Panel, panel),
+                element(
+                    "title",
+                    "title",
+                    ScreenStyle::Outline,
+                    [panel[0] + 1, panel[1] + 1, panel[2] - 2, title_h],
+                ),
+            ];
+            for row in 0..slot_rows {
+                for col in 0..slot_cols {
+                    let (x, y) = (area_x + col * (slot + gap), area_y + row * (slot + gap));
+                    elements.push(element("slot", "slots", ScreenStyle::Panel, [x, y, slot, slot]));
+                }
+            }
+            elements.push(element(
+                "cursor",
+                "cursor",
+                ScreenStyle::Outline,
+                [area_x, area_y, slot, slot],
+            ));
+            elements
+        }
+    }
+}
+
 /// Get colors for a built-in palette preset.
 fn get_preset_colors(name: &str) -> Result<Vec<(String, String)>, String> {
     match name {
@@ -674,4 +1262,109 @@ version = "0.1.0"
         let parsed: Result<serde_json::Value, _> = serde_json::from_str(&content);
         assert!(parsed.is_ok(), "Palette template should be valid JSON");
     }
+
+    #[test]
+    fn test_generate_sprite_layout_renders() {
+        let parts: Vec<String> = ["head", "torso", "legs"].map(String::from).to_vec();
+        let content = generate_sprite_layout("hero", 16, 16, None, &parts).unwrap();
+        assert!(content.starts_with("// hero: 16x16 sprite skeleton"));
+
+        let parsed = crate::parser::parse_stream(std::io::Cursor::new(&content));
+        assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+        let mut palettes = crate::registry::PaletteRegistry::new();
+        let mut sprite = None;
+        for object in parsed.objects {
+            match object {
+                crate::models::TtpObject::Palette(p) => {
+                    assert_eq!(p.roles.as_ref().unwrap().len(), 3);
+                    palettes.register(p);
+                }
+                crate::models::TtpObject::Sprite(s) => sprite = Some(s),
+                _ => {}
+            }
+        }
+        let sprite = sprite.unwrap();
+        assert!(sprite.regions.as_ref().unwrap()["legs"].symmetric.is_some());
+
+        let palette = palettes.resolve_lenient(&sprite).palette.colors;
+        let (image, warnings) = crate::renderer::render_sprite(&sprite, &palette);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        // Head on top, legs mirrored at the bottom
+        assert_eq!(image.get_pixel(8, 0)[3], 255);
+        assert_eq!(image.get_pixel(4, 15), image.get_pixel(11, 15));
+        assert_eq!(image.get_pixel(8, 15)[3], 0);
+    }
+
+    #[test]
+    fn test_generate_sprite_layout_builtin_palette() {
+        let parts: Vec<String> = ["skin", "body"].map(String::from).to_vec();
+        let content = generate_sprite_layout("npc", 8, 8, Some("@nes"), &parts).unwrap();
+        assert!(content.contains("\"skin\": \"#FCB8B8\""));
+        assert!(content.contains("built-in @nes palette"));
+        assert!(!content.contains("\"#000000\""));
+
+        let err = generate_sprite_layout("npc", 8, 8, Some("@nope"), &parts).unwrap_err();
+        assert!(err.contains("unknown built-in palette 'nope'"));
+    }
+
+    #[test]
+    fn test_generate_sprite_layout_invalid() {
+        let parts = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(generate_sprite_layout("a", 8, 8, None, &[]).is_err());
+        assert!(generate_sprite_layout("a", 8, 8, None, &parts(&["head", "head"])).is_err());
+        assert!(generate_sprite_layout("a", 8, 2, None, &parts(&["a", "b", "c"])).is_err());
+        assert!(generate_sprite_layout("a", 8, 3, None, &parts(&["a", "b", "c"])).is_ok());
+    }
+
+    #[test]
+    fn test_generate_screen_layouts() {
+        use crate::models::TtpObject;
+        use clap::ValueEnum;
+
+        for layout in ScreenLayout::value_variants() {
+            let name = format!("{}_screen", layout.as_str());
+            let content = generate_screen(*layout, &name, 320, 180, None).unwrap();
+            let parsed = crate::parser::parse_stream(std::io::Cursor::new(&content));
+            assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+
+            let mut sprites = Vec::new();
+            let mut composition = None;
+            for object in parsed.objects {
+                match object {
+                    TtpObject::Sprite(sprite) => sprites.push(sprite),
+                    TtpObject::Composition(comp) => composition = Some(comp),
+                    _ => {}
+                }
+            }
+            // Every tile fits its cell, so strict rendering accepts the screen
+            assert!(sprites.iter().all(|s| s.size == Some([4, 4])), "{}", name);
+            let comp = composition.unwrap();
+            assert_eq!(comp.cell_size, Some([4, 4]));
+            assert!(comp.layers.len() >= 2, "{}", name);
+            for layer in &comp.layers {
+                let map = layer.map.as_ref().unwrap();
+                assert_eq!(map.len(), 45);
+                for key in map.iter().flat_map(|row| row.chars()) {
+                    match comp.sprites.get(&key.to_string()) {
+                        Some(Some(tile)) => assert!(sprites.iter().any(|s| s.name == tile.name())),
+                        Some(None) => assert_eq!(key, '.'),
+                        None => panic!("{}: key '{}' has no sprite", name, key),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_screen_options() {
+        let content =
+            generate_screen(ScreenLayout::Inventory, "bag", 160, 144, Some("@gameboy")).unwrap();
+        assert!(content.contains("\"cell_size\": [8, 8]"));
+        assert!(content.contains("built-in @gameboy palette"));
+        assert!(content.contains("//   slot: "));
+
+        assert!(generate_screen(ScreenLayout::Title, "t", 322, 180, None).is_err());
+        assert!(generate_screen(ScreenLayout::Title, "t", 64, 64, None).is_err());
+        assert!(generate_screen(ScreenLayout::Title, "t", 320, 180, Some("@nope")).is_err());
+    }
 }
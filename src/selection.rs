@@ -0,0 +1,143 @@
+//! Selecting objects by label, name or type
+//!
+//! `--only` and `--exclude` take selectors of the form `key=glob`, letting a
+//! large file be built in slices:
+//!
+//! | Selector | Matches |
+//! |----------|---------|
+//! | `label=ui` | Objects whose `labels` include one matching the glob |
+//! | `name=debug_*` | Objects whose name matches the glob |
+//! | `type=sprite` | Objects of the given `"type"` |
+//!
+//! An object is selected when it matches any `--only` selector (or there are
+//! none) and no `--exclude` selector. Selection only decides which objects
+//! produce output: excluded sprites still resolve as animation frames and
+//! composition layers.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::TtpObject;
+
+/// What a [`Selector`] compares against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorKey {
+    Label,
+    Name,
+    Type,
+}
+
+/// A single `key=glob` selector
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub key: SelectorKey,
+    pub pattern: glob::Pattern,
+}
+
+impl Selector {
+    /// Whether an object with this type, name and labels matches.
+    pub fn matches(&self, kind: &str, name: &str, labels: &[String]) -> bool {
+        match self.key {
+            SelectorKey::Label => labels.iter().any(|label| self.pattern.matches(label)),
+            SelectorKey::Name => self.pattern.matches(name),
+            SelectorKey::Type => self.pattern.matches(kind),
+        }
+    }
+}
+
+impl FromStr for Selector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid selector '{}': expected key=value", s))?;
+        let key = match key.trim() {
+            "label" => SelectorKey::Label,
+            "name" => SelectorKey::Name,
+            "type" => SelectorKey::Type,
+            other => {
+                return Err(format!(
+                    "Invalid selector '{}': unknown key '{}' (expected label, name or type)",
+                    s, other
+                ))
+            }
+        };
+        let pattern = glob::Pattern::new(value.trim())
+            .map_err(|e| format!("Invalid selector '{}': {}", s, e))?;
+        Ok(Self { key, pattern })
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = match self.key {
+            SelectorKey::Label => "label",
+            SelectorKey::Name => "name",
+            SelectorKey::Type => "type",
+        };
+        write!(f, "{}={}", key, self.pattern)
+    }
+}
+
+/// `--only` and `--exclude` selectors together
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selection {
+    pub only: Vec<Selector>,
+    pub exclude: Vec<Selector>,
+}
+
+impl Selection {
+    /// Whether no selectors are set, so everything is selected.
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether an object with this type, name and labels is selected.
+    pub fn includes(&self, kind: &str, name: &str, labels: &[String]) -> bool {
+        let wanted =
+            self.only.is_empty() || self.only.iter().any(|s| s.matches(kind, name, labels));
+        wanted && !self.exclude.iter().any(|s| s.matches(kind, name, labels))
+    }
+
+    /// Whether a parsed object is selected.
+    pub fn includes_object(&self, obj: &TtpObject) -> bool {
+        self.includes(obj.kind(), obj.name(), obj.labels())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        let selector: Selector = "name=debug_*".parse().unwrap();
+        assert_eq!(selector.key, SelectorKey::Name);
+        assert_eq!(selector.to_string(), "name=debug_*");
+
+        assert!("debug".parse::<Selector>().unwrap_err().contains("key=value"));
+        assert!("tag=ui".parse::<Selector>().unwrap_err().contains("unknown key 'tag'"));
+        assert!("name=[".parse::<Selector>().is_err());
+    }
+
+    #[test]
+    fn test_selection_includes() {
+        let selection = Selection {
+            only: vec!["label=ui".parse().unwrap(), "type=animation".parse().unwrap()],
+            exclude: vec!["name=debug_*".parse().unwrap()],
+        };
+        assert!(selection.includes("sprite", "button", &labels(&["ui", "menu"])));
+        assert!(selection.includes("animation", "walk", &[]));
+        assert!(!selection.includes("sprite", "hero", &labels(&["enemy"])));
+        assert!(!selection.includes("sprite", "debug_button", &labels(&["ui"])));
+
+        let everything = Selection::default();
+        assert!(everything.is_empty());
+        assert!(everything.includes("composition", "scene", &[]));
+    }
+}
@@ -0,0 +1,291 @@
+//! Animation sequences
+//!
+//! A [`Sequence`] chains existing animations into one, for trailers and preview
+//! reels. [`flatten_sequence`] expands it into a plain frame-based
+//! [`Animation`] with per-frame durations, so every animation renderer can play
+//! it unchanged. Crossfades need pixels, so they are applied afterwards by
+//! [`apply_crossfades`] once the frames have been rendered.
+
+use std::collections::HashMap;
+
+use image::{Rgba, RgbaImage};
+
+use crate::models::{Animation, Duration, Sequence};
+
+/// A crossfade into the frame at `frame` (an index into the flattened frames).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossfade {
+    /// Index of the first frame of the step being faded in
+    pub frame: usize,
+    /// Length of the transition in milliseconds
+    pub duration_ms: u32,
+}
+
+/// A sequence expanded into a single animation.
+#[derive(Debug, Clone)]
+pub struct FlattenedSequence {
+    /// Frame-based animation covering every step
+    pub animation: Animation,
+    /// Transitions to insert once frames are rendered
+    pub crossfades: Vec<Crossfade>,
+}
+
+/// Expand `sequence` into one frame-based animation.
+///
+/// Each step's frames are repeated `repeat` times in the step animation's
+/// playback direction, keeping its per-frame timing. Only frame-based
+/// animations can be sequenced; CSS keyframe and palette-cycle animations are
+/// rejected.
+pub fn flatten_sequence(
+    sequence: &Sequence,
+    animations: &HashMap<String, Animation>,
+) -> Result<FlattenedSequence, String> {
+    let mut frames = Vec::new();
+    let mut durations = Vec::new();
+    let mut crossfades = Vec::new();
+
+    for (i, step) in sequence.steps.iter().enumerate() {
+        let context = || format!("sequence '{}' step {}", sequence.name, i + 1);
+        let anim = animations
+            .get(&step.animation)
+            .ok_or_else(|| format!("{}: unknown animation '{}'", context(), step.animation))?;
+        if anim.is_css_keyframes() || !anim.is_frame_based() || anim.has_palette_cycle() {
+            return Err(format!(
+                "{}: animation '{}' is not frame-based and cannot be sequenced",
+                context(),
+                anim.name
+            ));
+        }
+
+        let anim_durations = anim.frame_durations().map_err(|e| format!("{}: {}", context(), e))?;
+        let order = anim.direction().frame_order(anim.frames.len());
+
+        if let Some(crossfade) = &step.crossfade {
+            let duration_ms = crossfade
+                .as_milliseconds()
+                .ok_or_else(|| format!("{}: invalid crossfade {}", context(), crossfade))?;
+            // Nothing to fade from on the first step
+            if !frames.is_empty() && duration_ms > 0 && step.repeat_count() > 0 {
+                crossfades.push(Crossfade { frame: frames.len(), duration_ms });
+            }
+        }
+
+        for _ in 0..step.repeat_count() {
+            for &idx in &order {
+                frames.push(anim.frames[idx].clone());
+                durations.push(Duration::Milliseconds(anim_durations[idx]));
+            }
+        }
+    }
+
+    if frames.is_empty() {
+        return Err(format!("sequence '{}' has no frames", sequence.name));
+    }
+
+    let animation = Animation {
+        name: sequence.name.clone(),
+        frames,
+        durations: Some(durations),
+        r#loop: Some(sequence.loops()),
+        labels: sequence.labels.clone(),
+        ..Default::default()
+    };
+
+    Ok(FlattenedSequence { animation, crossfades })
+}
+
+/// Insert crossfade frames into rendered sequence frames.
+///
+/// Each crossfade adds blended frames between the last frame of the previous
+/// step and the first frame of the next, at the next step's frame rate, so the
+/// sequence gets longer by the crossfade duration. Frames of different sizes
+/// are aligned at the top-left corner.
+pub fn apply_crossfades(
+    frames: Vec<RgbaImage>,
+    durations: Vec<u32>,
+    crossfades: &[Crossfade],
+) -> (Vec<RgbaImage>, Vec<u32>) {
+    if crossfades.is_empty() {
+        return (frames, durations);
+    }
+
+    let by_frame: HashMap<usize, u32> =
+        crossfades.iter().map(|c| (c.frame, c.duration_ms)).collect();
+    let mut out_frames = Vec::with_capacity(frames.len());
+    let mut out_durations = Vec::with_capacity(durations.len());
+
+    for (i, (frame, duration)) in frames.iter().zip(&durations).enumerate() {
+        if let Some(&fade_ms) = by_frame.get(&i).filter(|_| i > 0) {
+            let steps = (fade_ms / (*duration).max(1)).max(1);
+            let step_ms = fade_ms / steps;
+            for s in 1..=steps {
+                let t = s as f32 / (steps + 1) as f32;
+                out_frames.push(blend(&frames[i - 1], frame, t));
+                // Give any rounding remainder to the last blended frame
+                let extra = if s == steps { fade_ms - step_ms * steps } else { 0 };
+                out_durations.push(step_ms + extra);
+            }
+        }
+        out_frames.push(frame.clone());
+        out_durations.push(*duration);
+    }
+
+    (out_frames, out_durations)
+}
+
+/// Blend `from` into `to` by `t` (0.0 = `from`, 1.0 = `to`).
+///
+/// Colors are mixed with premultiplied alpha so fading against transparency
+/// doesn't darken edges.
+pub fn blend(from: &RgbaImage, to: &RgbaImage, t: f32) -> RgbaImage {
+    let width = from.width().max(to.width());
+    let height = from.height().max(to.height());
+    let transparent = Rgba([0, 0, 0, 0]);
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let a = from.get_pixel_checked(x, y).copied().unwrap_or(transparent);
+        let b = to.get_pixel_checked(x, y).copied().unwrap_or(transparent);
+
+        let alpha_a = a[3] as f32 / 255.0;
+        let alpha_b = b[3] as f32 / 255.0;
+        let alpha = alpha_a + (alpha_b - alpha_a) * t;
+        if alpha <= 0.0 {
+            return transparent;
+        }
+
+        let mut out = [0u8; 4];
+        for c in 0..3 {
+            let pa = a[c] as f32 * alpha_a;
+            let pb = b[c] as f32 * alpha_b;
+            out[c] = ((pa + (pb - pa) * t) / alpha).round().clamp(0.0, 255.0) as u8;
+        }
+        out[3] = (alpha * 255.0).round() as u8;
+        Rgba(out)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PaletteCycle, PlaybackDirection, SequenceStep};
+
+    fn anim(name: &str, frames: &[&str], duration: u32) -> Animation {
+        Animation {
+            name: name.to_string(),
+            frames: frames.iter().map(|f| f.to_string()).collect(),
+            duration: Some(Duration::Milliseconds(duration)),
+            ..Default::default()
+        }
+    }
+
+    fn step(animation: &str, repeat: Option<u32>, crossfade: Option<u32>) -> SequenceStep {
+        SequenceStep {
+            animation: animation.to_string(),
+            repeat,
+            crossfade: crossfade.map(Duration::Milliseconds),
+        }
+    }
+
+    #[test]
+    fn test_flatten_sequence() {
+        let mut attack = anim("attack", &["a1", "a2"], 50);
+        attack.durations = Some(vec![Duration::Milliseconds(200), Duration::Milliseconds(50)]);
+        let animations = HashMap::from([
+            ("idle".to_string(), anim("idle", &["i1", "i2"], 100)),
+            ("attack".to_string(), attack),
+        ]);
+        let sequence = Sequence {
+            name: "reel".to_string(),
+            steps: vec![
+                step("idle", Some(2), None),
+                step("attack", None, Some(100)),
+                step("idle", None, None),
+            ],
+            r#loop: Some(false),
+            labels: Vec::new(),
+        };
+
+        let flat = flatten_sequence(&sequence, &animations).unwrap();
+        assert_eq!(flat.animation.frames, vec!["i1", "i2", "i1", "i2", "a1", "a2", "i1", "i2"]);
+        assert_eq!(
+            flat.animation.frame_durations().unwrap(),
+            vec![100, 100, 100, 100, 200, 50, 100, 100]
+        );
+        assert!(!flat.animation.loops());
+        assert_eq!(flat.crossfades, vec![Crossfade { frame: 4, duration_ms: 100 }]);
+    }
+
+    #[test]
+    fn test_flatten_sequence_uses_step_direction() {
+        let mut bounce = anim("bounce", &["b1", "b2", "b3"], 100);
+        bounce.direction = Some(PlaybackDirection::Reverse);
+        let animations = HashMap::from([("bounce".to_string(), bounce)]);
+        let sequence = Sequence {
+            name: "reel".to_string(),
+            steps: vec![step("bounce", None, Some(100))],
+            ..Default::default()
+        };
+
+        let flat = flatten_sequence(&sequence, &animations).unwrap();
+        assert_eq!(flat.animation.frames, vec!["b3", "b2", "b1"]);
+        // No previous step to fade from
+        assert!(flat.crossfades.is_empty());
+    }
+
+    #[test]
+    fn test_flatten_sequence_errors() {
+        let mut water = anim("water", &["w"], 100);
+        water.palette_cycle = Some(vec![PaletteCycle {
+            tokens: vec!["{a}".to_string(), "{b}".to_string()],
+            duration: None,
+            direction: None,
+        }]);
+        let animations = HashMap::from([
+            ("idle".to_string(), anim("idle", &["i1"], 100)),
+            ("water".to_string(), water),
+        ]);
+
+        let cycled = Sequence {
+            name: "reel".to_string(),
+            steps: vec![step("idle", None, None), step("water", None, None)],
+            ..Default::default()
+        };
+        let err = flatten_sequence(&cycled, &animations).unwrap_err();
+        assert!(err.contains("step 2"), "{}", err);
+
+        let unknown = Sequence {
+            name: "reel".to_string(),
+            steps: vec![step("missing", None, None)],
+            ..Default::default()
+        };
+        let err = flatten_sequence(&unknown, &animations).unwrap_err();
+        assert!(err.contains("unknown animation 'missing'"), "{}", err);
+
+        let empty = Sequence { name: "reel".to_string(), ..Default::default() };
+        assert!(flatten_sequence(&empty, &animations).is_err());
+    }
+
+    #[test]
+    fn test_apply_crossfades() {
+        let red = RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        let blue = RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255]));
+        let crossfades = [Crossfade { frame: 1, duration_ms: 100 }];
+
+        let (frames, durations) = apply_crossfades(vec![red, blue], vec![100, 50], &crossfades);
+        // Two 50ms blend frames at the incoming rate
+        assert_eq!(durations, vec![100, 50, 50, 50]);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[1].get_pixel(0, 0).0, [170, 0, 85, 255]);
+        assert_eq!(frames[2].get_pixel(0, 0).0, [85, 0, 170, 255]);
+    }
+
+    #[test]
+    fn test_blend_with_transparency() {
+        let clear = RgbaImage::new(1, 1);
+        let white = RgbaImage::from_pixel(2, 1, Rgba([255, 255, 255, 255]));
+        let mid = blend(&clear, &white, 0.5);
+        assert_eq!(mid.dimensions(), (2, 1));
+        // Color stays white while alpha fades in
+        assert_eq!(mid.get_pixel(0, 0).0, [255, 255, 255, 128]);
+    }
+}
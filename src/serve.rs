@@ -0,0 +1,982 @@
+//! HTTP preview server for `pxl serve`
+//!
+//! Serves rendered sprites, compositions, and animations on demand, plus a
+//! small index page that reloads itself whenever a watched source file changes.
+//!
+//! # Routes
+//!
+//! | Route | Response |
+//! |-------|----------|
+//! | `/` | Index page listing every object (auto-reloading) |
+//! | `/sprite/{name}.png?scale=N&crop=x,y,w,h` | Rendered sprite or variant |
+//! | `/composition/{name}.png?scale=N&crop=x,y,w,h` | Rendered composition |
+//! | `/anim/{name}.gif?scale=N` | Animated GIF |
+//! | `/anim/{name}.png?scale=N` | Horizontal spritesheet |
+//! | `/objects.json` | Object names as JSON |
+//! | `/__version` | Reload counter polled by the index page |
+//!
+//! `crop` renders only that rectangle of a sprite or composition (before
+//! scaling), for tile-server style access to large images. `reference=1`
+//! draws a sprite over its `reference` image; the index page always does.
+//! `overlay=grid,boxes,...` draws debug guides over sprites and compositions
+//! (see [`crate::overlay`]).
+//!
+//! The server is intentionally dependency-free (plain `std::net`) and only
+//! speaks enough HTTP/1.1 for browsers and `curl`. A fixed pool of
+//! [`WORKERS`] threads answers requests; further connections wait their turn.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use image::RgbaImage;
+use notify::RecursiveMode;
+use notify_debouncer_mini::new_debouncer;
+use thiserror::Error;
+
+use crate::cli::{find_pixelsrc_files, is_pixelsrc_file};
+use crate::composition::{apply_palette_lock, render_composition};
+use crate::config::LimitsConfig;
+use crate::gif::{encode_gif_with_durations, GifOptions};
+use crate::limits::check_object;
+use crate::models::{Animation, Composition, Sequence, Sprite, TtpObject};
+use crate::output::{encode_png, scale_image};
+use crate::overlay::{draw_overlays, Guides, Overlay};
+use crate::parser::parse_stream;
+use crate::playback::precompute_animation;
+use crate::registry::{PaletteRegistry, ResolvedSprite, SpriteRegistry};
+use crate::renderer::{crop_image, render_resolved, render_resolved_region, Rect};
+use crate::selection::Selection;
+use crate::sequence::{apply_crossfades, flatten_sequence};
+use crate::spritesheet::render_spritesheet;
+use crate::underlay::underlay_sprite;
+
+/// Largest `?scale=` accepted by the preview routes.
+pub const MAX_SCALE: u8 = 32;
+
+/// Number of connections served at once.
+pub const WORKERS: usize = 8;
+
+/// Error starting or running the preview server
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ServeError {
+    /// Failed to bind the listening socket
+    #[error("Failed to bind {addr}: {source}")]
+    Bind { addr: String, source: std::io::Error },
+    /// Failed to initialize the file watcher
+    #[error("Failed to watch sources: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+/// Everything parsed from the served source files.
+#[derive(Debug, Default)]
+pub struct PreviewCatalog {
+    palettes: PaletteRegistry,
+    sprite_registry: SpriteRegistry,
+    sprites: HashMap<String, Sprite>,
+    /// Directory of the file defining each sprite, for reference images
+    sprite_dirs: HashMap<String, PathBuf>,
+    variants: Vec<String>,
+    compositions: HashMap<String, Composition>,
+    animations: HashMap<String, Animation>,
+    sequences: HashMap<String, Sequence>,
+    /// Objects over these limits are skipped with a warning
+    limits: LimitsConfig,
+    /// Parse warnings from the most recent load
+    pub warnings: Vec<String>,
+}
+
+impl PreviewCatalog {
+    /// Parse every file in `files` into a single catalog.
+    ///
+    /// Later definitions override earlier ones with the same name.
+    pub fn load(files: &[PathBuf]) -> Self {
+        Self::load_with_limits(files, LimitsConfig::default())
+    }
+
+    /// Like [`Self::load`], skipping objects over a project's `[limits]`.
+    pub fn load_with_limits(files: &[PathBuf], limits: LimitsConfig) -> Self {
+        let mut catalog = Self { limits, ..Self::default() };
+        for path in files {
+            let file = match File::open(path) {
+                Ok(f) => f,
+                Err(e) => {
+                    catalog.warnings.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            };
+            let result = parse_stream(BufReader::new(file));
+            for w in result.warnings {
+                catalog.warnings.push(format!("{}:{}: {}", path.display(), w.line, w.message));
+            }
+            let dir = path.parent().unwrap_or(Path::new(""));
+            for obj in result.objects {
+                catalog.add(obj, dir);
+            }
+        }
+        catalog
+    }
+
+    /// Parse a single in-memory source string.
+    pub fn from_source(source: &str) -> Self {
+        let mut catalog = Self::default();
+        let result = parse_stream(std::io::Cursor::new(source));
+        for w in result.warnings {
+            catalog.warnings.push(format!("line {}: {}", w.line, w.message));
+        }
+        for obj in result.objects {
+            catalog.add(obj, Path::new(""));
+        }
+        catalog
+    }
+
+    fn add(&mut self, obj: TtpObject, dir: &Path) {
+        if let Err(e) = check_object(&obj, &self.limits) {
+            self.warnings.push(e);
+            return;
+        }
+        match obj {
+            TtpObject::Palette(p) => self.palettes.register(p),
+            TtpObject::Sprite(s) => {
+                if let Some(reference) = &s.reference {
+                    if !dir.join(&reference.path).is_file() {
+                        self.warnings.push(format!(
+                            "sprite '{}': reference image '{}' not found",
+                            s.name, reference.path
+                        ));
+                    }
+                }
+                self.sprite_registry.register_sprite(s.clone());
+                self.sprite_dirs.insert(s.name.clone(), dir.to_path_buf());
+                self.sprites.insert(s.name.clone(), s);
+            }
+            TtpObject::Variant(v) => {
+                self.variants.push(v.name.clone());
+                self.sprite_registry.register_variant(v);
+            }
+            TtpObject::Composition(c) => {
+                self.compositions.insert(c.name.clone(), c);
+            }
+            TtpObject::Animation(a) => {
+                self.animations.insert(a.name.clone(), a);
+            }
+            TtpObject::Sequence(s) => {
+                self.sequences.insert(s.name.clone(), s);
+            }
+            _ => {}
+        }
+    }
+
+    /// Sprite and variant names, sorted.
+    pub fn sprite_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.sprites.keys().map(|s| s.as_str()).collect();
+        names.extend(self.variants.iter().map(|s| s.as_str()));
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Composition names, sorted.
+    pub fn composition_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.compositions.keys().map(|s| s.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Animation and sequence names, sorted.
+    pub fn animation_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> =
+            self.animations.keys().chain(self.sequences.keys()).map(|s| s.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// A sprite's definition (`None` for variants and unknown names).
+    pub fn sprite(&self, name: &str) -> Option<&Sprite> {
+        self.sprites.get(name)
+    }
+
+    /// An animation's definition (`None` for sequences and unknown names).
+    pub fn animation(&self, name: &str) -> Option<&Animation> {
+        self.animations.get(name)
+    }
+
+    /// Every palette in the catalog.
+    pub fn palettes(&self) -> &PaletteRegistry {
+        &self.palettes
+    }
+
+    /// Animation and sequence names picked by `--only`/`--exclude`, sorted.
+    pub fn select_animations(&self, selection: &Selection) -> Vec<&str> {
+        let selected = |name: &&str| match self.animations.get(*name) {
+            Some(anim) => selection.includes("animation", name, &anim.labels),
+            None => selection.includes("sequence", name, &self.sequences[*name].labels),
+        };
+        self.animation_names().into_iter().filter(selected).collect()
+    }
+
+    /// Render a sprite or variant (transforms and `source` references applied).
+    pub fn render_sprite(&self, name: &str) -> Result<RgbaImage, String> {
+        if !self.sprite_registry.contains(name) {
+            return Err(format!("sprite '{}' not found", name));
+        }
+        let resolved = self
+            .sprite_registry
+            .resolve(name, &self.palettes, false)
+            .map_err(|e| format!("sprite '{}': {}", name, e))?;
+        Ok(render_resolved(&resolved).0)
+    }
+
+    /// Render a sprite or variant at `scale`, over the sprite's reference
+    /// image if it has one that loads.
+    pub fn render_sprite_preview(&self, name: &str, scale: u8) -> Result<RgbaImage, String> {
+        let image = scale_image(self.render_sprite(name)?, scale);
+        match (self.sprites.get(name), self.sprite_dirs.get(name)) {
+            (Some(sprite), Some(dir)) => {
+                // A missing reference is reported as a load warning instead
+                Ok(underlay_sprite(image.clone(), sprite, dir).unwrap_or(image))
+            }
+            _ => Ok(image),
+        }
+    }
+
+    /// Overlay guides for a sprite, from its metadata and nine-slice.
+    pub fn sprite_guides(&self, name: &str) -> Guides {
+        self.sprites
+            .get(name)
+            .map(|s| Guides::for_sprite(s.metadata.as_ref(), s.nine_slice.as_ref()))
+            .unwrap_or_default()
+    }
+
+    /// Overlay guides for a composition: its cell boundaries.
+    pub fn composition_guides(&self, name: &str) -> Guides {
+        self.compositions.get(name).map(Guides::for_composition).unwrap_or_default()
+    }
+
+    /// Render only the `rect` part of a sprite or variant.
+    ///
+    /// `rect` is clipped to the sprite; one entirely outside it is an error.
+    pub fn render_sprite_region(&self, name: &str, rect: Rect) -> Result<RgbaImage, String> {
+        Ok(render_resolved_region(&self.resolve_sprite(name)?, rect)?.0)
+    }
+
+    fn resolve_sprite(&self, name: &str) -> Result<ResolvedSprite, String> {
+        if !self.sprite_registry.contains(name) {
+            return Err(format!("sprite '{}' not found", name));
+        }
+        self.sprite_registry
+            .resolve(name, &self.palettes, false)
+            .map_err(|e| format!("sprite '{}': {}", name, e))
+    }
+
+    /// Render a composition by name.
+    pub fn render_composition(&self, name: &str) -> Result<RgbaImage, String> {
+        let comp = self
+            .compositions
+            .get(name)
+            .ok_or_else(|| format!("composition '{}' not found", name))?;
+
+        let mut rendered = HashMap::new();
+        let referenced = comp.base.iter().map(String::as_str).chain(comp.sprite_names());
+        for sprite_name in referenced.chain(comp.mask_sprites().map(String::as_str)) {
+            if rendered.contains_key(sprite_name) {
+                continue;
+            }
+            if let Ok(image) = self.render_sprite(sprite_name) {
+                rendered.insert(sprite_name.to_string(), image);
+            }
+        }
+
+        let (mut image, _) = render_composition(comp, &rendered, false, None)
+            .map_err(|e| format!("composition '{}': {}", name, e))?;
+        apply_palette_lock(comp, &mut image, &rendered, &self.palettes, &mut Vec::new());
+        Ok(image)
+    }
+
+    /// Render every frame of an animation or sequence, returning frames and per-frame durations.
+    pub fn render_animation(&self, name: &str) -> Result<(Vec<RgbaImage>, Vec<u32>, bool), String> {
+        if let Some(anim) = self.animations.get(name) {
+            let (timeline, _) = precompute_animation(anim, &self.sprites, &self.palettes);
+            if timeline.frames.is_empty() {
+                return Err(format!("animation '{}' has no renderable frames", name));
+            }
+            return Ok((timeline.frames, timeline.durations, timeline.loops));
+        }
+
+        let sequence =
+            self.sequences.get(name).ok_or_else(|| format!("animation '{}' not found", name))?;
+        let flat = flatten_sequence(sequence, &self.animations)?;
+        let (timeline, _) = precompute_animation(&flat.animation, &self.sprites, &self.palettes);
+        if timeline.frames.is_empty() {
+            return Err(format!("sequence '{}' has no renderable frames", name));
+        }
+        let (frames, durations) = if timeline.frames.len() == flat.animation.frames.len() {
+            apply_crossfades(timeline.frames, timeline.durations, &flat.crossfades)
+        } else {
+            (timeline.frames, timeline.durations)
+        };
+        Ok((frames, durations, timeline.loops))
+    }
+}
+
+/// An HTTP response produced by [`handle_request`].
+#[derive(Debug)]
+pub struct Response {
+    /// HTTP status code
+    pub status: u16,
+    /// `Content-Type` header value
+    pub content_type: &'static str,
+    /// Response body
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self { status: 200, content_type, body }
+    }
+
+    fn text(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: message.into().into_bytes(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Shared server state: the current catalog plus a reload counter.
+#[derive(Debug)]
+pub struct ServeState {
+    sources: Vec<PathBuf>,
+    catalog: RwLock<PreviewCatalog>,
+    version: AtomicU64,
+    /// `--define` flags in effect when the server started, for reloads
+    defines: Vec<String>,
+    /// Project `[limits]` applied on every load
+    limits: LimitsConfig,
+}
+
+/// Expand source paths into the Pixelsrc files they contain.
+pub fn expand_sources(sources: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in sources {
+        if path.is_dir() {
+            let mut found = find_pixelsrc_files(path);
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files
+}
+
+impl ServeState {
+    /// Load the catalog from `sources` (files or directories).
+    pub fn new(sources: Vec<PathBuf>) -> Self {
+        Self::with_limits(sources, LimitsConfig::default())
+    }
+
+    /// Like [`Self::new`], skipping objects over `limits` on every load.
+    pub fn with_limits(sources: Vec<PathBuf>, limits: LimitsConfig) -> Self {
+        let catalog = PreviewCatalog::load_with_limits(&expand_sources(&sources), limits.clone());
+        Self {
+            sources,
+            catalog: RwLock::new(catalog),
+            version: AtomicU64::new(1),
+            defines: crate::conditions::defines(),
+            limits,
+        }
+    }
+
+    /// Build state from an already-parsed catalog (no backing files).
+    pub fn from_catalog(catalog: PreviewCatalog) -> Self {
+        Self {
+            sources: Vec::new(),
+            catalog: RwLock::new(catalog),
+            version: AtomicU64::new(1),
+            defines: crate::conditions::defines(),
+            limits: LimitsConfig::default(),
+        }
+    }
+
+    /// Re-scan and re-parse all sources, then bump the reload counter.
+    pub fn reload(&self) {
+        let catalog = crate::conditions::with_defines(self.defines.clone(), || {
+            PreviewCatalog::load_with_limits(&expand_sources(&self.sources), self.limits.clone())
+        });
+        for w in &catalog.warnings {
+            eprintln!("Warning: {}", w);
+        }
+        *self.catalog.write().unwrap_or_else(|e| e.into_inner()) = catalog;
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Current reload counter.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+}
+
+/// Query parameters accepted by the image routes.
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    scale: u8,
+    crop: Option<Rect>,
+    reference: bool,
+    overlays: Vec<Overlay>,
+}
+
+/// Split a request target into its path and query parameters.
+fn parse_target(target: &str) -> Result<(&str, Query), String> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut scale = 1u8;
+    let mut crop = None;
+    let mut reference = false;
+    let mut overlays = Vec::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        if let Some(value) = pair.strip_prefix("scale=") {
+            scale = value
+                .parse::<u8>()
+                .ok()
+                .filter(|s| (1..=MAX_SCALE).contains(s))
+                .ok_or_else(|| format!("scale must be between 1 and {}", MAX_SCALE))?;
+        } else if let Some(value) = pair.strip_prefix("crop=") {
+            let rect = percent_decode(value).parse().map_err(|e| format!("crop: {}", e))?;
+            crop = Some(rect);
+        } else if let Some(value) = pair.strip_prefix("reference=") {
+            reference = matches!(value, "1" | "true");
+        } else if let Some(value) = pair.strip_prefix("overlay=") {
+            for kind in percent_decode(value).split(',').filter(|k| !k.is_empty()) {
+                overlays.push(kind.parse().map_err(|e| format!("overlay: {}", e))?);
+            }
+        }
+    }
+    Ok((path, Query { scale, crop, reference, overlays }))
+}
+
+/// Decode `%XX` escapes in a path segment.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if let (b'%', Some(&[hi, lo])) = (bytes[i], bytes.get(i + 1..i + 3)) {
+            if let (Some(hi), Some(lo)) = (hex_value(hi), hex_value(lo)) {
+                out.push(hi << 4 | lo);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Encode `s` as a URL path segment, keeping only unreserved characters.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn index_page(catalog: &PreviewCatalog, version: u64) -> String {
+    let mut html = String::from(
+        "<!doctype html>\n<html><head><meta charset=\"utf-8\"><title>pxl serve</title>\n<style>\
+         body{font-family:sans-serif;background:#222;color:#eee}\
+         figure{display:inline-block;margin:8px;text-align:center}\
+         img{image-rendering:pixelated;background:#444}</style></head><body>\n",
+    );
+
+    let sections: [(&str, &str, &str, Vec<&str>); 3] = [
+        ("Sprites", "sprite", "png?reference=1&", catalog.sprite_names()),
+        ("Compositions", "composition", "png?", catalog.composition_names()),
+        ("Animations", "anim", "gif?", catalog.animation_names()),
+    ];
+    for (title, route, ext, names) in sections {
+        if names.is_empty() {
+            continue;
+        }
+        html.push_str(&format!("<h2>{}</h2>\n", title));
+        for name in names {
+            html.push_str(&format!(
+                "<figure><img src=\"/{}/{}.{}scale=4&v={}\"><figcaption>{}</figcaption></figure>\n",
+                route,
+                percent_encode(name),
+                ext,
+                version,
+                html_escape(name)
+            ));
+        }
+    }
+
+    if !catalog.warnings.is_empty() {
+        html.push_str("<h2>Warnings</h2><pre>");
+        for w in &catalog.warnings {
+            html.push_str(&html_escape(w));
+            html.push('\n');
+        }
+        html.push_str("</pre>\n");
+    }
+
+    html.push_str(&format!(
+        "<script>\nlet v={};\nsetInterval(async()=>{{try{{const r=await fetch('/__version');\
+         if(Number(await r.text())!==v)location.reload();}}catch(e){{}}}},500);\n</script>\n\
+         </body></html>\n",
+        version
+    ));
+    html
+}
+
+/// Draw `overlays` on a scaled image whose source was cropped to `crop`.
+fn with_overlays(
+    mut image: RgbaImage,
+    scale: u8,
+    overlays: &[Overlay],
+    guides: Guides,
+    crop: Option<Rect>,
+) -> RgbaImage {
+    if !overlays.is_empty() {
+        let guides = match crop {
+            Some(rect) => guides.cropped(rect),
+            None => guides,
+        };
+        draw_overlays(&mut image, scale as u32, overlays, &guides);
+    }
+    image
+}
+
+/// Produce the response for a `GET` of `target` (path plus optional query).
+pub fn handle_request(state: &ServeState, target: &str) -> Response {
+    let (path, Query { scale, crop, reference, overlays }) = match parse_target(target) {
+        Ok(parsed) => parsed,
+        Err(e) => return Response::text(400, e),
+    };
+    let catalog = state.catalog.read().unwrap_or_else(|e| e.into_inner());
+
+    let scaled_png = |result: Result<RgbaImage, String>| match result {
+        Ok(image) => match encode_png(&image) {
+            Ok(bytes) => Response::ok("image/png", bytes),
+            Err(e) => Response::text(500, e.to_string()),
+        },
+        Err(e) => Response::text(404, e),
+    };
+    let png = |result: Result<RgbaImage, String>| {
+        scaled_png(result.map(|image| scale_image(image, scale)))
+    };
+
+    if path == "/" || path == "/index.html" {
+        return Response::ok(
+            "text/html; charset=utf-8",
+            index_page(&catalog, state.version()).into_bytes(),
+        );
+    }
+    if path == "/__version" {
+        return Response::text(200, state.version().to_string());
+    }
+    if path == "/objects.json" {
+        let objects: BTreeMap<&str, Vec<&str>> = BTreeMap::from([
+            ("sprites", catalog.sprite_names()),
+            ("compositions", catalog.composition_names()),
+            ("animations", catalog.animation_names()),
+        ]);
+        let body = serde_json::to_vec_pretty(&objects).unwrap_or_default();
+        return Response::ok("application/json", body);
+    }
+
+    if let Some(name) = path.strip_prefix("/sprite/").and_then(|n| n.strip_suffix(".png")) {
+        let name = percent_decode(name);
+        let image = match crop {
+            None if reference => catalog.render_sprite_preview(&name, scale),
+            None => catalog.render_sprite(&name).map(|image| scale_image(image, scale)),
+            Some(rect) => match catalog.resolve_sprite(&name) {
+                Ok(resolved) => match render_resolved_region(&resolved, rect) {
+                    Ok((image, _)) => Ok(scale_image(image, scale)),
+                    Err(e) => return Response::text(400, format!("crop: {}", e)),
+                },
+                Err(e) => Err(e),
+            },
+        };
+        let guides = catalog.sprite_guides(&name);
+        return scaled_png(image.map(|image| with_overlays(image, scale, &overlays, guides, crop)));
+    }
+    if let Some(name) = path.strip_prefix("/composition/").and_then(|n| n.strip_suffix(".png")) {
+        let name = percent_decode(name);
+        let image = match (catalog.render_composition(&name), crop) {
+            (Ok(image), Some(rect)) => match crop_image(&image, rect) {
+                Ok(cropped) => Ok(scale_image(cropped, scale)),
+                Err(e) => return Response::text(400, format!("crop: {}", e)),
+            },
+            (image, _) => image.map(|image| scale_image(image, scale)),
+        };
+        let guides = catalog.composition_guides(&name);
+        return scaled_png(image.map(|image| with_overlays(image, scale, &overlays, guides, crop)));
+    }
+    if crop.is_some() {
+        return Response::text(400, "crop only applies to sprites and compositions");
+    }
+    if !overlays.is_empty() {
+        return Response::text(400, "overlay only applies to sprites and compositions");
+    }
+    if let Some(rest) = path.strip_prefix("/anim/") {
+        let (name, as_gif) = if let Some(n) = rest.strip_suffix(".gif") {
+            (n, true)
+        } else if let Some(n) = rest.strip_suffix(".png") {
+            (n, false)
+        } else {
+            return Response::text(404, format!("unknown animation format: {}", rest));
+        };
+        let (frames, durations, loops) = match catalog.render_animation(&percent_decode(name)) {
+            Ok(rendered) => rendered,
+            Err(e) => return Response::text(404, e),
+        };
+        let frames: Vec<RgbaImage> = frames.into_iter().map(|f| scale_image(f, scale)).collect();
+        if as_gif {
+            let mut bytes = Vec::new();
+            return match encode_gif_with_durations(
+                &frames,
+                &durations,
+                loops,
+                &GifOptions::default(),
+                &mut bytes,
+            ) {
+                Ok(()) => Response::ok("image/gif", bytes),
+                Err(e) => Response::text(500, e.to_string()),
+            };
+        }
+        return png(Ok(render_spritesheet(&frames, None)));
+    }
+
+    Response::text(404, format!("no route for {}", path))
+}
+
+fn handle_connection(state: &ServeState, mut stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(5)));
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Drain headers; the body (if any) is ignored
+    let mut header = String::new();
+    while reader.read_line(&mut header).map(|n| n > 0).unwrap_or(false) {
+        if header == "\r\n" || header == "\n" {
+            break;
+        }
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => handle_request(state, target),
+        (Some(_), Some(_)) => Response::text(405, "only GET is supported"),
+        _ => Response::text(400, "malformed request"),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    );
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&response.body);
+}
+
+/// Options for [`serve`]
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    /// Address to bind, e.g. `127.0.0.1:8080`
+    pub addr: String,
+    /// Source files or directories to serve
+    pub sources: Vec<PathBuf>,
+    /// Watch sources and reload on change
+    pub watch: bool,
+    /// Project `[limits]`; larger objects are skipped with a warning
+    pub limits: LimitsConfig,
+}
+
+/// Run the preview server until the process is terminated.
+pub fn serve(options: ServeOptions) -> Result<(), ServeError> {
+    let listener = TcpListener::bind(&options.addr)
+        .map_err(|source| ServeError::Bind { addr: options.addr.clone(), source })?;
+    let state = Arc::new(ServeState::with_limits(options.sources.clone(), options.limits.clone()));
+
+    // Keep the debouncer alive for the lifetime of the server
+    let _debouncer = if !options.watch {
+        None
+    } else {
+        let watch_state = Arc::clone(&state);
+        let mut debouncer = new_debouncer(
+            Duration::from_millis(100),
+            move |res: notify_debouncer_mini::DebounceEventResult| {
+                if let Ok(events) = res {
+                    if events.iter().any(|e| is_pixelsrc_file(&e.path)) {
+                        watch_state.reload();
+                        println!("Reloaded (version {})", watch_state.version());
+                    }
+                }
+            },
+        )?;
+        for path in &options.sources {
+            debouncer.watcher().watch(path, RecursiveMode::Recursive)?;
+        }
+        Some(debouncer)
+    };
+
+    // Accepted connections queue up for a fixed set of workers
+    let (sender, receiver) = sync_channel::<TcpStream>(WORKERS);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let (state, receiver) = (Arc::clone(&state), Arc::clone(&receiver));
+        std::thread::spawn(move || loop {
+            let next = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => return,
+            };
+            match next {
+                Ok(stream) => handle_connection(&state, stream),
+                Err(_) => return,
+            }
+        });
+    }
+
+    for stream in listener.incoming().flatten() {
+        if sender.send(stream).is_err() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "red", "size": [2, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+{"type": "sprite", "name": "blue", "size": [2, 2], "palette": "p", "regions": {"b": {"rect": [0, 0, 2, 2]}}}
+{"type": "composition", "name": "scene", "size": [4, 2], "sprites": {"R": "red", "B": "blue"}, "cell_size": [2, 2], "layers": [{"map": ["RB"]}]}
+{"type": "animation", "name": "flash", "frames": ["red", "blue"], "duration": 100}"##;
+
+    fn state() -> ServeState {
+        ServeState::from_catalog(PreviewCatalog::from_source(SOURCE))
+    }
+
+    fn decode(response: &Response) -> RgbaImage {
+        image::load_from_memory(&response.body).unwrap().to_rgba8()
+    }
+
+    #[test]
+    fn test_sprite_route_with_scale() {
+        let response = handle_request(&state(), "/sprite/red.png?scale=3");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "image/png");
+        let image = decode(&response);
+        assert_eq!(image.dimensions(), (6, 6));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_crop_query() {
+        let response = handle_request(&state(), "/composition/scene.png?crop=1,0,2,2&scale=2");
+        assert_eq!(response.status, 200);
+        let image = decode(&response);
+        assert_eq!(image.dimensions(), (4, 4));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 0).0, [0, 0, 255, 255]);
+
+        // Clipped to the sprite, however large the request
+        let response = handle_request(&state(), "/sprite/red.png?crop=1%2C1%2C4%2C4");
+        let image = decode(&response);
+        assert_eq!(image.dimensions(), (1, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        let response = handle_request(&state(), "/composition/scene.png?crop=0,0,4000000000,1");
+        assert_eq!(response.status, 200);
+
+        for target in [
+            "/sprite/red.png?crop=2,0,1,1",
+            "/composition/scene.png?crop=0,9,1,1",
+            "/sprite/red.png?crop=1,1,4294967295,1",
+            "/sprite/red.png?crop=1,1",
+        ] {
+            assert_eq!(handle_request(&state(), target).status, 400, "{}", target);
+        }
+        assert_eq!(handle_request(&state(), "/sprite/missing.png?crop=0,0,1,1").status, 404);
+        assert_eq!(handle_request(&state(), "/anim/flash.gif?crop=0,0,1,1").status, 400);
+    }
+
+    #[test]
+    fn test_composition_route() {
+        let response = handle_request(&state(), "/composition/scene.png");
+        assert_eq!(response.status, 200);
+        let image = decode(&response);
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.get_pixel(3, 0).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_animation_routes() {
+        let gif = handle_request(&state(), "/anim/flash.gif");
+        assert_eq!(gif.status, 200);
+        assert_eq!(&gif.body[0..6], b"GIF89a");
+
+        let sheet = handle_request(&state(), "/anim/flash.png");
+        assert_eq!(decode(&sheet).dimensions(), (4, 2));
+    }
+
+    #[test]
+    fn test_index_lists_objects_and_polls_version() {
+        let response = handle_request(&state(), "/");
+        let html = String::from_utf8(response.body).unwrap();
+        assert!(html.contains("/sprite/red.png"));
+        assert!(html.contains("/composition/scene.png"));
+        assert!(html.contains("/anim/flash.gif"));
+        assert!(html.contains("/__version"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(handle_request(&state(), "/sprite/missing.png").status, 404);
+        assert_eq!(handle_request(&state(), "/sprite/red.png?scale=0").status, 400);
+        assert_eq!(handle_request(&state(), "/nope").status, 404);
+    }
+
+    #[test]
+    fn test_reload_bumps_version() {
+        let state = state();
+        let before = state.version();
+        state.reload();
+        assert_eq!(state.version(), before + 1);
+        assert_eq!(handle_request(&state, "/__version").body, (before + 1).to_string().as_bytes());
+    }
+
+    #[test]
+    fn test_oversized_objects_are_skipped() {
+        let catalog = PreviewCatalog::from_source(
+            r##"{"type": "sprite", "name": "huge", "size": [100000, 100000], "palette": {"x": "#FF0000"}, "regions": {}}"##,
+        );
+        assert!(catalog.sprite_names().is_empty());
+        assert!(catalog.warnings[0].contains("limits.max_size"));
+    }
+
+    #[test]
+    fn test_reference_underlay() {
+        let dir = tempfile::tempdir().unwrap();
+        RgbaImage::from_pixel(1, 1, image::Rgba([0, 255, 0, 255]))
+            .save(dir.path().join("ref.png"))
+            .unwrap();
+        let source = r##"{"type": "sprite", "name": "dot", "size": [2, 1], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]]}}, "reference": {"path": "ref.png", "opacity": 0.5}}
+{"type": "sprite", "name": "lost", "size": [1, 1], "palette": {}, "reference": {"path": "gone.png"}}"##;
+        let file = dir.path().join("dot.pxl");
+        std::fs::write(&file, source).unwrap();
+        let state = ServeState::new(vec![file]);
+
+        let response = handle_request(&state, "/sprite/dot.png?reference=1&scale=2");
+        let image = decode(&response);
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 1).0, [0, 255, 0, 128]);
+
+        // Plain renders never include the reference
+        let image = decode(&handle_request(&state, "/sprite/dot.png"));
+        assert_eq!(image.get_pixel(1, 0).0[3], 0);
+
+        let html = String::from_utf8(handle_request(&state, "/").body).unwrap();
+        assert!(html.contains("/sprite/dot.png?reference=1&scale=4"));
+        assert!(html.contains("reference image 'gone.png' not found"));
+    }
+
+    #[test]
+    fn test_overlay_query() {
+        let response = handle_request(&state(), "/composition/scene.png?overlay=cells&scale=2");
+        let image = decode(&response);
+        // Cell boundary between the two 2x2 cells, at output column 4
+        assert_eq!(image.get_pixel(4, 0).0, [0, 192, 255, 255]);
+        assert_eq!(image.get_pixel(3, 0).0, [255, 0, 0, 255]);
+
+        let response = handle_request(&state(), "/sprite/red.png?overlay=grid%2Corigin&scale=2");
+        assert_eq!(response.status, 200);
+        assert_eq!(handle_request(&state(), "/sprite/red.png?overlay=axes").status, 400);
+        assert_eq!(handle_request(&state(), "/anim/flash.gif?overlay=grid").status, 400);
+    }
+
+    #[test]
+    fn test_project_limits_apply_on_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("dots.pxl");
+        std::fs::write(&file, SOURCE).unwrap();
+        let limits = LimitsConfig { max_size: [2, 2], ..LimitsConfig::default() };
+        let state = ServeState::with_limits(vec![file], limits);
+
+        let check = |state: &ServeState| {
+            let catalog = state.catalog.read().unwrap();
+            assert_eq!(catalog.sprite_names(), ["blue", "red"]);
+            assert!(catalog.composition_names().is_empty(), "4x2 scene is over the limit");
+            assert!(catalog.warnings.iter().any(|w| w.contains("limits.max_size")));
+        };
+        check(&state);
+        state.reload();
+        check(&state);
+    }
+
+    #[test]
+    fn test_index_links_encode_names() {
+        let source = r##"{"type": "sprite", "name": "a b?c#d%e/f&g", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}"##;
+        let state = ServeState::from_catalog(PreviewCatalog::from_source(source));
+        let html = String::from_utf8(handle_request(&state, "/").body).unwrap();
+        let link = "/sprite/a%20b%3Fc%23d%25e%2Ff%26g.png";
+        assert!(html.contains(&format!("src=\"{}?reference=1&scale=4", link)), "{}", html);
+        assert!(html.contains("<figcaption>a b?c#d%e/f&amp;g</figcaption>"));
+        assert_eq!(handle_request(&state, link).status, 200);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("hero%20idle"), "hero idle");
+        assert_eq!(percent_decode("plain"), "plain");
+        assert_eq!(percent_decode("bad%zz"), "bad%zz");
+        assert_eq!(percent_decode("%aé.png"), "%aé.png");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode(&percent_encode("café/?#%")), "café/?#%");
+    }
+}
@@ -5,6 +5,8 @@
 
 use std::collections::HashSet;
 
+use serde::{Deserialize, Serialize};
+
 /// Rasterize a set of points.
 ///
 /// Takes a collection of (x, y) coordinates and returns them as a HashSet.
@@ -310,6 +312,129 @@ pub fn intersect(regions: &[HashSet<(i32, i32)>]) -> HashSet<(i32, i32)> {
     result
 }
 
+/// Find pixels that appear in an odd number of input regions.
+///
+/// With two regions this is the symmetric difference: pixels in either one
+/// but not both.
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::shapes::xor;
+/// use std::collections::HashSet;
+///
+/// let region1: HashSet<(i32, i32)> = [(0, 0), (1, 0), (2, 0)].into_iter().collect();
+/// let region2: HashSet<(i32, i32)> = [(1, 0), (2, 0), (3, 0)].into_iter().collect();
+/// let result = xor(&[region1, region2]);
+/// assert_eq!(result.len(), 2);
+/// assert!(result.contains(&(0, 0)));
+/// assert!(result.contains(&(3, 0)));
+/// ```
+pub fn xor(regions: &[HashSet<(i32, i32)>]) -> HashSet<(i32, i32)> {
+    let mut result = HashSet::new();
+    for region in regions {
+        for pixel in region {
+            if !result.remove(pixel) {
+                result.insert(*pixel);
+            }
+        }
+    }
+    result
+}
+
+/// Grow a region by `radius` pixels in every direction (morphological dilation).
+///
+/// Uses a square neighborhood, so corners grow diagonally like the `outline`
+/// transform.
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::shapes::dilate;
+/// use std::collections::HashSet;
+///
+/// let dot: HashSet<(i32, i32)> = [(5, 5)].into_iter().collect();
+/// let grown = dilate(&dot, 1);
+/// assert_eq!(grown.len(), 9);
+/// assert!(grown.contains(&(4, 4)));
+/// ```
+pub fn dilate(pixels: &HashSet<(i32, i32)>, radius: u32) -> HashSet<(i32, i32)> {
+    let r = radius as i32;
+    // A square neighborhood is separable: grow along rows, then along columns
+    let rows: HashSet<(i32, i32)> =
+        pixels.iter().flat_map(|&(x, y)| (-r..=r).map(move |d| (x + d, y))).collect();
+    rows.iter().flat_map(|&(x, y)| (-r..=r).map(move |d| (x, y + d))).collect()
+}
+
+/// Shrink a region by `radius` pixels from every edge (morphological erosion).
+///
+/// A pixel survives only if its whole square neighborhood is in the region.
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::shapes::{erode, rasterize_rect};
+///
+/// let shrunk = erode(&rasterize_rect(0, 0, 5, 5), 1);
+/// assert_eq!(shrunk, rasterize_rect(1, 1, 3, 3));
+/// ```
+pub fn erode(pixels: &HashSet<(i32, i32)>, radius: u32) -> HashSet<(i32, i32)> {
+    let r = radius as i32;
+    let rows: HashSet<(i32, i32)> = pixels
+        .iter()
+        .copied()
+        .filter(|&(x, y)| (-r..=r).all(|d| pixels.contains(&(x + d, y))))
+        .collect();
+    rows.iter().copied().filter(|&(x, y)| (-r..=r).all(|d| rows.contains(&(x, y + d)))).collect()
+}
+
+/// Corner style of a generated outline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutlineCorners {
+    /// Diagonal neighbors are outlined too, closing corners (default)
+    #[default]
+    Square,
+    /// Only pixels within `thickness` steps along rows and columns, leaving
+    /// outer corners open for a softer silhouette
+    Rounded,
+}
+
+/// The ring of pixels up to `thickness` away from a region, outside it.
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::shapes::{outline, rasterize_rect, OutlineCorners};
+///
+/// let square = rasterize_rect(1, 1, 2, 2);
+/// assert_eq!(outline(&square, 1, OutlineCorners::Square).len(), 12);
+/// // Without the four diagonal corners
+/// assert_eq!(outline(&square, 1, OutlineCorners::Rounded).len(), 8);
+/// ```
+pub fn outline(
+    pixels: &HashSet<(i32, i32)>,
+    thickness: u32,
+    corners: OutlineCorners,
+) -> HashSet<(i32, i32)> {
+    let grown = match corners {
+        OutlineCorners::Square => dilate(pixels, thickness),
+        OutlineCorners::Rounded => {
+            let t = thickness as i32;
+            pixels
+                .iter()
+                .flat_map(|&(x, y)| {
+                    (-t..=t).flat_map(move |dy| {
+                        let w = t - dy.abs();
+                        (-w..=w).map(move |dx| (x + dx, y + dy))
+                    })
+                })
+                .collect()
+        }
+    };
+    grown.into_iter().filter(|p| !pixels.contains(p)).collect()
+}
+
 // ============================================================================
 // Fill Operations
 // ============================================================================
@@ -603,6 +728,101 @@ pub fn rasterize_polygon(vertices: &[(i32, i32)]) -> HashSet<(i32, i32)> {
     pixels
 }
 
+/// Fill rule deciding which parts of a path are inside.
+///
+/// The rules only differ for self-intersecting paths and overlapping subpaths.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillRule {
+    /// Inside where the winding number is non-zero (SVG default)
+    #[default]
+    NonZero,
+    /// Inside where a ray crosses an odd number of edges, so overlaps cut holes
+    EvenOdd,
+}
+
+/// Rasterize filled path contours with sub-pixel edge positions.
+///
+/// Each contour is implicitly closed. A pixel is filled when its integer
+/// coordinate lies inside the path under `rule` or on one of its edges, so
+/// integer-aligned paths cover the same pixels as [`rasterize_polygon`].
+///
+/// # Examples
+///
+/// ```
+/// use pixelsrc::shapes::{rasterize_path, FillRule};
+///
+/// // An outer square with a same-direction inner square
+/// let outer = vec![[0.0, 0.0], [8.0, 0.0], [8.0, 8.0], [0.0, 8.0]];
+/// let inner = vec![[2.0, 2.0], [6.0, 2.0], [6.0, 6.0], [2.0, 6.0]];
+/// let contours = [outer, inner];
+///
+/// assert!(rasterize_path(&contours, FillRule::NonZero).contains(&(4, 4)));
+/// assert!(!rasterize_path(&contours, FillRule::EvenOdd).contains(&(4, 4)));
+/// ```
+pub fn rasterize_path(contours: &[Vec<[f32; 2]>], rule: FillRule) -> HashSet<(i32, i32)> {
+    let mut pixels = HashSet::new();
+    let round = |p: [f32; 2]| (p[0].round() as i32, p[1].round() as i32);
+
+    let mut edges: Vec<([f64; 2], [f64; 2])> = Vec::new();
+    for contour in contours {
+        if contour.len() == 1 {
+            pixels.insert(round(contour[0]));
+        }
+        for (i, &a) in contour.iter().enumerate() {
+            let b = contour[(i + 1) % contour.len()];
+            // Edges are always part of the shape, like polygon boundaries
+            pixels.extend(rasterize_line(round(a), round(b)));
+            edges.push(([a[0] as f64, a[1] as f64], [b[0] as f64, b[1] as f64]));
+        }
+    }
+    if edges.is_empty() {
+        return pixels;
+    }
+
+    let min_y = edges.iter().map(|(a, b)| a[1].min(b[1])).fold(f64::INFINITY, f64::min);
+    let max_y = edges.iter().map(|(a, b)| a[1].max(b[1])).fold(f64::NEG_INFINITY, f64::max);
+
+    // Scanline fill at integer rows, tracking edge direction for the winding number
+    for y in min_y.ceil() as i32..=max_y.floor() as i32 {
+        let yf = y as f64;
+        let mut crossings: Vec<(f64, i32)> = edges
+            .iter()
+            .filter_map(|&(a, b)| {
+                // Half-open span so shared vertices are counted once
+                let direction = if a[1] <= yf && b[1] > yf {
+                    1
+                } else if b[1] <= yf && a[1] > yf {
+                    -1
+                } else {
+                    return None;
+                };
+                Some((a[0] + (yf - a[1]) * (b[0] - a[0]) / (b[1] - a[1]), direction))
+            })
+            .collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            winding += match rule {
+                FillRule::NonZero => pair[0].1,
+                FillRule::EvenOdd => 1,
+            };
+            let inside = match rule {
+                FillRule::NonZero => winding != 0,
+                FillRule::EvenOdd => winding % 2 == 1,
+            };
+            if inside {
+                for x in pair[0].0.ceil() as i32..=pair[1].0.floor() as i32 {
+                    pixels.insert((x, y));
+                }
+            }
+        }
+    }
+
+    pixels
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1096,6 +1316,40 @@ mod tests {
         assert!(result.contains(&(2, 0)));
     }
 
+    #[test]
+    fn test_xor_basic() {
+        let region1: HashSet<(i32, i32)> = [(0, 0), (1, 0), (2, 0)].into_iter().collect();
+        let region2: HashSet<(i32, i32)> = [(1, 0), (2, 0), (3, 0)].into_iter().collect();
+        let result = xor(&[region1, region2]);
+        assert_eq!(result, [(0, 0), (3, 0)].into_iter().collect());
+    }
+
+    #[test]
+    fn test_xor_odd_coverage() {
+        // A pixel in all three regions is covered an odd number of times
+        let region1: HashSet<(i32, i32)> = [(0, 0), (1, 0)].into_iter().collect();
+        let region2: HashSet<(i32, i32)> = [(1, 0), (2, 0)].into_iter().collect();
+        let region3: HashSet<(i32, i32)> = [(1, 0), (2, 0)].into_iter().collect();
+        let result = xor(&[region1, region2, region3]);
+        assert_eq!(result, [(0, 0), (1, 0)].into_iter().collect());
+        assert!(xor(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_dilate_square_neighborhood() {
+        let rect = rasterize_rect(2, 2, 2, 1);
+        assert_eq!(dilate(&rect, 2), rasterize_rect(0, 0, 6, 5));
+        assert_eq!(dilate(&rect, 0), rect);
+    }
+
+    #[test]
+    fn test_erode_shrinks_and_removes_thin_parts() {
+        let rect = rasterize_rect(0, 0, 6, 5);
+        assert_eq!(erode(&rect, 2), rasterize_rect(2, 2, 2, 1));
+        assert!(erode(&rasterize_line((0, 0), (8, 0)), 1).is_empty());
+        assert_eq!(erode(&dilate(&rect, 3), 3), rect);
+    }
+
     // ========================================================================
     // Flood Fill Tests
     // ========================================================================
@@ -1276,4 +1530,48 @@ mod tests {
         // Should not reach outside
         assert!(!filled.contains(&(5, 5)));
     }
+
+    #[test]
+    fn test_rasterize_path_matches_polygon_on_integer_vertices() {
+        let square = vec![[0.0, 0.0], [5.0, 0.0], [5.0, 5.0], [0.0, 5.0]];
+        assert_eq!(
+            rasterize_path(&[square], FillRule::NonZero),
+            rasterize_polygon(&[(0, 0), (5, 0), (5, 5), (0, 5)])
+        );
+    }
+
+    #[test]
+    fn test_rasterize_path_fill_rules() {
+        let outer = vec![[0.0, 0.0], [8.0, 0.0], [8.0, 8.0], [0.0, 8.0]];
+        let same_way = vec![[2.0, 2.0], [6.0, 2.0], [6.0, 6.0], [2.0, 6.0]];
+        let reversed: Vec<[f32; 2]> = same_way.iter().rev().copied().collect();
+
+        let nonzero = rasterize_path(&[outer.clone(), same_way.clone()], FillRule::NonZero);
+        assert!(nonzero.contains(&(4, 4)));
+        let evenodd = rasterize_path(&[outer.clone(), same_way], FillRule::EvenOdd);
+        assert!(!evenodd.contains(&(4, 4)));
+        assert!(evenodd.contains(&(1, 4)));
+        // Inner edges stay filled
+        assert!(evenodd.contains(&(2, 4)));
+
+        // A reversed inner contour is a hole under both rules
+        let hole = rasterize_path(&[outer, reversed], FillRule::NonZero);
+        assert!(!hole.contains(&(4, 4)));
+    }
+
+    #[test]
+    fn test_rasterize_path_sub_pixel_edges() {
+        // Triangle with fractional vertices fills only pixels whose coordinates are inside
+        let triangle = vec![[0.5, 0.5], [6.5, 0.5], [0.5, 6.5]];
+        let pixels = rasterize_path(&[triangle], FillRule::NonZero);
+        assert!(pixels.contains(&(1, 1)));
+        assert!(pixels.contains(&(3, 3)));
+        assert!(!pixels.contains(&(5, 5)));
+    }
+
+    #[test]
+    fn test_rasterize_path_single_point_and_empty() {
+        assert_eq!(rasterize_path(&[vec![[2.4, 3.6]]], FillRule::EvenOdd), [(2, 4)].into());
+        assert!(rasterize_path(&[], FillRule::NonZero).is_empty());
+    }
 }
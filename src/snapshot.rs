@@ -0,0 +1,375 @@
+//! Snapshot (approval) testing for whole projects
+//!
+//! `pxl snapshot write` renders every sprite, variant, composition, animation
+//! and sequence in a project and records each one's dimensions and a hash of
+//! its pixels in a JSON file; `pxl snapshot check` renders them again and
+//! reports anything that changed:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "objects": {
+//!     "animation/walk": {"width": 16, "height": 16, "frames": 4, "hash": "9f1c..."},
+//!     "sprite/hero": {"width": 16, "height": 16, "hash": "03ab..."}
+//!   }
+//! }
+//! ```
+//!
+//! Hashes are FNV-1a over raw RGBA pixels, so they are stable across
+//! platforms. Baseline images can be saved alongside the snapshot, letting
+//! `check` count changed pixels and write diff images.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::build::manifest::fnv1a_hash;
+use crate::serve::PreviewCatalog;
+use crate::spritesheet::render_spritesheet;
+
+/// Current snapshot file format version.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Snapshot file used when none is given.
+pub const DEFAULT_SNAPSHOT: &str = "pxl-snapshot.json";
+
+/// Recorded state of one rendered object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectSnapshot {
+    pub width: u32,
+    pub height: u32,
+    /// Frame count, for animations and sequences
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub frames: Option<usize>,
+    /// FNV-1a hash of every frame's dimensions and RGBA pixels, as hex
+    pub hash: String,
+}
+
+impl ObjectSnapshot {
+    /// Record a still image.
+    pub fn of_image(image: &RgbaImage) -> Self {
+        Self::of_frames(std::slice::from_ref(image), None)
+    }
+
+    /// Record animation frames; dimensions are the first frame's.
+    pub fn of_animation(frames: &[RgbaImage]) -> Self {
+        Self::of_frames(frames, Some(frames.len()))
+    }
+
+    fn of_frames(frames: &[RgbaImage], count: Option<usize>) -> Self {
+        let mut data = Vec::new();
+        for frame in frames {
+            data.extend_from_slice(&frame.width().to_le_bytes());
+            data.extend_from_slice(&frame.height().to_le_bytes());
+            data.extend_from_slice(frame.as_raw());
+        }
+        let (width, height) = frames.first().map(|f| f.dimensions()).unwrap_or_default();
+        Self { width, height, frames: count, hash: format!("{:016x}", fnv1a_hash(&data)) }
+    }
+}
+
+/// Every object's recorded state, keyed by `kind/name`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub objects: BTreeMap<String, ObjectSnapshot>,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self { version: SNAPSHOT_VERSION, objects: BTreeMap::new() }
+    }
+}
+
+impl Snapshot {
+    /// Read a snapshot file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read snapshot '{}': {}", path.display(), e))?;
+        let snapshot: Snapshot = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid snapshot '{}': {}", path.display(), e))?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(format!(
+                "Snapshot '{}' has version {}, newer than supported ({})",
+                path.display(),
+                snapshot.version,
+                SNAPSHOT_VERSION
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot as pretty JSON with a trailing newline.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        json.push('\n');
+        std::fs::write(path, json)
+            .map_err(|e| format!("Cannot write snapshot '{}': {}", path.display(), e))
+    }
+}
+
+/// One rendered object: a still image, or animation frames
+#[derive(Debug, Clone)]
+pub struct RenderedObject {
+    /// `kind/name`, e.g. `sprite/hero`
+    pub key: String,
+    pub frames: Vec<RgbaImage>,
+    pub animated: bool,
+}
+
+impl RenderedObject {
+    pub fn snapshot(&self) -> ObjectSnapshot {
+        if self.animated {
+            ObjectSnapshot::of_animation(&self.frames)
+        } else {
+            ObjectSnapshot::of_image(&self.frames[0])
+        }
+    }
+
+    /// The object as one image: animation frames side by side.
+    pub fn image(&self) -> RgbaImage {
+        if self.animated {
+            render_spritesheet(&self.frames, None)
+        } else {
+            self.frames[0].clone()
+        }
+    }
+}
+
+/// Render every object in a catalog, in key order.
+///
+/// Objects that fail to render are returned as `(key, error)` pairs.
+pub fn render_all(catalog: &PreviewCatalog) -> (Vec<RenderedObject>, Vec<(String, String)>) {
+    let mut rendered = Vec::new();
+    let mut errors = Vec::new();
+    let stills = catalog
+        .sprite_names()
+        .into_iter()
+        .map(|name| (format!("sprite/{}", name), catalog.render_sprite(name)))
+        .chain(
+            catalog
+                .composition_names()
+                .into_iter()
+                .map(|name| (format!("composition/{}", name), catalog.render_composition(name))),
+        );
+    for (key, result) in stills {
+        match result {
+            Ok(image) => {
+                rendered.push(RenderedObject { key, frames: vec![image], animated: false })
+            }
+            Err(e) => errors.push((key, e)),
+        }
+    }
+    for name in catalog.animation_names() {
+        let key = format!("animation/{}", name);
+        match catalog.render_animation(name) {
+            Ok((frames, _, _)) => rendered.push(RenderedObject { key, frames, animated: true }),
+            Err(e) => errors.push((key, e)),
+        }
+    }
+    rendered.sort_by(|a, b| a.key.cmp(&b.key));
+    (rendered, errors)
+}
+
+/// Snapshot of rendered objects.
+pub fn snapshot_of(objects: &[RenderedObject]) -> Snapshot {
+    let objects = objects.iter().map(|o| (o.key.clone(), o.snapshot())).collect();
+    Snapshot { objects, ..Default::default() }
+}
+
+/// How an object differs from its snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Not in the snapshot
+    Added,
+    /// In the snapshot but no longer rendered
+    Removed,
+    /// Dimensions changed
+    Resized { old: (u32, u32), new: (u32, u32) },
+    /// Frame count changed
+    Frames { old: usize, new: usize },
+    /// Same dimensions, different pixels
+    Pixels,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added => write!(f, "new, not in snapshot"),
+            Change::Removed => write!(f, "missing, in snapshot but not rendered"),
+            Change::Resized { old, new } => {
+                write!(f, "size changed from {}x{} to {}x{}", old.0, old.1, new.0, new.1)
+            }
+            Change::Frames { old, new } => {
+                write!(f, "frame count changed from {} to {}", old, new)
+            }
+            Change::Pixels => write!(f, "pixels changed"),
+        }
+    }
+}
+
+/// Compare a fresh snapshot against the expected one, in key order.
+pub fn compare(expected: &Snapshot, actual: &Snapshot) -> Vec<(String, Change)> {
+    let mut changes = Vec::new();
+    for (key, old) in &expected.objects {
+        let Some(new) = actual.objects.get(key) else {
+            changes.push((key.clone(), Change::Removed));
+            continue;
+        };
+        let change = if (old.width, old.height) != (new.width, new.height) {
+            Change::Resized { old: (old.width, old.height), new: (new.width, new.height) }
+        } else if old.frames != new.frames {
+            Change::Frames { old: old.frames.unwrap_or(1), new: new.frames.unwrap_or(1) }
+        } else if old.hash != new.hash {
+            Change::Pixels
+        } else {
+            continue;
+        };
+        changes.push((key.clone(), change));
+    }
+    for key in actual.objects.keys().filter(|k| !expected.objects.contains_key(*k)) {
+        changes.push((key.clone(), Change::Added));
+    }
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    changes
+}
+
+/// Path of an object's image under `dir`: `dir/<kind>/<name>.png`.
+pub fn image_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.png", key))
+}
+
+/// Pixels that differ between two images of the same size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelDiff {
+    /// Number of differing pixels
+    pub count: usize,
+    /// Bounding box `(x, y, width, height)` of the differing pixels
+    pub bounds: (u32, u32, u32, u32),
+}
+
+impl fmt::Display for PixelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x, y, w, h) = self.bounds;
+        let plural = if self.count == 1 { "" } else { "s" };
+        write!(f, "{} pixel{} differ within {}x{} at ({}, {})", self.count, plural, w, h, x, y)
+    }
+}
+
+/// Count the pixels that differ between two same-sized images.
+///
+/// Returns `None` when the sizes differ or nothing changed.
+pub fn pixel_diff(old: &RgbaImage, new: &RgbaImage) -> Option<PixelDiff> {
+    if old.dimensions() != new.dimensions() {
+        return None;
+    }
+    let (mut min, mut max, mut count) = ((u32::MAX, u32::MAX), (0, 0), 0);
+    for (x, y, pixel) in new.enumerate_pixels() {
+        if pixel != old.get_pixel(x, y) {
+            count += 1;
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+    (count > 0)
+        .then(|| PixelDiff { count, bounds: (min.0, min.1, max.0 - min.0 + 1, max.1 - min.1 + 1) })
+}
+
+/// Side-by-side diff image: the old image, the new one, and a mask with
+/// changed pixels in red over a faded copy of the new image.
+///
+/// Panels are separated by a one-pixel gap; each is as large as the larger
+/// of the two images.
+pub fn diff_image(old: &RgbaImage, new: &RgbaImage) -> RgbaImage {
+    let width = old.width().max(new.width());
+    let height = old.height().max(new.height());
+    let mut mask = RgbaImage::new(width, height);
+    for (x, y, out) in mask.enumerate_pixels_mut() {
+        let before = (x < old.width() && y < old.height()).then(|| *old.get_pixel(x, y));
+        let after = (x < new.width() && y < new.height()).then(|| *new.get_pixel(x, y));
+        *out = if before != after {
+            Rgba([255, 0, 0, 255])
+        } else {
+            let mut faded = after.unwrap_or(Rgba([0, 0, 0, 0]));
+            faded[3] /= 4;
+            faded
+        };
+    }
+
+    let mut result = RgbaImage::new(width * 3 + 2, height);
+    image::imageops::replace(&mut result, old, 0, 0);
+    image::imageops::replace(&mut result, new, (width + 1) as i64, 0);
+    image::imageops::replace(&mut result, &mask, (width * 2 + 2) as i64, 0);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(w: u32, h: u32, color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(w, h, Rgba(color))
+    }
+
+    #[test]
+    fn test_compare() {
+        let red = image(2, 2, [255, 0, 0, 255]);
+        let mut expected = Snapshot::default();
+        expected.objects.insert("sprite/a".into(), ObjectSnapshot::of_image(&red));
+        expected.objects.insert("sprite/b".into(), ObjectSnapshot::of_image(&red));
+        expected.objects.insert("sprite/c".into(), ObjectSnapshot::of_image(&red));
+        expected.objects.insert(
+            "animation/walk".into(),
+            ObjectSnapshot::of_animation(std::slice::from_ref(&red)),
+        );
+
+        let mut actual = Snapshot::default();
+        actual.objects.insert("sprite/a".into(), ObjectSnapshot::of_image(&red));
+        actual.objects.insert("sprite/b".into(), ObjectSnapshot::of_image(&image(3, 2, [0; 4])));
+        actual.objects.insert("sprite/d".into(), ObjectSnapshot::of_image(&red));
+        let frames = [red.clone(), red.clone()];
+        actual.objects.insert("animation/walk".into(), ObjectSnapshot::of_animation(&frames));
+
+        assert_eq!(
+            compare(&expected, &actual),
+            vec![
+                ("animation/walk".to_string(), Change::Frames { old: 1, new: 2 }),
+                ("sprite/b".to_string(), Change::Resized { old: (2, 2), new: (3, 2) }),
+                ("sprite/c".to_string(), Change::Removed),
+                ("sprite/d".to_string(), Change::Added),
+            ]
+        );
+        assert!(compare(&expected, &expected).is_empty());
+    }
+
+    #[test]
+    fn test_hash_depends_on_pixels() {
+        let a = ObjectSnapshot::of_image(&image(2, 2, [255, 0, 0, 255]));
+        let b = ObjectSnapshot::of_image(&image(2, 2, [255, 0, 1, 255]));
+        assert_eq!(a, ObjectSnapshot::of_image(&image(2, 2, [255, 0, 0, 255])));
+        assert_ne!(a.hash, b.hash);
+        assert_eq!((a.width, a.height, a.frames), (2, 2, None));
+    }
+
+    #[test]
+    fn test_pixel_diff_and_image() {
+        let old = image(4, 3, [0, 0, 255, 255]);
+        let mut new = old.clone();
+        new.put_pixel(1, 1, Rgba([0, 255, 0, 255]));
+        new.put_pixel(2, 2, Rgba([0, 255, 0, 255]));
+
+        let diff = pixel_diff(&old, &new).unwrap();
+        assert_eq!(diff, PixelDiff { count: 2, bounds: (1, 1, 2, 2) });
+        assert_eq!(diff.to_string(), "2 pixels differ within 2x2 at (1, 1)");
+        assert_eq!(pixel_diff(&old, &old), None);
+
+        let image = diff_image(&old, &new);
+        assert_eq!(image.dimensions(), (14, 3));
+        assert_eq!(*image.get_pixel(5 + 1, 1), Rgba([0, 255, 0, 255]));
+        assert_eq!(*image.get_pixel(10 + 1, 1), Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(10, 0)[3], 63);
+    }
+}
@@ -1,4 +1,7 @@
 //! Spritesheet rendering - combines multiple frames into a grid layout
+//!
+//! Frames of different sizes can first be lined up on their pivots with
+//! [`pivot_layout`] and [`align_frames`].
 
 use image::{Rgba, RgbaImage};
 
@@ -77,6 +80,72 @@ pub fn render_spritesheet(frames: &[RgbaImage], cols: Option<u32>) -> RgbaImage
     sheet
 }
 
+/// A shared canvas that lines frames up on their pivots
+#[derive(Debug, Clone, PartialEq)]
+pub struct PivotLayout {
+    /// Size of the shared canvas
+    pub size: (u32, u32),
+    /// Where every frame's pivot lands on the canvas
+    pub pivot: [i32; 2],
+    /// Top-left position of each frame on the canvas
+    pub offsets: Vec<[u32; 2]>,
+}
+
+impl PivotLayout {
+    /// Whether every frame already fills the canvas, so alignment is a no-op
+    pub fn is_identity(&self, sizes: &[(u32, u32)]) -> bool {
+        self.offsets.iter().all(|o| *o == [0, 0]) && sizes.iter().all(|s| *s == self.size)
+    }
+}
+
+/// Compute a canvas that keeps each frame's pivot at the same spot.
+///
+/// `pivots` are per-frame points (usually a sprite's `metadata.origin`) in
+/// the frame's own pixel coordinates. Frames without a pivot are aligned by
+/// their top-left corner. Returns `None` when no frame has a pivot, which
+/// leaves the usual top-left padding in place.
+pub fn pivot_layout(sizes: &[(u32, u32)], pivots: &[Option<[i32; 2]>]) -> Option<PivotLayout> {
+    if sizes.is_empty() || pivots.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let pivot_of = |i: usize| pivots.get(i).copied().flatten().unwrap_or([0, 0]);
+    let (mut left, mut top, mut right, mut bottom) = (i32::MIN, i32::MIN, i32::MIN, i32::MIN);
+    for (i, &(w, h)) in sizes.iter().enumerate() {
+        let [px, py] = pivot_of(i);
+        left = left.max(px);
+        top = top.max(py);
+        right = right.max(w as i32 - px);
+        bottom = bottom.max(h as i32 - py);
+    }
+
+    let offsets = (0..sizes.len())
+        .map(|i| {
+            let [px, py] = pivot_of(i);
+            [(left - px) as u32, (top - py) as u32]
+        })
+        .collect();
+    Some(PivotLayout {
+        size: ((left + right) as u32, (top + bottom) as u32),
+        pivot: [left, top],
+        offsets,
+    })
+}
+
+/// Place frames on a shared canvas so their pivots line up.
+pub fn align_frames(frames: Vec<RgbaImage>, layout: &PivotLayout) -> Vec<RgbaImage> {
+    let (width, height) = layout.size;
+    frames
+        .into_iter()
+        .zip(&layout.offsets)
+        .map(|(frame, [x, y])| {
+            let mut canvas = RgbaImage::from_pixel(width, height, TRANSPARENT);
+            image::imageops::replace(&mut canvas, &frame, *x as i64, *y as i64);
+            canvas
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,4 +334,26 @@ mod tests {
         assert_eq!(*sheet.get_pixel(0, 2), green);
         assert_eq!(*sheet.get_pixel(0, 4), blue);
     }
+
+    #[test]
+    fn test_pivot_layout_keeps_feet_planted() {
+        // A tall idle frame and a short crouch frame, both with feet at the bottom center
+        let sizes = [(4, 8), (6, 4)];
+        let layout = pivot_layout(&sizes, &[Some([2, 8]), Some([3, 4])]).unwrap();
+        assert_eq!(layout.size, (6, 8));
+        assert_eq!(layout.pivot, [3, 8]);
+        assert_eq!(layout.offsets, vec![[1, 0], [0, 4]]);
+
+        let red = Rgba([255, 0, 0, 255]);
+        let frames = vec![make_solid_frame(4, 8, red), make_solid_frame(6, 4, red)];
+        let aligned = align_frames(frames, &layout);
+        assert_eq!(aligned[0].dimensions(), (6, 8));
+        assert_eq!(*aligned[0].get_pixel(0, 7), TRANSPARENT);
+        assert_eq!(*aligned[1].get_pixel(0, 7), red);
+        assert_eq!(*aligned[1].get_pixel(0, 3), TRANSPARENT);
+
+        assert_eq!(pivot_layout(&sizes, &[None, None]), None);
+        let same = pivot_layout(&[(4, 4), (4, 4)], &[Some([2, 4]), Some([2, 4])]).unwrap();
+        assert!(same.is_identity(&[(4, 4), (4, 4)]));
+    }
 }
@@ -0,0 +1,240 @@
+//! Stamps: reusable pixel patterns placed inside sprites
+//!
+//! A stamp is a small grid defined once and drawn into a sprite's regions as
+//! often as needed, each placement optionally flipped and rotated:
+//!
+//! ```json5
+//! { type: "stamp", name: "rivet", grid: [".h", "hs"], tokens: { h: "shine", s: "steel" } }
+//! { type: "sprite", name: "plate", size: [16, 16], palette: "metal",
+//!   regions: { steel: { rect: [0, 0, 16, 16] } },
+//!   stamps: [{ name: "rivet", at: [1, 1] }, { name: "rivet", at: [13, 1], flip: "h" }] }
+//! ```
+//!
+//! Stamps are resolved per document, after `extends`, so a stamp may be
+//! defined before or after the sprites that use it. Each placed pixel is
+//! added as a point to the region of its token, which keeps its `z` and
+//! `role`; tokens the sprite doesn't draw with yet get a new region. Pixels
+//! left of or above the canvas are clipped. Unknown stamps and invalid
+//! rotations produce a warning and are skipped.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{Flip, RegionDef, Sprite, Stamp, StampPlacement, TtpObject};
+
+/// Draw the stamps of every sprite in `objects` into its regions.
+///
+/// Returns a warning message for each placement that couldn't be drawn,
+/// with its sprite's index in `objects`.
+pub fn resolve_stamps(objects: &mut [TtpObject]) -> Vec<(usize, String)> {
+    // Later definitions of a name win, as with sprite templates
+    let mut stamps: HashMap<String, Stamp> = HashMap::new();
+    for object in objects.iter() {
+        if let TtpObject::Stamp(stamp) = object {
+            stamps.insert(stamp.name.clone(), stamp.clone());
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (index, object) in objects.iter_mut().enumerate() {
+        let TtpObject::Sprite(sprite) = object else {
+            continue;
+        };
+        for message in apply_stamps(sprite, &stamps) {
+            warnings.push((index, message));
+        }
+    }
+    warnings
+}
+
+/// Draw a sprite's `stamps` into its regions and clear them.
+///
+/// Returns a warning message for each placement that was skipped.
+pub fn apply_stamps(sprite: &mut Sprite, stamps: &HashMap<String, Stamp>) -> Vec<String> {
+    let Some(placements) = sprite.stamps.take() else {
+        return Vec::new();
+    };
+
+    let mut warnings = Vec::new();
+    let mut pixels: BTreeMap<String, Vec<[u32; 2]>> = BTreeMap::new();
+    for placement in &placements {
+        let Some(stamp) = stamps.get(&placement.name) else {
+            warnings.push(format!(
+                "Sprite '{}' places unknown stamp '{}'",
+                sprite.name, placement.name
+            ));
+            continue;
+        };
+        match place(stamp, placement) {
+            Ok(placed) => {
+                for (x, y, token) in placed {
+                    pixels.entry(token.to_string()).or_default().push([x, y]);
+                }
+            }
+            Err(message) => warnings.push(format!("Sprite '{}': {}", sprite.name, message)),
+        }
+    }
+
+    if !pixels.is_empty() {
+        let regions = sprite.regions.get_or_insert_with(HashMap::new);
+        for (token, points) in pixels {
+            merge_points(regions, &token, points);
+        }
+    }
+    warnings
+}
+
+/// The pixels of `stamp` at its placement, as `(x, y, token)`.
+///
+/// Pixels that land left of or above the canvas are dropped.
+pub fn place<'a>(
+    stamp: &'a Stamp,
+    placement: &StampPlacement,
+) -> Result<Vec<(u32, u32, &'a str)>, String> {
+    let turns = match placement.rotate.unwrap_or(0) {
+        0 => 0,
+        90 => 1,
+        180 => 2,
+        270 => 3,
+        other => {
+            return Err(format!(
+                "stamp '{}' rotation {} is not 0, 90, 180 or 270",
+                placement.name, other
+            ))
+        }
+    };
+    let [width, height] = stamp.size();
+    let (flip_x, flip_y) = match placement.flip {
+        None => (false, false),
+        Some(Flip::H) => (true, false),
+        Some(Flip::V) => (false, true),
+        Some(Flip::Hv) => (true, true),
+    };
+
+    let mut placed = Vec::new();
+    for (x, y, token) in stamp.pixels() {
+        let x = if flip_x { width - 1 - x } else { x };
+        let y = if flip_y { height - 1 - y } else { y };
+        // Clockwise quarter turns
+        let (x, y) = match turns {
+            1 => (height - 1 - y, x),
+            2 => (width - 1 - x, height - 1 - y),
+            3 => (y, width - 1 - x),
+            _ => (x, y),
+        };
+        let x = i64::from(placement.at[0]) + i64::from(x);
+        let y = i64::from(placement.at[1]) + i64::from(y);
+        if let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) {
+            placed.push((x, y, token));
+        }
+    }
+    Ok(placed)
+}
+
+/// Add `points` to the token's region, keeping its z-order and role.
+fn merge_points(regions: &mut HashMap<String, RegionDef>, token: &str, points: Vec<[u32; 2]>) {
+    let stamped = RegionDef { points: Some(points), ..Default::default() };
+    match regions.get_mut(token) {
+        Some(existing) => {
+            let mut drawn = std::mem::take(existing);
+            *existing = RegionDef {
+                z: drawn.z.take(),
+                role: drawn.role.take(),
+                union: Some(vec![drawn, stamped]),
+                ..Default::default()
+            };
+        }
+        None => {
+            regions.insert(token.to_string(), stamped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Role;
+
+    fn stamp(grid: &[&str]) -> Stamp {
+        Stamp {
+            name: "arrow".to_string(),
+            grid: grid.iter().map(|row| row.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn placed(stamp: &Stamp, placement: StampPlacement) -> Vec<(u32, u32, &str)> {
+        let mut pixels = place(stamp, &placement).unwrap();
+        pixels.sort();
+        pixels
+    }
+
+    #[test]
+    fn test_place_flip_and_rotate() {
+        // a.
+        // bc
+        let arrow = stamp(&["a.", "bc"]);
+        let at =
+            |x, y| StampPlacement { name: "arrow".to_string(), at: [x, y], ..Default::default() };
+
+        assert_eq!(placed(&arrow, at(3, 5)), [(3, 5, "a"), (3, 6, "b"), (4, 6, "c")]);
+        assert_eq!(
+            placed(&arrow, StampPlacement { flip: Some(Flip::H), ..at(0, 0) }),
+            [(0, 1, "c"), (1, 0, "a"), (1, 1, "b")]
+        );
+        // Clockwise: ba / c.
+        assert_eq!(
+            placed(&arrow, StampPlacement { rotate: Some(90), ..at(0, 0) }),
+            [(0, 0, "b"), (0, 1, "c"), (1, 0, "a")]
+        );
+        assert_eq!(
+            placed(&arrow, StampPlacement { rotate: Some(180), ..at(0, 0) }),
+            placed(&arrow, StampPlacement { flip: Some(Flip::Hv), ..at(0, 0) })
+        );
+        // Flip first, then rotate: .a / cb rotated 270 gives ab / .c
+        assert_eq!(
+            placed(&arrow, StampPlacement { flip: Some(Flip::H), rotate: Some(270), ..at(0, 0) }),
+            [(0, 0, "a"), (1, 0, "b"), (1, 1, "c")]
+        );
+        // Off-canvas pixels are clipped
+        assert_eq!(placed(&arrow, at(-1, 0)), [(0, 1, "c")]);
+
+        let error = place(&arrow, &StampPlacement { rotate: Some(45), ..at(0, 0) }).unwrap_err();
+        assert!(error.contains("rotation 45"), "{}", error);
+    }
+
+    #[test]
+    fn test_tokens_map_grid_characters() {
+        let leaf = Stamp {
+            tokens: HashMap::from([("g".to_string(), "leaf_light".to_string())]),
+            ..stamp(&["g d", ".g."])
+        };
+        assert_eq!(leaf.size(), [3, 2]);
+        let mut pixels = leaf.pixels();
+        pixels.sort();
+        assert_eq!(pixels, [(0, 0, "leaf_light"), (1, 1, "leaf_light"), (2, 0, "d")]);
+    }
+
+    #[test]
+    fn test_resolve_stamps_merges_into_regions() {
+        let objects = crate::parser::parse_stream(std::io::Cursor::new(
+            r##"{"type": "sprite", "name": "tree", "size": [8, 8], "palette": {"g": "#0F0", "t": "#840"}, "regions": {"g": {"rect": [0, 0, 2, 2], "z": 5, "role": "fill"}}, "stamps": [{"name": "leaf", "at": [4, 4]}, {"name": "leaf", "at": [6, 0], "flip": "h"}, {"name": "bush", "at": [0, 0]}]}
+{"type": "stamp", "name": "leaf", "grid": ["g.", "tg"]}"##,
+        ));
+        assert_eq!(objects.warnings.len(), 1);
+        assert!(objects.warnings[0].message.contains("unknown stamp 'bush'"));
+
+        let TtpObject::Sprite(tree) = &objects.objects[0] else { panic!("expected sprite") };
+        assert_eq!(tree.stamps, None);
+        let regions = tree.regions.as_ref().unwrap();
+
+        let leaves = &regions["g"];
+        assert_eq!(leaves.z, Some(5));
+        assert_eq!(leaves.role, Some(Role::Fill));
+        let union = leaves.union.as_ref().unwrap();
+        assert_eq!(union[0].rect, Some([0, 0, 2, 2]));
+        assert_eq!(union[0].z, None);
+        assert_eq!(union[1].points, Some(vec![[4, 4], [5, 5], [7, 0], [6, 1]]));
+
+        assert_eq!(regions["t"].points, Some(vec![[4, 5], [7, 1]]));
+    }
+}
@@ -5,12 +5,15 @@
 //! operations, then rasterized and rendered in z-order.
 
 use crate::color::parse_color;
-use crate::models::{RegionDef, Role};
-use crate::path::parse_path;
-use crate::renderer::Warning;
+use crate::models::{RegionDef, Role, SpriteLayer};
+use crate::modifiers::apply_jitter_range;
+use crate::path::parse_path_contours;
+use crate::renderer::{apply_opacity, Rect, Warning, WarningCategory};
+use crate::rng::object_seed;
 use crate::shapes::{
-    flood_fill, intersect, rasterize_ellipse, rasterize_line, rasterize_points, rasterize_polygon,
-    rasterize_rect, rasterize_stroke, subtract, union,
+    dilate, erode, flood_fill, intersect, outline, rasterize_ellipse, rasterize_line,
+    rasterize_path, rasterize_points, rasterize_polygon, rasterize_rect, rasterize_stroke,
+    subtract, union, xor,
 };
 use image::{Rgba, RgbaImage};
 use std::collections::{HashMap, HashSet};
@@ -97,14 +100,13 @@ pub fn rasterize_region(
             polygon_data.iter().map(|[x, y]| (*x as i32, *y as i32)).collect();
         pixels = rasterize_polygon(&vertices);
     } else if let Some(path_str) = &region.path {
-        match parse_path(path_str) {
-            Ok(vertices) => {
-                let vertices_i32: Vec<(i32, i32)> =
-                    vertices.iter().map(|[x, y]| (*x as i32, *y as i32)).collect();
-                pixels = rasterize_polygon(&vertices_i32);
+        match parse_path_contours(path_str) {
+            Ok(contours) => {
+                pixels = rasterize_path(&contours, region.fill_rule.unwrap_or_default());
             }
             Err(e) => {
-                warnings.push(Warning::new(format!("Invalid path: {}", e)));
+                warnings
+                    .push(Warning::new(WarningCategory::Render, format!("Invalid path: {}", e)));
             }
         }
     } else if let Some(fill_ref) = &region.fill {
@@ -113,13 +115,16 @@ pub fn rasterize_region(
             if let Some(boundary) = all_regions.get(token_name) {
                 pixels = flood_fill(boundary, None, canvas_width, canvas_height);
             } else {
-                warnings.push(Warning::new(format!(
-                    "Unknown token '{}' in fill reference",
-                    token_name
-                )));
+                warnings.push(Warning::new(
+                    WarningCategory::Render,
+                    format!("Unknown token '{}' in fill reference", token_name),
+                ));
             }
         } else {
-            warnings.push(Warning::new(format!("Invalid fill reference: {}", fill_ref)));
+            warnings.push(Warning::new(
+                WarningCategory::Render,
+                format!("Invalid fill reference: {}", fill_ref),
+            ));
         }
     } else if let Some(source_name) = &region.auto_shadow {
         // Generate shadow by offsetting source region's pixels
@@ -129,10 +134,21 @@ pub fn rasterize_region(
                 pixels.insert((x + offset[0], y + offset[1]));
             }
         } else {
-            warnings.push(Warning::new(format!(
-                "Unknown token '{}' in auto-shadow reference",
-                source_name
-            )));
+            warnings.push(Warning::new(
+                WarningCategory::Render,
+                format!("Unknown token '{}' in auto-shadow reference", source_name),
+            ));
+        }
+    } else if let Some(source_name) = &region.auto_outline {
+        // Generate outline around source region's pixels
+        if let Some(source_pixels) = all_regions.get(source_name) {
+            let thickness = region.thickness.unwrap_or(1);
+            pixels = outline(source_pixels, thickness, region.outline_corners.unwrap_or_default());
+        } else {
+            warnings.push(Warning::new(
+                WarningCategory::Render,
+                format!("Unknown token '{}' in auto-outline reference", source_name),
+            ));
         }
     }
     // Handle compound operations
@@ -173,6 +189,22 @@ pub fn rasterize_region(
             sub_regions.push(sub_pixels);
         }
         pixels = intersect(&sub_regions);
+    } else if let Some(xor_regions) = &region.xor {
+        let mut sub_regions = Vec::new();
+        for sub_region in xor_regions {
+            let sub_pixels =
+                rasterize_region(sub_region, all_regions, canvas_width, canvas_height, warnings);
+            sub_regions.push(sub_pixels);
+        }
+        pixels = xor(&sub_regions);
+    }
+
+    // Handle 'expand'/'contract' modifiers (expand first, so both together close gaps)
+    if let Some(radius) = region.expand {
+        pixels = dilate(&pixels, radius);
+    }
+    if let Some(radius) = region.contract {
+        pixels = erode(&pixels, radius);
     }
 
     // Handle 'except' modifier (subtract named regions)
@@ -182,8 +214,10 @@ pub fn rasterize_region(
             if let Some(except_pixels) = all_regions.get(token_name) {
                 except_sets.push(except_pixels.clone());
             } else {
-                warnings
-                    .push(Warning::new(format!("Unknown token '{}' in except clause", token_name)));
+                warnings.push(Warning::new(
+                    WarningCategory::Render,
+                    format!("Unknown token '{}' in except clause", token_name),
+                ));
             }
         }
         if !except_sets.is_empty() {
@@ -274,11 +308,99 @@ fn apply_symmetric(
     result
 }
 
+/// Rasterize every region of a sprite, keyed by token name.
+///
+/// Regions with fill, auto-shadow or auto-outline references are deferred to a second pass
+/// so the regions they refer to exist. Jitter is applied per token with a
+/// stream seeded from `"{sprite}.{token}"` (or the region's `seed`).
+fn rasterize_regions(
+    name: &str,
+    regions: &HashMap<String, RegionDef>,
+    width: i32,
+    height: i32,
+    warnings: &mut Vec<Warning>,
+) -> HashMap<String, HashSet<(i32, i32)>> {
+    let mut rasterized_regions: HashMap<String, HashSet<(i32, i32)>> = HashMap::new();
+
+    // We need to rasterize in dependency order. For now, we'll do a simple two-pass:
+    // 1. Rasterize regions without fill/auto-shadow/auto-outline references
+    // 2. Rasterize regions with fill/auto-shadow/auto-outline references
+    let (pending, ready): (Vec<_>, Vec<_>) = regions.iter().partition(|(_, region)| {
+        region.fill.is_some() || region.auto_shadow.is_some() || region.auto_outline.is_some()
+    });
+
+    for (token, region) in ready.into_iter().chain(pending) {
+        let mut pixels = rasterize_region(region, &rasterized_regions, width, height, warnings);
+        if let Some(jitter) = &region.jitter {
+            let seed = object_seed(&format!("{}.{}", name, token), region.seed.map(u64::from));
+            pixels = apply_jitter_range(
+                &pixels,
+                jitter.x.unwrap_or([0, 0]),
+                jitter.y.unwrap_or([0, 0]),
+                seed,
+            );
+        }
+        rasterized_regions.insert(token.clone(), pixels);
+    }
+
+    rasterized_regions
+}
+
+/// Palette tokens for the pixels of a selective auto-outline.
+///
+/// Each outline pixel looks at the nearest pixel of the region it outlines
+/// and at the region visible there (other than the outline itself), and takes
+/// the `outline-colors` entry for that neighbor's token, or else its role.
+/// Pixels without an entry are left out and keep the outline's own token.
+fn selective_outline(
+    token: &str,
+    regions: &HashMap<String, RegionDef>,
+    rasterized_regions: &HashMap<String, HashSet<(i32, i32)>>,
+    region_order: &[(&String, i32)],
+) -> HashMap<(i32, i32), String> {
+    let mut recolored = HashMap::new();
+    let Some(region) = regions.get(token) else { return recolored };
+    let (Some(colors), Some(source_name)) = (&region.outline_colors, &region.auto_outline) else {
+        return recolored;
+    };
+    let (Some(source), Some(pixels)) =
+        (rasterized_regions.get(source_name), rasterized_regions.get(token))
+    else {
+        return recolored;
+    };
+
+    // Where to look for the outlined region, nearest first
+    let t = region.thickness.unwrap_or(1) as i32;
+    let mut offsets: Vec<(i32, i32)> = (-t..=t)
+        .flat_map(|dy| (-t..=t).map(move |dx| (dx, dy)))
+        .filter(|&offset| offset != (0, 0))
+        .collect();
+    offsets.sort_by_key(|&(dx, dy)| (dx * dx + dy * dy, dy, dx));
+
+    for &(x, y) in pixels {
+        let Some(inner) =
+            offsets.iter().map(|(dx, dy)| (x + dx, y + dy)).find(|p| source.contains(p))
+        else {
+            continue;
+        };
+        let neighbor = region_order.iter().rev().map(|(name, _)| name.as_str()).find(|name| {
+            *name != token && rasterized_regions.get(*name).is_some_and(|p| p.contains(&inner))
+        });
+        let Some(neighbor) = neighbor else { continue };
+
+        let role = regions.get(neighbor).and_then(|r| r.role.as_ref()).map(Role::to_string);
+        if let Some(color) = colors.get(neighbor).or_else(|| role.and_then(|r| colors.get(&r))) {
+            recolored.insert((x, y), color.replace('*', neighbor));
+        }
+    }
+    recolored
+}
+
 /// Render a structured sprite (regions format) to an RGBA image buffer.
 ///
 /// # Arguments
 ///
-/// * `name` - Sprite name (for error messages)
+/// * `name` - Sprite name (for error messages and jitter seeds)
 /// * `size` - Sprite size [width, height]
 /// * `regions` - Map of token names to region definitions
 /// * `palette` - Map of token names to hex color strings
@@ -291,105 +413,281 @@ pub fn render_structured(
     size: Option<[u32; 2]>,
     regions: &HashMap<String, RegionDef>,
     palette: &HashMap<String, String>,
+) -> (RgbaImage, Vec<Warning>) {
+    render_structured_clipped(name, size, regions, palette, None)
+}
+
+/// Render only the `rect` sub-rectangle of a structured sprite.
+///
+/// `rect` is first clipped to the canvas [`render_structured`] would produce,
+/// and fails if it lies outside it. Only pixels inside `rect` are written, so
+/// a small window of a huge sprite doesn't allocate or fill the whole canvas.
+pub fn render_structured_region(
+    name: &str,
+    size: Option<[u32; 2]>,
+    regions: &HashMap<String, RegionDef>,
+    palette: &HashMap<String, String>,
+    rect: Rect,
+) -> Result<(RgbaImage, Vec<Warning>), String> {
+    // Sprites without a usable size render as one transparent pixel
+    let [width, height] = size
+        .filter(|&[w, h]| (1..=i32::MAX as u32).contains(&w) && (1..=i32::MAX as u32).contains(&h))
+        .unwrap_or([1, 1]);
+    let rect = rect.clamp_to(width, height)?;
+    Ok(render_structured_clipped(name, size, regions, palette, Some(rect)))
+}
+
+fn render_structured_clipped(
+    name: &str,
+    size: Option<[u32; 2]>,
+    regions: &HashMap<String, RegionDef>,
+    palette: &HashMap<String, String>,
+    clip: Option<Rect>,
 ) -> (RgbaImage, Vec<Warning>) {
     let mut warnings = Vec::new();
+    let colors = parse_palette_colors(palette, &mut warnings);
+    let image = render_structured_colors(name, size, regions, &colors, clip, &mut warnings);
+    (image, warnings)
+}
+
+/// Parse every color of `palette`, in token order.
+///
+/// Invalid colors render magenta and produce a warning.
+fn parse_palette_colors(
+    palette: &HashMap<String, String>,
+    warnings: &mut Vec<Warning>,
+) -> HashMap<String, Rgba<u8>> {
+    let mut entries: Vec<_> = palette.iter().collect();
+    entries.sort();
+    entries
+        .into_iter()
+        .map(|(token, color)| {
+            let rgba = parse_color(color).unwrap_or_else(|e| {
+                warnings.push(Warning::new(
+                    WarningCategory::Render,
+                    format!("Invalid color '{}' for token {}: {}, using magenta", color, token, e),
+                ));
+                MAGENTA
+            });
+            (token.clone(), rgba)
+        })
+        .collect()
+}
+
+/// Render a structured sprite against an already-parsed palette.
+///
+/// Layers of a sprite share one palette, so [`render_layers`] parses it once
+/// and passes the same `colors` to every layer.
+fn render_structured_colors(
+    name: &str,
+    size: Option<[u32; 2]>,
+    regions: &HashMap<String, RegionDef>,
+    colors: &HashMap<String, Rgba<u8>>,
+    clip: Option<Rect>,
+    warnings: &mut Vec<Warning>,
+) -> RgbaImage {
+    let blank = || RgbaImage::from_pixel(1, 1, TRANSPARENT);
 
     // Determine canvas size
     let (width, height) = if let Some([w, h]) = size {
         (w as i32, h as i32)
     } else {
-        warnings.push(Warning::new(format!("Structured sprite '{}' requires explicit size", name)));
-        return (RgbaImage::from_pixel(1, 1, TRANSPARENT), warnings);
+        warnings.push(Warning::new(
+            WarningCategory::Render,
+            format!("Structured sprite '{}' requires explicit size", name),
+        ));
+        return blank();
     };
 
     if width <= 0 || height <= 0 {
-        warnings.push(Warning::new(format!(
-            "Invalid size for sprite '{}': {}x{}",
-            name, width, height
-        )));
-        return (RgbaImage::from_pixel(1, 1, TRANSPARENT), warnings);
-    }
-
-    // Build color lookup with parsed RGBA values
-    let mut color_cache: HashMap<String, Rgba<u8>> = HashMap::new();
-    for (token, hex_color) in palette {
-        match parse_color(hex_color) {
-            Ok(rgba) => {
-                color_cache.insert(token.clone(), rgba);
-            }
-            Err(e) => {
-                warnings.push(Warning::new(format!(
-                    "Invalid color '{}' for token {}: {}, using magenta",
-                    hex_color, token, e
-                )));
-                color_cache.insert(token.clone(), MAGENTA);
-            }
-        }
-    }
-
-    // Rasterize all regions first (needed for fill references)
-    let mut rasterized_regions: HashMap<String, HashSet<(i32, i32)>> = HashMap::new();
-
-    // We need to rasterize in dependency order. For now, we'll do a simple two-pass:
-    // 1. Rasterize regions without fill/auto-shadow references
-    // 2. Rasterize regions with fill/auto-shadow references
-    let mut pending_regions: Vec<(String, RegionDef)> = Vec::new();
-
-    for (token, region) in regions {
-        if region.fill.is_some() || region.auto_shadow.is_some() {
-            // Defer regions with fill or auto-shadow references
-            pending_regions.push((token.clone(), region.clone()));
-        } else {
-            let pixels =
-                rasterize_region(region, &rasterized_regions, width, height, &mut warnings);
-            rasterized_regions.insert(token.clone(), pixels);
-        }
+        warnings.push(Warning::new(
+            WarningCategory::Render,
+            format!("Invalid size for sprite '{}': {}x{}", name, width, height),
+        ));
+        return blank();
     }
 
-    // Now process regions with fill/auto-shadow references
-    for (token, region) in pending_regions {
-        let pixels = rasterize_region(&region, &rasterized_regions, width, height, &mut warnings);
-        rasterized_regions.insert(token, pixels);
-    }
+    let rasterized_regions = rasterize_regions(name, regions, width, height, warnings);
 
-    // Create image
-    let mut image = RgbaImage::new(width as u32, height as u32);
+    // Cover the clip rectangle (already inside the sprite) or the whole sprite
+    let rect = clip.unwrap_or(Rect { x: 0, y: 0, width: width as u32, height: height as u32 });
+    let (left, top) = (rect.x as i64, rect.y as i64);
+    let right = (left + rect.width as i64).min(width as i64);
+    let bottom = (top + rect.height as i64).min(height as i64);
+    let mut image = RgbaImage::new(rect.width, rect.height);
 
     // Collect regions with their z-order for sorting
     // Uses explicit z if provided, otherwise infers from semantic role
-    let mut region_order: Vec<(String, i32)> = regions
+    let mut region_order: Vec<(&String, i32)> = regions
         .iter()
         .map(|(token, region)| {
-            let z = region.z.unwrap_or_else(|| default_z_for_role(region.role.as_ref()));
-            (token.clone(), z)
+            (token, region.z.unwrap_or_else(|| default_z_for_role(region.role.as_ref())))
         })
         .collect();
 
     // Sort by z-order (lowest to highest)
     region_order.sort_by_key(|(_, z)| *z);
 
-    // Render regions in z-order
-    for (token, _z) in region_order {
-        if let Some(pixels) = rasterized_regions.get(&token) {
-            let color = if let Some(&rgba) = color_cache.get(&token) {
-                rgba
-            } else {
-                warnings
-                    .push(Warning::new(format!("Unknown token {} in sprite '{}'", token, name)));
-                color_cache.insert(token.clone(), MAGENTA);
-                MAGENTA
+    // Paint regions in z-order
+    for (token, _z) in &region_order {
+        if let Some(pixels) = rasterized_regions.get(*token) {
+            let mut color_of = |token: &str| {
+                colors.get(token).copied().unwrap_or_else(|| {
+                    warnings.push(Warning::new(
+                        WarningCategory::Render,
+                        format!("Unknown token {} in sprite '{}'", token, name),
+                    ));
+                    MAGENTA
+                })
             };
+            let color = color_of(token);
+
+            // Selective outlines take their color from what they border
+            let recolored = selective_outline(token, regions, &rasterized_regions, &region_order);
+            let mut outline_colors = HashMap::new();
+            for outline in recolored.values() {
+                if !outline_colors.contains_key(outline) {
+                    outline_colors.insert(outline.clone(), color_of(outline));
+                }
+            }
 
-            // Render all pixels for this region
-            for (x, y) in pixels {
-                if *x >= 0 && *x < width && *y >= 0 && *y < height {
-                    image.put_pixel(*x as u32, *y as u32, color);
+            // Paint all pixels of this region inside the sprite and clip rectangle
+            for &(x, y) in pixels {
+                let color = recolored.get(&(x, y)).map_or(color, |c| outline_colors[c]);
+                let (x, y) = (x as i64, y as i64);
+                if x >= left && x < right && y >= top && y < bottom {
+                    image.put_pixel((x - left) as u32, (y - top) as u32, color);
                 }
             }
         }
     }
 
-    (image, warnings)
+    image
+}
+
+/// Resolve which token is visible at each pixel of a structured sprite.
+///
+/// Regions are layered in the same z-order as [`render_structured`], with
+/// ties broken by token name. Pixels no region covers are `None`.
+///
+/// # Returns
+///
+/// Rows of tokens, top to bottom, and any warnings generated.
+pub fn token_grid(
+    name: &str,
+    width: u32,
+    height: u32,
+    regions: &HashMap<String, RegionDef>,
+) -> (Vec<Vec<Option<String>>>, Vec<Warning>) {
+    let mut warnings = Vec::new();
+    let rasterized_regions =
+        rasterize_regions(name, regions, width as i32, height as i32, &mut warnings);
+
+    let mut region_order: Vec<(&String, i32)> = regions
+        .iter()
+        .map(|(token, region)| {
+            (token, region.z.unwrap_or_else(|| default_z_for_role(region.role.as_ref())))
+        })
+        .collect();
+    region_order.sort_by(|(a, za), (b, zb)| za.cmp(zb).then(a.cmp(b)));
+
+    let mut grid = vec![vec![None; width as usize]; height as usize];
+    for (token, _z) in &region_order {
+        let recolored = selective_outline(token, regions, &rasterized_regions, &region_order);
+        for &(x, y) in rasterized_regions.get(*token).into_iter().flatten() {
+            if x >= 0 && (x as u32) < width && y >= 0 && (y as u32) < height {
+                let shown = recolored.get(&(x, y)).unwrap_or(token);
+                grid[y as usize][x as usize] = Some(shown.to_string());
+            }
+        }
+    }
+
+    (grid, warnings)
+}
+
+/// Name of the implicit layer holding a layered sprite's top-level `regions`.
+pub const BASE_LAYER: &str = "base";
+
+/// Rendered layer images, back-to-front, paired with their layer names.
+pub type LayerImages = Vec<(String, RgbaImage)>;
+
+/// Render each layer of a layered sprite to its own full-size image.
+///
+/// Top-level `regions`, if any, form an implicit bottom layer named
+/// [`BASE_LAYER`]. Layers are returned back-to-front: sorted by `z`
+/// (default 0), with ties keeping declaration order. Each layer's `opacity`
+/// is applied to its own image.
+///
+/// # Returns
+///
+/// The named layer images and any warnings generated.
+pub fn render_layers(
+    name: &str,
+    size: Option<[u32; 2]>,
+    regions: Option<&HashMap<String, RegionDef>>,
+    layers: &[SpriteLayer],
+    palette: &HashMap<String, String>,
+) -> (LayerImages, Vec<Warning>) {
+    let mut warnings = Vec::new();
+
+    // (layer name, z, regions, jitter seed name, opacity); the base layer keeps the sprite's own seeds
+    type Entry<'a> = (&'a str, i32, &'a HashMap<String, RegionDef>, String, Option<f64>);
+    let mut ordered: Vec<Entry> = Vec::new();
+    if let Some(regions) = regions.filter(|r| !r.is_empty()) {
+        ordered.push((BASE_LAYER, i32::MIN, regions, name.to_string(), None));
+    }
+    for layer in layers {
+        if ordered.iter().any(|(existing, ..)| *existing == layer.name) {
+            warnings.push(Warning::new(
+                WarningCategory::Render,
+                format!("Duplicate layer '{}' in sprite '{}'", layer.name, name),
+            ));
+        }
+        let seed_name = format!("{}/{}", name, layer.name);
+        ordered.push((&layer.name, layer.z.unwrap_or(0), &layer.regions, seed_name, layer.opacity));
+    }
+    ordered.sort_by_key(|(_, z, ..)| *z);
+
+    // Every layer shares the sprite's palette, so parse it once
+    let colors = parse_palette_colors(palette, &mut warnings);
+
+    let images = ordered
+        .into_iter()
+        .map(|(layer_name, _, regions, seed_name, opacity)| {
+            let mut layer_warnings = Vec::new();
+            let mut image = render_structured_colors(
+                &seed_name,
+                size,
+                regions,
+                &colors,
+                None,
+                &mut layer_warnings,
+            );
+            apply_opacity(&mut image, opacity);
+            for warning in layer_warnings {
+                // Sprite-level problems (e.g. missing size) would repeat for every layer
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+            (layer_name.to_string(), image)
+        })
+        .collect();
+
+    (images, warnings)
+}
+
+/// Alpha-composite layer images back-to-front into a single image.
+pub fn composite_layers(layers: &[(String, RgbaImage)]) -> RgbaImage {
+    let mut layers = layers.iter();
+    let Some((_, bottom)) = layers.next() else {
+        return RgbaImage::from_pixel(1, 1, TRANSPARENT);
+    };
+    let mut image = bottom.clone();
+    for (_, layer) in layers {
+        image::imageops::overlay(&mut image, layer, 0, 0);
+    }
+    image
 }
 
 /// Extract bounding boxes for all anchor-role regions in a structured sprite.
@@ -400,6 +698,7 @@ pub fn render_structured(
 ///
 /// # Arguments
 ///
+/// * `name` - Sprite name (seeds region jitter)
 /// * `regions` - Map of token names to region definitions
 /// * `canvas_width` - Width of the sprite canvas
 /// * `canvas_height` - Height of the sprite canvas
@@ -408,43 +707,15 @@ pub fn render_structured(
 ///
 /// A vector of `AnchorBounds` representing the bounding box of each anchor region.
 pub fn extract_anchor_bounds(
+    name: &str,
     regions: &HashMap<String, RegionDef>,
     canvas_width: i32,
     canvas_height: i32,
 ) -> Vec<crate::transforms::AnchorBounds> {
     let mut anchor_bounds = Vec::new();
     let mut warnings = Vec::new();
-
-    // First pass: rasterize non-fill regions (needed for fill references)
-    let mut rasterized_regions: HashMap<String, HashSet<(i32, i32)>> = HashMap::new();
-    let mut pending_regions: Vec<(String, RegionDef)> = Vec::new();
-
-    for (token, region) in regions {
-        if region.fill.is_some() || region.auto_shadow.is_some() {
-            pending_regions.push((token.clone(), region.clone()));
-        } else {
-            let pixels = rasterize_region(
-                region,
-                &rasterized_regions,
-                canvas_width,
-                canvas_height,
-                &mut warnings,
-            );
-            rasterized_regions.insert(token.clone(), pixels);
-        }
-    }
-
-    // Second pass: rasterize fill/auto-shadow regions
-    for (token, region) in pending_regions {
-        let pixels = rasterize_region(
-            &region,
-            &rasterized_regions,
-            canvas_width,
-            canvas_height,
-            &mut warnings,
-        );
-        rasterized_regions.insert(token, pixels);
-    }
+    let rasterized_regions =
+        rasterize_regions(name, regions, canvas_width, canvas_height, &mut warnings);
 
     // Now extract bounding boxes for anchor regions
     for (token, region) in regions {
@@ -466,6 +737,7 @@ mod tests {
     use super::*;
     use crate::parser::parse_line;
     use crate::registry::{PaletteRegistry, SpriteRegistry};
+    use crate::shapes::OutlineCorners;
 
     #[test]
     fn test_parse_fill_reference() {
@@ -476,6 +748,60 @@ mod tests {
         assert_eq!(parse_fill_reference("inside("), None);
     }
 
+    #[test]
+    fn test_render_structured_region_matches_crop() {
+        let regions = HashMap::from([
+            ("r".to_string(), RegionDef { rect: Some([0, 0, 4, 4]), ..Default::default() }),
+            (
+                "b".to_string(),
+                RegionDef { rect: Some([2, 2, 2, 2]), z: Some(1), ..Default::default() },
+            ),
+        ]);
+        let palette = HashMap::from([
+            ("r".to_string(), "#FF0000".to_string()),
+            ("b".to_string(), "#0000FF".to_string()),
+        ]);
+        let (full, _) = render_structured("s", Some([4, 4]), &regions, &palette);
+
+        let rect = Rect { x: 1, y: 1, width: 2, height: 2 };
+        let (region, warnings) =
+            render_structured_region("s", Some([4, 4]), &regions, &palette, rect).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(region, crate::renderer::crop_image(&full, rect).unwrap());
+        assert_eq!(region.get_pixel(1, 1).0, [0, 0, 255, 255]);
+
+        // Clipped to the sprite's edge, however large the request
+        let rect = Rect { x: 3, y: 2, width: u32::MAX - 3, height: 1 << 30 };
+        let (region, _) =
+            render_structured_region("s", Some([4, 4]), &regions, &palette, rect).unwrap();
+        assert_eq!(region.dimensions(), (1, 2));
+        assert_eq!(region.get_pixel(0, 1).0, [0, 0, 255, 255]);
+
+        let outside = Rect { x: 4, y: 0, width: 1, height: 1 };
+        let err = render_structured_region("s", Some([4, 4]), &regions, &palette, outside);
+        assert!(err.unwrap_err().contains("outside the 4x4"));
+
+        // Without a size the canvas is a single pixel
+        let rect = Rect { x: 0, y: 0, width: 1 << 20, height: 1 << 20 };
+        let (region, _) = render_structured_region("s", None, &regions, &palette, rect).unwrap();
+        assert_eq!(region.dimensions(), (1, 1));
+    }
+
+    #[test]
+    fn test_render_structured_bad_colors_are_magenta() {
+        let regions = HashMap::from([
+            ("x".to_string(), RegionDef { rect: Some([0, 0, 1, 1]), ..Default::default() }),
+            ("y".to_string(), RegionDef { rect: Some([1, 0, 1, 1]), ..Default::default() }),
+        ]);
+        let palette = HashMap::from([("x".to_string(), "not-a-color".to_string())]);
+        let (image, warnings) = render_structured("s", Some([2, 1]), &regions, &palette);
+        assert_eq!(*image.get_pixel(0, 0), MAGENTA);
+        assert_eq!(*image.get_pixel(1, 0), MAGENTA);
+        let messages: Vec<_> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert!(messages.iter().any(|m| m.contains("Invalid color 'not-a-color' for token x")));
+        assert!(messages.contains(&"Unknown token y in sprite 's'"));
+    }
+
     #[test]
     fn test_rasterize_region_rect() {
         let region = RegionDef { rect: Some([0, 0, 3, 2]), ..Default::default() };
@@ -559,6 +885,24 @@ mod tests {
         assert_eq!(*image.get_pixel(5, 5), Rgba([255, 0, 0, 255]));
     }
 
+    #[test]
+    fn test_token_grid() {
+        let mut regions = HashMap::new();
+        regions
+            .insert("b".to_string(), RegionDef { rect: Some([0, 0, 3, 1]), ..Default::default() });
+        regions.insert(
+            "a".to_string(),
+            RegionDef { points: Some(vec![[1, 0]]), z: Some(1), ..Default::default() },
+        );
+
+        let (grid, warnings) = token_grid("test", 4, 2, &regions);
+        assert!(warnings.is_empty());
+
+        let row = |y: usize| grid[y].iter().map(|t| t.as_deref()).collect::<Vec<_>>();
+        assert_eq!(row(0), [Some("b"), Some("a"), Some("b"), None]);
+        assert_eq!(row(1), [None; 4]);
+    }
+
     #[test]
     fn test_verification_example_from_spec() {
         // This is the verification example from the task specification
@@ -639,6 +983,87 @@ mod tests {
         assert!(warnings.is_empty());
     }
 
+    #[test]
+    fn test_rasterize_auto_outline_corners() {
+        let mut all_regions = HashMap::new();
+        all_regions.insert("body".to_string(), rasterize_rect(2, 2, 2, 2));
+        let mut warnings = Vec::new();
+
+        let mut outline_region =
+            RegionDef { auto_outline: Some("body".to_string()), ..Default::default() };
+        let square = rasterize_region(&outline_region, &all_regions, 10, 10, &mut warnings);
+        assert_eq!(square.len(), 12);
+        assert!(square.contains(&(1, 1)));
+        assert!(!square.contains(&(2, 2)), "outline stays outside the body");
+
+        outline_region.outline_corners = Some(OutlineCorners::Rounded);
+        outline_region.thickness = Some(2);
+        let rounded = rasterize_region(&outline_region, &all_regions, 10, 10, &mut warnings);
+        assert!(rounded.contains(&(0, 2)));
+        assert!(rounded.contains(&(1, 1)));
+        assert!(!rounded.contains(&(0, 0)));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_render_selective_outline() {
+        let mut regions = HashMap::new();
+        regions.insert(
+            "body".to_string(),
+            RegionDef { rect: Some([2, 2, 4, 4]), ..Default::default() },
+        );
+        regions.insert(
+            "skin".to_string(),
+            RegionDef {
+                rect: Some([2, 2, 2, 4]),
+                role: Some(Role::Fill),
+                z: Some(1),
+                ..Default::default()
+            },
+        );
+        regions.insert(
+            "hair".to_string(),
+            RegionDef { rect: Some([4, 3, 2, 3]), z: Some(1), ..Default::default() },
+        );
+        regions.insert(
+            "outline".to_string(),
+            RegionDef {
+                auto_outline: Some("body".to_string()),
+                outline_corners: Some(OutlineCorners::Rounded),
+                outline_colors: Some(HashMap::from([
+                    ("hair".to_string(), "hair_dark".to_string()),
+                    ("fill".to_string(), "*_1".to_string()),
+                ])),
+                ..Default::default()
+            },
+        );
+        let palette = HashMap::from([
+            ("body".to_string(), "#808080".to_string()),
+            ("skin".to_string(), "#FFCC99".to_string()),
+            ("skin_1".to_string(), "#CC9966".to_string()),
+            ("hair".to_string(), "#FFFF00".to_string()),
+            ("hair_dark".to_string(), "#999900".to_string()),
+            ("outline".to_string(), "#000000".to_string()),
+        ]);
+
+        let (image, warnings) = render_structured("test", Some([8, 8]), &regions, &palette);
+        assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+        // Next to skin (by role), with `*` standing for the neighbor's token
+        assert_eq!(*image.get_pixel(1, 3), Rgba([0xCC, 0x99, 0x66, 255]));
+        assert_eq!(*image.get_pixel(2, 1), Rgba([0xCC, 0x99, 0x66, 255]));
+        // Next to hair (by token)
+        assert_eq!(*image.get_pixel(6, 4), Rgba([0x99, 0x99, 0x00, 255]));
+        assert_eq!(*image.get_pixel(4, 6), Rgba([0x99, 0x99, 0x00, 255]));
+        // Next to bare body, which has no entry: the outline's own color
+        assert_eq!(*image.get_pixel(4, 1), Rgba([0, 0, 0, 255]));
+        // Rounded corners
+        assert_eq!(image.get_pixel(1, 1)[3], 0);
+
+        let (grid, _) = token_grid("test", 8, 8, &regions);
+        assert_eq!(grid[3][1].as_deref(), Some("skin_1"));
+        assert_eq!(grid[1][4].as_deref(), Some("outline"));
+    }
+
     #[test]
     fn test_render_structured_with_auto_shadow() {
         let mut regions = HashMap::new();
@@ -761,4 +1186,137 @@ mod tests {
         // Red anchor visible where not overlapped
         assert_eq!(*image.get_pixel(2, 2), Rgba([255, 0, 0, 255]));
     }
+
+    #[test]
+    fn test_jitter_is_seeded_per_token() {
+        use crate::models::JitterSpec;
+
+        let row: Vec<[u32; 2]> = (0..8).map(|x| [x * 2, 6]).collect();
+        let jittered = |seed: Option<u32>| RegionDef {
+            points: Some(row.clone()),
+            jitter: Some(JitterSpec { x: None, y: Some([-3, 0]) }),
+            seed,
+            ..Default::default()
+        };
+        let mut regions = HashMap::new();
+        regions.insert("a".to_string(), jittered(None));
+        regions.insert("b".to_string(), jittered(None));
+        regions.insert("c".to_string(), jittered(Some(7)));
+        regions.insert("d".to_string(), jittered(Some(7)));
+
+        let mut warnings = Vec::new();
+        let first = rasterize_regions("grass", &regions, 16, 8, &mut warnings);
+        let second = rasterize_regions("grass", &regions, 16, 8, &mut warnings);
+        assert_eq!(first, second);
+
+        // Each pixel keeps its column and only moves up to 3 rows up
+        assert!(first["a"].iter().all(|&(x, y)| x % 2 == 0 && (3..=6).contains(&y)));
+        // Unseeded tokens draw from their own stream, a shared seed shares the pattern
+        assert_ne!(first["a"], first["b"]);
+        assert_eq!(first["c"], first["d"]);
+
+        let reseeded = crate::rng::with_global_seed(1, || {
+            rasterize_regions("grass", &regions, 16, 8, &mut warnings)
+        });
+        assert_ne!(first["a"], reseeded["a"]);
+    }
+
+    #[test]
+    fn test_rasterize_xor_and_morphology() {
+        let all_regions = HashMap::new();
+        let mut warnings = Vec::new();
+        let rect = |x, y, w, h| RegionDef { rect: Some([x, y, w, h]), ..Default::default() };
+
+        let frame =
+            RegionDef { xor: Some(vec![rect(0, 0, 6, 6), rect(1, 1, 4, 4)]), ..Default::default() };
+        let pixels = rasterize_region(&frame, &all_regions, 10, 10, &mut warnings);
+        assert_eq!(pixels, rasterize_stroke(0, 0, 6, 6, 1));
+
+        let glow = RegionDef { expand: Some(1), ..rect(2, 2, 2, 2) };
+        let pixels = rasterize_region(&glow, &all_regions, 10, 10, &mut warnings);
+        assert_eq!(pixels, rasterize_rect(1, 1, 4, 4));
+
+        let inset = RegionDef { contract: Some(1), ..rect(2, 2, 4, 4) };
+        let pixels = rasterize_region(&inset, &all_regions, 10, 10, &mut warnings);
+        assert_eq!(pixels, rasterize_rect(3, 3, 2, 2));
+
+        // Expand then contract fills the one-pixel gap between two bars
+        let bars = RegionDef {
+            union: Some(vec![rect(0, 0, 2, 4), rect(3, 0, 2, 4)]),
+            expand: Some(1),
+            contract: Some(1),
+            ..Default::default()
+        };
+        let pixels = rasterize_region(&bars, &all_regions, 10, 10, &mut warnings);
+        assert!(pixels.contains(&(2, 1)));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_rasterize_curved_path_with_fill_rule() {
+        use crate::shapes::FillRule;
+
+        let all_regions = HashMap::new();
+        let mut warnings = Vec::new();
+        // A ring: two circles drawn as arcs in the same direction
+        let ring = |fill_rule| RegionDef {
+            path: Some(
+                "M0,6 A6,6 0 0,1 12,6 A6,6 0 0,1 0,6 Z M3,6 A3,3 0 0,1 9,6 A3,3 0 0,1 3,6 Z"
+                    .to_string(),
+            ),
+            fill_rule,
+            ..Default::default()
+        };
+
+        let disc = rasterize_region(&ring(None), &all_regions, 16, 16, &mut warnings);
+        let donut =
+            rasterize_region(&ring(Some(FillRule::EvenOdd)), &all_regions, 16, 16, &mut warnings);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert!(disc.contains(&(6, 6)));
+        assert!(!donut.contains(&(6, 6)));
+        assert!(donut.contains(&(1, 6)) && donut.contains(&(6, 1)));
+        assert!(!disc.contains(&(1, 1)), "corners stay outside the circle");
+    }
+
+    #[test]
+    fn test_render_layers_back_to_front() {
+        let palette: HashMap<String, String> = [("a", "#FF0000"), ("b", "#0000FF")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let rect = |x, y, w, h| RegionDef { rect: Some([x, y, w, h]), ..Default::default() };
+        let layer = |name: &str, z, token: &str, region| SpriteLayer {
+            name: name.to_string(),
+            z,
+            regions: HashMap::from([(token.to_string(), region)]),
+            opacity: None,
+        };
+        let base = HashMap::from([("a".to_string(), rect(0, 0, 4, 4))]);
+        // Declared top-first; z decides the order
+        let layers = vec![
+            layer("top", Some(2), "a", rect(2, 0, 2, 2)),
+            layer("mid", None, "b", rect(0, 0, 3, 3)),
+        ];
+
+        let (images, warnings) = render_layers("s", Some([4, 4]), Some(&base), &layers, &palette);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let names: Vec<&str> = images.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["base", "mid", "top"]);
+        assert_eq!(images[1].1.get_pixel(3, 3)[3], 0, "layers render on their own canvas");
+
+        let image = composite_layers(&images);
+        assert_eq!(*image.get_pixel(3, 3), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 1), Rgba([0, 0, 255, 255]));
+        assert_eq!(*image.get_pixel(2, 1), Rgba([255, 0, 0, 255]));
+
+        let duplicate = vec![layer("base", None, "b", rect(0, 0, 1, 1))];
+        let (_, warnings) = render_layers("s", Some([4, 4]), Some(&base), &duplicate, &palette);
+        assert!(warnings[0].message.contains("Duplicate layer 'base'"));
+
+        // Layer opacity fades only that layer
+        let ghost = vec![SpriteLayer { opacity: Some(0.5), ..layers[1].clone() }];
+        let (images, _) = render_layers("s", Some([4, 4]), Some(&base), &ghost, &palette);
+        assert_eq!(images[0].1.get_pixel(0, 0)[3], 255);
+        assert_eq!(*images[1].1.get_pixel(0, 0), Rgba([0, 0, 255, 128]));
+    }
 }
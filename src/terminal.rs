@@ -9,6 +9,9 @@ use std::collections::HashMap;
 /// ANSI escape sequence to reset all formatting
 pub const ANSI_RESET: &str = "\x1b[0m";
 
+/// Color of the 256-color gray (236) drawn for transparent pixels
+pub const TRANSPARENT_CELL: Rgba<u8> = Rgba([48, 48, 48, 255]);
+
 /// Convert RGBA color to ANSI 24-bit background escape sequence.
 ///
 /// Transparent colors (alpha = 0) are rendered as a dark gray background
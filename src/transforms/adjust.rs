@@ -0,0 +1,129 @@
+//! Color adjustment transforms
+//!
+//! Per-pixel color operations (tint, hue rotate, saturation, brightness,
+//! contrast, posterize) that leave alpha untouched. They apply to rendered
+//! sprites and to composition layers before blending.
+
+use image::{Rgba, RgbaImage};
+
+use crate::color::{hsl_to_rgb, rgb_to_hsl};
+
+/// Map every visible pixel's RGB channels through `f`, keeping alpha.
+fn map_rgb(image: &RgbaImage, f: impl Fn([u8; 3]) -> [u8; 3]) -> RgbaImage {
+    let mut result = image.clone();
+    for pixel in result.pixels_mut() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let [r, g, b] = f([pixel[0], pixel[1], pixel[2]]);
+        *pixel = Rgba([r, g, b, pixel[3]]);
+    }
+    result
+}
+
+/// Map each RGB channel independently through `f` (values 0.0-1.0).
+fn map_channels(image: &RgbaImage, f: impl Fn(f64) -> f64) -> RgbaImage {
+    map_rgb(image, |rgb| rgb.map(|c| to_u8(f(c as f64 / 255.0))))
+}
+
+fn to_u8(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Mix each pixel toward `color` by `strength` (0.0 = unchanged, 1.0 = solid color).
+pub fn tint(image: &RgbaImage, color: Rgba<u8>, strength: f64) -> RgbaImage {
+    let strength = strength.clamp(0.0, 1.0);
+    map_rgb(image, |rgb| {
+        let mut out = rgb;
+        for (channel, target) in out.iter_mut().zip([color[0], color[1], color[2]]) {
+            let mixed = *channel as f64 + (target as f64 - *channel as f64) * strength;
+            *channel = mixed.round() as u8;
+        }
+        out
+    })
+}
+
+/// Rotate every pixel's hue by `degrees`.
+pub fn hue_rotate(image: &RgbaImage, degrees: f64) -> RgbaImage {
+    map_rgb(image, |[r, g, b]| {
+        let hsl = rgb_to_hsl(r, g, b).shift(degrees, 0.0, 0.0);
+        let (r, g, b) = hsl_to_rgb(&hsl);
+        [r, g, b]
+    })
+}
+
+/// Scale saturation around each pixel's luma (0.0 = grayscale, 1.0 = unchanged).
+pub fn saturate(image: &RgbaImage, amount: f64) -> RgbaImage {
+    map_rgb(image, |rgb| {
+        let [r, g, b] = rgb.map(|c| c as f64 / 255.0);
+        let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        [r, g, b].map(|c| to_u8(luma + (c - luma) * amount))
+    })
+}
+
+/// Multiply every channel by `amount` (1.0 = unchanged).
+pub fn brightness(image: &RgbaImage, amount: f64) -> RgbaImage {
+    map_channels(image, |c| c * amount)
+}
+
+/// Scale every channel around mid-gray by `amount` (1.0 = unchanged).
+pub fn contrast(image: &RgbaImage, amount: f64) -> RgbaImage {
+    map_channels(image, |c| (c - 0.5) * amount + 0.5)
+}
+
+/// Reduce every channel to `levels` evenly spaced values.
+pub fn posterize(image: &RgbaImage, levels: u8) -> RgbaImage {
+    let steps = levels.max(2) as f64 - 1.0;
+    map_channels(image, |c| (c * steps).round() / steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(r: u8, g: u8, b: u8, a: u8) -> RgbaImage {
+        RgbaImage::from_pixel(1, 1, Rgba([r, g, b, a]))
+    }
+
+    #[test]
+    fn test_tint_mixes_toward_color_and_keeps_alpha() {
+        let image = tint(&pixel(0, 0, 200, 128), Rgba([255, 0, 0, 255]), 0.5);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([128, 0, 100, 128]));
+    }
+
+    #[test]
+    fn test_transparent_pixels_untouched() {
+        let image = brightness(&pixel(10, 20, 30, 0), 2.0);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([10, 20, 30, 0]));
+    }
+
+    #[test]
+    fn test_hue_rotate() {
+        let image = hue_rotate(&pixel(255, 0, 0, 255), 120.0);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn test_saturate_zero_is_grayscale() {
+        let image = saturate(&pixel(200, 40, 40, 255), 0.0);
+        let p = image.get_pixel(0, 0);
+        assert_eq!(p[0], p[1]);
+        assert_eq!(p[1], p[2]);
+    }
+
+    #[test]
+    fn test_brightness_and_contrast() {
+        assert_eq!(
+            *brightness(&pixel(100, 200, 0, 255), 0.5).get_pixel(0, 0),
+            Rgba([50, 100, 0, 255])
+        );
+        let image = contrast(&pixel(64, 128, 191, 255), 2.0);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 129, 255, 255]));
+    }
+
+    #[test]
+    fn test_posterize() {
+        let image = posterize(&pixel(60, 100, 200, 255), 2);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+    }
+}
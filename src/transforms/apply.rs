@@ -4,7 +4,9 @@
 
 use image::RgbaImage;
 
+use super::adjust;
 use super::anchor::scale_image;
+use super::effects;
 use super::types::{Transform, TransformError};
 
 /// Resolve a palette token to an RGBA color.
@@ -44,12 +46,26 @@ fn resolve_token_color(
     })
 }
 
+/// Resolve a color string, falling back to a palette token lookup.
+fn resolve_color(
+    color: &str,
+    palette: Option<&std::collections::HashMap<String, String>>,
+) -> Result<image::Rgba<u8>, TransformError> {
+    match crate::color::parse_color(color) {
+        Ok(rgba) => Ok(rgba),
+        Err(_) => resolve_token_color(Some(color), palette, image::Rgba([0, 0, 0, 255])),
+    }
+}
+
 /// Apply a single transform to an image.
 ///
-/// Handles geometric and spatial transforms that operate on pixel data:
+/// Handles geometric, spatial and color transforms that operate on pixel data:
 /// - MirrorH, MirrorV, Rotate
 /// - Scale, SkewX, SkewY
 /// - Tile, Pad, Crop, Shift
+/// - Tint, HueRotate, Saturate, Brightness, Contrast, Posterize
+/// - Glow, Emboss, PixelNoise
+/// - Plugin (registered [`crate::plugin::TransformPlugin`]s)
 ///
 /// Animation transforms (Pingpong, Reverse, etc.) should use `apply_animation_transform` instead.
 ///
@@ -162,6 +178,24 @@ pub fn apply_image_transform(
                 resolve_token_color(token.as_deref(), palette, image::Rgba([0, 0, 0, 128]))?;
             Ok(apply_shadow(image, *x, *y, color))
         }
+        Transform::Tint { color, strength } => {
+            let color = resolve_color(color, palette)?;
+            Ok(adjust::tint(image, color, *strength))
+        }
+        Transform::HueRotate { degrees } => Ok(adjust::hue_rotate(image, *degrees)),
+        Transform::Saturate { amount } => Ok(adjust::saturate(image, *amount)),
+        Transform::Brightness { amount } => Ok(adjust::brightness(image, *amount)),
+        Transform::Contrast { amount } => Ok(adjust::contrast(image, *amount)),
+        Transform::Posterize { levels } => Ok(adjust::posterize(image, *levels)),
+        Transform::Glow { radius, color } => {
+            let color = color.as_deref().map(|c| resolve_color(c, palette)).transpose()?;
+            Ok(effects::glow(image, *radius, color))
+        }
+        Transform::Emboss { strength } => Ok(effects::emboss(image, *strength)),
+        Transform::PixelNoise { amount, seed } => Ok(effects::pixel_noise(image, *amount, *seed)),
+        Transform::Plugin { name, params } => crate::plugin::transform(name)
+            .ok_or_else(|| TransformError::UnknownOperation(name.clone()))?
+            .apply(image, params),
         // Color-based transforms not yet implemented
         Transform::SelOut { .. }
         | Transform::Dither { .. }
@@ -917,4 +951,32 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_apply_tint_resolves_palette_token() {
+        let img = RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255]));
+        let palette =
+            std::collections::HashMap::from([("night".to_string(), "#0000FF".to_string())]);
+        let tint = Transform::Tint { color: "{night}".to_string(), strength: 1.0 };
+
+        let result = apply_image_transform(&img, &tint, Some(&palette)).unwrap();
+        assert_eq!(result.get_pixel(0, 0), &image::Rgba([0, 0, 255, 255]));
+
+        // Unknown tokens without a palette are an error
+        assert!(apply_image_transform(&img, &tint, None).is_err());
+    }
+    #[test]
+    fn test_apply_glow_resolves_palette_token() {
+        let mut img = RgbaImage::new(3, 1);
+        img.put_pixel(1, 0, image::Rgba([255, 255, 255, 255]));
+        let palette =
+            std::collections::HashMap::from([("spark".to_string(), "#FFCC00".to_string())]);
+        let glow = Transform::Glow { radius: 1, color: Some("{spark}".to_string()) };
+
+        let result = apply_image_transform(&img, &glow, Some(&palette)).unwrap();
+        let halo = result.get_pixel(0, 0);
+        assert_eq!([halo[0], halo[1], halo[2]], [255, 204, 0]);
+        assert!(halo[3] > 0);
+        assert!(apply_image_transform(&img, &glow, None).is_err());
+    }
 }
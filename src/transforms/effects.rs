@@ -0,0 +1,199 @@
+//! Self-blend effect transforms
+//!
+//! Cheap finishing touches that blend a sprite with a processed copy of
+//! itself: a blurred additive glow, an emboss bevel and per-pixel noise.
+//! Output is deterministic; noise draws from a seeded [`Rng`].
+
+use image::{Rgba, RgbaImage};
+
+use crate::rng::Rng;
+
+/// Box blur of premultiplied RGBA, `radius` pixels in each direction.
+///
+/// Returns premultiplied channels (0.0-1.0) in row-major order; pixels
+/// beyond the edge count as transparent.
+fn blur(image: &RgbaImage, radius: u32) -> Vec<[f64; 4]> {
+    let (w, h) = (image.width() as usize, image.height() as usize);
+    let r = radius as usize;
+    let window = (2 * r + 1) as f64;
+    let source: Vec<[f64; 4]> = image
+        .pixels()
+        .map(|p| {
+            let a = p[3] as f64 / 255.0;
+            [p[0] as f64 / 255.0 * a, p[1] as f64 / 255.0 * a, p[2] as f64 / 255.0 * a, a]
+        })
+        .collect();
+
+    let mean = |samples: &mut dyn Iterator<Item = [f64; 4]>| {
+        let mut sum = [0.0; 4];
+        for sample in samples {
+            for (s, c) in sum.iter_mut().zip(sample) {
+                *s += c;
+            }
+        }
+        sum.map(|c| c / window)
+    };
+
+    let mut horizontal = vec![[0.0; 4]; w * h];
+    for y in 0..h {
+        let row = &source[y * w..(y + 1) * w];
+        for x in 0..w {
+            let span = x.saturating_sub(r)..(x + r + 1).min(w);
+            horizontal[y * w + x] = mean(&mut row[span].iter().copied());
+        }
+    }
+    let mut result = vec![[0.0; 4]; w * h];
+    for y in 0..h {
+        for x in 0..w {
+            let span = y.saturating_sub(r)..(y + r + 1).min(h);
+            result[y * w + x] = mean(&mut span.map(|sy| horizontal[sy * w + x]));
+        }
+    }
+    result
+}
+
+fn to_u8(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Add a blurred copy of the sprite to itself, spilling light into
+/// transparent pixels around it.
+///
+/// With a `color`, the glow takes that color and the blurred copy only
+/// supplies its shape. The canvas is not enlarged; `pad` first to give the
+/// glow room.
+pub fn glow(image: &RgbaImage, radius: u32, color: Option<Rgba<u8>>) -> RgbaImage {
+    let blurred = blur(image, radius);
+    let mut result = image.clone();
+    for (pixel, glow) in result.pixels_mut().zip(blurred) {
+        let glow = match color {
+            Some(c) => {
+                let a = glow[3] * c[3] as f64 / 255.0;
+                [c[0] as f64 / 255.0 * a, c[1] as f64 / 255.0 * a, c[2] as f64 / 255.0 * a, a]
+            }
+            None => glow,
+        };
+        let a = pixel[3] as f64 / 255.0;
+        let out_a = a + glow[3] * (1.0 - a);
+        if out_a <= 0.0 {
+            continue;
+        }
+        // Additive in premultiplied space: light adds, never darkens
+        let rgb = [0, 1, 2].map(|i| to_u8((pixel[i] as f64 / 255.0 * a + glow[i]) / out_a));
+        *pixel = Rgba([rgb[0], rgb[1], rgb[2], to_u8(out_a)]);
+    }
+    result
+}
+
+/// Bevel the sprite as if lit from the top-left.
+///
+/// Each visible pixel is brightened or darkened by the luma gradient across
+/// it, scaled by `strength` (1.0 = full emboss). Transparent neighbours
+/// count as black, so the sprite's own edges catch the light. Alpha is kept.
+pub fn emboss(image: &RgbaImage, strength: f64) -> RgbaImage {
+    let (w, h) = image.dimensions();
+    let luma = |x: i64, y: i64| -> f64 {
+        if x < 0 || y < 0 || x >= w as i64 || y >= h as i64 {
+            return 0.0;
+        }
+        let p = image.get_pixel(x as u32, y as u32);
+        let l = 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64;
+        l / 255.0 * p[3] as f64 / 255.0
+    };
+    let mut result = image.clone();
+    for (x, y, pixel) in result.enumerate_pixels_mut() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let (x, y) = (x as i64, y as i64);
+        // Emboss kernel: -1 toward the light, +1 away from it
+        let edge = luma(x + 1, y + 1) + luma(x + 1, y) + luma(x, y + 1)
+            - luma(x - 1, y - 1)
+            - luma(x - 1, y)
+            - luma(x, y - 1);
+        let offset = edge / 3.0 * strength;
+        for c in 0..3 {
+            pixel[c] = to_u8(pixel[c] as f64 / 255.0 + offset);
+        }
+    }
+    result
+}
+
+/// Jitter the brightness of each visible pixel by up to `amount`
+/// (0.0-1.0), drawing from a stream seeded by `seed`.
+///
+/// The same seed always gives the same pattern. Without one, the pattern
+/// follows the global seed (`pxl render --seed`).
+pub fn pixel_noise(image: &RgbaImage, amount: f64, seed: Option<u64>) -> RgbaImage {
+    let mut rng = Rng::for_object("pixel-noise", seed);
+    let mut result = image.clone();
+    for pixel in result.pixels_mut() {
+        // Draw for every pixel so the pattern doesn't shift with the shape
+        let offset = rng.range(-amount, amount);
+        if pixel[3] == 0 {
+            continue;
+        }
+        for c in 0..3 {
+            pixel[c] = to_u8(pixel[c] as f64 / 255.0 + offset);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dot() -> RgbaImage {
+        let mut image = RgbaImage::new(5, 5);
+        image.put_pixel(2, 2, Rgba([200, 100, 50, 255]));
+        image
+    }
+
+    #[test]
+    fn test_glow_spills_into_transparent_pixels() {
+        let image = glow(&dot(), 1, None);
+        assert_eq!(*image.get_pixel(2, 2), Rgba([222, 111, 56, 255]), "center brightens");
+        let halo = image.get_pixel(1, 2);
+        assert!(halo[3] > 0, "neighbors pick up glow");
+        assert_eq!([halo[0], halo[1], halo[2]], [200, 100, 50], "glow keeps the sprite's hue");
+        assert_eq!(image.get_pixel(0, 0)[3], 0, "outside the radius stays clear");
+    }
+
+    #[test]
+    fn test_glow_color() {
+        let image = glow(&dot(), 1, Some(Rgba([255, 255, 0, 255])));
+        let halo = image.get_pixel(2, 1);
+        assert_eq!([halo[0], halo[1], halo[2]], [255, 255, 0]);
+        assert_eq!(glow(&dot(), 0, Some(Rgba([0, 0, 0, 0]))), dot(), "invisible glow is a no-op");
+    }
+
+    #[test]
+    fn test_emboss_lights_top_left() {
+        let mut image = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 0]));
+        for y in 1..3 {
+            for x in 1..3 {
+                image.put_pixel(x, y, Rgba([128, 128, 128, 255]));
+            }
+        }
+        let embossed = emboss(&image, 1.0);
+        assert!(embossed.get_pixel(1, 1)[0] > 128, "top-left corner catches light");
+        assert!(embossed.get_pixel(2, 2)[0] < 128, "bottom-right corner falls in shadow");
+        assert_eq!(embossed.get_pixel(0, 0)[3], 0, "transparent pixels stay clear");
+        assert_eq!(emboss(&image, 0.0), image);
+    }
+
+    #[test]
+    fn test_pixel_noise_is_deterministic() {
+        let image = RgbaImage::from_pixel(8, 8, Rgba([128, 128, 128, 255]));
+        let a = pixel_noise(&image, 0.1, Some(7));
+        assert_eq!(a, pixel_noise(&image, 0.1, Some(7)));
+        assert_ne!(a, pixel_noise(&image, 0.1, Some(8)));
+        assert_ne!(a, image);
+        for pixel in a.pixels() {
+            assert!((102..=154).contains(&pixel[0]), "within amount: {}", pixel[0]);
+            assert_eq!(pixel[3], 255);
+        }
+        assert_eq!(pixel_noise(&dot(), 0.5, None).get_pixel(0, 0)[3], 0);
+    }
+}
@@ -4,7 +4,7 @@
 
 use std::collections::HashMap;
 
-use super::parsing::{parse_transform_str, parse_transform_value};
+use super::parsing::parse_transform_spec;
 use super::types::{Transform, TransformError};
 
 /// Error type for expression evaluation
@@ -403,7 +403,7 @@ pub fn generate_frame_transforms(
     // Handle simple ops-only transform
     if transform_def.is_simple() {
         if let Some(ops) = &transform_def.ops {
-            return ops.iter().map(parse_transform_spec_internal).collect();
+            return ops.iter().map(parse_transform_spec).collect();
         }
         return Ok(vec![]);
     }
@@ -414,7 +414,7 @@ pub fn generate_frame_transforms(
             let cycle_len = cycle.len();
             if cycle_len > 0 {
                 let cycle_index = (frame as usize) % cycle_len;
-                return cycle[cycle_index].iter().map(parse_transform_spec_internal).collect();
+                return cycle[cycle_index].iter().map(parse_transform_spec).collect();
             }
         }
         return Ok(vec![]);
@@ -486,7 +486,7 @@ pub fn generate_frame_transforms(
 
     // Handle compose (parallel composition)
     if let Some(compose) = &transform_def.compose {
-        return compose.iter().map(parse_transform_spec_internal).collect();
+        return compose.iter().map(parse_transform_spec).collect();
     }
 
     Ok(vec![])
@@ -549,24 +549,6 @@ fn property_values_to_transforms(
     Ok(transforms)
 }
 
-/// Parse a TransformSpec into a Transform (internal version for this module).
-fn parse_transform_spec_internal(
-    spec: &crate::models::TransformSpec,
-) -> Result<Transform, TransformError> {
-    match spec {
-        crate::models::TransformSpec::String(s) => parse_transform_str(s),
-        crate::models::TransformSpec::Object { op, params } => {
-            // Convert params to serde_json::Value object for parsing
-            let mut obj = serde_json::Map::new();
-            obj.insert("op".to_string(), serde_json::Value::String(op.clone()));
-            for (k, v) in params {
-                obj.insert(k.clone(), v.clone());
-            }
-            parse_transform_value(&serde_json::Value::Object(obj))
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
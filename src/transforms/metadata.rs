@@ -0,0 +1,371 @@
+//! Metadata remapping for image transforms
+//!
+//! Keeps a sprite's origin, attach points, collision boxes, slices and
+//! nine-slice borders lined up with its pixels when transforms mirror, rotate, scale or
+//! move them, and traces single pixels back through them. Points are pixel-grid corners, so `mirror-h` maps an origin `x`
+//! to `width - x` and a box at `x` with width `w` to `width - x - w`.
+
+use std::collections::HashMap;
+
+use crate::models::{CollisionBox, NineSlice, SpriteMetadata};
+
+use super::types::Transform;
+
+/// Axis-aligned affine map: `x' = a*x + b*y + tx`, `y' = c*x + d*y + ty`
+#[derive(Debug, Clone, Copy)]
+struct Affine {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    tx: f64,
+    ty: f64,
+}
+
+impl Affine {
+    fn translate(tx: f64, ty: f64) -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx, ty }
+    }
+
+    fn apply(&self, [x, y]: [f64; 2]) -> [f64; 2] {
+        [self.a * x + self.b * y + self.tx, self.c * x + self.d * y + self.ty]
+    }
+
+    /// Map a point back to where it was before the transform
+    fn invert(&self, [x, y]: [f64; 2]) -> [f64; 2] {
+        let det = self.a * self.d - self.b * self.c;
+        let (x, y) = (x - self.tx, y - self.ty);
+        [(self.d * x - self.b * y) / det, (self.a * y - self.c * x) / det]
+    }
+
+    /// Map a rectangle by its corners, returning `[min_x, min_y, max_x, max_y]`
+    fn apply_rect(&self, [x0, y0, x1, y1]: [f64; 4]) -> [f64; 4] {
+        let [ax, ay] = self.apply([x0, y0]);
+        let [bx, by] = self.apply([x1, y1]);
+        [ax.min(bx), ay.min(by), ax.max(bx), ay.max(by)]
+    }
+}
+
+/// How a transform moves sprite coordinates
+enum Remap {
+    /// Pixels stay where they are (color adjustments, animation transforms)
+    Identity,
+    /// Pixels move by an axis-aligned affine map
+    Affine(Affine),
+    /// Pixels are repeated or sheared, so metadata has no single new position
+    Ambiguous(&'static str),
+}
+
+/// Work out how `transform` moved coordinates when it turned a `from`-sized
+/// image into a `to`-sized one
+fn remap(transform: &Transform, from: (u32, u32), to: (u32, u32)) -> Remap {
+    let (w, h) = (from.0 as f64, from.1 as f64);
+    let affine = |a, b, c, d, tx, ty| Remap::Affine(Affine { a, b, c, d, tx, ty });
+    match transform {
+        Transform::MirrorH => affine(-1.0, 0.0, 0.0, 1.0, w, 0.0),
+        Transform::MirrorV => affine(1.0, 0.0, 0.0, -1.0, 0.0, h),
+        Transform::Rotate { degrees: 90 } => affine(0.0, -1.0, 1.0, 0.0, h, 0.0),
+        Transform::Rotate { degrees: 180 } => affine(-1.0, 0.0, 0.0, -1.0, w, h),
+        Transform::Rotate { degrees: 270 } => affine(0.0, 1.0, -1.0, 0.0, 0.0, w),
+        Transform::Scale { .. } if from.0 > 0 && from.1 > 0 => {
+            affine(to.0 as f64 / w, 0.0, 0.0, to.1 as f64 / h, 0.0, 0.0)
+        }
+        Transform::Pad { size } => Remap::Affine(Affine::translate(*size as f64, *size as f64)),
+        Transform::Outline { width, .. } => {
+            Remap::Affine(Affine::translate(*width as f64, *width as f64))
+        }
+        Transform::Shadow { x, y, .. } => {
+            Remap::Affine(Affine::translate((-x).max(0) as f64, (-y).max(0) as f64))
+        }
+        Transform::Shift { x, y } => Remap::Affine(Affine::translate(*x as f64, *y as f64)),
+        Transform::Crop { x, y, .. } => {
+            Remap::Affine(Affine::translate(-(*x as f64), -(*y as f64)))
+        }
+        Transform::Tile { .. } => Remap::Ambiguous("tiling repeats the sprite"),
+        Transform::SkewX { .. } | Transform::SkewY { .. } => {
+            Remap::Ambiguous("skewing shears the sprite")
+        }
+        _ => Remap::Identity,
+    }
+}
+
+/// Round a mapped coordinate, noting whether it landed between pixels
+fn snap(value: f64, fractional: &mut bool) -> i32 {
+    let rounded = value.round();
+    if (value - rounded).abs() > 1e-6 {
+        *fractional = true;
+    }
+    rounded as i32
+}
+
+fn point_inside([x, y]: [i32; 2], (w, h): (u32, u32)) -> bool {
+    x >= 0 && y >= 0 && x <= w as i32 && y <= h as i32
+}
+
+fn box_inside(b: &CollisionBox, (w, h): (u32, u32)) -> bool {
+    b.x >= 0 && b.y >= 0 && b.x + b.w as i32 <= w as i32 && b.y + b.h as i32 <= h as i32
+}
+
+/// Remap sprite metadata through a transform that turned a `from`-sized image
+/// into a `to`-sized one.
+///
+/// Mirrors, rotations, scaling, padding, outlines, shadows, shifts and crops
+/// move the origin, attach points, boxes and slices with the pixels. Tiling and
+/// skewing leave metadata where it was, since there is no single place for it
+/// to go, and say so in a warning. Warnings are also returned when a point or
+/// box no longer lands on whole pixels, or when a transform pushes something
+/// that was inside the sprite outside of it.
+pub fn transform_metadata(
+    metadata: &SpriteMetadata,
+    transform: &Transform,
+    from: (u32, u32),
+    to: (u32, u32),
+) -> (SpriteMetadata, Vec<String>) {
+    let affine = match remap(transform, from, to) {
+        Remap::Identity => return (metadata.clone(), Vec::new()),
+        Remap::Ambiguous(reason) => {
+            return (metadata.clone(), vec![format!("metadata left unchanged because {}", reason)]);
+        }
+        Remap::Affine(affine) => affine,
+    };
+
+    let mut warnings = Vec::new();
+    let mut map_point = |label: &str, [x, y]: [i32; 2]| {
+        let [mx, my] = affine.apply([x as f64, y as f64]);
+        let mut fractional = false;
+        let point = [snap(mx, &mut fractional), snap(my, &mut fractional)];
+        if fractional {
+            warnings.push(format!("{} lands between pixels and was rounded", label));
+        }
+        if point_inside([x, y], from) && !point_inside(point, to) {
+            warnings.push(format!("{} falls outside the transformed sprite", label));
+        }
+        point
+    };
+    let origin = metadata.origin.map(|p| map_point("origin", p));
+    let attach_in = metadata.attach_in.map(|p| map_point("attach_in", p));
+    let attach_out = metadata.attach_out.map(|p| map_point("attach_out", p));
+
+    let boxes =
+        metadata.boxes.as_ref().map(|b| map_boxes("box", b, &affine, from, to, &mut warnings));
+    let slices =
+        metadata.slices.as_ref().map(|b| map_boxes("slice", b, &affine, from, to, &mut warnings));
+
+    (SpriteMetadata { origin, boxes, attach_in, attach_out, slices }, warnings)
+}
+
+/// Remap named rectangles (collision boxes or slices) through an affine map,
+/// warning about rounding and boxes pushed outside the sprite.
+fn map_boxes(
+    kind: &str,
+    boxes: &HashMap<String, CollisionBox>,
+    affine: &Affine,
+    from: (u32, u32),
+    to: (u32, u32),
+    warnings: &mut Vec<String>,
+) -> HashMap<String, CollisionBox> {
+    let mut names: Vec<&String> = boxes.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let b = &boxes[name];
+            let corners =
+                [b.x as f64, b.y as f64, (b.x + b.w as i32) as f64, (b.y + b.h as i32) as f64];
+            let [x0, y0, x1, y1] = affine.apply_rect(corners);
+            let mut fractional = false;
+            let (x, y) = (snap(x0, &mut fractional), snap(y0, &mut fractional));
+            let w = (snap(x1, &mut fractional) - x).max(0) as u32;
+            let h = (snap(y1, &mut fractional) - y).max(0) as u32;
+            let mapped = CollisionBox { x, y, w, h };
+            if fractional {
+                warnings.push(format!("{} '{}' lands between pixels and was rounded", kind, name));
+            }
+            if box_inside(b, from) && !box_inside(&mapped, to) {
+                warnings
+                    .push(format!("{} '{}' extends outside the transformed sprite", kind, name));
+            }
+            (name.clone(), mapped)
+        })
+        .collect()
+}
+
+/// Remap nine-slice borders through a transform that turned a `from`-sized
+/// image into a `to`-sized one.
+///
+/// Borders follow their edges, so `mirror-h` swaps `left` and `right` and
+/// `rotate:90` turns `left` into `top`. Padding and outlines widen every
+/// border. Tiling and skewing leave no stretchable center, so the nine-slice
+/// is dropped with a warning.
+pub fn transform_nine_slice(
+    nine_slice: &NineSlice,
+    transform: &Transform,
+    from: (u32, u32),
+    to: (u32, u32),
+) -> (Option<NineSlice>, Vec<String>) {
+    let affine = match remap(transform, from, to) {
+        Remap::Identity => return (Some(nine_slice.clone()), Vec::new()),
+        Remap::Ambiguous(reason) => {
+            return (None, vec![format!("nine_slice dropped because {}", reason)]);
+        }
+        Remap::Affine(affine) => affine,
+    };
+
+    // Map the stretchable center and measure the new borders around it
+    let center = [
+        nine_slice.left as f64,
+        nine_slice.top as f64,
+        from.0 as f64 - nine_slice.right as f64,
+        from.1 as f64 - nine_slice.bottom as f64,
+    ];
+    let [x0, y0, x1, y1] = affine.apply_rect(center);
+    let mut fractional = false;
+    let borders = [
+        snap(x0, &mut fractional),
+        snap(to.0 as f64 - x1, &mut fractional),
+        snap(y0, &mut fractional),
+        snap(to.1 as f64 - y1, &mut fractional),
+    ];
+
+    let mut warnings = Vec::new();
+    if fractional {
+        warnings.push("nine_slice borders land between pixels and were rounded".to_string());
+    }
+    if borders.iter().any(|&border| border < 0) {
+        warnings.push("nine_slice borders were cut off by the transform".to_string());
+    }
+    let [left, right, top, bottom] = borders.map(|border| border.max(0) as u32);
+    (Some(NineSlice { left, right, top, bottom }), warnings)
+}
+
+/// Find the pixel of a `from`-sized image that `transform` moved to pixel
+/// `(x, y)` of the `to`-sized result.
+///
+/// Returns `Ok(None)` when the pixel lies outside the original image (added
+/// by padding, an outline or a shadow), and an error naming the reason when
+/// the transform has no single source for it (skewing). Tiled pixels trace
+/// back to the tile they repeat.
+pub fn source_pixel(
+    transform: &Transform,
+    from: (u32, u32),
+    to: (u32, u32),
+    [x, y]: [u32; 2],
+) -> Result<Option<[u32; 2]>, &'static str> {
+    if let Transform::Tile { .. } = transform {
+        return Ok((from.0 > 0 && from.1 > 0).then(|| [x % from.0, y % from.1]));
+    }
+    let affine = match remap(transform, from, to) {
+        Remap::Identity => return Ok(Some([x, y])),
+        Remap::Ambiguous(reason) => return Err(reason),
+        Remap::Affine(affine) => affine,
+    };
+
+    // Follow the pixel's center, which stays inside its source pixel
+    let [sx, sy] = affine.invert([x as f64 + 0.5, y as f64 + 0.5]).map(f64::floor);
+    let inside = sx >= 0.0 && sy >= 0.0 && sx < from.0 as f64 && sy < from.1 as f64;
+    Ok(inside.then_some([sx as u32, sy as u32]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> SpriteMetadata {
+        SpriteMetadata {
+            origin: Some([2, 8]),
+            boxes: Some(HashMap::from([(
+                "hit".to_string(),
+                CollisionBox { x: 5, y: 1, w: 3, h: 2 },
+            )])),
+            attach_in: None,
+            attach_out: Some([8, 4]),
+            slices: Some(HashMap::from([(
+                "icon".to_string(),
+                CollisionBox { x: 0, y: 0, w: 2, h: 8 },
+            )])),
+        }
+    }
+
+    #[test]
+    fn test_mirror_h_flips_boxes_and_anchors() {
+        let (meta, warnings) = transform_metadata(&metadata(), &Transform::MirrorH, (8, 8), (8, 8));
+        assert!(warnings.is_empty());
+        assert_eq!(meta.origin, Some([6, 8]));
+        assert_eq!(meta.attach_out, Some([0, 4]));
+        assert_eq!(meta.boxes.unwrap()["hit"], CollisionBox { x: 0, y: 1, w: 3, h: 2 });
+        assert_eq!(meta.slices.unwrap()["icon"], CollisionBox { x: 6, y: 0, w: 2, h: 8 });
+    }
+
+    #[test]
+    fn test_rotate_swaps_box_dimensions() {
+        // 8x4 sprite rotated clockwise becomes 4x8
+        let rotate = Transform::Rotate { degrees: 90 };
+        let (meta, warnings) = transform_metadata(&metadata(), &rotate, (8, 4), (4, 8));
+        assert_eq!(meta.boxes.unwrap()["hit"], CollisionBox { x: 1, y: 5, w: 2, h: 3 });
+        assert_eq!(meta.attach_out, Some([0, 8]));
+        // The origin was already below the 8x4 sprite, so it isn't reported
+        assert_eq!(meta.origin, Some([-4, 2]));
+        assert!(warnings.is_empty());
+
+        let ccw = Transform::Rotate { degrees: 270 };
+        let (meta, _) = transform_metadata(&metadata(), &ccw, (8, 8), (8, 8));
+        assert_eq!(meta.boxes.unwrap()["hit"], CollisionBox { x: 1, y: 0, w: 2, h: 3 });
+    }
+
+    #[test]
+    fn test_ambiguous_and_lossy_transforms_warn() {
+        let tile = Transform::Tile { w: 2, h: 1 };
+        let (meta, warnings) = transform_metadata(&metadata(), &tile, (8, 8), (16, 8));
+        assert_eq!(meta, metadata());
+        assert_eq!(warnings, vec!["metadata left unchanged because tiling repeats the sprite"]);
+
+        let scale = Transform::Scale { x: 0.5, y: 0.5 };
+        let (meta, warnings) = transform_metadata(&metadata(), &scale, (8, 8), (4, 4));
+        assert_eq!(meta.origin, Some([1, 4]));
+        assert!(warnings.iter().any(|w| w.contains("box 'hit' lands between pixels")));
+
+        let crop = Transform::Crop { x: 4, y: 0, w: 4, h: 8 };
+        let (_, warnings) = transform_metadata(&metadata(), &crop, (8, 8), (4, 8));
+        assert!(warnings.iter().any(|w| w.contains("origin falls outside")));
+    }
+
+    #[test]
+    fn test_nine_slice_borders_follow_edges() {
+        let ns = NineSlice { left: 1, right: 2, top: 3, bottom: 4 };
+        let (mirrored, _) = transform_nine_slice(&ns, &Transform::MirrorH, (16, 16), (16, 16));
+        assert_eq!(mirrored, Some(NineSlice { left: 2, right: 1, top: 3, bottom: 4 }));
+
+        let rotate = Transform::Rotate { degrees: 90 };
+        let (rotated, _) = transform_nine_slice(&ns, &rotate, (16, 12), (12, 16));
+        assert_eq!(rotated, Some(NineSlice { left: 4, right: 3, top: 1, bottom: 2 }));
+
+        let pad = Transform::Pad { size: 2 };
+        let (padded, _) = transform_nine_slice(&ns, &pad, (16, 16), (20, 20));
+        assert_eq!(padded, Some(NineSlice { left: 3, right: 4, top: 5, bottom: 6 }));
+
+        let skew = Transform::SkewX { degrees: 20.0 };
+        let (skewed, warnings) = transform_nine_slice(&ns, &skew, (16, 16), (22, 16));
+        assert_eq!(skewed, None);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_source_pixel() {
+        // mirror-h on an 8-wide sprite: column 6 came from column 1
+        assert_eq!(source_pixel(&Transform::MirrorH, (8, 4), (8, 4), [6, 2]), Ok(Some([1, 2])));
+        // rotate:90 turns an 8x4 sprite into 4x8; the top-right pixel was top-left
+        let rotate = Transform::Rotate { degrees: 90 };
+        assert_eq!(source_pixel(&rotate, (8, 4), (4, 8), [3, 0]), Ok(Some([0, 0])));
+        // scale:2 doubles every pixel
+        let scale = Transform::Scale { x: 2.0, y: 2.0 };
+        assert_eq!(source_pixel(&scale, (4, 4), (8, 8), [5, 3]), Ok(Some([2, 1])));
+        // Padding pixels have no source
+        let pad = Transform::Pad { size: 2 };
+        assert_eq!(source_pixel(&pad, (4, 4), (8, 8), [1, 1]), Ok(None));
+        assert_eq!(source_pixel(&pad, (4, 4), (8, 8), [2, 3]), Ok(Some([0, 1])));
+        // Tiles repeat the sprite
+        let tile = Transform::Tile { w: 2, h: 1 };
+        assert_eq!(source_pixel(&tile, (4, 4), (8, 4), [5, 2]), Ok(Some([1, 2])));
+        assert!(source_pixel(&Transform::SkewX { degrees: 20.0 }, (4, 4), (6, 4), [0, 0]).is_err());
+    }
+}
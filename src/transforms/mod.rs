@@ -6,18 +6,24 @@
 //! # Module Structure
 //!
 //! - [`types`] - Core transform types and error definitions
+//! - [`adjust`] - Color adjustments (tint, hue, saturation, posterize)
+//! - [`effects`] - Self-blend effects (glow, emboss, pixel noise)
 //! - [`dither`] - Dither patterns for pixel art effects
 //! - [`parsing`] - Transform parsing from strings and JSON
 //! - [`css`] - CSS transform string parsing
 //! - [`apply`] - Transform application to images and animations
 //! - [`expression`] - Expression evaluation for keyframe animations
 //! - [`anchor`] - Anchor-preserving scaling for pixel art
+//! - [`metadata`] - Remapping sprite metadata to follow transformed pixels
 
+pub mod adjust;
 pub mod anchor;
 pub mod apply;
 pub mod css;
 pub mod dither;
+pub mod effects;
 pub mod expression;
+pub mod metadata;
 pub mod parsing;
 pub mod types;
 
@@ -32,7 +38,10 @@ pub use dither::{DitherPattern, GradientDirection};
 pub use expression::{
     generate_frame_transforms, interpolate_keyframes, ExpressionError, ExpressionEvaluator,
 };
-pub use parsing::{parse_token_pair, parse_transform_str, parse_transform_value};
+pub use metadata::{source_pixel, transform_metadata, transform_nine_slice};
+pub use parsing::{
+    parse_token_pair, parse_transform_spec, parse_transform_str, parse_transform_value,
+};
 pub use types::{explain_transform, Transform, TransformError};
 
 /// Result type alias for transform operations.
@@ -9,6 +9,10 @@ use std::collections::HashMap;
 
 use super::dither::{DitherPattern, GradientDirection};
 use super::types::{Transform, TransformError};
+use crate::models::TransformSpec;
+
+/// Emboss strength when none is given.
+const DEFAULT_EMBOSS_STRENGTH: f64 = 0.5;
 
 /// Parse transform from string syntax: "mirror-h", "rotate:90", "tile:3x2"
 ///
@@ -190,7 +194,76 @@ pub fn parse_transform_str(s: &str) -> Result<Transform, TransformError> {
             parse_subpixel_str(subpixel_params)
         }
 
-        _ => Err(TransformError::UnknownOperation(op.to_string())),
+        // Color adjustment
+        // String syntax: tint:color[,strength], e.g. "tint:#FF8800,0.3"
+        "tint" => {
+            let tint_params = params.ok_or_else(|| TransformError::MissingParameter {
+                op: "tint".to_string(),
+                param: "color[,strength]".to_string(),
+            })?;
+            parse_tint_str(tint_params)
+        }
+        "hue-rotate" | "huerotate" | "hue" => {
+            let degrees = params.ok_or_else(|| TransformError::MissingParameter {
+                op: "hue-rotate".to_string(),
+                param: "degrees".to_string(),
+            })?;
+            let degrees = degrees.trim();
+            let degrees = degrees.strip_suffix("deg").unwrap_or(degrees);
+            let degrees = degrees.strip_suffix('°').unwrap_or(degrees);
+            Ok(Transform::HueRotate { degrees: parse_f64("hue-rotate", "degrees", degrees)? })
+        }
+        "saturate" | "saturation" => {
+            Ok(Transform::Saturate { amount: parse_amount_str("saturate", params)? })
+        }
+        "brightness" => {
+            Ok(Transform::Brightness { amount: parse_amount_str("brightness", params)? })
+        }
+        "contrast" => Ok(Transform::Contrast { amount: parse_amount_str("contrast", params)? }),
+        "posterize" => {
+            let levels = params.ok_or_else(|| TransformError::MissingParameter {
+                op: "posterize".to_string(),
+                param: "levels".to_string(),
+            })?;
+            let levels =
+                levels.trim().parse::<u8>().map_err(|_| TransformError::InvalidParameter {
+                    op: "posterize".to_string(),
+                    message: format!("cannot parse '{}' as levels", levels),
+                })?;
+            validate_levels(levels)?;
+            Ok(Transform::Posterize { levels })
+        }
+
+        // Self-blend effects
+        // String syntax: glow[:radius[,color]], e.g. "glow:2,#FFAA5580"
+        "glow" => parse_glow_str(params),
+        "emboss" => {
+            let strength = match params {
+                Some(strength) => parse_f64("emboss", "strength", strength)?,
+                None => DEFAULT_EMBOSS_STRENGTH,
+            };
+            validate_amount("emboss", strength)?;
+            Ok(Transform::Emboss { strength })
+        }
+        // String syntax: pixel-noise:amount[:seed], e.g. "pixel-noise:0.05:42"
+        "pixel-noise" | "noise" => {
+            let noise_params = params.ok_or_else(|| TransformError::MissingParameter {
+                op: "pixel-noise".to_string(),
+                param: "amount[:seed]".to_string(),
+            })?;
+            let (amount, seed) = match noise_params.split_once(':') {
+                Some((amount, seed)) => (amount, Some(parse_seed(seed))),
+                None => (noise_params, None),
+            };
+            let amount = parse_f64("pixel-noise", "amount", amount)?;
+            validate_noise_amount(amount)?;
+            Ok(Transform::PixelNoise { amount, seed })
+        }
+
+        _ => {
+            let params = params.map(|args| HashMap::from([("args".to_string(), args.into())]));
+            plugin_transform(op, params.unwrap_or_default())
+        }
     }
 }
 
@@ -220,6 +293,14 @@ pub fn parse_transform_value(value: &Value) -> Result<Transform, TransformError>
     }
 }
 
+/// Parse a transform spec from a format attribute (string or object syntax)
+pub fn parse_transform_spec(spec: &TransformSpec) -> Result<Transform, TransformError> {
+    match spec {
+        TransformSpec::String(s) => parse_transform_str(s),
+        TransformSpec::Object { op, params } => parse_transform_object(op, params),
+    }
+}
+
 /// Parse transform from object syntax with explicit parameters
 fn parse_transform_object(
     op: &str,
@@ -485,7 +566,83 @@ fn parse_transform_object(
             Ok(Transform::Subpixel { x, y })
         }
 
-        _ => Err(TransformError::UnknownOperation(op.to_string())),
+        // Color adjustment
+        "tint" => {
+            let color = params
+                .get("color")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| TransformError::MissingParameter {
+                    op: "tint".to_string(),
+                    param: "color".to_string(),
+                })?
+                .to_string();
+            let strength = params.get("strength").and_then(|v| v.as_f64()).unwrap_or(0.5);
+            validate_strength(strength)?;
+            Ok(Transform::Tint { color, strength })
+        }
+        "hue-rotate" | "huerotate" | "hue" => {
+            let degrees = get_f64_param(params, "degrees", "hue-rotate")?;
+            Ok(Transform::HueRotate { degrees })
+        }
+        "saturate" | "saturation" => {
+            let amount = get_f64_param(params, "amount", "saturate")?;
+            validate_amount("saturate", amount)?;
+            Ok(Transform::Saturate { amount })
+        }
+        "brightness" => {
+            let amount = get_f64_param(params, "amount", "brightness")?;
+            validate_amount("brightness", amount)?;
+            Ok(Transform::Brightness { amount })
+        }
+        "contrast" => {
+            let amount = get_f64_param(params, "amount", "contrast")?;
+            validate_amount("contrast", amount)?;
+            Ok(Transform::Contrast { amount })
+        }
+        "posterize" => {
+            let levels = get_u32_param(params, "levels", "posterize")?;
+            let levels = u8::try_from(levels).unwrap_or(u8::MAX);
+            validate_levels(levels)?;
+            Ok(Transform::Posterize { levels })
+        }
+
+        // Self-blend effects
+        "glow" => {
+            let radius = params.get("radius").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+            let color = params.get("color").and_then(|v| v.as_str()).map(str::to_string);
+            Ok(Transform::Glow { radius, color })
+        }
+        "emboss" => {
+            let strength =
+                params.get("strength").and_then(|v| v.as_f64()).unwrap_or(DEFAULT_EMBOSS_STRENGTH);
+            validate_amount("emboss", strength)?;
+            Ok(Transform::Emboss { strength })
+        }
+        "pixel-noise" | "noise" => {
+            let amount = get_f64_param(params, "amount", "pixel-noise")?;
+            validate_noise_amount(amount)?;
+            let seed = match params.get("seed") {
+                Some(Value::String(seed)) => Some(parse_seed(seed)),
+                Some(seed) => {
+                    Some(seed.as_u64().ok_or_else(|| TransformError::InvalidParameter {
+                        op: "pixel-noise".to_string(),
+                        message: "seed must be a non-negative integer or a string".to_string(),
+                    })?)
+                }
+                None => None,
+            };
+            Ok(Transform::PixelNoise { amount, seed })
+        }
+
+        _ => plugin_transform(op, params.clone()),
+    }
+}
+
+/// A transform from a registered [`crate::plugin::TransformPlugin`].
+fn plugin_transform(op: &str, params: HashMap<String, Value>) -> Result<Transform, TransformError> {
+    match crate::plugin::transform(op) {
+        Some(_) => Ok(Transform::Plugin { name: op.to_lowercase(), params }),
+        None => Err(TransformError::UnknownOperation(op.to_string())),
     }
 }
 
@@ -806,6 +963,108 @@ fn parse_subpixel_str(s: &str) -> Result<Transform, TransformError> {
     Ok(Transform::Subpixel { x, y })
 }
 
+/// Parse tint from string syntax: color[,strength] (strength defaults to 0.5)
+/// Example: "#FF8800,0.3" or "{night}"
+fn parse_tint_str(s: &str) -> Result<Transform, TransformError> {
+    let s = s.trim();
+    // Colors like rgb(1, 2, 3) contain commas, so only a trailing number is a strength
+    let (color, strength) = match s.rsplit_once(',') {
+        Some((color, strength)) if !strength.contains(')') => {
+            (color.trim(), parse_f64("tint", "strength", strength)?)
+        }
+        _ => (s, 0.5),
+    };
+    if color.is_empty() {
+        return Err(TransformError::MissingParameter {
+            op: "tint".to_string(),
+            param: "color".to_string(),
+        });
+    }
+    validate_strength(strength)?;
+    Ok(Transform::Tint { color: color.to_string(), strength })
+}
+
+/// Parse glow from string syntax: radius[,color]
+fn parse_glow_str(params: Option<&str>) -> Result<Transform, TransformError> {
+    let Some(params) = params.map(str::trim).filter(|p| !p.is_empty()) else {
+        return Ok(Transform::Glow { radius: 1, color: None });
+    };
+    // Only the first comma splits; colors like rgb(1, 2, 3) keep theirs
+    let (radius, color) = match params.split_once(',') {
+        Some((radius, color)) => (radius, Some(color.trim().to_string())),
+        None => (params, None),
+    };
+    let radius = radius.trim().parse::<u32>().map_err(|_| TransformError::InvalidParameter {
+        op: "glow".to_string(),
+        message: format!("cannot parse '{}' as radius", radius.trim()),
+    })?;
+    Ok(Transform::Glow { radius, color: color.filter(|c| !c.is_empty()) })
+}
+
+/// Parse a noise seed: an integer, or any other text hashed to one.
+fn parse_seed(s: &str) -> u64 {
+    let s = s.trim();
+    s.parse::<u64>().unwrap_or_else(|_| crate::rng::name_hash(s))
+}
+
+/// Parse the non-negative amount of a saturate/brightness/contrast string.
+fn parse_amount_str(op: &str, params: Option<&str>) -> Result<f64, TransformError> {
+    let amount = params.ok_or_else(|| TransformError::MissingParameter {
+        op: op.to_string(),
+        param: "amount".to_string(),
+    })?;
+    let amount = parse_f64(op, "amount", amount)?;
+    validate_amount(op, amount)?;
+    Ok(amount)
+}
+
+fn parse_f64(op: &str, param: &str, s: &str) -> Result<f64, TransformError> {
+    s.trim().parse::<f64>().map_err(|_| TransformError::InvalidParameter {
+        op: op.to_string(),
+        message: format!("cannot parse '{}' as {}", s.trim(), param),
+    })
+}
+
+fn validate_strength(strength: f64) -> Result<(), TransformError> {
+    if !(0.0..=1.0).contains(&strength) {
+        return Err(TransformError::InvalidParameter {
+            op: "tint".to_string(),
+            message: "strength must be between 0.0 and 1.0".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_amount(op: &str, amount: f64) -> Result<(), TransformError> {
+    if amount < 0.0 {
+        return Err(TransformError::InvalidParameter {
+            op: op.to_string(),
+            message: "amount must not be negative".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_noise_amount(amount: f64) -> Result<(), TransformError> {
+    if !(0.0..=1.0).contains(&amount) {
+        return Err(TransformError::InvalidParameter {
+            op: "pixel-noise".to_string(),
+            message: "amount must be between 0.0 and 1.0".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_levels(levels: u8) -> Result<(), TransformError> {
+    if levels < 2 {
+        return Err(TransformError::InvalidParameter {
+            op: "posterize".to_string(),
+            message: "levels must be at least 2".to_string(),
+        });
+    }
+    Ok(())
+}
+
 /// Parse a token pair like "{dark},{light}" or "dark,light"
 /// Handles braces correctly for tokens like {token_name}
 pub fn parse_token_pair(s: &str) -> Option<(String, String)> {
@@ -859,6 +1118,17 @@ fn get_u32_param(
     })
 }
 
+fn get_f64_param(
+    params: &HashMap<String, Value>,
+    key: &str,
+    op: &str,
+) -> Result<f64, TransformError> {
+    params.get(key).and_then(|v| v.as_f64()).ok_or_else(|| TransformError::MissingParameter {
+        op: op.to_string(),
+        param: key.to_string(),
+    })
+}
+
 fn get_i32_param(
     params: &HashMap<String, Value>,
     key: &str,
@@ -1225,4 +1495,94 @@ mod tests {
         // Missing first token
         assert_eq!(parse_token_pair(",{b}"), None);
     }
+
+    #[test]
+    fn test_parse_color_adjustments_str() {
+        assert_eq!(
+            parse_transform_str("tint:#FF8800,0.3").unwrap(),
+            Transform::Tint { color: "#FF8800".to_string(), strength: 0.3 }
+        );
+        assert_eq!(
+            parse_transform_str("tint:rgb(10, 20, 30)").unwrap(),
+            Transform::Tint { color: "rgb(10, 20, 30)".to_string(), strength: 0.5 }
+        );
+        assert_eq!(
+            parse_transform_str("hue-rotate:90deg").unwrap(),
+            Transform::HueRotate { degrees: 90.0 }
+        );
+        assert_eq!(
+            parse_transform_str("saturate:0.5").unwrap(),
+            Transform::Saturate { amount: 0.5 }
+        );
+        assert_eq!(parse_transform_str("posterize:4").unwrap(), Transform::Posterize { levels: 4 });
+
+        assert!(parse_transform_str("tint:#FF8800,1.5").is_err());
+        assert!(parse_transform_str("brightness:-1").is_err());
+        assert!(parse_transform_str("posterize:1").is_err());
+        assert!(parse_transform_str("contrast").is_err());
+    }
+
+    #[test]
+    fn test_parse_color_adjustments_spec() {
+        let spec: TransformSpec =
+            serde_json::from_str(r#"{"op": "tint", "color": "{night}", "strength": 0.4}"#).unwrap();
+        assert_eq!(
+            parse_transform_spec(&spec).unwrap(),
+            Transform::Tint { color: "{night}".to_string(), strength: 0.4 }
+        );
+
+        let spec: TransformSpec =
+            serde_json::from_str(r#"{"op": "brightness", "amount": 0.6}"#).unwrap();
+        assert_eq!(parse_transform_spec(&spec).unwrap(), Transform::Brightness { amount: 0.6 });
+
+        let spec = TransformSpec::String("contrast:1.2".to_string());
+        assert_eq!(parse_transform_spec(&spec).unwrap(), Transform::Contrast { amount: 1.2 });
+    }
+    #[test]
+    fn test_parse_effects_str() {
+        assert_eq!(
+            parse_transform_str("glow:2,#FFAA5580").unwrap(),
+            Transform::Glow { radius: 2, color: Some("#FFAA5580".to_string()) }
+        );
+        assert_eq!(
+            parse_transform_str("glow:1,rgb(255, 200, 0)").unwrap(),
+            Transform::Glow { radius: 1, color: Some("rgb(255, 200, 0)".to_string()) }
+        );
+        assert_eq!(
+            parse_transform_str("glow").unwrap(),
+            Transform::Glow { radius: 1, color: None }
+        );
+        assert_eq!(parse_transform_str("emboss").unwrap(), Transform::Emboss { strength: 0.5 });
+        assert_eq!(parse_transform_str("emboss:1").unwrap(), Transform::Emboss { strength: 1.0 });
+        assert_eq!(
+            parse_transform_str("pixel-noise:0.05:42").unwrap(),
+            Transform::PixelNoise { amount: 0.05, seed: Some(42) }
+        );
+        assert_eq!(
+            parse_transform_str("noise:0.1:grass").unwrap(),
+            Transform::PixelNoise { amount: 0.1, seed: Some(crate::rng::name_hash("grass")) }
+        );
+
+        assert!(parse_transform_str("glow:big").is_err());
+        assert!(parse_transform_str("pixel-noise").is_err());
+        assert!(parse_transform_str("pixel-noise:2").is_err());
+    }
+
+    #[test]
+    fn test_parse_effects_object() {
+        let value = serde_json::json!({"op": "glow", "radius": 3, "color": "{spark}"});
+        assert_eq!(
+            parse_transform_value(&value).unwrap(),
+            Transform::Glow { radius: 3, color: Some("{spark}".to_string()) }
+        );
+        let value = serde_json::json!({"op": "pixel-noise", "amount": 0.05, "seed": 7});
+        assert_eq!(
+            parse_transform_value(&value).unwrap(),
+            Transform::PixelNoise { amount: 0.05, seed: Some(7) }
+        );
+        let value = serde_json::json!({"op": "pixel-noise", "amount": 0.05, "seed": -1});
+        assert!(parse_transform_value(&value).is_err());
+        let value = serde_json::json!({"op": "emboss"});
+        assert_eq!(parse_transform_value(&value).unwrap(), Transform::Emboss { strength: 0.5 });
+    }
 }
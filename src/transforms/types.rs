@@ -163,6 +163,66 @@ pub enum Transform {
         /// Vertical sub-pixel offset (0.0-1.0)
         y: f64,
     },
+
+    // Color adjustment
+    /// Mix colors toward a tint color
+    Tint {
+        /// Color string (`#RRGGBB`, etc.) or palette token
+        color: String,
+        /// Mix amount (0.0 = unchanged, 1.0 = solid color)
+        strength: f64,
+    },
+    /// Rotate hues around the color wheel
+    HueRotate {
+        /// Rotation in degrees
+        degrees: f64,
+    },
+    /// Scale saturation (0.0 = grayscale, 1.0 = unchanged)
+    Saturate {
+        amount: f64,
+    },
+    /// Scale brightness (1.0 = unchanged)
+    Brightness {
+        amount: f64,
+    },
+    /// Scale contrast around mid-gray (1.0 = unchanged)
+    Contrast {
+        amount: f64,
+    },
+    /// Reduce each color channel to a number of levels
+    Posterize {
+        levels: u8,
+    },
+
+    // Self-blend effects
+    /// Add a blurred copy of the sprite to itself
+    Glow {
+        /// Blur radius in pixels
+        radius: u32,
+        /// Glow color or palette token (default: the sprite's own colors)
+        color: Option<String>,
+    },
+    /// Bevel the sprite as if lit from the top-left
+    Emboss {
+        /// Effect strength (1.0 = full emboss)
+        strength: f64,
+    },
+    /// Jitter the brightness of each pixel with seeded noise
+    PixelNoise {
+        /// Maximum brightness change (0.0-1.0)
+        amount: f64,
+        /// Seed for the noise pattern (default: follows the global seed)
+        seed: Option<u64>,
+    },
+
+    // Plugins
+    /// A transform registered through [`crate::plugin::register_transform`]
+    Plugin {
+        /// Registered plugin name (lowercase)
+        name: String,
+        /// Parameters passed to the plugin
+        params: HashMap<String, serde_json::Value>,
+    },
 }
 
 /// Generate a plain-language explanation of a transform's effect
@@ -323,6 +383,44 @@ pub fn explain_transform(transform: &Transform) -> String {
                 x_pct, y_pct
             )
         }
+
+        // Color adjustment
+        Transform::Tint { color, strength } => {
+            format!("Tint toward {} at {}% strength", color, (strength * 100.0) as i32)
+        }
+        Transform::HueRotate { degrees } => format!("Rotate hues by {}°", degrees),
+        Transform::Saturate { amount } => {
+            format!("Set saturation to {}%", (amount * 100.0) as i32)
+        }
+        Transform::Brightness { amount } => {
+            format!("Set brightness to {}%", (amount * 100.0) as i32)
+        }
+        Transform::Contrast { amount } => {
+            format!("Set contrast to {}%", (amount * 100.0) as i32)
+        }
+        Transform::Posterize { levels } => {
+            format!("Posterize to {} levels per color channel", levels)
+        }
+
+        // Self-blend effects
+        Transform::Glow { radius, color } => match color {
+            Some(color) => format!("Glow {} with radius {}px", color, radius),
+            None => format!("Glow with radius {}px", radius),
+        },
+        Transform::Emboss { strength } => {
+            format!("Emboss at {}% strength", (strength * 100.0) as i32)
+        }
+        Transform::PixelNoise { amount, seed } => match seed {
+            Some(seed) => format!("Pixel noise up to {}% (seed {})", (amount * 100.0) as i32, seed),
+            None => format!("Pixel noise up to {}%", (amount * 100.0) as i32),
+        },
+
+        Transform::Plugin { name, .. } => {
+            match crate::plugin::transform(name).map(|p| p.description().to_string()) {
+                Some(description) if !description.is_empty() => description,
+                _ => format!("Apply plugin transform '{}'", name),
+            }
+        }
     }
 }
 
@@ -484,4 +582,31 @@ mod tests {
             "Sub-pixel shift: 50% right, 25% down (smooth motion via color blending)"
         );
     }
+
+    #[test]
+    fn test_explain_transform_color_adjustments() {
+        let tint = Transform::Tint { color: "#FF8800".to_string(), strength: 0.25 };
+        assert_eq!(explain_transform(&tint), "Tint toward #FF8800 at 25% strength");
+        assert_eq!(
+            explain_transform(&Transform::HueRotate { degrees: 90.0 }),
+            "Rotate hues by 90°"
+        );
+        assert_eq!(
+            explain_transform(&Transform::Posterize { levels: 4 }),
+            "Posterize to 4 levels per color channel"
+        );
+    }
+    #[test]
+    fn test_explain_transform_effects() {
+        let glow = Transform::Glow { radius: 2, color: Some("{spark}".to_string()) };
+        assert_eq!(explain_transform(&glow), "Glow {spark} with radius 2px");
+        assert_eq!(
+            explain_transform(&Transform::Emboss { strength: 0.5 }),
+            "Emboss at 50% strength"
+        );
+        assert_eq!(
+            explain_transform(&Transform::PixelNoise { amount: 0.05, seed: Some(3) }),
+            "Pixel noise up to 5% (seed 3)"
+        );
+    }
 }
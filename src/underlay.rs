@@ -0,0 +1,90 @@
+//! Reference image underlays for previews.
+//!
+//! A sprite's `reference` image is stretched to the preview's size, faded to
+//! its opacity and drawn beneath the sprite, so pixels can be traced over
+//! concept art. Only preview paths (`pxl show`, `pxl serve`, LSP hovers) call
+//! into this module; renders and exports never see the reference.
+
+use std::path::Path;
+
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+
+use crate::models::{Sprite, SpriteReference};
+
+/// Load a reference image, resolving its path against `base_dir`.
+pub fn load_reference(reference: &SpriteReference, base_dir: &Path) -> Result<RgbaImage, String> {
+    let path = base_dir.join(&reference.path);
+    image::open(&path)
+        .map(|image| image.to_rgba8())
+        .map_err(|e| format!("reference '{}': {}", path.display(), e))
+}
+
+/// Draw `image` over `reference`, stretched to the image's size and faded to
+/// `opacity` (0.0-1.0).
+pub fn apply_underlay(image: &RgbaImage, reference: &RgbaImage, opacity: f64) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let mut result = if reference.dimensions() == (width, height) {
+        reference.clone()
+    } else {
+        imageops::resize(reference, width, height, FilterType::Triangle)
+    };
+    let opacity = opacity.clamp(0.0, 1.0);
+    for pixel in result.pixels_mut() {
+        pixel[3] = (pixel[3] as f64 * opacity).round() as u8;
+    }
+    imageops::overlay(&mut result, image, 0, 0);
+    result
+}
+
+/// Preview a rendered sprite over its reference image, if it has one.
+///
+/// `image` may be scaled; the reference is stretched to whatever size it is,
+/// keeping concept art detail at higher preview scales.
+pub fn underlay_sprite(
+    image: RgbaImage,
+    sprite: &Sprite,
+    base_dir: &Path,
+) -> Result<RgbaImage, String> {
+    match &sprite.reference {
+        Some(reference) => {
+            let underlay = load_reference(reference, base_dir)?;
+            Ok(apply_underlay(&image, &underlay, reference.opacity))
+        }
+        None => Ok(image),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_apply_underlay() {
+        let mut image = RgbaImage::new(4, 4);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let reference = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 255, 255]));
+
+        let result = apply_underlay(&image, &reference, 0.5);
+        assert_eq!(result.dimensions(), (4, 4));
+        // Opaque sprite pixels cover the reference
+        assert_eq!(*result.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        // Transparent pixels show the faded, stretched reference
+        assert_eq!(*result.get_pixel(3, 3), Rgba([0, 0, 255, 128]));
+    }
+
+    #[test]
+    fn test_underlay_sprite_without_reference() {
+        let image = RgbaImage::from_pixel(2, 2, Rgba([1, 2, 3, 255]));
+        let result = underlay_sprite(image.clone(), &Sprite::default(), Path::new(".")).unwrap();
+        assert_eq!(result, image);
+
+        let sprite = Sprite {
+            reference: Some(SpriteReference { path: "missing.png".to_string(), opacity: 0.3 }),
+            ..Default::default()
+        };
+        let err = underlay_sprite(image, &sprite, Path::new("/nonexistent")).unwrap_err();
+        assert!(err.contains("missing.png"));
+    }
+}
@@ -4,10 +4,15 @@
 //! common mistakes like undefined tokens and invalid colors.
 
 use crate::color::parse_color;
+use crate::conditions::take_condition;
+use crate::config::LimitsConfig;
+use crate::limits::check_object;
 use crate::models::{
-    Import, Palette, PaletteRef, Particle, Relationship, RelationshipType, TtpObject,
+    Composition, Import, Palette, PaletteRef, Particle, Relationship, RelationshipType, TtpObject,
 };
+use crate::palette_cycle::capped_total_frames;
 use crate::palette_parser::{PaletteParser, ParseMode};
+use crate::variables::VariableRegistry;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
@@ -85,6 +90,22 @@ pub enum IssueType {
     UnusedImport,
     /// Import alias or imported name shadows a locally defined name
     ShadowedImport,
+    /// Object exceeds a `[limits]` guardrail (size, colors, or frames)
+    LimitExceeded,
+    /// Palette alias or deprecated token names a token that doesn't exist
+    InvalidAlias,
+    /// Sprite draws with a token its palette marks as deprecated
+    DeprecatedToken,
+    /// `calc()` expression in a numeric field can't be evaluated
+    InvalidExpression,
+    /// `when` condition is malformed
+    InvalidCondition,
+    /// Animation or random set names a sprite that isn't defined
+    UnknownSprite,
+    /// Animation frames have different dimensions
+    FrameSizeMismatch,
+    /// Sprite places a stamp that isn't defined
+    UnknownStamp,
 }
 
 impl std::fmt::Display for IssueType {
@@ -110,6 +131,14 @@ impl std::fmt::Display for IssueType {
             IssueType::UnresolvedImport => write!(f, "unresolved_import"),
             IssueType::UnusedImport => write!(f, "unused_import"),
             IssueType::ShadowedImport => write!(f, "shadowed_import"),
+            IssueType::LimitExceeded => write!(f, "limit_exceeded"),
+            IssueType::InvalidAlias => write!(f, "invalid_alias"),
+            IssueType::DeprecatedToken => write!(f, "deprecated_token"),
+            IssueType::InvalidExpression => write!(f, "invalid_expression"),
+            IssueType::InvalidCondition => write!(f, "invalid_condition"),
+            IssueType::UnknownSprite => write!(f, "unknown_sprite"),
+            IssueType::FrameSizeMismatch => write!(f, "frame_size_mismatch"),
+            IssueType::UnknownStamp => write!(f, "unknown_stamp"),
         }
     }
 }
@@ -180,12 +209,32 @@ struct TrackedImport {
     used: bool,
 }
 
+/// What the validator knows about an object animations can use as a frame
+#[derive(Debug, Clone, Default)]
+struct FrameSource {
+    /// Explicit size, if any
+    size: Option<[u32; 2]>,
+    /// Palette tokens it defines, if known
+    tokens: Option<HashSet<String>>,
+    /// Sprite or variant it builds on (`extends` or a variant's `base`)
+    base: Option<String>,
+    /// Whether the base's palette tokens also apply
+    inherits_tokens: bool,
+    /// Whether transforms may change its size from the base's
+    resized: bool,
+}
+
+/// A frame's effective size and palette tokens, where known
+type ResolvedFrame = (Option<[u32; 2]>, Option<HashSet<String>>);
+
 /// Validator for Pixelsrc files
 pub struct Validator {
     /// Collected validation issues
     issues: Vec<ValidationIssue>,
     /// Known palette names -> set of defined tokens
     palettes: HashMap<String, HashSet<String>>,
+    /// Known palette names -> deprecated token -> replacement
+    deprecated_tokens: HashMap<String, HashMap<String, String>>,
     /// Built-in palette names
     builtin_palettes: HashSet<String>,
     /// Known sprite names (for duplicate detection)
@@ -204,6 +253,34 @@ pub struct Validator {
     local_names: HashSet<String>,
     /// Names imported via import declarations (import alias → items)
     imported_names: HashSet<String>,
+    /// `--` variables from palettes so far (for `calc()` expressions)
+    variables: VariableRegistry,
+    /// Size, color, and frame guardrails
+    limits: LimitsConfig,
+    /// Sprites, variants and compositions by name (for animation frame checks)
+    frame_sources: HashMap<String, FrameSource>,
+    /// Animations whose frames are checked by [`Validator::finish`]
+    pending_animations: Vec<(usize, crate::models::Animation)>,
+    /// Stamps by name -> tokens they draw with
+    stamps: HashMap<String, HashSet<String>>,
+    /// Sprites whose stamps are checked by [`Validator::finish`]
+    pending_stamps: Vec<PendingStamps>,
+    /// Known random set names
+    random_sets: HashSet<String>,
+    /// Random sets whose choices are checked by [`Validator::finish`]
+    pending_random_sets: Vec<(usize, crate::models::RandomSet)>,
+}
+
+/// A sprite's stamp placements, checked once every stamp is known
+#[derive(Debug, Clone)]
+struct PendingStamps {
+    line: usize,
+    sprite: String,
+    stamps: Vec<String>,
+    /// Tokens of the sprite's palette, if known
+    palette_tokens: Option<HashSet<String>>,
+    /// Tokens the sprite's regions use (already checked)
+    used_tokens: HashSet<String>,
 }
 
 impl Default for Validator {
@@ -222,6 +299,7 @@ impl Validator {
         Self {
             issues: Vec::new(),
             palettes: HashMap::new(),
+            deprecated_tokens: HashMap::new(),
             builtin_palettes,
             sprite_names: HashSet::new(),
             animation_names: HashSet::new(),
@@ -231,9 +309,23 @@ impl Validator {
             tracked_imports: Vec::new(),
             local_names: HashSet::new(),
             imported_names: HashSet::new(),
+            variables: VariableRegistry::new(),
+            limits: LimitsConfig::default(),
+            frame_sources: HashMap::new(),
+            pending_animations: Vec::new(),
+            stamps: HashMap::new(),
+            pending_stamps: Vec::new(),
+            random_sets: HashSet::new(),
+            pending_random_sets: Vec::new(),
         }
     }
 
+    /// Use `limits` instead of the default guardrails
+    pub fn with_limits(mut self, limits: LimitsConfig) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Validate a single line of input
     pub fn validate_line(&mut self, line_number: usize, content: &str) {
         // Skip empty lines
@@ -296,12 +388,16 @@ impl Validator {
             "palette",
             "sprite",
             "animation",
+            "sequence",
             "composition",
             "variant",
             "import",
             "transform",
             "particle",
             "state-rules",
+            "theme",
+            "stamp",
+            "random_set",
         ];
         if !valid_types.contains(&type_str) {
             self.issues.push(
@@ -315,8 +411,42 @@ impl Validator {
             return;
         }
 
+        // Check 4: `when` condition; inactive objects aren't part of the document
+        let type_str = type_str.to_string();
+        let mut json_value = json_value;
+        match take_condition(&mut json_value) {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(message) => {
+                self.issues.push(ValidationIssue::error(
+                    line_number,
+                    IssueType::InvalidCondition,
+                    message,
+                ));
+                return;
+            }
+        }
+
+        // Check 5: calc() expressions in numeric fields
+        let evaluated = match self.variables.evaluate_expressions(&mut json_value) {
+            Ok(evaluated) => evaluated,
+            Err(e) => {
+                self.issues.push(ValidationIssue::error(
+                    line_number,
+                    IssueType::InvalidExpression,
+                    format!("Cannot evaluate expression: {}", e),
+                ));
+                return;
+            }
+        };
+
         // Now parse as TtpObject for semantic validation
-        let ttp_obj: TtpObject = match json5::from_str(content) {
+        let parsed = if evaluated {
+            serde_json::from_value(json_value).map_err(|e| e.to_string())
+        } else {
+            json5::from_str(content).map_err(|e| e.to_string())
+        };
+        let ttp_obj: TtpObject = match parsed {
             Ok(obj) => obj,
             Err(e) => {
                 // This shouldn't happen if type is valid, but handle gracefully
@@ -329,6 +459,14 @@ impl Validator {
             }
         };
 
+        if let Err(message) = check_object(&ttp_obj, &self.limits) {
+            self.issues.push(ValidationIssue::error(
+                line_number,
+                IssueType::LimitExceeded,
+                message,
+            ));
+        }
+
         // Validate based on object type
         match ttp_obj {
             TtpObject::Palette(palette) => {
@@ -338,13 +476,16 @@ impl Validator {
                 self.validate_sprite(line_number, &sprite);
             }
             TtpObject::Animation(animation) => {
-                self.validate_animation(line_number, &animation.name);
+                self.validate_animation(line_number, &animation);
+            }
+            TtpObject::Sequence(sequence) => {
+                self.validate_sequence(line_number, &sequence);
             }
             TtpObject::Composition(composition) => {
-                self.validate_composition(line_number, &composition.name);
+                self.validate_composition(line_number, &composition);
             }
             TtpObject::Variant(variant) => {
-                self.validate_variant(line_number, &variant.name, &variant.palette);
+                self.validate_variant(line_number, &variant);
             }
             TtpObject::Particle(particle) => {
                 self.validate_particle(line_number, &particle);
@@ -358,6 +499,15 @@ impl Validator {
             TtpObject::Import(import) => {
                 self.validate_import(line_number, &import);
             }
+            TtpObject::Theme(theme) => {
+                self.validate_theme(line_number, &theme);
+            }
+            TtpObject::Stamp(stamp) => {
+                self.validate_stamp(line_number, &stamp);
+            }
+            TtpObject::RandomSet(set) => {
+                self.validate_random_set(line_number, &set);
+            }
         }
     }
 
@@ -417,6 +567,47 @@ impl Validator {
         }
     }
 
+    /// Validate a theme definition
+    fn validate_theme(&mut self, line_number: usize, theme: &crate::models::Theme) {
+        // Themes share namespace with other named objects
+        if !self.sprite_names.insert(theme.name.clone()) {
+            self.issues.push(
+                ValidationIssue::warning(
+                    line_number,
+                    IssueType::DuplicateName,
+                    format!("Duplicate theme name \"{}\"", theme.name),
+                )
+                .with_context(format!("theme \"{}\"", theme.name)),
+            );
+        }
+
+        if theme.palettes.is_empty() {
+            self.issues.push(
+                ValidationIssue::warning(
+                    line_number,
+                    IssueType::EmptyGrid,
+                    "Theme swaps no palettes".to_string(),
+                )
+                .with_context(format!("theme \"{}\"", theme.name)),
+            );
+        }
+
+        let mut replacements: Vec<&String> = theme.palettes.values().collect();
+        replacements.sort();
+        for replacement in replacements {
+            if replacement.starts_with('@') && !self.builtin_palettes.contains(replacement) {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::MissingPalette,
+                        format!("Unknown built-in palette \"{}\"", replacement),
+                    )
+                    .with_context(format!("theme \"{}\"", theme.name)),
+                );
+            }
+        }
+    }
+
     /// Validate an import declaration
     fn validate_import(&mut self, line_number: usize, import: &crate::models::Import) {
         let errors = import.validate();
@@ -480,6 +671,10 @@ impl Validator {
         let colors = &palette.colors;
         // Track as a local name
         self.local_names.insert(name.to_string());
+        // Variables are visible to expressions in later objects
+        for (key, value) in colors.iter().filter(|(key, _)| key.starts_with("--")) {
+            self.variables.define(key, value);
+        }
         // Check for duplicate name
         if !self.palette_names.insert(name.to_string()) {
             self.issues.push(
@@ -564,6 +759,26 @@ impl Validator {
             self.validate_relationships(line_number, name, &defined_tokens, rels);
         }
 
+        // Validate aliases and deprecated tokens, which then count as defined
+        let aliases = palette.aliases.iter().chain(palette.deprecated.iter()).flatten();
+        let alias_names: HashSet<&String> = aliases.clone().map(|(alias, _)| alias).collect();
+        for (alias, target) in aliases {
+            if !defined_tokens.contains(target) && !alias_names.contains(target) {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::InvalidAlias,
+                        format!("Alias {} references undefined token {}", alias, target),
+                    )
+                    .with_context(format!("palette \"{}\"", name)),
+                );
+            }
+        }
+        defined_tokens.extend(alias_names.into_iter().cloned());
+        if let Some(deprecated) = &palette.deprecated {
+            self.deprecated_tokens.insert(name.to_string(), deprecated.clone());
+        }
+
         // Register palette tokens
         self.palettes.insert(name.to_string(), defined_tokens);
     }
@@ -666,11 +881,42 @@ impl Validator {
             );
         }
 
-        // Get palette tokens for validation
-        let palette_tokens = self.get_palette_tokens(&sprite.palette, line_number, name);
+        // Get palette tokens for validation (a template may supply the palette)
+        let palette_tokens = if sprite.extends.is_some() && sprite.palette_unset() {
+            None
+        } else if sprite.palette_unset() {
+            self.issues.push(
+                ValidationIssue::error(
+                    line_number,
+                    IssueType::MissingPalette,
+                    format!("Sprite \"{}\" has no palette", name),
+                )
+                .with_context(format!("sprite \"{}\"", name)),
+            );
+            None
+        } else {
+            self.get_palette_tokens(&sprite.palette, line_number, name)
+        };
+
+        // A template's palette carries over unless a named palette replaces it
+        self.frame_sources.insert(
+            name.to_string(),
+            FrameSource {
+                size: sprite.size,
+                tokens: palette_tokens.clone(),
+                base: sprite.extends.clone(),
+                inherits_tokens: !matches!(sprite.palette, PaletteRef::Named(_)),
+                resized: false,
+            },
+        );
 
-        // Validate sprites have regions defined (unless they reference a source sprite)
-        if sprite.regions.is_none() && sprite.source.is_none() {
+        // Validate sprites have regions defined (unless they reference a source or template)
+        if sprite.regions.is_none()
+            && sprite.layers.is_none()
+            && sprite.stamps.is_none()
+            && sprite.source.is_none()
+            && sprite.extends.is_none()
+        {
             self.issues.push(
                 ValidationIssue::warning(
                     line_number,
@@ -682,6 +928,13 @@ impl Validator {
             );
         }
 
+        let context = format!("sprite \"{}\"", name);
+        self.check_opacity(line_number, "Sprite", sprite.opacity, &context);
+        for layer in sprite.layers.iter().flatten() {
+            let what = format!("Layer \"{}\"", layer.name);
+            self.check_opacity(line_number, &what, layer.opacity, &context);
+        }
+
         // Collect all tokens used in regions
         let mut all_tokens_used: HashSet<String> = HashSet::new();
         if let Some(regions) = &sprite.regions {
@@ -689,6 +942,31 @@ impl Validator {
                 all_tokens_used.insert(token.clone());
             }
         }
+        for layer in sprite.layers.iter().flatten() {
+            all_tokens_used.extend(layer.regions.keys().cloned());
+        }
+
+        for placement in sprite.stamps.iter().flatten() {
+            if !matches!(placement.rotate, None | Some(0 | 90 | 180 | 270)) {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::RangeValidation,
+                        format!("Stamp \"{}\" rotation must be 0, 90, 180 or 270", placement.name),
+                    )
+                    .with_context(format!("sprite \"{}\"", name)),
+                );
+            }
+        }
+        if let Some(stamps) = &sprite.stamps {
+            self.pending_stamps.push(PendingStamps {
+                line: line_number,
+                sprite: name.to_string(),
+                stamps: stamps.iter().map(|p| p.name.clone()).collect(),
+                palette_tokens: palette_tokens.clone(),
+                used_tokens: all_tokens_used.clone(),
+            });
+        }
 
         // Check for undefined tokens (only if we have palette info)
         if let Some(ref defined_tokens) = palette_tokens {
@@ -711,6 +989,29 @@ impl Validator {
                 }
             }
         }
+
+        // Check for deprecated tokens of a named palette
+        if let PaletteRef::Named(palette_name) = &sprite.palette {
+            if let Some(deprecated) = self.deprecated_tokens.get(palette_name) {
+                let mut used: Vec<&String> =
+                    all_tokens_used.iter().filter(|t| deprecated.contains_key(*t)).collect();
+                used.sort();
+                for token in used {
+                    self.issues.push(
+                        ValidationIssue::warning(
+                            line_number,
+                            IssueType::DeprecatedToken,
+                            format!(
+                                "Token {} is deprecated in palette \"{}\"",
+                                token, palette_name
+                            ),
+                        )
+                        .with_context(format!("sprite \"{}\"", name))
+                        .with_suggestion(format!("use {} instead", deprecated[token])),
+                    );
+                }
+            }
+        }
     }
 
     /// Get tokens defined in a palette reference
@@ -774,7 +1075,9 @@ impl Validator {
     }
 
     /// Validate an animation definition
-    fn validate_animation(&mut self, line_number: usize, name: &str) {
+    fn validate_animation(&mut self, line_number: usize, animation: &crate::models::Animation) {
+        let name = &animation.name;
+
         // Check for duplicate name
         if !self.animation_names.insert(name.to_string()) {
             self.issues.push(
@@ -786,10 +1089,430 @@ impl Validator {
                 .with_context(format!("animation \"{}\"", name)),
             );
         }
+
+        // Per-frame durations must line up with the frames
+        if let Err(error) = animation.frame_durations() {
+            self.issues.push(
+                ValidationIssue::error(line_number, IssueType::RangeValidation, error)
+                    .with_context(format!("animation \"{}\"", name)),
+            );
+        }
+
+        // Motion presets generate the keyframes, so frames and keyframes are ignored
+        if let Some(motion) = &animation.motion {
+            if let Err(error) = crate::motion_preset::validate_preset(motion) {
+                self.issues.push(
+                    ValidationIssue::error(line_number, IssueType::RangeValidation, error)
+                        .with_context(format!("animation \"{}\"", name)),
+                );
+            }
+            if !animation.frames.is_empty() || animation.keyframes.is_some() {
+                self.issues.push(
+                    ValidationIssue::warning(
+                        line_number,
+                        IssueType::RangeValidation,
+                        "motion preset replaces frames and keyframes".to_string(),
+                    )
+                    .with_context(format!("animation \"{}\"", name)),
+                );
+            }
+        }
+
+        // Palette cycles render LCM-of-periods frames; renders truncate at the cap
+        if let (_, Some(warning)) = capped_total_frames(animation, self.limits.max_frames) {
+            self.issues.push(
+                ValidationIssue::warning(line_number, IssueType::LimitExceeded, warning)
+                    .with_context(format!("animation \"{}\"", name)),
+            );
+        }
+
+        // Frames may be defined further down, so they are checked in `finish`
+        self.pending_animations.push((line_number, animation.clone()));
+    }
+
+    /// Size and palette tokens of a frame, following `extends` and variant bases.
+    ///
+    /// Returns `None` if nothing called `name` is defined.
+    fn resolve_frame(&self, name: &str, seen: &mut Vec<String>) -> Option<ResolvedFrame> {
+        let source = self.frame_sources.get(name)?;
+        if seen.iter().any(|s| s == name) {
+            return Some((source.size, None));
+        }
+        seen.push(name.to_string());
+
+        let base = source.base.as_deref().and_then(|base| self.resolve_frame(base, seen));
+        let (base_size, base_tokens) = match (&source.base, base) {
+            (_, Some(base)) => base,
+            (None, None) => (None, None),
+            // Unknown base: nothing is known about what it provides
+            (Some(_), None) => return Some((source.size, None)),
+        };
+
+        let size = source.size.or(if source.resized { None } else { base_size });
+        let tokens = if source.inherits_tokens && source.base.is_some() {
+            match (&source.tokens, base_tokens) {
+                (Some(own), Some(mut inherited)) => {
+                    inherited.extend(own.iter().cloned());
+                    Some(inherited)
+                }
+                (None, inherited) => inherited,
+                (Some(_), None) => None,
+            }
+        } else {
+            source.tokens.clone()
+        };
+        Some((size, tokens))
+    }
+
+    /// Whether `name` may come from an import, so it can't be checked locally
+    fn is_external(&self, name: &str) -> bool {
+        name.contains(':')
+            || self.imported_names.contains(name)
+            || self
+                .tracked_imports
+                .iter()
+                .any(|t| !t.import.is_selective() && t.import.alias.is_none())
+    }
+
+    /// Check an animation's frames against every sprite, variant and composition
+    fn check_animation_frames(&mut self, line_number: usize, animation: &crate::models::Animation) {
+        let context = format!("animation \"{}\"", animation.name);
+        let known: Vec<String> = self.frame_sources.keys().cloned().collect();
+        let known: Vec<&str> = known.iter().map(String::as_str).collect();
+        let mut issues = Vec::new();
+        let mut unknown = |what: String, name: &str| {
+            let mut issue = ValidationIssue::warning(line_number, IssueType::UnknownSprite, what)
+                .with_context(context.clone());
+            if let Some(suggestion) = suggest_token(name, &known) {
+                issue = issue.with_suggestion(format!("did you mean {}?", suggestion));
+            }
+            issues.push(issue);
+        };
+
+        // Motion presets replace frames and keyframes with a single sprite
+        if let Some(motion) = &animation.motion {
+            if !self.frame_sources.contains_key(&motion.sprite) && !self.is_external(&motion.sprite)
+            {
+                unknown(
+                    format!("Motion sprite \"{}\" is not defined", motion.sprite),
+                    &motion.sprite,
+                );
+            }
+            self.issues.extend(issues);
+            return;
+        }
+
+        let mut frames: Vec<&str> = Vec::new();
+        for frame in &animation.frames {
+            if !frames.contains(&frame.as_str()) {
+                frames.push(frame);
+            }
+        }
+
+        let mut resolved = Vec::new();
+        for &frame in &frames {
+            match self.resolve_frame(frame, &mut Vec::new()) {
+                Some((size, tokens)) => resolved.push((frame, size, tokens)),
+                None if self.is_external(frame) => {}
+                None => unknown(
+                    format!("Frame \"{}\" is not a sprite, variant or composition", frame),
+                    frame,
+                ),
+            }
+        }
+
+        let mut keyframes: Vec<(&String, &str)> = animation
+            .keyframes
+            .iter()
+            .flatten()
+            .filter_map(|(key, kf)| Some((key, kf.sprite.as_deref()?)))
+            .collect();
+        keyframes.sort();
+        for (key, sprite) in keyframes {
+            if !self.frame_sources.contains_key(sprite) && !self.is_external(sprite) {
+                unknown(
+                    format!("Keyframe \"{}\" sprite \"{}\" is not defined", key, sprite),
+                    sprite,
+                );
+            }
+        }
+
+        // Frames of different sizes render padded to the largest
+        let mut sized = resolved.iter().filter_map(|(frame, size, _)| Some((frame, (*size)?)));
+        if let Some((first, [w, h])) = sized.next() {
+            for (frame, [fw, fh]) in sized {
+                if [fw, fh] != [w, h] {
+                    issues.push(
+                        ValidationIssue::warning(
+                            line_number,
+                            IssueType::FrameSizeMismatch,
+                            format!(
+                                "Frame \"{}\" is {}x{}, but frame \"{}\" is {}x{}",
+                                frame, fw, fh, first, w, h
+                            ),
+                        )
+                        .with_context(context.clone()),
+                    );
+                }
+            }
+        }
+
+        // Palette cycles rotate tokens of each frame's palette
+        let bare = |token: &str| token.trim_start_matches('{').trim_end_matches('}').to_string();
+        for (frame, _, tokens) in &resolved {
+            let Some(tokens) = tokens else { continue };
+            let defined: HashSet<String> = tokens.iter().map(|t| bare(t)).collect();
+            let mut missing: Vec<&String> = animation
+                .palette_cycles()
+                .iter()
+                .flat_map(|cycle| &cycle.tokens)
+                .filter(|token| !defined.contains(&bare(token)))
+                .collect();
+            missing.sort();
+            missing.dedup();
+            for token in missing {
+                issues.push(
+                    ValidationIssue::warning(
+                        line_number,
+                        IssueType::UndefinedToken,
+                        format!(
+                            "Palette cycle token {} is not in the palette of frame \"{}\"",
+                            token, frame
+                        ),
+                    )
+                    .with_context(context.clone()),
+                );
+            }
+        }
+
+        self.issues.extend(issues);
+    }
+
+    /// Run checks that need the whole input.
+    ///
+    /// Animations may use frames defined after them (or in a later file), so
+    /// their frames are checked here, once everything has been validated.
+    /// [`Validator::into_issues`] calls this; call it yourself before reading
+    /// [`Validator::issues`].
+    pub fn finish(&mut self) {
+        for (line_number, animation) in std::mem::take(&mut self.pending_animations) {
+            self.check_animation_frames(line_number, &animation);
+        }
+        for pending in std::mem::take(&mut self.pending_stamps) {
+            self.check_sprite_stamps(&pending);
+        }
+        for (line_number, set) in std::mem::take(&mut self.pending_random_sets) {
+            self.check_random_set_choices(line_number, &set);
+        }
+    }
+
+    /// Check that a random set's choices are sprites, compositions or sets.
+    fn check_random_set_choices(&mut self, line_number: usize, set: &crate::models::RandomSet) {
+        let context = format!("random_set \"{}\"", set.name);
+        let mut known: Vec<&str> = self.frame_sources.keys().map(String::as_str).collect();
+        known.extend(self.random_sets.iter().map(String::as_str));
+        let mut issues = Vec::new();
+        for choice in &set.choices {
+            let name = choice.name();
+            if known.contains(&name) || self.is_external(name) {
+                continue;
+            }
+            let mut issue = ValidationIssue::warning(
+                line_number,
+                IssueType::UnknownSprite,
+                format!("Choice \"{}\" is not a sprite, composition or random set", name),
+            )
+            .with_context(context.clone());
+            if let Some(suggestion) = suggest_token(name, &known) {
+                issue = issue.with_suggestion(format!("did you mean {}?", suggestion));
+            }
+            issues.push(issue);
+        }
+        self.issues.extend(issues);
+    }
+
+    /// Validate a random set definition
+    fn validate_random_set(&mut self, line_number: usize, set: &crate::models::RandomSet) {
+        let context = format!("random_set \"{}\"", set.name);
+        if !self.random_sets.insert(set.name.clone()) {
+            self.issues.push(
+                ValidationIssue::warning(
+                    line_number,
+                    IssueType::DuplicateName,
+                    format!("Duplicate random_set name \"{}\"", set.name),
+                )
+                .with_context(context.clone()),
+            );
+        }
+        if set.choices.is_empty() {
+            self.issues.push(
+                ValidationIssue::error(
+                    line_number,
+                    IssueType::EmptyGrid,
+                    "Random set has no choices".to_string(),
+                )
+                .with_context(context.clone()),
+            );
+        }
+
+        let mut weights: Vec<(String, f64)> =
+            set.choices.iter().map(|c| (format!("Choice \"{}\"", c.name()), c.weight())).collect();
+        let mut tokens: Vec<_> = set.regions.iter().collect();
+        tokens.sort_by(|a, b| a.0.cmp(b.0));
+        for (token, alternatives) in tokens {
+            if alternatives.is_empty() {
+                self.issues.push(
+                    ValidationIssue::warning(
+                        line_number,
+                        IssueType::EmptyGrid,
+                        format!("Region {} has no alternatives", token),
+                    )
+                    .with_context(context.clone()),
+                );
+            }
+            for (i, alternative) in alternatives.iter().enumerate() {
+                weights.push((
+                    format!("Region {} alternative {}", token, i + 1),
+                    alternative.weight(),
+                ));
+            }
+        }
+        for (what, weight) in weights {
+            if !weight.is_finite() || weight <= 0.0 {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::RangeValidation,
+                        format!("{} weight must be positive, got {}", what, weight),
+                    )
+                    .with_context(context.clone()),
+                );
+            }
+        }
+
+        // Choices may be defined further down, so they are checked in `finish`
+        self.pending_random_sets.push((line_number, set.clone()));
+    }
+
+    /// Check that a sprite's stamps exist and draw with its palette's tokens.
+    fn check_sprite_stamps(&mut self, pending: &PendingStamps) {
+        let context = format!("sprite \"{}\"", pending.sprite);
+        let mut reported = pending.used_tokens.clone();
+        for name in &pending.stamps {
+            let Some(tokens) = self.stamps.get(name) else {
+                let known: Vec<&str> = self.stamps.keys().map(String::as_str).collect();
+                let mut issue = ValidationIssue::warning(
+                    pending.line,
+                    IssueType::UnknownStamp,
+                    format!("Stamp \"{}\" is not defined", name),
+                )
+                .with_context(context.clone());
+                if let Some(suggestion) = suggest_token(name, &known) {
+                    issue = issue.with_suggestion(format!("did you mean {}?", suggestion));
+                }
+                self.issues.push(issue);
+                continue;
+            };
+            let Some(defined) = &pending.palette_tokens else { continue };
+            let mut undefined: Vec<&String> =
+                tokens.iter().filter(|t| !defined.contains(*t)).collect();
+            undefined.sort();
+            for token in undefined {
+                if reported.insert(token.clone()) {
+                    self.issues.push(
+                        ValidationIssue::warning(
+                            pending.line,
+                            IssueType::UndefinedToken,
+                            format!("Undefined token {} in stamp \"{}\"", token, name),
+                        )
+                        .with_context(context.clone()),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Validate a stamp definition
+    fn validate_stamp(&mut self, line_number: usize, stamp: &crate::models::Stamp) {
+        let tokens: HashSet<String> =
+            stamp.pixels().into_iter().map(|(_, _, token)| token.to_string()).collect();
+        if tokens.is_empty() {
+            self.issues.push(
+                ValidationIssue::warning(
+                    line_number,
+                    IssueType::EmptyGrid,
+                    "Stamp has no filled pixels".to_string(),
+                )
+                .with_context(format!("stamp \"{}\"", stamp.name)),
+            );
+        }
+        if self.stamps.insert(stamp.name.clone(), tokens).is_some() {
+            self.issues.push(
+                ValidationIssue::warning(
+                    line_number,
+                    IssueType::DuplicateName,
+                    format!("Duplicate stamp name \"{}\"", stamp.name),
+                )
+                .with_context(format!("stamp \"{}\"", stamp.name)),
+            );
+        }
+    }
+
+    /// Validate a sequence definition
+    fn validate_sequence(&mut self, line_number: usize, sequence: &crate::models::Sequence) {
+        // Sequences render as animations, so they share the animation namespace
+        if !self.animation_names.insert(sequence.name.clone()) {
+            self.issues.push(
+                ValidationIssue::warning(
+                    line_number,
+                    IssueType::DuplicateName,
+                    format!("Duplicate animation name \"{}\"", sequence.name),
+                )
+                .with_context(format!("sequence \"{}\"", sequence.name)),
+            );
+        }
+
+        if sequence.steps.is_empty() {
+            self.issues.push(
+                ValidationIssue::warning(
+                    line_number,
+                    IssueType::EmptyGrid,
+                    "Sequence has no steps".to_string(),
+                )
+                .with_context(format!("sequence \"{}\"", sequence.name)),
+            );
+        }
+
+        for (i, step) in sequence.steps.iter().enumerate() {
+            if step.repeat == Some(0) {
+                self.issues.push(
+                    ValidationIssue::warning(
+                        line_number,
+                        IssueType::RangeValidation,
+                        format!("Step {} ('{}') repeats 0 times", i + 1, step.animation),
+                    )
+                    .with_context(format!("sequence \"{}\"", sequence.name)),
+                );
+            }
+            if step.crossfade.as_ref().is_some_and(|d| d.as_milliseconds().is_none()) {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::RangeValidation,
+                        format!("Step {} ('{}') has an invalid crossfade", i + 1, step.animation),
+                    )
+                    .with_context(format!("sequence \"{}\"", sequence.name)),
+                );
+            }
+        }
     }
 
     /// Validate a composition definition
-    fn validate_composition(&mut self, line_number: usize, name: &str) {
+    fn validate_composition(&mut self, line_number: usize, composition: &Composition) {
+        let name = &composition.name;
+        self.frame_sources
+            .insert(name.to_string(), FrameSource { size: composition.size, ..Default::default() });
+
         // Check for duplicate name
         if !self.composition_names.insert(name.to_string()) {
             self.issues.push(
@@ -801,15 +1524,112 @@ impl Validator {
                 .with_context(format!("composition \"{}\"", name)),
             );
         }
+
+        self.check_lighting(line_number, composition);
+
+        for (index, layer) in composition.layers.iter().enumerate() {
+            let Some(shadow) = &layer.contact_shadow else {
+                continue;
+            };
+            let context = format!("composition \"{}\"", name);
+            let what = match &layer.name {
+                Some(layer_name) => format!("Layer \"{}\" contact shadow", layer_name),
+                None => format!("Layer {} contact shadow", index + 1),
+            };
+            self.check_opacity(line_number, &what, shadow.opacity, &context);
+            if let Some(Err(e)) = shadow.color.as_deref().map(parse_color) {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::InvalidColor,
+                        format!("{} color is invalid: {}", what, e),
+                    )
+                    .with_context(context),
+                );
+            }
+        }
     }
 
-    /// Validate a variant definition
-    fn validate_variant(
+    /// Check a composition's `ambient` and `lights` colors and ranges.
+    fn check_lighting(&mut self, line_number: usize, composition: &Composition) {
+        let context = format!("composition \"{}\"", composition.name);
+        if let Some(ambient) = &composition.ambient {
+            if let Err(e) = parse_color(ambient) {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::InvalidColor,
+                        format!("Invalid ambient color \"{}\": {}", ambient, e),
+                    )
+                    .with_context(context.clone()),
+                );
+            }
+        }
+
+        for light in &composition.lights {
+            let at = format!("[{}, {}]", light.at[0], light.at[1]);
+            if let Some(Err(e)) = light.color.as_deref().map(parse_color) {
+                self.issues.push(
+                    ValidationIssue::error(
+                        line_number,
+                        IssueType::InvalidColor,
+                        format!(
+                            "Invalid color \"{}\" for light at {}: {}",
+                            light.color.as_deref().unwrap_or_default(),
+                            at,
+                            e
+                        ),
+                    )
+                    .with_context(context.clone()),
+                );
+            }
+            if light.radius == 0 {
+                self.issues.push(
+                    ValidationIssue::warning(
+                        line_number,
+                        IssueType::RangeValidation,
+                        format!("Light at {} has radius 0 and lights nothing", at),
+                    )
+                    .with_context(context.clone()),
+                );
+            }
+            if light.intensity.is_some_and(|i| i < 0.0) {
+                self.issues.push(
+                    ValidationIssue::warning(
+                        line_number,
+                        IssueType::RangeValidation,
+                        format!("Light at {} has negative intensity and lights nothing", at),
+                    )
+                    .with_context(context.clone()),
+                );
+            }
+        }
+    }
+
+    /// Report an `opacity` outside 0.0-1.0 (renders clamp it).
+    fn check_opacity(
         &mut self,
         line_number: usize,
-        name: &str,
-        palette: &HashMap<String, String>,
+        what: &str,
+        opacity: Option<f64>,
+        context: &str,
     ) {
+        let Some(opacity) = opacity.filter(|o| !(0.0..=1.0).contains(o)) else {
+            return;
+        };
+        self.issues.push(
+            ValidationIssue::warning(
+                line_number,
+                IssueType::RangeValidation,
+                format!("{} opacity {} is outside 0.0-1.0 and will be clamped", what, opacity),
+            )
+            .with_context(context.to_string()),
+        );
+    }
+
+    /// Validate a variant definition
+    fn validate_variant(&mut self, line_number: usize, variant: &crate::models::Variant) {
+        let (name, palette) = (&variant.name, &variant.palette);
         // Check for duplicate name
         if !self.variant_names.insert(name.to_string()) {
             self.issues.push(
@@ -822,6 +1642,20 @@ impl Validator {
             );
         }
 
+        let context = format!("variant \"{}\"", name);
+        self.check_opacity(line_number, "Variant", variant.opacity, &context);
+
+        self.frame_sources.insert(
+            name.to_string(),
+            FrameSource {
+                size: None,
+                tokens: Some(palette.keys().cloned().collect()),
+                base: Some(variant.base.clone()),
+                inherits_tokens: true,
+                resized: variant.transform.is_some(),
+            },
+        );
+
         // Validate palette override colors
         for (token, color) in palette {
             if let Err(e) = parse_color(color) {
@@ -1122,8 +1956,9 @@ impl Validator {
         &self.issues
     }
 
-    /// Consume the validator and return all issues
-    pub fn into_issues(self) -> Vec<ValidationIssue> {
+    /// Consume the validator and return all issues, after [`Validator::finish`]
+    pub fn into_issues(mut self) -> Vec<ValidationIssue> {
+        self.finish();
         self.issues
     }
 
@@ -1284,6 +2119,177 @@ mod tests {
         assert!(validator.has_warnings());
     }
 
+    #[test]
+    fn test_validate_expressions() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r##"{"type": "palette", "name": "p", "colors": {"--tile": "8", "a": "#FF0000"}}"##,
+        );
+        validator.validate_line(
+            2,
+            r#"{"type": "sprite", "name": "ok", "size": ["calc(var(--tile) * 2)", 8], "palette": "p", "regions": {"a": {"rect": [0, 0, 1, 1]}}}"#,
+        );
+        assert!(validator.issues().is_empty(), "{:?}", validator.issues());
+
+        validator.validate_line(
+            3,
+            r#"{"type": "sprite", "name": "bad", "size": ["calc(var(--gap) * 2)", 8], "palette": "p", "regions": {}}"#,
+        );
+        assert_eq!(validator.issues().len(), 1);
+        assert_eq!(validator.issues()[0].issue_type, IssueType::InvalidExpression);
+        assert!(validator.issues()[0].message.contains("--gap"));
+    }
+
+    #[test]
+    fn test_validate_conditions() {
+        let hero = |when: &str| {
+            format!(
+                r#"{{"type": "sprite", "name": "hero", "when": "{}", "size": [1, 1], "palette": "@pico8", "regions": {{}}}}"#,
+                when
+            )
+        };
+        let mut validator = Validator::new();
+        validator.validate_line(1, &hero("!hd"));
+        validator.validate_line(2, &hero("hd"));
+        assert!(validator.issues().is_empty(), "inactive duplicates are skipped");
+
+        validator.validate_line(3, &hero("hd &&"));
+        assert_eq!(validator.issues().len(), 1);
+        assert_eq!(validator.issues()[0].issue_type, IssueType::InvalidCondition);
+    }
+
+    #[test]
+    fn test_validate_extends_without_palette() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r#"{"type": "sprite", "name": "knight", "extends": "humanoid", "metadata": {"origin": [8, 24]}}"#,
+        );
+        assert!(validator.issues().is_empty(), "{:?}", validator.issues());
+    }
+
+    #[test]
+    fn test_validate_stamps() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r##"{"type": "sprite", "name": "bush", "size": [8, 8], "palette": {"g": "#0F0"}, "stamps": [{"name": "leaf", "at": [0, 0]}, {"name": "leef", "at": [4, 4], "rotate": 45}]}"##,
+        );
+        validator.validate_line(2, r#"{"type": "stamp", "name": "leaf", "grid": ["g.", "gt"]}"#);
+        validator.finish();
+
+        let issues: Vec<_> = validator
+            .issues()
+            .iter()
+            .map(|i| (i.line, i.issue_type.clone(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (
+                    1,
+                    IssueType::RangeValidation,
+                    "Stamp \"leef\" rotation must be 0, 90, 180 or 270"
+                ),
+                (1, IssueType::UndefinedToken, "Undefined token t in stamp \"leaf\""),
+                (1, IssueType::UnknownStamp, "Stamp \"leef\" is not defined"),
+            ]
+        );
+        assert_eq!(validator.issues()[2].suggestion.as_deref(), Some("did you mean leaf?"));
+    }
+
+    #[test]
+    fn test_validate_random_set() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r#"{"type": "random_set", "name": "rock", "choices": ["rock_a", {"name": "rok_b", "weight": 0}], "regions": {"moss": [{"weight": -1}]}}"#,
+        );
+        validator.validate_line(
+            2,
+            r##"{"type": "sprite", "name": "rock_b", "size": [2, 2], "palette": {"r": "#888"}, "regions": {"r": {"rect": [0, 0, 2, 2]}}}"##,
+        );
+        validator.validate_line(3, r#"{"type": "random_set", "name": "rock_a", "choices": []}"#);
+        validator.finish();
+
+        let issues: Vec<_> = validator
+            .issues()
+            .iter()
+            .map(|i| (i.line, i.issue_type.clone(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (1, IssueType::RangeValidation, "Choice \"rok_b\" weight must be positive, got 0"),
+                (
+                    1,
+                    IssueType::RangeValidation,
+                    "Region moss alternative 1 weight must be positive, got -1"
+                ),
+                (3, IssueType::EmptyGrid, "Random set has no choices"),
+                (
+                    1,
+                    IssueType::UnknownSprite,
+                    "Choice \"rok_b\" is not a sprite, composition or random set"
+                ),
+            ]
+        );
+        assert_eq!(validator.issues()[3].suggestion.as_deref(), Some("did you mean rock_b?"));
+    }
+
+    #[test]
+    fn test_validate_contact_shadow() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r#"{"type": "composition", "name": "yard", "size": [8, 8], "sprites": {}, "layers": [{"name": "hero", "contact_shadow": {"opacity": 1.5}}, {"contact_shadow": {"color": "soot"}}]}"#,
+        );
+        validator.finish();
+
+        let issues: Vec<_> =
+            validator.issues().iter().map(|i| (i.issue_type.clone(), i.message.as_str())).collect();
+        assert_eq!(issues.len(), 2, "{:?}", issues);
+        assert_eq!(
+            issues[0],
+            (
+                IssueType::RangeValidation,
+                "Layer \"hero\" contact shadow opacity 1.5 is outside 0.0-1.0 and will be clamped"
+            )
+        );
+        assert_eq!(issues[1].0, IssueType::InvalidColor);
+        assert!(issues[1].1.starts_with("Layer 2 contact shadow color is invalid"));
+    }
+
+    #[test]
+    fn test_validate_lighting() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r##"{"type": "composition", "name": "camp", "size": [8, 8], "ambient": "dusk", "lights": [{"at": [4, 4], "radius": 0, "color": "#FFDD88"}, {"at": [1, 2], "radius": 3, "color": "fire", "intensity": -1}], "sprites": {}, "layers": []}"##,
+        );
+        validator.finish();
+
+        let issues: Vec<_> =
+            validator.issues().iter().map(|i| (i.issue_type.clone(), i.message.as_str())).collect();
+        assert_eq!(issues.len(), 4, "{:?}", issues);
+        assert_eq!(issues[0].0, IssueType::InvalidColor);
+        assert!(issues[0].1.starts_with("Invalid ambient color \"dusk\""));
+        assert_eq!(
+            issues[1],
+            (IssueType::RangeValidation, "Light at [4, 4] has radius 0 and lights nothing")
+        );
+        assert_eq!(issues[2].0, IssueType::InvalidColor);
+        assert!(issues[2].1.starts_with("Invalid color \"fire\" for light at [1, 2]"));
+        assert_eq!(
+            issues[3],
+            (
+                IssueType::RangeValidation,
+                "Light at [1, 2] has negative intensity and lights nothing"
+            )
+        );
+    }
+
     #[test]
     fn test_validate_invalid_color() {
         let mut validator = Validator::new();
@@ -1616,6 +2622,89 @@ mod tests {
         assert_eq!(issues.len(), 1, "Expected JSON syntax error for invalid relationship type");
     }
 
+    #[test]
+    fn test_validate_animation_durations_count() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r#"{"type": "animation", "name": "ok", "frames": ["a", "b"], "durations": [100, "1s"]}"#,
+        );
+        validator.validate_line(
+            2,
+            r#"{"type": "animation", "name": "bad", "frames": ["a", "b"], "durations": [100]}"#,
+        );
+
+        let range_issues: Vec<_> = validator
+            .issues()
+            .iter()
+            .filter(|i| i.issue_type == IssueType::RangeValidation)
+            .collect();
+        assert_eq!(range_issues.len(), 1, "{:?}", validator.issues());
+        assert_eq!(range_issues[0].line, 2);
+        assert!(range_issues[0].message.contains("1 durations for 2 frames"));
+    }
+
+    #[test]
+    fn test_validate_animation_frames() {
+        let mut validator = Validator::new();
+        // Animations are checked against sprites defined later in the file
+        validator.validate_line(
+            1,
+            r#"{"type": "animation", "name": "walk", "frames": ["idle", "step", "idel"], "palette_cycle": [{"tokens": ["{w1}", "{w2}"]}]}"#,
+        );
+        validator.validate_line(
+            2,
+            r##"{"type": "sprite", "name": "idle", "size": [16, 16], "palette": {"w1": "#00F", "w2": "#0FF"}, "regions": {"w1": {"rect": [0, 0, 16, 16]}}}"##,
+        );
+        validator.validate_line(
+            3,
+            r##"{"type": "sprite", "name": "step", "size": [16, 24], "palette": {"w1": "#00F"}, "regions": {"w1": {"rect": [0, 0, 16, 24]}}}"##,
+        );
+        assert!(validator.issues().is_empty(), "{:?}", validator.issues());
+
+        let issues = validator.into_issues();
+        let unknown: Vec<_> =
+            issues.iter().filter(|i| i.issue_type == IssueType::UnknownSprite).collect();
+        assert_eq!(unknown.len(), 1, "{:?}", issues);
+        assert_eq!(unknown[0].line, 1);
+        assert!(unknown[0].message.contains("\"idel\""));
+        assert_eq!(unknown[0].suggestion.as_deref(), Some("did you mean idle?"));
+
+        let sizes: Vec<_> =
+            issues.iter().filter(|i| i.issue_type == IssueType::FrameSizeMismatch).collect();
+        assert_eq!(sizes.len(), 1, "{:?}", issues);
+        assert!(sizes[0].message.contains("\"step\" is 16x24"));
+
+        let cycles: Vec<_> =
+            issues.iter().filter(|i| i.issue_type == IssueType::UndefinedToken).collect();
+        assert_eq!(cycles.len(), 1, "{:?}", issues);
+        assert!(cycles[0].message.contains("{w2}"));
+        assert!(cycles[0].message.contains("\"step\""));
+    }
+
+    #[test]
+    fn test_validate_animation_frames_through_variants() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r##"{"type": "sprite", "name": "hero", "size": [8, 8], "palette": {"skin": "#FC9"}, "regions": {"skin": {"rect": [0, 0, 8, 8]}}}"##,
+        );
+        validator.validate_line(
+            2,
+            r##"{"type": "variant", "name": "hero_hit", "base": "hero", "palette": {"skin": "#FFF"}}"##,
+        );
+        validator.validate_line(
+            3,
+            r#"{"type": "animation", "name": "hit", "frames": ["hero", "hero_hit"], "keyframes": {"50%": {"sprite": "hero_ko"}}, "palette_cycle": [{"tokens": ["skin"]}]}"#,
+        );
+
+        let issues = validator.into_issues();
+        // Variants share the base's size and palette; only the keyframe is unknown
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].issue_type, IssueType::UnknownSprite);
+        assert!(issues[0].message.contains("\"hero_ko\""));
+    }
+
     #[test]
     fn test_validate_import_shadowed_alias() {
         let mut validator = Validator::new();
@@ -1742,4 +2831,79 @@ mod tests {
             .collect();
         assert!(!cycle_issues.is_empty(), "Expected circular dependency issue for self-reference");
     }
+
+    #[test]
+    fn test_validate_aliases_and_deprecated_tokens() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r##"{"type": "palette", "name": "p", "colors": {"skin": "#E8B89D"}, "aliases": {"skn": "skin", "face": "skn", "bad": "nope"}, "deprecated": {"flesh": "skin"}}"##,
+        );
+        validator.validate_line(
+            2,
+            r##"{"type": "sprite", "name": "hero", "size": [1, 3], "palette": "p", "regions": {"skn": {"points": [[0, 0]]}, "face": {"points": [[0, 1]]}, "flesh": {"points": [[0, 2]]}}}"##,
+        );
+
+        let issues = validator.issues();
+        let types: Vec<_> = issues.iter().map(|i| (i.line, i.issue_type.clone())).collect();
+        assert_eq!(types, vec![(1, IssueType::InvalidAlias), (2, IssueType::DeprecatedToken)]);
+        assert_eq!(issues[0].message, "Alias bad references undefined token nope");
+        assert_eq!(issues[1].suggestion.as_deref(), Some("use skin instead"));
+    }
+
+    #[test]
+    fn test_validate_opacity_range() {
+        let mut validator = Validator::new();
+        validator.validate_line(
+            1,
+            r##"{"type": "sprite", "name": "ghost", "size": [1, 1], "palette": {"a": "#FFFFFF"}, "opacity": 0.5, "regions": {"a": {"points": [[0, 0]]}}, "layers": [{"name": "glow", "opacity": 1.5, "regions": {}}]}"##,
+        );
+        validator.validate_line(
+            2,
+            r##"{"type": "variant", "name": "ghost_off", "base": "ghost", "palette": {}, "opacity": -1}"##,
+        );
+
+        let issues = validator.issues();
+        let types: Vec<_> = issues.iter().map(|i| (i.line, i.issue_type.clone())).collect();
+        assert_eq!(types, vec![(1, IssueType::RangeValidation), (2, IssueType::RangeValidation)]);
+        assert_eq!(
+            issues[0].message,
+            "Layer \"glow\" opacity 1.5 is outside 0.0-1.0 and will be clamped"
+        );
+        assert_eq!(issues[1].context.as_deref(), Some("variant \"ghost_off\""));
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let limits = LimitsConfig { max_size: [16, 16], ..Default::default() };
+        let mut validator = Validator::new().with_limits(limits);
+        validator.validate_line(
+            1,
+            r##"{"type": "sprite", "name": "huge", "size": [64, 8], "palette": {"x": "#FF0000"}, "regions": {"x": {"rect": [0, 0, 1, 1]}}}"##,
+        );
+        let issue = validator
+            .issues()
+            .iter()
+            .find(|i| i.issue_type == IssueType::LimitExceeded)
+            .expect("Expected limit issue for oversized sprite");
+        assert_eq!(issue.severity, Severity::Error);
+        assert!(issue.message.contains("64x8"));
+    }
+
+    #[test]
+    fn test_validate_palette_cycle_frame_cap() {
+        let limits = LimitsConfig { max_frames: 100, ..Default::default() };
+        let mut validator = Validator::new().with_limits(limits);
+        validator.validate_line(
+            1,
+            r#"{"type": "animation", "name": "storm", "frames": ["sky"], "palette_cycle": [{"tokens": ["a", "b", "c", "d", "e", "f", "g"]}, {"tokens": ["h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r"]}, {"tokens": ["s", "t", "u", "v", "w", "x", "y", "z", "A", "B", "C", "D", "E"]}]}"#,
+        );
+        let issue = validator
+            .issues()
+            .iter()
+            .find(|i| i.issue_type == IssueType::LimitExceeded)
+            .expect("Expected frame cap warning");
+        assert_eq!(issue.severity, Severity::Warning);
+        assert!(issue.message.contains("need 1001 frames (LCM of periods 7, 11, 13)"));
+    }
 }
@@ -5,6 +5,7 @@
 //! - Variable resolution with `var(--name)` or `var(--name, fallback)` syntax
 //! - Circular dependency detection
 //! - Nested variable references
+//! - `calc()` arithmetic for numeric fields (sizes, offsets, durations)
 //!
 //! # Example
 //!
@@ -41,6 +42,9 @@ pub enum VariableError {
     /// Maximum recursion depth exceeded
     #[error("maximum variable resolution depth exceeded")]
     MaxDepthExceeded,
+    /// Numeric expression could not be evaluated
+    #[error("invalid expression: {0}")]
+    InvalidExpression(String),
 }
 
 /// Maximum depth for variable resolution to prevent stack overflow
@@ -292,6 +296,221 @@ impl VariableRegistry {
     pub fn names(&self) -> impl Iterator<Item = &String> {
         self.variables.keys()
     }
+
+    /// Evaluate a numeric expression
+    ///
+    /// Accepts a plain number or `calc()` arithmetic with `+`, `-`, `*`, `/`
+    /// and parentheses. `var()` references must resolve to numbers or to
+    /// further expressions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pixelsrc::variables::VariableRegistry;
+    ///
+    /// let mut reg = VariableRegistry::new();
+    /// reg.define("--tile", "8");
+    /// reg.define("--wide", "calc(var(--tile) * 2)");
+    ///
+    /// assert_eq!(reg.evaluate("calc(var(--tile) * 4)").unwrap(), 32.0);
+    /// assert_eq!(reg.evaluate("calc(var(--wide) + 1)").unwrap(), 17.0);
+    /// assert_eq!(reg.evaluate("calc((var(--gap, 2) + 3) / 2)").unwrap(), 2.5);
+    /// ```
+    pub fn evaluate(&self, expr: &str) -> Result<f64, VariableError> {
+        Expression { registry: self, source: expr, pos: 0 }.evaluate()
+    }
+
+    /// Replace each `calc()` string in a parsed object with its value
+    ///
+    /// Palettes and `--` variable definitions are left alone, since their
+    /// strings are colors or expressions used elsewhere. Whole results become
+    /// integers so they fit fields like `size`. Returns whether anything was
+    /// replaced.
+    pub fn evaluate_expressions(
+        &self,
+        object: &mut serde_json::Value,
+    ) -> Result<bool, VariableError> {
+        if object.get("type").and_then(|t| t.as_str()) == Some("palette") {
+            return Ok(false);
+        }
+        self.evaluate_value(object)
+    }
+
+    fn evaluate_value(&self, value: &mut serde_json::Value) -> Result<bool, VariableError> {
+        use serde_json::Value;
+
+        match value {
+            Value::String(s) if is_expression(s) => {
+                let result = self.evaluate(s)?;
+                *value = if result.fract() == 0.0 && result.abs() < i64::MAX as f64 {
+                    Value::from(result as i64)
+                } else {
+                    Value::from(result)
+                };
+                Ok(true)
+            }
+            Value::Array(items) => {
+                let mut replaced = false;
+                for item in items {
+                    replaced |= self.evaluate_value(item)?;
+                }
+                Ok(replaced)
+            }
+            Value::Object(fields) => {
+                let mut replaced = false;
+                for (key, field) in fields.iter_mut().filter(|(key, _)| !key.starts_with("--")) {
+                    replaced |= self.evaluate_value(field).map_err(|e| match e {
+                        VariableError::InvalidExpression(msg) => {
+                            VariableError::InvalidExpression(format!("{} (in '{}')", msg, key))
+                        }
+                        other => other,
+                    })?;
+                }
+                Ok(replaced)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Whether `value` is a `calc()` expression
+pub fn is_expression(value: &str) -> bool {
+    let value = value.trim();
+    value.starts_with("calc(") && value.ends_with(')')
+}
+
+/// Recursive-descent evaluator for `calc()` arithmetic
+struct Expression<'a> {
+    registry: &'a VariableRegistry,
+    source: &'a str,
+    pos: usize,
+}
+
+impl Expression<'_> {
+    fn evaluate(mut self) -> Result<f64, VariableError> {
+        let value = self.sum()?;
+        if let Some(c) = self.peek() {
+            return Err(self.error(format!("unexpected '{}'", c)));
+        }
+        if !value.is_finite() {
+            return Err(self.error("result is not a finite number".to_string()));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, message: String) -> VariableError {
+        VariableError::InvalidExpression(format!("{} in '{}'", message, self.source))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.source[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.source[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), VariableError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", c)))
+        }
+    }
+
+    fn sum(&mut self) -> Result<f64, VariableError> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<f64, VariableError> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat('*') {
+                value *= self.factor()?;
+            } else if self.eat('/') {
+                let divisor = self.factor()?;
+                if divisor == 0.0 {
+                    return Err(self.error("division by zero".to_string()));
+                }
+                value /= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<f64, VariableError> {
+        if self.eat('-') {
+            return Ok(-self.factor()?);
+        }
+        if self.eat('+') {
+            return self.factor();
+        }
+        if self.eat('(') {
+            let value = self.sum()?;
+            self.expect(')')?;
+            return Ok(value);
+        }
+
+        let rest = &self.source[self.pos..];
+        if let Some(inner) = rest.strip_prefix("calc(") {
+            self.pos += rest.len() - inner.len();
+            let value = self.sum()?;
+            self.expect(')')?;
+            return Ok(value);
+        }
+        if rest.starts_with("var(") {
+            // Resolve the whole reference, then evaluate what it expands to
+            let end =
+                matching_paren(rest).ok_or_else(|| self.error("unclosed var()".to_string()))?;
+            self.pos += end;
+            let resolved = self.registry.resolve(&rest[..end])?;
+            return Expression { registry: self.registry, source: &resolved, pos: 0 }.evaluate();
+        }
+
+        let len = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        match rest[..len].parse::<f64>() {
+            Ok(value) => {
+                self.pos += len;
+                Ok(value)
+            }
+            Err(_) => Err(self.error(format!("expected a number at '{}'", rest))),
+        }
+    }
+}
+
+/// Byte length of a `name(...)` call at the start of `s`, through its closing paren
+fn matching_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
 }
 
 impl Registry<String> for VariableRegistry {
@@ -662,4 +881,67 @@ mod tests {
         reg.define("--b", "2");
         assert_eq!(check_registry::<String>(&reg), 2);
     }
+
+    #[test]
+    fn test_evaluate_arithmetic() {
+        let reg = VariableRegistry::new();
+        assert_eq!(reg.evaluate("12").unwrap(), 12.0);
+        assert_eq!(reg.evaluate("calc(2 + 3 * 4)").unwrap(), 14.0);
+        assert_eq!(reg.evaluate("calc((2 + 3) * 4)").unwrap(), 20.0);
+        assert_eq!(reg.evaluate("calc(10 - -2 / 4)").unwrap(), 10.5);
+        assert_eq!(reg.evaluate("calc(1 + calc(2 * 2))").unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_evaluate_variables() {
+        let mut reg = VariableRegistry::new();
+        reg.define("--tile", "8");
+        reg.define("--sum", "2 + 1");
+        assert_eq!(reg.evaluate("calc(var(--tile) * 4)").unwrap(), 32.0);
+        // A variable's expression is evaluated on its own, like parentheses
+        assert_eq!(reg.evaluate("calc(var(--sum) * 2)").unwrap(), 6.0);
+        assert_eq!(reg.evaluate("calc(var(--missing, 3) + 1)").unwrap(), 4.0);
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let mut reg = VariableRegistry::new();
+        reg.define("--color", "#FF0000");
+        reg.define("--a", "var(--b)");
+        reg.define("--b", "var(--a)");
+
+        let invalid =
+            |expr: &str| matches!(reg.evaluate(expr), Err(VariableError::InvalidExpression(_)));
+        assert!(invalid("calc(1 / 0)"));
+        assert!(invalid("calc(var(--color) * 2)"));
+        assert!(invalid("calc(1 +)"));
+        assert!(invalid("calc((1 + 2)"));
+        assert!(invalid("calc(1 2)"));
+        assert!(matches!(reg.evaluate("calc(var(--a))"), Err(VariableError::Circular(_))));
+        assert!(matches!(reg.evaluate("calc(var(--nope))"), Err(VariableError::Undefined(_))));
+    }
+
+    #[test]
+    fn test_evaluate_expressions_in_object() {
+        let mut reg = VariableRegistry::new();
+        reg.define("--tile", "8");
+
+        let mut sprite = serde_json::json!({
+            "type": "sprite",
+            "size": ["calc(var(--tile) * 4)", 32],
+            "opacity": "calc(1 / 4)",
+            "palette": {"--scale": "calc(var(--tile) / 2)"},
+        });
+        assert!(reg.evaluate_expressions(&mut sprite).unwrap());
+        assert_eq!(sprite["size"], serde_json::json!([32, 32]));
+        assert_eq!(sprite["opacity"], serde_json::json!(0.25));
+        assert_eq!(sprite["palette"]["--scale"], "calc(var(--tile) / 2)");
+
+        let mut palette = serde_json::json!({"type": "palette", "colors": {"x": "calc(1 + 1)"}});
+        assert!(!reg.evaluate_expressions(&mut palette).unwrap());
+
+        let mut bad = serde_json::json!({"type": "sprite", "size": ["calc(var(--tile) / 0)", 8]});
+        let err = reg.evaluate_expressions(&mut bad).unwrap_err();
+        assert!(err.to_string().contains("(in 'size')"), "{}", err);
+    }
 }
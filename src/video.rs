@@ -0,0 +1,249 @@
+//! Video export (MP4/WebM) of animations through an `ffmpeg` sidecar process
+//!
+//! Enabled with the `video` cargo feature. Frames are scaled and padded here,
+//! resampled to a constant frame rate, and piped to `ffmpeg` as raw RGBA; the
+//! encoder itself is whatever `ffmpeg` binary is on `PATH` (or named by the
+//! `PXL_FFMPEG` environment variable):
+//!
+//! | Format | Codec | Alpha |
+//! |--------|-------|-------|
+//! | MP4 | H.264 (`libx264`) | Flattened onto black |
+//! | WebM | VP9 (`libvpx-vp9`) | Kept (`yuva420p`) |
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use image::{Rgba, RgbaImage};
+use thiserror::Error;
+
+use crate::output::{scale_image, AlphaOptions};
+
+/// Environment variable naming the ffmpeg binary to run.
+pub const FFMPEG_ENV: &str = "PXL_FFMPEG";
+
+/// Error type for video export
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum VideoError {
+    /// There are no frames to encode
+    #[error("No frames to encode")]
+    NoFrames,
+    /// The frame rate is zero
+    #[error("Frame rate must be at least 1")]
+    InvalidFps,
+    /// ffmpeg could not be started
+    #[error("Failed to run '{tool}': {source} (install ffmpeg or set {FFMPEG_ENV})")]
+    Spawn {
+        tool: String,
+        #[source]
+        source: std::io::Error,
+    },
+    /// ffmpeg ran but reported failure
+    #[error("ffmpeg failed: {0}")]
+    Ffmpeg(String),
+    /// IO error while piping frames
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Video container and codec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFormat {
+    /// H.264 in MP4, the most widely playable
+    Mp4,
+    /// VP9 in WebM, with transparency
+    Webm,
+}
+
+impl VideoFormat {
+    /// File extension for the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            VideoFormat::Mp4 => "mp4",
+            VideoFormat::Webm => "webm",
+        }
+    }
+
+    fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            VideoFormat::Mp4 => &[
+                "-c:v",
+                "libx264",
+                "-preset",
+                "slow",
+                "-crf",
+                "18",
+                "-pix_fmt",
+                "yuv420p",
+                "-movflags",
+                "+faststart",
+            ],
+            VideoFormat::Webm => {
+                &["-c:v", "libvpx-vp9", "-crf", "30", "-b:v", "0", "-pix_fmt", "yuva420p"]
+            }
+        }
+    }
+}
+
+/// Video encoding settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoOptions {
+    pub format: VideoFormat,
+    /// Output frames per second
+    pub fps: u32,
+    /// Nearest-neighbor scale factor applied before encoding
+    pub scale: u8,
+}
+
+/// Which source frame to show for each output frame at a constant `fps`.
+///
+/// Each source frame `i` is on screen for `durations_ms[i]` milliseconds
+/// (frames past the end reuse the last duration); output frame `k` shows the
+/// frame on screen at `k / fps` seconds. At least one frame is produced.
+///
+/// ```
+/// use pixelsrc::video::frame_schedule;
+///
+/// // 100ms and 50ms frames at 20 fps: two ticks, then one
+/// assert_eq!(frame_schedule(2, &[100, 50], 20), vec![0, 0, 1]);
+/// ```
+pub fn frame_schedule(frames: usize, durations_ms: &[u32], fps: u32) -> Vec<usize> {
+    let fallback = durations_ms.last().copied().unwrap_or(100);
+    let mut ends = Vec::with_capacity(frames);
+    let mut total: u64 = 0;
+    for i in 0..frames {
+        total += durations_ms.get(i).copied().unwrap_or(fallback) as u64;
+        ends.push(total);
+    }
+
+    let count = ((total * fps as u64 + 500) / 1000).max(1);
+    let mut schedule = Vec::with_capacity(count as usize);
+    let mut current = 0;
+    for k in 0..count {
+        let time_ms = k * 1000 / fps as u64;
+        while current + 1 < frames && ends[current] <= time_ms {
+            current += 1;
+        }
+        schedule.push(current);
+    }
+    schedule
+}
+
+/// Arguments passed to ffmpeg for a `width`x`height` raw RGBA stream on stdin.
+pub fn ffmpeg_args(options: &VideoOptions, width: u32, height: u32, path: &Path) -> Vec<String> {
+    let mut args: Vec<String> =
+        ["-hide_banner", "-loglevel", "error", "-y", "-f", "rawvideo", "-pix_fmt", "rgba", "-s"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+    args.push(format!("{}x{}", width, height));
+    args.extend(["-framerate".to_string(), options.fps.to_string(), "-i".into(), "-".into()]);
+    args.extend(options.format.codec_args().iter().map(|s| s.to_string()));
+    args.push(path.display().to_string());
+    args
+}
+
+/// Encode animation frames as a video file.
+///
+/// Frames are placed at the top-left of a canvas as wide and as tall as the
+/// largest frame, grown to even dimensions as the YUV 4:2:0 codecs require.
+pub fn encode_video(
+    frames: &[RgbaImage],
+    durations_ms: &[u32],
+    options: &VideoOptions,
+    path: &Path,
+) -> Result<(), VideoError> {
+    if frames.is_empty() {
+        return Err(VideoError::NoFrames);
+    }
+    if options.fps == 0 {
+        return Err(VideoError::InvalidFps);
+    }
+
+    let alpha = match options.format {
+        VideoFormat::Mp4 => {
+            AlphaOptions { matte: Some(Rgba([0, 0, 0, 255])), ..Default::default() }
+        }
+        VideoFormat::Webm => AlphaOptions::default(),
+    };
+    let scaled: Vec<RgbaImage> =
+        frames.iter().map(|frame| scale_image(frame.clone(), options.scale)).collect();
+    let (width, height) =
+        scaled.iter().fold((0, 0), |(w, h), frame| (w.max(frame.width()), h.max(frame.height())));
+    let (width, height) = (width.div_ceil(2) * 2, height.div_ceil(2) * 2);
+    let prepared: Vec<Vec<u8>> = scaled
+        .iter()
+        .map(|frame| {
+            let mut canvas = RgbaImage::new(width, height);
+            image::imageops::replace(&mut canvas, frame, 0, 0);
+            alpha.apply(canvas).into_raw()
+        })
+        .collect();
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    // ffmpeg logs to a file rather than a pipe, so a chatty encoder can't
+    // block on a full stderr pipe while frames are still being written
+    let mut log = tempfile::tempfile()?;
+    let tool = std::env::var(FFMPEG_ENV).unwrap_or_else(|_| "ffmpeg".to_string());
+    let mut child = Command::new(&tool)
+        .args(ffmpeg_args(options, width, height, path))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::from(log.try_clone()?))
+        .spawn()
+        .map_err(|source| VideoError::Spawn { tool: tool.clone(), source })?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let written = frame_schedule(frames.len(), durations_ms, options.fps)
+        .into_iter()
+        .try_for_each(|i| stdin.write_all(&prepared[i]));
+    drop(stdin);
+
+    let status = child.wait()?;
+    if !status.success() {
+        let mut stderr = Vec::new();
+        log.seek(SeekFrom::Start(0))?;
+        log.read_to_end(&mut stderr)?;
+        let message = String::from_utf8_lossy(&stderr).trim().to_string();
+        return Err(VideoError::Ffmpeg(if message.is_empty() {
+            status.to_string()
+        } else {
+            message
+        }));
+    }
+    written?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_schedule() {
+        // 10fps, 100ms per frame: one output frame each
+        assert_eq!(frame_schedule(3, &[100], 10), vec![0, 1, 2]);
+        // 30fps: 1/30s ticks land 3 or 4 to a 120ms frame
+        assert_eq!(frame_schedule(2, &[120, 120], 30), vec![0, 0, 0, 0, 1, 1, 1]);
+        // Frames shorter than a tick can be skipped
+        assert_eq!(frame_schedule(3, &[50, 10, 100], 10), vec![0, 2]);
+        assert_eq!(frame_schedule(1, &[1], 30), vec![0]);
+    }
+
+    #[test]
+    fn test_ffmpeg_args() {
+        let options = VideoOptions { format: VideoFormat::Webm, fps: 24, scale: 1 };
+        let args = ffmpeg_args(&options, 64, 32, Path::new("out/walk.webm"));
+        let joined = args.join(" ");
+        assert!(joined.contains("-f rawvideo -pix_fmt rgba -s 64x32 -framerate 24 -i -"));
+        assert!(joined.contains("-c:v libvpx-vp9"));
+        assert!(joined.contains("yuva420p"));
+        assert_eq!(args.last().map(String::as_str), Some("out/walk.webm"));
+    }
+}
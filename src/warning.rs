@@ -0,0 +1,285 @@
+//! Warnings with provenance
+//!
+//! Lenient loading and rendering report problems as [`Warning`]s instead of
+//! failing. Each warning has a [`WarningCategory`] naming the stage that
+//! raised it and, where known, the object it is about. A [`SourceMap`] built
+//! from the parser's object spans then points it at the file and line that
+//! defines that object:
+//!
+//! ```text
+//! hero.pxl:3: sprite 'hero': Unknown token skin in sprite 'hero'
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::parser::ParseResult;
+
+/// The stage of loading or rendering that raised a warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningCategory {
+    /// Malformed input
+    Parse,
+    /// Missing palettes, colors or CSS variables
+    Palette,
+    /// Sprite and variant resolution
+    Sprite,
+    /// Rasterizing regions and layers
+    Render,
+    /// Sprite `transform` lists and metadata remapping
+    Transform,
+    /// Composition layout and layers
+    Composition,
+    /// Animation frames, tags and sequences
+    Animation,
+    /// Imports and includes
+    Import,
+    /// Options, output files and anything else
+    #[default]
+    Other,
+}
+
+impl fmt::Display for WarningCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WarningCategory::Parse => "parse",
+            WarningCategory::Palette => "palette",
+            WarningCategory::Sprite => "sprite",
+            WarningCategory::Render => "render",
+            WarningCategory::Transform => "transform",
+            WarningCategory::Composition => "composition",
+            WarningCategory::Animation => "animation",
+            WarningCategory::Import => "import",
+            WarningCategory::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The object a warning is about
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct ObjectRef {
+    /// Object type, as in its `"type"` field (e.g. `"sprite"`)
+    pub kind: String,
+    /// Object name
+    pub name: String,
+}
+
+/// A warning generated while loading or rendering
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Warning {
+    pub message: String,
+    pub category: WarningCategory,
+    /// Object the warning is about, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object: Option<ObjectRef>,
+    /// File defining the object, once located
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// 1-based line, once located
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+impl Warning {
+    pub fn new(category: WarningCategory, message: impl Into<String>) -> Self {
+        Self { message: message.into(), category, object: None, file: None, line: None }
+    }
+
+    /// Attribute the warning to an object, unless it already names a more
+    /// specific one (e.g. a sprite inside a composition).
+    pub fn for_object(mut self, kind: &str, name: &str) -> Self {
+        if self.object.is_none() {
+            self.object = Some(ObjectRef { kind: kind.to_string(), name: name.to_string() });
+        }
+        self
+    }
+
+    /// Point the warning at a source line.
+    pub fn at(mut self, file: Option<&Path>, line: usize) -> Self {
+        self.file = file.map(Path::to_path_buf);
+        self.line = Some(line);
+        self
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: ", file.display(), line)?,
+            (None, Some(line)) => write!(f, "line {}: ", line)?,
+            _ => {}
+        }
+        if let Some(object) = &self.object {
+            write!(f, "{} '{}': ", object.kind, object.name)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for Warning {
+    fn from(message: String) -> Self {
+        Self::new(WarningCategory::Other, message)
+    }
+}
+
+impl From<&str> for Warning {
+    fn from(message: &str) -> Self {
+        Self::new(WarningCategory::Other, message)
+    }
+}
+
+impl From<crate::models::Warning> for Warning {
+    fn from(warning: crate::models::Warning) -> Self {
+        Self::new(WarningCategory::Parse, warning.message).at(None, warning.line)
+    }
+}
+
+impl From<crate::registry::SpriteWarning> for Warning {
+    fn from(warning: crate::registry::SpriteWarning) -> Self {
+        Self::new(WarningCategory::Sprite, warning.message)
+    }
+}
+
+impl From<crate::registry::PaletteWarning> for Warning {
+    fn from(warning: crate::registry::PaletteWarning) -> Self {
+        Self::new(WarningCategory::Palette, warning.message)
+    }
+}
+
+/// Where the objects of a parsed file are defined
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    file: Option<PathBuf>,
+    lines: HashMap<(String, String), usize>,
+}
+
+impl SourceMap {
+    /// Map each object in `result` to the first line of its span in `file`.
+    ///
+    /// If two objects share a type and name, the first one's line is kept.
+    pub fn new(file: Option<&Path>, result: &ParseResult) -> Self {
+        let mut lines = HashMap::new();
+        for (obj, span) in result.objects.iter().zip(&result.spans) {
+            let key = (obj.kind().to_string(), obj.name().to_string());
+            lines.entry(key).or_insert(span.start);
+        }
+        Self { file: file.map(Path::to_path_buf), lines }
+    }
+
+    /// The line defining an object, if it came from this file.
+    pub fn line_of(&self, kind: &str, name: &str) -> Option<usize> {
+        self.lines.get(&(kind.to_string(), name.to_string())).copied()
+    }
+
+    /// Fill in the file and line of a warning from the object it names.
+    ///
+    /// Warnings that already have a line (parse warnings) only gain the file.
+    pub fn locate(&self, mut warning: Warning) -> Warning {
+        if warning.line.is_none() {
+            let object = warning.object.as_ref();
+            warning.line = object.and_then(|o| self.line_of(&o.kind, &o.name));
+        }
+        if warning.line.is_some() && warning.file.is_none() {
+            warning.file = self.file.clone();
+        }
+        warning
+    }
+}
+
+/// Warnings collected over a command, located as they are added
+#[derive(Debug, Clone, Default)]
+pub struct Warnings {
+    sources: SourceMap,
+    items: Vec<Warning>,
+}
+
+impl Warnings {
+    pub fn new(sources: SourceMap) -> Self {
+        Self { sources, items: Vec::new() }
+    }
+
+    pub fn push(&mut self, warning: impl Into<Warning>) {
+        let warning = self.sources.locate(warning.into());
+        self.items.push(warning);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Warning> {
+        self.items.iter()
+    }
+}
+
+impl<W: Into<Warning>> Extend<W> for Warnings {
+    fn extend<I: IntoIterator<Item = W>>(&mut self, iter: I) {
+        for warning in iter {
+            self.push(warning);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Warnings {
+    type Item = &'a Warning;
+    type IntoIter = std::slice::Iter<'a, Warning>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_stream;
+
+    #[test]
+    fn test_display() {
+        let warning = Warning::new(WarningCategory::Render, "Unknown token x");
+        assert_eq!(warning.to_string(), "Unknown token x");
+
+        let warning = warning.for_object("sprite", "hero").for_object("composition", "scene");
+        assert_eq!(warning.to_string(), "sprite 'hero': Unknown token x");
+
+        let warning = warning.at(Some(Path::new("art/hero.pxl")), 3);
+        assert_eq!(warning.to_string(), "art/hero.pxl:3: sprite 'hero': Unknown token x");
+    }
+
+    #[test]
+    fn test_source_map_locates_objects() {
+        let source = r##"{"type": "palette", "name": "hero", "colors": {}}
+
+{"type": "sprite", "name": "hero",
+ "size": [1, 1], "regions": {}, "palette": "hero"}
+not json
+"##;
+        let parsed = parse_stream(source.as_bytes());
+        let mut warnings = Warnings::new(SourceMap::new(Some(Path::new("hero.pxl")), &parsed));
+        warnings.extend(parsed.warnings);
+        warnings.push(Warning::new(WarningCategory::Render, "oops").for_object("sprite", "hero"));
+        warnings.push(Warning::new(WarningCategory::Palette, "gone").for_object("palette", "x"));
+
+        let located: Vec<_> = warnings.iter().map(|w| (w.category, w.line)).collect();
+        assert_eq!(
+            located,
+            [
+                (WarningCategory::Parse, Some(5)),
+                (WarningCategory::Render, Some(3)),
+                (WarningCategory::Palette, None)
+            ]
+        );
+        assert!(warnings.iter().nth(1).unwrap().to_string().starts_with("hero.pxl:3: sprite"));
+        // Unlocated warnings don't name the file
+        assert_eq!(warnings.iter().nth(2).unwrap().file, None);
+    }
+}
@@ -2,12 +2,16 @@
 //!
 //! Provides WebAssembly bindings for rendering pixelsrc JSONL to images.
 
+use std::collections::HashMap;
 use std::io::Cursor;
 
 use wasm_bindgen::prelude::*;
 
-use crate::models::TtpObject;
+use crate::config::LimitsConfig;
+use crate::limits::check_object;
+use crate::models::{Animation, Sprite, TtpObject};
 use crate::parser::parse_stream;
+use crate::playback::{animation_frame_at, precompute_animation, AnimationTimeline};
 use crate::registry::PaletteRegistry;
 use crate::renderer::render_sprite;
 
@@ -55,14 +59,19 @@ impl RenderResult {
 }
 
 /// Parse JSONL and build registry, returning (registry, sprites, warnings)
-fn parse_and_prepare(jsonl: &str) -> (PaletteRegistry, Vec<crate::models::Sprite>, Vec<String>) {
+fn parse_and_prepare(jsonl: &str) -> (PaletteRegistry, Vec<Sprite>, Vec<String>) {
     let parse_result = parse_stream(Cursor::new(jsonl));
     let mut registry = PaletteRegistry::new();
     let mut sprites = Vec::new();
-    let warnings: Vec<String> =
+    let mut warnings: Vec<String> =
         parse_result.warnings.iter().map(|w| format!("line {}: {}", w.line, w.message)).collect();
+    let limits = LimitsConfig::default();
 
     for obj in parse_result.objects {
+        if let Err(e) = check_object(&obj, &limits) {
+            warnings.push(e);
+            continue;
+        }
         match obj {
             TtpObject::Palette(p) => {
                 registry.register(p);
@@ -99,16 +108,7 @@ pub fn render_to_png(jsonl: &str) -> Vec<u8> {
     let (image, _render_warnings) = render_sprite(sprite, &resolved.palette.colors);
 
     // Encode to PNG
-    let mut png_data = Vec::new();
-    {
-        use image::ImageEncoder;
-        let encoder = image::codecs::png::PngEncoder::new(&mut png_data);
-        encoder
-            .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8)
-            .ok();
-    }
-
-    png_data
+    crate::output::encode_png(&image).unwrap_or_default()
 }
 
 /// Render the first sprite in a JSONL string to RGBA pixels.
@@ -183,6 +183,174 @@ pub fn validate(jsonl: &str) -> Vec<String> {
     warnings
 }
 
+/// Parse JSONL for animation playback, returning (registry, sprites by name, animations, warnings)
+fn parse_animations(
+    jsonl: &str,
+) -> (PaletteRegistry, HashMap<String, Sprite>, Vec<Animation>, Vec<String>) {
+    let parse_result = parse_stream(Cursor::new(jsonl));
+    let mut registry = PaletteRegistry::new();
+    let mut sprites = HashMap::new();
+    let mut animations = Vec::new();
+    let mut warnings: Vec<String> =
+        parse_result.warnings.iter().map(|w| format!("line {}: {}", w.line, w.message)).collect();
+
+    let limits = LimitsConfig::default();
+    for obj in parse_result.objects {
+        if let Err(e) = check_object(&obj, &limits) {
+            warnings.push(e);
+            continue;
+        }
+        match obj {
+            TtpObject::Palette(p) => registry.register(p),
+            TtpObject::Sprite(s) => {
+                sprites.insert(s.name.clone(), s);
+            }
+            TtpObject::Animation(a) => animations.push(a),
+            _ => {}
+        }
+    }
+
+    (registry, sprites, animations, warnings)
+}
+
+/// The animation called `name`, or the first one, warning if there is none
+fn find_animation<'a>(
+    animations: &'a [Animation],
+    name: Option<String>,
+    warnings: &mut Vec<String>,
+) -> Option<&'a Animation> {
+    let animation = match &name {
+        Some(n) => animations.iter().find(|a| &a.name == n),
+        None => animations.first(),
+    };
+    if animation.is_none() {
+        warnings.push(match name {
+            Some(n) => format!("No animation named '{}' found in input", n),
+            None => "No animations found in input".to_string(),
+        });
+    }
+    animation
+}
+
+/// Render the frame of an animation visible at `time_ms`, for scrubbing.
+///
+/// Unlike [`AnimationPlayer`], only the requested frame is rendered, and CSS
+/// keyframes are evaluated at exactly `time_ms` instead of at 20 fps samples.
+///
+/// # Arguments
+/// * `jsonl` - JSONL string containing palettes, sprites, and animations
+/// * `name` - Animation to render (default: the first animation in the input)
+/// * `time_ms` - Milliseconds since playback started
+#[wasm_bindgen]
+pub fn render_animation_frame(jsonl: &str, name: Option<String>, time_ms: f64) -> RenderResult {
+    let (registry, sprites, animations, mut warnings) = parse_animations(jsonl);
+
+    let Some(anim) = find_animation(&animations, name, &mut warnings) else {
+        return RenderResult { width: 0, height: 0, pixels: Vec::new(), warnings };
+    };
+    let (image, anim_warnings) = animation_frame_at(anim, &sprites, &registry, time_ms);
+    warnings.extend(anim_warnings);
+
+    RenderResult {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+        warnings,
+    }
+}
+
+/// Precomputed animation player for `requestAnimationFrame`-driven playback.
+///
+/// All frames (including palette cycles and sampled CSS keyframes) are rendered
+/// once at construction, so JS only has to call `frame_at(now - start)` per tick.
+#[wasm_bindgen]
+pub struct AnimationPlayer {
+    timeline: AnimationTimeline,
+    warnings: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl AnimationPlayer {
+    /// Build a player for an animation in a JSONL string.
+    ///
+    /// # Arguments
+    /// * `jsonl` - JSONL string containing palettes, sprites, and animations
+    /// * `name` - Animation to play (default: the first animation in the input)
+    #[wasm_bindgen(constructor)]
+    pub fn new(jsonl: &str, name: Option<String>) -> AnimationPlayer {
+        let (registry, sprites, animations, mut warnings) = parse_animations(jsonl);
+
+        let timeline = match find_animation(&animations, name, &mut warnings) {
+            Some(anim) => {
+                let (timeline, anim_warnings) = precompute_animation(anim, &sprites, &registry);
+                warnings.extend(anim_warnings);
+                timeline
+            }
+            None => AnimationTimeline { frames: Vec::new(), durations: Vec::new(), loops: true },
+        };
+
+        AnimationPlayer { timeline, warnings }
+    }
+
+    /// Width of every frame in pixels
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.timeline.width()
+    }
+
+    /// Height of every frame in pixels
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.timeline.height()
+    }
+
+    /// Number of precomputed frames
+    #[wasm_bindgen(getter)]
+    pub fn frame_count(&self) -> usize {
+        self.timeline.frame_count()
+    }
+
+    /// Duration of one pass through the animation in milliseconds
+    #[wasm_bindgen(getter)]
+    pub fn duration_ms(&self) -> f64 {
+        self.timeline.total_duration_ms() as f64
+    }
+
+    /// Whether the animation loops
+    #[wasm_bindgen(getter)]
+    pub fn loops(&self) -> bool {
+        self.timeline.loops
+    }
+
+    /// Any warnings generated while parsing and precomputing frames
+    #[wasm_bindgen(getter)]
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+
+    /// Index of the frame visible `time_ms` milliseconds after playback started
+    pub fn frame_index_at(&self, time_ms: f64) -> usize {
+        self.timeline.frame_index_at(time_ms)
+    }
+
+    /// RGBA pixels (4 bytes per pixel) of the frame visible at `time_ms`.
+    ///
+    /// Returns an empty array if the animation has no frames.
+    pub fn frame_at(&self, time_ms: f64) -> Vec<u8> {
+        self.timeline.frame_at(time_ms).map(|f| f.as_raw().clone()).unwrap_or_default()
+    }
+
+    /// RGBA pixels of a frame by index (empty if out of range)
+    pub fn frame(&self, index: usize) -> Vec<u8> {
+        self.timeline.frames.get(index).map(|f| f.as_raw().clone()).unwrap_or_default()
+    }
+
+    /// Display duration of a frame by index in milliseconds (0 if out of range)
+    pub fn frame_duration(&self, index: usize) -> u32 {
+        self.timeline.durations.get(index).copied().unwrap_or(0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,4 +422,38 @@ mod tests {
         let result = render_to_png(r#"{"type": "palette", "name": "empty", "colors": {}}"#);
         assert!(result.is_empty());
     }
+
+    const BLINK: &str = r##"{"type": "sprite", "name": "on", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "off", "size": [1, 1], "palette": {"x": "#0000FF"}, "regions": {"x": {"points": [[0, 0]]}}}
+{"type": "animation", "name": "blink", "frames": ["on", "off"], "duration": 100}"##;
+
+    #[test]
+    fn test_animation_player_frame_at() {
+        let player = AnimationPlayer::new(BLINK, None);
+        assert_eq!(player.frame_count(), 2);
+        assert_eq!(player.width(), 1);
+        assert_eq!(player.duration_ms(), 200.0);
+        assert_eq!(player.frame_at(0.0), vec![255, 0, 0, 255]);
+        assert_eq!(player.frame_at(150.0), vec![0, 0, 255, 255]);
+        assert_eq!(player.frame_index_at(250.0), 0);
+    }
+
+    #[test]
+    fn test_animation_player_unknown_name() {
+        let player = AnimationPlayer::new(BLINK, Some("run".to_string()));
+        assert_eq!(player.frame_count(), 0);
+        assert!(player.frame_at(0.0).is_empty());
+        assert!(player.warnings().iter().any(|w| w.contains("run")));
+    }
+
+    #[test]
+    fn test_render_animation_frame() {
+        let result = render_animation_frame(BLINK, Some("blink".to_string()), 150.0);
+        assert_eq!((result.width(), result.height()), (1, 1));
+        assert_eq!(result.pixels(), vec![0, 0, 255, 255]);
+
+        let result = render_animation_frame(BLINK, Some("run".to_string()), 0.0);
+        assert!(result.pixels().is_empty());
+        assert!(result.warnings().iter().any(|w| w.contains("run")));
+    }
 }
@@ -267,7 +267,7 @@ fn format_error_display(target_id: &str, error_msg: &str) -> String {
 }
 
 /// Get current timestamp for logging
-fn timestamp() -> String {
+pub(crate) fn timestamp() -> String {
     use std::time::SystemTime;
     let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
     let secs = now.as_secs() % 86400; // seconds since midnight
@@ -0,0 +1,75 @@
+// Pixelsrc web scene loader: draws a `pxl render --format web` bundle on a
+// canvas, with pointer parallax and repeating, scrolling layers.
+//
+//   <canvas id="scene" style="image-rendering: pixelated"></canvas>
+//   <script src="scene/loader.js"></script>
+//   <script>pxlScene(document.getElementById("scene"), "scene/scene.json");</script>
+//
+// Options: `range` is how far (in scene pixels) the camera pans as the pointer
+// crosses the canvas (default 16). The returned object's `setCamera(x, y)`
+// pans the camera directly, e.g. from page scroll.
+async function pxlScene(canvas, manifestUrl, options = {}) {
+  const url = new URL(manifestUrl, location.href);
+  const manifest = await (await fetch(url)).json();
+  const images = await Promise.all(
+    manifest.layers.map(
+      (layer) =>
+        new Promise((resolve, reject) => {
+          const image = new Image();
+          image.onload = () => resolve(image);
+          image.onerror = reject;
+          image.src = new URL(layer.file, url).href;
+        })
+    )
+  );
+
+  canvas.width = manifest.width;
+  canvas.height = manifest.height;
+  const ctx = canvas.getContext("2d");
+  ctx.imageSmoothingEnabled = false;
+
+  let camera = [0, 0];
+  const range = options.range ?? 16;
+  canvas.addEventListener("pointermove", (event) => {
+    const rect = canvas.getBoundingClientRect();
+    camera = [
+      ((event.clientX - rect.left) / rect.width - 0.5) * range,
+      ((event.clientY - rect.top) / rect.height - 0.5) * range,
+    ];
+  });
+
+  // Positions covering the canvas for a repeated axis, else just `start`
+  const positions = (start, size, span, repeats) => {
+    if (!repeats) return [start];
+    const out = [];
+    for (let p = (((start % size) + size) % size) - size; p < span; p += size) out.push(p);
+    return out;
+  };
+  const axes = { x: [true, false], y: [false, true], both: [true, true] };
+  const start = performance.now();
+  function draw(now) {
+    const seconds = (now - start) / 1000;
+    ctx.clearRect(0, 0, canvas.width, canvas.height);
+    manifest.layers.forEach((layer, i) => {
+      ctx.globalCompositeOperation = layer.blend;
+      ctx.globalAlpha = layer.opacity;
+      const [repeatX, repeatY] = axes[layer.repeat] ?? [false, false];
+      const [scrollX, scrollY] = layer.scroll ?? [0, 0];
+      const x = layer.x - camera[0] * layer.parallax[0] + scrollX * seconds;
+      const y = layer.y - camera[1] * layer.parallax[1] + scrollY * seconds;
+      for (const ox of positions(x, layer.width, canvas.width, repeatX)) {
+        for (const oy of positions(y, layer.height, canvas.height, repeatY)) {
+          ctx.drawImage(images[i], Math.round(ox), Math.round(oy));
+        }
+      }
+    });
+    requestAnimationFrame(draw);
+  }
+  requestAnimationFrame(draw);
+
+  return {
+    setCamera(x, y) {
+      camera = [x, y];
+    },
+  };
+}
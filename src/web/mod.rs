@@ -0,0 +1,242 @@
+//! Web bundle export: composition layers reassembled on an HTML canvas
+//!
+//! `pxl render --format web` writes a directory per composition holding one
+//! PNG per layer, a `scene.json` manifest and a small `loader.js` that draws
+//! the layers on a `<canvas>` with pointer parallax and scrolling layers:
+//!
+//! ```text
+//! scene/
+//!   scene.json      canvas size and layers, bottom first
+//!   layer0.png      each layer cropped to its visible pixels
+//!   layer1.png
+//!   loader.js       pxlScene(canvas, "scene/scene.json")
+//! ```
+//!
+//! Layers are cropped to their visible pixels, with the crop's position as
+//! the layer offset. Repeated layers are cut to one tile along their
+//! repeated axes, and scrolling layers that don't repeat keep the full canvas
+//! and wrap around it. Fully transparent layers are left out.
+
+use std::fs;
+use std::path::Path;
+
+use image::RgbaImage;
+use serde::Serialize;
+
+use crate::composition::{BlendMode, RenderedLayer};
+use crate::models::Repeat;
+use crate::output::{save_png, OutputError};
+
+/// The canvas loader written next to every bundle.
+pub const LOADER_JS: &str = include_str!("loader.js");
+
+/// The `scene.json` manifest of a web bundle.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebManifest {
+    /// Composition name
+    pub name: String,
+    /// Canvas width in pixels
+    pub width: u32,
+    /// Canvas height in pixels
+    pub height: u32,
+    /// Layers, bottom first
+    pub layers: Vec<WebLayer>,
+}
+
+/// A layer of a web bundle.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebLayer {
+    /// Layer name
+    pub name: String,
+    /// PNG file, relative to the manifest
+    pub file: String,
+    /// Horizontal offset on the canvas
+    pub x: u32,
+    /// Vertical offset on the canvas
+    pub y: u32,
+    /// Image width
+    pub width: u32,
+    /// Image height
+    pub height: u32,
+    /// Canvas `globalCompositeOperation`
+    pub blend: String,
+    /// Layer opacity (0.0-1.0)
+    pub opacity: f64,
+    /// Camera parallax factors `[x, y]` (1.0 moves with the scene)
+    pub parallax: [f64; 2],
+    /// Axes the image is tiled along to cover the canvas
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<Repeat>,
+    /// Drift in pixels per second, wrapping around the canvas
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scroll: Option<[f64; 2]>,
+}
+
+/// A web bundle ready to be written: its manifest and one image per layer.
+#[derive(Debug, Clone)]
+pub struct WebBundle {
+    pub manifest: WebManifest,
+    pub images: Vec<RgbaImage>,
+}
+
+/// Canvas `globalCompositeOperation` for a blend mode.
+///
+/// Returns `None` for modes a canvas cannot express (`subtract`); those
+/// layers are drawn with `source-over`.
+pub fn canvas_composite_op(blend: BlendMode) -> Option<&'static str> {
+    match blend {
+        BlendMode::Normal => Some("source-over"),
+        BlendMode::Multiply => Some("multiply"),
+        BlendMode::Screen => Some("screen"),
+        BlendMode::Overlay => Some("overlay"),
+        BlendMode::Add => Some("lighter"),
+        BlendMode::Subtract => None,
+        BlendMode::Difference => Some("difference"),
+        BlendMode::Darken => Some("darken"),
+        BlendMode::Lighten => Some("lighten"),
+    }
+}
+
+/// Bounding box `[x, y, width, height]` of an image's visible pixels.
+pub fn content_bounds(image: &RgbaImage) -> Option<[u32; 4]> {
+    let mut bounds: Option<[u32; 4]> = None;
+    for (x, y, _) in image.enumerate_pixels().filter(|(_, _, p)| p[3] > 0) {
+        let [x0, y0, x1, y1] = bounds.get_or_insert([x, y, x, y]);
+        *x0 = (*x0).min(x);
+        *y0 = (*y0).min(y);
+        *x1 = (*x1).max(x);
+        *y1 = (*y1).max(y);
+    }
+    bounds.map(|[x0, y0, x1, y1]| [x0, y0, x1 - x0 + 1, y1 - y0 + 1])
+}
+
+impl WebBundle {
+    /// Build a bundle from a composition's rendered layers (bottom first).
+    pub fn new(name: &str, width: u32, height: u32, layers: Vec<RenderedLayer>) -> Self {
+        let mut manifest =
+            WebManifest { name: name.to_string(), width, height, layers: Vec::new() };
+        let mut images = Vec::new();
+        for layer in layers {
+            let Some([x, y, w, h]) = content_bounds(&layer.image) else {
+                continue;
+            };
+            // Scrolling layers wrap around the canvas
+            let repeat = layer.repeat.or(layer.scroll.map(|_| Repeat::Both));
+            let [repeat_x, repeat_y] = repeat.map_or([false, false], Repeat::axes);
+            let tile_size = match layer.repeat {
+                Some(_) => layer.tile_size,
+                None => [layer.image.width(), layer.image.height()],
+            };
+            let (x, w) = if repeat_x { (0, tile_size[0]) } else { (x, w) };
+            let (y, h) = if repeat_y { (0, tile_size[1]) } else { (y, h) };
+            let image = image::imageops::crop_imm(&layer.image, x, y, w, h).to_image();
+            manifest.layers.push(WebLayer {
+                name: layer.name,
+                file: format!("layer{}.png", images.len()),
+                x,
+                y,
+                width: image.width(),
+                height: image.height(),
+                blend: canvas_composite_op(layer.blend).unwrap_or("source-over").to_string(),
+                opacity: layer.opacity.clamp(0.0, 1.0),
+                parallax: layer.parallax,
+                repeat,
+                scroll: layer.scroll,
+            });
+            images.push(image);
+        }
+        WebBundle { manifest, images }
+    }
+
+    /// Write the manifest, layer images and loader into `dir`, creating it.
+    pub fn save(&self, dir: &Path) -> Result<(), OutputError> {
+        fs::create_dir_all(dir)?;
+        for (layer, image) in self.manifest.layers.iter().zip(&self.images) {
+            save_png(image, &dir.join(&layer.file))?;
+        }
+        let json = serde_json::to_string_pretty(&self.manifest).map_err(std::io::Error::other)?;
+        fs::write(dir.join("scene.json"), json + "\n")?;
+        fs::write(dir.join("loader.js"), LOADER_JS)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn layer(name: &str, pixels: &[(u32, u32)], scroll: Option<[f64; 2]>) -> RenderedLayer {
+        let mut image = RgbaImage::new(8, 4);
+        for &(x, y) in pixels {
+            image.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        }
+        RenderedLayer {
+            name: name.to_string(),
+            image,
+            blend: BlendMode::Add,
+            opacity: 0.5,
+            parallax: [0.25, 0.5],
+            repeat: None,
+            tile_size: [8, 4],
+            scroll,
+        }
+    }
+
+    #[test]
+    fn test_bundle_crops_layers() {
+        let bundle = WebBundle::new(
+            "sky",
+            8,
+            4,
+            vec![
+                layer("empty", &[], None),
+                layer("sun", &[(2, 1), (4, 2)], None),
+                layer("clouds", &[(1, 1)], Some([3.0, 0.0])),
+            ],
+        );
+
+        let layers = &bundle.manifest.layers;
+        assert_eq!(layers.len(), 2);
+        assert_eq!((layers[0].name.as_str(), layers[0].file.as_str()), ("sun", "layer0.png"));
+        assert_eq!([layers[0].x, layers[0].y, layers[0].width, layers[0].height], [2, 1, 3, 2]);
+        assert_eq!(layers[0].blend, "lighter");
+        assert_eq!(bundle.images[0].get_pixel(2, 1).0, [255, 0, 0, 255]);
+
+        // Scrolling layers keep the whole canvas to wrap around it
+        assert_eq!([layers[1].x, layers[1].y, layers[1].width, layers[1].height], [0, 0, 8, 4]);
+        assert_eq!(layers[1].scroll, Some([3.0, 0.0]));
+        assert_eq!(layers[1].repeat, Some(Repeat::Both));
+    }
+
+    #[test]
+    fn test_bundle_cuts_repeated_tiles() {
+        let mut hills = layer("hills", &[(0, 2), (1, 3), (2, 2), (3, 3)], None);
+        hills.repeat = Some(Repeat::X);
+        hills.tile_size = [2, 4];
+        let bundle = WebBundle::new("sky", 8, 4, vec![hills]);
+
+        // One tile across, cropped to the content down
+        let hills = &bundle.manifest.layers[0];
+        assert_eq!([hills.x, hills.y, hills.width, hills.height], [0, 2, 2, 2]);
+        assert_eq!(hills.repeat, Some(Repeat::X));
+    }
+
+    #[test]
+    fn test_save_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = WebBundle::new("sky", 8, 4, vec![layer("sun", &[(0, 0)], None)]);
+        bundle.save(&dir.path().join("sky")).unwrap();
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("sky/scene.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest["layers"][0]["file"], "layer0.png");
+        assert_eq!(manifest["layers"][0]["parallax"], serde_json::json!([0.25, 0.5]));
+        assert!(manifest["layers"][0].get("scroll").is_none());
+        assert!(dir.path().join("sky/layer0.png").exists());
+        assert!(fs::read_to_string(dir.path().join("sky/loader.js"))
+            .unwrap()
+            .contains("function pxlScene"));
+    }
+}
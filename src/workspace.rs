@@ -0,0 +1,444 @@
+//! Multi-document workspaces
+//!
+//! A [`Workspace`] owns several named documents, typically one per file, and
+//! merges their objects with deterministic precedence:
+//!
+//! ```
+//! use pixelsrc::workspace::{MergeOptions, Workspace};
+//!
+//! let mut workspace = Workspace::new();
+//! workspace.add_source("base.pxl", r##"{"type": "palette", "name": "ui", "colors": {"a": "#000"}}"##);
+//! workspace.add_source("theme.pxl", r##"{"type": "palette", "name": "ui", "colors": {"a": "#FFF"}}"##);
+//!
+//! let merged = workspace.merge(&MergeOptions::default());
+//! assert_eq!(merged.origin("palette", "ui"), Some("theme.pxl"));
+//! assert_eq!(merged.palettes().get("ui").unwrap().colors["a"], "#FFF");
+//! assert_eq!(merged.warnings.len(), 1);
+//! ```
+//!
+//! Documents keep the order they were added in. By default a later document
+//! overrides an earlier one ([`Precedence::LastWins`]) and each override is
+//! reported as a warning. Within one document the last definition always
+//! wins, as when registering a single file.
+//!
+//! With [`MergeOptions::prefix`], every object is also available under its
+//! qualified name `document:name` (see [`qualify`]), so overridden
+//! definitions stay reachable. References inside objects are not rewritten:
+//! a short name still resolves to the winning definition.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::models::TtpObject;
+use crate::parser::{parse_str, ParseResult};
+use crate::registry::{CompositionRegistry, PaletteRegistry, SpriteRegistry, TransformRegistry};
+use crate::warning::{Warning, WarningCategory};
+
+/// Separator between a document name and an object name in qualified names.
+pub const SEPARATOR: char = ':';
+
+/// The qualified name of object `name` in `document`: `document:name`.
+pub fn qualify(document: &str, name: &str) -> String {
+    format!("{}{}{}", document, SEPARATOR, name)
+}
+
+/// Split a qualified name into its document and object name.
+///
+/// Splits at the last separator, so document names may contain one (e.g.
+/// Windows paths). Unqualified names have no document.
+pub fn split_qualified(name: &str) -> (Option<&str>, &str) {
+    match name.rsplit_once(SEPARATOR) {
+        Some((document, name)) => (Some(document), name),
+        None => (None, name),
+    }
+}
+
+/// Which document's definition wins when several define the same object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Precedence {
+    /// Later documents override earlier ones (overlay)
+    #[default]
+    LastWins,
+    /// The first document to define a name keeps it
+    FirstWins,
+}
+
+/// Options for [`Workspace::merge`].
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    /// Which definition wins a name collision between documents
+    pub precedence: Precedence,
+    /// Also register every object under its qualified `document:name`
+    pub prefix: bool,
+}
+
+/// One named document in a [`Workspace`].
+#[derive(Debug, Clone)]
+pub struct WorkspaceDocument {
+    name: String,
+    parsed: ParseResult,
+}
+
+impl WorkspaceDocument {
+    /// The document's name (for files, its path).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The parsed objects, in source order.
+    pub fn objects(&self) -> &[TtpObject] {
+        &self.parsed.objects
+    }
+
+    /// Warnings from parsing the document.
+    pub fn warnings(&self) -> &[crate::models::Warning] {
+        &self.parsed.warnings
+    }
+
+    /// The 1-based line the `index`th object starts on.
+    fn line(&self, index: usize) -> usize {
+        self.parsed.spans.get(index).map_or(1, |span| span.start)
+    }
+}
+
+/// A set of named documents merged into one namespace.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    documents: Vec<WorkspaceDocument>,
+}
+
+impl Workspace {
+    /// Create an empty workspace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a parsed document.
+    ///
+    /// Replacing an existing document keeps its place in the precedence order.
+    pub fn insert(&mut self, name: impl Into<String>, parsed: ParseResult) {
+        let name = name.into();
+        match self.documents.iter_mut().find(|d| d.name == name) {
+            Some(document) => document.parsed = parsed,
+            None => self.documents.push(WorkspaceDocument { name, parsed }),
+        }
+    }
+
+    /// Parse `source` and add it as document `name`.
+    pub fn add_source(&mut self, name: impl Into<String>, source: &str) {
+        self.insert(name, parse_str(source));
+    }
+
+    /// Read and add the file at `path`, named by its path.
+    pub fn add_file(&mut self, path: &Path) -> std::io::Result<()> {
+        let parsed = crate::parser::parse_file(path)?;
+        self.insert(path.display().to_string(), parsed);
+        Ok(())
+    }
+
+    /// Remove document `name`, returning it if it was present.
+    pub fn remove(&mut self, name: &str) -> Option<WorkspaceDocument> {
+        let index = self.documents.iter().position(|d| d.name == name)?;
+        Some(self.documents.remove(index))
+    }
+
+    /// The document named `name`.
+    pub fn get(&self, name: &str) -> Option<&WorkspaceDocument> {
+        self.documents.iter().find(|d| d.name == name)
+    }
+
+    /// All documents, in the order they were added.
+    pub fn documents(&self) -> impl Iterator<Item = &WorkspaceDocument> {
+        self.documents.iter()
+    }
+
+    /// Number of documents.
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether the workspace has no documents.
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Merge every document's objects into one namespace.
+    ///
+    /// Imports are kept from every document. Other objects are keyed by type
+    /// and name (sprites and variants share names) and resolved by
+    /// `options.precedence`.
+    pub fn merge(&self, options: &MergeOptions) -> MergedWorkspace {
+        // Each document's own definitions, last one per key
+        let mut defined: Vec<HashMap<(&str, &str), usize>> = Vec::new();
+        for document in &self.documents {
+            let mut keys = HashMap::new();
+            for (index, object) in document.objects().iter().enumerate() {
+                if let Some(key) = merge_key(object) {
+                    keys.insert(key, index);
+                }
+            }
+            defined.push(keys);
+        }
+
+        // The winning document for each key
+        let mut winners: HashMap<(&str, &str), usize> = HashMap::new();
+        for (doc, keys) in defined.iter().enumerate() {
+            for key in keys.keys() {
+                let winner = winners.entry(*key).or_insert(doc);
+                if options.precedence == Precedence::LastWins {
+                    *winner = doc;
+                }
+            }
+        }
+
+        let mut merged = MergedWorkspace::default();
+        for (doc, document) in self.documents.iter().enumerate() {
+            for (index, object) in document.objects().iter().enumerate() {
+                let Some(key) = merge_key(object) else {
+                    merged.objects.push(object.clone());
+                    continue;
+                };
+                if defined[doc][&key] != index {
+                    continue;
+                }
+                let winner = winners[&key];
+                if winner == doc {
+                    merged.objects.push(object.clone());
+                    merged.origins.insert(owned(key), document.name.clone());
+                } else {
+                    let message = format!(
+                        "overridden by the definition in '{}'",
+                        self.documents[winner].name
+                    );
+                    merged.warnings.push(
+                        Warning::new(WarningCategory::Import, message)
+                            .for_object(object.kind(), object.name())
+                            .at(Some(Path::new(&document.name)), document.line(index)),
+                    );
+                }
+            }
+        }
+
+        if options.prefix {
+            for (doc, document) in self.documents.iter().enumerate() {
+                for (index, object) in document.objects().iter().enumerate() {
+                    let Some(key) = merge_key(object) else { continue };
+                    if defined[doc][&key] != index {
+                        continue;
+                    }
+                    let qualified = qualify(&document.name, object.name());
+                    merged
+                        .origins
+                        .insert((key.0.to_string(), qualified.clone()), document.name.clone());
+                    merged.objects.push(renamed(object, qualified));
+                }
+            }
+        }
+
+        merged
+    }
+}
+
+/// The namespace and name an object is merged under; `None` for imports.
+fn merge_key(object: &TtpObject) -> Option<(&'static str, &str)> {
+    match object {
+        TtpObject::Import(_) => None,
+        // Variants resolve through the same names as sprites
+        TtpObject::Variant(v) => Some(("sprite", &v.name)),
+        _ => Some((object.kind(), object.name())),
+    }
+}
+
+fn owned((kind, name): (&str, &str)) -> (String, String) {
+    (kind.to_string(), name.to_string())
+}
+
+/// A copy of `object` named `name`.
+fn renamed(object: &TtpObject, name: String) -> TtpObject {
+    let mut object = object.clone();
+    match &mut object {
+        TtpObject::Palette(p) => p.name = name,
+        TtpObject::Sprite(s) => s.name = name,
+        TtpObject::Variant(v) => v.name = name,
+        TtpObject::Composition(c) => c.name = name,
+        TtpObject::Animation(a) => a.name = name,
+        TtpObject::Sequence(s) => s.name = name,
+        TtpObject::Particle(p) => p.name = name,
+        TtpObject::Transform(t) => t.name = name,
+        TtpObject::StateRules(sr) => sr.name = name,
+        TtpObject::Theme(t) => t.name = name,
+        TtpObject::Stamp(s) => s.name = name,
+        TtpObject::RandomSet(r) => r.name = name,
+        TtpObject::Import(_) => {}
+    }
+    object
+}
+
+/// The result of [`Workspace::merge`].
+#[derive(Debug, Clone, Default)]
+pub struct MergedWorkspace {
+    /// Winning objects in document order, then source order, followed by
+    /// qualified copies when prefixing
+    pub objects: Vec<TtpObject>,
+    /// One warning per definition overridden by another document
+    pub warnings: Vec<Warning>,
+    /// (namespace, name) → defining document
+    origins: HashMap<(String, String), String>,
+}
+
+impl MergedWorkspace {
+    /// The document whose definition of `kind` `name` won.
+    pub fn origin(&self, kind: &str, name: &str) -> Option<&str> {
+        let kind = if kind == "variant" { "sprite" } else { kind };
+        self.origins.get(&(kind.to_string(), name.to_string())).map(String::as_str)
+    }
+
+    /// The merged palettes.
+    pub fn palettes(&self) -> PaletteRegistry {
+        let mut registry = PaletteRegistry::new();
+        for object in &self.objects {
+            if let TtpObject::Palette(p) = object {
+                registry.register(p.clone());
+            }
+        }
+        registry
+    }
+
+    /// The merged sprites and variants.
+    pub fn sprites(&self) -> SpriteRegistry {
+        let mut registry = SpriteRegistry::new();
+        for object in &self.objects {
+            match object {
+                TtpObject::Sprite(s) => registry.register_sprite(s.clone()),
+                TtpObject::Variant(v) => registry.register_variant(v.clone()),
+                _ => {}
+            }
+        }
+        registry
+    }
+
+    /// The merged compositions.
+    pub fn compositions(&self) -> CompositionRegistry {
+        let mut registry = CompositionRegistry::new();
+        for object in &self.objects {
+            if let TtpObject::Composition(c) = object {
+                registry.register(c.clone());
+            }
+        }
+        registry
+    }
+
+    /// The merged user-defined transforms.
+    pub fn transforms(&self) -> TransformRegistry {
+        let mut registry = TransformRegistry::new();
+        for object in &self.objects {
+            if let TtpObject::Transform(t) = object {
+                registry.register(t.clone());
+            }
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r##"{"type": "palette", "name": "ui", "colors": {"a": "#000000"}}
+{"type": "sprite", "name": "hero", "size": [1, 1], "palette": "ui", "regions": {"a": {"rect": [0, 0, 1, 1]}}}
+{"type": "import", "from": "./shared"}"##;
+
+    const THEME: &str = r##"{"type": "palette", "name": "ui", "colors": {"a": "#FFFFFF"}}
+{"type": "variant", "name": "hero", "base": "hero", "palette": {"a": "#FF0000"}}
+{"type": "import", "from": "./shared"}"##;
+
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        workspace.add_source("base", BASE);
+        workspace.add_source("theme", THEME);
+        workspace
+    }
+
+    fn palette_color(merged: &MergedWorkspace, name: &str) -> String {
+        merged.palettes().get(name).unwrap().colors["a"].clone()
+    }
+
+    #[test]
+    fn test_qualified_names() {
+        assert_eq!(qualify("chars/hero", "idle"), "chars/hero:idle");
+        assert_eq!(split_qualified("chars/hero:idle"), (Some("chars/hero"), "idle"));
+        assert_eq!(split_qualified("C:/art/hero.pxl:idle"), (Some("C:/art/hero.pxl"), "idle"));
+        assert_eq!(split_qualified("idle"), (None, "idle"));
+    }
+
+    #[test]
+    fn test_merge_last_wins() {
+        let merged = workspace().merge(&MergeOptions::default());
+        assert_eq!(palette_color(&merged, "ui"), "#FFFFFF");
+        assert_eq!(merged.origin("palette", "ui"), Some("theme"));
+        // A variant and a sprite with the same name collide
+        assert_eq!(merged.origin("sprite", "hero"), Some("theme"));
+        assert_eq!(merged.origin("variant", "hero"), Some("theme"));
+
+        let imports = merged.objects.iter().filter(|o| o.kind() == "import").count();
+        assert_eq!(imports, 2, "imports are kept from every document");
+
+        assert_eq!(merged.warnings.len(), 2);
+        let warning = &merged.warnings[0];
+        assert_eq!(
+            warning.to_string(),
+            "base:1: palette 'ui': overridden by the definition in 'theme'"
+        );
+        assert_eq!(merged.warnings[1].line, Some(2));
+    }
+
+    #[test]
+    fn test_merge_first_wins() {
+        let options = MergeOptions { precedence: Precedence::FirstWins, ..Default::default() };
+        let merged = workspace().merge(&options);
+        assert_eq!(palette_color(&merged, "ui"), "#000000");
+        assert_eq!(merged.origin("sprite", "hero"), Some("base"));
+        assert!(merged.warnings[0].to_string().starts_with("theme:1:"));
+    }
+
+    #[test]
+    fn test_merge_prefixed() {
+        let options = MergeOptions { prefix: true, ..Default::default() };
+        let merged = workspace().merge(&options);
+        assert_eq!(palette_color(&merged, "ui"), "#FFFFFF");
+        assert_eq!(palette_color(&merged, "base:ui"), "#000000");
+        assert_eq!(palette_color(&merged, "theme:ui"), "#FFFFFF");
+        assert_eq!(merged.origin("palette", "base:ui"), Some("base"));
+
+        let sprites = merged.sprites();
+        assert!(sprites.contains("base:hero"));
+        assert!(sprites.contains("theme:hero"));
+    }
+
+    #[test]
+    fn test_last_definition_in_a_document_wins() {
+        let mut workspace = Workspace::new();
+        workspace.add_source(
+            "one",
+            r##"{"type": "palette", "name": "p", "colors": {"a": "#111111"}}
+{"type": "palette", "name": "p", "colors": {"a": "#222222"}}"##,
+        );
+        let options = MergeOptions { precedence: Precedence::FirstWins, ..Default::default() };
+        let merged = workspace.merge(&options);
+        assert_eq!(palette_color(&merged, "p"), "#222222");
+        assert!(merged.warnings.is_empty(), "only overrides between documents warn");
+    }
+
+    #[test]
+    fn test_replace_keeps_position() {
+        let mut workspace = workspace();
+        workspace.add_source("base", BASE.replace("#000000", "#123456").as_str());
+        let names: Vec<&str> = workspace.documents().map(|d| d.name()).collect();
+        assert_eq!(names, ["base", "theme"]);
+        assert_eq!(palette_color(&workspace.merge(&MergeOptions::default()), "ui"), "#FFFFFF");
+
+        assert!(workspace.remove("theme").is_some());
+        assert_eq!(workspace.len(), 1);
+        assert_eq!(palette_color(&workspace.merge(&MergeOptions::default()), "ui"), "#123456");
+    }
+}
@@ -941,23 +941,58 @@ mod export_tests {
             frames: HashMap::from([
                 (
                     "idle_1".to_string(),
-                    AtlasFrame { x: 0, y: 0, w: 32, h: 32, origin: Some([16, 32]), boxes: None },
+                    AtlasFrame {
+                        x: 0,
+                        y: 0,
+                        w: 32,
+                        h: 32,
+                        origin: Some([16, 32]),
+                        ..Default::default()
+                    },
                 ),
                 (
                     "idle_2".to_string(),
-                    AtlasFrame { x: 32, y: 0, w: 32, h: 32, origin: Some([16, 32]), boxes: None },
+                    AtlasFrame {
+                        x: 32,
+                        y: 0,
+                        w: 32,
+                        h: 32,
+                        origin: Some([16, 32]),
+                        ..Default::default()
+                    },
                 ),
                 (
                     "walk_1".to_string(),
-                    AtlasFrame { x: 64, y: 0, w: 32, h: 32, origin: Some([16, 32]), boxes: None },
+                    AtlasFrame {
+                        x: 64,
+                        y: 0,
+                        w: 32,
+                        h: 32,
+                        origin: Some([16, 32]),
+                        ..Default::default()
+                    },
                 ),
                 (
                     "walk_2".to_string(),
-                    AtlasFrame { x: 96, y: 0, w: 32, h: 32, origin: Some([16, 32]), boxes: None },
+                    AtlasFrame {
+                        x: 96,
+                        y: 0,
+                        w: 32,
+                        h: 32,
+                        origin: Some([16, 32]),
+                        ..Default::default()
+                    },
                 ),
                 (
                     "jump".to_string(),
-                    AtlasFrame { x: 0, y: 32, w: 32, h: 48, origin: Some([16, 48]), boxes: None },
+                    AtlasFrame {
+                        x: 0,
+                        y: 32,
+                        w: 32,
+                        h: 48,
+                        origin: Some([16, 48]),
+                        ..Default::default()
+                    },
                 ),
             ]),
             animations: HashMap::from([
@@ -967,6 +1002,10 @@ mod export_tests {
                         frames: vec!["idle_1".to_string(), "idle_2".to_string()],
                         fps: 8,
                         tags: None,
+                        durations: None,
+                        canvas: None,
+                        offsets: None,
+                        direction: None,
                     },
                 ),
                 (
@@ -975,6 +1014,10 @@ mod export_tests {
                         frames: vec!["walk_1".to_string(), "walk_2".to_string()],
                         fps: 12,
                         tags: None,
+                        durations: None,
+                        canvas: None,
+                        offsets: None,
+                        direction: None,
                     },
                 ),
             ]),
@@ -1050,7 +1093,7 @@ mod export_tests {
             size: [64, 64],
             frames: HashMap::from([(
                 "icon".to_string(),
-                AtlasFrame { x: 0, y: 0, w: 64, h: 64, origin: None, boxes: None },
+                AtlasFrame { x: 0, y: 0, w: 64, h: 64, ..Default::default() },
             )]),
             animations: HashMap::new(),
         };
@@ -1111,7 +1154,7 @@ mod export_tests {
             size: [128, 128],
             frames: HashMap::from([(
                 "sprite".to_string(),
-                AtlasFrame { x: 10, y: 20, w: 32, h: 32, origin: None, boxes: None },
+                AtlasFrame { x: 10, y: 20, w: 32, h: 32, ..Default::default() },
             )]),
             animations: HashMap::new(),
         };
@@ -1142,6 +1185,9 @@ mod export_tests {
                     h: 32,
                     origin: Some([16, 32]), // Bottom center
                     boxes: None,
+                    attach_in: None,
+                    attach_out: None,
+                    slices: None,
                 },
             )]),
             animations: HashMap::new(),
@@ -1290,6 +1336,9 @@ mod export_tests {
                     h: 32,
                     origin: Some([16, 16]), // Center origin
                     boxes: None,
+                    attach_in: None,
+                    attach_out: None,
+                    slices: None,
                 },
             )]),
             animations: HashMap::new(),
@@ -1311,7 +1360,7 @@ mod export_tests {
             size: [64, 64],
             frames: HashMap::from([(
                 "icon".to_string(),
-                AtlasFrame { x: 0, y: 0, w: 64, h: 64, origin: None, boxes: None },
+                AtlasFrame { x: 0, y: 0, w: 64, h: 64, ..Default::default() },
             )]),
             animations: HashMap::new(),
         };
@@ -1350,6 +1399,9 @@ mod export_tests {
                     h: 32,
                     origin: None,
                     boxes: None,
+                    attach_in: None,
+                    attach_out: None,
+                    slices: None,
                 },
             );
         }
@@ -0,0 +1,111 @@
+//! CLI integration tests for --premultiply-alpha and --matte
+//!
+//! These tests verify that alpha handling is applied the same way to sprite
+//! PNGs, spritesheets, and GIF frames.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+
+const FIXTURE: &str = "tests/fixtures/valid/alpha_glass.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_alpha_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn render(args: &[&str], output_path: &Path) {
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE])
+        .args(args)
+        .arg("-o")
+        .arg(output_path)
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+}
+
+/// Test that --premultiply-alpha scales color channels by alpha
+#[test]
+fn test_premultiply_alpha_sprite() {
+    let output_path = output_dir().join("pane_premultiplied.png");
+    render(&["--sprite", "pane", "--premultiply-alpha"], &output_path);
+
+    let img = image::open(&output_path).expect("Failed to open PNG").to_rgba8();
+    assert_eq!(img.get_pixel(0, 0).0, [128, 0, 0, 128]);
+    assert_eq!(img.get_pixel(1, 0).0, [0, 0, 0, 0]);
+    assert_eq!(img.get_pixel(2, 0).0, [0, 255, 0, 255]);
+}
+
+/// Test that --matte flattens the sprite onto an opaque background
+#[test]
+fn test_matte_sprite() {
+    let output_path = output_dir().join("pane_matte.png");
+    render(&["--sprite", "pane", "--matte", "white"], &output_path);
+
+    let img = image::open(&output_path).expect("Failed to open PNG").to_rgba8();
+    assert_eq!(img.get_pixel(0, 0).0, [255, 127, 127, 255]);
+    assert_eq!(img.get_pixel(1, 0).0, [255, 255, 255, 255]);
+    assert_eq!(img.get_pixel(2, 0).0, [0, 255, 0, 255]);
+}
+
+/// Test that --matte applies to every GIF frame
+#[test]
+fn test_matte_gif() {
+    let output_path = output_dir().join("flicker_matte.gif");
+    render(&["--gif", "--matte", "#0000FF"], &output_path);
+
+    let file = fs::File::open(&output_path).expect("Failed to open GIF");
+    let decoder = GifDecoder::new(std::io::BufReader::new(file)).expect("Invalid GIF");
+    let frames: Vec<_> = decoder.into_frames().map(|f| f.expect("Invalid frame")).collect();
+    assert_eq!(frames.len(), 2);
+    for frame in &frames {
+        let buffer = frame.buffer();
+        assert_eq!(buffer.get_pixel(1, 0).0, [0, 0, 255, 255], "Transparent pixel is matted");
+    }
+    assert_eq!(frames[1].buffer().get_pixel(0, 0).0, [0, 0, 255, 255]);
+}
+
+/// Test that --premultiply-alpha applies to spritesheets
+#[test]
+fn test_premultiply_alpha_spritesheet() {
+    let output_path = output_dir().join("flicker_sheet.png");
+    render(&["--spritesheet", "--premultiply-alpha"], &output_path);
+
+    let img = image::open(&output_path).expect("Failed to open PNG").to_rgba8();
+    assert_eq!(img.dimensions(), (6, 1));
+    assert_eq!(img.get_pixel(0, 0).0, [128, 0, 0, 128]);
+    assert_eq!(img.get_pixel(3, 0).0, [0, 0, 0, 0]);
+}
+
+/// Test that an invalid matte color is rejected
+#[test]
+fn test_matte_invalid_color() {
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--matte", "#GG0000"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--matte"));
+}
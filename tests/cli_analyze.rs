@@ -0,0 +1,180 @@
+//! CLI integration tests for the analyze command (pxl analyze)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_analyze_test").join(test);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+const HEROES: &str = r##"{"type": "palette", "name": "hero", "colors": {"_": "#00000000", "hat": "#AA0000", "body": "#3366FF"}}
+{"type": "sprite", "name": "hero_right", "size": [4, 4], "palette": "hero", "regions": {"hat": {"rect": [0, 0, 3, 1]}, "body": {"rect": [1, 1, 2, 3]}}}
+{"type": "sprite", "name": "hero_left", "size": [4, 4], "palette": "hero", "regions": {"hat": {"rect": [1, 0, 3, 1]}, "body": {"rect": [1, 1, 2, 3]}}}
+{"type": "sprite", "name": "ghost", "size": [4, 4], "palette": "hero", "regions": {"body": {"points": [[0, 0], [3, 3]]}}}"##;
+
+/// Test that a mirrored copy is reported across files, and unrelated sprites are not
+#[test]
+fn test_analyze_duplicates_json() {
+    let dir = output_dir("duplicates");
+    let first = dir.join("heroes.pxl");
+    let second = dir.join("copy.pxl");
+    fs::write(&first, HEROES).unwrap();
+    fs::write(&second, HEROES.lines().take(2).collect::<Vec<_>>().join("\n")).unwrap();
+
+    let output = Command::new(pxl_binary())
+        .arg("analyze")
+        .arg(&first)
+        .arg(&second)
+        .args(["--duplicates", "--format", "json"])
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["sprites_compared"], 4);
+
+    let duplicates = report["duplicates"].as_array().unwrap();
+    let pairs: Vec<(&str, &str, &str)> = duplicates
+        .iter()
+        .map(|d| {
+            (
+                d["first"]["sprite"].as_str().unwrap(),
+                d["second"]["sprite"].as_str().unwrap(),
+                d["transform"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert!(pairs.contains(&("hero_right", "hero_left", "mirrored")), "{:?}", pairs);
+    assert!(pairs.contains(&("hero_right", "hero_right", "identical")), "{:?}", pairs);
+    assert!(duplicates
+        .iter()
+        .all(|d| d["first"]["sprite"] != "ghost" && d["second"]["sprite"] != "ghost"));
+}
+
+/// Test that an out-of-range threshold is rejected
+#[test]
+fn test_analyze_duplicates_invalid_threshold() {
+    let dir = output_dir("threshold");
+    let file = dir.join("heroes.pxl");
+    fs::write(&file, HEROES).unwrap();
+
+    let output = Command::new(pxl_binary())
+        .arg("analyze")
+        .arg(&file)
+        .args(["--duplicates", "--threshold", "1.5"])
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--threshold"));
+}
+
+/// Test that a saved JSON report can be compared against the current corpus
+#[test]
+fn test_analyze_compare_reports() {
+    let dir = output_dir("compare");
+    let file = dir.join("heroes.pxl");
+    let old = dir.join("old.json");
+    fs::write(&file, HEROES.lines().take(2).collect::<Vec<_>>().join("\n")).unwrap();
+
+    let output = Command::new(pxl_binary())
+        .arg("analyze")
+        .arg(&file)
+        .args(["--format", "json", "-o"])
+        .arg(&old)
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&old).unwrap()).unwrap();
+    assert_eq!(report["tokens"]["{hat}"], 3);
+    assert_eq!(report["dimensions"]["4x4"], 1);
+    assert!(report["compression"]["rle_ratio"].as_f64().unwrap() > 1.0);
+
+    // Add two more sprites and compare
+    fs::write(&file, HEROES).unwrap();
+    let output = Command::new(pxl_binary())
+        .arg("analyze")
+        .arg(&file)
+        .arg("--compare")
+        .arg(&old)
+        .output()
+        .expect("Failed to execute pxl");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("total_sprites"), "stdout: {}", stdout);
+    assert!(stdout.contains("(+2)"), "stdout: {}", stdout);
+    assert!(stdout.contains("TOKEN CHANGES"), "stdout: {}", stdout);
+}
+
+/// Test that an unreadable comparison report is an error
+#[test]
+fn test_analyze_compare_invalid_report() {
+    let dir = output_dir("compare_invalid");
+    let file = dir.join("heroes.pxl");
+    let old = dir.join("old.json");
+    fs::write(&file, HEROES).unwrap();
+    fs::write(&old, "not json").unwrap();
+
+    let output = Command::new(pxl_binary())
+        .arg("analyze")
+        .arg(&file)
+        .arg("--compare")
+        .arg(&old)
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid analysis report"));
+}
+
+/// Test color statistics of a rendered sprite, and suggestions for unknown names
+#[test]
+fn test_analyze_colors() {
+    let dir = output_dir("colors");
+    let file = dir.join("heroes.pxl");
+    fs::write(&file, HEROES).unwrap();
+
+    let output = Command::new(pxl_binary())
+        .arg("analyze")
+        .arg(&file)
+        .args(["--colors", "hero_right", "--format", "json"])
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["kind"], "sprite");
+    assert_eq!(report["unique_colors"], 2);
+    assert_eq!(report["alpha"]["opaque_pixels"], 9);
+    assert_eq!(report["histogram"][0]["color"], "#00000000");
+    assert_eq!(report["histogram"][1], serde_json::json!({"color": "#3366FFFF", "count": 6}));
+    assert!(report["palette_match"]["palette"].is_string());
+
+    let output = Command::new(pxl_binary())
+        .arg("analyze")
+        .arg(&file)
+        .args(["--colors", "hero_rigth"])
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hero_right"));
+}
@@ -0,0 +1,95 @@
+//! CLI integration tests for `pxl animate`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"skin": "#FFCC99", "shirt": "#3366CC", "boots": "#222222"}}
+{"type": "sprite", "name": "hero", "size": [8, 8], "palette": "p", "regions": {"skin": {"rect": [2, 0, 4, 3]}, "shirt": {"rect": [1, 3, 6, 4]}, "boots": {"rect": [1, 7, 6, 1]}}}
+"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn setup() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("hero.pxl"), SOURCE).unwrap();
+    dir
+}
+
+/// Test that breathe prints sprites and an animation that render as a GIF
+#[test]
+fn test_animate_breathe() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["animate", "hero.pxl", "hero", "--preset", "breathe"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let objects: Vec<serde_json::Value> =
+        stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(objects.len(), 3);
+    assert_eq!(objects[0]["name"], "hero_breathe_1");
+    assert_eq!(objects[1]["size"], serde_json::json!([8, 8]));
+    assert_eq!(objects[2]["type"], "animation");
+    assert_eq!(
+        objects[2]["frames"],
+        serde_json::json!(["hero", "hero_breathe_1", "hero_breathe_2", "hero_breathe_1"])
+    );
+
+    fs::write(dir.path().join("all.pxl"), format!("{}{}", SOURCE, stdout)).unwrap();
+    let output = pxl(dir.path(), &["render", "all.pxl", "--gif", "-o", "idle.gif"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(dir.path().join("idle.gif").exists());
+}
+
+/// Test that --output writes to a file and --frames shortens the loop
+#[test]
+fn test_animate_output_file() {
+    let dir = setup();
+
+    let args =
+        ["animate", "hero.pxl", "hero", "--preset", "breathe", "--frames", "2", "-o", "idle.pxl"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout.is_empty());
+
+    let written = fs::read_to_string(dir.path().join("idle.pxl")).unwrap();
+    let last: serde_json::Value = serde_json::from_str(written.lines().last().unwrap()).unwrap();
+    assert_eq!(last["frames"], serde_json::json!(["hero", "hero_breathe_1"]));
+}
+
+/// Test that unknown sprites and out-of-range options are rejected
+#[test]
+fn test_animate_errors() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["animate", "hero.pxl", "hro", "--preset", "breathe"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Did you mean 'hero'?"));
+
+    let args = ["animate", "hero.pxl", "hero", "--preset", "breathe", "--frames", "6"];
+    let output = pxl(dir.path(), &args);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 to 4 frames"));
+}
@@ -0,0 +1,109 @@
+//! CLI integration tests for per-frame animation durations
+//!
+//! These tests verify that `durations` reaches the GIF frame delays and the
+//! Aseprite atlas frame data, and that a mismatched count is rejected.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+
+const FIXTURE: &str = "tests/fixtures/valid/animation_durations.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_timing_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Test that each GIF frame gets its own delay
+#[test]
+fn test_gif_per_frame_delays() {
+    let output_path = output_dir().join("attack.gif");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--gif", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let file = fs::File::open(&output_path).expect("Failed to open GIF");
+    let decoder = GifDecoder::new(std::io::BufReader::new(file)).expect("Invalid GIF");
+    let delays: Vec<u32> = decoder
+        .into_frames()
+        .map(|f| {
+            let (numer, denom) = f.expect("Invalid frame").delay().numer_denom_ms();
+            numer / denom
+        })
+        .collect();
+    assert_eq!(delays, vec![300, 50, 200]);
+}
+
+/// Test that the Aseprite atlas records per-frame durations
+#[test]
+fn test_aseprite_atlas_frame_durations() {
+    let dir = output_dir().join("atlas");
+    fs::create_dir_all(&dir).ok();
+    let output_path = dir.join("attack.png");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--format", "atlas-aseprite", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let json_path = fs::read_dir(&dir)
+        .unwrap()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .find(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .expect("No atlas JSON written");
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(json_path).unwrap()).unwrap();
+
+    assert_eq!(json["frames"]["windup.png"]["duration"], 300);
+    assert_eq!(json["frames"]["strike.png"]["duration"], 50);
+    assert_eq!(json["frames"]["recover.png"]["duration"], 200);
+}
+
+/// Test that a durations list that doesn't match the frames fails in strict mode
+#[test]
+fn test_mismatched_durations_strict() {
+    let dir = output_dir();
+    let input = dir.join("mismatched.jsonl");
+    let source = fs::read_to_string(FIXTURE)
+        .unwrap()
+        .replace(r#""durations": [300, 50, "0.2s"]"#, r#""durations": [300, 50]"#);
+    fs::write(&input, source).unwrap();
+
+    let output = Command::new(pxl_binary())
+        .args(["render", "--gif", "--strict", "-o"])
+        .arg(dir.join("mismatched.gif"))
+        .arg(&input)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 durations for 3 frames"));
+}
@@ -0,0 +1,66 @@
+//! CLI integration tests for origins and attachment points in atlas exports
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "sprite", "name": "arm", "size": [8, 4], "palette": {"_": "#00000000", "s": "#C0C0C0"}, "regions": {"s": {"rect": [0, 1, 8, 2]}}, "metadata": {"origin": [0, 2], "attach_in": [1, 2], "attach_out": [7, 2]}}
+{"type": "sprite", "name": "gem", "size": [2, 2], "palette": {"_": "#00000000", "s": "#C0C0C0"}, "regions": {"s": {"rect": [0, 0, 2, 2]}}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn atlas_json(dir: &Path, format: &str, scale: &str) -> serde_json::Value {
+    let args = ["render", "arm.pxl", "--format", format, "--scale", scale, "-o", "atlas.png"];
+    let output = pxl(dir, &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_str(&fs::read_to_string(dir.join("atlas.json")).unwrap()).unwrap()
+}
+
+/// Test that attachment points reach the native, Aseprite and Godot atlas formats
+#[test]
+fn test_atlas_attach_points() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("arm.pxl"), SOURCE).unwrap();
+
+    // Native JSON scales the points with the image
+    let json = atlas_json(dir.path(), "atlas", "2");
+    assert_eq!(json["frames"]["arm"]["attach_in"], serde_json::json!([2, 4]));
+    assert_eq!(json["frames"]["arm"]["attach_out"], serde_json::json!([14, 4]));
+    assert!(json["frames"]["gem"].get("attach_out").is_none());
+
+    // Aseprite gets one slice per point, keyed by frame index ("arm.png" sorts first)
+    let json = atlas_json(dir.path(), "atlas-aseprite", "1");
+    let slices = json["meta"]["slices"].as_array().unwrap();
+    let names: Vec<&str> = slices.iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["origin", "attach_in", "attach_out"]);
+    let key = &slices[2]["keys"][0];
+    assert_eq!(key["frame"], 0);
+    assert_eq!(key["bounds"], serde_json::json!({"x": 7, "y": 2, "w": 1, "h": 1}));
+
+    let json = atlas_json(dir.path(), "atlas-godot", "1");
+    let sprites = json["textures"][0]["sprites"].as_array().unwrap();
+    let arm = sprites.iter().find(|s| s["name"] == "arm").unwrap();
+    assert_eq!(arm["attach_out"], serde_json::json!({"x": 7, "y": 2}));
+}
@@ -0,0 +1,83 @@
+//! CLI integration tests for `pxl validate --baseline` / `--write-baseline`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn sprite(name: &str, token: &str) -> String {
+    format!(
+        r##"{{"type": "sprite", "name": "{name}", "size": [2, 1], "palette": {{"x": "#FF0000"}}, "regions": {{"{token}": {{"rect": [0, 0, 1, 1]}}}}}}"##
+    )
+}
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that a baseline suppresses known issues, even after they move, but not new ones
+#[test]
+fn test_strict_validate_with_baseline() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("legacy.pxl"), sprite("hero", "y")).unwrap();
+
+    let output = pxl(dir.path(), &["validate", "--strict", "legacy.pxl"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = pxl(dir.path(), &["validate", "legacy.pxl", "--write-baseline", "baseline.json"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let baseline: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("baseline.json")).unwrap())
+            .unwrap();
+    assert_eq!(baseline["issues"]["sprite \"hero\""]["undefined_token"], 1);
+
+    // The known issue moves to another line
+    fs::write(dir.path().join("legacy.pxl"), format!("\n{}\n", sprite("hero", "y"))).unwrap();
+    let args = ["validate", "--strict", "--json", "--baseline", "baseline.json", "legacy.pxl"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stdout: {}", String::from_utf8_lossy(&output.stdout));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["baselined"], 1);
+
+    // A new issue in another object still fails
+    fs::write(
+        dir.path().join("legacy.pxl"),
+        format!("{}\n{}\n", sprite("hero", "y"), sprite("villain", "z")),
+    )
+    .unwrap();
+    let output = pxl(dir.path(), &args);
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["warnings"].as_array().unwrap().len(), 1);
+    assert_eq!(report["warnings"][0]["context"], "sprite \"villain\"");
+}
+
+/// Test that a missing baseline file is an error
+#[test]
+fn test_missing_baseline() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("legacy.pxl"), sprite("hero", "x")).unwrap();
+
+    let output = pxl(dir.path(), &["validate", "--baseline", "missing.json", "legacy.pxl"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Cannot read baseline 'missing.json'"));
+}
@@ -0,0 +1,101 @@
+//! CLI integration tests for the bridge command (pxl bridge)
+//!
+//! These tests use `--once` to run a single sync: edits saved to the PNG are
+//! folded back into the source, and the PNG is re-exported from the source.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use image::Rgba;
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_bridge_test").join(test);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+const FACE: &str = r##"{"type": "palette", "name": "face", "colors": {"_": "#00000000", "skin": "#FFCC99", "eye": "#000000"}}
+{"type": "sprite", "name": "face", "size": [4, 4], "palette": "face", "regions": {"skin": {"rect": [0, 0, 4, 4]}, "eye": {"points": [[1, 1]], "z": 10}}}"##;
+
+fn bridge_once(source: &Path, sprite: &str, png: &Path) -> Output {
+    Command::new(pxl_binary())
+        .arg("bridge")
+        .arg(source)
+        .arg(sprite)
+        .arg("--png")
+        .arg(png)
+        .arg("--once")
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that a PNG edit is synced into the source, keeping palette tokens
+#[test]
+fn test_bridge_syncs_png_edits() {
+    let dir = output_dir("sync");
+    let source = dir.join("face.pxl");
+    let png = dir.join("face.png");
+    fs::write(&source, FACE).unwrap();
+    fs::remove_file(&png).ok();
+
+    // First sync exports the sprite
+    let output = bridge_once(&source, "face", &png);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Exported 'face'"));
+
+    // Paint an eye in the editor, slightly off the palette color
+    let mut image = image::open(&png).unwrap().to_rgba8();
+    image.put_pixel(2, 1, Rgba([6, 3, 0, 255]));
+    image.save(&png).unwrap();
+
+    let output = bridge_once(&source, "face", &png);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("Synced 1 pixel(s)"), "stdout: {}", stdout);
+    assert!(stdout.contains("Snapped 1 pixel(s)"), "stdout: {}", stdout);
+
+    let content = fs::read_to_string(&source).unwrap();
+    assert!(content.contains(r#""eye":{"points":[[1,1],[2,1]]"#), "source: {}", content);
+
+    // The PNG is re-exported with the snapped palette color
+    let image = image::open(&png).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(2, 1), &Rgba([0, 0, 0, 255]));
+
+    // Nothing left to sync
+    let output = bridge_once(&source, "face", &png);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No changes"));
+    assert_eq!(fs::read_to_string(&source).unwrap(), content);
+}
+
+/// Test that a resized PNG is rejected without touching the source
+#[test]
+fn test_bridge_rejects_resized_png() {
+    let dir = output_dir("resize");
+    let source = dir.join("face.pxl");
+    let png = dir.join("face.png");
+    fs::write(&source, FACE).unwrap();
+    image::RgbaImage::new(8, 8).save(&png).unwrap();
+
+    let output = bridge_once(&source, "face", &png);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("resizing is not supported"), "stderr: {}", stderr);
+    assert_eq!(fs::read_to_string(&source).unwrap(), FACE);
+}
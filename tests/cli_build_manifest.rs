@@ -0,0 +1,99 @@
+//! CLI integration tests for `pxl build --manifest-out`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const CONFIG: &str = r#"[project]
+name = "arcade"
+src = "src"
+
+[atlases.characters]
+sources = ["**/*.pxl"]
+"#;
+
+const HERO: &str = r##"{"type": "palette", "name": "hero", "colors": {"_": "#00000000", "x": "#FF0000"}}
+{"type": "sprite", "name": "hero_idle", "size": [2, 2], "palette": "hero", "regions": {"x": {"rect": [0, 0, 2, 2]}}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("pxl.toml"), CONFIG).unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/hero.pxl"), HERO).unwrap();
+    dir
+}
+
+/// A rebuild, incremental or not, writes the same manifest
+#[test]
+fn test_manifest_out_is_reproducible() {
+    let dir = project();
+
+    let output = pxl(dir.path(), &["build", "--manifest-out", "first.json"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let first = fs::read_to_string(dir.path().join("first.json")).unwrap();
+
+    let manifest: serde_json::Value = serde_json::from_str(&first).unwrap();
+    let artifacts = manifest["artifacts"].as_array().unwrap();
+    let atlas = artifacts.iter().find(|a| a["path"] == "build/characters.png").unwrap();
+    assert_eq!(atlas["target"], "atlas:characters");
+    assert_eq!(atlas["sources"][0]["path"], "src/hero.pxl");
+    assert_eq!(atlas["objects"], serde_json::json!(["palette:hero", "sprite:hero_idle"]));
+
+    // Everything is up to date now, and skipped targets are still listed
+    let output = pxl(dir.path(), &["build", "--manifest-out", "second.json"]);
+    assert!(output.status.success());
+    assert_eq!(first, fs::read_to_string(dir.path().join("second.json")).unwrap());
+
+    let output = pxl(dir.path(), &["build", "--force", "--manifest-out", "third.json"]);
+    assert!(output.status.success());
+    assert_eq!(first, fs::read_to_string(dir.path().join("third.json")).unwrap());
+}
+
+/// Changing a source changes its hash and the atlas image's
+#[test]
+fn test_manifest_out_tracks_changes() {
+    let dir = project();
+    assert!(pxl(dir.path(), &["build", "--manifest-out", "before.json"]).status.success());
+    fs::write(dir.path().join("src/hero.pxl"), HERO.replace("#FF0000", "#00FF00")).unwrap();
+    assert!(pxl(dir.path(), &["build", "--manifest-out", "after.json"]).status.success());
+
+    let read = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(dir.path().join(name)).unwrap()).unwrap()
+    };
+    let (before, after) = (read("before.json"), read("after.json"));
+    let atlas = |m: &serde_json::Value| {
+        m["artifacts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["path"] == "build/characters.png")
+            .cloned()
+            .unwrap()
+    };
+    assert_ne!(atlas(&before)["hash"], atlas(&after)["hash"]);
+    assert_ne!(atlas(&before)["sources"], atlas(&after)["sources"]);
+}
@@ -0,0 +1,62 @@
+//! CLI integration tests for `pxl copy` and `pxl paste`
+//!
+//! The system clipboard isn't available in CI, so these tests cover argument
+//! handling and object lookup, which happen before the clipboard is touched.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/multiple_sprites.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// Test that copying an unknown object suggests the closest name
+#[test]
+fn test_copy_unknown_name() {
+    let output = Command::new(pxl_binary())
+        .args(["copy", FIXTURE, "green_dott"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("named 'green_dott'"), "stderr: {}", stderr);
+    assert!(stderr.contains("green_dot"), "Should suggest green_dot: {}", stderr);
+}
+
+/// Test that a zero scale is rejected
+#[test]
+fn test_copy_invalid_scale() {
+    let output = Command::new(pxl_binary())
+        .args(["copy", FIXTURE, "green_dot", "--scale", "0"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+/// Test that paste validates --max-colors before reading the clipboard
+#[test]
+fn test_paste_invalid_max_colors() {
+    let output = Command::new(pxl_binary())
+        .args(["paste", "--import", "--max-colors", "1"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--max-colors"));
+}
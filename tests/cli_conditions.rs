@@ -0,0 +1,63 @@
+//! CLI integration tests for `when` conditions and `--define`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const FIXTURE: &str = "tests/fixtures/valid/conditions.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// Render `sprite` into its own directory with extra arguments.
+fn render(sprite: &str, dir: &str, args: &[&str]) -> (Output, PathBuf) {
+    let dir = std::env::temp_dir().join("pxl_conditions_test").join(dir);
+    fs::create_dir_all(&dir).ok();
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--strict", "--sprite", sprite, "-o"])
+        .arg(format!("{}/", dir.display()))
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl");
+    (output, dir.join(format!("{}.png", sprite)))
+}
+
+/// Test that the standard sprite is used without defines
+#[test]
+fn test_default_objects() {
+    let (output, png) = render("hero", "default", &[]);
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(image::open(png).expect("Failed to open PNG").width(), 2);
+}
+
+/// Test that `--define` swaps in the conditional sprite
+#[test]
+fn test_define_selects_objects() {
+    let (output, png) = render("hero", "hd", &["--define", "hd"]);
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(image::open(png).expect("Failed to open PNG").width(), 4);
+}
+
+/// Test that debug-only objects don't exist unless their flag is defined
+#[test]
+fn test_inactive_object_is_missing() {
+    let (output, _) = render("grid", "missing", &[]);
+    assert!(!output.status.success(), "grid should not exist without --define debug");
+
+    let (output, png) = render("grid", "debug", &["--define", "debug"]);
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(png.exists());
+}
@@ -0,0 +1,54 @@
+//! CLI integration tests for layer contact shadows (`contact_shadow`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// A red post standing on a white floor, casting a shadow down onto it
+const SCENE: &str = r##"{"type": "palette", "name": "yard", "colors": {"_": "#00000000", "w": "#C8C8C8", "r": "#FF0000"}}
+{"type": "sprite", "name": "floor", "size": [1, 1], "palette": "yard", "regions": {"w": {"rect": [0, 0, 1, 1]}}}
+{"type": "sprite", "name": "post", "size": [1, 1], "palette": "yard", "regions": {"r": {"rect": [0, 0, 1, 1]}}}
+{"type": "composition", "name": "yard", "size": [3, 4], "sprites": {".": null, "F": "floor", "P": "post"}, "layers": [{"map": ["FFF", "FFF", "FFF", "..."]}, {"map": [".P.", ".P.", "...", "..."], "contact_shadow": {}}]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// The shadow darkens the floor under the post and stops at empty canvas
+#[test]
+fn test_contact_shadow_under_layer() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("yard.pxl"), SCENE).unwrap();
+
+    let output = pxl(
+        dir.path(),
+        &["render", "yard.pxl", "--composition", "yard", "--strict", "-o", "yard.png"],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let image = image::open(dir.path().join("yard.png")).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(1, 1).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(1, 2).0, [100, 100, 100, 255]);
+    assert_eq!(image.get_pixel(0, 2).0, [200, 200, 200, 255]);
+    assert_eq!(image.get_pixel(1, 3).0, [0, 0, 0, 0]);
+}
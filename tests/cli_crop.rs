@@ -0,0 +1,129 @@
+//! CLI integration tests for region-of-interest rendering (`--crop`) and
+//! `pxl crop --auto`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SPRITES: &str = r##"// imported with padding
+{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000"}}
+{"type": "sprite", "name": "coin", "size": [16, 16], "palette": "p", "regions": {"r": {"rect": [4, 6, 8, 8]}}, "metadata": {"origin": [8, 14]}}
+{"type": "sprite", "name": "tight", "size": [2, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+const MAP: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "field", "size": [8, 8], "palette": "p", "regions": {"r": {"rect": [0, 0, 8, 8]}, "b": {"rect": [4, 4, 4, 4], "z": 1}}}
+{"type": "composition", "name": "world", "size": [16, 8], "cell_size": [8, 8], "sprites": {"F": "field"}, "layers": [{"map": ["FF"]}]}"##;
+
+fn image(path: &Path) -> image::RgbaImage {
+    image::open(path).unwrap().to_rgba8()
+}
+
+/// Test that --crop renders only the requested rectangle, before scaling
+#[test]
+fn test_render_crop_sprite_and_composition() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("map.pxl"), MAP).unwrap();
+
+    let args = ["render", "map.pxl", "-s", "field", "--crop", "3,3,2,2", "--scale", "2"];
+    let output = pxl(dir.path(), &[&args[..], &["-o", "out/"]].concat());
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let tile = image(&dir.path().join("out/field.png"));
+    assert_eq!(tile.dimensions(), (4, 4));
+    assert_eq!(tile.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    assert_eq!(tile.get_pixel(3, 3).0, [0, 0, 255, 255]);
+
+    // Clipped at the image's edge, so a huge rectangle never allocates more than the image
+    let args = ["render", "map.pxl", "-c", "world", "--crop", "12,0,4000000000,4000000000"];
+    let output = pxl(dir.path(), &[&args[..], &["-o", "edge.png"]].concat());
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let edge = image(&dir.path().join("edge.png"));
+    assert_eq!(edge.dimensions(), (4, 8));
+    assert_eq!(edge.get_pixel(0, 4).0, [0, 0, 255, 255]);
+
+    let args = ["render", "map.pxl", "-s", "field", "--crop", "6,0,100000,100000"];
+    let output = pxl(dir.path(), &[&args[..], &["-o", "edge/"]].concat());
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(image(&dir.path().join("edge/field.png")).dimensions(), (2, 8));
+}
+
+/// Test that malformed, overflowing and out-of-range rectangles are rejected
+#[test]
+fn test_render_crop_invalid() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("map.pxl"), MAP).unwrap();
+
+    for crop in ["0,0,0,4", "1,0,4294967295,1"] {
+        let output = pxl(dir.path(), &["render", "map.pxl", "--crop", crop]);
+        assert_eq!(output.status.code(), Some(2), "--crop {}", crop);
+    }
+
+    for object in [["-s", "field", "--crop", "8,0,1,1"], ["-c", "world", "--crop", "0,8,1,1"]] {
+        let output =
+            pxl(dir.path(), &[&["render", "map.pxl"], &object[..], &["-o", "out.png"]].concat());
+        assert_eq!(output.status.code(), Some(1), "{:?}", object);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("lies outside the"), "stderr: {}", stderr);
+    }
+    assert!(!dir.path().join("out.png").exists());
+
+    let output = pxl(dir.path(), &["render", "map.pxl", "--crop", "0,0,4,4", "--format", "ora"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--crop cannot be used with"));
+}
+
+/// Test that --auto crops in place, and --check reports without writing
+#[test]
+fn test_crop_auto() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sprites.pxl");
+    fs::write(&path, SPRITES).unwrap();
+
+    let output = pxl(dir.path(), &["crop", "sprites.pxl", "--auto", "--check", "--json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["count"], 1);
+    assert_eq!(json["crops"][0]["offset"], serde_json::json!([4, 6]));
+    assert_eq!(fs::read_to_string(&path).unwrap(), SPRITES);
+
+    let output = pxl(dir.path(), &["crop", "sprites.pxl", "--auto"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {}", stderr);
+    assert!(stderr.contains("sprites.pxl:3: sprite 'coin' 16x16 -> 8x8"), "stderr: {}", stderr);
+
+    let cropped = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = cropped.lines().collect();
+    assert_eq!(lines[0], "// imported with padding");
+    assert!(lines[2].contains(r#""size":[8,8]"#), "{}", cropped);
+    assert!(lines[2].contains(r#""rect":[0,0,8,8]"#), "{}", cropped);
+    assert!(lines[2].contains(r#""origin":[4,8]"#), "{}", cropped);
+    assert_eq!(lines[3], SPRITES.lines().nth(3).unwrap());
+
+    // --auto is required
+    let output = pxl(dir.path(), &["crop", "sprites.pxl"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,105 @@
+//! CLI integration tests for cursor export
+//!
+//! These tests verify that `--format cur` writes a Windows cursor with the
+//! hotspot taken from `metadata.origin`, and that `--format ani` packs an
+//! animation into an animated cursor.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/cursor_arrow.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_cursor_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Test that a .cur uses the scaled sprite origin as its hotspot
+#[test]
+fn test_render_cur_hotspot() {
+    let output_path = output_dir().join("arrow.cur");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--format", "cur", "--sprite", "arrow", "--scale", "2", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let cur = fs::read(&output_path).expect("Failed to read cursor");
+    assert_eq!(u16_at(&cur, 2), 2, "Should be a cursor resource");
+    assert_eq!(u16_at(&cur, 4), 1, "Should hold one image");
+    assert_eq!((cur[6], cur[7]), (8, 8), "Should be scaled to 8x8");
+    assert_eq!((u16_at(&cur, 10), u16_at(&cur, 12)), (2, 2), "Hotspot should be origin * scale");
+}
+
+/// Test that every sprite gets its own .cur when no sprite is selected
+#[test]
+fn test_render_cur_all_sprites() {
+    let dir = output_dir().join("all/");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--format", "cur", "-o"])
+        .arg(&dir)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+    for name in ["arrow", "busy_1", "busy_2"] {
+        assert!(dir.join(format!("{}.cur", name)).exists(), "Missing {}.cur", name);
+    }
+}
+
+/// Test that an animation exports as an .ani with one cursor per frame
+#[test]
+fn test_render_ani() {
+    let output_path = output_dir().join("busy.ani");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--format", "ani", "--animation", "busy", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let ani = fs::read(&output_path).expect("Failed to read animated cursor");
+    assert_eq!(&ani[0..4], b"RIFF");
+    assert_eq!(&ani[8..12], b"ACON");
+    assert_eq!(u32_at(&ani, 24), 2, "Should have two frames");
+
+    // 200ms = 12 jiffies per frame
+    assert_eq!(&ani[56..60], b"rate");
+    assert_eq!((u32_at(&ani, 64), u32_at(&ani, 68)), (12, 12));
+
+    // Hotspot comes from the first frame's origin
+    let icon = ani.windows(4).position(|w| w == b"icon").expect("Missing icon chunk") + 8;
+    assert_eq!((u16_at(&ani, icon + 10), u16_at(&ani, icon + 12)), (2, 2));
+}
@@ -0,0 +1,67 @@
+//! CLI integration tests for palette cycle strips (`--format cycle-strip`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const POOL: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "w1": "#0066FF", "w2": "#0088FF", "f1": "#FF6600", "f2": "#FF8800", "f3": "#FFAA00"}}
+{"type": "sprite", "name": "pool", "size": [5, 2], "palette": "p", "regions": {"w1": {"rect": [0, 0, 1, 2]}, "w2": {"rect": [1, 0, 1, 2]}, "f1": {"rect": [2, 0, 1, 2]}, "f2": {"rect": [3, 0, 1, 2]}, "f3": {"rect": [4, 0, 1, 2]}}}
+{"type": "animation", "name": "shimmer", "frames": ["pool"], "palette_cycle": [{"tokens": ["w1", "w2"], "duration": 300}, {"tokens": ["f1", "f2", "f3"], "duration": 150}]}
+{"type": "animation", "name": "still", "frames": ["pool"]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test one row per cycle, plus the combined timeline on request
+#[test]
+fn test_cycle_strip_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("pool.pxl"), POOL).unwrap();
+
+    let args = ["render", "pool.pxl", "--format", "cycle-strip", "--animation", "shimmer"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("pool_shimmer.png"));
+    let strip = image::open(dir.path().join("pool_shimmer.png")).unwrap().to_rgba8();
+    // 3 columns as wide as a 3-digit label (11px); rows are 2px frame + 1px + 5px label
+    assert_eq!(strip.dimensions(), (3 * 11 + 2 * 2, 2 * 8 + 2));
+
+    let output = pxl(dir.path(), &[&args[..], &["--cycle-timeline", "-o", "t.png"]].concat());
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let strip = image::open(dir.path().join("t.png")).unwrap().to_rgba8();
+    // LCM of 2 and 3 steps
+    assert_eq!(strip.dimensions(), (6 * 11 + 5 * 2, 3 * 8 + 2 * 2));
+}
+
+/// Test that animations without a palette cycle are rejected
+#[test]
+fn test_cycle_strip_requires_cycle() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("pool.pxl"), POOL).unwrap();
+
+    let args = ["render", "pool.pxl", "--format", "cycle-strip", "--animation", "still"];
+    let output = pxl(dir.path(), &args);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no palette_cycle"));
+}
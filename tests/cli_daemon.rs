@@ -0,0 +1,134 @@
+//! CLI integration tests for `pxl daemon`
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use serde_json::{json, Value};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// A running daemon and its startup banner
+struct Daemon {
+    child: Child,
+    addr: String,
+    token: String,
+}
+
+/// Start `pxl daemon` on a free port inside `dir`
+fn start_daemon(dir: &Path) -> Daemon {
+    let mut child = Command::new(pxl_binary().canonicalize().unwrap())
+        .args(["daemon", "--port", "0"])
+        .current_dir(dir)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute pxl");
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut banner = |prefix: &str| {
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        line.trim().strip_prefix(prefix).expect("daemon banner").to_string()
+    };
+    let addr = banner("Listening on ");
+    let token = banner("Token ");
+    // Read the whole banner so the daemon never prints to a closed pipe
+    let root = banner("Writing under ");
+    assert_eq!(Path::new(&root), dir.canonicalize().unwrap());
+    Daemon { child, addr, token }
+}
+
+#[test]
+fn test_daemon_renders_over_socket_and_shuts_down() {
+    let dir = tempfile::tempdir().unwrap();
+    let Daemon { child: mut daemon, addr, token } = start_daemon(dir.path());
+
+    let stream = TcpStream::connect(&addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    let mut call = |mut request: Value| -> Value {
+        request["token"] = json!(token);
+        writeln!(writer, "{}", request).unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    };
+
+    let input = Path::new("tests/fixtures/valid/theme_night.jsonl").canonicalize().unwrap();
+    let output = dir.path().join("tree.png");
+
+    assert_eq!(call(json!({ "id": 1, "method": "ping" }))["ok"], true);
+
+    let render = json!({ "id": 2, "method": "render", "input": input, "sprite": "tree", "scale": 2, "output": "tree.png" });
+    let first = call(render.clone());
+    assert_eq!(first["ok"], true, "{}", first);
+    assert_eq!(first["cached"], false);
+    assert_eq!(image::open(&output).unwrap().to_rgba8().dimensions(), (4, 4));
+    assert_eq!(call(render)["cached"], true);
+
+    let outside = dir.path().parent().unwrap().join("tree.png");
+    let escape = call(
+        json!({ "id": 3, "method": "render", "input": input, "sprite": "tree", "output": outside }),
+    );
+    assert!(escape["error"].as_str().unwrap().contains("outside the project"), "{}", escape);
+
+    let valid = call(json!({ "id": 4, "method": "validate", "input": input }));
+    assert_eq!(valid["valid"], true, "{}", valid);
+
+    assert_eq!(call(json!({ "id": 5, "method": "shutdown" }))["ok"], true);
+    assert!(daemon.wait().unwrap().success());
+}
+
+#[test]
+fn test_daemon_drops_unauthenticated_connections() {
+    let dir = tempfile::tempdir().unwrap();
+    let Daemon { child: mut daemon, addr, token } = start_daemon(dir.path());
+
+    // What a browser's cross-origin POST looks like on the wire
+    let mut stream = TcpStream::connect(&addr).unwrap();
+    write!(stream, "POST / HTTP/1.1\r\nHost: {}\r\n\r\n{{\"method\": \"shutdown\"}}\n", addr)
+        .unwrap();
+    let mut reply = String::new();
+    BufReader::new(stream).read_to_string(&mut reply).unwrap();
+    assert_eq!(reply.lines().count(), 1, "connection closes after the first line: {}", reply);
+
+    let mut stream = TcpStream::connect(&addr).unwrap();
+    writeln!(stream, "{}", json!({ "method": "shutdown", "token": "guess" })).unwrap();
+    let mut reply = String::new();
+    BufReader::new(stream).read_to_string(&mut reply).unwrap();
+    assert!(reply.contains("missing or invalid token"), "{}", reply);
+
+    // Still running: an authenticated request gets through
+    let mut stream = TcpStream::connect(&addr).unwrap();
+    writeln!(stream, "{}", json!({ "method": "shutdown", "token": token })).unwrap();
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).unwrap();
+    assert!(reply.contains("\"ok\":true"), "{}", reply);
+    assert!(daemon.wait().unwrap().success());
+}
+
+#[test]
+fn test_daemon_refuses_non_loopback_host() {
+    let output = Command::new(pxl_binary())
+        .args(["daemon", "--host", "0.0.0.0", "--port", "0"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only listens on loopback"));
+}
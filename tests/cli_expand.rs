@@ -0,0 +1,89 @@
+//! CLI integration tests for `pxl expand`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "stone", "colors": {"_": "#0000", "rock": "#888888", "moss": "#33AA55", "crack": "#222222"}}
+{"type": "sprite", "name": "rock_round", "size": [6, 6], "palette": "stone", "regions": {"rock": {"circle": [3, 3, 2]}}}
+{"type": "sprite", "name": "rock_flat", "size": [6, 6], "palette": "stone", "regions": {"rock": {"rect": [0, 3, 6, 3]}}}
+{"type": "random_set", "name": "rock", "choices": ["rock_round", {"name": "rock_flat", "weight": 2}], "regions": {"moss": [{"region": {"rect": [2, 1, 2, 1], "z": 1}}, {"region": {"points": [[1, 3]], "z": 1}}, {"weight": 2}], "crack": [{"region": {"line": [[2, 4], [4, 5]], "z": 2}}, {"region": {"points": [[3, 4]], "z": 2}}, {}]}}
+"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn setup() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("rocks.pxl"), SOURCE).unwrap();
+    dir
+}
+
+/// Expansion prints distinct, renderable sprites and is stable per seed
+#[test]
+fn test_expand_random_set() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["expand", "rocks.pxl", "rock", "--count", "12"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let objects: Vec<serde_json::Value> =
+        stdout.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(objects.len(), 12);
+    assert_eq!(objects[0]["name"], "rock_01");
+    assert_eq!(objects[11]["name"], "rock_12");
+    assert!(objects.iter().all(|o| o["type"] == "sprite" && o["palette"] == "stone"));
+
+    let again = pxl(dir.path(), &["expand", "rocks.pxl", "rock", "--count", "12"]);
+    assert_eq!(String::from_utf8(again.stdout).unwrap(), stdout);
+    let reseeded =
+        pxl(dir.path(), &["expand", "rocks.pxl", "rock", "--count", "12", "--seed", "9"]);
+    assert_ne!(String::from_utf8(reseeded.stdout).unwrap(), stdout);
+
+    fs::write(dir.path().join("all.pxl"), format!("{}{}", SOURCE, stdout)).unwrap();
+    let output =
+        pxl(dir.path(), &["render", "all.pxl", "--sprite", "rock_07", "--strict", "-o", "r.png"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(dir.path().join("r.png").exists());
+}
+
+/// Asking for more objects than there are combinations warns, and unknown
+/// sets are an error with a suggestion
+#[test]
+fn test_expand_limits_and_errors() {
+    let dir = setup();
+
+    // 2 choices x 3 moss x 3 crack alternatives
+    let output = pxl(dir.path(), &["expand", "rocks.pxl", "rock", "-n", "30", "-o", "gen.pxl"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("gave only 18 distinct objects of 30 requested"), "{}", stderr);
+    assert_eq!(fs::read_to_string(dir.path().join("gen.pxl")).unwrap().lines().count(), 18);
+
+    let output = pxl(dir.path(), &["expand", "rocks.pxl", "rocks"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No random_set named 'rocks'"), "{}", stderr);
+    assert!(stderr.contains("rock"), "{}", stderr);
+}
@@ -0,0 +1,89 @@
+//! CLI integration tests for explaining single pixels
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "hero", "colors": {"_": "#00000000", "r": "#FF0000"}}
+{"type": "sprite", "name": "knight", "size": [4, 2], "palette": "hero", "regions": {"r": {"rect": [0, 0, 1, 2]}, "q": {"points": [[3, 1]]}}, "transform": ["mirror-h", "pad:1"]}
+"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn setup() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("knight.pxl"), SOURCE).unwrap();
+    dir
+}
+
+/// Test that a pixel is traced through transforms to its token and palette
+#[test]
+fn test_explain_pixel_text() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["explain", "knight.pxl", "-n", "knight", "--pixel", "4,1"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Color: #FF0000FF"), "{}", stdout);
+    assert!(stdout.contains("Token: r at (0, 0)"), "{}", stdout);
+    assert!(stdout.contains("palette 'hero'"), "{}", stdout);
+    assert!(stdout.contains("mirror-h"), "{}", stdout);
+}
+
+/// Test JSON output, fallbacks and pixels added by transforms
+#[test]
+fn test_explain_pixel_json() {
+    let dir = setup();
+
+    let output =
+        pxl(dir.path(), &["explain", "knight.pxl", "-n", "knight", "--pixel", "1,2", "--json"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["token"], "q");
+    assert_eq!(json["source"], serde_json::json!([3, 1]));
+    assert_eq!(json["color"], "#FF00FFFF");
+    assert!(json["fallback"].as_str().unwrap().contains("not defined"));
+    assert_eq!(json["transforms"][1]["transform"], "pad:1");
+
+    let output =
+        pxl(dir.path(), &["explain", "knight.pxl", "-n", "knight", "--pixel", "0,0", "--json"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["source"], serde_json::Value::Null);
+    assert!(json["note"].as_str().unwrap().contains("added by pad:1"));
+}
+
+/// Test invalid pixel queries
+#[test]
+fn test_explain_pixel_errors() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["explain", "knight.pxl", "--pixel", "0,0"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = pxl(dir.path(), &["explain", "knight.pxl", "-n", "knight", "--pixel", "zero"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = pxl(dir.path(), &["explain", "knight.pxl", "-n", "knight", "--pixel", "9,9"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("outside sprite 'knight' (6x4)"));
+}
@@ -0,0 +1,144 @@
+//! CLI integration tests for `pxl export`
+//!
+//! These tests verify the emoji and ANSI text exports, that the Discord
+//! format produces a padded 128x128 custom emoji PNG, and that slices are cut
+//! into PNGs of their own.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/multiple_sprites.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_export_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Test that emoji export prints one emoji per pixel, defaulting to the first sprite
+#[test]
+fn test_export_emoji_stdout() {
+    let output = Command::new(pxl_binary())
+        .args(["export", FIXTURE, "--format", "emoji"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Export failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "⬜🟩⬜\n🟩🟩🟩\n⬜🟩⬜\n");
+}
+
+/// Test that ANSI export uses half-blocks with true-color escapes
+#[test]
+fn test_export_ansi_to_file() {
+    let output_path = output_dir().join("red_dot.ans");
+
+    let output = Command::new(pxl_binary())
+        .args(["export", FIXTURE, "--format", "ansi", "--sprite", "red_dot", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Export failed: {}", String::from_utf8_lossy(&output.stderr));
+    let text = fs::read_to_string(&output_path).expect("Failed to read ANSI output");
+    // 3 rows of pixels fit in 2 lines of half-blocks
+    assert_eq!(text.lines().count(), 2);
+    assert!(text.contains("\x1b[38;2;255;0;0m▀"));
+}
+
+/// Test that Discord export writes a padded 128x128 emoji
+#[test]
+fn test_export_discord_png() {
+    let output_path = output_dir().join("yellow_dot.png");
+
+    let output = Command::new(pxl_binary())
+        .args(["export", FIXTURE, "--format", "discord", "--sprite", "yellow_dot"])
+        .args(["--padding", "16", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Export failed: {}", String::from_utf8_lossy(&output.stderr));
+    let img = image::open(&output_path).expect("Failed to open PNG").to_rgba8();
+    assert_eq!(img.dimensions(), (128, 128));
+    // 3x3 scaled 32x into the 96px inner area, centered
+    assert_eq!(img.get_pixel(16, 64).0, [255, 255, 0, 255]);
+    assert_eq!(img.get_pixel(15, 64)[3], 0);
+    assert_eq!(img.get_pixel(16, 16)[3], 0, "Corner pixel of the sprite is transparent");
+}
+
+/// Test that an unknown sprite is reported
+#[test]
+fn test_export_unknown_sprite() {
+    let output = Command::new(pxl_binary())
+        .args(["export", FIXTURE, "--format", "emoji", "--sprite", "red_dott"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No sprite named 'red_dott'"), "stderr: {}", stderr);
+    assert!(stderr.contains("red_dot"), "Should suggest red_dot: {}", stderr);
+}
+
+/// Test that each named slice is saved as its own PNG
+#[test]
+fn test_export_slices() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("ui.pxl");
+    fs::write(
+        &input,
+        r##"{"type": "palette", "name": "ui", "colors": {"_": "#00000000", "n": "#3060C0", "h": "#60A0FF"}}
+{"type": "sprite", "name": "ui_sheet", "size": [8, 3], "palette": "ui", "regions": {"n": {"rect": [0, 0, 4, 3]}, "h": {"rect": [4, 0, 4, 3]}}, "metadata": {"slices": {"button_normal": {"x": 0, "y": 0, "w": 4, "h": 3}, "button_hover": {"x": 4, "y": 0, "w": 4, "h": 3}, "edge": {"x": 6, "y": 1, "w": 4, "h": 2}}}}"##,
+    )
+    .unwrap();
+    let kit = dir.path().join("kit");
+    fs::create_dir(&kit).unwrap();
+
+    let output = Command::new(pxl_binary())
+        .args(["export", "--format", "slices", "--sprite", "ui_sheet", "-o"])
+        .arg(&kit)
+        .arg(&input)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Export failed: {}", String::from_utf8_lossy(&output.stderr));
+    let hover = image::open(kit.join("button_hover.png")).unwrap().to_rgba8();
+    assert_eq!(hover.dimensions(), (4, 3));
+    assert_eq!(hover.get_pixel(0, 0).0, [0x60, 0xA0, 0xFF, 255]);
+    let normal = image::open(kit.join("button_normal.png")).unwrap().to_rgba8();
+    assert_eq!(normal.get_pixel(3, 2).0, [0x30, 0x60, 0xC0, 255]);
+    // Clipped to the sprite's right edge
+    let edge = image::open(kit.join("edge.png")).unwrap().to_rgba8();
+    assert_eq!(edge.dimensions(), (2, 2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("slice 'edge' extends outside the sprite"), "stderr: {}", stderr);
+}
+
+/// Test that sprites without slices are rejected
+#[test]
+fn test_export_slices_requires_slices() {
+    let output = Command::new(pxl_binary())
+        .args(["export", FIXTURE, "--format", "slices", "--sprite", "red_dot"])
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has no slices"));
+}
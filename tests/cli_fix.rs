@@ -0,0 +1,87 @@
+//! CLI integration tests for the fix command (pxl fix)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_fix_test").join(test);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+const SPRITES: &str = r##"// sprites
+{"type": "palette", "name": "hero", "colors": {"_": "#00000000", "skin": "#FFCC99"}}
+{"type": "palette", "name": "hero_copy", "colors": {"_": "#00000000", "skin": "#FFCC99"}}
+{"type": "sprite", "name": "hero_idle", "size": [1, 1], "palette": "hero_copy", "regions": {"skin": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}
+"##;
+
+fn fix(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary())
+        .arg("fix")
+        .arg(dir.join("sprites.pxl"))
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that fixes are written in place and leave untouched lines alone
+#[test]
+fn test_fix_rewrites_file() {
+    let dir = output_dir("rewrite");
+    let path = dir.join("sprites.pxl");
+    fs::write(&path, SPRITES).unwrap();
+
+    let output = fix(&dir, &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {}", stderr);
+    assert!(stderr.contains("sprites.pxl:3: [dedupe-palettes]"), "stderr: {}", stderr);
+    assert!(stderr.contains("sprites.pxl:5: [extract-inline-palette]"), "stderr: {}", stderr);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2 fixes applied."));
+
+    let fixed = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = fixed.lines().collect();
+    assert_eq!(&lines[..2], &SPRITES.lines().collect::<Vec<_>>()[..2]);
+    assert!(lines[2].contains(r#""palette":"hero""#), "{}", fixed);
+    assert!(lines[3].contains(r#""name":"dot_palette""#), "{}", fixed);
+    assert!(lines[4].contains(r#""palette":"dot_palette""#), "{}", fixed);
+
+    let output = fix(&dir, &["--check"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Nothing to fix."));
+}
+
+/// Test that --check reports fixes as JSON without writing and fails
+#[test]
+fn test_fix_check_json() {
+    let dir = output_dir("check");
+    let path = dir.join("sprites.pxl");
+    fs::write(&path, SPRITES).unwrap();
+
+    let output = fix(&dir, &["--check", "--json"]);
+    assert!(!output.status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), SPRITES);
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["applied"], false);
+    assert_eq!(json["count"], 2);
+    assert_eq!(json["fixes"][0]["fix"], "dedupe-palettes");
+    assert_eq!(json["fixes"][0]["line"], 3);
+}
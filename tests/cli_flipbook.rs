@@ -0,0 +1,74 @@
+//! CLI integration tests for flip-book print sheets (`--format flipbook`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const WALK: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "f1", "size": [4, 4], "palette": "p", "regions": {"r": {"rect": [0, 0, 4, 4]}}}
+{"type": "sprite", "name": "f2", "size": [4, 4], "palette": "p", "regions": {"b": {"rect": [0, 0, 4, 4]}}}
+{"type": "animation", "name": "walk", "frames": ["f1", "f2", "f1"]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that the default flip-book output is a PDF sized to the paper
+#[test]
+fn test_flipbook_pdf() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("walk.pxl"), WALK).unwrap();
+
+    let output =
+        pxl(dir.path(), &["render", "walk.pxl", "--format", "flipbook", "--paper", "letter"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("walk_walk.pdf"));
+
+    let pdf = fs::read(dir.path().join("walk_walk.pdf")).unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.starts_with("%PDF-"));
+    assert!(text.contains("/Count 1"));
+    assert!(text.contains("/MediaBox [0 0 612.00 792.00]"), "letter is 8.5x11in");
+    assert!(text.contains("/Title (walk)"));
+}
+
+/// Test PNG output at a custom DPI, one image per page
+#[test]
+fn test_flipbook_png_pages() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("walk.pxl"), WALK).unwrap();
+
+    let args = ["render", "walk.pxl", "--format", "flipbook", "--dpi", "100", "-o", "walk.png"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let page = image::open(dir.path().join("walk.png")).unwrap().to_rgba8();
+    assert_eq!(page.dimensions(), (827, 1169), "A4 at 100 DPI");
+    assert_eq!(*page.get_pixel(0, 0), image::Rgba([255, 255, 255, 255]));
+
+    // Frames too large for a card fail with a hint
+    let args = ["render", "walk.pxl", "--format", "flipbook", "--dpi", "72", "--scale", "128"];
+    let output = pxl(dir.path(), &args);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("don't fit"));
+}
@@ -0,0 +1,78 @@
+//! CLI integration tests for lining up animation frames on their origins
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// A tall idle frame and a short, wide crouch frame, both with feet at the bottom center
+const SOURCE: &str = r##"{"type": "sprite", "name": "idle", "size": [4, 8], "palette": {"_": "#00000000", "r": "#FF0000"}, "regions": {"r": {"rect": [0, 0, 4, 8]}}, "metadata": {"origin": [2, 8]}}
+{"type": "sprite", "name": "crouch", "size": [6, 4], "palette": {"_": "#00000000", "b": "#0000FF"}, "regions": {"b": {"rect": [0, 0, 6, 4]}}, "metadata": {"origin": [3, 4]}}
+{"type": "animation", "name": "move", "frames": ["idle", "crouch"]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that spritesheet cells keep the origins at the same spot
+#[test]
+fn test_spritesheet_aligns_on_origin() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("move.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "move.pxl", "--spritesheet", "-o", "sheet.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let sheet = image::open(dir.path().join("sheet.png")).unwrap().to_rgba8();
+    assert_eq!(sheet.dimensions(), (12, 8), "two 6x8 cells");
+
+    // Idle is centered in its cell; crouch sits on the same floor line
+    assert_eq!(sheet.get_pixel(0, 7)[3], 0);
+    assert_eq!(sheet.get_pixel(1, 7)[0], 255);
+    assert_eq!(sheet.get_pixel(6, 3)[3], 0);
+    assert_eq!(sheet.get_pixel(6, 7)[2], 255);
+}
+
+/// Test that atlas animations record the shared canvas and frame offsets
+#[test]
+fn test_atlas_frame_offsets() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("move.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "move.pxl", "--format", "atlas", "-o", "atlas.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("atlas.json")).unwrap()).unwrap();
+    let anim = &json["animations"]["move"];
+    assert_eq!(anim["canvas"], serde_json::json!([6, 8]));
+    assert_eq!(anim["offsets"], serde_json::json!([[1, 0], [0, 4]]));
+
+    let args = ["render", "move.pxl", "--format", "atlas-aseprite", "-o", "ase.png"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("ase.json")).unwrap()).unwrap();
+    let crouch = &json["frames"]["crouch.png"];
+    assert_eq!(crouch["trimmed"], true);
+    assert_eq!(crouch["spriteSourceSize"], serde_json::json!({"x": 0, "y": 4, "w": 6, "h": 4}));
+    assert_eq!(crouch["sourceSize"], serde_json::json!({"w": 6, "h": 8}));
+}
@@ -0,0 +1,112 @@
+//! CLI integration tests for output framing (`--background`, `--margin`, `--canvas`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000"}}
+{"type": "sprite", "name": "dot", "size": [4, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+{"type": "composition", "name": "scene", "size": [4, 2], "sprites": {"D": "dot"}, "layers": [{"map": ["D"]}], "cell_size": [4, 2]}
+{"type": "animation", "name": "blink", "frames": ["dot", "dot"]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+const RED: image::Rgba<u8> = image::Rgba([255, 0, 0, 255]);
+const BLUE: image::Rgba<u8> = image::Rgba([0, 0, 255, 255]);
+
+/// Test letterboxing a sprite onto a fixed canvas over a solid background
+#[test]
+fn test_canvas_letterbox() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("dot.pxl"), SOURCE).unwrap();
+
+    let args = [
+        "render",
+        "dot.pxl",
+        "-s",
+        "dot",
+        "-o",
+        "card.png",
+        "--canvas",
+        "40x30",
+        "--margin",
+        "2",
+        "--background",
+        "#0000FF",
+    ];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    // 9x fits 36x26: the 36x18 sprite sits at (2, 6). The file has more than
+    // one object, so the output name is suffixed.
+    let card = image::open(dir.path().join("card_dot.png")).unwrap().to_rgba8();
+    assert_eq!(card.dimensions(), (40, 30));
+    assert_eq!(*card.get_pixel(2, 6), RED);
+    assert_eq!(*card.get_pixel(19, 23), RED);
+    assert_eq!(*card.get_pixel(20, 6), BLUE, "transparent pixels show the background");
+    assert_eq!(*card.get_pixel(2, 5), BLUE);
+
+    // Too small once the margin is taken off
+    let args = ["render", "dot.pxl", "-s", "dot", "--canvas", "8x8", "--margin", "3"];
+    let output = pxl(dir.path(), &args);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("doesn't fit a 8x8 canvas"));
+}
+
+/// Test margins and a checkerboard background on a scaled composition
+#[test]
+fn test_margin_checkerboard() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("dot.pxl"), SOURCE).unwrap();
+
+    let args = [
+        "render",
+        "dot.pxl",
+        "-c",
+        "scene",
+        "-o",
+        "scene.png",
+        "--scale",
+        "2",
+        "--margin",
+        "4",
+        "--background",
+        "checkerboard:4",
+    ];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let scene = image::open(dir.path().join("scene.png")).unwrap().to_rgba8();
+    assert_eq!(scene.dimensions(), (16, 12));
+    assert_eq!(*scene.get_pixel(4, 4), RED);
+    assert_eq!(*scene.get_pixel(0, 0), image::Rgba([255, 255, 255, 255]));
+    assert_eq!(*scene.get_pixel(4, 0), image::Rgba([204, 204, 204, 255]));
+
+    // Only single PNGs can be framed
+    let output = pxl(dir.path(), &["render", "dot.pxl", "--gif", "--margin", "1"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = pxl(dir.path(), &["render", "dot.pxl", "--background", "plaid"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,96 @@
+//! CLI integration tests for GIF encoding options (`--gif-*`)
+
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "s": "#0000FF40"}}
+{"type": "sprite", "name": "a", "size": [16, 16], "palette": "p", "regions": {"r": {"rect": [0, 0, 16, 14]}, "s": {"rect": [0, 14, 16, 2]}}}
+{"type": "sprite", "name": "b", "size": [16, 16], "palette": "p", "regions": {"r": {"rect": [0, 0, 16, 13]}, "s": {"rect": [0, 14, 16, 2]}}}
+{"type": "animation", "name": "blink", "frames": ["a", "b", "a", "a"]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn frames(path: &Path) -> Vec<image::RgbaImage> {
+    let file = fs::File::open(path).unwrap();
+    let decoder = GifDecoder::new(std::io::BufReader::new(file)).unwrap();
+    decoder.into_frames().map(|f| f.unwrap().into_buffer()).collect()
+}
+
+/// Test that optimized GIFs are smaller and decode to the same frames
+#[test]
+fn test_gif_optimize() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("blink.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "blink.pxl", "--gif", "-o", "small.gif"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let args = ["render", "blink.pxl", "--gif", "-o", "full.gif", "--no-gif-optimize"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let small = dir.path().join("small.gif");
+    let full = dir.path().join("full.gif");
+    assert!(fs::metadata(&small).unwrap().len() < fs::metadata(&full).unwrap().len());
+
+    // The repeated last frame is folded into the previous one's delay
+    let (small, full) = (frames(&small), frames(&full));
+    assert_eq!(full.len(), 4);
+    assert_eq!(small.len(), 3);
+    assert_eq!(small[..], full[..3]);
+    // The 25% alpha shadow is below the default threshold
+    assert_eq!(small[0].get_pixel(0, 15)[3], 0);
+}
+
+/// Test the alpha threshold, dithering and palette options
+#[test]
+fn test_gif_threshold_and_quantization_flags() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("blink.pxl"), SOURCE).unwrap();
+
+    let args = [
+        "render",
+        "blink.pxl",
+        "--gif",
+        "-o",
+        "shadow.gif",
+        "--gif-alpha-threshold",
+        "64",
+        "--gif-palette",
+        "per-frame",
+        "--gif-dither",
+        "floyd-steinberg",
+    ];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let shadow = frames(&dir.path().join("shadow.gif"));
+    assert_eq!(*shadow[0].get_pixel(0, 15), image::Rgba([0, 0, 255, 255]));
+    assert_eq!(*shadow[1].get_pixel(3, 13), image::Rgba([0, 0, 0, 0]));
+
+    let output = pxl(dir.path(), &["render", "blink.pxl", "--gif", "--gif-dither", "random"]);
+    assert_eq!(output.status.code(), Some(2));
+}
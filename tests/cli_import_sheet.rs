@@ -0,0 +1,102 @@
+//! CLI integration tests for sprite sheet import (`pxl import --sheet`)
+
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// A 3-frame 4x4 sheet laid out as red, blue, red, with an empty fourth cell
+fn write_sheet(path: &Path) {
+    let mut image = RgbaImage::new(16, 4);
+    for (cell, color) in [(0, [255, 0, 0, 255]), (1, [0, 0, 255, 255]), (2, [255, 0, 0, 255])] {
+        for y in 1..3 {
+            for x in 1..3 {
+                image.put_pixel(cell * 4 + x, y, Rgba(color));
+            }
+        }
+    }
+    image.save(path).unwrap();
+}
+
+fn objects(path: &Path) -> Vec<serde_json::Value> {
+    fs::read_to_string(path).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+/// Test that identical frames are deduplicated and the result renders
+#[test]
+fn test_import_sheet_dedupe() {
+    let dir = tempfile::tempdir().unwrap();
+    write_sheet(&dir.path().join("walk.png"));
+
+    let args = ["import", "walk.png", "--sheet", "4x4", "--dedupe", "-o", "walk.pxl"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("3 frames, 2 sprites"), "stdout: {}", stdout);
+
+    let objects = objects(&dir.path().join("walk.pxl"));
+    let types: Vec<&str> = objects.iter().map(|o| o["type"].as_str().unwrap()).collect();
+    assert_eq!(types, vec!["palette", "sprite", "sprite", "animation"]);
+    assert_eq!(objects[3]["name"], "walk");
+    assert_eq!(objects[3]["frames"], serde_json::json!(["walk_1", "walk_2", "walk_1"]));
+
+    let output = pxl(dir.path(), &["render", "walk.pxl", "--gif", "-o", "walk.gif"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(dir.path().join("walk.gif").exists());
+}
+
+/// Test gutter detection and that every frame is kept without --dedupe
+#[test]
+fn test_import_sheet_auto() {
+    let dir = tempfile::tempdir().unwrap();
+    write_sheet(&dir.path().join("walk.png"));
+
+    let output = pxl(dir.path(), &["import", "walk.png", "--sheet", "auto", "-o", "walk.pxl"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let objects = objects(&dir.path().join("walk.pxl"));
+    let sprites: Vec<_> = objects.iter().filter(|o| o["type"] == "sprite").collect();
+    assert_eq!(sprites.len(), 3);
+    assert!(sprites.iter().all(|s| s["size"] == serde_json::json!([2, 2])));
+}
+
+/// Test invalid sheet arguments
+#[test]
+fn test_import_sheet_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    write_sheet(&dir.path().join("walk.png"));
+
+    let output = pxl(dir.path(), &["import", "walk.png", "--dedupe"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = pxl(dir.path(), &["import", "walk.png", "--sheet", "32x32"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("larger than"));
+
+    let output = pxl(dir.path(), &["import", "walk.png", "--sheet", "4x4", "--analyze"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,67 @@
+//! CLI integration tests for indexed PNG-8 output (`--indexed`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF", "g": "#00FF00"}}
+{"type": "sprite", "name": "flag", "size": [2, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 1]}, "b": {"points": [[0, 1]]}}}
+{"type": "composition", "name": "flags", "size": [4, 2], "sprites": {"F": "flag"}, "layers": [{"map": ["F.."]}], "cell_size": [2, 2]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// The RGB triples in a PNG's PLTE chunk
+fn plte(png: &[u8]) -> Vec<[u8; 3]> {
+    let start = png.windows(4).position(|w| w == b"PLTE").expect("PLTE chunk");
+    let len = u32::from_be_bytes(png[start - 4..start].try_into().unwrap()) as usize;
+    png[start + 4..start + 4 + len].chunks(3).map(|c| [c[0], c[1], c[2]]).collect()
+}
+
+/// Test that sprites keep their whole palette, transparent first then by token
+#[test]
+fn test_render_indexed_sprite_and_composition() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("flags.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "flags.pxl", "--indexed", "--scale", "2"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let png = fs::read(dir.path().join("flags_flag.png")).unwrap();
+    assert_eq!(png[25], 3, "color type 3 is indexed");
+    assert_eq!(plte(&png), [[0, 0, 0], [0, 0, 255], [0, 255, 0], [255, 0, 0]]);
+    let image = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (4, 4));
+    assert_eq!(*image.get_pixel(0, 2), image::Rgba([0, 0, 255, 255]));
+    assert_eq!(image.get_pixel(3, 3)[3], 0);
+
+    // Compositions use the colors they contain, in order of appearance
+    let png = fs::read(dir.path().join("flags_flags.png")).unwrap();
+    assert_eq!(png[25], 3);
+    assert_eq!(plte(&png), [[255, 0, 0], [0, 0, 0], [0, 0, 255]]);
+
+    let output = pxl(dir.path(), &["render", "flags.pxl", "--indexed", "--format", "atlas"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--indexed cannot be used"));
+}
@@ -0,0 +1,75 @@
+//! CLI integration tests for the init command (pxl init)
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_init_test").join(test);
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Run `pxl init` inside `dir`, feeding `stdin` to the wizard
+fn init(dir: &Path, args: &[&str], stdin: &str) -> Output {
+    let binary = fs::canonicalize(pxl_binary()).unwrap();
+    let mut child = Command::new(binary)
+        .arg("init")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute pxl init");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_init_interactive_prompts_for_name_and_preset() {
+    let dir = output_dir("interactive");
+
+    let output = init(&dir, &["retro", "--interactive"], "my-retro-game\nnope\n5\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "init failed: {}", stdout);
+    assert!(stdout.contains("5) gameboy"));
+    assert!(stdout.contains("Unknown preset 'nope'"));
+    assert!(stdout.contains("(gameboy preset)"));
+
+    let config = fs::read_to_string(dir.join("retro/pxl.toml")).unwrap();
+    assert!(config.contains("name = \"my-retro-game\""));
+    assert!(config.contains("max_colors = 4"));
+    assert!(dir.join("retro/src/pxl/palettes/dmg.pxl").exists());
+}
+
+#[test]
+fn test_init_unknown_preset_lists_presets() {
+    let dir = output_dir("unknown");
+
+    let output = init(&dir, &["proj", "--preset", "snes"], "");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("gameboy, pico8, hd"));
+    assert!(!dir.join("proj").exists());
+}
@@ -0,0 +1,87 @@
+//! CLI integration tests for PNG provenance metadata and `pxl inspect`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "warm", "colors": {"_": "#00000000", "r": "#FF0000"}}
+{"type": "sprite", "name": "coin", "size": [2, 2], "palette": "warm", "regions": {"r": {"rect": [0, 0, 2, 2]}}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn inspect_json(dir: &Path, png: &str) -> serde_json::Value {
+    let output = pxl(dir, &["inspect", png, "--json"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Test that rendered PNGs carry provenance that inspect reads back
+#[test]
+fn test_render_embeds_provenance() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("coins.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "coins.pxl", "-o", "coin.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let meta = inspect_json(dir.path(), "coin.png");
+    assert_eq!(meta["source"], "coins.pxl");
+    assert_eq!(meta["object"], "coin");
+    assert_eq!(meta["palette"], "warm");
+    assert_eq!(meta["version"], env!("CARGO_PKG_VERSION"));
+    assert!(meta["hash"].as_str().unwrap().starts_with("fnv1a:"));
+
+    let output = pxl(dir.path(), &["inspect", "coin.png"]);
+    let text = String::from_utf8_lossy(&output.stdout);
+    assert!(text.contains("pixelsrc:object:"), "stdout: {}", text);
+
+    // The hash follows the definition, not the file
+    let edited = SOURCE.replace("[0, 0, 2, 2]", "[0, 0, 1, 1]");
+    fs::write(dir.path().join("coins.pxl"), edited).unwrap();
+    pxl(dir.path(), &["render", "coins.pxl", "-o", "coin2.png"]);
+    assert_ne!(inspect_json(dir.path(), "coin2.png")["hash"], meta["hash"]);
+}
+
+/// Test that --no-metadata leaves the PNG untagged
+#[test]
+fn test_render_no_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("coins.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "coins.pxl", "-o", "coin.png", "--no-metadata"]);
+    assert!(output.status.success());
+    assert_eq!(inspect_json(dir.path(), "coin.png"), serde_json::json!({}));
+}
+
+/// Test that inspect rejects files that aren't PNGs
+#[test]
+fn test_inspect_not_png() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("coins.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["inspect", "coins.pxl"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a PNG file"));
+}
@@ -0,0 +1,95 @@
+//! CLI integration tests for composition layer groups (--show-layer, --hide-layer)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const FIXTURE: &str = "tests/fixtures/valid/composition_layer_groups.jsonl";
+
+const SKY: [u8; 4] = [0x60, 0x90, 0xE0, 255];
+const TREE: [u8; 4] = [0x20, 0x60, 0x20, 255];
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_layer_groups_test").join(name);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn render(args: &[&str], output_path: &Path) -> Output {
+    Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--composition", "forest", "--strict"])
+        .args(args)
+        .arg("-o")
+        .arg(output_path)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn open(path: &Path) -> image::RgbaImage {
+    image::open(path).expect("Failed to open PNG").to_rgba8()
+}
+
+/// Hidden groups are skipped unless shown; visible groups render
+#[test]
+fn test_default_render_skips_hidden_group() {
+    let path = output_dir("default").join("forest.png");
+    let output = render(&[], &path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let img = open(&path);
+    assert_eq!(img.get_pixel(0, 0).0, TREE);
+    assert_eq!(img.get_pixel(3, 0).0, TREE);
+}
+
+/// --hide-layer matches group names by glob
+#[test]
+fn test_hide_layer_hides_group() {
+    let path = output_dir("hide").join("forest.png");
+    let output = render(&["--hide-layer", "fore*"], &path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let img = open(&path);
+    assert_eq!(img.get_pixel(0, 0).0, SKY);
+    assert_eq!(img.get_pixel(3, 0).0, SKY);
+}
+
+/// --show-layer reveals a hidden group at the group's opacity; --hide-layer
+/// matches single layers too
+#[test]
+fn test_show_layer_reveals_hidden_group() {
+    let path = output_dir("show").join("forest.png");
+    let output = render(&["--show-layer", "weather", "--hide-layer", "bushes"], &path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let img = open(&path);
+    let misted_tree = img.get_pixel(0, 0).0;
+    let misted_sky = img.get_pixel(3, 0).0;
+    assert_ne!(misted_tree, TREE);
+    assert!(misted_tree[0] > TREE[0] && misted_tree[0] < 0xFF, "{:?}", misted_tree);
+    assert!(misted_sky[0] > SKY[0] && misted_sky[0] < 0xFF, "{:?}", misted_sky);
+}
+
+/// Strict mode fails when a toggle matches nothing
+#[test]
+fn test_unmatched_toggle_fails_in_strict_mode() {
+    let path = output_dir("unmatched").join("forest.png");
+    let output = render(&["--hide-layer", "clouds"], &path);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("matched no composition layers"));
+}
@@ -0,0 +1,88 @@
+//! CLI integration tests for layered sprites (--split-layers)
+//!
+//! These tests verify that sprite layers are composited back-to-front by z
+//! and that --split-layers saves each layer next to the composite.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/layered_knight.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_layers_test").join(name);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn render(args: &[&str], output_path: &Path) {
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE])
+        .args(args)
+        .arg("-o")
+        .arg(output_path)
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+}
+
+fn open(path: &Path) -> image::RgbaImage {
+    image::open(path).expect("Failed to open PNG").to_rgba8()
+}
+
+/// Test that layers composite by z, not declaration order
+#[test]
+fn test_layers_composite_by_z() {
+    let path = output_dir("composite").join("knight.png");
+    render(&[], &path);
+
+    let img = open(&path);
+    assert_eq!(img.dimensions(), (8, 8));
+    assert_eq!(img.get_pixel(3, 1).0, [0xC0, 0x30, 0x30, 255], "helmet (z 2) on top");
+    assert_eq!(img.get_pixel(1, 2).0, [0x80, 0x90, 0xA0, 255], "armor (z 1) over base");
+    assert_eq!(img.get_pixel(2, 6).0, [0xE0, 0xB0, 0x90, 255], "base shows below armor");
+}
+
+/// Test that --split-layers saves one PNG per layer
+#[test]
+fn test_split_layers_saves_each_layer() {
+    let dir = output_dir("split");
+    let path = dir.join("knight.png");
+    render(&["--split-layers", "--scale", "2"], &path);
+
+    for layer in ["base", "armor", "helmet"] {
+        let layer_path = dir.join(format!("knight_{}.png", layer));
+        assert!(layer_path.exists(), "missing {}", layer_path.display());
+        assert_eq!(open(&layer_path).dimensions(), (16, 16), "layers are scaled");
+    }
+
+    let helmet = open(&dir.join("knight_helmet.png"));
+    assert_eq!(helmet.get_pixel(6, 0)[3], 255);
+    assert_eq!(helmet.get_pixel(4, 12)[3], 0, "layers do not include other layers");
+}
+
+/// Test that no layer files are written without --split-layers
+#[test]
+fn test_layers_not_split_by_default() {
+    let dir = output_dir("default");
+    let path = dir.join("knight.png");
+    fs::remove_file(dir.join("knight_armor.png")).ok();
+    render(&[], &path);
+    assert!(!dir.join("knight_armor.png").exists());
+}
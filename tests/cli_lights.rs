@@ -0,0 +1,64 @@
+//! CLI integration tests for composition lighting (`ambient` and `lights`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// A gray floor at night, lit at its left end by a white lamp
+const SCENE: &str = r##"{"type": "palette", "name": "night", "colors": {"_": "#00000000", "g": "#808080", "l": "#B0B0B0", "d": "#202020"}}
+{"type": "sprite", "name": "floor", "size": [1, 1], "palette": "night", "regions": {"g": {"rect": [0, 0, 1, 1]}}}
+{"type": "composition", "name": "yard", "size": [8, 1], "ambient": "#404040", "lights": [{"at": [0, 0], "radius": 4, "falloff": "hard"}], "sprites": {"F": "floor"}, "layers": [{"map": ["FFFFFFFF"]}]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn render_yard(scene: &str) -> image::RgbaImage {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("yard.pxl"), scene).unwrap();
+
+    let output = pxl(
+        dir.path(),
+        &["render", "yard.pxl", "--composition", "yard", "--strict", "-o", "yard.png"],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    image::open(dir.path().join("yard.png")).unwrap().to_rgba8()
+}
+
+/// Ambient darkens the whole scene; the lamp brightens pixels in its radius
+#[test]
+fn test_lights_over_ambient() {
+    let image = render_yard(SCENE);
+    assert_eq!(image.get_pixel(0, 0).0, [160, 160, 160, 255]);
+    assert_eq!(image.get_pixel(3, 0).0, [160, 160, 160, 255]);
+    assert_eq!(image.get_pixel(4, 0).0, [32, 32, 32, 255]);
+}
+
+/// Lighting runs before palette_lock, so lit colors snap to the palette
+#[test]
+fn test_lights_with_palette_lock() {
+    let locked = SCENE.replace(r#""ambient""#, r#""palette_lock": "night", "ambient""#);
+    let image = render_yard(&locked);
+    assert_eq!(image.get_pixel(0, 0).0, [0xB0, 0xB0, 0xB0, 255]);
+    assert_eq!(image.get_pixel(7, 0).0, [0x20, 0x20, 0x20, 255]);
+}
@@ -0,0 +1,112 @@
+//! CLI integration tests for `[limits]` guardrails in render and validate
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn sprite(size: u32) -> String {
+    format!(
+        r##"{{"type": "sprite", "name": "wall", "size": [{size}, {size}], "palette": {{"x": "#FF0000"}}, "regions": {{"x": {{"rect": [0, 0, 1, 1]}}}}}}"##
+    )
+}
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that the default size limit stops a huge sprite before rendering
+#[test]
+fn test_render_rejects_oversized_sprite() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("huge.pxl"), sprite(100_000)).unwrap();
+
+    let output = pxl(dir.path(), &["render", "huge.pxl", "-o", "huge.png"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Sprite 'wall' is 100000x100000, over the limit of 4096x4096 (limits.max_size)"
+        ),
+        "stderr: {}",
+        stderr
+    );
+    assert!(!dir.path().join("huge.png").exists());
+}
+
+/// Test that limits from pxl.toml apply to validate and render
+#[test]
+fn test_limits_from_config() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("pxl.toml"),
+        "[project]\nname = \"limits\"\n\n[limits]\nmax_size = [4, 4]\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("wall.pxl"), sprite(8)).unwrap();
+
+    let output = pxl(dir.path(), &["validate", "wall.pxl", "--json"]);
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["errors"][0]["type"], "limit_exceeded");
+
+    let output = pxl(dir.path(), &["render", "wall.pxl", "-o", "wall.png"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("over the limit of 4x4"));
+}
+
+const CYCLES: &str = r##"{"type": "palette", "name": "glow", "colors": {"a": "#100000", "b": "#200000", "c": "#300000", "d": "#400000", "e": "#500000"}}
+{"type": "sprite", "name": "lamp", "size": [5, 1], "palette": "glow", "regions": {"a": {"points": [[0, 0]]}, "b": {"points": [[1, 0]]}, "c": {"points": [[2, 0]]}, "d": {"points": [[3, 0]]}, "e": {"points": [[4, 0]]}}}
+{"type": "animation", "name": "flicker", "frames": ["lamp"], "palette_cycle": [{"tokens": ["a", "b", "c"]}, {"tokens": ["a", "b", "c", "d", "e"]}]}"##;
+
+/// Test that palette cycle frames are truncated at limits.max_frames, or rejected with --strict
+#[test]
+fn test_palette_cycle_frame_cap() {
+    use image::AnimationDecoder;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("pxl.toml"),
+        "[project]\nname = \"limits\"\n\n[limits]\nmax_frames = 4\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("lamp.pxl"), CYCLES).unwrap();
+
+    let output = pxl(dir.path(), &["render", "lamp.pxl", "--gif", "-o", "lamp.gif"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {}", stderr);
+    assert!(
+        stderr.contains("need 15 frames (LCM of periods 3, 5), truncated to 4"),
+        "stderr: {}",
+        stderr
+    );
+    let file = fs::File::open(dir.path().join("lamp.gif")).unwrap();
+    let frames = image::codecs::gif::GifDecoder::new(file).unwrap().into_frames().count();
+    assert_eq!(frames, 4);
+
+    let output = pxl(dir.path(), &["render", "lamp.pxl", "--gif", "-o", "lamp.gif", "--strict"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = pxl(dir.path(), &["validate", "lamp.pxl", "--json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["warnings"][0]["type"], "limit_exceeded");
+}
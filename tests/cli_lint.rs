@@ -0,0 +1,94 @@
+//! CLI integration tests for the lint command (pxl lint)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_lint_test").join(test);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+const SPRITES: &str = r##"{"type": "palette", "name": "hero", "colors": {"_": "#00000000", "skin": "#FFCC99"}}
+{"type": "sprite", "name": "HeroIdle", "size": [1, 1], "palette": "hero", "regions": {"skin": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]]}}}"##;
+
+fn lint(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary())
+        .arg("lint")
+        .arg(dir.join("sprites.pxl"))
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that default rules only warn
+#[test]
+fn test_lint_warns_by_default() {
+    let dir = output_dir("defaults");
+    fs::write(dir.join("sprites.pxl"), SPRITES).unwrap();
+    fs::remove_file(dir.join("pxl.toml")).ok();
+
+    let output = lint(&dir, &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {}", stderr);
+    assert!(stderr.contains("sprites.pxl:2: warning[snake-case-names]"), "stderr: {}", stderr);
+    assert!(stderr.contains("sprites.pxl:3: warning[named-palette]"), "stderr: {}", stderr);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Found 0 errors, 2 warnings."));
+}
+
+/// Test that rule levels from pxl.toml are applied and deny fails the run
+#[test]
+fn test_lint_config_levels() {
+    let dir = output_dir("config");
+    fs::write(dir.join("sprites.pxl"), SPRITES).unwrap();
+    fs::write(
+        dir.join("pxl.toml"),
+        "[project]\nname = \"lint\"\n\n[lint.rules]\nnamed-palette = \"deny\"\nsnake-case-names = \"allow\"\n",
+    )
+    .unwrap();
+
+    let output = lint(&dir, &["--json"]);
+    assert!(!output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["passed"], false);
+    assert_eq!(report["errors"], 1);
+    assert_eq!(report["warnings"], 0);
+    assert_eq!(report["diagnostics"][0]["rule"], "named-palette");
+    assert_eq!(report["diagnostics"][0]["level"], "deny");
+    assert_eq!(report["diagnostics"][0]["line"], 3);
+}
+
+/// Test that an unknown rule in pxl.toml is reported
+#[test]
+fn test_lint_unknown_rule() {
+    let dir = output_dir("unknown_rule");
+    fs::write(dir.join("sprites.pxl"), SPRITES).unwrap();
+    fs::write(
+        dir.join("pxl.toml"),
+        "[project]\nname = \"lint\"\n\n[lint.rules]\nbogus = \"deny\"\n",
+    )
+    .unwrap();
+
+    let output = lint(&dir, &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("lint.rules.bogus"));
+}
@@ -0,0 +1,100 @@
+//! CLI integration tests for `--locale` in render and export
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const MENU: &str = r##"{"type": "palette", "name": "ui", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF", "g": "#00FF00"}}
+{"type": "sprite", "name": "title", "size": [2, 1], "palette": "ui", "regions": {"r": {"rect": [0, 0, 2, 1]}}, "locale": {"ja": {"sprite": "title_ja"}}}
+{"type": "sprite", "name": "title_ja", "size": [2, 1], "palette": "ui", "regions": {"b": {"rect": [0, 0, 2, 1]}}}
+{"type": "sprite", "name": "icon", "size": [2, 1], "palette": "ui", "regions": {"g": {"rect": [0, 0, 2, 1]}}}
+{"type": "composition", "name": "menu", "size": [2, 2], "sprites": {"T": "title", "I": "icon"}, "layers": [{"map": ["T", "I"]}], "locale": {"ja": {"sprites": {"I": "title_ja"}}}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn pixel(path: &Path, x: u32, y: u32) -> [u8; 4] {
+    image::open(path).unwrap().to_rgba8().get_pixel(x, y).0
+}
+
+/// Test that --locale swaps sprites and composition sprite map entries
+#[test]
+fn test_render_with_locale() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("menu.pxl"), MENU).unwrap();
+
+    let output = pxl(dir.path(), &["render", "menu.pxl", "-c", "menu", "-o", "en.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(pixel(&dir.path().join("en.png"), 0, 0), [255, 0, 0, 255]);
+    assert_eq!(pixel(&dir.path().join("en.png"), 0, 1), [0, 255, 0, 255]);
+
+    let output =
+        pxl(dir.path(), &["render", "menu.pxl", "-c", "menu", "--locale", "ja", "-o", "ja.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(pixel(&dir.path().join("ja.png"), 0, 0), [0, 0, 255, 255]);
+    assert_eq!(pixel(&dir.path().join("ja.png"), 0, 1), [0, 0, 255, 255]);
+
+    // The swapped sprite keeps its own name and output file
+    let output =
+        pxl(dir.path(), &["render", "menu.pxl", "-s", "title", "--locale", "ja", "-o", "out/"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(pixel(&dir.path().join("out/title.png"), 1, 0), [0, 0, 255, 255]);
+}
+
+/// Test that a missing substitution warns, and fails under --strict
+#[test]
+fn test_render_locale_missing_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = MENU.replace(r#""sprite": "title_ja""#, r#""sprite": "title_jp""#);
+    fs::write(dir.path().join("menu.pxl"), source).unwrap();
+
+    let args = ["render", "menu.pxl", "-s", "title", "--locale", "ja", "-o", "out/"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("'title' locale 'ja' sprite 'title_jp' not found"));
+    assert_eq!(pixel(&dir.path().join("out/title.png"), 0, 0), [255, 0, 0, 255]);
+
+    let output = pxl(dir.path(), &[&args[..], &["--strict"]].concat());
+    assert_eq!(output.status.code(), Some(1));
+}
+
+/// Test that export resolves locale substitutions too
+#[test]
+fn test_export_with_locale() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("menu.pxl"), MENU).unwrap();
+
+    let export = |locale: Option<&str>| {
+        let mut args = vec!["export", "menu.pxl", "--format", "emoji", "-s", "title"];
+        if let Some(locale) = locale {
+            args.extend(["--locale", locale]);
+        }
+        let output = pxl(dir.path(), &args);
+        assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_ne!(export(None), export(Some("ja")));
+    assert_eq!(export(Some("fr")), export(None));
+}
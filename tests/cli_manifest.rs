@@ -0,0 +1,120 @@
+//! CLI integration tests for `pxl render --manifest`
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn fixture(name: &str) -> String {
+    Path::new("tests/fixtures/valid").join(name).canonicalize().unwrap().display().to_string()
+}
+
+/// Write `manifest` into a temp dir and render it
+fn render_manifest(manifest: serde_json::Value, extra: &[&str]) -> (TempDir, Output) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("jobs.json");
+    std::fs::write(&path, manifest.to_string()).unwrap();
+    let output = Command::new(pxl_binary())
+        .arg("render")
+        .args(extra)
+        .arg("--manifest")
+        .arg(&path)
+        .output()
+        .expect("Failed to execute pxl");
+    (dir, output)
+}
+
+fn dimensions(path: &Path) -> (u32, u32) {
+    image::open(path)
+        .unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+        .to_rgba8()
+        .dimensions()
+}
+
+#[test]
+fn test_manifest_renders_all_jobs() {
+    let (dir, output) = render_manifest(
+        serde_json::json!({
+            "input": fixture("theme_night.jsonl"),
+            "jobs": [
+                { "sprite": "tree", "scale": 3, "output": "out/tree.png" },
+                { "composition": "grove", "output": "out/grove.png" },
+                { "input": fixture("animation.jsonl"), "animation": "blink_anim", "output": "out/blink.gif" },
+                { "input": fixture("animation.jsonl"), "animation": "blink_anim", "scale": 2, "output": "out/blink_sheet.png" }
+            ]
+        }),
+        &[],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let out = dir.path().join("out");
+    assert_eq!(dimensions(&out.join("tree.png")), (6, 6));
+    assert_eq!(dimensions(&out.join("grove.png")), (4, 2));
+    assert_eq!(dimensions(&out.join("blink.gif")), (2, 2));
+    assert_eq!(dimensions(&out.join("blink_sheet.png")), (8, 4));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).matches("Saved:").count(), 4);
+}
+
+#[test]
+fn test_manifest_uses_positional_input_as_default() {
+    let input = fixture("theme_night.jsonl");
+    let (dir, output) = render_manifest(
+        serde_json::json!({ "jobs": [{ "sprite": "bush", "output": "bush.png" }] }),
+        &[&input],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(dimensions(&dir.path().join("bush.png")), (2, 2));
+}
+
+#[test]
+fn test_manifest_invalid_job_renders_nothing() {
+    let (dir, output) = render_manifest(
+        serde_json::json!({
+            "input": fixture("theme_night.jsonl"),
+            "jobs": [
+                { "sprite": "tree", "output": "tree.png" },
+                { "sprite": "bush", "format": "gif", "output": "bush.gif" }
+            ]
+        }),
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Job 2 (sprite 'bush')"), "stderr: {}", stderr);
+    assert!(!dir.path().join("tree.png").exists());
+}
+
+#[test]
+fn test_manifest_failed_job_does_not_stop_others() {
+    let (dir, output) = render_manifest(
+        serde_json::json!({
+            "input": fixture("theme_night.jsonl"),
+            "jobs": [
+                { "sprite": "missing", "output": "missing.png" },
+                { "sprite": "tree", "output": "tree.png" }
+            ]
+        }),
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("sprite 'missing' not found"), "stderr: {}", stderr);
+    assert!(stderr.contains("1 of 2 jobs failed"), "stderr: {}", stderr);
+    assert!(dir.path().join("tree.png").exists());
+}
@@ -0,0 +1,84 @@
+//! CLI integration tests for lighting map output
+//!
+//! These tests verify that `--maps` writes normal and emissive maps next to the
+//! albedo render with matching dimensions, including atlas placement.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/lighting_maps.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_maps_test").join(name);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Test that a sprite render writes normal and emissive maps alongside it
+#[test]
+fn test_sprite_maps() {
+    let dir = output_dir("sprite");
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--sprite", "lamp", "--maps", "normal,emissive", "--scale", "2"])
+        .arg("-o")
+        .arg(dir.join("lamp.png"))
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let albedo = image::open(dir.join("lamp.png")).expect("albedo").to_rgba8();
+    let normal = image::open(dir.join("lamp_normal.png")).expect("normal map").to_rgba8();
+    let emissive = image::open(dir.join("lamp_emissive.png")).expect("emissive map").to_rgba8();
+    assert_eq!(albedo.dimensions(), (12, 12));
+    assert_eq!(normal.dimensions(), albedo.dimensions());
+    assert_eq!(emissive.dimensions(), albedo.dimensions());
+
+    // Flat interior faces the viewer
+    assert_eq!(normal.get_pixel(2, 6).0, [128, 128, 255, 255]);
+    // Only the glow region is emissive
+    assert_eq!(emissive.get_pixel(7, 7).0, [0xFF, 0xD0, 0x40, 255]);
+    assert_eq!(emissive.get_pixel(0, 0)[3], 0);
+}
+
+/// Test that atlas maps share the albedo atlas layout
+#[test]
+fn test_atlas_maps() {
+    let dir = output_dir("atlas");
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--format", "atlas", "--maps", "emissive", "--padding", "1"])
+        .arg("-o")
+        .arg(dir.join("lights.png"))
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let albedo = image::open(dir.join("lights.png")).expect("albedo atlas").to_rgba8();
+    let emissive = image::open(dir.join("lights_emissive.png")).expect("emissive atlas").to_rgba8();
+    assert_eq!(emissive.dimensions(), albedo.dimensions());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("lights.json")).unwrap()).unwrap();
+    let lamp = &json["frames"]["lamp"];
+    let (x, y) = (lamp["x"].as_u64().unwrap() as u32, lamp["y"].as_u64().unwrap() as u32);
+    assert_eq!(emissive.get_pixel(x + 3, y + 3), albedo.get_pixel(x + 3, y + 3));
+    assert_eq!(emissive.get_pixel(x, y)[3], 0);
+}
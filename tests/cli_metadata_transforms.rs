@@ -0,0 +1,59 @@
+//! CLI integration tests for sprite metadata that follows transforms
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "sprite", "name": "hero", "size": [8, 4], "palette": {"_": "#00000000", "r": "#FF0000"}, "regions": {"r": {"rect": [0, 0, 2, 4]}}, "transform": ["mirror-h"], "metadata": {"origin": [2, 4], "boxes": {"hit": {"x": 0, "y": 0, "w": 2, "h": 4}}}}
+{"type": "sprite", "name": "floor", "size": [4, 4], "palette": {"_": "#00000000", "r": "#FF0000"}, "regions": {"r": {"rect": [0, 0, 4, 1]}}, "transform": ["tile:2x1"], "metadata": {"origin": [0, 4]}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that atlas metadata is mirrored along with the sprite's pixels
+#[test]
+fn test_atlas_metadata_follows_mirror() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("hero.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "hero.pxl", "--format", "atlas", "-o", "atlas.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("atlas.json")).unwrap()).unwrap();
+    let hero = &json["frames"]["hero"];
+    assert_eq!(hero["origin"], serde_json::json!([6, 4]));
+    assert_eq!(hero["boxes"]["hit"], serde_json::json!({"x": 6, "y": 0, "w": 2, "h": 4}));
+
+    // The red column moved to the right edge with its hit box
+    let atlas = image::open(dir.path().join("atlas.png")).unwrap().to_rgba8();
+    let (x, y) = (hero["x"].as_u64().unwrap() as u32, hero["y"].as_u64().unwrap() as u32);
+    assert_eq!(atlas.get_pixel(x + 7, y)[3], 255);
+    assert_eq!(atlas.get_pixel(x, y)[3], 0);
+
+    // Tiling has no single place for the origin, so it stays put with a warning
+    assert_eq!(json["frames"]["floor"]["origin"], serde_json::json!([0, 4]));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("sprite 'floor': metadata left unchanged"), "stderr: {}", stderr);
+}
@@ -0,0 +1,72 @@
+//! CLI integration tests for sprite, variant and sprite layer `opacity`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/opacity.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_opacity_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Render one sprite (`--sprite`) or composition (`--composition`) and load the PNG.
+fn render(flag: &str, name: &str) -> image::RgbaImage {
+    let dir = output_dir();
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--strict", flag, name, "-o"])
+        .arg(format!("{}/", dir.display()))
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+    image::open(dir.join(format!("{}.png", name))).expect("Failed to open PNG").to_rgba8()
+}
+
+/// Test that sprite opacity scales the alpha of every pixel
+#[test]
+fn test_sprite_opacity() {
+    let img = render("--sprite", "ghost");
+    assert_eq!(img.get_pixel(0, 0).0, [255, 255, 255, 128]);
+}
+
+/// Test that a variant's opacity replaces its base sprite's
+#[test]
+fn test_variant_opacity() {
+    let img = render("--composition", "faint");
+    assert_eq!(img.get_pixel(0, 0).0, [255, 255, 255, 64]);
+}
+
+/// Test that layer opacity only fades that layer
+#[test]
+fn test_layer_opacity() {
+    let img = render("--sprite", "lamp");
+    assert_eq!(img.get_pixel(0, 0).0, [255, 255, 255, 255]);
+    assert_eq!(img.get_pixel(1, 0).0, [255, 255, 255, 128]);
+}
+
+/// Test that compositions blend a translucent sprite over what's beneath it
+#[test]
+fn test_composition_uses_sprite_opacity() {
+    let img = render("--composition", "haunted");
+    let [r, g, b, a] = img.get_pixel(0, 0).0;
+    assert_eq!(a, 255);
+    assert!((127..=129).contains(&r) && r == g && g == b, "got {:?}", [r, g, b]);
+}
@@ -0,0 +1,187 @@
+//! CLI integration tests for OpenRaster export (--format ora)
+//!
+//! These tests verify that compositions and layered sprites export as .ora
+//! archives whose layers keep their names, order, blend modes and opacity,
+//! and that `pxl import` turns such archives back into a composition.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_ora_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn export_ora(fixture: &str, args: &[&str], output_path: &Path) -> Output {
+    let output = Command::new(pxl_binary())
+        .args(["render", fixture, "--format", "ora"])
+        .args(args)
+        .arg("-o")
+        .arg(output_path)
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "Export failed: {}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+/// Read the entries of a stored (uncompressed) ZIP archive in order
+fn zip_entries(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+    let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while u32_at(at) == 0x0403_4b50 {
+        assert_eq!(u16_at(at + 8), 0, "entries are stored");
+        let size = u32_at(at + 18);
+        let name_len = u16_at(at + 26);
+        let data = at + 30 + name_len + u16_at(at + 28);
+        let name = String::from_utf8(bytes[at + 30..at + 30 + name_len].to_vec()).unwrap();
+        entries.push((name, bytes[data..data + size].to_vec()));
+        at = data + size;
+    }
+    entries
+}
+
+/// Test that composition layers keep order, blend mode and opacity
+#[test]
+fn test_composition_exports_layers() {
+    let path = output_dir().join("landscape.ora");
+    let output = export_ora(
+        "tests/fixtures/valid/ora_scene.jsonl",
+        &["--composition", "landscape", "--scale", "2"],
+        &path,
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no subtract blend mode"), "{}", stderr);
+
+    let entries = zip_entries(&fs::read(&path).unwrap());
+    assert_eq!(entries[0], ("mimetype".to_string(), b"image/openraster".to_vec()));
+
+    let stack = String::from_utf8(entries[1].1.clone()).unwrap();
+    let names: Vec<&str> =
+        stack.split("name=\"").skip(1).map(|s| s.split('"').next().unwrap()).collect();
+    assert_eq!(names, ["layer_3", "shadows", "hills", "base"], "top layer first");
+    assert!(stack.contains(
+        "name=\"shadows\" src=\"data/layer2.png\" composite-op=\"svg:multiply\" opacity=\"0.500\""
+    ));
+    assert!(stack.contains("w=\"8\" h=\"8\""));
+
+    // Layers are unblended and full canvas size
+    let (_, shadows) = entries.iter().find(|(name, _)| name == "data/layer2.png").unwrap();
+    let shadows = image::load_from_memory(shadows).unwrap().to_rgba8();
+    assert_eq!(shadows.dimensions(), (8, 8));
+    assert_eq!(shadows.get_pixel(0, 4).0, [0x40, 0x40, 0x80, 255]);
+    assert_eq!(shadows.get_pixel(4, 4)[3], 0);
+
+    assert!(entries.iter().any(|(name, _)| name == "mergedimage.png"));
+    assert!(entries.iter().any(|(name, _)| name == "Thumbnails/thumbnail.png"));
+}
+
+/// Test that a sprite's layers become ORA layers
+#[test]
+fn test_layered_sprite_exports_layers() {
+    let path = output_dir().join("knight.ora");
+    export_ora("tests/fixtures/valid/layered_knight.jsonl", &["--sprite", "knight"], &path);
+
+    let entries = zip_entries(&fs::read(&path).unwrap());
+    let stack = String::from_utf8(entries[1].1.clone()).unwrap();
+    let names: Vec<&str> =
+        stack.split("name=\"").skip(1).map(|s| s.split('"').next().unwrap()).collect();
+    assert_eq!(names, ["helmet", "armor", "base"]);
+}
+
+/// Test that an exported .ora imports back into a composition that renders the same image
+#[test]
+fn test_import_roundtrip() {
+    let dir = output_dir();
+    let ora = dir.join("roundtrip.ora");
+    let jsonl = dir.join("roundtrip.jsonl");
+    let original = dir.join("roundtrip_original.png");
+    let reimported = dir.join("roundtrip_reimported.png");
+    let fixture = "tests/fixtures/valid/ora_scene.jsonl";
+    export_ora(fixture, &["--composition", "landscape"], &ora);
+
+    let output = Command::new(pxl_binary())
+        .arg("import")
+        .arg(&ora)
+        .arg("-o")
+        .arg(&jsonl)
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "Import failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("(4x4, 4 layers)"), "{}", stdout);
+
+    let content = fs::read_to_string(&jsonl).unwrap();
+    let comp: serde_json::Value = serde_json::from_str(content.lines().last().unwrap()).unwrap();
+    assert_eq!(comp["name"], "roundtrip");
+    assert_eq!(comp["layers"][2]["blend"], "multiply");
+    assert_eq!(comp["layers"][2]["opacity"], 0.5);
+
+    for (input, name, png) in
+        [(fixture, "landscape", &original), (jsonl.to_str().unwrap(), "roundtrip", &reimported)]
+    {
+        let output = Command::new(pxl_binary())
+            .args(["render", input, "--strict", "--composition", name, "-o"])
+            .arg(png)
+            .output()
+            .expect("Failed to execute pxl");
+        assert!(
+            output.status.success(),
+            "Render failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    // The subtract layer (top-left cell) exports as normal; everything else matches
+    let original = image::open(&original).unwrap().to_rgba8();
+    let reimported = image::open(&reimported).unwrap().to_rgba8();
+    assert_eq!(original.dimensions(), reimported.dimensions());
+    for (x, y, pixel) in original.enumerate_pixels() {
+        if x >= 2 || y >= 2 {
+            assert_eq!(pixel, reimported.get_pixel(x, y), "pixel ({}, {})", x, y);
+        }
+    }
+}
+
+/// Test that malformed or oversized layered files fail cleanly instead of aborting
+#[test]
+fn test_import_rejects_oversized_layers() {
+    let dir = output_dir();
+    for (fixture, message) in [
+        ("huge_canvas.ora", "OpenRaster canvas is 1000000x1000000, larger than the 4096x4096"),
+        ("huge_layer.ora", "Image size exceeds limit"),
+        ("huge_canvas.psd", "PSD canvas is 100000x100000, larger than the 4096x4096"),
+        ("overflow_edges.psd", "Layer 'edge' is 4294967295x1, larger than the 4096x4096"),
+    ] {
+        let output = Command::new(pxl_binary())
+            .arg("import")
+            .arg(Path::new("tests/fixtures/import").join(fixture))
+            .arg("-o")
+            .arg(dir.join("rejected.jsonl"))
+            .output()
+            .expect("Failed to execute pxl");
+        assert_eq!(output.status.code(), Some(1), "{} should fail with an error", fixture);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{}: {}", fixture, stderr);
+    }
+}
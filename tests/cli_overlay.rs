@@ -0,0 +1,80 @@
+//! CLI integration tests for render debug overlays
+
+use image::Rgba;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "sprite", "name": "hero", "size": [6, 6], "palette": {"_": "#00000000", "r": "#FF0000"}, "regions": {"r": {"rect": [5, 0, 1, 1]}}, "metadata": {"origin": [4, 4], "boxes": {"hit": {"x": 1, "y": 1, "w": 2, "h": 2}}}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that guides are drawn at scaled positions from the sprite metadata
+#[test]
+fn test_render_overlays() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("hero.pxl"), SOURCE).unwrap();
+
+    let args = [
+        "render",
+        "hero.pxl",
+        "-o",
+        "hero.png",
+        "--scale",
+        "2",
+        "--overlay",
+        "boxes,origin,grid:3",
+    ];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let image = image::open(dir.path().join("hero.png")).unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (12, 12));
+    // Sprite pixels are untouched away from guides
+    assert_eq!(*image.get_pixel(11, 0), Rgba([255, 0, 0, 255]));
+    // Hit box outline around source pixels (1,1)-(2,2)
+    assert_eq!(*image.get_pixel(2, 2), Rgba([255, 0, 0, 255]));
+    assert_eq!(*image.get_pixel(5, 5), Rgba([255, 0, 0, 255]));
+    assert_eq!(image.get_pixel(3, 3)[3], 0);
+    // Origin crosshair through the middle of pixel (4,4)
+    assert_eq!(*image.get_pixel(9, 9), Rgba([255, 128, 0, 255]));
+    assert_eq!(*image.get_pixel(4, 9), Rgba([255, 128, 0, 255]));
+    // Grid line every 3 source pixels
+    assert_eq!(*image.get_pixel(6, 0), Rgba([128, 128, 128, 128]));
+    assert_eq!(image.get_pixel(7, 0)[3], 0);
+}
+
+/// Test that overlays are rejected where they cannot be drawn
+#[test]
+fn test_overlay_invalid() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("hero.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "hero.pxl", "--overlay", "axes"]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = pxl(dir.path(), &["render", "hero.pxl", "--gif", "--overlay", "grid"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--overlay"));
+}
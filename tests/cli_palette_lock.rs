@@ -0,0 +1,78 @@
+//! CLI integration tests for composition palette locking (`palette_lock`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// A gray shadow multiplied over a red floor, which gives a dark red
+const SCENE: &str = r##"{"type": "palette", "name": "dusk", "colors": {"_": "#00000000", "r": "#C80000", "g": "#808080", "d": "#500000"}}
+{"type": "sprite", "name": "floor", "size": [1, 1], "palette": "dusk", "regions": {"r": {"rect": [0, 0, 1, 1]}}}
+{"type": "sprite", "name": "shadow", "size": [1, 1], "palette": "dusk", "regions": {"g": {"rect": [0, 0, 1, 1]}}}
+{"type": "composition", "name": "room", "size": [2, 1], "palette_lock": "dusk", "sprites": {".": null, "F": "floor", "S": "shadow"}, "layers": [{"map": ["FF"]}, {"map": ["S."], "blend": "multiply"}]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Blended pixels snap to the locked palette and the count is reported
+#[test]
+fn test_palette_lock_snaps_blends() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("room.pxl"), SCENE).unwrap();
+
+    let output = pxl(
+        dir.path(),
+        &["render", "room.pxl", "--composition", "room", "--strict", "-o", "room.png"],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("snapped 1 of 2 pixels to dusk"), "{}", stderr);
+
+    let image = image::open(dir.path().join("room.png")).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(0, 0).0, [0x50, 0, 0, 255]);
+    assert_eq!(image.get_pixel(1, 0).0, [0xC8, 0, 0, 255]);
+}
+
+/// Locking to a missing palette is a warning, an error with --strict
+#[test]
+fn test_palette_lock_unknown_palette() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("room.pxl"),
+        SCENE.replace(r#""palette_lock": "dusk""#, r#""palette_lock": "dawn""#),
+    )
+    .unwrap();
+
+    let output =
+        pxl(dir.path(), &["render", "room.pxl", "--composition", "room", "-o", "room.png"]);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("palette_lock palette 'dawn' not found")
+    );
+
+    let output = pxl(
+        dir.path(),
+        &["render", "room.pxl", "--composition", "room", "--strict", "-o", "room.png"],
+    );
+    assert!(!output.status.success());
+}
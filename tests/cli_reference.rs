@@ -0,0 +1,80 @@
+//! CLI integration tests for sprite reference image underlays
+
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "sprite", "name": "dot", "size": [2, 2], "palette": {"r": "#FF0000"}, "regions": {"r": {"points": [[0, 0]]}}, "reference": {"path": "concept.png", "opacity": 0.5}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn setup() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("art")).unwrap();
+    fs::write(dir.path().join("art/dot.pxl"), SOURCE).unwrap();
+    RgbaImage::from_pixel(1, 1, Rgba([0, 0, 255, 255]))
+        .save(dir.path().join("art/concept.png"))
+        .unwrap();
+    dir
+}
+
+/// Test that show previews the sprite over its reference, resolved next to the source
+#[test]
+fn test_show_draws_reference() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["show", "art/dot.pxl", "-o", "preview.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let preview = image::open(dir.path().join("preview.png")).unwrap().to_rgba8();
+    assert_eq!(*preview.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+    assert_eq!(*preview.get_pixel(1, 1), Rgba([0, 0, 255, 128]));
+
+    let output = pxl(dir.path(), &["show", "art/dot.pxl"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Sprite: dot (2x2)"));
+}
+
+/// Test that renders never include the reference
+#[test]
+fn test_render_ignores_reference() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["render", "art/dot.pxl", "-o", "dot.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let image = image::open(dir.path().join("dot.png")).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(1, 1)[3], 0);
+}
+
+/// Test that a missing reference only warns
+#[test]
+fn test_show_missing_reference() {
+    let dir = setup();
+    fs::remove_file(dir.path().join("art/concept.png")).unwrap();
+
+    let output = pxl(dir.path(), &["show", "art/dot.pxl", "-o", "preview.png"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: reference"));
+}
@@ -0,0 +1,129 @@
+//! CLI integration tests for deterministic PNG output (`--reproducible`, `--png-*`)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "dot", "size": [8, 8], "palette": "p", "regions": {"r": {"rect": [0, 0, 8, 4]}, "b": {"circle": [4, 6, 2]}}}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Names of the chunks in a PNG file, in order
+fn chunk_names(png: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut pos = 8;
+    while pos + 8 <= png.len() {
+        let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+        names.push(String::from_utf8_lossy(&png[pos + 4..pos + 8]).into_owned());
+        pos += 12 + len;
+    }
+    names
+}
+
+/// Test that --reproducible output depends only on the pixels
+#[test]
+fn test_reproducible_strips_ancillary_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("a")).unwrap();
+    fs::write(dir.path().join("dot.pxl"), SOURCE).unwrap();
+    fs::write(dir.path().join("a/moved.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "dot.pxl", "-o", "plain.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let plain = fs::read(dir.path().join("plain.png")).unwrap();
+    assert!(chunk_names(&plain).iter().any(|c| c == "tEXt"), "provenance by default");
+
+    for (input, out) in [("dot.pxl", "one.png"), ("a/moved.pxl", "two.png")] {
+        let output = pxl(dir.path(), &["render", input, "-o", out, "--reproducible"]);
+        assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    let one = fs::read(dir.path().join("one.png")).unwrap();
+    assert_eq!(chunk_names(&one), ["IHDR", "IDAT", "IEND"]);
+    assert_eq!(one, fs::read(dir.path().join("two.png")).unwrap(), "source path doesn't matter");
+
+    let args = ["render", "dot.pxl", "-o", "indexed.png", "--reproducible", "--indexed"];
+    let output = pxl(dir.path(), &args);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let indexed = fs::read(dir.path().join("indexed.png")).unwrap();
+    assert_eq!(chunk_names(&indexed), ["IHDR", "PLTE", "tRNS", "IDAT", "IEND"]);
+}
+
+/// Test that encoder settings change the bytes but not the pixels
+#[test]
+fn test_png_encoder_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("dot.pxl"), SOURCE).unwrap();
+
+    let render = |out: &str, extra: &[&str]| {
+        let mut args = vec!["render", "dot.pxl", "-o", out, "--scale", "8", "--no-metadata"];
+        args.extend_from_slice(extra);
+        let output = pxl(dir.path(), &args);
+        assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+        fs::read(dir.path().join(out)).unwrap()
+    };
+
+    let default = render("default.png", &[]);
+    let pinned =
+        render("pinned.png", &["--png-compression", "default", "--png-filter", "adaptive"]);
+    assert_eq!(default, pinned, "defaults are the pinned settings");
+
+    let raw = render("raw.png", &["--png-compression", "fast", "--png-filter", "none"]);
+    assert_ne!(raw, default);
+    assert_eq!(
+        image::load_from_memory(&raw).unwrap().to_rgba8(),
+        image::load_from_memory(&default).unwrap().to_rgba8()
+    );
+
+    let output = pxl(dir.path(), &["render", "dot.pxl", "--png-filter", "bogus"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+/// Test that `pxl build` writes through the same pinned encoder and flags
+#[test]
+fn test_build_uses_png_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    let config =
+        "[project]\nname = \"dots\"\nsrc = \"src\"\n\n[atlases.dots]\nsources = [\"*.pxl\"]\n";
+    fs::write(dir.path().join("pxl.toml"), config).unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/dot.pxl"), SOURCE).unwrap();
+
+    let build = |extra: &[&str]| {
+        let mut args = vec!["build", "--force"];
+        args.extend_from_slice(extra);
+        let output = pxl(dir.path(), &args);
+        assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+        fs::read(dir.path().join("build/dots.png")).unwrap()
+    };
+
+    let default = build(&[]);
+    let pixels = image::load_from_memory(&default).unwrap().to_rgba8();
+    assert_eq!(default, pixelsrc::output::encode_png(&pixels).unwrap());
+
+    let raw = build(&["--png-compression", "fast", "--png-filter", "none"]);
+    assert_ne!(raw, default);
+    assert_eq!(image::load_from_memory(&raw).unwrap().to_rgba8(), pixels);
+}
@@ -0,0 +1,92 @@
+//! CLI integration tests for seeded randomness (--seed)
+//!
+//! These tests verify that jittered regions render identically across runs,
+//! that the global seed reshuffles them, and that atlases record the seed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/jitter_grass.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_seed_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn render(args: &[&str], file_name: &str) -> image::RgbaImage {
+    let output_path = output_dir().join(file_name);
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE])
+        .args(args)
+        .arg("-o")
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+    image::open(&output_path).expect("Failed to open PNG").to_rgba8()
+}
+
+/// Test that jitter is reproducible without an explicit seed
+#[test]
+fn test_jitter_is_deterministic() {
+    let first = render(&["--sprite", "tuft"], "tuft_a.png");
+    let second = render(&["--sprite", "tuft"], "tuft_b.png");
+    assert_eq!(first, second);
+    assert_eq!(first, render(&["--sprite", "tuft", "--seed", "0"], "tuft_zero.png"));
+}
+
+/// Test that jitter moves pixels within the declared ranges
+#[test]
+fn test_jitter_stays_in_range() {
+    let img = render(&["--sprite", "tuft", "--seed", "1234"], "tuft_range.png");
+    let filled: Vec<(u32, u32)> =
+        img.enumerate_pixels().filter(|(_, _, p)| p[3] > 0).map(|(x, y, _)| (x, y)).collect();
+    assert!(!filled.is_empty());
+    assert!(filled.iter().all(|&(_, y)| (3..=6).contains(&y)), "{:?}", filled);
+}
+
+/// Test that --seed changes the jitter pattern
+#[test]
+fn test_seed_reshuffles_jitter() {
+    let base = render(&["--sprite", "tuft"], "tuft_default.png");
+    let seeded: Vec<_> = (1..=4)
+        .map(|seed| render(&["--sprite", "tuft", "--seed", &seed.to_string()], "tuft_seeded.png"))
+        .collect();
+    assert!(seeded.iter().any(|img| *img != base), "--seed should change jitter");
+}
+
+/// Test that the effective seed is written to atlas metadata
+#[test]
+fn test_atlas_records_seed() {
+    let dir = output_dir().join("atlas");
+    fs::create_dir_all(&dir).ok();
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--format", "atlas", "--seed", "99"])
+        .arg("-o")
+        .arg(dir.join("meadow.png"))
+        .output()
+        .expect("Failed to execute pxl");
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("meadow.json")).unwrap()).unwrap();
+    assert_eq!(json["seed"], 99);
+}
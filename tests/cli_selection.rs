@@ -0,0 +1,115 @@
+//! CLI integration tests for `--only`/`--exclude` object selection
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"r": "#FF0000", "g": "#00FF00"}}
+{"type": "sprite", "name": "button", "labels": ["ui"], "size": [1, 1], "palette": "p", "regions": {"r": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "debug_button", "labels": ["ui"], "size": [1, 1], "palette": "p", "regions": {"r": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "slime", "labels": ["enemy"], "size": [1, 1], "palette": "p", "regions": {"g": {"points": [[0, 0]]}}}
+{"type": "sprite", "name": "slime_2", "size": [1, 1], "palette": "p", "regions": {"r": {"points": [[0, 0]]}}}
+{"type": "animation", "name": "slime_idle", "labels": ["enemy"], "frames": ["slime", "slime_2"]}
+{"type": "animation", "name": "blink", "labels": ["ui"], "frames": ["button", "debug_button"]}
+"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn setup() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("game.pxl"), SOURCE).unwrap();
+    dir
+}
+
+fn outputs(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Test that --only and --exclude pick which sprites are rendered
+#[test]
+fn test_render_only_label_exclude_name() {
+    let dir = setup();
+
+    let output = pxl(
+        dir.path(),
+        &["render", "game.pxl", "--only", "label=ui", "--exclude", "name=debug_*", "-o", "out/"],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(outputs(&dir.path().join("out")), ["button.png"]);
+}
+
+/// Test that unselected sprites still resolve as animation frames
+#[test]
+fn test_render_animation_uses_unselected_frames() {
+    let dir = setup();
+
+    let output = pxl(
+        dir.path(),
+        &["render", "game.pxl", "--spritesheet", "--only", "label=enemy", "-o", "sheet.png"],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let sheet = image::open(dir.path().join("sheet.png")).unwrap().to_rgba8();
+    assert_eq!(sheet.dimensions(), (2, 1));
+    assert_eq!(sheet.get_pixel(0, 0).0, [0, 255, 0, 255]);
+    // slime_2 has no label but is still drawn as the second frame
+    assert_eq!(sheet.get_pixel(1, 0).0, [255, 0, 0, 255]);
+}
+
+/// Test that a selection matching nothing is an error
+#[test]
+fn test_render_nothing_selected() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["render", "game.pxl", "--only", "label=boss"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("match --only/--exclude"));
+
+    let output = pxl(dir.path(), &["render", "game.pxl", "--only", "ui"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected key=value"));
+}
+
+/// Test that export picks its default sprite from the selection
+#[test]
+fn test_export_default_from_selection() {
+    let dir = setup();
+
+    let output =
+        pxl(dir.path(), &["export", "game.pxl", "--format", "ansi", "--only", "type=sprite"]);
+    assert!(output.status.success());
+    let all = String::from_utf8_lossy(&output.stdout).into_owned();
+
+    let output =
+        pxl(dir.path(), &["export", "game.pxl", "--format", "ansi", "--only", "label=enemy"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let enemy = String::from_utf8_lossy(&output.stdout).into_owned();
+    // The first sprite is red; the first enemy is green
+    assert_ne!(all, enemy);
+}
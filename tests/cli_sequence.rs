@@ -0,0 +1,100 @@
+//! CLI integration tests for animation sequences
+//!
+//! These tests verify that a sequence renders like an animation, with its steps
+//! repeated in order and crossfade frames inserted between steps.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+
+const FIXTURE: &str = "tests/fixtures/valid/sequence_reel.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_sequence_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Test that a sequence renders to a GIF with crossfade frames
+#[test]
+fn test_sequence_gif() {
+    let output_path = output_dir().join("reel.gif");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--gif", "--animation", "reel", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let file = fs::File::open(&output_path).expect("Failed to open GIF");
+    let decoder = GifDecoder::new(std::io::BufReader::new(file)).expect("Invalid GIF");
+    let delays: Vec<u32> = decoder
+        .into_frames()
+        .map(|f| {
+            let (numer, denom) = f.expect("Invalid frame").delay().numer_denom_ms();
+            numer / denom
+        })
+        .collect();
+    // idle x2, one 200ms blend frame, attack, idle
+    assert_eq!(delays, vec![100, 100, 100, 100, 200, 200, 100, 100]);
+}
+
+/// Test that a sequence renders to a spritesheet
+#[test]
+fn test_sequence_spritesheet() {
+    let output_path = output_dir().join("reel_sheet.png");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--spritesheet", "--animation", "reel", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    // Eight 2x2 frames in a horizontal strip
+    let img = image::open(&output_path).expect("Failed to open output image");
+    assert_eq!((img.width(), img.height()), (16, 2));
+}
+
+/// Test that a sequence referencing a missing animation fails in strict mode
+#[test]
+fn test_sequence_unknown_animation_strict() {
+    let dir = output_dir();
+    let input = dir.join("broken_reel.jsonl");
+    let source = fs::read_to_string(FIXTURE)
+        .unwrap()
+        .replace(r#"{"animation": "attack", "crossfade": 200}"#, r#"{"animation": "attak"}"#);
+    fs::write(&input, source).unwrap();
+
+    let output = Command::new(pxl_binary())
+        .args(["render", "--gif", "--strict", "--animation", "reel", "-o"])
+        .arg(dir.join("broken_reel.gif"))
+        .arg(&input)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown animation 'attak'"));
+}
@@ -0,0 +1,112 @@
+//! CLI integration tests for `pxl snapshot write` and `pxl snapshot check`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"r": "#FF0000", "g": "#00FF00"}}
+{"type": "sprite", "name": "hero", "size": [2, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+{"type": "sprite", "name": "hero_2", "size": [2, 2], "palette": "p", "regions": {"g": {"rect": [0, 0, 2, 2]}}}
+{"type": "animation", "name": "walk", "frames": ["hero", "hero_2"]}
+"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn setup() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("game.pxl"), SOURCE).unwrap();
+    dir
+}
+
+/// Test that a fresh snapshot records every object and checks clean
+#[test]
+fn test_snapshot_write_then_check() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["snapshot", "write", "game.pxl"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let snapshot: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("pxl-snapshot.json")).unwrap())
+            .unwrap();
+    let objects = snapshot["objects"].as_object().unwrap();
+    let keys: Vec<&str> = objects.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["animation/walk", "sprite/hero", "sprite/hero_2"]);
+    assert_eq!(objects["animation/walk"]["frames"], 2);
+    assert_eq!(objects["sprite/hero"]["width"], 2);
+
+    let output = pxl(dir.path(), &["snapshot", "check", "game.pxl"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("3 objects match"));
+}
+
+/// Test that a changed pixel fails the check with a readable diff and a diff image
+#[test]
+fn test_snapshot_check_reports_changes() {
+    let dir = setup();
+    let output = pxl(dir.path(), &["snapshot", "write", "game.pxl", "--images", "baseline"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(dir.path().join("baseline/sprite/hero.png").exists());
+    assert!(dir.path().join("baseline/animation/walk.png").exists());
+
+    let changed = SOURCE.replace(
+        r#""regions": {"r": {"rect": [0, 0, 2, 2]}}"#,
+        r#""regions": {"r": {"points": [[0, 0], [1, 0], [0, 1]]}, "g": {"points": [[1, 1]]}}"#,
+    );
+    let changed = format!(
+        "{}{}\n",
+        changed,
+        r##"{"type": "sprite", "name": "coin", "size": [1, 1], "palette": "p", "regions": {"r": {"points": [[0, 0]]}}}"##
+    );
+    fs::write(dir.path().join("game.pxl"), changed).unwrap();
+
+    let output = pxl(
+        dir.path(),
+        &["snapshot", "check", "game.pxl", "--images", "baseline", "--diff", "diffs"],
+    );
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("sprite/hero: pixels changed (1 pixel differ within 1x1 at (1, 1))"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("animation/walk: pixels changed"), "{}", stdout);
+    assert!(stdout.contains("sprite/coin: new, not in snapshot"), "{}", stdout);
+    assert!(stdout.contains("3 objects differ"), "{}", stdout);
+
+    let diff = image::open(dir.path().join("diffs/sprite/hero.png")).unwrap();
+    assert_eq!((diff.width(), diff.height()), (8, 2));
+    assert!(dir.path().join("diffs/sprite/coin.png").exists());
+}
+
+/// Test that checking without a snapshot file is an error
+#[test]
+fn test_snapshot_check_missing_file() {
+    let dir = setup();
+    let output = pxl(dir.path(), &["snapshot", "check", "game.pxl"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Cannot read snapshot"));
+}
@@ -0,0 +1,93 @@
+//! CLI integration tests for sprite stack rendering
+//!
+//! These tests verify that `--stack` renders sprite slices bottom to top with a
+//! vertical offset, and that `--gif` spins the stack through a full rotation.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+
+const FIXTURE: &str = "tests/fixtures/valid/stack_crate.jsonl";
+const LAYERS: &str = "crate_base,crate_mid,crate_lid";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_stack_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Test that a stack PNG offsets each layer by the spacing
+#[test]
+fn test_stack_png() {
+    let output_path = output_dir().join("crate.png");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--stack", LAYERS, "--stack-spacing", "2", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let img = image::open(&output_path).expect("Failed to open PNG").to_rgba8();
+    // 4px layers lifted 2px apart
+    assert_eq!(img.dimensions(), (4, 8));
+    // Lid on top, base showing below it
+    assert_eq!(img.get_pixel(0, 0).0, [0xA0, 0x66, 0x2E, 255]);
+    assert_eq!(img.get_pixel(0, 7).0, [0x5C, 0x3A, 0x1E, 255]);
+}
+
+/// Test that a stack GIF holds one frame per rotation step
+#[test]
+fn test_stack_rotation_gif() {
+    let output_path = output_dir().join("crate.gif");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--stack", LAYERS, "--gif", "--stack-frames", "8", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let file = fs::File::open(&output_path).expect("Failed to open GIF");
+    let decoder = GifDecoder::new(std::io::BufReader::new(file)).expect("Invalid GIF");
+    let frames = decoder.into_frames().collect_frames().expect("Invalid frames");
+    assert_eq!(frames.len(), 8);
+    // Canvas fits the layer diagonal plus the stack height
+    assert!(frames.iter().all(|f| f.buffer().dimensions() == (6, 8)));
+}
+
+/// Test that an unknown layer name is an error
+#[test]
+fn test_stack_unknown_layer() {
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--stack", "crate_base,crate_top"])
+        .arg("-o")
+        .arg(output_dir().join("unknown.png"))
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("crate_top"), "stderr: {}", stderr);
+}
@@ -0,0 +1,72 @@
+//! CLI integration tests for stamps placed in sprites
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// A green plate with a red "L" rivet stamped in three corners
+const PLATE: &str = r##"{"type": "palette", "name": "metal", "colors": {"_": "#00000000", "plate": "#00FF00", "rivet": "#FF0000"}}
+{"type": "stamp", "name": "corner", "grid": ["r.", "rr"], "tokens": {"r": "rivet"}}
+{"type": "sprite", "name": "plate", "size": [6, 6], "palette": "metal", "regions": {"plate": {"rect": [0, 0, 6, 6], "z": 0}, "rivet": {"points": [[3, 3]], "z": 1}}, "stamps": [{"name": "corner", "at": [0, 0]}, {"name": "corner", "at": [4, 0], "flip": "h"}, {"name": "corner", "at": [0, 4], "rotate": 90}]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Each placement is drawn with its flip and rotation, over the plate
+#[test]
+fn test_render_stamps() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("plate.pxl"), PLATE).unwrap();
+
+    let output = pxl(dir.path(), &["render", "plate.pxl", "--strict", "-o", "plate.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let image = image::open(dir.path().join("plate.png")).unwrap().to_rgba8();
+    let rows: Vec<String> = (0..6)
+        .map(|y| {
+            (0..6)
+                .map(|x| if image.get_pixel(x, y).0 == [255, 0, 0, 255] { 'r' } else { '.' })
+                .collect()
+        })
+        .collect();
+    assert_eq!(rows, ["r....r", "rr..rr", "......", "...r..", "rr....", "r....."]);
+}
+
+/// Placing an undefined stamp is a warning, an error with --strict
+#[test]
+fn test_unknown_stamp() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("plate.pxl"),
+        PLATE.replace(r#"{"name": "corner", "at": [4, 0]"#, r#"{"name": "bolt", "at": [4, 0]"#),
+    )
+    .unwrap();
+
+    let output = pxl(dir.path(), &["render", "plate.pxl", "-o", "plate.png"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown stamp 'bolt'"));
+
+    let output = pxl(dir.path(), &["render", "plate.pxl", "--strict", "-o", "plate.png"]);
+    assert!(!output.status.success());
+}
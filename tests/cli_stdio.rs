@@ -0,0 +1,90 @@
+//! CLI integration tests for `-` as stdin/stdout
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const FIXTURE: &str = "tests/fixtures/valid/theme_night.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// Run pxl with the fixture piped to stdin
+fn pxl(args: &[&str]) -> Output {
+    let mut child = Command::new(pxl_binary())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute pxl");
+    let input = std::fs::read(FIXTURE).unwrap();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_render_stdin_to_stdout_png() {
+    let output = pxl(&["render", "-", "--sprite", "tree", "-o", "-"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let image = image::load_from_memory(&output.stdout).expect("stdout is a PNG").to_rgba8();
+    assert_eq!(image.dimensions(), (2, 2));
+    assert_eq!(image.get_pixel(0, 0).0, [0x40, 0xA0, 0x40, 255]);
+}
+
+#[test]
+fn test_render_stdin_composition_with_theme() {
+    let output = pxl(&["render", "-", "--composition", "grove", "--theme", "night", "-o", "-"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let image = image::load_from_memory(&output.stdout).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(0, 0).0, [0x10, 0x30, 0x20, 255]);
+}
+
+#[test]
+fn test_render_stdout_rejects_multiple_images() {
+    let output = pxl(&["render", "-", "-o", "-"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("exactly one image"));
+
+    let output = pxl(&["render", "-", "--format", "atlas", "-o", "-"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be written to stdout"));
+}
+
+#[test]
+fn test_fmt_validate_analyze_stdin() {
+    let output = pxl(&["fmt", "-"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"name\": \"forest_day\""));
+
+    let output = pxl(&["validate", "-", "--json"]);
+    assert!(output.status.success(), "stdout: {}", String::from_utf8_lossy(&output.stdout));
+
+    let output = pxl(&["analyze", "-", "--format", "json", "-o", "-"]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["total_sprites"], 2);
+}
+
+#[test]
+fn test_export_stdin_to_stdout_png() {
+    let output = pxl(&["export", "-", "--format", "discord", "--sprite", "bush", "-o", "-"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let image = image::load_from_memory(&output.stdout).unwrap();
+    assert_eq!((image.width(), image.height()), (128, 128));
+}
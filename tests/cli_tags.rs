@@ -0,0 +1,114 @@
+//! CLI integration tests for the --tag option
+//!
+//! These tests verify that per-tag exports only include the tagged frame range
+//! and that atlas metadata carries the tag information.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FIXTURE: &str = "tests/fixtures/valid/atf_frame_tags.jsonl";
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_tag_test");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Test that a tagged spritesheet only contains the tag's frames
+#[test]
+fn test_tag_spritesheet() {
+    let output_path = output_dir().join("run_sheet.png");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--spritesheet", "--tag", "run", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    // Two 3x3 frames in a horizontal strip
+    let img = image::open(&output_path).expect("Failed to open output image");
+    assert_eq!((img.width(), img.height()), (6, 3));
+}
+
+/// Test that an unknown tag is an error
+#[test]
+fn test_tag_unknown() {
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--gif", "--tag", "attack", "-o"])
+        .arg(output_dir().join("unknown.gif"))
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no tag named 'attack'"));
+}
+
+/// Test that Aseprite atlas JSON includes frameTags
+#[test]
+fn test_tag_aseprite_frame_tags() {
+    let output_path = output_dir().join("tags_atlas.png");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--format", "atlas-aseprite", "-o"])
+        .arg(&output_path)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let json = fs::read_to_string(output_path.with_extension("json")).expect("atlas JSON");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("valid JSON");
+    let tags = value["meta"]["frameTags"].as_array().expect("frameTags array");
+    let names: Vec<&str> = tags.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["idle", "run", "jump"]);
+
+    // Ranges index the name-sorted frames object: idle1, idle2, jump, run1, run2
+    assert_eq!([&tags[1]["from"], &tags[1]["to"]], [3, 4]);
+    assert_eq!([&tags[2]["from"], &tags[2]["to"]], [2, 2]);
+    assert_eq!(tags[1]["direction"], "forward");
+    assert!(tags[1].get("animation").is_none());
+}
+
+/// Test that Aseprite frameTags carry the animation's playback direction
+#[test]
+fn test_tag_aseprite_direction() {
+    let source = fs::read_to_string(FIXTURE)
+        .expect("fixture")
+        .replace(r#""duration": 100,"#, r#""duration": 100, "direction": "pingpong","#);
+    let input = output_dir().join("pingpong.jsonl");
+    fs::write(&input, source).expect("write input");
+    let output_path = output_dir().join("pingpong_atlas.png");
+
+    let output = Command::new(pxl_binary())
+        .args(["render", "--format", "atlas-aseprite", "-o"])
+        .arg(&output_path)
+        .arg(&input)
+        .output()
+        .expect("Failed to execute pxl");
+
+    assert!(output.status.success(), "Render failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let json = fs::read_to_string(output_path.with_extension("json")).expect("atlas JSON");
+    let value: serde_json::Value = serde_json::from_str(&json).expect("valid JSON");
+    let tags = value["meta"]["frameTags"].as_array().expect("frameTags array");
+    assert!(tags.iter().all(|t| t["direction"] == "pingpong"));
+}
@@ -0,0 +1,93 @@
+//! CLI integration tests for themes (pxl render --theme)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const FIXTURE: &str = "tests/fixtures/valid/theme_night.jsonl";
+
+const DAY_LEAF: [u8; 4] = [0x40, 0xA0, 0x40, 255];
+const NIGHT_LEAF: [u8; 4] = [0x10, 0x30, 0x20, 255];
+const NIGHT_TRUNK: [u8; 4] = [0x30, 0x20, 0x18, 255];
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pxl_theme_test").join(name);
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+fn render(args: &[&str], output_path: &Path) -> Output {
+    Command::new(pxl_binary())
+        .args(["render", FIXTURE, "--composition", "grove"])
+        .args(args)
+        .arg("-o")
+        .arg(output_path)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn open(path: &Path) -> image::RgbaImage {
+    image::open(path).expect("Failed to open PNG").to_rgba8()
+}
+
+/// Without --theme, sprites use their own palettes
+#[test]
+fn test_render_without_theme() {
+    let path = output_dir("day").join("grove.png");
+    let output = render(&["--strict"], &path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(open(&path).get_pixel(0, 0).0, DAY_LEAF);
+}
+
+/// --theme swaps the palette for every sprite in the composition
+#[test]
+fn test_render_with_theme_swaps_palettes() {
+    let path = output_dir("night").join("grove.png");
+    let output = render(&["--strict", "--theme", "night"], &path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let img = open(&path);
+    assert_eq!(img.get_pixel(0, 0).0, NIGHT_LEAF);
+    assert_eq!(img.get_pixel(0, 1).0, NIGHT_TRUNK);
+    assert_eq!(img.get_pixel(3, 1).0, NIGHT_LEAF);
+}
+
+/// Unknown themes are invalid arguments
+#[test]
+fn test_unknown_theme_fails() {
+    let path = output_dir("unknown").join("grove.png");
+    let output = render(&["--theme", "winter"], &path);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Theme 'winter' not found"));
+}
+
+/// A missing replacement palette warns, and fails under --strict
+#[test]
+fn test_missing_replacement_palette() {
+    let path = output_dir("broken").join("grove.png");
+    let output = render(&["--theme", "broken"], &path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("replacement palette 'forest_dusk' not found"));
+    assert_eq!(open(&path).get_pixel(0, 0).0, DAY_LEAF);
+
+    let output = render(&["--theme", "broken", "--strict"], &path);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,169 @@
+//! CLI integration tests for video export (`pxl export --format mp4/webm`)
+//!
+//! The encoder is replaced by a script (via `PXL_FFMPEG`) that saves the raw
+//! frames ffmpeg would receive, so these tests don't need ffmpeg installed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"_": "#00000000", "r": "#FF0000", "b": "#0000FF"}}
+{"type": "sprite", "name": "f1", "size": [3, 2], "palette": "p", "regions": {"r": {"rect": [0, 0, 2, 2]}}}
+{"type": "sprite", "name": "f2", "size": [3, 2], "palette": "p", "regions": {"b": {"rect": [0, 0, 3, 2]}}}
+{"type": "animation", "name": "walk", "frames": ["f1", "f2"], "duration": 100}"##;
+
+fn pxl(dir: &Path, args: &[&str], ffmpeg: &str) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .env("PXL_FFMPEG", ffmpeg)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Test that frames are scaled, padded and resampled to the frame rate
+#[cfg(all(unix, feature = "video"))]
+#[test]
+fn test_export_video_frames() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("walk.pxl"), SOURCE).unwrap();
+    let script = dir.path().join("ffmpeg");
+    fs::write(
+        &script,
+        "#!/bin/sh\necho \"$@\" > args.txt\nfor last; do :; done\ncat > \"$last\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    let ffmpeg = script.to_str().unwrap();
+
+    let args = ["export", "walk.pxl", "--format", "mp4", "--fps", "20", "--scale", "3"];
+    let output = pxl(dir.path(), &args, ffmpeg);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("walk_walk.mp4"));
+
+    // 9x6 scaled up to even 10x6; 200ms at 20fps is 4 frames
+    let args = fs::read_to_string(dir.path().join("args.txt")).unwrap();
+    assert!(args.contains("-s 10x6 -framerate 20"), "args: {}", args);
+    assert!(args.contains("libx264"));
+    let raw = fs::read(dir.path().join("walk_walk.mp4")).unwrap();
+    assert_eq!(raw.len(), 4 * 10 * 6 * 4);
+    let frame = |i: usize, x: usize, y: usize| &raw[(i * 60 + y * 10 + x) * 4..][..4];
+    assert_eq!(frame(0, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(frame(1, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(frame(1, 6, 0), [0, 0, 0, 255], "MP4 flattens onto black");
+    assert_eq!(frame(2, 6, 0), [0, 0, 255, 255]);
+
+    // WebM keeps alpha
+    let args = ["export", "walk.pxl", "--format", "webm", "-a", "walk", "-o", "out/w.webm"];
+    let output = pxl(dir.path(), &args, ffmpeg);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let raw = fs::read(dir.path().join("out/w.webm")).unwrap();
+    assert_eq!(raw.len(), 6 * 4 * 2 * 4, "30fps for 200ms");
+    assert_eq!(&raw[2 * 4..3 * 4], [0, 0, 0, 0]);
+}
+
+/// Write an executable stand-in for ffmpeg into `dir`
+#[cfg(all(unix, feature = "video"))]
+fn fake_ffmpeg(dir: &Path, body: &str) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("ffmpeg");
+    fs::write(&script, format!("#!/bin/sh\n{}", body)).unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    script.to_str().unwrap().to_string()
+}
+
+/// Test that the canvas fits the largest frame rather than the first
+#[cfg(all(unix, feature = "video"))]
+#[test]
+fn test_export_video_sizes_canvas_to_largest_frame() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = SOURCE.replace(
+        r#""f2", "size": [3, 2], "palette": "p", "regions": {"b": {"rect": [0, 0, 3, 2]}}"#,
+        r#""f2", "size": [3, 5], "palette": "p", "regions": {"b": {"rect": [0, 0, 3, 5]}}"#,
+    );
+    fs::write(dir.path().join("walk.pxl"), source).unwrap();
+    let ffmpeg =
+        fake_ffmpeg(dir.path(), "echo \"$@\" > args.txt\nfor last; do :; done\ncat > \"$last\"\n");
+
+    let args = ["export", "walk.pxl", "--format", "webm", "--fps", "10", "-o", "w.webm"];
+    let output = pxl(dir.path(), &args, &ffmpeg);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let args = fs::read_to_string(dir.path().join("args.txt")).unwrap();
+    assert!(args.contains("-s 4x6"), "args: {}", args);
+
+    // The second frame's bottom rows are kept, not clipped to the first frame
+    let raw = fs::read(dir.path().join("w.webm")).unwrap();
+    assert_eq!(raw.len(), 2 * 4 * 6 * 4);
+    assert_eq!(&raw[(24 + 4 * 4) * 4..][..4], [0, 0, 255, 255]);
+}
+
+/// Test that a noisy encoder can't deadlock the export, and that its log is reported on failure
+#[cfg(all(unix, feature = "video"))]
+#[test]
+fn test_export_video_encoder_log() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("walk.pxl"), SOURCE).unwrap();
+
+    // More log output than a pipe buffer holds, before reading any frames
+    let noisy =
+        "head -c 1000000 /dev/zero | tr '\\0' x >&2\nfor last; do :; done\ncat > \"$last\"\n";
+    let ffmpeg = fake_ffmpeg(dir.path(), noisy);
+    let args = ["export", "walk.pxl", "--format", "mp4", "--scale", "64", "-o", "w.mp4"];
+    let output = pxl(dir.path(), &args, &ffmpeg);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(fs::metadata(dir.path().join("w.mp4")).unwrap().len() > 1 << 16);
+
+    let ffmpeg = fake_ffmpeg(dir.path(), "echo \"Unknown encoder 'libx264'\" >&2\nexit 1\n");
+    let output = pxl(dir.path(), &args, &ffmpeg);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ffmpeg failed: Unknown encoder 'libx264'"), "stderr: {}", stderr);
+}
+
+/// Test the errors for a missing encoder and unknown animations
+#[cfg(feature = "video")]
+#[test]
+fn test_export_video_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("walk.pxl"), SOURCE).unwrap();
+
+    let args = ["export", "walk.pxl", "--format", "webm"];
+    let output = pxl(dir.path(), &args, "/nonexistent/ffmpeg");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("install ffmpeg"));
+
+    let args = ["export", "walk.pxl", "--format", "webm", "-a", "wlak"];
+    let output = pxl(dir.path(), &args, "/nonexistent/ffmpeg");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("walk"));
+}
+
+/// Test that video options are rejected for other formats
+#[test]
+fn test_video_options_need_video_format() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("walk.pxl"), SOURCE).unwrap();
+
+    let output = pxl(dir.path(), &["export", "walk.pxl", "--format", "ansi", "--fps", "30"], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only apply to video formats"));
+}
@@ -0,0 +1,66 @@
+//! CLI integration tests for warning provenance (file, line and object)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+const SOURCE: &str = r##"{"type": "palette", "name": "p", "colors": {"r": "#FF0000"}}
+{"type": "sprite", "name": "dot", "size": [2, 2], "palette": "p",
+ "regions": {"r": {"points": [[0, 0]]}, "skin": {"points": [[1, 1]]}}}
+this is not json
+"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+fn setup() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("dot.pxl"), SOURCE).unwrap();
+    dir
+}
+
+/// Test that render warnings point at the line defining the sprite
+#[test]
+fn test_render_warning_names_file_and_line() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["render", "dot.pxl", "-o", "dot.png"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("dot.pxl:2: sprite 'dot': "), "stderr: {}", stderr);
+    assert!(stderr.contains("skin"), "stderr: {}", stderr);
+    // Parse warnings keep their own line
+    assert!(stderr.contains("dot.pxl:4: "), "stderr: {}", stderr);
+}
+
+/// Test that strict mode reports the same located warnings as errors
+#[test]
+fn test_strict_errors_are_located() {
+    let dir = setup();
+
+    let output = pxl(dir.path(), &["render", "dot.pxl", "-o", "dot.png", "--strict"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: dot.pxl:"), "stderr: {}", stderr);
+}
@@ -0,0 +1,113 @@
+//! CLI integration tests for web bundle export (--format web)
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Get the path to the pxl binary
+fn pxl_binary() -> PathBuf {
+    // Try release first, then debug
+    let release = Path::new("target/release/pxl");
+    if release.exists() {
+        return release.to_path_buf();
+    }
+
+    let debug = Path::new("target/debug/pxl");
+    if debug.exists() {
+        return debug.to_path_buf();
+    }
+
+    panic!("pxl binary not found. Run 'cargo build' first.");
+}
+
+/// A sky, a far hill with parallax and drifting clouds
+const SCENE: &str = r##"{"type": "palette", "name": "day", "colors": {"_": "#00000000", "b": "#4080FF", "g": "#208020", "w": "#FFFFFF"}}
+{"type": "sprite", "name": "sky", "size": [1, 1], "palette": "day", "regions": {"b": {"rect": [0, 0, 1, 1]}}}
+{"type": "sprite", "name": "hill", "size": [1, 1], "palette": "day", "regions": {"g": {"rect": [0, 0, 1, 1]}}}
+{"type": "sprite", "name": "cloud", "size": [1, 1], "palette": "day", "regions": {"w": {"rect": [0, 0, 1, 1]}}}
+{"type": "composition", "name": "valley", "size": [4, 3], "sprites": {".": null, "S": "sky", "H": "hill", "C": "cloud"}, "layers": [{"name": "sky", "map": ["SSSS", "SSSS", "SSSS"]}, {"name": "hill", "map": ["....", "..HH", ".HHH"], "parallax": 0.5}, {"name": "clouds", "map": [".C..", "....", "...."], "blend": "screen", "opacity": 0.8, "scroll": [2, 0]}]}"##;
+
+fn pxl(dir: &Path, args: &[&str]) -> Output {
+    Command::new(pxl_binary().canonicalize().unwrap())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to execute pxl")
+}
+
+/// Each layer gets a cropped PNG and a manifest entry; the loader is included
+#[test]
+fn test_web_bundle() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("valley.pxl"), SCENE).unwrap();
+
+    let output = pxl(
+        dir.path(),
+        &["render", "valley.pxl", "--format", "web", "--scale", "2", "--strict", "-o", "out/"],
+    );
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let bundle = dir.path().join("out/valley");
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(bundle.join("scene.json")).unwrap()).unwrap();
+    assert_eq!((manifest["width"].as_u64(), manifest["height"].as_u64()), (Some(8), Some(6)));
+
+    let layers = manifest["layers"].as_array().unwrap();
+    let names: Vec<&str> = layers.iter().map(|l| l["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["sky", "hill", "clouds"]);
+
+    let hill = &layers[1];
+    assert_eq!((hill["x"].as_u64(), hill["y"].as_u64()), (Some(2), Some(2)));
+    assert_eq!((hill["width"].as_u64(), hill["height"].as_u64()), (Some(6), Some(4)));
+    assert_eq!(hill["parallax"], serde_json::json!([0.5, 0.5]));
+    let image = image::open(bundle.join("layer1.png")).unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (6, 4));
+
+    let clouds = &layers[2];
+    assert_eq!(clouds["blend"], "screen");
+    assert_eq!(clouds["opacity"], 0.8);
+    assert_eq!(clouds["scroll"], serde_json::json!([4.0, 0.0]));
+
+    let loader = fs::read_to_string(bundle.join("loader.js")).unwrap();
+    assert!(loader.contains("function pxlScene"));
+}
+
+/// A composition filter that matches nothing fails with a suggestion
+#[test]
+fn test_web_unknown_composition() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("valley.pxl"), SCENE).unwrap();
+
+    let output =
+        pxl(dir.path(), &["render", "valley.pxl", "--format", "web", "--composition", "valey"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No composition named 'valey'"), "{}", stderr);
+    assert!(stderr.contains("valley"), "{}", stderr);
+}
+
+/// Repeated layers are cut to one tile and keep their repeat axes
+#[test]
+fn test_web_repeated_layer() {
+    let dir = tempfile::tempdir().unwrap();
+    let scene = SCENE.replace(
+        r#"{"name": "hill", "map": ["....", "..HH", ".HHH"], "parallax": 0.5}"#,
+        r#"{"name": "hill", "map": ["..", ".H"], "parallax": [0.5, 0], "repeat": "x"}"#,
+    );
+    fs::write(dir.path().join("valley.pxl"), scene).unwrap();
+
+    let output = pxl(dir.path(), &["render", "valley.pxl", "--format", "web", "-o", "out/"]);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+
+    let manifest: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("out/valley/scene.json")).unwrap(),
+    )
+    .unwrap();
+    let hill = &manifest["layers"][1];
+    assert_eq!(hill["repeat"], "x");
+    assert_eq!(hill["parallax"], serde_json::json!([0.5, 0.0]));
+    assert_eq!(
+        [&hill["x"], &hill["y"], &hill["width"], &hill["height"]].map(|v| v.as_u64().unwrap()),
+        [0, 1, 2, 1]
+    );
+}
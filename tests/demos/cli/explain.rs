@@ -99,15 +99,20 @@ fn test_explain_animation() {
             "walk_4".to_string(),
         ],
         keyframes: None,
+        motion: None,
         source: None,
         transform: None,
         duration: Some(pixelsrc::models::Duration::Milliseconds(150)),
+        durations: None,
         timing_function: None,
+        subpixel: None,
         r#loop: None,
+        direction: None,
         palette_cycle: None,
         tags: None,
         frame_metadata: None,
         attachments: None,
+        labels: Vec::new(),
     };
 
     let explanation = explain_animation(&animation);
@@ -132,10 +137,19 @@ fn test_explain_composition() {
         size: Some([64, 64]),
         cell_size: Some([8, 8]),
         sprites: HashMap::from([
-            ("a".to_string(), Some("player".to_string())),
-            ("b".to_string(), Some("enemy".to_string())),
+            ("a".to_string(), Some("player".into())),
+            ("b".to_string(), Some("enemy".into())),
         ]),
         layers: vec![],
+        groups: vec![],
+        locale: HashMap::new(),
+        auto_sort: None,
+        map_delimiter: None,
+        labels: Vec::new(),
+        palette_lock: None,
+        lights: Vec::new(),
+        ambient: None,
+        camera: None,
     };
 
     let explanation = explain_composition(&composition);
@@ -79,6 +79,51 @@ fn test_init_preset_game() {
     assert!(project_path.join("pxl.toml").exists(), "pxl.toml should exist");
 }
 
+/// @demo cli/init#preset_gameboy
+/// @title GB-Style Preset Initialization
+/// @description Initialize a 4-color, 8x8 tile project with `pxl init --preset gameboy`.
+#[test]
+fn test_init_preset_gameboy() {
+    let temp = temp_dir();
+    let project_path = temp.path().join("gb_project");
+
+    let result = init_project(&project_path, "gb_project", "gameboy");
+    assert!(result.is_ok(), "GB preset should succeed: {:?}", result.err());
+
+    let config = fs::read_to_string(project_path.join("pxl.toml")).unwrap();
+    assert!(config.contains("max_colors = 4"), "GB preset should cap lint at 4 colors");
+    assert!(project_path.join("src/pxl/tiles/ground.pxl").exists(), "Tiles should exist");
+}
+
+/// @demo cli/init#preset_pico8
+/// @title PICO-8 Preset Initialization
+/// @description Initialize a 16-color, 128x128 sheet project with `pxl init --preset pico8`.
+#[test]
+fn test_init_preset_pico8() {
+    let temp = temp_dir();
+    let project_path = temp.path().join("pico8_project");
+
+    let result = init_project(&project_path, "pico8_project", "pico8");
+    assert!(result.is_ok(), "PICO-8 preset should succeed: {:?}", result.err());
+
+    let config = fs::read_to_string(project_path.join("pxl.toml")).unwrap();
+    assert!(config.contains("[atlases.sheet]"), "PICO-8 preset should define a sheet atlas");
+}
+
+/// @demo cli/init#preset_hd
+/// @title HD Preset Initialization
+/// @description Initialize a modern HD pixel art project with `pxl init --preset hd`.
+#[test]
+fn test_init_preset_hd() {
+    let temp = temp_dir();
+    let project_path = temp.path().join("hd_project");
+
+    let result = init_project(&project_path, "hd_project", "hd");
+    assert!(result.is_ok(), "HD preset should succeed: {:?}", result.err());
+
+    assert!(project_path.join("src/pxl/sprites/hero.pxl").exists(), "Hero sprite should exist");
+}
+
 // ============================================================================
 // Error Handling Tests
 // ============================================================================
@@ -137,6 +182,10 @@ fn test_preset_parsing() {
     assert_eq!(Preset::from_str("artist"), Some(Preset::Artist));
     assert_eq!(Preset::from_str("animator"), Some(Preset::Animator));
     assert_eq!(Preset::from_str("game"), Some(Preset::Game));
+    assert_eq!(Preset::from_str("gameboy"), Some(Preset::GameBoy));
+    assert_eq!(Preset::from_str("GB"), Some(Preset::GameBoy));
+    assert_eq!(Preset::from_str("pico-8"), Some(Preset::Pico8));
+    assert_eq!(Preset::from_str("hd"), Some(Preset::Hd));
 
     assert_eq!(Preset::from_str("unknown"), None);
 }
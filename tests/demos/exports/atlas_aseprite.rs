@@ -11,15 +11,15 @@ fn build_aseprite_metadata() -> AtlasMetadata {
     let mut frames = HashMap::new();
     frames.insert(
         "player_idle".to_string(),
-        AtlasFrame { x: 0, y: 0, w: 16, h: 16, origin: None, boxes: None },
+        AtlasFrame { x: 0, y: 0, w: 16, h: 16, ..Default::default() },
     );
     frames.insert(
         "player_walk_1".to_string(),
-        AtlasFrame { x: 16, y: 0, w: 16, h: 16, origin: None, boxes: None },
+        AtlasFrame { x: 16, y: 0, w: 16, h: 16, ..Default::default() },
     );
     frames.insert(
         "player_walk_2".to_string(),
-        AtlasFrame { x: 32, y: 0, w: 16, h: 16, origin: None, boxes: None },
+        AtlasFrame { x: 32, y: 0, w: 16, h: 16, ..Default::default() },
     );
 
     let mut animations = HashMap::new();
@@ -29,6 +29,10 @@ fn build_aseprite_metadata() -> AtlasMetadata {
             frames: vec!["player_walk_1".to_string(), "player_walk_2".to_string()],
             fps: 8,
             tags: None,
+            durations: None,
+            canvas: None,
+            offsets: None,
+            direction: None,
         },
     );
 
@@ -40,6 +40,9 @@ fn build_atlas_metadata(jsonl: &str) -> AtlasMetadata {
                         h,
                         origin,
                         boxes: None,
+                        attach_in: None,
+                        attach_out: None,
+                        slices: None,
                     },
                 );
                 frame_index += 1;
@@ -51,6 +54,10 @@ fn build_atlas_metadata(jsonl: &str) -> AtlasMetadata {
                         frames: a.frames.clone(),
                         fps: 1000 / a.duration_ms().max(1), // Convert ms/frame to fps
                         tags: None,
+                        durations: None,
+                        canvas: None,
+                        offsets: None,
+                        direction: None,
                     },
                 );
             }
@@ -36,6 +36,9 @@ fn build_atlas_metadata(jsonl: &str) -> AtlasMetadata {
                     h,
                     origin,
                     boxes: None,
+                    attach_in: None,
+                    attach_out: None,
+                    slices: None,
                 },
             );
             frame_index += 1;
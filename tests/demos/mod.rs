@@ -84,10 +84,14 @@ pub fn parse_content(jsonl: &str) -> (PaletteRegistry, SpriteRegistry, HashMap<S
                 animations.insert(a.name.clone(), a);
             }
             TtpObject::Composition(_) => {}
+            TtpObject::Sequence(_) => {}
+            TtpObject::Theme(_) => {}
             TtpObject::Particle(_) => {}
             TtpObject::Transform(_) => {}
             TtpObject::StateRules(_) => {}
             TtpObject::Import(_) => {}
+            TtpObject::Stamp(_) => {}
+            TtpObject::RandomSet(_) => {}
         }
     }
 
@@ -116,10 +120,14 @@ pub fn parse_compositions(
                 compositions.insert(c.name.clone(), c);
             }
             TtpObject::Animation(_) => {}
+            TtpObject::Sequence(_) => {}
+            TtpObject::Theme(_) => {}
             TtpObject::Particle(_) => {}
             TtpObject::Transform(_) => {}
             TtpObject::StateRules(_) => {}
             TtpObject::Import(_) => {}
+            TtpObject::Stamp(_) => {}
+            TtpObject::RandomSet(_) => {}
         }
     }
 
@@ -210,7 +218,7 @@ pub fn assert_composition_sprites_resolve(jsonl: &str, composition_name: &str) {
 
     // Check all sprite values can be resolved
     for (key, sprite_name_opt) in &comp.sprites {
-        if let Some(sprite_name) = sprite_name_opt {
+        if let Some(sprite_name) = sprite_name_opt.as_ref().map(|s| s.name()) {
             sprite_registry
                 .resolve(sprite_name, &palette_registry, false)
                 .unwrap_or_else(|e| {
@@ -235,11 +243,8 @@ pub fn capture_render_info(jsonl: &str, sprite_name: &str) -> RenderInfo {
 
     let (image, _warnings) = render_resolved(&resolved);
 
-    // Calculate SHA256 of PNG bytes
-    let mut png_bytes = Vec::new();
-    image
-        .write_to(&mut Cursor::new(&mut png_bytes), image::ImageOutputFormat::Png)
-        .expect("Failed to encode PNG");
+    // Calculate SHA256 of PNG bytes (pinned encoder settings, as `pxl render` writes them)
+    let png_bytes = pixelsrc::output::encode_png(&image).expect("Failed to encode PNG");
 
     let mut hasher = Sha256::new();
     hasher.update(&png_bytes);
@@ -284,10 +289,7 @@ pub fn capture_scaled_render_info(jsonl: &str, sprite_name: &str, scale_factor:
     let scaled = scale_image(image, scale_factor);
 
     // Calculate SHA256 of scaled PNG bytes
-    let mut png_bytes = Vec::new();
-    scaled
-        .write_to(&mut Cursor::new(&mut png_bytes), image::ImageOutputFormat::Png)
-        .expect("Failed to encode scaled PNG");
+    let png_bytes = pixelsrc::output::encode_png(&scaled).expect("Failed to encode scaled PNG");
 
     let mut hasher = Sha256::new();
     hasher.update(&png_bytes);
@@ -508,10 +510,7 @@ pub fn capture_spritesheet_info(
     let sheet = render_spritesheet(&frame_images, cols);
 
     // Calculate SHA256 of PNG bytes
-    let mut png_bytes = Vec::new();
-    sheet
-        .write_to(&mut Cursor::new(&mut png_bytes), image::ImageOutputFormat::Png)
-        .expect("Failed to encode spritesheet PNG");
+    let png_bytes = pixelsrc::output::encode_png(&sheet).expect("Failed to encode spritesheet PNG");
 
     let mut hasher = Sha256::new();
     hasher.update(&png_bytes);
@@ -194,8 +194,8 @@ fn test_mcp_tools_list() {
     let result = resp.get("result").expect("tools/list should return result");
     let tools = result["tools"].as_array().expect("tools should be an array");
 
-    // We have 11 implemented tools
-    assert_eq!(tools.len(), 11, "expected 11 tools, got {}", tools.len());
+    // We have 12 implemented tools
+    assert_eq!(tools.len(), 12, "expected 12 tools, got {}", tools.len());
 
     // Collect tool names
     let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
@@ -211,6 +211,7 @@ fn test_mcp_tools_list() {
         "pixelsrc_diff",
         "pixelsrc_validate",
         "pixelsrc_suggest",
+        "pixelsrc_palette_swap",
     ];
     for name in &expected {
         assert!(names.contains(name), "missing tool: {}", name);
@@ -288,6 +289,28 @@ fn test_mcp_tool_render() {
     client.shutdown();
 }
 
+#[test]
+fn test_mcp_tool_palette_swap() {
+    let mut client = McpClient::spawn();
+    client.initialize();
+
+    let source = r##"{"type": "sprite", "name": "dot", "size": [1, 1], "palette": {"_": "#00000000", "x": "#FF0000"}, "regions": {"x": {"points": [[0, 0]], "z": 0}}}"##;
+
+    let resp = client.call_tool(
+        "pixelsrc_palette_swap",
+        serde_json::json!({ "source": source, "sprite": "dot", "colors": { "x": "#00FF00" } }),
+    );
+    let result = resp.get("result").expect("tool call should return result");
+    let content = result["content"].as_array().expect("content should be array");
+    assert_eq!(content[0]["type"].as_str().unwrap(), "image");
+
+    let text = content[1]["text"].as_str().unwrap();
+    assert!(text.contains("\"type\":\"variant\""), "should return a variant line: {}", text);
+    assert!(text.contains("dot_swap"));
+
+    client.shutdown();
+}
+
 #[test]
 fn test_mcp_tool_render_error() {
     let mut client = McpClient::spawn();